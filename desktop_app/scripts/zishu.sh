@@ -0,0 +1,94 @@
+#!/bin/bash
+
+# =============================================================================
+# Zishu-sensei 自动化接口命令行客户端
+# =============================================================================
+#
+# 通过本地 Unix socket 调用桌宠的自动化接口（需在设置中开启）。
+# 客户端需要先在应用中授权（grant_automation_client）才能调用对应方法。
+#
+# 使用方法：
+#   ./scripts/zishu.sh ping
+#   ./scripts/zishu.sh notify "build finished" [标题]
+#   ./scripts/zishu.sh workflows
+#   ./scripts/zishu.sh run <workflow_id> ['{"key":"value"}']
+#
+# 环境变量：
+#   ZISHU_DATA_DIR   应用数据目录，automation.sock 与 automation_token 位于其中
+#                    （默认与 utils::config::get_app_data_dir 一致，并遵循 data_location.json 中的迁移位置）：
+#                      Linux   ~/.config/zishu-sensei
+#                      macOS   ~/Library/Application Support/zishu-sensei
+#                      Windows 使用命名管道，不支持本脚本
+#   ZISHU_CLIENT     客户端名称，对应权限表中的授权（默认 script）
+#
+# 作者：Zishu Team
+# 版本：1.0.0
+# =============================================================================
+
+set -euo pipefail
+
+APP_DIR_NAME="zishu-sensei"
+
+if [ -z "${ZISHU_DATA_DIR:-}" ]; then
+    if [ "$(uname)" = "Darwin" ]; then
+        ZISHU_DATA_DIR="$HOME/Library/Application Support/$APP_DIR_NAME"
+    else
+        ZISHU_DATA_DIR="${XDG_CONFIG_HOME:-$HOME/.config}/$APP_DIR_NAME"
+    fi
+    # 数据目录被迁移时，默认目录中的 data_location.json 记录新位置
+    POINTER="$ZISHU_DATA_DIR/data_location.json"
+    if [ -r "$POINTER" ]; then
+        ZISHU_DATA_DIR="$(python3 -c 'import json, sys; print(json.load(open(sys.argv[1]))["data_dir"])' "$POINTER")"
+    fi
+fi
+
+SOCKET="$ZISHU_DATA_DIR/automation.sock"
+TOKEN_FILE="$ZISHU_DATA_DIR/automation_token"
+
+usage() {
+    sed -n '11,14p' "$0" | sed 's/^#   //'
+    exit 1
+}
+
+[ $# -ge 1 ] || usage
+[ -S "$SOCKET" ] || { echo "自动化接口未开启: $SOCKET" >&2; exit 1; }
+[ -r "$TOKEN_FILE" ] || { echo "无法读取令牌: $TOKEN_FILE" >&2; exit 1; }
+
+case "$1" in
+    ping)      METHOD="ping";          PARAMS_ARGS=() ;;
+    notify)    [ $# -ge 2 ] || usage;  METHOD="notify";        PARAMS_ARGS=("body=$2" "title=${3:-}") ;;
+    workflows) METHOD="workflow.list"; PARAMS_ARGS=() ;;
+    run)       [ $# -ge 2 ] || usage;  METHOD="workflow.run";  PARAMS_ARGS=("workflow_id=$2" "input=${3:-}") ;;
+    *)         usage ;;
+esac
+
+# 用 Python 完成 JSON 编码与 socket 通信，避免依赖 socat/nc 的不同实现
+exec python3 - "$SOCKET" "$TOKEN_FILE" "${ZISHU_CLIENT:-script}" "$METHOD" ${PARAMS_ARGS[@]+"${PARAMS_ARGS[@]}"} <<'PY'
+import json, socket, sys
+
+sock_path, token_file, client, method, *pairs = sys.argv[1:]
+params = {}
+for pair in pairs:
+    key, _, value = pair.partition("=")
+    if not value:
+        continue
+    params[key] = json.loads(value) if key == "input" else value
+
+with open(token_file) as f:
+    token = f.read().strip()
+
+conn = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+conn.connect(sock_path)
+stream = conn.makefile("rw", encoding="utf-8")
+for request in (
+    {"id": 1, "method": "auth", "params": {"token": token, "client": client}},
+    {"id": 2, "method": method, "params": params},
+):
+    stream.write(json.dumps(request) + "\n")
+    stream.flush()
+    response = json.loads(stream.readline())
+    if "error" in response:
+        print(f"错误 {response['error']['code']}: {response['error']['message']}", file=sys.stderr)
+        sys.exit(1)
+print(json.dumps(response.get("result"), ensure_ascii=False, indent=2))
+PY
@@ -0,0 +1,106 @@
+# Backend-generated UI strings (English)
+
+# Tray menu
+tray-chat = 💬 Start chat
+tray-settings = ⚙️ Settings
+tray-character-settings = 🎭 Character
+tray-theme-settings = 🎨 Theme
+tray-adapter-settings = 🔧 Adapters
+tray-sound-settings = 🔊 Sound
+tray-system-settings = 📱 System
+tray-character-actions = 🎭 Character actions
+tray-character-idle = 😊 Idle
+tray-character-wave = 👋 Wave
+tray-character-dance = 💃 Dance
+tray-extensions = 🧩 Extensions
+tray-adapter-market = 🔄 Adapter market
+tray-workflow-editor = 📋 Workflow editor
+tray-screenshot = 📸 Screenshot
+tray-show-window = 👁️ Show window
+tray-hide-window = 🙈 Hide window
+tray-toggle-always-on-top = 📌 Toggle always on top
+tray-about = ℹ️ About
+tray-check-updates = 🔄 Check for updates
+tray-restart = 🔄 Restart
+tray-quit = ❌ Quit
+tray-tooltip-database-down = Zishu Sensei - Database unavailable, reconnecting ({ $error })
+tray-connection-lost = connection lost
+
+# Window titles
+window-title-chat = Zishu Sensei - Chat
+window-title-settings = Zishu Sensei - Settings
+window-title-workflow = Zishu Sensei - Workflow Editor
+
+# Dialogs
+dialog-about-title = About Zishu Sensei
+dialog-about-version = Version: { $version }
+dialog-about-description = An intelligent desktop pet built with Tauri, React and Live2D
+dialog-restart-title = Restart
+dialog-restart-message = Restart the app now?
+dialog-quit-title = Quit
+dialog-quit-message = Quit the app?
+
+# Notifications
+notify-error-body = Error: { $error }
+notify-minimized-to-tray = The app is still running in the system tray
+notify-always-on-top-enabled = The window now stays on top
+notify-always-on-top-disabled = The window no longer stays on top
+notify-checking-updates = Checking for updates...
+notify-open-chat-failed = Could not open the chat window
+notify-open-settings-failed = Could not open the settings window
+notify-open-market-failed = Could not open the adapter market
+notify-open-workflow-failed = Could not open the workflow editor
+notify-screenshot-failed = Screenshot failed
+notify-backup-verification-failed = Database backup verification failed
+notify-backup-nothing-to-verify = The backup contains no data to verify
+notify-action-view-backups = View backups
+notify-achievement-unlocked = Achievement unlocked: { $title }
+notify-disk-space-critical = Disk space critically low
+notify-disk-space-low = Disk space low
+notify-disk-space-body = Only { $remaining } MB left before { $operation }. Check storage usage in Settings to free up space.
+notify-action-free-space = Free up space
+notify-calendar-event-upcoming = Starting soon: { $title }
+notify-calendar-event-body = Starts in { $minutes } min ({ $time })
+notify-calendar-event-speech = "{ $title }" starts in { $minutes } minutes. Don't forget!
+
+# Error messages
+error-disk-space-critical = Not enough disk space for { $operation }: only { $remaining } MB would be left
+
+# Operations named by disk space checks
+operation-write-logs = writing logs
+operation-download-model = downloading the model
+operation-download-product = downloading the product
+
+# Built-in actions in the command palette
+action-tray-chat = Start chat
+action-tray-character-settings = Character settings
+action-tray-theme-settings = Theme settings
+action-tray-adapter-settings = Manage adapters
+action-tray-sound-settings = Sound settings
+action-tray-system-settings = System settings
+action-tray-adapter-market = Adapter market
+action-tray-workflow-editor = Workflow editor
+action-tray-screenshot = Screenshot
+action-tray-show-window = Show window
+action-tray-hide-window = Hide window
+action-tray-toggle-always-on-top = Toggle always on top
+action-tray-character-idle = Character: idle
+action-tray-character-wave = Character: wave
+action-tray-character-dance = Character: dance
+action-tray-about = About
+action-tray-check-updates = Check for updates
+action-tray-restart = Restart app
+action-tray-quit = Quit
+action-quick-chat-open = Open quick chat
+action-quick-chat-popup = Quick chat popup
+action-quick-chat-focus-input = Focus input
+action-quick-chat-close = Close quick chat
+action-context-menu-chat = Chat
+action-context-menu-settings = Settings
+action-context-menu-adapters = Adapters
+action-context-menu-character = Character
+action-context-menu-theme-light = Light theme
+action-context-menu-theme-dark = Dark theme
+action-context-menu-minimize = Minimize
+action-context-menu-close = Close
+action-voice-push-to-talk = Push to talk
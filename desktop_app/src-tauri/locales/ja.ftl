@@ -0,0 +1,106 @@
+# バックエンドが生成する UI 文字列（日本語）
+
+# トレイメニュー
+tray-chat = 💬 会話を始める
+tray-settings = ⚙️ 設定
+tray-character-settings = 🎭 キャラクター設定
+tray-theme-settings = 🎨 テーマ設定
+tray-adapter-settings = 🔧 アダプター管理
+tray-sound-settings = 🔊 サウンド設定
+tray-system-settings = 📱 システム設定
+tray-character-actions = 🎭 キャラクターの動作
+tray-character-idle = 😊 待機
+tray-character-wave = 👋 手を振る
+tray-character-dance = 💃 ダンス
+tray-extensions = 🧩 拡張
+tray-adapter-market = 🔄 アダプターマーケット
+tray-workflow-editor = 📋 ワークフローエディター
+tray-screenshot = 📸 スクリーンショット
+tray-show-window = 👁️ ウィンドウを表示
+tray-hide-window = 🙈 ウィンドウを隠す
+tray-toggle-always-on-top = 📌 最前面表示の切り替え
+tray-about = ℹ️ このアプリについて
+tray-check-updates = 🔄 アップデートを確認
+tray-restart = 🔄 再起動
+tray-quit = ❌ 終了
+tray-tooltip-database-down = Zishu Sensei - データベースに接続できません。再接続中（{ $error }）
+tray-connection-lost = 接続が切断されました
+
+# ウィンドウタイトル
+window-title-chat = Zishu Sensei - チャット
+window-title-settings = Zishu Sensei - 設定
+window-title-workflow = Zishu Sensei - ワークフローエディター
+
+# ダイアログ
+dialog-about-title = Zishu Sensei について
+dialog-about-version = バージョン: { $version }
+dialog-about-description = Tauri + React + Live2D で開発されたインテリジェントなデスクトップペット
+dialog-restart-title = 再起動
+dialog-restart-message = アプリを再起動しますか？
+dialog-quit-title = 終了
+dialog-quit-message = アプリを終了しますか？
+
+# 通知
+notify-error-body = エラー: { $error }
+notify-minimized-to-tray = アプリはシステムトレイで動作しています
+notify-always-on-top-enabled = ウィンドウを最前面に表示します
+notify-always-on-top-disabled = ウィンドウの最前面表示を解除しました
+notify-checking-updates = アップデートを確認しています...
+notify-open-chat-failed = チャットウィンドウを開けません
+notify-open-settings-failed = 設定ウィンドウを開けません
+notify-open-market-failed = アダプターマーケットを開けません
+notify-open-workflow-failed = ワークフローエディターを開けません
+notify-screenshot-failed = スクリーンショットに失敗しました
+notify-backup-verification-failed = データベースバックアップの検証に失敗しました
+notify-backup-nothing-to-verify = バックアップに検証できるデータがありません
+notify-action-view-backups = バックアップを見る
+notify-achievement-unlocked = 実績解除：{ $title }
+notify-disk-space-critical = ディスク容量が非常に不足しています
+notify-disk-space-low = ディスク容量が不足しています
+notify-disk-space-body = { $operation }の前に残り { $remaining } MB です。設定でストレージ使用量を確認して空き容量を増やしてください
+notify-action-free-space = 空き容量を増やす
+notify-calendar-event-upcoming = まもなく開始：{ $title }
+notify-calendar-event-body = { $minutes } 分後（{ $time }）に開始
+notify-calendar-event-speech = 「{ $title }」があと { $minutes } 分で始まるよ。忘れないでね！
+
+# エラーメッセージ
+error-disk-space-critical = ディスク容量が不足しているため{ $operation }できません：完了後の残りは { $remaining } MB です
+
+# ディスク容量チェックの操作名
+operation-write-logs = ログ書き込み
+operation-download-model = モデルのダウンロード
+operation-download-product = 製品のダウンロード
+
+# コマンドパレットの組み込みアクション
+action-tray-chat = 会話を始める
+action-tray-character-settings = キャラクター設定
+action-tray-theme-settings = テーマ設定
+action-tray-adapter-settings = アダプター管理
+action-tray-sound-settings = サウンド設定
+action-tray-system-settings = システム設定
+action-tray-adapter-market = アダプターマーケット
+action-tray-workflow-editor = ワークフローエディター
+action-tray-screenshot = スクリーンショット
+action-tray-show-window = ウィンドウを表示
+action-tray-hide-window = ウィンドウを隠す
+action-tray-toggle-always-on-top = 最前面表示の切り替え
+action-tray-character-idle = キャラクター: 待機
+action-tray-character-wave = キャラクター: 手を振る
+action-tray-character-dance = キャラクター: ダンス
+action-tray-about = このアプリについて
+action-tray-check-updates = アップデートを確認
+action-tray-restart = アプリを再起動
+action-tray-quit = 終了
+action-quick-chat-open = クイックチャットを開く
+action-quick-chat-popup = クイックチャットのポップアップ
+action-quick-chat-focus-input = 入力欄にフォーカス
+action-quick-chat-close = クイックチャットを閉じる
+action-context-menu-chat = チャット
+action-context-menu-settings = 設定
+action-context-menu-adapters = アダプター
+action-context-menu-character = キャラクター
+action-context-menu-theme-light = ライトテーマ
+action-context-menu-theme-dark = ダークテーマ
+action-context-menu-minimize = 最小化
+action-context-menu-close = 閉じる
+action-voice-push-to-talk = プッシュトゥトーク
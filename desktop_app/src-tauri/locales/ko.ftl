@@ -0,0 +1,106 @@
+# 백엔드에서 생성하는 UI 문자열 (한국어)
+
+# 트레이 메뉴
+tray-chat = 💬 대화 시작
+tray-settings = ⚙️ 설정
+tray-character-settings = 🎭 캐릭터 설정
+tray-theme-settings = 🎨 테마 설정
+tray-adapter-settings = 🔧 어댑터 관리
+tray-sound-settings = 🔊 사운드 설정
+tray-system-settings = 📱 시스템 설정
+tray-character-actions = 🎭 캐릭터 동작
+tray-character-idle = 😊 대기
+tray-character-wave = 👋 손 흔들기
+tray-character-dance = 💃 춤추기
+tray-extensions = 🧩 확장
+tray-adapter-market = 🔄 어댑터 마켓
+tray-workflow-editor = 📋 워크플로 편집기
+tray-screenshot = 📸 스크린샷
+tray-show-window = 👁️ 창 표시
+tray-hide-window = 🙈 창 숨기기
+tray-toggle-always-on-top = 📌 항상 위 전환
+tray-about = ℹ️ 정보
+tray-check-updates = 🔄 업데이트 확인
+tray-restart = 🔄 다시 시작
+tray-quit = ❌ 종료
+tray-tooltip-database-down = Zishu Sensei - 데이터베이스를 사용할 수 없어 다시 연결하는 중 ({ $error })
+tray-connection-lost = 연결 끊김
+
+# 창 제목
+window-title-chat = Zishu Sensei - 채팅
+window-title-settings = Zishu Sensei - 설정
+window-title-workflow = Zishu Sensei - 워크플로 편집기
+
+# 대화 상자
+dialog-about-title = Zishu Sensei 정보
+dialog-about-version = 버전: { $version }
+dialog-about-description = Tauri + React + Live2D로 개발된 지능형 데스크톱 펫
+dialog-restart-title = 다시 시작
+dialog-restart-message = 앱을 다시 시작할까요?
+dialog-quit-title = 종료
+dialog-quit-message = 앱을 종료할까요?
+
+# 알림
+notify-error-body = 오류: { $error }
+notify-minimized-to-tray = 앱이 시스템 트레이에서 계속 실행 중입니다
+notify-always-on-top-enabled = 창을 항상 위에 표시합니다
+notify-always-on-top-disabled = 창을 더 이상 항상 위에 표시하지 않습니다
+notify-checking-updates = 업데이트를 확인하는 중...
+notify-open-chat-failed = 채팅 창을 열 수 없습니다
+notify-open-settings-failed = 설정 창을 열 수 없습니다
+notify-open-market-failed = 어댑터 마켓을 열 수 없습니다
+notify-open-workflow-failed = 워크플로 편집기를 열 수 없습니다
+notify-screenshot-failed = 스크린샷 실패
+notify-backup-verification-failed = 데이터베이스 백업 검증 실패
+notify-backup-nothing-to-verify = 백업에 검증할 데이터가 없습니다
+notify-action-view-backups = 백업 보기
+notify-achievement-unlocked = 업적 달성: { $title }
+notify-disk-space-critical = 디스크 공간이 매우 부족합니다
+notify-disk-space-low = 디스크 공간 부족
+notify-disk-space-body = { $operation } 전에 { $remaining } MB 남았습니다. 설정에서 저장 공간 사용량을 확인하고 정리하세요
+notify-action-free-space = 공간 확보
+notify-calendar-event-upcoming = 곧 시작: { $title }
+notify-calendar-event-body = { $minutes }분 후({ $time }) 시작
+notify-calendar-event-speech = "{ $title }" 일정이 { $minutes }분 후에 시작해요. 잊지 마세요!
+
+# 오류 메시지
+error-disk-space-critical = 디스크 공간이 부족하여 { $operation }할 수 없습니다: 완료 후 { $remaining } MB만 남습니다
+
+# 디스크 공간 검사의 작업 이름
+operation-write-logs = 로그 기록
+operation-download-model = 모델 다운로드
+operation-download-product = 제품 다운로드
+
+# 명령 팔레트의 기본 동작
+action-tray-chat = 대화 시작
+action-tray-character-settings = 캐릭터 설정
+action-tray-theme-settings = 테마 설정
+action-tray-adapter-settings = 어댑터 관리
+action-tray-sound-settings = 사운드 설정
+action-tray-system-settings = 시스템 설정
+action-tray-adapter-market = 어댑터 마켓
+action-tray-workflow-editor = 워크플로 편집기
+action-tray-screenshot = 스크린샷
+action-tray-show-window = 창 표시
+action-tray-hide-window = 창 숨기기
+action-tray-toggle-always-on-top = 항상 위 전환
+action-tray-character-idle = 캐릭터: 대기
+action-tray-character-wave = 캐릭터: 손 흔들기
+action-tray-character-dance = 캐릭터: 춤추기
+action-tray-about = 정보
+action-tray-check-updates = 업데이트 확인
+action-tray-restart = 앱 다시 시작
+action-tray-quit = 종료
+action-quick-chat-open = 빠른 대화 열기
+action-quick-chat-popup = 빠른 대화 팝업
+action-quick-chat-focus-input = 입력창에 포커스
+action-quick-chat-close = 빠른 대화 닫기
+action-context-menu-chat = 채팅
+action-context-menu-settings = 설정
+action-context-menu-adapters = 어댑터
+action-context-menu-character = 캐릭터
+action-context-menu-theme-light = 밝은 테마
+action-context-menu-theme-dark = 어두운 테마
+action-context-menu-minimize = 최소화
+action-context-menu-close = 닫기
+action-voice-push-to-talk = 눌러서 말하기
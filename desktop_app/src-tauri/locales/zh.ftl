@@ -0,0 +1,107 @@
+# 后端生成的界面文字（中文，源语言）
+# 修改或新增键时同步更新其他语言的文字表
+
+# 托盘菜单
+tray-chat = 💬 开始对话
+tray-settings = ⚙️ 设置
+tray-character-settings = 🎭 角色设置
+tray-theme-settings = 🎨 主题设置
+tray-adapter-settings = 🔧 适配器管理
+tray-sound-settings = 🔊 声音设置
+tray-system-settings = 📱 系统设置
+tray-character-actions = 🎭 角色动作
+tray-character-idle = 😊 待机
+tray-character-wave = 👋 挥手
+tray-character-dance = 💃 跳舞
+tray-extensions = 🧩 扩展
+tray-adapter-market = 🔄 适配器市场
+tray-workflow-editor = 📋 工作流编辑器
+tray-screenshot = 📸 截图
+tray-show-window = 👁️ 显示窗口
+tray-hide-window = 🙈 隐藏窗口
+tray-toggle-always-on-top = 📌 切换置顶
+tray-about = ℹ️ 关于
+tray-check-updates = 🔄 检查更新
+tray-restart = 🔄 重启应用
+tray-quit = ❌ 退出
+tray-tooltip-database-down = Zishu Sensei - 数据库不可用，正在重连（{ $error }）
+tray-connection-lost = 连接中断
+
+# 窗口标题
+window-title-chat = Zishu Sensei - 聊天
+window-title-settings = Zishu Sensei - 设置
+window-title-workflow = Zishu Sensei - 工作流编辑器
+
+# 对话框
+dialog-about-title = 关于 Zishu Sensei
+dialog-about-version = 版本: { $version }
+dialog-about-description = 基于 Tauri + React + Live2D 开发的智能桌面宠物应用
+dialog-restart-title = 重启应用
+dialog-restart-message = 确定要重启应用吗？
+dialog-quit-title = 退出应用
+dialog-quit-message = 确定要退出应用吗？
+
+# 通知
+notify-error-body = 错误: { $error }
+notify-minimized-to-tray = 应用已最小化到系统托盘
+notify-always-on-top-enabled = 窗口已设置为置顶
+notify-always-on-top-disabled = 窗口已取消置顶
+notify-checking-updates = 正在检查更新...
+notify-open-chat-failed = 无法打开聊天窗口
+notify-open-settings-failed = 无法打开设置窗口
+notify-open-market-failed = 无法打开适配器市场
+notify-open-workflow-failed = 无法打开工作流编辑器
+notify-screenshot-failed = 截图失败
+notify-backup-verification-failed = 数据库备份验证失败
+notify-backup-nothing-to-verify = 备份中没有可验证的数据
+notify-action-view-backups = 查看备份
+notify-achievement-unlocked = 解锁成就：{ $title }
+notify-disk-space-critical = 磁盘空间严重不足
+notify-disk-space-low = 磁盘空间不足
+notify-disk-space-body = { $operation }前剩余 { $remaining } MB，可在设置中查看存储占用并清理
+notify-action-free-space = 清理空间
+notify-calendar-event-upcoming = 即将开始：{ $title }
+notify-calendar-event-body = { $minutes } 分钟后（{ $time }）开始
+notify-calendar-event-speech = 「{ $title }」还有 { $minutes } 分钟就开始了，别忘了哦！
+
+# 错误信息
+error-disk-space-critical = 磁盘空间不足，无法{ $operation }：完成后仅剩 { $remaining } MB
+
+# 磁盘空间检查的操作名称
+operation-write-logs = 日志写入
+operation-download-model = 下载模型
+operation-download-product = 下载产品
+
+# 命令面板中的内置动作
+action-tray-chat = 开始对话
+action-tray-character-settings = 角色设置
+action-tray-theme-settings = 主题设置
+action-tray-adapter-settings = 适配器管理
+action-tray-sound-settings = 声音设置
+action-tray-system-settings = 系统设置
+action-tray-adapter-market = 适配器市场
+action-tray-workflow-editor = 工作流编辑器
+action-tray-screenshot = 截图
+action-tray-show-window = 显示窗口
+action-tray-hide-window = 隐藏窗口
+action-tray-toggle-always-on-top = 切换置顶
+action-tray-character-idle = 角色待机
+action-tray-character-wave = 角色挥手
+action-tray-character-dance = 角色跳舞
+action-tray-about = 关于
+action-tray-check-updates = 检查更新
+action-tray-restart = 重启应用
+action-tray-quit = 退出
+action-quick-chat-open = 打开快速对话
+action-quick-chat-popup = 快速对话弹窗
+action-quick-chat-focus-input = 聚焦输入框
+action-quick-chat-close = 关闭快速对话
+action-context-menu-chat = 聊天
+action-context-menu-settings = 设置
+action-context-menu-adapters = 适配器
+action-context-menu-character = 角色
+action-context-menu-theme-light = 浅色主题
+action-context-menu-theme-dark = 深色主题
+action-context-menu-minimize = 最小化
+action-context-menu-close = 关闭
+action-voice-push-to-talk = 按键说话
@@ -0,0 +1,547 @@
+//! # 日历宿主接口的数据模型
+//!
+//! 适配器通过 `commands::calendar_host` 读写用户日历，本模块负责日历事件模型、
+//! ICS（RFC 5545）文件的解析与生成，以及即将开始的事件与到期提醒的计算。
+//!
+//! - 适配器创建的事件写入应用数据目录下的本地日历（`calendar.ics`），
+//!   并用 `X-ZISHU-ADAPTER` 记录创建者
+//! - 系统日历（macOS 日历 / Outlook 等）通过其导出或订阅的 ICS 文件接入，只读
+//! - 支持 UTC、`TZID` 与浮动时间，以及全天事件；重复事件（`RRULE`）只取第一次
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 本地日历（适配器可写）的 ID
+pub const LOCAL_CALENDAR_ID: &str = "local";
+/// 未指定结束时间的事件默认时长
+const DEFAULT_DURATION_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// 提前提醒的最长时间（分钟）
+const MAX_LEAD_MINUTES: u32 = 24 * 60;
+/// ICS 行的最大长度（字节），超出时折行
+const MAX_LINE_OCTETS: usize = 75;
+const ADAPTER_PROPERTY: &str = "X-ZISHU-ADAPTER";
+
+/// 日历事件（时间为 Unix 毫秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    pub start: i64,
+    pub end: i64,
+    #[serde(default)]
+    pub all_day: bool,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 所属日历
+    pub calendar_id: String,
+    /// 创建该事件的适配器
+    #[serde(default)]
+    pub created_by: Option<String>,
+}
+
+impl CalendarEvent {
+    /// 提醒去重用的键（事件改期后会再次提醒）
+    pub fn reminder_key(&self) -> String {
+        format!("{}:{}:{}", self.calendar_id, self.uid, self.start)
+    }
+}
+
+/// 适配器提交的新事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewCalendarEvent {
+    pub title: String,
+    pub start: i64,
+    /// 缺省时普通事件持续一小时，全天事件持续一天
+    #[serde(default)]
+    pub end: Option<i64>,
+    #[serde(default)]
+    pub all_day: bool,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl NewCalendarEvent {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.title.trim().is_empty() {
+            return Err("事件标题不能为空".to_string());
+        }
+        if self.start <= 0 {
+            return Err("事件开始时间无效".to_string());
+        }
+        if let Some(end) = self.end {
+            if end < self.start {
+                return Err("事件结束时间不能早于开始时间".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成写入本地日历的事件
+    pub fn into_event(self, adapter_id: &str) -> CalendarEvent {
+        let default_duration = if self.all_day { DAY_MS } else { DEFAULT_DURATION_MS };
+        CalendarEvent {
+            uid: format!("{}@zishu-sensei", uuid::Uuid::new_v4()),
+            title: self.title.trim().to_string(),
+            start: self.start,
+            end: self.end.unwrap_or(self.start + default_duration),
+            all_day: self.all_day,
+            location: self.location.filter(|location| !location.trim().is_empty()),
+            description: self.description.filter(|description| !description.trim().is_empty()),
+            calendar_id: LOCAL_CALENDAR_ID.to_string(),
+            created_by: Some(adapter_id.to_string()),
+        }
+    }
+}
+
+/// 导入的只读日历（系统日历导出或订阅的 ICS 文件）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarSource {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// 日历设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarSettings {
+    /// 事件开始前提醒
+    pub reminders_enabled: bool,
+    /// 提前多少分钟提醒
+    pub lead_minutes: u32,
+    /// 提醒时由角色朗读
+    pub speak_reminders: bool,
+    #[serde(default)]
+    pub sources: Vec<CalendarSource>,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            reminders_enabled: true,
+            lead_minutes: 10,
+            speak_reminders: true,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl CalendarSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.lead_minutes == 0 || self.lead_minutes > MAX_LEAD_MINUTES {
+            return Err(format!("提前提醒时间必须在 1 到 {} 分钟之间", MAX_LEAD_MINUTES));
+        }
+        let mut ids = HashSet::new();
+        for source in &self.sources {
+            if source.id.trim().is_empty() || source.id == LOCAL_CALENDAR_ID {
+                return Err(format!("日历 ID 无效: {}", source.id));
+            }
+            if !ids.insert(source.id.as_str()) {
+                return Err(format!("日历 ID 重复: {}", source.id));
+            }
+            if source.path.trim().is_empty() {
+                return Err(format!("日历 {} 缺少文件路径", source.name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn lead_ms(&self) -> i64 {
+        i64::from(self.lead_minutes) * 60 * 1000
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// ================================
+// ICS 解析
+// ================================
+
+/// 一行属性：名称（大写）、参数与值
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 展开折行（以空格或制表符开头的行接在上一行后面）
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // 参数值可以用引号包含 `:` 与 `;`
+    let mut in_quotes = false;
+    let mut split_at = None;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                split_at = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let split_at = split_at?;
+    let (head, value) = (&line[..split_at], &line[split_at + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: value.to_string() })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+/// 解析 `DTSTART`/`DTEND`，返回（Unix 毫秒，是否为日期）
+fn parse_date_time(property: &Property) -> Option<(i64, bool)> {
+    let value = property.value.trim();
+    if property.param("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let midnight = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+        return Some((midnight.timestamp_millis(), true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).timestamp_millis(), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let millis = match property.param("TZID").and_then(|tz| tz.parse::<chrono_tz::Tz>().ok()) {
+        Some(tz) => tz.from_local_datetime(&naive).earliest()?.timestamp_millis(),
+        // 浮动时间与无法识别的时区按本地时间处理
+        None => Local.from_local_datetime(&naive).earliest()?.timestamp_millis(),
+    };
+    Some((millis, false))
+}
+
+/// 解析 `DURATION`（如 `PT1H30M`、`P1D`），返回毫秒
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut total: i64 = 0;
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(time) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = time;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit_ms = match (rest[digits..].chars().next()?, in_time) {
+            ('W', false) => 7 * DAY_MS,
+            ('D', false) => DAY_MS,
+            ('H', true) => 60 * 60 * 1000,
+            ('M', true) => 60 * 1000,
+            ('S', true) => 1000,
+            _ => return None,
+        };
+        total += amount * unit_ms;
+        rest = &rest[digits + 1..];
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn build_event(properties: &[Property], calendar_id: &str, index: usize) -> Option<CalendarEvent> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+    let text = |name: &str| {
+        find(name)
+            .map(|property| unescape_text(&property.value))
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let (start, all_day) = parse_date_time(find("DTSTART")?)?;
+    let end = match (find("DTEND").and_then(parse_date_time), find("DURATION")) {
+        (Some((end, _)), _) => end,
+        (None, Some(duration)) => start + parse_duration(&duration.value)?,
+        (None, None) if all_day => start + DAY_MS,
+        (None, None) => start,
+    };
+
+    Some(CalendarEvent {
+        uid: text("UID").unwrap_or_else(|| format!("{}-{}", calendar_id, index)),
+        title: text("SUMMARY").unwrap_or_else(|| "（无标题）".to_string()),
+        start,
+        end: end.max(start),
+        all_day,
+        location: text("LOCATION"),
+        description: text("DESCRIPTION"),
+        calendar_id: calendar_id.to_string(),
+        created_by: text(ADAPTER_PROPERTY),
+    })
+}
+
+/// 解析 ICS 文本中的事件；无法识别开始时间的事件被跳过
+pub fn parse_ics(text: &str, calendar_id: &str) -> Result<Vec<CalendarEvent>, String> {
+    let lines = unfold(text.trim_start_matches('\u{feff}'));
+    if !lines.first().is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("不是有效的 ICS 日历文件".to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    // 嵌套组件（如 VALARM）的属性不属于事件本身
+    let mut nested_depth = 0usize;
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        let value = property.value.trim().to_ascii_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some(Vec::new()),
+            ("BEGIN", Some(_)) => nested_depth += 1,
+            ("END", Some(_)) if nested_depth > 0 => nested_depth -= 1,
+            ("END", Some(_)) if value == "VEVENT" => {
+                let properties = current.take().unwrap_or_default();
+                if let Some(event) = build_event(&properties, calendar_id, events.len()) {
+                    events.push(event);
+                }
+            }
+            (_, Some(properties)) if nested_depth == 0 => properties.push(property),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+// ================================
+// ICS 生成
+// ================================
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 按 75 字节折行（不拆开多字节字符）
+fn push_folded(output: &mut String, line: &str) {
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            output.push_str("\r\n ");
+            line_octets = 1;
+        }
+        output.push(c);
+        line_octets += c.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+
+fn format_utc(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn format_date(millis: i64) -> String {
+    Local
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|time| time.format("%Y%m%d").to_string())
+        .unwrap_or_default()
+}
+
+/// 生成 ICS 文本
+pub fn to_ics(events: &[CalendarEvent], stamp: DateTime<Utc>) -> String {
+    let mut output = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//Zishu-sensei//Calendar//ZH", "CALSCALE:GREGORIAN"] {
+        push_folded(&mut output, line);
+    }
+    let stamp = format_utc(stamp.timestamp_millis());
+    for event in events {
+        push_folded(&mut output, "BEGIN:VEVENT");
+        push_folded(&mut output, &format!("UID:{}", event.uid));
+        push_folded(&mut output, &format!("DTSTAMP:{}", stamp));
+        if event.all_day {
+            push_folded(&mut output, &format!("DTSTART;VALUE=DATE:{}", format_date(event.start)));
+            push_folded(&mut output, &format!("DTEND;VALUE=DATE:{}", format_date(event.end)));
+        } else {
+            push_folded(&mut output, &format!("DTSTART:{}", format_utc(event.start)));
+            push_folded(&mut output, &format!("DTEND:{}", format_utc(event.end)));
+        }
+        push_folded(&mut output, &format!("SUMMARY:{}", escape_text(&event.title)));
+        if let Some(location) = &event.location {
+            push_folded(&mut output, &format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(description) = &event.description {
+            push_folded(&mut output, &format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(adapter_id) = &event.created_by {
+            push_folded(&mut output, &format!("{}:{}", ADAPTER_PROPERTY, escape_text(adapter_id)));
+        }
+        push_folded(&mut output, "END:VEVENT");
+    }
+    push_folded(&mut output, "END:VCALENDAR");
+    output
+}
+
+// ================================
+// 查询与提醒
+// ================================
+
+/// 与时间段 `[from, to)` 有重叠的事件，按开始时间排列
+pub fn events_between(events: &[CalendarEvent], from: i64, to: i64) -> Vec<CalendarEvent> {
+    let mut matched: Vec<CalendarEvent> = events
+        .iter()
+        .filter(|event| event.start < to && (event.end > from || event.start >= from))
+        .cloned()
+        .collect();
+    matched.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.title.cmp(&b.title)));
+    matched
+}
+
+/// 需要提醒的事件：未开始、`lead_ms` 内开始、尚未提醒过的非全天事件
+pub fn due_reminders<'a>(
+    events: &'a [CalendarEvent],
+    now: i64,
+    lead_ms: i64,
+    announced: &HashSet<String>,
+) -> Vec<&'a CalendarEvent> {
+    let mut due: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|event| !event.all_day && event.start > now && event.start - now <= lead_ms)
+        .filter(|event| !announced.contains(&event.reminder_key()))
+        .collect();
+    due.sort_by_key(|event| event.start);
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:standup@example.com\r\n\
+DTSTART;TZID=Asia/Shanghai:20240301T093000\r\nDURATION:PT15M\r\nSUMMARY:站会\\, 周五\r\n\
+DESCRIPTION:第一行\\n第二\r\n 行\r\nBEGIN:VALARM\r\nDESCRIPTION:提醒\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:review\r\nDTSTART:20240301T060000Z\r\nDTEND:20240301T070000Z\r\nSUMMARY:评审\r\n\
+END:VEVENT\r\nBEGIN:VEVENT\r\nSUMMARY:缺少开始时间\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_ics() {
+        let events = parse_ics(SAMPLE, "work").unwrap();
+        assert_eq!(events.len(), 2);
+
+        let standup = &events[0];
+        assert_eq!(standup.title, "站会, 周五");
+        assert_eq!(standup.description.as_deref(), Some("第一行\n第二行"));
+        // 09:30 北京时间 = 01:30 UTC
+        assert_eq!(standup.start, Utc.with_ymd_and_hms(2024, 3, 1, 1, 30, 0).unwrap().timestamp_millis());
+        assert_eq!(standup.end - standup.start, 15 * 60 * 1000);
+        assert_eq!(standup.calendar_id, "work");
+
+        assert_eq!(events[1].end - events[1].start, 60 * 60 * 1000);
+        assert!(parse_ics("BEGIN:VEVENT\r\nEND:VEVENT", "work").is_err());
+    }
+
+    #[test]
+    fn test_ics_round_trip() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap().timestamp_millis();
+        let event = NewCalendarEvent {
+            title: "和设计组同步; 讨论新角色".repeat(3),
+            start,
+            end: None,
+            all_day: false,
+            location: Some("会议室 A".to_string()),
+            description: Some("议程:\n1. 立绘".to_string()),
+        }
+        .into_event("meeting-helper");
+
+        let text = to_ics(std::slice::from_ref(&event), Utc::now());
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_OCTETS + 1));
+        let parsed = parse_ics(&text, LOCAL_CALENDAR_ID).unwrap();
+        assert_eq!(parsed, vec![event]);
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        let event = |uid: &str, start: i64, all_day: bool| CalendarEvent {
+            uid: uid.to_string(),
+            title: uid.to_string(),
+            start,
+            end: start + DEFAULT_DURATION_MS,
+            all_day,
+            location: None,
+            description: None,
+            calendar_id: LOCAL_CALENDAR_ID.to_string(),
+            created_by: None,
+        };
+        let minute = 60 * 1000;
+        let events = vec![
+            event("later", 30 * minute, false),
+            event("soon", 5 * minute, false),
+            event("started", -minute, false),
+            event("holiday", 2 * minute, true),
+            event("next", 10 * minute, false),
+        ];
+
+        let mut announced = HashSet::new();
+        let due: Vec<&str> = due_reminders(&events, 0, 10 * minute, &announced)
+            .iter()
+            .map(|event| event.uid.as_str())
+            .collect();
+        assert_eq!(due, vec!["soon", "next"]);
+
+        announced.insert(events[1].reminder_key());
+        assert_eq!(due_reminders(&events, 0, 10 * minute, &announced).len(), 1);
+
+        let upcoming = events_between(&events, 0, 20 * minute);
+        assert_eq!(upcoming.iter().map(|event| event.uid.as_str()).collect::<Vec<_>>(), vec!["started", "holiday", "soon", "next"]);
+    }
+}
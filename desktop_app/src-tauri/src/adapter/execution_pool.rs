@@ -0,0 +1,649 @@
+//! 适配器执行池
+//!
+//! 限制适配器操作的并发数：全局上限与每个适配器的上限同时生效，超出的请求进入等待队列。
+//! 队列按优先级调度，交互请求优先于后台/工作流请求；后台请求等待超过
+//! [`BACKGROUND_AGING`] 后按交互优先级处理，避免被持续的交互请求饿死。
+//!
+//! 超时覆盖排队与执行两个阶段。超时或取消时执行任务的 future 会被丢弃，
+//! 同时触发 [`CancelHandle`]，便于已派生出去的工作协作式地停止。
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+use crate::utils::resource_accounting::{self, Subsystem};
+
+/// 后台请求等待超过该时长后提升为交互优先级
+pub const BACKGROUND_AGING: Duration = Duration::from_secs(30);
+/// 用于计算等待时间分位数的最近样本数
+const WAIT_SAMPLES: usize = 512;
+
+/// 执行优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionPriority {
+    /// 用户正在等待结果
+    #[default]
+    Interactive,
+    /// 后台任务与工作流
+    Background,
+}
+
+/// 并发限制
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolLimits {
+    /// 全局最大并发数
+    pub max_concurrent: usize,
+    /// 每个适配器的默认最大并发数
+    pub per_adapter: usize,
+    /// 单独设置的适配器并发数
+    pub per_adapter_overrides: HashMap<String, usize>,
+    /// 等待队列长度上限
+    pub max_queue: usize,
+    /// 请求未指定超时时使用的超时（秒）
+    pub default_timeout_secs: u64,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            per_adapter: 2,
+            per_adapter_overrides: HashMap::new(),
+            max_queue: 64,
+            default_timeout_secs: 120,
+        }
+    }
+}
+
+impl PoolLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent == 0 {
+            return Err("全局并发数必须大于 0".to_string());
+        }
+        if self.per_adapter == 0 || self.per_adapter_overrides.values().any(|limit| *limit == 0) {
+            return Err("适配器并发数必须大于 0".to_string());
+        }
+        if self.default_timeout_secs == 0 {
+            return Err("默认超时必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    fn adapter_limit(&self, adapter_id: &str) -> usize {
+        self.per_adapter_overrides
+            .get(adapter_id)
+            .copied()
+            .unwrap_or(self.per_adapter)
+    }
+}
+
+/// 执行池错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    /// 等待队列已满
+    QueueFull,
+    /// 超时（`queued` 表示超时发生时仍在排队）
+    Timeout { queued: bool },
+    /// 被取消
+    Cancelled,
+}
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::QueueFull => write!(f, "适配器执行队列已满"),
+            Self::Timeout { queued: true } => write!(f, "排队等待执行超时"),
+            Self::Timeout { queued: false } => write!(f, "适配器执行超时"),
+            Self::Cancelled => write!(f, "适配器执行已取消"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+/// 协作式取消句柄
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<CancelInner>,
+}
+
+#[derive(Debug, Default)]
+struct CancelInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 等待取消
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// 等待时间统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaitStats {
+    /// 获得执行槽位的请求数
+    pub started: u64,
+    pub average_wait_ms: f64,
+    pub p50_wait_ms: u64,
+    pub p95_wait_ms: u64,
+    pub max_wait_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct WaitRecorder {
+    started: u64,
+    total_ms: u64,
+    max_ms: u64,
+    recent: VecDeque<u64>,
+}
+
+impl WaitRecorder {
+    fn record(&mut self, wait: Duration) {
+        let ms = wait.as_millis() as u64;
+        self.started += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+        if self.recent.len() == WAIT_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(ms);
+    }
+
+    fn snapshot(&self) -> WaitStats {
+        let mut sorted: Vec<u64> = self.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        let percentile = |p: f64| -> u64 {
+            if sorted.is_empty() {
+                0
+            } else {
+                sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+            }
+        };
+        WaitStats {
+            started: self.started,
+            average_wait_ms: if self.started > 0 { self.total_ms as f64 / self.started as f64 } else { 0.0 },
+            p50_wait_ms: percentile(0.5),
+            p95_wait_ms: percentile(0.95),
+            max_wait_ms: self.max_ms,
+        }
+    }
+}
+
+/// 执行池状态与指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolMetrics {
+    pub running: usize,
+    pub queued: usize,
+    /// 各适配器正在执行的数量
+    pub running_by_adapter: HashMap<String, usize>,
+    /// 各适配器排队的数量
+    pub queued_by_adapter: HashMap<String, usize>,
+    pub interactive: WaitStats,
+    pub background: WaitStats,
+    pub completed: u64,
+    pub timeouts: u64,
+    pub cancellations: u64,
+    pub rejected: u64,
+}
+
+struct Waiter {
+    id: u64,
+    adapter_id: String,
+    priority: ExecutionPriority,
+    enqueued_at: Instant,
+    grant: oneshot::Sender<()>,
+}
+
+impl Waiter {
+    fn effective_priority(&self, now: Instant) -> ExecutionPriority {
+        if self.priority == ExecutionPriority::Background && now.duration_since(self.enqueued_at) >= BACKGROUND_AGING {
+            ExecutionPriority::Interactive
+        } else {
+            self.priority
+        }
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    limits: PoolLimits,
+    running: usize,
+    running_by_adapter: HashMap<String, usize>,
+    /// 按入队顺序排列
+    queue: Vec<Waiter>,
+    next_id: u64,
+    interactive: WaitRecorder,
+    background: WaitRecorder,
+    completed: u64,
+    timeouts: u64,
+    cancellations: u64,
+    rejected: u64,
+}
+
+impl PoolState {
+    fn has_capacity(&self, adapter_id: &str) -> bool {
+        self.running < self.limits.max_concurrent
+            && self.running_by_adapter.get(adapter_id).copied().unwrap_or(0) < self.limits.adapter_limit(adapter_id)
+    }
+
+    fn occupy(&mut self, adapter_id: &str) {
+        self.running += 1;
+        *self.running_by_adapter.entry(adapter_id.to_string()).or_default() += 1;
+    }
+
+    fn release(&mut self, adapter_id: &str) {
+        self.running = self.running.saturating_sub(1);
+        if let Some(count) = self.running_by_adapter.get_mut(adapter_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.running_by_adapter.remove(adapter_id);
+            }
+        }
+    }
+
+    fn recorder(&mut self, priority: ExecutionPriority) -> &mut WaitRecorder {
+        match priority {
+            ExecutionPriority::Interactive => &mut self.interactive,
+            ExecutionPriority::Background => &mut self.background,
+        }
+    }
+
+    /// 把空出的槽位分配给排队的请求（优先级高者优先，同优先级先到先得）
+    fn dispatch(&mut self) {
+        loop {
+            let now = Instant::now();
+            let next = self
+                .queue
+                .iter()
+                .enumerate()
+                .filter(|(_, waiter)| self.has_capacity(&waiter.adapter_id))
+                .min_by_key(|(index, waiter)| (waiter.effective_priority(now) != ExecutionPriority::Interactive, *index))
+                .map(|(index, _)| index);
+            let Some(index) = next else {
+                return;
+            };
+            let waiter = self.queue.remove(index);
+            // 接收端已丢弃（超时或取消）时不占用槽位
+            if waiter.grant.send(()).is_ok() {
+                self.occupy(&waiter.adapter_id);
+                let wait = now.duration_since(waiter.enqueued_at);
+                self.recorder(waiter.priority).record(wait);
+            }
+        }
+    }
+}
+
+/// 执行槽位，释放时调度下一个排队请求
+struct ExecutionPermit {
+    state: Arc<Mutex<PoolState>>,
+    adapter_id: String,
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.release(&self.adapter_id);
+        state.dispatch();
+    }
+}
+
+/// 排队中的请求；在获得槽位前被丢弃时移出队列，若恰好已分配槽位则归还
+struct QueuedRequest {
+    state: Arc<Mutex<PoolState>>,
+    id: u64,
+    adapter_id: String,
+    armed: bool,
+}
+
+impl Drop for QueuedRequest {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let mut state = self.state.lock();
+        if let Some(index) = state.queue.iter().position(|waiter| waiter.id == self.id) {
+            state.queue.remove(index);
+        } else {
+            state.release(&self.adapter_id);
+            state.dispatch();
+        }
+    }
+}
+
+/// 适配器执行池
+#[derive(Clone, Default)]
+pub struct ExecutionPool {
+    state: Arc<Mutex<PoolState>>,
+    executions: Arc<Mutex<HashMap<String, CancelHandle>>>,
+}
+
+impl ExecutionPool {
+    pub fn new(limits: PoolLimits) -> Self {
+        let pool = Self::default();
+        pool.state.lock().limits = limits;
+        pool
+    }
+
+    pub fn limits(&self) -> PoolLimits {
+        self.state.lock().limits.clone()
+    }
+
+    /// 更新并发限制；提高上限时立即调度排队的请求
+    pub fn set_limits(&self, limits: PoolLimits) {
+        let mut state = self.state.lock();
+        state.limits = limits;
+        state.dispatch();
+    }
+
+    async fn acquire(&self, adapter_id: &str, priority: ExecutionPriority) -> Result<ExecutionPermit, PoolError> {
+        let receiver = {
+            let mut state = self.state.lock();
+            if state.queue.is_empty() && state.has_capacity(adapter_id) {
+                state.occupy(adapter_id);
+                state.recorder(priority).record(Duration::ZERO);
+                None
+            } else if state.queue.len() >= state.limits.max_queue {
+                state.rejected += 1;
+                return Err(PoolError::QueueFull);
+            } else {
+                let (grant, receiver) = oneshot::channel();
+                state.next_id += 1;
+                let id = state.next_id;
+                state.queue.push(Waiter {
+                    id,
+                    adapter_id: adapter_id.to_string(),
+                    priority,
+                    enqueued_at: Instant::now(),
+                    grant,
+                });
+                // 队列非空但该适配器可能有空位
+                state.dispatch();
+                Some((receiver, id))
+            }
+        };
+
+        if let Some((receiver, id)) = receiver {
+            let mut queued = QueuedRequest {
+                state: self.state.clone(),
+                id,
+                adapter_id: adapter_id.to_string(),
+                armed: true,
+            };
+            // 发送端只会在分配槽位后被消费，池存活期间不会被直接丢弃
+            let _ = receiver.await;
+            queued.armed = false;
+        }
+
+        Ok(ExecutionPermit {
+            state: self.state.clone(),
+            adapter_id: adapter_id.to_string(),
+        })
+    }
+
+    /// 在池中执行任务
+    ///
+    /// `execution_id` 用于 [`ExecutionPool::cancel`]；`task` 收到的取消句柄在超时或取消时触发。
+    pub async fn run<F, Fut, T>(
+        &self,
+        adapter_id: &str,
+        priority: ExecutionPriority,
+        timeout: Option<Duration>,
+        execution_id: Option<String>,
+        task: F,
+    ) -> Result<T, PoolError>
+    where
+        F: FnOnce(CancelHandle) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let cancel = CancelHandle::default();
+        if let Some(id) = &execution_id {
+            self.executions.lock().insert(id.clone(), cancel.clone());
+        }
+        let timeout = timeout.unwrap_or_else(|| Duration::from_secs(self.limits().default_timeout_secs));
+        let started = Arc::new(AtomicBool::new(false));
+
+        let work = {
+            let started = started.clone();
+            let cancel = cancel.clone();
+            async move {
+                let permit = self.acquire(adapter_id, priority).await?;
+                started.store(true, Ordering::SeqCst);
+                let _activity = resource_accounting::track(Subsystem::Adapters);
+                let output = task(cancel).await;
+                drop(permit);
+                Ok(output)
+            }
+        };
+
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(PoolError::Cancelled),
+            outcome = tokio::time::timeout(timeout, work) => match outcome {
+                Ok(result) => result,
+                Err(_) => Err(PoolError::Timeout { queued: !started.load(Ordering::SeqCst) }),
+            },
+        };
+
+        if let Some(id) = &execution_id {
+            self.executions.lock().remove(id);
+        }
+        let mut state = self.state.lock();
+        match &result {
+            Ok(_) => state.completed += 1,
+            Err(PoolError::Timeout { .. }) => {
+                state.timeouts += 1;
+                cancel.cancel();
+            }
+            Err(PoolError::Cancelled) => state.cancellations += 1,
+            Err(PoolError::QueueFull) => {}
+        }
+        result
+    }
+
+    /// 取消排队中或执行中的任务
+    pub fn cancel(&self, execution_id: &str) -> bool {
+        match self.executions.lock().get(execution_id) {
+            Some(handle) => {
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        let state = self.state.lock();
+        let mut queued_by_adapter: HashMap<String, usize> = HashMap::new();
+        for waiter in &state.queue {
+            *queued_by_adapter.entry(waiter.adapter_id.clone()).or_default() += 1;
+        }
+        PoolMetrics {
+            running: state.running,
+            queued: state.queue.len(),
+            running_by_adapter: state.running_by_adapter.clone(),
+            queued_by_adapter,
+            interactive: state.interactive.snapshot(),
+            background: state.background.snapshot(),
+            completed: state.completed,
+            timeouts: state.timeouts,
+            cancellations: state.cancellations,
+            rejected: state.rejected,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref POOL: ExecutionPool = ExecutionPool::new(PoolLimits::default());
+}
+
+/// 全局适配器执行池
+pub fn global() -> &'static ExecutionPool {
+    &POOL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max_concurrent: usize, per_adapter: usize) -> PoolLimits {
+        PoolLimits {
+            max_concurrent,
+            per_adapter,
+            ..PoolLimits::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_per_adapter_limit_queues_requests() {
+        let pool = ExecutionPool::new(limits(4, 1));
+        let (release, blocked) = oneshot::channel::<()>();
+
+        let first = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.run("ocr", ExecutionPriority::Interactive, None, None, |_| async {
+                    let _ = blocked.await;
+                })
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let second = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.run("ocr", ExecutionPriority::Interactive, None, None, |_| async { 2 }).await })
+        };
+        // 其他适配器不受影响
+        let other = pool.run("tts", ExecutionPriority::Interactive, None, None, |_| async { 3 }).await;
+        assert_eq!(other, Ok(3));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let metrics = pool.metrics();
+        assert_eq!(metrics.running_by_adapter.get("ocr"), Some(&1));
+        assert_eq!(metrics.queued_by_adapter.get("ocr"), Some(&1));
+
+        release.send(()).unwrap();
+        first.await.unwrap().unwrap();
+        assert_eq!(second.await.unwrap(), Ok(2));
+        let metrics = pool.metrics();
+        assert_eq!((metrics.running, metrics.queued, metrics.completed), (0, 0, 3));
+        assert!(metrics.interactive.max_wait_ms >= 15);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_requests_run_before_background() {
+        let pool = ExecutionPool::new(limits(1, 1));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release, blocked) = oneshot::channel::<()>();
+
+        let holder = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.run("a", ExecutionPriority::Interactive, None, None, |_| async {
+                    let _ = blocked.await;
+                })
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let mut tasks = Vec::new();
+        for (name, priority) in [
+            ("background", ExecutionPriority::Background),
+            ("interactive", ExecutionPriority::Interactive),
+        ] {
+            let pool = pool.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                pool.run("a", priority, None, None, |_| async move { order.lock().push(name) }).await
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        release.send(()).unwrap();
+        holder.await.unwrap().unwrap();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(*order.lock(), vec!["interactive", "background"]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_cancellation_release_slots() {
+        let pool = ExecutionPool::new(limits(1, 1));
+
+        let timed_out = pool
+            .run("a", ExecutionPriority::Interactive, Some(Duration::from_millis(20)), None, |cancel| async move {
+                cancel.cancelled().await;
+            })
+            .await;
+        assert_eq!(timed_out, Err(PoolError::Timeout { queued: false }));
+
+        let cancelled = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.run("a", ExecutionPriority::Interactive, None, Some("job-1".to_string()), |_| {
+                    std::future::pending::<()>()
+                })
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(pool.cancel("job-1"));
+        assert_eq!(cancelled.await.unwrap(), Err(PoolError::Cancelled));
+
+        let metrics = pool.metrics();
+        assert_eq!((metrics.running, metrics.timeouts, metrics.cancellations), (0, 1, 1));
+        assert_eq!(pool.run("a", ExecutionPriority::Background, None, None, |_| async { 1 }).await, Ok(1));
+    }
+
+    #[tokio::test]
+    async fn test_queue_limit_rejects_requests() {
+        let pool = ExecutionPool::new(PoolLimits {
+            max_queue: 0,
+            ..limits(1, 1)
+        });
+        let (release, blocked) = oneshot::channel::<()>();
+        let holder = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                pool.run("a", ExecutionPriority::Interactive, None, None, |_| async {
+                    let _ = blocked.await;
+                })
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let rejected = pool.run("a", ExecutionPriority::Interactive, None, None, |_| async {}).await;
+        assert_eq!(rejected, Err(PoolError::QueueFull));
+        assert_eq!(pool.metrics().rejected, 1);
+
+        release.send(()).unwrap();
+        holder.await.unwrap().unwrap();
+    }
+}
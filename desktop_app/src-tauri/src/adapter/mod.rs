@@ -1,3 +1,6 @@
+pub mod calendar;
+pub mod execution_pool;
+
 use tauri::AppHandle;
 
 /// 初始化适配器系统
@@ -0,0 +1,404 @@
+//! # 无障碍命令模块
+//!
+//! 提供无障碍相关的设置与接口：
+//! - 减少动态效果（关闭角色待机动画，经资源预算的分配结果限制渲染帧率）
+//! - 强制高对比度主题
+//! - 将重要事件（新消息、错误）播报给平台屏幕阅读器
+//!
+//! 与安全模式的主题覆盖一样，主题和待机动画只在读取配置时覆盖，不修改磁盘上的应用配置，
+//! 关闭后恢复用户原来的选择。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+use crate::utils::resource_budget;
+use crate::AppConfig;
+
+/// 减少动态效果模式下的默认帧率上限
+pub const REDUCED_MOTION_MAX_FPS: u32 = 30;
+
+/// 高对比度模式使用的主题：内置主题中前景/背景对比度最高的深色主题
+pub const HIGH_CONTRAST_THEME: &str = "dark";
+
+/// 无障碍设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccessibilitySettings {
+    /// 减少动态效果
+    pub reduced_motion: bool,
+    /// 减少动态效果时的帧率上限
+    pub max_fps: u32,
+    /// 强制高对比度主题
+    pub high_contrast: bool,
+    /// 是否启用屏幕阅读器播报
+    pub announcements_enabled: bool,
+    /// 播报新消息
+    pub announce_new_messages: bool,
+    /// 播报错误
+    pub announce_errors: bool,
+    /// 更新时间
+    pub updated_at: i64,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            reduced_motion: false,
+            max_fps: REDUCED_MOTION_MAX_FPS,
+            high_contrast: false,
+            announcements_enabled: true,
+            announce_new_messages: true,
+            announce_errors: true,
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// 播报优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementPriority {
+    /// 礼貌播报，不打断当前朗读
+    Polite,
+    /// 立即播报，打断当前朗读
+    Assertive,
+}
+
+/// 播报事件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementKind {
+    NewMessage,
+    Error,
+    General,
+}
+
+/// 发送给前端的播报负载
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnouncementPayload {
+    pub message: String,
+    pub kind: AnnouncementKind,
+    pub priority: AnnouncementPriority,
+    /// 是否已通过平台原生接口送达（前端据此决定是否需要写入 aria-live 区域）
+    pub native: bool,
+    pub timestamp: i64,
+}
+
+/// 无障碍状态
+pub struct AccessibilityState {
+    pub settings: Mutex<AccessibilitySettings>,
+}
+
+impl Default for AccessibilityState {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(AccessibilitySettings::default()),
+        }
+    }
+}
+
+fn get_accessibility_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("accessibility_settings.json"))
+}
+
+fn save_accessibility_settings(
+    settings: &AccessibilitySettings,
+) -> Result<(), String> {
+    let config_path = get_accessibility_config_path()?;
+    let json_data = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&config_path, json_data)
+        .map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+/// 从磁盘加载无障碍设置到状态中（启动时调用）
+pub fn initialize_accessibility_settings(app_handle: &AppHandle) -> Result<(), String> {
+    let config_path = get_accessibility_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    let settings: AccessibilitySettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse settings file: {}", e))?;
+
+    if let Some(state) = app_handle.try_state::<AccessibilityState>() {
+        *state.settings.lock().unwrap() = settings.clone();
+    }
+    apply_accessibility_settings(app_handle, &settings);
+    Ok(())
+}
+
+/// 按无障碍设置覆盖配置（不修改磁盘上的配置）
+pub fn apply_config_overrides(app_handle: &AppHandle, config: &mut AppConfig) {
+    if let Some(state) = app_handle.try_state::<AccessibilityState>() {
+        override_config(&state.settings.lock().unwrap(), config);
+    }
+}
+
+fn override_config(settings: &AccessibilitySettings, config: &mut AppConfig) {
+    if settings.high_contrast {
+        config.theme.current_theme = HIGH_CONTRAST_THEME.to_string();
+        // 自定义样式和调色板可能降低对比度
+        config.theme.custom_css = None;
+        config.theme.color_blind_palette = None;
+    }
+    if settings.reduced_motion {
+        config.character.auto_idle = false;
+    }
+}
+
+/// 应用设置：更新渲染帧率上限，并把覆盖后的主题与角色配置广播到所有窗口
+fn apply_accessibility_settings(app_handle: &AppHandle, settings: &AccessibilitySettings) {
+    let fps_cap = settings.reduced_motion.then_some(settings.max_fps);
+    if let Some(allocation) = resource_budget::set_fps_cap(fps_cap) {
+        crate::commands::resource_budget::broadcast_allocation(app_handle, &allocation);
+    }
+
+    let Some(app_state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    let mut config = app_state.config.lock().clone();
+    crate::commands::safe_mode::apply_theme_override(&mut config);
+    override_config(settings, &mut config);
+
+    let _ = app_handle.emit_all("accessibility-reduced-motion", serde_json::json!({
+        "enabled": settings.reduced_motion,
+        "character": config.character,
+        "max_fps": fps_cap,
+    }));
+    let _ = app_handle.emit_all("accessibility-high-contrast", serde_json::json!({
+        "enabled": settings.high_contrast,
+        "theme": config.theme,
+    }));
+}
+
+fn update_and_apply(
+    app_handle: &AppHandle,
+    state: &State<'_, AccessibilityState>,
+    update: impl FnOnce(&mut AccessibilitySettings),
+) -> Result<AccessibilitySettings, String> {
+    let settings = {
+        let mut settings = state.settings.lock().unwrap();
+        update(&mut settings);
+        settings.max_fps = settings.max_fps.clamp(1, 240);
+        settings.updated_at = chrono::Utc::now().timestamp();
+        settings.clone()
+    };
+
+    save_accessibility_settings(&settings)?;
+    apply_accessibility_settings(app_handle, &settings);
+    Ok(settings)
+}
+
+/// 获取无障碍设置
+#[tauri::command]
+pub async fn get_accessibility_settings(
+    state: State<'_, AccessibilityState>,
+) -> Result<AccessibilitySettings, String> {
+    Ok(state.settings.lock().unwrap().clone())
+}
+
+/// 更新无障碍设置
+#[tauri::command]
+pub async fn update_accessibility_settings(
+    app_handle: AppHandle,
+    state: State<'_, AccessibilityState>,
+    settings: AccessibilitySettings,
+) -> Result<AccessibilitySettings, String> {
+    update_and_apply(&app_handle, &state, |current| *current = settings)
+}
+
+/// 启用或禁用减少动态效果模式
+#[tauri::command]
+pub async fn set_reduced_motion(
+    app_handle: AppHandle,
+    state: State<'_, AccessibilityState>,
+    enabled: bool,
+    max_fps: Option<u32>,
+) -> Result<AccessibilitySettings, String> {
+    info!("设置减少动态效果: {}", enabled);
+    update_and_apply(&app_handle, &state, |settings| {
+        settings.reduced_motion = enabled;
+        if let Some(max_fps) = max_fps {
+            settings.max_fps = max_fps;
+        }
+    })
+}
+
+/// 启用或禁用高对比度主题强制
+#[tauri::command]
+pub async fn set_high_contrast(
+    app_handle: AppHandle,
+    state: State<'_, AccessibilityState>,
+    enabled: bool,
+) -> Result<AccessibilitySettings, String> {
+    info!("设置高对比度模式: {}", enabled);
+    update_and_apply(&app_handle, &state, |settings| settings.high_contrast = enabled)
+}
+
+/// 向屏幕阅读器播报一条消息
+#[tauri::command]
+pub async fn announce_to_screen_reader(
+    app_handle: AppHandle,
+    message: String,
+    priority: Option<AnnouncementPriority>,
+) -> Result<bool, String> {
+    if message.trim().is_empty() {
+        return Err("播报内容不能为空".to_string());
+    }
+
+    Ok(announce(
+        &app_handle,
+        &message,
+        AnnouncementKind::General,
+        priority.unwrap_or(AnnouncementPriority::Polite),
+    ))
+}
+
+/// 播报重要事件
+///
+/// 根据当前无障碍设置过滤后，优先通过平台原生接口播报，
+/// 同时向前端发送 `accessibility-announcement` 事件作为 aria-live 兜底。
+/// 返回是否实际发出了播报。
+pub fn announce(
+    app_handle: &AppHandle,
+    message: &str,
+    kind: AnnouncementKind,
+    priority: AnnouncementPriority,
+) -> bool {
+    if let Some(state) = app_handle.try_state::<AccessibilityState>() {
+        let settings = state.settings.lock().unwrap();
+        if !should_announce(&settings, kind) {
+            return false;
+        }
+    }
+
+    let native = post_platform_announcement(message, priority);
+    let payload = AnnouncementPayload {
+        message: message.to_string(),
+        kind,
+        priority,
+        native,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    };
+
+    if let Err(e) = app_handle.emit_all("accessibility-announcement", payload) {
+        warn!("发送无障碍播报事件失败: {}", e);
+    }
+    true
+}
+
+fn should_announce(settings: &AccessibilitySettings, kind: AnnouncementKind) -> bool {
+    settings.announcements_enabled
+        && match kind {
+            AnnouncementKind::NewMessage => settings.announce_new_messages,
+            AnnouncementKind::Error => settings.announce_errors,
+            AnnouncementKind::General => true,
+        }
+}
+
+/// macOS: 通过 NSAccessibility 发送播报请求给 VoiceOver
+#[cfg(target_os = "macos")]
+fn post_platform_announcement(message: &str, priority: AnnouncementPriority) -> bool {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSAccessibilityAnnouncementRequestedNotification: id;
+        static NSAccessibilityAnnouncementKey: id;
+        static NSAccessibilityPriorityKey: id;
+        fn NSAccessibilityPostNotificationWithUserInfo(element: id, notification: id, user_info: id);
+    }
+
+    // NSAccessibilityPriorityMedium = 50, NSAccessibilityPriorityHigh = 90
+    let level: i64 = match priority {
+        AnnouncementPriority::Polite => 50,
+        AnnouncementPriority::Assertive => 90,
+    };
+
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let text = NSString::alloc(nil).init_str(message);
+        let priority_value: id = msg_send![class!(NSNumber), numberWithInteger: level];
+        let keys = [NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey];
+        let values = [text, priority_value];
+        let user_info: id = msg_send![
+            class!(NSDictionary),
+            dictionaryWithObjects: values.as_ptr()
+            forKeys: keys.as_ptr()
+            count: 2usize
+        ];
+        NSAccessibilityPostNotificationWithUserInfo(
+            app,
+            NSAccessibilityAnnouncementRequestedNotification,
+            user_info,
+        );
+        // alloc/init 返回的字符串由调用方持有，字典已保留自己的引用
+        let _: () = msg_send![text, release];
+    }
+    true
+}
+
+/// 其他平台：由前端 aria-live 区域转交给 Narrator / Orca
+#[cfg(not(target_os = "macos"))]
+fn post_platform_announcement(_message: &str, _priority: AnnouncementPriority) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_settings() {
+        let settings = AccessibilitySettings::default();
+        assert!(!settings.reduced_motion);
+        assert!(!settings.high_contrast);
+        assert_eq!(settings.max_fps, REDUCED_MOTION_MAX_FPS);
+    }
+
+    #[test]
+    fn test_override_config_forces_theme_and_disables_idle() {
+        let mut config = AppConfig::default();
+        config.theme.custom_css = Some("body { color: #777; }".to_string());
+        let mut settings = AccessibilitySettings::default();
+
+        override_config(&settings, &mut config);
+        assert_eq!(config.theme.current_theme, AppConfig::default().theme.current_theme);
+        assert!(config.character.auto_idle);
+
+        settings.high_contrast = true;
+        settings.reduced_motion = true;
+        override_config(&settings, &mut config);
+        assert_eq!(config.theme.current_theme, HIGH_CONTRAST_THEME);
+        assert_eq!(config.theme.custom_css, None);
+        assert!(!config.character.auto_idle);
+    }
+
+    #[test]
+    fn test_should_announce_respects_settings() {
+        let mut settings = AccessibilitySettings::default();
+        assert!(should_announce(&settings, AnnouncementKind::NewMessage));
+
+        settings.announce_errors = false;
+        assert!(!should_announce(&settings, AnnouncementKind::Error));
+        assert!(should_announce(&settings, AnnouncementKind::General));
+
+        settings.announcements_enabled = false;
+        assert!(!should_announce(&settings, AnnouncementKind::General));
+    }
+}
@@ -0,0 +1,209 @@
+//! # 成就命令模块
+//!
+//! 聊天、工作流等功能在完成操作后调用 [`track`] 记录指标，随后检查成就是否解锁；
+//! 新解锁的成就通过 `achievement-unlocked` 事件和系统通知告知用户。
+//!
+//! 适配器可以通过 `register_adapter_achievement` 注册自己的成就，并用
+//! `report_adapter_achievement_progress` 上报指标；Rust 侧模块可直接调用 [`register_achievement`]。
+
+use chrono::{Datelike, NaiveDate, Utc};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::notifications::{self, NewNotification};
+use crate::database::achievement::{
+    self, AchievementCriterion, AchievementDefinition, AchievementStatus, METRIC_MESSAGES_SENT,
+};
+use crate::state::tray_state::NotificationType;
+use crate::utils::clock;
+use crate::utils::i18n;
+
+fn registry() -> Result<std::sync::Arc<crate::database::Database>, String> {
+    crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())
+}
+
+fn today() -> NaiveDate {
+    let now = clock::global().local_now();
+    NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()).unwrap_or_default()
+}
+
+fn adapter_source(adapter_id: &str) -> String {
+    format!("adapter:{}", adapter_id)
+}
+
+fn adapter_metric(adapter_id: &str, metric: &str) -> String {
+    format!("adapter:{}:{}", adapter_id, metric)
+}
+
+/// 内置成就与注册的成就
+async fn all_definitions() -> Result<Vec<AchievementDefinition>, String> {
+    let db = registry()?;
+    let mut definitions = achievement::builtin_definitions();
+    definitions.extend(
+        db.achievement_registry
+            .definitions()
+            .await
+            .map_err(|e| format!("读取成就定义失败: {}", e))?,
+    );
+    Ok(definitions)
+}
+
+async fn load_statuses() -> Result<Vec<AchievementStatus>, String> {
+    let db = registry()?;
+    let definitions = all_definitions().await?;
+    let counters = db
+        .achievement_registry
+        .counters()
+        .await
+        .map_err(|e| format!("读取成就进度失败: {}", e))?;
+    let days = db
+        .achievement_registry
+        .recent_active_days()
+        .await
+        .map_err(|e| format!("读取活跃日期失败: {}", e))?;
+    let unlocks = db
+        .achievement_registry
+        .unlocks()
+        .await
+        .map_err(|e| format!("读取成就解锁记录失败: {}", e))?;
+    let streak = achievement::current_streak(&days, today());
+
+    Ok(definitions
+        .into_iter()
+        .map(|definition| AchievementStatus {
+            progress: definition.criterion.progress(&counters, streak),
+            target: definition.criterion.target(),
+            unlocked_at: unlocks.get(&definition.id).copied(),
+            definition,
+        })
+        .collect())
+}
+
+/// 解锁已达成的成就，返回本次新解锁的成就
+async fn check_unlocks(app: &AppHandle) -> Result<Vec<AchievementStatus>, String> {
+    let db = registry()?;
+    let now = Utc::now().timestamp();
+    let mut unlocked = Vec::new();
+    for mut status in load_statuses().await? {
+        if status.unlocked_at.is_some() || status.progress < status.target {
+            continue;
+        }
+        let inserted = db
+            .achievement_registry
+            .unlock(&status.definition.id, now)
+            .await
+            .map_err(|e| format!("保存成就解锁记录失败: {}", e))?;
+        if inserted {
+            status.unlocked_at = Some(now);
+            notify_unlocked(app, &status);
+            unlocked.push(status);
+        }
+    }
+    Ok(unlocked)
+}
+
+fn notify_unlocked(app: &AppHandle, status: &AchievementStatus) {
+    info!("解锁成就: {}", status.definition.id);
+    let _ = app.emit_all("achievement-unlocked", status);
+
+    notifications::post(
+        app,
+        NewNotification::new(
+            i18n::t_args("notify-achievement-unlocked", &[("title", status.definition.title.as_str())]),
+            status.definition.description.clone(),
+            NotificationType::Success,
+        )
+        .source("achievements"),
+    );
+}
+
+/// 累加指标并检查成就
+pub async fn record_activity(app: &AppHandle, metric: &str, amount: i64) -> Result<Vec<AchievementStatus>, String> {
+    let db = registry()?;
+    db.achievement_registry
+        .increment(metric, amount, Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("更新成就进度失败: {}", e))?;
+    // 连续天数按发送消息的日期计算
+    if metric == METRIC_MESSAGES_SENT {
+        db.achievement_registry
+            .record_active_day(today())
+            .await
+            .map_err(|e| format!("记录活跃日期失败: {}", e))?;
+    }
+    check_unlocks(app).await
+}
+
+/// 在后台记录指标（不阻塞调用方，失败只记录日志）
+pub fn track(app: &AppHandle, metric: &str, amount: i64) {
+    let app = app.clone();
+    let metric = metric.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = record_activity(&app, &metric, amount).await {
+            warn!("记录成就指标 {} 失败: {}", metric, e);
+        }
+    });
+}
+
+/// 注册成就（供其他模块与适配器使用）
+pub async fn register_achievement(definition: AchievementDefinition) -> Result<(), String> {
+    definition.validate()?;
+    if achievement::builtin_definitions().iter().any(|d| d.id == definition.id) {
+        return Err(format!("成就 ID 与内置成就冲突: {}", definition.id));
+    }
+    registry()?
+        .achievement_registry
+        .save_definition(&definition, Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("保存成就定义失败: {}", e))
+}
+
+/// 删除适配器注册的成就（卸载适配器时调用）
+pub async fn remove_adapter_achievements(adapter_id: &str) -> Result<u64, String> {
+    registry()?
+        .achievement_registry
+        .delete_definitions_by_source(&adapter_source(adapter_id))
+        .await
+        .map_err(|e| format!("删除适配器成就失败: {}", e))
+}
+
+/// 获取所有成就及进度
+#[tauri::command]
+pub async fn get_achievements() -> Result<Vec<AchievementStatus>, String> {
+    load_statuses().await
+}
+
+/// 适配器注册成就
+///
+/// 成就 ID 与计数指标会自动加上适配器前缀，避免与其他来源冲突。
+#[tauri::command]
+pub async fn register_adapter_achievement(
+    adapter_id: String,
+    mut definition: AchievementDefinition,
+) -> Result<AchievementDefinition, String> {
+    if adapter_id.trim().is_empty() {
+        return Err("适配器 ID 不能为空".to_string());
+    }
+    definition.id = format!("{}:{}", adapter_id, definition.id);
+    definition.source = adapter_source(&adapter_id);
+    if let AchievementCriterion::Counter { metric, .. } = &mut definition.criterion {
+        *metric = adapter_metric(&adapter_id, metric);
+    }
+    register_achievement(definition.clone()).await?;
+    Ok(definition)
+}
+
+/// 适配器上报指标，返回新解锁的成就
+#[tauri::command]
+pub async fn report_adapter_achievement_progress(
+    app_handle: AppHandle,
+    adapter_id: String,
+    metric: String,
+    amount: Option<i64>,
+) -> Result<Vec<AchievementStatus>, String> {
+    let amount = amount.unwrap_or(1);
+    if amount <= 0 {
+        return Err("指标增量必须大于 0".to_string());
+    }
+    record_activity(&app_handle, &adapter_metric(&adapter_id, &metric), amount).await
+}
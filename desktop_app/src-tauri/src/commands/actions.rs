@@ -0,0 +1,302 @@
+//! # 统一动作命令模块
+//!
+//! 将托盘菜单、快速对话和右键菜单中的所有动作汇总到一个目录中，
+//! 供命令面板（`list_all_actions` / `invoke_action`）和键盘快捷键调用，
+//! 从而支持完全键盘操作。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::commands::quick_chat;
+use crate::commands::shortcuts::{self, ShortcutConfig, ShortcutRegistry};
+use crate::events::tray::TrayEventHandler;
+use crate::utils::i18n;
+
+/// 动作来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionSource {
+    /// 系统托盘菜单
+    Tray,
+    /// 快速对话
+    QuickChat,
+    /// 桌宠右键菜单
+    ContextMenu,
+    /// 用户定义的快捷键宏
+    Macro,
+    /// 快捷回复
+    QuickReply,
+    /// 语音输入
+    Voice,
+}
+
+/// 动作描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDescriptor {
+    /// 动作ID，形如 `tray.chat`
+    pub id: String,
+    /// 显示名称
+    pub label: String,
+    /// 分类
+    pub category: String,
+    /// 动作来源
+    pub source: ActionSource,
+    /// 当前绑定的快捷键ID
+    pub shortcut_id: Option<String>,
+}
+
+/// 内置动作目录：(ID, 名称, 分类, 来源)
+const BUILTIN_ACTIONS: &[(&str, &str, &str, ActionSource)] = &[
+    // 托盘菜单
+    ("tray.chat", "开始对话", "chat", ActionSource::Tray),
+    ("tray.character_settings", "角色设置", "settings", ActionSource::Tray),
+    ("tray.theme_settings", "主题设置", "settings", ActionSource::Tray),
+    ("tray.adapter_settings", "适配器管理", "settings", ActionSource::Tray),
+    ("tray.sound_settings", "声音设置", "settings", ActionSource::Tray),
+    ("tray.system_settings", "系统设置", "settings", ActionSource::Tray),
+    ("tray.adapter_market", "适配器市场", "tools", ActionSource::Tray),
+    ("tray.workflow_editor", "工作流编辑器", "tools", ActionSource::Tray),
+    ("tray.screenshot", "截图", "tools", ActionSource::Tray),
+    ("tray.show_window", "显示窗口", "window", ActionSource::Tray),
+    ("tray.hide_window", "隐藏窗口", "window", ActionSource::Tray),
+    ("tray.toggle_always_on_top", "切换置顶", "window", ActionSource::Tray),
+    ("tray.character_idle", "角色待机", "character", ActionSource::Tray),
+    ("tray.character_wave", "角色挥手", "character", ActionSource::Tray),
+    ("tray.character_dance", "角色跳舞", "character", ActionSource::Tray),
+    ("tray.about", "关于", "app", ActionSource::Tray),
+    ("tray.check_updates", "检查更新", "app", ActionSource::Tray),
+    ("tray.restart", "重启应用", "app", ActionSource::Tray),
+    ("tray.quit", "退出", "app", ActionSource::Tray),
+    // 快速对话
+    ("quick_chat.open", "打开快速对话", "chat", ActionSource::QuickChat),
+    ("quick_chat.popup", "快速对话弹窗", "chat", ActionSource::QuickChat),
+    ("quick_chat.focus_input", "聚焦输入框", "chat", ActionSource::QuickChat),
+    ("quick_chat.close", "关闭快速对话", "chat", ActionSource::QuickChat),
+    // 右键菜单
+    ("context_menu.chat", "聊天", "chat", ActionSource::ContextMenu),
+    ("context_menu.settings", "设置", "settings", ActionSource::ContextMenu),
+    ("context_menu.adapters", "适配器", "settings", ActionSource::ContextMenu),
+    ("context_menu.character", "角色", "character", ActionSource::ContextMenu),
+    ("context_menu.theme-light", "浅色主题", "theme", ActionSource::ContextMenu),
+    ("context_menu.theme-dark", "深色主题", "theme", ActionSource::ContextMenu),
+    ("context_menu.minimize", "最小化", "window", ActionSource::ContextMenu),
+    ("context_menu.close", "关闭", "window", ActionSource::ContextMenu),
+    // 语音输入
+    ("voice.push_to_talk", "按键说话", "voice", ActionSource::Voice),
+];
+
+/// 内置动作名称按界面语言显示，文字表中没有时使用目录中的名称
+fn builtin_action_label(id: &str, label: &str) -> String {
+    i18n::lookup(&format!("action-{}", id.replace(['.', '_'], "-"))).unwrap_or_else(|| label.to_string())
+}
+
+/// 动作ID别名：(别名, 动作ID)
+const ACTION_ALIASES: &[(&str, &str)] = &[("quick-chat", "quick_chat.popup")];
+
+/// 将别名解析为动作ID
+fn canonical_action_id(action_id: &str) -> &str {
+    ACTION_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == action_id)
+        .map_or(action_id, |(_, id)| *id)
+}
+
+/// 将动作ID拆分为 (来源前缀, 本地ID)
+fn split_action_id(action_id: &str) -> Option<(&str, &str)> {
+    action_id.split_once('.').filter(|(prefix, local)| !prefix.is_empty() && !local.is_empty())
+}
+
+/// 收集所有可用动作（内置动作 + 宏 + 快捷回复）
+fn collect_actions(registry: &ShortcutRegistry) -> Vec<ActionDescriptor> {
+    let bound: std::collections::HashMap<String, String> = registry
+        .shortcuts
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(|b| b.config.action_id.clone().map(|action| (action, b.config.id.clone())))
+        .collect();
+
+    let mut actions: Vec<ActionDescriptor> = BUILTIN_ACTIONS
+        .iter()
+        .map(|(id, label, category, source)| ActionDescriptor {
+            id: id.to_string(),
+            label: builtin_action_label(id, label),
+            category: category.to_string(),
+            source: *source,
+            shortcut_id: bound.get(*id).cloned(),
+        })
+        .collect();
+
+    for shortcut_macro in registry.macros.lock().unwrap().values() {
+        let id = format!("macro.{}", shortcut_macro.id);
+        actions.push(ActionDescriptor {
+            shortcut_id: bound.get(&id).cloned().or_else(|| shortcut_macro.shortcut_id.clone()),
+            id,
+            label: shortcut_macro.name.clone(),
+            category: "macro".to_string(),
+            source: ActionSource::Macro,
+        });
+    }
+
+    for reply in registry.quick_replies.lock().unwrap().values() {
+        let id = format!("quick_reply.{}", reply.id);
+        actions.push(ActionDescriptor {
+            shortcut_id: bound.get(&id).cloned().or_else(|| reply.shortcut_id.clone()),
+            id,
+            label: reply.label.clone(),
+            category: "quick_reply".to_string(),
+            source: ActionSource::QuickReply,
+        });
+    }
+
+    actions
+}
+
+/// 动作ID（或别名）是否存在
+pub(crate) fn is_known_action(registry: &ShortcutRegistry, action_id: &str) -> bool {
+    let action_id = canonical_action_id(action_id);
+    collect_actions(registry).iter().any(|a| a.id == action_id)
+}
+
+/// 执行动作
+///
+/// 托盘动作、快速对话弹窗与按键说话直接在后端执行；其余快速对话、右键菜单、宏和快捷回复通过事件交给前端处理。
+pub fn dispatch_action(
+    app_handle: &AppHandle,
+    action_id: &str,
+    payload: Option<serde_json::Value>,
+) -> Result<(), String> {
+    let action_id = canonical_action_id(action_id);
+    let (prefix, local_id) =
+        split_action_id(action_id).ok_or_else(|| format!("无效的动作ID: {}", action_id))?;
+
+    info!("执行动作: {}", action_id);
+
+    match prefix {
+        "tray" => {
+            if !BUILTIN_ACTIONS.iter().any(|(id, ..)| *id == action_id) {
+                return Err(format!("未知的托盘动作: {}", action_id));
+            }
+            TrayEventHandler::new(app_handle.clone()).handle_menu_item_click(local_id);
+            Ok(())
+        }
+        "quick_chat" if local_id == "popup" => quick_chat::open_quick_chat(app_handle),
+        "voice" if local_id == "push_to_talk" => {
+            crate::commands::audio::set_push_to_talk_state(app_handle, None).map(|_| ())
+        }
+        "quick_chat" | "context_menu" => {
+            if !BUILTIN_ACTIONS.iter().any(|(id, ..)| *id == action_id) {
+                return Err(format!("未知的动作: {}", action_id));
+            }
+            let event = if prefix == "quick_chat" { "quick-chat-action" } else { "context-menu-action" };
+            app_handle
+                .emit_all(event, serde_json::json!({
+                    "action": local_id,
+                    "payload": payload,
+                    "timestamp": chrono::Utc::now().timestamp_millis(),
+                }))
+                .map_err(|e| format!("发送动作事件失败: {}", e))
+        }
+        "macro" | "quick_reply" => {
+            let registry = app_handle.state::<ShortcutRegistry>();
+            let event_payload = if prefix == "macro" {
+                let macros = registry.macros.lock().unwrap();
+                let shortcut_macro = macros.get(local_id).ok_or_else(|| format!("宏 {} 不存在", local_id))?;
+                serde_json::json!({ "macro": shortcut_macro, "payload": payload })
+            } else {
+                let replies = registry.quick_replies.lock().unwrap();
+                let reply = replies.get(local_id).ok_or_else(|| format!("快捷回复 {} 不存在", local_id))?;
+                serde_json::json!({ "reply": reply, "payload": payload })
+            };
+            let event = if prefix == "macro" { "macro-action" } else { "quick-reply-action" };
+            app_handle
+                .emit_all(event, event_payload)
+                .map_err(|e| format!("发送动作事件失败: {}", e))
+        }
+        _ => Err(format!("未知的动作来源: {}", prefix)),
+    }
+}
+
+/// 列出所有可通过键盘调用的动作
+#[tauri::command]
+pub async fn list_all_actions(
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<ActionDescriptor>, String> {
+    Ok(collect_actions(&registry))
+}
+
+/// 调用指定动作
+#[tauri::command]
+pub async fn invoke_action(
+    app_handle: AppHandle,
+    id: String,
+    payload: Option<serde_json::Value>,
+) -> Result<(), String> {
+    dispatch_action(&app_handle, &id, payload)?;
+
+    let _ = app_handle.emit_all("action-invoked", serde_json::json!({
+        "id": id,
+        "timestamp": chrono::Utc::now().timestamp_millis(),
+    }));
+    Ok(())
+}
+
+/// 将动作绑定到全局快捷键
+///
+/// 快捷键触发时直接在后端执行动作，主窗口隐藏时同样有效。
+#[tauri::command]
+pub async fn bind_action_shortcut(
+    app_handle: AppHandle,
+    registry: State<'_, ShortcutRegistry>,
+    action_id: String,
+    mut config: ShortcutConfig,
+) -> Result<String, String> {
+    shortcuts::validate_shortcut_config(config.clone())?;
+
+    config.scope = "global".to_string();
+    config.action_id = Some(action_id);
+    shortcuts::register_shortcut(app_handle, registry, config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_action_id() {
+        assert_eq!(split_action_id("tray.chat"), Some(("tray", "chat")));
+        assert_eq!(split_action_id("context_menu.theme-dark"), Some(("context_menu", "theme-dark")));
+        assert_eq!(split_action_id("tray"), None);
+        assert_eq!(split_action_id(".chat"), None);
+        assert_eq!(canonical_action_id("quick-chat"), "quick_chat.popup");
+        assert_eq!(canonical_action_id("tray.chat"), "tray.chat");
+    }
+
+    #[test]
+    fn test_builtin_action_ids_are_unique() {
+        let mut ids: Vec<_> = BUILTIN_ACTIONS.iter().map(|(id, ..)| *id).collect();
+        let total = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), total);
+    }
+
+    #[test]
+    fn test_collect_actions_includes_quick_replies() {
+        let registry = ShortcutRegistry::new();
+        registry.quick_replies.lock().unwrap().insert(
+            "greet".to_string(),
+            crate::commands::shortcuts::QuickReply {
+                id: "greet".to_string(),
+                label: "Greet".to_string(),
+                text: "Hello".to_string(),
+                shortcut_id: None,
+            },
+        );
+
+        let actions = collect_actions(&registry);
+        assert!(actions.iter().any(|a| a.id == "quick_reply.greet" && a.source == ActionSource::QuickReply));
+        assert!(actions.iter().any(|a| a.id == "tray.chat"));
+    }
+}
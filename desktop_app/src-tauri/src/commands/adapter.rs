@@ -7,11 +7,23 @@ use serde::{Deserialize, Serialize};
 use tracing::{info, error, warn};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
 
 use crate::{
     commands::*,
+    commands::cache::{self, CachedCommand},
+    config::BackendFeature,
+    http::trust_store::PinnedSend,
     state::AppState,
+    utils::adapter_cache::{
+        self, AdapterCacheSettings, AdapterUsageStats, CacheMetadata, CachePolicy, CacheSource, CachedResult,
+    },
+    utils::adapter_dev,
+    utils::metric_registry,
+    utils::safe_mode::{self, SkippedKind},
+    adapter::execution_pool::{self, ExecutionPriority, PoolLimits, PoolMetrics},
+    commands::adapter_logs,
+    commands::permission_consent::{self, PermissionRequirement},
+    database::logging::LogLevel,
 };
 
 // ================================
@@ -74,6 +86,12 @@ pub struct AdapterCapability {
     pub required_params: Vec<String>,
     /// Optional parameters
     pub optional_params: Vec<String>,
+    /// Result cache policy (only deterministic capabilities should declare one)
+    #[serde(default)]
+    pub cache: Option<CachePolicy>,
+    /// Permissions the user must grant before this capability runs
+    #[serde(default)]
+    pub permissions: Vec<PermissionRequirement>,
 }
 
 /// Adapter resource requirements
@@ -190,8 +208,41 @@ pub struct AdapterExecutionRequest {
     pub action: String,
     /// Parameters
     pub params: HashMap<String, serde_json::Value>,
-    /// Execution timeout (seconds)
+    /// Execution timeout (seconds), covering both queueing and execution
     pub timeout: Option<u64>,
+    /// Scheduling priority in the execution pool
+    #[serde(default)]
+    pub priority: ExecutionPriority,
+    /// Caller-chosen ID used to cancel the execution
+    #[serde(default)]
+    pub execution_id: Option<String>,
+}
+
+/// Adapter execution response
+///
+/// Serializes like a plain `CommandResponse` with an extra `cache` field, so callers
+/// reading `data` are unaffected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterExecutionResponse {
+    #[serde(flatten)]
+    pub response: CommandResponse<serde_json::Value>,
+    /// Cache information, present when the capability is cacheable
+    pub cache: Option<CacheMetadata>,
+}
+
+impl AdapterExecutionResponse {
+    fn plain(response: CommandResponse<serde_json::Value>) -> Self {
+        Self { response, cache: None }
+    }
+}
+
+/// Result of purging cached adapter results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterCachePurgeReport {
+    /// Entries removed from the in-memory cache
+    pub memory_entries: usize,
+    /// Entries removed from Redis
+    pub redis_entries: u64,
 }
 
 /// Adapter configuration update request
@@ -248,7 +299,7 @@ pub async fn get_adapters(
 ) -> Result<CommandResponse<Vec<AdapterInfo>>, String> {
     info!("获取适配器列表");
     
-    match get_adapters_from_backend().await {
+    match cache::cached(CachedCommand::Adapters, "", get_adapters_from_backend).await {
         Ok(adapters) => {
             info!("成功获取 {} 个适配器", adapters.len());
     Ok(CommandResponse::success(adapters))
@@ -273,6 +324,7 @@ pub async fn install_adapter(
         Ok(success) => {
             if success {
                 info!("适配器 {} 安装成功", request.adapter_id);
+                cache::invalidate(CachedCommand::Adapters).await;
     Ok(CommandResponse::success_with_message(
         true,
                     format!("适配器 {} 安装成功", request.adapter_id),
@@ -302,6 +354,9 @@ pub async fn uninstall_adapter(
         Ok(success) => {
             if success {
                 info!("适配器 {} 卸载成功", adapter_id);
+                adapter_logs::record(&adapter_id, LogLevel::Info, "适配器已卸载", None);
+                cache::invalidate(CachedCommand::Adapters).await;
+                purge_cached_results(Some(&adapter_id), None).await;
     Ok(CommandResponse::success_with_message(
         true,
         format!("适配器 {} 已卸载", adapter_id),
@@ -324,17 +379,88 @@ pub async fn execute_adapter(
     request: AdapterExecutionRequest,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<CommandResponse<serde_json::Value>, String> {
+) -> Result<AdapterExecutionResponse, String> {
     info!("执行适配器操作: {} - {}", request.adapter_id, request.action);
+    if let Err(e) = safe_mode::ensure_allowed(SkippedKind::Adapter, &request.adapter_id) {
+        return Ok(AdapterExecutionResponse::plain(CommandResponse::error(e)));
+    }
+    
+    let capability = find_capability(&request.adapter_id, &request.action).await;
+    if let Some(capability) = &capability {
+        // 缺少的权限会请用户确认，执行一直等到用户作出决定
+        if let Err(e) = permission_consent::ensure_permissions(&app_handle, &request.adapter_id, &capability.permissions).await {
+            adapter_logs::record(&request.adapter_id, LogLevel::Warn, format!("操作 {} 被拦截: {}", request.action, e), None);
+            return Ok(AdapterExecutionResponse::plain(CommandResponse::error(e)));
+        }
+    }
     
-    match execute_adapter_action(&request).await {
+    let cache_policy = adapter_cache::effective_policy(
+        &request.adapter_id,
+        capability.and_then(|capability| capability.cache),
+    );
+    let cache_key = cache_policy
+        .as_ref()
+        .map(|_| adapter_cache::cache_key(&request.adapter_id, &request.action, &request.params));
+    if let (Some(key), Some(policy)) = (&cache_key, &cache_policy) {
+        if let Some((result, source)) = lookup_cached_result(key, &request.adapter_id, &request.action, policy).await {
+            info!("适配器 {} 操作 {} 命中结果缓存", request.adapter_id, request.action);
+            return Ok(AdapterExecutionResponse {
+                cache: Some(CacheMetadata::hit(key.clone(), &result, source)),
+                response: CommandResponse::success(result.value),
+            });
+        }
+    }
+    
+    let started = std::time::Instant::now();
+    let timeout = request.timeout.filter(|secs| *secs > 0).map(std::time::Duration::from_secs);
+    let outcome = execution_pool::global()
+        .run(
+            &request.adapter_id,
+            request.priority,
+            timeout,
+            request.execution_id.clone(),
+            |_| execute_adapter_action(&request),
+        )
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    adapter_cache::record_execution(&request.adapter_id, &request.action, started.elapsed(), outcome.is_ok());
+    metric_registry::record_outcome(
+        metric_registry::ADAPTER_EXECUTIONS_TOTAL,
+        metric_registry::ADAPTER_EXECUTION_DURATION,
+        &[("adapter", &request.adapter_id)],
+        outcome.is_ok(),
+        started.elapsed(),
+    );
+    
+    match outcome {
         Ok(result) => {
             info!("适配器 {} 操作 {} 执行成功", request.adapter_id, request.action);
-            Ok(CommandResponse::success(result))
+            adapter_logs::record_output_logs(&request.adapter_id, &result);
+            adapter_logs::record(
+                &request.adapter_id,
+                LogLevel::Info,
+                format!("操作 {} 执行成功", request.action),
+                Some(serde_json::json!({ "duration_ms": started.elapsed().as_millis() as u64 })),
+            );
+            let cache = match (cache_key, &cache_policy) {
+                (Some(key), Some(policy)) => {
+                    let stored = store_cached_result(&key, &request.adapter_id, &request.action, result.clone(), policy).await;
+                    Some(CacheMetadata::miss(key, stored.as_ref()))
+                }
+                _ => None,
+            };
+            Ok(AdapterExecutionResponse { response: CommandResponse::success(result), cache })
         }
         Err(e) => {
             error!("执行适配器操作失败: {}", e);
-            Ok(CommandResponse::error(format!("执行适配器操作失败: {}", e)))
+            adapter_logs::record(
+                &request.adapter_id,
+                LogLevel::Error,
+                format!("操作 {} 执行失败: {}", request.action, e),
+                Some(serde_json::json!({ "duration_ms": started.elapsed().as_millis() as u64 })),
+            );
+            Ok(AdapterExecutionResponse::plain(CommandResponse::error(format!("执行适配器操作失败: {}", e))))
         }
     }
 }
@@ -373,6 +499,9 @@ pub async fn update_adapter_config(
         Ok(success) => {
             if success {
                 info!("适配器 {} 配置更新成功", request.adapter_id);
+                cache::invalidate(CachedCommand::Adapters).await;
+                // 配置变化后旧的执行结果不再可信
+                purge_cached_results(Some(&request.adapter_id), None).await;
                 Ok(CommandResponse::success_with_message(
                     true,
                     format!("适配器 {} 配置已更新", request.adapter_id),
@@ -439,22 +568,29 @@ pub async fn load_adapter(
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<bool>, String> {
     info!("加载适配器: {}", adapter_id);
+    if let Err(e) = safe_mode::ensure_allowed(SkippedKind::Adapter, &adapter_id) {
+        return Ok(CommandResponse::error(e));
+    }
     
     match load_adapter_in_backend(&adapter_id).await {
         Ok(success) => {
             if success {
                 info!("适配器 {} 加载成功", adapter_id);
+                cache::invalidate(CachedCommand::Adapters).await;
+                adapter_logs::record(&adapter_id, LogLevel::Info, "适配器已加载", None);
                 Ok(CommandResponse::success_with_message(
                     true,
                     format!("适配器 {} 加载成功", adapter_id),
                 ))
             } else {
                 warn!("适配器 {} 加载失败", adapter_id);
+                adapter_logs::record(&adapter_id, LogLevel::Warn, "适配器加载失败", None);
                 Ok(CommandResponse::error(format!("适配器 {} 加载失败", adapter_id)))
             }
         }
         Err(e) => {
             error!("加载适配器失败: {}", e);
+            adapter_logs::record(&adapter_id, LogLevel::Error, format!("加载适配器失败: {}", e), None);
             Ok(CommandResponse::error(format!("加载适配器失败: {}", e)))
         }
     }
@@ -473,6 +609,7 @@ pub async fn unload_adapter(
         Ok(success) => {
             if success {
                 info!("适配器 {} 卸载成功", adapter_id);
+                cache::invalidate(CachedCommand::Adapters).await;
     Ok(CommandResponse::success_with_message(
         true,
                     format!("适配器 {} 卸载成功", adapter_id),
@@ -603,6 +740,7 @@ pub async fn toggle_adapter(
     match db.adapter_registry.set_adapter_enabled(&adapter_id, enabled).await {
         Ok(_) => {
             info!("适配器 {} 已{}", adapter_id, if enabled { "启用" } else { "禁用" });
+            cache::invalidate(CachedCommand::Adapters).await;
             Ok(CommandResponse::success_with_message(
                 true,
                 format!("适配器已{}", if enabled { "启用" } else { "禁用" }),
@@ -637,8 +775,10 @@ pub async fn remove_installed_adapter(
         }
     };
     
-    // 删除文件
-    if std::path::Path::new(&adapter.install_path).exists() {
+    // 删除文件（开发模式的适配器指向开发者的源码目录，只退出开发模式）
+    if adapter.source == adapter_dev::DEV_SOURCE {
+        crate::commands::adapter_dev::stop_session(&adapter_id);
+    } else if std::path::Path::new(&adapter.install_path).exists() {
         if let Err(e) = std::fs::remove_dir_all(&adapter.install_path) {
             warn!("删除适配器文件失败: {}", e);
         }
@@ -648,6 +788,11 @@ pub async fn remove_installed_adapter(
     match db.adapter_registry.delete_adapter(&adapter_id).await {
         Ok(_) => {
             info!("适配器 {} 已删除", adapter_id);
+            cache::invalidate(CachedCommand::Adapters).await;
+            purge_cached_results(Some(&adapter_id), None).await;
+            if let Err(e) = crate::commands::achievements::remove_adapter_achievements(&adapter_id).await {
+                warn!("{}", e);
+            }
             Ok(CommandResponse::success_with_message(
                 true,
                 "适配器已删除".to_string(),
@@ -660,6 +805,236 @@ pub async fn remove_installed_adapter(
     }
 }
 
+// ================================
+// 结果缓存与使用统计命令
+// ================================
+
+/// 结果在 Redis 中的键前缀，完整的键为 `前缀 + 资料ID: + 适配器:操作: + 缓存键`
+const ADAPTER_CACHE_KEY_PREFIX: &str = "zishu_cache:adapter:";
+
+fn adapter_cache_category(adapter_id: &str, action: &str) -> String {
+    format!("{}:{}:", adapter_id, action)
+}
+
+/// 先查内存缓存，未命中且开启持久化时再查 Redis，并把取回的结果写回内存
+async fn lookup_cached_result(
+    key: &str,
+    adapter_id: &str,
+    action: &str,
+    policy: &CachePolicy,
+) -> Option<(CachedResult, CacheSource)> {
+    if let Some(result) = adapter_cache::lookup(key, adapter_id, action) {
+        return Some((result, CacheSource::Memory));
+    }
+    if !adapter_cache::settings().persist {
+        return None;
+    }
+    let service = cache::redis_service(ADAPTER_CACHE_KEY_PREFIX).await?;
+    match service.get::<CachedResult>(&adapter_cache_category(adapter_id, action), key).await {
+        Ok(Some(result)) if result.expires_at > Utc::now() => {
+            adapter_cache::promote(key.to_string(), adapter_id, action, result.clone(), policy);
+            Some((result, CacheSource::Redis))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            warn!("读取适配器结果缓存失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 写入内存缓存，开启持久化时同时写入 Redis
+async fn store_cached_result(
+    key: &str,
+    adapter_id: &str,
+    action: &str,
+    value: serde_json::Value,
+    policy: &CachePolicy,
+) -> Option<CachedResult> {
+    let result = adapter_cache::store(key.to_string(), adapter_id, action, value, policy)?;
+    if adapter_cache::settings().persist {
+        if let Some(service) = cache::redis_service(ADAPTER_CACHE_KEY_PREFIX).await {
+            let category = adapter_cache_category(adapter_id, action);
+            if let Err(e) = service.set(&category, key, &result, policy.ttl_secs).await {
+                warn!("写入适配器结果缓存失败: {}", e);
+            }
+        }
+    }
+    Some(result)
+}
+
+/// 从内存与 Redis 中移除缓存的结果（不指定适配器时清空全部）
+pub(crate) async fn purge_cached_results(adapter_id: Option<&str>, action: Option<&str>) -> AdapterCachePurgeReport {
+    let memory_entries = adapter_cache::invalidate(adapter_id, action);
+    let pattern = format!(
+        "{}{}:{}:*",
+        cache::profile_key_prefix(ADAPTER_CACHE_KEY_PREFIX),
+        adapter_id.unwrap_or("*"),
+        action.unwrap_or("*")
+    );
+    let redis_entries = cache::delete_redis_keys(&pattern).await.unwrap_or_else(|e| {
+        warn!("清除 Redis 中的适配器结果缓存失败: {}", e);
+        0
+    });
+    AdapterCachePurgeReport { memory_entries, redis_entries }
+}
+
+fn adapter_cache_settings_path() -> Result<std::path::PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(app_data_dir.join("adapter_cache.json"))
+}
+
+/// 加载结果缓存设置（应用启动时调用）
+pub fn initialize_adapter_cache() -> Result<(), String> {
+    let path = adapter_cache_settings_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read adapter cache settings: {}", e))?;
+    let settings: AdapterCacheSettings = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse adapter cache settings: {}", e))?;
+    settings.validate()?;
+    adapter_cache::set_settings(settings);
+    Ok(())
+}
+
+/// 清除适配器执行结果缓存（不指定适配器时清空全部）
+#[tauri::command]
+pub async fn invalidate_adapter_cache(
+    adapter_id: Option<String>,
+    action: Option<String>,
+) -> Result<CommandResponse<usize>, String> {
+    let report = purge_cached_results(adapter_id.as_deref(), action.as_deref()).await;
+    info!("已清除 {} 条适配器结果缓存", report.memory_entries);
+    Ok(CommandResponse::success(report.memory_entries))
+}
+
+/// 清除适配器的全部缓存结果，包括 Redis 中持久化的结果（不指定适配器时清空全部）
+#[tauri::command]
+pub async fn purge_adapter_cache(adapter_id: Option<String>) -> Result<CommandResponse<AdapterCachePurgeReport>, String> {
+    let report = purge_cached_results(adapter_id.as_deref(), None).await;
+    info!(
+        "已清除适配器结果缓存: 内存 {} 条，Redis {} 条",
+        report.memory_entries, report.redis_entries
+    );
+    Ok(CommandResponse::success(report))
+}
+
+/// 获取结果缓存设置
+#[tauri::command]
+pub async fn get_adapter_cache_settings() -> Result<CommandResponse<AdapterCacheSettings>, String> {
+    Ok(CommandResponse::success(adapter_cache::settings()))
+}
+
+/// 更新结果缓存设置（持久化开关与按适配器的开关、有效期）
+#[tauri::command]
+pub async fn update_adapter_cache_settings(
+    settings: AdapterCacheSettings,
+) -> Result<CommandResponse<AdapterCacheSettings>, String> {
+    if let Err(e) = settings.validate() {
+        return Ok(CommandResponse::error(e));
+    }
+    let path = adapter_cache_settings_path()?;
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize adapter cache settings: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write adapter cache settings: {}", e))?;
+
+    // 设置变化的适配器按新的有效期重新缓存
+    let previous = adapter_cache::settings();
+    let changed: std::collections::BTreeSet<String> = previous
+        .adapters
+        .keys()
+        .chain(settings.adapters.keys())
+        .filter(|id| previous.adapters.get(*id) != settings.adapters.get(*id))
+        .cloned()
+        .collect();
+    adapter_cache::set_settings(settings.clone());
+    for adapter_id in changed {
+        purge_cached_results(Some(&adapter_id), None).await;
+    }
+    info!("适配器结果缓存设置已更新: 持久化 {}，单独设置 {} 个适配器", settings.persist, settings.adapters.len());
+    Ok(CommandResponse::success(settings))
+}
+
+/// 获取适配器使用统计（含缓存命中情况）
+#[tauri::command]
+pub async fn get_adapter_usage_stats(
+    adapter_id: Option<String>,
+) -> Result<CommandResponse<Vec<AdapterUsageStats>>, String> {
+    Ok(CommandResponse::success(adapter_cache::usage_stats(adapter_id.as_deref())))
+}
+
+// ================================
+// 执行池命令
+// ================================
+
+fn adapter_pool_settings_path() -> Result<std::path::PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(app_data_dir.join("adapter_pool.json"))
+}
+
+/// 加载执行池并发限制（应用启动时调用）
+pub fn initialize_adapter_pool() -> Result<(), String> {
+    let path = adapter_pool_settings_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read adapter pool settings: {}", e))?;
+    let limits: PoolLimits = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse adapter pool settings: {}", e))?;
+    limits.validate()?;
+    execution_pool::global().set_limits(limits);
+    Ok(())
+}
+
+/// 获取执行池状态与排队等待指标
+#[tauri::command]
+pub async fn get_adapter_pool_metrics() -> Result<CommandResponse<PoolMetrics>, String> {
+    Ok(CommandResponse::success(execution_pool::global().metrics()))
+}
+
+/// 获取执行池并发限制
+#[tauri::command]
+pub async fn get_adapter_pool_limits() -> Result<CommandResponse<PoolLimits>, String> {
+    Ok(CommandResponse::success(execution_pool::global().limits()))
+}
+
+/// 更新执行池并发限制
+#[tauri::command]
+pub async fn update_adapter_pool_limits(
+    limits: PoolLimits,
+) -> Result<CommandResponse<PoolLimits>, String> {
+    if let Err(e) = limits.validate() {
+        return Ok(CommandResponse::error(e));
+    }
+    let path = adapter_pool_settings_path()?;
+    let content = serde_json::to_string_pretty(&limits)
+        .map_err(|e| format!("Failed to serialize adapter pool settings: {}", e))?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write adapter pool settings: {}", e))?;
+
+    execution_pool::global().set_limits(limits.clone());
+    info!("适配器执行池限制已更新: 全局 {}，单适配器 {}", limits.max_concurrent, limits.per_adapter);
+    Ok(CommandResponse::success(limits))
+}
+
+/// 取消排队中或执行中的适配器操作
+#[tauri::command]
+pub async fn cancel_adapter_execution(execution_id: String) -> Result<CommandResponse<bool>, String> {
+    let cancelled = execution_pool::global().cancel(&execution_id);
+    if cancelled {
+        info!("已取消适配器执行: {}", execution_id);
+    }
+    Ok(CommandResponse::success(cancelled))
+}
+
 // ================================
 // 版本管理命令
 // ================================
@@ -908,12 +1283,13 @@ pub async fn add_adapter_permission(
 
 /// Get adapters from backend API
 async fn get_adapters_from_backend() -> Result<Vec<AdapterInfo>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .get(&format!("{}/api/models/", backend_url))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -932,13 +1308,14 @@ async fn get_adapters_from_backend() -> Result<Vec<AdapterInfo>, String> {
 
 /// Install adapter from backend
 async fn install_adapter_from_backend(request: &AdapterInstallRequest) -> Result<bool, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .post(&format!("{}/api/marketplace/", backend_url))
         .json(request)
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -954,7 +1331,8 @@ async fn install_adapter_from_backend(request: &AdapterInstallRequest) -> Result
 
 /// Uninstall adapter from backend
 async fn uninstall_adapter_from_backend(adapter_id: &str) -> Result<bool, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
@@ -963,7 +1341,7 @@ async fn uninstall_adapter_from_backend(adapter_id: &str) -> Result<bool, String
             "adapter_name": adapter_id,
             "force_unload": true
         }))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -979,13 +1357,14 @@ async fn uninstall_adapter_from_backend(adapter_id: &str) -> Result<bool, String
 
 /// Execute adapter action
 async fn execute_adapter_action(request: &AdapterExecutionRequest) -> Result<serde_json::Value, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .post(&format!("{}/api/models/execute", backend_url))
         .json(request)
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1002,14 +1381,32 @@ async fn execute_adapter_action(request: &AdapterExecutionRequest) -> Result<ser
     }
 }
 
+/// 查找能力声明（读取已安装适配器元数据中的 `capabilities`）
+async fn find_capability(adapter_id: &str, action: &str) -> Option<AdapterCapability> {
+    let db = get_database()?;
+    let adapter = match db.adapter_registry.get_adapter(adapter_id).await {
+        Ok(adapter) => adapter?,
+        Err(e) => {
+            warn!("读取适配器 {} 元数据失败，跳过能力声明: {}", adapter_id, e);
+            return None;
+        }
+    };
+    let capabilities = adapter.metadata.get("capabilities")?.as_array()?.clone();
+    capabilities
+        .into_iter()
+        .filter_map(|value| serde_json::from_value::<AdapterCapability>(value).ok())
+        .find(|capability| capability.name == action)
+}
+
 /// Get adapter configuration from backend
 async fn get_adapter_config_from_backend(adapter_id: &str) -> Result<HashMap<String, serde_json::Value>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .get(&format!("{}/api/models/adapter/{}/info", backend_url, adapter_id))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1028,13 +1425,14 @@ async fn get_adapter_config_from_backend(adapter_id: &str) -> Result<HashMap<Str
 
 /// Update adapter configuration in backend
 async fn update_adapter_config_in_backend(request: &AdapterConfigUpdateRequest) -> Result<bool, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .put(&format!("{}/api/models/adapter/{}/config", backend_url, request.adapter_id))
         .json(&request.config)
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1050,7 +1448,8 @@ async fn update_adapter_config_in_backend(request: &AdapterConfigUpdateRequest)
 
 /// Search adapters in marketplace
 async fn search_adapters_in_marketplace(request: &AdapterSearchRequest) -> Result<PaginatedResponse<serde_json::Value>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     let mut query_params: Vec<(&str, String)> = vec![
@@ -1085,7 +1484,7 @@ async fn search_adapters_in_marketplace(request: &AdapterSearchRequest) -> Resul
     match client
         .get(&url)
         .query(&query_pairs)
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1104,12 +1503,13 @@ async fn search_adapters_in_marketplace(request: &AdapterSearchRequest) -> Resul
 
 /// Get adapter details from backend
 async fn get_adapter_details_from_backend(adapter_id: &str) -> Result<AdapterMetadata, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
         .get(&format!("{}/api/marketplace/{}", backend_url, adapter_id))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1174,7 +1574,8 @@ async fn get_adapter_details_from_backend(adapter_id: &str) -> Result<AdapterMet
 
 /// Load adapter in backend
 async fn load_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
@@ -1183,7 +1584,7 @@ async fn load_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
             "adapter_name": adapter_id,
             "force_reload": false
         }))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1199,7 +1600,8 @@ async fn load_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
 
 /// Unload adapter in backend
 async fn unload_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     match client
@@ -1208,7 +1610,7 @@ async fn unload_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
             "adapter_name": adapter_id,
             "force_unload": false
         }))
-        .send()
+        .send_pinned()
         .await
     {
         Ok(response) => {
@@ -1224,7 +1626,8 @@ async fn unload_adapter_in_backend(adapter_id: &str) -> Result<bool, String> {
 
 /// Get adapter status from backend
 async fn get_adapter_status_from_backend(adapter_id: Option<&str>) -> Result<serde_json::Value, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Core);
     let backend_url = get_backend_url();
     
     let url = if let Some(id) = adapter_id {
@@ -1233,7 +1636,7 @@ async fn get_adapter_status_from_backend(adapter_id: Option<&str>) -> Result<ser
         format!("{}/api/models/status", backend_url)
     };
     
-    match client.get(&url).send().await {
+    match client.get(&url).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<serde_json::Value>().await {
@@ -1248,16 +1651,10 @@ async fn get_adapter_status_from_backend(adapter_id: Option<&str>) -> Result<ser
     }
 }
 
-/// Get backend URL from environment or use router
+/// Get backend URL from endpoint profiles, environment or router
 fn get_backend_url() -> String {
-    // 优先使用环境变量
-    if let Ok(url) = std::env::var("ZISHU_BACKEND_URL") {
-        return url;
-    }
-    
-    // 使用路由器获取核心服务 URL（适配器功能在核心服务）
-    let router = crate::config::ApiRouter::new();
-    router.core_url()
+    // 适配器功能默认在核心服务
+    crate::config::backend_profiles::base_url_for(BackendFeature::Core)
 }
 
 // ================================
@@ -1447,6 +1844,8 @@ mod tests {
             level: CapabilityLevel::Advanced,
             required_params: vec!["param1".to_string(), "param2".to_string()],
             optional_params: vec!["optional1".to_string()],
+            cache: None,
+            permissions: Vec::new(),
         };
 
         let serialized = serde_json::to_string(&capability).expect("序列化失败");
@@ -1558,6 +1957,8 @@ mod tests {
             action: "generate".to_string(),
             params,
             timeout: Some(30),
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
 
         assert_eq!(request.adapter_id, "test_adapter");
@@ -1795,6 +2196,8 @@ mod tests {
             action: "ping".to_string(),
             params: HashMap::new(),
             timeout: Some(1), // Very short timeout
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         
         assert_eq!(request.params.len(), 0);
@@ -1811,6 +2214,8 @@ mod tests {
             action: "process".to_string(),
             params: large_params,
             timeout: None,
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         
         assert_eq!(large_request.params.len(), 1000);
@@ -2106,6 +2511,8 @@ mod tests {
             action: "test".to_string(),
             params: HashMap::new(),
             timeout: Some(0), // 0 seconds
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         assert_eq!(request_small.timeout, Some(0));
 
@@ -2115,6 +2522,8 @@ mod tests {
             action: "test".to_string(),
             params: HashMap::new(),
             timeout: Some(u64::MAX), // Maximum possible timeout
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         assert_eq!(request_large.timeout, Some(u64::MAX));
 
@@ -2124,6 +2533,8 @@ mod tests {
             action: "test".to_string(),
             params: HashMap::new(),
             timeout: None,
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         assert_eq!(request_none.timeout, None);
     }
@@ -2164,6 +2575,8 @@ mod tests {
             level: CapabilityLevel::Expert,
             required_params: (0..100).map(|i| format!("required_param_{}", i)).collect(),
             optional_params: (0..200).map(|i| format!("optional_param_{}", i)).collect(),
+            cache: Some(CachePolicy::default()),
+            permissions: Vec::new(),
         };
 
         assert_eq!(capability.required_params.len(), 100);
@@ -2284,6 +2697,8 @@ mod tests {
             action: "generate".to_string(),
             params: execution_params,
             timeout: Some(30),
+            priority: ExecutionPriority::Interactive,
+            execution_id: None,
         };
         
         // Verify all requests are properly structured
@@ -2336,7 +2751,67 @@ pub fn get_command_metadata() -> std::collections::HashMap<String, CommandMetada
         name: "execute_adapter".to_string(),
         description: "执行适配器操作".to_string(),
         input_type: Some("AdapterExecutionRequest".to_string()),
-        output_type: Some("serde_json::Value".to_string()),
+        output_type: Some("AdapterExecutionResponse".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("invalidate_adapter_cache".to_string(), CommandMetadata {
+        name: "invalidate_adapter_cache".to_string(),
+        description: "清除适配器执行结果缓存".to_string(),
+        input_type: Some("Option<String>, Option<String>".to_string()),
+        output_type: Some("usize".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("purge_adapter_cache".to_string(), CommandMetadata {
+        name: "purge_adapter_cache".to_string(),
+        description: "清除适配器的全部缓存结果（含 Redis）".to_string(),
+        input_type: Some("Option<String>".to_string()),
+        output_type: Some("AdapterCachePurgeReport".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("get_adapter_cache_settings".to_string(), CommandMetadata {
+        name: "get_adapter_cache_settings".to_string(),
+        description: "获取适配器结果缓存设置".to_string(),
+        input_type: None,
+        output_type: Some("AdapterCacheSettings".to_string()),
+        required_permission: PermissionLevel::Public,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("update_adapter_cache_settings".to_string(), CommandMetadata {
+        name: "update_adapter_cache_settings".to_string(),
+        description: "更新适配器结果缓存设置".to_string(),
+        input_type: Some("AdapterCacheSettings".to_string()),
+        output_type: Some("AdapterCacheSettings".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("get_adapter_usage_stats".to_string(), CommandMetadata {
+        name: "get_adapter_usage_stats".to_string(),
+        description: "获取适配器使用统计".to_string(),
+        input_type: Some("Option<String>".to_string()),
+        output_type: Some("Vec<AdapterUsageStats>".to_string()),
+        required_permission: PermissionLevel::Public,
+        is_async: true,
+        category: "adapter".to_string(),
+    });
+    
+    metadata.insert("cancel_adapter_execution".to_string(), CommandMetadata {
+        name: "cancel_adapter_execution".to_string(),
+        description: "取消适配器执行".to_string(),
+        input_type: Some("String".to_string()),
+        output_type: Some("bool".to_string()),
         required_permission: PermissionLevel::User,
         is_async: true,
         category: "adapter".to_string(),
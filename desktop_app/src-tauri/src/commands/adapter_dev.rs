@@ -0,0 +1,462 @@
+//! # 适配器开发模式命令
+//!
+//! `start_adapter_dev` 指向本地的适配器源码目录：校验 `adapter.json` 后以来源 `dev` 登记到已安装
+//! 适配器（不校验签名，元数据带 `dev` 标记），启动入口进程并监控目录。文件变化后重新校验清单并
+//! 重启进程，清单有误时保留旧进程。
+//!
+//! 进程的标准输出与标准错误逐行以 `dev-adapter-log` 事件推送；启动、退出与重新加载的消息同时
+//! 写入该适配器的日志流（见 [`adapter_logs`]）。清单格式见 [`crate::utils::adapter_dev`]。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::commands::adapter_logs;
+use crate::config::BackendFeature;
+use crate::database::adapter::{AdapterInstallStatus, InstalledAdapter};
+use crate::database::logging::LogLevel;
+use crate::utils::adapter_dev::{self, DevAdapterManifest, DevLogLine, DevLogStream, DEV_SOURCE};
+use crate::utils::clock;
+use crate::utils::resource_accounting::{self, Subsystem};
+use crate::utils::safe_mode::{self, SkippedKind};
+
+/// 文件变化归并窗口：最后一次变化后静默这么久才重新加载
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+struct DevSession {
+    dir: PathBuf,
+    manifest: DevAdapterManifest,
+    generation: u32,
+    running: bool,
+    started_at: i64,
+    last_reload_at: Option<i64>,
+    last_error: Option<String>,
+    /// 通知当前进程退出
+    stop: Option<oneshot::Sender<()>>,
+    /// 保持文件监控存活
+    _watcher: RecommendedWatcher,
+}
+
+lazy_static! {
+    /// 适配器 ID -> 开发会话
+    static ref SESSIONS: Mutex<HashMap<String, DevSession>> = Mutex::new(HashMap::new());
+    /// 串行化启动与重新加载，避免同时拉起两个进程
+    static ref RELOAD_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 开发中的适配器状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DevAdapterInfo {
+    pub adapter_id: String,
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    /// 界面上显示的标记
+    pub badge: &'static str,
+    pub signed: bool,
+    pub running: bool,
+    pub generation: u32,
+    pub started_at: i64,
+    pub last_reload_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl DevSession {
+    fn info(&self) -> DevAdapterInfo {
+        DevAdapterInfo {
+            adapter_id: self.manifest.id.clone(),
+            name: self.manifest.name.clone(),
+            version: self.manifest.version.clone(),
+            path: self.dir.display().to_string(),
+            badge: DEV_SOURCE,
+            signed: false,
+            running: self.running,
+            generation: self.generation,
+            started_at: self.started_at,
+            last_reload_at: self.last_reload_at,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+fn session_info(adapter_id: &str) -> Result<DevAdapterInfo, String> {
+    SESSIONS
+        .lock()
+        .get(adapter_id)
+        .map(DevSession::info)
+        .ok_or_else(|| format!("适配器 {} 不在开发模式中", adapter_id))
+}
+
+fn emit_line(app: &AppHandle, adapter_id: &str, stream: DevLogStream, generation: u32, line: String) {
+    let _ = app.emit_all(
+        "dev-adapter-log",
+        DevLogLine {
+            adapter_id: adapter_id.to_string(),
+            stream,
+            line,
+            generation,
+            timestamp: clock::now().timestamp_millis(),
+        },
+    );
+}
+
+/// 开发模式自身的消息：推送给前端并写入适配器日志
+fn system_message(app: &AppHandle, adapter_id: &str, generation: u32, level: LogLevel, message: String) {
+    adapter_logs::record(adapter_id, level, message.clone(), Some(serde_json::json!({ "dev": true })));
+    emit_line(app, adapter_id, DevLogStream::System, generation, message);
+}
+
+fn pump_lines<R>(app: AppHandle, adapter_id: String, stream: DevLogStream, generation: u32, reader: R)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            emit_line(&app, &adapter_id, stream, generation, line);
+        }
+    });
+}
+
+/// 启动入口进程，返回通知其退出的句柄
+fn spawn_process(
+    app: &AppHandle,
+    dir: &Path,
+    manifest: &DevAdapterManifest,
+    generation: u32,
+) -> Result<oneshot::Sender<()>, String> {
+    let (program, args) = manifest.launch_command(dir);
+    let mut command = tokio::process::Command::new(&program);
+    command
+        .args(&args)
+        .current_dir(dir)
+        .envs(&manifest.env)
+        .env("ZISHU_ADAPTER_ID", &manifest.id)
+        .env("ZISHU_ADAPTER_DEV", "1")
+        .env("ZISHU_BACKEND_URL", crate::config::backend_profiles::base_url_for(BackendFeature::Core))
+        .env("PYTHONUNBUFFERED", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("启动适配器进程 {} 失败: {}", program.display(), e))?;
+
+    let pid = child.id();
+    if let Some(pid) = pid {
+        resource_accounting::register_process(pid, Subsystem::Adapters);
+    }
+    let adapter_id = manifest.id.clone();
+    if let Some(stdout) = child.stdout.take() {
+        pump_lines(app.clone(), adapter_id.clone(), DevLogStream::Stdout, generation, stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        pump_lines(app.clone(), adapter_id.clone(), DevLogStream::Stderr, generation, stderr);
+    }
+
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let task_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::select! {
+            status = child.wait() => {
+                let message = match status {
+                    Ok(status) => format!("适配器进程已退出: {}", status),
+                    Err(e) => format!("等待适配器进程失败: {}", e),
+                };
+                if let Some(session) = SESSIONS.lock().get_mut(&adapter_id) {
+                    if session.generation == generation {
+                        session.running = false;
+                    }
+                }
+                system_message(&task_app, &adapter_id, generation, LogLevel::Warn, message);
+            }
+            _ = stop_rx => {
+                if let Err(e) = child.kill().await {
+                    warn!("结束适配器 {} 的开发进程失败: {}", adapter_id, e);
+                }
+            }
+        }
+        if let Some(pid) = pid {
+            resource_accounting::unregister_process(pid);
+        }
+    });
+
+    system_message(
+        app,
+        &manifest.id,
+        generation,
+        LogLevel::Info,
+        format!("已启动适配器进程: {} {}", program.display(), args.join(" ")),
+    );
+    Ok(stop_tx)
+}
+
+/// 以来源 `dev` 登记到已安装适配器；已有同 ID 的正式适配器时拒绝
+async fn register(dir: &Path, manifest: &DevAdapterManifest) -> Result<(), String> {
+    let Some(db) = crate::database::get_database() else {
+        warn!("数据库未初始化，开发中的适配器 {} 未登记", manifest.id);
+        return Ok(());
+    };
+    let existing = db
+        .adapter_registry
+        .get_adapter(&manifest.id)
+        .await
+        .map_err(|e| format!("查询适配器失败: {}", e))?;
+    if existing.as_ref().is_some_and(|adapter| adapter.source != DEV_SOURCE) {
+        return Err(format!("已安装 ID 为 {} 的正式适配器，请修改开发中适配器的 ID", manifest.id));
+    }
+
+    let exists = existing.is_some();
+    let now = Utc::now();
+    let mut metadata = HashMap::new();
+    metadata.insert("dev".to_string(), serde_json::json!(true));
+    metadata.insert("signed".to_string(), serde_json::json!(false));
+    metadata.insert("capabilities".to_string(), serde_json::json!(manifest.capabilities));
+    let adapter = InstalledAdapter {
+        id: manifest.id.clone(),
+        name: manifest.id.clone(),
+        display_name: manifest.name.clone(),
+        version: manifest.version.clone(),
+        install_path: dir.display().to_string(),
+        status: AdapterInstallStatus::Installed,
+        enabled: true,
+        auto_update: false,
+        source: DEV_SOURCE.to_string(),
+        source_id: None,
+        description: manifest.description.clone(),
+        author: manifest.author.clone(),
+        license: None,
+        homepage_url: None,
+        installed_at: existing.as_ref().map_or(now, |adapter| adapter.installed_at),
+        updated_at: now,
+        last_used_at: existing.as_ref().and_then(|adapter| adapter.last_used_at),
+        config: existing.map(|adapter| adapter.config).unwrap_or_default(),
+        metadata,
+    };
+    let result = if exists {
+        db.adapter_registry.update_adapter(adapter).await
+    } else {
+        db.adapter_registry.add_adapter(adapter).await
+    };
+    result.map_err(|e| format!("登记开发中的适配器失败: {}", e))
+}
+
+/// 监控开发目录，变化归并后重新加载
+fn watch_directory(app: AppHandle, adapter_id: String, dir: PathBuf) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+        Ok(event) if !event.kind.is_access() => {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("适配器开发目录监控出错: {}", e),
+    })
+    .map_err(|e| format!("创建文件监控失败: {}", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("监控 {} 失败: {}", dir.display(), e))?;
+
+    std::thread::Builder::new()
+        .name(format!("adapter-dev-{}", adapter_id))
+        .spawn(move || {
+            // 监控随会话结束被丢弃后通道关闭，线程随之退出
+            while let Ok(path) = rx.recv() {
+                let mut changed = vec![path];
+                while let Ok(path) = rx.recv_timeout(RELOAD_DEBOUNCE) {
+                    changed.push(path);
+                }
+                let relevant = SESSIONS.lock().get(&adapter_id).map(|session| {
+                    changed
+                        .iter()
+                        .find(|path| session.manifest.is_relevant_change(&dir, path))
+                        .cloned()
+                });
+                match relevant {
+                    None => break,
+                    Some(None) => continue,
+                    Some(Some(path)) => {
+                        info!("适配器 {} 的源码变化，重新加载: {}", adapter_id, path.display());
+                        if let Err(e) = tauri::async_runtime::block_on(reload(&app, &adapter_id)) {
+                            warn!("重新加载开发中的适配器 {} 失败: {}", adapter_id, e);
+                        }
+                    }
+                }
+            }
+        })
+        .map_err(|e| format!("启动适配器开发监控线程失败: {}", e))?;
+    Ok(watcher)
+}
+
+/// 重新校验清单并重启进程；清单有误时保留旧进程
+async fn reload(app: &AppHandle, adapter_id: &str) -> Result<DevAdapterInfo, String> {
+    let _guard = RELOAD_LOCK.lock().await;
+    let (dir, generation) = {
+        let sessions = SESSIONS.lock();
+        let session = sessions
+            .get(adapter_id)
+            .ok_or_else(|| format!("适配器 {} 不在开发模式中", adapter_id))?;
+        (session.dir.clone(), session.generation + 1)
+    };
+
+    let loaded = adapter_dev::load_manifest(&dir).and_then(|manifest| {
+        if manifest.id == adapter_id {
+            Ok(manifest)
+        } else {
+            Err(format!("开发中不能修改适配器 ID（{} -> {}），请重新启动开发模式", adapter_id, manifest.id))
+        }
+    });
+    let manifest = match loaded {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            if let Some(session) = SESSIONS.lock().get_mut(adapter_id) {
+                session.last_error = Some(e.clone());
+            }
+            system_message(app, adapter_id, generation - 1, LogLevel::Error, format!("清单校验失败，未重新加载: {}", e));
+            return Err(e);
+        }
+    };
+
+    let old_stop = SESSIONS.lock().get_mut(adapter_id).and_then(|session| session.stop.take());
+    if let Some(stop) = old_stop {
+        let _ = stop.send(());
+    }
+    let spawned = spawn_process(app, &dir, &manifest, generation);
+    if let Err(e) = register(&dir, &manifest).await {
+        warn!("{}", e);
+    }
+    // 代码变化后旧的执行结果不再可信
+    crate::commands::adapter::purge_cached_results(Some(adapter_id), None).await;
+
+    let mut sessions = SESSIONS.lock();
+    let session = sessions
+        .get_mut(adapter_id)
+        .ok_or_else(|| format!("适配器 {} 已退出开发模式", adapter_id))?;
+    session.generation = generation;
+    session.last_reload_at = Some(clock::now().timestamp());
+    session.manifest = manifest;
+    match spawned {
+        Ok(stop) => {
+            session.stop = Some(stop);
+            session.running = true;
+            session.last_error = None;
+        }
+        Err(e) => {
+            session.running = false;
+            session.last_error = Some(e.clone());
+            drop(sessions);
+            system_message(app, adapter_id, generation, LogLevel::Error, e.clone());
+            return Err(e);
+        }
+    }
+    Ok(session.info())
+}
+
+/// 结束开发会话：停止进程与目录监控，返回会话是否存在
+pub fn stop_session(adapter_id: &str) -> bool {
+    match SESSIONS.lock().remove(adapter_id) {
+        Some(mut session) => {
+            if let Some(stop) = session.stop.take() {
+                let _ = stop.send(());
+            }
+            info!("适配器 {} 已退出开发模式", adapter_id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 校验开发目录中的清单（不启动）
+#[tauri::command]
+pub async fn validate_dev_adapter(path: String) -> Result<DevAdapterManifest, String> {
+    adapter_dev::load_manifest(Path::new(&path))
+}
+
+/// 以开发模式加载本地适配器目录；同一适配器已在开发模式中时重新加载
+#[tauri::command]
+pub async fn start_adapter_dev(app_handle: AppHandle, path: String) -> Result<DevAdapterInfo, String> {
+    let dir = std::fs::canonicalize(&path).map_err(|e| format!("适配器目录不存在: {} ({})", path, e))?;
+    let manifest = adapter_dev::load_manifest(&dir)?;
+    safe_mode::ensure_allowed(SkippedKind::Adapter, &manifest.id)?;
+
+    let existing_dir = SESSIONS.lock().get(&manifest.id).map(|session| session.dir.clone());
+    match existing_dir {
+        Some(existing) if existing == dir => return reload(&app_handle, &manifest.id).await,
+        Some(existing) => {
+            return Err(format!("适配器 {} 已在开发模式中（{}）", manifest.id, existing.display()));
+        }
+        None => {}
+    }
+
+    register(&dir, &manifest).await?;
+    let _guard = RELOAD_LOCK.lock().await;
+    let watcher = watch_directory(app_handle.clone(), manifest.id.clone(), dir.clone())?;
+    let stop = spawn_process(&app_handle, &dir, &manifest, 1)?;
+    let session = DevSession {
+        dir,
+        manifest,
+        generation: 1,
+        running: true,
+        started_at: clock::now().timestamp(),
+        last_reload_at: None,
+        last_error: None,
+        stop: Some(stop),
+        _watcher: watcher,
+    };
+    let info = session.info();
+    info!("适配器 {} 进入开发模式: {}", info.adapter_id, info.path);
+    SESSIONS.lock().insert(info.adapter_id.clone(), session);
+    Ok(info)
+}
+
+/// 手动重新加载开发中的适配器
+#[tauri::command]
+pub async fn reload_adapter_dev(app_handle: AppHandle, adapter_id: String) -> Result<DevAdapterInfo, String> {
+    reload(&app_handle, &adapter_id).await
+}
+
+/// 退出开发模式，并从已安装适配器中移除（不会删除源码目录）
+#[tauri::command]
+pub async fn stop_adapter_dev(adapter_id: String) -> Result<bool, String> {
+    if !stop_session(&adapter_id) {
+        return Ok(false);
+    }
+    if let Some(db) = crate::database::get_database() {
+        let registered = db.adapter_registry.get_adapter(&adapter_id).await.ok().flatten();
+        if registered.is_some_and(|adapter| adapter.source == DEV_SOURCE) {
+            db.adapter_registry
+                .delete_adapter(&adapter_id)
+                .await
+                .map_err(|e| format!("移除开发中的适配器失败: {}", e))?;
+        }
+    }
+    crate::commands::adapter::purge_cached_results(Some(&adapter_id), None).await;
+    Ok(true)
+}
+
+/// 获取开发模式中的适配器
+#[tauri::command]
+pub async fn get_dev_adapters() -> Result<Vec<DevAdapterInfo>, String> {
+    let mut adapters: Vec<DevAdapterInfo> = SESSIONS.lock().values().map(DevSession::info).collect();
+    adapters.sort_by(|a, b| a.adapter_id.cmp(&b.adapter_id));
+    Ok(adapters)
+}
+
+/// 获取单个开发中适配器的状态
+#[tauri::command]
+pub async fn get_dev_adapter(adapter_id: String) -> Result<DevAdapterInfo, String> {
+    session_info(&adapter_id)
+}
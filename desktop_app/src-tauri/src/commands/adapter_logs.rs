@@ -0,0 +1,231 @@
+//! # 适配器日志命令模块
+//!
+//! 适配器相关的日志通过 [`record`] 写入各适配器自己的日志流（`adapter_logs` 表），
+//! 适配器执行结果中附带的 `logs` 也会写入；数据库中断期间的日志排队到恢复后再写入。`get_adapter_logs` 查询历史日志；`tail_adapter_logs`
+//! 开始实时跟踪，新日志以 `adapter-log` 事件推送（载荷带 `tail_id`），
+//! `stop_adapter_log_tail` 结束跟踪。
+//!
+//! 每个适配器的保留策略（天数与条数）可以单独配置，后台任务每小时按策略清理。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::database::database_manager;
+use crate::database::logging::{AdapterLogEntry, AdapterLogFilter, AdapterLogRetention, LogLevel};
+use crate::utils::watchdog;
+
+/// 实时跟踪的缓冲条数，跟不上时丢弃最旧的
+const TAIL_CHANNEL_CAPACITY: usize = 256;
+/// 开始跟踪时默认带上的最近日志条数
+const DEFAULT_TAIL_BACKLOG: i64 = 100;
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const RETENTION_STALL_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+
+lazy_static! {
+    static ref LOG_CHANNEL: broadcast::Sender<AdapterLogEntry> = broadcast::channel(TAIL_CHANNEL_CAPACITY).0;
+    static ref TAILS: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>> = Mutex::new(HashMap::new());
+}
+
+async fn append(
+    adapter_id: &str,
+    level: LogLevel,
+    message: &str,
+    context: Option<&serde_json::Value>,
+    timestamp_ms: i64,
+) -> Result<(), String> {
+    let db = registry()?;
+    let entry = db
+        .logging_registry
+        .append_adapter_log(adapter_id, level, message, context, timestamp_ms)
+        .await
+        .map_err(|e| format!("保存适配器 {} 的日志失败: {}", adapter_id, e))?;
+    // 没有跟踪者时发送失败，忽略即可
+    let _ = LOG_CHANNEL.send(entry);
+    Ok(())
+}
+
+/// 写入适配器日志（后台写入数据库，并推送给正在跟踪的前端）
+///
+/// 数据库连接中断期间日志先排队，恢复后按原时间写入。
+pub fn record(adapter_id: &str, level: LogLevel, message: impl Into<String>, context: Option<serde_json::Value>) {
+    let adapter_id = adapter_id.to_string();
+    let message = message.into();
+    let timestamp_ms = chrono::Utc::now().timestamp_millis();
+    if !database_manager::postgres_available() {
+        database_manager::queue_write("adapter_log", move || {
+            let (adapter_id, message, context) = (adapter_id.clone(), message.clone(), context.clone());
+            async move { append(&adapter_id, level, &message, context.as_ref(), timestamp_ms).await }
+        });
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if crate::database::get_database().is_none() {
+            debug!("数据库未初始化，适配器日志未保存: [{}] {}", adapter_id, message);
+            return;
+        }
+        if let Err(e) = append(&adapter_id, level, &message, context.as_ref(), timestamp_ms).await {
+            warn!("{}", e);
+        }
+    });
+}
+
+/// 记录适配器执行结果中附带的日志（`logs: [{level, message, context}]`）
+pub fn record_output_logs(adapter_id: &str, output: &serde_json::Value) {
+    let Some(logs) = output.get("logs").and_then(|logs| logs.as_array()) else {
+        return;
+    };
+    for log in logs {
+        let Some(message) = log.get("message").and_then(|m| m.as_str()) else {
+            continue;
+        };
+        let level = log
+            .get("level")
+            .and_then(|level| level.as_str())
+            .and_then(|level| level.parse().ok())
+            .unwrap_or(LogLevel::Info);
+        record(adapter_id, level, message, log.get("context").cloned());
+    }
+}
+
+fn registry() -> Result<std::sync::Arc<crate::database::Database>, String> {
+    crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())
+}
+
+/// 启动适配器日志清理任务
+pub fn start_adapter_log_retention() {
+    watchdog::supervise("adapter_log_retention", RETENTION_STALL_AFTER, move |heartbeat| {
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                if let Some(db) = crate::database::get_database() {
+                    if let Err(e) = db.logging_registry.apply_all_adapter_log_retention().await {
+                        warn!("清理适配器日志失败: {}", e);
+                    }
+                }
+                tokio::time::sleep(RETENTION_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// 开始跟踪的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterLogTail {
+    pub tail_id: String,
+    /// 最近的日志（新的在前）
+    pub recent: Vec<AdapterLogEntry>,
+}
+
+/// `adapter-log` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+struct AdapterLogEvent<'a> {
+    tail_id: &'a str,
+    entry: &'a AdapterLogEntry,
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 查询适配器日志（新的在前）
+#[tauri::command]
+pub async fn get_adapter_logs(adapter_id: String, filter: Option<AdapterLogFilter>) -> Result<Vec<AdapterLogEntry>, String> {
+    registry()?
+        .logging_registry
+        .get_adapter_logs(&adapter_id, &filter.unwrap_or_default())
+        .await
+        .map_err(|e| format!("读取适配器日志失败: {}", e))
+}
+
+/// 开始实时跟踪适配器日志，新日志以 `adapter-log` 事件推送
+#[tauri::command]
+pub async fn tail_adapter_logs(
+    app_handle: AppHandle,
+    adapter_id: String,
+    min_level: Option<LogLevel>,
+    backlog: Option<i64>,
+) -> Result<AdapterLogTail, String> {
+    // 先订阅再读历史，避免两者之间的日志丢失
+    let mut receiver = LOG_CHANNEL.subscribe();
+    let recent = registry()?
+        .logging_registry
+        .get_adapter_logs(&adapter_id, &AdapterLogFilter {
+            min_level,
+            limit: Some(backlog.unwrap_or(DEFAULT_TAIL_BACKLOG)),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| format!("读取适配器日志失败: {}", e))?;
+
+    let tail_id = uuid::Uuid::new_v4().to_string();
+    let last_seen = recent.first().map(|entry| entry.id).unwrap_or(0);
+    let min_level = min_level.unwrap_or(LogLevel::Trace);
+    let task_tail_id = tail_id.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(entry) => {
+                    if entry.adapter_id != adapter_id || entry.level < min_level || entry.id <= last_seen {
+                        continue;
+                    }
+                    let _ = app_handle.emit_all("adapter-log", AdapterLogEvent {
+                        tail_id: &task_tail_id,
+                        entry: &entry,
+                    });
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("适配器日志跟踪跟不上，跳过了 {} 条", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    TAILS.lock().insert(tail_id.clone(), task);
+
+    Ok(AdapterLogTail { tail_id, recent })
+}
+
+/// 结束实时跟踪
+#[tauri::command]
+pub async fn stop_adapter_log_tail(tail_id: String) -> Result<bool, String> {
+    match TAILS.lock().remove(&tail_id) {
+        Some(task) => {
+            task.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 获取适配器的日志保留策略
+#[tauri::command]
+pub async fn get_adapter_log_retention(adapter_id: String) -> Result<AdapterLogRetention, String> {
+    registry()?
+        .logging_registry
+        .get_adapter_log_retention(&adapter_id)
+        .await
+        .map_err(|e| format!("读取日志保留策略失败: {}", e))
+}
+
+/// 设置适配器的日志保留策略，并立即按新策略清理
+#[tauri::command]
+pub async fn set_adapter_log_retention(adapter_id: String, retention: AdapterLogRetention) -> Result<u64, String> {
+    retention.validate()?;
+    let db = registry()?;
+    db.logging_registry
+        .set_adapter_log_retention(&adapter_id, &retention)
+        .await
+        .map_err(|e| format!("保存日志保留策略失败: {}", e))?;
+    let removed = db
+        .logging_registry
+        .apply_adapter_log_retention(&adapter_id, chrono::Utc::now().timestamp_millis())
+        .await
+        .map_err(|e| format!("清理适配器日志失败: {}", e))?;
+    info!("适配器 {} 的日志保留策略已更新，清理了 {} 条日志", adapter_id, removed);
+    Ok(removed)
+}
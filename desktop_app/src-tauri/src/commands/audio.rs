@@ -1,13 +1,39 @@
 /*!
  * 音频录制和播放命令
  * 提供跨平台的音频捕获功能，不依赖浏览器 API
+ *
+ * 语音输入支持两种模式：语音活动检测（说话自动开始 / 结束）与按键说话，
+ * 每句话发出 `speech-started` / `speech-ended` 事件，并可自动转写（本地 whisper 或后端接口）。
  */
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{StreamConfig};
 use hound::{WavSpec, WavWriter};
-use std::sync::{Arc, Mutex};
-use tauri::State;
+use std::fs;
+use std::path::PathBuf;
+use base64::{engine::general_purpose, Engine as _};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::config::backend_profiles::{self, BackendFeature};
+use crate::http::trust_store::PinnedSend;
+
+use crate::utils::audio_routing::{
+    self, AudioFeature, AudioRoutingSettings, DeviceChanges, DeviceChoice, DeviceSnapshot,
+};
+use crate::utils::hotword;
+use crate::utils::permission_checker::HardwareChecker;
+use crate::utils::voice_activity::{
+    self, TranscriptionEngine, VadEvent, VoiceActivityDetector, VoiceInputMode, VoiceInputSettings,
+};
+use crate::utils::watchdog;
+
+/// 设备列表轮询间隔（cpal 没有跨平台的设备变化通知）
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// 设备监控的心跳停滞阈值
+const DEVICE_MONITOR_STALL_AFTER: Duration = Duration::from_secs(60);
 
 /// 音频录制状态
 pub struct AudioState {
@@ -65,9 +91,103 @@ pub fn list_audio_devices() -> Result<Vec<String>, String> {
     Ok(device_names)
 }
 
+/// 当前的设备列表快照
+pub fn snapshot_devices() -> DeviceSnapshot {
+    let host = cpal::default_host();
+    DeviceSnapshot {
+        inputs: host
+            .input_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default(),
+        outputs: host
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default(),
+        default_input: host.default_input_device().and_then(|d| d.name().ok()),
+        default_output: host.default_output_device().and_then(|d| d.name().ok()),
+    }
+}
+
+/// 按路由设置解析功能使用的设备，指定设备不可用时回退到默认设备
+pub fn resolve_device(feature: AudioFeature) -> Result<(cpal::Device, DeviceChoice), String> {
+    let host = cpal::default_host();
+    let snapshot = snapshot_devices();
+    let routing = audio_routing::current_settings();
+    let (available, default) = if feature.is_input() {
+        (&snapshot.inputs, snapshot.default_input.as_deref())
+    } else {
+        (&snapshot.outputs, snapshot.default_output.as_deref())
+    };
+    let choice = audio_routing::choose_device(routing.device_for(feature), available, default);
+    let name = choice.device.clone().ok_or_else(|| "未找到可用的音频设备".to_string())?;
+
+    let matches_name = |d: &cpal::Device| d.name().map_or(false, |n| n == name);
+    let device = if feature.is_input() {
+        host.input_devices().map_err(|e| format!("获取音频设备失败: {}", e))?.find(matches_name)
+    } else {
+        host.output_devices().map_err(|e| format!("获取音频设备失败: {}", e))?.find(matches_name)
+    }
+    .ok_or_else(|| format!("音频设备 {} 已不可用", name))?;
+
+    if choice.fell_back {
+        tracing::warn!("{:?} 指定的音频设备不可用，已回退到默认设备 {}", feature, name);
+    }
+    Ok((device, choice))
+}
+
+/// 在当前线程构建麦克风输入流，样本以 f32 形式送入通道
+///
+/// 返回的 Stream 不能跨线程移动，调用方需在采集线程内持有。
+pub fn build_sample_stream(
+    feature: AudioFeature,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<(cpal::Stream, usize, u32), String> {
+    let (device, _) = resolve_device(feature)?;
+    let supported_config = device
+        .default_input_config()
+        .map_err(|e| format!("获取音频配置失败: {}", e))?;
+    let stream_config: cpal::StreamConfig = supported_config.config();
+    let channels = stream_config.channels as usize;
+    let sample_rate = stream_config.sample_rate.0;
+
+    let err_fn = move |err| tracing::error!("{:?} 输入流错误: {}", feature, err);
+    let stream = match supported_config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &stream_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let _ = sender.send(data.to_vec());
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &stream_config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                let _ = sender.send(data.iter().map(|&s| s as f32 / i16::MAX as f32).collect());
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &stream_config,
+            move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                let _ = sender.send(data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0).collect());
+            },
+            err_fn,
+            None,
+        ),
+        _ => return Err("不支持的采样格式".to_string()),
+    }
+    .map_err(|e| format!("创建输入流失败: {}", e))?;
+
+    stream.play().map_err(|e| format!("启动输入流失败: {}", e))?;
+    Ok((stream, channels, sample_rate))
+}
+
 /// 开始录音
 #[tauri::command]
 pub fn start_recording(
+    app_handle: AppHandle,
     state: State<'_, AudioState>,
     config: Option<AudioConfig>,
 ) -> Result<(), String> {
@@ -81,11 +201,14 @@ pub fn start_recording(
         }
     }
     
-    // 获取默认音频主机和输入设备
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or("未找到默认音频输入设备".to_string())?;
+    // 按路由设置选择输入设备（不可用时回退到默认设备）
+    let (device, choice) = resolve_device(AudioFeature::Recording)?;
+    if choice.fell_back {
+        let _ = app_handle.emit_all("audio-device-route-changed", serde_json::json!({
+            "feature": AudioFeature::Recording,
+            "choice": choice,
+        }));
+    }
     
     // 获取支持的配置
     let supported_config = device
@@ -109,7 +232,15 @@ pub fn start_recording(
     let is_recording_flag = Arc::clone(&state.is_recording);
     let channels = config.channels;
     
-    let err_fn = |err| eprintln!("录音流错误: {}", err);
+    // 设备被拔出等流错误通知前端，避免录音静默中断
+    let error_handle = app_handle.clone();
+    let err_fn = move |err: cpal::StreamError| {
+        tracing::error!("录音流错误: {}", err);
+        let _ = error_handle.emit_all("audio-device-error", serde_json::json!({
+            "feature": AudioFeature::Recording,
+            "error": err.to_string(),
+        }));
+    };
     
     // 创建音频流
     let stream = match supported_config.sample_format() {
@@ -303,3 +434,530 @@ pub fn cancel_recording(state: State<'_, AudioState>) -> Result<(), String> {
     println!("✅ 录音已取消");
     Ok(())
 }
+
+// ================================
+// 设备路由与热插拔
+// ================================
+
+fn get_audio_routing_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("audio_routing.json"))
+}
+
+/// 从磁盘加载设备路由设置（启动时调用）
+pub fn initialize_audio_routing() -> Result<(), String> {
+    let config_path = get_audio_routing_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read audio routing settings: {}", e))?;
+    let settings: AudioRoutingSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse audio routing settings: {}", e))?;
+
+    audio_routing::set_settings(settings);
+    Ok(())
+}
+
+/// 设备变化时通知前端，并让受影响的功能切换设备
+fn handle_device_changes(app_handle: &AppHandle, changes: &DeviceChanges, snapshot: &DeviceSnapshot) {
+    tracing::info!("音频设备变化: {:?}", changes);
+    let _ = app_handle.emit_all("audio-devices-changed", serde_json::json!({
+        "changes": changes,
+        "devices": snapshot,
+    }));
+
+    let routing = audio_routing::current_settings();
+    for feature in AudioFeature::ALL {
+        if !changes.affects(feature, &routing, snapshot) {
+            continue;
+        }
+        let (available, default) = if feature.is_input() {
+            (&snapshot.inputs, snapshot.default_input.as_deref())
+        } else {
+            (&snapshot.outputs, snapshot.default_output.as_deref())
+        };
+        let choice = audio_routing::choose_device(routing.device_for(feature), available, default);
+        let _ = app_handle.emit_all("audio-device-route-changed", serde_json::json!({
+            "feature": feature,
+            "choice": choice,
+        }));
+
+        match feature {
+            AudioFeature::Hotword => {
+                if let Err(e) = crate::commands::hotword::restart_listening(app_handle) {
+                    tracing::warn!("切换唤醒词监听设备失败: {}", e);
+                }
+            }
+            AudioFeature::Recording => {
+                if let Err(e) = restart_voice_input(app_handle) {
+                    tracing::warn!("切换语音输入设备失败: {}", e);
+                }
+                // 录音流无法在运行中切换设备，通知前端重新开始录音
+                let recording = *app_handle.state::<AudioState>().is_recording.lock().unwrap();
+                if recording {
+                    let _ = app_handle.emit_all("audio-device-lost", serde_json::json!({
+                        "feature": feature,
+                        "choice": choice,
+                    }));
+                }
+            }
+            AudioFeature::TtsOutput => {}
+        }
+    }
+}
+
+/// 启动设备热插拔监控
+pub fn start_audio_device_monitor(app_handle: AppHandle) {
+    watchdog::supervise("audio_device_monitor", DEVICE_MONITOR_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        let spawned = std::thread::Builder::new()
+            .name("audio-device-monitor".to_string())
+            .spawn(move || {
+                let mut previous = snapshot_devices();
+                while heartbeat.beat() {
+                    std::thread::sleep(DEVICE_POLL_INTERVAL);
+                    let current = snapshot_devices();
+                    let changes = audio_routing::diff_devices(&previous, &current);
+                    if !changes.is_empty() {
+                        handle_device_changes(&app_handle, &changes, &current);
+                    }
+                    previous = current;
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!("启动音频设备监控失败: {}", e);
+        }
+    });
+}
+
+/// 获取输入/输出设备列表和默认设备
+#[tauri::command]
+pub fn list_audio_devices_detailed() -> Result<DeviceSnapshot, String> {
+    Ok(snapshot_devices())
+}
+
+/// 获取各功能的设备路由设置
+#[tauri::command]
+pub fn get_audio_routing() -> Result<AudioRoutingSettings, String> {
+    Ok(audio_routing::current_settings())
+}
+
+/// 保存各功能的设备路由设置
+#[tauri::command]
+pub fn update_audio_routing(app_handle: AppHandle, settings: AudioRoutingSettings) -> Result<(), String> {
+    let previous = audio_routing::current_settings();
+    audio_routing::set_settings(settings.clone());
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize audio routing settings: {}", e))?;
+    fs::write(get_audio_routing_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write audio routing settings: {}", e))?;
+
+    if previous.hotword_device != settings.hotword_device {
+        crate::commands::hotword::restart_listening(&app_handle)?;
+    }
+    if previous.recording_device != settings.recording_device {
+        restart_voice_input(&app_handle)?;
+    }
+    Ok(())
+}
+
+// ================================
+// 语音输入（语音活动检测 / 按键说话）
+// ================================
+
+/// 采集线程的接收超时（用于及时响应停止与按键说话松开）
+const VOICE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// 后端转写请求超时
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct VoiceListener {
+    stop: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+lazy_static::lazy_static! {
+    static ref VOICE_SETTINGS: parking_lot::RwLock<VoiceInputSettings> =
+        parking_lot::RwLock::new(VoiceInputSettings::default());
+    static ref VOICE_LISTENER: parking_lot::Mutex<Option<VoiceListener>> = parking_lot::Mutex::new(None);
+}
+
+/// 按键说话是否处于按下状态
+static PUSH_TO_TALK: AtomicBool = AtomicBool::new(false);
+/// 语句编号，`speech-started` / `speech-ended` / `speech-transcribed` 用同一编号关联
+static NEXT_UTTERANCE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 转写结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Transcription {
+    pub text: String,
+    pub engine: TranscriptionEngine,
+    /// 转写耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+fn get_voice_input_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("voice_input_settings.json"))
+}
+
+fn emit_voice_listening(app_handle: &AppHandle, listening: bool) {
+    let _ = app_handle.emit_all("voice-input-listening-changed", serde_json::json!({
+        "listening": listening,
+        "mode": VOICE_SETTINGS.read().mode,
+    }));
+}
+
+fn begin_utterance(app_handle: &AppHandle, mode: VoiceInputMode) -> u64 {
+    let id = NEXT_UTTERANCE_ID.fetch_add(1, Ordering::Relaxed);
+    let _ = app_handle.emit_all("speech-started", serde_json::json!({ "id": id, "mode": mode }));
+    id
+}
+
+/// 一句话结束：发出 `speech-ended`（附 WAV 音频），开启自动转写时再发出 `speech-transcribed`
+fn finish_utterance(app_handle: &AppHandle, id: u64, mode: VoiceInputMode, samples: Vec<f32>) {
+    let duration_ms = samples.len() as u64 * 1000 / hotword::SAMPLE_RATE as u64;
+    let wav = voice_activity::encode_wav(&samples)
+        .map_err(|e| tracing::warn!("{}", e))
+        .ok();
+    let _ = app_handle.emit_all("speech-ended", serde_json::json!({
+        "id": id,
+        "mode": mode,
+        "duration_ms": duration_ms,
+        "audio": wav.as_ref().map(|wav| general_purpose::STANDARD.encode(wav)),
+    }));
+
+    let settings = VOICE_SETTINGS.read().clone();
+    let Some(wav) = wav.filter(|_| settings.auto_transcribe) else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        match transcribe_wav(&wav, None, settings.language.as_deref(), &settings).await {
+            Ok(transcription) => {
+                let _ = app_handle.emit_all("speech-transcribed", serde_json::json!({
+                    "id": id,
+                    "transcription": transcription,
+                }));
+            }
+            Err(e) => {
+                tracing::warn!("语音转写失败: {}", e);
+                let _ = app_handle.emit_all("speech-transcription-failed", serde_json::json!({
+                    "id": id,
+                    "error": e,
+                }));
+            }
+        }
+    });
+}
+
+fn run_voice_listener(
+    app_handle: AppHandle,
+    mode: VoiceInputMode,
+    stop: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<(), String>>,
+) {
+    let (sender, receiver) = mpsc::channel();
+    // cpal::Stream 不能跨线程移动，必须在采集线程内创建并持有
+    let (_stream, channels, sample_rate) = match build_sample_stream(AudioFeature::Recording, sender) {
+        Ok(stream) => stream,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let settings = VOICE_SETTINGS.read().clone();
+    let max_samples = settings.max_utterance_samples();
+    let mut detector = VoiceActivityDetector::new(&settings);
+    let mut current: Option<u64> = None;
+    let mut held: Option<(u64, Vec<f32>)> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        let samples = match receiver.recv_timeout(VOICE_POLL_INTERVAL) {
+            Ok(samples) => hotword::to_mono_16k(&samples, channels, sample_rate),
+            Err(mpsc::RecvTimeoutError::Timeout) => Vec::new(),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                tracing::warn!("语音输入的麦克风已断开");
+                break;
+            }
+        };
+
+        match mode {
+            VoiceInputMode::Vad => {
+                for event in detector.push(&samples) {
+                    match event {
+                        VadEvent::SpeechStarted => current = Some(begin_utterance(&app_handle, mode)),
+                        VadEvent::SpeechEnded(audio) => {
+                            let id = current.take().unwrap_or_else(|| begin_utterance(&app_handle, mode));
+                            finish_utterance(&app_handle, id, mode, audio);
+                        }
+                    }
+                }
+            }
+            VoiceInputMode::PushToTalk => {
+                let pressed = PUSH_TO_TALK.load(Ordering::Relaxed);
+                if let Some((_, audio)) = held.as_mut() {
+                    audio.extend(samples);
+                    // 松开或超过单句上限时结束
+                    if !pressed || audio.len() >= max_samples {
+                        PUSH_TO_TALK.store(false, Ordering::Relaxed);
+                        if let Some((id, audio)) = held.take() {
+                            finish_utterance(&app_handle, id, mode, audio);
+                        }
+                    }
+                } else if pressed {
+                    held = Some((begin_utterance(&app_handle, mode), samples));
+                }
+            }
+            VoiceInputMode::Off => break,
+        }
+    }
+
+    PUSH_TO_TALK.store(false, Ordering::Relaxed);
+    if !stop.load(Ordering::Relaxed) {
+        // 非主动停止（设备断开等）
+        VOICE_LISTENER.lock().take();
+        emit_voice_listening(&app_handle, false);
+    }
+}
+
+/// 按当前模式开始采集（模式为关闭时不做任何事）
+pub fn start_voice_input(app_handle: &AppHandle) -> Result<(), String> {
+    let mode = VOICE_SETTINGS.read().mode;
+    if mode == VoiceInputMode::Off {
+        return Ok(());
+    }
+    let mut listener = VOICE_LISTENER.lock();
+    if listener.is_some() {
+        return Ok(());
+    }
+
+    HardwareChecker::check_microphone("feature", "voice_input")
+        .map_err(|e| format!("语音输入需要麦克风权限: {}", e))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread = {
+        let app_handle = app_handle.clone();
+        let stop = Arc::clone(&stop);
+        std::thread::Builder::new()
+            .name("voice-input".to_string())
+            .spawn(move || run_voice_listener(app_handle, mode, stop, ready_tx))
+            .map_err(|e| format!("启动语音输入线程失败: {}", e))?
+    };
+
+    match ready_rx.recv_timeout(Duration::from_secs(5)) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return Err(e),
+        Err(_) => {
+            stop.store(true, Ordering::Relaxed);
+            return Err("打开麦克风超时".to_string());
+        }
+    }
+
+    *listener = Some(VoiceListener { stop, thread });
+    drop(listener);
+    emit_voice_listening(app_handle, true);
+    tracing::info!("语音输入已启动: {:?}", mode);
+    Ok(())
+}
+
+/// 停止采集
+pub fn stop_voice_input(app_handle: &AppHandle) {
+    let listener = VOICE_LISTENER.lock().take();
+    if let Some(listener) = listener {
+        listener.stop.store(true, Ordering::Relaxed);
+        if listener.thread.join().is_err() {
+            tracing::warn!("语音输入线程异常退出");
+        }
+        emit_voice_listening(app_handle, false);
+        tracing::info!("语音输入已停止");
+    }
+}
+
+/// 正在采集时重启采集线程（设备或模式变化后）
+fn restart_voice_input(app_handle: &AppHandle) -> Result<(), String> {
+    if VOICE_LISTENER.lock().is_none() {
+        return Ok(());
+    }
+    stop_voice_input(app_handle);
+    start_voice_input(app_handle)
+}
+
+/// 加载语音输入设置，开启时开始采集（启动时调用）
+pub fn initialize_voice_input(app_handle: &AppHandle) -> Result<(), String> {
+    let config_path = get_voice_input_config_path()?;
+    if config_path.exists() {
+        let json_data = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read voice input settings: {}", e))?;
+        let settings: VoiceInputSettings = serde_json::from_str(&json_data)
+            .map_err(|e| format!("Failed to parse voice input settings: {}", e))?;
+        settings.validate()?;
+        *VOICE_SETTINGS.write() = settings;
+    }
+    start_voice_input(app_handle)
+}
+
+/// 按下 / 松开按键说话，`active` 为空时切换
+///
+/// 全局快捷键只有按下事件，绑定 `voice.push_to_talk` 动作时按一次开始、再按一次结束；
+/// 窗口内可以在按键按下和松开时分别调用，实现按住说话。
+pub fn set_push_to_talk_state(app_handle: &AppHandle, active: Option<bool>) -> Result<bool, String> {
+    if VOICE_SETTINGS.read().mode != VoiceInputMode::PushToTalk {
+        return Err("当前不是按键说话模式".to_string());
+    }
+    if VOICE_LISTENER.lock().is_none() {
+        start_voice_input(app_handle)?;
+    }
+    let active = active.unwrap_or_else(|| !PUSH_TO_TALK.load(Ordering::Relaxed));
+    PUSH_TO_TALK.store(active, Ordering::Relaxed);
+    Ok(active)
+}
+
+/// 用本地 whisper.cpp 命令行转写
+async fn transcribe_local(wav: &[u8], settings: &VoiceInputSettings, language: Option<&str>) -> Result<String, String> {
+    let (Some(binary), Some(model)) = (settings.whisper_binary.as_deref(), settings.whisper_model.as_deref()) else {
+        return Err("未设置本地 whisper 可执行文件与模型路径".to_string());
+    };
+    let path = std::env::temp_dir().join(format!("zishu-voice-{}.wav", uuid::Uuid::new_v4()));
+    tokio::fs::write(&path, wav)
+        .await
+        .map_err(|e| format!("写入临时音频失败: {}", e))?;
+
+    let output = tokio::process::Command::new(binary)
+        .arg("-m")
+        .arg(model)
+        .arg("-f")
+        .arg(&path)
+        .arg("-l")
+        .arg(language.unwrap_or("auto"))
+        // 不输出时间戳与进度
+        .args(["-nt", "-np"])
+        .kill_on_drop(true)
+        .output()
+        .await;
+    let _ = tokio::fs::remove_file(&path).await;
+
+    let output = output.map_err(|e| format!("启动 whisper 失败: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("whisper 转写失败: {}", stderr.trim().lines().last().unwrap_or_default()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// 用后端语音识别接口转写
+async fn transcribe_backend(wav: &[u8], language: Option<&str>) -> Result<String, String> {
+    #[derive(serde::Deserialize)]
+    struct TranscribeResponse {
+        text: String,
+    }
+
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+    let client = backend_profiles::client_for(BackendFeature::Core);
+    let url = format!("{}/api/voice/transcribe", backend_profiles::base_url_for(BackendFeature::Core));
+    let response = client
+        .post(&url)
+        .timeout(TRANSCRIBE_TIMEOUT)
+        .json(&serde_json::json!({
+            "audio": general_purpose::STANDARD.encode(wav),
+            "language": language,
+        }))
+        .send_pinned()
+        .await
+        .map_err(|e| format!("请求语音识别失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("语音识别失败: {}", response.status()));
+    }
+    let body: TranscribeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("解析语音识别结果失败: {}", e))?;
+    Ok(body.text.trim().to_string())
+}
+
+async fn transcribe_wav(
+    wav: &[u8],
+    engine: Option<TranscriptionEngine>,
+    language: Option<&str>,
+    settings: &VoiceInputSettings,
+) -> Result<Transcription, String> {
+    let engine = settings.resolve_engine(engine);
+    let started = std::time::Instant::now();
+    let text = match engine {
+        TranscriptionEngine::Local => transcribe_local(wav, settings, language).await?,
+        _ => transcribe_backend(wav, language).await?,
+    };
+    Ok(Transcription { text, engine, elapsed_ms: started.elapsed().as_millis() as u64 })
+}
+
+/// 获取语音输入设置
+#[tauri::command]
+pub fn get_voice_input_settings() -> Result<VoiceInputSettings, String> {
+    Ok(VOICE_SETTINGS.read().clone())
+}
+
+/// 保存语音输入设置，并按新模式重新开始或停止采集
+#[tauri::command]
+pub fn update_voice_input_settings(app_handle: AppHandle, settings: VoiceInputSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize voice input settings: {}", e))?;
+    fs::write(get_voice_input_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write voice input settings: {}", e))?;
+
+    *VOICE_SETTINGS.write() = settings;
+    stop_voice_input(&app_handle);
+    start_voice_input(&app_handle)
+}
+
+/// 按下 / 松开按键说话（`active` 为空时切换），返回切换后的状态
+#[tauri::command]
+pub fn push_to_talk(app_handle: AppHandle, active: Option<bool>) -> Result<bool, String> {
+    set_push_to_talk_state(&app_handle, active)
+}
+
+/// 转写一段录音
+///
+/// `audio` 为 Base64 编码的 WAV，或 `stop_recording` 返回的 16 kHz 单声道 16 位 PCM；
+/// `engine` 为空时使用设置中的引擎。
+#[tauri::command]
+pub async fn transcribe_audio(
+    audio: String,
+    engine: Option<TranscriptionEngine>,
+    language: Option<String>,
+) -> Result<Transcription, String> {
+    let bytes = general_purpose::STANDARD
+        .decode(audio.trim())
+        .map_err(|e| format!("Base64 解码失败: {}", e))?;
+    let wav = if bytes.starts_with(b"RIFF") {
+        bytes
+    } else {
+        voice_activity::encode_wav(&voice_activity::pcm16_to_samples(&bytes))?
+    };
+    if wav.len() <= 44 {
+        return Err("没有可转写的音频".to_string());
+    }
+
+    let settings = VOICE_SETTINGS.read().clone();
+    let language = language.or_else(|| settings.language.clone());
+    transcribe_wav(&wav, engine, language.as_deref(), &settings).await
+}
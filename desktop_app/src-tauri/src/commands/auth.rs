@@ -5,11 +5,23 @@
 //! - Token 管理（存储、获取、刷新）
 //! - 用户信息获取
 //! - 设备管理
+//! - 社区账号的 OAuth2 + PKCE 浏览器登录（流程见 [`crate::utils::oauth`]）
+//!
+//! 浏览器登录得到的令牌加密保存在当前用户资料目录的 `oauth_tokens.json`，
+//! 加密密钥保存在系统密钥链；`get_auth_token` 优先返回该令牌，快过期时自动刷新。
 
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 use crate::commands::{CommandMetadata, PermissionLevel, ZishuResult};
+use crate::config::api_router::ApiRouter;
+use crate::utils::encryption::{EncryptedData, EncryptionManager};
+use crate::utils::get_profile_data_dir;
+use crate::utils::oauth::{self, OAuthEndpoints, OAuthTokens, Pkce, TokenResponse};
 
 // ================================
 // 类型定义
@@ -84,6 +96,11 @@ pub async fn save_auth_token(token: String) -> ZishuResult<()> {
 pub async fn get_auth_token() -> ZishuResult<String> {
     tracing::debug!("🔍 获取访问令牌");
     
+    // 浏览器登录的令牌优先
+    if let Some(token) = oauth_access_token().await? {
+        return Ok(token);
+    }
+    
     match keyring::Entry::new("zishu-sensei", "auth_token") {
         Ok(entry) => {
             match entry.get_password() {
@@ -206,6 +223,306 @@ pub async fn clear_refresh_token() -> ZishuResult<()> {
     }
 }
 
+// ================================
+// OAuth2 浏览器登录
+// ================================
+
+/// 密钥链中令牌加密密钥的条目名
+const OAUTH_KEY_ENTRY: &str = "oauth_key";
+const OAUTH_TOKENS_FILE: &str = "oauth_tokens.json";
+/// 等待浏览器回调的最长时间
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+/// 回调请求头的最大长度
+const MAX_CALLBACK_REQUEST: usize = 8 * 1024;
+
+lazy_static::lazy_static! {
+    /// 串行化登录、刷新与退出，避免并发刷新用掉同一个刷新令牌
+    static ref OAUTH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 登录状态（不包含令牌本身）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthStatus {
+    pub logged_in: bool,
+    pub expires_at: Option<i64>,
+    pub scope: Option<String>,
+}
+
+impl From<&OAuthTokens> for AuthStatus {
+    fn from(tokens: &OAuthTokens) -> Self {
+        Self {
+            logged_in: true,
+            expires_at: tokens.expires_at,
+            scope: tokens.scope.clone(),
+        }
+    }
+}
+
+fn oauth_endpoints() -> OAuthEndpoints {
+    let client_id = std::env::var("ZISHU_OAUTH_CLIENT_ID")
+        .unwrap_or_else(|_| oauth::DEFAULT_CLIENT_ID.to_string());
+    OAuthEndpoints::for_base_url(&ApiRouter::new().community_url(), &client_id)
+}
+
+/// 令牌加密器；密钥不存在时按 `create` 决定是否生成
+fn token_encryption(create: bool) -> Result<Option<EncryptionManager>, String> {
+    use base64::Engine;
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    let entry = keyring::Entry::new("zishu-sensei", OAUTH_KEY_ENTRY)
+        .map_err(|e| format!("创建存储条目失败: {}", e))?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = engine.decode(encoded).map_err(|e| format!("令牌密钥损坏: {}", e))?;
+            let key: [u8; 32] = bytes.try_into().map_err(|_| "令牌密钥长度错误".to_string())?;
+            Ok(Some(EncryptionManager::new(key)))
+        }
+        Err(keyring::Error::NoEntry) if create => {
+            let key = crate::utils::encryption::generate_random_key()
+                .map_err(|e| format!("生成令牌密钥失败: {}", e))?;
+            entry
+                .set_password(&engine.encode(key))
+                .map_err(|e| format!("保存令牌密钥失败: {}", e))?;
+            Ok(Some(EncryptionManager::new(key)))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("读取令牌密钥失败: {}", e)),
+    }
+}
+
+fn tokens_path() -> Result<PathBuf, String> {
+    Ok(get_profile_data_dir()?.join(OAUTH_TOKENS_FILE))
+}
+
+fn load_tokens() -> Result<Option<OAuthTokens>, String> {
+    let path = tokens_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let Some(encryption) = token_encryption(false)? else {
+        // 密钥丢失时令牌无法解密，视为未登录
+        tracing::warn!("⚠️  令牌密钥不存在，丢弃已保存的登录令牌");
+        let _ = std::fs::remove_file(&path);
+        return Ok(None);
+    };
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("读取登录令牌失败: {}", e))?;
+    let encrypted: EncryptedData = serde_json::from_str(&json).map_err(|e| format!("解析登录令牌失败: {}", e))?;
+    let plaintext = encryption
+        .decrypt_string(&encrypted)
+        .map_err(|e| format!("解密登录令牌失败: {}", e))?;
+    serde_json::from_str(&plaintext)
+        .map(Some)
+        .map_err(|e| format!("解析登录令牌失败: {}", e))
+}
+
+fn save_tokens(tokens: &OAuthTokens) -> Result<(), String> {
+    let encryption = token_encryption(true)?.ok_or("令牌密钥不可用")?;
+    let plaintext = serde_json::to_string(tokens).map_err(|e| format!("序列化登录令牌失败: {}", e))?;
+    let encrypted = encryption
+        .encrypt_string(&plaintext)
+        .map_err(|e| format!("加密登录令牌失败: {}", e))?;
+    let path = tokens_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&encrypted).map_err(|e| format!("序列化登录令牌失败: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("保存登录令牌失败: {}", e))
+}
+
+fn clear_tokens() -> Result<(), String> {
+    let path = tokens_path()?;
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("删除登录令牌失败: {}", e)),
+    }
+}
+
+/// 令牌请求失败的原因
+enum TokenRequestError {
+    /// 授权服务器拒绝（授权码或刷新令牌无效）
+    Rejected(String),
+    /// 网络或服务器错误，可以稍后重试
+    Unavailable(String),
+}
+
+impl TokenRequestError {
+    fn message(self) -> String {
+        match self {
+            TokenRequestError::Rejected(message) | TokenRequestError::Unavailable(message) => message,
+        }
+    }
+}
+
+async fn request_tokens(endpoints: &OAuthEndpoints, params: &[(&str, &str)]) -> Result<TokenResponse, TokenRequestError> {
+    let response = crate::http::proxy::client()
+        .post(&endpoints.token_url)
+        .form(params)
+        .send()
+        .await
+        .map_err(|e| TokenRequestError::Unavailable(format!("请求令牌失败: {}", e)))?;
+    let status = response.status();
+    if status.is_client_error() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(TokenRequestError::Rejected(format!("授权服务器拒绝了请求 ({}): {}", status, body)));
+    }
+    if !status.is_success() {
+        return Err(TokenRequestError::Unavailable(format!("授权服务器错误: {}", status)));
+    }
+    response
+        .json::<TokenResponse>()
+        .await
+        .map_err(|e| TokenRequestError::Unavailable(format!("解析令牌响应失败: {}", e)))
+}
+
+/// 等待浏览器回调，返回授权码
+async fn wait_for_callback(listener: TcpListener, state: &str) -> Result<String, String> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(|e| format!("接收回调失败: {}", e))?;
+
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_CALLBACK_REQUEST {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+        let request = String::from_utf8_lossy(&request);
+        let request_line = request.lines().next().unwrap_or_default();
+
+        let outcome = oauth::parse_callback(request_line, state);
+        let (status, message) = match &outcome {
+            Ok(Some(_)) => ("200 OK", "登录成功，可以关闭此页面并返回紫舒老师。"),
+            Ok(None) => ("404 Not Found", "Not Found"),
+            Err(_) => ("400 Bad Request", "登录失败，请返回紫舒老师重试。"),
+        };
+        let body = format!("<!doctype html><meta charset=\"utf-8\"><p>{}</p>", message);
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+
+        match outcome {
+            Ok(Some(code)) => return Ok(code),
+            Ok(None) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 当前有效的访问令牌，快过期时用刷新令牌续期；未通过浏览器登录时为 `None`
+pub async fn oauth_access_token() -> ZishuResult<Option<String>> {
+    let _guard = OAUTH_LOCK.lock().await;
+    let Some(tokens) = load_tokens()? else {
+        return Ok(None);
+    };
+    let now = chrono::Utc::now().timestamp();
+    if !tokens.needs_refresh(now) {
+        return Ok(Some(tokens.access_token));
+    }
+
+    let Some(refresh_token) = tokens.refresh_token.clone() else {
+        clear_tokens()?;
+        return Err("登录已过期，请重新登录".to_string());
+    };
+    let endpoints = oauth_endpoints();
+    let params = [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", endpoints.client_id.as_str()),
+    ];
+    match request_tokens(&endpoints, &params).await {
+        Ok(response) => {
+            let refreshed = OAuthTokens::from_response(response, Some(refresh_token), now);
+            save_tokens(&refreshed)?;
+            tracing::info!("🔄 访问令牌已刷新");
+            Ok(Some(refreshed.access_token))
+        }
+        Err(TokenRequestError::Rejected(e)) => {
+            tracing::warn!("⚠️  刷新令牌被拒绝，需要重新登录: {}", e);
+            clear_tokens()?;
+            Err("登录已过期，请重新登录".to_string())
+        }
+        Err(TokenRequestError::Unavailable(e)) => Err(e),
+    }
+}
+
+/// 用系统浏览器登录社区账号（OAuth2 授权码 + PKCE）
+#[tauri::command]
+pub async fn login_with_browser(app_handle: AppHandle) -> ZishuResult<AuthStatus> {
+    let _guard = OAUTH_LOCK.try_lock().map_err(|_| "正在登录中".to_string())?;
+    tracing::info!("🔐 开始浏览器登录");
+
+    let endpoints = oauth_endpoints();
+    let pkce = Pkce::generate();
+    let state = oauth::generate_state();
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("启动登录回调监听失败: {}", e))?;
+    let port = listener.local_addr().map_err(|e| format!("启动登录回调监听失败: {}", e))?.port();
+    let redirect_uri = format!("http://127.0.0.1:{}{}", port, oauth::CALLBACK_PATH);
+    let authorization_url = endpoints.authorization_url(&redirect_uri, &pkce, &state)?;
+
+    tauri::api::shell::open(&app_handle.shell_scope(), &authorization_url, None)
+        .map_err(|e| format!("打开浏览器失败: {}", e))?;
+
+    let code = tokio::time::timeout(LOGIN_TIMEOUT, wait_for_callback(listener, &state))
+        .await
+        .map_err(|_| "等待浏览器登录超时".to_string())??;
+
+    let params = [
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", endpoints.client_id.as_str()),
+        ("code_verifier", pkce.verifier.as_str()),
+    ];
+    let response = request_tokens(&endpoints, &params).await.map_err(TokenRequestError::message)?;
+    let tokens = OAuthTokens::from_response(response, None, chrono::Utc::now().timestamp());
+    save_tokens(&tokens)?;
+
+    let status = AuthStatus::from(&tokens);
+    tracing::info!("✅ 浏览器登录成功");
+    let _ = app_handle.emit_all("auth-changed", &status);
+    Ok(status)
+}
+
+/// 退出登录：撤销刷新令牌并删除本地保存的所有令牌
+#[tauri::command]
+pub async fn logout(app_handle: AppHandle) -> ZishuResult<()> {
+    let _guard = OAUTH_LOCK.lock().await;
+    tracing::info!("🚪 退出登录");
+
+    // 撤销失败不影响本地退出
+    if let Ok(Some(tokens)) = load_tokens() {
+        let endpoints = oauth_endpoints();
+        if let Some(refresh_token) = &tokens.refresh_token {
+            let params = [
+                ("token", refresh_token.as_str()),
+                ("token_type_hint", "refresh_token"),
+                ("client_id", endpoints.client_id.as_str()),
+            ];
+            if let Err(e) = crate::http::proxy::client().post(&endpoints.revoke_url).form(&params).send().await {
+                tracing::warn!("⚠️  撤销刷新令牌失败: {}", e);
+            }
+        }
+    }
+
+    clear_tokens()?;
+    clear_auth_token().await?;
+    clear_refresh_token().await?;
+
+    let status = AuthStatus { logged_in: false, expires_at: None, scope: None };
+    let _ = app_handle.emit_all("auth-changed", &status);
+    Ok(())
+}
+
 // ================================
 // 设备信息命令
 // ================================
@@ -311,6 +628,32 @@ pub fn get_command_metadata() -> HashMap<String, CommandMetadata> {
         },
     );
     
+    metadata.insert(
+        "login_with_browser".to_string(),
+        CommandMetadata {
+            name: "login_with_browser".to_string(),
+            description: "通过系统浏览器登录社区账号（OAuth2 + PKCE）".to_string(),
+            input_type: None,
+            output_type: Some("AuthStatus".to_string()),
+            required_permission: PermissionLevel::Public,
+            is_async: true,
+            category: "auth".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "logout".to_string(),
+        CommandMetadata {
+            name: "logout".to_string(),
+            description: "退出登录并删除保存的令牌".to_string(),
+            input_type: None,
+            output_type: Some("()".to_string()),
+            required_permission: PermissionLevel::Public,
+            is_async: true,
+            category: "auth".to_string(),
+        },
+    );
+    
     metadata.insert(
         "get_device_name".to_string(),
         CommandMetadata {
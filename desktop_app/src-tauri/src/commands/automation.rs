@@ -0,0 +1,472 @@
+//! # 自动化接口命令模块
+//!
+//! 在本机开放一个行式 JSON-RPC 接口（Unix socket / Windows 命名管道），让脚本可以
+//! 发送通知、运行工作流，例如 `scripts/zishu.sh notify "build finished"`。
+//!
+//! - 接口默认关闭，在设置中开启；socket `automation.sock` 与访问令牌 `automation_token`
+//!   位于 [`get_app_data_dir`](crate::utils::config::get_app_data_dir) 返回的数据目录，
+//!   `scripts/zishu.sh` 按相同规则定位（修改路径时需同步更新脚本）
+//! - 方法按 [`automation_rpc::methods`] 映射到权限表中 `automation` 实体（客户端名）的授权
+//! - 连接、认证与调用记录在 `automation_audit.log`（每行一条 JSON）
+
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+use crate::utils::automation_rpc::{self, AuditEntry, AuditEvent, MethodSpec, RpcError, RpcHandler};
+use crate::utils::permission_checker::PermissionChecker;
+use crate::utils::security_audit::{log_audit_failure, AuditEventType};
+
+/// 权限表中的实体类型
+const ENTITY_TYPE: &str = "automation";
+const TOKEN_FILE: &str = "automation_token";
+const AUDIT_FILE: &str = "automation_audit.log";
+/// 审计日志超过该大小时轮换为 `.1`
+const MAX_AUDIT_BYTES: u64 = 2 * 1024 * 1024;
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\zishu-sensei-automation";
+
+lazy_static! {
+    static ref TOKEN: RwLock<String> = RwLock::new(String::new());
+    static ref SERVER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+    static ref AUDIT_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// 自动化接口设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutomationSettings {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 自动化接口状态
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationStatus {
+    pub enabled: bool,
+    pub running: bool,
+    /// socket 路径或命名管道名
+    pub endpoint: String,
+    pub token_path: String,
+    pub methods: Vec<MethodSpec>,
+}
+
+fn app_data_dir() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(app_data_dir)
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(app_data_dir()?.join("automation_settings.json"))
+}
+
+fn load_settings() -> Result<AutomationSettings, String> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(AutomationSettings::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read automation settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse automation settings: {}", e))
+}
+
+fn save_settings(settings: &AutomationSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize automation settings: {}", e))?;
+    fs::write(settings_path()?, json).map_err(|e| format!("Failed to write automation settings: {}", e))
+}
+
+#[cfg(unix)]
+fn endpoint() -> Result<String, String> {
+    Ok(app_data_dir()?.join("automation.sock").to_string_lossy().into_owned())
+}
+
+#[cfg(windows)]
+fn endpoint() -> Result<String, String> {
+    Ok(PIPE_NAME.to_string())
+}
+
+/// 写入仅当前用户可读的令牌文件
+fn write_token(path: &Path, token: &str) -> Result<(), String> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| format!("Failed to write automation token: {}", e))?;
+    file.write_all(token.as_bytes())
+        .map_err(|e| format!("Failed to write automation token: {}", e))
+}
+
+/// 读取令牌，不存在时生成
+fn load_or_create_token() -> Result<(), String> {
+    let path = app_data_dir()?.join(TOKEN_FILE);
+    let token = match fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => token.trim().to_string(),
+        _ => {
+            let token = automation_rpc::generate_token();
+            write_token(&path, &token)?;
+            token
+        }
+    };
+    *TOKEN.write() = token;
+    Ok(())
+}
+
+fn append_audit(entry: &AuditEntry) {
+    let Some(path) = AUDIT_PATH.read().clone() else {
+        return;
+    };
+    if fs::metadata(&path).map(|m| m.len() > MAX_AUDIT_BYTES).unwrap_or(false) {
+        let _ = fs::rename(&path, path.with_extension("log.1"));
+    }
+    let line = match serde_json::to_string(entry) {
+        Ok(line) => line,
+        Err(_) => return,
+    };
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        warn!("写入自动化审计日志失败: {}", e);
+    }
+}
+
+struct AppRpcHandler {
+    app: AppHandle,
+}
+
+impl AppRpcHandler {
+    fn notify(&self, client: &str, params: &Value) -> Result<Value, RpcError> {
+        let body = params
+            .get("body")
+            .and_then(Value::as_str)
+            .filter(|body| !body.trim().is_empty())
+            .ok_or_else(|| RpcError::invalid_params("缺少通知内容 body"))?;
+        let title = params
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| self.app.package_info().name.clone());
+
+        use tauri::api::notification::Notification;
+        Notification::new(&self.app.config().tauri.bundle.identifier)
+            .title(&title)
+            .body(body)
+            .show()
+            .map_err(|e| RpcError::internal(format!("显示通知失败: {}", e)))?;
+        let _ = self.app.emit_all(
+            "automation-notification",
+            serde_json::json!({ "client": client, "title": title, "body": body }),
+        );
+        Ok(serde_json::json!({ "shown": true }))
+    }
+
+    async fn list_workflows(&self) -> Result<Value, RpcError> {
+        let workflows = crate::commands::workflow_api::api_list_workflows(self.app.state::<AppState>(), None, None)
+            .await
+            .map_err(RpcError::internal)?;
+        serde_json::to_value(workflows).map_err(|e| RpcError::internal(e.to_string()))
+    }
+
+    async fn run_workflow(&self, params: Value) -> Result<Value, RpcError> {
+        let workflow_id = params
+            .get("workflow_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("缺少 workflow_id"))?
+            .to_string();
+        let input: Option<HashMap<String, Value>> = match params.get("input") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(
+                serde_json::from_value(value.clone()).map_err(|_| RpcError::invalid_params("input 必须是对象"))?,
+            ),
+        };
+        let execution = crate::commands::workflow_api::api_execute_workflow(
+            self.app.clone(),
+            workflow_id,
+            input,
+            None,
+            None,
+            None,
+        )
+        .await
+        .map_err(RpcError::internal)?;
+        serde_json::to_value(execution).map_err(|e| RpcError::internal(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcHandler for AppRpcHandler {
+    fn verify_token(&self, token: &str) -> bool {
+        automation_rpc::tokens_match(&TOKEN.read(), token)
+    }
+
+    async fn authorize(&self, client: &str, method: &MethodSpec) -> Result<(), String> {
+        let Some((permission_type, level)) = method.permission.clone() else {
+            return Ok(());
+        };
+        let client = client.to_string();
+        let name = method.name;
+        // 权限表接口是同步的（内部 block_on），放到阻塞线程执行
+        tokio::task::spawn_blocking(move || {
+            PermissionChecker::check_and_log(
+                ENTITY_TYPE,
+                &client,
+                permission_type,
+                level,
+                Some(name.to_string()),
+                format!("rpc:{}", name),
+            )
+        })
+        .await
+        .map_err(|e| format!("权限检查失败: {}", e))?
+    }
+
+    async fn call(&self, client: &str, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "ping" => Ok(serde_json::json!({
+                "pong": true,
+                "version": self.app.package_info().version.to_string(),
+            })),
+            "notify" => self.notify(client, &params),
+            "workflow.list" => self.list_workflows().await,
+            "workflow.run" => self.run_workflow(params).await,
+            other => Err(RpcError::new(automation_rpc::METHOD_NOT_FOUND, format!("未知方法: {}", other))),
+        }
+    }
+
+    fn audit(&self, entry: AuditEntry) {
+        if matches!(entry.event, AuditEvent::AuthFailed | AuditEvent::Denied) {
+            log_audit_failure(
+                AuditEventType::SecurityViolation,
+                &format!("自动化接口 {:?}: {}", entry.event, entry.method.as_deref().unwrap_or("-")),
+                entry.detail.as_deref().unwrap_or(""),
+                entry.client.as_deref(),
+            );
+        }
+        append_audit(&entry);
+    }
+}
+
+fn connection_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+#[cfg(unix)]
+async fn listen(handler: Arc<AppRpcHandler>, endpoint: String) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // 上次异常退出时留下的 socket 文件会导致 bind 失败
+    let _ = fs::remove_file(&endpoint);
+    let listener = tokio::net::UnixListener::bind(&endpoint)?;
+    fs::set_permissions(&endpoint, fs::Permissions::from_mode(0o600))?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let handler = handler.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = automation_rpc::serve_connection(stream, handler.as_ref(), connection_id()).await {
+                warn!("自动化连接异常断开: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(windows)]
+async fn listen(handler: Arc<AppRpcHandler>, endpoint: String) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&endpoint)?;
+    loop {
+        server.connect().await?;
+        let connected = server;
+        server = ServerOptions::new().reject_remote_clients(true).create(&endpoint)?;
+        let handler = handler.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = automation_rpc::serve_connection(connected, handler.as_ref(), connection_id()).await {
+                warn!("自动化连接异常断开: {}", e);
+            }
+        });
+    }
+}
+
+fn stop_server() {
+    if let Some(handle) = SERVER.lock().take() {
+        handle.abort();
+        info!("自动化接口已关闭");
+    }
+}
+
+fn start_server(app_handle: &AppHandle) -> Result<(), String> {
+    stop_server();
+    let endpoint = endpoint()?;
+    let handler = Arc::new(AppRpcHandler { app: app_handle.clone() });
+    info!("自动化接口监听: {}", endpoint);
+    let handle = tauri::async_runtime::spawn(async move {
+        if let Err(e) = listen(handler, endpoint).await {
+            warn!("自动化接口停止: {}", e);
+        }
+    });
+    *SERVER.lock() = Some(handle);
+    Ok(())
+}
+
+fn status(settings: &AutomationSettings) -> Result<AutomationStatus, String> {
+    Ok(AutomationStatus {
+        enabled: settings.enabled,
+        running: SERVER.lock().is_some(),
+        endpoint: endpoint()?,
+        token_path: app_data_dir()?.join(TOKEN_FILE).to_string_lossy().into_owned(),
+        methods: automation_rpc::methods(),
+    })
+}
+
+/// 加载令牌与设置，开启时启动接口（应用启动时调用）
+pub fn initialize_automation(app_handle: &AppHandle) -> Result<(), String> {
+    *AUDIT_PATH.write() = Some(app_data_dir()?.join(AUDIT_FILE));
+    load_or_create_token()?;
+    if load_settings()?.enabled {
+        start_server(app_handle)?;
+    }
+    Ok(())
+}
+
+/// 获取自动化接口状态
+#[tauri::command]
+pub async fn get_automation_status() -> Result<AutomationStatus, String> {
+    let settings = load_settings()?;
+    status(&settings)
+}
+
+/// 更新自动化接口设置并启动或关闭接口
+#[tauri::command]
+pub async fn update_automation_settings(
+    app_handle: AppHandle,
+    settings: AutomationSettings,
+) -> Result<AutomationStatus, String> {
+    save_settings(&settings)?;
+    if settings.enabled {
+        start_server(&app_handle)?;
+    } else {
+        stop_server();
+    }
+    status(&settings)
+}
+
+/// 重新生成访问令牌（已认证的连接不受影响，新连接需使用新令牌）
+#[tauri::command]
+pub async fn regenerate_automation_token() -> Result<String, String> {
+    let token = automation_rpc::generate_token();
+    write_token(&app_data_dir()?.join(TOKEN_FILE), &token)?;
+    *TOKEN.write() = token.clone();
+    info!("自动化接口令牌已更新");
+    Ok(token)
+}
+
+/// 获取最近的连接审计记录（新记录在前）
+#[tauri::command]
+pub async fn get_automation_audit_log(limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+    let Some(path) = AUDIT_PATH.read().clone() else {
+        return Ok(Vec::new());
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read automation audit log: {}", e)),
+    };
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+        .collect())
+}
+
+async fn set_client_methods(client: String, methods: Vec<String>, grant: bool) -> Result<(), String> {
+    let client = client.trim().to_string();
+    if client.is_empty() {
+        return Err("客户端名称不能为空".to_string());
+    }
+    let specs = methods
+        .iter()
+        .map(|name| automation_rpc::find_method(name).ok_or_else(|| format!("未知方法: {}", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        for (permission_type, level) in specs.into_iter().filter_map(|spec| spec.permission) {
+            let result = if grant {
+                db.permission_registry.grant_permission(
+                    ENTITY_TYPE.to_string(),
+                    client.clone(),
+                    permission_type,
+                    level,
+                    None,
+                    Some("user".to_string()),
+                    None,
+                )
+            } else {
+                db.permission_registry.revoke_permission(
+                    ENTITY_TYPE.to_string(),
+                    client.clone(),
+                    permission_type,
+                    None,
+                    None,
+                )
+            };
+            result.map_err(|e| format!("更新自动化权限失败: {}", e))?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("更新自动化权限失败: {}", e))?
+}
+
+/// 允许客户端调用指定方法
+#[tauri::command]
+pub async fn grant_automation_client(client: String, methods: Vec<String>) -> Result<(), String> {
+    set_client_methods(client, methods, true).await
+}
+
+/// 撤销客户端调用指定方法的权限
+#[tauri::command]
+pub async fn revoke_automation_client(client: String, methods: Vec<String>) -> Result<(), String> {
+    set_client_methods(client, methods, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 命令行客户端的默认路径必须与 `get_app_data_dir` 保持一致
+    #[test]
+    fn test_cli_script_uses_app_data_dir() {
+        let script = include_str!("../../../scripts/zishu.sh");
+        assert!(script.contains(r#"ZISHU_DATA_DIR="${XDG_CONFIG_HOME:-$HOME/.config}/$APP_DIR_NAME""#));
+        assert!(script.contains(r#"ZISHU_DATA_DIR="$HOME/Library/Application Support/$APP_DIR_NAME""#));
+        assert!(script.contains(r#"APP_DIR_NAME="zishu-sensei""#));
+        assert!(script.contains(crate::utils::config::DATA_DIR_POINTER_FILE));
+        assert!(script.contains(&format!("$ZISHU_DATA_DIR/{}", TOKEN_FILE)));
+        assert!(script.contains("$ZISHU_DATA_DIR/automation.sock"));
+
+        if cfg!(target_os = "linux") {
+            let expected = dirs::config_dir().unwrap().join("zishu-sensei");
+            assert_eq!(crate::utils::config::default_app_data_dir().unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! # 后端连接命令模块
+//!
+//! 管理后端端点配置（名称、地址、认证方式、按功能覆盖），提供连通性探测，
+//! 并暴露与后端协商出的 API 版本和功能集合。
+//! 配置持久化到应用数据目录，认证凭据保存在系统密钥链中。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::config::backend_profiles::{
+    self, BackendAuthMode, BackendFeature, BackendProfile, BackendProfileSettings,
+};
+use crate::http::capabilities::{self, BackendCapabilities};
+use crate::utils::keyring_store;
+
+/// 客户端支持的后端 API 主版本号
+pub const SUPPORTED_BACKEND_MAJOR_VERSION: u64 = 1;
+
+/// 后端连接测试结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConnectionResult {
+    pub base_url: String,
+    pub reachable: bool,
+    pub healthy: bool,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    /// 后端报告的版本
+    pub version: Option<String>,
+    /// 版本是否兼容；后端未报告版本时为 None
+    pub compatible: Option<bool>,
+    pub error: Option<String>,
+}
+
+fn get_profiles_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("backend_profiles.json"))
+}
+
+fn save_profile_credential(profile_id: &str, secret: Option<&str>) -> Result<(), String> {
+    let result = keyring_store::store(&backend_profiles::credential_key(profile_id), secret);
+    backend_profiles::invalidate_credential(profile_id);
+    result
+}
+
+/// 检查后端版本是否兼容（按主版本号判断）
+pub fn is_version_compatible(version: &str) -> Option<bool> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u64>().ok())
+        .map(|major| major == SUPPORTED_BACKEND_MAJOR_VERSION)
+}
+
+/// 从磁盘加载端点配置（启动时调用）
+pub fn initialize_backend_profiles() -> Result<(), String> {
+    let config_path = get_profiles_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read backend profiles: {}", e))?;
+    let settings: BackendProfileSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse backend profiles: {}", e))?;
+
+    backend_profiles::set_settings(settings)?;
+    info!("后端端点配置已加载");
+    Ok(())
+}
+
+/// 获取后端端点配置
+#[tauri::command]
+pub async fn get_backend_profiles() -> Result<BackendProfileSettings, String> {
+    Ok(backend_profiles::current_settings())
+}
+
+/// 保存后端端点配置，立即对新请求生效
+#[tauri::command]
+pub async fn update_backend_profiles(
+    app_handle: AppHandle,
+    settings: BackendProfileSettings,
+) -> Result<(), String> {
+    let previous = backend_profiles::current_settings();
+    backend_profiles::set_settings(settings.clone())?;
+
+    // 清理已删除配置的认证凭据
+    for removed in previous.profiles.iter().filter(|p| settings.profile(&p.id).is_none()) {
+        if let Err(e) = save_profile_credential(&removed.id, None) {
+            warn!("清理端点配置 {} 的凭据失败: {}", removed.id, e);
+        }
+    }
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize backend profiles: {}", e))?;
+    fs::write(get_profiles_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write backend profiles: {}", e))?;
+
+    let _ = app_handle.emit_all("backend-profiles-changed", serde_json::json!({
+        "active_profile": settings.active_profile,
+    }));
+
+    info!("后端端点配置已更新: {} 个配置", settings.profiles.len());
+
+    // 端点变化后重新协商
+    tauri::async_runtime::spawn(negotiate_backend_capabilities(app_handle));
+    Ok(())
+}
+
+/// 设置端点配置的认证凭据（令牌或 API Key），传入空值则清除
+#[tauri::command]
+pub async fn set_backend_profile_credential(
+    profile_id: String,
+    secret: Option<String>,
+) -> Result<(), String> {
+    let settings = backend_profiles::current_settings();
+    let profile = settings
+        .profile(&profile_id)
+        .ok_or_else(|| format!("端点配置不存在: {}", profile_id))?;
+    if profile.auth_mode == BackendAuthMode::None && secret.is_some() {
+        return Err("该端点配置未启用认证".to_string());
+    }
+    save_profile_credential(&profile_id, secret.as_deref())
+}
+
+/// 获取各功能当前使用的后端地址
+#[tauri::command]
+pub async fn get_backend_endpoints() -> Result<std::collections::HashMap<BackendFeature, String>, String> {
+    Ok([
+        BackendFeature::Core,
+        BackendFeature::Market,
+        BackendFeature::Workflow,
+        BackendFeature::Skills,
+    ]
+    .into_iter()
+    .map(|feature| (feature, backend_profiles::base_url_for(feature)))
+    .collect())
+}
+
+/// 探测后端健康状态、延迟与版本兼容性
+///
+/// 可以传入尚未保存的配置进行测试；都不传时测试核心服务当前使用的地址。
+#[tauri::command]
+pub async fn test_backend_connection(
+    profile: Option<BackendProfile>,
+    profile_id: Option<String>,
+) -> Result<BackendConnectionResult, String> {
+    let base_url = match (profile, profile_id) {
+        (Some(profile), _) => profile.base_url,
+        (None, Some(id)) => backend_profiles::current_settings()
+            .profile(&id)
+            .map(|p| p.base_url.clone())
+            .ok_or_else(|| format!("端点配置不存在: {}", id))?,
+        (None, None) => backend_profiles::base_url_for(BackendFeature::Core),
+    };
+    backend_profiles::validate_base_url(&base_url)?;
+    let base_url = base_url.trim_end_matches('/').to_string();
+
+    let client = crate::http::proxy::client_builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let started = Instant::now();
+    let result = client.get(format!("{}/health", base_url)).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            return Ok(BackendConnectionResult {
+                base_url,
+                reachable: false,
+                healthy: false,
+                status: None,
+                latency_ms,
+                version: None,
+                compatible: None,
+                error: Some(crate::http::trust_store::classify_request_error(e).to_string()),
+            })
+        }
+    };
+
+    let status = response.status();
+    let header_version = response
+        .headers()
+        .get("x-api-version")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body: Option<serde_json::Value> = response.json().await.ok();
+    let version = header_version.or_else(|| {
+        body.as_ref()
+            .and_then(|b| b.get("version").or_else(|| b.get("api_version")))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    });
+    let compatible = version.as_deref().and_then(is_version_compatible);
+
+    Ok(BackendConnectionResult {
+        base_url,
+        reachable: true,
+        healthy: status.is_success(),
+        status: Some(status.as_u16()),
+        latency_ms,
+        error: match compatible {
+            Some(false) => Some(format!(
+                "后端版本 {} 与客户端不兼容（需要 {}.x）",
+                version.as_deref().unwrap_or_default(),
+                SUPPORTED_BACKEND_MAJOR_VERSION
+            )),
+            _ if !status.is_success() => Some(format!("健康检查返回 {}", status)),
+            _ => None,
+        },
+        version,
+        compatible,
+    })
+}
+
+/// 与后端协商 API 版本并广播结果（启动及端点变化时调用）
+pub async fn negotiate_backend_capabilities(app_handle: AppHandle) -> BackendCapabilities {
+    let capabilities = capabilities::negotiate().await;
+    let _ = app_handle.emit_all("backend-capabilities-changed", &capabilities);
+    capabilities
+}
+
+/// 获取协商后的后端能力
+#[tauri::command]
+pub async fn get_backend_capabilities() -> Result<BackendCapabilities, String> {
+    Ok(capabilities::current())
+}
+
+/// 重新与后端协商 API 版本
+#[tauri::command]
+pub async fn refresh_backend_capabilities(app_handle: AppHandle) -> Result<BackendCapabilities, String> {
+    Ok(negotiate_backend_capabilities(app_handle).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_version_compatible() {
+        assert_eq!(is_version_compatible("1.4.2"), Some(true));
+        assert_eq!(is_version_compatible("v1"), Some(true));
+        assert_eq!(is_version_compatible("2.0.0"), Some(false));
+        assert_eq!(is_version_compatible("dev"), None);
+    }
+}
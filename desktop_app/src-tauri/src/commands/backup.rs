@@ -0,0 +1,724 @@
+//! # 备份命令模块
+//!
+//! 后台任务按设置的间隔导出数据库备份、清理旧备份，并定期把最新备份恢复到临时
+//! schema 做恢复演练（见 [`crate::database::backup`]）。演练失败时发送桌面通知、
+//! 向前端发出 `backup-verification-failed` 事件，并转发到手机（如已开启）。
+//!
+//! `export_profile` / `import_profile` 把设置、聊天记录、模型配置、提示词、角色配置与
+//! 已安装适配器的元数据打包为一个加密文件（格式见 [`crate::utils::profile_archive`]），
+//! 过程中发出 `profile-export-progress` / `profile-import-progress` 进度事件。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info, warn};
+
+use crate::{AppConfig, AppState};
+use crate::commands::notifications::{self, NewNotification};
+use crate::database::adapter::InstalledAdapter;
+use crate::database::backup::{BackupManifest, VerificationReport, BACKUP_EXTENSION};
+use crate::database::character_registry::CharacterConfig;
+use crate::database::conversation::{Conversation, Message};
+use crate::database::model_config::ModelConfigData;
+use crate::database::prompt_registry::PromptData;
+use crate::state::tray_state::NotificationType;
+use crate::utils::config::{save_config, validate_config};
+use crate::utils::i18n;
+use crate::utils::mobile_relay::RelayCategory;
+use crate::utils::profile_archive::{self, ProfileArchive};
+use crate::utils::resource_budget::{self, WorkKind};
+use crate::utils::watchdog;
+
+/// 后台任务的检查间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// 启动后首次检查前的等待时间（等待数据库初始化完成）
+const STARTUP_DELAY: Duration = Duration::from_secs(2 * 60);
+/// 后台任务心跳停滞阈值（备份与恢复演练可能较慢）
+const STALL_AFTER: Duration = Duration::from_secs(3 * 60 * 60);
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_interval_hours() -> u32 {
+    24
+}
+
+fn default_max_backups() -> u32 {
+    7
+}
+
+fn default_verify_interval_hours() -> u32 {
+    24 * 7
+}
+
+/// 数据库备份设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseBackupSettings {
+    /// 是否自动备份
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 备份间隔（小时）
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    /// 保留的备份数量
+    #[serde(default = "default_max_backups")]
+    pub max_backups: u32,
+    /// 恢复演练间隔（小时），0 表示不自动演练
+    #[serde(default = "default_verify_interval_hours")]
+    pub verify_interval_hours: u32,
+}
+
+impl Default for DatabaseBackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: default_interval_hours(),
+            max_backups: default_max_backups(),
+            verify_interval_hours: default_verify_interval_hours(),
+        }
+    }
+}
+
+/// 备份文件信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFileInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified_at: i64,
+}
+
+lazy_static! {
+    static ref SETTINGS: RwLock<DatabaseBackupSettings> = RwLock::new(DatabaseBackupSettings::default());
+}
+
+fn get_backup_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("database_backup_settings.json"))
+}
+
+fn get_backup_dir() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    Ok(app_data_dir.join("backups").join("database"))
+}
+
+/// 备份目录中的备份文件（新文件在前）
+fn list_backup_files(dir: &Path) -> Vec<BackupFileInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let mut files: Vec<BackupFileInfo> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(&suffix))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified_at = metadata
+                .modified()
+                .ok()?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()?
+                .as_secs() as i64;
+            Some(BackupFileInfo {
+                path: entry.path().to_string_lossy().into_owned(),
+                size_bytes: metadata.len(),
+                modified_at,
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| b.modified_at.cmp(&a.modified_at).then_with(|| b.path.cmp(&a.path)));
+    files
+}
+
+fn prune_backups(dir: &Path, keep: u32) {
+    for file in list_backup_files(dir).into_iter().skip(keep.max(1) as usize) {
+        match fs::remove_file(&file.path) {
+            Ok(()) => info!("已删除旧备份: {}", file.path),
+            Err(e) => warn!("删除旧备份失败 {}: {}", file.path, e),
+        }
+    }
+}
+
+/// 从磁盘加载备份设置（启动时调用）
+pub fn initialize_database_backup() -> Result<(), String> {
+    let config_path = get_backup_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read database backup settings: {}", e))?;
+    let settings: DatabaseBackupSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse database backup settings: {}", e))?;
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+async fn run_backup() -> Result<BackupManifest, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let dir = get_backup_dir()?;
+    let (path, manifest) = db
+        .backup_registry
+        .create_backup(&dir, chrono::Utc::now())
+        .await
+        .map_err(|e| format!("创建数据库备份失败: {}", e))?;
+    info!("数据库备份完成: {} ({} 张表)", path.display(), manifest.tables.len());
+    prune_backups(&dir, SETTINGS.read().max_backups);
+    Ok(manifest)
+}
+
+/// 提醒用户恢复演练失败
+fn alert_verification_failure(app_handle: &AppHandle, report: &VerificationReport) {
+    let failed: Vec<String> = report
+        .checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| match &check.detail {
+            Some(detail) => format!("{}: {}", check.name, detail),
+            None => check.name.clone(),
+        })
+        .collect();
+    let summary = if failed.is_empty() {
+        i18n::t("notify-backup-nothing-to-verify")
+    } else {
+        failed.join("\n")
+    };
+    error!("数据库备份验证失败 {}: {}", report.backup_file, summary);
+
+    let title = i18n::t("notify-backup-verification-failed");
+    notifications::post(
+        app_handle,
+        NewNotification::new(title.clone(), summary.clone(), NotificationType::Error)
+            .source("backup")
+            .action("open_backup_settings", &i18n::t("notify-action-view-backups"), "open_settings", serde_json::json!({ "section": "backup" })),
+    );
+    let _ = app_handle.emit_all("backup-verification-failed", report);
+    crate::commands::mobile_relay::relay(RelayCategory::BackupFailure, title, summary);
+}
+
+async fn run_verification(app_handle: &AppHandle) -> Result<VerificationReport, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let dir = get_backup_dir()?;
+    let latest = list_backup_files(&dir).into_iter().next().ok_or("没有可验证的备份")?;
+    let report = db
+        .backup_registry
+        .verify_backup(Path::new(&latest.path))
+        .await
+        .map_err(|e| format!("恢复演练失败: {}", e))?;
+    if let Err(e) = db.backup_registry.record_verification(&report).await {
+        warn!("记录备份验证结果失败: {}", e);
+    }
+    if report.success {
+        info!(
+            "备份验证通过: {} ({} 张表, {} 行, {}ms)",
+            report.backup_file, report.tables_restored, report.rows_restored, report.duration_ms
+        );
+    } else {
+        alert_verification_failure(app_handle, &report);
+    }
+    Ok(report)
+}
+
+async fn last_verified_at() -> Option<i64> {
+    let db = crate::database::get_database()?;
+    let reports = db.backup_registry.verifications(1).await.ok()?;
+    reports.first().map(|report| report.verified_at)
+}
+
+async fn scheduled_tick(app_handle: &AppHandle) {
+    let settings = SETTINGS.read().clone();
+    if !settings.enabled {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    let Ok(dir) = get_backup_dir() else {
+        return;
+    };
+
+    let latest = list_backup_files(&dir).into_iter().next();
+    let backup_due = !matches!(&latest, Some(file) if now - file.modified_at < i64::from(settings.interval_hours) * 3600);
+    if backup_due {
+        resource_budget::defer(WorkKind::Background, "database_backup").await;
+        if let Err(e) = run_backup().await {
+            warn!("{}", e);
+        }
+    }
+
+    if settings.verify_interval_hours == 0 {
+        return;
+    }
+    let verify_due = !matches!(
+        last_verified_at().await,
+        Some(at) if now - at < i64::from(settings.verify_interval_hours) * 3600
+    );
+    if !verify_due {
+        return;
+    }
+    resource_budget::defer(WorkKind::Background, "backup_verification").await;
+    // 演练本身无法执行（如没有备份或数据库不可用）同样需要提醒
+    if let Err(e) = run_verification(app_handle).await {
+        let report = VerificationReport::failed(dir.to_string_lossy().into_owned(), e);
+        if let Some(db) = crate::database::get_database() {
+            if let Err(e) = db.backup_registry.record_verification(&report).await {
+                warn!("记录备份验证结果失败: {}", e);
+            }
+        }
+        alert_verification_failure(app_handle, &report);
+    }
+}
+
+/// 启动自动备份与恢复演练任务（应用启动时调用）
+pub fn start_backup_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("database_backup", STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            while heartbeat.beat() {
+                scheduled_tick(&app_handle).await;
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// 获取备份设置
+#[tauri::command]
+pub async fn get_database_backup_settings() -> Result<DatabaseBackupSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 更新备份设置
+#[tauri::command]
+pub async fn update_database_backup_settings(
+    settings: DatabaseBackupSettings,
+) -> Result<(), String> {
+    if settings.interval_hours == 0 {
+        return Err("备份间隔至少为 1 小时".to_string());
+    }
+    if settings.max_backups == 0 {
+        return Err("至少保留 1 个备份".to_string());
+    }
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize database backup settings: {}", e))?;
+    fs::write(get_backup_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write database backup settings: {}", e))?;
+
+    info!("数据库备份设置已更新: enabled={}", settings.enabled);
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 列出现有备份（新备份在前）
+#[tauri::command]
+pub async fn list_database_backups() -> Result<Vec<BackupFileInfo>, String> {
+    Ok(list_backup_files(&get_backup_dir()?))
+}
+
+/// 立即创建备份
+#[tauri::command]
+pub async fn create_database_backup() -> Result<BackupManifest, String> {
+    run_backup().await
+}
+
+/// 立即对最新备份做恢复演练
+#[tauri::command]
+pub async fn verify_latest_backup(app_handle: AppHandle) -> Result<VerificationReport, String> {
+    run_verification(&app_handle).await
+}
+
+/// 获取最近的恢复演练结果
+#[tauri::command]
+pub async fn get_backup_verifications(limit: Option<i64>) -> Result<Vec<VerificationReport>, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.backup_registry
+        .verifications(limit.unwrap_or(20).clamp(1, 200))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ================================
+// 用户资料导出 / 导入
+// ================================
+
+/// 导出时单独保存的设置文件（AppConfig 之外，数据目录下的各类 `*.json` 设置）；
+/// 多设备同步状态与启动耗时记录只属于本机，不导出
+const EXCLUDED_SETTINGS_FILES: &[&str] = &[
+    "config.json",
+    "config.backup.json",
+    "sync_state.json",
+    "startup_profiles.json",
+];
+/// 每处理多少个对话发送一次进度
+const CONVERSATION_PROGRESS_STEP: usize = 20;
+
+/// 对话及其消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationBundle {
+    pub conversation: Conversation,
+    /// 会话级模型参数覆盖
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
+    pub messages: Vec<Message>,
+}
+
+/// 导出的完整用户资料
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    pub app_version: String,
+    pub exported_at: i64,
+    pub settings: AppConfig,
+    /// 文件名 -> 内容
+    #[serde(default)]
+    pub settings_files: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub conversations: Vec<ConversationBundle>,
+    #[serde(default)]
+    pub model_configs: Vec<ModelConfigData>,
+    #[serde(default)]
+    pub prompts: Vec<PromptData>,
+    #[serde(default)]
+    pub character_configs: Vec<CharacterConfig>,
+    /// 已安装适配器的元数据（不含适配器文件）
+    #[serde(default)]
+    pub adapters: Vec<InstalledAdapter>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileExportSummary {
+    pub path: String,
+    pub size_bytes: u64,
+    pub conversations: usize,
+    pub messages: usize,
+    pub model_configs: usize,
+    pub prompts: usize,
+    pub character_configs: usize,
+    pub adapters: usize,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileImportSummary {
+    pub conversations_imported: usize,
+    /// 本机已存在、未覆盖的对话
+    pub conversations_skipped: usize,
+    pub messages_imported: usize,
+    pub model_configs: usize,
+    pub prompts: usize,
+    pub character_configs: usize,
+    pub adapters_updated: usize,
+    /// 本机未安装的适配器，需要重新安装
+    pub missing_adapters: Vec<InstalledAdapter>,
+    /// 已写入的设置文件，重启后生效
+    pub settings_files: Vec<String>,
+    pub restart_required: bool,
+    /// 导入失败的条目
+    pub errors: Vec<String>,
+}
+
+fn emit_profile_progress(app_handle: &AppHandle, event: &str, stage: &str, completed: usize, total: usize) {
+    let _ = app_handle.emit_all(event, serde_json::json!({
+        "stage": stage,
+        "completed": completed,
+        "total": total,
+    }));
+}
+
+fn is_settings_file_name(name: &str) -> bool {
+    name.ends_with(".json")
+        && !EXCLUDED_SETTINGS_FILES.contains(&name)
+        && Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name)
+}
+
+fn read_settings_files(app_data_dir: &Path) -> BTreeMap<String, serde_json::Value> {
+    let Ok(entries) = fs::read_dir(app_data_dir) else {
+        return BTreeMap::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !is_settings_file_name(&name) {
+                return None;
+            }
+            let content = fs::read_to_string(entry.path()).ok()?;
+            match serde_json::from_str(&content) {
+                Ok(value) => Some((name, value)),
+                Err(e) => {
+                    warn!("跳过无法解析的设置文件 {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+pub(crate) async fn collect_profile(app_handle: &AppHandle) -> Result<ProfileBundle, String> {
+    const EVENT: &str = "profile-export-progress";
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+
+    emit_profile_progress(app_handle, EVENT, "settings", 0, 1);
+    let settings = app_handle.state::<AppState>().config.lock().clone();
+    let settings_files = crate::utils::config::get_app_data_dir()
+        .map(|dir| read_settings_files(&dir))
+        .unwrap_or_default();
+
+    let list = db
+        .conversation_history
+        .list_conversations(i64::MIN, i64::MAX)
+        .await
+        .map_err(|e| format!("读取对话列表失败: {}", e))?;
+    let total = list.len();
+    let mut conversations = Vec::with_capacity(total);
+    for (index, conversation) in list.into_iter().enumerate() {
+        let history = &db.conversation_history;
+        let messages = history
+            .get_messages(&conversation.id)
+            .await
+            .map_err(|e| format!("读取对话 {} 失败: {}", conversation.id, e))?
+            .into_iter()
+            .filter(|message| !message.is_deleted())
+            .collect();
+        let parameters = history.get_parameters(&conversation.id).await.unwrap_or_default();
+        conversations.push(ConversationBundle { conversation, parameters, messages });
+        if (index + 1) % CONVERSATION_PROGRESS_STEP == 0 || index + 1 == total {
+            emit_profile_progress(app_handle, EVENT, "conversations", index + 1, total);
+        }
+    }
+
+    emit_profile_progress(app_handle, EVENT, "configs", 0, 1);
+    let model_configs = db
+        .model_config_registry
+        .get_all_configs_async()
+        .await
+        .map_err(|e| format!("读取模型配置失败: {}", e))?;
+    let prompts = db
+        .prompt_registry
+        .get_all_prompts()
+        .await
+        .map_err(|e| format!("读取提示词失败: {}", e))?;
+    let mut character_configs = Vec::new();
+    let characters = db
+        .character_registry
+        .get_all_characters_async()
+        .await
+        .map_err(|e| format!("读取角色失败: {}", e))?;
+    for character in characters {
+        match db.character_registry.get_character_config_async(&character.id).await {
+            Ok(Some(config)) => character_configs.push(config),
+            Ok(None) => {}
+            Err(e) => warn!("读取角色配置失败 {}: {}", character.id, e),
+        }
+    }
+    let adapters = db
+        .adapter_registry
+        .get_all_adapters()
+        .await
+        .map_err(|e| format!("读取适配器失败: {}", e))?;
+    emit_profile_progress(app_handle, EVENT, "configs", 1, 1);
+
+    Ok(ProfileBundle {
+        app_version: app_handle.package_info().version.to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        settings,
+        settings_files,
+        conversations,
+        model_configs,
+        prompts,
+        character_configs,
+        adapters,
+    })
+}
+
+async fn restore_conversations(
+    app_handle: &AppHandle,
+    conversations: Vec<ConversationBundle>,
+    summary: &mut ProfileImportSummary,
+) -> Result<(), String> {
+    const EVENT: &str = "profile-import-progress";
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let history = &db.conversation_history;
+    let total = conversations.len();
+
+    for (index, bundle) in conversations.into_iter().enumerate() {
+        let id = bundle.conversation.id.clone();
+        let result: Result<bool, Box<dyn std::error::Error + Send + Sync>> = async {
+            if history.get_conversation(&id).await?.is_some() {
+                return Ok(false);
+            }
+            history.create_conversation(bundle.conversation).await?;
+            if let Some(parameters) = &bundle.parameters {
+                history.set_parameters(&id, Some(parameters), chrono::Utc::now().timestamp()).await?;
+            }
+            for message in bundle.messages {
+                history.add_message(message).await?;
+                summary.messages_imported += 1;
+            }
+            Ok(true)
+        }
+        .await;
+        match result {
+            Ok(true) => summary.conversations_imported += 1,
+            Ok(false) => summary.conversations_skipped += 1,
+            Err(e) => summary.errors.push(format!("对话 {}: {}", id, e)),
+        }
+        if (index + 1) % CONVERSATION_PROGRESS_STEP == 0 || index + 1 == total {
+            emit_profile_progress(app_handle, EVENT, "conversations", index + 1, total);
+        }
+    }
+    Ok(())
+}
+
+/// 恢复资料：设置与配置覆盖本机同名条目，本机已有的对话保留不动
+pub(crate) async fn restore_profile(app_handle: &AppHandle, bundle: ProfileBundle) -> Result<ProfileImportSummary, String> {
+    const EVENT: &str = "profile-import-progress";
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let mut summary = ProfileImportSummary::default();
+
+    emit_profile_progress(app_handle, EVENT, "settings", 0, 1);
+    match validate_config(&bundle.settings) {
+        Ok(()) => {
+            save_config(app_handle, &bundle.settings)
+                .await
+                .map_err(|e| format!("保存设置失败: {}", e))?;
+            *app_handle.state::<AppState>().config.lock() = bundle.settings;
+        }
+        Err(e) => summary.errors.push(format!("设置无效，已跳过: {}", e)),
+    }
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    for (name, value) in bundle.settings_files {
+        if !is_settings_file_name(&name) {
+            summary.errors.push(format!("跳过无效的设置文件名: {}", name));
+            continue;
+        }
+        let written = serde_json::to_string_pretty(&value)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(app_data_dir.join(&name), json).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => summary.settings_files.push(name),
+            Err(e) => summary.errors.push(format!("设置文件 {}: {}", name, e)),
+        }
+    }
+    summary.restart_required = !summary.settings_files.is_empty();
+
+    restore_conversations(app_handle, bundle.conversations, &mut summary).await?;
+
+    emit_profile_progress(app_handle, EVENT, "configs", 0, 1);
+    for config in bundle.model_configs {
+        let id = config.id.clone();
+        match db.model_config_registry.save_config_async(config).await {
+            Ok(()) => summary.model_configs += 1,
+            Err(e) => summary.errors.push(format!("模型配置 {}: {}", id, e)),
+        }
+    }
+    for prompt in bundle.prompts {
+        let id = prompt.id.clone();
+        let result = match db.prompt_registry.get_prompt(&id).await {
+            Ok(Some(_)) => db.prompt_registry.update_prompt(&id, prompt).await,
+            Ok(None) => db.prompt_registry.create_prompt(prompt).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => summary.prompts += 1,
+            Err(e) => summary.errors.push(format!("提示词 {}: {}", id, e)),
+        }
+    }
+    for config in bundle.character_configs {
+        let id = config.character_id.clone();
+        match db.character_registry.save_character_config_async(config).await {
+            Ok(()) => summary.character_configs += 1,
+            Err(e) => summary.errors.push(format!("角色配置 {}: {}", id, e)),
+        }
+    }
+    // 适配器文件不在导出范围内：本机已安装的恢复设置，未安装的交给前端提示重新安装
+    for adapter in bundle.adapters {
+        match db.adapter_registry.get_adapter(&adapter.id).await {
+            Ok(Some(installed)) => {
+                let restored = InstalledAdapter {
+                    enabled: adapter.enabled,
+                    auto_update: adapter.auto_update,
+                    config: adapter.config,
+                    ..installed
+                };
+                match db.adapter_registry.update_adapter(restored).await {
+                    Ok(()) => summary.adapters_updated += 1,
+                    Err(e) => summary.errors.push(format!("适配器 {}: {}", adapter.id, e)),
+                }
+            }
+            Ok(None) => summary.missing_adapters.push(adapter),
+            Err(e) => summary.errors.push(format!("适配器 {}: {}", adapter.id, e)),
+        }
+    }
+    emit_profile_progress(app_handle, EVENT, "configs", 1, 1);
+    crate::commands::cache::invalidate_all().await;
+
+    Ok(summary)
+}
+
+/// 导出完整用户资料为加密文件
+#[tauri::command]
+pub async fn export_profile(app_handle: AppHandle, path: String, password: String) -> Result<ProfileExportSummary, String> {
+    profile_archive::validate_password(&password)?;
+    let bundle = collect_profile(&app_handle).await?;
+    let mut summary = ProfileExportSummary {
+        path: path.clone(),
+        size_bytes: 0,
+        conversations: bundle.conversations.len(),
+        messages: bundle.conversations.iter().map(|c| c.messages.len()).sum(),
+        model_configs: bundle.model_configs.len(),
+        prompts: bundle.prompts.len(),
+        character_configs: bundle.character_configs.len(),
+        adapters: bundle.adapters.len(),
+    };
+
+    emit_profile_progress(&app_handle, "profile-export-progress", "encrypting", 0, 1);
+    let plaintext = serde_json::to_vec(&bundle).map_err(|e| format!("序列化资料失败: {}", e))?;
+    let archive = tokio::task::spawn_blocking(move || profile_archive::seal(&password, &plaintext))
+        .await
+        .map_err(|e| format!("加密资料失败: {}", e))??;
+    let json = serde_json::to_vec(&archive).map_err(|e| format!("序列化资料失败: {}", e))?;
+    fs::write(&path, &json).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    summary.size_bytes = json.len() as u64;
+    emit_profile_progress(&app_handle, "profile-export-progress", "done", 1, 1);
+
+    info!(
+        "用户资料已导出: {} ({} 个对话, {} 字节)",
+        summary.path, summary.conversations, summary.size_bytes
+    );
+    Ok(summary)
+}
+
+/// 从加密文件导入用户资料
+#[tauri::command]
+pub async fn import_profile(app_handle: AppHandle, path: String, password: String) -> Result<ProfileImportSummary, String> {
+    emit_profile_progress(&app_handle, "profile-import-progress", "decrypting", 0, 1);
+    let content = fs::read(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let archive: ProfileArchive =
+        serde_json::from_slice(&content).map_err(|_| "不是资料导出文件".to_string())?;
+    let plaintext = tokio::task::spawn_blocking(move || profile_archive::open(&password, &archive))
+        .await
+        .map_err(|e| format!("解密资料失败: {}", e))??;
+    let bundle: ProfileBundle =
+        serde_json::from_slice(&plaintext).map_err(|e| format!("解析资料失败: {}", e))?;
+
+    info!("导入用户资料: 来自版本 {}，导出于 {}", bundle.app_version, bundle.exported_at);
+    let summary = restore_profile(&app_handle, bundle).await?;
+    emit_profile_progress(&app_handle, "profile-import-progress", "done", 1, 1);
+    info!(
+        "用户资料导入完成: {} 个对话, {} 个错误",
+        summary.conversations_imported,
+        summary.errors.len()
+    );
+    Ok(summary)
+}
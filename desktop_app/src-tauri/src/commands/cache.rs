@@ -0,0 +1,307 @@
+//! # 命令结果缓存
+//!
+//! 读多写少的命令（角色列表、模型配置列表、市场搜索、适配器列表）的结果通过 [`CacheService`]
+//! 缓存在 Redis 中，有效期取自各命令的 [`CacheConfig`]。修改这些数据的命令调用 [`invalidate`]
+//! 清除对应缓存。Redis 未启用或连接断开时直接执行原命令（计为旁路），不影响功能。
+//! 键中带有当前用户资料的 ID，切换资料后不会读到另一个资料的结果。
+//!
+//! 命中、未命中与旁路次数按命令统计，通过 `get_cache_stats` 查看。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use tracing::{debug, info, warn};
+
+use crate::commands::{generate_cache_key, CacheConfig, CommandResponse};
+use crate::database::backends::DatabaseBackend;
+use crate::database::cache_service::CacheService;
+
+/// 命令缓存在 Redis 中的键前缀（之后是资料 ID，见 [`profile_key_prefix`]）
+const KEY_PREFIX: &str = "zishu_cache:cmd:";
+
+/// 带缓存的命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CachedCommand {
+    Characters,
+    ModelConfigs,
+    MarketSearch,
+    Adapters,
+}
+
+impl CachedCommand {
+    pub const ALL: [CachedCommand; 4] = [
+        CachedCommand::Characters,
+        CachedCommand::ModelConfigs,
+        CachedCommand::MarketSearch,
+        CachedCommand::Adapters,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CachedCommand::Characters => "get_characters",
+            CachedCommand::ModelConfigs => "get_all_model_configs",
+            CachedCommand::MarketSearch => "search_market_products",
+            CachedCommand::Adapters => "get_adapters",
+        }
+    }
+
+    /// 命令的缓存配置：本地数据缓存较久，后端状态（适配器加载情况）只缓存很短时间
+    pub fn config(self) -> CacheConfig {
+        let defaults = CacheConfig::default();
+        match self {
+            CachedCommand::Characters | CachedCommand::ModelConfigs => CacheConfig {
+                ttl_seconds: 600,
+                max_entries: 1,
+                ..defaults
+            },
+            CachedCommand::MarketSearch => CacheConfig {
+                ttl_seconds: 120,
+                max_entries: 200,
+                ..defaults
+            },
+            CachedCommand::Adapters => CacheConfig {
+                ttl_seconds: 30,
+                max_entries: 1,
+                ..defaults
+            },
+        }
+    }
+
+    fn category(self) -> String {
+        format!("{}:", self.name())
+    }
+}
+
+/// 单个命令的缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandCacheStats {
+    pub command: &'static str,
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Redis 不可用或读取失败时直接执行命令的次数
+    pub bypassed: u64,
+    pub invalidations: u64,
+    /// 命中率（命中 / (命中 + 未命中)）
+    pub hit_rate: f64,
+    /// 本次运行中写入过的参数组合数
+    pub cached_keys: usize,
+}
+
+/// `get_cache_stats` 返回的缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub redis_available: bool,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub commands: Vec<CommandCacheStats>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: u64,
+    misses: u64,
+    bypassed: u64,
+    invalidations: u64,
+    keys: HashSet<String>,
+}
+
+impl Counters {
+    /// 记录写入的键；参数组合数达到上限后不再缓存新的组合
+    fn remember(&mut self, key: &str, max_entries: usize) -> bool {
+        if self.keys.contains(key) {
+            return true;
+        }
+        if self.keys.len() >= max_entries {
+            return false;
+        }
+        self.keys.insert(key.to_string());
+        true
+    }
+
+    fn stats(&self, command: CachedCommand) -> CommandCacheStats {
+        let config = command.config();
+        CommandCacheStats {
+            command: command.name(),
+            enabled: config.enabled,
+            ttl_seconds: config.ttl_seconds,
+            hits: self.hits,
+            misses: self.misses,
+            bypassed: self.bypassed,
+            invalidations: self.invalidations,
+            hit_rate: hit_rate(self.hits, self.misses),
+            cached_keys: self.keys.len(),
+        }
+    }
+}
+
+fn hit_rate(hits: u64, misses: u64) -> f64 {
+    match hits + misses {
+        0 => 0.0,
+        total => hits as f64 / total as f64,
+    }
+}
+
+lazy_static! {
+    static ref COUNTERS: Mutex<BTreeMap<CachedCommand, Counters>> = Mutex::new(BTreeMap::new());
+}
+
+fn with_counters<R>(command: CachedCommand, f: impl FnOnce(&mut Counters) -> R) -> R {
+    f(COUNTERS.lock().entry(command).or_default())
+}
+
+/// 当前资料的键前缀：`prefix + 资料ID:`
+pub fn profile_key_prefix(prefix: &str) -> String {
+    format!("{}{}:", prefix, crate::utils::profiles::active_id())
+}
+
+/// 已连接的 Redis 上的缓存服务（键带 `prefix` 与当前资料 ID 前缀）
+pub async fn redis_service(prefix: &str) -> Option<CacheService> {
+    let redis = crate::database::get_database_manager()?.redis()?;
+    if !redis.read().await.is_connected() {
+        return None;
+    }
+    Some(CacheService::new(redis).with_prefix(&profile_key_prefix(prefix)))
+}
+
+async fn cache_service() -> Option<CacheService> {
+    redis_service(KEY_PREFIX).await
+}
+
+/// 删除 Redis 中匹配 `pattern` 的键，Redis 不可用时返回 0
+pub async fn delete_redis_keys(pattern: &str) -> Result<u64, String> {
+    let Some(redis) = crate::database::get_database_manager().and_then(|m| m.redis()) else {
+        return Ok(0);
+    };
+    let backend = redis.read().await;
+    if !backend.is_connected() {
+        return Ok(0);
+    }
+    backend.delete_matching(pattern).await.map_err(|e| e.to_string())
+}
+
+/// 带缓存执行命令：命中时返回缓存的结果，否则执行 `load` 并缓存成功的结果
+///
+/// `params` 为影响结果的参数（序列化后的请求），无参数的命令传空串。
+pub async fn cached<T, F, Fut>(command: CachedCommand, params: &str, load: F) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let config = command.config();
+    let service = if config.enabled { cache_service().await } else { None };
+    let Some(service) = service else {
+        with_counters(command, |c| c.bypassed += 1);
+        return load().await;
+    };
+
+    let category = command.category();
+    let key = generate_cache_key(command.name(), params);
+    match service.get::<T>(&category, &key).await {
+        Ok(Some(value)) => {
+            with_counters(command, |c| c.hits += 1);
+            debug!("命令缓存命中: {}", command.name());
+            return Ok(value);
+        }
+        Ok(None) => with_counters(command, |c| c.misses += 1),
+        Err(e) => {
+            warn!("读取命令缓存失败 {}: {}", command.name(), e);
+            with_counters(command, |c| c.bypassed += 1);
+            return load().await;
+        }
+    }
+
+    let value = load().await?;
+    if with_counters(command, |c| c.remember(&key, config.max_entries)) {
+        if let Err(e) = service.set(&category, &key, &value, config.ttl_seconds).await {
+            warn!("写入命令缓存失败 {}: {}", command.name(), e);
+        }
+    }
+    Ok(value)
+}
+
+/// 清除命令的全部缓存（修改对应数据后调用）
+pub async fn invalidate(command: CachedCommand) {
+    with_counters(command, |c| {
+        c.invalidations += 1;
+        c.keys.clear();
+    });
+
+    let pattern = format!("{}{}*", profile_key_prefix(KEY_PREFIX), command.category());
+    match delete_redis_keys(&pattern).await {
+        Ok(deleted) => debug!("已清除 {} 条 {} 命令缓存", deleted, command.name()),
+        Err(e) => warn!("清除命令缓存失败 {}: {}", command.name(), e),
+    }
+}
+
+/// 清除所有命令缓存（整体恢复数据后调用）
+pub async fn invalidate_all() {
+    for command in CachedCommand::ALL {
+        invalidate(command).await;
+    }
+}
+
+/// 获取命令缓存的命中统计
+#[tauri::command]
+pub async fn get_cache_stats() -> Result<CommandResponse<CacheStats>, String> {
+    let redis_available = cache_service().await.is_some();
+    let commands: Vec<CommandCacheStats> = {
+        let mut counters = COUNTERS.lock();
+        CachedCommand::ALL
+            .iter()
+            .map(|command| counters.entry(*command).or_default().stats(*command))
+            .collect()
+    };
+    let hits = commands.iter().map(|c| c.hits).sum();
+    let misses = commands.iter().map(|c| c.misses).sum();
+    info!("命令缓存统计: 命中 {} 次，未命中 {} 次", hits, misses);
+
+    Ok(CommandResponse::success(CacheStats {
+        redis_available,
+        hits,
+        misses,
+        hit_rate: hit_rate(hits, misses),
+        commands,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_rate_and_stats() {
+        assert_eq!(hit_rate(0, 0), 0.0);
+        assert_eq!(hit_rate(3, 1), 0.75);
+
+        let counters = Counters { hits: 1, misses: 1, ..Counters::default() };
+        let stats = counters.stats(CachedCommand::MarketSearch);
+        assert_eq!(stats.command, "search_market_products");
+        assert_eq!(stats.hit_rate, 0.5);
+        assert_eq!(stats.ttl_seconds, CachedCommand::MarketSearch.config().ttl_seconds);
+    }
+
+    #[test]
+    fn test_key_prefix_includes_profile() {
+        let prefix = profile_key_prefix(KEY_PREFIX);
+        assert!(prefix.starts_with(KEY_PREFIX));
+        assert!(prefix.ends_with(&format!("{}:", crate::utils::profiles::active_id())));
+    }
+
+    #[test]
+    fn test_remember_respects_max_entries() {
+        let mut counters = Counters::default();
+        assert!(counters.remember("a", 2));
+        assert!(counters.remember("b", 2));
+        assert!(!counters.remember("c", 2));
+        // 已缓存的组合可以继续刷新
+        assert!(counters.remember("a", 2));
+        assert_eq!(counters.keys.len(), 2);
+    }
+}
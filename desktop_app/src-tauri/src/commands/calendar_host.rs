@@ -0,0 +1,332 @@
+//! # 适配器日历宿主接口
+//!
+//! 提醒、日程类适配器通过 `host_calendar_*` 命令读写用户日历（模型与 ICS 格式见
+//! [`crate::adapter::calendar`]）。每次调用都要求适配器拥有 `calendar` 权限：
+//! 读取需要 `read` 级别，创建与删除需要 `write` 级别，缺少时由
+//! [`permission_consent::ensure_permissions`] 请用户确认。
+//!
+//! - 适配器只能写入本地日历，且只能删除自己创建的事件
+//! - 导入的系统日历（ICS 文件）只读，在设置中管理
+//! - 后台任务在事件开始前提醒：发出系统通知，角色说出提醒并按设置朗读
+//!
+//! 本地日历变化时发送 `calendar-changed`。
+
+use chrono::{Local, TimeZone, Utc};
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use crate::adapter::calendar::{
+    self, CalendarEvent, CalendarSettings, NewCalendarEvent, LOCAL_CALENDAR_ID,
+};
+use crate::commands::notifications::{self, NewNotification};
+use crate::commands::permission_consent::{self, PermissionRequirement};
+use crate::commands::{speech_queue, tts};
+use crate::database::permission::{PermissionLevel, PermissionType};
+use crate::state::tray_state::NotificationType;
+use crate::utils::i18n;
+use crate::utils::speech_queue::{Utterance, UtteranceKind};
+use crate::utils::watchdog;
+
+/// 适配器需要的权限类型
+const CALENDAR_PERMISSION: &str = "calendar";
+const LOCAL_CALENDAR_FILE: &str = "calendar.ics";
+/// 未指定时间段时列出未来 7 天的事件
+const DEFAULT_LIST_WINDOW_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+/// 检查即将开始的事件的间隔
+const REMINDER_INTERVAL: Duration = Duration::from_secs(30);
+const REMINDER_STALL_AFTER: Duration = Duration::from_secs(10 * 60);
+
+lazy_static! {
+    static ref SETTINGS: RwLock<CalendarSettings> = RwLock::new(CalendarSettings::default());
+    /// 本地日历的读改写在同一把锁内完成
+    static ref LOCAL_LOCK: Mutex<()> = Mutex::new(());
+    /// 已提醒过的事件（见 [`CalendarEvent::reminder_key`]）
+    static ref ANNOUNCED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+fn get_calendar_data_path(file_name: &str) -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join(file_name))
+}
+
+/// 加载日历设置（在 `main.rs` 启动时调用）
+pub fn initialize_calendar_host() -> Result<(), String> {
+    let config_path = get_calendar_data_path("calendar_host.json")?;
+    let settings: CalendarSettings = if config_path.exists() {
+        let json_data = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read calendar settings: {}", e))?;
+        serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse calendar settings: {}", e))?
+    } else {
+        CalendarSettings::default()
+    };
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn requirement(level: PermissionLevel, reason: &str) -> PermissionRequirement {
+    PermissionRequirement {
+        permission_type: PermissionType::Custom(CALENDAR_PERMISSION.to_string()),
+        level,
+        scope: None,
+        reason: Some(reason.to_string()),
+    }
+}
+
+fn read_local() -> Result<Vec<CalendarEvent>, String> {
+    let path = get_calendar_data_path(LOCAL_CALENDAR_FILE)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(&path).map_err(|e| format!("读取本地日历失败: {}", e))?;
+    calendar::parse_ics(&text, LOCAL_CALENDAR_ID)
+}
+
+fn write_local(events: &[CalendarEvent]) -> Result<(), String> {
+    let path = get_calendar_data_path(LOCAL_CALENDAR_FILE)?;
+    fs::write(&path, calendar::to_ics(events, Utc::now())).map_err(|e| format!("写入本地日历失败: {}", e))
+}
+
+/// 本地日历与已启用的导入日历中的全部事件；无法读取的导入日历被跳过
+fn load_all_events() -> Result<Vec<CalendarEvent>, String> {
+    let mut events = {
+        let _guard = LOCAL_LOCK.lock();
+        read_local()?
+    };
+    let sources = SETTINGS.read().sources.clone();
+    for source in sources.iter().filter(|source| source.enabled) {
+        match fs::read_to_string(&source.path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| calendar::parse_ics(&text, &source.id))
+        {
+            Ok(imported) => events.extend(imported),
+            Err(e) => warn!("读取日历 {} 失败: {}", source.name, e),
+        }
+    }
+    Ok(events)
+}
+
+fn emit_changed(app_handle: &AppHandle) {
+    let _ = app_handle.emit_all("calendar-changed", ());
+}
+
+// ================================
+// 事件提醒
+// ================================
+
+/// 提醒一个即将开始的事件
+fn announce(app_handle: &AppHandle, event: &CalendarEvent, now: i64, speak_aloud: bool) {
+    let minutes = ((event.start - now + 59_999) / 60_000).max(1).to_string();
+    let time = Local
+        .timestamp_millis_opt(event.start)
+        .single()
+        .map(|start| start.format("%H:%M").to_string())
+        .unwrap_or_default();
+    let args = [("title", event.title.as_str()), ("minutes", minutes.as_str()), ("time", time.as_str())];
+
+    let mut body = i18n::t_args("notify-calendar-event-body", &args);
+    if let Some(location) = &event.location {
+        body.push_str(&format!(" · {}", location));
+    }
+    notifications::post(
+        app_handle,
+        NewNotification::new(i18n::t_args("notify-calendar-event-upcoming", &args), body, NotificationType::Info)
+            .source("calendar"),
+    );
+
+    let line = i18n::t_args("notify-calendar-event-speech", &args);
+    speech_queue::say(app_handle, Utterance::new(UtteranceKind::Reminder, line.clone(), "calendar"));
+    if speak_aloud {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = tts::speak(&app_handle, &line, None, None).await {
+                warn!("朗读日程提醒失败: {}", e);
+            }
+        });
+    }
+}
+
+/// 检查一次即将开始的事件
+fn check_reminders(app_handle: &AppHandle) {
+    let settings = SETTINGS.read().clone();
+    if !settings.reminders_enabled {
+        return;
+    }
+    let events = match load_all_events() {
+        Ok(events) => events,
+        Err(e) => {
+            debug!("读取日历失败，跳过本次提醒: {}", e);
+            return;
+        }
+    };
+
+    let now = Utc::now().timestamp_millis();
+    let due: Vec<CalendarEvent> = {
+        let mut announced = ANNOUNCED.lock();
+        let due: Vec<CalendarEvent> = calendar::due_reminders(&events, now, settings.lead_ms(), &announced)
+            .into_iter()
+            .cloned()
+            .collect();
+        announced.extend(due.iter().map(CalendarEvent::reminder_key));
+        // 已开始的事件不会再提醒，不再需要记录
+        let current: HashSet<String> = events
+            .iter()
+            .filter(|event| event.start > now)
+            .map(CalendarEvent::reminder_key)
+            .collect();
+        announced.retain(|key| current.contains(key));
+        due
+    };
+
+    for event in &due {
+        info!("日程即将开始: {} ({})", event.title, event.calendar_id);
+        announce(app_handle, event, now, settings.speak_reminders);
+    }
+}
+
+/// 启动日程提醒任务
+pub fn start_calendar_reminders(app_handle: AppHandle) {
+    watchdog::supervise("calendar_reminders", REMINDER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                check_reminders(&app_handle);
+                tokio::time::sleep(REMINDER_INTERVAL).await;
+            }
+        });
+    });
+}
+
+// ================================
+// 适配器调用的宿主接口
+// ================================
+
+/// 列出时间段内的事件（默认从现在起 7 天），包含导入的系统日历
+#[tauri::command]
+pub async fn host_calendar_list_events(
+    app_handle: AppHandle,
+    adapter_id: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let from = from.unwrap_or_else(|| Utc::now().timestamp_millis());
+    let to = to.unwrap_or(from + DEFAULT_LIST_WINDOW_MS);
+    if to < from {
+        return Err("结束时间不能早于开始时间".to_string());
+    }
+    permission_consent::ensure_permissions(
+        &app_handle,
+        &adapter_id,
+        &[requirement(PermissionLevel::Read, "读取日历中的日程")],
+    )
+    .await?;
+
+    let events = load_all_events()?;
+    Ok(calendar::events_between(&events, from, to))
+}
+
+/// 在本地日历中创建事件
+#[tauri::command]
+pub async fn host_calendar_create_event(
+    app_handle: AppHandle,
+    adapter_id: String,
+    event: NewCalendarEvent,
+) -> Result<CalendarEvent, String> {
+    event.validate()?;
+    permission_consent::ensure_permissions(
+        &app_handle,
+        &adapter_id,
+        &[requirement(PermissionLevel::Write, "在日历中添加日程")],
+    )
+    .await?;
+
+    let event = event.into_event(&adapter_id);
+    {
+        let _guard = LOCAL_LOCK.lock();
+        let mut events = read_local()?;
+        events.push(event.clone());
+        write_local(&events)?;
+    }
+    info!("适配器 {} 创建日程: {}", adapter_id, event.title);
+    emit_changed(&app_handle);
+    Ok(event)
+}
+
+/// 删除适配器自己创建的事件；事件不存在时返回 `false`
+#[tauri::command]
+pub async fn host_calendar_delete_event(app_handle: AppHandle, adapter_id: String, uid: String) -> Result<bool, String> {
+    permission_consent::ensure_permissions(
+        &app_handle,
+        &adapter_id,
+        &[requirement(PermissionLevel::Write, "删除日历中的日程")],
+    )
+    .await?;
+
+    {
+        let _guard = LOCAL_LOCK.lock();
+        let mut events = read_local()?;
+        let Some(index) = events.iter().position(|event| event.uid == uid) else {
+            return Ok(false);
+        };
+        if events[index].created_by.as_deref() != Some(adapter_id.as_str()) {
+            return Err(format!("适配器 {} 只能删除自己创建的日程", adapter_id));
+        }
+        events.remove(index);
+        write_local(&events)?;
+    }
+    info!("适配器 {} 删除日程: {}", adapter_id, uid);
+    emit_changed(&app_handle);
+    Ok(true)
+}
+
+// ================================
+// 设置页命令
+// ================================
+
+/// 即将开始的事件（设置页与角色面板显示，默认未来 24 小时）
+#[tauri::command]
+pub async fn get_upcoming_calendar_events(hours: Option<u32>) -> Result<Vec<CalendarEvent>, String> {
+    let now = Utc::now().timestamp_millis();
+    let window_ms = i64::from(hours.unwrap_or(24).clamp(1, 24 * 31)) * 60 * 60 * 1000;
+    let events = load_all_events()?;
+    Ok(calendar::events_between(&events, now, now + window_ms))
+}
+
+#[tauri::command]
+pub async fn get_calendar_settings() -> Result<CalendarSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 更新日历设置；新导入的日历文件必须能够解析
+#[tauri::command]
+pub async fn update_calendar_settings(app_handle: AppHandle, settings: CalendarSettings) -> Result<(), String> {
+    settings.validate()?;
+    for source in settings.sources.iter().filter(|source| source.enabled) {
+        let text = fs::read_to_string(&source.path).map_err(|e| format!("无法读取日历 {}: {}", source.name, e))?;
+        calendar::parse_ics(&text, &source.id).map_err(|e| format!("日历 {} 无效: {}", source.name, e))?;
+    }
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize calendar settings: {}", e))?;
+    fs::write(get_calendar_data_path("calendar_host.json")?, json_data)
+        .map_err(|e| format!("Failed to write calendar settings: {}", e))?;
+
+    info!(
+        "日历设置已更新: reminders={}, lead={}分钟, sources={}",
+        settings.reminders_enabled,
+        settings.lead_minutes,
+        settings.sources.len()
+    );
+    *SETTINGS.write() = settings;
+    emit_changed(&app_handle);
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! # 截图命令模块
+//!
+//! 截取整个显示器、应用窗口或桌面区域（坐标规则见 [`crate::utils::screen_capture`]），
+//! 需要屏幕录制权限（`HardwareScreenCapture`）。截图以 PNG 保存到文件管理。
+//!
+//! 指定 `attach_to_chat` 时截图作为图片附件排队，随该会话的下一条消息发送给多模态模型，
+//! 并发出 `chat-attachment-added` 事件。无痕会话的截图只保存在内存中，不写入文件管理。
+
+use base64::Engine;
+use chrono::Utc;
+use image::RgbaImage;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::commands::file::{self, UploadFileRequest};
+use crate::commands::incognito;
+use crate::utils::bridge::ImageAttachment;
+use crate::utils::permission_checker::HardwareChecker;
+use crate::utils::screen_capture::{self, CaptureRegion};
+
+/// 每个会话最多排队的图片附件数
+const MAX_PENDING_ATTACHMENTS: usize = 4;
+
+lazy_static! {
+    /// 等待随下一条消息发送的图片附件，按会话 ID 分组（新会话为空字符串）
+    static ref PENDING_ATTACHMENTS: Mutex<HashMap<String, Vec<ImageAttachment>>> = Mutex::new(HashMap::new());
+}
+
+/// 截图选项
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CaptureOptions {
+    /// 作为图片附件随下一条消息发送
+    #[serde(default)]
+    pub attach_to_chat: bool,
+    /// 附件所属的会话（为空时随新会话的第一条消息发送）
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// 截图结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureResult {
+    /// 文件管理中的文件 ID（无痕会话的截图不保存）
+    pub file_id: Option<String>,
+    pub file_path: Option<String>,
+    pub display_id: u32,
+    pub width: u32,
+    pub height: u32,
+    pub captured_at: i64,
+    pub attached: bool,
+}
+
+/// 取出会话排队的图片附件（发送消息时调用）
+pub fn take_chat_attachments(session_id: Option<&str>) -> Vec<ImageAttachment> {
+    PENDING_ATTACHMENTS
+        .lock()
+        .remove(session_id.unwrap_or_default())
+        .unwrap_or_default()
+}
+
+fn displays() -> Result<Vec<Screen>, String> {
+    Screen::all().map_err(|e| format!("获取显示器列表失败: {}", e))
+}
+
+fn display_bounds(screen: &Screen) -> CaptureRegion {
+    let info = &screen.display_info;
+    CaptureRegion { x: info.x, y: info.y, width: info.width, height: info.height }
+}
+
+/// 截取区域，区域为桌面坐标；`None` 表示截取整个显示器
+fn grab(display_id: Option<u32>, region: Option<CaptureRegion>) -> Result<(u32, RgbaImage), String> {
+    let screens = displays()?;
+    let (screen, area) = match region {
+        Some(region) => {
+            region.validate()?;
+            let bounds: Vec<_> = screens.iter().map(|s| (s.display_info.id, display_bounds(s))).collect();
+            let (id, area) = screen_capture::locate(&region, &bounds).ok_or("截图区域不在任何显示器上")?;
+            let screen = screens.iter().find(|s| s.display_info.id == id).ok_or("显示器不存在")?;
+            (screen, Some(area))
+        }
+        None => {
+            let screen = match display_id {
+                Some(id) => screens.iter().find(|s| s.display_info.id == id),
+                None => screens.iter().find(|s| s.display_info.is_primary).or_else(|| screens.first()),
+            };
+            (screen.ok_or("显示器不存在")?, None)
+        }
+    };
+
+    let image = match area {
+        Some(area) => screen.capture_area(area.x, area.y, area.width, area.height),
+        None => screen.capture(),
+    }
+    .map_err(|e| format!("截图失败: {}", e))?;
+    Ok((screen.display_info.id, image))
+}
+
+fn encode_png(image: RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgba8(image)
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Png)
+        .map_err(|e| format!("编码截图失败: {}", e))?;
+    Ok(bytes)
+}
+
+async fn capture(
+    app_handle: &AppHandle,
+    kind: &str,
+    display_id: Option<u32>,
+    region: Option<CaptureRegion>,
+    options: CaptureOptions,
+) -> Result<CaptureResult, String> {
+    HardwareChecker::check_screen_capture("feature", "screen_capture")
+        .map_err(|e| format!("截图需要屏幕录制权限: {}", e))?;
+
+    let (display_id, image) = tokio::task::spawn_blocking(move || grab(display_id, region))
+        .await
+        .map_err(|e| format!("截图任务失败: {}", e))??;
+    let (width, height) = image.dimensions();
+    let png = encode_png(image)?;
+    let captured_at = Utc::now();
+
+    let session_id = options.session_id.filter(|id| !id.is_empty());
+    let incognito = incognito::is_incognito(session_id.as_deref());
+    let saved = if incognito {
+        None
+    } else {
+        let request = UploadFileRequest {
+            file_name: screen_capture::file_name(kind, captured_at),
+            file_data: png.clone(),
+            conversation_id: session_id.clone(),
+            message_id: None,
+            tags: Some("screenshot".to_string()),
+            description: None,
+        };
+        Some(file::upload_file(app_handle.clone(), request).await?.file_info)
+    };
+
+    if options.attach_to_chat {
+        let attachment = ImageAttachment {
+            mime_type: "image/png".to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(&png),
+            file_id: saved.as_ref().map(|file| file.id.clone()),
+        };
+        let mut pending = PENDING_ATTACHMENTS.lock();
+        let queue = pending.entry(session_id.clone().unwrap_or_default()).or_default();
+        if queue.len() >= MAX_PENDING_ATTACHMENTS {
+            return Err(format!("每条消息最多附带 {} 张截图", MAX_PENDING_ATTACHMENTS));
+        }
+        queue.push(attachment);
+        drop(pending);
+
+        let _ = app_handle.emit_all("chat-attachment-added", serde_json::json!({
+            "session_id": session_id,
+            "file_id": saved.as_ref().map(|file| &file.id),
+            "width": width,
+            "height": height,
+        }));
+    }
+
+    info!("已截图（{}）: {}x{}，显示器 {}", kind, width, height, display_id);
+    Ok(CaptureResult {
+        file_id: saved.as_ref().map(|file| file.id.clone()),
+        file_path: saved.map(|file| file.file_path),
+        display_id,
+        width,
+        height,
+        captured_at: captured_at.timestamp(),
+        attached: options.attach_to_chat,
+    })
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 截取整个显示器，未指定时截取主显示器
+#[tauri::command]
+pub async fn capture_screen(
+    app_handle: AppHandle,
+    display_id: Option<u32>,
+    options: Option<CaptureOptions>,
+) -> Result<CaptureResult, String> {
+    capture(&app_handle, "screen", display_id, None, options.unwrap_or_default()).await
+}
+
+/// 截取应用窗口（按窗口标签）所在的区域
+#[tauri::command]
+pub async fn capture_window(
+    app_handle: AppHandle,
+    label: String,
+    options: Option<CaptureOptions>,
+) -> Result<CaptureResult, String> {
+    let window = app_handle.get_window(&label).ok_or_else(|| format!("窗口不存在: {}", label))?;
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let position = window.outer_position().map_err(|e| e.to_string())?.to_logical::<i32>(scale);
+    let size = window.outer_size().map_err(|e| e.to_string())?.to_logical::<u32>(scale);
+    let region = CaptureRegion { x: position.x, y: position.y, width: size.width, height: size.height };
+    capture(&app_handle, "window", None, Some(region), options.unwrap_or_default()).await
+}
+
+/// 截取桌面区域
+#[tauri::command]
+pub async fn capture_region(
+    app_handle: AppHandle,
+    region: CaptureRegion,
+    options: Option<CaptureOptions>,
+) -> Result<CaptureResult, String> {
+    capture(&app_handle, "region", None, Some(region), options.unwrap_or_default()).await
+}
+
+/// 清空会话排队的截图附件，返回清除数量
+#[tauri::command]
+pub async fn clear_chat_attachments(session_id: Option<String>) -> Result<usize, String> {
+    Ok(take_chat_attachments(session_id.as_deref()).len())
+}
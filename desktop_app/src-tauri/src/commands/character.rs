@@ -7,13 +7,17 @@
 //! - Character configuration
 use tauri::{AppHandle, State, Manager};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use tracing::{info, error, warn};
 
 use crate::{
     commands::*,
     commands::live2d_assets,
+    commands::cache::{self, CachedCommand},
     state::AppState,
     utils::*,
+    utils::character_binding::{self, AppliedBinding, BindingSettings, CharacterBinding},
+    utils::event_journal::{self, EventKind},
 };
 
 fn fallback_characters() -> Vec<CharacterInfo> {
@@ -119,16 +123,37 @@ pub async fn get_characters(
     
     info!("✅ [get_characters] 数据库实例获取成功");
     
-    // Load characters from database
-    let characters_data = db.character_registry.get_all_characters_async().await
-        .map_err(|e| {
-            error!("❌ [get_characters] 获取角色列表失败: {}", e);
-            format!("获取角色列表失败: {}", e)
-        })?;
-    
-    info!("📊 [get_characters] 从数据库获取到 {} 个角色", characters_data.len());
+    // Load characters from database (cached, invalidated when characters are registered or switched)
+    let characters = cache::cached(CachedCommand::Characters, "", || async move {
+        let characters_data = db.character_registry.get_all_characters_async().await
+            .map_err(|e| {
+                error!("❌ [get_characters] 获取角色列表失败: {}", e);
+                format!("获取角色列表失败: {}", e)
+            })?;
+
+        info!("📊 [get_characters] 从数据库获取到 {} 个角色", characters_data.len());
+
+        // Convert to CharacterInfo
+        Ok(characters_data
+            .into_iter()
+            .map(|c| {
+                info!("  - 角色: {} ({}), path: {}, motions: {}, expressions: {}", 
+                    c.id, c.name, &c.path, c.motions.len(), c.expressions.len());
+                CharacterInfo {
+                    id: c.id,
+                    name: c.name,
+                    description: Some(c.description),
+                    preview_image: c.preview_image,
+                    model_path: c.path,
+                    motions: c.motions,
+                    expressions: c.expressions,
+                    is_active: c.is_active,
+                }
+            })
+            .collect::<Vec<CharacterInfo>>())
+    }).await?;
 
-    if characters_data.is_empty() {
+    if characters.is_empty() {
         warn!("⚠️ [get_characters] 数据库角色列表为空，返回默认角色列表兜底");
         return Ok(CommandResponse::success_with_message(
             fallback_characters(),
@@ -136,25 +161,6 @@ pub async fn get_characters(
         ));
     }
     
-    // Convert to CharacterInfo
-    let characters: Vec<CharacterInfo> = characters_data
-        .into_iter()
-        .map(|c| {
-            info!("  - 角色: {} ({}), path: {}, motions: {}, expressions: {}", 
-                c.id, c.name, &c.path, c.motions.len(), c.expressions.len());
-            CharacterInfo {
-                id: c.id,
-                name: c.name,
-                description: Some(c.description),
-                preview_image: c.preview_image,
-                model_path: c.path,
-                motions: c.motions,
-                expressions: c.expressions,
-                is_active: c.is_active,
-            }
-        })
-        .collect();
-    
     // Best-effort: ensure the active character's Live2D assets exist in cache.
     // This avoids a blank character when the active model isn't pre-downloaded at startup.
     if let Some(active) = characters.iter().find(|c| c.is_active) {
@@ -210,6 +216,7 @@ pub async fn get_character_info(
 #[tauri::command]
 pub async fn switch_character(
     character_id: String,
+    apply_binding: Option<bool>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<CharacterInfo>, String> {
@@ -246,6 +253,7 @@ pub async fn switch_character(
     // Set new active character in database
     db.character_registry.set_active_character_async(&character_id).await
         .map_err(|e| format!("设置激活角色失败: {}", e))?;
+    cache::invalidate(CachedCommand::Characters).await;
     
     // Update config
     let mut config = state.config.lock().clone();
@@ -258,6 +266,24 @@ pub async fn switch_character(
         return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
     }
     
+    // Apply the character's model / prompt / voice binding to the active session
+    let bindings = load_bindings().unwrap_or_else(|e| {
+        warn!("读取角色绑定失败: {}", e);
+        BindingSettings::default()
+    });
+    let applied = if bindings.should_apply(apply_binding) {
+        apply_character_binding(&state, &character_id, bindings.binding_for(&character_id)).await
+    } else {
+        info!("本次切换不应用角色绑定");
+        None
+    };
+    character_binding::activate(applied.clone());
+    
+    event_journal::record(EventKind::CharacterSwitched, serde_json::json!({
+        "from": old_character,
+        "to": character_id,
+    }));
+    
     // Build character info response
     let character_info = CharacterInfo {
         id: character_data.id.clone(),
@@ -276,9 +302,19 @@ pub async fn switch_character(
             "old_character": old_character,
             "new_character": character_id,
             "character_info": character_info,
+            "binding": applied,
         }));
     }
     
+    // 应用新角色的季节服装与表情
+    let seasonal_handle = app_handle.clone();
+    let seasonal_character = character_id.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::commands::seasonal::apply_seasonal_content(&seasonal_handle, &seasonal_character, true).await {
+            warn!("应用季节内容失败: {}", e);
+        }
+    });
+    
     info!("角色切换成功: {:?} -> {}", old_character, character_id);
     Ok(CommandResponse::success_with_message(
         character_info,
@@ -440,6 +476,144 @@ pub async fn set_character_scale(
     ))
 }
 
+// ================================
+// Character Bindings
+// ================================
+
+fn get_bindings_dir() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir)
+}
+
+fn load_bindings() -> Result<BindingSettings, String> {
+    character_binding::load(&get_bindings_dir()?)
+}
+
+/// Apply a character binding to the active chat session
+///
+/// Returns `None` when the binding is empty. A missing model config only skips the model part.
+async fn apply_character_binding(
+    state: &AppState,
+    character_id: &str,
+    binding: CharacterBinding,
+) -> Option<AppliedBinding> {
+    if binding.is_empty() {
+        return None;
+    }
+
+    let mut applied = AppliedBinding {
+        character_id: character_id.to_string(),
+        binding,
+        model_id: None,
+        adapter_id: None,
+    };
+
+    if let Some(config_id) = applied.binding.model_config_id.clone() {
+        let model_config = match crate::database::get_database() {
+            Some(db) => db.model_config_registry.get_config_async(&config_id).await.unwrap_or_else(|e| {
+                warn!("读取角色绑定的模型配置失败: {}", e);
+                None
+            }),
+            None => None,
+        };
+        match model_config {
+            Some(config) => {
+                state.chat.set_model_config(crate::state::ModelConfig {
+                    model_id: config.model_id.clone(),
+                    adapter_id: config.adapter_id.clone(),
+                    temperature: config.temperature,
+                    top_p: config.top_p,
+                    max_tokens: config.max_tokens,
+                });
+                applied.model_id = Some(config.model_id);
+                applied.adapter_id = config.adapter_id;
+            }
+            None => warn!("角色 {} 绑定的模型配置不存在: {}", character_id, config_id),
+        }
+    }
+
+    if let Some(mut session) = state.chat.get_current_session() {
+        session.character_id = Some(character_id.to_string());
+        if applied.model_id.is_some() {
+            session.model_id = applied.model_id.clone();
+        }
+        state.chat.set_current_session(session);
+    }
+
+    info!("已应用角色绑定: {} {:?}", character_id, applied.binding);
+    Some(applied)
+}
+
+/// Re-apply the active character's binding on startup
+pub async fn restore_character_binding(app_handle: &AppHandle) {
+    let bindings = match load_bindings() {
+        Ok(bindings) => bindings,
+        Err(e) => {
+            warn!("读取角色绑定失败: {}", e);
+            return;
+        }
+    };
+    if !bindings.apply_on_switch {
+        return;
+    }
+    let state = app_handle.state::<AppState>();
+    let character_id = state.config.lock().character.current_character.clone();
+    let applied = apply_character_binding(&state, &character_id, bindings.binding_for(&character_id)).await;
+    character_binding::activate(applied);
+}
+
+/// Get character bindings and the apply-on-switch toggle
+#[tauri::command]
+pub async fn get_character_bindings() -> Result<CommandResponse<BindingSettings>, String> {
+    Ok(CommandResponse::success(load_bindings()?))
+}
+
+/// Set (or clear, when empty) the binding of a character
+#[tauri::command]
+pub async fn set_character_binding(
+    character_id: String,
+    binding: CharacterBinding,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<CharacterBinding>, String> {
+    info!("设置角色绑定: {} {:?}", character_id, binding);
+
+    let dir = get_bindings_dir()?;
+    let mut bindings = character_binding::load(&dir)?;
+    bindings.set_binding(&character_id, binding.clone());
+    character_binding::save(&dir, &bindings)?;
+
+    // Editing the current character's binding takes effect immediately
+    let is_current = state.config.lock().character.current_character == character_id;
+    if is_current && bindings.apply_on_switch {
+        let applied = apply_character_binding(&state, &character_id, binding.clone()).await;
+        character_binding::activate(applied);
+    }
+
+    Ok(CommandResponse::success_with_message(
+        binding,
+        format!("角色 {} 的绑定已保存", character_id),
+    ))
+}
+
+/// Toggle whether switching characters applies their bindings
+#[tauri::command]
+pub async fn set_apply_binding_on_switch(
+    enabled: bool,
+) -> Result<CommandResponse<bool>, String> {
+    info!("切换角色时应用绑定: {}", enabled);
+
+    let dir = get_bindings_dir()?;
+    let mut bindings = character_binding::load(&dir)?;
+    bindings.apply_on_switch = enabled;
+    character_binding::save(&dir, &bindings)?;
+
+    Ok(CommandResponse::success(enabled))
+}
+
 // ================================
 // Command Metadata
 // ================================
@@ -565,6 +739,7 @@ pub async fn save_character_config(
     // Save to database
     db.character_registry.save_character_config_async(db_config).await
         .map_err(|e| format!("保存角色配置失败: {}", e))?;
+    crate::commands::emotion::invalidate_config(&config.character_id);
     
     info!("角色配置保存成功: {}", config.character_id);
     Ok(CommandResponse::success(()))
@@ -8,7 +8,6 @@ use tauri::AppHandle;
 use serde::{Deserialize, Serialize};
 use tracing::{info, error, warn};
 use std::collections::HashMap;
-use reqwest::Client;
 
 use crate::commands::*;
 
@@ -363,7 +362,7 @@ async fn register_adapter_internal(
     info!("注册{}适配器: {} -> {}", adapter_type, template.name, api_endpoint);
     
     // 创建HTTP客户端
-    let client = Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
@@ -423,8 +422,8 @@ fn get_backend_url() -> String {
 }
 
 /// 从存储中获取所有模板
-async fn get_templates_from_storage(app_handle: &AppHandle) -> Result<Vec<CharacterTemplateData>, String> {
-    let templates_dir = get_templates_directory(app_handle)?;
+async fn get_templates_from_storage() -> Result<Vec<CharacterTemplateData>, String> {
+    let templates_dir = get_templates_directory()?;
     
     if !templates_dir.exists() {
         std::fs::create_dir_all(&templates_dir).map_err(|e| {
@@ -452,9 +451,8 @@ async fn get_templates_from_storage(app_handle: &AppHandle) -> Result<Vec<Charac
 /// 保存模板到存储
 async fn save_template_to_storage(
     template: &CharacterTemplateData,
-    app_handle: &AppHandle,
 ) -> Result<(), String> {
-    let templates_dir = get_templates_directory(app_handle)?;
+    let templates_dir = get_templates_directory()?;
     std::fs::create_dir_all(&templates_dir).map_err(|e| {
         format!("创建模板目录失败: {}", e)
     })?;
@@ -491,9 +489,8 @@ async fn save_template_to_storage(
 /// 从存储中删除模板
 async fn delete_template_from_storage(
     template_id: &str,
-    app_handle: &AppHandle,
 ) -> Result<(), String> {
-    let templates_dir = get_templates_directory(app_handle)?;
+    let templates_dir = get_templates_directory()?;
     let index_file = templates_dir.join("templates_index.json");
     
     if !index_file.exists() {
@@ -522,10 +519,8 @@ async fn delete_template_from_storage(
 }
 
 /// 获取模板存储目录
-fn get_templates_directory(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
-    let app_data_dir = app_handle.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+fn get_templates_directory() -> Result<std::path::PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     Ok(app_data_dir.join("character_templates"))
 }
@@ -3,16 +3,33 @@
 //! 处理所有聊天相关的 Tauri 命令，与 Python API 服务器通信
 
 use crate::create_command;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Instant;
 use tracing::{info, warn};
 
 use crate::{commands::*, AppState, ZishuResult};
 use crate::utils::bridge::{
-    PythonApiBridge, ChatRequest, ChatMessage, MessageRole,
+    is_unreachable, PythonApiBridge, ChatRequest, ChatMessage, MessageRole,
 };
 use crate::commands::prompt;
+use crate::commands::language::{self, MessageLanguage};
+use crate::commands::accessibility::{self, AnnouncementKind, AnnouncementPriority};
+use crate::utils::security_audit::{log_audit_failure, log_audit_success, AuditEventType};
+use crate::database::message_content::{self, ContentBlock};
+use crate::utils::conversation_export;
+use crate::utils::character_binding;
+use crate::utils::cost_preview;
+use crate::utils::event_journal::{self, EventKind};
+use crate::state::ChatSession;
+use crate::commands::cost_preview::CostConfirmationRequired;
+use crate::commands::{achievements, capture, chat_outbox, emotion, incognito, llm_middleware, local_llm, session_memory, statistics, tts};
+use crate::commands::llm_middleware::LlmRequestContext;
+use crate::commands::session_parameters;
+use crate::utils::model_parameters::{EffectiveParameters, ParameterOverrides};
+use crate::database::achievement::METRIC_MESSAGES_SENT;
+use crate::database::pet_statistics::INTERACTION_MESSAGE;
 
 // ================================
 // 命令元数据
@@ -73,6 +90,97 @@ pub fn get_command_metadata() -> HashMap<String, CommandMetadata> {
         },
     );
     
+    metadata.insert(
+        "edit_message".to_string(),
+        CommandMetadata {
+            name: "edit_message".to_string(),
+            description: "编辑自己发送的消息（保留修订历史）".to_string(),
+            input_type: Some("EditMessageInput".to_string()),
+            output_type: Some("MessageChangeResponse".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "delete_message".to_string(),
+        CommandMetadata {
+            name: "delete_message".to_string(),
+            description: "删除自己发送的消息（软删除）".to_string(),
+            input_type: Some("DeleteMessageInput".to_string()),
+            output_type: Some("MessageChangeResponse".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "get_message_revisions".to_string(),
+        CommandMetadata {
+            name: "get_message_revisions".to_string(),
+            description: "获取消息修订历史".to_string(),
+            input_type: Some("GetMessageRevisionsInput".to_string()),
+            output_type: Some("Vec<MessageRevision>".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "post_structured_message".to_string(),
+        CommandMetadata {
+            name: "post_structured_message".to_string(),
+            description: "发送结构化消息（代码、表格、任务列表、手绘）".to_string(),
+            input_type: Some("PostStructuredMessageInput".to_string()),
+            output_type: Some("Message".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "export_conversation".to_string(),
+        CommandMetadata {
+            name: "export_conversation".to_string(),
+            description: "导出会话为 Markdown 或 JSON".to_string(),
+            input_type: Some("ExportConversationInput".to_string()),
+            output_type: Some("ExportConversationResponse".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "export_chat_session".to_string(),
+        CommandMetadata {
+            name: "export_chat_session".to_string(),
+            description: "导出聊天会话（Markdown 文稿或可无损导入的 JSON 存档，含附件）".to_string(),
+            input_type: Some("ExportChatSessionInput".to_string()),
+            output_type: Some("ExportChatSessionResponse".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "import_chat_session".to_string(),
+        CommandMetadata {
+            name: "import_chat_session".to_string(),
+            description: "从 Markdown 文稿或 JSON 存档导入聊天会话".to_string(),
+            input_type: Some("ImportChatSessionInput".to_string()),
+            output_type: Some("ImportChatSessionResponse".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "chat".to_string(),
+        },
+    );
+    
     metadata
 }
 
@@ -112,6 +220,9 @@ pub struct SendMessageInput {
     /// 上下文消息列表（可选）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_messages: Option<Vec<ContextMessage>>,
+    /// 已确认费用预估（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm_cost: Option<bool>,
 }
 
 /// 上下文消息
@@ -141,6 +252,15 @@ pub struct ChatResponse {
     /// 完成原因
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<String>,
+    /// 本地保存的用户消息 ID（用于后续编辑/删除）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_message_id: Option<String>,
+    /// 用户消息的语言（用于选择语音）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<MessageLanguage>,
+    /// 本次请求实际使用的模型参数及其来源
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<EffectiveParameters>,
 }
 
 /// Token 使用统计
@@ -216,16 +336,159 @@ pub struct SetModelResponse {
     pub adapter_id: Option<String>,
 }
 
+/// 编辑消息输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditMessageInput {
+    /// 消息 ID
+    pub message_id: String,
+    /// 新内容（提供 `blocks` 时忽略）
+    #[serde(default)]
+    pub content: String,
+    /// 新的结构化内容块
+    #[serde(default)]
+    pub blocks: Option<Vec<ContentBlock>>,
+}
+
+/// 删除消息输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteMessageInput {
+    /// 消息 ID
+    pub message_id: String,
+}
+
+/// 获取消息修订历史输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMessageRevisionsInput {
+    /// 消息 ID
+    pub message_id: String,
+}
+
+/// 发送结构化消息输入
+///
+/// 结构化消息（代码、表格、任务列表、白板手绘等）只保存到会话中，不触发模型回复。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostStructuredMessageInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 内容块
+    pub blocks: Vec<ContentBlock>,
+}
+
+/// 导出会话输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConversationInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 导出格式：`markdown`（默认）或 `json`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// 导出会话响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConversationResponse {
+    pub session_id: String,
+    pub format: String,
+    pub file_name: String,
+    pub content: String,
+    pub message_count: usize,
+}
+
+/// 导出聊天会话输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportChatSessionInput {
+    /// 会话 ID
+    pub session_id: String,
+    /// 导出格式：`markdown`（默认，便于阅读）或 `json`（可无损导入）
+    #[serde(default)]
+    pub format: Option<String>,
+    /// 会话使用的角色（默认取会话记录的角色）
+    #[serde(default)]
+    pub character_id: Option<String>,
+    /// 写入的文件路径；Markdown 的附件复制到同名的 `_files` 文件夹。为空时只返回内容
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// 导出聊天会话响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportChatSessionResponse {
+    pub session_id: String,
+    pub format: String,
+    pub file_name: String,
+    /// 导出内容（写入文件时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message_count: usize,
+    pub attachment_count: usize,
+}
+
+/// 导入聊天会话输入（`content` 与 `path` 二选一）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportChatSessionInput {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    /// `markdown` 或 `json`；为空时按扩展名或内容判断
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// 导入聊天会话响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportChatSessionResponse {
+    /// 导入后的会话 ID（与已有会话冲突时重新生成）
+    pub session_id: String,
+    pub title: String,
+    pub message_count: usize,
+    /// 恢复到文件管理的附件数
+    pub attachment_count: usize,
+    /// 跳过的附件等提示
+    pub warnings: Vec<String>,
+}
+
+/// 消息变更响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageChangeResponse {
+    pub message_id: String,
+    pub session_id: String,
+    /// 编辑后的修订号（删除时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<i32>,
+    pub changed_at: i64,
+    pub deleted: bool,
+}
+
 // ================================
 // 命令处理器实现
 // ================================
 
 /// 发送消息处理器
+///
+/// Python API 不可达时消息进入发件箱，返回 `QueuedMessage`，送达后通过 `chat-message-delivered` 通知。
 pub async fn send_message_handler(
     input: SendMessageInput,
     app: AppHandle,
 ) -> ZishuResult<serde_json::Value> {
-    log_command_execution("send_message", Some(&serde_json::to_string(&input).unwrap_or_default()));
+    deliver_message(input, app, true).await
+}
+
+/// 发送消息；`queue_offline` 为 false 时（发件箱重试）失败直接返回错误
+pub(crate) async fn deliver_message(
+    input: SendMessageInput,
+    app: AppHandle,
+    queue_offline: bool,
+) -> ZishuResult<serde_json::Value> {
+    // 无痕会话不记录消息内容
+    let incognito = incognito::is_incognito(input.session_id.as_deref());
+    if incognito {
+        log_command_execution("send_message", input.session_id.as_deref());
+    } else {
+        log_command_execution("send_message", Some(&serde_json::to_string(&input).unwrap_or_default()));
+    }
     
     // 验证输入
     if input.message.trim().is_empty() {
@@ -236,53 +499,46 @@ pub async fn send_message_handler(
         return Err("消息内容过长（最大 10000 字符）".to_string());
     }
     
+    if incognito {
+        incognito::ensure_active(input.session_id.as_deref().unwrap_or_default())?;
+    }
+    
+    // 后端不可达时放入发件箱的原始请求（发件箱会写入数据库，无痕会话不排队）
+    let outbox_input = (queue_offline && !incognito).then(|| input.clone());
+    
+    // 识别消息语言（会话覆盖优先）
+    let message_language = language::resolve_message_language(input.session_id.as_deref(), &input.message);
+    
     // 获取或创建 API 桥接客户端
     let bridge = PythonApiBridge::default().map_err(|e| {
         handle_command_error("send_message", &format!("创建 API 客户端失败: {}", e))
     })?;
     
-    // 构建消息列表
-    let mut messages = Vec::new();
+    // 未显式指定时使用当前角色绑定的模型与提示词
+    let binding = character_binding::active_for(input.character_id.as_deref());
+    let model = input.model.clone().or_else(|| binding.as_ref().and_then(|b| b.model_id.clone()));
+    let adapter = input.adapter.clone().or_else(|| binding.as_ref().and_then(|b| b.adapter_id.clone()));
+    let bound_prompt = binding.as_ref().and_then(|b| b.binding.prompt_id.clone());
     
-    // 检查是否使用本地LLM模型，如果是，添加Prompt作为系统消息
+    // 检查是否使用本地LLM模型（本地模型叠加Prompt，且不计费）
     // 支持多种模型ID格式：
     // 1. 以 "local_llm_" 开头的模型
     // 2. 等于 "local_llm" 的模型  
     // 3. 注册的本地LLM模型的adapter_id
-    let use_local_llm = if let Some(model_id) = input.model.as_ref() {
+    let use_local_llm = if let Some(model_id) = model.as_ref() {
         // 检查是否是本地LLM前缀格式
         if model_id.starts_with("local_llm_") || model_id == "local_llm" {
             true
         } else {
             // 检查是否是注册的本地LLM模型ID（从本地索引查询）
-            is_local_llm_model(model_id, &app).await.unwrap_or(false)
+            is_local_llm_model(model_id).await.unwrap_or(false)
         }
     } else {
         false
     };
     
-    if use_local_llm {
-        // 获取当前使用的Prompt
-        match get_current_prompt_internal(&app).await {
-            Ok(Some(prompt)) => {
-                // 将Prompt内容作为系统消息添加
-                messages.push(ChatMessage {
-                    role: MessageRole::System,
-                    content: format!("{}\n\n{}", 
-                        prompt.content,
-                        prompt.character_setting.as_ref().unwrap_or(&"".to_string())
-                    ),
-                });
-                info!("已应用Prompt: {}", prompt.name);
-            }
-            Ok(None) => {
-                warn!("未找到当前使用的Prompt，将使用默认行为");
-            }
-            Err(e) => {
-                warn!("获取Prompt失败: {}，将使用默认行为", e);
-            }
-        }
-    }
+    // 构建消息列表
+    let mut messages = Vec::new();
     
     // 添加上下文消息
     if let Some(context_messages) = input.context_messages {
@@ -308,31 +564,93 @@ pub async fn send_message_handler(
         content: input.message.clone(),
     });
     
-    // 构建请求
-    let request = ChatRequest {
-        messages,
-        model: input.model.clone(),
-        adapter: input.adapter.clone(),
+    // 经过中间件管道（提示词、RAG、语言、安全过滤、预算、日志）
+    let mut middleware_ctx = LlmRequestContext {
+        app: app.clone(),
+        session_id: input.session_id.clone(),
         character_id: input.character_id.clone(),
-        max_tokens: input.max_tokens,
+        model: model.clone(),
+        use_local_llm,
+        language: message_language.clone(),
+        bound_prompt,
+        user_message: input.message.clone(),
+        messages,
+        started_at: Instant::now(),
+        incognito,
+    };
+    llm_middleware::run_before_request(&mut middleware_ctx).await?;
+    
+    // 模型参数：会话覆盖 > 角色绑定 > 请求携带的全局配置
+    let request_parameters = ParameterOverrides {
         temperature: input.temperature,
         top_p: input.top_p,
+        max_tokens: input.max_tokens,
+    };
+    let parameters = session_parameters::resolve(
+        input.session_id.as_deref(),
+        input.character_id.as_deref(),
+        &request_parameters,
+    ).await;
+    
+    // 提示词较大时先返回费用预估，前端确认后带 confirm_cost 重新发送（本地模型不计费）
+    if !use_local_llm && input.confirm_cost != Some(true) {
+        let contents = middleware_ctx.messages.iter().map(|m| m.content.as_str());
+        if let Some(preview) = cost_preview::check(input.session_id.as_deref(), model.as_deref(), contents, Some(parameters.max_tokens)) {
+            info!(
+                "提示词约 {} tokens，超过阈值 {}，等待用户确认",
+                preview.estimated_prompt_tokens, preview.threshold_tokens
+            );
+            return Ok(serde_json::to_value(CostConfirmationRequired::new(input.session_id.clone(), preview, parameters)).unwrap());
+        }
+    }
+    
+    // 构建请求
+    let request = ChatRequest {
+        messages: std::mem::take(&mut middleware_ctx.messages),
+        model,
+        adapter,
+        character_id: input.character_id.clone(),
+        max_tokens: Some(parameters.max_tokens),
+        temperature: Some(parameters.temperature),
+        top_p: Some(parameters.top_p),
         stream: input.stream,
         session_id: input.session_id.clone(),
+        images: capture::take_chat_attachments(input.session_id.as_deref()),
     };
     
-    // 发送请求到 Python API
-    let response = bridge.send_chat_message(request).await.map_err(|e| {
-        handle_command_error("send_message", &format!("发送消息失败: {}", e))
-    })?;
+    // 发送请求到 Python API；本地推理已加载该模型时在进程内生成
+    let result = if use_local_llm && local_llm::serves(request.model.as_deref()) {
+        local_llm::chat_completion(&app, request).await.map_err(anyhow::Error::msg)
+    } else {
+        bridge.send_chat_message(request).await
+    };
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            if let Some(mut queued) = outbox_input.filter(|_| is_unreachable(&e)) {
+                // 费用预估已经确认（或无需确认），重试时不再询问
+                queued.confirm_cost = Some(true);
+                match chat_outbox::enqueue(&app, queued, &e.to_string()).await {
+                    Ok(queued) => return Ok(serde_json::to_value(queued).unwrap()),
+                    Err(queue_error) => warn!("消息加入发件箱失败: {}", queue_error),
+                }
+            }
+            let error_msg = handle_command_error("send_message", &format!("发送消息失败: {}", e));
+            accessibility::announce(&app, &error_msg, AnnouncementKind::Error, AnnouncementPriority::Assertive);
+            return Err(error_msg);
+        }
+    };
     
     // 解析响应
     let choice = response.choices.first().ok_or_else(|| {
         "响应中没有选择项".to_string()
     })?;
     
+    let mut reply = choice.message.content.clone();
+    llm_middleware::run_after_response(&middleware_ctx, &mut reply).await;
+    
     let chat_response = ChatResponse {
-        message: choice.message.content.clone(),
+        message: reply,
         session_id: response.session_id.clone().unwrap_or_else(|| "default".to_string()),
         message_id: response.id.clone(),
         model: response.model.clone(),
@@ -343,7 +661,33 @@ pub async fn send_message_handler(
             total_tokens: response.usage.total_tokens,
         }),
         finish_reason: choice.finish_reason.clone(),
+        user_message_id: None,
+        language: Some(message_language),
+        parameters: Some(parameters),
     };
+    let user_message_id = match input.session_id.as_deref().filter(|_| incognito) {
+        Some(session_id) => incognito::record_exchange(session_id, &input.message, &chat_response.message),
+        None => record_exchange(&input.message, &chat_response).await,
+    };
+    let chat_response = ChatResponse { user_message_id, ..chat_response };
+    track_session(&app, &chat_response, input.character_id.clone());
+    session_memory::maybe_summarize(&app, &chat_response.session_id);
+    achievements::track(&app, METRIC_MESSAGES_SENT, 1);
+    statistics::track_interaction(
+        INTERACTION_MESSAGE,
+        input.character_id.clone(),
+        Some(chat_response.model.clone()),
+        None,
+    );
+    
+    // 通知屏幕阅读器有新消息
+    accessibility::announce(&app, &chat_response.message, AnnouncementKind::NewMessage, AnnouncementPriority::Polite);
+    
+    // 开启自动朗读时让角色读出回复
+    tts::speak_reply(&app, &chat_response.message, chat_response.language.as_ref().map(|l| l.voice_locale.clone()));
+    
+    // 根据回复的情绪切换角色表情
+    emotion::on_reply(&app, input.character_id.clone(), choice.message.emotion.clone(), &chat_response.message);
     
     // 返回 JSON 响应
     Ok(serde_json::to_value(chat_response).unwrap())
@@ -417,6 +761,7 @@ pub async fn clear_chat_history_handler(
         message: response.message,
         session_id: response.session_id,
     };
+    event_journal::record(EventKind::SessionCleared, serde_json::json!({ "session_id": clear_response.session_id }));
     
     // 返回 JSON 响应
     Ok(serde_json::to_value(clear_response).unwrap())
@@ -494,6 +839,7 @@ pub async fn set_chat_model_handler(
     db.model_config_registry.save_config(model_config.clone()).map_err(|e| {
         handle_command_error("set_chat_model", &format!("保存模型配置失败: {}", e))
     })?;
+    crate::commands::cache::invalidate(crate::commands::cache::CachedCommand::ModelConfigs).await;
     
     // 更新应用状态中的模型配置
     let state_config = crate::state::ModelConfig {
@@ -517,6 +863,339 @@ pub async fn set_chat_model_handler(
     Ok(serde_json::to_value(response).unwrap())
 }
 
+/// 编辑消息处理器
+pub async fn edit_message_handler(
+    input: EditMessageInput,
+    app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    log_command_execution("edit_message", Some(&input.message_id));
+    
+    let plain_text = match &input.blocks {
+        Some(blocks) => {
+            message_content::validate_blocks(blocks)?;
+            message_content::blocks_to_plain_text(blocks)
+        }
+        None => input.content.clone(),
+    };
+    let content = plain_text.trim();
+    if content.is_empty() {
+        return Err("消息内容不能为空".to_string());
+    }
+    if input.blocks.is_none() && content.len() > 10000 {
+        return Err("消息内容过长（最大 10000 字符）".to_string());
+    }
+    
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let message = load_own_message(history, &input.message_id).await?;
+    
+    let now = chrono::Utc::now().timestamp();
+    let revision = match history.edit_message(&message.id, content, input.blocks.as_deref(), now).await {
+        Ok(revision) => revision,
+        Err(e) => {
+            let error = e.to_string();
+            log_audit_failure(AuditEventType::MessageEdit, "编辑消息", &error, Some(&message.id));
+            return Err(handle_command_error("edit_message", &format!("编辑消息失败: {}", error)));
+        }
+    };
+    log_audit_success(
+        AuditEventType::MessageEdit,
+        &format!("会话 {} 中的消息已编辑（修订 {}）", message.conversation_id, revision),
+        Some(&message.id),
+    );
+    
+    sync_message_artifacts(&app, &message.id, &message.conversation_id, Some(content), now).await;
+    
+    let response = MessageChangeResponse {
+        message_id: message.id,
+        session_id: message.conversation_id,
+        revision: Some(revision),
+        changed_at: now,
+        deleted: false,
+    };
+    let _ = app.emit_all("chat-message-changed", &response);
+    Ok(serde_json::to_value(response).unwrap())
+}
+
+/// 删除消息处理器（软删除）
+pub async fn delete_message_handler(
+    input: DeleteMessageInput,
+    app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    log_command_execution("delete_message", Some(&input.message_id));
+    
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let message = load_own_message(history, &input.message_id).await?;
+    
+    let now = chrono::Utc::now().timestamp();
+    if let Err(e) = history.soft_delete_message(&message.id, now).await {
+        let error = e.to_string();
+        log_audit_failure(AuditEventType::MessageDelete, "删除消息", &error, Some(&message.id));
+        return Err(handle_command_error("delete_message", &format!("删除消息失败: {}", error)));
+    }
+    log_audit_success(
+        AuditEventType::MessageDelete,
+        &format!("会话 {} 中的消息已删除", message.conversation_id),
+        Some(&message.id),
+    );
+    
+    sync_message_artifacts(&app, &message.id, &message.conversation_id, None, now).await;
+    
+    let response = MessageChangeResponse {
+        message_id: message.id,
+        session_id: message.conversation_id,
+        revision: None,
+        changed_at: now,
+        deleted: true,
+    };
+    let _ = app.emit_all("chat-message-changed", &response);
+    Ok(serde_json::to_value(response).unwrap())
+}
+
+/// 获取消息修订历史处理器
+pub async fn get_message_revisions_handler(
+    input: GetMessageRevisionsInput,
+    _app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    log_command_execution("get_message_revisions", Some(&input.message_id));
+    
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let revisions = db.conversation_history.get_message_revisions(&input.message_id).await.map_err(|e| {
+        handle_command_error("get_message_revisions", &format!("获取修订历史失败: {}", e))
+    })?;
+    
+    Ok(serde_json::to_value(revisions).unwrap())
+}
+
+/// 发送结构化消息处理器
+pub async fn post_structured_message_handler(
+    input: PostStructuredMessageInput,
+    app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    use crate::database::conversation::{Message, MessageRole as StoredRole};
+    
+    log_command_execution("post_structured_message", Some(&input.session_id));
+    
+    message_content::validate_blocks(&input.blocks)?;
+    let content = message_content::blocks_to_plain_text(&input.blocks);
+    
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let now = chrono::Utc::now().timestamp();
+    let title: String = content.chars().take(30).collect();
+    let language = crate::utils::language_detector::detect(&content).map(|d| d.language);
+    let message = Message {
+        id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: input.session_id.clone(),
+        role: StoredRole::User,
+        content,
+        created_at: now,
+        edited_at: None,
+        deleted_at: None,
+        payload: Some(input.blocks),
+        language,
+    };
+    
+    let result = async {
+        history.ensure_conversation(&message.conversation_id, &title, now).await?;
+        history.add_message(message.clone()).await
+    }.await;
+    if let Err(e) = result {
+        return Err(handle_command_error("post_structured_message", &format!("保存消息失败: {}", e)));
+    }
+    
+    sync_message_artifacts(&app, &message.id, &message.conversation_id, Some(&message.content), now).await;
+    let _ = app.emit_all("chat-message-added", &message);
+    achievements::track(&app, METRIC_MESSAGES_SENT, 1);
+    statistics::track_interaction(INTERACTION_MESSAGE, None, None, None);
+    Ok(serde_json::to_value(message).unwrap())
+}
+
+/// 导出会话处理器
+pub async fn export_conversation_handler(
+    input: ExportConversationInput,
+    _app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    log_command_execution("export_conversation", Some(&input.session_id));
+    
+    let format = input.format.as_deref().unwrap_or("markdown").to_lowercase();
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let conversation = history
+        .get_conversation(&input.session_id)
+        .await
+        .map_err(|e| handle_command_error("export_conversation", &format!("获取会话失败: {}", e)))?
+        .ok_or_else(|| format!("会话 {} 不存在", input.session_id))?;
+    let messages = history
+        .get_messages(&input.session_id)
+        .await
+        .map_err(|e| handle_command_error("export_conversation", &format!("获取会话消息失败: {}", e)))?;
+    
+    let (content, extension) = match format.as_str() {
+        "markdown" | "md" => (conversation_export::to_markdown(&conversation, &messages), "md"),
+        "json" => (conversation_export::to_json(&conversation, &messages)?, "json"),
+        other => return Err(format!("不支持的导出格式: {}", other)),
+    };
+    
+    let response = ExportConversationResponse {
+        session_id: conversation.id.clone(),
+        format: extension.to_string(),
+        file_name: format!("conversation-{}.{}", conversation.id, extension),
+        content,
+        message_count: messages.iter().filter(|m| !m.is_deleted()).count(),
+    };
+    Ok(serde_json::to_value(response).unwrap())
+}
+
+/// 导出聊天会话处理器
+pub async fn export_chat_session_handler(
+    input: ExportChatSessionInput,
+    app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    log_command_execution("export_chat_session", Some(&input.session_id));
+    
+    let format = input.format.as_deref().unwrap_or("markdown").to_lowercase();
+    let extension = match format.as_str() {
+        "markdown" | "md" => "md",
+        "json" => "json",
+        other => return Err(format!("不支持的导出格式: {}", other)),
+    };
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let conversation = history
+        .get_conversation(&input.session_id)
+        .await
+        .map_err(|e| handle_command_error("export_chat_session", &format!("获取会话失败: {}", e)))?
+        .ok_or_else(|| format!("会话 {} 不存在", input.session_id))?;
+    let messages = history
+        .get_messages(&input.session_id)
+        .await
+        .map_err(|e| handle_command_error("export_chat_session", &format!("获取会话消息失败: {}", e)))?;
+    
+    let character = session_character(&app, &input.session_id, input.character_id.as_deref()).await;
+    let target = input.path.as_deref().map(std::path::PathBuf::from);
+    let files = crate::commands::file::conversation_files(&app, &conversation.id)?;
+    let attachments = archive_attachments(&files, extension == "json", target.as_deref())?;
+    let archive = conversation_export::SessionArchive::new(conversation, messages, character, attachments);
+    
+    let content = if extension == "json" {
+        archive.to_json()?
+    } else {
+        conversation_export::transcript(&archive.conversation, &archive.messages, &archive.speakers(), &archive.attachments)
+    };
+    if let Some(target) = &target {
+        std::fs::write(target, &content)
+            .map_err(|e| handle_command_error("export_chat_session", &format!("写入导出文件失败: {}", e)))?;
+        info!("会话 {} 已导出到 {}", archive.conversation.id, target.display());
+    }
+    
+    let response = ExportChatSessionResponse {
+        session_id: archive.conversation.id.clone(),
+        format: extension.to_string(),
+        file_name: format!("conversation-{}.{}", archive.conversation.id, extension),
+        content: target.is_none().then_some(content),
+        path: input.path,
+        message_count: archive.messages.len(),
+        attachment_count: archive.attachments.len(),
+    };
+    Ok(serde_json::to_value(response).unwrap())
+}
+
+/// 导入聊天会话处理器
+pub async fn import_chat_session_handler(
+    input: ImportChatSessionInput,
+    app: AppHandle,
+) -> ZishuResult<serde_json::Value> {
+    use base64::Engine;
+    use crate::commands::file::{upload_file, UploadFileRequest};
+    
+    log_command_execution("import_chat_session", input.path.as_deref());
+    
+    let (content, extension) = match (input.content, &input.path) {
+        (Some(content), _) => (content, None),
+        (None, Some(path)) => {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| handle_command_error("import_chat_session", &format!("读取导入文件失败: {}", e)))?;
+            let extension = std::path::Path::new(path)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase());
+            (content, extension)
+        }
+        (None, None) => return Err("需要提供导入内容或文件路径".to_string()),
+    };
+    let format = input
+        .format
+        .map(|f| f.to_lowercase())
+        .or(extension)
+        .unwrap_or_else(|| {
+            let format = if content.trim_start().starts_with('{') { "json" } else { "markdown" };
+            format.to_string()
+        });
+    let mut archive = match format.as_str() {
+        "json" => conversation_export::SessionArchive::from_json(&content)?,
+        "markdown" | "md" => archive_from_transcript(conversation_export::parse_transcript(&content)?),
+        other => return Err(format!("不支持的导入格式: {}", other)),
+    };
+    
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let history = &db.conversation_history;
+    let exists = history
+        .get_conversation(&archive.conversation.id)
+        .await
+        .map_err(|e| handle_command_error("import_chat_session", &format!("获取会话失败: {}", e)))?
+        .is_some();
+    if exists || !validate_session_id(&archive.conversation.id) {
+        reassign_archive_ids(&mut archive);
+    }
+    history
+        .import_conversation(&archive.conversation, &archive.messages)
+        .await
+        .map_err(|e| handle_command_error("import_chat_session", &format!("保存会话失败: {}", e)))?;
+    
+    let mut warnings = Vec::new();
+    let mut attachment_count = 0;
+    for attachment in &archive.attachments {
+        let Some(data) = &attachment.data else {
+            warnings.push(format!("附件 {} 没有文件内容，已跳过", attachment.original_name));
+            continue;
+        };
+        let file_data = match base64::engine::general_purpose::STANDARD.decode(data) {
+            Ok(file_data) => file_data,
+            Err(e) => {
+                warnings.push(format!("附件 {} 内容无效: {}", attachment.original_name, e));
+                continue;
+            }
+        };
+        let request = UploadFileRequest {
+            file_name: attachment.original_name.clone(),
+            file_data,
+            conversation_id: Some(archive.conversation.id.clone()),
+            message_id: attachment.message_id.clone(),
+            tags: None,
+            description: None,
+        };
+        match upload_file(app.clone(), request).await {
+            Ok(_) => attachment_count += 1,
+            Err(e) => warnings.push(format!("恢复附件 {} 失败: {}", attachment.original_name, e)),
+        }
+    }
+    for warning in &warnings {
+        warn!("导入会话 {}: {}", archive.conversation.id, warning);
+    }
+    
+    let response = ImportChatSessionResponse {
+        session_id: archive.conversation.id.clone(),
+        title: archive.conversation.title.clone(),
+        message_count: archive.messages.len(),
+        attachment_count,
+        warnings,
+    };
+    info!("已导入会话 {}（{} 条消息）", response.session_id, response.message_count);
+    let _ = app.emit_all("chat-session-imported", &response);
+    Ok(serde_json::to_value(response).unwrap())
+}
+
 // ================================
 // 命令注册宏调用
 // ================================
@@ -533,18 +1212,308 @@ create_command!(clear_chat_history, ClearHistoryInput, clear_chat_history_handle
 // 设置聊天模型命令（需要 state）
 create_command!(set_chat_model, SetModelInput, set_chat_model_handler);
 
+// 编辑消息命令（不需要 state）
+create_command!(edit_message, EditMessageInput, edit_message_handler, no_state);
+
+// 删除消息命令（不需要 state）
+create_command!(delete_message, DeleteMessageInput, delete_message_handler, no_state);
+
+// 获取消息修订历史命令（不需要 state）
+create_command!(get_message_revisions, GetMessageRevisionsInput, get_message_revisions_handler, no_state);
+
+// 发送结构化消息命令（不需要 state）
+create_command!(post_structured_message, PostStructuredMessageInput, post_structured_message_handler, no_state);
+
+// 导出会话命令（不需要 state）
+create_command!(export_conversation, ExportConversationInput, export_conversation_handler, no_state);
+
+// 导出聊天会话命令（不需要 state）
+create_command!(export_chat_session, ExportChatSessionInput, export_chat_session_handler, no_state);
+
+// 导入聊天会话命令（不需要 state）
+create_command!(import_chat_session, ImportChatSessionInput, import_chat_session_handler, no_state);
+
 // ================================
 // 辅助函数
 // ================================
 
+/// 记录会话活动；本次运行中首次出现的会话记为开始并写入事件日志
+fn track_session(app: &AppHandle, response: &ChatResponse, character_id: Option<String>) {
+    let state = app.state::<AppState>();
+    let chat = &state.chat;
+    if chat.get_session(&response.session_id).is_some() {
+        chat.update_session_activity(&response.session_id);
+        return;
+    }
+    
+    let now = chrono::Utc::now().timestamp();
+    let incognito = incognito::is_incognito(Some(&response.session_id));
+    chat.set_current_session(ChatSession {
+        session_id: response.session_id.clone(),
+        created_at: now,
+        last_activity: now,
+        message_count: 1,
+        model_id: Some(response.model.clone()),
+        character_id: character_id.clone(),
+        incognito,
+    });
+    if incognito {
+        return;
+    }
+    event_journal::record(EventKind::SessionStarted, serde_json::json!({
+        "session_id": response.session_id,
+        "character_id": character_id,
+        "model": response.model,
+        "parameters": response.parameters,
+    }));
+}
+
+/// 将一轮对话保存到本地消息库（尽力而为），返回用户消息 ID
+async fn record_exchange(user_message: &str, response: &ChatResponse) -> Option<String> {
+    use crate::database::conversation::{Message, MessageRole as StoredRole};
+    
+    let db = crate::database::get_database()?;
+    let history = &db.conversation_history;
+    let now = chrono::Utc::now().timestamp();
+    let title: String = user_message.chars().take(30).collect();
+    let user_message_id = uuid::Uuid::new_v4().to_string();
+    
+    let result = async {
+        history.ensure_conversation(&response.session_id, &title, now).await?;
+        history.add_message(Message {
+            id: user_message_id.clone(),
+            conversation_id: response.session_id.clone(),
+            role: StoredRole::User,
+            content: user_message.to_string(),
+            created_at: now,
+            edited_at: None,
+            deleted_at: None,
+            payload: None,
+            language: response
+                .language
+                .as_ref()
+                .and_then(|l| l.detected.as_ref())
+                .map(|d| d.language.clone()),
+        }).await?;
+        history.add_message(Message {
+            id: response.message_id.clone(),
+            conversation_id: response.session_id.clone(),
+            role: StoredRole::Assistant,
+            content: response.message.clone(),
+            created_at: now,
+            edited_at: None,
+            deleted_at: None,
+            payload: None,
+            language: None,
+        }).await
+    }.await;
+    
+    match result {
+        Ok(()) => Some(user_message_id),
+        Err(e) => {
+            warn!("保存聊天记录到本地失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 加载可由用户修改的消息：必须存在、未删除且由用户发送
+async fn load_own_message(
+    history: &crate::database::conversation::ConversationHistory,
+    message_id: &str,
+) -> Result<crate::database::conversation::Message, String> {
+    use crate::database::conversation::MessageRole as StoredRole;
+    
+    let message = history
+        .get_message(message_id)
+        .await
+        .map_err(|e| format!("获取消息失败: {}", e))?
+        .ok_or_else(|| format!("消息 {} 不存在", message_id))?;
+    
+    if message.is_deleted() {
+        return Err("消息已删除".to_string());
+    }
+    if message.role != StoredRole::User {
+        return Err("只能修改自己发送的消息".to_string());
+    }
+    Ok(message)
+}
+
+/// 更新或失效消息的下游数据：向量索引中的嵌入与会话摘要
+///
+/// `content` 为 `None` 表示消息已删除。
+async fn sync_message_artifacts(
+    app: &AppHandle,
+    message_id: &str,
+    session_id: &str,
+    content: Option<&str>,
+    timestamp: i64,
+) {
+    use crate::database::vector_search_service::{ConversationMessage, VectorSearchService};
+    
+    if let Some(qdrant) = crate::database::get_database_manager().and_then(|m| m.qdrant()) {
+        let service = VectorSearchService::new(qdrant);
+        let result = match content {
+            Some(content) => match service.embed_one("conversations", content).await {
+                Ok(vector) => {
+                    let payload = ConversationMessage {
+                        message_id: message_id.to_string(),
+                        user_id: "local".to_string(),
+                        session_id: session_id.to_string(),
+                        content: content.to_string(),
+                        message_type: "user".to_string(),
+                        timestamp,
+                        metadata: Some(serde_json::json!({ "edited": true })),
+                    };
+                    service.store_conversation_message(message_id, vector, &payload).await
+                }
+                Err(e) => Err(e),
+            },
+            None => service.delete_conversation_message(message_id).await,
+        };
+        if let Err(e) = result {
+            warn!("同步消息 {} 的向量索引失败: {}", message_id, e);
+        }
+    }
+    
+    // 摘要基于原始消息生成，任何编辑/删除都需要重新生成
+    let _ = app.emit_all("chat-summary-invalidated", serde_json::json!({
+        "session_id": session_id,
+        "message_id": message_id,
+    }));
+}
+
+/// 会话使用的角色：显式指定，或本次运行中会话记录的角色
+async fn session_character(
+    app: &AppHandle,
+    session_id: &str,
+    character_id: Option<&str>,
+) -> Option<conversation_export::ArchivedCharacter> {
+    let character_id = match character_id {
+        Some(id) => id.to_string(),
+        None => app.state::<AppState>().chat.get_session(session_id)?.character_id?,
+    };
+    let character = match crate::database::get_database() {
+        Some(db) => db.character_registry.get_character_async(&character_id).await.ok().flatten(),
+        None => None,
+    };
+    let name = character
+        .map(|c| if c.display_name.is_empty() { c.name } else { c.display_name })
+        .unwrap_or_else(|| character_id.clone());
+    Some(conversation_export::ArchivedCharacter { id: character_id, name })
+}
+
+/// 把会话中的文件整理为导出附件
+///
+/// JSON 存档内嵌文件内容；导出 Markdown 到文件时，附件复制到文稿旁的 `<文件名>_files` 文件夹并以相对路径链接。
+/// 读取失败的文件只保留元数据。
+fn archive_attachments(
+    files: &[crate::database::file::FileInfo],
+    embed: bool,
+    target: Option<&std::path::Path>,
+) -> Result<Vec<conversation_export::ArchivedAttachment>, String> {
+    use base64::Engine;
+    
+    let folder = match target {
+        Some(target) if !embed && !files.is_empty() => {
+            let stem = target.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let name = format!("{}_files", stem);
+            let dir = target.with_file_name(&name);
+            std::fs::create_dir_all(&dir).map_err(|e| format!("创建附件文件夹失败: {}", e))?;
+            Some((name, dir))
+        }
+        _ => None,
+    };
+    
+    let mut used_names = std::collections::HashSet::new();
+    let mut attachments = Vec::with_capacity(files.len());
+    for file in files {
+        let mut attachment = conversation_export::ArchivedAttachment {
+            file_id: file.id.clone(),
+            message_id: file.message_id.clone(),
+            original_name: file.original_name.clone(),
+            mime_type: file.mime_type.clone(),
+            size: file.file_size,
+            data: None,
+            link: None,
+        };
+        if embed {
+            match std::fs::read(&file.file_path) {
+                Ok(bytes) => attachment.data = Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+                Err(e) => warn!("读取附件 {} 失败，只导出元数据: {}", file.original_name, e),
+            }
+        } else if let Some((folder_name, dir)) = &folder {
+            // 同名附件加上序号
+            let mut name = file.original_name.clone();
+            let mut index = 1;
+            while !used_names.insert(name.clone()) {
+                index += 1;
+                name = format!("{}-{}", index, file.original_name);
+            }
+            match std::fs::copy(&file.file_path, dir.join(&name)) {
+                Ok(_) => attachment.link = Some(format!("{}/{}", folder_name, name)),
+                Err(e) => warn!("复制附件 {} 失败: {}", file.original_name, e),
+            }
+        }
+        attachments.push(attachment);
+    }
+    Ok(attachments)
+}
+
+/// 由 Markdown 文稿构造存档（只有文本内容，没有附件）
+fn archive_from_transcript(transcript: conversation_export::Transcript) -> conversation_export::SessionArchive {
+    use crate::database::conversation::{Conversation, Message};
+    
+    let session_id = generate_session_id();
+    let now = chrono::Utc::now().timestamp();
+    let first = transcript.messages.first().map(|m| m.created_at).unwrap_or(now);
+    let last = transcript.messages.last().map(|m| m.created_at).unwrap_or(now);
+    let messages = transcript
+        .messages
+        .into_iter()
+        .map(|m| Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: session_id.clone(),
+            role: m.role,
+            content: m.content,
+            created_at: m.created_at,
+            // 文稿中只有编辑标记，没有编辑时间
+            edited_at: m.edited.then_some(m.created_at),
+            deleted_at: None,
+            payload: None,
+            language: None,
+        })
+        .collect();
+    let conversation = Conversation {
+        id: session_id,
+        title: transcript.title,
+        created_at: transcript.created_at.unwrap_or(first),
+        updated_at: last,
+    };
+    conversation_export::SessionArchive::new(conversation, messages, None, Vec::new())
+}
+
+/// 以新会话导入：重新生成会话与消息 ID，附件随消息 ID 更新
+fn reassign_archive_ids(archive: &mut conversation_export::SessionArchive) {
+    let session_id = generate_session_id();
+    let mut ids = HashMap::new();
+    for message in &mut archive.messages {
+        let id = uuid::Uuid::new_v4().to_string();
+        ids.insert(std::mem::replace(&mut message.id, id.clone()), id);
+        message.conversation_id = session_id.clone();
+    }
+    for attachment in &mut archive.attachments {
+        attachment.message_id = attachment.message_id.as_ref().and_then(|id| ids.get(id).cloned());
+    }
+    archive.conversation.id = session_id;
+}
+
 /// 检查模型ID是否是本地LLM模型
-async fn is_local_llm_model(model_id: &str, app: &AppHandle) -> Result<bool, String> {
+async fn is_local_llm_model(model_id: &str) -> Result<bool, String> {
     use crate::commands::local_llm::LocalLLMModel;
     
     // 读取本地LLM模型索引
-    let models_dir = app.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?
+    let models_dir = crate::utils::config::get_app_data_dir()?
         .join("local_llm_models");
     
     let index_file = models_dir.join("models_index.json");
@@ -565,15 +1534,19 @@ async fn is_local_llm_model(model_id: &str, app: &AppHandle) -> Result<bool, Str
 }
 
 /// 获取当前使用的Prompt（内部函数）
-async fn get_current_prompt_internal(app: &AppHandle) -> Result<Option<prompt::Prompt>, String> {
+///
+/// 指定语言时优先使用 `metadata.language` 与之匹配的Prompt（默认Prompt优先），
+/// 没有匹配时回退到默认Prompt。角色绑定了Prompt且该Prompt启用时优先使用。
+pub(crate) async fn get_current_prompt_internal(
+    language: Option<&str>,
+    bound_prompt: Option<&str>,
+) -> Result<Option<prompt::Prompt>, String> {
     use tauri::State;
     use crate::state::AppState;
     
     // 这里我们需要访问AppState，但由于我们在命令处理器中，可以直接调用命令
     // 为了简化，我们直接读取存储
-    let prompts_dir = app.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?
+    let prompts_dir = crate::utils::config::get_app_data_dir()?
         .join("prompts");
     
     let index_file = prompts_dir.join("prompts_index.json");
@@ -589,7 +1562,19 @@ async fn get_current_prompt_internal(app: &AppHandle) -> Result<Option<prompt::P
         format!("解析Prompt索引失败: {}", e)
     })?;
     
-    Ok(prompts.into_iter().find(|p| p.is_default && p.is_enabled))
+    let matches_language = |p: &prompt::Prompt| {
+        language.is_some() && p.metadata.get("language").and_then(|v| v.as_str()) == language
+    };
+    let mut enabled: Vec<prompt::Prompt> = prompts.into_iter().filter(|p| p.is_enabled).collect();
+    if let Some(bound) = enabled.iter().position(|p| Some(p.id.as_str()) == bound_prompt) {
+        return Ok(Some(enabled.swap_remove(bound)));
+    }
+    enabled.sort_by_key(|p| (!matches_language(p), !p.is_default));
+    
+    Ok(enabled
+        .into_iter()
+        .next()
+        .filter(|p| p.is_default || matches_language(p)))
 }
 
 /// 生成会话 ID
@@ -678,6 +1663,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act
@@ -709,6 +1695,7 @@ mod tests {
             top_p: Some(0.9),
             stream: Some(false),
             context_messages: Some(context_messages),
+            confirm_cost: None,
         };
         
         // Act
@@ -741,6 +1728,9 @@ mod tests {
             processing_time: Some(1.5),
             usage: Some(usage),
             finish_reason: Some("stop".to_string()),
+            user_message_id: None,
+            language: None,
+            parameters: None,
         };
         
         // Act
@@ -813,6 +1803,8 @@ mod tests {
         assert!(metadata.contains_key("get_chat_history"));
         assert!(metadata.contains_key("clear_chat_history"));
         assert!(metadata.contains_key("set_chat_model"));
+        assert!(metadata.contains_key("edit_message"));
+        assert!(metadata.contains_key("delete_message"));
         
         // 验证send_message元数据
         let send_msg_meta = &metadata["send_message"];
@@ -840,6 +1832,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act & Assert - 空消息应该在handler中被拒绝
@@ -862,6 +1855,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act & Assert
@@ -884,6 +1878,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act
@@ -928,6 +1923,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: Some(context_messages.clone()),
+            confirm_cost: None,
         };
         
         // Act
@@ -960,6 +1956,7 @@ mod tests {
             top_p: Some(0.0), // 最小top_p
             stream: Some(false),
             context_messages: Some(vec![]), // 空上下文列表
+            confirm_cost: None,
         };
         
         // Act
@@ -987,6 +1984,7 @@ mod tests {
             top_p: Some(1.0), // 最大top_p
             stream: Some(true),
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act
@@ -1038,6 +2036,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: None,
+            confirm_cost: None,
         };
         
         // Act
@@ -1074,6 +2073,7 @@ mod tests {
             top_p: None,
             stream: None,
             context_messages: Some(context_messages),
+            confirm_cost: None,
         };
         
         // Act
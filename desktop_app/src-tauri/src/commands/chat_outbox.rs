@@ -0,0 +1,239 @@
+//! # 聊天发件箱命令模块
+//!
+//! Python API 不可达时，`send_message` 把消息写入发件箱（`chat_outbox` 表）并立即返回 [`QueuedMessage`]。
+//! 后台任务按指数退避重试到期的消息：
+//!
+//! - 送达：发送 `chat-message-delivered`，附带与 `send_message` 相同的回复
+//! - 失败：发送 `chat-message-failed`，`will_retry` 为 false 表示已放弃，可通过 `retry_outbox_message` 手动重试
+//!
+//! 未送达的消息同时记录在 `ChatState` 中，便于界面显示会话的发送状态。
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use crate::commands::chat::{self, SendMessageInput};
+use crate::database::chat_outbox::{OutboxEntry, OutboxStatus};
+use crate::utils::bridge::PythonApiBridge;
+use crate::utils::watchdog;
+use crate::AppState;
+
+/// 检查到期消息的间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+const FLUSH_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// 每次最多处理的消息数
+const FLUSH_BATCH: i64 = 20;
+/// 已送达消息的保留时间（秒）
+const DELIVERED_RETENTION_SECS: i64 = 7 * 24 * 3600;
+
+/// 消息进入发件箱时 `send_message` 的返回值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    /// 恒为 true，便于前端与正常回复区分
+    pub queued: bool,
+    pub outbox_id: String,
+    pub session_id: Option<String>,
+    pub next_attempt_at: i64,
+}
+
+/// 把消息放入发件箱
+pub async fn enqueue(app_handle: &AppHandle, input: SendMessageInput, error: &str) -> Result<QueuedMessage, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let payload = serde_json::to_value(&input).map_err(|e| format!("序列化消息失败: {}", e))?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let entry = db
+        .chat_outbox
+        .enqueue(&id, input.session_id.as_deref(), &payload, error, Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("写入发件箱失败: {}", e))?;
+
+    app_handle.state::<AppState>().chat.track_outbox(&entry.id, entry.session_id.clone());
+    info!("后端不可达，消息已加入发件箱: {}", entry.id);
+    let queued = QueuedMessage {
+        queued: true,
+        outbox_id: entry.id,
+        session_id: entry.session_id,
+        next_attempt_at: entry.next_attempt_at,
+    };
+    let _ = app_handle.emit_all("chat-message-queued", &queued);
+    Ok(queued)
+}
+
+fn emit_failed(app_handle: &AppHandle, entry: &OutboxEntry) {
+    let _ = app_handle.emit_all("chat-message-failed", serde_json::json!({
+        "outbox_id": entry.id,
+        "session_id": entry.session_id,
+        "error": entry.last_error,
+        "attempts": entry.attempts,
+        "will_retry": entry.status == OutboxStatus::Pending,
+        "next_attempt_at": entry.next_attempt_at,
+    }));
+}
+
+async fn record_failure(app_handle: &AppHandle, entry: &OutboxEntry, error: &str, permanent: bool) {
+    let Some(db) = crate::database::get_database() else {
+        return;
+    };
+    match db.chat_outbox.record_failure(&entry.id, error, Utc::now().timestamp(), permanent).await {
+        Ok(Some(updated)) => {
+            if updated.status == OutboxStatus::Failed {
+                warn!("发件箱消息 {} 发送失败，已放弃: {}", updated.id, error);
+            }
+            emit_failed(app_handle, &updated);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("更新发件箱消息 {} 失败: {}", entry.id, e),
+    }
+}
+
+/// 重新发送一条消息
+async fn deliver(app_handle: &AppHandle, entry: &OutboxEntry) -> bool {
+    let input: SendMessageInput = match serde_json::from_value(entry.payload.clone()) {
+        Ok(input) => input,
+        Err(e) => {
+            record_failure(app_handle, entry, &format!("消息内容无效: {}", e), true).await;
+            return false;
+        }
+    };
+
+    match chat::deliver_message(input, app_handle.clone(), false).await {
+        Ok(response) => {
+            if let Some(db) = crate::database::get_database() {
+                if let Err(e) = db.chat_outbox.mark_delivered(&entry.id, Utc::now().timestamp()).await {
+                    warn!("更新发件箱消息 {} 失败: {}", entry.id, e);
+                }
+            }
+            app_handle.state::<AppState>().chat.untrack_outbox(&entry.id);
+            info!("发件箱消息已送达: {}", entry.id);
+            let _ = app_handle.emit_all("chat-message-delivered", serde_json::json!({
+                "outbox_id": entry.id,
+                "session_id": entry.session_id,
+                "response": response,
+            }));
+            true
+        }
+        Err(e) => {
+            record_failure(app_handle, entry, &e, false).await;
+            false
+        }
+    }
+}
+
+/// 处理到期的消息；后端仍不可达时全部记为一次失败
+///
+/// 同一会话中较早的消息失败后，较晚的消息放回等待，保证按顺序送达。
+async fn flush(app_handle: &AppHandle) -> Result<(), String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let entries = db
+        .chat_outbox
+        .claim_due(Utc::now().timestamp(), FLUSH_BATCH)
+        .await
+        .map_err(|e| format!("读取发件箱失败: {}", e))?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let online = match PythonApiBridge::default() {
+        Ok(bridge) => bridge.health_check().await.unwrap_or(false),
+        Err(_) => false,
+    };
+    let mut blocked_sessions = HashSet::new();
+    for entry in &entries {
+        if !online {
+            record_failure(app_handle, entry, "后端不可达", false).await;
+            continue;
+        }
+        if blocked_sessions.contains(&entry.session_id) {
+            if let Err(e) = db.chat_outbox.release(&entry.id, Utc::now().timestamp()).await {
+                warn!("更新发件箱消息 {} 失败: {}", entry.id, e);
+            }
+            continue;
+        }
+        if !deliver(app_handle, entry).await {
+            blocked_sessions.insert(entry.session_id.clone());
+        }
+    }
+    Ok(())
+}
+
+/// 恢复发件箱状态（启动时调用）：中断的发送恢复为等待，未送达的消息记入 `ChatState`
+pub async fn initialize_chat_outbox(app_handle: &AppHandle) {
+    let Some(db) = crate::database::get_database() else {
+        return;
+    };
+    let now = Utc::now().timestamp();
+    if let Err(e) = db.chat_outbox.reset_sending(now).await {
+        warn!("恢复发件箱失败: {}", e);
+        return;
+    }
+    if let Err(e) = db.chat_outbox.purge_delivered(now - DELIVERED_RETENTION_SECS).await {
+        warn!("清理发件箱失败: {}", e);
+    }
+    match db.chat_outbox.undelivered(None).await {
+        Ok(entries) => {
+            let state = app_handle.state::<AppState>();
+            for entry in &entries {
+                state.chat.track_outbox(&entry.id, entry.session_id.clone());
+            }
+            if !entries.is_empty() {
+                info!("发件箱中有 {} 条未送达的消息", entries.len());
+            }
+        }
+        Err(e) => warn!("读取发件箱失败: {}", e),
+    }
+}
+
+/// 启动发件箱重试任务
+pub fn start_chat_outbox_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("chat_outbox", FLUSH_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tokio::time::sleep(FLUSH_INTERVAL).await;
+                if let Err(e) = flush(&app_handle).await {
+                    debug!("发件箱未处理: {}", e);
+                }
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 列出未送达的消息（可按会话过滤）
+#[tauri::command]
+pub async fn list_outbox_messages(session_id: Option<String>) -> Result<Vec<OutboxEntry>, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.chat_outbox
+        .undelivered(session_id.as_deref())
+        .await
+        .map_err(|e| format!("读取发件箱失败: {}", e))
+}
+
+/// 立即重试一条消息（包括已放弃的消息）
+#[tauri::command]
+pub async fn retry_outbox_message(id: String) -> Result<bool, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.chat_outbox
+        .requeue(&id, Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("更新发件箱失败: {}", e))
+}
+
+/// 取消一条未送达的消息
+#[tauri::command]
+pub async fn cancel_outbox_message(app_handle: AppHandle, id: String) -> Result<bool, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let removed = db
+        .chat_outbox
+        .remove(&id)
+        .await
+        .map_err(|e| format!("更新发件箱失败: {}", e))?;
+    app_handle.state::<AppState>().chat.untrack_outbox(&id);
+    Ok(removed)
+}
@@ -0,0 +1,270 @@
+//! # 剪贴板历史命令模块
+//!
+//! 用户开启后，后台定时检查剪贴板文本的变化并记入历史，`copy_to_clipboard` 写入的文本也会记录。
+//! 记录与查看历史都需要剪贴板权限（`SystemClipboard`）。含敏感信息的条目用钥匙串中的
+//! 独立密钥加密，历史按资料保存在 `clipboard_history.json`。新记录通过 `clipboard-history-updated`
+//! 事件推送。
+//!
+//! 历史的去重、固定与搜索见 [`crate::utils::clipboard_history`]。
+
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, ClipboardManager, Manager};
+use tracing::{debug, warn};
+
+use crate::utils::clipboard_history::{self, ClipboardHistory, ClipboardItem, ClipboardSettings};
+use crate::utils::encryption::{self, EncryptionManager};
+use crate::utils::permission_checker::SystemChecker;
+
+/// 钥匙串中加密敏感条目的密钥
+const HISTORY_KEY_ENTRY: &str = "clipboard_history_key";
+const HISTORY_FILE: &str = "clipboard_history.json";
+const DEFAULT_LIMIT: usize = 50;
+
+lazy_static! {
+    static ref SETTINGS: RwLock<ClipboardSettings> = RwLock::new(ClipboardSettings::default());
+    /// 首次使用时从磁盘加载
+    static ref HISTORY: Mutex<Option<ClipboardHistory>> = Mutex::new(None);
+    /// 最近一次看到的剪贴板内容哈希，避免重复记录
+    static ref LAST_SEEN: Mutex<Option<String>> = Mutex::new(None);
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("clipboard_settings.json"))
+}
+
+/// 加载剪贴板历史设置（启动时调用）
+pub fn initialize_clipboard_history() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read clipboard settings: {}", e))?;
+    let settings: ClipboardSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse clipboard settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn ensure_permission() -> Result<(), String> {
+    SystemChecker::check_clipboard("feature", "clipboard_history")
+        .map_err(|e| format!("剪贴板历史需要剪贴板权限: {}", e))
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(crate::utils::get_profile_data_dir()?.join(HISTORY_FILE))
+}
+
+fn history_encryption(create: bool) -> Result<Option<EncryptionManager>, String> {
+    encryption::keyring_encryption(HISTORY_KEY_ENTRY, create)
+}
+
+fn load_history() -> Result<ClipboardHistory, String> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(ClipboardHistory::default());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("读取剪贴板历史失败: {}", e))?;
+    let mut history: ClipboardHistory =
+        serde_json::from_str(&json).map_err(|e| format!("解析剪贴板历史失败: {}", e))?;
+
+    // 密钥丢失后敏感条目无法解密，直接丢弃
+    if history.entries().iter().any(|entry| entry.sensitive) && history_encryption(false)?.is_none() {
+        warn!("剪贴板历史密钥不存在，丢弃加密的条目");
+        history.remove_sensitive();
+    }
+    Ok(history)
+}
+
+fn save_history(history: &ClipboardHistory) -> Result<(), String> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string(history).map_err(|e| format!("序列化剪贴板历史失败: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("保存剪贴板历史失败: {}", e))
+}
+
+/// 在已加载的历史上操作，`save` 为真时写回磁盘
+fn with_history<T>(save: bool, f: impl FnOnce(&mut ClipboardHistory) -> Result<T, String>) -> Result<T, String> {
+    let mut guard = HISTORY.lock();
+    if guard.is_none() {
+        *guard = Some(load_history()?);
+    }
+    let history = guard.as_mut().expect("history loaded");
+    let result = f(history)?;
+    if save {
+        save_history(history)?;
+    }
+    Ok(result)
+}
+
+/// 记录一段复制的文本（未开启剪贴板历史或没有权限时忽略）
+pub fn record_text(app_handle: &AppHandle, text: &str) {
+    let settings = SETTINGS.read().clone();
+    if !settings.enabled {
+        return;
+    }
+    *LAST_SEEN.lock() = Some(clipboard_history::content_hash(text));
+    if let Err(e) = ensure_permission() {
+        debug!("跳过剪贴板记录: {}", e);
+        return;
+    }
+
+    let now = crate::utils::clock::now().timestamp_millis();
+    let recorded = with_history(true, |history| {
+        let entry = history.record(text, &settings, now, |plaintext| {
+            let encryption = history_encryption(true)?.ok_or("剪贴板历史密钥不可用")?;
+            encryption
+                .encrypt_string(plaintext)
+                .map_err(|e| format!("加密剪贴板内容失败: {}", e))
+        })?;
+        Ok(entry.map(ClipboardItem::from))
+    });
+    match recorded {
+        Ok(Some(item)) => {
+            let _ = app_handle.emit_all("clipboard-history-updated", &item);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("记录剪贴板历史失败: {}", e),
+    }
+}
+
+/// 后台检查剪贴板变化（启动时调用）
+pub fn start_clipboard_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = SETTINGS.read().clone();
+            tokio::time::sleep(Duration::from_millis(settings.poll_interval_ms)).await;
+            if !settings.enabled {
+                continue;
+            }
+
+            let text = match app_handle.clipboard_manager().read_text() {
+                Ok(Some(text)) => text,
+                Ok(None) => continue,
+                Err(e) => {
+                    debug!("读取剪贴板失败: {}", e);
+                    continue;
+                }
+            };
+            let hash = clipboard_history::content_hash(&text);
+            {
+                let mut last_seen = LAST_SEEN.lock();
+                match last_seen.as_ref() {
+                    Some(last) if *last == hash => continue,
+                    // 刚开启时剪贴板里已有的内容不记录
+                    None => {
+                        *last_seen = Some(hash);
+                        continue;
+                    }
+                    Some(_) => {}
+                }
+            }
+            record_text(&app_handle, &text);
+        }
+    });
+}
+
+/// 查询剪贴板历史，固定的条目在前
+#[tauri::command]
+pub async fn get_clipboard_history(
+    query: Option<String>,
+    pinned_only: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<ClipboardItem>, String> {
+    ensure_permission()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).max(1);
+    with_history(false, |history| {
+        Ok(history.search(query.as_deref(), pinned_only.unwrap_or(false), limit))
+    })
+}
+
+/// 固定或取消固定一条记录（固定的记录不会因超出条数被清理）
+#[tauri::command]
+pub async fn pin_clipboard_item(id: String, pinned: bool) -> Result<(), String> {
+    ensure_permission()?;
+    let max_entries = SETTINGS.read().max_entries;
+    with_history(true, |history| {
+        if !history.set_pinned(&id, pinned) {
+            return Err(format!("剪贴板记录不存在: {}", id));
+        }
+        history.trim(max_entries);
+        Ok(())
+    })
+}
+
+/// 把一条历史记录重新复制到剪贴板
+#[tauri::command]
+pub async fn copy_clipboard_item(app_handle: AppHandle, id: String) -> Result<(), String> {
+    ensure_permission()?;
+    let text = with_history(false, |history| {
+        let entry = history.get(&id).ok_or_else(|| format!("剪贴板记录不存在: {}", id))?;
+        match (&entry.content, &entry.encrypted) {
+            (Some(content), _) => Ok(content.clone()),
+            (None, Some(encrypted)) => {
+                let encryption = history_encryption(false)?.ok_or("剪贴板历史密钥不存在")?;
+                encryption
+                    .decrypt_string(encrypted)
+                    .map_err(|e| format!("解密剪贴板内容失败: {}", e))
+            }
+            (None, None) => Err("剪贴板记录内容缺失".to_string()),
+        }
+    })?;
+
+    app_handle
+        .clipboard_manager()
+        .write_text(text.clone())
+        .map_err(|e| format!("复制失败: {}", e))?;
+    record_text(&app_handle, &text);
+    Ok(())
+}
+
+/// 清空剪贴板历史，默认保留固定的记录；返回删除的条数
+#[tauri::command]
+pub async fn clear_clipboard_history(include_pinned: Option<bool>) -> Result<usize, String> {
+    let include_pinned = include_pinned.unwrap_or(false);
+    with_history(true, |history| Ok(history.clear(include_pinned)))
+}
+
+/// 获取剪贴板历史设置
+#[tauri::command]
+pub async fn get_clipboard_settings() -> Result<ClipboardSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存剪贴板历史设置；开启时检查剪贴板权限
+#[tauri::command]
+pub async fn update_clipboard_settings(settings: ClipboardSettings) -> Result<(), String> {
+    settings.validate()?;
+    if settings.enabled {
+        ensure_permission()?;
+    }
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize clipboard settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write clipboard settings: {}", e))?;
+
+    let max_entries = settings.max_entries;
+    if !settings.enabled {
+        *LAST_SEEN.lock() = None;
+    }
+    *SETTINGS.write() = settings;
+    with_history(true, |history| {
+        history.trim(max_entries);
+        Ok(())
+    })
+}
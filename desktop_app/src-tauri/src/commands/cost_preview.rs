@@ -0,0 +1,97 @@
+//! # 费用预估命令模块
+//!
+//! 读取与更新费用预估设置（阈值与价格表，保存在 `cost_preview_settings.json`），以及
+//! 会话级的“不再询问”开关。预估逻辑见 [`crate::utils::cost_preview`]；`send_message`
+//! 在需要确认时返回 [`CostConfirmationRequired`]，前端确认后带上 `confirm_cost` 重新发送。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::utils::cost_preview::{self, CostPreview, CostPreviewSettings};
+use crate::utils::model_parameters::EffectiveParameters;
+
+/// `send_message` 需要用户确认费用时的返回值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostConfirmationRequired {
+    /// 恒为 true，便于前端与正常回复区分
+    pub requires_confirmation: bool,
+    pub session_id: Option<String>,
+    pub preview: CostPreview,
+    /// 预估所用的模型参数
+    pub parameters: EffectiveParameters,
+}
+
+impl CostConfirmationRequired {
+    pub fn new(session_id: Option<String>, preview: CostPreview, parameters: EffectiveParameters) -> Self {
+        Self {
+            requires_confirmation: true,
+            session_id,
+            preview,
+            parameters,
+        }
+    }
+}
+
+fn get_cost_preview_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("cost_preview_settings.json"))
+}
+
+/// 从磁盘加载费用预估设置（启动时调用）
+pub fn initialize_cost_preview() -> Result<(), String> {
+    let config_path = get_cost_preview_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read cost preview settings: {}", e))?;
+    let settings: CostPreviewSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse cost preview settings: {}", e))?;
+    cost_preview::set_settings(settings);
+    Ok(())
+}
+
+/// 获取费用预估设置
+#[tauri::command]
+pub async fn get_cost_preview_settings() -> Result<CostPreviewSettings, String> {
+    Ok(cost_preview::settings())
+}
+
+/// 更新费用预估设置
+#[tauri::command]
+pub async fn update_cost_preview_settings(
+    settings: CostPreviewSettings,
+) -> Result<CostPreviewSettings, String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize cost preview settings: {}", e))?;
+    fs::write(get_cost_preview_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write cost preview settings: {}", e))?;
+
+    info!(
+        "费用预估设置已更新: enabled={}, threshold={} tokens, {} 个模型价格",
+        settings.enabled,
+        settings.threshold_tokens,
+        settings.prices.len()
+    );
+    cost_preview::set_settings(settings.clone());
+    Ok(settings)
+}
+
+/// 设置会话的“不再询问”（仅在本次运行期间有效）
+#[tauri::command]
+pub async fn set_cost_preview_skipped(session_id: String, skipped: bool) -> Result<(), String> {
+    if session_id.trim().is_empty() {
+        return Err("会话 ID 不能为空".to_string());
+    }
+    cost_preview::set_session_skipped(&session_id, skipped);
+    Ok(())
+}
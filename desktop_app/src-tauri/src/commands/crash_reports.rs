@@ -0,0 +1,92 @@
+//! # 崩溃报告命令模块
+//!
+//! 列出本机保存的崩溃报告（生成方式见 [`crate::utils::crash_reporter`]），并在用户确认后上传。
+//! 上传前对日志与调用栈中的敏感信息脱敏；报告附带的 minidump 作为文件一并上传。
+//! 上传地址读取环境变量 `CRASH_REPORT_URL`。
+
+use reqwest::multipart::{Form, Part};
+use tracing::{info, warn};
+
+use crate::utils::crash_reporter::{self, CrashReport};
+use crate::utils::data_masking::DataMasker;
+
+const DEFAULT_UPLOAD_URL: &str = "https://api.zishu-sensei.com/crash-reports";
+
+fn report_dir() -> Result<std::path::PathBuf, String> {
+    crash_reporter::report_dir().ok_or_else(|| "崩溃报告未启用".to_string())
+}
+
+/// 上传的报告内容（脱敏后）
+fn masked(report: &CrashReport) -> CrashReport {
+    let masker = DataMasker::new();
+    CrashReport {
+        message: masker.mask_all_sensitive(&report.message),
+        backtrace: report.backtrace.as_deref().map(|trace| masker.mask_all_sensitive(trace)),
+        log_tail: report.log_tail.iter().map(|line| masker.mask_all_sensitive(line)).collect(),
+        ..report.clone()
+    }
+}
+
+async fn upload(report: &CrashReport, minidump: Option<Vec<u8>>) -> Result<(), String> {
+    let upload_url = std::env::var("CRASH_REPORT_URL").unwrap_or_else(|_| DEFAULT_UPLOAD_URL.to_string());
+    let report_json = serde_json::to_string(&masked(report)).map_err(|e| format!("序列化崩溃报告失败: {}", e))?;
+
+    let mut form = Form::new().text("report", report_json);
+    if let Some(bytes) = minidump {
+        let part = Part::bytes(bytes)
+            .file_name(format!("{}.dmp", report.id))
+            .mime_str("application/octet-stream")
+            .map_err(|e| e.to_string())?;
+        form = form.part("minidump", part);
+    }
+
+    let client = crate::http::proxy::client_builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .post(&upload_url)
+        .multipart(form)
+        .header("User-Agent", format!("Zishu-Sensei/{}", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await
+        .map_err(|e| format!("上传崩溃报告失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("服务器返回错误状态: {}", response.status()));
+    }
+    Ok(())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 列出本机的崩溃报告，最新的在前
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReport>, String> {
+    Ok(crash_reporter::list_reports(&report_dir()?))
+}
+
+/// 上传崩溃报告（用户确认后调用），返回更新了上传时间的报告
+#[tauri::command]
+pub async fn submit_crash_report(report_id: String) -> Result<CrashReport, String> {
+    let dir = report_dir()?;
+    let mut report = crash_reporter::load_report(&dir, &report_id).ok_or("崩溃报告不存在")?;
+
+    let minidump = match &report.minidump {
+        Some(name) => match tokio::fs::read(dir.join(name)).await {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                warn!("读取崩溃转储失败，仅上传报告: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    upload(&report, minidump).await?;
+
+    report.submitted_at = Some(chrono::Utc::now().timestamp());
+    crash_reporter::save_report(&dir, &report).map_err(|e| format!("保存崩溃报告失败: {}", e))?;
+    info!("崩溃报告已上传: {}", report.id);
+    Ok(report)
+}
@@ -1,5 +1,7 @@
 use crate::commands::CommandMetadata;
+use crate::database::database_manager::{self, DatabaseHealth};
 use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
 
 pub fn get_command_metadata() -> HashMap<String, CommandMetadata> { 
     HashMap::new() 
@@ -27,6 +29,20 @@ pub fn perform_health_check() -> Result<HashMap<String, String>, String> {
     Ok(health_info)
 }
 
+/// 启动数据库连接健康监测：状态变化时发出 `database-health-changed` 事件，并同步托盘图标状态
+pub fn start_database_health_watchdog(app_handle: AppHandle) {
+    database_manager::start_health_watchdog(move |health| {
+        let _ = app_handle.emit_all("database-health-changed", health);
+        crate::events::tray::helpers::apply_database_health(&app_handle, health);
+    });
+}
+
+/// 获取数据库连接健康状态
+#[tauri::command]
+pub async fn get_database_health() -> Result<DatabaseHealth, String> {
+    Ok(database_manager::health())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
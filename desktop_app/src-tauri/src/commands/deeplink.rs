@@ -93,9 +93,7 @@ async fn handle_download_character(
     });
     
     // 获取应用数据目录
-    let app_data_dir = app.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let characters_dir = app_data_dir.join("characters");
     fs::create_dir_all(&characters_dir)
@@ -193,7 +191,7 @@ async fn download_file<F>(
 where
     F: Fn(f64),
 {
-    let client = reqwest::Client::new();
+    let client = crate::http::proxy::client();
     let response = client.get(url)
         .send()
         .await
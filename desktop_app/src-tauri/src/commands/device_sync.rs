@@ -0,0 +1,700 @@
+//! # 多设备同步命令模块
+//!
+//! 把对话与设置加密后同步到社区账号（格式与合并规则见 [`crate::utils::device_sync`]）。
+//! 每轮同步先发现本机修改，再拉取其他设备的变更并按向量时钟覆盖或合并，最后上传本机修改。
+//!
+//! 后台任务定期检查同步服务是否可达：离线时只更新状态，网络恢复后立即同步一次，
+//! 在线时按设定的间隔同步。状态变化时发送 `sync-status-changed`，应用了其他设备的条目时
+//! 发送 `sync-item-applied`。同步口令保存在系统密钥链中，各设备需要设置同一口令。
+//!
+//! 对话按用户资料分开保存，因此同步设置、同步状态与口令也按资料保存；
+//! 切换资料时重新加载，避免把另一个资料的对话当作已删除。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use crate::config::api_router::ApiRouter;
+use crate::http::sync_client::{PushRequest, SyncApiClient};
+use crate::utils::config::{get_app_data_dir, save_config, validate_config};
+use crate::utils::device_sync::{
+    self, item_key, ClockOrdering, ConversationPayload, SettingsPayload, SyncCategory, SyncCipher,
+    SyncEnvelope, SyncSettings, SyncState,
+};
+use crate::utils::keyring_store;
+use crate::utils::profile_archive;
+use crate::utils::profiles::{self, DEFAULT_PROFILE_ID};
+use crate::utils::watchdog;
+use crate::state::AppState;
+
+const PASSPHRASE_ENTRY: &str = "sync_passphrase";
+/// 设置条目的 ID（设置只有一个条目）
+const SETTINGS_ITEM_ID: &str = "app";
+/// 检查网络与同步计划的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// 同步阶段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    #[default]
+    Disabled,
+    Idle,
+    Syncing,
+    /// 同步服务不可达，网络恢复后自动同步
+    Offline,
+    Error,
+}
+
+/// 一轮同步的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// 应用到本机的其他设备条目
+    pub pulled: usize,
+    /// 上传的本机条目
+    pub pushed: usize,
+    /// 并发修改后合并的条目
+    pub merged: usize,
+    /// 服务端已有更新版本、留待下一轮合并的条目
+    pub rejected: usize,
+    pub finished_at: i64,
+}
+
+/// `get_sync_status` 与 `sync-status-changed` 事件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    /// 上次检查时同步服务是否可达
+    pub online: Option<bool>,
+    pub passphrase_set: bool,
+    pub last_sync_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+}
+
+lazy_static! {
+    static ref SETTINGS: RwLock<SyncSettings> = RwLock::new(SyncSettings::default());
+    static ref STATUS: Mutex<SyncStatus> = Mutex::new(SyncStatus::default());
+    /// 同一时间只运行一轮同步
+    static ref SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 资料的同步文件路径
+fn sync_data_path(data_dir: &Path, profile_id: &str, file_name: &str) -> Result<PathBuf, String> {
+    let profile_dir = profiles::profile_dir(data_dir, profile_id);
+    fs::create_dir_all(&profile_dir)
+        .map_err(|e| format!("Failed to create profile data directory: {}", e))?;
+    Ok(profile_dir.join(file_name))
+}
+
+/// 当前资料的同步文件路径
+fn get_sync_data_path(file_name: &str) -> Result<PathBuf, String> {
+    sync_data_path(&get_app_data_dir()?, &profiles::active_id(), file_name)
+}
+
+/// 当前资料在密钥链中的口令条目名
+fn passphrase_entry() -> String {
+    let profile_id = profiles::active_id();
+    if profile_id == DEFAULT_PROFILE_ID {
+        PASSPHRASE_ENTRY.to_string()
+    } else {
+        format!("{}:{}", PASSPHRASE_ENTRY, profile_id)
+    }
+}
+
+fn load_passphrase() -> Option<String> {
+    keyring_store::load(&passphrase_entry())
+}
+
+fn read_state(path: &Path) -> Result<SyncState, String> {
+    if !path.exists() {
+        return Ok(SyncState::default());
+    }
+    let json_data = fs::read_to_string(path).map_err(|e| format!("Failed to read sync state: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse sync state: {}", e))
+}
+
+fn write_state(path: &Path, state: &SyncState) -> Result<(), String> {
+    let json_data =
+        serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+    fs::write(path, json_data).map_err(|e| format!("Failed to write sync state: {}", e))
+}
+
+fn load_state() -> Result<SyncState, String> {
+    read_state(&get_sync_data_path("sync_state.json")?)
+}
+
+fn save_state(state: &SyncState) -> Result<(), String> {
+    write_state(&get_sync_data_path("sync_state.json")?, state)
+}
+
+/// 更新状态并通知前端
+fn update_status(app_handle: &AppHandle, update: impl FnOnce(&mut SyncStatus)) {
+    let status = {
+        let mut status = STATUS.lock();
+        update(&mut status);
+        status.passphrase_set = load_passphrase().is_some();
+        status.clone()
+    };
+    let _ = app_handle.emit_all("sync-status-changed", &status);
+}
+
+/// 从磁盘加载当前资料的同步设置（启动与切换资料时调用）
+pub fn initialize_device_sync(app_handle: &AppHandle) -> Result<(), String> {
+    let config_path = get_sync_data_path("sync_settings.json")?;
+    let settings: SyncSettings = if config_path.exists() {
+        let json_data = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read sync settings: {}", e))?;
+        serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse sync settings: {}", e))?
+    } else {
+        SyncSettings::default()
+    };
+
+    let last_sync_at = load_state().ok().and_then(|state| state.last_sync_at);
+    let enabled = settings.enabled;
+    *SETTINGS.write() = settings;
+    update_status(app_handle, |status| {
+        *status = SyncStatus {
+            phase: if enabled { SyncPhase::Idle } else { SyncPhase::Disabled },
+            last_sync_at,
+            ..SyncStatus::default()
+        };
+    });
+    Ok(())
+}
+
+/// 切换资料前调用：等待进行中的同步结束，并在持有期间阻止新的同步
+pub async fn lock_for_profile_switch() -> tokio::sync::MutexGuard<'static, ()> {
+    SYNC_LOCK.lock().await
+}
+
+/// 创建带社区账号令牌的同步客户端（同步必须登录）
+async fn sync_client() -> Result<SyncApiClient, String> {
+    let token = crate::commands::auth::get_auth_token()
+        .await
+        .map_err(|_| "多设备同步需要先登录社区账号".to_string())?;
+    let mut client = SyncApiClient::new(ApiRouter::new().community_url())
+        .map_err(|e| format!("创建 API 客户端失败: {}", e))?;
+    client.set_auth_token(Some(token));
+    Ok(client)
+}
+
+// ================================
+// 本机条目
+// ================================
+
+/// 本机条目的当前内容，`content` 为空表示已删除
+struct LocalItem {
+    category: SyncCategory,
+    item_id: String,
+    content: Option<Vec<u8>>,
+    hash: Option<String>,
+}
+
+impl LocalItem {
+    fn new<T: Serialize>(category: SyncCategory, item_id: &str, value: &T) -> Result<Self, String> {
+        Ok(Self {
+            category,
+            item_id: item_id.to_string(),
+            content: Some(serde_json::to_vec(value).map_err(|e| format!("序列化同步内容失败: {}", e))?),
+            hash: Some(device_sync::content_hash(value)?),
+        })
+    }
+
+    fn deleted(category: SyncCategory, item_id: &str) -> Self {
+        Self { category, item_id: item_id.to_string(), content: None, hash: None }
+    }
+}
+
+fn settings_payload(app_handle: &AppHandle) -> Result<SettingsPayload, String> {
+    let config = app_handle.state::<AppState>().config.lock().clone();
+    Ok(SettingsPayload {
+        character: serde_json::to_value(&config.character).map_err(|e| e.to_string())?,
+        theme: serde_json::to_value(&config.theme).map_err(|e| e.to_string())?,
+    })
+}
+
+/// 同步过但本机已不存在的对话（`state` 必须是当前资料的同步状态）
+fn deleted_conversations(state: &SyncState, present: &BTreeMap<String, LocalItem>) -> Vec<LocalItem> {
+    let prefix = item_key(SyncCategory::Conversations, "");
+    state
+        .items
+        .iter()
+        .filter(|(key, item)| item.hash.is_some() && !present.contains_key(*key))
+        .filter_map(|(key, _)| key.strip_prefix(&prefix))
+        .map(|id| LocalItem::deleted(SyncCategory::Conversations, id))
+        .collect()
+}
+
+/// 收集开启同步的类别中本机的全部条目；同步过但本机已不存在的对话记为删除
+async fn collect_local(
+    app_handle: &AppHandle,
+    settings: &SyncSettings,
+    state: &SyncState,
+) -> Result<BTreeMap<String, LocalItem>, String> {
+    let mut items = BTreeMap::new();
+
+    if settings.categories.conversations {
+        let db = crate::database::get_database().ok_or("数据库未初始化")?;
+        let conversations = db
+            .conversation_history
+            .list_conversations(i64::MIN, i64::MAX)
+            .await
+            .map_err(|e| format!("读取对话失败: {}", e))?;
+        for conversation in conversations {
+            let mut messages = db
+                .conversation_history
+                .get_messages(&conversation.id)
+                .await
+                .map_err(|e| format!("读取对话消息失败: {}", e))?;
+            messages.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+            let id = conversation.id.clone();
+            let payload = ConversationPayload { conversation, messages };
+            items.insert(
+                item_key(SyncCategory::Conversations, &id),
+                LocalItem::new(SyncCategory::Conversations, &id, &payload)?,
+            );
+        }
+
+        for item in deleted_conversations(state, &items) {
+            items.insert(item_key(SyncCategory::Conversations, &item.item_id), item);
+        }
+    }
+
+    if settings.categories.settings {
+        items.insert(
+            item_key(SyncCategory::Settings, SETTINGS_ITEM_ID),
+            LocalItem::new(SyncCategory::Settings, SETTINGS_ITEM_ID, &settings_payload(app_handle)?)?,
+        );
+    }
+
+    Ok(items)
+}
+
+/// 把其他设备的条目写入本机，返回写入后的内容摘要
+async fn apply_remote(
+    app_handle: &AppHandle,
+    category: SyncCategory,
+    item_id: &str,
+    content: Option<&[u8]>,
+) -> Result<Option<String>, String> {
+    let hash = match (category, content) {
+        (SyncCategory::Conversations, None) => {
+            let db = crate::database::get_database().ok_or("数据库未初始化")?;
+            db.conversation_history
+                .delete_conversation(item_id)
+                .await
+                .map_err(|e| format!("删除对话失败: {}", e))?;
+            None
+        }
+        (SyncCategory::Conversations, Some(content)) => {
+            let payload: ConversationPayload =
+                serde_json::from_slice(content).map_err(|e| format!("解析同步的对话失败: {}", e))?;
+            let db = crate::database::get_database().ok_or("数据库未初始化")?;
+            db.conversation_history
+                .upsert_synced_conversation(&payload.conversation, &payload.messages)
+                .await
+                .map_err(|e| format!("写入同步的对话失败: {}", e))?;
+            Some(device_sync::content_hash(&payload)?)
+        }
+        // 设置条目不会被删除
+        (SyncCategory::Settings, None) => return Ok(None),
+        (SyncCategory::Settings, Some(content)) => {
+            let payload: SettingsPayload =
+                serde_json::from_slice(content).map_err(|e| format!("解析同步的设置失败: {}", e))?;
+            let mut config = app_handle.state::<AppState>().config.lock().clone();
+            config.character =
+                serde_json::from_value(payload.character.clone()).map_err(|e| format!("同步的角色设置无效: {}", e))?;
+            config.theme =
+                serde_json::from_value(payload.theme.clone()).map_err(|e| format!("同步的主题设置无效: {}", e))?;
+            validate_config(&config)?;
+            save_config(app_handle, &config)
+                .await
+                .map_err(|e| format!("保存设置失败: {}", e))?;
+            *app_handle.state::<AppState>().config.lock() = config;
+            Some(device_sync::content_hash(&payload)?)
+        }
+    };
+
+    let _ = app_handle.emit_all("sync-item-applied", serde_json::json!({
+        "category": category,
+        "item_id": item_id,
+        "deleted": content.is_none(),
+    }));
+    Ok(hash)
+}
+
+/// 并发修改时的结果内容：对话按消息合并，设置取修改时间较晚的一方；本机修改优先于其他设备的删除
+fn resolve_concurrent(
+    category: SyncCategory,
+    local: Option<Vec<u8>>,
+    remote: Option<Vec<u8>>,
+    remote_is_newer: bool,
+) -> Result<Option<Vec<u8>>, String> {
+    Ok(match (local, remote) {
+        (None, None) => None,
+        (Some(local), None) => Some(local),
+        (None, Some(remote)) => Some(remote),
+        (Some(local), Some(remote)) => match category {
+            SyncCategory::Conversations => {
+                let parse = |bytes: &[u8]| {
+                    serde_json::from_slice::<ConversationPayload>(bytes).map_err(|e| format!("解析对话失败: {}", e))
+                };
+                let merged = device_sync::merge_conversations(parse(&local)?, parse(&remote)?);
+                Some(serde_json::to_vec(&merged).map_err(|e| e.to_string())?)
+            }
+            SyncCategory::Settings if remote_is_newer => Some(remote),
+            SyncCategory::Settings => Some(local),
+        },
+    })
+}
+
+// ================================
+// 同步流程
+// ================================
+
+async fn sync_round(
+    app_handle: &AppHandle,
+    client: &SyncApiClient,
+    settings: &SyncSettings,
+    passphrase: String,
+) -> Result<SyncReport, String> {
+    let device_id = crate::commands::auth::get_device_id()
+        .await
+        .map_err(|e| format!("获取设备ID失败: {}", e))?;
+    let now = Utc::now().timestamp();
+    let mut state = load_state()?;
+    let previous_items = state.items.clone();
+    let mut cipher = SyncCipher::new(passphrase);
+    let mut report = SyncReport::default();
+
+    // 发现本机修改
+    let mut local = collect_local(app_handle, settings, &state).await?;
+    let mut to_push = BTreeSet::new();
+    for (key, item) in &local {
+        let entry = state.items.entry(key.clone()).or_default();
+        if entry.hash != item.hash {
+            entry.clock.increment(&device_id);
+            entry.modified_at = now;
+            to_push.insert(key.clone());
+        }
+    }
+
+    // 拉取其他设备的变更
+    let pulled = client.pull(state.cursor).await.map_err(|e| format!("拉取同步数据失败: {}", e))?;
+    for envelope in pulled.items {
+        if !settings.categories.allows(envelope.category) {
+            continue;
+        }
+        let key = envelope.key();
+        let entry = state.items.entry(key.clone()).or_default();
+        let ordering = entry.clock.compare(&envelope.clock);
+        if matches!(ordering, ClockOrdering::Equal) {
+            continue;
+        }
+        if matches!(ordering, ClockOrdering::After) {
+            to_push.insert(key);
+            continue;
+        }
+
+        let remote = match &envelope.payload {
+            Some(payload) if !envelope.deleted => Some(cipher.open(payload)?),
+            _ => None,
+        };
+        if matches!(ordering, ClockOrdering::Before) {
+            entry.hash = apply_remote(app_handle, envelope.category, &envelope.item_id, remote.as_deref()).await?;
+            entry.clock = envelope.clock.clone();
+            entry.modified_at = envelope.updated_at;
+            local.remove(&key);
+            to_push.remove(&key);
+            report.pulled += 1;
+            continue;
+        }
+
+        let local_content = local.get(&key).and_then(|item| item.content.clone());
+        let resolved = resolve_concurrent(
+            envelope.category,
+            local_content.clone(),
+            remote,
+            envelope.updated_at > entry.modified_at,
+        )?;
+        let hash = if resolved != local_content {
+            apply_remote(app_handle, envelope.category, &envelope.item_id, resolved.as_deref()).await?
+        } else {
+            local.get(&key).and_then(|item| item.hash.clone())
+        };
+        debug!("合并并发修改的同步条目: {}", key);
+        entry.clock.merge(&envelope.clock);
+        entry.clock.increment(&device_id);
+        entry.modified_at = now;
+        local.insert(
+            key.clone(),
+            LocalItem { category: envelope.category, item_id: envelope.item_id.clone(), content: resolved, hash },
+        );
+        to_push.insert(key);
+        report.merged += 1;
+    }
+    state.cursor = pulled.cursor;
+
+    // 上传本机修改
+    let mut envelopes = Vec::new();
+    for key in &to_push {
+        let (Some(item), Some(entry)) = (local.get(key), state.items.get(key)) else {
+            continue;
+        };
+        let payload = match &item.content {
+            Some(content) => Some(cipher.seal(content)?),
+            None => None,
+        };
+        envelopes.push(SyncEnvelope {
+            category: item.category,
+            item_id: item.item_id.clone(),
+            clock: entry.clock.clone(),
+            device_id: device_id.clone(),
+            updated_at: entry.modified_at,
+            deleted: item.content.is_none(),
+            payload,
+        });
+    }
+    if !envelopes.is_empty() {
+        let response = client
+            .push(&PushRequest { device_id: device_id.clone(), items: envelopes })
+            .await
+            .map_err(|e| format!("上传同步数据失败: {}", e))?;
+        let rejected: BTreeSet<String> = response.rejected.into_iter().collect();
+        for key in &to_push {
+            if rejected.contains(key) {
+                // 恢复到本轮之前的状态，下一轮拉取服务端的新版本后重新合并
+                match previous_items.get(key) {
+                    Some(previous) => state.items.insert(key.clone(), previous.clone()),
+                    None => state.items.remove(key),
+                };
+                report.rejected += 1;
+            } else if let (Some(item), Some(entry)) = (local.get(key), state.items.get_mut(key)) {
+                entry.hash = item.hash.clone();
+                report.pushed += 1;
+            }
+        }
+    }
+
+    report.finished_at = Utc::now().timestamp();
+    state.last_sync_at = Some(report.finished_at);
+    save_state(&state)?;
+    Ok(report)
+}
+
+/// 执行一轮同步；服务不可达时进入离线状态
+async fn run_sync(app_handle: &AppHandle) -> Result<SyncReport, String> {
+    let Ok(_guard) = SYNC_LOCK.try_lock() else {
+        return Err("同步正在进行中".to_string());
+    };
+    let settings = SETTINGS.read().clone();
+    if !settings.enabled {
+        return Err("未开启多设备同步".to_string());
+    }
+    let passphrase = load_passphrase().ok_or("请先设置同步口令")?;
+    let client = sync_client().await?;
+    if !client.is_reachable().await {
+        update_status(app_handle, |status| {
+            status.phase = SyncPhase::Offline;
+            status.online = Some(false);
+        });
+        return Err("无法连接同步服务，网络恢复后会自动同步".to_string());
+    }
+
+    update_status(app_handle, |status| {
+        status.phase = SyncPhase::Syncing;
+        status.online = Some(true);
+    });
+    match sync_round(app_handle, &client, &settings, passphrase).await {
+        Ok(report) => {
+            info!(
+                "多设备同步完成: 拉取 {}，上传 {}，合并 {}，待重试 {}",
+                report.pulled, report.pushed, report.merged, report.rejected
+            );
+            update_status(app_handle, |status| {
+                status.phase = SyncPhase::Idle;
+                status.last_sync_at = Some(report.finished_at);
+                status.last_error = None;
+                status.last_report = Some(report.clone());
+            });
+            Ok(report)
+        }
+        Err(e) => {
+            warn!("多设备同步失败: {}", e);
+            update_status(app_handle, |status| {
+                status.phase = SyncPhase::Error;
+                status.last_error = Some(e.clone());
+            });
+            Err(e)
+        }
+    }
+}
+
+/// 启动同步计划：定期检查服务是否可达，网络恢复或到达间隔时同步
+pub fn start_sync_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("device_sync", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                let settings = SETTINGS.read().clone();
+                if !settings.enabled || load_passphrase().is_none() {
+                    continue;
+                }
+                let Ok(client) = sync_client().await else {
+                    continue;
+                };
+
+                let online = client.is_reachable().await;
+                let (was_online, last_sync_at) = {
+                    let status = STATUS.lock();
+                    (status.online, status.last_sync_at)
+                };
+                if !online {
+                    if was_online != Some(false) {
+                        info!("同步服务不可达，等待网络恢复");
+                        update_status(&app_handle, |status| {
+                            status.phase = SyncPhase::Offline;
+                            status.online = Some(false);
+                        });
+                    }
+                    continue;
+                }
+
+                let reconnected = was_online == Some(false);
+                let due = last_sync_at.map_or(true, |at| {
+                    Utc::now().timestamp() - at >= i64::from(settings.interval_minutes) * 60
+                });
+                if reconnected || due {
+                    if reconnected {
+                        info!("网络已恢复，开始同步");
+                    }
+                    if let Err(e) = run_sync(&app_handle).await {
+                        debug!("自动同步未完成: {}", e);
+                    }
+                }
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取同步设置
+#[tauri::command]
+pub async fn get_sync_settings() -> Result<SyncSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 更新同步设置（总开关、间隔与各类别开关）
+#[tauri::command]
+pub async fn update_sync_settings(app_handle: AppHandle, settings: SyncSettings) -> Result<(), String> {
+    settings.validate()?;
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize sync settings: {}", e))?;
+    fs::write(get_sync_data_path("sync_settings.json")?, json_data)
+        .map_err(|e| format!("Failed to write sync settings: {}", e))?;
+
+    info!(
+        "多设备同步设置已更新: enabled={}, conversations={}, settings={}",
+        settings.enabled, settings.categories.conversations, settings.categories.settings
+    );
+    let enabled = settings.enabled;
+    *SETTINGS.write() = settings;
+    update_status(&app_handle, |status| {
+        status.phase = match (enabled, status.phase) {
+            (false, _) => SyncPhase::Disabled,
+            (true, SyncPhase::Disabled) => SyncPhase::Idle,
+            (true, phase) => phase,
+        };
+    });
+    Ok(())
+}
+
+/// 设置同步口令
+///
+/// 更换口令后本机的全部条目会用新口令重新上传；其他设备需要设置同一口令才能读取。
+#[tauri::command]
+pub async fn set_sync_passphrase(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    profile_archive::validate_password(&passphrase)?;
+    keyring_store::store(&passphrase_entry(), Some(&passphrase))?;
+
+    let _guard = SYNC_LOCK.lock().await;
+    let mut state = load_state()?;
+    for item in state.items.values_mut() {
+        item.hash = None;
+    }
+    save_state(&state)?;
+    update_status(&app_handle, |_| {});
+    info!("同步口令已更新");
+    Ok(())
+}
+
+/// 立即同步一次
+#[tauri::command]
+pub async fn sync_now(app_handle: AppHandle) -> Result<SyncReport, String> {
+    run_sync(&app_handle).await
+}
+
+/// 获取同步状态
+#[tauri::command]
+pub async fn get_sync_status() -> Result<SyncStatus, String> {
+    let mut status = STATUS.lock().clone();
+    status.passphrase_set = load_passphrase().is_some();
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::device_sync::ItemState;
+
+    fn synced_state(conversation_ids: &[&str]) -> SyncState {
+        let mut state = SyncState::default();
+        for id in conversation_ids {
+            let item = ItemState { hash: Some(format!("hash-{}", id)), ..ItemState::default() };
+            state.items.insert(item_key(SyncCategory::Conversations, id), item);
+        }
+        state
+    }
+
+    fn local_conversations(ids: &[&str]) -> BTreeMap<String, LocalItem> {
+        ids.iter()
+            .map(|id| {
+                let item = LocalItem::new(SyncCategory::Conversations, id, &id.to_string()).unwrap();
+                (item_key(SyncCategory::Conversations, id), item)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_profile_switch_does_not_push_tombstones() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let default_path = sync_data_path(data_dir.path(), DEFAULT_PROFILE_ID, "sync_state.json").unwrap();
+        write_state(&default_path, &synced_state(&["home-1", "home-2"])).unwrap();
+
+        // 切换到另一个资料后读取的是该资料自己的同步状态
+        let work_path = sync_data_path(data_dir.path(), "work", "sync_state.json").unwrap();
+        assert_ne!(default_path, work_path);
+        let work_state = read_state(&work_path).unwrap();
+        assert!(deleted_conversations(&work_state, &local_conversations(&["work-1"])).is_empty());
+
+        // 回到默认资料时，只有真正删除的对话才记为删除
+        let default_state = read_state(&default_path).unwrap();
+        let deleted = deleted_conversations(&default_state, &local_conversations(&["home-1"]));
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].item_id, "home-2");
+        assert!(deleted[0].content.is_none());
+    }
+}
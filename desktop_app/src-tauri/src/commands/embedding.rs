@@ -0,0 +1,103 @@
+//! # 嵌入提供者命令模块
+//!
+//! 配置向量搜索各集合使用的嵌入提供者，测试提供者连通性与维度，
+//! 并在提供者变化后重新嵌入集合（进度通过 `embedding-reembed-progress` 事件推送）。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+use crate::database::embedding::{self, EmbeddingProviderConfig, EmbeddingSettings};
+use crate::database::vector_search_service::{ReembedReport, VectorSearchService};
+
+/// 更新嵌入设置的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingSettingsUpdate {
+    pub settings: EmbeddingSettings,
+    /// 提供者已变化、需要重新嵌入的集合
+    pub stale_collections: Vec<String>,
+}
+
+/// 提供者测试结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingProviderTest {
+    pub fingerprint: String,
+    pub dimension: usize,
+    pub elapsed_ms: u64,
+}
+
+/// 加载嵌入设置（应用启动时调用）
+pub fn initialize_embedding() -> Result<(), String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    embedding::initialize(app_data_dir.join("embedding_settings.json"))
+}
+
+/// 获取嵌入设置
+#[tauri::command]
+pub async fn get_embedding_settings() -> Result<EmbeddingSettingsUpdate, String> {
+    let settings = embedding::current_settings();
+    Ok(EmbeddingSettingsUpdate {
+        stale_collections: settings.stale_collections(),
+        settings,
+    })
+}
+
+/// 保存嵌入设置，返回需要重新嵌入的集合
+#[tauri::command]
+pub async fn update_embedding_settings(
+    app_handle: AppHandle,
+    settings: EmbeddingSettings,
+) -> Result<EmbeddingSettingsUpdate, String> {
+    let settings = embedding::set_settings(settings)?;
+    let stale_collections = settings.stale_collections();
+    if !stale_collections.is_empty() {
+        let _ = app_handle.emit_all("embedding-reembed-required", &stale_collections);
+    }
+    Ok(EmbeddingSettingsUpdate {
+        settings,
+        stale_collections,
+    })
+}
+
+/// 用一段示例文本测试提供者，校验返回维度与配置一致
+#[tauri::command]
+pub async fn test_embedding_provider(provider: EmbeddingProviderConfig) -> Result<EmbeddingProviderTest, String> {
+    let started = Instant::now();
+    let instance = provider.build().map_err(|e| e.to_string())?;
+    embedding::embed_checked(instance.as_ref(), &["Zishu embedding test".to_string()])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(EmbeddingProviderTest {
+        fingerprint: provider.fingerprint(),
+        dimension: provider.dimension(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 用集合当前配置的提供者重新嵌入
+#[tauri::command]
+pub async fn reembed_collection(app_handle: AppHandle, collection: String) -> Result<ReembedReport, String> {
+    let qdrant = crate::database::get_database_manager()
+        .and_then(|m| m.qdrant())
+        .ok_or("向量搜索未启用")?;
+    let service = VectorSearchService::new(qdrant);
+
+    let report = service
+        .reembed_collection(&collection, |done, total| {
+            let _ = app_handle.emit_all(
+                "embedding-reembed-progress",
+                serde_json::json!({ "collection": collection, "done": done, "total": total }),
+            );
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit_all("embedding-reembed-completed", &report);
+    Ok(report)
+}
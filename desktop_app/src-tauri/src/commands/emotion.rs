@@ -0,0 +1,229 @@
+//! # 角色情绪命令模块
+//!
+//! 每条聊天回复送入当前角色的情绪状态机（见 [`crate::utils::emotion_engine`]），情绪切换时
+//! 通过 `character::set_expression` 设置映射的表情、播放映射的动作，并发出
+//! `character-emotion-changed` 事件。后台每分钟检查一次情绪消退，回到平静时恢复默认表情。
+//!
+//! 情绪到表情 / 动作的映射随角色配置保存在 `character_configs` 中。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+
+use crate::commands::character::{self, PlayMotionRequest, SetExpressionRequest};
+use crate::database::character_registry::CharacterConfig;
+use crate::state::AppState;
+use crate::utils::clock;
+use crate::utils::emotion_engine::{
+    self, Emotion, EmotionConfig, EmotionMachine, EmotionSnapshot, EmotionTransition,
+};
+
+/// 检查情绪消退的间隔
+const DECAY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// 情绪动作的优先级（高于待机动作）
+const MOTION_PRIORITY: u8 = 2;
+
+lazy_static! {
+    /// 角色 ID -> 情绪状态机
+    static ref MACHINES: Mutex<HashMap<String, EmotionMachine>> = Mutex::new(HashMap::new());
+    /// 角色 ID -> 情绪配置（从 `character_configs` 加载）
+    static ref CONFIGS: Mutex<HashMap<String, EmotionConfig>> = Mutex::new(HashMap::new());
+}
+
+/// 角色的当前情绪
+#[derive(Debug, Clone, Serialize)]
+pub struct CharacterEmotion {
+    pub character_id: String,
+    #[serde(flatten)]
+    pub state: EmotionSnapshot,
+}
+
+/// `character-emotion-changed` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+struct EmotionChanged {
+    character_id: String,
+    #[serde(flatten)]
+    transition: EmotionTransition,
+    expression: Option<String>,
+    motion: Option<String>,
+}
+
+fn now() -> i64 {
+    clock::now().timestamp()
+}
+
+fn resolve_character(app: &AppHandle, character_id: Option<String>) -> Option<String> {
+    character_id.or_else(|| {
+        app.try_state::<AppState>()
+            .map(|state| state.config.lock().character.current_character.clone())
+    })
+}
+
+async fn load_character_config(character_id: &str) -> Result<Option<CharacterConfig>, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.character_registry
+        .get_character_config_async(character_id)
+        .await
+        .map_err(|e| format!("获取角色配置失败: {}", e))
+}
+
+async fn emotion_config(character_id: &str) -> EmotionConfig {
+    let cached = CONFIGS.lock().get(character_id).cloned();
+    if let Some(config) = cached {
+        return config;
+    }
+    let config = match load_character_config(character_id).await {
+        Ok(stored) => EmotionConfig::from_config_json(stored.as_ref().and_then(|c| c.config_json.as_deref())),
+        Err(e) => {
+            debug!("读取情绪配置失败，使用默认配置: {}", e);
+            return EmotionConfig::default();
+        }
+    };
+    CONFIGS.lock().insert(character_id.to_string(), config.clone());
+    config
+}
+
+/// 角色配置被其他命令改写后丢弃缓存的情绪配置
+pub fn invalidate_config(character_id: &str) {
+    CONFIGS.lock().remove(character_id);
+}
+
+/// 设置切换后情绪的表情与动作
+async fn express(app: &AppHandle, character_id: &str, transition: EmotionTransition, config: &EmotionConfig) {
+    let expression = config.expressions.get(&transition.to).cloned();
+    let motion = config.motions.get(&transition.to).cloned();
+
+    if let Some(expression) = &expression {
+        let request = SetExpressionRequest {
+            character_id: Some(character_id.to_string()),
+            expression: expression.clone(),
+        };
+        if let Err(e) = character::set_expression(request, app.clone(), app.state()).await {
+            warn!("设置情绪表情失败: {}", e);
+        }
+    }
+    if let Some(motion) = &motion {
+        let request = PlayMotionRequest {
+            character_id: Some(character_id.to_string()),
+            motion: motion.clone(),
+            priority: Some(MOTION_PRIORITY),
+            loop_motion: Some(false),
+        };
+        if let Err(e) = character::play_motion(request, app.clone(), app.state()).await {
+            warn!("播放情绪动作失败: {}", e);
+        }
+    }
+
+    let _ = app.emit_all(
+        "character-emotion-changed",
+        EmotionChanged { character_id: character_id.to_string(), transition, expression, motion },
+    );
+}
+
+/// 根据聊天回复更新角色情绪（`tag` 为后端返回的情绪标签）
+pub fn on_reply(app: &AppHandle, character_id: Option<String>, tag: Option<String>, reply: &str) {
+    let Some(character_id) = resolve_character(app, character_id) else {
+        return;
+    };
+    let app = app.clone();
+    let reply = reply.to_string();
+    tauri::async_runtime::spawn(async move {
+        let config = emotion_config(&character_id).await;
+        if !config.enabled {
+            return;
+        }
+
+        let signal = tag
+            .as_deref()
+            .and_then(Emotion::from_tag)
+            .map(|emotion| (emotion, emotion_engine::TAG_STRENGTH))
+            .or_else(|| config.use_local_classifier.then(|| emotion_engine::classify(&reply)).flatten());
+        let Some((emotion, strength)) = signal else {
+            return;
+        };
+
+        let now = now();
+        let transition = MACHINES
+            .lock()
+            .entry(character_id.clone())
+            .or_insert_with(|| EmotionMachine::new(now))
+            .observe(emotion, strength, now);
+        if let Some(transition) = transition {
+            express(&app, &character_id, transition, &config).await;
+        }
+    });
+}
+
+/// 后台检查情绪消退（启动时调用）
+pub fn start_emotion_decay(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(DECAY_CHECK_INTERVAL).await;
+            let now = now();
+            let settled: Vec<_> = MACHINES
+                .lock()
+                .iter_mut()
+                .filter_map(|(character_id, machine)| machine.tick(now).map(|t| (character_id.clone(), t)))
+                .collect();
+            for (character_id, transition) in settled {
+                let config = emotion_config(&character_id).await;
+                if config.enabled {
+                    express(&app_handle, &character_id, transition, &config).await;
+                }
+            }
+        }
+    });
+}
+
+/// 获取角色的当前情绪（默认当前角色）
+#[tauri::command]
+pub async fn get_character_emotion(
+    app_handle: AppHandle,
+    character_id: Option<String>,
+) -> Result<CharacterEmotion, String> {
+    let character_id = resolve_character(&app_handle, character_id).ok_or("没有当前角色")?;
+    let now = now();
+    let state = MACHINES
+        .lock()
+        .get(&character_id)
+        .map(|machine| machine.snapshot(now))
+        .unwrap_or_else(|| EmotionMachine::new(now).snapshot(now));
+    Ok(CharacterEmotion { character_id, state })
+}
+
+/// 获取角色的情绪配置
+#[tauri::command]
+pub async fn get_emotion_config(character_id: String) -> Result<EmotionConfig, String> {
+    let stored = load_character_config(&character_id).await?;
+    Ok(EmotionConfig::from_config_json(stored.as_ref().and_then(|c| c.config_json.as_deref())))
+}
+
+/// 保存角色的情绪配置（写入 `character_configs`，保留其他配置）
+#[tauri::command]
+pub async fn update_emotion_config(character_id: String, config: EmotionConfig) -> Result<(), String> {
+    config.validate()?;
+
+    let stored = load_character_config(&character_id).await?;
+    let mut character_config = stored.unwrap_or_else(|| CharacterConfig {
+        character_id: character_id.clone(),
+        scale: 1.0,
+        position_x: 0.0,
+        position_y: 0.0,
+        interaction_enabled: true,
+        config_json: None,
+    });
+    character_config.config_json = Some(config.merge_into(character_config.config_json.as_deref())?);
+
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.character_registry
+        .save_character_config_async(character_config)
+        .await
+        .map_err(|e| format!("保存情绪配置失败: {}", e))?;
+
+    CONFIGS.lock().insert(character_id, config);
+    Ok(())
+}
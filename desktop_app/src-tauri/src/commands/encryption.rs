@@ -287,13 +287,9 @@ pub async fn unload_key(key_id: String) -> Result<(), CommandError> {
 #[tauri::command]
 pub async fn store_encrypted_field(
     request: StoreEncryptedFieldRequest,
-    app_handle: tauri::AppHandle,
 ) -> Result<(), CommandError> {
     // 获取存储路径
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let storage_path = app_data_dir.join("encrypted_storage.db");
     let storage = EncryptedStorage::new(&storage_path)?;
@@ -333,13 +329,9 @@ pub async fn store_encrypted_field(
 #[tauri::command]
 pub async fn retrieve_encrypted_field(
     request: RetrieveEncryptedFieldRequest,
-    app_handle: tauri::AppHandle,
 ) -> Result<String, CommandError> {
     // 获取存储路径
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let storage_path = app_data_dir.join("encrypted_storage.db");
     let storage = EncryptedStorage::new(&storage_path)?;
@@ -363,12 +355,8 @@ pub async fn retrieve_encrypted_field(
 #[tauri::command]
 pub async fn delete_encrypted_field(
     id: String,
-    app_handle: tauri::AppHandle,
 ) -> Result<(), CommandError> {
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let storage_path = app_data_dir.join("encrypted_storage.db");
     let storage = EncryptedStorage::new(&storage_path)?;
@@ -417,14 +405,10 @@ pub async fn mask_all_sensitive(text: String) -> Result<String, CommandError> {
 #[tauri::command]
 pub async fn query_audit_logs(
     request: QueryAuditLogsRequest,
-    app_handle: tauri::AppHandle,
 ) -> Result<Vec<AuditEvent>, CommandError> {
     use crate::utils::security_audit::{SecurityAuditLogger, AuditEventType as AET, AuditLevel as AL};
 
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let audit_db_path = app_data_dir.join("security_audit.db");
     let logger = SecurityAuditLogger::new(&audit_db_path)
@@ -471,14 +455,10 @@ pub async fn query_audit_logs(
 #[tauri::command]
 pub async fn cleanup_audit_logs(
     days: i64,
-    app_handle: tauri::AppHandle,
 ) -> Result<usize, CommandError> {
     use crate::utils::security_audit::SecurityAuditLogger;
 
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let audit_db_path = app_data_dir.join("security_audit.db");
     let logger = SecurityAuditLogger::new(&audit_db_path)
@@ -492,15 +472,10 @@ pub async fn cleanup_audit_logs(
 
 /// 获取审计日志统计
 #[tauri::command]
-pub async fn get_audit_statistics(
-    app_handle: tauri::AppHandle,
-) -> Result<crate::utils::security_audit::AuditStatistics, CommandError> {
+pub async fn get_audit_statistics() -> Result<crate::utils::security_audit::AuditStatistics, CommandError> {
     use crate::utils::security_audit::SecurityAuditLogger;
 
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     let audit_db_path = app_data_dir.join("security_audit.db");
     let logger = SecurityAuditLogger::new(&audit_db_path)
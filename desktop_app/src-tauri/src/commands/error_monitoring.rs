@@ -13,6 +13,8 @@ use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::State;
 
+use crate::utils::watchdog::{self, SubsystemHealth, SubsystemStatus};
+
 // ================================
 // 全局状态管理
 // ================================
@@ -510,8 +512,9 @@ pub fn get_system_health(state: State<ErrorMonitorState>) -> CommandResult<Syste
         Err(e) => return CommandResult::error(format!("Failed to get statistics: {}", e)),
     };
 
-    // 计算健康评分
-    let health_score = calculate_health_score(&stats);
+    // 计算健康评分（后台子系统失败或频繁重启同样扣分）
+    let subsystems = watchdog::health();
+    let health_score = calculate_health_score(&stats).saturating_sub(subsystem_penalty(&subsystems));
     let status = if health_score >= 90 {
         "healthy"
     } else if health_score >= 70 {
@@ -531,7 +534,9 @@ pub fn get_system_health(state: State<ErrorMonitorState>) -> CommandResult<Syste
         last_error_time: stats.hourly_trend.last()
             .map(|h| h.hour.clone())
             .unwrap_or_else(|| "N/A".to_string()),
-        recovery_suggestions: generate_recovery_suggestions(&stats),
+        recovery_suggestions: generate_recovery_suggestions(&stats, &subsystems),
+        subsystem_restarts: subsystems.iter().map(|s| s.restarts).sum(),
+        subsystems,
     };
 
     CommandResult::success(health)
@@ -599,8 +604,33 @@ fn calculate_health_score(stats: &ErrorStatistics) -> u8 {
     score
 }
 
+/// 后台子系统状态扣分
+fn subsystem_penalty(subsystems: &[SubsystemHealth]) -> u8 {
+    subsystems.iter().fold(0u8, |penalty, subsystem| {
+        let cost = match subsystem.status {
+            SubsystemStatus::Failed => 20,
+            SubsystemStatus::Stalled => 10,
+            SubsystemStatus::Healthy if subsystem.consecutive_restarts > 0 => 5,
+            SubsystemStatus::Healthy => 0,
+        };
+        penalty.saturating_add(cost)
+    })
+}
+
+/// 后台子系统相关的恢复建议
+fn subsystem_suggestions(subsystems: &[SubsystemHealth]) -> Vec<String> {
+    subsystems
+        .iter()
+        .filter_map(|subsystem| match subsystem.status {
+            SubsystemStatus::Failed => Some(format!("后台任务 {} 多次重启失败，建议重启应用并查看日志", subsystem.name)),
+            SubsystemStatus::Stalled => Some(format!("后台任务 {} 无响应，正在等待自动重启", subsystem.name)),
+            SubsystemStatus::Healthy => None,
+        })
+        .collect()
+}
+
 /// 生成恢复建议
-fn generate_recovery_suggestions(stats: &ErrorStatistics) -> Vec<String> {
+fn generate_recovery_suggestions(stats: &ErrorStatistics, subsystems: &[SubsystemHealth]) -> Vec<String> {
     let mut suggestions = Vec::new();
 
     if stats.new_errors > 20 {
@@ -630,6 +660,8 @@ fn generate_recovery_suggestions(stats: &ErrorStatistics) -> Vec<String> {
         suggestions.push("错误频率较高，建议启用详细日志记录".to_string());
     }
 
+    suggestions.extend(subsystem_suggestions(subsystems));
+
     if suggestions.is_empty() {
         suggestions.push("系统运行良好，继续保持当前配置".to_string());
     }
@@ -650,4 +682,8 @@ pub struct SystemHealth {
     pub critical_errors: i64,
     pub last_error_time: String,
     pub recovery_suggestions: Vec<String>,
+    /// 看门狗累计重启后台子系统的次数
+    pub subsystem_restarts: u32,
+    /// 各后台子系统的心跳与重启情况
+    pub subsystems: Vec<SubsystemHealth>,
 }
@@ -0,0 +1,23 @@
+//! # 事件日志命令模块
+//!
+//! 读取只追加的事件日志，并从事件重放出某一时刻的状态（设置、当前角色、权限与会话），
+//! 用于排查问题。日志的写入与重放逻辑见 [`crate::utils::event_journal`]。
+
+use crate::utils::event_journal::{self, EventRange, JournalEvent, ReplayedState};
+
+/// 单次最多返回的事件数
+const MAX_STREAM_EVENTS: usize = 1000;
+
+/// 按序号范围获取事件流（最多 1000 条）
+#[tauri::command]
+pub async fn get_event_stream(range: Option<EventRange>) -> Result<Vec<JournalEvent>, String> {
+    let mut range = range.unwrap_or_default();
+    range.limit = Some(range.limit.unwrap_or(MAX_STREAM_EVENTS).min(MAX_STREAM_EVENTS));
+    event_journal::read(&range)
+}
+
+/// 从日志起点重放到指定序号（为空时重放全部），返回重建的状态
+#[tauri::command]
+pub async fn replay_event_journal(until_seq: Option<u64>) -> Result<ReplayedState, String> {
+    event_journal::replay_journal(until_seq)
+}
@@ -0,0 +1,90 @@
+//! # 事件订阅命令模块
+//!
+//! 窗口通过 `subscribe_events` 订阅高频事件频道（可带节流与过滤），后端用 [`publish`]
+//! 只向订阅了该频道的窗口投递。订阅管理见 [`crate::utils::event_subscription`]。
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Window};
+use tracing::{debug, warn};
+
+use crate::utils::event_subscription::{self, SubscriptionInfo, SubscriptionOptions};
+
+/// 系统监控数据（每个采样周期一次）
+pub const CHANNEL_SYSTEM_MONITOR: &str = "system-monitor-update";
+/// 资源预算重新分配
+pub const CHANNEL_RESOURCE_BUDGET: &str = "resource-budget-changed";
+/// 口型同步参数（播放期间每帧一次）
+pub const CHANNEL_LIPSYNC_FRAME: &str = "lipsync-frame";
+/// Live2D 参数帧（有变化时每帧一次）
+pub const CHANNEL_LIVE2D_PARAMETERS: &str = "live2d-parameters";
+/// 多角色互动（注册的角色实例自动订阅 `pet-*`）
+pub const CHANNEL_PET_INTERACTION: &str = "pet-interaction";
+/// 角色实例名册变化
+pub const CHANNEL_PET_INSTANCES: &str = "pet-instances-changed";
+
+/// 把事件投递给订阅了该频道的窗口；没有订阅者时不做序列化
+pub fn publish<S: Serialize>(app_handle: &AppHandle, channel: &str, payload: &S) {
+    if !event_subscription::has_subscribers(channel) {
+        return;
+    }
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("序列化事件 {} 失败: {}", channel, e);
+            return;
+        }
+    };
+
+    for label in event_subscription::deliveries(channel, &payload) {
+        match app_handle.get_window(&label) {
+            Some(window) => {
+                if let Err(e) = window.emit(channel, &payload) {
+                    warn!("向窗口 {} 发送事件 {} 失败: {}", label, channel, e);
+                }
+            }
+            None => {
+                // 窗口已经不存在，清理遗留的订阅
+                event_subscription::remove_window(&label);
+            }
+        }
+    }
+}
+
+/// 窗口销毁时清除其订阅
+pub fn handle_window_destroyed(window_label: &str) {
+    let removed = event_subscription::remove_window(window_label);
+    if removed > 0 {
+        debug!("窗口 {} 已销毁，清除 {} 个事件订阅", window_label, removed);
+    }
+}
+
+/// 为当前窗口订阅事件频道（频道以 `*` 结尾时按前缀匹配）
+#[tauri::command]
+pub async fn subscribe_events(
+    window: Window,
+    channel: String,
+    options: Option<SubscriptionOptions>,
+) -> Result<(), String> {
+    if channel.trim().is_empty() || channel == "*" {
+        return Err("订阅频道不能为空".to_string());
+    }
+    debug!("窗口 {} 订阅事件频道: {}", window.label(), channel);
+    event_subscription::subscribe(window.label(), &channel, options.unwrap_or_default());
+    Ok(())
+}
+
+/// 取消当前窗口对事件频道的订阅
+#[tauri::command]
+pub async fn unsubscribe_events(window: Window, channel: String) -> Result<bool, String> {
+    Ok(event_subscription::unsubscribe(window.label(), &channel))
+}
+
+/// 列出事件订阅及投递统计（`all` 为 true 时列出所有窗口）
+#[tauri::command]
+pub async fn list_event_subscriptions(
+    window: Window,
+    all: Option<bool>,
+) -> Result<Vec<SubscriptionInfo>, String> {
+    let label = (!all.unwrap_or(false)).then(|| window.label());
+    Ok(event_subscription::list(label))
+}
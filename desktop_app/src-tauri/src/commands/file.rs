@@ -698,10 +698,7 @@ pub async fn upload_file(
     }
 
     // 准备存储目录
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Failed to get app data dir")?;
+    let app_dir = crate::utils::config::get_app_data_dir()?;
 
     let upload_dir = app_dir.join(UPLOAD_DIR);
     fs::create_dir_all(&upload_dir).map_err(|e| format!("Failed to create upload dir: {}", e))?;
@@ -773,6 +770,23 @@ pub async fn get_file(app_handle: AppHandle, file_id: String) -> Result<FileInfo
         .ok_or_else(|| "File not found".to_string())
 }
 
+/// 按文件 ID 查找磁盘路径（供其他命令模块使用）
+pub(crate) fn resolve_file_path(app_handle: &AppHandle, file_id: &str) -> Result<PathBuf, String> {
+    let conn = get_db_connection(app_handle)?;
+    let file_info = get_file_info(&conn, file_id)
+        .map_err(|e| format!("Failed to get file: {}", e))?
+        .ok_or_else(|| "File not found".to_string())?;
+    Ok(PathBuf::from(file_info.file_path))
+}
+
+/// 列出会话中的全部文件（供其他命令模块使用）
+pub(crate) fn conversation_files(app_handle: &AppHandle, conversation_id: &str) -> Result<Vec<FileInfo>, String> {
+    let conn = get_db_connection(app_handle)?;
+    list_files(&conn, Some(conversation_id), None, None, None)
+        .map(|files| files.into_iter().filter(|file| !file.is_deleted).collect())
+        .map_err(|e| format!("Failed to list files: {}", e))
+}
+
 /// 读取文件内容
 #[tauri::command]
 pub async fn read_file_content(
@@ -0,0 +1,373 @@
+//! # 专注计时命令模块
+//!
+//! 桌宠陪伴的番茄钟：`start_focus_session` 开始专注（或继续暂停的计时），`pause_focus_session`
+//! 暂停，`stop_focus_session` 结束本轮。专注结束后按设置进入短休息或长休息，阶段边界时主窗口
+//! 收到 `play-motion` 事件播放设置中的动作，计时状态变化通过 `focus-state-changed` 推送，
+//! 阶段完成时发出 `focus-phase-completed`。
+//!
+//! 完成的专注写入 `focus_sessions` 表，`get_focus_stats` 汇总每天与本周的专注时长。
+//! 计时逻辑见 [`crate::utils::focus_timer`]。
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+use crate::utils::clock;
+use crate::utils::focus_timer::{self, FocusDay, FocusPhase, FocusSessionRecord, FocusSettings, FocusTimer};
+
+const DEFAULT_STATS_DAYS: u32 = 7;
+const MAX_STATS_DAYS: u32 = 90;
+/// 阶段边界动作的优先级（高于待机动作）
+const REACTION_PRIORITY: u32 = 2;
+
+#[derive(Default)]
+struct FocusRuntime {
+    timer: Option<FocusTimer>,
+    /// 本轮已完成的专注次数（长休息结束后清零）
+    completed_in_cycle: u32,
+    /// 开始、暂停或停止时递增，旧的计时任务随之失效
+    generation: u64,
+}
+
+lazy_static! {
+    static ref SETTINGS: RwLock<FocusSettings> = RwLock::new(FocusSettings::default());
+    static ref RUNTIME: Mutex<FocusRuntime> = Mutex::new(FocusRuntime::default());
+}
+
+/// 计时状态（`focus-state-changed` 事件载荷）
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusState {
+    pub phase: FocusPhase,
+    pub task: Option<String>,
+    pub paused: bool,
+    pub duration_secs: i64,
+    pub remaining_secs: i64,
+    /// 运行中时的结束时间（Unix 毫秒）
+    pub ends_at: Option<i64>,
+    pub completed_in_cycle: u32,
+    pub sessions_before_long_break: u32,
+}
+
+/// `focus-phase-completed` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+struct PhaseCompleted {
+    phase: FocusPhase,
+    next_phase: FocusPhase,
+    /// 下一阶段是否已自动开始
+    auto_started: bool,
+    completed_in_cycle: u32,
+}
+
+/// 专注统计
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusStats {
+    pub today: FocusDay,
+    /// 本周（周一起）的专注时长与次数
+    pub week_focus_secs: i64,
+    pub week_sessions: usize,
+    /// 最近若干天每天的专注（旧的在前）
+    pub daily: Vec<FocusDay>,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("focus_timer.json"))
+}
+
+/// 加载专注计时设置（启动时调用）
+pub fn initialize_focus_timer() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read focus timer settings: {}", e))?;
+    let settings: FocusSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse focus timer settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn snapshot(runtime: &FocusRuntime) -> Option<FocusState> {
+    let now = now_ms();
+    runtime.timer.as_ref().map(|timer| FocusState {
+        phase: timer.phase,
+        task: timer.task.clone(),
+        paused: timer.is_paused(),
+        duration_secs: timer.duration_ms / 1000,
+        remaining_secs: (timer.remaining_ms(now) + 999) / 1000,
+        ends_at: timer.ends_at(now),
+        completed_in_cycle: runtime.completed_in_cycle,
+        sessions_before_long_break: SETTINGS.read().sessions_before_long_break,
+    })
+}
+
+fn emit_state(app_handle: &AppHandle, state: &Option<FocusState>) {
+    let _ = app_handle.emit_all("focus-state-changed", state);
+}
+
+fn current_character(app_handle: &AppHandle) -> Option<String> {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.config.lock().character.current_character.clone())
+}
+
+/// 让角色播放阶段边界的动作
+fn react(app_handle: &AppHandle, motion: &str) {
+    if motion.is_empty() {
+        return;
+    }
+    if let Some(main_window) = app_handle.get_window("main") {
+        let _ = main_window.emit(
+            "play-motion",
+            serde_json::json!({
+                "character_id": current_character(app_handle),
+                "motion": motion,
+                "priority": REACTION_PRIORITY,
+                "loop": false,
+            }),
+        );
+    }
+}
+
+/// 计时到点后结束当前阶段
+fn schedule(app_handle: &AppHandle, generation: u64, remaining_ms: i64) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(remaining_ms.max(0) as u64)).await;
+        finish_phase(&app_handle, generation);
+    });
+}
+
+/// 开始一个阶段，返回新的状态
+fn begin_phase(
+    app_handle: &AppHandle,
+    runtime: &mut FocusRuntime,
+    phase: FocusPhase,
+    duration_ms: i64,
+    task: Option<String>,
+) -> Option<FocusState> {
+    runtime.generation += 1;
+    runtime.timer = Some(FocusTimer::start(phase, duration_ms, task, now_ms()));
+    schedule(app_handle, runtime.generation, duration_ms);
+    snapshot(runtime)
+}
+
+fn finish_phase(app_handle: &AppHandle, generation: u64) {
+    let settings = SETTINGS.read().clone();
+    let mut runtime = RUNTIME.lock();
+    let now = now_ms();
+    let finished = match &runtime.timer {
+        Some(timer) if runtime.generation == generation && timer.is_finished(now) => timer.clone(),
+        _ => return,
+    };
+
+    if finished.phase == FocusPhase::Work {
+        runtime.completed_in_cycle += 1;
+        let record = FocusSessionRecord {
+            started_at: finished.started_at / 1000,
+            ended_at: now / 1000,
+            focus_secs: finished.duration_ms / 1000,
+            task: finished.task.clone(),
+            character_id: current_character(app_handle),
+        };
+        tauri::async_runtime::spawn(async move {
+            let Some(db) = crate::database::get_database() else {
+                return;
+            };
+            if let Err(e) = db.pet_statistics_registry.record_focus_session(&record).await {
+                warn!("保存专注记录失败: {}", e);
+            }
+        });
+        react(app_handle, &settings.motions.work_complete);
+        info!("🍅 完成第 {} 次专注", runtime.completed_in_cycle);
+    } else {
+        if finished.phase == FocusPhase::LongBreak {
+            runtime.completed_in_cycle = 0;
+        }
+        react(app_handle, &settings.motions.break_complete);
+    }
+
+    let next_phase = settings.next_phase(finished.phase, runtime.completed_in_cycle);
+    let auto_start = if next_phase.is_break() { settings.auto_start_breaks } else { settings.auto_start_work };
+    let state = if auto_start {
+        begin_phase(app_handle, &mut runtime, next_phase, settings.duration_ms(next_phase), finished.task)
+    } else {
+        runtime.generation += 1;
+        runtime.timer = None;
+        None
+    };
+    let completed = PhaseCompleted {
+        phase: finished.phase,
+        next_phase,
+        auto_started: auto_start,
+        completed_in_cycle: runtime.completed_in_cycle,
+    };
+    drop(runtime);
+
+    let _ = app_handle.emit_all("focus-phase-completed", &completed);
+    emit_state(app_handle, &state);
+}
+
+fn local_midnight(day: NaiveDate) -> i64 {
+    day.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|midnight| midnight.timestamp())
+        .unwrap_or(0)
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0).single().map(|t| t.date_naive())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 开始专注；有暂停的计时时继续该计时
+///
+/// `minutes` 为空时使用设置中的专注时长。
+#[tauri::command]
+pub async fn start_focus_session(
+    app_handle: AppHandle,
+    task: Option<String>,
+    minutes: Option<u32>,
+) -> Result<FocusState, String> {
+    let settings = SETTINGS.read().clone();
+    let mut runtime = RUNTIME.lock();
+
+    let state = match runtime.timer.as_mut() {
+        Some(timer) if timer.is_paused() => {
+            let now = now_ms();
+            timer.resume(now);
+            let remaining = timer.remaining_ms(now);
+            runtime.generation += 1;
+            schedule(&app_handle, runtime.generation, remaining);
+            snapshot(&runtime)
+        }
+        Some(_) => return Err("专注计时正在进行中".to_string()),
+        None => {
+            let duration_ms = match minutes {
+                Some(minutes) if !(1..=180).contains(&minutes) => {
+                    return Err("专注时长必须在 1 到 180 分钟之间".to_string());
+                }
+                Some(minutes) => i64::from(minutes) * 60_000,
+                None => settings.duration_ms(FocusPhase::Work),
+            };
+            let task = task.map(|task| task.trim().to_string()).filter(|task| !task.is_empty());
+            let state = begin_phase(&app_handle, &mut runtime, FocusPhase::Work, duration_ms, task);
+            react(&app_handle, &settings.motions.work_start);
+            state
+        }
+    };
+    drop(runtime);
+
+    emit_state(&app_handle, &state);
+    state.ok_or_else(|| "开始专注失败".to_string())
+}
+
+/// 暂停当前计时
+#[tauri::command]
+pub async fn pause_focus_session(app_handle: AppHandle) -> Result<FocusState, String> {
+    let mut runtime = RUNTIME.lock();
+    let timer = runtime.timer.as_mut().ok_or("没有进行中的专注计时")?;
+    if timer.pause(now_ms()) {
+        runtime.generation += 1;
+    }
+    let state = snapshot(&runtime);
+    drop(runtime);
+
+    emit_state(&app_handle, &state);
+    state.ok_or_else(|| "暂停专注失败".to_string())
+}
+
+/// 结束本轮计时（未完成的专注不计入统计）
+#[tauri::command]
+pub async fn stop_focus_session(app_handle: AppHandle) -> Result<bool, String> {
+    let stopped = {
+        let mut runtime = RUNTIME.lock();
+        let stopped = runtime.timer.take().is_some();
+        runtime.generation += 1;
+        runtime.completed_in_cycle = 0;
+        stopped
+    };
+    if stopped {
+        emit_state(&app_handle, &None);
+    }
+    Ok(stopped)
+}
+
+/// 获取当前计时状态（没有计时时为空）
+#[tauri::command]
+pub async fn get_focus_state() -> Result<Option<FocusState>, String> {
+    Ok(snapshot(&RUNTIME.lock()))
+}
+
+/// 获取今天、本周与最近 `days` 天（默认 7 天）的专注统计
+#[tauri::command]
+pub async fn get_focus_stats(days: Option<u32>) -> Result<FocusStats, String> {
+    let days = days.unwrap_or(DEFAULT_STATS_DAYS).clamp(1, MAX_STATS_DAYS);
+    let today = clock::now().with_timezone(&Local).date_naive();
+    let week_start = today - ChronoDuration::days(i64::from(today.weekday().num_days_from_monday()));
+    let range_start = today - ChronoDuration::days(i64::from(days) - 1);
+    let first_day = range_start.min(week_start);
+
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let records = db
+        .pet_statistics_registry
+        .focus_sessions(local_midnight(first_day), clock::now().timestamp())
+        .await
+        .map_err(|e| format!("读取专注记录失败: {}", e))?;
+
+    let totals = focus_timer::daily_totals(&records, first_day, today, local_date);
+    let this_week = totals.iter().filter(|day| day.date >= week_start);
+    let (week_focus_secs, week_sessions) = this_week.fold((0, 0), |(secs, sessions), day| {
+        (secs + day.focus_secs, sessions + day.sessions)
+    });
+    let today_entry = totals.last().cloned().unwrap_or(FocusDay { date: today, focus_secs: 0, sessions: 0 });
+
+    Ok(FocusStats {
+        today: today_entry,
+        week_focus_secs,
+        week_sessions,
+        daily: totals.into_iter().filter(|day| day.date >= range_start).collect(),
+    })
+}
+
+/// 获取专注计时设置
+#[tauri::command]
+pub async fn get_focus_settings() -> Result<FocusSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存专注计时设置（进行中的计时按原时长继续）
+#[tauri::command]
+pub async fn update_focus_settings(settings: FocusSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize focus timer settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write focus timer settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
@@ -0,0 +1,375 @@
+//! # 唤醒词命令模块
+//!
+//! 可选的常驻唤醒词监听（默认关闭）。监听前必须获得麦克风权限并完成唤醒词录制；
+//! 监听期间托盘提示和 `hotword-listening-changed` 事件会持续标明麦克风正在使用。
+//! 检测到唤醒词后显示主窗口并打开快速对话的按住说话流程。
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::utils::audio_routing::AudioFeature;
+use crate::utils::hotword::{self, HotwordDetector, HotwordModel, HotwordSettings};
+use crate::utils::permission_checker::HardwareChecker;
+
+/// CPU 占用的统计周期
+const CPU_SAMPLE_PERIOD: Duration = Duration::from_secs(2);
+
+struct Listener {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+lazy_static::lazy_static! {
+    static ref LISTENER: Mutex<Option<Listener>> = Mutex::new(None);
+    static ref STATUS: RwLock<HotwordStatus> = RwLock::new(HotwordStatus::default());
+    /// 串行化模型文件的读写
+    static ref MODEL_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 唤醒词监听状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HotwordStatus {
+    pub listening: bool,
+    pub phrase: String,
+    pub enrolled_samples: usize,
+    pub model_ready: bool,
+    /// 当前评估间隔（毫秒），CPU 超出预算时自动增大
+    pub eval_interval_ms: u64,
+    /// 监听线程最近的 CPU 占用（单核百分比）
+    pub cpu_percent: f32,
+    pub last_detected_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+fn app_data_file(name: &str) -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join(name))
+}
+
+fn load_model() -> Result<HotwordModel, String> {
+    let path = app_data_file("hotword_model.json")?;
+    if !path.exists() {
+        return Ok(HotwordModel::default());
+    }
+    let json_data = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read hotword model: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse hotword model: {}", e))
+}
+
+fn save_model(model: &HotwordModel) -> Result<(), String> {
+    let json_data = serde_json::to_string(model)
+        .map_err(|e| format!("Failed to serialize hotword model: {}", e))?;
+    fs::write(app_data_file("hotword_model.json")?, json_data)
+        .map_err(|e| format!("Failed to write hotword model: {}", e))
+}
+
+fn refresh_model_status(model: &HotwordModel) {
+    let mut status = STATUS.write();
+    status.enrolled_samples = model.templates.len();
+    status.model_ready = model.is_ready();
+    status.phrase = hotword::current_settings().phrase;
+}
+
+/// 更新监听指示（托盘提示 + 前端事件）
+fn set_listening(app_handle: &AppHandle, listening: bool) {
+    STATUS.write().listening = listening;
+    let settings = hotword::current_settings();
+
+    if settings.show_indicator {
+        use crate::events::tray::helpers;
+        let tooltip = if listening {
+            format!("Zishu Sensei · 正在监听唤醒词「{}」", settings.phrase)
+        } else {
+            "Zishu Sensei".to_string()
+        };
+        if let Err(e) = helpers::update_tray_tooltip(app_handle, &tooltip) {
+            warn!("更新托盘提示失败: {}", e);
+        }
+    }
+
+    let _ = app_handle.emit_all("hotword-listening-changed", serde_json::json!({
+        "listening": listening,
+        "phrase": settings.phrase,
+        "show_indicator": settings.show_indicator,
+    }));
+}
+
+fn on_hotword_detected(app_handle: &AppHandle, distance: f32) {
+    let now = chrono::Utc::now().timestamp();
+    STATUS.write().last_detected_at = Some(now);
+    info!("检测到唤醒词 (距离 {:.3})", distance);
+
+    let _ = app_handle.emit_all("hotword-detected", serde_json::json!({
+        "distance": distance,
+        "timestamp": now,
+    }));
+
+    if let Some(window) = app_handle.get_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if let Err(e) = crate::commands::actions::dispatch_action(
+        app_handle,
+        "quick_chat.open",
+        Some(serde_json::json!({ "source": "hotword", "push_to_talk": true })),
+    ) {
+        warn!("唤醒快速对话失败: {}", e);
+    }
+}
+
+fn run_listener(
+    app_handle: AppHandle,
+    model: HotwordModel,
+    stop: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<(), String>>,
+) {
+    let (sender, receiver) = mpsc::channel();
+    // cpal::Stream 不能跨线程移动，必须在监听线程内创建并持有
+    let (_stream, channels, sample_rate) = match crate::commands::audio::build_sample_stream(AudioFeature::Hotword, sender) {
+        Ok(stream) => stream,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let settings = hotword::current_settings();
+    let mut detector = HotwordDetector::new(model, &settings);
+    let mut busy = Duration::ZERO;
+    let mut period_start = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        let samples = match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok(samples) => samples,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                STATUS.write().last_error = Some("麦克风输入已断开".to_string());
+                break;
+            }
+        };
+
+        let started = Instant::now();
+        let detected = detector.push(&hotword::to_mono_16k(&samples, channels, sample_rate));
+        busy += started.elapsed();
+
+        if let Some(distance) = detected {
+            on_hotword_detected(&app_handle, distance);
+        }
+
+        let wall = period_start.elapsed();
+        if wall >= CPU_SAMPLE_PERIOD {
+            let stride = hotword::adjust_eval_stride(detector.eval_stride(), busy, wall, settings.max_cpu_percent);
+            if stride != detector.eval_stride() {
+                info!("唤醒词评估间隔调整为 {} ms", stride * 10);
+                detector.set_eval_stride(stride);
+            }
+            {
+                let mut status = STATUS.write();
+                status.cpu_percent = busy.as_secs_f32() / wall.as_secs_f32() * 100.0;
+                status.eval_interval_ms = detector.eval_stride() as u64 * 10;
+            }
+            busy = Duration::ZERO;
+            period_start = Instant::now();
+        }
+    }
+
+    if !stop.load(Ordering::Relaxed) {
+        // 非主动停止（设备断开等）
+        LISTENER.lock().take();
+        set_listening(&app_handle, false);
+    }
+}
+
+/// 开始监听（需要麦克风权限和已录制的唤醒词）
+pub fn start_listening(app_handle: &AppHandle) -> Result<(), String> {
+    let mut listener = LISTENER.lock();
+    if listener.is_some() {
+        return Ok(());
+    }
+
+    HardwareChecker::check_microphone("feature", "hotword")
+        .map_err(|e| format!("唤醒词监听需要麦克风权限: {}", e))?;
+
+    let model = load_model()?;
+    refresh_model_status(&model);
+    if !model.is_ready() {
+        return Err(format!("请先录制至少 {} 遍唤醒词", hotword::MIN_TEMPLATES));
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread = {
+        let app_handle = app_handle.clone();
+        let stop = Arc::clone(&stop);
+        std::thread::Builder::new()
+            .name("hotword-listener".to_string())
+            .spawn(move || run_listener(app_handle, model, stop, ready_tx))
+            .map_err(|e| format!("启动监听线程失败: {}", e))?
+    };
+
+    match ready_rx.recv_timeout(Duration::from_secs(5)) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            STATUS.write().last_error = Some(e.clone());
+            return Err(e);
+        }
+        Err(_) => {
+            stop.store(true, Ordering::Relaxed);
+            return Err("打开麦克风超时".to_string());
+        }
+    }
+
+    *listener = Some(Listener { stop, thread });
+    {
+        let mut status = STATUS.write();
+        status.last_error = None;
+        status.eval_interval_ms = hotword::DEFAULT_EVAL_STRIDE as u64 * 10;
+    }
+    drop(listener);
+    set_listening(app_handle, true);
+    info!("唤醒词监听已启动");
+    Ok(())
+}
+
+/// 停止监听
+pub fn stop_listening(app_handle: &AppHandle) {
+    let listener = LISTENER.lock().take();
+    if let Some(listener) = listener {
+        listener.stop.store(true, Ordering::Relaxed);
+        if listener.thread.join().is_err() {
+            warn!("唤醒词监听线程异常退出");
+        }
+        set_listening(app_handle, false);
+        info!("唤醒词监听已停止");
+    }
+}
+
+/// 正在监听时重启监听线程（设备路由变化后重新选择输入设备）
+pub fn restart_listening(app_handle: &AppHandle) -> Result<(), String> {
+    if LISTENER.lock().is_none() {
+        return Ok(());
+    }
+    stop_listening(app_handle);
+    start_listening(app_handle)
+}
+
+/// 加载唤醒词设置，启用时开始监听（启动时调用）
+pub fn initialize_hotword(app_handle: &AppHandle) -> Result<(), String> {
+    let config_path = app_data_file("hotword_settings.json")?;
+    if config_path.exists() {
+        let json_data = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read hotword settings: {}", e))?;
+        let settings: HotwordSettings = serde_json::from_str(&json_data)
+            .map_err(|e| format!("Failed to parse hotword settings: {}", e))?;
+        hotword::set_settings(settings)?;
+    }
+
+    refresh_model_status(&load_model()?);
+    if hotword::current_settings().enabled {
+        start_listening(app_handle)?;
+    }
+    Ok(())
+}
+
+/// 获取唤醒词设置
+#[tauri::command]
+pub async fn get_hotword_settings() -> Result<HotwordSettings, String> {
+    Ok(hotword::current_settings())
+}
+
+/// 保存唤醒词设置，并按 `enabled` 启停监听
+#[tauri::command]
+pub async fn update_hotword_settings(
+    app_handle: AppHandle,
+    settings: HotwordSettings,
+) -> Result<HotwordStatus, String> {
+    hotword::set_settings(settings.clone())?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize hotword settings: {}", e))?;
+    fs::write(app_data_file("hotword_settings.json")?, json_data)
+        .map_err(|e| format!("Failed to write hotword settings: {}", e))?;
+
+    // 灵敏度、CPU 预算等参数在监听线程启动时读取，重启以生效
+    stop_listening(&app_handle);
+    if settings.enabled {
+        start_listening(&app_handle)?;
+    }
+    Ok(STATUS.read().clone())
+}
+
+/// 手动开始监听
+#[tauri::command]
+pub async fn start_hotword_listening(app_handle: AppHandle) -> Result<HotwordStatus, String> {
+    start_listening(&app_handle)?;
+    Ok(STATUS.read().clone())
+}
+
+/// 手动停止监听
+#[tauri::command]
+pub async fn stop_hotword_listening(app_handle: AppHandle) -> Result<HotwordStatus, String> {
+    stop_listening(&app_handle);
+    Ok(STATUS.read().clone())
+}
+
+/// 获取监听状态
+#[tauri::command]
+pub async fn get_hotword_status() -> Result<HotwordStatus, String> {
+    Ok(STATUS.read().clone())
+}
+
+/// 录制一遍唤醒词
+///
+/// `audio_data` 为 `stop_recording` 返回的 Base64 编码 16 位 PCM 单声道数据。
+#[tauri::command]
+pub async fn enroll_hotword_sample(
+    audio_data: String,
+    sample_rate: Option<u32>,
+) -> Result<HotwordStatus, String> {
+    let bytes = base64::decode(&audio_data).map_err(|e| format!("Base64 解码失败: {}", e))?;
+    let samples: Vec<f32> = bytes
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / i16::MAX as f32)
+        .collect();
+    let samples = hotword::to_mono_16k(&samples, 1, sample_rate.unwrap_or(hotword::SAMPLE_RATE));
+
+    let model = {
+        let _guard = MODEL_LOCK.lock();
+        let mut model = load_model()?;
+        model.phrase = hotword::current_settings().phrase;
+        model.enroll(&samples)?;
+        save_model(&model)?;
+        model
+    };
+    refresh_model_status(&model);
+    info!("已录制唤醒词样本 {}/{}", model.templates.len(), hotword::MIN_TEMPLATES);
+    Ok(STATUS.read().clone())
+}
+
+/// 删除已录制的唤醒词并停止监听
+#[tauri::command]
+pub async fn reset_hotword_model(app_handle: AppHandle) -> Result<HotwordStatus, String> {
+    stop_listening(&app_handle);
+    {
+        let _guard = MODEL_LOCK.lock();
+        let path = app_data_file("hotword_model.json")?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove hotword model: {}", e))?;
+        }
+    }
+    refresh_model_status(&HotwordModel::default());
+    Ok(STATUS.read().clone())
+}
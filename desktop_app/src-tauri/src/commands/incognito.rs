@@ -0,0 +1,165 @@
+//! # 无痕会话命令模块
+//!
+//! `start_incognito_session` 创建无痕会话（规则见 [`crate::utils::incognito`]），
+//! 会话在 `ChatState` 中带有 `incognito` 标记，消息只保存在本模块的内存中。
+//! 会话关闭或空闲超时后清除并发出 `incognito-session-ended` 事件（`reason` 为 `closed` 或 `timeout`）。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::state::ChatSession;
+use crate::utils::incognito::{self, IncognitoMessage, IncognitoSession, DEFAULT_TIMEOUT_SECS};
+use crate::utils::model_parameters::ParameterOverrides;
+use crate::utils::watchdog;
+use crate::AppState;
+
+/// 检查超时的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+const SWEEP_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+
+lazy_static! {
+    static ref SESSIONS: Mutex<HashMap<String, IncognitoSession>> = Mutex::new(HashMap::new());
+}
+
+/// 会话是否为无痕会话（按 ID 判断，会话被清除后依然成立）
+pub fn is_incognito(session_id: Option<&str>) -> bool {
+    session_id.is_some_and(incognito::is_incognito_id)
+}
+
+/// 无痕会话必须仍然存在；已结束的无痕会话不能继续发送消息
+pub fn ensure_active(session_id: &str) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    match SESSIONS.lock().get(session_id) {
+        Some(session) if !session.is_expired(now) => Ok(()),
+        _ => Err("无痕会话已结束".to_string()),
+    }
+}
+
+/// 在内存中记录一轮对话，返回用户消息 ID
+pub fn record_exchange(session_id: &str, user_message: &str, reply: &str) -> Option<String> {
+    let now = chrono::Utc::now().timestamp();
+    let mut sessions = SESSIONS.lock();
+    let session = sessions.get_mut(session_id)?;
+    let user_message_id = session.push("user", user_message, now);
+    session.push("assistant", reply, now);
+    Some(user_message_id)
+}
+
+/// 无痕会话的参数覆盖
+pub fn parameters(session_id: &str) -> ParameterOverrides {
+    SESSIONS
+        .lock()
+        .get(session_id)
+        .map(|session| session.parameters.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_parameters(session_id: &str, overrides: ParameterOverrides) -> Result<(), String> {
+    let mut sessions = SESSIONS.lock();
+    let session = sessions.get_mut(session_id).ok_or("无痕会话已结束")?;
+    session.parameters = overrides;
+    Ok(())
+}
+
+/// 清除会话的全部内容
+fn wipe(app_handle: &AppHandle, session_id: &str, reason: &str) -> bool {
+    let removed = SESSIONS.lock().remove(session_id).is_some();
+    app_handle.state::<AppState>().chat.remove_session(session_id);
+    if removed {
+        info!("无痕会话已清除 ({})", reason);
+        let _ = app_handle.emit_all("incognito-session-ended", serde_json::json!({
+            "session_id": session_id,
+            "reason": reason,
+        }));
+    }
+    removed
+}
+
+fn sweep_expired(app_handle: &AppHandle) {
+    let now = chrono::Utc::now().timestamp();
+    let expired: Vec<String> = SESSIONS
+        .lock()
+        .values()
+        .filter(|session| session.is_expired(now))
+        .map(|session| session.session_id.clone())
+        .collect();
+    for session_id in expired {
+        wipe(app_handle, &session_id, "timeout");
+    }
+}
+
+/// 启动无痕会话超时清理任务
+pub fn start_incognito_sweeper(app_handle: AppHandle) {
+    watchdog::supervise("incognito_sessions", SWEEP_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                sweep_expired(&app_handle);
+                tokio::time::sleep(SWEEP_INTERVAL).await;
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 开始无痕会话并设为当前会话
+#[tauri::command]
+pub async fn start_incognito_session(
+    app_handle: AppHandle,
+    character_id: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<ChatSession, String> {
+    let timeout_secs = timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    incognito::validate_timeout(timeout_secs)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let session = IncognitoSession::new(character_id, timeout_secs, now);
+    let chat_session = ChatSession {
+        session_id: session.session_id.clone(),
+        created_at: now,
+        last_activity: now,
+        message_count: 0,
+        model_id: None,
+        character_id: session.character_id.clone(),
+        incognito: true,
+    };
+    SESSIONS.lock().insert(session.session_id.clone(), session);
+    app_handle.state::<AppState>().chat.set_current_session(chat_session.clone());
+    Ok(chat_session)
+}
+
+/// 结束无痕会话并清除消息，返回会话是否存在
+#[tauri::command]
+pub async fn end_incognito_session(app_handle: AppHandle, session_id: String) -> Result<bool, String> {
+    if !incognito::is_incognito_id(&session_id) {
+        return Err("不是无痕会话".to_string());
+    }
+    Ok(wipe(&app_handle, &session_id, "closed"))
+}
+
+/// 获取无痕会话的消息
+#[tauri::command]
+pub async fn get_incognito_messages(session_id: String) -> Result<Vec<IncognitoMessage>, String> {
+    ensure_active(&session_id)?;
+    Ok(SESSIONS
+        .lock()
+        .get(&session_id)
+        .map(|session| session.messages.clone())
+        .unwrap_or_default())
+}
+
+/// 获取无痕会话的状态（不含消息），会话已结束时返回空
+#[tauri::command]
+pub async fn get_incognito_session(session_id: String) -> Result<Option<IncognitoSession>, String> {
+    Ok(SESSIONS.lock().get(&session_id).map(|session| IncognitoSession {
+        messages: Vec::new(),
+        ..session.clone()
+    }))
+}
@@ -0,0 +1,403 @@
+//! # 展示模式命令模块
+//!
+//! 管理副显示器上的全屏展示窗口（`kiosk`）：调度器按设置与计划时段开关窗口，
+//! 每个轮播周期向窗口推送 `kiosk-dashboard`（当前卡片与汇总数据），并定期推送
+//! `kiosk-jitter`（防烧屏偏移，由前端平移画面内容）。规则见 [`crate::utils::kiosk`]。
+
+use chrono::{Datelike, Local, Utc};
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, PhysicalPosition, Position, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::commands::statistics::{self, PetStatistics};
+use crate::system_monitor;
+use crate::utils::kiosk::{self, DashboardCard, KioskSettings, MonitorRect, WeatherLocation};
+use crate::utils::watchdog;
+
+pub const KIOSK_WINDOW_LABEL: &str = "kiosk";
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// 天气缓存时间
+const WEATHER_TTL: Duration = Duration::from_secs(30 * 60);
+const WEATHER_API: &str = "https://api.open-meteo.com/v1/forecast";
+
+lazy_static! {
+    static ref SETTINGS: RwLock<KioskSettings> = RwLock::new(KioskSettings::default());
+    static ref RUNTIME: Mutex<KioskRuntime> = Mutex::new(KioskRuntime::default());
+    static ref WEATHER_CACHE: Mutex<Option<(Instant, WeatherLocation, WeatherInfo)>> = Mutex::new(None);
+}
+
+#[derive(Debug, Default)]
+struct KioskRuntime {
+    /// 手动开关，计划时段切换时清除
+    manual: Option<bool>,
+    /// 上次检查时计划时段是否开启
+    scheduled: Option<bool>,
+    tick: u64,
+    jitter_step: u64,
+    last_rotate: Option<Instant>,
+    last_jitter: Option<Instant>,
+}
+
+/// 时钟卡片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockInfo {
+    pub time: String,
+    pub date: String,
+    /// 1 = 周一 … 7 = 周日
+    pub weekday: u32,
+    pub utc_offset_minutes: i32,
+}
+
+/// 天气卡片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherInfo {
+    pub location: String,
+    pub temperature_c: f64,
+    pub wind_speed_kmh: f64,
+    pub weather_code: u32,
+    pub description: String,
+    pub fetched_at: i64,
+}
+
+/// 系统状态卡片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSummary {
+    pub cpu_usage: f32,
+    pub memory_usage: f32,
+}
+
+/// 展示窗口使用的汇总数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KioskDashboard {
+    pub generated_at: i64,
+    /// 当前轮播到的卡片
+    pub card: DashboardCard,
+    pub clock: ClockInfo,
+    pub weather: Option<WeatherInfo>,
+    pub stats: Option<PetStatistics>,
+    pub system: Option<SystemSummary>,
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoResponse {
+    current_weather: OpenMeteoCurrent,
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoCurrent {
+    temperature: f64,
+    windspeed: f64,
+    weathercode: u32,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("kiosk_settings.json"))
+}
+
+/// 加载展示模式设置（启动时调用）
+pub fn initialize_kiosk() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read kiosk settings: {}", e))?;
+    let settings: KioskSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse kiosk settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 当前显示器列表
+fn monitors(app_handle: &AppHandle) -> Result<Vec<MonitorRect>, String> {
+    let window = app_handle
+        .windows()
+        .into_values()
+        .next()
+        .ok_or("没有可用的窗口")?;
+    let primary_name = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    let monitors = window
+        .available_monitors()
+        .map_err(|e| format!("获取显示器列表失败: {}", e))?;
+    Ok(monitors
+        .iter()
+        .map(|m| MonitorRect {
+            name: m.name().cloned(),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+            primary: primary_name.is_some() && m.name() == primary_name.as_ref(),
+        })
+        .collect())
+}
+
+fn open_kiosk_window(app_handle: &AppHandle, settings: &KioskSettings) -> Result<(), String> {
+    if app_handle.get_window(KIOSK_WINDOW_LABEL).is_some() {
+        return Ok(());
+    }
+    let monitors = monitors(app_handle)?;
+    let monitor = kiosk::choose_monitor(&monitors, settings.monitor.as_deref())
+        .ok_or("没有可用的副显示器")?
+        .clone();
+
+    let window = WindowBuilder::new(
+        app_handle,
+        KIOSK_WINDOW_LABEL,
+        WindowUrl::App("index.html#/kiosk".into()),
+    )
+    .title("Zishu Sensei - 展示模式")
+    .decorations(false)
+    .resizable(false)
+    .skip_taskbar(true)
+    .visible(false)
+    .build()
+    .map_err(|e| format!("创建展示窗口失败: {}", e))?;
+
+    // 先移动到目标显示器再全屏，全屏会占满窗口所在的显示器
+    window
+        .set_position(Position::Physical(PhysicalPosition::new(monitor.x, monitor.y)))
+        .map_err(|e| format!("移动展示窗口失败: {}", e))?;
+    window
+        .set_fullscreen(true)
+        .map_err(|e| format!("展示窗口全屏失败: {}", e))?;
+    let _ = window.show();
+    info!("展示模式已开启: {}", monitor.name.as_deref().unwrap_or("未命名显示器"));
+    Ok(())
+}
+
+fn close_kiosk_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window(KIOSK_WINDOW_LABEL) {
+        if let Err(e) = window.close() {
+            warn!("关闭展示窗口失败: {}", e);
+        } else {
+            info!("展示模式已关闭");
+        }
+    }
+}
+
+async fn fetch_weather(location: &WeatherLocation) -> Result<WeatherInfo, String> {
+    {
+        let cache = WEATHER_CACHE.lock();
+        if let Some((fetched, cached_location, weather)) = cache.as_ref() {
+            if cached_location == location && fetched.elapsed() < WEATHER_TTL {
+                return Ok(weather.clone());
+            }
+        }
+    }
+
+    let client = crate::http::proxy::client_builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response: OpenMeteoResponse = client
+        .get(WEATHER_API)
+        .query(&[
+            ("latitude", location.latitude.to_string()),
+            ("longitude", location.longitude.to_string()),
+            ("current_weather", "true".to_string()),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("获取天气失败: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("解析天气失败: {}", e))?;
+
+    let current = response.current_weather;
+    let weather = WeatherInfo {
+        location: location.name.clone(),
+        temperature_c: current.temperature,
+        wind_speed_kmh: current.windspeed,
+        weather_code: current.weathercode,
+        description: kiosk::weather_description(current.weathercode).to_string(),
+        fetched_at: Utc::now().timestamp(),
+    };
+    *WEATHER_CACHE.lock() = Some((Instant::now(), location.clone(), weather.clone()));
+    Ok(weather)
+}
+
+/// 汇总展示窗口的数据；单项数据获取失败时该项为空
+async fn collect_dashboard(app_handle: &AppHandle, card: DashboardCard) -> KioskDashboard {
+    let settings = SETTINGS.read().clone();
+    let now = Local::now();
+
+    let weather = match &settings.weather_location {
+        Some(location) => match fetch_weather(location).await {
+            Ok(weather) => Some(weather),
+            Err(e) => {
+                warn!("{}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let stats = match statistics::get_pet_statistics(None, None).await {
+        Ok(stats) => Some(stats),
+        Err(e) => {
+            warn!("获取展示模式统计失败: {}", e);
+            None
+        }
+    };
+    let system = system_monitor::get_system_monitor_stats(app_handle).map(|stats| SystemSummary {
+        cpu_usage: stats.cpu_usage,
+        memory_usage: stats.memory_usage,
+    });
+
+    KioskDashboard {
+        generated_at: now.timestamp(),
+        card,
+        clock: ClockInfo {
+            time: now.format("%H:%M").to_string(),
+            date: now.format("%Y-%m-%d").to_string(),
+            weekday: now.weekday().number_from_monday(),
+            utc_offset_minutes: now.offset().local_minus_utc() / 60,
+        },
+        weather,
+        stats,
+        system,
+    }
+}
+
+/// 一次调度：按计划开关窗口，窗口开启时轮播卡片与防烧屏平移
+async fn tick(app_handle: &AppHandle) {
+    let settings = SETTINGS.read().clone();
+    let now = Local::now();
+    let scheduled = settings.should_show(now.weekday(), now.time());
+
+    let (show, push_card, push_jitter) = {
+        let mut runtime = RUNTIME.lock();
+        if runtime.scheduled != Some(scheduled) {
+            runtime.scheduled = Some(scheduled);
+            runtime.manual = None;
+        }
+        let show = runtime.manual.unwrap_or(scheduled);
+        let due = |last: Option<Instant>, every: Duration| match last {
+            Some(t) => t.elapsed() >= every,
+            None => true,
+        };
+
+        let mut push_card = None;
+        let mut push_jitter = None;
+        if show {
+            if due(runtime.last_rotate, Duration::from_secs(settings.rotate_secs)) {
+                push_card = Some(settings.card_at(runtime.tick));
+                runtime.tick += 1;
+                runtime.last_rotate = Some(Instant::now());
+            }
+            if due(runtime.last_jitter, Duration::from_secs(settings.jitter_interval_mins * 60)) {
+                push_jitter = Some(kiosk::jitter_offset(runtime.jitter_step, settings.jitter_px));
+                runtime.jitter_step += 1;
+                runtime.last_jitter = Some(Instant::now());
+            }
+        } else {
+            runtime.last_rotate = None;
+            runtime.last_jitter = None;
+        }
+        (show, push_card, push_jitter)
+    };
+
+    if !show {
+        close_kiosk_window(app_handle);
+        return;
+    }
+    if let Err(e) = open_kiosk_window(app_handle, &settings) {
+        warn!("开启展示模式失败: {}", e);
+        return;
+    }
+    let Some(window) = app_handle.get_window(KIOSK_WINDOW_LABEL) else {
+        return;
+    };
+    if let Some(card) = push_card {
+        let dashboard = collect_dashboard(app_handle, card).await;
+        let _ = window.emit("kiosk-dashboard", &dashboard);
+    }
+    if let Some((x, y)) = push_jitter {
+        let _ = window.emit("kiosk-jitter", serde_json::json!({ "x": x, "y": y }));
+    }
+}
+
+/// 启动展示模式调度器
+pub fn start_kiosk_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("kiosk_mode", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tick(&app_handle).await;
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取展示模式设置
+#[tauri::command]
+pub async fn get_kiosk_settings() -> Result<KioskSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存展示模式设置（下一次调度时生效，换显示器时重新打开窗口）
+#[tauri::command]
+pub async fn update_kiosk_settings(app_handle: AppHandle, settings: KioskSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize kiosk settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write kiosk settings: {}", e))?;
+
+    let monitor_changed = SETTINGS.read().monitor != settings.monitor;
+    *SETTINGS.write() = settings;
+    if monitor_changed {
+        close_kiosk_window(&app_handle);
+    }
+    Ok(())
+}
+
+/// 手动开启或关闭展示模式，直到下一次计划时段切换；`None` 表示恢复按计划
+#[tauri::command]
+pub async fn set_kiosk_active(app_handle: AppHandle, active: Option<bool>) -> Result<(), String> {
+    RUNTIME.lock().manual = active;
+    tick(&app_handle).await;
+    Ok(())
+}
+
+/// 列出可用于展示模式的显示器
+#[tauri::command]
+pub async fn list_kiosk_monitors(app_handle: AppHandle) -> Result<Vec<MonitorRect>, String> {
+    monitors(&app_handle)
+}
+
+/// 获取展示窗口的汇总数据（窗口打开后立即获取一次，之后随轮播推送）
+#[tauri::command]
+pub async fn get_kiosk_dashboard(app_handle: AppHandle) -> Result<KioskDashboard, String> {
+    let card = {
+        let settings = SETTINGS.read();
+        settings.card_at(RUNTIME.lock().tick.saturating_sub(1))
+    };
+    Ok(collect_dashboard(&app_handle, card).await)
+}
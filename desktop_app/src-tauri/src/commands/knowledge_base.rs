@@ -0,0 +1,322 @@
+//! # 文档知识库命令模块
+//!
+//! `import_documents` 导入文件或文件夹中的 txt / md / pdf 文档：提取文本、切分为文本块，
+//! 用 `document_chunks` 集合配置的嵌入模型分批生成向量并写入 Qdrant。导入过程中发出
+//! `knowledge-import-progress` 事件，完成后发出 `knowledge-import-completed`。
+//!
+//! 已导入的文档记录在当前用户资料目录的 `knowledge_base.json` 中，内容没有变化的文件不会重复嵌入，
+//! 重新导入时文档变短多出的旧文本块会被删除。`query_knowledge_base` 按相似度返回文本块，
+//! 供聊天时作为上下文使用。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::sleep_learning;
+use crate::database::vector_search_service::{
+    document_chunk_point_id, Document, VectorSearchService, DOCUMENT_CHUNK_COLLECTION,
+};
+use crate::utils::get_profile_data_dir;
+use crate::utils::knowledge_base::{self, DocumentFormat};
+
+/// 每批嵌入的文本块数
+const EMBED_BATCH_SIZE: usize = 32;
+const MANIFEST_FILE: &str = "knowledge_base.json";
+const DEFAULT_QUERY_LIMIT: usize = 5;
+const MAX_QUERY_LIMIT: usize = 50;
+
+lazy_static::lazy_static! {
+    /// 同一时间只进行一次导入，避免记录文件被并发覆盖
+    static ref IMPORT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 已导入的文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedDocument {
+    pub path: String,
+    pub title: String,
+    pub format: DocumentFormat,
+    pub content_hash: String,
+    pub chunk_count: usize,
+    pub imported_at: i64,
+}
+
+/// 已导入文档的记录，按路径索引
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct KnowledgeManifest {
+    documents: BTreeMap<String, ImportedDocument>,
+}
+
+impl KnowledgeManifest {
+    fn path() -> Result<PathBuf, String> {
+        Ok(get_profile_data_dir()?.join(MANIFEST_FILE))
+    }
+
+    fn load() -> Result<Self, String> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(&path).map_err(|e| format!("读取知识库记录失败: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("解析知识库记录失败: {}", e))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("序列化知识库记录失败: {}", e))?;
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+        }
+        std::fs::write(path, json).map_err(|e| format!("保存知识库记录失败: {}", e))
+    }
+}
+
+/// 导入进度
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    /// 正在处理的文件
+    pub file: String,
+    pub files_done: usize,
+    pub files_total: usize,
+    /// 当前文件已写入的文本块数
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+}
+
+/// 导入失败的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// 新导入或内容变化后重新导入的文件数
+    pub imported: usize,
+    /// 内容没有变化而跳过的文件数
+    pub unchanged: usize,
+    pub failed: Vec<ImportFailure>,
+    /// 写入的文本块数
+    pub chunks: usize,
+}
+
+/// 知识库检索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeChunk {
+    pub source_path: String,
+    pub title: String,
+    pub chunk_index: usize,
+    pub content: String,
+    pub score: f32,
+}
+
+fn document_key(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 导入单个文件；内容没有变化时返回 `None`
+async fn import_file(
+    service: &VectorSearchService,
+    path: &Path,
+    previous: Option<&ImportedDocument>,
+    on_chunks: impl Fn(usize, usize),
+) -> Result<Option<ImportedDocument>, String> {
+    let format = DocumentFormat::from_path(path).ok_or("不支持的文件格式")?;
+    let file = path.to_path_buf();
+    let text = tokio::task::spawn_blocking(move || knowledge_base::extract_text(&file, format))
+        .await
+        .map_err(|e| format!("读取文件失败: {}", e))??;
+
+    let content_hash = knowledge_base::content_hash(&text);
+    if previous.is_some_and(|previous| previous.content_hash == content_hash) {
+        return Ok(None);
+    }
+
+    let chunks = knowledge_base::chunk_text(
+        &text,
+        knowledge_base::DEFAULT_CHUNK_SIZE,
+        knowledge_base::DEFAULT_CHUNK_OVERLAP,
+    );
+    if chunks.is_empty() {
+        return Err("文档中没有可提取的文本".to_string());
+    }
+
+    let key = document_key(path);
+    let title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| key.clone());
+    let now = chrono::Utc::now().timestamp();
+    let total = chunks.len();
+
+    for (batch_index, batch) in chunks.chunks(EMBED_BATCH_SIZE).enumerate() {
+        let vectors = service
+            .embed(DOCUMENT_CHUNK_COLLECTION, batch)
+            .await
+            .map_err(|e| format!("生成向量失败: {}", e))?;
+        let items = batch
+            .iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(offset, (content, vector))| {
+                let chunk_index = batch_index * EMBED_BATCH_SIZE + offset;
+                let document = Document {
+                    document_id: key.clone(),
+                    title: title.clone(),
+                    content: content.clone(),
+                    document_type: format.as_str().to_string(),
+                    author: None,
+                    tags: Vec::new(),
+                    created_at: now,
+                    metadata: Some(serde_json::json!({
+                        "source_path": key,
+                        "chunk_index": chunk_index,
+                        "chunk_count": total,
+                    })),
+                };
+                (document_chunk_point_id(&key, chunk_index), vector, document)
+            })
+            .collect();
+        service
+            .store_document_chunks(items)
+            .await
+            .map_err(|e| format!("写入向量库失败: {}", e))?;
+        on_chunks((batch_index * EMBED_BATCH_SIZE + batch.len()).min(total), total);
+    }
+
+    // 文档变短后多出的旧文本块
+    if let Some(previous) = previous {
+        for chunk_index in total..previous.chunk_count {
+            if let Err(e) = service.delete_document_chunk(&document_chunk_point_id(&key, chunk_index)).await {
+                warn!("删除旧文本块失败 {}#{}: {}", key, chunk_index, e);
+            }
+        }
+    }
+
+    Ok(Some(ImportedDocument {
+        path: key,
+        title,
+        format,
+        content_hash,
+        chunk_count: total,
+        imported_at: now,
+    }))
+}
+
+/// 按相似度检索知识库中的文本块（供聊天上下文使用）；向量库不可用时返回空列表
+pub(crate) async fn search(query: &str, limit: usize, min_score: f32) -> Result<Vec<KnowledgeChunk>, String> {
+    let Some(service) = sleep_learning::vector_service() else {
+        return Ok(Vec::new());
+    };
+    let vector = service
+        .embed_one(DOCUMENT_CHUNK_COLLECTION, query)
+        .await
+        .map_err(|e| format!("生成查询向量失败: {}", e))?;
+    let hits = service
+        .search_document_chunks(vector, limit)
+        .await
+        .map_err(|e| format!("检索知识库失败: {}", e))?;
+
+    Ok(hits
+        .into_iter()
+        .filter(|hit| hit.score >= min_score)
+        .filter_map(|hit| {
+            let document = serde_json::from_value::<Document>(hit.payload).ok()?;
+            let chunk_index = document
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("chunk_index"))
+                .and_then(|value| value.as_u64())
+                .unwrap_or_default() as usize;
+            Some(KnowledgeChunk {
+                source_path: document.document_id,
+                title: document.title,
+                chunk_index,
+                content: document.content,
+                score: hit.score,
+            })
+        })
+        .collect())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 导入文档（文件或文件夹），返回导入结果
+#[tauri::command]
+pub async fn import_documents(app_handle: AppHandle, paths: Vec<String>) -> Result<ImportReport, String> {
+    let service = sleep_learning::vector_service().ok_or("向量搜索未启用")?;
+    let _guard = IMPORT_LOCK.try_lock().map_err(|_| "正在导入文档".to_string())?;
+
+    let roots: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    let files = tokio::task::spawn_blocking(move || knowledge_base::collect_files(&roots))
+        .await
+        .map_err(|e| format!("扫描文件失败: {}", e))?;
+    info!("📚 开始导入 {} 个文档", files.len());
+
+    let mut manifest = KnowledgeManifest::load()?;
+    let mut report = ImportReport::default();
+    let files_total = files.len();
+
+    for (files_done, path) in files.iter().enumerate() {
+        let file = path.to_string_lossy().into_owned();
+        let emit_progress = |chunks_done: usize, chunks_total: usize| {
+            let _ = app_handle.emit_all("knowledge-import-progress", &ImportProgress {
+                file: file.clone(),
+                files_done,
+                files_total,
+                chunks_done,
+                chunks_total,
+            });
+        };
+        emit_progress(0, 0);
+
+        let previous = manifest.documents.get(&document_key(path));
+        match import_file(&service, path, previous, emit_progress).await {
+            Ok(Some(document)) => {
+                report.imported += 1;
+                report.chunks += document.chunk_count;
+                manifest.documents.insert(document.path.clone(), document);
+                // 每个文件完成后保存，中途失败时已导入的文件不会重复嵌入
+                manifest.save()?;
+            }
+            Ok(None) => report.unchanged += 1,
+            Err(error) => {
+                warn!("导入文档失败 {}: {}", file, error);
+                report.failed.push(ImportFailure { path: file, error });
+            }
+        }
+    }
+
+    info!(
+        "📚 文档导入完成: 导入 {}，未变化 {}，失败 {}，共 {} 个文本块",
+        report.imported,
+        report.unchanged,
+        report.failed.len(),
+        report.chunks
+    );
+    let _ = app_handle.emit_all("knowledge-import-completed", &report);
+    Ok(report)
+}
+
+/// 检索知识库，按相似度从高到低返回文本块
+#[tauri::command]
+pub async fn query_knowledge_base(
+    query: String,
+    limit: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<Vec<KnowledgeChunk>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).clamp(1, MAX_QUERY_LIMIT);
+    search(&query, limit, min_score.unwrap_or(0.0)).await
+}
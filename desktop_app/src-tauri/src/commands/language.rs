@@ -1,8 +1,21 @@
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
+use crate::utils::i18n;
+use crate::utils::language_detector::{self, Detection};
+
+/// 支持的语言代码
+pub const SUPPORTED_LANGUAGE_CODES: [&str; 4] = ["zh", "en", "ja", "ko"];
+
+lazy_static::lazy_static! {
+    /// 串行化会话语言覆盖文件的读写
+    static ref SESSION_LANGUAGES_LOCK: Mutex<()> = Mutex::new(());
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanguageSettings {
     pub language: String,
@@ -22,11 +35,8 @@ impl Default for LanguageSettings {
     }
 }
 
-fn get_language_config_path(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Failed to get app data directory")?;
+fn get_language_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     // 确保目录存在
     fs::create_dir_all(&app_data_dir)?;
@@ -39,11 +49,11 @@ pub async fn save_language_setting(
     app_handle: AppHandle,
     language: String,
 ) -> Result<(), String> {
-    let config_path = get_language_config_path(&app_handle)
+    let config_path = get_language_config_path()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
     
     // 读取现有设置或使用默认值
-    let mut settings = load_language_settings_internal(&app_handle)
+    let mut settings = load_language_settings_internal()
         .unwrap_or_default();
     
     // 更新语言设置
@@ -58,21 +68,18 @@ pub async fn save_language_setting(
         .map_err(|e| format!("Failed to write settings file: {}", e))?;
     
     println!("Language setting saved: {}", settings.language);
+    apply_language_settings(&app_handle, &settings);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn load_language_settings(
-    app_handle: AppHandle,
-) -> Result<LanguageSettings, String> {
-    load_language_settings_internal(&app_handle)
+pub async fn load_language_settings() -> Result<LanguageSettings, String> {
+    load_language_settings_internal()
         .map_err(|e| format!("Failed to load language settings: {}", e))
 }
 
-fn load_language_settings_internal(
-    app_handle: &AppHandle,
-) -> Result<LanguageSettings, Box<dyn std::error::Error>> {
-    let config_path = get_language_config_path(app_handle)?;
+fn load_language_settings_internal() -> Result<LanguageSettings, Box<dyn std::error::Error>> {
+    let config_path = get_language_config_path()?;
     
     if !config_path.exists() {
         // 如果配置文件不存在，返回默认设置
@@ -110,7 +117,7 @@ pub async fn update_language_settings(
     app_handle: AppHandle,
     settings: LanguageSettings,
 ) -> Result<(), String> {
-    let config_path = get_language_config_path(&app_handle)
+    let config_path = get_language_config_path()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
     
     let mut updated_settings = settings;
@@ -123,6 +130,7 @@ pub async fn update_language_settings(
         .map_err(|e| format!("Failed to write settings file: {}", e))?;
     
     println!("Language settings updated: {:?}", updated_settings);
+    apply_language_settings(&app_handle, &updated_settings);
     Ok(())
 }
 
@@ -130,7 +138,7 @@ pub async fn update_language_settings(
 pub async fn reset_language_settings(
     app_handle: AppHandle,
 ) -> Result<LanguageSettings, String> {
-    let config_path = get_language_config_path(&app_handle)
+    let config_path = get_language_config_path()
         .map_err(|e| format!("Failed to get config path: {}", e))?;
     
     let default_settings = LanguageSettings::default();
@@ -142,6 +150,7 @@ pub async fn reset_language_settings(
         .map_err(|e| format!("Failed to write settings file: {}", e))?;
     
     println!("Language settings reset to default");
+    apply_language_settings(&app_handle, &default_settings);
     Ok(default_settings)
 }
 
@@ -208,9 +217,23 @@ pub fn emit_language_changed_event(
     Ok(())
 }
 
+/// 切换后端文字的语言；语言变化时重建托盘菜单、更新窗口标题并发送 `language-changed` 事件
+pub fn apply_language_settings(app_handle: &AppHandle, settings: &LanguageSettings) {
+    let previous = i18n::language();
+    if !i18n::set_language(&settings.language, &settings.fallback_language) {
+        return;
+    }
+    if let Err(e) = crate::events::tray::helpers::refresh_localized_labels(app_handle) {
+        tracing::warn!("Failed to refresh localized labels: {}", e);
+    }
+    if let Err(e) = emit_language_changed_event(app_handle, &previous, &i18n::language()) {
+        tracing::warn!("Failed to emit language changed event: {}", e);
+    }
+}
+
 // 初始化语言设置
 pub async fn initialize_language_settings(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let settings = load_language_settings_internal(app_handle)?;
+    let mut settings = load_language_settings_internal()?;
     
     // 如果是首次运行且启用了自动检测
     if settings.auto_detect {
@@ -220,25 +243,182 @@ pub async fn initialize_language_settings(app_handle: &AppHandle) -> Result<(),
             println!("Auto-detected language: {}, current: {}", detected_language, settings.language);
             
             // 更新设置
-            let mut new_settings = settings.clone();
-            new_settings.language = detected_language.clone();
-            new_settings.updated_at = chrono::Utc::now().timestamp();
+            settings.language = detected_language;
+            settings.updated_at = chrono::Utc::now().timestamp();
             
-            let config_path = get_language_config_path(app_handle)?;
-            let json_data = serde_json::to_string_pretty(&new_settings)?;
+            let config_path = get_language_config_path()?;
+            let json_data = serde_json::to_string_pretty(&settings)?;
             fs::write(&config_path, json_data)?;
-            
-            // 发送语言变化事件
-            if let Err(e) = emit_language_changed_event(app_handle, &settings.language, &detected_language) {
-                eprintln!("Failed to emit language changed event: {}", e);
-            }
         }
     }
     
+    // 后端文字（托盘菜单、通知等）使用同一语言，语言变化时发送事件
+    apply_language_settings(app_handle, &settings);
     println!("Language settings initialized: {:?}", settings);
     Ok(())
 }
 
+// ================================
+// 按消息的语言检测与会话语言覆盖
+// ================================
+
+fn get_session_languages_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_language_config_path()?.with_file_name("session_languages.json"))
+}
+
+fn load_session_languages() -> Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    let path = get_session_languages_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json_data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&json_data)?)
+}
+
+/// 消息语言的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSource {
+    /// 会话级语言覆盖
+    Session,
+    /// 根据消息内容自动检测
+    Detected,
+    /// 全局语言设置
+    Global,
+}
+
+/// 消息语言解析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageLanguage {
+    /// 用于选择提示词和语音的语言
+    pub language: String,
+    pub source: LanguageSource,
+    /// 消息内容的检测结果（与会话覆盖无关，用于统计）
+    pub detected: Option<Detection>,
+    /// 对应的语音区域代码
+    pub voice_locale: String,
+}
+
+/// 解析消息应使用的语言：会话覆盖 > 自动检测（全局开启时）> 全局设置
+pub fn resolve_message_language(session_id: Option<&str>, text: &str) -> MessageLanguage {
+    let settings = load_language_settings_internal().unwrap_or_default();
+    let detected = language_detector::detect(text);
+    let session_language = session_id.and_then(|id| {
+        load_session_languages()
+            .ok()
+            .and_then(|mut overrides| overrides.remove(id))
+    });
+
+    let (language, source) = match (session_language, &detected) {
+        (Some(language), _) => (language, LanguageSource::Session),
+        (None, Some(detection)) if settings.auto_detect => (detection.language.clone(), LanguageSource::Detected),
+        _ => (settings.language.clone(), LanguageSource::Global),
+    };
+
+    MessageLanguage {
+        voice_locale: language_detector::voice_locale(&language).to_string(),
+        language,
+        source,
+        detected,
+    }
+}
+
+/// 设置会话语言（`None` 表示跟随全局设置）
+#[tauri::command]
+pub async fn set_session_language(
+    app_handle: AppHandle,
+    session_id: String,
+    language: Option<String>,
+) -> Result<(), String> {
+    if let Some(language) = &language {
+        if !SUPPORTED_LANGUAGE_CODES.contains(&language.as_str()) {
+            return Err(format!("Unsupported language: {}", language));
+        }
+    }
+
+    let _guard = SESSION_LANGUAGES_LOCK.lock();
+    let mut overrides = load_session_languages()
+        .map_err(|e| format!("Failed to load session languages: {}", e))?;
+    match &language {
+        Some(language) => overrides.insert(session_id.clone(), language.clone()),
+        None => overrides.remove(&session_id),
+    };
+
+    let path = get_session_languages_path()
+        .map_err(|e| format!("Failed to get config path: {}", e))?;
+    let json_data = serde_json::to_string_pretty(&overrides)
+        .map_err(|e| format!("Failed to serialize session languages: {}", e))?;
+    fs::write(&path, json_data)
+        .map_err(|e| format!("Failed to write session languages: {}", e))?;
+
+    let _ = app_handle.emit_all("session-language-changed", serde_json::json!({
+        "session_id": session_id,
+        "language": language,
+    }));
+    Ok(())
+}
+
+/// 获取会话语言覆盖
+#[tauri::command]
+pub async fn get_session_language(session_id: String) -> Result<Option<String>, String> {
+    Ok(load_session_languages()
+        .map_err(|e| format!("Failed to load session languages: {}", e))?
+        .remove(&session_id))
+}
+
+/// 检测文本语言
+#[tauri::command]
+pub async fn detect_text_language(text: String) -> Result<Option<Detection>, String> {
+    Ok(language_detector::detect(&text))
+}
+
+/// 单个语言的使用量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageUsage {
+    pub language: String,
+    pub message_count: i64,
+    pub ratio: f64,
+}
+
+/// 会话语言使用统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLanguageStats {
+    pub session_id: String,
+    pub total_messages: i64,
+    pub languages: Vec<LanguageUsage>,
+    pub session_language: Option<String>,
+}
+
+/// 统计会话中用户消息的语言分布
+#[tauri::command]
+pub async fn get_session_language_stats(
+    session_id: String,
+) -> Result<SessionLanguageStats, String> {
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let counts = db
+        .conversation_history
+        .get_language_stats(&session_id)
+        .await
+        .map_err(|e| format!("Failed to load language stats: {}", e))?;
+
+    let total_messages: i64 = counts.iter().map(|(_, count)| count).sum();
+    let languages = counts
+        .into_iter()
+        .map(|(language, message_count)| LanguageUsage {
+            language,
+            message_count,
+            ratio: if total_messages > 0 { message_count as f64 / total_messages as f64 } else { 0.0 },
+        })
+        .collect();
+
+    Ok(SessionLanguageStats {
+        session_language: get_session_language(session_id.clone()).await?,
+        session_id,
+        total_messages,
+        languages,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,306 @@
+//! # 口型同步播放命令
+//!
+//! `play_audio_with_lipsync` 通过 TTS 输出设备播放文件模块中的音频，同时按播放进度向
+//! Live2D 渲染窗口推送口型参数（`lipsync-frame` 频道，需先订阅），口型同时写入 Live2D
+//! 参数帧（见 `commands::live2d_parameters`）。口型分析与同步偏移设置见 [`crate::utils::lipsync`]。
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::Sample;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::audio::resolve_device;
+use crate::commands::event_subscription::{publish, CHANNEL_LIPSYNC_FRAME};
+use crate::commands::live2d_parameters;
+use crate::live2d_protocol::parameters::ParameterSource;
+use crate::utils::audio_routing::AudioFeature;
+use crate::utils::lipsync::{self, DecodedAudio, LipSyncSettings, MouthFrame};
+
+lazy_static::lazy_static! {
+    /// 当前播放的停止标志
+    static ref CURRENT_PLAYBACK: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+}
+
+/// 播放信息
+#[derive(Debug, Clone, Serialize)]
+pub struct LipSyncPlayback {
+    pub file_id: String,
+    pub duration_ms: u64,
+    pub frame_count: usize,
+    pub device: Option<String>,
+}
+
+/// 推送给渲染窗口的口型参数
+#[derive(Debug, Clone, Serialize)]
+struct LipSyncFrameEvent<'a> {
+    file_id: &'a str,
+    position_ms: u64,
+    #[serde(rename = "ParamMouthOpenY")]
+    mouth_open: f32,
+    #[serde(rename = "ParamMouthForm")]
+    mouth_form: f32,
+}
+
+fn get_lipsync_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("lipsync_settings.json"))
+}
+
+/// 从磁盘加载口型同步设置（启动时调用）
+pub fn initialize_lipsync_settings() -> Result<(), String> {
+    let config_path = get_lipsync_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read lipsync settings: {}", e))?;
+    let settings: LipSyncSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse lipsync settings: {}", e))?;
+    settings.validate()?;
+
+    lipsync::set_settings(settings);
+    Ok(())
+}
+
+pub(crate) fn stop_current_playback() -> bool {
+    match CURRENT_PLAYBACK.lock().take() {
+        Some(stop) => {
+            stop.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 在输出设备上建立播放流；`played` 记录已送入设备的采样数，播放完后填充静音
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    audio: Arc<DecodedAudio>,
+    played: Arc<AtomicUsize>,
+    app_handle: AppHandle,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            let start = played.fetch_add(data.len(), Ordering::SeqCst);
+            for (i, out) in data.iter_mut().enumerate() {
+                let sample = audio.samples.get(start + i).copied().unwrap_or(0.0);
+                *out = T::from_sample(sample.clamp(-1.0, 1.0));
+            }
+        },
+        move |err: cpal::StreamError| {
+            warn!("口型同步播放流错误: {}", err);
+            let _ = app_handle.emit_all("audio-device-error", serde_json::json!({
+                "feature": AudioFeature::TtsOutput,
+                "error": err.to_string(),
+            }));
+        },
+        None,
+    )
+}
+
+/// 按设备的采样格式建立并启动播放流
+fn start_output_stream(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    audio: Arc<DecodedAudio>,
+    played: Arc<AtomicUsize>,
+    app_handle: AppHandle,
+) -> Result<cpal::Stream, String> {
+    let stream_config: cpal::StreamConfig = config.clone().into();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_output_stream::<f32>(device, &stream_config, audio, played, app_handle),
+        cpal::SampleFormat::I16 => build_output_stream::<i16>(device, &stream_config, audio, played, app_handle),
+        cpal::SampleFormat::U16 => build_output_stream::<u16>(device, &stream_config, audio, played, app_handle),
+        _ => return Err("不支持的采样格式".to_string()),
+    }
+    .map_err(|e| format!("创建播放流失败: {}", e))?;
+    stream.play().map_err(|e| format!("启动播放失败: {}", e))?;
+    Ok(stream)
+}
+
+/// 播放线程：保持播放流并按播放进度推送口型参数
+fn run_playback(
+    app_handle: AppHandle,
+    file_id: String,
+    audio: DecodedAudio,
+    frames: Vec<MouthFrame>,
+    stop: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<Option<String>, String>>,
+) {
+    let setup = resolve_device(AudioFeature::TtsOutput).and_then(|(device, choice)| {
+        let config = device
+            .default_output_config()
+            .map_err(|e| format!("获取音频配置失败: {}", e))?;
+        Ok((device, choice, config))
+    });
+    let (device, choice, config) = match setup {
+        Ok(setup) => setup,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    let audio = Arc::new(audio.resample(config.sample_rate().0, config.channels()));
+    let total_samples = audio.samples.len();
+    let samples_per_ms = (config.sample_rate().0 as f64 * config.channels() as f64) / 1000.0;
+    let played = Arc::new(AtomicUsize::new(0));
+    let stream = match start_output_stream(&device, &config, audio, played.clone(), app_handle.clone()) {
+        Ok(stream) => stream,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(choice.device));
+
+    let _ = app_handle.emit_all("lipsync-started", serde_json::json!({ "file_id": file_id }));
+    let mut last_frame = None;
+    while !stop.load(Ordering::SeqCst) && played.load(Ordering::SeqCst) < total_samples {
+        // 每次读取最新设置，校准偏移时立即生效
+        let settings = lipsync::current_settings();
+        let position_ms = (played.load(Ordering::SeqCst) as f64 / samples_per_ms) as u64;
+        let frame = lipsync::frame_at(&frames, position_ms, settings.offset_ms).copied();
+        let (open, form) = frame.map_or((0.0, 0.0), |f| (f.open, f.form));
+        if last_frame != Some((open, form)) {
+            live2d_parameters::set_parameters(
+                ParameterSource::LipSync,
+                [("ParamMouthOpenY", open), ("ParamMouthForm", form)],
+            );
+            publish(
+                &app_handle,
+                CHANNEL_LIPSYNC_FRAME,
+                &LipSyncFrameEvent {
+                    file_id: &file_id,
+                    position_ms,
+                    mouth_open: open,
+                    mouth_form: form,
+                },
+            );
+            last_frame = Some((open, form));
+        }
+        std::thread::sleep(Duration::from_millis(1000 / settings.frame_rate.max(1) as u64));
+    }
+    drop(stream);
+
+    // 结束时闭嘴，口型交还给其他来源
+    live2d_parameters::release_parameters(ParameterSource::LipSync);
+    publish(
+        &app_handle,
+        CHANNEL_LIPSYNC_FRAME,
+        &LipSyncFrameEvent {
+            file_id: &file_id,
+            position_ms: (played.load(Ordering::SeqCst).min(total_samples) as f64 / samples_per_ms) as u64,
+            mouth_open: 0.0,
+            mouth_form: 0.0,
+        },
+    );
+    let stopped = stop.load(Ordering::SeqCst);
+    {
+        let mut current = CURRENT_PLAYBACK.lock();
+        if current.as_ref().is_some_and(|flag| Arc::ptr_eq(flag, &stop)) {
+            *current = None;
+        }
+    }
+    let _ = app_handle.emit_all("lipsync-finished", serde_json::json!({
+        "file_id": file_id,
+        "stopped": stopped,
+    }));
+}
+
+/// 播放 WAV 文件并同步角色口型，`file_id` 用于标识事件中的这次播放；会中断正在进行的口型同步播放
+pub(crate) async fn play_wav_with_lipsync(
+    app_handle: AppHandle,
+    file_id: String,
+    path: PathBuf,
+) -> Result<LipSyncPlayback, String> {
+    let audio = tauri::async_runtime::spawn_blocking(move || lipsync::decode_wav(&path))
+        .await
+        .map_err(|e| format!("解码音频失败: {}", e))??;
+    if audio.frame_count() == 0 {
+        return Err("音频文件为空".to_string());
+    }
+
+    let settings = lipsync::current_settings();
+    let frames = lipsync::analyze(&audio.to_mono(), audio.sample_rate, &settings);
+    let duration_ms = audio.duration_ms();
+    let frame_count = frames.len();
+
+    stop_current_playback();
+    let stop = Arc::new(AtomicBool::new(false));
+    *CURRENT_PLAYBACK.lock() = Some(stop.clone());
+
+    // cpal 的 Stream 不能跨线程移动，在播放线程中创建
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread_file_id = file_id.clone();
+    std::thread::Builder::new()
+        .name("lipsync-playback".to_string())
+        .spawn(move || run_playback(app_handle, thread_file_id, audio, frames, stop, ready_tx))
+        .map_err(|e| format!("启动播放线程失败: {}", e))?;
+
+    let device = ready_rx
+        .recv()
+        .map_err(|_| "播放线程意外退出".to_string())?
+        .map_err(|e| {
+            stop_current_playback();
+            e
+        })?;
+    info!("口型同步播放开始: {} ({} ms, {} 帧)", file_id, duration_ms, frame_count);
+
+    Ok(LipSyncPlayback {
+        file_id,
+        duration_ms,
+        frame_count,
+        device,
+    })
+}
+
+/// 播放音频文件并同步角色口型（目前支持 WAV）；会中断正在进行的口型同步播放
+#[tauri::command]
+pub async fn play_audio_with_lipsync(app_handle: AppHandle, file_id: String) -> Result<LipSyncPlayback, String> {
+    let path = crate::commands::file::resolve_file_path(&app_handle, &file_id)?;
+    play_wav_with_lipsync(app_handle, file_id, path).await
+}
+
+/// 停止口型同步播放，返回是否有正在进行的播放
+#[tauri::command]
+pub async fn stop_lipsync() -> Result<bool, String> {
+    Ok(stop_current_playback())
+}
+
+/// 获取口型同步设置
+#[tauri::command]
+pub async fn get_lipsync_settings() -> Result<LipSyncSettings, String> {
+    Ok(lipsync::current_settings())
+}
+
+/// 保存口型同步设置（包括同步偏移校准），播放中立即生效
+#[tauri::command]
+pub async fn update_lipsync_settings(settings: LipSyncSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize lipsync settings: {}", e))?;
+    fs::write(get_lipsync_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write lipsync settings: {}", e))?;
+
+    lipsync::set_settings(settings);
+    Ok(())
+}
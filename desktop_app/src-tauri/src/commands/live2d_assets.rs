@@ -3,11 +3,19 @@
 //! Goal: download model manifest + required files into a user cache directory,
 //! then serve them to the WebView via a custom scheme (zishu://live2d/...).
 
+use lazy_static::lazy_static;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
 use tracing::{info, warn};
 
 use crate::commands::CommandResponse;
+use crate::database::character_registry::CharacterData;
+use crate::utils::live2d_watcher::{self, ModelChanges, MANIFEST_FILE, MODELS_DIR};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrepareLive2DResult {
@@ -22,7 +30,7 @@ pub(crate) async fn ensure_live2d_model_cached_best_effort(model_id: &str) -> Re
         .await
         .map_err(|e| format!("Failed to create live2d cache dir: {}", e))?;
 
-    let client = reqwest::Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -348,7 +356,28 @@ async fn ensure_default_model_cached(
 }
 
 #[tauri::command]
-pub async fn prepare_live2d_assets(_app: AppHandle) -> Result<CommandResponse<PrepareLive2DResult>, String> {
+pub async fn prepare_live2d_assets() -> Result<CommandResponse<PrepareLive2DResult>, String> {
+    use crate::utils::startup_profiler::{self, BootPhase};
+
+    // 启动后第一次准备资源计入启动耗时
+    if startup_profiler::has_span(BootPhase::Live2DAssets) {
+        return prepare_assets().await;
+    }
+    let span = startup_profiler::start(BootPhase::Live2DAssets);
+    let result = prepare_assets().await;
+    match &result {
+        Ok(_) => span.finish(),
+        Err(e) => span.fail(e.clone()),
+    }
+    if startup_profiler::is_ready() {
+        if let Err(e) = crate::commands::startup::save_startup_profile() {
+            warn!("保存启动耗时记录失败: {}", e);
+        }
+    }
+    result
+}
+
+async fn prepare_assets() -> Result<CommandResponse<PrepareLive2DResult>, String> {
     let cache_root = get_live2d_cache_dir()?;
     tokio::fs::create_dir_all(&cache_root)
         .await
@@ -359,7 +388,7 @@ pub async fn prepare_live2d_assets(_app: AppHandle) -> Result<CommandResponse<Pr
     // If we already have a manifest cached, we can operate offline.
     let mut used_remote = false;
 
-    let client = reqwest::Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -418,3 +447,230 @@ pub async fn prepare_live2d_assets(_app: AppHandle) -> Result<CommandResponse<Pr
         "Live2D assets prepared".to_string(),
     ))
 }
+
+// ================================
+// 模型热重载
+// ================================
+
+/// 文件变化归并窗口：最后一次变化后静默这么久才重新注册
+const HOT_RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+lazy_static! {
+    /// 保持文件监控存活
+    static ref MODEL_WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+}
+
+/// 发送给前端的模型变化事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterModelsChanged {
+    /// 重新注册的模型
+    pub model_ids: Vec<String>,
+    /// 目录已删除、未重新注册的模型
+    pub removed_ids: Vec<String>,
+    pub manifest_changed: bool,
+}
+
+fn models_root() -> Result<PathBuf, String> {
+    Ok(get_live2d_cache_dir()?.join(MODELS_DIR))
+}
+
+/// 清单中的全部模型（字段与启动时导入一致）
+fn manifest_characters(root: &Path) -> Result<Vec<CharacterData>, String> {
+    let manifest_path = root.join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read cached models.json: {}", e))?;
+    let manifest: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse models.json: {}", e))?;
+
+    Ok(manifest["models"]
+        .as_array()
+        .map(|models| models.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|model| {
+            let id = model["id"].as_str().filter(|id| !id.is_empty())?;
+            let name = model["name"].as_str().unwrap_or(id);
+            Some(CharacterData {
+                id: id.to_string(),
+                name: name.to_string(),
+                display_name: model["displayName"].as_str().unwrap_or(name).to_string(),
+                path: model["path"].as_str().unwrap_or("").to_string(),
+                preview_image: model["previewImage"].as_str().map(str::to_string),
+                description: model["description"].as_str().unwrap_or("").to_string(),
+                gender: model["gender"].as_str().unwrap_or("neutral").to_string(),
+                size: model["size"].as_str().unwrap_or("0").to_string(),
+                features: model["features"]
+                    .as_array()
+                    .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default(),
+                motions: vec![],
+                expressions: vec![],
+                is_active: false,
+            })
+        })
+        .collect())
+}
+
+/// 重新注册变化的模型，返回发送给前端的事件
+async fn reload_models(root: &Path, changes: &ModelChanges) -> Result<CharacterModelsChanged, String> {
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let manifest = manifest_characters(root)?;
+
+    let mut ids: Vec<String> = changes.model_ids.iter().cloned().collect();
+    if changes.manifest_changed {
+        ids.extend(manifest.iter().map(|c| c.id.clone()).filter(|id| !changes.model_ids.contains(id)));
+    }
+
+    let mut reloaded = Vec::new();
+    let mut removed = Vec::new();
+    for id in ids {
+        let scanned = live2d_watcher::scan_model(root, &id)?;
+        let existing = db
+            .character_registry
+            .get_character_async(&id)
+            .await
+            .map_err(|e| format!("读取角色 {} 失败: {}", id, e))?;
+        let from_manifest = manifest.iter().find(|c| c.id == id).cloned();
+
+        // 已注册的角色保留名称、激活状态等信息；清单变化时以清单为准
+        let base = match (existing, from_manifest) {
+            (Some(existing), Some(listed)) if changes.manifest_changed => CharacterData {
+                is_active: existing.is_active,
+                ..listed
+            },
+            (Some(existing), _) => existing,
+            (None, Some(listed)) => listed,
+            (None, None) => match &scanned {
+                Some(model) => CharacterData {
+                    id: id.clone(),
+                    name: id.clone(),
+                    display_name: id.clone(),
+                    path: model.path.clone(),
+                    preview_image: None,
+                    description: String::new(),
+                    gender: "neutral".to_string(),
+                    size: "0".to_string(),
+                    features: vec![],
+                    motions: vec![],
+                    expressions: vec![],
+                    is_active: false,
+                },
+                None => {
+                    removed.push(id);
+                    continue;
+                }
+            },
+        };
+
+        let character = match scanned {
+            Some(model) => CharacterData {
+                path: model.path,
+                motions: model.motions,
+                expressions: model.expressions,
+                ..base
+            },
+            // 模型目录不存在：只有清单里有它时才更新清单信息
+            None if changes.manifest_changed && manifest.iter().any(|c| c.id == id) => base,
+            None => {
+                removed.push(id);
+                continue;
+            }
+        };
+
+        db.character_registry
+            .register_character_async(character)
+            .await
+            .map_err(|e| format!("重新注册角色 {} 失败: {}", id, e))?;
+        reloaded.push(id);
+    }
+    if !reloaded.is_empty() {
+        crate::commands::cache::invalidate(crate::commands::cache::CachedCommand::Characters).await;
+    }
+
+    Ok(CharacterModelsChanged {
+        model_ids: reloaded,
+        removed_ids: removed,
+        manifest_changed: changes.manifest_changed,
+    })
+}
+
+async fn apply_model_changes(app_handle: &AppHandle, root: &Path, changes: ModelChanges) {
+    match reload_models(root, &changes).await {
+        Ok(event) => {
+            info!(
+                "Live2D 模型热重载: 重新注册 {:?}，已删除 {:?}",
+                event.model_ids, event.removed_ids
+            );
+            if let Err(e) = app_handle.emit_all("character-models-changed", &event) {
+                warn!("发送模型变化事件失败: {}", e);
+            }
+        }
+        Err(e) => warn!("Live2D 模型热重载失败: {}", e),
+    }
+}
+
+/// 开始监控 `live2d_models` 目录，模型变化时重新注册并通知前端
+pub fn start_model_watcher(app_handle: AppHandle) -> Result<(), String> {
+    let root = models_root()?;
+    std::fs::create_dir_all(&root).map_err(|e| format!("Failed to create live2d models dir: {}", e))?;
+
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+        Ok(event) if !event.kind.is_access() => {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Live2D 模型目录监控出错: {}", e),
+    })
+    .map_err(|e| format!("创建文件监控失败: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("监控 {} 失败: {}", root.display(), e))?;
+    *MODEL_WATCHER.lock() = Some(watcher);
+
+    std::thread::Builder::new()
+        .name("live2d-model-watcher".to_string())
+        .spawn(move || {
+            while let Ok(path) = rx.recv() {
+                let mut changes = ModelChanges::default();
+                changes.record(&root, &path);
+                // 归并一次保存产生的多个事件
+                while let Ok(path) = rx.recv_timeout(HOT_RELOAD_DEBOUNCE) {
+                    changes.record(&root, &path);
+                }
+                if !changes.is_empty() {
+                    tauri::async_runtime::block_on(apply_model_changes(&app_handle, &root, changes));
+                }
+            }
+        })
+        .map_err(|e| format!("启动模型监控线程失败: {}", e))?;
+
+    info!("Live2D 模型热重载已启动");
+    Ok(())
+}
+
+/// 手动重新扫描全部模型（清单与所有模型目录）
+#[tauri::command]
+pub async fn rescan_live2d_models(app_handle: AppHandle) -> Result<CharacterModelsChanged, String> {
+    let root = models_root()?;
+    let mut changes = ModelChanges {
+        manifest_changed: true,
+        ..ModelChanges::default()
+    };
+    if let Ok(entries) = std::fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                changes.record(&root, &entry.path());
+            }
+        }
+    }
+
+    let event = reload_models(&root, &changes).await?;
+    let _ = app_handle.emit_all("character-models-changed", &event);
+    Ok(event)
+}
@@ -0,0 +1,210 @@
+//! # Live2D 参数命令模块
+//!
+//! 后端以固定帧率向渲染窗口推送 Live2D 参数帧（`live2d-parameters` 频道，需先订阅；
+//! 帧格式与来源优先级见 [`crate::live2d_protocol::parameters`]）。
+//!
+//! - 视线跟随与待机呼吸由刷新线程按 [`MotionSettings`] 计算
+//! - 口型同步与行为引擎在进程内调用 [`set_parameters`]，不经过 IPC
+//! - 前端与适配器通过 `set_live2d_parameter` / `set_live2d_parameters` 手动设置，优先级最高
+//!
+//! 没有窗口订阅时刷新线程只保持心跳，不计算参数。
+
+use lazy_static::lazy_static;
+use mouse_position::mouse_position::Mouse;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+use crate::commands::event_subscription::{publish, CHANNEL_LIVE2D_PARAMETERS};
+use crate::live2d_protocol::parameters::{self, MotionSettings, ParameterMixer, ParameterSource};
+use crate::utils::{event_subscription, watchdog};
+
+/// 显示模型的窗口
+const MODEL_WINDOW: &str = "main";
+const TICKER_STALL_AFTER: Duration = Duration::from_secs(10);
+
+lazy_static! {
+    static ref MIXER: Mutex<ParameterMixer> = Mutex::new(ParameterMixer::default());
+    static ref MOTION_SETTINGS: RwLock<MotionSettings> = RwLock::new(MotionSettings::default());
+}
+
+/// 设置来源的参数值（口型同步、行为引擎等进程内调用）
+pub fn set_parameters<'a>(source: ParameterSource, params: impl IntoIterator<Item = (&'a str, f32)>) {
+    let mut mixer = MIXER.lock();
+    for (id, value) in params {
+        mixer.set(source, id, value);
+    }
+}
+
+/// 来源不再控制任何参数
+pub fn release_parameters(source: ParameterSource) {
+    MIXER.lock().clear(source);
+}
+
+fn get_motion_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("live2d_motion_settings.json"))
+}
+
+/// 从磁盘加载动作设置（启动时调用）
+pub fn initialize_motion_settings() -> Result<(), String> {
+    let config_path = get_motion_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read Live2D motion settings: {}", e))?;
+    let settings: MotionSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse Live2D motion settings: {}", e))?;
+    settings.validate()?;
+
+    *MOTION_SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 模型窗口中心（物理像素）
+fn model_center(app_handle: &AppHandle) -> Option<(f64, f64)> {
+    let window = app_handle.get_window(MODEL_WINDOW)?;
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    Some((
+        position.x as f64 + size.width as f64 / 2.0,
+        position.y as f64 + size.height as f64 / 2.0,
+    ))
+}
+
+/// 更新视线跟随与呼吸，推送一帧
+fn tick(app_handle: &AppHandle, settings: &MotionSettings, elapsed: Duration) {
+    let cursor = match Mouse::get_mouse_position() {
+        Mouse::Position { x, y } => Some((x as f64, y as f64)),
+        Mouse::Error => None,
+    };
+    let tracking = settings
+        .eye_tracking
+        .then(|| cursor.zip(model_center(app_handle)))
+        .flatten()
+        .map(|(cursor, center)| parameters::eye_tracking(cursor, center, settings.tracking_reach));
+
+    let frame = {
+        let mut mixer = MIXER.lock();
+        match tracking {
+            Some(params) => {
+                for (id, value) in params {
+                    mixer.set(ParameterSource::EyeTracking, id, value);
+                }
+            }
+            None => mixer.clear(ParameterSource::EyeTracking),
+        }
+        if settings.breathing_amplitude > 0.0 {
+            let breath = parameters::breathing(elapsed.as_secs_f64(), settings.breathing_amplitude);
+            mixer.set(ParameterSource::Idle, "ParamBreath", breath);
+        } else {
+            mixer.clear(ParameterSource::Idle);
+        }
+        mixer.next_frame(elapsed.as_millis() as u64)
+    };
+    if let Some(frame) = frame {
+        publish(app_handle, CHANNEL_LIVE2D_PARAMETERS, &frame);
+    }
+}
+
+/// 启动参数刷新线程
+pub fn start_parameter_ticker(app_handle: AppHandle) {
+    watchdog::supervise("live2d_parameters", TICKER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        let spawned = std::thread::Builder::new()
+            .name("live2d-parameters".to_string())
+            .spawn(move || {
+                let started = Instant::now();
+                let mut subscribed = false;
+                while heartbeat.beat() {
+                    let settings = MOTION_SETTINGS.read().clone();
+                    let has_subscribers = event_subscription::has_subscribers(CHANNEL_LIVE2D_PARAMETERS);
+                    if has_subscribers && !subscribed {
+                        // 新订阅的窗口需要完整的参数
+                        MIXER.lock().invalidate();
+                    }
+                    subscribed = has_subscribers;
+                    if has_subscribers {
+                        tick(&app_handle, &settings, started.elapsed());
+                    }
+                    std::thread::sleep(Duration::from_millis(1000 / settings.frame_rate.max(1) as u64));
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("启动 Live2D 参数刷新线程失败: {}", e);
+        }
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 手动设置单个参数，直到被释放
+#[tauri::command]
+pub async fn set_live2d_parameter(id: String, value: f32) -> Result<f32, String> {
+    parameters::validate_parameter_id(&id)?;
+    set_parameters(ParameterSource::Manual, [(id.as_str(), value)]);
+    Ok(parameters::clamp(&id, value))
+}
+
+/// 一次手动设置多个参数
+#[tauri::command]
+pub async fn set_live2d_parameters(params: HashMap<String, f32>) -> Result<(), String> {
+    for id in params.keys() {
+        parameters::validate_parameter_id(id)?;
+    }
+    set_parameters(ParameterSource::Manual, params.iter().map(|(id, value)| (id.as_str(), *value)));
+    Ok(())
+}
+
+/// 释放手动设置的参数（未指定时释放全部），交还给其他来源
+#[tauri::command]
+pub async fn release_live2d_parameters(ids: Option<Vec<String>>) -> Result<(), String> {
+    match ids {
+        Some(ids) => {
+            let mut mixer = MIXER.lock();
+            for id in &ids {
+                mixer.release(ParameterSource::Manual, id);
+            }
+        }
+        None => release_parameters(ParameterSource::Manual),
+    }
+    Ok(())
+}
+
+/// 当前合并后的全部参数（渲染窗口加载后用于同步）
+#[tauri::command]
+pub async fn get_live2d_parameters() -> Result<BTreeMap<String, f32>, String> {
+    Ok(MIXER.lock().resolve())
+}
+
+/// 获取动作设置
+#[tauri::command]
+pub async fn get_live2d_motion_settings() -> Result<MotionSettings, String> {
+    Ok(MOTION_SETTINGS.read().clone())
+}
+
+/// 保存动作设置，立即生效
+#[tauri::command]
+pub async fn update_live2d_motion_settings(settings: MotionSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize Live2D motion settings: {}", e))?;
+    fs::write(get_motion_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write Live2D motion settings: {}", e))?;
+
+    *MOTION_SETTINGS.write() = settings;
+    Ok(())
+}
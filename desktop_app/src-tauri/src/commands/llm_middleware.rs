@@ -0,0 +1,400 @@
+//! # LLM 中间件命令模块
+//!
+//! 聊天命令组装好上下文与用户消息后调用 [`run_before_request`]，按设置中的顺序依次执行启用的中间件；
+//! 收到回复后调用 [`run_after_response`]，按相反顺序处理回复文本。安全过滤失败会取消发送，
+//! 其余中间件失败只记录日志并跳过。费用确认不属于管道，始终在管道之后检查。
+//! 步骤与选项见 [`crate::utils::llm_middleware`]。
+//!
+//! 长期记忆中间件注入的过往会话摘要由 [`crate::commands::session_memory`] 生成与检索，
+//! 关闭该中间件时也不再自动生成摘要。
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::commands::chat::get_current_prompt_internal;
+use crate::commands::language::{LanguageSource, MessageLanguage};
+use crate::commands::prompt::render_for_chat;
+use crate::commands::search::{self, HybridSearchRequest};
+use crate::commands::session_memory;
+use crate::database::hybrid_search::FusionMethod;
+use crate::utils::bridge::{ChatMessage, MessageRole};
+use crate::utils::data_masking::DataMasker;
+use crate::utils::language_detector;
+use crate::utils::llm_middleware::{
+    estimate_prompt_tokens, find_blocked_term, insert_system, trim_to_budget, MemoryOptions, MiddlewareKind,
+    MiddlewareMetrics, MiddlewareSettings,
+};
+
+/// RAG 注入的单个片段最多保留的字符数
+const MAX_SNIPPET_CHARS: usize = 300;
+
+lazy_static! {
+    static ref SETTINGS: RwLock<MiddlewareSettings> = RwLock::new(MiddlewareSettings::default());
+    static ref METRICS: Mutex<HashMap<MiddlewareKind, MiddlewareMetrics>> = Mutex::new(HashMap::new());
+}
+
+/// 一次聊天请求在管道中的状态
+pub struct LlmRequestContext {
+    pub app: AppHandle,
+    pub session_id: Option<String>,
+    pub character_id: Option<String>,
+    pub model: Option<String>,
+    pub use_local_llm: bool,
+    pub language: MessageLanguage,
+    /// 角色绑定的提示词
+    pub bound_prompt: Option<String>,
+    pub user_message: String,
+    /// 发往模型的消息（上下文与当前用户消息）
+    pub messages: Vec<ChatMessage>,
+    pub started_at: Instant,
+    /// 无痕会话：不写日志
+    pub incognito: bool,
+}
+
+/// 中间件
+#[async_trait]
+pub trait LlmMiddleware: Send + Sync {
+    /// 发送前修改请求；返回错误时由管道决定取消发送或跳过
+    async fn before_request(&self, ctx: &mut LlmRequestContext, settings: &MiddlewareSettings) -> Result<(), String>;
+
+    /// 收到回复后修改回复文本
+    async fn after_response(
+        &self,
+        _ctx: &LlmRequestContext,
+        _reply: &mut String,
+        _settings: &MiddlewareSettings,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// 本地模型叠加当前提示词与角色设定
+struct PromptLayering;
+
+#[async_trait]
+impl LlmMiddleware for PromptLayering {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, _settings: &MiddlewareSettings) -> Result<(), String> {
+        if !ctx.use_local_llm {
+            return Ok(());
+        }
+        match get_current_prompt_internal(Some(&ctx.language.language), ctx.bound_prompt.as_deref()).await? {
+            Some(prompt) => {
+                let prompt = render_for_chat(
+                    &ctx.app,
+                    prompt,
+                    ctx.character_id.as_deref(),
+                    Some(&ctx.language.language),
+                )
+                .await;
+                let content = format!("{}\n\n{}", prompt.content, prompt.character_setting.unwrap_or_default());
+                ctx.messages.insert(0, ChatMessage { role: MessageRole::System, content });
+                info!("已应用Prompt: {}", prompt.name);
+            }
+            None => warn!("未找到当前使用的Prompt，将使用默认行为"),
+        }
+        Ok(())
+    }
+}
+
+/// 检索相关的历史对话片段作为参考上下文
+struct RagInjection;
+
+#[async_trait]
+impl LlmMiddleware for RagInjection {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, settings: &MiddlewareSettings) -> Result<(), String> {
+        let options = &settings.rag;
+        let response = search::run_hybrid_search(HybridSearchRequest {
+            query: ctx.user_message.clone(),
+            collection: "conversations".to_string(),
+            session_id: options.same_session_only.then(|| ctx.session_id.clone()).flatten(),
+            // 多取一条，当前消息可能已被索引
+            limit: options.limit + 1,
+            fusion: FusionMethod::default(),
+            use_keyword: true,
+            use_vector: true,
+        })
+        .await?;
+
+        let snippets: Vec<String> = response
+            .hits
+            .into_iter()
+            .filter(|hit| hit.score >= options.min_score && hit.content.trim() != ctx.user_message.trim())
+            .take(options.limit)
+            .map(|hit| format!("- {}", hit.content.chars().take(MAX_SNIPPET_CHARS).collect::<String>()))
+            .collect();
+        if snippets.is_empty() {
+            return Ok(());
+        }
+        insert_system(
+            &mut ctx.messages,
+            format!("以下是与当前话题相关的历史对话片段，仅供参考：\n{}", snippets.join("\n")),
+        );
+        Ok(())
+    }
+}
+
+/// 注入与当前消息相关的过往会话摘要
+struct LongTermMemory;
+
+#[async_trait]
+impl LlmMiddleware for LongTermMemory {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, settings: &MiddlewareSettings) -> Result<(), String> {
+        let options = &settings.memory;
+        let summaries = session_memory::recall(
+            &ctx.user_message,
+            ctx.session_id.as_deref(),
+            options.limit,
+            options.min_score,
+        )
+        .await?;
+        if summaries.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<String> = summaries
+            .into_iter()
+            .map(|entry| match entry.title.trim() {
+                "" => format!("- {}", entry.content),
+                title => format!("- 《{}》{}", title, entry.content),
+            })
+            .collect();
+        insert_system(
+            &mut ctx.messages,
+            format!("以下是与用户过往会话的摘要，仅供参考：\n{}", lines.join("\n")),
+        );
+        Ok(())
+    }
+}
+
+/// 语言来自会话覆盖或自动检测时，要求模型使用该语言回复
+struct Translation;
+
+#[async_trait]
+impl LlmMiddleware for Translation {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, _settings: &MiddlewareSettings) -> Result<(), String> {
+        if ctx.language.source != LanguageSource::Global {
+            let instruction = language_detector::reply_instruction(&ctx.language.language).to_string();
+            insert_system(&mut ctx.messages, instruction);
+        }
+        Ok(())
+    }
+}
+
+/// 拦截屏蔽词，对密钥与 Token 脱敏
+struct SafetyFilter;
+
+fn mask_secrets(masker: &DataMasker, text: &str) -> String {
+    let masked = masker.mask_api_keys(text);
+    masker.mask_tokens(&masked).into_owned()
+}
+
+#[async_trait]
+impl LlmMiddleware for SafetyFilter {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, settings: &MiddlewareSettings) -> Result<(), String> {
+        if let Some(term) = find_blocked_term(&ctx.user_message, &settings.safety.blocked_terms) {
+            return Err(format!("消息包含屏蔽词「{}」，已取消发送", term));
+        }
+        if settings.safety.mask_secrets {
+            let masker = DataMasker::new();
+            for message in ctx.messages.iter_mut() {
+                message.content = mask_secrets(&masker, &message.content);
+            }
+        }
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        _ctx: &LlmRequestContext,
+        reply: &mut String,
+        settings: &MiddlewareSettings,
+    ) -> Result<(), String> {
+        if settings.safety.mask_secrets {
+            *reply = mask_secrets(&DataMasker::new(), reply);
+        }
+        Ok(())
+    }
+}
+
+/// 超出提示词预算时裁剪最早的上下文
+struct TokenBudget;
+
+#[async_trait]
+impl LlmMiddleware for TokenBudget {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, settings: &MiddlewareSettings) -> Result<(), String> {
+        let max_prompt_tokens = settings.token_budget.max_prompt_tokens;
+        let removed = trim_to_budget(&mut ctx.messages, max_prompt_tokens);
+        if removed > 0 {
+            info!("提示词超出预算 {} tokens，已裁剪 {} 条上下文", max_prompt_tokens, removed);
+        }
+        let estimated = estimate_prompt_tokens(&ctx.messages);
+        if estimated > max_prompt_tokens {
+            warn!("裁剪后提示词仍约 {} tokens，超出预算 {}", estimated, max_prompt_tokens);
+        }
+        Ok(())
+    }
+}
+
+/// 记录请求与回复的概要
+struct Logging;
+
+#[async_trait]
+impl LlmMiddleware for Logging {
+    async fn before_request(&self, ctx: &mut LlmRequestContext, _settings: &MiddlewareSettings) -> Result<(), String> {
+        if ctx.incognito {
+            return Ok(());
+        }
+        info!(
+            "LLM 请求: 会话 {:?}，模型 {:?}，{} 条消息，约 {} tokens，语言 {}",
+            ctx.session_id,
+            ctx.model,
+            ctx.messages.len(),
+            estimate_prompt_tokens(&ctx.messages),
+            ctx.language.language
+        );
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        ctx: &LlmRequestContext,
+        reply: &mut String,
+        _settings: &MiddlewareSettings,
+    ) -> Result<(), String> {
+        if ctx.incognito {
+            return Ok(());
+        }
+        info!(
+            "LLM 回复: 会话 {:?}，{} 字，耗时 {} ms",
+            ctx.session_id,
+            reply.chars().count(),
+            ctx.started_at.elapsed().as_millis()
+        );
+        Ok(())
+    }
+}
+
+fn middleware(kind: MiddlewareKind) -> Box<dyn LlmMiddleware> {
+    match kind {
+        MiddlewareKind::PromptLayering => Box::new(PromptLayering),
+        MiddlewareKind::RagInjection => Box::new(RagInjection),
+        MiddlewareKind::LongTermMemory => Box::new(LongTermMemory),
+        MiddlewareKind::Translation => Box::new(Translation),
+        MiddlewareKind::SafetyFilter => Box::new(SafetyFilter),
+        MiddlewareKind::TokenBudget => Box::new(TokenBudget),
+        MiddlewareKind::Logging => Box::new(Logging),
+    }
+}
+
+fn record(kind: MiddlewareKind, started: Instant, failed: bool) {
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    METRICS.lock().entry(kind).or_default().record(elapsed_ms, failed);
+}
+
+/// 长期记忆启用时返回其选项
+pub(crate) fn memory_options() -> Option<MemoryOptions> {
+    let settings = SETTINGS.read();
+    settings
+        .is_enabled(MiddlewareKind::LongTermMemory)
+        .then(|| settings.memory.clone())
+}
+
+/// 按顺序执行启用的中间件；安全过滤失败时返回错误，调用方应取消发送
+pub async fn run_before_request(ctx: &mut LlmRequestContext) -> Result<(), String> {
+    let settings = SETTINGS.read().clone();
+    for kind in settings.enabled_steps() {
+        let started = Instant::now();
+        let result = middleware(kind).before_request(ctx, &settings).await;
+        record(kind, started, result.is_err());
+        if let Err(e) = result {
+            if kind == MiddlewareKind::SafetyFilter {
+                return Err(e);
+            }
+            warn!("中间件 {} 执行失败，已跳过: {}", kind.as_str(), e);
+        }
+    }
+    Ok(())
+}
+
+/// 按相反顺序处理回复文本，失败只记录日志
+pub async fn run_after_response(ctx: &LlmRequestContext, reply: &mut String) {
+    let settings = SETTINGS.read().clone();
+    for kind in settings.enabled_steps().into_iter().rev() {
+        let started = Instant::now();
+        let result = middleware(kind).after_response(ctx, reply, &settings).await;
+        record(kind, started, result.is_err());
+        if let Err(e) = result {
+            warn!("中间件 {} 处理回复失败: {}", kind.as_str(), e);
+        }
+    }
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("llm_middleware.json"))
+}
+
+/// 加载中间件设置（启动时调用）
+pub fn initialize_llm_middleware() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read LLM middleware settings: {}", e))?;
+    let settings = serde_json::from_str::<MiddlewareSettings>(&json_data)
+        .map_err(|e| format!("Failed to parse LLM middleware settings: {}", e))?
+        .with_missing_steps();
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取中间件设置
+#[tauri::command]
+pub async fn get_llm_middleware_settings() -> Result<MiddlewareSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存中间件设置（顺序、开关与各步选项），对之后的请求生效
+#[tauri::command]
+pub async fn update_llm_middleware_settings(settings: MiddlewareSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize LLM middleware settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write LLM middleware settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 获取各中间件的耗时指标
+#[tauri::command]
+pub async fn get_llm_middleware_metrics() -> Result<HashMap<MiddlewareKind, MiddlewareMetrics>, String> {
+    Ok(METRICS.lock().clone())
+}
+
+/// 清空耗时指标
+#[tauri::command]
+pub async fn reset_llm_middleware_metrics() -> Result<(), String> {
+    METRICS.lock().clear();
+    Ok(())
+}
@@ -3,16 +3,25 @@
 //! 提供本地LLM模型的上传、下载、删除、列表等管理功能
 //! 本地LLM模型 + prompt 是智能硬适配器的一种，但对用户隐藏这个技术细节
 //! 模型注册通过 zishu 后端核心服务进行管理
+//!
+//! 启用 `local-inference` 特性时，已注册的 GGUF 模型可以通过 `start_local_inference`
+//! 在进程内加载运行（llama.cpp），聊天请求使用该模型时不经过 Python 后端；生成的文本片段以
+//! `local-inference-token` 事件流式推送。同一时间只运行一个模型。
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use serde::{Deserialize, Serialize};
 use tracing::{info, error, warn};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
 
 use crate::commands::*;
+use crate::utils::bridge::{ChatChoice, ChatCompletionResponse, ChatMessageResponse, ChatRequest, ChatUsage, MessageRole};
+use crate::utils::local_inference::{
+    GenerationOutput, GenerationParams, InferenceConfig, InferenceMessage, LlamaRunner,
+};
 
 // ================================
 // 数据类型定义
@@ -229,6 +238,12 @@ pub async fn delete_local_llm_model(
 ) -> Result<CommandResponse<bool>, String> {
     info!("删除本地LLM模型: {}", request.model_id);
     
+    // 正在运行的模型先停止
+    let is_running = RUNNING.lock().as_ref().is_some_and(|r| r.model_id == request.model_id);
+    if is_running {
+        stop_running(&app_handle).await;
+    }
+    
     match delete_model_files(&request, &app_handle).await {
         Ok(_) => {
             info!("模型删除成功: {}", request.model_id);
@@ -248,11 +263,10 @@ pub async fn delete_local_llm_model(
 #[tauri::command]
 pub async fn verify_local_llm_model(
     request: VerifyModelRequest,
-    app_handle: AppHandle,
 ) -> Result<CommandResponse<VerifyModelResponse>, String> {
     info!("验证本地LLM模型: {}", request.model_id);
     
-    match verify_model_file(&request, &app_handle).await {
+    match verify_model_file(&request).await {
         Ok(response) => {
             info!("模型验证完成: {} - {}", request.model_id, response.message);
             Ok(CommandResponse::success(response))
@@ -306,13 +320,290 @@ pub async fn get_local_llm_model(
     }
 }
 
+// ================================
+// 本地推理
+// ================================
+
+/// 正在运行的模型
+struct RunningModel {
+    model_id: String,
+    model_name: String,
+    config: InferenceConfig,
+    started_at: i64,
+    runner: Arc<LlamaRunner>,
+    /// 只有一个推理上下文，同一时间只生成一个回复
+    generation: Arc<tokio::sync::Mutex<()>>,
+    /// 停止推理时置位，正在进行的生成尽快结束
+    cancel: Arc<AtomicBool>,
+}
+
+lazy_static::lazy_static! {
+    static ref RUNNING: parking_lot::Mutex<Option<Arc<RunningModel>>> = parking_lot::Mutex::new(None);
+    /// 加载模型期间拒绝重复启动
+    static ref START_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 本地推理状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalInferenceStatus {
+    /// 是否编译了本地推理支持（`local-inference` 特性）
+    pub available: bool,
+    pub running: bool,
+    pub model_id: Option<String>,
+    pub model_name: Option<String>,
+    pub config: Option<InferenceConfig>,
+    pub started_at: Option<i64>,
+}
+
+/// 本地补全请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalCompletionRequest {
+    /// 用于匹配 `local-inference-token` 事件（为空时自动生成）
+    #[serde(default)]
+    pub request_id: Option<String>,
+    pub messages: Vec<InferenceMessage>,
+    #[serde(default)]
+    pub params: Option<GenerationParams>,
+}
+
+/// 本地补全响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalCompletionResponse {
+    pub request_id: String,
+    pub model_id: String,
+    #[serde(flatten)]
+    pub output: GenerationOutput,
+}
+
+/// `local-inference-token` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+struct InferenceTokenEvent<'a> {
+    request_id: &'a str,
+    text: &'a str,
+}
+
+fn inference_status() -> LocalInferenceStatus {
+    let running = RUNNING.lock().clone();
+    LocalInferenceStatus {
+        available: cfg!(feature = "local-inference"),
+        running: running.is_some(),
+        model_id: running.as_ref().map(|r| r.model_id.clone()),
+        model_name: running.as_ref().map(|r| r.model_name.clone()),
+        config: running.as_ref().map(|r| r.config.clone()),
+        started_at: running.as_ref().map(|r| r.started_at),
+    }
+}
+
+/// 停止正在运行的模型；正在进行的生成结束后模型才真正释放
+async fn stop_running(app_handle: &AppHandle) -> Option<String> {
+    let running = RUNNING.lock().take()?;
+    running.cancel.store(true, Ordering::Relaxed);
+    if let Some(db) = crate::database::get_database() {
+        if let Err(e) = db.local_llm_registry.set_loaded_status(&running.model_id, false).await {
+            warn!("更新模型加载状态失败: {}", e);
+        }
+    }
+    info!("本地推理已停止: {}", running.model_name);
+    let _ = app_handle.emit_all("local-inference-status", inference_status());
+    Some(running.model_id.clone())
+}
+
+/// 正在运行的模型能否处理该模型 ID 的请求（`local_llm`、`local_llm_<模型ID>` 或模型 ID 本身）
+pub(crate) fn serves(model: Option<&str>) -> bool {
+    let Some(running) = RUNNING.lock().clone() else {
+        return false;
+    };
+    match model {
+        Some("local_llm") => true,
+        Some(model) => model == running.model_id || model.strip_prefix("local_llm_") == Some(running.model_id.as_str()),
+        None => false,
+    }
+}
+
+/// 用正在运行的模型生成回复，文本片段以 `local-inference-token` 事件推送
+async fn generate(
+    app_handle: &AppHandle,
+    request_id: &str,
+    messages: Vec<InferenceMessage>,
+    params: GenerationParams,
+) -> Result<(String, GenerationOutput), String> {
+    let running = RUNNING.lock().clone().ok_or("本地推理未启动")?;
+    let _guard = running.generation.clone().lock_owned().await;
+    if running.cancel.load(Ordering::Relaxed) {
+        return Err("本地推理已停止".to_string());
+    }
+
+    let model_id = running.model_id.clone();
+    let app_handle = app_handle.clone();
+    let request_id = request_id.to_string();
+    let output = tokio::task::spawn_blocking(move || {
+        running.runner.generate(&messages, &params, &running.cancel, |text| {
+            let _ = app_handle.emit_all("local-inference-token", InferenceTokenEvent {
+                request_id: &request_id,
+                text,
+            });
+        })
+    })
+    .await
+    .map_err(|e| format!("推理任务失败: {}", e))??;
+    Ok((model_id, output))
+}
+
+/// 以 OpenAI 兼容格式完成聊天请求（供聊天命令使用，`request.model` 需满足 [`serves`]）
+pub(crate) async fn chat_completion(app_handle: &AppHandle, request: ChatRequest) -> Result<ChatCompletionResponse, String> {
+    let messages = request
+        .messages
+        .into_iter()
+        .map(|m| InferenceMessage {
+            role: match m.role {
+                MessageRole::System => "system",
+                MessageRole::Assistant => "assistant",
+                MessageRole::User | MessageRole::Function => "user",
+            }
+            .to_string(),
+            content: m.content,
+        })
+        .collect();
+    let defaults = GenerationParams::default();
+    let params = GenerationParams {
+        max_tokens: request.max_tokens.unwrap_or(defaults.max_tokens),
+        temperature: request.temperature.unwrap_or(defaults.temperature),
+        top_p: request.top_p.unwrap_or(defaults.top_p),
+        ..defaults
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let started = std::time::Instant::now();
+    let (model_id, output) = generate(app_handle, &id, messages, params).await?;
+    Ok(ChatCompletionResponse {
+        id,
+        object: "chat.completion".to_string(),
+        created: Utc::now().timestamp(),
+        model: model_id,
+        choices: vec![ChatChoice {
+            index: 0,
+            message: ChatMessageResponse {
+                role: "assistant".to_string(),
+                content: output.text,
+                session_id: request.session_id.clone(),
+                emotion: None,
+                processing_time: Some(started.elapsed().as_secs_f64()),
+            },
+            finish_reason: Some(output.finish_reason),
+        }],
+        usage: ChatUsage {
+            prompt_tokens: output.prompt_tokens as i32,
+            completion_tokens: output.completion_tokens as i32,
+            total_tokens: (output.prompt_tokens + output.completion_tokens) as i32,
+        },
+        session_id: request.session_id,
+    })
+}
+
+/// 在进程内加载 GGUF 模型并开始本地推理（已有运行中的模型时先停止）
+#[tauri::command]
+pub async fn start_local_inference(
+    model_id: String,
+    config: Option<InferenceConfig>,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<LocalInferenceStatus>, String> {
+    info!("启动本地推理: {}", model_id);
+    
+    let config = config.unwrap_or_default();
+    if let Err(e) = config.validate() {
+        return Ok(CommandResponse::error(e));
+    }
+    let Ok(_guard) = START_LOCK.try_lock() else {
+        return Ok(CommandResponse::error("正在加载模型".to_string()));
+    };
+    
+    let db = crate::database::get_database()
+        .ok_or_else(|| "数据库未初始化".to_string())?;
+    let model = match db.local_llm_registry.get_model(&model_id).await {
+        Ok(Some(model)) => model,
+        Ok(None) => return Ok(CommandResponse::error(format!("模型不存在: {}", model_id))),
+        Err(e) => return Ok(CommandResponse::error(format!("获取模型详情失败: {}", e))),
+    };
+    let path = PathBuf::from(&model.model_path);
+    if !model.model_type.eq_ignore_ascii_case("gguf") {
+        return Ok(CommandResponse::error(format!("本地推理只支持 GGUF 模型，当前为 {}", model.model_type)));
+    }
+    if !path.exists() {
+        return Ok(CommandResponse::error(format!("模型文件不存在: {}", model.model_path)));
+    }
+    
+    stop_running(&app_handle).await;
+    
+    let load_config = config.clone();
+    let runner = match tokio::task::spawn_blocking(move || LlamaRunner::load(&path, &load_config)).await {
+        Ok(Ok(runner)) => runner,
+        Ok(Err(e)) => {
+            error!("加载本地模型失败: {}", e);
+            return Ok(CommandResponse::error(e));
+        }
+        Err(e) => return Ok(CommandResponse::error(format!("加载任务失败: {}", e))),
+    };
+    
+    *RUNNING.lock() = Some(Arc::new(RunningModel {
+        model_id: model.id.clone(),
+        model_name: model.name.clone(),
+        config,
+        started_at: Utc::now().timestamp(),
+        runner: Arc::new(runner),
+        generation: Arc::new(tokio::sync::Mutex::new(())),
+        cancel: Arc::new(AtomicBool::new(false)),
+    }));
+    if let Err(e) = db.local_llm_registry.set_loaded_status(&model.id, true).await {
+        warn!("更新模型加载状态失败: {}", e);
+    }
+    
+    let status = inference_status();
+    let _ = app_handle.emit_all("local-inference-status", &status);
+    info!("本地推理已启动: {}", model.name);
+    Ok(CommandResponse::success_with_message(status, format!("模型 {} 已加载", model.name)))
+}
+
+/// 停止本地推理并释放模型
+#[tauri::command]
+pub async fn stop_local_inference(app_handle: AppHandle) -> Result<CommandResponse<bool>, String> {
+    let stopped = stop_running(&app_handle).await.is_some();
+    Ok(CommandResponse::success(stopped))
+}
+
+/// 获取本地推理状态
+#[tauri::command]
+pub async fn get_local_inference_status() -> Result<CommandResponse<LocalInferenceStatus>, String> {
+    Ok(CommandResponse::success(inference_status()))
+}
+
+/// 用正在运行的模型生成回复；文本片段以 `local-inference-token` 事件流式推送
+#[tauri::command]
+pub async fn local_chat_completion(
+    request: LocalCompletionRequest,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<LocalCompletionResponse>, String> {
+    let request_id = request.request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let params = request.params.unwrap_or_default();
+    match generate(&app_handle, &request_id, request.messages, params).await {
+        Ok((model_id, output)) => Ok(CommandResponse::success(LocalCompletionResponse {
+            request_id,
+            model_id,
+            output,
+        })),
+        Err(e) => {
+            error!("本地推理失败: {}", e);
+            Ok(CommandResponse::error(e))
+        }
+    }
+}
+
 // ================================
 // 内部实现函数
 // ================================
 
 /// 从存储中获取所有模型
-async fn get_models_from_storage(app_handle: &AppHandle) -> Result<Vec<LocalLLMModel>, String> {
-    let models_dir = get_models_directory(app_handle)?;
+async fn get_models_from_storage() -> Result<Vec<LocalLLMModel>, String> {
+    let models_dir = get_models_directory()?;
     
     if !models_dir.exists() {
         std::fs::create_dir_all(&models_dir).map_err(|e| {
@@ -402,7 +693,7 @@ async fn register_model_path(
     // 在正式请求前做一次后端健康检查（5秒超时，给一些buffer）
     info!("开始健康检查: {}", health_url);
     let health_start = std::time::Instant::now();
-    let health_client = Client::builder()
+    let health_client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(5))
         .connect_timeout(std::time::Duration::from_secs(2))
         .build()
@@ -440,7 +731,7 @@ async fn register_model_path(
     }
     
     // 创建带超时的 HTTP 客户端（120秒整体超时）
-    let client = Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(120))
         .build()
         .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
@@ -557,7 +848,7 @@ async fn upload_model_file(
     }
     
     // 创建目标目录
-    let models_dir = get_models_directory(app_handle)?;
+    let models_dir = get_models_directory()?;
     std::fs::create_dir_all(&models_dir).map_err(|e| {
         format!("创建模型目录失败: {}", e)
     })?;
@@ -755,6 +1046,17 @@ async fn download_model_from_source(
     request: &DownloadModelRequest,
     app_handle: &AppHandle,
 ) -> Result<LocalLLMModel, String> {
+    let expected_size = request.options
+        .get("size_bytes")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    crate::commands::storage::ensure_space(
+        app_handle,
+        &get_models_directory()?,
+        expected_size,
+        &crate::utils::i18n::t("operation-download-model"),
+    )?;
+
     // 这里应该实现实际的下载逻辑
     // 目前返回错误，提示用户手动上传
     Err("模型下载功能需要实现。请先手动下载模型文件，然后使用上传功能。".to_string())
@@ -766,7 +1068,7 @@ async fn delete_model_files(
     app_handle: &AppHandle,
 ) -> Result<(), String> {
     // 获取模型信息
-    let models = get_models_from_storage(app_handle).await?;
+    let models = get_models_from_storage().await?;
     let model = models.iter().find(|m| m.id == request.model_id)
         .ok_or("模型不存在")?;
     
@@ -781,7 +1083,7 @@ async fn delete_model_files(
         let model_path = Path::new(&model.model_path);
         if model_path.exists() {
             // 检查路径是否在应用数据目录内（安全措施：只删除应用管理的文件）
-            let models_dir = get_models_directory(app_handle)?;
+            let models_dir = get_models_directory()?;
             if model_path.starts_with(&models_dir) {
                 if model_path.is_file() {
                     std::fs::remove_file(model_path).map_err(|e| {
@@ -809,9 +1111,8 @@ async fn delete_model_files(
 /// 验证模型文件
 async fn verify_model_file(
     request: &VerifyModelRequest,
-    app_handle: &AppHandle,
 ) -> Result<VerifyModelResponse, String> {
-    let model = get_model_by_id(&request.model_id, app_handle).await?
+    let model = get_model_by_id(&request.model_id).await?
         .ok_or("模型不存在")?;
     
     verify_model_internal(&model).await
@@ -854,17 +1155,14 @@ async fn verify_model_internal(model: &LocalLLMModel) -> Result<VerifyModelRespo
 /// 根据ID获取模型
 async fn get_model_by_id(
     model_id: &str,
-    app_handle: &AppHandle,
 ) -> Result<Option<LocalLLMModel>, String> {
-    let models = get_models_from_storage(app_handle).await?;
+    let models = get_models_from_storage().await?;
     Ok(models.into_iter().find(|m| m.id == model_id))
 }
 
 /// 获取模型存储目录
-fn get_models_directory(app_handle: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app_handle.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+fn get_models_directory() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     Ok(app_data_dir.join("local_llm_models"))
 }
@@ -1008,6 +1306,35 @@ pub fn get_command_metadata() -> HashMap<String, CommandMetadata> {
         category: "local_llm".to_string(),
     });
     
+    metadata.insert("start_local_inference".to_string(), CommandMetadata {
+        name: "start_local_inference".to_string(),
+        description: "加载 GGUF 模型并开始本地推理".to_string(),
+        input_type: Some("InferenceConfig".to_string()),
+        output_type: Some("LocalInferenceStatus".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "local_llm".to_string(),
+    });
+    
+    metadata.insert("stop_local_inference".to_string(), CommandMetadata {
+        name: "stop_local_inference".to_string(),
+        description: "停止本地推理并释放模型".to_string(),
+        input_type: None,
+        output_type: Some("bool".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "local_llm".to_string(),
+    });
+    
+    metadata.insert("local_chat_completion".to_string(), CommandMetadata {
+        name: "local_chat_completion".to_string(),
+        description: "用本地模型生成回复（流式推送文本片段）".to_string(),
+        input_type: Some("LocalCompletionRequest".to_string()),
+        output_type: Some("LocalCompletionResponse".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "local_llm".to_string(),
+    });
+    
     metadata
 }
-
@@ -417,11 +417,10 @@ async fn upload_logs_batch(
     db: &LogDatabase,
     config: &RemoteLogConfig,
 ) -> Result<usize, String> {
-    use reqwest::Client;
     use serde_json::json;
     use std::time::Duration;
     
-    let client = Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(Duration::from_secs(config.timeout_seconds))
         .build()
         .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
@@ -5,19 +5,39 @@
 //! - 下载和安装
 //! - 版本检查和更新
 //! - 评分和评论（只读）
+//! - 付费产品的购买凭证验证
+//!
+//! 下载付费产品前需要有效的购买凭证：本地没有缓存时向市场请求当前账号的凭证，
+//! 验证签名后加密缓存（见 [`crate::utils::market_license`]）。
 
 use tauri::{AppHandle, State};
 use serde::{Deserialize, Serialize};
 use tracing::{info, error, warn};
-use reqwest::Client;
 use tokio::fs;
 
 use crate::{
     commands::*,
+    commands::cache::{self, CachedCommand},
+    config::BackendFeature,
+    http::trust_store::PinnedSend,
     state::AppState,
     database::get_database,
+    utils::{
+        encryption::EncryptedData,
+        get_profile_data_dir,
+        market_license::{self, License, LicenseInfo, LicenseStore, SignedReceipt},
+    },
 };
 
+/// 密钥链中许可缓存加密密钥的条目名
+const LICENSE_KEY_ENTRY: &str = "market_license_key";
+const LICENSES_FILE: &str = "market_licenses.json";
+
+lazy_static::lazy_static! {
+    /// 串行化许可缓存的读写
+    static ref LICENSE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
 // ================================
 // 数据类型
 // ================================
@@ -82,6 +102,9 @@ pub struct MarketProduct {
     pub is_featured: bool,
     /// 是否已验证
     pub is_verified: bool,
+    /// 是否为付费产品（下载前需要有效的购买凭证）
+    #[serde(default)]
+    pub is_paid: bool,
     /// 创建时间
     pub created_at: String,
     /// 更新时间
@@ -222,7 +245,12 @@ pub async fn search_market_products(
 ) -> Result<CommandResponse<PaginatedResponse<MarketProduct>>, String> {
     info!("搜索市场产品: {:?}", request.query);
     
-    match search_products_in_market(&request).await {
+    let params = serde_json::to_string(&request).map_err(|e| format!("序列化搜索条件失败: {}", e))?;
+    let results = cache::cached(CachedCommand::MarketSearch, &params, || {
+        search_products_in_market(&request)
+    })
+    .await;
+    match results {
         Ok(results) => {
             info!("搜索到 {} 个产品", results.total);
             Ok(CommandResponse::success(results))
@@ -313,6 +341,8 @@ pub async fn download_market_product(
     match download_product(&product_id, version.as_deref(), &app_handle).await {
         Ok(file_path) => {
             info!("产品下载成功: {}", file_path);
+            // 下载次数变化，搜索结果需要重新获取
+            cache::invalidate(CachedCommand::MarketSearch).await;
             Ok(CommandResponse::success_with_message(
                 file_path,
                 "下载成功".to_string(),
@@ -325,6 +355,39 @@ pub async fn download_market_product(
     }
 }
 
+/// 获取产品的许可状态
+///
+/// 本地有缓存的许可时直接返回；否则查询产品是否付费，市场不可达时视为未授权。
+#[tauri::command]
+pub async fn get_license_status(product_id: String) -> Result<LicenseInfo, String> {
+    let now = chrono::Utc::now().timestamp();
+    let store = {
+        let _guard = LICENSE_LOCK.lock().await;
+        load_licenses()?
+    };
+    if let Some(license) = store.get(&product_id) {
+        return Ok(LicenseInfo::new(&product_id, Some(license), None, now));
+    }
+
+    let is_paid = match get_product_details(&product_id).await {
+        Ok(product) => Some(product.is_paid),
+        Err(e) => {
+            warn!("查询产品 {} 是否付费失败: {}", product_id, e);
+            None
+        }
+    };
+    Ok(LicenseInfo::new(&product_id, None, is_paid, now))
+}
+
+/// 导入购买凭证（例如在网页上购买后复制的凭证），验证通过后缓存
+#[tauri::command]
+pub async fn activate_product_license(product_id: String, receipt: SignedReceipt) -> Result<LicenseInfo, String> {
+    let account_id = current_account_id(&login_token().await?).await?;
+    let license = verify_and_store(&product_id, receipt, &account_id).await?;
+    info!("产品 {} 的购买凭证已验证: {}", product_id, license.claims.receipt_id);
+    Ok(LicenseInfo::new(&product_id, Some(&license), Some(true), license.verified_at))
+}
+
 /// 检查产品更新
 #[tauri::command]
 pub async fn check_product_updates(
@@ -412,7 +475,8 @@ pub struct MarketCategory {
 
 /// 从市场搜索产品
 async fn search_products_in_market(request: &MarketSearchRequest) -> Result<PaginatedResponse<MarketProduct>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     let mut query_params: Vec<(&str, String)> = vec![
@@ -454,7 +518,7 @@ async fn search_products_in_market(request: &MarketSearchRequest) -> Result<Pagi
         .map(|(k, v)| (k.to_string(), v))
         .collect();
     
-    match client.get(&url).query(&query_pairs).send().await {
+    match client.get(&url).query(&query_pairs).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<PaginatedResponse<MarketProduct>>().await {
@@ -470,13 +534,14 @@ async fn search_products_in_market(request: &MarketSearchRequest) -> Result<Pagi
 }
 
 /// 获取产品详情
-async fn get_product_details(product_id: &str) -> Result<MarketProduct, String> {
-    let client = Client::new();
+pub(crate) async fn get_product_details(product_id: &str) -> Result<MarketProduct, String> {
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     let url = format!("{}/api/marketplace/products/{}", backend_url, product_id);
     
-    match client.get(&url).send().await {
+    match client.get(&url).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<MarketProduct>().await {
@@ -496,7 +561,8 @@ async fn get_featured_products_from_market(
     product_type: Option<MarketProductType>,
     limit: Option<u32>,
 ) -> Result<Vec<MarketProduct>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     let mut query_params: Vec<(&str, String)> = vec![];
@@ -514,7 +580,7 @@ async fn get_featured_products_from_market(
         .map(|(k, v)| (k.to_string(), v))
         .collect();
     
-    match client.get(&url).query(&query_pairs).send().await {
+    match client.get(&url).query(&query_pairs).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<Vec<MarketProduct>>().await {
@@ -535,7 +601,8 @@ async fn get_reviews_from_market(
     page: Option<u32>,
     page_size: Option<u32>,
 ) -> Result<PaginatedResponse<ProductReview>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     let url = format!("{}/api/marketplace/products/{}/reviews", backend_url, product_id);
@@ -553,7 +620,7 @@ async fn get_reviews_from_market(
         .map(|(k, v)| (k.to_string(), v))
         .collect();
     
-    match client.get(&url).query(&query_pairs).send().await {
+    match client.get(&url).query(&query_pairs).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<PaginatedResponse<ProductReview>>().await {
@@ -569,17 +636,23 @@ async fn get_reviews_from_market(
 }
 
 /// 下载产品
-async fn download_product(
+pub(crate) async fn download_product(
     product_id: &str,
     version: Option<&str>,
     app_handle: &AppHandle,
 ) -> Result<String, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     // 获取产品详情
     let product = get_product_details(product_id).await?;
     
+    // 付费产品需要有效的购买凭证
+    if product.is_paid {
+        ensure_license(product_id).await?;
+    }
+    
     // 确定下载URL
     let download_url = if let Some(ver) = version {
         // 查找指定版本
@@ -593,10 +666,7 @@ async fn download_product(
     };
     
     // 创建下载目录
-    let download_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?
+    let download_dir = crate::utils::config::get_app_data_dir()?
         .join("downloads");
     
     fs::create_dir_all(&download_dir)
@@ -608,9 +678,15 @@ async fn download_product(
     let file_path = download_dir.join(&file_name);
     
     // 下载文件
-    match client.get(&download_url).send().await {
+    match client.get(&download_url).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
+                crate::commands::storage::ensure_space(
+                    app_handle,
+                    &download_dir,
+                    response.content_length().unwrap_or(0),
+                    &crate::utils::i18n::t("operation-download-product"),
+                )?;
                 let bytes = response.bytes().await
                     .map_err(|e| format!("读取下载内容失败: {}", e))?;
                 
@@ -666,7 +742,8 @@ async fn check_updates_for_products(product_ids: &[String]) -> Result<Vec<Produc
 
 /// 获取市场类别
 async fn get_categories_from_market(product_type: Option<MarketProductType>) -> Result<Vec<MarketCategory>, String> {
-    let client = Client::new();
+    crate::http::capabilities::ensure_feature(BackendFeature::Market).map_err(|e| e.to_string())?;
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
     let backend_url = get_backend_url();
     
     let mut query_params: Vec<(&str, String)> = vec![];
@@ -680,7 +757,7 @@ async fn get_categories_from_market(product_type: Option<MarketProductType>) ->
         .map(|(k, v)| (k.to_string(), v))
         .collect();
     
-    match client.get(&url).query(&query_pairs).send().await {
+    match client.get(&url).query(&query_pairs).send_pinned().await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<Vec<MarketCategory>>().await {
@@ -699,16 +776,129 @@ async fn get_categories_from_market(product_type: Option<MarketProductType>) ->
 // 辅助函数
 // ================================
 
-/// 获取后端URL - 市场功能使用社区平台
-fn get_backend_url() -> String {
-    // 优先使用环境变量
-    if let Ok(url) = std::env::var("ZISHU_BACKEND_URL") {
-        return url;
+fn licenses_path() -> Result<std::path::PathBuf, String> {
+    Ok(get_profile_data_dir()?.join(LICENSES_FILE))
+}
+
+/// 读取本地许可缓存；加密密钥丢失时缓存无法解密，视为空
+fn load_licenses() -> Result<LicenseStore, String> {
+    let path = licenses_path()?;
+    if !path.exists() {
+        return Ok(LicenseStore::default());
     }
-    
-    // 使用路由器获取社区平台 URL（市场功能在社区平台）
-    let router = crate::config::ApiRouter::new();
-    router.community_url()
+    let Some(encryption) = crate::utils::encryption::keyring_encryption(LICENSE_KEY_ENTRY, false)? else {
+        warn!("许可缓存密钥不存在，丢弃已缓存的许可");
+        let _ = std::fs::remove_file(&path);
+        return Ok(LicenseStore::default());
+    };
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("读取许可缓存失败: {}", e))?;
+    let encrypted: EncryptedData = serde_json::from_str(&json).map_err(|e| format!("解析许可缓存失败: {}", e))?;
+    let plaintext = encryption
+        .decrypt_string(&encrypted)
+        .map_err(|e| format!("解密许可缓存失败: {}", e))?;
+    serde_json::from_str(&plaintext).map_err(|e| format!("解析许可缓存失败: {}", e))
+}
+
+fn save_licenses(store: &LicenseStore) -> Result<(), String> {
+    let encryption = crate::utils::encryption::keyring_encryption(LICENSE_KEY_ENTRY, true)?
+        .ok_or("许可缓存密钥不可用")?;
+    let plaintext = serde_json::to_string(store).map_err(|e| format!("序列化许可缓存失败: {}", e))?;
+    let encrypted = encryption
+        .encrypt_string(&plaintext)
+        .map_err(|e| format!("加密许可缓存失败: {}", e))?;
+    let path = licenses_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&encrypted).map_err(|e| format!("序列化许可缓存失败: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("保存许可缓存失败: {}", e))
+}
+
+/// 验证购买凭证并写入本地缓存
+async fn verify_and_store(product_id: &str, receipt: SignedReceipt, account_id: &str) -> Result<License, String> {
+    let now = chrono::Utc::now().timestamp();
+    let claims =
+        market_license::verify_receipt(&receipt, &market_license::receipt_keys(), product_id, account_id, now)?;
+    let license = License { receipt, claims, verified_at: now };
+
+    let _guard = LICENSE_LOCK.lock().await;
+    let mut store = load_licenses()?;
+    store.insert(license.clone());
+    save_licenses(&store)?;
+    Ok(license)
+}
+
+/// 社区账号的访问令牌
+async fn login_token() -> Result<String, String> {
+    crate::commands::auth::get_auth_token()
+        .await
+        .map_err(|_| "该产品需要购买，请先登录社区账号".to_string())
+}
+
+/// 向社区平台查询当前登录账号的 ID
+async fn current_account_id(token: &str) -> Result<String, String> {
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
+    let url = format!("{}/api/v1/auth/me", get_backend_url());
+    let response = client
+        .get(&url)
+        .bearer_auth(token)
+        .send_pinned()
+        .await
+        .map_err(|e| format!("查询当前账号失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("查询当前账号失败: {}", response.status()));
+    }
+    let status: serde_json::Value = response.json().await.map_err(|e| format!("解析当前账号失败: {}", e))?;
+    match status.get("user_id") {
+        Some(serde_json::Value::String(id)) => Ok(id.clone()),
+        Some(serde_json::Value::Number(id)) => Ok(id.to_string()),
+        _ => Err("解析当前账号失败: 缺少 user_id".to_string()),
+    }
+}
+
+/// 向市场请求当前账号对该产品的购买凭证
+async fn fetch_receipt(product_id: &str, token: &str) -> Result<SignedReceipt, String> {
+    let client = crate::config::backend_profiles::client_for(BackendFeature::Market);
+    let url = format!("{}/api/marketplace/products/{}/receipt", get_backend_url(), product_id);
+
+    let response = client
+        .get(&url)
+        .bearer_auth(token)
+        .send_pinned()
+        .await
+        .map_err(|e| format!("请求购买凭证失败: {}", e))?;
+    match response.status() {
+        status if status.is_success() => response
+            .json::<SignedReceipt>()
+            .await
+            .map_err(|e| format!("解析购买凭证失败: {}", e)),
+        reqwest::StatusCode::PAYMENT_REQUIRED | reqwest::StatusCode::NOT_FOUND => {
+            Err("尚未购买该产品".to_string())
+        }
+        status => Err(format!("请求购买凭证失败: {}", status)),
+    }
+}
+
+/// 确认当前账号对付费产品有有效的许可：优先使用本地缓存，缺失或过期时向市场重新请求
+async fn ensure_license(product_id: &str) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    let token = login_token().await?;
+    let account_id = current_account_id(&token).await?;
+    {
+        let _guard = LICENSE_LOCK.lock().await;
+        if load_licenses()?.valid(product_id, &account_id, now).is_some() {
+            return Ok(());
+        }
+    }
+    let receipt = fetch_receipt(product_id, &token).await?;
+    let license = verify_and_store(product_id, receipt, &account_id).await?;
+    info!("已验证产品 {} 的购买凭证: {}", product_id, license.claims.receipt_id);
+    Ok(())
+}
+
+/// 获取后端URL - 市场功能默认使用社区平台，可通过端点配置覆盖
+fn get_backend_url() -> String {
+    crate::config::backend_profiles::base_url_for(BackendFeature::Market)
 }
 
 /// 比较版本号（简单实现）
@@ -774,6 +964,26 @@ pub fn get_command_metadata() -> std::collections::HashMap<String, CommandMetada
         category: "market".to_string(),
     });
     
+    metadata.insert("get_license_status".to_string(), CommandMetadata {
+        name: "get_license_status".to_string(),
+        description: "获取产品许可状态".to_string(),
+        input_type: Some("String".to_string()),
+        output_type: Some("LicenseInfo".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "market".to_string(),
+    });
+    
+    metadata.insert("activate_product_license".to_string(), CommandMetadata {
+        name: "activate_product_license".to_string(),
+        description: "导入并验证购买凭证".to_string(),
+        input_type: Some("SignedReceipt".to_string()),
+        output_type: Some("LicenseInfo".to_string()),
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "market".to_string(),
+    });
+    
     metadata.insert("check_product_updates".to_string(), CommandMetadata {
         name: "check_product_updates".to_string(),
         description: "检查产品更新".to_string(),
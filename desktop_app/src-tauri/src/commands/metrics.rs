@@ -0,0 +1,244 @@
+//! # 指标导出命令模块
+//!
+//! 在本机开放 Prometheus 抓取端点 `GET /metrics`，导出 [`metric_registry`] 中的指标。
+//!
+//! - 端点默认关闭，在设置中开启；只能绑定回环地址，非本机连接直接断开
+//! - 设置保存在应用数据目录的 `metrics_settings.json`
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+
+use crate::utils::metric_registry;
+
+/// 请求头的最大长度
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// 读取请求的超时时间
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref SERVER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+}
+
+fn default_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    9464
+}
+
+/// 指标端点设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 监听地址，只允许回环地址
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: default_bind_address(),
+            port: default_port(),
+        }
+    }
+}
+
+impl MetricsSettings {
+    /// 解析监听地址，拒绝非回环地址
+    fn socket_addr(&self) -> Result<SocketAddr, String> {
+        let ip: IpAddr = self
+            .bind_address
+            .trim()
+            .parse()
+            .map_err(|_| format!("无效的监听地址: {}", self.bind_address))?;
+        if !ip.is_loopback() {
+            return Err(format!("指标端点只能绑定本机回环地址: {}", ip));
+        }
+        if self.port == 0 {
+            return Err("端口不能为 0".to_string());
+        }
+        Ok(SocketAddr::new(ip, self.port))
+    }
+}
+
+/// 指标端点状态
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub url: String,
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(app_data_dir.join("metrics_settings.json"))
+}
+
+fn load_settings() -> Result<MetricsSettings, String> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(MetricsSettings::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read metrics settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse metrics settings: {}", e))
+}
+
+fn save_settings(settings: &MetricsSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize metrics settings: {}", e))?;
+    fs::write(settings_path()?, json).map_err(|e| format!("Failed to write metrics settings: {}", e))
+}
+
+async fn serve_connection(mut stream: tokio::net::TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read = async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    if tokio::time::timeout(READ_TIMEOUT, read).await.is_err() {
+        return Ok(());
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let (status, body) = metric_registry::handle_http_request(&head, metric_registry::render);
+    let content_type = if status.starts_with("200") {
+        metric_registry::CONTENT_TYPE
+    } else {
+        "text/plain; charset=utf-8"
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn listen(listener: std::net::TcpListener) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        // 绑定在回环地址时不应出现，防御性检查
+        if !peer.ip().is_loopback() {
+            warn!("拒绝非本机的指标请求: {}", peer);
+            continue;
+        }
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = serve_connection(stream).await {
+                warn!("指标请求处理失败: {}", e);
+            }
+        });
+    }
+}
+
+fn stop_server() {
+    if let Some(handle) = SERVER.lock().take() {
+        handle.abort();
+        info!("指标端点已关闭");
+    }
+}
+
+fn start_server(settings: &MetricsSettings) -> Result<(), String> {
+    stop_server();
+    let addr = settings.socket_addr()?;
+    // 同步绑定，端口被占用等错误可以直接返回给设置页
+    let listener = std::net::TcpListener::bind(addr).map_err(|e| format!("指标端点绑定 {} 失败: {}", addr, e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("指标端点初始化失败: {}", e))?;
+    info!("指标端点监听: http://{}/metrics", addr);
+    let handle = tauri::async_runtime::spawn(async move {
+        if let Err(e) = listen(listener).await {
+            warn!("指标端点停止: {}", e);
+        }
+    });
+    *SERVER.lock() = Some(handle);
+    Ok(())
+}
+
+fn status(settings: &MetricsSettings) -> MetricsStatus {
+    MetricsStatus {
+        enabled: settings.enabled,
+        running: SERVER.lock().is_some(),
+        url: format!("http://{}:{}/metrics", settings.bind_address, settings.port),
+    }
+}
+
+/// 包装 `generate_handler!` 生成的处理函数，按命令名统计调用次数
+pub fn instrument_handler<R: tauri::Runtime>(
+    handler: impl Fn(tauri::Invoke<R>) + Send + Sync + 'static,
+) -> impl Fn(tauri::Invoke<R>) + Send + Sync + 'static {
+    move |invoke| {
+        metric_registry::increment_counter(
+            metric_registry::COMMANDS_TOTAL,
+            &[("command", invoke.message.command())],
+        );
+        handler(invoke)
+    }
+}
+
+/// 加载设置，开启时启动指标端点（应用启动时调用）
+pub fn initialize_metrics() -> Result<(), String> {
+    let settings = load_settings()?;
+    if settings.enabled {
+        start_server(&settings)?;
+    }
+    Ok(())
+}
+
+/// 获取指标端点状态
+#[tauri::command]
+pub async fn get_metrics_status() -> Result<MetricsStatus, String> {
+    Ok(status(&load_settings()?))
+}
+
+/// 获取指标端点设置
+#[tauri::command]
+pub async fn get_metrics_settings() -> Result<MetricsSettings, String> {
+    load_settings()
+}
+
+/// 更新指标端点设置并启动或关闭端点
+#[tauri::command]
+pub async fn update_metrics_settings(
+    settings: MetricsSettings,
+) -> Result<MetricsStatus, String> {
+    settings.socket_addr()?;
+    if settings.enabled {
+        start_server(&settings)?;
+    } else {
+        stop_server();
+    }
+    save_settings(&settings)?;
+    Ok(status(&settings))
+}
+
+/// 获取当前指标的 Prometheus 文本（供设置页预览）
+#[tauri::command]
+pub async fn get_metrics_text() -> Result<String, String> {
+    Ok(metric_registry::render())
+}
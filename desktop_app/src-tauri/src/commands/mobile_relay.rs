@@ -0,0 +1,242 @@
+//! # 移动端转发命令模块
+//!
+//! 把选定类别的通知转发到手机（见 [`crate::utils::mobile_relay`]）。其他模块调用
+//! [`relay`] 提交通知，是否发送由设置中的总开关与类别开关决定；每日总结由后台任务
+//! 在设定的小时汇总当天统计后发送。访问令牌与端到端加密密钥保存在系统密钥链中。
+
+use chrono::{Local, NaiveDate, TimeZone, Timelike};
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::commands::statistics::{self, StatisticsRange};
+use crate::config::backend_profiles::{self, BackendFeature};
+use crate::utils::encryption;
+use crate::utils::keyring_store;
+use crate::utils::mobile_relay::{self, RelayCategory, RelayNotification, RelayProvider, RelaySettings};
+use crate::utils::watchdog;
+
+const TOKEN_ENTRY: &str = "mobile_relay_token";
+const KEY_ENTRY: &str = "mobile_relay_key";
+const MASK: &str = "********";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// 每日总结的检查间隔
+const SUMMARY_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// 每日总结任务的心跳停滞阈值
+const SUMMARY_STALL_AFTER: Duration = Duration::from_secs(60 * 60);
+
+lazy_static! {
+    static ref SETTINGS: RwLock<RelaySettings> = RwLock::new(RelaySettings::default());
+    /// 最近一次发送每日总结的日期
+    static ref LAST_SUMMARY: Mutex<Option<NaiveDate>> = Mutex::new(None);
+}
+
+fn get_relay_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("mobile_relay_settings.json"))
+}
+
+fn save_secret(name: &str, secret: Option<&str>) {
+    if let Err(e) = keyring_store::store(name, secret) {
+        warn!("保存 {} 失败: {}", name, e);
+    }
+}
+
+/// 从磁盘加载转发设置（启动时调用）
+pub fn initialize_mobile_relay() -> Result<(), String> {
+    let config_path = get_relay_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read mobile relay settings: {}", e))?;
+    let mut settings: RelaySettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse mobile relay settings: {}", e))?;
+    settings.access_token = keyring_store::load(TOKEN_ENTRY);
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+async fn send(settings: &RelaySettings, notification: &RelayNotification) -> Result<(), String> {
+    let provider = settings.provider.as_ref().ok_or("未配置推送服务")?;
+    let key = if settings.end_to_end_encryption {
+        let key = keyring_store::load(KEY_ENTRY).ok_or("已开启端到端加密，请先生成配对密钥")?;
+        Some(mobile_relay::decode_key(&key)?)
+    } else {
+        None
+    };
+    // Zishu 推送服务使用核心服务的登录凭据
+    let access_token = match provider {
+        RelayProvider::ZishuPush { .. } => backend_profiles::bearer_token_for(BackendFeature::Core),
+        _ => settings.access_token.clone(),
+    };
+    let request = mobile_relay::prepare_request(
+        provider,
+        notification,
+        access_token.as_deref(),
+        key.as_ref(),
+        &backend_profiles::base_url_for(BackendFeature::Core),
+    )?;
+
+    let client = crate::http::proxy::client_builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let mut builder = client
+        .post(&request.url)
+        .header(reqwest::header::CONTENT_TYPE, request.content_type)
+        .body(request.body);
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let response = builder.send().await.map_err(|e| format!("推送通知失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("推送服务返回错误: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// 转发通知（在后台发送，未开启或类别关闭时忽略）
+pub fn relay(category: RelayCategory, title: impl Into<String>, body: impl Into<String>) {
+    let settings = SETTINGS.read().clone();
+    if !settings.enabled || !settings.categories.allows(category) {
+        return;
+    }
+    let notification = RelayNotification {
+        category,
+        title: title.into(),
+        body: body.into(),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = send(&settings, &notification).await {
+            warn!("转发通知到手机失败: {}", e);
+        }
+    });
+}
+
+async fn daily_summary() -> Result<String, String> {
+    let now = Local::now();
+    let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default();
+    let from = Local.from_local_datetime(&midnight).earliest().map(|t| t.timestamp());
+    let stats = statistics::get_pet_statistics(
+        Some(StatisticsRange { from, to: Some(now.timestamp()) }),
+        Some(true),
+    )
+    .await?;
+
+    let mut lines = vec![
+        format!("今天聊了 {} 条消息，互动 {} 次", stats.messages.total, stats.total_interactions),
+        format!("陪伴时长 {} 分钟", stats.uptime.total_secs / 60),
+    ];
+    if let Some(workflow) = stats.top_workflows.first() {
+        lines.push(format!("最常用的工作流: {}（{} 次）", workflow.key, workflow.count));
+    }
+    if stats.habits.current_streak > 1 {
+        lines.push(format!("已连续聊天 {} 天", stats.habits.current_streak));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// 启动每日总结任务（应用启动时调用）
+pub fn start_daily_summary_scheduler() {
+    watchdog::supervise("daily_summary", SUMMARY_STALL_AFTER, move |heartbeat| {
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                let settings = SETTINGS.read().clone();
+                let now = Local::now();
+                let due = settings.enabled
+                    && settings.categories.daily_summary
+                    && now.hour() == settings.daily_summary_hour
+                    && *LAST_SUMMARY.lock() != Some(now.date_naive());
+                if due {
+                    *LAST_SUMMARY.lock() = Some(now.date_naive());
+                    match daily_summary().await {
+                        Ok(body) => relay(RelayCategory::DailySummary, "今日总结", body),
+                        Err(e) => warn!("生成每日总结失败: {}", e),
+                    }
+                }
+                tokio::time::sleep(SUMMARY_CHECK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// 获取转发设置（不返回访问令牌）
+#[tauri::command]
+pub async fn get_mobile_relay_settings() -> Result<RelaySettings, String> {
+    let mut settings = SETTINGS.read().clone();
+    settings.access_token = settings.access_token.map(|_| MASK.to_string());
+    Ok(settings)
+}
+
+/// 更新转发设置
+#[tauri::command]
+pub async fn update_mobile_relay_settings(
+    mut settings: RelaySettings,
+) -> Result<(), String> {
+    // 前端回传掩码时保留原令牌
+    if settings.access_token.as_deref() == Some(MASK) {
+        settings.access_token = SETTINGS.read().access_token.clone();
+    }
+    settings.validate()?;
+    save_secret(TOKEN_ENTRY, settings.access_token.as_deref());
+
+    let mut persisted = settings.clone();
+    persisted.access_token = None;
+    let json_data = serde_json::to_string_pretty(&persisted)
+        .map_err(|e| format!("Failed to serialize mobile relay settings: {}", e))?;
+    fs::write(get_relay_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write mobile relay settings: {}", e))?;
+
+    info!("移动端转发设置已更新: enabled={}", settings.enabled);
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 生成新的端到端加密配对密钥，返回 Base64 编码的密钥供手机端导入
+///
+/// 重新生成后旧密钥加密的通知无法再用新密钥解密，手机端需要重新配对。
+#[tauri::command]
+pub async fn generate_mobile_relay_key() -> Result<String, String> {
+    let key = encryption::generate_random_key().map_err(|e| e.to_string())?;
+    let encoded = mobile_relay::encode_key(&key);
+    save_secret(KEY_ENTRY, Some(&encoded));
+    info!("已生成新的移动端配对密钥");
+    Ok(encoded)
+}
+
+/// 发送测试通知（忽略总开关与类别开关，直接返回发送结果）
+#[tauri::command]
+pub async fn send_mobile_relay_test() -> Result<(), String> {
+    let settings = SETTINGS.read().clone();
+    let notification = RelayNotification {
+        category: RelayCategory::Test,
+        title: "测试通知".to_string(),
+        body: "手机端已能收到桌宠的消息".to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    send(&settings, &notification).await
+}
+
+/// 前端转发通知（如提醒）
+#[tauri::command]
+pub async fn relay_mobile_notification(
+    category: RelayCategory,
+    title: String,
+    body: String,
+) -> Result<(), String> {
+    if category == RelayCategory::Test {
+        return Err("测试通知请使用 send_mobile_relay_test".to_string());
+    }
+    relay(category, title, body);
+    Ok(())
+}
@@ -150,6 +150,143 @@ pub mod auth;
 /// Live2D 资源缓存与准备命令
 pub mod live2d_assets;
 
+/// 无障碍命令
+pub mod accessibility;
+
+/// 统一动作（命令面板/键盘操作）命令
+pub mod actions;
+
+/// 网络设置（代理、信任库）命令
+pub mod network;
+
+/// 后端连接命令
+pub mod backend;
+
+/// 存储空间命令
+pub mod storage;
+
+/// 会话分享命令
+pub mod sharing;
+
+/// 唤醒词命令
+pub mod hotword;
+
+/// 嵌入提供者命令
+pub mod embedding;
+
+/// 混合检索命令
+pub mod search;
+
+/// 会话话题分析命令
+pub mod topics;
+
+/// 季节内容命令
+pub mod seasonal;
+
+/// 成就命令
+pub mod achievements;
+
+/// 桌宠统计命令
+pub mod statistics;
+
+/// 自动化接口命令
+pub mod automation;
+
+/// 移动端转发命令
+pub mod mobile_relay;
+
+/// 数据库备份与资料导出命令
+pub mod backup;
+
+/// 安全模式命令
+pub mod safe_mode;
+
+/// 指标导出命令
+pub mod metrics;
+
+/// 资源预算命令
+pub mod resource_budget;
+
+/// 费用预估命令
+pub mod cost_preview;
+
+/// 事件日志命令
+pub mod event_journal;
+
+/// 事件订阅命令
+pub mod event_subscription;
+
+/// 口型同步播放命令
+pub mod lipsync;
+
+/// 空闲时整理命令
+pub mod sleep_learning;
+
+/// 多角色互动命令
+pub mod pet_interaction;
+
+/// 展示模式命令
+pub mod kiosk;
+/// 桌宠摆放命令
+pub mod pet_placement;
+
+/// 快速对话弹窗命令
+pub mod quick_chat;
+
+/// 聊天发件箱命令
+pub mod chat_outbox;
+
+/// 托盘状态持久化命令
+pub mod tray_state;
+
+/// 对话气泡命令
+pub mod speech_queue;
+
+/// 语音合成命令
+pub mod tts;
+/// LLM 中间件命令
+pub mod llm_middleware;
+/// 工作流定时触发命令
+pub mod workflow_schedule;
+/// 会话参数命令
+pub mod session_parameters;
+/// 无痕会话命令
+pub mod incognito;
+/// 会话记忆命令
+pub mod session_memory;
+/// 托盘扩展菜单命令
+pub mod tray_menu;
+/// 崩溃报告命令
+pub mod crash_reports;
+/// 截图命令
+pub mod capture;
+/// Live2D 参数命令
+pub mod live2d_parameters;
+/// 用户资料命令
+pub mod profiles;
+/// 通知中心命令
+pub mod notifications;
+/// 文档知识库命令
+pub mod knowledge_base;
+/// 适配器日志命令
+pub mod adapter_logs;
+/// 权限确认命令
+pub mod permission_consent;
+/// 专注计时命令
+pub mod focus;
+/// 剪贴板历史命令
+pub mod clipboard;
+/// 角色情绪命令
+pub mod emotion;
+/// 命令结果缓存
+pub mod cache;
+/// 适配器开发模式命令
+pub mod adapter_dev;
+/// 多设备同步命令
+pub mod device_sync;
+/// 适配器日历宿主接口
+pub mod calendar_host;
+
 // ================================
 // 公共命令类型定义
 // ================================
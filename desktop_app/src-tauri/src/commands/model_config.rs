@@ -9,6 +9,7 @@ use std::collections::HashMap;
 
 use crate::{commands::*, AppState, ZishuResult};
 use crate::database::{get_database, model_config::*};
+use crate::commands::cache::{self, CachedCommand};
 
 // ================================
 // 命令元数据
@@ -269,6 +270,7 @@ pub async fn save_model_config_handler(
     db.model_config_registry.save_config(input.clone()).map_err(|e| {
         handle_command_error("save_model_config", &format!("保存配置失败: {}", e))
     })?;
+    cache::invalidate(CachedCommand::ModelConfigs).await;
     
     // 如果设置为默认，更新 AppState
     if input.is_default {
@@ -328,6 +330,7 @@ pub async fn delete_model_config_handler(
     db.model_config_registry.delete_config(&input.config_id).map_err(|e| {
         handle_command_error("delete_model_config", &format!("删除配置失败: {}", e))
     })?;
+    cache::invalidate(CachedCommand::ModelConfigs).await;
     
     let response = DeleteConfigResponse {
         success: true,
@@ -348,9 +351,11 @@ pub async fn get_all_model_configs_handler(
         handle_command_error("get_all_model_configs", "数据库未初始化")
     })?;
     
-    let configs = db.model_config_registry.get_all_configs().map_err(|e| {
-        handle_command_error("get_all_model_configs", &format!("获取配置列表失败: {}", e))
-    })?;
+    let configs = cache::cached(CachedCommand::ModelConfigs, "", || async move {
+        db.model_config_registry.get_all_configs().map_err(|e| {
+            handle_command_error("get_all_model_configs", &format!("获取配置列表失败: {}", e))
+        })
+    }).await?;
     
     let response = GetAllConfigsResponse {
         total: configs.len(),
@@ -396,6 +401,7 @@ pub async fn set_default_model_config_handler(
     db.model_config_registry.set_default_config(&input.config_id).map_err(|e| {
         handle_command_error("set_default_model_config", &format!("设置默认配置失败: {}", e))
     })?;
+    cache::invalidate(CachedCommand::ModelConfigs).await;
     
     // 更新 AppState
     let config = db.model_config_registry.get_config(&input.config_id).map_err(|e| {
@@ -516,6 +522,7 @@ pub async fn import_model_config_handler(
         })?;
         vec![config]
     };
+    cache::invalidate(CachedCommand::ModelConfigs).await;
     
     let response = ImportConfigResponse {
         success: true,
@@ -0,0 +1,221 @@
+//! # 网络设置命令模块
+//!
+//! 管理出站 HTTP 的代理设置与自定义 CA 信任库，设置在运行时立即生效并持久化到应用数据目录。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::http::proxy::{self, SystemProxyInfo};
+use crate::http::trust_store::{self, TrustedCertificate};
+use crate::state::settings::ProxySettings;
+use crate::utils::keyring_store;
+
+/// 返回给前端的代理密码掩码
+const PASSWORD_MASK: &str = "********";
+/// 代理密码在密钥链中的条目名
+const PROXY_PASSWORD_ENTRY: &str = "proxy_password";
+
+/// 代理连通性测试结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyTestResult {
+    pub success: bool,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    /// 请求是否经过代理（目标命中绕过列表时为 false）
+    pub via_proxy: bool,
+}
+
+fn get_proxy_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("proxy_settings.json"))
+}
+
+/// 代理密码保存在系统密钥链中，不写入配置文件
+fn save_proxy_password(password: Option<&str>) {
+    if let Err(e) = keyring_store::store(PROXY_PASSWORD_ENTRY, password) {
+        warn!("保存代理密码失败: {}", e);
+    }
+}
+
+/// 从磁盘加载代理设置（启动时调用）
+pub fn initialize_proxy_settings() -> Result<(), String> {
+    let config_path = get_proxy_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read proxy settings: {}", e))?;
+    let mut settings: ProxySettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse proxy settings: {}", e))?;
+    settings.password = keyring_store::load(PROXY_PASSWORD_ENTRY);
+
+    proxy::set_settings(settings)?;
+    info!("代理设置已加载");
+    Ok(())
+}
+
+/// 当前代理设置，密码以掩码代替
+pub fn masked_proxy_settings() -> ProxySettings {
+    let mut settings = proxy::current_settings();
+    settings.password = settings.password.map(|_| PASSWORD_MASK.to_string());
+    settings
+}
+
+/// 应用并持久化代理设置，立即对新请求生效（`update_proxy_settings` 与
+/// `update_system_config` 共用），返回掩码后的设置
+pub fn apply_proxy_settings(app_handle: &AppHandle, mut settings: ProxySettings) -> Result<ProxySettings, String> {
+    // 前端回传掩码时保留原密码
+    if settings.password.as_deref() == Some(PASSWORD_MASK) {
+        settings.password = proxy::current_settings().password;
+    }
+
+    proxy::set_settings(settings.clone())?;
+    save_proxy_password(settings.password.as_deref());
+
+    let mut persisted = settings.clone();
+    persisted.password = None;
+    let json_data = serde_json::to_string_pretty(&persisted)
+        .map_err(|e| format!("Failed to serialize proxy settings: {}", e))?;
+    fs::write(get_proxy_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write proxy settings: {}", e))?;
+
+    let _ = app_handle.emit_all("proxy-settings-changed", serde_json::json!({
+        "enabled": settings.enabled,
+        "use_system_proxy": settings.use_system_proxy,
+    }));
+
+    info!("代理设置已更新: enabled={}, system={}", settings.enabled, settings.use_system_proxy);
+    Ok(masked_proxy_settings())
+}
+
+/// 获取代理设置（不返回密码）
+#[tauri::command]
+pub async fn get_proxy_settings() -> Result<ProxySettings, String> {
+    Ok(masked_proxy_settings())
+}
+
+/// 更新代理设置，立即对新请求生效
+#[tauri::command]
+pub async fn update_proxy_settings(
+    app_handle: AppHandle,
+    settings: ProxySettings,
+) -> Result<(), String> {
+    apply_proxy_settings(&app_handle, settings).map(|_| ())
+}
+
+/// 检测系统代理
+#[tauri::command]
+pub async fn detect_system_proxy() -> Result<SystemProxyInfo, String> {
+    Ok(proxy::detect_system_proxy())
+}
+
+/// 通过当前代理设置测试到指定地址的连通性
+#[tauri::command]
+pub async fn test_proxy_connection(url: String) -> Result<ProxyTestResult, String> {
+    let client = proxy::client_builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let via_proxy = proxy::routes_through_proxy(&url);
+    let started = Instant::now();
+    let result = client.get(&url).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    Ok(match result {
+        Ok(response) => ProxyTestResult {
+            success: response.status().is_success(),
+            status: Some(response.status().as_u16()),
+            latency_ms,
+            error: None,
+            via_proxy,
+        },
+        Err(e) => ProxyTestResult {
+            success: false,
+            status: None,
+            latency_ms,
+            error: Some(trust_store::classify_request_error(e).to_string()),
+            via_proxy,
+        },
+    })
+}
+
+// ================================
+// 信任库
+// ================================
+
+/// 加载自定义 CA 信任库（启动时调用）
+pub fn initialize_trust_store() -> Result<(), String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+    trust_store::load(app_data_dir.join("trust_store"))
+}
+
+/// 导入 PEM 证书，`pem` 与 `path` 二选一
+#[tauri::command]
+pub async fn import_trusted_certificate(
+    app_handle: AppHandle,
+    pem: Option<String>,
+    path: Option<String>,
+    label: String,
+) -> Result<Vec<TrustedCertificate>, String> {
+    let pem = match (pem, path) {
+        (Some(pem), _) => pem,
+        (None, Some(path)) => fs::read_to_string(&path)
+            .map_err(|e| format!("读取证书文件失败: {}", e))?,
+        (None, None) => return Err("请提供证书内容或证书文件路径".to_string()),
+    };
+
+    let imported = trust_store::import_pem(&pem, &label)?;
+    let _ = app_handle.emit_all("trust-store-changed", serde_json::json!({
+        "imported": imported.iter().map(|c| &c.fingerprint).collect::<Vec<_>>(),
+    }));
+
+    info!("已导入 {} 个受信任证书", imported.len());
+    Ok(imported)
+}
+
+/// 列出受信任的证书
+#[tauri::command]
+pub async fn list_trusted_certificates() -> Result<Vec<TrustedCertificate>, String> {
+    Ok(trust_store::list_certificates())
+}
+
+/// 删除受信任的证书
+#[tauri::command]
+pub async fn remove_trusted_certificate(
+    app_handle: AppHandle,
+    fingerprint: String,
+) -> Result<bool, String> {
+    let removed = trust_store::remove_certificate(&fingerprint)?;
+    if removed {
+        let _ = app_handle.emit_all("trust-store-changed", serde_json::json!({
+            "removed": fingerprint,
+        }));
+    }
+    Ok(removed)
+}
+
+/// 获取按主机固定的证书指纹
+#[tauri::command]
+pub async fn get_certificate_pins() -> Result<HashMap<String, Vec<String>>, String> {
+    Ok(trust_store::host_pins())
+}
+
+/// 设置主机的证书固定指纹，传入空列表取消固定
+#[tauri::command]
+pub async fn set_certificate_pins(host: String, fingerprints: Vec<String>) -> Result<(), String> {
+    trust_store::set_host_pins(&host, fingerprints)?;
+    info!("已更新主机 {} 的证书固定", host);
+    Ok(())
+}
@@ -0,0 +1,283 @@
+//! # 通知中心命令模块
+//!
+//! 所有模块通过 [`notify`]（同步代码用 [`post`]）发出通知：通知保存到数据库的通知中心，
+//! 同步到托盘的未读计数，发出 `notification-added` 事件，并在
+//! `SystemConfig::show_notifications` 开启时显示系统通知。
+//!
+//! 通知可以带操作按钮，按钮的 `command` 必须是通过 [`register_action`] 注册的处理函数，
+//! 前端点击按钮时调用 `invoke_notification_action`，不能借通知执行任意命令。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::database::notification::{self, NotificationAction, StoredNotification};
+use crate::state::tray_state::{NotificationType, TrayNotification};
+use crate::AppState;
+
+/// 默认返回的通知数
+const DEFAULT_LIST_LIMIT: i64 = 100;
+
+/// 通知操作的处理函数，参数为按钮的 `args`
+pub type ActionHandler = Arc<dyn Fn(&AppHandle, &serde_json::Value) -> Result<(), String> + Send + Sync>;
+
+lazy_static! {
+    static ref ACTION_HANDLERS: RwLock<HashMap<String, ActionHandler>> = RwLock::new(HashMap::new());
+}
+
+/// 注册通知按钮可以执行的命令
+pub fn register_action(
+    command: &str,
+    handler: impl Fn(&AppHandle, &serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+) {
+    ACTION_HANDLERS.write().insert(command.to_string(), Arc::new(handler));
+}
+
+pub fn is_registered(command: &str) -> bool {
+    ACTION_HANDLERS.read().contains_key(command)
+}
+
+fn show_window(app: &AppHandle, label: &str) -> Result<(), String> {
+    let window = app.get_window(label).ok_or_else(|| format!("窗口不存在: {}", label))?;
+    window.show().map_err(|e| format!("显示窗口失败: {}", e))?;
+    let _ = window.unminimize();
+    window.set_focus().map_err(|e| format!("聚焦窗口失败: {}", e))
+}
+
+/// 注册内置的通知操作（启动时调用）
+///
+/// - `show_window`：显示并聚焦窗口，`args.label` 默认为 `main`
+/// - `open_settings`：打开主窗口的设置页，`args.section` 为设置分区
+/// - `open_url`：用系统浏览器打开 `args.url`（仅 http/https）
+pub fn register_builtin_actions() {
+    register_action("show_window", |app, args| {
+        show_window(app, args.get("label").and_then(|v| v.as_str()).unwrap_or("main"))
+    });
+    register_action("open_settings", |app, args| {
+        show_window(app, "main")?;
+        app.emit_all("open-settings", serde_json::json!({ "section": args.get("section") }))
+            .map_err(|e| format!("发送事件失败: {}", e))
+    });
+    register_action("open_url", |app, args| {
+        let url = args.get("url").and_then(|v| v.as_str()).ok_or("缺少 url 参数")?;
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(format!("不支持的链接: {}", url));
+        }
+        tauri::api::shell::open(&app.shell_scope(), url, None).map_err(|e| format!("打开链接失败: {}", e))
+    });
+}
+
+/// 待发出的通知
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub title: String,
+    pub body: String,
+    pub notification_type: NotificationType,
+    pub source: Option<String>,
+    pub actions: Vec<NotificationAction>,
+}
+
+impl NewNotification {
+    pub fn new(title: impl Into<String>, body: impl Into<String>, notification_type: NotificationType) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            notification_type,
+            source: None,
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    /// 添加操作按钮
+    pub fn action(mut self, id: &str, label: &str, command: &str, args: serde_json::Value) -> Self {
+        self.actions.push(NotificationAction {
+            id: id.to_string(),
+            label: label.to_string(),
+            command: command.to_string(),
+            args,
+        });
+        self
+    }
+}
+
+fn show_native(app: &AppHandle, title: &str, body: &str) {
+    let enabled = app
+        .try_state::<AppState>()
+        .map(|state| state.config.lock().system.show_notifications)
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+
+    use tauri::api::notification::Notification;
+    if let Err(e) = Notification::new(&app.config().tauri.bundle.identifier)
+        .title(title)
+        .body(body)
+        .show()
+    {
+        warn!("显示系统通知失败: {}", e);
+    }
+}
+
+/// 发出通知：保存到通知中心、同步托盘未读计数、通知前端并显示系统通知
+pub async fn notify(app: &AppHandle, notification: NewNotification) -> Result<StoredNotification, String> {
+    notification::validate_actions(&notification.actions, is_registered)?;
+
+    let stored = StoredNotification {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: notification.title,
+        body: notification.body,
+        notification_type: notification.notification_type,
+        source: notification.source,
+        actions: notification.actions,
+        created_at: chrono::Utc::now().timestamp(),
+        read_at: None,
+        dismissed_at: None,
+    };
+
+    // 数据库不可用时仍然显示通知，只是不会保留
+    match crate::database::get_database() {
+        Some(db) => {
+            if let Err(e) = db.notifications.insert(&stored).await {
+                warn!("保存通知失败: {}", e);
+            }
+        }
+        None => warn!("数据库未初始化，通知不会保存: {}", stored.title),
+    }
+
+    if let Some(state) = app.try_state::<AppState>() {
+        state.tray.add_notification(TrayNotification {
+            id: stored.id.clone(),
+            title: stored.title.clone(),
+            body: stored.body.clone(),
+            notification_type: stored.notification_type.clone(),
+            created_at: chrono::Utc::now(),
+            is_read: false,
+        });
+    }
+
+    let _ = app.emit_all("notification-added", &stored);
+    show_native(app, &stored.title, &stored.body);
+    Ok(stored)
+}
+
+/// 在后台发出通知（供同步代码使用）
+pub fn post(app: &AppHandle, notification: NewNotification) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = notify(&app, notification).await {
+            warn!("发出通知失败: {}", e);
+        }
+    });
+}
+
+fn registry() -> Result<std::sync::Arc<crate::database::Database>, String> {
+    crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())
+}
+
+/// 通知列表
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationList {
+    pub notifications: Vec<StoredNotification>,
+    pub unread_count: i64,
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取通知（新的在前，不含已忽略的通知）
+#[tauri::command]
+pub async fn get_notifications(unread_only: Option<bool>, limit: Option<i64>) -> Result<NotificationList, String> {
+    let db = registry()?;
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, notification::MAX_STORED);
+    let notifications = db
+        .notifications
+        .list(unread_only.unwrap_or(false), limit)
+        .await
+        .map_err(|e| format!("读取通知失败: {}", e))?;
+    let unread_count = db
+        .notifications
+        .unread_count()
+        .await
+        .map_err(|e| format!("读取未读通知数失败: {}", e))?;
+    Ok(NotificationList { notifications, unread_count })
+}
+
+/// 标记通知已读
+#[tauri::command]
+pub async fn mark_notification_read(app_handle: AppHandle, id: String) -> Result<bool, String> {
+    let updated = registry()?
+        .notifications
+        .mark_read(&id, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("标记通知已读失败: {}", e))?;
+    app_handle.state::<AppState>().tray.mark_notification_read(&id);
+    Ok(updated)
+}
+
+/// 忽略通知，之后不再出现在通知列表中
+#[tauri::command]
+pub async fn dismiss_notification(app_handle: AppHandle, id: String) -> Result<bool, String> {
+    let dismissed = registry()?
+        .notifications
+        .dismiss(&id, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("忽略通知失败: {}", e))?;
+    app_handle.state::<AppState>().tray.mark_notification_read(&id);
+    Ok(dismissed)
+}
+
+/// 清空通知中心
+#[tauri::command]
+pub async fn clear_notifications(app_handle: AppHandle) -> Result<u64, String> {
+    let removed = registry()?
+        .notifications
+        .clear()
+        .await
+        .map_err(|e| format!("清空通知失败: {}", e))?;
+    app_handle.state::<AppState>().tray.clear_notifications();
+    info!("已清空 {} 条通知", removed);
+    Ok(removed)
+}
+
+/// 执行通知的操作按钮，并将通知标记为已读
+#[tauri::command]
+pub async fn invoke_notification_action(
+    app_handle: AppHandle,
+    id: String,
+    action_id: String,
+) -> Result<(), String> {
+    let db = registry()?;
+    let stored = db
+        .notifications
+        .get(&id)
+        .await
+        .map_err(|e| format!("读取通知失败: {}", e))?
+        .ok_or_else(|| format!("通知不存在: {}", id))?;
+    let action = stored
+        .action(&action_id)
+        .ok_or_else(|| format!("通知没有该操作: {}", action_id))?;
+    let handler = ACTION_HANDLERS
+        .read()
+        .get(&action.command)
+        .cloned()
+        .ok_or_else(|| format!("未注册的通知命令: {}", action.command))?;
+
+    handler(&app_handle, &action.args)?;
+
+    if let Err(e) = db.notifications.mark_read(&id, chrono::Utc::now().timestamp()).await {
+        warn!("标记通知已读失败: {}", e);
+    }
+    app_handle.state::<AppState>().tray.mark_notification_read(&id);
+    Ok(())
+}
@@ -16,6 +16,7 @@ use chrono::{DateTime, Utc};
 use crate::{
     commands::*,
     state::AppState,
+    utils::event_journal::{self, EventKind},
     database::{
         get_database,
         permission::{
@@ -316,6 +317,13 @@ pub async fn grant_permission(
                 Some(&request.entity_id),
             );
             
+            event_journal::record(EventKind::PermissionGranted, serde_json::json!({
+                "entity_type": request.entity_type,
+                "entity_id": request.entity_id,
+                "permission_type": request.permission_type,
+                "level": request.level,
+            }));
+            
             // 触发权限授予事件
             let _ = app_handle.emit_all("permission-granted", serde_json::json!({
                 "entity_type": request.entity_type,
@@ -369,6 +377,12 @@ pub async fn deny_permission(
                 Some(&request.entity_id),
             );
             
+            event_journal::record(EventKind::PermissionDenied, serde_json::json!({
+                "entity_type": request.entity_type,
+                "entity_id": request.entity_id,
+                "permission_type": request.permission_type,
+            }));
+            
             // 触发权限拒绝事件
             let _ = app_handle.emit_all("permission-denied", serde_json::json!({
                 "entity_type": request.entity_type,
@@ -421,6 +435,12 @@ pub async fn revoke_permission(
                 Some(&request.entity_id),
             );
             
+            event_journal::record(EventKind::PermissionRevoked, serde_json::json!({
+                "entity_type": request.entity_type,
+                "entity_id": request.entity_id,
+                "permission_type": request.permission_type,
+            }));
+            
             // 触发权限撤销事件
             let _ = app_handle.emit_all("permission-revoked", serde_json::json!({
                 "entity_type": request.entity_type,
@@ -0,0 +1,368 @@
+//! # 权限确认命令模块
+//!
+//! 适配器在能力声明中列出所需的权限（`permissions`），执行前由 [`ensure_permissions`] 检查：
+//! 已授予的直接放行，仍在有效期内的拒绝直接拦截，其余的请用户确认，执行一直阻塞到用户作出决定。
+//!
+//! - 主窗口可见时发出 `permission-request` 事件，由前端显示确认框并调用
+//!   `respond_permission_request` 回复；主窗口不可见时改用系统对话框
+//! - 同一实体对同一权限的并发请求合并为一次确认
+//! - 用户选择记住时，决定写入 `permission_grants`，24 小时内不再询问
+//! - 超时未回复视为拒绝（不记住）
+//!
+//! 请求结束后发出 `permission-request-resolved` 事件，用于关闭其他窗口中的确认框。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::database::permission::{PermissionLevel, PermissionType};
+use crate::utils::event_journal::{self, EventKind};
+use crate::utils::security_audit::{self, AuditEventType};
+
+const ADAPTER_ENTITY: &str = "adapter";
+/// 等待用户决定的最长时间
+const CONSENT_TIMEOUT: Duration = Duration::from_secs(120);
+/// 记住决定的时长
+const REMEMBER_HOURS: i64 = 24;
+const PROMPT_WINDOW: &str = "main";
+
+/// 能力声明中的权限需求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRequirement {
+    pub permission_type: PermissionType,
+    pub level: PermissionLevel,
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// 向用户说明为什么需要该权限
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// 等待确认的权限请求（`permission-request` 事件载荷）
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentRequest {
+    pub request_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub permission_type: PermissionType,
+    pub level: PermissionLevel,
+    pub scope: Option<String>,
+    pub reason: Option<String>,
+    /// 截止时间（毫秒时间戳），之后视为拒绝
+    pub deadline: i64,
+}
+
+impl ConsentRequest {
+    fn same_permission(&self, other: &ConsentRequest) -> bool {
+        self.entity_type == other.entity_type
+            && self.entity_id == other.entity_id
+            && self.permission_type == other.permission_type
+            && self.level == other.level
+            && self.scope == other.scope
+    }
+
+    fn describe(&self) -> String {
+        let mut message = format!(
+            "{} {} 请求 {} 权限（级别: {}）",
+            if self.entity_type == ADAPTER_ENTITY { "适配器" } else { self.entity_type.as_str() },
+            self.entity_id,
+            self.permission_type,
+            self.level
+        );
+        if let Some(scope) = &self.scope {
+            message.push_str(&format!("\n范围: {}", scope));
+        }
+        if let Some(reason) = &self.reason {
+            message.push_str(&format!("\n用途: {}", reason));
+        }
+        message.push_str("\n\n是否允许？");
+        message
+    }
+}
+
+/// 用户的决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConsentDecision {
+    granted: bool,
+    /// 24 小时内记住该决定
+    remember: bool,
+}
+
+/// `permission-request-resolved` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+struct ConsentResolved<'a> {
+    request_id: &'a str,
+    granted: bool,
+    remember: bool,
+}
+
+struct PendingConsent {
+    request: ConsentRequest,
+    waiters: Vec<oneshot::Sender<ConsentDecision>>,
+}
+
+lazy_static! {
+    static ref PENDING: Mutex<HashMap<String, PendingConsent>> = Mutex::new(HashMap::new());
+}
+
+/// 登记请求；已有相同的请求在等待时合并。返回 `(请求, 是否需要弹出确认, 接收决定)`
+fn register(request: ConsentRequest) -> (ConsentRequest, bool, oneshot::Receiver<ConsentDecision>) {
+    let (sender, receiver) = oneshot::channel();
+    let mut pending = PENDING.lock();
+    if let Some(existing) = pending.values_mut().find(|p| p.request.same_permission(&request)) {
+        existing.waiters.push(sender);
+        return (existing.request.clone(), false, receiver);
+    }
+    pending.insert(request.request_id.clone(), PendingConsent {
+        request: request.clone(),
+        waiters: vec![sender],
+    });
+    (request, true, receiver)
+}
+
+/// 结束请求并通知所有等待者；请求不存在（已结束）时返回 `None`
+fn resolve(request_id: &str, decision: ConsentDecision) -> Option<ConsentRequest> {
+    let pending = PENDING.lock().remove(request_id)?;
+    for waiter in pending.waiters {
+        let _ = waiter.send(decision);
+    }
+    Some(pending.request)
+}
+
+/// 结束请求，按需记住决定，并通知前端
+fn finish(app_handle: &AppHandle, request_id: &str, decision: ConsentDecision) -> bool {
+    let Some(request) = resolve(request_id, decision) else {
+        return false;
+    };
+    info!(
+        "权限请求 {} 已{}: {}::{} -> {}",
+        request_id,
+        if decision.granted { "允许" } else { "拒绝" },
+        request.entity_type,
+        request.entity_id,
+        request.permission_type
+    );
+
+    let kind = if decision.granted { EventKind::PermissionGranted } else { EventKind::PermissionDenied };
+    event_journal::record(kind, serde_json::json!({
+        "entity_type": request.entity_type,
+        "entity_id": request.entity_id,
+        "permission_type": request.permission_type,
+        "level": request.level,
+        "remember": decision.remember,
+    }));
+    if decision.remember {
+        remember_decision(request.clone(), decision.granted);
+    }
+
+    let _ = app_handle.emit_all("permission-request-resolved", ConsentResolved {
+        request_id,
+        granted: decision.granted,
+        remember: decision.remember,
+    });
+    true
+}
+
+/// 把决定写入 `permission_grants`，24 小时后过期
+fn remember_decision(request: ConsentRequest, granted: bool) {
+    let Some(db) = crate::database::get_database() else {
+        warn!("数据库未初始化，权限决定未保存");
+        return;
+    };
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(REMEMBER_HOURS);
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = db.permission_registry.remember_decision(
+            request.entity_type,
+            request.entity_id,
+            request.permission_type,
+            request.level,
+            request.scope,
+            granted,
+            expires_at,
+        ) {
+            warn!("保存权限决定失败: {}", e);
+        }
+    });
+}
+
+/// 显示确认：主窗口可见时交给前端，否则使用系统对话框
+fn prompt(app_handle: &AppHandle, request: &ConsentRequest) {
+    let frontend_visible = app_handle
+        .get_window(PROMPT_WINDOW)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    if frontend_visible {
+        let _ = app_handle.emit_all("permission-request", request);
+        return;
+    }
+
+    use tauri::api::dialog;
+    let app_handle = app_handle.clone();
+    let request_id = request.request_id.clone();
+    dialog::ask(None::<&tauri::Window>, "权限请求", request.describe(), move |granted| {
+        let remember_question = if granted { "24 小时内不再询问，直接允许？" } else { "24 小时内不再询问，直接拒绝？" };
+        dialog::ask(None::<&tauri::Window>, "记住选择", remember_question, move |remember| {
+            finish(&app_handle, &request_id, ConsentDecision { granted, remember });
+        });
+    });
+}
+
+/// 请用户确认权限，阻塞到用户作出决定或超时；返回是否允许
+pub(crate) async fn request_consent(
+    app_handle: &AppHandle,
+    entity_type: &str,
+    entity_id: &str,
+    requirement: &PermissionRequirement,
+) -> bool {
+    let request = ConsentRequest {
+        request_id: uuid::Uuid::new_v4().to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id: entity_id.to_string(),
+        permission_type: requirement.permission_type.clone(),
+        level: requirement.level.clone(),
+        scope: requirement.scope.clone(),
+        reason: requirement.reason.clone(),
+        deadline: chrono::Utc::now().timestamp_millis() + CONSENT_TIMEOUT.as_millis() as i64,
+    };
+    let (request, is_new, receiver) = register(request);
+    if is_new {
+        info!("等待用户确认权限: {}::{} -> {}", entity_type, entity_id, request.permission_type);
+        prompt(app_handle, &request);
+    }
+
+    match tokio::time::timeout(CONSENT_TIMEOUT, receiver).await {
+        Ok(Ok(decision)) => decision.granted,
+        _ => {
+            warn!("权限请求 {} 超时，视为拒绝", request.request_id);
+            finish(app_handle, &request.request_id, ConsentDecision { granted: false, remember: false });
+            false
+        }
+    }
+}
+
+/// 已保存的决定：`Some(true)` 已授予，`Some(false)` 已拒绝且未过期，`None` 需要询问
+async fn stored_decision(entity_type: &str, entity_id: &str, requirement: &PermissionRequirement) -> Option<bool> {
+    let db = crate::database::get_database()?;
+    let entity_type = entity_type.to_string();
+    let entity_id = entity_id.to_string();
+    let requirement = requirement.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let registry = &db.permission_registry;
+        let scope = requirement.scope.as_deref();
+        if registry.check_permission(&entity_type, &entity_id, &requirement.permission_type, &requirement.level, scope)? {
+            return Ok(Some(true));
+        }
+        if registry.is_denied(&entity_type, &entity_id, &requirement.permission_type, scope)? {
+            return Ok(Some(false));
+        }
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(None)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(decision)) => decision,
+        Ok(Err(e)) => {
+            warn!("读取权限授权失败，改为询问用户: {}", e);
+            None
+        }
+        Err(e) => {
+            warn!("读取权限授权失败，改为询问用户: {}", e);
+            None
+        }
+    }
+}
+
+/// 确保适配器拥有所需的全部权限，缺少的逐项请用户确认
+pub(crate) async fn ensure_permissions(
+    app_handle: &AppHandle,
+    adapter_id: &str,
+    requirements: &[PermissionRequirement],
+) -> Result<(), String> {
+    for requirement in requirements {
+        let granted = match stored_decision(ADAPTER_ENTITY, adapter_id, requirement).await {
+            Some(granted) => granted,
+            None => request_consent(app_handle, ADAPTER_ENTITY, adapter_id, requirement).await,
+        };
+        if !granted {
+            security_audit::log_audit_failure(
+                AuditEventType::PermissionGrant,
+                &format!("适配器请求的 {} 权限未获允许", requirement.permission_type),
+                "用户拒绝",
+                Some(adapter_id),
+            );
+            return Err(format!(
+                "适配器 {} 未获得 {} 权限（级别: {}）",
+                adapter_id, requirement.permission_type, requirement.level
+            ));
+        }
+    }
+    Ok(())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 回复权限请求；请求已结束（超时或已回复）时返回 `false`
+#[tauri::command]
+pub async fn respond_permission_request(
+    app_handle: AppHandle,
+    request_id: String,
+    granted: bool,
+    remember: Option<bool>,
+) -> Result<bool, String> {
+    let decision = ConsentDecision {
+        granted,
+        remember: remember.unwrap_or(false),
+    };
+    Ok(finish(&app_handle, &request_id, decision))
+}
+
+/// 获取正在等待确认的权限请求（前端启动晚于事件时使用）
+#[tauri::command]
+pub async fn get_pending_permission_requests() -> Result<Vec<ConsentRequest>, String> {
+    let mut requests: Vec<ConsentRequest> = PENDING.lock().values().map(|p| p.request.clone()).collect();
+    requests.sort_by_key(|request| request.deadline);
+    Ok(requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(entity_id: &str) -> ConsentRequest {
+        ConsentRequest {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            entity_type: ADAPTER_ENTITY.to_string(),
+            entity_id: entity_id.to_string(),
+            permission_type: PermissionType::FileRead,
+            level: PermissionLevel::Read,
+            scope: None,
+            reason: None,
+            deadline: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_one_decision() {
+        let (first, first_is_new, first_rx) = register(request("consent_test_a"));
+        let (second, second_is_new, second_rx) = register(request("consent_test_a"));
+        let (other, other_is_new, _other_rx) = register(request("consent_test_b"));
+        assert!(first_is_new && !second_is_new && other_is_new);
+        assert_eq!(first.request_id, second.request_id);
+        assert_ne!(first.request_id, other.request_id);
+
+        let decision = ConsentDecision { granted: true, remember: false };
+        assert!(resolve(&first.request_id, decision).is_some());
+        assert_eq!(first_rx.await.unwrap(), decision);
+        assert_eq!(second_rx.await.unwrap(), decision);
+        // 已结束的请求不能再次回复
+        assert!(resolve(&first.request_id, decision).is_none());
+        assert!(resolve(&other.request_id, decision).is_some());
+    }
+}
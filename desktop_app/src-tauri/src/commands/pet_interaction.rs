@@ -0,0 +1,306 @@
+//! # 多角色互动命令模块
+//!
+//! 每个角色实例窗口启动后调用 `register_pet_instance` 加入名册，并自动订阅 `pet-*` 频道：
+//! 名册变化通过 `pet-instances-changed` 推送，互动通过 `pet-interaction` 推送给所有实例，
+//! 参与者按 `start_at`（Unix 毫秒）同时开始播放动作或显示对白。窗口销毁时自动退出名册。
+//! 限流与互动内容的规则见 [`crate::utils::pet_interaction`]。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Window};
+use tracing::{debug, info, warn};
+
+use crate::commands::event_subscription::{publish, CHANNEL_PET_INSTANCES, CHANNEL_PET_INTERACTION};
+use crate::utils::bridge::{ChatMessage, ChatRequest, MessageRole, PythonApiBridge};
+use crate::utils::event_subscription::{self, SubscriptionOptions};
+use crate::utils::pet_interaction::{
+    self, InteractionEngine, InteractionPlan, PetInstance, PetInteractionSettings,
+};
+use crate::utils::resource_budget::{self, WorkKind};
+use crate::utils::watchdog;
+
+/// 互动开始前给各窗口的准备时间（毫秒），保证同时开始
+const START_DELAY_MS: i64 = 500;
+/// 调度器心跳停滞阈值
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(10 * 60);
+
+const DIALOGUE_INSTRUCTION: &str = "你要为两个桌面宠物角色写一段简短的互动对白。\
+每行格式为「名字：台词」，两人交替说话，每句不超过 20 个字，语气轻松可爱，不要旁白和其他说明。";
+
+lazy_static! {
+    static ref SETTINGS: RwLock<PetInteractionSettings> = RwLock::new(PetInteractionSettings::default());
+    static ref ENGINE: Mutex<InteractionEngine> = Mutex::new(InteractionEngine::default());
+}
+
+/// 推送给角色实例的互动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetInteraction {
+    pub id: String,
+    /// 参与的实例窗口
+    pub participants: Vec<String>,
+    #[serde(flatten)]
+    pub plan: InteractionPlan,
+    /// 开始时间（Unix 毫秒）
+    pub start_at: i64,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("pet_interaction.json"))
+}
+
+/// 加载互动设置（启动时调用）
+pub fn initialize_pet_interaction() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read pet interaction settings: {}", e))?;
+    let settings: PetInteractionSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse pet interaction settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn publish_instances(app_handle: &AppHandle) {
+    let instances = ENGINE.lock().instances();
+    publish(app_handle, CHANNEL_PET_INSTANCES, &instances);
+}
+
+/// 窗口销毁时退出名册
+pub fn handle_window_destroyed(app_handle: &AppHandle, window_label: &str) {
+    if ENGINE.lock().unregister(window_label) {
+        debug!("角色实例 {} 已退出", window_label);
+        publish_instances(app_handle);
+    }
+}
+
+/// 请 LLM 生成两人之间的对白
+async fn generate_dialogue(a: &PetInstance, b: &PetInstance, max_lines: usize) -> Result<String, String> {
+    resource_budget::defer(WorkKind::Llm, "pet_interaction").await;
+    let bridge = PythonApiBridge::default().map_err(|e| format!("创建 API 客户端失败: {}", e))?;
+    let request = ChatRequest {
+        messages: vec![
+            ChatMessage {
+                role: MessageRole::System,
+                content: DIALOGUE_INSTRUCTION.to_string(),
+            },
+            ChatMessage {
+                role: MessageRole::User,
+                content: format!("角色：{} 和 {}。由 {} 先开口，共 {} 行以内。", a.name, b.name, a.name, max_lines),
+            },
+        ],
+        model: None,
+        adapter: None,
+        character_id: None,
+        max_tokens: Some(200),
+        temperature: Some(0.9),
+        top_p: None,
+        stream: Some(false),
+        session_id: None,
+        images: Vec::new(),
+    };
+
+    let response = bridge
+        .send_chat_message(request)
+        .await
+        .map_err(|e| format!("互动对白请求失败: {}", e))?;
+    Ok(response
+        .choices
+        .first()
+        .map(|choice| choice.message.content.clone())
+        .unwrap_or_default())
+}
+
+/// 为一对实例决定互动内容：优先对白（允许时随机选择），没有共有动作时也使用对白
+async fn plan_interaction(a: &PetInstance, b: &PetInstance, settings: &PetInteractionSettings) -> InteractionPlan {
+    let (prefer_dialogue, pick) = {
+        let mut rng = rand::thread_rng();
+        (rng.gen_bool(0.5), rng.gen::<usize>())
+    };
+    let motion = pet_interaction::shared_motion(a, b, pick);
+    if let Some(motion) = motion.clone() {
+        if !settings.allow_dialogue || !prefer_dialogue {
+            return InteractionPlan::SyncMotion { motion };
+        }
+    }
+
+    let lines = match generate_dialogue(a, b, settings.max_exchange_lines).await {
+        Ok(text) => pet_interaction::parse_exchange(&text, a, b, settings.max_exchange_lines),
+        Err(e) => {
+            warn!("生成互动对白失败: {}", e);
+            Vec::new()
+        }
+    };
+    match (lines.len() >= 2, motion) {
+        (true, _) => InteractionPlan::Exchange { lines },
+        (false, Some(motion)) => InteractionPlan::SyncMotion { motion },
+        (false, None) => InteractionPlan::Exchange {
+            lines: pet_interaction::fallback_exchange(a, b),
+        },
+    }
+}
+
+/// 尝试发起一次互动；`force` 为 true 时不掷骰子（仍遵守开关与限流）
+async fn try_interact(app_handle: &AppHandle, force: bool) -> Result<Option<PetInteraction>, String> {
+    let settings = SETTINGS.read().clone();
+    if !settings.enabled {
+        return Err("角色互动已关闭".to_string());
+    }
+    if !force && !rand::thread_rng().gen_bool(settings.chance) {
+        return Ok(None);
+    }
+
+    let now = Utc::now().timestamp();
+    let id = uuid::Uuid::new_v4().to_string();
+    let (a, b) = {
+        let mut engine = ENGINE.lock();
+        if let Some(reason) = engine.rate_limited(&settings, now) {
+            return if force { Err(reason.to_string()) } else { Ok(None) };
+        }
+        let pairs = engine.eligible_pairs(&settings, now);
+        if pairs.is_empty() {
+            return if force { Err("没有可以互动的角色实例".to_string()) } else { Ok(None) };
+        }
+        let (a, b) = &pairs[rand::thread_rng().gen_range(0..pairs.len())];
+        let (Some(a), Some(b)) = (engine.instance(a).cloned(), engine.instance(b).cloned()) else {
+            return Ok(None);
+        };
+        // 生成对白期间占住互动位，避免重复发起
+        engine.begin(&id, &a.window_label, &b.window_label, now);
+        (a, b)
+    };
+
+    let plan = plan_interaction(&a, &b, &settings).await;
+    let interaction = PetInteraction {
+        id: id.clone(),
+        participants: vec![a.window_label.clone(), b.window_label.clone()],
+        start_at: Utc::now().timestamp_millis() + START_DELAY_MS,
+        plan,
+    };
+    info!("角色互动: {} 与 {}", a.name, b.name);
+    publish(app_handle, CHANNEL_PET_INTERACTION, &interaction);
+
+    let duration = Duration::from_millis(START_DELAY_MS as u64 + interaction.plan.duration_ms());
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        ENGINE.lock().finish(&id);
+    });
+    Ok(Some(interaction))
+}
+
+/// 启动互动调度器
+pub fn start_pet_interaction_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("pet_interaction", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                let interval = SETTINGS.read().check_interval_secs;
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+                if !SETTINGS.read().enabled {
+                    continue;
+                }
+                if let Err(e) = try_interact(&app_handle, false).await {
+                    debug!("角色互动未发起: {}", e);
+                }
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 当前窗口作为角色实例加入名册
+#[tauri::command]
+pub async fn register_pet_instance(
+    app_handle: AppHandle,
+    window: Window,
+    character_id: String,
+) -> Result<PetInstance, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let character = db
+        .character_registry
+        .get_character_async(&character_id)
+        .await
+        .map_err(|e| format!("读取角色失败: {}", e))?
+        .ok_or_else(|| format!("角色不存在: {}", character_id))?;
+
+    let instance = PetInstance {
+        window_label: window.label().to_string(),
+        character_id,
+        name: character.display_name,
+        motions: character.motions,
+        busy: false,
+    };
+    ENGINE.lock().register(instance.clone());
+    event_subscription::subscribe(window.label(), "pet-*", SubscriptionOptions::default());
+    info!("角色实例加入: {} ({})", instance.window_label, instance.name);
+    publish_instances(&app_handle);
+    Ok(instance)
+}
+
+/// 当前窗口退出名册
+#[tauri::command]
+pub async fn unregister_pet_instance(app_handle: AppHandle, window: Window) -> Result<bool, String> {
+    let removed = ENGINE.lock().unregister(window.label());
+    if removed {
+        publish_instances(&app_handle);
+    }
+    Ok(removed)
+}
+
+/// 标记当前实例是否忙碌（忙碌时不参与互动）
+#[tauri::command]
+pub async fn set_pet_busy(window: Window, busy: bool) -> Result<bool, String> {
+    Ok(ENGINE.lock().set_busy(window.label(), busy))
+}
+
+/// 列出运行中的角色实例
+#[tauri::command]
+pub async fn list_pet_instances() -> Result<Vec<PetInstance>, String> {
+    Ok(ENGINE.lock().instances())
+}
+
+/// 立即发起一次互动（仍遵守开关与限流）
+#[tauri::command]
+pub async fn trigger_pet_interaction(app_handle: AppHandle) -> Result<Option<PetInteraction>, String> {
+    try_interact(&app_handle, true).await
+}
+
+/// 获取互动设置
+#[tauri::command]
+pub async fn get_pet_interaction_settings() -> Result<PetInteractionSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存互动设置
+#[tauri::command]
+pub async fn update_pet_interaction_settings(
+    settings: PetInteractionSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize pet interaction settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write pet interaction settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
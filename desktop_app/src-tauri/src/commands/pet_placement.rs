@@ -0,0 +1,395 @@
+//! # 桌宠摆放命令模块
+//!
+//! 跟踪桌宠窗口所在的显示器与相对位置（几何计算见 [`crate::utils::pet_placement`]），
+//! 保存在 `pet_placement.json`，启动时恢复到上次的显示器：
+//!
+//! - 拖动桌宠时记录所在显示器与相对位置
+//! - 缩放因子变化（换到 DPI 不同的显示器或修改系统缩放）时按逻辑尺寸调整窗口大小
+//! - 所在显示器断开时回到主显示器
+//! - 漫游模式下定时换到下一个显示器
+//!
+//! 桌宠换显示器时发送 `pet-monitor-changed`（`reason` 为 `manual` / `roam` / `disconnected`）。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Position, Size, Window};
+use tracing::{debug, info, warn};
+
+use crate::utils::pet_placement::{
+    self, PetPlacement, PlacementMode, PlacementSettings, Screen,
+};
+use crate::utils::watchdog;
+use crate::utils::window_layout::Rect;
+
+/// 桌宠窗口
+const PET_WINDOW: &str = "main";
+/// 检查漫游与显示器断开的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const CHECK_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// 停止移动后等待该时长再保存
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// 保存的设置与摆放状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PlacementFile {
+    settings: PlacementSettings,
+    placement: PetPlacement,
+}
+
+struct PlacementState {
+    file: PlacementFile,
+    last_roam: Instant,
+    save_generation: u64,
+    /// 由本模块调整、尚未收到调整大小事件的窗口尺寸
+    expected_size: Option<(u32, u32)>,
+}
+
+lazy_static! {
+    static ref STATE: Mutex<PlacementState> = Mutex::new(PlacementState {
+        file: PlacementFile::default(),
+        last_roam: Instant::now(),
+        save_generation: 0,
+        expected_size: None,
+    });
+}
+
+/// 摆放状态与当前显示器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetPlacementStatus {
+    pub settings: PlacementSettings,
+    pub placement: PetPlacement,
+    pub monitors: Vec<Screen>,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("pet_placement.json"))
+}
+
+fn save() -> Result<(), String> {
+    let file = STATE.lock().file.clone();
+    let json_data = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize pet placement: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write pet placement: {}", e))
+}
+
+/// 停止变化一小段时间后保存
+fn schedule_save(app_handle: &AppHandle) {
+    let generation = {
+        let mut state = STATE.lock();
+        state.save_generation += 1;
+        state.save_generation
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        if STATE.lock().save_generation != generation {
+            return;
+        }
+        if let Err(e) = save() {
+            warn!("保存桌宠摆放失败: {}", e);
+        }
+    });
+}
+
+/// 当前显示器列表
+fn screens(window: &Window) -> Result<Vec<Screen>, String> {
+    let primary_name = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    let monitors = window
+        .available_monitors()
+        .map_err(|e| format!("获取显示器列表失败: {}", e))?;
+    Ok(monitors
+        .iter()
+        .map(|m| Screen {
+            name: m.name().cloned(),
+            rect: Rect {
+                x: m.position().x,
+                y: m.position().y,
+                width: m.size().width as i32,
+                height: m.size().height as i32,
+            },
+            scale_factor: m.scale_factor(),
+            primary: primary_name.is_some() && m.name() == primary_name.as_ref(),
+        })
+        .collect())
+}
+
+fn window_rect(window: &Window) -> Option<Rect> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    Some(Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width as i32,
+        height: size.height as i32,
+    })
+}
+
+/// 把桌宠放到显示器上的相对位置，并按该显示器的缩放因子调整大小
+fn place_on(window: &Window, screen: &Screen, placement: &PetPlacement) -> Result<(), String> {
+    let size = window.outer_size().map_err(|e| format!("获取窗口大小失败: {}", e))?;
+    let (mut width, mut height) = (size.width as i32, size.height as i32);
+    if placement.logical_width > 0.0 && placement.logical_height > 0.0 {
+        (width, height) = pet_placement::physical_size(placement.logical_width, placement.logical_height, screen.scale_factor);
+        STATE.lock().expected_size = Some((width as u32, height as u32));
+        window
+            .set_size(Size::Physical(PhysicalSize::new(width as u32, height as u32)))
+            .map_err(|e| format!("调整窗口大小失败: {}", e))?;
+    }
+    let (x, y) = pet_placement::position_in(&screen.rect, (placement.anchor_x, placement.anchor_y), width, height);
+    window
+        .set_position(Position::Physical(PhysicalPosition::new(x, y)))
+        .map_err(|e| format!("移动窗口失败: {}", e))
+}
+
+/// 把桌宠移到另一个显示器，保持相对位置与逻辑尺寸
+fn move_to(app_handle: &AppHandle, window: &Window, screen: &Screen, reason: &str) -> Result<(), String> {
+    let placement = STATE.lock().file.placement.clone();
+    // 移动窗口会同步触发移动事件，不能持有锁
+    place_on(window, screen, &placement)?;
+    {
+        let mut state = STATE.lock();
+        state.file.placement.monitor = screen.name.clone();
+        state.last_roam = Instant::now();
+    }
+    schedule_save(app_handle);
+    info!("桌宠移到显示器 {} ({})", screen.name.as_deref().unwrap_or("未命名显示器"), reason);
+    let _ = app_handle.emit_all("pet-monitor-changed", serde_json::json!({
+        "monitor": screen.name,
+        "scale_factor": screen.scale_factor,
+        "reason": reason,
+    }));
+    Ok(())
+}
+
+/// 加载摆放设置并把桌宠放回上次的显示器（启动时调用）
+pub fn initialize_pet_placement(app_handle: &AppHandle) -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if config_path.exists() {
+        let json_data = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read pet placement: {}", e))?;
+        let file: PlacementFile = serde_json::from_str(&json_data)
+            .map_err(|e| format!("Failed to parse pet placement: {}", e))?;
+        file.settings.validate()?;
+        STATE.lock().file = file;
+    }
+
+    let Some(window) = app_handle.get_window(PET_WINDOW) else {
+        return Ok(());
+    };
+    let placement = STATE.lock().file.placement.clone();
+    if placement.logical_width <= 0.0 {
+        // 首次启动：记录当前位置与尺寸
+        if let Some(rect) = window_rect(&window) {
+            record_rect(&window, rect);
+        }
+        return Ok(());
+    }
+    let screens = screens(&window)?;
+    if let Some(screen) = pet_placement::resolve_screen(&screens, placement.monitor.as_deref()) {
+        place_on(&window, screen, &placement)?;
+        STATE.lock().file.placement.monitor = screen.name.clone();
+    }
+    Ok(())
+}
+
+/// 记录桌宠所在的显示器、相对位置与逻辑尺寸
+fn record_rect(window: &Window, rect: Rect) {
+    let Ok(screens) = screens(window) else {
+        return;
+    };
+    let Some(screen) = pet_placement::screen_containing(&screens, &rect) else {
+        return;
+    };
+    let (anchor_x, anchor_y) = pet_placement::anchor_in(&rect, &screen.rect);
+    let mut state = STATE.lock();
+    let placement = &mut state.file.placement;
+    placement.monitor = screen.name.clone();
+    placement.anchor_x = anchor_x;
+    placement.anchor_y = anchor_y;
+    if placement.logical_width <= 0.0 {
+        placement.logical_width = rect.width as f64 / screen.scale_factor;
+        placement.logical_height = rect.height as f64 / screen.scale_factor;
+    }
+}
+
+/// 处理桌宠窗口移动事件
+pub fn handle_moved(window: &Window, position: PhysicalPosition<i32>) {
+    if window.label() != PET_WINDOW {
+        return;
+    }
+    let Ok(size) = window.outer_size() else {
+        return;
+    };
+    let rect = Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width as i32,
+        height: size.height as i32,
+    };
+    record_rect(window, rect);
+    schedule_save(&window.app_handle());
+}
+
+/// 处理桌宠窗口调整大小事件：记录新的逻辑尺寸（本模块按缩放因子调整的除外）
+pub fn handle_resized(window: &Window, size: PhysicalSize<u32>) {
+    if window.label() != PET_WINDOW || size.width == 0 || size.height == 0 {
+        return;
+    }
+    let Ok(scale_factor) = window.scale_factor() else {
+        return;
+    };
+    {
+        let mut state = STATE.lock();
+        if state.expected_size == Some((size.width, size.height)) {
+            state.expected_size = None;
+            return;
+        }
+        state.file.placement.logical_width = size.width as f64 / scale_factor;
+        state.file.placement.logical_height = size.height as f64 / scale_factor;
+    }
+    schedule_save(&window.app_handle());
+}
+
+/// 处理缩放因子变化：按保存的逻辑尺寸调整大小，并保持在所在显示器上的相对位置
+pub fn handle_scale_factor_changed(window: &Window, scale_factor: f64) {
+    if window.label() != PET_WINDOW {
+        return;
+    }
+    let placement = STATE.lock().file.placement.clone();
+    if placement.logical_width <= 0.0 || placement.logical_height <= 0.0 {
+        return;
+    }
+    let (width, height) = pet_placement::physical_size(placement.logical_width, placement.logical_height, scale_factor);
+    STATE.lock().expected_size = Some((width as u32, height as u32));
+    if let Err(e) = window.set_size(Size::Logical(LogicalSize::new(placement.logical_width, placement.logical_height))) {
+        warn!("按缩放因子调整桌宠大小失败: {}", e);
+        return;
+    }
+    let screen = screens(window)
+        .ok()
+        .and_then(|screens| pet_placement::resolve_screen(&screens, placement.monitor.as_deref()).cloned());
+    if let Some(screen) = screen {
+        let (x, y) = pet_placement::position_in(&screen.rect, (placement.anchor_x, placement.anchor_y), width, height);
+        if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+            warn!("调整桌宠位置失败: {}", e);
+        }
+    }
+    debug!("桌宠缩放因子变为 {}，已按逻辑尺寸调整", scale_factor);
+}
+
+/// 检查显示器断开与漫游
+fn check_placement(app_handle: &AppHandle) {
+    let Some(window) = app_handle.get_window(PET_WINDOW) else {
+        return;
+    };
+    if !window.is_visible().unwrap_or(false) {
+        return;
+    }
+    let Ok(screens) = screens(&window) else {
+        return;
+    };
+    let (settings, monitor, last_roam) = {
+        let state = STATE.lock();
+        (state.file.settings.clone(), state.file.placement.monitor.clone(), state.last_roam)
+    };
+
+    let connected = match &monitor {
+        Some(name) => screens.iter().any(|screen| screen.name.as_deref() == Some(name.as_str())),
+        None => true,
+    };
+    let target = if !connected {
+        pet_placement::resolve_screen(&screens, None).map(|screen| (screen, "disconnected"))
+    } else if settings.mode == PlacementMode::Roam
+        && last_roam.elapsed() >= Duration::from_secs(settings.roam_interval_secs)
+    {
+        pet_placement::next_roam_screen(&screens, monitor.as_deref()).map(|screen| (screen, "roam"))
+    } else {
+        None
+    };
+    if let Some((screen, reason)) = target {
+        if let Err(e) = move_to(app_handle, &window, screen, reason) {
+            warn!("桌宠换显示器失败: {}", e);
+        }
+    }
+}
+
+/// 启动漫游与显示器检查任务
+pub fn start_pet_placement_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("pet_placement", CHECK_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                check_placement(&app_handle);
+            }
+        });
+    });
+}
+
+fn status(window: &Window) -> Result<PetPlacementStatus, String> {
+    let file = STATE.lock().file.clone();
+    Ok(PetPlacementStatus {
+        settings: file.settings,
+        placement: file.placement,
+        monitors: screens(window)?,
+    })
+}
+
+fn pet_window(app_handle: &AppHandle) -> Result<Window, String> {
+    app_handle.get_window(PET_WINDOW).ok_or_else(|| "桌宠窗口不存在".to_string())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 把桌宠移到指定显示器（不指定时为主显示器），保持相对位置；漫游模式下重新开始计时
+#[tauri::command]
+pub async fn set_pet_monitor(app_handle: AppHandle, monitor: Option<String>) -> Result<PetPlacementStatus, String> {
+    let window = pet_window(&app_handle)?;
+    let screens = screens(&window)?;
+    let screen = match monitor.as_deref() {
+        Some(name) => screens
+            .iter()
+            .find(|screen| screen.name.as_deref() == Some(name))
+            .ok_or_else(|| format!("显示器不存在: {}", name))?,
+        None => pet_placement::resolve_screen(&screens, None).ok_or("没有可用的显示器")?,
+    };
+    move_to(&app_handle, &window, screen, "manual")?;
+    status(&window)
+}
+
+/// 获取桌宠摆放状态与显示器列表
+#[tauri::command]
+pub async fn get_pet_placement(app_handle: AppHandle) -> Result<PetPlacementStatus, String> {
+    status(&pet_window(&app_handle)?)
+}
+
+/// 保存摆放设置（固定 / 漫游与漫游间隔）
+#[tauri::command]
+pub async fn update_pet_placement_settings(settings: PlacementSettings) -> Result<(), String> {
+    settings.validate()?;
+    {
+        let mut state = STATE.lock();
+        if state.file.settings.mode != settings.mode {
+            state.last_roam = Instant::now();
+        }
+        state.file.settings = settings;
+    }
+    save()
+}
@@ -1,11 +1,34 @@
-//! 隐私管理命令接口 (Simplified for PostgreSQL migration)
+//! 隐私管理命令接口
+//!
+//! 数据保留策略按类别（聊天记录、日志、性能数据、审计日志、文件）设置保留天数，后台任务按策略
+//! 定期删除过期数据，完成后发出 `privacy-cleanup-completed` 事件。`preview_cleanup` 只统计将被删除
+//! 的数据；`purge_all_personal_data` 清空所有个人数据（包括 Redis 缓存与向量索引）并回收数据库空间。
+//! 清理逻辑见 [`crate::utils::data_cleanup`]。
 
 use crate::database::privacy::PrivacySettings;
+use crate::database::backends::DatabaseBackend;
+use crate::database::vector_search_service::{
+    CONVERSATION_COLLECTION, DOCUMENT_CHUNK_COLLECTION, SESSION_SUMMARY_COLLECTION,
+};
 use crate::utils::anonymizer::{
     AnonymizationOptions, Anonymizer, AnonymousStatistics, UsageStatistics,
 };
-use crate::utils::data_cleanup::{CleanupResult, CleanupType};
-use tauri::{AppHandle, State};
+use crate::utils::clock;
+use crate::utils::data_cleanup::{
+    self, CleanupReport, CleanupResult, CleanupType, DataCleanupManager, RetentionPolicy,
+    RETENTION_CATEGORIES,
+};
+use crate::utils::get_app_log_dir;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+/// 启动后首次按策略清理前的等待时间
+const CLEANUP_STARTUP_DELAY: Duration = Duration::from_secs(5 * 60);
+/// 清空个人数据时删除的 Redis 键（数据库后端与缓存服务的前缀）
+const REDIS_KEY_PATTERN: &str = "zishu*";
 
 /// 隐私管理器状态
 pub struct PrivacyState {
@@ -70,13 +93,120 @@ pub fn get_anonymous_statistics(
     Ok(AnonymousStatistics::default())
 }
 
-/// 清理用户数据
+fn get_retention_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("data_retention.json"))
+}
+
+/// 从磁盘加载数据保留策略（启动时调用）
+pub fn initialize_data_retention() -> Result<(), String> {
+    let config_path = get_retention_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read data retention policy: {}", e))?;
+    let policy: RetentionPolicy = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse data retention policy: {}", e))?;
+    data_cleanup::set_policy(policy)
+}
+
+fn cleanup_manager() -> Result<DataCleanupManager, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let manager = DataCleanupManager::new(db.get_pool());
+    Ok(match get_app_log_dir() {
+        Ok(log_dir) => manager.with_log_dir(log_dir),
+        Err(_) => manager,
+    })
+}
+
+/// 启动按保留策略定期清理的后台任务
+pub fn start_retention_cleanup_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(CLEANUP_STARTUP_DELAY).await;
+        loop {
+            let policy = data_cleanup::current_policy();
+            if policy.auto_cleanup_enabled {
+                let result = match cleanup_manager() {
+                    Ok(manager) => manager.cleanup(&policy, &RETENTION_CATEGORIES, clock::now()).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(report) if report.total_items > 0 => {
+                        let _ = app_handle.emit_all("privacy-cleanup-completed", &report);
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("按保留策略清理数据失败: {}", e),
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(policy.interval_hours.max(1) * 60 * 60)).await;
+        }
+    });
+}
+
+/// 按保留策略清理所选类别的过期数据
 #[tauri::command]
 pub async fn cleanup_user_data(
-    _cleanup_types: Vec<CleanupType>,
-    _app: AppHandle,
+    cleanup_types: Vec<CleanupType>,
+    app: AppHandle,
 ) -> Result<CleanupResult, String> {
-    Ok(CleanupResult::default())
+    let report = cleanup_manager()?
+        .cleanup(&data_cleanup::current_policy(), &cleanup_types, clock::now())
+        .await?;
+    let _ = app.emit_all("privacy-cleanup-completed", &report);
+    Ok(report.result())
+}
+
+/// 预览按当前保留策略将被删除的数据（不删除）
+#[tauri::command]
+pub async fn preview_cleanup() -> Result<CleanupReport, String> {
+    cleanup_manager()?
+        .preview(&data_cleanup::current_policy(), clock::now())
+        .await
+}
+
+/// 清空所有个人数据并回收空间
+///
+/// 删除聊天记录、日志、性能数据、审计日志与文件、剪贴板历史，以及 Redis 中的缓存与 Qdrant 中的向量索引。
+/// 设置与已安装的角色、适配器不受影响。
+#[tauri::command]
+pub async fn purge_all_personal_data(app: AppHandle) -> Result<CleanupResult, String> {
+    warn!("清空所有个人数据");
+    let mut result = cleanup_manager()?.purge_all().await?;
+
+    if let Some(manager) = crate::database::get_database_manager() {
+        if let Some(redis) = manager.redis() {
+            match redis.read().await.delete_matching(REDIS_KEY_PATTERN).await {
+                Ok(deleted) => result.items_deleted += deleted as usize,
+                Err(e) => warn!("清空 Redis 缓存失败: {}", e),
+            }
+        }
+        if let Some(qdrant) = manager.qdrant() {
+            let backend = qdrant.read().await;
+            for collection in [CONVERSATION_COLLECTION, SESSION_SUMMARY_COLLECTION, DOCUMENT_CHUNK_COLLECTION] {
+                if !backend.collection_exists(collection).await.unwrap_or(false) {
+                    continue;
+                }
+                if let Err(e) = backend.drop_collection(collection).await {
+                    warn!("删除向量集合 {} 失败: {}", collection, e);
+                }
+            }
+        }
+    }
+
+    match super::clipboard::clear_clipboard_history(Some(true)).await {
+        Ok(deleted) => result.items_deleted += deleted,
+        Err(e) => warn!("清空剪贴板历史失败: {}", e),
+    }
+
+    info!("个人数据已清空：{} 条记录，释放 {} 字节文件", result.items_deleted, result.bytes_freed);
+    let _ = app.emit_all("personal-data-purged", &result);
+    Ok(result)
 }
 
 /// 导出用户数据（符合GDPR）
@@ -87,25 +217,29 @@ pub async fn export_user_data(_app: AppHandle) -> Result<String, String> {
 
 /// 删除所有用户数据（符合GDPR "被遗忘权"）
 #[tauri::command]
-pub async fn delete_all_user_data(_app: AppHandle) -> Result<(), String> {
-    Ok(())
+pub async fn delete_all_user_data(app: AppHandle) -> Result<(), String> {
+    purge_all_personal_data(app).await.map(|_| ())
 }
 
 /// 获取数据保留策略
 #[tauri::command]
-pub fn get_data_retention_policy(_state: State<PrivacyState>) -> Result<serde_json::Value, String> {
-    Ok(serde_json::json!({
-        "retention_days": 90,
-        "auto_cleanup_enabled": true
-    }))
+pub async fn get_data_retention_policy() -> Result<RetentionPolicy, String> {
+    Ok(data_cleanup::current_policy())
 }
 
-/// 更新数据保留策略
+/// 保存数据保留策略（下一次定期清理时生效）
 #[tauri::command]
-pub fn update_data_retention_policy(
-    _policy: serde_json::Value,
-    _state: State<PrivacyState>,
+pub async fn update_data_retention_policy(
+    policy: RetentionPolicy,
 ) -> Result<(), String> {
+    data_cleanup::set_policy(policy.clone())?;
+
+    let json_data = serde_json::to_string_pretty(&policy)
+        .map_err(|e| format!("Failed to serialize data retention policy: {}", e))?;
+    fs::write(get_retention_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write data retention policy: {}", e))?;
+
+    info!("数据保留策略已更新");
     Ok(())
 }
 
@@ -114,7 +248,6 @@ mod tests {
     use super::*;
     use crate::utils::anonymizer::{AnonymizationOptions, UsageStatistics};
     use crate::utils::data_cleanup::CleanupType;
-    use serde_json::json;
 
     /// 创建测试用的PrivacyState
     fn create_test_privacy_state() -> Result<PrivacyState, String> {
@@ -245,37 +378,16 @@ mod tests {
         assert!(true); // 占位测试
     }
 
-    #[test]
-    fn test_get_data_retention_policy_returns_default() {
-        // Arrange
-        let privacy_state = create_test_privacy_state().unwrap();
-        let state = unsafe { std::mem::transmute(&privacy_state as *const PrivacyState) };
-        
+    #[tokio::test]
+    async fn test_get_data_retention_policy_returns_default() {
         // Act
-        let result = get_data_retention_policy(state);
-        
-        // Assert
-        assert!(result.is_ok());
-        let policy = result.unwrap();
-        assert_eq!(policy["retention_days"], 90);
-        assert_eq!(policy["auto_cleanup_enabled"], true);
-    }
+        let result = get_data_retention_policy().await;
 
-    #[test]
-    fn test_update_data_retention_policy_success() {
-        // Arrange
-        let privacy_state = create_test_privacy_state().unwrap();
-        let state = unsafe { std::mem::transmute(&privacy_state as *const PrivacyState) };
-        let policy = json!({
-            "retention_days": 120,
-            "auto_cleanup_enabled": false
-        });
-        
-        // Act
-        let result = update_data_retention_policy(policy, state);
-        
         // Assert
         assert!(result.is_ok());
+        let policy = result.unwrap();
+        assert!(policy.auto_cleanup_enabled);
+        assert_eq!(policy.logs_days, 30);
     }
 
     // 边界条件测试
@@ -299,21 +411,4 @@ mod tests {
         // 实际测试应该在集成测试中进行
         assert!(true); // 占位测试
     }
-
-    #[test]
-    fn test_update_data_retention_policy_with_invalid_json() {
-        // Arrange
-        let privacy_state = create_test_privacy_state().unwrap();
-        let state = unsafe { std::mem::transmute(&privacy_state as *const PrivacyState) };
-        let invalid_policy = json!({
-            "invalid_field": "test"
-        });
-        
-        // Act
-        let result = update_data_retention_policy(invalid_policy, state);
-        
-        // Assert
-        // 由于当前实现只返回Ok(())，所以这里也会成功
-        assert!(result.is_ok());
-    }
 }
@@ -0,0 +1,299 @@
+//! # 用户资料命令模块
+//!
+//! 创建、切换、删除与列出用户资料（隔离规则见 [`crate::utils::profiles`]）。
+//!
+//! 切换资料时先打开该资料的数据库 schema 并加载设置，全部成功后才更新资料列表，
+//! 失败时恢复之前的资料；然后清空内存中的聊天会话并发出 `profile-switched` 事件，
+//! 前端应重新加载设置与会话列表。
+//! 启动时如果有多个资料或当前资料设置了密码，发出 `profile-selector` 事件让用户选择资料。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::database;
+use crate::utils::config::{get_app_data_dir, get_profile_data_dir, load_config};
+use crate::utils::key_manager::GLOBAL_KEY_MANAGER;
+use crate::utils::profile_archive;
+use crate::utils::profiles::{self, Profile, ProfileRegistry, ProfileRole};
+use crate::AppState;
+
+lazy_static::lazy_static! {
+    /// 串行化资料列表的读改写与切换
+    static ref PROFILE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// 资料列表
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    pub active: String,
+    /// 是否需要让用户选择资料
+    pub selector_required: bool,
+    /// 是否已设置管理员密码
+    pub admin_password_set: bool,
+}
+
+impl From<ProfileRegistry> for ProfileList {
+    fn from(registry: ProfileRegistry) -> Self {
+        Self {
+            selector_required: registry.needs_selector(),
+            admin_password_set: registry.admin_password_hash.is_some(),
+            profiles: registry.profiles,
+            active: registry.active,
+        }
+    }
+}
+
+/// 创建资料请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateProfileRequest {
+    pub name: String,
+    #[serde(default = "default_role")]
+    pub role: ProfileRole,
+    /// 设置后为资料生成受密码保护的加密密钥，切换到该资料时需要输入
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+fn default_role() -> ProfileRole {
+    ProfileRole::Member
+}
+
+fn load_registry() -> Result<ProfileRegistry, String> {
+    Ok(profiles::load(&get_app_data_dir()?))
+}
+
+fn save_registry(registry: &ProfileRegistry) -> Result<(), String> {
+    profiles::save(&get_app_data_dir()?, registry)
+}
+
+/// 加载资料列表并设置当前资料（启动时在数据库与设置加载之前调用）
+pub fn initialize_profiles() -> Result<(), String> {
+    let registry = load_registry()?;
+    profiles::set_active_id(&registry.active);
+    if registry.active != profiles::DEFAULT_PROFILE_ID {
+        info!("当前用户资料: {}", registry.active);
+    }
+    Ok(())
+}
+
+/// 有多个资料或当前资料需要解锁时发出资料选择事件
+pub fn emit_profile_selector(app_handle: &AppHandle) {
+    match load_registry() {
+        Ok(registry) if registry.needs_selector() => {
+            let _ = app_handle.emit_all("profile-selector", ProfileList::from(registry));
+        }
+        Ok(_) => {}
+        Err(e) => warn!("读取资料列表失败: {}", e),
+    }
+}
+
+/// 当前资料的加密管理器（资料未设置密码或尚未解锁时为 `None`）
+pub fn active_encryption() -> Option<crate::utils::encryption::EncryptionManager> {
+    let registry = load_registry().ok()?;
+    let profile = registry.active_profile()?;
+    GLOBAL_KEY_MANAGER.get_manager(&profile.key_id()).ok()
+}
+
+/// 当前资料的名称（默认资料返回 `None`）
+pub fn active_profile_name() -> Option<String> {
+    let registry = load_registry().ok()?;
+    registry
+        .active_profile()
+        .filter(|profile| profile.id != profiles::DEFAULT_PROFILE_ID)
+        .map(|profile| profile.name.clone())
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 列出所有资料
+#[tauri::command]
+pub async fn list_profiles() -> Result<ProfileList, String> {
+    Ok(load_registry()?.into())
+}
+
+/// 创建资料（需要管理员资料）
+#[tauri::command]
+pub async fn create_profile(request: CreateProfileRequest) -> Result<Profile, String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let mut registry = load_registry()?;
+    registry.require_admin()?;
+    if let Some(password) = &request.password {
+        profile_archive::validate_password(password)?;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let profile = registry.create(
+        profiles::new_profile_id(),
+        &request.name,
+        request.role,
+        request.password.is_some(),
+        now,
+    )?;
+
+    // 在资料自己的 schema 中建好全部表，切换时不再依赖 public 中的表
+    let schema = profiles::schema_name(&profile.id).ok_or("资料 ID 无效")?;
+    database::provision_schema(schema.clone())
+        .await
+        .map_err(|e| format!("创建资料数据库失败: {}", e))?;
+
+    let result = (|| -> Result<(), String> {
+        if let Some(password) = &request.password {
+            GLOBAL_KEY_MANAGER
+                .generate_master_key(&profile.key_id(), password, &format!("用户资料加密: {}", profile.name), None)
+                .map_err(|e| format!("生成资料密钥失败: {}", e))?;
+            // 创建时不解锁，切换到该资料时再加载
+            GLOBAL_KEY_MANAGER.unload_key(&profile.key_id());
+        }
+        std::fs::create_dir_all(profiles::profile_dir(&get_app_data_dir()?, &profile.id))
+            .map_err(|e| format!("创建资料目录失败: {}", e))?;
+        save_registry(&registry)
+    })();
+    if let Err(e) = result {
+        if let Err(drop_error) = drop_schema(&schema).await {
+            warn!("清理资料数据库失败: {}", drop_error);
+        }
+        return Err(e);
+    }
+
+    info!("已创建用户资料: {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+/// 设置或修改管理员密码（需要管理员资料；已设置时需要提供当前密码）
+#[tauri::command]
+pub async fn set_admin_password(current_password: Option<String>, password: String) -> Result<(), String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let mut registry = load_registry()?;
+    registry.require_admin()?;
+    profile_archive::validate_password(&password)?;
+    registry.set_admin_password(current_password.as_deref(), &password)?;
+    save_registry(&registry)?;
+    info!("已更新管理员密码");
+    Ok(())
+}
+
+/// 切换到资料；设置了密码的资料需要提供该密码，从其他资料进入管理员资料需要管理员密码
+#[tauri::command]
+pub async fn switch_profile(
+    app_handle: AppHandle,
+    id: String,
+    password: Option<String>,
+) -> Result<Profile, String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    // 切换期间不能进行同步，否则会用一个资料的同步状态比对另一个资料的对话
+    let _sync_guard = crate::commands::device_sync::lock_for_profile_switch().await;
+    let mut registry = load_registry()?;
+    let target = registry.get(&id).cloned().ok_or_else(|| format!("资料不存在: {}", id))?;
+    let previous = registry.active.clone();
+    registry.authorize_switch(&target, password.as_deref())?;
+
+    let unlocked = target.encrypted && target.id != previous;
+    if unlocked {
+        let password = password.ok_or("该资料需要密码")?;
+        GLOBAL_KEY_MANAGER
+            .load_key(&target.key_id(), &password)
+            .map_err(|_| "资料密码错误".to_string())?;
+    }
+
+    // 先打开资料的数据库并加载设置，全部成功后才提交资料列表
+    profiles::set_active_id(&id);
+    let opened = match database::init_database(app_handle.clone()).await {
+        Ok(()) => open_profile_data(&app_handle).await.map_err(|e| (e, true)),
+        Err(e) => Err((format!("打开资料数据库失败: {}", e), false)),
+    };
+    let committed = opened.and_then(|config| {
+        let profile = registry.activate(&id, chrono::Utc::now().timestamp()).map_err(|e| (e, true))?;
+        save_registry(&registry).map_err(|e| (e, true))?;
+        Ok((profile, config))
+    });
+    let (profile, config) = match committed {
+        Ok(committed) => committed,
+        Err((e, database_switched)) => {
+            profiles::set_active_id(&previous);
+            if unlocked {
+                GLOBAL_KEY_MANAGER.unload_key(&target.key_id());
+            }
+            if database_switched {
+                if let Err(restore_error) = database::init_database(app_handle.clone()).await {
+                    warn!("恢复之前资料的数据库失败: {}", restore_error);
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    if previous != target.id {
+        if let Some(previous) = registry.get(&previous) {
+            GLOBAL_KEY_MANAGER.unload_key(&previous.key_id());
+        }
+    }
+
+    // 应用资料的设置并清空内存中的会话
+    let state = app_handle.state::<AppState>();
+    *state.config.lock() = config;
+    for session in state.chat.get_all_sessions() {
+        state.chat.remove_session(&session.session_id);
+    }
+    state.chat.clear_current_session();
+    if let Err(e) = crate::commands::device_sync::initialize_device_sync(&app_handle) {
+        warn!("加载资料的同步设置失败: {}", e);
+    }
+    if let Err(e) = crate::commands::shortcuts::initialize_shortcut_library(&app_handle) {
+        warn!("加载资料的宏与快捷回复失败: {}", e);
+    }
+    // Redis 中的缓存键带资料 ID，内存中的适配器结果缓存需要清空
+    let cleared = crate::utils::adapter_cache::invalidate(None, None);
+    if cleared > 0 {
+        info!("已清空 {} 条适配器结果缓存", cleared);
+    }
+
+    info!("已切换到用户资料: {} ({})", profile.name, profile.id);
+    let _ = app_handle.emit_all("profile-switched", &profile);
+    Ok(profile)
+}
+
+/// 准备当前资料的目录并读取其设置
+async fn open_profile_data(app_handle: &AppHandle) -> Result<crate::AppConfig, String> {
+    std::fs::create_dir_all(get_profile_data_dir()?).map_err(|e| format!("创建资料目录失败: {}", e))?;
+    load_config(app_handle).await.map_err(|e| format!("加载资料设置失败: {}", e))
+}
+
+async fn drop_schema(schema: &str) -> Result<(), String> {
+    let db = database::get_database().ok_or("Database not initialized")?;
+    let client = db.get_pool().get().await.map_err(|e| format!("数据库连接失败: {}", e))?;
+    client
+        .batch_execute(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema))
+        .await
+        .map_err(|e| format!("删除资料数据失败: {}", e))
+}
+
+/// 删除资料及其设置、数据库 schema 与密钥（需要管理员资料）
+#[tauri::command]
+pub async fn delete_profile(id: String) -> Result<(), String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let mut registry = load_registry()?;
+    registry.require_admin()?;
+    let profile = registry.remove(&id)?;
+
+    if let Some(schema) = profiles::schema_name(&profile.id) {
+        drop_schema(&schema).await?;
+    }
+
+    let dir = profiles::profile_dir(&get_app_data_dir()?, &profile.id);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("删除资料目录失败: {}", e))?;
+    }
+    if profile.encrypted {
+        if let Err(e) = GLOBAL_KEY_MANAGER.delete_key(&profile.key_id()) {
+            warn!("删除资料密钥失败: {}", e);
+        }
+    }
+    save_registry(&registry)?;
+
+    info!("已删除用户资料: {} ({})", profile.name, profile.id);
+    Ok(())
+}
@@ -4,18 +4,26 @@
 //! Prompt用于角色扮演，与本地LLM模型配合使用
 //! 
 //! **已迁移到数据库存储**
+//!
+//! 内容与角色设定支持模板语法（变量、条件块、子Prompt引用），
+//! 保存时校验，聊天时用 [`render_for_chat`] 按当前状态填充变量。
 
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use serde::{Deserialize, Serialize};
 use tracing::{info, error, warn};
-use std::collections::HashMap;
-use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Local, Utc};
 
 use crate::{
     commands::*,
+    database::prompt_registry::PromptData,
     state::AppState,
+    utils::prompt_template,
 };
 
+/// 元数据中保存自定义模板变量的键
+const VARIABLES_METADATA_KEY: &str = "variables";
+
 // ================================
 // 数据类型定义
 // ================================
@@ -64,6 +72,9 @@ pub struct CreatePromptRequest {
     pub character_setting: Option<String>,
     /// 是否设为默认
     pub set_as_default: bool,
+    /// 自定义模板变量（可选）
+    #[serde(default)]
+    pub variables: Option<HashMap<String, String>>,
 }
 
 /// 更新Prompt请求
@@ -81,6 +92,9 @@ pub struct UpdatePromptRequest {
     pub character_setting: Option<String>,
     /// 是否设为默认（可选）
     pub set_as_default: Option<bool>,
+    /// 更新的自定义模板变量（可选，整体替换）
+    #[serde(default)]
+    pub variables: Option<HashMap<String, String>>,
 }
 
 /// 删除Prompt请求
@@ -99,6 +113,167 @@ pub struct ApplyPromptRequest {
     pub model_id: Option<String>,
 }
 
+/// 预览Prompt渲染结果请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderPromptPreviewRequest {
+    /// 预览已保存的Prompt（内容与角色设定）
+    pub prompt_id: Option<String>,
+    /// 预览未保存的模板内容（优先于 prompt_id）
+    pub content: Option<String>,
+    /// 临时覆盖的变量值
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// 用于填充 `character` 的角色ID（默认当前角色）
+    pub character_id: Option<String>,
+}
+
+/// Prompt渲染预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreview {
+    /// 渲染后的文本
+    pub rendered: String,
+    /// 渲染时可用的变量及其值
+    pub variables: BTreeMap<String, String>,
+    /// 引用了但没有值的变量
+    pub missing: Vec<String>,
+    /// 用到的子Prompt
+    pub includes: Vec<String>,
+}
+
+// ================================
+// 模板
+// ================================
+
+/// Prompt元数据中的自定义变量
+fn custom_variables(metadata: &HashMap<String, serde_json::Value>) -> BTreeMap<String, String> {
+    let Some(serde_json::Value::Object(variables)) = metadata.get(VARIABLES_METADATA_KEY) else {
+        return BTreeMap::new();
+    };
+    variables
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// 校验自定义变量名并写入元数据
+fn set_custom_variables(
+    metadata: &mut HashMap<String, serde_json::Value>,
+    variables: HashMap<String, String>,
+) -> Result<(), String> {
+    if let Some(name) = variables.keys().find(|name| !prompt_template::is_valid_variable_name(name)) {
+        return Err(format!("变量名无效: {}", name));
+    }
+    metadata.insert(VARIABLES_METADATA_KEY.to_string(), serde_json::json!(variables));
+    Ok(())
+}
+
+/// 可被引用的子Prompt内容（按ID与名称索引）
+async fn load_sub_prompts() -> Result<HashMap<String, String>, String> {
+    let db = crate::database::get_database()
+        .ok_or_else(|| "数据库未初始化".to_string())?;
+    let prompts = db.prompt_registry.get_all_prompts().await
+        .map_err(|e| format!("获取Prompt列表失败: {}", e))?;
+    let mut sub_prompts = HashMap::new();
+    for prompt in prompts {
+        sub_prompts.insert(prompt.name, prompt.content.clone());
+        sub_prompts.insert(prompt.id, prompt.content);
+    }
+    Ok(sub_prompts)
+}
+
+/// 校验待保存Prompt的模板；`previous_name` 为改名前的名称
+async fn validate_templates(prompt: &PromptData, previous_name: Option<&str>) -> Result<(), String> {
+    let mut sub_prompts = load_sub_prompts().await?;
+    // 用待保存的内容替换旧内容，以便发现经由自身的循环引用
+    if let Some(name) = previous_name {
+        sub_prompts.remove(name);
+    }
+    sub_prompts.insert(prompt.id.clone(), prompt.content.clone());
+    sub_prompts.insert(prompt.name.clone(), prompt.content.clone());
+    let resolve = |name: &str| sub_prompts.get(name).cloned();
+
+    prompt_template::validate(&prompt.content, &resolve)
+        .map_err(|e| format!("Prompt模板无效: {}", e))?;
+    if let Some(setting) = &prompt.character_setting {
+        prompt_template::validate(setting, &resolve)
+            .map_err(|e| format!("角色设定模板无效: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 按当前应用状态计算内置变量
+pub(crate) async fn template_values(
+    app: &AppHandle,
+    character_id: Option<&str>,
+    language: Option<&str>,
+) -> BTreeMap<String, String> {
+    let character_id = character_id.map(str::to_string).or_else(|| {
+        app.try_state::<AppState>()
+            .map(|state| state.config.lock().character.current_character.clone())
+    });
+    let character = match (character_id, crate::database::get_database()) {
+        (Some(id), Some(db)) => Some(
+            db.character_registry
+                .get_character_async(&id)
+                .await
+                .ok()
+                .flatten()
+                .map(|c| if c.display_name.is_empty() { c.name } else { c.display_name })
+                .unwrap_or(id),
+        ),
+        (id, _) => id,
+    };
+    prompt_template::builtin_values(
+        Local::now(),
+        super::profiles::active_profile_name(),
+        character,
+        language.map(str::to_string),
+    )
+}
+
+/// 渲染聊天时使用的Prompt；模板出错时保留原文
+pub(crate) async fn render_for_chat(
+    app: &AppHandle,
+    mut prompt: Prompt,
+    character_id: Option<&str>,
+    language: Option<&str>,
+) -> Prompt {
+    let sub_prompts = match load_sub_prompts().await {
+        Ok(sub_prompts) => sub_prompts,
+        Err(e) => {
+            warn!("加载子Prompt失败: {}", e);
+            HashMap::new()
+        }
+    };
+    let resolve = |name: &str| sub_prompts.get(name).cloned();
+    let mut variables = template_values(app, character_id, language).await;
+    variables.extend(custom_variables(&prompt.metadata));
+
+    let render = |template: &str| match prompt_template::render(template, &variables, &resolve) {
+        Ok(output) => {
+            if !output.missing.is_empty() {
+                warn!("Prompt {} 的变量没有值: {}", prompt.name, output.missing.join(", "));
+            }
+            output.text
+        }
+        Err(e) => {
+            warn!("渲染Prompt {} 失败，使用原文: {}", prompt.name, e);
+            template.to_string()
+        }
+    };
+    let content = render(&prompt.content);
+    let character_setting = prompt.character_setting.as_deref().map(render);
+    prompt.content = content;
+    prompt.character_setting = character_setting;
+    prompt
+}
+
 // ================================
 // 命令处理器
 // ================================
@@ -165,7 +340,7 @@ pub async fn create_prompt(
     let prompt_id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp();
     
-    let db_prompt = crate::database::prompt_registry::PromptData {
+    let mut db_prompt = PromptData {
         id: prompt_id.clone(),
         name: request.name.clone(),
         content: request.content.clone(),
@@ -180,6 +355,15 @@ pub async fn create_prompt(
         metadata: HashMap::new(),
     };
     
+    if let Some(variables) = request.variables.clone() {
+        if let Err(e) = set_custom_variables(&mut db_prompt.metadata, variables) {
+            return Ok(CommandResponse::error(e));
+        }
+    }
+    if let Err(e) = validate_templates(&db_prompt, None).await {
+        return Ok(CommandResponse::error(e));
+    }
+    
     match db.prompt_registry.create_prompt(db_prompt.clone()).await {
         Ok(_) => {
             if request.set_as_default {
@@ -238,7 +422,8 @@ pub async fn update_prompt(
     };
     
     // 更新字段
-    let updated_prompt = crate::database::prompt_registry::PromptData {
+    let previous_name = existing_prompt.name.clone();
+    let mut updated_prompt = PromptData {
         id: existing_prompt.id.clone(),
         name: request.name.unwrap_or(existing_prompt.name),
         content: request.content.unwrap_or(existing_prompt.content),
@@ -253,6 +438,15 @@ pub async fn update_prompt(
         metadata: existing_prompt.metadata.clone(),
     };
     
+    if let Some(variables) = request.variables {
+        if let Err(e) = set_custom_variables(&mut updated_prompt.metadata, variables) {
+            return Ok(CommandResponse::error(e));
+        }
+    }
+    if let Err(e) = validate_templates(&updated_prompt, Some(&previous_name)).await {
+        return Ok(CommandResponse::error(e));
+    }
+    
     match db.prompt_registry.update_prompt(&request.prompt_id, updated_prompt.clone()).await {
         Ok(_) => {
             if request.set_as_default.unwrap_or(false) {
@@ -438,6 +632,56 @@ pub async fn get_current_prompt(
     }
 }
 
+/// 预览Prompt模板的渲染结果
+#[tauri::command]
+pub async fn render_prompt_preview(
+    request: RenderPromptPreviewRequest,
+    app_handle: AppHandle,
+    _state: State<'_, AppState>,
+) -> Result<CommandResponse<PromptPreview>, String> {
+    let db = crate::database::get_database()
+        .ok_or_else(|| "数据库未初始化".to_string())?;
+    
+    let saved = match &request.prompt_id {
+        Some(prompt_id) => match db.prompt_registry.get_prompt(prompt_id).await {
+            Ok(Some(p)) => Some(p),
+            Ok(None) => return Ok(CommandResponse::error(format!("Prompt不存在: {}", prompt_id))),
+            Err(e) => return Ok(CommandResponse::error(format!("获取Prompt失败: {}", e))),
+        },
+        None => None,
+    };
+    let template = match (request.content, &saved) {
+        (Some(content), _) => content,
+        (None, Some(p)) => match &p.character_setting {
+            Some(setting) => format!("{}\n\n{}", p.content, setting),
+            None => p.content.clone(),
+        },
+        (None, None) => return Ok(CommandResponse::error("需要提供Prompt ID或模板内容".to_string())),
+    };
+    
+    let language = crate::utils::i18n::language();
+    let mut variables = template_values(&app_handle, request.character_id.as_deref(), Some(&language)).await;
+    if let Some(p) = &saved {
+        variables.extend(custom_variables(&p.metadata));
+    }
+    variables.extend(request.variables);
+    
+    let sub_prompts = match load_sub_prompts().await {
+        Ok(sub_prompts) => sub_prompts,
+        Err(e) => return Ok(CommandResponse::error(e)),
+    };
+    let resolve = |name: &str| sub_prompts.get(name).cloned();
+    match prompt_template::render(&template, &variables, &resolve) {
+        Ok(output) => Ok(CommandResponse::success(PromptPreview {
+            rendered: output.text,
+            variables,
+            missing: output.missing,
+            includes: output.includes,
+        })),
+        Err(e) => Ok(CommandResponse::error(format!("Prompt模板无效: {}", e))),
+    }
+}
+
 // ================================
 // 命令元数据
 // ================================
@@ -515,5 +759,15 @@ pub fn get_command_metadata() -> HashMap<String, CommandMetadata> {
         category: "prompt".to_string(),
     });
     
+    metadata.insert("render_prompt_preview".to_string(), CommandMetadata {
+        name: "render_prompt_preview".to_string(),
+        description: "预览Prompt模板的渲染结果".to_string(),
+        input_type: Some("RenderPromptPreviewRequest".to_string()),
+        output_type: Some("PromptPreview".to_string()),
+        required_permission: PermissionLevel::Public,
+        is_async: true,
+        category: "prompt".to_string(),
+    });
+    
     metadata
 }
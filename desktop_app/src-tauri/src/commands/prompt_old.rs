@@ -207,12 +207,11 @@ pub async fn create_prompt(
 #[tauri::command]
 pub async fn update_prompt(
     request: UpdatePromptRequest,
-    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<Prompt>, String> {
     info!("更新Prompt: {}", request.prompt_id);
     
-    match update_prompt_internal(&request, &app_handle).await {
+    match update_prompt_internal(&request).await {
         Ok(prompt) => {
             info!("Prompt更新成功: {}", prompt.id);
             Ok(CommandResponse::success_with_message(
@@ -231,12 +230,11 @@ pub async fn update_prompt(
 #[tauri::command]
 pub async fn delete_prompt(
     request: DeletePromptRequest,
-    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<bool>, String> {
     info!("删除Prompt: {}", request.prompt_id);
     
-    match delete_prompt_internal(&request, &app_handle).await {
+    match delete_prompt_internal(&request).await {
         Ok(_) => {
             info!("Prompt删除成功: {}", request.prompt_id);
             Ok(CommandResponse::success_with_message(
@@ -255,12 +253,11 @@ pub async fn delete_prompt(
 #[tauri::command]
 pub async fn apply_prompt(
     request: ApplyPromptRequest,
-    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<bool>, String> {
     info!("应用Prompt: {}", request.prompt_id);
     
-    match apply_prompt_internal(&request, &app_handle, &state).await {
+    match apply_prompt_internal(&request, &state).await {
         Ok(_) => {
             info!("Prompt应用成功: {}", request.prompt_id);
             Ok(CommandResponse::success_with_message(
@@ -279,12 +276,11 @@ pub async fn apply_prompt(
 #[tauri::command]
 pub async fn get_prompt(
     prompt_id: String,
-    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<Prompt>, String> {
     info!("获取Prompt详情: {}", prompt_id);
     
-    match get_prompt_by_id(&prompt_id, &app_handle).await {
+    match get_prompt_by_id(&prompt_id).await {
         Ok(Some(prompt)) => {
             info!("成功获取Prompt详情: {}", prompt.name);
             Ok(CommandResponse::success(prompt))
@@ -303,12 +299,11 @@ pub async fn get_prompt(
 /// 获取当前使用的Prompt
 #[tauri::command]
 pub async fn get_current_prompt(
-    app_handle: AppHandle,
     _state: State<'_, AppState>,
 ) -> Result<CommandResponse<Option<Prompt>>, String> {
     info!("获取当前使用的Prompt");
     
-    match get_default_prompt(&app_handle).await {
+    match get_default_prompt().await {
         Ok(prompt) => {
             Ok(CommandResponse::success(prompt))
         }
@@ -324,8 +319,8 @@ pub async fn get_current_prompt(
 // ================================
 
 /// 从存储中获取所有Prompt
-async fn get_prompts_from_storage(app_handle: &AppHandle) -> Result<Vec<Prompt>, String> {
-    let prompts_dir = get_prompts_directory(app_handle)?;
+async fn get_prompts_from_storage() -> Result<Vec<Prompt>, String> {
+    let prompts_dir = get_prompts_directory()?;
     
     if !prompts_dir.exists() {
         std::fs::create_dir_all(&prompts_dir).map_err(|e| {
@@ -354,7 +349,6 @@ async fn get_prompts_from_storage(app_handle: &AppHandle) -> Result<Vec<Prompt>,
 /// 创建Prompt
 async fn create_prompt_internal(
     request: &CreatePromptRequest,
-    app_handle: &AppHandle,
 ) -> Result<Prompt, String> {
     // 验证输入
     if request.name.trim().is_empty() {
@@ -367,7 +361,7 @@ async fn create_prompt_internal(
     
     // 如果设为默认，先取消其他默认Prompt
     if request.set_as_default {
-        unset_all_default_prompts(app_handle).await?;
+        unset_all_default_prompts().await?;
     }
     
     // 生成Prompt ID
@@ -390,7 +384,7 @@ async fn create_prompt_internal(
     };
     
     // 保存Prompt
-    save_prompt_to_index(&prompt, app_handle)?;
+    save_prompt_to_index(&prompt)?;
     
     Ok(prompt)
 }
@@ -398,9 +392,8 @@ async fn create_prompt_internal(
 /// 更新Prompt
 async fn update_prompt_internal(
     request: &UpdatePromptRequest,
-    app_handle: &AppHandle,
 ) -> Result<Prompt, String> {
-    let mut prompts = get_prompts_from_storage(app_handle).await?;
+    let mut prompts = get_prompts_from_storage().await?;
     
     let prompt = prompts.iter_mut()
         .find(|p| p.id == request.prompt_id)
@@ -425,7 +418,7 @@ async fn update_prompt_internal(
     
     if let Some(set_as_default) = request.set_as_default {
         if set_as_default {
-            unset_all_default_prompts(app_handle).await?;
+            unset_all_default_prompts().await?;
         }
         prompt.is_default = set_as_default;
     }
@@ -436,7 +429,7 @@ async fn update_prompt_internal(
     let prompt_clone = prompt.clone();
     
     // 保存更新（现在可以安全地借用prompts，因为prompt的借用已经结束）
-    save_all_prompts_to_index(&prompts, app_handle)?;
+    save_all_prompts_to_index(&prompts)?;
     
     Ok(prompt_clone)
 }
@@ -444,13 +437,12 @@ async fn update_prompt_internal(
 /// 删除Prompt
 async fn delete_prompt_internal(
     request: &DeletePromptRequest,
-    app_handle: &AppHandle,
 ) -> Result<(), String> {
-    let mut prompts = get_prompts_from_storage(app_handle).await?;
+    let mut prompts = get_prompts_from_storage().await?;
     
     prompts.retain(|p| p.id != request.prompt_id);
     
-    save_all_prompts_to_index(&prompts, app_handle)?;
+    save_all_prompts_to_index(&prompts)?;
     
     Ok(())
 }
@@ -458,17 +450,16 @@ async fn delete_prompt_internal(
 /// 应用Prompt
 async fn apply_prompt_internal(
     request: &ApplyPromptRequest,
-    app_handle: &AppHandle,
     _state: &AppState,
 ) -> Result<(), String> {
-    let mut prompts = get_prompts_from_storage(app_handle).await?;
+    let mut prompts = get_prompts_from_storage().await?;
     
     let prompt = prompts.iter_mut()
         .find(|p| p.id == request.prompt_id)
         .ok_or("Prompt不存在")?;
     
     // 取消所有默认Prompt
-    unset_all_default_prompts(app_handle).await?;
+    unset_all_default_prompts().await?;
     
     // 设置为默认
     prompt.is_default = true;
@@ -482,7 +473,7 @@ async fn apply_prompt_internal(
     }
     
     // 重新加载所有Prompt以更新状态
-    let mut all_prompts = get_prompts_from_storage(app_handle).await?;
+    let mut all_prompts = get_prompts_from_storage().await?;
     for p in all_prompts.iter_mut() {
         if p.id == request.prompt_id {
             p.is_default = true;
@@ -497,7 +488,7 @@ async fn apply_prompt_internal(
         }
     }
     
-    save_all_prompts_to_index(&all_prompts, app_handle)?;
+    save_all_prompts_to_index(&all_prompts)?;
     
     Ok(())
 }
@@ -505,43 +496,40 @@ async fn apply_prompt_internal(
 /// 根据ID获取Prompt
 async fn get_prompt_by_id(
     prompt_id: &str,
-    app_handle: &AppHandle,
 ) -> Result<Option<Prompt>, String> {
-    let prompts = get_prompts_from_storage(app_handle).await?;
+    let prompts = get_prompts_from_storage().await?;
     Ok(prompts.into_iter().find(|p| p.id == prompt_id))
 }
 
 /// 获取默认Prompt
-async fn get_default_prompt(app_handle: &AppHandle) -> Result<Option<Prompt>, String> {
-    let prompts = get_prompts_from_storage(app_handle).await?;
+async fn get_default_prompt() -> Result<Option<Prompt>, String> {
+    let prompts = get_prompts_from_storage().await?;
     Ok(prompts.into_iter().find(|p| p.is_default && p.is_enabled))
 }
 
 /// 取消所有默认Prompt
-async fn unset_all_default_prompts(app_handle: &AppHandle) -> Result<(), String> {
-    let mut prompts = get_prompts_from_storage(app_handle).await?;
+async fn unset_all_default_prompts() -> Result<(), String> {
+    let mut prompts = get_prompts_from_storage().await?;
     
     for prompt in prompts.iter_mut() {
         prompt.is_default = false;
     }
     
-    save_all_prompts_to_index(&prompts, app_handle)?;
+    save_all_prompts_to_index(&prompts)?;
     
     Ok(())
 }
 
 /// 获取Prompt存储目录
-fn get_prompts_directory(app_handle: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app_handle.path_resolver()
-        .app_data_dir()
-        .ok_or("无法获取应用数据目录")?;
+fn get_prompts_directory() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
     
     Ok(app_data_dir.join("prompts"))
 }
 
 /// 保存Prompt到索引
-fn save_prompt_to_index(prompt: &Prompt, app_handle: &AppHandle) -> Result<(), String> {
-    let prompts_dir = get_prompts_directory(app_handle)?;
+fn save_prompt_to_index(prompt: &Prompt) -> Result<(), String> {
+    let prompts_dir = get_prompts_directory()?;
     let index_file = prompts_dir.join("prompts_index.json");
     
     let mut prompts = if index_file.exists() {
@@ -560,12 +548,12 @@ fn save_prompt_to_index(prompt: &Prompt, app_handle: &AppHandle) -> Result<(), S
         prompts.push(prompt.clone());
     }
     
-    save_all_prompts_to_index(&prompts, app_handle)
+    save_all_prompts_to_index(&prompts)
 }
 
 /// 保存所有Prompt到索引
-fn save_all_prompts_to_index(prompts: &[Prompt], app_handle: &AppHandle) -> Result<(), String> {
-    let prompts_dir = get_prompts_directory(app_handle)?;
+fn save_all_prompts_to_index(prompts: &[Prompt]) -> Result<(), String> {
+    let prompts_dir = get_prompts_directory()?;
     std::fs::create_dir_all(&prompts_dir).map_err(|e| {
         format!("创建Prompt目录失败: {}", e)
     })?;
@@ -0,0 +1,142 @@
+//! # 快速对话弹窗命令模块
+//!
+//! 绑定到 `quick-chat` 动作的全局快捷键会在桌宠旁边弹出一个无边框的小输入框（`quick-chat` 窗口）。
+//! 输入的内容经 [`chat::send_message_handler`] 发送，回复作为聊天回复加入桌宠的对话气泡队列
+//! （见 [`speech_queue`]）；弹窗连续使用同一个会话。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager, PhysicalPosition, Position, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::commands::chat::{self, ChatResponse, SendMessageInput};
+use crate::commands::speech_queue;
+use crate::utils::speech_queue::{Utterance, UtteranceKind};
+use crate::utils::window_layout::{self, Rect};
+
+pub const QUICK_CHAT_WINDOW_LABEL: &str = "quick-chat";
+/// 弹窗尺寸（逻辑像素）
+const POPUP_WIDTH: f64 = 360.0;
+const POPUP_HEIGHT: f64 = 56.0;
+/// 弹窗与桌宠之间的间距（逻辑像素）
+const POPUP_GAP: f64 = 8.0;
+
+lazy_static! {
+    /// 弹窗使用的会话，首次回复后确定
+    static ref SESSION_ID: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// 弹窗在桌宠旁边的位置（物理像素）；桌宠窗口不存在时返回 `None`，使用默认位置
+fn popup_position(app_handle: &AppHandle) -> Option<(i32, i32)> {
+    let pet = app_handle.get_window("main")?;
+    let scale = pet.scale_factor().ok()?;
+    let (position, size) = (pet.outer_position().ok()?, pet.outer_size().ok()?);
+    let monitor = pet.current_monitor().ok().flatten()?;
+
+    let anchor = Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width as i32,
+        height: size.height as i32,
+    };
+    let screen = Rect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width as i32,
+        height: monitor.size().height as i32,
+    };
+    Some(window_layout::anchor_popup(
+        anchor,
+        (POPUP_WIDTH * scale) as i32,
+        (POPUP_HEIGHT * scale) as i32,
+        screen,
+        (POPUP_GAP * scale) as i32,
+    ))
+}
+
+/// 打开（或重新定位并聚焦）快速对话弹窗
+pub fn open_quick_chat(app_handle: &AppHandle) -> Result<(), String> {
+    let window = match app_handle.get_window(QUICK_CHAT_WINDOW_LABEL) {
+        Some(window) => window,
+        None => WindowBuilder::new(
+            app_handle,
+            QUICK_CHAT_WINDOW_LABEL,
+            WindowUrl::App("index.html#/quick-chat".into()),
+        )
+        .title("Zishu Sensei - 快速对话")
+        .inner_size(POPUP_WIDTH, POPUP_HEIGHT)
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("创建快速对话窗口失败: {}", e))?,
+    };
+
+    if let Some((x, y)) = popup_position(app_handle) {
+        if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+            warn!("移动快速对话窗口失败: {}", e);
+        }
+    }
+    window.show().map_err(|e| format!("显示快速对话窗口失败: {}", e))?;
+    let _ = window.set_focus();
+    Ok(())
+}
+
+fn hide_quick_chat(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window(QUICK_CHAT_WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 发送弹窗中输入的内容，回复以对话气泡显示在桌宠旁边
+///
+/// 返回 `send_message` 的结果；需要确认费用时弹窗保持打开，前端带 `confirm_cost` 重新发送。
+#[tauri::command]
+pub async fn submit_quick_chat(
+    app_handle: AppHandle,
+    text: String,
+    confirm_cost: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let input = SendMessageInput {
+        message: text,
+        session_id: SESSION_ID.lock().clone(),
+        model: None,
+        adapter: None,
+        character_id: None,
+        max_tokens: None,
+        temperature: None,
+        top_p: None,
+        stream: Some(false),
+        context_messages: None,
+        confirm_cost,
+    };
+    let result = chat::send_message_handler(input, app_handle.clone()).await?;
+
+    let Ok(response) = serde_json::from_value::<ChatResponse>(result.clone()) else {
+        return Ok(result);
+    };
+    *SESSION_ID.lock() = Some(response.session_id.clone());
+    hide_quick_chat(&app_handle);
+
+    let utterance = Utterance::new(UtteranceKind::Reply, response.message, QUICK_CHAT_WINDOW_LABEL)
+        .in_session(response.session_id, Some(response.message_id));
+    speech_queue::say(&app_handle, utterance);
+    Ok(result)
+}
+
+/// 关闭快速对话弹窗（`new_session` 为 true 时下次使用新会话）
+#[tauri::command]
+pub async fn close_quick_chat(app_handle: AppHandle, new_session: Option<bool>) -> Result<(), String> {
+    hide_quick_chat(&app_handle);
+    if new_session.unwrap_or(false) {
+        *SESSION_ID.lock() = None;
+        info!("快速对话会话已重置");
+    }
+    Ok(())
+}
@@ -10,6 +10,9 @@ use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::State;
 
+use crate::utils::metric_registry;
+use crate::utils::resource_accounting::{self, Subsystem};
+
 // ============================================================================
 // 类型定义
 // ============================================================================
@@ -349,6 +352,8 @@ pub fn record_render_performance(
         .map_err(|e| e.to_string())?
         .as_millis() as u64;
 
+    metric_registry::observe(metric_registry::RENDER_DURATION, &[], render_time);
+
     let record = RenderRecord {
         component_name,
         render_time,
@@ -366,6 +371,13 @@ pub fn record_render_performance(
     Ok(())
 }
 
+/// 更新帧相关的指标
+fn set_frame_gauges(fps: f64, frame_time: f64, draw_calls: usize) {
+    metric_registry::set_gauge(metric_registry::RENDER_FPS, &[], fps);
+    metric_registry::set_gauge(metric_registry::RENDER_FRAME_TIME, &[], frame_time);
+    metric_registry::set_gauge(metric_registry::RENDER_DRAW_CALLS, &[], draw_calls as f64);
+}
+
 /// 记录帧性能
 #[tauri::command]
 pub fn record_frame_performance(
@@ -379,6 +391,8 @@ pub fn record_frame_performance(
         .map_err(|e| e.to_string())?
         .as_millis() as u64;
 
+    set_frame_gauges(fps, frame_time, draw_calls);
+
     let record = FrameRecord {
         timestamp,
         frame_time,
@@ -405,6 +419,10 @@ pub fn update_webgl_stats(
     fps: f64,
     state: State<'_, Arc<Mutex<RenderingState>>>,
 ) -> Result<(), String> {
+    set_frame_gauges(fps, frame_time, draw_calls);
+    metric_registry::set_gauge(metric_registry::RENDER_TEXTURE_MEMORY, &[], texture_memory as f64);
+    resource_accounting::set_gpu_memory(Subsystem::Live2DRenderer, texture_memory as u64);
+
     let stats = WebGLPerformanceStats {
         draw_calls,
         triangles,
@@ -0,0 +1,83 @@
+//! # 资源预算命令模块
+//!
+//! 读取与更新用户设定的 CPU / 内存 / 网络预算（保存在 `resource_budget_settings.json`），
+//! 并查询当前的资源分配。分配逻辑见 [`crate::utils::resource_budget`]；分配变化时向前端
+//! 发出 `resource-budget-changed` 事件，渲染层据此调整帧率上限。
+//!
+//! `get_resource_breakdown` 按子系统列出资源占用（核算方式见 [`crate::utils::resource_accounting`]）。
+
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::utils::resource_accounting::{self, ResourceBreakdown};
+use crate::utils::resource_budget::{self, Allocation, BudgetStatus, ResourceBudget};
+
+fn get_budget_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("resource_budget_settings.json"))
+}
+
+/// 把变化后的分配通知订阅了资源预算频道的窗口
+pub fn broadcast_allocation(app_handle: &AppHandle, allocation: &Allocation) {
+    crate::commands::event_subscription::publish(
+        app_handle,
+        crate::commands::event_subscription::CHANNEL_RESOURCE_BUDGET,
+        allocation,
+    );
+}
+
+/// 从磁盘加载资源预算（启动时调用）
+pub fn initialize_resource_budget() -> Result<(), String> {
+    let config_path = get_budget_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read resource budget settings: {}", e))?;
+    let budget: ResourceBudget = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse resource budget settings: {}", e))?;
+    resource_budget::set_budget(budget);
+    Ok(())
+}
+
+/// 获取资源预算状态（预算、平滑后的使用量、压力等级、分配与被推迟的任务）
+#[tauri::command]
+pub async fn get_resource_budget_status() -> Result<BudgetStatus, String> {
+    Ok(resource_budget::status())
+}
+
+/// 更新资源预算
+#[tauri::command]
+pub async fn update_resource_budget(
+    app_handle: AppHandle,
+    budget: ResourceBudget,
+) -> Result<BudgetStatus, String> {
+    budget.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&budget)
+        .map_err(|e| format!("Failed to serialize resource budget settings: {}", e))?;
+    fs::write(get_budget_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write resource budget settings: {}", e))?;
+
+    info!(
+        "资源预算已更新: enabled={}, cpu={}%, memory={}MB, network={}KB/s",
+        budget.enabled, budget.cpu_percent, budget.memory_mb, budget.network_kbps
+    );
+    if let Some(allocation) = resource_budget::set_budget(budget) {
+        broadcast_allocation(&app_handle, &allocation);
+    }
+    Ok(resource_budget::status())
+}
+
+/// 获取各子系统的资源占用（系统监控最近一次采样）
+#[tauri::command]
+pub async fn get_resource_breakdown() -> Result<ResourceBreakdown, String> {
+    resource_accounting::latest().ok_or_else(|| "尚未采集资源数据，请确认系统监控已启动".to_string())
+}
@@ -0,0 +1,74 @@
+//! # 安全模式命令模块
+//!
+//! 查询本次启动是否处于安全模式以及跳过了哪些组件，并提供以安全模式 / 正常模式
+//! 重启的入口（供崩溃对话框与设置页使用）。判定逻辑见 [`crate::utils::safe_mode`]。
+
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::utils::safe_mode::{self, SafeModeReport, SkippedKind};
+use crate::AppConfig;
+
+/// 记录安全模式下未启用的主题
+pub fn record_skipped_theme(config: &AppConfig) {
+    let default_theme = AppConfig::default().theme.current_theme;
+    if config.theme.current_theme != default_theme {
+        safe_mode::skip_with_detail(
+            SkippedKind::Theme,
+            &config.theme.current_theme,
+            Some(format!("已使用默认主题 {}", default_theme)),
+        );
+    }
+    if config.theme.custom_css.as_deref().is_some_and(|css| !css.trim().is_empty()) {
+        safe_mode::skip_with_detail(SkippedKind::Theme, "custom_css", Some("未加载自定义 CSS".to_string()));
+    }
+}
+
+/// 记录安全模式下未加载的已启用适配器
+pub async fn record_skipped_adapters() {
+    let Some(db) = crate::database::get_database() else {
+        return;
+    };
+    match db.adapter_registry.get_enabled_adapters().await {
+        Ok(adapters) => {
+            for adapter in adapters {
+                safe_mode::skip_with_detail(SkippedKind::Adapter, &adapter.id, Some(adapter.display_name));
+            }
+        }
+        Err(e) => warn!("读取已启用的适配器失败: {}", e),
+    }
+}
+
+/// 在安全模式下覆盖主题配置（不修改磁盘上的配置）
+pub fn apply_theme_override(config: &mut AppConfig) {
+    if safe_mode::is_active() {
+        config.theme = AppConfig::default().theme;
+    }
+}
+
+/// 获取安全模式报告
+#[tauri::command]
+pub async fn get_safe_mode_report() -> Result<SafeModeReport, String> {
+    Ok(safe_mode::report())
+}
+
+/// 以安全模式重启应用
+#[tauri::command]
+pub async fn restart_in_safe_mode(app_handle: AppHandle) -> Result<(), String> {
+    safe_mode::request_next_launch()?;
+    info!("请求以安全模式重启");
+    safe_mode::mark_clean_exit();
+    app_handle.restart();
+    Ok(())
+}
+
+/// 退出安全模式（以正常模式重启）
+#[tauri::command]
+pub async fn exit_safe_mode(app_handle: AppHandle) -> Result<(), String> {
+    info!("退出安全模式并重启");
+    // 用户主动退出安全模式时不再沿用之前的崩溃计数
+    safe_mode::mark_stable();
+    safe_mode::mark_clean_exit();
+    app_handle.restart();
+    Ok(())
+}
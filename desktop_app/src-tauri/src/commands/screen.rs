@@ -330,7 +330,7 @@ pub async fn capture_and_understand_screen(
     };
     
     // 发送请求到后端
-    let client = reqwest::Client::new();
+    let client = crate::http::proxy::client();
     let response = client
         .post(&api_url)
         .json(&understand_request)
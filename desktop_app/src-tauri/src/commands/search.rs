@@ -0,0 +1,188 @@
+//! # 检索命令模块
+//!
+//! `hybrid_search` 同时执行 PostgreSQL 全文检索与 Qdrant 向量检索并融合得分，
+//! 供 RAG 上下文构建与记忆召回使用。任一路不可用时退化为单路检索，并在结果中说明原因。
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::database::hybrid_search::{self, FusionMethod, HybridHit, SearchCandidate};
+use crate::database::vector_search_service::{embedding_text, VectorSearchService};
+
+/// 会话消息集合（唯一同时具备全文索引与向量索引的集合）
+const CONVERSATIONS_COLLECTION: &str = "conversations";
+/// 每路检索的候选数为返回数量的倍数
+const CANDIDATE_MULTIPLIER: usize = 4;
+const MAX_LIMIT: usize = 100;
+
+fn default_collection() -> String {
+    CONVERSATIONS_COLLECTION.to_string()
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 混合检索请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchRequest {
+    pub query: String,
+    /// 向量集合；全文检索只覆盖会话消息
+    #[serde(default = "default_collection")]
+    pub collection: String,
+    /// 限定会话
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub fusion: FusionMethod,
+    #[serde(default = "default_true")]
+    pub use_keyword: bool,
+    #[serde(default = "default_true")]
+    pub use_vector: bool,
+}
+
+/// 混合检索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchResponse {
+    pub hits: Vec<HybridHit>,
+    /// 全文检索候选数
+    pub keyword_candidates: usize,
+    /// 向量检索候选数
+    pub vector_candidates: usize,
+    /// 退化说明（某一路不可用或失败）
+    pub degraded: Vec<String>,
+}
+
+/// 执行混合检索（供 RAG 与记忆召回直接调用）
+pub async fn run_hybrid_search(request: HybridSearchRequest) -> Result<HybridSearchResponse, String> {
+    let query = request.query.trim();
+    if query.is_empty() {
+        return Err("检索内容不能为空".to_string());
+    }
+    if !request.use_keyword && !request.use_vector {
+        return Err("至少需要启用一种检索方式".to_string());
+    }
+    request.fusion.validate()?;
+
+    let limit = request.limit.clamp(1, MAX_LIMIT);
+    let candidate_limit = limit * CANDIDATE_MULTIPLIER;
+    let session_id = request.session_id.as_deref();
+    let mut degraded = Vec::new();
+
+    let keyword = if !request.use_keyword {
+        Vec::new()
+    } else if request.collection != CONVERSATIONS_COLLECTION {
+        degraded.push(format!("集合 {} 没有全文索引，仅使用向量检索", request.collection));
+        Vec::new()
+    } else {
+        match keyword_candidates(query, session_id, candidate_limit).await {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                warn!("混合检索的全文检索失败: {}", e);
+                degraded.push(e);
+                Vec::new()
+            }
+        }
+    };
+
+    let vector = if !request.use_vector {
+        Vec::new()
+    } else {
+        match vector_candidates(query, &request.collection, session_id, candidate_limit).await {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                warn!("混合检索的向量检索失败: {}", e);
+                degraded.push(e);
+                Vec::new()
+            }
+        }
+    };
+
+    let keyword_count = keyword.len();
+    let vector_count = vector.len();
+    Ok(HybridSearchResponse {
+        hits: hybrid_search::fuse(keyword, vector, &request.fusion, limit),
+        keyword_candidates: keyword_count,
+        vector_candidates: vector_count,
+        degraded,
+    })
+}
+
+async fn keyword_candidates(
+    query: &str,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchCandidate>, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let rows = db
+        .conversation_history
+        .search_messages(query, session_id, limit as i64)
+        .await
+        .map_err(|e| format!("全文检索失败: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(message, rank)| SearchCandidate {
+            id: message.id,
+            content: message.content,
+            session_id: Some(message.conversation_id),
+            score: rank,
+            payload: None,
+        })
+        .collect())
+}
+
+async fn vector_candidates(
+    query: &str,
+    collection: &str,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchCandidate>, String> {
+    let qdrant = crate::database::get_database_manager()
+        .and_then(|m| m.qdrant())
+        .ok_or("向量搜索未启用")?;
+    let service = VectorSearchService::new(qdrant);
+    let query_vector = service
+        .embed_one(collection, query)
+        .await
+        .map_err(|e| format!("生成查询向量失败: {}", e))?;
+
+    // Qdrant 后端尚不支持过滤条件，限定会话时多取候选后在本地筛选
+    let fetch = if session_id.is_some() { limit * CANDIDATE_MULTIPLIER } else { limit };
+    let results = service
+        .search(collection, query_vector, fetch)
+        .await
+        .map_err(|e| format!("向量检索失败: {}", e))?;
+
+    Ok(results
+        .into_iter()
+        .filter_map(|result| {
+            let field = |name: &str| result.payload.get(name).and_then(|v| v.as_str()).map(str::to_string);
+            let result_session = field("session_id");
+            if session_id.is_some() && result_session.as_deref() != session_id {
+                return None;
+            }
+            Some(SearchCandidate {
+                // 会话消息以消息 ID 与全文检索结果对齐
+                id: field("message_id").unwrap_or(result.id),
+                content: embedding_text(collection, &result.payload).unwrap_or_default(),
+                session_id: result_session,
+                score: result.score,
+                payload: Some(result.payload),
+            })
+        })
+        .take(limit)
+        .collect())
+}
+
+/// 混合检索（全文 + 向量，得分融合）
+#[tauri::command]
+pub async fn hybrid_search(request: HybridSearchRequest) -> Result<HybridSearchResponse, String> {
+    run_hybrid_search(request).await
+}
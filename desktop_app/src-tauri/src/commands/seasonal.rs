@@ -0,0 +1,217 @@
+//! # 季节内容命令模块
+//!
+//! 管理角色的季节/节日服装与表情配置（保存在角色配置 `config_json` 的 `seasonal` 字段），
+//! 后台定时按当天日期与用户所在地区自动切换，切换结果通过 `character-outfit-changed`
+//! 与 `set-expression` 事件推送给主窗口。
+
+use chrono::{Datelike, NaiveDate};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::database::character_registry::CharacterConfig;
+use crate::utils::clock;
+use crate::utils::region_detector::RegionDetector;
+use crate::utils::seasonal_content::{SeasonalConfig, SeasonalOverride, SeasonalSelection};
+use crate::utils::watchdog;
+
+/// 后台检查间隔
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// 调度器心跳停滞阈值
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+/// 角色配置 JSON 中的字段名
+const CONFIG_KEY: &str = "seasonal";
+
+lazy_static! {
+    /// 每个角色最近一次推送的服装与表情，避免重复切换
+    static ref LAST_APPLIED: Mutex<HashMap<String, (Option<String>, Option<String>)>> = Mutex::new(HashMap::new());
+}
+
+async fn load_character_config(character_id: &str) -> Result<CharacterConfig, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let config = db
+        .character_registry
+        .get_character_config_async(character_id)
+        .await
+        .map_err(|e| format!("获取角色配置失败: {}", e))?;
+    Ok(config.unwrap_or_else(|| CharacterConfig {
+        character_id: character_id.to_string(),
+        scale: 1.0,
+        position_x: 0.0,
+        position_y: 0.0,
+        interaction_enabled: true,
+        config_json: None,
+    }))
+}
+
+fn config_object(config: &CharacterConfig) -> serde_json::Map<String, serde_json::Value> {
+    config
+        .config_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+        .and_then(|value| match value {
+            serde_json::Value::Object(map) => Some(map),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// 读取角色的季节内容配置
+pub async fn load_seasonal_config(character_id: &str) -> Result<SeasonalConfig, String> {
+    let config = load_character_config(character_id).await?;
+    match config_object(&config).remove(CONFIG_KEY) {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("季节内容配置格式错误: {}", e)),
+        None => Ok(SeasonalConfig::default()),
+    }
+}
+
+/// 保存角色的季节内容配置（保留 `config_json` 中的其他字段）
+pub async fn save_seasonal_config(character_id: &str, seasonal: &SeasonalConfig) -> Result<(), String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let mut config = load_character_config(character_id).await?;
+    let mut object = config_object(&config);
+    object.insert(
+        CONFIG_KEY.to_string(),
+        serde_json::to_value(seasonal).map_err(|e| format!("序列化季节内容配置失败: {}", e))?,
+    );
+    config.config_json = Some(serde_json::Value::Object(object).to_string());
+    db.character_registry
+        .save_character_config_async(config)
+        .await
+        .map_err(|e| format!("保存角色配置失败: {}", e))
+}
+
+fn user_country() -> String {
+    RegionDetector::detect_system_region().country
+}
+
+fn today() -> NaiveDate {
+    let now = clock::global().local_now();
+    NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()).unwrap_or_default()
+}
+
+/// 计算角色当天的季节内容并推送给前端；`force` 为 false 时结果未变化则不推送
+pub async fn apply_seasonal_content(
+    app_handle: &AppHandle,
+    character_id: &str,
+    force: bool,
+) -> Result<SeasonalSelection, String> {
+    let config = load_seasonal_config(character_id).await?;
+    let selection = config.resolve(today(), &user_country(), clock::now());
+
+    let current = (selection.outfit.clone(), selection.expression.clone());
+    let changed = LAST_APPLIED.lock().insert(character_id.to_string(), current.clone()) != Some(current);
+    if !changed && !force {
+        return Ok(selection);
+    }
+
+    if let Some(main_window) = app_handle.get_window("main") {
+        let _ = main_window.emit(
+            "character-outfit-changed",
+            serde_json::json!({
+                "character_id": character_id,
+                "outfit": selection.outfit,
+                "textures": selection.textures,
+                "season": selection.season,
+                "matched_rules": selection.matched_rules,
+            }),
+        );
+        if let Some(expression) = &selection.expression {
+            let _ = main_window.emit(
+                "set-expression",
+                serde_json::json!({
+                    "character_id": character_id,
+                    "expression": expression,
+                }),
+            );
+        }
+    }
+    info!(
+        "角色 {} 季节内容: 服装 {:?}，表情 {:?}",
+        character_id, selection.outfit, selection.expression
+    );
+    Ok(selection)
+}
+
+/// 启动季节内容调度（应用启动时调用）
+pub fn start_seasonal_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("seasonal_scheduler", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                if let Some(db) = crate::database::get_database() {
+                    match db.character_registry.get_active_character_async().await {
+                        Ok(Some(character)) => {
+                            if let Err(e) = apply_seasonal_content(&app_handle, &character.id, false).await {
+                                warn!("应用季节内容失败: {}", e);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => warn!("获取当前角色失败: {}", e),
+                    }
+                }
+                tokio::time::sleep(SCHEDULER_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// 获取角色的季节内容配置
+#[tauri::command]
+pub async fn get_seasonal_config(character_id: String) -> Result<SeasonalConfig, String> {
+    load_seasonal_config(&character_id).await
+}
+
+/// 保存角色的季节内容配置并立即应用
+#[tauri::command]
+pub async fn update_seasonal_config(
+    app_handle: AppHandle,
+    character_id: String,
+    config: SeasonalConfig,
+) -> Result<SeasonalSelection, String> {
+    config.validate()?;
+    save_seasonal_config(&character_id, &config).await?;
+    apply_seasonal_content(&app_handle, &character_id, true).await
+}
+
+/// 设置或清除用户覆盖（覆盖优先于所有规则）
+#[tauri::command]
+pub async fn set_seasonal_override(
+    app_handle: AppHandle,
+    character_id: String,
+    user_override: Option<SeasonalOverride>,
+) -> Result<SeasonalSelection, String> {
+    let mut config = load_seasonal_config(&character_id).await?;
+    config.user_override = user_override;
+    config.validate()?;
+    save_seasonal_config(&character_id, &config).await?;
+    apply_seasonal_content(&app_handle, &character_id, true).await
+}
+
+/// 预览某天（默认今天）某地区（默认当前地区）的季节内容，不会应用
+///
+/// 传入 `config` 时预览尚未保存的配置。
+#[tauri::command]
+pub async fn preview_seasonal_content(
+    character_id: String,
+    date: Option<String>,
+    country: Option<String>,
+    config: Option<SeasonalConfig>,
+) -> Result<SeasonalSelection, String> {
+    let config = match config {
+        Some(config) => {
+            config.validate()?;
+            config
+        }
+        None => load_seasonal_config(&character_id).await?,
+    };
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| format!("日期格式无效: {}", e))?,
+        None => today(),
+    };
+    let country = country.unwrap_or_else(user_country);
+    Ok(config.resolve(date, &country, clock::now()))
+}
@@ -0,0 +1,222 @@
+//! # 长期记忆命令模块
+//!
+//! 会话每新增一定数量的消息（见 [`crate::utils::llm_middleware::MemoryOptions`]），聊天命令在保存
+//! 消息后调用 [`maybe_summarize`] 在后台生成摘要。摘要与空闲整理生成的摘要写入同一份
+//! `session_summaries.json`，同时嵌入到 Qdrant 的 `session_summaries` 集合；`long_term_memory`
+//! 中间件在发送前检索相关的过往摘要注入提示词。
+//!
+//! 摘要更新后发出 `session-summary-updated` 事件；`rebuild_memory_index` 按摘要文件重建向量索引，
+//! 过程中发出 `memory-index-progress` 事件。
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::{incognito, llm_middleware, sleep_learning};
+use crate::database::vector_search_service::{SessionSummaryEntry, SESSION_SUMMARY_COLLECTION};
+use crate::utils::sleep_learning::SessionSummary;
+use crate::AppState;
+
+/// 带标题的会话摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummaryRecord {
+    pub session_id: String,
+    pub title: Option<String>,
+    #[serde(flatten)]
+    pub summary: SessionSummary,
+}
+
+/// 重建索引结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryIndexReport {
+    pub indexed: usize,
+    pub failed: usize,
+}
+
+/// 把摘要写入向量索引（尽力而为，向量库不可用时跳过）
+pub(crate) async fn index_summary(session_id: &str, title: &str, summary: &SessionSummary) {
+    let Some(service) = sleep_learning::vector_service() else {
+        return;
+    };
+    let entry = SessionSummaryEntry {
+        session_id: session_id.to_string(),
+        title: title.to_string(),
+        content: summary.summary.clone(),
+        message_count: summary.message_count,
+        generated_at: summary.generated_at,
+    };
+    let result = match service.embed_one(SESSION_SUMMARY_COLLECTION, &entry.content).await {
+        Ok(vector) => service.store_session_summary(vector, &entry).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!("索引会话 {} 的摘要失败: {}", session_id, e);
+    }
+}
+
+/// 检索与文本相关的过往会话摘要（不含 `exclude_session`）
+pub(crate) async fn recall(
+    query: &str,
+    exclude_session: Option<&str>,
+    limit: usize,
+    min_score: f32,
+) -> Result<Vec<SessionSummaryEntry>, String> {
+    let Some(service) = sleep_learning::vector_service() else {
+        return Ok(Vec::new());
+    };
+    let vector = service
+        .embed_one(SESSION_SUMMARY_COLLECTION, query)
+        .await
+        .map_err(|e| format!("生成查询向量失败: {}", e))?;
+    // 多取一条，当前会话的摘要可能排在前面
+    let hits = service
+        .search_session_summaries(vector, limit + 1)
+        .await
+        .map_err(|e| format!("检索会话摘要失败: {}", e))?;
+    Ok(hits
+        .into_iter()
+        .filter(|hit| hit.score >= min_score)
+        .filter_map(|hit| serde_json::from_value::<SessionSummaryEntry>(hit.payload).ok())
+        .filter(|entry| Some(entry.session_id.as_str()) != exclude_session)
+        .take(limit)
+        .collect())
+}
+
+async fn summarize_session(app_handle: &AppHandle, session_id: &str) -> Result<bool, String> {
+    let Some(options) = llm_middleware::memory_options() else {
+        return Ok(false);
+    };
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let history = &db.conversation_history;
+    let messages = history
+        .get_messages(session_id)
+        .await
+        .map_err(|e| format!("读取消息失败: {}", e))?;
+    let message_count = messages.iter().filter(|m| !m.is_deleted()).count();
+    let existing = sleep_learning::load_summaries()?.remove(session_id);
+    if !options.summary_due(message_count, existing.map(|s| s.message_count)) {
+        return Ok(false);
+    }
+
+    let title = history
+        .get_conversation(session_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|conversation| conversation.title)
+        .unwrap_or_default();
+    let summary = SessionSummary {
+        summary: sleep_learning::summarize(&title, &messages).await?,
+        message_count,
+        generated_at: Utc::now().timestamp(),
+    };
+    sleep_learning::store_summary(session_id, summary.clone())?;
+    index_summary(session_id, &title, &summary).await;
+
+    let _ = app_handle.emit_all("session-summary-updated", &SessionSummaryRecord {
+        session_id: session_id.to_string(),
+        title: Some(title),
+        summary,
+    });
+    Ok(true)
+}
+
+/// 会话足够长时在后台生成摘要（同一会话同时只有一个摘要任务，无痕会话不生成）
+pub(crate) fn maybe_summarize(app_handle: &AppHandle, session_id: &str) {
+    if incognito::is_incognito(Some(session_id)) || llm_middleware::memory_options().is_none() {
+        return;
+    }
+    if !app_handle.state::<AppState>().chat.begin_summary(session_id) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    let session_id = session_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        match summarize_session(&app_handle, &session_id).await {
+            Ok(true) => info!("会话摘要已更新: {}", session_id),
+            Ok(false) => {}
+            Err(e) => warn!("生成会话摘要失败 {}: {}", session_id, e),
+        }
+        app_handle.state::<AppState>().chat.finish_summary(&session_id);
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取会话摘要（按生成时间倒序），可只查询指定会话
+#[tauri::command]
+pub async fn get_session_summaries(
+    session_ids: Option<Vec<String>>,
+) -> Result<Vec<SessionSummaryRecord>, String> {
+    let summaries = sleep_learning::load_summaries()?;
+    let db = crate::database::get_database();
+    let mut records = Vec::new();
+    for (session_id, summary) in summaries {
+        if session_ids.as_ref().is_some_and(|ids| !ids.contains(&session_id)) {
+            continue;
+        }
+        let title = match &db {
+            Some(db) => db.conversation_history.get_conversation(&session_id).await.ok().flatten().map(|c| c.title),
+            None => None,
+        };
+        records.push(SessionSummaryRecord { session_id, title, summary });
+    }
+    records.sort_by(|a, b| b.summary.generated_at.cmp(&a.summary.generated_at));
+    Ok(records)
+}
+
+/// 按已保存的摘要重建向量索引（更换嵌入提供者或索引损坏时使用）
+#[tauri::command]
+pub async fn rebuild_memory_index(app_handle: AppHandle) -> Result<MemoryIndexReport, String> {
+    let service = sleep_learning::vector_service().ok_or("向量数据库不可用")?;
+    let summaries = sleep_learning::load_summaries()?;
+    let db = crate::database::get_database();
+
+    if service
+        .collection_exists(SESSION_SUMMARY_COLLECTION)
+        .await
+        .map_err(|e| format!("读取向量集合失败: {}", e))?
+    {
+        service
+            .delete_collection(SESSION_SUMMARY_COLLECTION)
+            .await
+            .map_err(|e| format!("删除旧索引失败: {}", e))?;
+    }
+
+    let total = summaries.len();
+    let mut report = MemoryIndexReport::default();
+    for (index, (session_id, summary)) in summaries.into_iter().enumerate() {
+        let title = match &db {
+            Some(db) => db.conversation_history.get_conversation(&session_id).await.ok().flatten().map(|c| c.title),
+            None => None,
+        };
+        let entry = SessionSummaryEntry {
+            session_id: session_id.clone(),
+            title: title.unwrap_or_default(),
+            content: summary.summary,
+            message_count: summary.message_count,
+            generated_at: summary.generated_at,
+        };
+        let result = match service.embed_one(SESSION_SUMMARY_COLLECTION, &entry.content).await {
+            Ok(vector) => service.store_session_summary(vector, &entry).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => report.indexed += 1,
+            Err(e) => {
+                warn!("索引会话 {} 的摘要失败: {}", session_id, e);
+                report.failed += 1;
+            }
+        }
+        let _ = app_handle.emit_all("memory-index-progress", serde_json::json!({
+            "completed": index + 1,
+            "total": total,
+        }));
+    }
+
+    info!("长期记忆索引已重建: {} 条成功, {} 条失败", report.indexed, report.failed);
+    Ok(report)
+}
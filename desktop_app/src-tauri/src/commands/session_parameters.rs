@@ -0,0 +1,151 @@
+//! # 会话参数命令模块
+//!
+//! 会话可以覆盖温度、Top-P 与最大 token 数，覆盖随会话保存在 `conversations.parameters`。
+//! `send_message` 每次发送前通过 [`resolve`] 逐层解析（规则见 [`crate::utils::model_parameters`]），
+//! 解析结果附在费用确认与聊天回复中，便于界面显示本次实际使用的参数。
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+use crate::commands::incognito;
+use crate::database::model_config::ModelConfigData;
+use crate::utils::character_binding;
+use crate::utils::model_parameters::{EffectiveParameters, ParameterOverrides, ParameterSource};
+
+/// 会话的参数覆盖与解析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionParameters {
+    pub session_id: String,
+    pub overrides: ParameterOverrides,
+    pub effective: EffectiveParameters,
+}
+
+fn config_layer(config: &ModelConfigData) -> ParameterOverrides {
+    ParameterOverrides {
+        temperature: Some(config.temperature),
+        top_p: Some(config.top_p),
+        max_tokens: Some(config.max_tokens),
+    }
+}
+
+/// 默认模型配置（没有时使用内置默认值）
+async fn default_layer() -> EffectiveParameters {
+    let default_config = match crate::database::get_database() {
+        Some(db) => match db.model_config_registry.get_all_configs_async().await {
+            Ok(configs) => configs.into_iter().find(|config| config.is_default && config.is_enabled),
+            Err(e) => {
+                warn!("读取默认模型配置失败: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let config = default_config.unwrap_or_else(ModelConfigData::default_config);
+    EffectiveParameters::from_defaults(config.temperature, config.top_p, config.max_tokens)
+}
+
+/// 当前角色绑定的模型配置
+async fn character_layer(character_id: Option<&str>) -> ParameterOverrides {
+    let Some(config_id) = character_binding::active_for(character_id).and_then(|applied| applied.binding.model_config_id) else {
+        return ParameterOverrides::default();
+    };
+    let Some(db) = crate::database::get_database() else {
+        return ParameterOverrides::default();
+    };
+    match db.model_config_registry.get_config_async(&config_id).await {
+        Ok(Some(config)) if config.is_enabled => config_layer(&config),
+        Ok(_) => ParameterOverrides::default(),
+        Err(e) => {
+            warn!("读取角色绑定的模型配置失败: {}", e);
+            ParameterOverrides::default()
+        }
+    }
+}
+
+/// 会话保存的参数覆盖
+async fn session_overrides(session_id: &str) -> Result<ParameterOverrides, String> {
+    if incognito::is_incognito(Some(session_id)) {
+        return Ok(incognito::parameters(session_id));
+    }
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let stored = db
+        .conversation_history
+        .get_parameters(session_id)
+        .await
+        .map_err(|e| format!("读取会话参数失败: {}", e))?;
+    Ok(stored
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+/// 解析本次请求使用的参数：会话 > 角色绑定 > 请求（全局配置）> 默认配置
+pub async fn resolve(
+    session_id: Option<&str>,
+    character_id: Option<&str>,
+    request: &ParameterOverrides,
+) -> EffectiveParameters {
+    let session = match session_id {
+        Some(id) => session_overrides(id).await.unwrap_or_else(|e| {
+            warn!("{}，忽略会话参数", e);
+            ParameterOverrides::default()
+        }),
+        None => ParameterOverrides::default(),
+    };
+    default_layer()
+        .await
+        .apply(ParameterSource::Request, request)
+        .apply(ParameterSource::Character, &character_layer(character_id).await)
+        .apply(ParameterSource::Session, &session)
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取会话的参数覆盖与解析结果；`request` 为前端将随消息发送的参数
+#[tauri::command]
+pub async fn get_session_parameters(
+    session_id: String,
+    character_id: Option<String>,
+    request: Option<ParameterOverrides>,
+) -> Result<SessionParameters, String> {
+    let overrides = session_overrides(&session_id).await?;
+    let effective = resolve(Some(&session_id), character_id.as_deref(), &request.unwrap_or_default()).await;
+    Ok(SessionParameters { session_id, overrides, effective })
+}
+
+/// 保存会话的参数覆盖，全部为空时清除
+#[tauri::command]
+pub async fn update_session_parameters(
+    app_handle: AppHandle,
+    session_id: String,
+    overrides: ParameterOverrides,
+    character_id: Option<String>,
+) -> Result<SessionParameters, String> {
+    if session_id.trim().is_empty() {
+        return Err("会话 ID 不能为空".to_string());
+    }
+    overrides.validate()?;
+
+    if incognito::is_incognito(Some(&session_id)) {
+        // 无痕会话的参数只保存在内存中
+        incognito::set_parameters(&session_id, overrides.clone())?;
+    } else {
+        let db = crate::database::get_database().ok_or("数据库未初始化")?;
+        let stored = if overrides.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_value(&overrides).map_err(|e| format!("序列化会话参数失败: {}", e))?)
+        };
+        db.conversation_history
+            .set_parameters(&session_id, stored.as_ref(), chrono::Utc::now().timestamp())
+            .await
+            .map_err(|e| format!("保存会话参数失败: {}", e))?;
+    }
+
+    let effective = resolve(Some(&session_id), character_id.as_deref(), &ParameterOverrides::default()).await;
+    let parameters = SessionParameters { session_id, overrides, effective };
+    let _ = app_handle.emit_all("session-parameters-changed", &parameters);
+    Ok(parameters)
+}
@@ -5,11 +5,12 @@
 //! - Resetting to defaults
 //! - Import/export functionality
 //! - Partial updates
+//! - Scheduled, encrypted backups to S3-compatible or WebDAV storage
 
 use std::path::PathBuf;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use serde::{Deserialize, Serialize};
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
 use crate::{
     commands::*,
@@ -27,7 +28,23 @@ use crate::utils::config::{
     get_config_diff,
     validate_config,
     save_config,
+    TEXT_SCALE_RANGE,
 };
+use crate::utils::config_timeline::{self, FieldChange, TimelineEntry};
+use crate::utils::keyring_store;
+use crate::utils::settings_journal::{self, SettingsChange, SettingsChangeRecord};
+use crate::utils::profile_archive::{self, ProfileArchive};
+use crate::utils::remote_backup::{self, RemoteBackupInfo, RemoteBackupSettings, RemoteStore};
+
+lazy_static::lazy_static! {
+    /// Serializes read-modify-write cycles of the settings change journal
+    static ref SETTINGS_JOURNAL_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+    /// Remote backup settings
+    static ref REMOTE_BACKUP_SETTINGS: parking_lot::RwLock<RemoteBackupSettings> =
+        parking_lot::RwLock::new(RemoteBackupSettings::default());
+    /// Keeps scheduled and manual remote backups from running concurrently
+    static ref REMOTE_BACKUP_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
 
 // ================================
 // Request/Response Types
@@ -80,6 +97,10 @@ pub struct UpdateCharacterConfigRequest {
 pub struct UpdateThemeConfigRequest {
     pub current_theme: Option<String>,
     pub custom_css: Option<String>,
+    #[serde(default)]
+    pub text_scale: Option<u32>,
+    #[serde(default)]
+    pub window_text_scales: Option<std::collections::HashMap<String, u32>>,
 }
 
 /// System config update request
@@ -89,6 +110,9 @@ pub struct UpdateSystemConfigRequest {
     pub minimize_to_tray: Option<bool>,
     pub close_to_tray: Option<bool>,
     pub show_notifications: Option<bool>,
+    /// 出站 HTTP 代理设置，密码为掩码时保留原密码
+    #[serde(default)]
+    pub proxy: Option<crate::state::settings::ProxySettings>,
 }
 
 // ================================
@@ -103,7 +127,9 @@ pub async fn get_settings(
 ) -> Result<CommandResponse<AppConfig>, String> {
     info!("获取应用设置");
     
-    let config = state.config.lock().clone();
+    let mut config = state.config.lock().clone();
+    crate::commands::safe_mode::apply_theme_override(&mut config);
+    crate::commands::accessibility::apply_config_overrides(&app_handle, &mut config);
     Ok(CommandResponse::success(config))
 }
 
@@ -147,7 +173,9 @@ pub async fn update_partial_settings(
 ) -> Result<CommandResponse<AppConfig>, String> {
     info!("部分更新应用设置");
     
+    let _journal_guard = SETTINGS_JOURNAL_LOCK.lock().await;
     let mut config = state.config.lock().clone();
+    let before = serde_json::to_value(&config).map_err(|e| e.to_string())?;
     
     // Merge updates
     if let Err(e) = merge_config(&mut config, updates) {
@@ -164,6 +192,16 @@ pub async fn update_partial_settings(
         return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
     }
     
+    // Record the change so it can be undone on its own
+    let after = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    let data_dir = get_profile_data_dir()?;
+    let mut journal = settings_journal::load(&data_dir);
+    if journal.record(config_timeline::diff(&before, &after), chrono::Utc::now().timestamp()).is_some() {
+        if let Err(e) = settings_journal::save(&data_dir, &journal) {
+            error!("{}", e);
+        }
+    }
+    
     info!("部分设置更新成功");
     Ok(CommandResponse::success_with_message(
         config,
@@ -390,12 +428,15 @@ pub async fn update_character_config(
 /// Get theme configuration
 #[tauri::command]
 pub async fn get_theme_config(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<CommandResponse<ThemeConfig>, String> {
     info!("获取主题配置");
     
-    let config = state.config.lock().theme.clone();
-    Ok(CommandResponse::success(config))
+    let mut config = state.config.lock().clone();
+    crate::commands::safe_mode::apply_theme_override(&mut config);
+    crate::commands::accessibility::apply_config_overrides(&app_handle, &mut config);
+    Ok(CommandResponse::success(config.theme))
 }
 
 /// Update theme configuration
@@ -416,6 +457,12 @@ pub async fn update_theme_config(
     if let Some(custom_css) = updates.custom_css {
         config.theme.custom_css = Some(custom_css);
     }
+    if let Some(text_scale) = updates.text_scale {
+        config.theme.text_scale = text_scale;
+    }
+    if let Some(window_text_scales) = updates.window_text_scales {
+        config.theme.window_text_scales = window_text_scales;
+    }
     
     // Validate config
     if let Err(e) = validate_config(&config) {
@@ -433,11 +480,218 @@ pub async fn update_theme_config(
         return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
     }
     
+    apply_text_scale(&app_handle, &theme_config);
+    
+    // Warn about palettes that fail WCAG AA contrast
+    let contrast_warnings = check_current_theme_contrast(&app_handle, &theme_config.current_theme);
+    if !contrast_warnings.is_empty() {
+        let _ = app_handle.emit_all("theme-contrast-warning", serde_json::json!({
+            "theme_id": theme_config.current_theme,
+            "warnings": contrast_warnings,
+        }));
+    }
+    
     info!("主题配置更新成功");
-    Ok(CommandResponse::success_with_message(
-        theme_config,
-        "主题配置更新成功".to_string(),
-    ))
+    let message = if contrast_warnings.is_empty() {
+        "主题配置更新成功".to_string()
+    } else {
+        format!("主题配置更新成功，但存在对比度问题: {}", contrast_warnings.join("; "))
+    };
+    Ok(CommandResponse::success_with_message(theme_config, message))
+}
+
+/// Run the WCAG contrast check against the theme stored in the theme database
+fn check_current_theme_contrast(app_handle: &AppHandle, theme_id: &str) -> Vec<String> {
+    use crate::database::theme::ThemeDatabase;
+    use crate::utils::theme_contrast::validate_theme_contrast;
+    let Some(db) = app_handle.try_state::<std::sync::Mutex<ThemeDatabase>>() else {
+        return Vec::new();
+    };
+    let theme = match db.lock() {
+        Ok(db) => db.get_theme(theme_id).ok().flatten(),
+        Err(_) => None,
+    };
+
+    match theme {
+        Some(theme) => {
+            let warnings = validate_theme_contrast(&theme.variables).warnings();
+            for warning in &warnings {
+                tracing::warn!("主题 {} 对比度不足: {}", theme_id, warning);
+            }
+            warnings
+        }
+        None => Vec::new(),
+    }
+}
+
+// ================================
+// Color-blind Palettes
+// ================================
+
+/// List the built-in color-blind friendly palettes
+#[tauri::command]
+pub async fn get_color_blind_palettes(
+) -> Result<CommandResponse<Vec<crate::utils::theme_contrast::ColorBlindPalette>>, String> {
+    Ok(CommandResponse::success(crate::utils::theme_contrast::color_blind_palettes()))
+}
+
+/// Select a color-blind friendly palette (independent of the main theme), or clear it with `None`
+#[tauri::command]
+pub async fn set_color_blind_palette(
+    palette_id: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<ThemeConfig>, String> {
+    info!("设置色盲友好调色板: {:?}", palette_id);
+
+    let mut config = state.config.lock().clone();
+    config.theme.color_blind_palette = palette_id.clone();
+
+    if let Err(e) = validate_config(&config) {
+        return Ok(CommandResponse::error(e));
+    }
+
+    let theme_config = config.theme.clone();
+    *state.config.lock() = config.clone();
+
+    if let Err(e) = save_config(&app_handle, &config).await {
+        error!("保存调色板配置失败: {}", e);
+        return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
+    }
+
+    let palette = palette_id
+        .as_deref()
+        .and_then(crate::utils::theme_contrast::find_color_blind_palette);
+    let _ = app_handle.emit_all("color-blind-palette-changed", serde_json::json!({
+        "palette": palette,
+    }));
+
+    Ok(CommandResponse::success(theme_config))
+}
+
+// ================================
+// Text Scaling
+// ================================
+
+/// Resolve the effective text scale for a window (per-window override or global value)
+pub fn effective_text_scale(theme: &ThemeConfig, window_label: &str) -> u32 {
+    theme
+        .window_text_scales
+        .get(window_label)
+        .copied()
+        .unwrap_or(theme.text_scale)
+}
+
+/// Build the script that injects the text scale CSS variables into a window
+fn text_scale_script(scale: u32) -> String {
+    format!(
+        "document.documentElement.style.setProperty('--zishu-text-scale', '{factor}');\
+         document.documentElement.style.setProperty('--zishu-text-scale-percent', '{scale}%');",
+        factor = scale as f64 / 100.0,
+        scale = scale,
+    )
+}
+
+/// Inject a text scale into a single window and notify it
+fn apply_text_scale_to_window(window: &tauri::Window, scale: u32, preview: bool) {
+    if let Err(e) = window.eval(&text_scale_script(scale)) {
+        error!("注入文字缩放样式失败 ({}): {}", window.label(), e);
+    }
+    let _ = window.emit("text-scale-changed", serde_json::json!({
+        "scale": scale,
+        "preview": preview,
+    }));
+}
+
+/// Apply text scaling to all open windows (speech bubble, quick input, etc.)
+pub fn apply_text_scale(app_handle: &AppHandle, theme: &ThemeConfig) {
+    for (label, window) in app_handle.windows() {
+        apply_text_scale_to_window(&window, effective_text_scale(theme, &label), false);
+    }
+}
+
+fn validate_text_scale(scale: u32) -> Result<(), String> {
+    if TEXT_SCALE_RANGE.contains(&scale) {
+        Ok(())
+    } else {
+        Err(format!(
+            "文字缩放比例必须在 {}-{}% 之间",
+            TEXT_SCALE_RANGE.start(),
+            TEXT_SCALE_RANGE.end()
+        ))
+    }
+}
+
+/// Preview a text scale without persisting it
+#[tauri::command]
+pub async fn preview_text_scale(
+    scale: u32,
+    window_label: Option<String>,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<u32>, String> {
+    if let Err(e) = validate_text_scale(scale) {
+        return Ok(CommandResponse::error(e));
+    }
+
+    match window_label {
+        Some(label) => match app_handle.get_window(&label) {
+            Some(window) => apply_text_scale_to_window(&window, scale, true),
+            None => return Ok(CommandResponse::error(format!("窗口 {} 不存在", label))),
+        },
+        None => {
+            for window in app_handle.windows().values() {
+                apply_text_scale_to_window(window, scale, true);
+            }
+        }
+    }
+
+    Ok(CommandResponse::success(scale))
+}
+
+/// Set or clear the text scale override for a specific window
+#[tauri::command]
+pub async fn set_window_text_scale(
+    window_label: String,
+    scale: Option<u32>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<ThemeConfig>, String> {
+    info!("设置窗口文字缩放: {} -> {:?}", window_label, scale);
+
+    let mut config = state.config.lock().clone();
+    match scale {
+        Some(scale) => {
+            config.theme.window_text_scales.insert(window_label, scale);
+        }
+        None => {
+            config.theme.window_text_scales.remove(&window_label);
+        }
+    }
+
+    if let Err(e) = validate_config(&config) {
+        return Ok(CommandResponse::error(e));
+    }
+
+    let theme_config = config.theme.clone();
+    *state.config.lock() = config.clone();
+
+    if let Err(e) = save_config(&app_handle, &config).await {
+        error!("保存文字缩放配置失败: {}", e);
+        return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
+    }
+
+    apply_text_scale(&app_handle, &theme_config);
+    Ok(CommandResponse::success(theme_config))
+}
+
+/// Get the effective text scale for the calling window (used on window load)
+#[tauri::command]
+pub async fn get_window_text_scale(
+    window: tauri::Window,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<u32>, String> {
+    let theme = state.config.lock().theme.clone();
+    Ok(CommandResponse::success(effective_text_scale(&theme, window.label())))
 }
 
 /// Get system configuration
@@ -447,7 +701,8 @@ pub async fn get_system_config(
 ) -> Result<CommandResponse<SystemConfig>, String> {
     info!("获取系统配置");
     
-    let config = state.config.lock().system.clone();
+    let mut config = state.config.lock().system.clone();
+    config.proxy = crate::commands::network::masked_proxy_settings();
     Ok(CommandResponse::success(config))
 }
 
@@ -482,8 +737,20 @@ pub async fn update_system_config(
         return Ok(CommandResponse::error(e));
     }
     
+    // 代理设置立即生效，只持久化到 proxy_settings.json
+    if let Some(proxy) = updates.proxy {
+        if let Err(e) = crate::commands::network::apply_proxy_settings(&app_handle, proxy) {
+            error!("代理设置无效: {}", e);
+            return Ok(CommandResponse::error(e));
+        }
+    }
+    // 应用配置中只保留空占位值（避免密码写入 config.json）；返回给前端的值
+    // 由 masked_proxy_settings() 覆盖，get_system_config 同样如此，不要直接读取这个字段
+    config.system.proxy = Default::default();
+    
     // Update state
-    let system_config = config.system.clone();
+    let mut system_config = config.system.clone();
+    system_config.proxy = crate::commands::network::masked_proxy_settings();
     *state.config.lock() = config.clone();
     
     // Save to disk
@@ -653,6 +920,428 @@ pub async fn compare_configs(
     Ok(CommandResponse::success(diff))
 }
 
+/// Result of rolling back to a config timeline point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineRollback {
+    /// Target timeline point
+    pub point_id: u64,
+    /// Field-level changes from the current config to the target
+    pub changes: Vec<FieldChange>,
+    /// Whether the rollback was applied (false for previews)
+    pub applied: bool,
+    /// Config at the target point
+    pub config: AppConfig,
+}
+
+/// Get the config change timeline (newest first)
+#[tauri::command]
+pub async fn get_config_timeline(
+    limit: Option<usize>,
+) -> Result<CommandResponse<Vec<TimelineEntry>>, String> {
+    let data_dir = get_profile_data_dir()?;
+    let timeline = config_timeline::load(&data_dir);
+    Ok(CommandResponse::success(timeline.entries(limit.unwrap_or(50))))
+}
+
+/// Roll back to a config timeline point, or preview the changes when `dry_run` is set
+#[tauri::command]
+pub async fn rollback_to_timeline_point(
+    id: u64,
+    dry_run: Option<bool>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<TimelineRollback>, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    info!("回滚配置到时间点 {} (预览: {})", id, dry_run);
+    
+    let data_dir = get_profile_data_dir()?;
+    let Some(target) = config_timeline::load(&data_dir).state_at(id) else {
+        return Ok(CommandResponse::error(format!("时间点不存在: {}", id)));
+    };
+    let config: AppConfig = match serde_json::from_value(target.clone()) {
+        Ok(config) => config,
+        Err(e) => return Ok(CommandResponse::error(format!("无法解析时间点 {} 的配置: {}", id, e))),
+    };
+    if let Err(e) = validate_config(&config) {
+        return Ok(CommandResponse::error(format!("时间点 {} 的配置无效: {}", id, e)));
+    }
+    
+    let current = serde_json::to_value(&*state.config.lock()).map_err(|e| e.to_string())?;
+    let changes = config_timeline::diff(&current, &target);
+    if dry_run || changes.is_empty() {
+        return Ok(CommandResponse::success(TimelineRollback { point_id: id, changes, applied: false, config }));
+    }
+    
+    *state.config.lock() = config.clone();
+    // The rollback itself becomes a new timeline point, so it can be undone as well
+    if let Err(e) = save_config(&app_handle, &config).await {
+        error!("保存回滚后的配置失败: {}", e);
+        return Ok(CommandResponse::error(format!("保存回滚后的配置失败: {}", e)));
+    }
+    
+    info!("配置已回滚到时间点 {}（{} 处变化）", id, changes.len());
+    Ok(CommandResponse::success_with_message(
+        TimelineRollback { point_id: id, changes, applied: true, config },
+        "配置回滚成功".to_string(),
+    ))
+}
+
+/// Result of undoing or redoing a settings change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsChangeStep {
+    /// The change that was undone or redone
+    pub change: SettingsChange,
+    /// Config after the step
+    pub config: AppConfig,
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// Undo (`undo = true`) or redo the latest settings change in the journal
+async fn step_settings_journal(
+    undo: bool,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<CommandResponse<SettingsChangeStep>, String> {
+    let _journal_guard = SETTINGS_JOURNAL_LOCK.lock().await;
+    let data_dir = get_profile_data_dir()?;
+    let mut journal = settings_journal::load(&data_dir);
+    
+    let current = serde_json::to_value(&*state.config.lock()).map_err(|e| e.to_string())?;
+    let step = if undo { journal.undo(&current) } else { journal.redo(&current) };
+    let Some((change, target)) = step else {
+        let message = if undo { "没有可撤销的设置修改" } else { "没有可重做的设置修改" };
+        return Ok(CommandResponse::error(message.to_string()));
+    };
+    let config: AppConfig = match serde_json::from_value(target) {
+        Ok(config) => config,
+        Err(e) => return Ok(CommandResponse::error(format!("无法应用设置修改 {}: {}", change.id, e))),
+    };
+    if let Err(e) = validate_config(&config) {
+        return Ok(CommandResponse::error(format!("设置修改 {} 无法{}: {}", change.id, if undo { "撤销" } else { "重做" }, e)));
+    }
+    
+    *state.config.lock() = config.clone();
+    if let Err(e) = save_config(app_handle, &config).await {
+        error!("保存配置失败: {}", e);
+        return Ok(CommandResponse::error(format!("保存配置失败: {}", e)));
+    }
+    // Only advance the journal once the config is saved
+    settings_journal::save(&data_dir, &journal)?;
+    
+    info!("已{}设置修改 {}（{} 个字段）", if undo { "撤销" } else { "重做" }, change.id, change.changes.len());
+    Ok(CommandResponse::success(SettingsChangeStep {
+        change,
+        config,
+        can_undo: journal.can_undo(),
+        can_redo: journal.can_redo(),
+    }))
+}
+
+/// Undo the latest settings change, reverting only the fields it touched
+#[tauri::command]
+pub async fn undo_settings_change(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<SettingsChangeStep>, String> {
+    step_settings_journal(true, &app_handle, &state).await
+}
+
+/// Redo the most recently undone settings change
+#[tauri::command]
+pub async fn redo_settings_change(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<SettingsChangeStep>, String> {
+    step_settings_journal(false, &app_handle, &state).await
+}
+
+/// Get the settings change journal (newest first); undone entries can be redone
+#[tauri::command]
+pub async fn get_settings_change_log(
+    limit: Option<usize>,
+) -> Result<CommandResponse<Vec<SettingsChangeRecord>>, String> {
+    let data_dir = get_profile_data_dir()?;
+    Ok(CommandResponse::success(settings_journal::load(&data_dir).records(limit.unwrap_or(50))))
+}
+
+// ================================
+// Remote Backups
+// ================================
+
+/// Keyring entry holding the S3 secret access key or WebDAV password
+const REMOTE_BACKUP_SECRET_ENTRY: &str = "remote_backup_secret";
+/// Keyring entry holding the password remote backups are encrypted with
+const REMOTE_BACKUP_PASSWORD_ENTRY: &str = "remote_backup_password";
+/// How often the scheduler checks whether a remote backup is due
+const REMOTE_BACKUP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+/// Delay before the first check (waits for the database to initialize)
+const REMOTE_BACKUP_STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(3 * 60);
+/// Heartbeat stall threshold (uploads of large profiles can be slow)
+const REMOTE_BACKUP_STALL_AFTER: std::time::Duration = std::time::Duration::from_secs(3 * 60 * 60);
+
+/// Remote backup settings together with whether the keyring secrets are set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteBackupStatus {
+    #[serde(flatten)]
+    pub settings: RemoteBackupSettings,
+    pub has_secret: bool,
+    pub has_password: bool,
+}
+
+fn get_remote_backup_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("remote_backup_settings.json"))
+}
+
+fn save_remote_backup_settings(settings: &RemoteBackupSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize remote backup settings: {}", e))?;
+    std::fs::write(get_remote_backup_config_path()?, json)
+        .map_err(|e| format!("Failed to write remote backup settings: {}", e))
+}
+
+/// Load remote backup settings from disk (called at startup)
+pub fn initialize_remote_backup() -> Result<(), String> {
+    let config_path = get_remote_backup_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = std::fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read remote backup settings: {}", e))?;
+    let settings: RemoteBackupSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse remote backup settings: {}", e))?;
+    settings.validate()?;
+    *REMOTE_BACKUP_SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn remote_store() -> Result<RemoteStore, String> {
+    let destination = REMOTE_BACKUP_SETTINGS
+        .read()
+        .destination
+        .clone()
+        .ok_or("尚未设置远程存储")?;
+    let secret = keyring_store::load(REMOTE_BACKUP_SECRET_ENTRY).ok_or("尚未设置远程存储的访问密钥")?;
+    Ok(RemoteStore::new(destination, secret))
+}
+
+fn record_remote_backup_result(result: &Result<RemoteBackupInfo, String>) {
+    let settings = {
+        let mut settings = REMOTE_BACKUP_SETTINGS.write();
+        match result {
+            Ok(backup) => {
+                settings.last_backup_at = Some(backup.created_at);
+                settings.last_error = None;
+            }
+            Err(e) => settings.last_error = Some(e.clone()),
+        }
+        settings.clone()
+    };
+    if let Err(e) = save_remote_backup_settings(&settings) {
+        error!("保存远程备份状态失败: {}", e);
+    }
+}
+
+/// Encrypt the full profile with the backup password, upload it and prune old remote backups
+async fn run_remote_backup(app_handle: &AppHandle) -> Result<RemoteBackupInfo, String> {
+    let _guard = REMOTE_BACKUP_LOCK.lock().await;
+    let store = remote_store()?;
+    let password = keyring_store::load(REMOTE_BACKUP_PASSWORD_ENTRY).ok_or("尚未设置备份密码")?;
+
+    let bundle = crate::commands::backup::collect_profile(app_handle).await?;
+    let plaintext = serde_json::to_vec(&bundle).map_err(|e| format!("序列化资料失败: {}", e))?;
+    let archive = tokio::task::spawn_blocking(move || profile_archive::seal(&password, &plaintext))
+        .await
+        .map_err(|e| format!("加密资料失败: {}", e))??;
+    let body = serde_json::to_vec(&archive).map_err(|e| format!("序列化资料失败: {}", e))?;
+
+    let now = crate::utils::clock::now();
+    let backup = RemoteBackupInfo {
+        name: remote_backup::backup_name(now),
+        size_bytes: Some(body.len() as u64),
+        created_at: now.timestamp(),
+    };
+    store.upload(&backup.name, body).await?;
+    info!("远程备份已上传: {} ({} 字节)", backup.name, backup.size_bytes.unwrap_or(0));
+
+    // Pruning failures don't fail the backup itself
+    let keep = REMOTE_BACKUP_SETTINGS.read().keep;
+    match store.list().await {
+        Ok(backups) => {
+            for name in remote_backup::expired_backups(&backups, keep) {
+                if let Err(e) = store.delete(&name).await {
+                    warn!("删除旧的远程备份 {} 失败: {}", name, e);
+                }
+            }
+        }
+        Err(e) => warn!("清理旧的远程备份失败: {}", e),
+    }
+    Ok(backup)
+}
+
+async fn remote_backup_tick(app_handle: &AppHandle) {
+    let (schedule, last_backup_at) = {
+        let settings = REMOTE_BACKUP_SETTINGS.read();
+        (settings.schedule, settings.last_backup_at)
+    };
+    if !schedule.is_due(last_backup_at, crate::utils::clock::now().timestamp()) {
+        return;
+    }
+    crate::utils::resource_budget::defer(crate::utils::resource_budget::WorkKind::Background, "remote_backup").await;
+    let result = run_remote_backup(app_handle).await;
+    if let Err(e) = &result {
+        warn!("自动远程备份失败: {}", e);
+    }
+    record_remote_backup_result(&result);
+}
+
+/// Start the scheduled remote backup task (called at startup)
+pub fn start_remote_backup_scheduler(app_handle: AppHandle) {
+    crate::utils::watchdog::supervise("remote_backup", REMOTE_BACKUP_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(REMOTE_BACKUP_STARTUP_DELAY).await;
+            while heartbeat.beat() {
+                remote_backup_tick(&app_handle).await;
+                tokio::time::sleep(REMOTE_BACKUP_CHECK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// Get remote backup settings
+#[tauri::command]
+pub async fn get_remote_backup_settings() -> Result<CommandResponse<RemoteBackupStatus>, String> {
+    Ok(CommandResponse::success(RemoteBackupStatus {
+        settings: REMOTE_BACKUP_SETTINGS.read().clone(),
+        has_secret: keyring_store::load(REMOTE_BACKUP_SECRET_ENTRY).is_some(),
+        has_password: keyring_store::load(REMOTE_BACKUP_PASSWORD_ENTRY).is_some(),
+    }))
+}
+
+/// Update remote backup settings; `secret` and `password` are stored in the keyring
+/// when given (an empty string removes them)
+#[tauri::command]
+pub async fn update_remote_backup_settings(
+    settings: RemoteBackupSettings,
+    secret: Option<String>,
+    password: Option<String>,
+) -> Result<CommandResponse<RemoteBackupStatus>, String> {
+    if let Err(e) = settings.validate() {
+        return Ok(CommandResponse::error(e));
+    }
+    if let Some(password) = password.as_deref().filter(|p| !p.is_empty()) {
+        if let Err(e) = profile_archive::validate_password(password) {
+            return Ok(CommandResponse::error(e));
+        }
+    }
+    if let Some(secret) = &secret {
+        keyring_store::store(REMOTE_BACKUP_SECRET_ENTRY, Some(secret))?;
+    }
+    if let Some(password) = &password {
+        keyring_store::store(REMOTE_BACKUP_PASSWORD_ENTRY, Some(password))?;
+    }
+
+    // Backup status is owned by the scheduler, not the settings form
+    let settings = {
+        let mut current = REMOTE_BACKUP_SETTINGS.write();
+        *current = RemoteBackupSettings {
+            last_backup_at: current.last_backup_at,
+            last_error: current.last_error.clone(),
+            ..settings
+        };
+        current.clone()
+    };
+    save_remote_backup_settings(&settings)?;
+    info!("远程备份设置已更新: {:?}", settings.schedule);
+    get_remote_backup_settings().await
+}
+
+/// Back up the full profile to remote storage now
+#[tauri::command]
+pub async fn create_remote_backup(app_handle: AppHandle) -> Result<CommandResponse<RemoteBackupInfo>, String> {
+    let result = run_remote_backup(&app_handle).await;
+    record_remote_backup_result(&result);
+    match result {
+        Ok(backup) => Ok(CommandResponse::success_with_message(backup, "远程备份成功".to_string())),
+        Err(e) => {
+            error!("远程备份失败: {}", e);
+            Ok(CommandResponse::error(format!("远程备份失败: {}", e)))
+        }
+    }
+}
+
+/// List backups in remote storage (newest first)
+#[tauri::command]
+pub async fn list_remote_backups() -> Result<CommandResponse<Vec<RemoteBackupInfo>>, String> {
+    let store = match remote_store() {
+        Ok(store) => store,
+        Err(e) => return Ok(CommandResponse::error(e)),
+    };
+    match store.list().await {
+        Ok(backups) => Ok(CommandResponse::success(backups)),
+        Err(e) => {
+            error!("获取远程备份列表失败: {}", e);
+            Ok(CommandResponse::error(format!("获取远程备份列表失败: {}", e)))
+        }
+    }
+}
+
+/// Download, decrypt and restore a remote backup
+///
+/// `password` defaults to the stored backup password. After a reinstall the keyring is
+/// empty, so a password that successfully decrypts the backup is stored for later
+/// scheduled backups.
+#[tauri::command]
+pub async fn restore_remote_backup(
+    name: String,
+    password: Option<String>,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<crate::commands::backup::ProfileImportSummary>, String> {
+    info!("从远程备份恢复: {}", name);
+    let stored_password = keyring_store::load(REMOTE_BACKUP_PASSWORD_ENTRY);
+    let Some(password) = password.filter(|p| !p.is_empty()).or_else(|| stored_password.clone()) else {
+        return Ok(CommandResponse::error("需要输入备份密码".to_string()));
+    };
+
+    let result: Result<_, String> = async {
+        let content = remote_store()?.download(&name).await?;
+        let archive: ProfileArchive =
+            serde_json::from_slice(&content).map_err(|_| "不是资料备份文件".to_string())?;
+        let key = password.clone();
+        let plaintext = tokio::task::spawn_blocking(move || profile_archive::open(&key, &archive))
+            .await
+            .map_err(|e| format!("解密资料失败: {}", e))??;
+        let bundle: crate::commands::backup::ProfileBundle =
+            serde_json::from_slice(&plaintext).map_err(|e| format!("解析资料失败: {}", e))?;
+        info!("恢复远程备份: 来自版本 {}，备份于 {}", bundle.app_version, bundle.exported_at);
+        crate::commands::backup::restore_profile(&app_handle, bundle).await
+    }
+    .await;
+
+    match result {
+        Ok(summary) => {
+            if stored_password.is_none() {
+                if let Err(e) = keyring_store::store(REMOTE_BACKUP_PASSWORD_ENTRY, Some(&password)) {
+                    warn!("{}", e);
+                }
+            }
+            info!("远程备份恢复完成: {} 个对话, {} 个错误", summary.conversations_imported, summary.errors.len());
+            Ok(CommandResponse::success_with_message(summary, "远程备份恢复成功".to_string()))
+        }
+        Err(e) => {
+            error!("恢复远程备份失败: {}", e);
+            Ok(CommandResponse::error(format!("恢复远程备份失败: {}", e)))
+        }
+    }
+}
+
 // ================================
 // Command Metadata
 // ================================
@@ -844,12 +1533,27 @@ mod tests {
         assert_eq!(deserialized.interaction_enabled, Some(false));
     }
 
+    #[test]
+    fn test_effective_text_scale_prefers_window_override() {
+        let mut theme = AppConfig::default().theme;
+        theme.text_scale = 120;
+        theme.window_text_scales.insert("bubble".to_string(), 150);
+        assert!(theme.color_blind_palette.is_none());
+
+        assert_eq!(effective_text_scale(&theme, "bubble"), 150);
+        assert_eq!(effective_text_scale(&theme, "main"), 120);
+        assert!(validate_text_scale(40).is_err());
+        assert!(validate_text_scale(100).is_ok());
+    }
+
     #[test]
     fn test_update_theme_config_request() {
         // Arrange
         let request = UpdateThemeConfigRequest {
             current_theme: Some("dark".to_string()),
             custom_css: Some("body { color: red; }".to_string()),
+            text_scale: None,
+            window_text_scales: None,
         };
         
         // Act
@@ -869,6 +1573,7 @@ mod tests {
             minimize_to_tray: Some(false),
             close_to_tray: Some(true),
             show_notifications: Some(false),
+            proxy: None,
         };
         
         // Act
@@ -971,6 +1676,8 @@ mod tests {
         let request = UpdateThemeConfigRequest {
             current_theme: Some("custom".to_string()),
             custom_css: Some(large_css.clone()),
+            text_scale: None,
+            window_text_scales: None,
         };
         
         // Act
@@ -1078,6 +1785,8 @@ mod tests {
         let request = UpdateThemeConfigRequest {
             current_theme: Some("custom".to_string()),
             custom_css: Some(special_css.to_string()),
+            text_scale: None,
+            window_text_scales: None,
         };
         
         // Act
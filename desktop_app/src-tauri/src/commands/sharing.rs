@@ -0,0 +1,272 @@
+//! # 会话分享命令模块
+//!
+//! 将会话发布为只读网页快照：上传前移除系统消息与已删除消息，并对内容中的
+//! 密钥、令牌、邮箱、电话等敏感信息脱敏。发布需要登录；已发布的快照记录
+//! 保存在应用数据目录中，用于展示分享状态和撤销。
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::config::BackendFeature;
+use crate::database::conversation::{Message, MessageRole};
+use crate::http::share_client::{PublishSnapshotRequest, ShareApiClient, SnapshotMessage};
+use crate::utils::data_masking::DataMasker;
+
+lazy_static::lazy_static! {
+    /// 串行化快照记录文件的读写
+    static ref SNAPSHOT_RECORDS_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 已发布的会话快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSnapshot {
+    pub session_id: String,
+    pub snapshot_id: String,
+    pub url: String,
+    pub published_at: i64,
+    pub expires_at: Option<i64>,
+    pub message_count: usize,
+}
+
+impl SharedSnapshot {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.map_or(false, |at| at <= now)
+    }
+}
+
+fn get_snapshot_records_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("shared_snapshots.json"))
+}
+
+fn load_records() -> Result<BTreeMap<String, SharedSnapshot>, String> {
+    let path = get_snapshot_records_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json_data = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read shared snapshots: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse shared snapshots: {}", e))
+}
+
+fn save_records(records: &BTreeMap<String, SharedSnapshot>) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(records)
+        .map_err(|e| format!("Failed to serialize shared snapshots: {}", e))?;
+    fs::write(get_snapshot_records_path()?, json_data)
+        .map_err(|e| format!("Failed to write shared snapshots: {}", e))
+}
+
+/// 创建带用户登录令牌的分享客户端（分享必须登录）
+async fn get_share_client() -> Result<ShareApiClient, String> {
+    crate::http::capabilities::ensure_feature(BackendFeature::Core).map_err(|e| e.to_string())?;
+
+    let token = crate::commands::auth::get_auth_token()
+        .await
+        .map_err(|_| "分享会话需要先登录".to_string())?;
+
+    let mut client = ShareApiClient::new(crate::config::backend_profiles::base_url_for(BackendFeature::Core))
+        .map_err(|e| format!("创建 API 客户端失败: {}", e))?;
+    client.set_auth_token(Some(token));
+    Ok(client)
+}
+
+/// 生成快照消息：仅保留未删除的用户/助手消息，并脱敏内容
+pub fn sanitize_messages(messages: &[Message]) -> Vec<SnapshotMessage> {
+    let masker = DataMasker::new();
+    messages
+        .iter()
+        .filter(|m| !m.is_deleted() && m.role != MessageRole::System)
+        .map(|m| SnapshotMessage {
+            role: match m.role {
+                MessageRole::User => "user",
+                _ => "assistant",
+            }
+            .to_string(),
+            content: masker.mask_all_sensitive(&m.to_markdown()),
+            created_at: m.created_at,
+            edited: m.edited_at.is_some(),
+        })
+        .collect()
+}
+
+/// 发布会话快照，返回可分享的链接
+///
+/// 会话已有快照时先撤销旧快照再重新发布。
+#[tauri::command]
+pub async fn publish_session_snapshot(
+    session_id: String,
+    expires_in_days: Option<u32>,
+    app_handle: AppHandle,
+) -> Result<SharedSnapshot, String> {
+    info!("发布会话快照: {}", session_id);
+
+    let db = crate::database::get_database().ok_or_else(|| "数据库未初始化".to_string())?;
+    let conversation = db
+        .conversation_history
+        .get_conversation(&session_id)
+        .await
+        .map_err(|e| format!("获取会话失败: {}", e))?
+        .ok_or_else(|| format!("会话 {} 不存在", session_id))?;
+    let messages = db
+        .conversation_history
+        .get_messages(&session_id)
+        .await
+        .map_err(|e| format!("获取会话消息失败: {}", e))?;
+
+    let snapshot_messages = sanitize_messages(&messages);
+    if snapshot_messages.is_empty() {
+        return Err("会话中没有可分享的消息".to_string());
+    }
+
+    let client = get_share_client().await?;
+    let now = chrono::Utc::now().timestamp();
+    let expires_at = expires_in_days.map(|days| now + i64::from(days) * 86_400);
+
+    if let Some(previous) = load_records()?.get(&session_id) {
+        if let Err(e) = client.revoke_snapshot(&previous.snapshot_id).await {
+            warn!("撤销旧快照 {} 失败: {}", previous.snapshot_id, e);
+        }
+    }
+
+    let request = PublishSnapshotRequest {
+        title: DataMasker::new().mask_all_sensitive(&conversation.title),
+        messages: snapshot_messages,
+        created_at: now,
+        expires_at,
+    };
+    let response = client
+        .publish_snapshot(&request)
+        .await
+        .map_err(|e| format!("发布快照失败: {}", e))?;
+
+    let snapshot = SharedSnapshot {
+        session_id: session_id.clone(),
+        snapshot_id: response.id,
+        url: response.url,
+        published_at: now,
+        expires_at,
+        message_count: request.messages.len(),
+    };
+
+    {
+        let _guard = SNAPSHOT_RECORDS_LOCK.lock();
+        let mut records = load_records()?;
+        records.insert(session_id.clone(), snapshot.clone());
+        save_records(&records)?;
+    }
+
+    crate::utils::security_audit::log_audit_success(
+        crate::utils::security_audit::AuditEventType::SensitiveDataAccess,
+        &format!("会话 {} 已发布为公开快照", session_id),
+        Some(&snapshot.snapshot_id),
+    );
+    let _ = app_handle.emit_all("session-snapshot-changed", &snapshot);
+
+    Ok(snapshot)
+}
+
+/// 撤销会话的公开快照
+#[tauri::command]
+pub async fn unpublish_snapshot(session_id: String, app_handle: AppHandle) -> Result<(), String> {
+    info!("撤销会话快照: {}", session_id);
+
+    let record = load_records()?
+        .get(&session_id)
+        .cloned()
+        .ok_or_else(|| format!("会话 {} 没有已发布的快照", session_id))?;
+
+    let client = get_share_client().await?;
+    match client.revoke_snapshot(&record.snapshot_id).await {
+        // 后端已不存在该快照（例如已过期）时视为撤销成功
+        Ok(()) | Err(crate::http::ApiError::ApiResponseError { status: 404, .. }) => {}
+        Err(e) => return Err(format!("撤销快照失败: {}", e)),
+    }
+
+    {
+        let _guard = SNAPSHOT_RECORDS_LOCK.lock();
+        let mut records = load_records()?;
+        records.remove(&session_id);
+        save_records(&records)?;
+    }
+
+    let _ = app_handle.emit_all("session-snapshot-changed", serde_json::json!({
+        "session_id": session_id,
+        "revoked": true,
+    }));
+    Ok(())
+}
+
+/// 获取所有仍然有效的公开快照（自动清理已过期的记录）
+#[tauri::command]
+pub async fn list_shared_snapshots() -> Result<Vec<SharedSnapshot>, String> {
+    let now = chrono::Utc::now().timestamp();
+    let _guard = SNAPSHOT_RECORDS_LOCK.lock();
+    let mut records = load_records()?;
+
+    let before = records.len();
+    records.retain(|_, snapshot| !snapshot.is_expired(now));
+    if records.len() != before {
+        save_records(&records)?;
+    }
+
+    Ok(records.into_values().collect())
+}
+
+/// 获取指定会话的公开快照
+#[tauri::command]
+pub async fn get_session_snapshot(
+    session_id: String,
+) -> Result<Option<SharedSnapshot>, String> {
+    let now = chrono::Utc::now().timestamp();
+    Ok(load_records()?
+        .remove(&session_id)
+        .filter(|snapshot| !snapshot.is_expired(now)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: MessageRole, content: &str, deleted: bool) -> Message {
+        Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: "session".to_string(),
+            role,
+            content: content.to_string(),
+            created_at: 0,
+            edited_at: None,
+            deleted_at: deleted.then_some(1),
+            payload: None,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_sanitize_skips_system_and_deleted() {
+        let messages = vec![
+            message(MessageRole::System, "system prompt", false),
+            message(MessageRole::User, "hello", false),
+            message(MessageRole::User, "", true),
+            message(MessageRole::Assistant, "hi", false),
+        ];
+        let sanitized = sanitize_messages(&messages);
+        assert_eq!(sanitized.len(), 2);
+        assert_eq!(sanitized[0].role, "user");
+        assert_eq!(sanitized[1].role, "assistant");
+    }
+
+    #[test]
+    fn test_sanitize_masks_email() {
+        let sanitized = sanitize_messages(&[message(MessageRole::User, "mail me at alice@example.com", false)]);
+        assert!(!sanitized[0].content.contains("alice@example.com"));
+    }
+}
@@ -6,9 +6,14 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager, Runtime, State};
 
+/// 用户宏与快捷回复的保存文件，与应用配置同在当前资料目录
+const LIBRARY_FILE: &str = "shortcut_library.json";
+
 /// 修饰键配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModifierKeys {
@@ -37,6 +42,9 @@ pub struct ShortcutConfig {
     pub prevent_default: bool,
     #[serde(default = "default_true")]
     pub customizable: bool,
+    /// 绑定的动作ID（见 `commands::actions`）
+    #[serde(default)]
+    pub action_id: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -52,21 +60,114 @@ pub struct ShortcutBinding {
     pub trigger_count: u64,
 }
 
+/// 宏步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroStep {
+    /// 要执行的动作ID
+    pub action: String,
+    /// 动作参数
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// 执行前的延迟（毫秒）
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+/// 快捷键宏：按顺序执行的一组动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutMacro {
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+    /// 触发该宏的快捷键ID
+    #[serde(default)]
+    pub shortcut_id: Option<String>,
+}
+
+/// 快捷回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickReply {
+    pub id: String,
+    pub label: String,
+    pub text: String,
+    /// 触发该回复的快捷键ID
+    #[serde(default)]
+    pub shortcut_id: Option<String>,
+}
+
 /// 快捷键注册表状态
 pub struct ShortcutRegistry {
     pub shortcuts: Mutex<HashMap<String, ShortcutBinding>>,
+    pub macros: Mutex<HashMap<String, ShortcutMacro>>,
+    pub quick_replies: Mutex<HashMap<String, QuickReply>>,
 }
 
 impl ShortcutRegistry {
     pub fn new() -> Self {
         Self {
             shortcuts: Mutex::new(HashMap::new()),
+            macros: Mutex::new(HashMap::new()),
+            quick_replies: Mutex::new(HashMap::new()),
         }
     }
+
+    /// 当前的宏与快捷回复（按ID排序）
+    fn library(&self) -> ShortcutLibrary {
+        let mut macros: Vec<ShortcutMacro> = self.macros.lock().unwrap().values().cloned().collect();
+        macros.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut quick_replies: Vec<QuickReply> = self.quick_replies.lock().unwrap().values().cloned().collect();
+        quick_replies.sort_by(|a, b| a.id.cmp(&b.id));
+
+        ShortcutLibrary { macros, quick_replies }
+    }
+
+    /// 用保存的内容替换内存中的宏与快捷回复
+    fn restore_library(&self, library: ShortcutLibrary) {
+        *self.macros.lock().unwrap() = library.macros.into_iter().map(|m| (m.id.clone(), m)).collect();
+        *self.quick_replies.lock().unwrap() = library.quick_replies.into_iter().map(|r| (r.id.clone(), r)).collect();
+    }
+}
+
+/// 保存到磁盘的宏与快捷回复
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ShortcutLibrary {
+    #[serde(default)]
+    macros: Vec<ShortcutMacro>,
+    #[serde(default)]
+    quick_replies: Vec<QuickReply>,
+}
+
+fn library_path() -> Result<PathBuf, String> {
+    Ok(crate::utils::get_profile_data_dir()?.join(LIBRARY_FILE))
+}
+
+fn save_library(registry: &ShortcutRegistry) -> Result<(), String> {
+    let path = library_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&registry.library())
+        .map_err(|e| format!("序列化宏与快捷回复失败: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("保存宏与快捷回复失败: {}", e))
+}
+
+/// 从当前资料目录加载宏与快捷回复（启动和切换资料时调用）
+pub fn initialize_shortcut_library(app_handle: &AppHandle) -> Result<(), String> {
+    let path = library_path()?;
+    let library = if path.exists() {
+        let json = fs::read_to_string(&path).map_err(|e| format!("读取宏与快捷回复失败: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("解析宏与快捷回复失败: {}", e))?
+    } else {
+        ShortcutLibrary::default()
+    };
+
+    app_handle.state::<ShortcutRegistry>().restore_library(library);
+    Ok(())
 }
 
 /// 将快捷键配置转换为快捷键字符串
-fn shortcut_to_string(config: &ShortcutConfig) -> String {
+pub(crate) fn shortcut_to_string(config: &ShortcutConfig) -> String {
     let mut parts = Vec::new();
 
     if config.modifiers.ctrl {
@@ -89,10 +190,41 @@ fn shortcut_to_string(config: &ShortcutConfig) -> String {
     parts.join("+")
 }
 
+/// 向系统注册全局快捷键
+///
+/// 触发时发送 `global-shortcut-triggered` 事件；绑定了动作（`action_id`）的快捷键同时在后端执行该动作，
+/// 例如 `quick-chat` 会在桌宠旁边弹出快速对话输入框。
+fn register_global(app: &AppHandle, config: &ShortcutConfig) -> Result<String, String> {
+    use tauri::GlobalShortcutManager;
+
+    let shortcut_string = shortcut_to_string(config);
+    let shortcut_clone = shortcut_string.clone();
+    let id_clone = config.id.clone();
+    let action_id = config.action_id.clone();
+    let app_clone = app.clone();
+
+    app.global_shortcut_manager()
+        .register(&shortcut_string, move || {
+            let _ = app_clone.emit_all("global-shortcut-triggered", json!({
+                "id": id_clone.clone(),
+                "shortcut": shortcut_clone.clone(),
+                "action_id": action_id.clone(),
+                "timestamp": chrono::Utc::now().timestamp_millis(),
+            }));
+            if let Some(action_id) = &action_id {
+                if let Err(e) = crate::commands::actions::dispatch_action(&app_clone, action_id, None) {
+                    tracing::warn!("快捷键动作执行失败: {} ({})", action_id, e);
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(shortcut_string)
+}
+
 /// 注册快捷键
 #[tauri::command]
-pub async fn register_shortcut<R: Runtime>(
-    app: AppHandle<R>,
+pub async fn register_shortcut(
+    app: AppHandle,
     registry: State<'_, ShortcutRegistry>,
     config: ShortcutConfig,
 ) -> Result<String, String> {
@@ -107,22 +239,16 @@ pub async fn register_shortcut<R: Runtime>(
         }
     }
 
+    // 验证绑定的动作是否存在
+    if let Some(action_id) = &config.action_id {
+        if !crate::commands::actions::is_known_action(&registry, action_id) {
+            return Err(format!("未知的动作: {}", action_id));
+        }
+    }
+
     // 如果是全局快捷键，注册到 Tauri
     if config.scope == "global" {
-        use tauri::GlobalShortcutManager;
-        
-        let shortcut_clone = shortcut_string.clone();
-        let id_clone = id.clone();
-        let app_clone = app.clone();
-        
-        match app.global_shortcut_manager().register(&shortcut_string, move || {
-            // 触发快捷键事件
-            let _ = app_clone.emit_all("global-shortcut-triggered", json!({
-                "id": id_clone.clone(),
-                "shortcut": shortcut_clone.clone(),
-                "timestamp": chrono::Utc::now().timestamp_millis(),
-            }));
-        }) {
+        match register_global(&app, &config) {
             Ok(_) => {
                 println!("全局快捷键已注册: {} ({})", id, shortcut_string);
             }
@@ -245,8 +371,8 @@ pub async fn get_shortcut_info(
 
 /// 更新快捷键配置
 #[tauri::command]
-pub async fn update_shortcut<R: Runtime>(
-    app: AppHandle<R>,
+pub async fn update_shortcut(
+    app: AppHandle,
     registry: State<'_, ShortcutRegistry>,
     id: String,
     config: ShortcutConfig,
@@ -262,8 +388,8 @@ pub async fn update_shortcut<R: Runtime>(
 
 /// 启用或禁用快捷键
 #[tauri::command]
-pub async fn toggle_shortcut<R: Runtime>(
-    app: AppHandle<R>,
+pub async fn toggle_shortcut(
+    app: AppHandle,
     registry: State<'_, ShortcutRegistry>,
     id: String,
     enabled: bool,
@@ -281,17 +407,7 @@ pub async fn toggle_shortcut<R: Runtime>(
 
             if enabled {
                 // 重新注册
-                let id_clone = id.clone();
-                let app_clone = app.clone();
-                let shortcut_clone = shortcut_string.clone();
-                
-                match app.global_shortcut_manager().register(&shortcut_string, move || {
-                    let _ = app_clone.emit_all("global-shortcut-triggered", json!({
-                        "id": id_clone.clone(),
-                        "shortcut": shortcut_clone.clone(),
-                        "timestamp": chrono::Utc::now().timestamp_millis(),
-                    }));
-                }) {
+                match register_global(&app, &binding.config) {
                     Ok(_) => println!("快捷键已重新启用: {}", id),
                     Err(e) => return Err(format!("启用快捷键失败: {}", e)),
                 }
@@ -438,6 +554,431 @@ pub fn validate_shortcut_config(config: ShortcutConfig) -> Result<bool, String>
     Ok(true)
 }
 
+// ================================
+// 宏与快捷回复
+// ================================
+
+/// 保存快捷键宏
+#[tauri::command]
+pub async fn save_shortcut_macro(
+    registry: State<'_, ShortcutRegistry>,
+    shortcut_macro: ShortcutMacro,
+) -> Result<(), String> {
+    if shortcut_macro.id.is_empty() {
+        return Err("宏ID不能为空".to_string());
+    }
+    if shortcut_macro.steps.is_empty() {
+        return Err("宏至少需要一个步骤".to_string());
+    }
+
+    registry.macros.lock().unwrap().insert(shortcut_macro.id.clone(), shortcut_macro);
+    save_library(&registry)
+}
+
+/// 获取所有快捷键宏
+#[tauri::command]
+pub async fn get_shortcut_macros(
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<ShortcutMacro>, String> {
+    let macros = registry.macros.lock().unwrap();
+    Ok(macros.values().cloned().collect())
+}
+
+/// 保存快捷回复
+#[tauri::command]
+pub async fn save_quick_reply(
+    registry: State<'_, ShortcutRegistry>,
+    reply: QuickReply,
+) -> Result<(), String> {
+    if reply.id.is_empty() {
+        return Err("快捷回复ID不能为空".to_string());
+    }
+
+    registry.quick_replies.lock().unwrap().insert(reply.id.clone(), reply);
+    save_library(&registry)
+}
+
+/// 获取所有快捷回复
+#[tauri::command]
+pub async fn get_quick_replies(
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<QuickReply>, String> {
+    let replies = registry.quick_replies.lock().unwrap();
+    Ok(replies.values().cloned().collect())
+}
+
+// ================================
+// 预设导入导出
+// ================================
+
+/// 当前预设格式版本
+pub const SHORTCUT_PRESET_VERSION: u32 = 1;
+
+/// 快捷键预设包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShortcutPreset {
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub exported_at: i64,
+    #[serde(default)]
+    pub shortcuts: Vec<ShortcutConfig>,
+    #[serde(default)]
+    pub macros: Vec<ShortcutMacro>,
+    #[serde(default)]
+    pub quick_replies: Vec<QuickReply>,
+}
+
+/// 导入冲突处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetConflictStrategy {
+    /// 跳过冲突项，保留现有配置
+    Skip,
+    /// 用预设中的配置覆盖现有配置
+    Overwrite,
+    /// 重命名冲突项；按键组合冲突的快捷键以禁用状态导入，等待用户重新绑定
+    Remap,
+}
+
+/// 预设导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PresetImportReport {
+    pub imported: Vec<String>,
+    pub skipped: Vec<String>,
+    pub overwritten: Vec<String>,
+    /// (原ID, 新ID)
+    pub remapped: Vec<(String, String)>,
+    /// 因按键组合冲突而以禁用状态导入的快捷键
+    pub disabled: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// 查找与给定配置按键组合冲突的已注册快捷键
+fn find_key_conflict(
+    shortcuts: &HashMap<String, ShortcutBinding>,
+    config: &ShortcutConfig,
+) -> Option<String> {
+    let shortcut_string = shortcut_to_string(config);
+    shortcuts
+        .iter()
+        .find(|(id, binding)| {
+            *id != &config.id
+                && binding.config.enabled
+                && binding.config.scope == config.scope
+                && shortcut_to_string(&binding.config) == shortcut_string
+        })
+        .map(|(id, _)| id.clone())
+}
+
+/// 为冲突项生成一个未被占用的新ID
+fn remap_id<F>(id: &str, is_taken: F) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    let mut index = 1;
+    loop {
+        let candidate = format!("{}_imported_{}", id, index);
+        if !is_taken(&candidate) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// 导出快捷键预设
+#[tauri::command]
+pub async fn export_shortcut_preset(
+    registry: State<'_, ShortcutRegistry>,
+    name: String,
+    description: Option<String>,
+) -> Result<String, String> {
+    let mut shortcuts: Vec<ShortcutConfig> = registry
+        .shortcuts
+        .lock()
+        .unwrap()
+        .values()
+        .map(|b| b.config.clone())
+        .collect();
+    shortcuts.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let ShortcutLibrary { macros, quick_replies } = registry.library();
+
+    let preset = ShortcutPreset {
+        version: SHORTCUT_PRESET_VERSION,
+        name,
+        description,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        shortcuts,
+        macros,
+        quick_replies,
+    };
+
+    serde_json::to_string_pretty(&preset).map_err(|e| format!("序列化快捷键预设失败: {}", e))
+}
+
+/// 导入快捷键预设
+#[tauri::command]
+pub async fn import_shortcut_preset(
+    app: AppHandle,
+    registry: State<'_, ShortcutRegistry>,
+    preset_json: String,
+    strategy: PresetConflictStrategy,
+) -> Result<PresetImportReport, String> {
+    let preset: ShortcutPreset = serde_json::from_str(&preset_json)
+        .map_err(|e| format!("解析快捷键预设失败: {}", e))?;
+
+    if preset.version > SHORTCUT_PRESET_VERSION {
+        return Err(format!(
+            "不支持的预设版本: {} (当前支持 {})",
+            preset.version, SHORTCUT_PRESET_VERSION
+        ));
+    }
+
+    let mut report = PresetImportReport::default();
+    // 快捷键ID重映射表，用于更新宏与快捷回复中的引用
+    let mut id_map: HashMap<String, String> = HashMap::new();
+
+    for mut config in preset.shortcuts {
+        if let Err(e) = validate_shortcut_config(config.clone()) {
+            report.errors.push(format!("{}: {}", config.id, e));
+            continue;
+        }
+
+        let original_id = config.id.clone();
+        let (id_taken, key_conflict) = {
+            let shortcuts = registry.shortcuts.lock().unwrap();
+            (
+                shortcuts.contains_key(&config.id),
+                find_key_conflict(&shortcuts, &config),
+            )
+        };
+
+        if id_taken || key_conflict.is_some() {
+            match strategy {
+                PresetConflictStrategy::Skip => {
+                    report.skipped.push(original_id);
+                    continue;
+                }
+                PresetConflictStrategy::Overwrite => {
+                    if id_taken {
+                        unregister_shortcut(app.clone(), registry.clone(), config.id.clone()).await?;
+                    }
+                    if let Some(conflict_id) = key_conflict {
+                        unregister_shortcut(app.clone(), registry.clone(), conflict_id).await?;
+                    }
+                    report.overwritten.push(original_id.clone());
+                }
+                PresetConflictStrategy::Remap => {
+                    if id_taken {
+                        let new_id = {
+                            let shortcuts = registry.shortcuts.lock().unwrap();
+                            remap_id(&config.id, |candidate| shortcuts.contains_key(candidate))
+                        };
+                        config.id = new_id.clone();
+                        id_map.insert(original_id.clone(), new_id.clone());
+                        report.remapped.push((original_id.clone(), new_id));
+                    }
+                    if key_conflict.is_some() {
+                        config.enabled = false;
+                        report.disabled.push(config.id.clone());
+                    }
+                }
+            }
+        }
+
+        // 禁用的全局快捷键仅记录到注册表，不向系统注册
+        let result = if config.enabled || config.scope != "global" {
+            register_shortcut(app.clone(), registry.clone(), config.clone())
+                .await
+                .map(|_| ())
+        } else {
+            let mut shortcuts = registry.shortcuts.lock().unwrap();
+            shortcuts.insert(
+                config.id.clone(),
+                ShortcutBinding {
+                    config: config.clone(),
+                    registered_at: chrono::Utc::now().timestamp_millis(),
+                    last_triggered: None,
+                    trigger_count: 0,
+                },
+            );
+            Ok(())
+        };
+
+        match result {
+            Ok(()) => report.imported.push(config.id),
+            Err(e) => report.errors.push(format!("{}: {}", original_id, e)),
+        }
+    }
+
+    {
+        let mut macros = registry.macros.lock().unwrap();
+        for mut shortcut_macro in preset.macros {
+            if let Some(new_id) = shortcut_macro.shortcut_id.as_ref().and_then(|id| id_map.get(id)) {
+                shortcut_macro.shortcut_id = Some(new_id.clone());
+            }
+
+            let original_id = shortcut_macro.id.clone();
+            if macros.contains_key(&shortcut_macro.id) {
+                match strategy {
+                    PresetConflictStrategy::Skip => {
+                        report.skipped.push(original_id);
+                        continue;
+                    }
+                    PresetConflictStrategy::Overwrite => report.overwritten.push(original_id.clone()),
+                    PresetConflictStrategy::Remap => {
+                        let new_id = remap_id(&shortcut_macro.id, |candidate| macros.contains_key(candidate));
+                        shortcut_macro.id = new_id.clone();
+                        report.remapped.push((original_id, new_id));
+                    }
+                }
+            }
+
+            report.imported.push(shortcut_macro.id.clone());
+            macros.insert(shortcut_macro.id.clone(), shortcut_macro);
+        }
+    }
+
+    {
+        let mut replies = registry.quick_replies.lock().unwrap();
+        for mut reply in preset.quick_replies {
+            if let Some(new_id) = reply.shortcut_id.as_ref().and_then(|id| id_map.get(id)) {
+                reply.shortcut_id = Some(new_id.clone());
+            }
+
+            let original_id = reply.id.clone();
+            if replies.contains_key(&reply.id) {
+                match strategy {
+                    PresetConflictStrategy::Skip => {
+                        report.skipped.push(original_id);
+                        continue;
+                    }
+                    PresetConflictStrategy::Overwrite => report.overwritten.push(original_id.clone()),
+                    PresetConflictStrategy::Remap => {
+                        let new_id = remap_id(&reply.id, |candidate| replies.contains_key(candidate));
+                        reply.id = new_id.clone();
+                        report.remapped.push((original_id, new_id));
+                    }
+                }
+            }
+
+            report.imported.push(reply.id.clone());
+            replies.insert(reply.id.clone(), reply);
+        }
+    }
+
+    if let Err(e) = save_library(&registry) {
+        report.errors.push(e);
+    }
+
+    let _ = app.emit_all("shortcut-preset-imported", json!({
+        "name": preset.name,
+        "imported": report.imported.len(),
+        "skipped": report.skipped.len(),
+        "timestamp": chrono::Utc::now().timestamp_millis(),
+    }));
+
+    Ok(report)
+}
+
 // 导入 serde_json 用于创建 JSON 数据
 use serde_json::json;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config(id: &str, key: &str) -> ShortcutConfig {
+        ShortcutConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            key: key.to_string(),
+            modifiers: ModifierKeys { ctrl: true, alt: false, shift: false, meta: false },
+            scope: "local".to_string(),
+            category: "custom".to_string(),
+            enabled: true,
+            prevent_default: false,
+            customizable: true,
+            action_id: None,
+        }
+    }
+
+    #[test]
+    fn test_remap_id_skips_taken_ids() {
+        let taken = ["chat_imported_1".to_string()];
+        let new_id = remap_id("chat", |candidate| taken.iter().any(|t| t == candidate));
+        assert_eq!(new_id, "chat_imported_2");
+    }
+
+    #[test]
+    fn test_find_key_conflict() {
+        let mut shortcuts = HashMap::new();
+        shortcuts.insert(
+            "open_chat".to_string(),
+            ShortcutBinding {
+                config: sample_config("open_chat", "K"),
+                registered_at: 0,
+                last_triggered: None,
+                trigger_count: 0,
+            },
+        );
+
+        assert_eq!(
+            find_key_conflict(&shortcuts, &sample_config("other", "K")),
+            Some("open_chat".to_string())
+        );
+        assert_eq!(find_key_conflict(&shortcuts, &sample_config("other", "J")), None);
+    }
+
+    #[test]
+    fn test_preset_roundtrip() {
+        let preset = ShortcutPreset {
+            version: SHORTCUT_PRESET_VERSION,
+            name: "team".to_string(),
+            description: None,
+            exported_at: 0,
+            shortcuts: vec![sample_config("open_chat", "K")],
+            macros: vec![],
+            quick_replies: vec![QuickReply {
+                id: "greet".to_string(),
+                label: "Greet".to_string(),
+                text: "Hello!".to_string(),
+                shortcut_id: Some("open_chat".to_string()),
+            }],
+        };
+
+        let json = serde_json::to_string(&preset).unwrap();
+        let parsed: ShortcutPreset = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.shortcuts.len(), 1);
+        assert_eq!(parsed.quick_replies[0].shortcut_id.as_deref(), Some("open_chat"));
+    }
+
+    #[test]
+    fn test_library_roundtrip() {
+        let registry = ShortcutRegistry::new();
+        registry.restore_library(ShortcutLibrary {
+            macros: vec![ShortcutMacro {
+                id: "wave".to_string(),
+                name: "Wave".to_string(),
+                steps: vec![MacroStep { action: "open_chat".to_string(), payload: None, delay_ms: 0 }],
+                shortcut_id: None,
+            }],
+            quick_replies: vec![
+                QuickReply { id: "thanks".to_string(), label: "Thanks".to_string(), text: "谢谢".to_string(), shortcut_id: None },
+                QuickReply { id: "greet".to_string(), label: "Greet".to_string(), text: "你好".to_string(), shortcut_id: None },
+            ],
+        });
+
+        let json = serde_json::to_string(&registry.library()).unwrap();
+        let restored = ShortcutRegistry::new();
+        restored.restore_library(serde_json::from_str(&json).unwrap());
+
+        let library = restored.library();
+        assert_eq!(library.macros[0].steps[0].action, "open_chat");
+        let ids: Vec<&str> = library.quick_replies.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["greet", "thanks"]);
+    }
+}
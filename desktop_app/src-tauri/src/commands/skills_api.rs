@@ -11,18 +11,18 @@ use tracing::{debug, error, info};
 
 /// 获取 Skills API 客户端
 fn get_skills_client(state: &AppState) -> Result<SkillsApiClient, String> {
-    // 从配置或环境变量读取 API 地址，Skills 使用核心服务
-    let api_url = std::env::var("ZISHU_API_URL")
-        .unwrap_or_else(|_| {
-            let router = crate::config::ApiRouter::new();
-            router.core_url()
-        });
+    crate::http::capabilities::ensure_feature(crate::config::BackendFeature::Skills)
+        .map_err(|e| e.to_string())?;
+
+    // 从端点配置或环境变量读取 API 地址，默认使用核心服务
+    let api_url = crate::config::backend_profiles::base_url_for(crate::config::BackendFeature::Skills);
 
     let mut client = SkillsApiClient::new(api_url)
         .map_err(|e| format!("创建 API 客户端失败: {}", e))?;
 
-    // TODO: 从状态中获取认证令牌
-    // client.set_auth_token(state.auth_token.clone());
+    client.set_auth_token(crate::config::backend_profiles::bearer_token_for(
+        crate::config::BackendFeature::Skills,
+    ));
 
     Ok(client)
 }
@@ -0,0 +1,480 @@
+//! # 空闲时整理命令模块
+//!
+//! 后台调度器每分钟检查一次：系统空闲且接通电源、且距上次完整整理已超过设定间隔（或上一轮
+//! 未完成）时运行整理任务。进度保存在 `sleep_learning_progress.json`，不再空闲时暂停，
+//! 下一个空闲时段从断点继续；完成后通过 `sleep-learning-completed` 事件推送报告。
+//! 各阶段的说明见 [`crate::utils::sleep_learning`]。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::session_memory;
+use crate::commands::topics::{self, TopicRange};
+use crate::database::conversation::{Message, MessageRole as StoredRole};
+use crate::database::embedding;
+use crate::database::vector_search_service::VectorSearchService;
+use crate::utils::bridge::{ChatMessage, ChatRequest, MessageRole, PythonApiBridge};
+use crate::utils::resource_budget::{self, WorkKind};
+use crate::utils::sleep_learning::{
+    self, ConsolidationPhase, ConsolidationProgress, SessionSummary, SleepLearningSettings, SystemConditions,
+};
+use crate::utils::watchdog;
+
+/// 检查空闲状态的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// 调度器心跳停滞阈值（单个阶段可能较慢）
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(3 * 60 * 60);
+/// 摘要与话题聚类覆盖的天数
+const WINDOW_DAYS: i64 = 90;
+/// 压缩的向量集合（对话消息）
+const CONVERSATION_COLLECTION: &str = "conversations";
+/// 压缩时每检查多少条向量确认一次仍然空闲
+const COMPACTION_CHECK_EVERY: usize = 200;
+/// 摘要输入中每条消息与全文的字符数上限
+const MAX_MESSAGE_CHARS: usize = 300;
+const MAX_TRANSCRIPT_CHARS: usize = 8000;
+
+const SUMMARY_INSTRUCTION: &str = "请阅读下面的对话记录，用不超过五句话总结其中的要点，\
+包括用户关心的问题、得出的结论和尚未解决的事项。只输出摘要本身。";
+
+lazy_static! {
+    static ref SETTINGS: RwLock<SleepLearningSettings> = RwLock::new(SleepLearningSettings::default());
+    static ref SUMMARIES_LOCK: Mutex<()> = Mutex::new(());
+}
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 整理状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SleepLearningStatus {
+    pub settings: SleepLearningSettings,
+    pub conditions: SystemConditions,
+    /// 当前系统状态是否允许运行
+    pub eligible: bool,
+    pub running: bool,
+    /// 当前或最近一轮整理的进度与报告
+    pub progress: Option<ConsolidationProgress>,
+}
+
+fn app_data_path(file_name: &str) -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join(file_name))
+}
+
+/// 加载空闲整理设置（启动时调用）
+pub fn initialize_sleep_learning() -> Result<(), String> {
+    let path = app_data_path("sleep_learning.json")?;
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&path).map_err(|e| format!("Failed to read sleep learning settings: {}", e))?;
+    let settings: SleepLearningSettings =
+        serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse sleep learning settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn load_progress() -> Result<Option<ConsolidationProgress>, String> {
+    let path = app_data_path("sleep_learning_progress.json")?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json_data = fs::read_to_string(&path).map_err(|e| format!("Failed to read sleep learning progress: {}", e))?;
+    serde_json::from_str(&json_data)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse sleep learning progress: {}", e))
+}
+
+fn save_progress(progress: &ConsolidationProgress) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(progress)
+        .map_err(|e| format!("Failed to serialize sleep learning progress: {}", e))?;
+    fs::write(app_data_path("sleep_learning_progress.json")?, json_data)
+        .map_err(|e| format!("Failed to write sleep learning progress: {}", e))
+}
+
+/// 读取已生成的会话摘要（会话 ID -> 摘要）
+pub(crate) fn load_summaries() -> Result<BTreeMap<String, SessionSummary>, String> {
+    let path = app_data_path("session_summaries.json")?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json_data = fs::read_to_string(&path).map_err(|e| format!("Failed to read session summaries: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse session summaries: {}", e))
+}
+
+fn save_summaries(summaries: &BTreeMap<String, SessionSummary>) -> Result<(), String> {
+    let json_data =
+        serde_json::to_string_pretty(summaries).map_err(|e| format!("Failed to serialize session summaries: {}", e))?;
+    fs::write(app_data_path("session_summaries.json")?, json_data)
+        .map_err(|e| format!("Failed to write session summaries: {}", e))
+}
+
+/// 保存一个会话的摘要（整理任务与聊天时的自动摘要都会写入，读改写期间加锁）
+pub(crate) fn store_summary(session_id: &str, summary: SessionSummary) -> Result<(), String> {
+    let _guard = SUMMARIES_LOCK.lock();
+    let mut summaries = load_summaries()?;
+    summaries.insert(session_id.to_string(), summary);
+    save_summaries(&summaries)
+}
+
+/// 运行期间的上下文：`force` 为 true 时忽略空闲条件（手动触发）
+struct RunContext<'a> {
+    app_handle: &'a AppHandle,
+    settings: SleepLearningSettings,
+    force: bool,
+}
+
+impl RunContext<'_> {
+    fn still_idle(&self) -> bool {
+        self.force || self.settings.allows(&SystemConditions::detect())
+    }
+}
+
+/// 对话记录文本（每条消息截断，总长度有上限）
+fn transcript(messages: &[Message]) -> String {
+    let mut text = String::new();
+    for message in messages.iter().filter(|m| !m.is_deleted()) {
+        let speaker = match message.role {
+            StoredRole::User => "用户",
+            StoredRole::Assistant => "助手",
+            StoredRole::System => continue,
+        };
+        let content: String = message.content.chars().take(MAX_MESSAGE_CHARS).collect();
+        let line = format!("{}：{}\n", speaker, content);
+        if text.chars().count() + line.chars().count() > MAX_TRANSCRIPT_CHARS {
+            break;
+        }
+        text.push_str(&line);
+    }
+    text
+}
+
+/// 请 LLM 总结会话
+pub(crate) async fn summarize(title: &str, messages: &[Message]) -> Result<String, String> {
+    resource_budget::defer(WorkKind::Llm, "session_summary").await;
+    let bridge = PythonApiBridge::default().map_err(|e| format!("创建 API 客户端失败: {}", e))?;
+    let request = ChatRequest {
+        messages: vec![
+            ChatMessage {
+                role: MessageRole::System,
+                content: SUMMARY_INSTRUCTION.to_string(),
+            },
+            ChatMessage {
+                role: MessageRole::User,
+                content: format!("会话标题：{}\n\n{}", title, transcript(messages)),
+            },
+        ],
+        model: None,
+        adapter: None,
+        character_id: None,
+        max_tokens: Some(300),
+        temperature: Some(0.3),
+        top_p: None,
+        stream: Some(false),
+        session_id: None,
+        images: Vec::new(),
+    };
+
+    let response = bridge
+        .send_chat_message(request)
+        .await
+        .map_err(|e| format!("会话摘要请求失败: {}", e))?;
+    let summary = response
+        .choices
+        .first()
+        .map(|choice| choice.message.content.trim().to_string())
+        .unwrap_or_default();
+    if summary.is_empty() {
+        return Err("会话摘要结果为空".to_string());
+    }
+    Ok(summary)
+}
+
+/// 阶段 1：为长会话重新生成摘要；返回 false 表示中途暂停
+async fn consolidate_summaries(ctx: &RunContext<'_>, progress: &mut ConsolidationProgress) -> Result<bool, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let history = &db.conversation_history;
+    let range = TopicRange::last_days(WINDOW_DAYS);
+    let conversations = history
+        .list_conversations(range.from, range.to)
+        .await
+        .map_err(|e| format!("读取会话失败: {}", e))?;
+    let summaries = load_summaries()?;
+
+    for conversation in conversations {
+        if progress.is_processed(&conversation.id) {
+            continue;
+        }
+        if !ctx.still_idle() {
+            return Ok(false);
+        }
+
+        match history.get_messages(&conversation.id).await {
+            Ok(messages) => {
+                let message_count = messages.iter().filter(|m| !m.is_deleted()).count();
+                if sleep_learning::needs_summary(message_count, summaries.get(&conversation.id), &ctx.settings) {
+                    match summarize(&conversation.title, &messages).await {
+                        Ok(summary) => {
+                            let summary = SessionSummary {
+                                summary,
+                                message_count,
+                                generated_at: Utc::now().timestamp(),
+                            };
+                            store_summary(&conversation.id, summary.clone())?;
+                            session_memory::index_summary(&conversation.id, &conversation.title, &summary).await;
+                            progress.report.summarized_sessions.push(conversation.id.clone());
+                        }
+                        Err(e) => progress.mark_failed(&conversation.id, &e),
+                    }
+                }
+            }
+            Err(e) => progress.mark_failed(&conversation.id, &format!("读取消息失败: {}", e)),
+        }
+        progress.mark_processed(&conversation.id);
+        save_progress(progress)?;
+    }
+    Ok(true)
+}
+
+pub(crate) fn vector_service() -> Option<VectorSearchService> {
+    crate::database::get_database_manager()
+        .and_then(|m| m.qdrant())
+        .map(VectorSearchService::new)
+}
+
+/// 阶段 2：重新嵌入提供者已变化的集合
+async fn consolidate_embeddings(ctx: &RunContext<'_>, progress: &mut ConsolidationProgress) -> Result<bool, String> {
+    let Some(service) = vector_service() else {
+        return Ok(true);
+    };
+
+    for collection in embedding::current_settings().stale_collections() {
+        if progress.is_processed(&collection) {
+            continue;
+        }
+        if !ctx.still_idle() {
+            return Ok(false);
+        }
+        resource_budget::defer(WorkKind::Background, "sleep_learning_reembed").await;
+
+        match service.reembed_collection(&collection, |_, _| {}).await {
+            Ok(report) => {
+                progress.report.reembedded_points += report.reembedded;
+                progress.report.reembedded_collections.push(collection.clone());
+                let _ = ctx.app_handle.emit_all("embedding-reembed-completed", &report);
+            }
+            Err(e) => progress.mark_failed(&collection, &e.to_string()),
+        }
+        progress.mark_processed(&collection);
+        save_progress(progress)?;
+    }
+    Ok(true)
+}
+
+/// 阶段 3：移除已删除（或软删除）消息遗留的向量
+async fn compact_vectors(ctx: &RunContext<'_>, progress: &mut ConsolidationProgress) -> Result<bool, String> {
+    let (Some(service), Some(db)) = (vector_service(), crate::database::get_database()) else {
+        return Ok(true);
+    };
+    if progress.is_processed(CONVERSATION_COLLECTION) {
+        return Ok(true);
+    }
+
+    let entries = service
+        .list_entries(CONVERSATION_COLLECTION)
+        .await
+        .map_err(|e| format!("读取向量失败: {}", e))?;
+    for (index, (id, payload)) in entries.iter().enumerate() {
+        // 已删除的向量不会再出现，暂停后重新扫描即可继续
+        if index % COMPACTION_CHECK_EVERY == 0 && !ctx.still_idle() {
+            save_progress(progress)?;
+            return Ok(false);
+        }
+        let message_id = payload.get("message_id").and_then(|v| v.as_str()).unwrap_or(id);
+        let orphaned = match db.conversation_history.get_message(message_id).await {
+            Ok(Some(message)) => message.is_deleted(),
+            Ok(None) => true,
+            Err(e) => {
+                warn!("检查消息 {} 失败，保留其向量: {}", message_id, e);
+                false
+            }
+        };
+        if orphaned {
+            match service.delete_vector(CONVERSATION_COLLECTION, id).await {
+                Ok(()) => progress.report.removed_vectors += 1,
+                Err(e) => warn!("移除向量 {} 失败: {}", id, e),
+            }
+        }
+    }
+    progress.mark_processed(CONVERSATION_COLLECTION);
+    save_progress(progress)?;
+    Ok(true)
+}
+
+/// 阶段 4：预先计算会话话题聚类
+async fn precompute_topics(ctx: &RunContext<'_>, progress: &mut ConsolidationProgress) -> Result<bool, String> {
+    const UNIT: &str = "topics";
+    if progress.is_processed(UNIT) {
+        return Ok(true);
+    }
+    if !ctx.still_idle() {
+        return Ok(false);
+    }
+
+    match topics::run_topic_analysis(ctx.app_handle, TopicRange::last_days(WINDOW_DAYS)).await {
+        Ok(analysis) => progress.report.topic_count = Some(analysis.topics.len()),
+        Err(e) => progress.mark_failed(UNIT, &e),
+    }
+    progress.mark_processed(UNIT);
+    save_progress(progress)?;
+    Ok(true)
+}
+
+/// 运行（或继续）一轮整理，返回当前进度
+async fn run_consolidation(app_handle: &AppHandle, force: bool) -> Result<ConsolidationProgress, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("空闲整理正在进行中".to_string());
+    }
+    let ctx = RunContext {
+        app_handle,
+        settings: SETTINGS.read().clone(),
+        force,
+    };
+    let result = run_phases(&ctx).await;
+    RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn run_phases(ctx: &RunContext<'_>) -> Result<ConsolidationProgress, String> {
+    let mut progress = match load_progress()? {
+        Some(progress) if !progress.is_finished() => {
+            info!("继续空闲整理 {}（{:?} 阶段）", progress.run_id, progress.phase);
+            progress
+        }
+        _ => ConsolidationProgress::new(uuid::Uuid::new_v4().to_string(), Utc::now().timestamp()),
+    };
+    let _ = ctx.app_handle.emit_all("sleep-learning-started", &progress.run_id);
+
+    while !progress.is_finished() {
+        let completed = match progress.phase {
+            ConsolidationPhase::Summaries => consolidate_summaries(ctx, &mut progress).await?,
+            ConsolidationPhase::Embeddings => consolidate_embeddings(ctx, &mut progress).await?,
+            ConsolidationPhase::Compaction => compact_vectors(ctx, &mut progress).await?,
+            ConsolidationPhase::TopicClusters => precompute_topics(ctx, &mut progress).await?,
+            ConsolidationPhase::Done => true,
+        };
+        if !completed {
+            progress.report.pauses += 1;
+            save_progress(&progress)?;
+            info!("系统不再空闲，空闲整理在 {:?} 阶段暂停", progress.phase);
+            let _ = ctx.app_handle.emit_all("sleep-learning-paused", &progress);
+            return Ok(progress);
+        }
+        progress.advance(Utc::now().timestamp());
+        save_progress(&progress)?;
+    }
+
+    info!(
+        "空闲整理完成: 摘要 {} 个会话，重新嵌入 {} 个集合，移除 {} 个向量",
+        progress.report.summarized_sessions.len(),
+        progress.report.reembedded_collections.len(),
+        progress.report.removed_vectors
+    );
+    let _ = ctx.app_handle.emit_all("sleep-learning-completed", &progress);
+    Ok(progress)
+}
+
+/// 启动空闲整理调度器
+pub fn start_sleep_learning_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("sleep_learning", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                let settings = SETTINGS.read().clone();
+                if !settings.allows(&SystemConditions::detect()) || crate::database::get_database().is_none() {
+                    continue;
+                }
+                let due = match load_progress() {
+                    Ok(Some(progress)) if !progress.is_finished() => true,
+                    Ok(progress) => {
+                        sleep_learning::is_due(progress.and_then(|p| p.finished_at), &settings, Utc::now().timestamp())
+                    }
+                    Err(e) => {
+                        warn!("读取空闲整理进度失败: {}", e);
+                        false
+                    }
+                };
+                if due {
+                    if let Err(e) = run_consolidation(&app_handle, false).await {
+                        warn!("空闲整理失败: {}", e);
+                    }
+                }
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取空闲整理的设置、系统状态与最近一轮的进度报告
+#[tauri::command]
+pub async fn get_sleep_learning_status() -> Result<SleepLearningStatus, String> {
+    let settings = SETTINGS.read().clone();
+    let conditions = SystemConditions::detect();
+    Ok(SleepLearningStatus {
+        eligible: settings.allows(&conditions),
+        settings,
+        conditions,
+        running: RUNNING.load(Ordering::SeqCst),
+        progress: load_progress()?,
+    })
+}
+
+/// 保存空闲整理设置
+#[tauri::command]
+pub async fn update_sleep_learning_settings(
+    settings: SleepLearningSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize sleep learning settings: {}", e))?;
+    fs::write(app_data_path("sleep_learning.json")?, json_data)
+        .map_err(|e| format!("Failed to write sleep learning settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 立即运行（或继续）整理，不检查空闲与电源状态
+#[tauri::command]
+pub async fn run_sleep_learning_now(app_handle: AppHandle) -> Result<ConsolidationProgress, String> {
+    run_consolidation(&app_handle, true).await
+}
+
+/// 获取会话摘要
+#[tauri::command]
+pub async fn get_session_summary(
+    conversation_id: String,
+) -> Result<Option<SessionSummary>, String> {
+    Ok(load_summaries()?.remove(&conversation_id))
+}
@@ -0,0 +1,258 @@
+//! # 对话气泡命令模块
+//!
+//! 各模块通过 [`say`] 让角色说话，而不是各自向窗口发送事件。每个角色窗口一个队列，
+//! 排队与打断规则见 [`crate::utils::speech_queue`]。队列变化推送给对应窗口：
+//!
+//! - `speech-bubble`：显示气泡（[`SpeechBubble`]），到 `ends_at` 时由后端结束
+//! - `speech-bubble-hidden`：气泡结束（到时、被打断或被关闭）
+//! - `speech-bubble-dropped`：排队的话未显示就被移除（过期、队列已满或被撤回）
+//!
+//! 用户点击关闭气泡时前端调用 `dismiss_utterance`，下一条随即显示。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+use crate::utils::speech_queue::{
+    ActiveUtterance, SpeechEvent, SpeechQueue, SpeechQueueSettings, Utterance, UtteranceKind,
+};
+use crate::utils::watchdog;
+
+/// 默认说话的窗口（桌宠主窗口）
+pub const DEFAULT_SPEAKER_WINDOW: &str = "main";
+/// 检查气泡是否到时的间隔
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+const TICK_STALL_AFTER: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref SETTINGS: RwLock<SpeechQueueSettings> = RwLock::new(SpeechQueueSettings::default());
+    /// 窗口标签 → 队列
+    static ref QUEUES: Mutex<HashMap<String, SpeechQueue>> = Mutex::new(HashMap::new());
+}
+
+/// 发给角色窗口的对话气泡
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechBubble {
+    pub id: String,
+    pub kind: UtteranceKind,
+    pub text: String,
+    pub source: String,
+    pub session_id: Option<String>,
+    pub message_id: Option<String>,
+    /// Unix 毫秒
+    pub started_at: i64,
+    pub ends_at: i64,
+}
+
+impl From<&ActiveUtterance> for SpeechBubble {
+    fn from(active: &ActiveUtterance) -> Self {
+        let utterance = &active.utterance;
+        Self {
+            id: utterance.id.clone(),
+            kind: utterance.kind,
+            text: utterance.text.clone(),
+            source: utterance.source.clone(),
+            session_id: utterance.session_id.clone(),
+            message_id: utterance.message_id.clone(),
+            started_at: active.started_at,
+            ends_at: active.ends_at,
+        }
+    }
+}
+
+/// 窗口的队列状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechQueueStatus {
+    pub current: Option<SpeechBubble>,
+    pub pending: Vec<Utterance>,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("speech_queue_settings.json"))
+}
+
+/// 加载对话气泡设置（启动时调用）
+pub fn initialize_speech_queue() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read speech queue settings: {}", e))?;
+    let settings: SpeechQueueSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse speech queue settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn emit_events(app_handle: &AppHandle, window_label: &str, events: Vec<SpeechEvent>) {
+    let Some(window) = app_handle.get_window(window_label) else {
+        if !events.is_empty() {
+            debug!("窗口 {} 不存在，对话气泡未显示", window_label);
+        }
+        return;
+    };
+    for event in events {
+        let _ = match event {
+            SpeechEvent::Show(active) => window.emit("speech-bubble", SpeechBubble::from(&active)),
+            SpeechEvent::Hide { id, reason } => {
+                window.emit("speech-bubble-hidden", serde_json::json!({ "id": id, "reason": reason }))
+            }
+            SpeechEvent::Dropped { id, reason } => {
+                window.emit("speech-bubble-dropped", serde_json::json!({ "id": id, "reason": reason }))
+            }
+        };
+    }
+}
+
+/// 让 `window_label` 窗口的角色说一句话，返回这句话的 ID
+pub fn say_in(app_handle: &AppHandle, window_label: &str, utterance: Utterance) -> String {
+    let id = utterance.id.clone();
+    let events = {
+        let settings = SETTINGS.read().clone();
+        let mut queues = QUEUES.lock();
+        queues
+            .entry(window_label.to_string())
+            .or_default()
+            .enqueue(utterance, &settings, Utc::now().timestamp_millis())
+    };
+    emit_events(app_handle, window_label, events);
+    id
+}
+
+/// 让桌宠主窗口的角色说一句话，返回这句话的 ID
+pub fn say(app_handle: &AppHandle, utterance: Utterance) -> String {
+    say_in(app_handle, DEFAULT_SPEAKER_WINDOW, utterance)
+}
+
+/// 关闭气泡或撤回排队中的话
+pub fn dismiss(app_handle: &AppHandle, window_label: &str, id: &str) -> bool {
+    let events = {
+        let settings = SETTINGS.read().clone();
+        let mut queues = QUEUES.lock();
+        match queues.get_mut(window_label) {
+            Some(queue) => queue.dismiss(id, &settings, Utc::now().timestamp_millis()),
+            None => Vec::new(),
+        }
+    };
+    let dismissed = !events.is_empty();
+    emit_events(app_handle, window_label, events);
+    dismissed
+}
+
+/// 窗口销毁时丢弃其队列
+pub fn handle_window_destroyed(window_label: &str) {
+    QUEUES.lock().remove(window_label);
+}
+
+fn tick(app_handle: &AppHandle) {
+    let settings = SETTINGS.read().clone();
+    let now = Utc::now().timestamp_millis();
+    let updates: Vec<(String, Vec<SpeechEvent>)> = {
+        let mut queues = QUEUES.lock();
+        let updates = queues
+            .iter_mut()
+            .map(|(label, queue)| (label.clone(), queue.tick(&settings, now)))
+            .filter(|(_, events)| !events.is_empty())
+            .collect();
+        queues.retain(|_, queue| !queue.is_idle());
+        updates
+    };
+    for (label, events) in updates {
+        emit_events(app_handle, &label, events);
+    }
+}
+
+/// 启动对话气泡计时任务
+pub fn start_speech_queue_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("speech_queue", TICK_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                tokio::time::sleep(TICK_INTERVAL).await;
+                tick(&app_handle);
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 让角色说一句话（前端与插件使用），返回这句话的 ID
+#[tauri::command]
+pub async fn enqueue_utterance(
+    app_handle: AppHandle,
+    kind: UtteranceKind,
+    text: String,
+    source: String,
+    window_label: Option<String>,
+    session_id: Option<String>,
+    duration_ms: Option<u64>,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("内容不能为空".to_string());
+    }
+    let mut utterance = Utterance::new(kind, text, source);
+    utterance.session_id = session_id;
+    utterance.duration_ms = duration_ms;
+    let window_label = window_label.unwrap_or_else(|| DEFAULT_SPEAKER_WINDOW.to_string());
+    Ok(say_in(&app_handle, &window_label, utterance))
+}
+
+/// 关闭气泡或撤回排队中的话
+#[tauri::command]
+pub async fn dismiss_utterance(app_handle: AppHandle, window_label: Option<String>, id: String) -> Result<bool, String> {
+    let window_label = window_label.unwrap_or_else(|| DEFAULT_SPEAKER_WINDOW.to_string());
+    Ok(dismiss(&app_handle, &window_label, &id))
+}
+
+/// 获取窗口当前显示与排队中的话
+#[tauri::command]
+pub async fn get_speech_queue(window_label: Option<String>) -> Result<SpeechQueueStatus, String> {
+    let window_label = window_label.unwrap_or_else(|| DEFAULT_SPEAKER_WINDOW.to_string());
+    let queues = QUEUES.lock();
+    Ok(match queues.get(&window_label) {
+        Some(queue) => SpeechQueueStatus {
+            current: queue.current().map(SpeechBubble::from),
+            pending: queue.pending(),
+        },
+        None => SpeechQueueStatus { current: None, pending: Vec::new() },
+    })
+}
+
+/// 获取对话气泡设置
+#[tauri::command]
+pub async fn get_speech_queue_settings() -> Result<SpeechQueueSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存对话气泡设置（对之后显示的气泡生效）
+#[tauri::command]
+pub async fn update_speech_queue_settings(settings: SpeechQueueSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize speech queue settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write speech queue settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
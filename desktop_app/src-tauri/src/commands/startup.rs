@@ -1,6 +1,9 @@
 use crate::utils::startup_manager::{StartupConfig, StartupPhase, StartupStats, STARTUP_MANAGER};
+use crate::utils::startup_profiler::{self, BootProfile, PhaseBreakdown, ProfileHistory};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// 更新启动配置
 #[tauri::command]
@@ -367,3 +370,80 @@ async fn update_startup_cache() -> Result<(), String> {
 
     Ok(())
 }
+
+// ================================
+// 启动耗时分析
+// ================================
+
+/// `get_startup_profile` 返回的启动耗时分析
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupProfileReport {
+    /// 本次启动（尚未完成时也包含已记录的阶段）
+    pub current: BootProfile,
+    /// 已保存的启动记录，按时间先后排列
+    pub history: Vec<BootProfile>,
+    /// 各阶段在历史记录中的耗时汇总
+    pub breakdown: Vec<PhaseBreakdown>,
+    /// 历史记录中完成启动的平均耗时（毫秒）
+    pub average_ready_ms: Option<f64>,
+}
+
+fn get_startup_profile_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("startup_profiles.json"))
+}
+
+fn load_startup_history() -> Result<ProfileHistory, String> {
+    let path = get_startup_profile_path()?;
+    if !path.exists() {
+        return Ok(ProfileHistory::default());
+    }
+    let json_data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read startup profiles: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse startup profiles: {}", e))
+}
+
+/// 把本次启动的记录写入历史
+pub fn save_startup_profile() -> Result<(), String> {
+    let mut history = load_startup_history()?;
+    history.record(startup_profiler::current());
+    let json_data = serde_json::to_string_pretty(&history)
+        .map_err(|e| format!("Failed to serialize startup profiles: {}", e))?;
+    std::fs::write(get_startup_profile_path()?, json_data)
+        .map_err(|e| format!("Failed to write startup profiles: {}", e))
+}
+
+/// 关键阶段都已记录时结束本次启动的计时并保存（`force` 用于初始化失败提前结束）
+pub fn complete_startup_profile(force: bool) {
+    if !startup_profiler::mark_ready(crate::utils::safe_mode::is_active(), force) {
+        return;
+    }
+    let profile = startup_profiler::current();
+    tracing::info!("启动完成，耗时 {} 毫秒", profile.ready_ms.unwrap_or_default());
+    if let Err(e) = save_startup_profile() {
+        tracing::warn!("保存启动耗时记录失败: {}", e);
+    }
+}
+
+/// 获取启动耗时分析（本次启动、最近的启动记录与各阶段汇总）
+#[tauri::command]
+pub async fn get_startup_profile() -> Result<StartupProfileReport, String> {
+    let current = startup_profiler::current();
+    let mut history = load_startup_history()?;
+    if current.ready_ms.is_some() {
+        history.record(current.clone());
+    }
+
+    let ready: Vec<u64> = history.boots.iter().filter_map(|boot| boot.ready_ms).collect();
+    let average_ready_ms = (!ready.is_empty()).then(|| ready.iter().sum::<u64>() as f64 / ready.len() as f64);
+    Ok(StartupProfileReport {
+        current,
+        breakdown: history.breakdown(),
+        history: history.boots,
+        average_ready_ms,
+    })
+}
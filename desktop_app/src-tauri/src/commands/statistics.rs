@@ -0,0 +1,254 @@
+//! # 桌宠统计命令模块
+//!
+//! 聊天、工作流等功能调用 [`track_interaction`] 记录互动，前端的点击、抚摸等互动通过
+//! `record_pet_interaction` 上报；后台每分钟记录一次应用会话以统计运行时长。
+//! `get_pet_statistics(range)` 把运行时长、互动、消息、工作流与活跃习惯汇总为一个结果，
+//! 相同时间范围的结果会缓存一分钟，有新互动时清空缓存。
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::database::achievement;
+use crate::database::pet_statistics::{
+    self, CountEntry, InteractionRecord, INTERACTION_MESSAGE, INTERACTION_WORKFLOW,
+};
+use crate::utils::clock;
+use crate::utils::watchdog;
+
+/// 应用会话心跳间隔
+const SESSION_HEARTBEAT: Duration = Duration::from_secs(60);
+/// 运行时长记录的心跳停滞阈值
+const SESSION_STALL_AFTER: Duration = Duration::from_secs(10 * 60);
+/// 统计结果缓存时间
+const CACHE_TTL: Duration = Duration::from_secs(60);
+/// 排行榜条目数
+const TOP_ENTRIES: i64 = 10;
+const MAX_KIND_LEN: usize = 32;
+
+lazy_static! {
+    /// 本次运行的会话 ID、启动时间（Unix 秒）与计时起点
+    static ref SESSION: (String, i64, Instant) =
+        (uuid::Uuid::new_v4().to_string(), chrono::Utc::now().timestamp(), Instant::now());
+    static ref CACHE: Mutex<HashMap<StatisticsRange, (Instant, PetStatistics)>> = Mutex::new(HashMap::new());
+}
+
+/// 统计时间范围（Unix 秒，闭区间，缺省表示不限）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StatisticsRange {
+    #[serde(default)]
+    pub from: Option<i64>,
+    #[serde(default)]
+    pub to: Option<i64>,
+}
+
+impl StatisticsRange {
+    fn bounds(&self) -> (i64, i64) {
+        (self.from.unwrap_or(0), self.to.unwrap_or(i64::MAX))
+    }
+}
+
+/// 运行时长统计（秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeStats {
+    /// 本次运行时长
+    pub current_session_secs: i64,
+    /// 时间范围内的累计运行时长
+    pub total_secs: i64,
+    pub sessions: usize,
+}
+
+/// 消息统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageStats {
+    pub total: i64,
+    pub by_character: Vec<CountEntry>,
+    pub by_model: Vec<CountEntry>,
+}
+
+/// 活跃习惯统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitStats {
+    /// 时间范围内聊过天的天数
+    pub active_days: usize,
+    pub current_streak: i64,
+    /// 时间范围内的最长连续天数
+    pub longest_streak: i64,
+    /// 按本地小时（0-23）统计的互动次数
+    pub hourly_interactions: Vec<i64>,
+    /// 互动最多的小时
+    pub peak_hour: Option<u32>,
+}
+
+/// 统计页数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PetStatistics {
+    pub range: StatisticsRange,
+    pub generated_at: i64,
+    pub uptime: UptimeStats,
+    pub total_interactions: i64,
+    pub interactions: Vec<CountEntry>,
+    pub messages: MessageStats,
+    pub favorite_character: Option<String>,
+    pub top_workflows: Vec<CountEntry>,
+    pub habits: HabitStats,
+}
+
+fn current_session_secs() -> i64 {
+    SESSION.2.elapsed().as_secs() as i64
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0).single().map(|t| t.date_naive())
+}
+
+fn today() -> NaiveDate {
+    let now = clock::global().local_now();
+    NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()).unwrap_or_default()
+}
+
+/// 在后台记录一次互动（不阻塞调用方，失败只记录日志）
+pub fn track_interaction(kind: &str, character_id: Option<String>, model: Option<String>, subject: Option<String>) {
+    let record = InteractionRecord {
+        kind: kind.to_string(),
+        character_id,
+        model,
+        subject,
+        occurred_at: chrono::Utc::now().timestamp(),
+    };
+    tauri::async_runtime::spawn(async move {
+        let Some(db) = crate::database::get_database() else {
+            return;
+        };
+        match db.pet_statistics_registry.record_interaction(&record).await {
+            Ok(()) => CACHE.lock().clear(),
+            Err(e) => warn!("记录互动 {} 失败: {}", record.kind, e),
+        }
+    });
+}
+
+/// 启动运行时长记录（应用启动时调用）
+pub fn start_uptime_tracking() {
+    watchdog::supervise("uptime_tracking", SESSION_STALL_AFTER, move |heartbeat| {
+        tauri::async_runtime::spawn(async move {
+            let (session_id, started_at, _) = &*SESSION;
+            while heartbeat.beat() {
+                if let Some(db) = crate::database::get_database() {
+                    if let Err(e) = db
+                        .pet_statistics_registry
+                        .touch_session(session_id, *started_at, chrono::Utc::now().timestamp())
+                        .await
+                    {
+                        warn!("记录应用运行时长失败: {}", e);
+                    }
+                }
+                tokio::time::sleep(SESSION_HEARTBEAT).await;
+            }
+        });
+    });
+}
+
+async fn collect(range: StatisticsRange) -> Result<PetStatistics, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let stats = &db.pet_statistics_registry;
+    let (from, to) = range.bounds();
+    let map_err = |e: Box<dyn std::error::Error + Send + Sync>| format!("读取统计数据失败: {}", e);
+
+    let sessions = stats.sessions(from, to).await.map_err(map_err)?;
+    let interactions = stats.counts_by_kind(from, to).await.map_err(map_err)?;
+    let by_character = stats
+        .counts_by_field(INTERACTION_MESSAGE, "character_id", from, to, TOP_ENTRIES)
+        .await
+        .map_err(map_err)?;
+    let by_model = stats
+        .counts_by_field(INTERACTION_MESSAGE, "model", from, to, TOP_ENTRIES)
+        .await
+        .map_err(map_err)?;
+    let top_workflows = stats
+        .counts_by_field(INTERACTION_WORKFLOW, "subject", from, to, TOP_ENTRIES)
+        .await
+        .map_err(map_err)?;
+    let utc_offset = i64::from(Local::now().offset().local_minus_utc());
+    let hourly = stats.hourly_counts(from, to, utc_offset).await.map_err(map_err)?;
+
+    let days = db
+        .achievement_registry
+        .recent_active_days()
+        .await
+        .map_err(|e| format!("读取活跃日期失败: {}", e))?;
+    let (first_day, last_day) = (local_date(from), local_date(to));
+    let days_in_range: Vec<NaiveDate> = days
+        .iter()
+        .copied()
+        .filter(|day| !matches!(first_day, Some(d) if *day < d) && !matches!(last_day, Some(d) if *day > d))
+        .collect();
+
+    let peak_hour = hourly
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .max_by_key(|(hour, count)| (**count, std::cmp::Reverse(*hour)))
+        .map(|(hour, _)| hour as u32);
+
+    Ok(PetStatistics {
+        range,
+        generated_at: chrono::Utc::now().timestamp(),
+        uptime: UptimeStats {
+            current_session_secs: current_session_secs(),
+            total_secs: pet_statistics::uptime_in_range(&sessions, from, to),
+            sessions: sessions.len(),
+        },
+        total_interactions: interactions.iter().map(|entry| entry.count).sum(),
+        messages: MessageStats {
+            total: interactions
+                .iter()
+                .find(|entry| entry.key == INTERACTION_MESSAGE)
+                .map_or(0, |entry| entry.count),
+            by_character: by_character.clone(),
+            by_model,
+        },
+        interactions,
+        favorite_character: by_character.first().map(|entry| entry.key.clone()),
+        top_workflows,
+        habits: HabitStats {
+            active_days: days_in_range.len(),
+            current_streak: achievement::current_streak(&days, today()),
+            longest_streak: pet_statistics::longest_streak(&days_in_range),
+            hourly_interactions: hourly.to_vec(),
+            peak_hour,
+        },
+    })
+}
+
+/// 获取统计页数据
+///
+/// `refresh` 为 true 时忽略缓存。
+#[tauri::command]
+pub async fn get_pet_statistics(range: Option<StatisticsRange>, refresh: Option<bool>) -> Result<PetStatistics, String> {
+    let range = range.unwrap_or_default();
+    if !refresh.unwrap_or(false) {
+        if let Some((cached_at, statistics)) = CACHE.lock().get(&range) {
+            if cached_at.elapsed() < CACHE_TTL {
+                return Ok(statistics.clone());
+            }
+        }
+    }
+    let statistics = collect(range).await?;
+    CACHE.lock().insert(range, (Instant::now(), statistics.clone()));
+    Ok(statistics)
+}
+
+/// 前端上报互动（如 `click`、`pet`、`drag`）
+#[tauri::command]
+pub async fn record_pet_interaction(kind: String, character_id: Option<String>) -> Result<(), String> {
+    let kind = kind.trim();
+    if kind.is_empty() || kind.len() > MAX_KIND_LEN {
+        return Err("互动类型无效".to_string());
+    }
+    track_interaction(kind, character_id, None, None);
+    Ok(())
+}
@@ -0,0 +1,278 @@
+//! # 存储空间命令模块
+//!
+//! 提供存储占用明细、磁盘空间检查与定向清理。大体积操作（模型下载、产品下载、
+//! 更新包下载、备份）在写盘前调用 [`ensure_space`]：空间偏低时发出通知，
+//! 低于严重阈值时拒绝执行。
+
+use parking_lot::Mutex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::notifications::{self, NewNotification};
+use crate::database::file::{cleanup_deleted_files, list_files, DummyConnection};
+use crate::state::tray_state::NotificationType;
+use crate::utils::disk_guard::{
+    self, CleanupAction, DiskGuardSettings, SpaceLevel, SpaceStatus, StorageBreakdown, StorageCategory,
+    StorageItem,
+};
+use crate::utils::get_app_log_dir;
+use crate::utils::i18n;
+
+/// 同一等级的通知最短间隔
+const NOTIFY_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+lazy_static::lazy_static! {
+    static ref LAST_NOTIFIED: Mutex<Option<(SpaceLevel, Instant)>> = Mutex::new(None);
+}
+
+fn get_disk_guard_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("disk_guard.json"))
+}
+
+fn app_data_subdir(name: &str) -> Option<PathBuf> {
+    crate::utils::config::get_app_data_dir().ok().map(|dir| dir.join(name))
+}
+
+fn asset_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|base| base.join("zishu-sensei").join("cache"))
+}
+
+/// 从磁盘加载磁盘空间守卫设置（启动时调用）
+pub fn initialize_disk_guard() -> Result<(), String> {
+    let config_path = get_disk_guard_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read disk guard settings: {}", e))?;
+    let settings: DiskGuardSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse disk guard settings: {}", e))?;
+
+    disk_guard::set_settings(settings)
+}
+
+/// 启动周期性磁盘空间检查（覆盖日志持续增长的场景）
+pub fn start_disk_space_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let minutes = disk_guard::current_settings().check_interval_minutes;
+            tokio::time::sleep(Duration::from_secs(minutes.max(1) * 60)).await;
+            if minutes == 0 {
+                continue;
+            }
+
+            if let Ok(log_dir) = get_app_log_dir() {
+                let status = disk_guard::check_space(&log_dir, 0);
+                if status.level != SpaceLevel::Ok {
+                    notify_low_space(&app_handle, &status, &i18n::t("operation-write-logs"));
+                }
+            }
+        }
+    });
+}
+
+/// 在执行大体积写盘操作前检查空间
+///
+/// 空间偏低时通知用户并继续；低于严重阈值时返回错误。
+pub fn ensure_space(
+    app_handle: &AppHandle,
+    path: &Path,
+    required_bytes: u64,
+    operation: &str,
+) -> Result<SpaceStatus, String> {
+    let status = disk_guard::check_space(path, required_bytes);
+    match status.level {
+        SpaceLevel::Ok => Ok(status),
+        SpaceLevel::Low => {
+            notify_low_space(app_handle, &status, operation);
+            Ok(status)
+        }
+        SpaceLevel::Critical => {
+            notify_low_space(app_handle, &status, operation);
+            let remaining_mb = (status.remaining_after().unwrap_or(0) / 1024 / 1024).to_string();
+            Err(i18n::t_args(
+                "error-disk-space-critical",
+                &[("operation", operation), ("remaining", remaining_mb.as_str())],
+            ))
+        }
+    }
+}
+
+fn notify_low_space(app_handle: &AppHandle, status: &SpaceStatus, operation: &str) {
+    {
+        let mut last = LAST_NOTIFIED.lock();
+        if let Some((level, at)) = *last {
+            if level >= status.level && at.elapsed() < NOTIFY_COOLDOWN {
+                return;
+            }
+        }
+        *last = Some((status.level, Instant::now()));
+    }
+
+    let remaining_mb = status.remaining_after().unwrap_or(0) / 1024 / 1024;
+    warn!("磁盘空间偏低（{}）: {:?} 剩余 {} MB", operation, status.mount_point, remaining_mb);
+
+    let _ = app_handle.emit_all("disk-space-warning", serde_json::json!({
+        "operation": operation,
+        "status": status,
+    }));
+
+    let (title, notification_type) = match status.level {
+        SpaceLevel::Critical => (i18n::t("notify-disk-space-critical"), NotificationType::Error),
+        _ => (i18n::t("notify-disk-space-low"), NotificationType::Warning),
+    };
+    notifications::post(
+        app_handle,
+        NewNotification::new(
+            title,
+            i18n::t_args(
+                "notify-disk-space-body",
+                &[("operation", operation), ("remaining", remaining_mb.to_string().as_str())],
+            ),
+            notification_type,
+        )
+        .source("storage")
+        .action("open_storage_settings", &i18n::t("notify-action-free-space"), "open_settings", serde_json::json!({ "section": "storage" })),
+    );
+}
+
+fn trashed_files() -> Vec<crate::database::file::FileInfo> {
+    list_files(&DummyConnection, None, None, None, None)
+        .map(|files| files.into_iter().filter(|f| f.is_deleted).collect())
+        .unwrap_or_default()
+}
+
+/// 获取存储占用明细与清理建议
+#[tauri::command]
+pub async fn get_storage_breakdown() -> Result<StorageBreakdown, String> {
+    let settings = disk_guard::current_settings();
+    let log_dir = get_app_log_dir()?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    let breakdown = tokio::task::spawn_blocking(move || {
+        let mut items = vec![disk_guard::measure(StorageCategory::Logs, &log_dir)];
+        if let Some(cache) = asset_cache_dir() {
+            items.push(disk_guard::measure(StorageCategory::AssetCache, &cache));
+        }
+        for (category, name) in [
+            (StorageCategory::Downloads, "downloads"),
+            (StorageCategory::Backups, "backups"),
+            (StorageCategory::Models, "local_llm_models"),
+            (StorageCategory::Uploads, "uploads"),
+        ] {
+            items.push(disk_guard::measure(category, &app_data_dir.join(name)));
+        }
+
+        let trashed = trashed_files();
+        items.push(StorageItem {
+            category: StorageCategory::TrashedFiles,
+            path: None,
+            bytes: trashed.iter().map(|f| f.file_size.max(0) as u64).sum(),
+            file_count: trashed.len(),
+        });
+
+        let old_logs = disk_guard::old_log_files(&log_dir, settings.old_log_days);
+        let suggestions = disk_guard::suggestions_for(&items, &old_logs, &settings);
+        StorageBreakdown {
+            total_bytes: items.iter().map(|i| i.bytes).sum(),
+            disk: disk_guard::check_space(&app_data_dir, 0),
+            items,
+            suggestions,
+        }
+    })
+    .await
+    .map_err(|e| format!("统计存储占用失败: {}", e))?;
+
+    Ok(breakdown)
+}
+
+/// 检查指定路径所在磁盘写入 `required_bytes` 后的空间等级
+#[tauri::command]
+pub async fn check_disk_space(path: String, required_bytes: Option<u64>) -> Result<SpaceStatus, String> {
+    Ok(disk_guard::check_space(Path::new(&path), required_bytes.unwrap_or(0)))
+}
+
+/// 执行清理操作，返回释放的字节数
+#[tauri::command]
+pub async fn run_storage_cleanup(action: CleanupAction, app_handle: AppHandle) -> Result<u64, String> {
+    info!("执行存储清理: {:?}", action);
+    let log_dir = get_app_log_dir()?;
+    let downloads_dir = app_data_subdir("downloads");
+
+    let freed = tokio::task::spawn_blocking(move || -> Result<u64, String> {
+        let mut freed = 0u64;
+        match action {
+            CleanupAction::OldLogs { older_than_days } => {
+                for (path, size) in disk_guard::old_log_files(&log_dir, older_than_days.max(1)) {
+                    if fs::remove_file(&path).is_ok() {
+                        freed += size;
+                    }
+                }
+            }
+            CleanupAction::AssetCache => {
+                if let Some(cache) = asset_cache_dir().filter(|p| p.exists()) {
+                    freed = disk_guard::measure(StorageCategory::AssetCache, &cache).bytes;
+                    fs::remove_dir_all(&cache).map_err(|e| format!("清空资源缓存失败: {}", e))?;
+                }
+            }
+            CleanupAction::Downloads => {
+                if let Some(dir) = downloads_dir.filter(|p| p.exists()) {
+                    freed = disk_guard::measure(StorageCategory::Downloads, &dir).bytes;
+                    fs::remove_dir_all(&dir).map_err(|e| format!("删除下载文件失败: {}", e))?;
+                    fs::create_dir_all(&dir).map_err(|e| format!("重建下载目录失败: {}", e))?;
+                }
+            }
+            CleanupAction::TrashedFiles => {
+                let files = cleanup_deleted_files(&DummyConnection, 0)
+                    .map_err(|e| format!("清理回收站失败: {}", e))?;
+                for file in files {
+                    if fs::remove_file(&file.file_path).is_ok() {
+                        freed += file.file_size.max(0) as u64;
+                    }
+                    if let Some(thumb) = file.thumbnail_path {
+                        let _ = fs::remove_file(thumb);
+                    }
+                }
+            }
+        }
+        Ok(freed)
+    })
+    .await
+    .map_err(|e| format!("存储清理任务异常退出: {}", e))??;
+
+    info!("存储清理完成，释放 {} 字节", freed);
+    let _ = app_handle.emit_all("storage-cleaned", serde_json::json!({ "freed_bytes": freed }));
+    Ok(freed)
+}
+
+/// 获取磁盘空间守卫设置
+#[tauri::command]
+pub async fn get_disk_guard_settings() -> Result<DiskGuardSettings, String> {
+    Ok(disk_guard::current_settings())
+}
+
+/// 保存磁盘空间守卫设置
+#[tauri::command]
+pub async fn update_disk_guard_settings(
+    settings: DiskGuardSettings,
+) -> Result<(), String> {
+    disk_guard::set_settings(settings.clone())?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize disk guard settings: {}", e))?;
+    fs::write(get_disk_guard_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write disk guard settings: {}", e))?;
+
+    *LAST_NOTIFIED.lock() = None;
+    info!("磁盘空间守卫设置已更新");
+    Ok(())
+}
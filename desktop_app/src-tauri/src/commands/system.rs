@@ -239,6 +239,7 @@ pub async fn restart_app(
     info!("重启应用");
     
     // Use tauri's restart API
+    crate::utils::safe_mode::mark_clean_exit();
     app_handle.restart();
     
     Ok(CommandResponse::success_with_message(
@@ -254,6 +255,8 @@ pub async fn quit_app(
 ) -> Result<CommandResponse<bool>, String> {
     info!("退出应用");
     
+    crate::utils::safe_mode::mark_clean_exit();
+    
     app_handle.exit(0);
     
     Ok(CommandResponse::success_with_message(
@@ -383,6 +386,76 @@ pub async fn get_app_log_path() -> Result<CommandResponse<String>, String> {
     }
 }
 
+/// Move the application data directory to `path`
+///
+/// 立即校验目标路径与剩余空间，迁移在下次启动、日志与数据库打开之前执行；
+/// 失败时自动回滚，旧目录保持不变。PostgreSQL 等服务中的数据不会被迁移。
+#[tauri::command]
+pub async fn set_data_directory(
+    path: String,
+    remove_old: Option<bool>,
+) -> Result<CommandResponse<data_migration::PendingMigration>, String> {
+    info!("计划迁移应用数据目录到: {}", path);
+    schedule_data_migration(PathBuf::from(path), remove_old.unwrap_or(false)).await
+}
+
+/// Move the application data directory back to the platform default location
+#[tauri::command]
+pub async fn reset_data_directory(
+    remove_old: Option<bool>,
+) -> Result<CommandResponse<data_migration::PendingMigration>, String> {
+    let target = default_app_data_dir()?;
+    info!("计划迁移应用数据目录回默认位置: {:?}", target);
+    schedule_data_migration(target, remove_old.unwrap_or(false)).await
+}
+
+async fn schedule_data_migration(
+    target: PathBuf,
+    remove_old: bool,
+) -> Result<CommandResponse<data_migration::PendingMigration>, String> {
+    let result = tokio::task::spawn_blocking(move || data_migration::schedule_migration(&target, remove_old))
+        .await
+        .map_err(|e| format!("数据迁移任务异常退出: {}", e))?;
+
+    match result {
+        Ok(pending) => Ok(CommandResponse::success_with_message(
+            pending,
+            "数据目录将在重启应用后迁移".to_string(),
+        )),
+        Err(e) => {
+            error!("数据目录迁移校验失败: {}", e);
+            Ok(CommandResponse::error(format!("数据目录迁移校验失败: {}", e)))
+        }
+    }
+}
+
+/// Cancel a data directory migration that has not run yet
+#[tauri::command]
+pub async fn cancel_data_directory_migration() -> Result<CommandResponse<bool>, String> {
+    match data_migration::cancel_pending_migration() {
+        Ok(cancelled) => Ok(CommandResponse::success(cancelled)),
+        Err(e) => Ok(CommandResponse::error(e)),
+    }
+}
+
+/// 数据目录迁移状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataMigrationStatus {
+    /// 等待下次启动执行的迁移
+    pub pending: Option<data_migration::PendingMigration>,
+    /// 本次启动时执行的迁移
+    pub last_startup: data_migration::StartupMigrationStatus,
+}
+
+/// Get the pending migration and the result of the migration run at startup
+#[tauri::command]
+pub async fn get_data_migration_status() -> Result<CommandResponse<DataMigrationStatus>, String> {
+    Ok(CommandResponse::success(DataMigrationStatus {
+        pending: data_migration::pending_migration(),
+        last_startup: data_migration::startup_status(),
+    }))
+}
+
 /// Set auto-start on system boot
 #[tauri::command]
 pub async fn set_auto_start(
@@ -529,10 +602,11 @@ pub async fn copy_to_clipboard(
     
     use tauri::ClipboardManager;
     
-    if let Err(e) = app_handle.clipboard_manager().write_text(text) {
+    if let Err(e) = app_handle.clipboard_manager().write_text(text.clone()) {
         error!("复制到剪贴板失败: {}", e);
         return Ok(CommandResponse::error(format!("复制失败: {}", e)));
     }
+    crate::commands::clipboard::record_text(&app_handle, &text);
     
     Ok(CommandResponse::success_with_message(
         true,
@@ -716,6 +790,11 @@ pub async fn add_recent_conversation(
 ) -> Result<CommandResponse<bool>, String> {
     info!("添加最近对话: {}", conversation_id);
     
+    // 无痕会话不出现在最近对话中
+    if crate::commands::incognito::is_incognito(Some(&conversation_id)) {
+        return Ok(CommandResponse::success_with_message(false, "无痕会话不加入最近对话".to_string()));
+    }
+    
     use crate::state::tray_state::RecentConversation;
     use chrono::Utc;
     
@@ -861,7 +940,37 @@ pub struct CheckLogRotationRequest {
     pub retention_days: u32,
 }
 
+/// Anonymized upload payload, returned by `preview_anonymized_logs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogUploadPreview {
+    /// Exact body that `upload_logs` would send
+    pub payload: serde_json::Value,
+    /// Number of logs in the payload
+    pub log_count: usize,
+    /// What was scrubbed
+    pub redactions: crate::utils::log_anonymizer::RedactionStats,
+}
+
+/// Anonymize logs and build the upload body
+fn build_upload_payload(logs: &[serde_json::Value]) -> LogUploadPreview {
+    let (logs, redactions) = crate::utils::log_anonymizer::anonymize_logs(logs);
+    let log_count = logs.len();
+    let payload = serde_json::json!({
+        "logs": logs,
+        "timestamp": chrono::Utc::now().timestamp(),
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+    });
+    LogUploadPreview {
+        payload,
+        log_count,
+        redactions,
+    }
+}
+
 /// Upload logs to backend
+///
+/// Logs are anonymized before leaving the machine; see `preview_anonymized_logs`.
 #[tauri::command]
 pub async fn upload_logs(
     request: UploadLogsRequest,
@@ -872,8 +981,13 @@ pub async fn upload_logs(
     let log_upload_url = std::env::var("LOG_UPLOAD_URL")
         .unwrap_or_else(|_| "https://api.zishu-sensei.com/logs/upload".to_string());
     
+    let upload = build_upload_payload(&request.logs);
+    if upload.redactions.total() > 0 {
+        info!("日志已匿名化: {:?}", upload.redactions);
+    }
+    
     // 尝试上传到后端 API
-    match upload_logs_to_backend(&log_upload_url, &request.logs).await {
+    match upload_logs_to_backend(&log_upload_url, &upload.payload).await {
         Ok(response) => {
             info!("日志上传成功: {:?}", response);
             Ok(CommandResponse::success_with_message(
@@ -898,28 +1012,30 @@ pub async fn upload_logs(
     }
 }
 
+/// Preview exactly what `upload_logs` would send for these logs
+#[tauri::command]
+pub async fn preview_anonymized_logs(
+    request: UploadLogsRequest,
+) -> Result<CommandResponse<LogUploadPreview>, String> {
+    let preview = build_upload_payload(&request.logs);
+    info!("预览匿名化日志: {} 条，处理 {} 处敏感信息", preview.log_count, preview.redactions.total());
+    Ok(CommandResponse::success(preview))
+}
+
 /// Upload logs to backend API
 async fn upload_logs_to_backend(
     upload_url: &str,
-    logs: &[serde_json::Value],
+    upload_data: &serde_json::Value,
 ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
     use reqwest;
     
-    let client = reqwest::Client::builder()
+    let client = crate::http::proxy::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()?;
     
-    // 准备上传数据
-    let upload_data = serde_json::json!({
-        "logs": logs,
-        "timestamp": chrono::Utc::now().timestamp(),
-        "app_version": env!("CARGO_PKG_VERSION"),
-        "os": std::env::consts::OS,
-    });
-    
     let response = client
         .post(upload_url)
-        .json(&upload_data)
+        .json(upload_data)
         .header("Content-Type", "application/json")
         .header("User-Agent", format!("Zishu-Sensei/{}", env!("CARGO_PKG_VERSION")))
         .send()
@@ -1156,6 +1272,44 @@ async fn cleanup_old_log_files(
     })
 }
 
+// ================================
+// 调试时钟命令
+// ================================
+
+/// 获取全局时钟状态（当前时间与偏移量）
+#[tauri::command]
+pub async fn debug_get_clock_status() -> Result<crate::utils::clock::ClockStatus, String> {
+    Ok(crate::utils::clock::status())
+}
+
+/// 推进全局时钟（仅开发构建可用），用于验证基于时间的功能
+#[tauri::command]
+pub async fn debug_fast_forward_time(
+    seconds: i64,
+    app_handle: AppHandle,
+) -> Result<crate::utils::clock::ClockStatus, String> {
+    if !cfg!(debug_assertions) {
+        return Err("时间快进仅在开发构建中可用".to_string());
+    }
+    if seconds < 0 {
+        return Err("不支持时间倒退，请使用 debug_reset_time".to_string());
+    }
+
+    let status = crate::utils::clock::fast_forward(chrono::Duration::seconds(seconds));
+    warn!("全局时钟已快进 {} 秒，当前偏移 {} 秒", seconds, status.offset_seconds);
+    let _ = app_handle.emit_all("clock-changed", &status);
+    Ok(status)
+}
+
+/// 重置全局时钟偏移
+#[tauri::command]
+pub async fn debug_reset_time(app_handle: AppHandle) -> Result<crate::utils::clock::ClockStatus, String> {
+    let status = crate::utils::clock::reset();
+    info!("全局时钟偏移已重置");
+    let _ = app_handle.emit_all("clock-changed", &status);
+    Ok(status)
+}
+
 // ================================
 // Command Metadata
 // ================================
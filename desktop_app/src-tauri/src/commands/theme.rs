@@ -6,17 +6,25 @@
  * - 主题安装和卸载
  * - 主题收藏管理
  * - 主题导入和导出
+ * - 主题市场主题包的下载、签名校验、安装与卸载（包格式见 utils::theme_package）
+ * - 临时预览主题（不写入配置）
  * 
  * 注意：评分、评论等社区功能通过社区平台 API 处理
  */
 
+use crate::commands::market::{self, MarketProductType};
 use crate::database::theme::{Theme, ThemeDatabase, ThemeStatistics};
+use crate::state::AppState;
+use crate::utils::safe_mode::{self, SkippedKind};
+use crate::utils::theme_contrast::validate_theme_contrast;
+use crate::utils::theme_package::{self, ThemePackage};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
 use chrono::Utc;
 use std::fs;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /**
  * 主题搜索选项
@@ -165,6 +173,11 @@ pub async fn install_theme(
         return Err("Theme already installed".to_string());
     }
     
+    // 对比度检查（仅警告，不阻止安装）
+    for warning in validate_theme_contrast(&theme.variables).warnings() {
+        tracing::warn!("主题 {} 对比度不足: {}", theme.id, warning);
+    }
+    
     // 标记为已安装
     db.mark_installed(&options.theme_id, true)
         .map_err(|e| format!("Failed to mark theme as installed: {}", e))?;
@@ -300,7 +313,9 @@ pub async fn import_theme(
     
     // 验证主题（如果需要）
     if options.validate.unwrap_or(true) {
-        // TODO: 实现主题验证逻辑
+        for warning in validate_theme_contrast(&theme.variables).warnings() {
+            tracing::warn!("主题 {} 对比度不足: {}", theme.id, warning);
+        }
     }
     
     let db = db.lock().map_err(|e| format!("Failed to lock database: {}", e))?;
@@ -341,10 +356,17 @@ pub async fn validate_theme(source: String) -> Result<serde_json::Value, String>
     // 尝试解析主题数据
     match serde_json::from_str::<Theme>(&theme_json) {
         Ok(theme) => {
+            let contrast = validate_theme_contrast(&theme.variables);
+            let warnings: Vec<serde_json::Value> = contrast
+                .warnings()
+                .into_iter()
+                .map(|message| serde_json::json!({ "field": "variables", "message": message }))
+                .collect();
             Ok(serde_json::json!({
                 "valid": true,
                 "errors": [],
-                "warnings": [],
+                "warnings": warnings,
+                "contrast": contrast,
                 "theme": theme,
             }))
         }
@@ -435,6 +457,224 @@ pub async fn apply_theme(
     Ok(())
 }
 
+/**
+ * 主题预览默认时长（秒）
+ */
+const DEFAULT_PREVIEW_SECS: u64 = 60;
+const MAX_PREVIEW_SECS: u64 = 10 * 60;
+
+/**
+ * 当前预览的代数，结束或替换预览时递增，使过期的自动结束任务失效
+ */
+static PREVIEW_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_PREVIEW: parking_lot::Mutex<Option<ThemePreview>> = parking_lot::Mutex::new(None);
+}
+
+/**
+ * 主题预览（前端临时应用，不写入配置）
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemePreview {
+    pub theme_id: String,
+    pub variables: serde_json::Value,
+    pub custom_css: Option<String>,
+    /// 主题包资源目录（内置主题为空）
+    pub asset_dir: Option<String>,
+    pub expires_at: i64,
+}
+
+/**
+ * 主题包安装目录
+ */
+fn themes_dir() -> Result<PathBuf, String> {
+    crate::utils::config::get_app_data_dir().map(|dir| dir.join("themes"))
+}
+
+/**
+ * 把主题包文件写入临时目录后替换安装目录，失败时保留旧版本
+ */
+fn extract_package(package: &ThemePackage, install_dir: &Path) -> Result<(), String> {
+    let staging = install_dir.with_extension("installing");
+    let _ = fs::remove_dir_all(&staging);
+    for (path, content) in &package.files {
+        let target = staging.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create theme directory: {}", e))?;
+        }
+        fs::write(&target, content).map_err(|e| format!("Failed to write theme file: {}", e))?;
+    }
+    if install_dir.exists() {
+        fs::remove_dir_all(install_dir).map_err(|e| format!("Failed to remove old theme files: {}", e))?;
+    }
+    fs::rename(&staging, install_dir).map_err(|e| format!("Failed to install theme files: {}", e))
+}
+
+fn end_preview(app_handle: &AppHandle) -> bool {
+    PREVIEW_GENERATION.fetch_add(1, Ordering::SeqCst);
+    let ended = ACTIVE_PREVIEW.lock().take();
+    if let Some(preview) = &ended {
+        let _ = app_handle.emit_all("theme-preview-ended", serde_json::json!({
+            "theme_id": preview.theme_id,
+        }));
+    }
+    ended.is_some()
+}
+
+/**
+ * 从主题市场下载并安装主题包；主题包必须带有受信任发布者的有效签名
+ */
+#[tauri::command]
+pub async fn install_market_theme(
+    product_id: String,
+    version: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Theme, String> {
+    safe_mode::ensure_allowed(SkippedKind::Theme, &product_id)?;
+    let product = market::get_product_details(&product_id).await?;
+    if product.product_type != MarketProductType::Theme {
+        return Err(format!("Market product is not a theme: {}", product_id));
+    }
+
+    let download_path = PathBuf::from(market::download_product(&product_id, version.as_deref(), &app_handle).await?);
+    let bytes = fs::read(&download_path).map_err(|e| format!("Failed to read theme package: {}", e));
+    let _ = fs::remove_file(&download_path);
+    let package = ThemePackage::read(&bytes?)?;
+    package.verify(&theme_package::trusted_keys())?;
+
+    let manifest = &package.manifest;
+    for warning in validate_theme_contrast(&manifest.variables).warnings() {
+        tracing::warn!("主题 {} 对比度不足: {}", manifest.id, warning);
+    }
+
+    let install_dir = themes_dir()?.join(&manifest.id);
+    extract_package(&package, &install_dir)?;
+
+    let now = Utc::now();
+    let theme = Theme {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        description: manifest.description.clone(),
+        author: manifest.author.clone().or_else(|| Some(product.author.name.clone())),
+        version: manifest.version.clone(),
+        category: manifest.category.clone(),
+        variables: manifest.variables.clone(),
+        custom_css: Some(package.stylesheet()?),
+        preview_image: manifest
+            .preview_image
+            .as_ref()
+            .map(|image| install_dir.join(image).to_string_lossy().to_string()),
+        is_dark: manifest.is_dark,
+        is_default: false,
+        installed: true,
+        favorited: false,
+        download_count: product.download_count as i64,
+        rating: product.rating,
+        tags: manifest.tags.clone(),
+        created_at: now,
+        updated_at: now,
+    };
+    let db = crate::database::get_database().ok_or("Database not initialized")?;
+    db.theme_registry
+        .upsert_theme_async(&theme)
+        .await
+        .map_err(|e| format!("Failed to save theme: {}", e))?;
+    db.theme_registry
+        .mark_installed_async(&theme.id, true)
+        .await
+        .map_err(|e| format!("Failed to mark theme as installed: {}", e))?;
+
+    tracing::info!("已安装市场主题 {} {}", theme.id, theme.version);
+    let _ = app_handle.emit_all("theme-installed", &theme);
+    Ok(theme)
+}
+
+/**
+ * 卸载市场安装的主题包（删除文件与主题记录）；当前使用中的主题不能卸载
+ */
+#[tauri::command]
+pub async fn uninstall_market_theme(
+    theme_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if state.config.lock().theme.current_theme == theme_id {
+        return Err("Cannot uninstall the theme currently in use".to_string());
+    }
+    let valid_id = theme_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    let install_dir = themes_dir()?.join(&theme_id);
+    if !valid_id || !install_dir.is_dir() {
+        return Err(format!("Theme package not installed: {}", theme_id));
+    }
+
+    if ACTIVE_PREVIEW.lock().as_ref().is_some_and(|preview| preview.theme_id == theme_id) {
+        end_preview(&app_handle);
+    }
+    fs::remove_dir_all(&install_dir).map_err(|e| format!("Failed to remove theme files: {}", e))?;
+    let db = crate::database::get_database().ok_or("Database not initialized")?;
+    db.theme_registry
+        .delete_theme_async(&theme_id)
+        .await
+        .map_err(|e| format!("Failed to delete theme: {}", e))?;
+
+    tracing::info!("已卸载市场主题 {}", theme_id);
+    let _ = app_handle.emit_all("theme-uninstalled", serde_json::json!({ "theme_id": theme_id }));
+    Ok(())
+}
+
+/**
+ * 临时预览主题：通知前端应用主题，到期或调用 end_theme_preview 后恢复，不修改配置
+ */
+#[tauri::command]
+pub async fn preview_theme(
+    theme_id: String,
+    duration_secs: Option<u64>,
+    app_handle: AppHandle,
+) -> Result<ThemePreview, String> {
+    safe_mode::ensure_allowed(SkippedKind::Theme, &theme_id)?;
+    let duration_secs = duration_secs.unwrap_or(DEFAULT_PREVIEW_SECS);
+    if duration_secs == 0 || duration_secs > MAX_PREVIEW_SECS {
+        return Err(format!("Preview duration must be between 1 and {} seconds", MAX_PREVIEW_SECS));
+    }
+
+    let db = crate::database::get_database().ok_or("Database not initialized")?;
+    let theme = db
+        .theme_registry
+        .get_theme_async(&theme_id)
+        .await
+        .map_err(|e| format!("Failed to get theme: {}", e))?
+        .ok_or_else(|| format!("Theme not found: {}", theme_id))?;
+    let asset_dir = themes_dir()?.join(&theme.id);
+
+    let preview = ThemePreview {
+        theme_id: theme.id,
+        variables: theme.variables,
+        custom_css: theme.custom_css,
+        asset_dir: asset_dir.is_dir().then(|| asset_dir.to_string_lossy().to_string()),
+        expires_at: Utc::now().timestamp() + duration_secs as i64,
+    };
+    let generation = PREVIEW_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *ACTIVE_PREVIEW.lock() = Some(preview.clone());
+    let _ = app_handle.emit_all("theme-preview", &preview);
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(duration_secs)).await;
+        if PREVIEW_GENERATION.load(Ordering::SeqCst) == generation {
+            end_preview(&app_handle);
+        }
+    });
+    Ok(preview)
+}
+
+/**
+ * 结束主题预览，返回是否有正在进行的预览
+ */
+#[tauri::command]
+pub async fn end_theme_preview(app_handle: AppHandle) -> Result<bool, String> {
+    Ok(end_preview(&app_handle))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
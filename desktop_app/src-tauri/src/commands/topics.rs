@@ -0,0 +1,376 @@
+//! # 会话话题命令模块
+//!
+//! 离线分析任务：把一段时间内的会话向量化后聚类，并用 LLM 为每个话题命名，
+//! 结果保存在 `conversation_topics.json`，每天在后台刷新一次。
+//! `get_conversation_topics(range)` 返回落在指定时间范围内的话题及按天统计的时间线，
+//! 供“这个月聊了什么”视图使用。
+
+use chrono::{Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::database::conversation::{Conversation, Message, MessageRole as StoredRole};
+use crate::database::embedding;
+use crate::utils::bridge::{ChatMessage, ChatRequest, MessageRole, PythonApiBridge};
+use crate::utils::resource_budget::{self, WorkKind};
+use crate::utils::topic_clustering;
+use crate::utils::watchdog;
+
+/// 话题分析使用的嵌入集合名（可在嵌入设置中单独指定提供者）
+const TOPIC_COLLECTION: &str = "conversation_topics";
+/// 后台分析覆盖的天数
+const ANALYSIS_WINDOW_DAYS: i64 = 90;
+/// 后台分析的刷新周期
+const ANALYSIS_INTERVAL_SECS: i64 = 24 * 60 * 60;
+/// 检查是否需要刷新的间隔
+const SCHEDULER_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// 话题分析调度器心跳停滞阈值（单次分析可能较慢）
+const SCHEDULER_STALL_AFTER: Duration = Duration::from_secs(3 * 60 * 60);
+const MAX_TOPICS: usize = 12;
+/// 每个会话参与分析的用户消息数与字符数上限
+const MESSAGES_PER_CONVERSATION: usize = 20;
+const MAX_TEXT_CHARS: usize = 2000;
+const EMBED_BATCH_SIZE: usize = 16;
+const KEYWORDS_PER_TOPIC: usize = 5;
+const MAX_LABEL_CHARS: usize = 20;
+
+const LABEL_INSTRUCTION: &str = "你会看到同一话题下若干会话的标题和关键词。\
+请用不超过 8 个字（英文不超过 4 个词）概括这个话题，只输出话题名称，不要标点和解释。";
+
+static ANALYSIS_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 时间范围（Unix 秒，闭区间）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TopicRange {
+    pub from: i64,
+    pub to: i64,
+}
+
+impl TopicRange {
+    pub(crate) fn last_days(days: i64) -> Self {
+        let to = Utc::now().timestamp();
+        Self { from: to - days * 24 * 60 * 60, to }
+    }
+
+    fn contains(&self, conversation: &Conversation) -> bool {
+        conversation.created_at <= self.to && conversation.updated_at >= self.from
+    }
+}
+
+/// 话题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTopic {
+    pub id: String,
+    pub label: String,
+    pub keywords: Vec<String>,
+    pub conversations: Vec<Conversation>,
+}
+
+/// 一次分析的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicAnalysis {
+    pub generated_at: i64,
+    pub range: TopicRange,
+    /// 生成向量的嵌入提供者指纹
+    pub provider: String,
+    pub topics: Vec<ConversationTopic>,
+}
+
+/// 时间线条目：某天某话题下活跃的会话数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicTimelineEntry {
+    /// 本地日期（YYYY-MM-DD）
+    pub date: String,
+    pub topic_id: String,
+    pub conversation_count: usize,
+}
+
+/// `get_conversation_topics` 的返回数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicTimeline {
+    pub generated_at: Option<i64>,
+    /// 分析结果是否完整覆盖请求的范围
+    pub covered: bool,
+    pub topics: Vec<ConversationTopic>,
+    pub timeline: Vec<TopicTimelineEntry>,
+}
+
+fn analysis_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("conversation_topics.json"))
+}
+
+fn load_analysis() -> Result<Option<TopicAnalysis>, String> {
+    let path = analysis_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json_data = fs::read_to_string(&path).map_err(|e| format!("Failed to read conversation topics: {}", e))?;
+    serde_json::from_str(&json_data)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse conversation topics: {}", e))
+}
+
+fn save_analysis(analysis: &TopicAnalysis) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(analysis)
+        .map_err(|e| format!("Failed to serialize conversation topics: {}", e))?;
+    fs::write(analysis_path()?, json_data).map_err(|e| format!("Failed to write conversation topics: {}", e))
+}
+
+/// 会话用于聚类的文本：标题 + 前若干条未删除的用户消息
+fn conversation_text(conversation: &Conversation, messages: &[Message]) -> String {
+    let mut text = conversation.title.clone();
+    for message in messages
+        .iter()
+        .filter(|m| !m.is_deleted() && matches!(m.role, StoredRole::User))
+        .take(MESSAGES_PER_CONVERSATION)
+    {
+        text.push('\n');
+        text.push_str(&message.content);
+    }
+    text.chars().take(MAX_TEXT_CHARS).collect()
+}
+
+/// 请 LLM 为话题命名
+async fn label_topic(conversations: &[Conversation], keywords: &[String]) -> Result<String, String> {
+    resource_budget::defer(WorkKind::Llm, "topic_labeling").await;
+    let bridge = PythonApiBridge::default().map_err(|e| format!("创建 API 客户端失败: {}", e))?;
+    let titles: Vec<String> = conversations.iter().take(10).map(|c| format!("- {}", c.title)).collect();
+    let request = ChatRequest {
+        messages: vec![
+            ChatMessage {
+                role: MessageRole::System,
+                content: LABEL_INSTRUCTION.to_string(),
+            },
+            ChatMessage {
+                role: MessageRole::User,
+                content: format!("会话标题：\n{}\n关键词：{}", titles.join("\n"), keywords.join("、")),
+            },
+        ],
+        model: None,
+        adapter: None,
+        character_id: None,
+        max_tokens: Some(32),
+        temperature: Some(0.2),
+        top_p: None,
+        stream: Some(false),
+        session_id: None,
+        images: Vec::new(),
+    };
+
+    let response = bridge
+        .send_chat_message(request)
+        .await
+        .map_err(|e| format!("话题命名请求失败: {}", e))?;
+    let label = response
+        .choices
+        .first()
+        .map(|choice| choice.message.content.lines().next().unwrap_or("").trim())
+        .unwrap_or("")
+        .trim_matches(|c: char| c == '"' || c == '“' || c == '”' || c == '「' || c == '」')
+        .chars()
+        .take(MAX_LABEL_CHARS)
+        .collect::<String>();
+    if label.is_empty() {
+        return Err("话题命名结果为空".to_string());
+    }
+    Ok(label)
+}
+
+/// LLM 不可用时的标签：前几个关键词，没有关键词时用最近会话的标题
+fn fallback_label(conversations: &[Conversation], keywords: &[String]) -> String {
+    if keywords.is_empty() {
+        return conversations
+            .iter()
+            .max_by_key(|c| c.updated_at)
+            .map(|c| c.title.chars().take(MAX_LABEL_CHARS).collect())
+            .unwrap_or_default();
+    }
+    keywords.iter().take(3).cloned().collect::<Vec<_>>().join("、")
+}
+
+async fn analyze(range: TopicRange) -> Result<TopicAnalysis, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let history = &db.conversation_history;
+    let conversations = history
+        .list_conversations(range.from, range.to)
+        .await
+        .map_err(|e| format!("读取会话失败: {}", e))?;
+
+    let mut entries = Vec::with_capacity(conversations.len());
+    let mut texts = Vec::with_capacity(conversations.len());
+    for conversation in conversations {
+        let messages = history
+            .get_messages(&conversation.id)
+            .await
+            .map_err(|e| format!("读取会话 {} 的消息失败: {}", conversation.id, e))?;
+        // 没有用户消息的会话不参与聚类
+        if !messages.iter().any(|m| !m.is_deleted() && matches!(m.role, StoredRole::User)) {
+            continue;
+        }
+        texts.push(conversation_text(&conversation, &messages));
+        entries.push(conversation);
+    }
+
+    let (provider_config, provider) = embedding::provider_for(TOPIC_COLLECTION).map_err(|e| e.to_string())?;
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(EMBED_BATCH_SIZE) {
+        vectors.extend(
+            embedding::embed_checked(provider.as_ref(), batch)
+                .await
+                .map_err(|e| format!("生成会话向量失败: {}", e))?,
+        );
+    }
+
+    let clustering = topic_clustering::cluster(&vectors, MAX_TOPICS);
+    let mut topics = Vec::with_capacity(clustering.k);
+    for (index, members) in clustering.members().into_iter().enumerate() {
+        let keywords = topic_clustering::cluster_keywords(&texts, &members, KEYWORDS_PER_TOPIC);
+        let conversations: Vec<Conversation> = members.iter().map(|&m| entries[m].clone()).collect();
+        let label = match label_topic(&conversations, &keywords).await {
+            Ok(label) => label,
+            Err(e) => {
+                warn!("话题命名失败，使用关键词作为标签: {}", e);
+                fallback_label(&conversations, &keywords)
+            }
+        };
+        topics.push(ConversationTopic {
+            id: format!("topic-{}", index + 1),
+            label,
+            keywords,
+            conversations,
+        });
+    }
+
+    Ok(TopicAnalysis {
+        generated_at: Utc::now().timestamp(),
+        range,
+        provider: provider_config.fingerprint(),
+        topics,
+    })
+}
+
+/// 运行话题分析并保存结果（同一时间只运行一个）
+pub async fn run_topic_analysis(app_handle: &AppHandle, range: TopicRange) -> Result<TopicAnalysis, String> {
+    if ANALYSIS_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("话题分析正在进行中".to_string());
+    }
+    let result = analyze(range).await;
+    ANALYSIS_RUNNING.store(false, Ordering::SeqCst);
+
+    let analysis = result?;
+    save_analysis(&analysis)?;
+    info!("会话话题分析完成: {} 个话题", analysis.topics.len());
+    let _ = app_handle.emit_all("conversation-topics-updated", analysis.generated_at);
+    Ok(analysis)
+}
+
+/// 启动后台话题分析（结果超过一天未刷新时重新分析最近 90 天）
+pub fn start_topic_analysis_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("topic_analysis", SCHEDULER_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                let due = match load_analysis() {
+                    Ok(Some(analysis)) => Utc::now().timestamp() - analysis.generated_at >= ANALYSIS_INTERVAL_SECS,
+                    Ok(None) => true,
+                    Err(e) => {
+                        warn!("读取话题分析结果失败: {}", e);
+                        true
+                    }
+                };
+                if due && crate::database::get_database().is_some() {
+                    resource_budget::defer(WorkKind::Background, "topic_analysis").await;
+                    if let Err(e) = run_topic_analysis(&app_handle, TopicRange::last_days(ANALYSIS_WINDOW_DAYS)).await {
+                        warn!("后台话题分析失败: {}", e);
+                    }
+                }
+                tokio::time::sleep(SCHEDULER_CHECK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+/// 按本地日期统计每个话题的活跃会话数
+fn build_timeline(topics: &[ConversationTopic], range: TopicRange) -> Vec<TopicTimelineEntry> {
+    let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for topic in topics {
+        for conversation in &topic.conversations {
+            // 范围开始前创建的会话计入范围的第一天
+            let timestamp = conversation.created_at.max(range.from);
+            let Some(date) = Local.timestamp_opt(timestamp, 0).single() else {
+                continue;
+            };
+            *counts
+                .entry((date.format("%Y-%m-%d").to_string(), topic.id.clone()))
+                .or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((date, topic_id), conversation_count)| TopicTimelineEntry {
+            date,
+            topic_id,
+            conversation_count,
+        })
+        .collect()
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 获取时间范围内的话题与时间线
+#[tauri::command]
+pub async fn get_conversation_topics(range: TopicRange) -> Result<TopicTimeline, String> {
+    if range.from > range.to {
+        return Err("时间范围无效".to_string());
+    }
+    let Some(analysis) = load_analysis()? else {
+        return Ok(TopicTimeline {
+            generated_at: None,
+            covered: false,
+            topics: Vec::new(),
+            timeline: Vec::new(),
+        });
+    };
+
+    let topics: Vec<ConversationTopic> = analysis
+        .topics
+        .into_iter()
+        .filter_map(|mut topic| {
+            topic.conversations.retain(|c| range.contains(c));
+            (!topic.conversations.is_empty()).then_some(topic)
+        })
+        .collect();
+
+    Ok(TopicTimeline {
+        generated_at: Some(analysis.generated_at),
+        covered: analysis.range.from <= range.from,
+        timeline: build_timeline(&topics, range),
+        topics,
+    })
+}
+
+/// 立即重新分析（默认最近 90 天）
+#[tauri::command]
+pub async fn refresh_conversation_topics(
+    app_handle: AppHandle,
+    range: Option<TopicRange>,
+) -> Result<TopicAnalysis, String> {
+    let range = range.unwrap_or_else(|| TopicRange::last_days(ANALYSIS_WINDOW_DAYS));
+    if range.from > range.to {
+        return Err("时间范围无效".to_string());
+    }
+    run_topic_analysis(&app_handle, range).await
+}
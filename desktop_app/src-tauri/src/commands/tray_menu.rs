@@ -0,0 +1,156 @@
+//! # 托盘扩展菜单命令模块
+//!
+//! 适配器与工作流通过 `register_tray_menu_item` 在运行时添加托盘菜单项（规则见
+//! [`crate::utils::tray_menu`]），注册或移除后立即重建托盘菜单，无需重启托盘。
+//!
+//! 点击扩展菜单项时由 [`handle_click`] 交给所有者处理：适配器执行注册时指定的操作，
+//! 工作流以 `tray` 方式执行。处理前后分别发出 `tray-menu-item-clicked` 与
+//! `tray-menu-item-completed` 事件，适配器与工作流的界面可据此刷新状态。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::adapter::AdapterExecutionRequest;
+use crate::commands::{adapter, workflow_api};
+use crate::events::tray::helpers;
+use crate::http::workflow_client::ExecuteWorkflowRequest;
+use crate::utils::tray_menu::{TrayMenuItem, TrayMenuOwner, TrayMenuRegistry};
+use crate::AppState;
+
+/// 托盘触发的工作流执行方式
+const EXECUTION_MODE: &str = "tray";
+
+/// 带托盘菜单 ID 的菜单项
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredTrayMenuItem {
+    pub menu_id: String,
+    #[serde(flatten)]
+    pub item: TrayMenuItem,
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<TrayMenuRegistry> = Mutex::new(TrayMenuRegistry::default());
+}
+
+/// 当前注册的扩展菜单项
+pub fn registered_items() -> Vec<TrayMenuItem> {
+    REGISTRY.lock().items().to_vec()
+}
+
+fn rebuild(app_handle: &AppHandle) {
+    if let Err(e) = helpers::rebuild_tray_menu(app_handle) {
+        warn!("重建托盘菜单失败: {}", e);
+    }
+}
+
+async fn dispatch(app_handle: &AppHandle, item: &TrayMenuItem) -> Result<(), String> {
+    match &item.owner {
+        TrayMenuOwner::Adapter { adapter_id, action } => {
+            let request = AdapterExecutionRequest {
+                adapter_id: adapter_id.clone(),
+                action: action.clone(),
+                params: item.params.clone(),
+                timeout: None,
+                priority: Default::default(),
+                execution_id: None,
+            };
+            let response = adapter::execute_adapter(request, app_handle.clone(), app_handle.state::<AppState>())
+                .await?
+                .response;
+            if response.success {
+                Ok(())
+            } else {
+                Err(response.error.unwrap_or_else(|| "适配器操作失败".to_string()))
+            }
+        }
+        TrayMenuOwner::Workflow { workflow_id } => {
+            let request = ExecuteWorkflowRequest {
+                input_data: (!item.params.is_empty()).then(|| item.params.clone()),
+                execution_mode: EXECUTION_MODE.to_string(),
+                debug: false,
+                breakpoints: Vec::new(),
+            };
+            let execution = workflow_api::execute(app_handle, workflow_id, request).await?;
+            match execution.error_message {
+                Some(error) => Err(error),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// 处理扩展菜单项的点击（由托盘事件处理器调用）
+pub fn handle_click(app_handle: &AppHandle, menu_id: &str) {
+    let Some(item) = REGISTRY.lock().find(menu_id).cloned() else {
+        warn!("托盘扩展菜单项已移除: {}", menu_id);
+        return;
+    };
+    if !item.enabled {
+        return;
+    }
+
+    let _ = app_handle.emit_all("tray-menu-item-clicked", serde_json::json!({
+        "menu_id": menu_id,
+        "item_id": item.item_id,
+        "owner": item.owner,
+    }));
+    let app_handle = app_handle.clone();
+    let menu_id = menu_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let result = dispatch(&app_handle, &item).await;
+        if let Err(e) = &result {
+            warn!("托盘扩展菜单项 {} 处理失败: {}", menu_id, e);
+        }
+        let _ = app_handle.emit_all("tray-menu-item-completed", serde_json::json!({
+            "menu_id": menu_id,
+            "owner": item.owner,
+            "success": result.is_ok(),
+            "error": result.err(),
+        }));
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 注册托盘菜单项（同一所有者下 ID 相同时替换），返回托盘菜单 ID
+#[tauri::command]
+pub async fn register_tray_menu_item(app_handle: AppHandle, item: TrayMenuItem) -> Result<String, String> {
+    let menu_id = REGISTRY.lock().register(item)?;
+    info!("已注册托盘扩展菜单项: {}", menu_id);
+    rebuild(&app_handle);
+    Ok(menu_id)
+}
+
+/// 移除托盘菜单项，返回是否存在
+#[tauri::command]
+pub async fn unregister_tray_menu_item(app_handle: AppHandle, menu_id: String) -> Result<bool, String> {
+    let removed = REGISTRY.lock().unregister(&menu_id);
+    if removed {
+        rebuild(&app_handle);
+    }
+    Ok(removed)
+}
+
+/// 移除某个适配器或工作流注册的全部托盘菜单项，返回移除数量
+#[tauri::command]
+pub async fn clear_tray_menu_items(app_handle: AppHandle, owner: TrayMenuOwner) -> Result<usize, String> {
+    let removed = REGISTRY.lock().remove_owner(&owner.key());
+    if removed > 0 {
+        rebuild(&app_handle);
+    }
+    Ok(removed)
+}
+
+/// 按显示顺序列出已注册的托盘菜单项
+#[tauri::command]
+pub async fn list_tray_menu_items() -> Result<Vec<RegisteredTrayMenuItem>, String> {
+    Ok(registered_items()
+        .into_iter()
+        .map(|item| RegisteredTrayMenuItem { menu_id: item.menu_id(), item })
+        .collect())
+}
@@ -0,0 +1,136 @@
+//! # 托盘状态持久化命令模块
+//!
+//! `TrayState` 的最近对话与通知定期保存到数据库（`tray_state` 表）。启动时读取保存的内容，
+//! 与实际的对话数据对账（移除已删除对话的引用，刷新标题与最后消息）后恢复；
+//! 托盘内容与对话数据不一致时可调用 `rebuild_tray_state` 重新对账并用最近的对话补足列表。
+
+use chrono::{TimeZone, Utc};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use crate::database::Database;
+use crate::state::tray_state::{reconcile_snapshot, ConversationRecord, TrayReconcileReport, TraySnapshot};
+use crate::utils::watchdog;
+use crate::AppState;
+
+/// 检查是否需要保存的间隔
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+const SAVE_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// 重建时从对话数据中读取的对话数
+const REBUILD_CANDIDATES: i64 = 10;
+
+/// 读取快照中引用的对话；`fill` 时同时读取最近的对话
+async fn conversation_records(
+    db: &Database,
+    snapshot: &TraySnapshot,
+    fill: bool,
+) -> Result<Vec<ConversationRecord>, String> {
+    let history = &db.conversation_history;
+    let ids: Vec<String> = snapshot.recent_conversations.iter().map(|c| c.id.clone()).collect();
+    let mut summaries = history
+        .conversation_summaries(Some(ids.clone()), ids.len() as i64)
+        .await
+        .map_err(|e| format!("读取对话失败: {}", e))?;
+    if fill {
+        let recent = history
+            .conversation_summaries(None, REBUILD_CANDIDATES)
+            .await
+            .map_err(|e| format!("读取对话失败: {}", e))?;
+        summaries.extend(recent.into_iter().filter(|r| !ids.contains(&r.id)));
+    }
+
+    let mut records: Vec<ConversationRecord> = summaries
+        .into_iter()
+        .map(|summary| ConversationRecord {
+            id: summary.id,
+            title: summary.title,
+            last_message: summary.last_message,
+            updated_at: Utc.timestamp_opt(summary.updated_at, 0).single().unwrap_or_else(Utc::now),
+        })
+        .collect();
+    records.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(records)
+}
+
+async fn save(app_handle: &AppHandle) -> Result<(), String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let snapshot = app_handle.state::<AppState>().tray.snapshot();
+    let value = serde_json::to_value(&snapshot).map_err(|e| format!("序列化托盘状态失败: {}", e))?;
+    db.tray_state
+        .save(&value, Utc::now().timestamp())
+        .await
+        .map_err(|e| format!("保存托盘状态失败: {}", e))
+}
+
+/// 对账并恢复托盘状态；`snapshot` 为空时使用当前内存中的状态
+async fn reconcile(app_handle: &AppHandle, snapshot: Option<TraySnapshot>, fill: bool) -> Result<TrayReconcileReport, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let state = app_handle.state::<AppState>();
+    let snapshot = snapshot.unwrap_or_else(|| state.tray.snapshot());
+    let records = conversation_records(&db, &snapshot, fill).await?;
+    let (snapshot, report) = reconcile_snapshot(snapshot, &records, fill);
+    state.tray.restore(snapshot);
+    if !report.dropped.is_empty() {
+        info!("托盘状态移除了 {} 个已不存在的对话", report.dropped.len());
+    }
+    Ok(report)
+}
+
+/// 恢复上次保存的托盘状态（启动时调用）
+pub async fn initialize_tray_state(app_handle: &AppHandle) {
+    let Some(db) = crate::database::get_database() else {
+        return;
+    };
+    let snapshot = match db.tray_state.load().await {
+        Ok(Some(value)) => match serde_json::from_value::<TraySnapshot>(value) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                warn!("托盘状态格式无效，已忽略: {}", e);
+                return;
+            }
+        },
+        Ok(None) => return,
+        Err(e) => {
+            warn!("读取托盘状态失败: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = reconcile(app_handle, Some(snapshot), false).await {
+        warn!("恢复托盘状态失败: {}", e);
+    }
+}
+
+/// 启动托盘状态保存任务（内容变化后保存）
+pub fn start_tray_state_saver(app_handle: AppHandle) {
+    watchdog::supervise("tray_state", SAVE_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut saved_revision = app_handle.state::<AppState>().tray.revision();
+            while heartbeat.beat() {
+                tokio::time::sleep(SAVE_INTERVAL).await;
+                let revision = app_handle.state::<AppState>().tray.revision();
+                if revision == saved_revision {
+                    continue;
+                }
+                match save(&app_handle).await {
+                    Ok(()) => saved_revision = revision,
+                    Err(e) => debug!("托盘状态未保存: {}", e),
+                }
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 与对话数据重新对账托盘状态，并用最近的对话补足列表
+#[tauri::command]
+pub async fn rebuild_tray_state(app_handle: AppHandle) -> Result<TrayReconcileReport, String> {
+    let report = reconcile(&app_handle, None, true).await?;
+    save(&app_handle).await?;
+    info!("托盘状态已重建: 保留 {}，新增 {}", report.kept, report.added);
+    Ok(report)
+}
@@ -0,0 +1,242 @@
+//! # 语音合成命令模块
+//!
+//! `synthesize_speech` 用当前引擎把文本合成为 WAV（按引擎、音色、语速与文本缓存在 `tts_cache`），
+//! `play_tts` 合成后经口型同步播放（见 [`crate::commands::lipsync`]），口型参数推送给 Live2D 渲染窗口，
+//! 播放事件中的 `file_id` 为 `tts:<音频 ID>`。开启 `speak_replies` 后聊天回复会自动朗读。
+//! 引擎与文本整理规则见 [`crate::utils::tts`]。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tracing::{debug, info, warn};
+
+use crate::commands::language;
+use crate::commands::lipsync::{self, LipSyncPlayback};
+use crate::utils::character_binding;
+use crate::utils::tts::{self, TtsRequest, TtsSettings};
+
+const CACHE_DIR: &str = "tts_cache";
+/// 缓存保留的音频数，超出时删除最早的
+const MAX_CACHE_FILES: usize = 200;
+
+lazy_static! {
+    static ref SETTINGS: RwLock<TtsSettings> = RwLock::new(TtsSettings::default());
+}
+
+/// 合成结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsAudio {
+    pub id: String,
+    pub path: String,
+    pub duration_ms: u64,
+    /// 实际朗读的文本（已整理）
+    pub text: String,
+    pub voice: Option<String>,
+    pub cached: bool,
+}
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(app_data_dir.join("tts_settings.json"))
+}
+
+fn get_cache_dir() -> Result<PathBuf, String> {
+    let cache_dir = crate::utils::config::get_app_data_dir()?
+        .join(CACHE_DIR);
+    fs::create_dir_all(&cache_dir).map_err(|e| format!("创建语音缓存目录失败: {}", e))?;
+    Ok(cache_dir)
+}
+
+/// 加载语音合成设置（启动时调用）
+pub fn initialize_tts() -> Result<(), String> {
+    let config_path = get_config_path()?;
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let json_data = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read TTS settings: {}", e))?;
+    let settings: TtsSettings = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Failed to parse TTS settings: {}", e))?;
+    settings.validate()?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 删除超出数量的最早缓存
+fn prune_cache(cache_dir: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "wav"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if files.len() <= MAX_CACHE_FILES {
+        return;
+    }
+    files.sort_by_key(|(modified, _)| *modified);
+    for (_, path) in files.iter().take(files.len() - MAX_CACHE_FILES) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn wav_duration_ms(path: &Path) -> Result<u64, String> {
+    let reader = hound::WavReader::open(path).map_err(|e| format!("语音文件无效: {}", e))?;
+    let sample_rate = reader.spec().sample_rate.max(1) as u64;
+    Ok(reader.duration() as u64 * 1000 / sample_rate)
+}
+
+/// 合成语音；未指定音色时依次使用当前角色绑定的音色与默认音色，未指定区域时按文本语言选择
+pub async fn synthesize(
+    text: &str,
+    voice: Option<String>,
+    locale: Option<String>,
+) -> Result<TtsAudio, String> {
+    let settings = SETTINGS.read().clone();
+    let text = tts::speakable_text(text, settings.max_chars);
+    if text.is_empty() {
+        return Err("没有可朗读的内容".to_string());
+    }
+    let voice = voice
+        .or_else(|| character_binding::active().and_then(|applied| applied.binding.tts_voice))
+        .or(settings.voice.clone());
+    let locale = locale.unwrap_or_else(|| language::resolve_message_language(None, &text).voice_locale);
+    let request = TtsRequest {
+        text,
+        voice,
+        locale,
+        rate: settings.rate,
+    };
+
+    let mut hasher = Sha256::new();
+    for part in [
+        settings.engine.fingerprint().as_str(),
+        request.voice.as_deref().unwrap_or_default(),
+        request.locale.as_str(),
+        &request.rate.to_string(),
+        request.text.as_str(),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let id = format!("{:x}", hasher.finalize());
+    let cache_dir = get_cache_dir()?;
+    let path = cache_dir.join(format!("{}.wav", id));
+
+    let cached = path.exists();
+    if !cached {
+        let engine = settings.engine.build()?;
+        let partial = cache_dir.join(format!("{}.partial.wav", id));
+        let result = engine.synthesize(&request, &partial).await;
+        if let Err(e) = result {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &path).map_err(|e| format!("保存语音文件失败: {}", e))?;
+        prune_cache(&cache_dir);
+        debug!("语音已合成: {} ({} 字)", id, request.text.chars().count());
+    }
+
+    Ok(TtsAudio {
+        duration_ms: wav_duration_ms(&path)?,
+        path: path.to_string_lossy().to_string(),
+        id,
+        text: request.text,
+        voice: request.voice,
+        cached,
+    })
+}
+
+/// 合成并播放，同步角色口型
+pub async fn speak(
+    app_handle: &AppHandle,
+    text: &str,
+    voice: Option<String>,
+    locale: Option<String>,
+) -> Result<LipSyncPlayback, String> {
+    let audio = synthesize(text, voice, locale).await?;
+    let playback = lipsync::play_wav_with_lipsync(
+        app_handle.clone(),
+        format!("tts:{}", audio.id),
+        PathBuf::from(&audio.path),
+    )
+    .await?;
+    info!("开始朗读: {} ({} ms)", audio.id, audio.duration_ms);
+    Ok(playback)
+}
+
+/// 开启自动朗读时朗读聊天回复（后台进行，失败只记录日志）
+pub fn speak_reply(app_handle: &AppHandle, text: &str, locale: Option<String>) {
+    if !SETTINGS.read().speak_replies {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    let text = text.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = speak(&app_handle, &text, None, locale).await {
+            warn!("朗读回复失败: {}", e);
+        }
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 合成语音（不播放）
+#[tauri::command]
+pub async fn synthesize_speech(
+    text: String,
+    voice: Option<String>,
+    locale: Option<String>,
+) -> Result<TtsAudio, String> {
+    synthesize(&text, voice, locale).await
+}
+
+/// 合成并播放语音，同步角色口型；会中断正在进行的播放
+#[tauri::command]
+pub async fn play_tts(
+    app_handle: AppHandle,
+    text: String,
+    voice: Option<String>,
+    locale: Option<String>,
+) -> Result<LipSyncPlayback, String> {
+    speak(&app_handle, &text, voice, locale).await
+}
+
+/// 停止朗读，返回是否有正在进行的播放
+#[tauri::command]
+pub async fn stop_tts() -> Result<bool, String> {
+    Ok(lipsync::stop_current_playback())
+}
+
+/// 获取语音合成设置
+#[tauri::command]
+pub async fn get_tts_settings() -> Result<TtsSettings, String> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 保存语音合成设置
+#[tauri::command]
+pub async fn update_tts_settings(settings: TtsSettings) -> Result<(), String> {
+    settings.validate()?;
+
+    let json_data = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize TTS settings: {}", e))?;
+    fs::write(get_config_path()?, json_data)
+        .map_err(|e| format!("Failed to write TTS settings: {}", e))?;
+
+    *SETTINGS.write() = settings;
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::commands::{CommandMetadata, PermissionLevel};
 use crate::database::update::{UpdateInfo, UpdateConfig, VersionHistory};
+use crate::utils::delta_update::StagedUpdate;
 use crate::utils::update_manager::{UpdateManager, UpdateEvent};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -57,10 +58,7 @@ pub async fn init_update_manager(
 ) -> Result<bool, String> {
     info!("Initializing update manager");
 
-    let app_data_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Failed to get app data directory")?;
+    let app_data_dir = crate::utils::config::get_app_data_dir()?;
 
     // 获取数据库连接池
     let db = crate::database::get_database()
@@ -80,7 +78,7 @@ pub async fn init_update_manager(
             
             {
                 let mut state_manager = state.manager.lock().unwrap();
-                *state_manager = Some(manager);
+                *state_manager = Some(manager.clone());
             }
             
             {
@@ -88,6 +86,15 @@ pub async fn init_update_manager(
                 *state_receiver = Some(event_receiver);
             }
 
+            // 安装上次暂存的更新
+            if manager.staged_update().is_some() {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = manager.apply_staged_update().await {
+                        error!("Failed to apply staged update: {}", e);
+                    }
+                });
+            }
+
             info!("Update manager initialized successfully");
             Ok(true)
         }
@@ -186,6 +193,57 @@ pub async fn install_update(
     }
 }
 
+/// 暂存已下载的更新，下次重启时安装
+#[tauri::command]
+pub async fn stage_update(
+    state: State<'_, UpdateManagerState>,
+    version: String,
+) -> Result<StagedUpdate, String> {
+    info!("Staging update for next restart: {}", version);
+
+    let manager = {
+        state.manager.lock().unwrap()
+            .as_ref()
+            .ok_or("Update manager not initialized")?
+            .clone()
+    };
+
+    manager.stage_update(&version).map_err(|e| {
+        error!("Failed to stage update: {}", e);
+        e.to_string()
+    })
+}
+
+/// 获取等待下次重启安装的更新
+#[tauri::command]
+pub async fn get_staged_update(
+    state: State<'_, UpdateManagerState>,
+) -> Result<Option<StagedUpdate>, String> {
+    let manager = {
+        state.manager.lock().unwrap()
+            .as_ref()
+            .ok_or("Update manager not initialized")?
+            .clone()
+    };
+
+    Ok(manager.staged_update())
+}
+
+/// 取消暂存的更新
+#[tauri::command]
+pub async fn unstage_update(
+    state: State<'_, UpdateManagerState>,
+) -> Result<bool, String> {
+    let manager = {
+        state.manager.lock().unwrap()
+            .as_ref()
+            .ok_or("Update manager not initialized")?
+            .clone()
+    };
+
+    Ok(manager.unstage_update())
+}
+
 /// 使用 Tauri 内置更新器安装更新（已禁用）
 #[tauri::command]
 pub async fn install_update_with_tauri(
@@ -345,6 +403,7 @@ pub async fn restart_application(
     info!("Restarting application");
 
     // 使用 Tauri 的重启功能
+    crate::utils::safe_mode::mark_clean_exit();
     app_handle.restart();
     info!("Application restart initiated");
     Ok(true)
@@ -461,6 +520,36 @@ pub fn get_command_metadata() -> std::collections::HashMap<String, CommandMetada
         category: "update".to_string(),
     });
 
+    commands.insert("stage_update".to_string(), CommandMetadata {
+        name: "stage_update".to_string(),
+        description: "暂存更新，下次重启时安装".to_string(),
+        input_type: None,
+        output_type: None,
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "update".to_string(),
+    });
+
+    commands.insert("get_staged_update".to_string(), CommandMetadata {
+        name: "get_staged_update".to_string(),
+        description: "获取等待下次重启安装的更新".to_string(),
+        input_type: None,
+        output_type: None,
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "update".to_string(),
+    });
+
+    commands.insert("unstage_update".to_string(), CommandMetadata {
+        name: "unstage_update".to_string(),
+        description: "取消暂存的更新".to_string(),
+        input_type: None,
+        output_type: None,
+        required_permission: PermissionLevel::User,
+        is_async: true,
+        category: "update".to_string(),
+    });
+
     commands.insert("install_update_with_tauri".to_string(), CommandMetadata {
         name: "install_update_with_tauri".to_string(),
         description: "使用 Tauri 内置更新器安装更新".to_string(),
@@ -5,6 +5,8 @@
 //! - Minimize to tray
 //! - Window positioning and sizing
 //! - Always-on-top toggle
+//! - Edge docking of the pet window
+//! - Click-through of the pet window outside the character
 
 use tauri::{AppHandle, Manager, State, Window, Position, Size, PhysicalPosition, PhysicalSize};
 use serde::{Deserialize, Serialize};
@@ -14,6 +16,11 @@ use crate::{
     commands::*,
     state::AppState,
     utils::*,
+    utils::window_layout::LayoutSettings,
+    utils::window_dock::DockSettings,
+    utils::click_through::{HitTestMask, HitTestMaskUpdate},
+    events::window_dock::DockState,
+    events::click_through::ClickThroughState,
 };
 
 // ================================
@@ -361,6 +368,184 @@ pub async fn close_window(
     }
 }
 
+/// Get window magnetism settings
+#[tauri::command]
+pub async fn get_window_layout_settings() -> Result<CommandResponse<LayoutSettings>, String> {
+    Ok(CommandResponse::success(crate::events::window_layout::settings()))
+}
+
+/// Update window magnetism settings
+#[tauri::command]
+pub async fn update_window_layout_settings(
+    settings: LayoutSettings,
+) -> Result<CommandResponse<LayoutSettings>, String> {
+    info!(
+        "更新窗口磁吸设置: enabled={}, snap_distance={}, move_as_group={}",
+        settings.enabled, settings.snap_distance, settings.move_as_group
+    );
+
+    if let Err(e) = crate::events::window_layout::update_settings(settings.clone()) {
+        error!("更新窗口磁吸设置失败: {}", e);
+        return Ok(CommandResponse::error(e));
+    }
+
+    Ok(CommandResponse::success_with_message(
+        settings,
+        "窗口磁吸设置已更新".to_string(),
+    ))
+}
+
+fn dock_window(app_handle: &AppHandle) -> Result<Window, String> {
+    app_handle
+        .get_window("main")
+        .ok_or_else(|| "主窗口不存在".to_string())
+}
+
+/// Persist dock preferences in `WindowConfig`
+async fn save_dock_settings(
+    app_handle: &AppHandle,
+    state: &State<'_, AppState>,
+    settings: DockSettings,
+) {
+    let config = {
+        let mut config = state.config.lock();
+        config.window.docking = settings;
+        config.clone()
+    };
+    if let Err(e) = save_config(app_handle, &config).await {
+        warn!("保存贴边停靠设置失败: {}", e);
+    }
+}
+
+/// Get the pet window's dock state
+#[tauri::command]
+pub async fn get_dock_state(app_handle: AppHandle) -> Result<CommandResponse<DockState>, String> {
+    Ok(CommandResponse::success(crate::events::window_dock::state(&app_handle)))
+}
+
+/// Enable or disable edge docking
+#[tauri::command]
+pub async fn set_docking_enabled(
+    enabled: bool,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<DockState>, String> {
+    info!("设置贴边停靠: {}", enabled);
+
+    let mut settings = state.config.lock().window.docking.clone();
+    settings.enabled = enabled;
+    save_dock_settings(&app_handle, &state, settings).await;
+
+    // A collapsed window slides back onto the screen
+    if !enabled {
+        if let Ok(window) = dock_window(&app_handle) {
+            crate::events::window_dock::undock(&window);
+        }
+    }
+
+    Ok(CommandResponse::success_with_message(
+        crate::events::window_dock::state(&app_handle),
+        format!("贴边停靠已{}", if enabled { "启用" } else { "禁用" }),
+    ))
+}
+
+/// Update edge docking settings
+#[tauri::command]
+pub async fn update_dock_settings(
+    settings: DockSettings,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<DockState>, String> {
+    if let Err(e) = settings.validate() {
+        return Ok(CommandResponse::error(e));
+    }
+    let enabled = settings.enabled;
+    save_dock_settings(&app_handle, &state, settings).await;
+
+    if !enabled {
+        if let Ok(window) = dock_window(&app_handle) {
+            crate::events::window_dock::undock(&window);
+        }
+    }
+
+    Ok(CommandResponse::success_with_message(
+        crate::events::window_dock::state(&app_handle),
+        "贴边停靠设置已更新".to_string(),
+    ))
+}
+
+/// Collapse the docked pet into the peeking state, or slide it back out
+#[tauri::command]
+pub async fn set_dock_peeking(
+    peeking: bool,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<DockState>, String> {
+    let window = dock_window(&app_handle)?;
+    match crate::events::window_dock::set_peeking(&window, peeking) {
+        Ok(dock_state) => Ok(CommandResponse::success(dock_state)),
+        Err(e) => Ok(CommandResponse::error(e)),
+    }
+}
+
+/// Report pointer hover over the docked pet (slides out on hover)
+#[tauri::command]
+pub async fn set_dock_hover(
+    hovering: bool,
+    app_handle: AppHandle,
+) -> Result<CommandResponse<DockState>, String> {
+    let window = dock_window(&app_handle)?;
+    match crate::events::window_dock::set_hover(&window, hovering) {
+        Ok(dock_state) => Ok(CommandResponse::success(dock_state)),
+        Err(e) => Ok(CommandResponse::error(e)),
+    }
+}
+
+/// Get the pet window's click-through state
+#[tauri::command]
+pub async fn get_click_through_state(
+    app_handle: AppHandle,
+) -> Result<CommandResponse<ClickThroughState>, String> {
+    Ok(CommandResponse::success(crate::events::click_through::state(&app_handle)))
+}
+
+/// Enable or disable click-through; persisted in `WindowConfig`
+#[tauri::command]
+pub async fn set_click_through(
+    enabled: bool,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CommandResponse<ClickThroughState>, String> {
+    info!("设置点击穿透: {}", enabled);
+
+    let config = {
+        let mut config = state.config.lock();
+        config.window.click_through = enabled;
+        config.clone()
+    };
+    if let Err(e) = save_config(&app_handle, &config).await {
+        warn!("保存点击穿透设置失败: {}", e);
+    }
+    crate::events::click_through::apply(&app_handle);
+
+    Ok(CommandResponse::success_with_message(
+        crate::events::click_through::state(&app_handle),
+        format!("点击穿透已{}", if enabled { "启用" } else { "禁用" }),
+    ))
+}
+
+/// Update the hit-test mask rendered by the Live2D view (`None` clears it)
+#[tauri::command]
+pub async fn update_hit_test_mask(
+    mask: Option<HitTestMaskUpdate>,
+) -> Result<CommandResponse<bool>, String> {
+    let mask = match mask.as_ref().map(HitTestMask::decode).transpose() {
+        Ok(mask) => mask,
+        Err(e) => return Ok(CommandResponse::error(e)),
+    };
+    crate::events::click_through::set_mask(mask);
+    Ok(CommandResponse::success(true))
+}
+
 // ================================
 // Command Metadata
 // ================================
@@ -394,6 +579,45 @@ pub fn get_command_metadata() -> std::collections::HashMap<String, CommandMetada
         },
     );
     
+    metadata.insert(
+        "set_docking_enabled".to_string(),
+        CommandMetadata {
+            name: "set_docking_enabled".to_string(),
+            description: "启用或禁用桌宠贴边停靠".to_string(),
+            input_type: Some("bool".to_string()),
+            output_type: Some("DockState".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "window".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "get_dock_state".to_string(),
+        CommandMetadata {
+            name: "get_dock_state".to_string(),
+            description: "获取桌宠贴边停靠状态".to_string(),
+            input_type: None,
+            output_type: Some("DockState".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "window".to_string(),
+        },
+    );
+    
+    metadata.insert(
+        "set_click_through".to_string(),
+        CommandMetadata {
+            name: "set_click_through".to_string(),
+            description: "启用或禁用桌宠窗口点击穿透".to_string(),
+            input_type: Some("bool".to_string()),
+            output_type: Some("ClickThroughState".to_string()),
+            required_permission: PermissionLevel::User,
+            is_async: true,
+            category: "window".to_string(),
+        },
+    );
+    
     metadata.insert(
         "toggle_always_on_top".to_string(),
         CommandMetadata {
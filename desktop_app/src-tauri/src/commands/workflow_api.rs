@@ -2,29 +2,50 @@
 //! 通过 HTTP 调用 Python 后端服务
 
 use crate::http::workflow_client::{
-    CreateWorkflowRequest, ExecuteWorkflowRequest, UpdateWorkflowRequest,
-    WorkflowApiClient, WorkflowExecutionResponse, WorkflowResponse,
+    CreateWorkflowRequest, DebugFrame, ExecuteWorkflowRequest, ExecutionTrace, ReplayExecutionRequest,
+    UpdateWorkflowRequest, WorkflowApiClient, WorkflowExecutionResponse, WorkflowResponse,
 };
+use crate::database::achievement::METRIC_WORKFLOW_RUNS;
+use crate::database::pet_statistics::INTERACTION_WORKFLOW;
 use crate::state::AppState;
+use crate::utils::mobile_relay::RelayCategory;
+use crate::utils::metric_registry;
+use crate::utils::resource_accounting::{self, Subsystem};
+use crate::utils::safe_mode::{self, SkippedKind};
+use crate::utils::workflow_export::{
+    self, EmbeddedPrompt, EmbeddedTemplate, ExportFormat, ExportedWorkflow, WorkflowBundle,
+    WorkflowExport,
+};
+use crate::utils::workflow_trace::{StepDirection, TraceCursor, TraceFrame};
+use lazy_static::lazy_static;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use tracing::{debug, error, info};
 
+/// 同时保留的单步查看游标数
+const MAX_TRACE_CURSORS: usize = 16;
+
+lazy_static! {
+    /// 执行 ID -> 单步查看游标
+    static ref TRACE_CURSORS: parking_lot::Mutex<HashMap<String, TraceCursor>> =
+        parking_lot::Mutex::new(HashMap::new());
+}
+
 /// 获取工作流 API 客户端
 fn get_workflow_client(state: &AppState) -> Result<WorkflowApiClient, String> {
-    // 从配置或环境变量读取 API 地址，工作流使用核心服务
-    let api_url = std::env::var("ZISHU_API_URL")
-        .unwrap_or_else(|_| {
-            let router = crate::config::ApiRouter::new();
-            router.core_url()
-        });
+    crate::http::capabilities::ensure_feature(crate::config::BackendFeature::Workflow)
+        .map_err(|e| e.to_string())?;
+
+    // 从端点配置或环境变量读取 API 地址，默认使用核心服务
+    let api_url = crate::config::backend_profiles::base_url_for(crate::config::BackendFeature::Workflow);
     
     let mut client = WorkflowApiClient::new(api_url)
         .map_err(|e| format!("创建 API 客户端失败: {}", e))?;
     
-    // TODO: 从状态中获取认证令牌
-    // client.set_auth_token(state.auth_token.clone());
+    client.set_auth_token(crate::config::backend_profiles::bearer_token_for(
+        crate::config::BackendFeature::Workflow,
+    ));
     
     Ok(client)
 }
@@ -153,27 +174,71 @@ pub async fn api_delete_workflow(
 // 工作流执行
 // ================================
 
+/// 把工作流失败转发到手机
+fn relay_workflow_failure(workflow_id: &str, error: &str) {
+    crate::commands::mobile_relay::relay(
+        RelayCategory::WorkflowFailure,
+        "工作流执行失败",
+        format!("{}: {}", workflow_id, error),
+    );
+}
+
+/// 执行工作流并记录指标；手动以外的执行方式在安全模式下被拒绝
+pub(crate) async fn execute(
+    app_handle: &AppHandle,
+    workflow_id: &str,
+    request: ExecuteWorkflowRequest,
+) -> Result<WorkflowExecutionResponse, String> {
+    // 安全模式下只允许手动执行，触发器发起的执行一律拒绝
+    if request.execution_mode != "manual" {
+        safe_mode::ensure_allowed(SkippedKind::Workflow, workflow_id)?;
+    }
+    
+    let client = get_workflow_client(&app_handle.state::<AppState>())?;
+    
+    let started = std::time::Instant::now();
+    let result = {
+        let _activity = resource_accounting::track(Subsystem::WorkflowEngine);
+        client.execute_workflow(workflow_id, request).await
+    };
+    let status = match &result {
+        Ok(execution) => execution.execution_status.as_str(),
+        Err(_) => "error",
+    };
+    metric_registry::increment_counter(metric_registry::WORKFLOW_EXECUTIONS_TOTAL, &[("status", status)]);
+    metric_registry::observe(metric_registry::WORKFLOW_EXECUTION_DURATION, &[], started.elapsed().as_secs_f64());
+    let execution = result.map_err(|e| {
+        relay_workflow_failure(workflow_id, &e.to_string());
+        format!("执行工作流失败: {}", e)
+    })?;
+    if execution.execution_status == "failed" {
+        relay_workflow_failure(workflow_id, execution.error_message.as_deref().unwrap_or("未知错误"));
+    }
+    crate::commands::achievements::track(app_handle, METRIC_WORKFLOW_RUNS, 1);
+    crate::commands::statistics::track_interaction(INTERACTION_WORKFLOW, None, None, Some(workflow_id.to_string()));
+    Ok(execution)
+}
+
 /// 执行工作流（通过 Python API）
 #[tauri::command]
 pub async fn api_execute_workflow(
-    state: State<'_, AppState>,
+    app_handle: AppHandle,
     workflow_id: String,
     input_data: Option<HashMap<String, JsonValue>>,
     execution_mode: Option<String>,
+    debug: Option<bool>,
+    breakpoints: Option<Vec<String>>,
 ) -> Result<WorkflowExecutionResponse, String> {
     info!("API: 执行工作流 - {}", workflow_id);
     
-    let client = get_workflow_client(&state)?;
-    
     let request = ExecuteWorkflowRequest {
         input_data,
         execution_mode: execution_mode.unwrap_or_else(|| "manual".to_string()),
+        debug: debug.unwrap_or(false),
+        breakpoints: breakpoints.unwrap_or_default(),
     };
     
-    client
-        .execute_workflow(&workflow_id, request)
-        .await
-        .map_err(|e| format!("执行工作流失败: {}", e))
+    execute(&app_handle, &workflow_id, request).await
 }
 
 /// 获取工作流执行历史（通过 Python API）
@@ -226,6 +291,176 @@ pub async fn api_cancel_execution(
         .map_err(|e| format!("取消执行失败: {}", e))
 }
 
+// ================================
+// 执行追踪与回放
+// ================================
+
+/// 获取执行追踪：每个节点的输入、输出与耗时（通过 Python API）
+#[tauri::command]
+pub async fn get_execution_trace(
+    state: State<'_, AppState>,
+    execution_id: String,
+) -> Result<ExecutionTrace, String> {
+    debug!("API: 获取执行追踪 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .get_execution_trace(&execution_id)
+        .await
+        .map_err(|e| format!("获取执行追踪失败: {}", e))
+}
+
+/// 回放执行：适配器、HTTP 等有副作用的节点使用原执行记录的输出（通过 Python API）
+///
+/// `live_nodes` 中的节点仍真实执行；`step` 为真时在每个节点前暂停，用 `debug_step` 逐个执行。
+#[tauri::command]
+pub async fn replay_execution(
+    state: State<'_, AppState>,
+    execution_id: String,
+    input_data: Option<HashMap<String, JsonValue>>,
+    live_nodes: Option<Vec<String>>,
+    breakpoints: Option<Vec<String>>,
+    step: Option<bool>,
+) -> Result<WorkflowExecutionResponse, String> {
+    info!("API: 回放执行 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    let request = ReplayExecutionRequest {
+        input_data,
+        live_nodes: live_nodes.unwrap_or_default(),
+        breakpoints: breakpoints.unwrap_or_default(),
+        step: step.unwrap_or(false),
+    };
+    
+    client
+        .replay_execution(&execution_id, request)
+        .await
+        .map_err(|e| format!("回放执行失败: {}", e))
+}
+
+/// 在本地逐个查看执行追踪中的节点，不会重新执行任何节点
+///
+/// 第一次查看或 `restart` 时从后端获取追踪。
+#[tauri::command]
+pub async fn step_execution(
+    state: State<'_, AppState>,
+    execution_id: String,
+    direction: Option<StepDirection>,
+) -> Result<TraceFrame, String> {
+    let direction = direction.unwrap_or(StepDirection::Next);
+    
+    let cached = direction != StepDirection::Restart && TRACE_CURSORS.lock().contains_key(&execution_id);
+    if !cached {
+        let client = get_workflow_client(&state)?;
+        let trace = client
+            .get_execution_trace(&execution_id)
+            .await
+            .map_err(|e| format!("获取执行追踪失败: {}", e))?;
+        
+        let mut cursors = TRACE_CURSORS.lock();
+        if cursors.len() >= MAX_TRACE_CURSORS && !cursors.contains_key(&execution_id) {
+            if let Some(evicted) = cursors.keys().next().cloned() {
+                cursors.remove(&evicted);
+            }
+        }
+        cursors.insert(execution_id.clone(), TraceCursor::new(trace));
+    }
+    
+    let mut cursors = TRACE_CURSORS.lock();
+    let cursor = cursors
+        .get_mut(&execution_id)
+        .ok_or_else(|| format!("执行追踪不存在: {}", execution_id))?;
+    Ok(cursor.step(direction))
+}
+
+// ================================
+// 工作流调试
+// ================================
+
+/// 获取调试帧：暂停时包含当前节点与变量作用域（通过 Python API）
+#[tauri::command]
+pub async fn get_debug_frame(
+    state: State<'_, AppState>,
+    execution_id: String,
+) -> Result<DebugFrame, String> {
+    debug!("API: 获取调试帧 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .get_debug_frame(&execution_id)
+        .await
+        .map_err(|e| format!("获取调试帧失败: {}", e))
+}
+
+/// 设置断点（通过 Python API）
+#[tauri::command]
+pub async fn set_debug_breakpoints(
+    state: State<'_, AppState>,
+    execution_id: String,
+    breakpoints: Vec<String>,
+) -> Result<DebugFrame, String> {
+    info!("API: 设置断点 - {} ({} 个)", execution_id, breakpoints.len());
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .set_debug_breakpoints(&execution_id, breakpoints)
+        .await
+        .map_err(|e| format!("设置断点失败: {}", e))
+}
+
+/// 修改暂停中执行的变量（通过 Python API）
+#[tauri::command]
+pub async fn update_debug_variables(
+    state: State<'_, AppState>,
+    execution_id: String,
+    variables: HashMap<String, JsonValue>,
+    remove: Option<Vec<String>>,
+) -> Result<DebugFrame, String> {
+    info!("API: 修改调试变量 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .update_debug_variables(&execution_id, variables, remove.unwrap_or_default())
+        .await
+        .map_err(|e| format!("修改调试变量失败: {}", e))
+}
+
+/// 继续执行到下一个断点（通过 Python API）
+#[tauri::command]
+pub async fn debug_continue(
+    state: State<'_, AppState>,
+    execution_id: String,
+) -> Result<DebugFrame, String> {
+    info!("API: 继续执行 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .debug_continue(&execution_id)
+        .await
+        .map_err(|e| format!("继续执行失败: {}", e))
+}
+
+/// 单步执行：执行当前节点后在下一个节点前暂停（通过 Python API）
+#[tauri::command]
+pub async fn debug_step(
+    state: State<'_, AppState>,
+    execution_id: String,
+) -> Result<DebugFrame, String> {
+    info!("API: 单步执行 - {}", execution_id);
+    
+    let client = get_workflow_client(&state)?;
+    
+    client
+        .debug_step(&execution_id)
+        .await
+        .map_err(|e| format!("单步执行失败: {}", e))
+}
+
 // ================================
 // 工作流状态管理
 // ================================
@@ -345,6 +580,117 @@ pub async fn api_create_from_template(
         .map_err(|e| format!("从模板创建工作流失败: {}", e))
 }
 
+// ================================
+// 工作流导出
+// ================================
+
+/// 查找节点引用的提示词与模板，返回内嵌资源与未找到的 ID
+async fn collect_embedded_assets(
+    client: &WorkflowApiClient,
+    definition: &JsonValue,
+) -> (Vec<EmbeddedPrompt>, Vec<EmbeddedTemplate>, Vec<String>) {
+    let refs = workflow_export::collect_references(definition);
+    let mut prompts = Vec::new();
+    let mut templates = Vec::new();
+    let mut missing = Vec::new();
+
+    if !refs.prompt_ids.is_empty() {
+        match crate::database::get_database() {
+            Some(db) => {
+                for prompt_id in &refs.prompt_ids {
+                    match db.prompt_registry.get_prompt(prompt_id).await {
+                        Ok(Some(prompt)) => prompts.push(EmbeddedPrompt {
+                            id: prompt.id,
+                            name: prompt.name,
+                            content: prompt.content,
+                            description: prompt.description,
+                            character_setting: prompt.character_setting,
+                        }),
+                        Ok(None) => missing.push(prompt_id.clone()),
+                        Err(e) => {
+                            error!("导出时读取Prompt失败: {} - {}", prompt_id, e);
+                            missing.push(prompt_id.clone());
+                        }
+                    }
+                }
+            }
+            None => missing.extend(refs.prompt_ids.iter().cloned()),
+        }
+    }
+
+    if !refs.template_ids.is_empty() {
+        let available = client.list_templates(100).await.unwrap_or_else(|e| {
+            error!("导出时获取模板列表失败: {}", e);
+            Vec::new()
+        });
+        for template_id in &refs.template_ids {
+            match available.iter().find(|t| &t.id == template_id) {
+                Some(template) => templates.push(EmbeddedTemplate {
+                    id: template.id.clone(),
+                    name: template.name.clone(),
+                    description: template.description.clone(),
+                    definition: template.definition.clone(),
+                }),
+                None => missing.push(template_id.clone()),
+            }
+        }
+    }
+
+    (prompts, templates, missing)
+}
+
+/// 导出工作流：JSON 包（内嵌引用的提示词与模板）或简单线性工作流的 shell / Python 脚本
+#[tauri::command]
+pub async fn export_workflow(
+    state: State<'_, AppState>,
+    workflow_id: String,
+    format: ExportFormat,
+) -> Result<WorkflowExport, String> {
+    info!("API: 导出工作流 - {} ({:?})", workflow_id, format);
+    
+    let client = get_workflow_client(&state)?;
+    
+    let workflow = client
+        .get_workflow(&workflow_id)
+        .await
+        .map_err(|e| format!("获取工作流详情失败: {}", e))?;
+    let exported = ExportedWorkflow {
+        name: workflow.name,
+        slug: workflow.slug,
+        description: workflow.description,
+        category: workflow.category,
+        tags: workflow.tags,
+        trigger_type: workflow.trigger_type,
+        trigger_config: workflow.trigger_config,
+        definition: workflow.definition,
+    };
+    
+    let content = match format {
+        ExportFormat::Json => {
+            let (prompts, templates, missing_references) =
+                collect_embedded_assets(&client, &exported.definition).await;
+            let bundle = WorkflowBundle {
+                format_version: workflow_export::BUNDLE_FORMAT_VERSION,
+                exported_at: chrono::Utc::now().to_rfc3339(),
+                workflow: exported.clone(),
+                prompts,
+                templates,
+                missing_references,
+            };
+            serde_json::to_string_pretty(&bundle)
+                .map_err(|e| format!("序列化工作流导出包失败: {}", e))?
+        }
+        ExportFormat::Shell => workflow_export::render_shell(&exported)?,
+        ExportFormat::Python => workflow_export::render_python(&exported)?,
+    };
+    
+    Ok(WorkflowExport {
+        format,
+        file_name: workflow_export::file_name(&exported, format),
+        content,
+    })
+}
+
 // ================================
 // 健康检查
 // ================================
@@ -0,0 +1,210 @@
+//! # 工作流定时触发命令模块
+//!
+//! `schedule_workflow` 为工作流添加 cron 定时触发（规则见 [`crate::utils::workflow_schedule`]），
+//! 触发保存在 `WorkflowRegistry` 中，重启后继续生效。后台任务到期时以 `scheduled` 方式执行工作流：
+//!
+//! - `workflow-schedule-started`：开始执行
+//! - `workflow-schedule-completed`：执行结束，附带执行 ID、状态与错误
+//!
+//! 应用未运行期间错过的触发在启动后只补执行一次。安全模式下定时执行会被拒绝并记录为失败。
+
+use chrono::{Local, Utc};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::commands::workflow_api;
+use crate::database::workflow::WorkflowSchedule;
+use crate::http::workflow_client::ExecuteWorkflowRequest;
+use crate::utils::watchdog;
+use crate::utils::workflow_schedule::{next_run, parse_cron, validate_cron};
+
+/// 检查到期触发的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const CHECK_STALL_AFTER: Duration = Duration::from_secs(5 * 60);
+/// 定时执行的执行方式
+const EXECUTION_MODE: &str = "scheduled";
+
+fn next_run_from_now(cron: &str) -> Option<i64> {
+    match parse_cron(cron) {
+        Ok(schedule) => next_run(&schedule, &Local::now()),
+        Err(e) => {
+            warn!("定时触发规则无效 ({}): {}", cron, e);
+            None
+        }
+    }
+}
+
+async fn run_schedule(app_handle: AppHandle, schedule: WorkflowSchedule) {
+    let started_at = Utc::now().timestamp();
+    let _ = app_handle.emit_all("workflow-schedule-started", serde_json::json!({
+        "schedule_id": schedule.id,
+        "workflow_id": schedule.workflow_id,
+        "started_at": started_at,
+    }));
+    info!("定时执行工作流: {} ({})", schedule.workflow_id, schedule.id);
+
+    let request = ExecuteWorkflowRequest {
+        input_data: schedule
+            .input_data
+            .clone()
+            .and_then(|data| serde_json::from_value::<HashMap<String, JsonValue>>(data).ok()),
+        execution_mode: EXECUTION_MODE.to_string(),
+        debug: false,
+        breakpoints: Vec::new(),
+    };
+    let (execution_id, status, error) = match workflow_api::execute(&app_handle, &schedule.workflow_id, request).await {
+        Ok(execution) => (Some(execution.id), execution.execution_status, execution.error_message),
+        Err(e) => (None, "error".to_string(), Some(e)),
+    };
+
+    if let Some(db) = crate::database::get_database() {
+        if let Err(e) = db.workflow_registry.record_schedule_result(&schedule.id, &status, error.as_deref()).await {
+            warn!("记录定时执行结果失败: {}", e);
+        }
+    }
+    let _ = app_handle.emit_all("workflow-schedule-completed", serde_json::json!({
+        "schedule_id": schedule.id,
+        "workflow_id": schedule.workflow_id,
+        "execution_id": execution_id,
+        "status": status,
+        "error": error,
+        "started_at": started_at,
+        "completed_at": Utc::now().timestamp(),
+    }));
+}
+
+/// 认领并执行到期的触发
+async fn run_due_schedules(app_handle: &AppHandle) {
+    let Some(db) = crate::database::get_database() else {
+        return;
+    };
+    let now = Utc::now().timestamp();
+    let due = match db.workflow_registry.due_schedules(now).await {
+        Ok(due) => due,
+        Err(e) => {
+            warn!("查询到期的定时触发失败: {}", e);
+            return;
+        }
+    };
+    for schedule in due {
+        let Some(expected) = schedule.next_run_at else {
+            continue;
+        };
+        // 先写入下次触发时间，执行时间较长也不会重复触发
+        let next_run_at = next_run_from_now(&schedule.cron);
+        match db.workflow_registry.claim_schedule_run(&schedule.id, expected, next_run_at, now).await {
+            Ok(true) => {
+                tauri::async_runtime::spawn(run_schedule(app_handle.clone(), schedule));
+            }
+            Ok(false) => {}
+            Err(e) => warn!("认领定时触发失败: {}", e),
+        }
+    }
+}
+
+/// 启动工作流定时触发任务
+pub fn start_workflow_scheduler(app_handle: AppHandle) {
+    watchdog::supervise("workflow_schedule", CHECK_STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            while heartbeat.beat() {
+                run_due_schedules(&app_handle).await;
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        });
+    });
+}
+
+// ================================
+// 命令
+// ================================
+
+/// 添加定时触发；指定 `schedule_id` 时修改已有触发（保留暂停状态）
+#[tauri::command]
+pub async fn schedule_workflow(
+    workflow_id: String,
+    cron: String,
+    input_data: Option<HashMap<String, JsonValue>>,
+    schedule_id: Option<String>,
+) -> Result<WorkflowSchedule, String> {
+    if workflow_id.trim().is_empty() {
+        return Err("工作流 ID 不能为空".to_string());
+    }
+    let cron = cron.split_whitespace().collect::<Vec<_>>().join(" ");
+    let parsed = validate_cron(&cron, &Local::now())?;
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+
+    let existing = match &schedule_id {
+        Some(id) => Some(
+            db.workflow_registry
+                .get_schedule(id)
+                .await
+                .map_err(|e| format!("读取定时触发失败: {}", e))?
+                .ok_or_else(|| format!("定时触发不存在: {}", id))?,
+        ),
+        None => None,
+    };
+    let paused = existing.as_ref().is_some_and(|s| s.paused);
+    let schedule = WorkflowSchedule {
+        id: schedule_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        workflow_id,
+        input_data: input_data
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| format!("序列化输入失败: {}", e))?,
+        paused,
+        next_run_at: if paused { None } else { next_run(&parsed, &Local::now()) },
+        last_run_at: existing.as_ref().and_then(|s| s.last_run_at),
+        last_status: existing.as_ref().and_then(|s| s.last_status.clone()),
+        last_error: existing.as_ref().and_then(|s| s.last_error.clone()),
+        created_at: existing.as_ref().map(|s| s.created_at).unwrap_or_else(|| Utc::now().timestamp()),
+        cron,
+    };
+    db.workflow_registry
+        .save_schedule(&schedule)
+        .await
+        .map_err(|e| format!("保存定时触发失败: {}", e))?;
+    info!("工作流 {} 已设置定时触发: {}", schedule.workflow_id, schedule.cron);
+    Ok(schedule)
+}
+
+/// 删除定时触发，返回是否存在
+#[tauri::command]
+pub async fn unschedule_workflow(schedule_id: String) -> Result<bool, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.workflow_registry
+        .delete_schedule(&schedule_id)
+        .await
+        .map_err(|e| format!("删除定时触发失败: {}", e))
+}
+
+/// 列出定时触发，可按工作流过滤
+#[tauri::command]
+pub async fn list_scheduled_workflows(workflow_id: Option<String>) -> Result<Vec<WorkflowSchedule>, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    db.workflow_registry
+        .list_schedules(workflow_id.as_deref())
+        .await
+        .map_err(|e| format!("读取定时触发失败: {}", e))
+}
+
+/// 暂停或恢复定时触发；恢复后从当前时间起计算下次触发，不补执行暂停期间的触发
+#[tauri::command]
+pub async fn pause_schedule(schedule_id: String, paused: bool) -> Result<WorkflowSchedule, String> {
+    let db = crate::database::get_database().ok_or("数据库未初始化")?;
+    let schedule = db
+        .workflow_registry
+        .get_schedule(&schedule_id)
+        .await
+        .map_err(|e| format!("读取定时触发失败: {}", e))?
+        .ok_or_else(|| format!("定时触发不存在: {}", schedule_id))?;
+    let next_run_at = if paused { None } else { next_run_from_now(&schedule.cron) };
+    db.workflow_registry
+        .set_schedule_paused(&schedule_id, paused, next_run_at)
+        .await
+        .map_err(|e| format!("更新定时触发失败: {}", e))?
+        .ok_or_else(|| format!("定时触发不存在: {}", schedule_id))
+}
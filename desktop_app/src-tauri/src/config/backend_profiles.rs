@@ -0,0 +1,285 @@
+//! 后端端点配置
+//!
+//! 自托管部署可以配置多个后端端点配置（名称、基础地址、认证方式），
+//! 并为不同功能（市场、工作流、技能等）指定不同的端点。
+//!
+//! 地址解析优先级：功能覆盖 > 当前激活的配置 > 环境变量 > [`ApiRouter`] 默认值。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::ApiRouter;
+
+lazy_static::lazy_static! {
+    static ref BACKEND_PROFILES: RwLock<BackendProfileSettings> =
+        RwLock::new(BackendProfileSettings::default());
+    /// 配置 ID -> 密钥链中的凭据（`None` 表示未保存），避免每次请求都读取密钥链
+    static ref CREDENTIAL_CACHE: RwLock<HashMap<String, Option<String>>> = RwLock::new(HashMap::new());
+}
+
+/// 认证方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendAuthMode {
+    /// 无认证
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`
+    Bearer,
+    /// `X-API-Key: <key>`
+    ApiKey,
+}
+
+/// 可单独指定端点的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendFeature {
+    /// 核心服务（对话、角色模板、适配器）
+    Core,
+    /// 适配器市场
+    Market,
+    /// 工作流
+    Workflow,
+    /// 技能
+    Skills,
+}
+
+impl BackendFeature {
+    /// 未配置任何端点时使用的默认地址
+    fn default_url(&self) -> String {
+        let router = ApiRouter::new();
+        match self {
+            BackendFeature::Market => std::env::var("ZISHU_BACKEND_URL")
+                .unwrap_or_else(|_| router.community_url()),
+            BackendFeature::Core => std::env::var("ZISHU_BACKEND_URL")
+                .unwrap_or_else(|_| router.core_url()),
+            BackendFeature::Workflow | BackendFeature::Skills => std::env::var("ZISHU_API_URL")
+                .unwrap_or_else(|_| router.core_url()),
+        }
+    }
+}
+
+/// 后端端点配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendProfile {
+    pub id: String,
+    pub name: String,
+    pub base_url: String,
+    #[serde(default)]
+    pub auth_mode: BackendAuthMode,
+}
+
+/// 后端端点配置集合
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackendProfileSettings {
+    #[serde(default)]
+    pub profiles: Vec<BackendProfile>,
+    /// 当前激活的配置ID
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// 功能 -> 配置ID
+    #[serde(default)]
+    pub feature_overrides: HashMap<BackendFeature, String>,
+}
+
+impl BackendProfileSettings {
+    /// 查找配置
+    pub fn profile(&self, id: &str) -> Option<&BackendProfile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// 获取某个功能实际使用的配置
+    pub fn profile_for(&self, feature: BackendFeature) -> Option<&BackendProfile> {
+        self.feature_overrides
+            .get(&feature)
+            .and_then(|id| self.profile(id))
+            .or_else(|| self.active_profile.as_deref().and_then(|id| self.profile(id)))
+    }
+
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::new();
+        for profile in &self.profiles {
+            if profile.id.trim().is_empty() {
+                return Err("端点配置ID不能为空".to_string());
+            }
+            if !seen.insert(profile.id.as_str()) {
+                return Err(format!("端点配置ID重复: {}", profile.id));
+            }
+            validate_base_url(&profile.base_url)?;
+        }
+
+        if let Some(active) = &self.active_profile {
+            if self.profile(active).is_none() {
+                return Err(format!("激活的端点配置不存在: {}", active));
+            }
+        }
+
+        for (feature, id) in &self.feature_overrides {
+            if self.profile(id).is_none() {
+                return Err(format!("功能 {:?} 引用的端点配置不存在: {}", feature, id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 校验基础地址
+pub fn validate_base_url(base_url: &str) -> Result<(), String> {
+    let url = url::Url::parse(base_url).map_err(|e| format!("无效的后端地址 {}: {}", base_url, e))?;
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(format!("不支持的协议: {}", scheme)),
+    }
+}
+
+/// 获取当前端点配置
+pub fn current_settings() -> BackendProfileSettings {
+    BACKEND_PROFILES.read().clone()
+}
+
+/// 替换端点配置（运行时生效）
+pub fn set_settings(settings: BackendProfileSettings) -> Result<(), String> {
+    settings.validate()?;
+    *BACKEND_PROFILES.write() = settings;
+    Ok(())
+}
+
+/// 获取某个功能的后端基础地址
+pub fn base_url_for(feature: BackendFeature) -> String {
+    BACKEND_PROFILES
+        .read()
+        .profile_for(feature)
+        .map(|p| p.base_url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| feature.default_url())
+}
+
+/// 密钥链中保存认证凭据的条目名
+pub fn credential_key(profile_id: &str) -> String {
+    format!("backend_profile_{}", profile_id)
+}
+
+/// 凭据更新或删除后清除缓存，下次请求重新读取密钥链
+pub fn invalidate_credential(profile_id: &str) {
+    CREDENTIAL_CACHE.write().remove(profile_id);
+}
+
+/// 读取配置的凭据；密钥链暂时不可用时不缓存结果
+fn cached_credential(profile_id: &str) -> Option<String> {
+    if let Some(secret) = CREDENTIAL_CACHE.read().get(profile_id) {
+        return secret.clone();
+    }
+
+    let secret = match crate::utils::keyring_store::try_load(&credential_key(profile_id)) {
+        Ok(secret) => secret,
+        Err(e) => {
+            tracing::warn!("读取后端认证凭据失败: {}", e);
+            return None;
+        }
+    };
+    CREDENTIAL_CACHE.write().insert(profile_id.to_string(), secret.clone());
+    secret
+}
+
+/// 获取某个功能的认证方式与凭据
+pub fn credentials_for(feature: BackendFeature) -> Option<(BackendAuthMode, String)> {
+    let profile = BACKEND_PROFILES.read().profile_for(feature).cloned()?;
+    if profile.auth_mode == BackendAuthMode::None {
+        return None;
+    }
+    cached_credential(&profile.id).map(|secret| (profile.auth_mode, secret))
+}
+
+/// 获取某个功能的 Bearer 令牌（供基于 [`crate::http::ApiClient`] 的客户端使用）
+pub fn bearer_token_for(feature: BackendFeature) -> Option<String> {
+    credentials_for(feature)
+        .filter(|(mode, _)| *mode == BackendAuthMode::Bearer)
+        .map(|(_, token)| token)
+}
+
+/// 创建已附带该功能认证信息的 HTTP 客户端；请求应通过 [`crate::http::trust_store::PinnedSend`] 发送以校验证书固定
+pub fn client_for(feature: BackendFeature) -> reqwest::Client {
+    let Some((mode, secret)) = credentials_for(feature) else {
+        return crate::http::proxy::client();
+    };
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    let (name, value) = match mode {
+        BackendAuthMode::Bearer => (reqwest::header::AUTHORIZATION, format!("Bearer {}", secret)),
+        BackendAuthMode::ApiKey => (reqwest::header::HeaderName::from_static("x-api-key"), secret),
+        BackendAuthMode::None => return crate::http::proxy::client(),
+    };
+    match reqwest::header::HeaderValue::from_str(&value) {
+        Ok(mut value) => {
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+        Err(e) => tracing::warn!("后端认证凭据包含无效字符: {}", e),
+    }
+
+    crate::http::proxy::client_builder()
+        .default_headers(headers)
+        .build()
+        .unwrap_or_else(|_| crate::http::proxy::client())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(id: &str, base_url: &str) -> BackendProfile {
+        BackendProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            base_url: base_url.to_string(),
+            auth_mode: BackendAuthMode::None,
+        }
+    }
+
+    #[test]
+    fn test_profile_for_prefers_feature_override() {
+        let mut settings = BackendProfileSettings {
+            profiles: vec![
+                profile("main", "https://zishu.example.com"),
+                profile("market", "https://market.example.com"),
+            ],
+            active_profile: Some("main".to_string()),
+            ..Default::default()
+        };
+        settings.feature_overrides.insert(BackendFeature::Market, "market".to_string());
+
+        assert_eq!(settings.profile_for(BackendFeature::Market).unwrap().id, "market");
+        assert_eq!(settings.profile_for(BackendFeature::Workflow).unwrap().id, "main");
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_settings() {
+        let duplicate = BackendProfileSettings {
+            profiles: vec![profile("a", "http://a"), profile("a", "http://b")],
+            ..Default::default()
+        };
+        assert!(duplicate.validate().is_err());
+
+        let bad_scheme = BackendProfileSettings {
+            profiles: vec![profile("a", "ftp://a")],
+            ..Default::default()
+        };
+        assert!(bad_scheme.validate().is_err());
+
+        let dangling = BackendProfileSettings {
+            active_profile: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(dangling.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalidate_credential_clears_cache() {
+        CREDENTIAL_CACHE.write().insert("cached".to_string(), Some("secret".to_string()));
+        assert_eq!(cached_credential("cached").as_deref(), Some("secret"));
+        invalidate_credential("cached");
+        assert!(!CREDENTIAL_CACHE.read().contains_key("cached"));
+    }
+}
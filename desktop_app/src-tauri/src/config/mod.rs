@@ -3,6 +3,8 @@
 //! 包含应用的各种配置
 
 pub mod api_router;
+pub mod backend_profiles;
 
 // 重新导出常用类型
 pub use api_router::{ApiBackend, ApiRouter};
+pub use backend_profiles::BackendFeature;
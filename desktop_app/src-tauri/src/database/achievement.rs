@@ -0,0 +1,325 @@
+//! 成就数据模块
+//!
+//! 成就由计数指标或连续活跃天数判定。内置成就覆盖聊天与工作流，
+//! 适配器可以注册自己的成就（指标名以 `adapter:<适配器ID>:` 为前缀），定义同样持久化。
+
+use chrono::{Duration, NaiveDate};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 发送的聊天消息数
+pub const METRIC_MESSAGES_SENT: &str = "messages_sent";
+/// 工作流运行次数
+pub const METRIC_WORKFLOW_RUNS: &str = "workflow_runs";
+/// 内置成就的来源
+pub const BUILTIN_SOURCE: &str = "builtin";
+/// 计算连续天数时最多读取的活跃日期数
+const MAX_ACTIVITY_DAYS: i64 = 400;
+
+fn builtin_source() -> String {
+    BUILTIN_SOURCE.to_string()
+}
+
+/// 成就判定条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AchievementCriterion {
+    /// 指标累计达到目标值
+    Counter { metric: String, target: i64 },
+    /// 连续活跃（发送过消息）的天数
+    Streak { days: i64 },
+}
+
+impl AchievementCriterion {
+    pub fn target(&self) -> i64 {
+        match self {
+            Self::Counter { target, .. } => *target,
+            Self::Streak { days } => *days,
+        }
+    }
+
+    /// 当前进度（不超过目标值）
+    pub fn progress(&self, counters: &HashMap<String, i64>, streak: i64) -> i64 {
+        let current = match self {
+            Self::Counter { metric, .. } => counters.get(metric).copied().unwrap_or(0),
+            Self::Streak { .. } => streak,
+        };
+        current.min(self.target())
+    }
+}
+
+/// 成就定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AchievementDefinition {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    pub criterion: AchievementCriterion,
+    /// 未解锁前不向用户展示说明
+    #[serde(default)]
+    pub hidden: bool,
+    /// 来源（`builtin` 或 `adapter:<适配器ID>`）
+    #[serde(default = "builtin_source")]
+    pub source: String,
+}
+
+impl AchievementDefinition {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() || self.title.trim().is_empty() {
+            return Err("成就 ID 和标题不能为空".to_string());
+        }
+        if self.criterion.target() <= 0 {
+            return Err("成就目标必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn builtin(id: &str, title: &str, description: &str, icon: &str, criterion: AchievementCriterion) -> AchievementDefinition {
+    AchievementDefinition {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: description.to_string(),
+        icon: Some(icon.to_string()),
+        criterion,
+        hidden: false,
+        source: builtin_source(),
+    }
+}
+
+/// 内置成就
+pub fn builtin_definitions() -> Vec<AchievementDefinition> {
+    vec![
+        builtin(
+            "first_chat",
+            "初次见面",
+            "发送第一条消息",
+            "💬",
+            AchievementCriterion::Counter { metric: METRIC_MESSAGES_SENT.to_string(), target: 1 },
+        ),
+        builtin(
+            "messages_100",
+            "无话不谈",
+            "累计发送 100 条消息",
+            "📚",
+            AchievementCriterion::Counter { metric: METRIC_MESSAGES_SENT.to_string(), target: 100 },
+        ),
+        builtin(
+            "streak_7",
+            "形影不离",
+            "连续 7 天聊天",
+            "🔥",
+            AchievementCriterion::Streak { days: 7 },
+        ),
+        builtin(
+            "first_workflow",
+            "自动化入门",
+            "第一次运行工作流",
+            "⚙️",
+            AchievementCriterion::Counter { metric: METRIC_WORKFLOW_RUNS.to_string(), target: 1 },
+        ),
+    ]
+}
+
+/// 截至 `today` 的连续活跃天数（今天尚未活跃时从昨天起算）
+pub fn current_streak(days: &[NaiveDate], today: NaiveDate) -> i64 {
+    let days: HashSet<NaiveDate> = days.iter().copied().collect();
+    let mut cursor = if days.contains(&today) { today } else { today - Duration::days(1) };
+    let mut streak = 0;
+    while days.contains(&cursor) {
+        streak += 1;
+        cursor -= Duration::days(1);
+    }
+    streak
+}
+
+/// 成就状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AchievementStatus {
+    #[serde(flatten)]
+    pub definition: AchievementDefinition,
+    pub progress: i64,
+    pub target: i64,
+    /// 解锁时间（Unix 秒）
+    pub unlocked_at: Option<i64>,
+}
+
+/// 成就数据表
+pub struct AchievementRegistry {
+    pool: Pool,
+}
+
+impl AchievementRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS achievement_counters (
+                    metric TEXT PRIMARY KEY,
+                    value BIGINT NOT NULL DEFAULT 0,
+                    updated_at BIGINT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS achievement_activity_days (
+                    day DATE PRIMARY KEY
+                );
+                CREATE TABLE IF NOT EXISTS achievement_unlocks (
+                    achievement_id TEXT PRIMARY KEY,
+                    unlocked_at BIGINT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS achievement_definitions (
+                    id TEXT PRIMARY KEY,
+                    source TEXT NOT NULL,
+                    definition JSONB NOT NULL,
+                    created_at BIGINT NOT NULL
+                );",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 累加指标，返回新值
+    pub async fn increment(
+        &self,
+        metric: &str,
+        amount: i64,
+        now: i64,
+    ) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO achievement_counters (metric, value, updated_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (metric) DO UPDATE
+                 SET value = achievement_counters.value + EXCLUDED.value, updated_at = EXCLUDED.updated_at
+                 RETURNING value",
+                &[&metric, &amount, &now],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    /// 所有指标的当前值
+    pub async fn counters(&self) -> Result<HashMap<String, i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client.query("SELECT metric, value FROM achievement_counters", &[]).await?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    /// 记录活跃日期
+    pub async fn record_active_day(&self, day: NaiveDate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO achievement_activity_days (day) VALUES ($1) ON CONFLICT (day) DO NOTHING",
+                &[&day],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 最近的活跃日期
+    pub async fn recent_active_days(&self) -> Result<Vec<NaiveDate>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT day FROM achievement_activity_days ORDER BY day DESC LIMIT $1",
+                &[&MAX_ACTIVITY_DAYS],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// 已解锁的成就及解锁时间
+    pub async fn unlocks(&self) -> Result<HashMap<String, i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query("SELECT achievement_id, unlocked_at FROM achievement_unlocks", &[])
+            .await?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    /// 解锁成就，已解锁时返回 false
+    pub async fn unlock(&self, achievement_id: &str, now: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let inserted = client
+            .execute(
+                "INSERT INTO achievement_unlocks (achievement_id, unlocked_at) VALUES ($1, $2)
+                 ON CONFLICT (achievement_id) DO NOTHING",
+                &[&achievement_id, &now],
+            )
+            .await?;
+        Ok(inserted == 1)
+    }
+
+    /// 保存注册的成就定义
+    pub async fn save_definition(
+        &self,
+        definition: &AchievementDefinition,
+        now: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let json = serde_json::to_value(definition)?;
+        client
+            .execute(
+                "INSERT INTO achievement_definitions (id, source, definition, created_at) VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (id) DO UPDATE SET source = EXCLUDED.source, definition = EXCLUDED.definition",
+                &[&definition.id, &definition.source, &json, &now],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 注册的成就定义（不含内置成就）
+    pub async fn definitions(&self) -> Result<Vec<AchievementDefinition>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query("SELECT definition FROM achievement_definitions ORDER BY created_at, id", &[])
+            .await?;
+        rows.iter()
+            .map(|row| Ok(serde_json::from_value(row.get::<_, serde_json::Value>(0))?))
+            .collect()
+    }
+
+    /// 删除某个来源注册的成就定义（已解锁记录保留）
+    pub async fn delete_definitions_by_source(&self, source: &str) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        Ok(client
+            .execute("DELETE FROM achievement_definitions WHERE source = $1", &[&source])
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn test_current_streak() {
+        let days = vec![day(10), day(9), day(8), day(6), day(5)];
+        assert_eq!(current_streak(&days, day(10)), 3);
+        // 今天还没聊天时，昨天为止的连续天数仍然有效
+        assert_eq!(current_streak(&days, day(11)), 3);
+        assert_eq!(current_streak(&days, day(12)), 0);
+        assert_eq!(current_streak(&[], day(1)), 0);
+    }
+
+    #[test]
+    fn test_criterion_progress() {
+        let counters: HashMap<String, i64> = [(METRIC_MESSAGES_SENT.to_string(), 42)].into_iter().collect();
+        let definitions = builtin_definitions();
+        let progress: Vec<i64> = definitions.iter().map(|d| d.criterion.progress(&counters, 3)).collect();
+        assert_eq!(progress, vec![1, 42, 3, 0]);
+        assert!(definitions.iter().all(|d| d.validate().is_ok()));
+    }
+}
@@ -0,0 +1,433 @@
+//! 数据库备份与恢复演练
+//!
+//! 备份为 gzip 压缩的 JSON 文件：清单记录每张表的行数与 SHA-256 校验和，表数据为
+//! `row_to_json` 导出的行。验证时把备份恢复到临时 schema（结构复制自当前表，含主键和
+//! 唯一约束），核对行数、校验和与表间引用，结束后删除临时 schema，并记录验证结果。
+//! 表名按连接的 `search_path` 解析，因此备份的是当前用户资料的表。
+
+use deadpool_postgres::Pool;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 备份文件格式版本
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+/// 备份文件扩展名
+pub const BACKUP_EXTENSION: &str = "json.gz";
+
+/// 参与备份的用户数据表（日志、性能指标等可再生数据不备份）
+pub const BACKUP_TABLES: &[&str] = &[
+    "characters",
+    "character_configs",
+    "character_motions",
+    "character_expressions",
+    "conversations",
+    "messages",
+    "message_revisions",
+    "model_configs",
+    "prompts",
+    "workflows",
+    "installed_adapters",
+    "achievement_counters",
+    "achievement_activity_days",
+    "achievement_unlocks",
+    "achievement_definitions",
+    "pet_interactions",
+    "app_sessions",
+];
+
+/// 恢复后检查的表间引用：(子表, 列, 父表, 父表列)
+const REFERENCE_CHECKS: &[(&str, &str, &str, &str)] = &[
+    ("messages", "conversation_id", "conversations", "id"),
+    ("message_revisions", "message_id", "messages", "id"),
+    ("character_configs", "character_id", "characters", "id"),
+    ("character_motions", "character_id", "characters", "id"),
+    ("character_expressions", "character_id", "characters", "id"),
+];
+
+/// 单表清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableManifest {
+    pub name: String,
+    pub rows: usize,
+    pub sha256: String,
+}
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: i64,
+    pub tables: Vec<TableManifest>,
+}
+
+/// 备份文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub manifest: BackupManifest,
+    pub tables: BTreeMap<String, Vec<Value>>,
+}
+
+/// 表数据的校验和
+pub fn table_checksum(rows: &[Value]) -> String {
+    let mut hasher = Sha256::new();
+    for row in rows {
+        hasher.update(row.to_string().as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+impl BackupFile {
+    pub fn new(created_at: i64, tables: BTreeMap<String, Vec<Value>>) -> Self {
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            created_at,
+            tables: tables
+                .iter()
+                .map(|(name, rows)| TableManifest { name: name.clone(), rows: rows.len(), sha256: table_checksum(rows) })
+                .collect(),
+        };
+        Self { manifest, tables }
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let reader = GzDecoder::new(BufReader::new(File::open(path)?));
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// 清单与数据不一致之处
+    pub fn manifest_mismatches(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.manifest.format_version > BACKUP_FORMAT_VERSION {
+            problems.push(format!("不支持的备份格式版本: {}", self.manifest.format_version));
+        }
+        for table in &self.manifest.tables {
+            match self.tables.get(&table.name) {
+                None => problems.push(format!("{}: 备份中缺少表数据", table.name)),
+                Some(rows) if rows.len() != table.rows => {
+                    problems.push(format!("{}: 行数 {} 与清单 {} 不符", table.name, rows.len(), table.rows))
+                }
+                Some(rows) if table_checksum(rows) != table.sha256 => {
+                    problems.push(format!("{}: 校验和不匹配", table.name))
+                }
+                Some(_) => {}
+            }
+        }
+        problems
+    }
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+impl IntegrityCheck {
+    fn new(name: impl Into<String>, problem: Option<String>) -> Self {
+        Self { name: name.into(), passed: problem.is_none(), detail: problem }
+    }
+}
+
+/// 恢复演练结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub backup_file: String,
+    pub backup_created_at: Option<i64>,
+    pub verified_at: i64,
+    pub success: bool,
+    pub duration_ms: i64,
+    pub tables_restored: usize,
+    pub rows_restored: usize,
+    pub checks: Vec<IntegrityCheck>,
+}
+
+impl VerificationReport {
+    /// 演练无法执行时的失败记录
+    pub fn failed(backup_file: String, error: String) -> Self {
+        Self {
+            backup_file,
+            backup_created_at: None,
+            verified_at: chrono::Utc::now().timestamp(),
+            success: false,
+            duration_ms: 0,
+            tables_restored: 0,
+            rows_restored: 0,
+            checks: vec![IntegrityCheck::new("执行恢复演练", Some(error))],
+        }
+    }
+
+    fn finish(mut self, started: Instant) -> Self {
+        self.success = !self.checks.is_empty() && self.checks.iter().all(|check| check.passed);
+        self.duration_ms = started.elapsed().as_millis() as i64;
+        self
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// 备份与恢复演练
+pub struct BackupRegistry {
+    pool: Pool,
+}
+
+impl BackupRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS backup_verifications (
+                    id BIGSERIAL PRIMARY KEY,
+                    backup_file TEXT NOT NULL,
+                    verified_at BIGINT NOT NULL,
+                    success BOOLEAN NOT NULL,
+                    report JSONB NOT NULL
+                );",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 导出用户数据表并写入 `dir`，返回备份文件路径
+    pub async fn create_backup(
+        &self,
+        dir: &Path,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(PathBuf, BackupManifest), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let mut tables = BTreeMap::new();
+        for table in BACKUP_TABLES {
+            let exists: bool = client
+                .query_one("SELECT to_regclass($1) IS NOT NULL", &[&table])
+                .await?
+                .get(0);
+            if !exists {
+                continue;
+            }
+            let sql = format!(
+                "SELECT COALESCE(json_agg(row_to_json(t)), '[]'::json) FROM {} t",
+                quote_ident(table)
+            );
+            let rows: Value = client.query_one(sql.as_str(), &[]).await?.get(0);
+            let rows = match rows {
+                Value::Array(rows) => rows,
+                _ => Vec::new(),
+            };
+            tables.insert(table.to_string(), rows);
+        }
+
+        let backup = BackupFile::new(now.timestamp(), tables);
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("zishu-db-{}.{}", now.format("%Y%m%d-%H%M%S"), BACKUP_EXTENSION));
+        let temp = path.with_extension("partial");
+        backup.write_to(&temp)?;
+        std::fs::rename(&temp, &path)?;
+        Ok((path, backup.manifest))
+    }
+
+    /// 把备份恢复到临时 schema 并检查完整性
+    pub async fn verify_backup(&self, path: &Path) -> Result<VerificationReport, Box<dyn std::error::Error + Send + Sync>> {
+        let started = Instant::now();
+        let mut report = VerificationReport {
+            backup_file: path.to_string_lossy().into_owned(),
+            backup_created_at: None,
+            verified_at: chrono::Utc::now().timestamp(),
+            success: false,
+            duration_ms: 0,
+            tables_restored: 0,
+            rows_restored: 0,
+            checks: Vec::new(),
+        };
+
+        let file = path.to_path_buf();
+        let backup = match tokio::task::spawn_blocking(move || BackupFile::read_from(&file)).await? {
+            Ok(backup) => backup,
+            Err(e) => {
+                report.checks.push(IntegrityCheck::new("读取备份文件", Some(e.to_string())));
+                return Ok(report.finish(started));
+            }
+        };
+        report.backup_created_at = Some(backup.manifest.created_at);
+        let mismatches = backup.manifest_mismatches();
+        report.checks.push(IntegrityCheck::new(
+            "清单校验",
+            (!mismatches.is_empty()).then(|| mismatches.join("; ")),
+        ));
+
+        let schema = format!("zishu_restore_{}", uuid::Uuid::new_v4().simple());
+        let mut client = self.pool.get().await?;
+        client.batch_execute(&format!("CREATE SCHEMA {}", quote_ident(&schema))).await?;
+        let outcome = match self.restore_into(&mut client, &schema, &backup, &mut report).await {
+            Ok(()) => self.check_references(&client, &schema, &backup, &mut report).await,
+            Err(e) => {
+                report.checks.push(IntegrityCheck::new("恢复到临时 schema", Some(e.to_string())));
+                Ok(())
+            }
+        };
+        // 无论检查是否出错都清理临时 schema
+        client
+            .batch_execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE", quote_ident(&schema)))
+            .await?;
+        outcome?;
+        Ok(report.finish(started))
+    }
+
+    async fn restore_into(
+        &self,
+        client: &mut deadpool_postgres::Client,
+        schema: &str,
+        backup: &BackupFile,
+        report: &mut VerificationReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let transaction = client.transaction().await?;
+        for (table, rows) in &backup.tables {
+            let target = format!("{}.{}", quote_ident(schema), quote_ident(table));
+            // 当前版本已删除的表无法复制结构，记为失败
+            transaction
+                .batch_execute(&format!("CREATE TABLE {} (LIKE {} INCLUDING ALL)", target, quote_ident(table)))
+                .await
+                .map_err(|e| format!("{}: 创建表失败: {}", table, e))?;
+            let payload = Value::Array(rows.clone());
+            transaction
+                .execute(
+                    format!("INSERT INTO {0} SELECT * FROM json_populate_recordset(NULL::{0}, $1::json)", target).as_str(),
+                    &[&payload],
+                )
+                .await
+                .map_err(|e| format!("{}: 写入数据失败: {}", table, e))?;
+            let count: i64 = transaction
+                .query_one(format!("SELECT COUNT(*) FROM {}", target).as_str(), &[])
+                .await?
+                .get(0);
+            report.checks.push(IntegrityCheck::new(
+                format!("恢复 {}", table),
+                (count as usize != rows.len()).then(|| format!("恢复 {} 行，备份中有 {} 行", count, rows.len())),
+            ));
+            report.tables_restored += 1;
+            report.rows_restored += count as usize;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn check_references(
+        &self,
+        client: &deadpool_postgres::Client,
+        schema: &str,
+        backup: &BackupFile,
+        report: &mut VerificationReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for (child, column, parent, parent_column) in REFERENCE_CHECKS {
+            if !backup.tables.contains_key(*child) || !backup.tables.contains_key(*parent) {
+                continue;
+            }
+            let sql = format!(
+                "SELECT COUNT(*) FROM {s}.{c} c WHERE NOT EXISTS (SELECT 1 FROM {s}.{p} p WHERE p.{pc} = c.{cc})",
+                s = quote_ident(schema),
+                c = quote_ident(child),
+                p = quote_ident(parent),
+                pc = quote_ident(parent_column),
+                cc = quote_ident(column),
+            );
+            let orphans: i64 = client.query_one(sql.as_str(), &[]).await?.get(0);
+            report.checks.push(IntegrityCheck::new(
+                format!("引用 {}.{} → {}", child, column, parent),
+                (orphans > 0).then(|| format!("{} 行引用了不存在的记录", orphans)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 记录验证结果
+    pub async fn record_verification(&self, report: &VerificationReport) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let json = serde_json::to_value(report)?;
+        client
+            .execute(
+                "INSERT INTO backup_verifications (backup_file, verified_at, success, report) VALUES ($1, $2, $3, $4)",
+                &[&report.backup_file, &report.verified_at, &report.success, &json],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 最近的验证结果（新记录在前）
+    pub async fn verifications(&self, limit: i64) -> Result<Vec<VerificationReport>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT report FROM backup_verifications ORDER BY verified_at DESC, id DESC LIMIT $1",
+                &[&limit],
+            )
+            .await?;
+        rows.iter()
+            .map(|row| Ok(serde_json::from_value(row.get::<_, Value>(0))?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> BackupFile {
+        let mut tables = BTreeMap::new();
+        tables.insert(
+            "conversations".to_string(),
+            vec![serde_json::json!({ "id": "c1", "title": "你好" })],
+        );
+        tables.insert(
+            "messages".to_string(),
+            vec![
+                serde_json::json!({ "id": "m1", "conversation_id": "c1", "content": "hi" }),
+                serde_json::json!({ "id": "m2", "conversation_id": "c1", "content": "yo" }),
+            ],
+        );
+        BackupFile::new(1_700_000_000, tables)
+    }
+
+    #[test]
+    fn test_backup_round_trip_preserves_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.json.gz");
+        sample().write_to(&path).unwrap();
+
+        let restored = BackupFile::read_from(&path).unwrap();
+        assert!(restored.manifest_mismatches().is_empty());
+        assert_eq!(restored.manifest.tables[1].rows, 2);
+    }
+
+    #[test]
+    fn test_detects_tampered_rows() {
+        let mut backup = sample();
+        backup.tables.get_mut("messages").unwrap()[0]["content"] = serde_json::json!("changed");
+        backup.tables.remove("conversations");
+        let problems = backup.manifest_mismatches();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.starts_with("messages: 校验和")));
+        assert!(problems.iter().any(|p| p.starts_with("conversations: 备份中缺少")));
+    }
+}
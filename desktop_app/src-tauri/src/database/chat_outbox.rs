@@ -0,0 +1,307 @@
+//! 聊天发件箱模块
+//!
+//! 后端不可达时 `send_message` 把消息写入发件箱，由 `commands::chat_outbox` 按指数退避重试，
+//! 直到送达或超过重试次数。
+
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+
+/// 首次重试前的等待时间（秒）
+const BASE_BACKOFF_SECS: i64 = 5;
+/// 重试等待时间上限（秒）
+const MAX_BACKOFF_SECS: i64 = 10 * 60;
+/// 超过该次数仍未送达时放弃
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// 发件箱消息状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// 等待（重新）发送
+    Pending,
+    /// 正在发送
+    Sending,
+    Delivered,
+    /// 已放弃，需要用户手动重试
+    Failed,
+}
+
+impl OutboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxStatus::Pending => "pending",
+            OutboxStatus::Sending => "sending",
+            OutboxStatus::Delivered => "delivered",
+            OutboxStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "sending" => OutboxStatus::Sending,
+            "delivered" => OutboxStatus::Delivered,
+            "failed" => OutboxStatus::Failed,
+            _ => OutboxStatus::Pending,
+        }
+    }
+}
+
+/// 发件箱中的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub session_id: Option<String>,
+    /// 原始的 `SendMessageInput`
+    pub payload: serde_json::Value,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl OutboxEntry {
+    fn from_row(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            session_id: row.get("session_id"),
+            payload: row.get("payload"),
+            status: OutboxStatus::parse(row.get("status")),
+            attempts: row.get("attempts"),
+            next_attempt_at: row.get("next_attempt_at"),
+            last_error: row.get("last_error"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+}
+
+/// 第 `attempts` 次失败后的等待时间（秒）：5s、10s、20s……，最多 10 分钟
+pub fn backoff_secs(attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    (BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS)
+}
+
+/// 失败后的下一状态与下次尝试时间；超过重试次数时为 `Failed`
+pub fn after_failure(attempts: i32, now: i64) -> (OutboxStatus, i64) {
+    if attempts >= MAX_ATTEMPTS {
+        (OutboxStatus::Failed, now)
+    } else {
+        (OutboxStatus::Pending, now + backoff_secs(attempts))
+    }
+}
+
+/// 聊天发件箱数据表
+pub struct ChatOutboxRegistry {
+    pool: Pool,
+}
+
+impl ChatOutboxRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS chat_outbox (
+                    id TEXT PRIMARY KEY,
+                    session_id TEXT,
+                    payload JSONB NOT NULL,
+                    status TEXT NOT NULL,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    next_attempt_at BIGINT NOT NULL,
+                    last_error TEXT,
+                    created_at BIGINT NOT NULL,
+                    updated_at BIGINT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_chat_outbox_due ON chat_outbox(status, next_attempt_at);",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 加入发件箱，第一次重试在退避时间之后
+    pub async fn enqueue(
+        &self,
+        id: &str,
+        session_id: Option<&str>,
+        payload: &serde_json::Value,
+        error: &str,
+        now: i64,
+    ) -> Result<OutboxEntry, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "INSERT INTO chat_outbox
+                    (id, session_id, payload, status, attempts, next_attempt_at, last_error, created_at, updated_at)
+                 VALUES ($1, $2, $3, 'pending', 1, $4, $5, $6, $6)
+                 RETURNING *",
+                &[&id, &session_id, payload, &(now + backoff_secs(1)), &error, &now],
+            )
+            .await?;
+        Ok(OutboxEntry::from_row(&row))
+    }
+
+    /// 取出到期的消息并标记为发送中（按加入顺序）
+    pub async fn claim_due(&self, now: i64, limit: i64) -> Result<Vec<OutboxEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "UPDATE chat_outbox SET status = 'sending', updated_at = $1
+                 WHERE id IN (
+                    SELECT id FROM chat_outbox
+                    WHERE status = 'pending' AND next_attempt_at <= $1
+                    ORDER BY created_at LIMIT $2
+                    FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING *",
+                &[&now, &limit],
+            )
+            .await?;
+        let mut entries: Vec<OutboxEntry> = rows.iter().map(OutboxEntry::from_row).collect();
+        entries.sort_by_key(|entry| entry.created_at);
+        Ok(entries)
+    }
+
+    /// 记录一次失败的尝试，返回更新后的消息
+    pub async fn record_failure(
+        &self,
+        id: &str,
+        error: &str,
+        now: i64,
+        permanent: bool,
+    ) -> Result<Option<OutboxEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(entry) = self.get(id).await? else {
+            return Ok(None);
+        };
+        let attempts = entry.attempts + 1;
+        let (status, next_attempt_at) = if permanent {
+            (OutboxStatus::Failed, now)
+        } else {
+            after_failure(attempts, now)
+        };
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "UPDATE chat_outbox
+                 SET status = $2, attempts = $3, next_attempt_at = $4, last_error = $5, updated_at = $6
+                 WHERE id = $1 RETURNING *",
+                &[&id, &status.as_str(), &attempts, &next_attempt_at, &error, &now],
+            )
+            .await?;
+        Ok(row.as_ref().map(OutboxEntry::from_row))
+    }
+
+    /// 标记为已送达
+    pub async fn mark_delivered(&self, id: &str, now: i64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE chat_outbox SET status = 'delivered', last_error = NULL, updated_at = $2 WHERE id = $1",
+                &[&id, &now],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 未尝试发送就放回等待（不计入重试次数）
+    pub async fn release(&self, id: &str, now: i64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE chat_outbox SET status = 'pending', updated_at = $2 WHERE id = $1 AND status = 'sending'",
+                &[&id, &now],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 立即重新排队（用于手动重试），重试次数清零
+    pub async fn requeue(&self, id: &str, now: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE chat_outbox SET status = 'pending', attempts = 0, next_attempt_at = $2, updated_at = $2
+                 WHERE id = $1 AND status IN ('pending', 'failed')",
+                &[&id, &now],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    /// 应用异常退出时遗留的发送中消息恢复为等待
+    pub async fn reset_sending(&self, now: i64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        Ok(client
+            .execute(
+                "UPDATE chat_outbox SET status = 'pending', next_attempt_at = $1, updated_at = $1
+                 WHERE status = 'sending'",
+                &[&now],
+            )
+            .await?)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<OutboxEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt("SELECT * FROM chat_outbox WHERE id = $1", &[&id]).await?;
+        Ok(row.as_ref().map(OutboxEntry::from_row))
+    }
+
+    /// 未送达的消息（可按会话过滤），按加入顺序
+    pub async fn undelivered(&self, session_id: Option<&str>) -> Result<Vec<OutboxEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT * FROM chat_outbox
+                 WHERE status <> 'delivered' AND ($1::TEXT IS NULL OR session_id = $1)
+                 ORDER BY created_at",
+                &[&session_id],
+            )
+            .await?;
+        Ok(rows.iter().map(OutboxEntry::from_row).collect())
+    }
+
+    pub async fn remove(&self, id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        Ok(client.execute("DELETE FROM chat_outbox WHERE id = $1", &[&id]).await? > 0)
+    }
+
+    /// 清理早于 `before` 的已送达消息
+    pub async fn purge_delivered(&self, before: i64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        Ok(client
+            .execute(
+                "DELETE FROM chat_outbox WHERE status = 'delivered' AND updated_at < $1",
+                &[&before],
+            )
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        assert_eq!(backoff_secs(1), 5);
+        assert_eq!(backoff_secs(2), 10);
+        assert_eq!(backoff_secs(4), 40);
+        assert_eq!(backoff_secs(0), 5);
+        assert_eq!(backoff_secs(20), MAX_BACKOFF_SECS);
+        assert_eq!(backoff_secs(i32::MAX), MAX_BACKOFF_SECS);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_attempts() {
+        assert_eq!(after_failure(1, 100), (OutboxStatus::Pending, 105));
+        assert_eq!(after_failure(MAX_ATTEMPTS - 1, 100).0, OutboxStatus::Pending);
+        assert_eq!(after_failure(MAX_ATTEMPTS, 100), (OutboxStatus::Failed, 100));
+        assert_eq!(OutboxStatus::parse(OutboxStatus::Failed.as_str()), OutboxStatus::Failed);
+    }
+}
@@ -5,6 +5,8 @@
 use deadpool_postgres::Pool;
 use serde::{Deserialize, Serialize};
 
+use super::message_content::{self, ContentBlock};
+
 /// 消息角色
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MessageRole {
@@ -21,6 +23,43 @@ pub struct Message {
     pub role: MessageRole,
     pub content: String,
     pub created_at: i64,
+    /// 最后编辑时间
+    #[serde(default)]
+    pub edited_at: Option<i64>,
+    /// 删除时间（软删除墓碑，内容不再返回）
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
+    /// 结构化内容块（为空时为纯文本消息）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Vec<ContentBlock>>,
+    /// 检测到的语言代码（zh / en / ja / ko）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl Message {
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Markdown 形式的消息内容（结构化消息按内容块渲染）
+    pub fn to_markdown(&self) -> String {
+        match &self.payload {
+            Some(blocks) => message_content::blocks_to_markdown(blocks),
+            None => self.content.clone(),
+        }
+    }
+}
+
+/// 消息修订记录（编辑前的内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRevision {
+    pub message_id: String,
+    pub revision: i32,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Vec<ContentBlock>>,
+    pub revised_at: i64,
 }
 
 /// 对话会话数据
@@ -32,6 +71,16 @@ pub struct Conversation {
     pub updated_at: i64,
 }
 
+/// 对话及其最后一条消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub updated_at: i64,
+    /// 最后一条未删除的消息
+    pub last_message: Option<String>,
+}
+
 /// 对话历史管理器
 pub struct ConversationHistory {
     pool: Pool,
@@ -60,6 +109,11 @@ impl ConversationHistory {
             )
             .await?;
 
+        // 会话级模型参数覆盖
+        client
+            .execute("ALTER TABLE conversations ADD COLUMN IF NOT EXISTS parameters JSONB", &[])
+            .await?;
+
         // 创建消息表
         client
             .execute(
@@ -75,6 +129,46 @@ impl ConversationHistory {
             )
             .await?;
 
+        // 编辑/删除标记
+        client
+            .execute("ALTER TABLE messages ADD COLUMN IF NOT EXISTS edited_at BIGINT", &[])
+            .await?;
+        client
+            .execute("ALTER TABLE messages ADD COLUMN IF NOT EXISTS deleted_at BIGINT", &[])
+            .await?;
+        client
+            .execute("ALTER TABLE messages ADD COLUMN IF NOT EXISTS payload JSONB", &[])
+            .await?;
+        client
+            .execute("ALTER TABLE messages ADD COLUMN IF NOT EXISTS language TEXT", &[])
+            .await?;
+        // 全文检索索引（simple 配置不做词干处理，中日韩文本由 ILIKE 兜底）
+        client
+            .execute(
+                "CREATE INDEX IF NOT EXISTS idx_messages_content_fts ON messages USING GIN (to_tsvector('simple', content))",
+                &[],
+            )
+            .await?;
+
+        // 创建消息修订表
+        client
+            .execute(
+                "CREATE TABLE IF NOT EXISTS message_revisions (
+                    message_id TEXT NOT NULL,
+                    revision INTEGER NOT NULL,
+                    content TEXT NOT NULL,
+                    payload JSONB,
+                    revised_at BIGINT NOT NULL,
+                    PRIMARY KEY (message_id, revision),
+                    FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+                )",
+                &[],
+            )
+            .await?;
+        client
+            .execute("ALTER TABLE message_revisions ADD COLUMN IF NOT EXISTS payload JSONB", &[])
+            .await?;
+
         Ok(())
     }
 
@@ -93,6 +187,56 @@ impl ConversationHistory {
         Ok(())
     }
 
+    /// 确保对话存在（不存在时创建），并刷新更新时间
+    pub async fn ensure_conversation(
+        &self,
+        id: &str,
+        title: &str,
+        now: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO conversations (id, title, created_at, updated_at) VALUES ($1, $2, $3, $3)
+                 ON CONFLICT (id) DO UPDATE SET
+                    updated_at = EXCLUDED.updated_at,
+                    title = CASE WHEN conversations.title = '' THEN EXCLUDED.title ELSE conversations.title END",
+                &[&id, &title, &now],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 会话的模型参数覆盖
+    pub async fn get_parameters(
+        &self,
+        id: &str,
+    ) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt("SELECT parameters FROM conversations WHERE id = $1", &[&id])
+            .await?;
+        Ok(row.and_then(|r| r.get(0)))
+    }
+
+    /// 保存会话的模型参数覆盖（`None` 清除）；会话尚无消息时先创建，标题在第一条消息时填写
+    pub async fn set_parameters(
+        &self,
+        id: &str,
+        parameters: Option<&serde_json::Value>,
+        now: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO conversations (id, title, created_at, updated_at, parameters) VALUES ($1, '', $2, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET parameters = EXCLUDED.parameters",
+                &[&id, &now, &parameters],
+            )
+            .await?;
+        Ok(())
+    }
+
     /// 获取对话
     pub async fn get_conversation(
         &self,
@@ -111,11 +255,77 @@ impl ConversationHistory {
         }))
     }
 
-    /// 添加消息
+    /// 列出与时间范围有交集的对话（按创建时间排序）
+    pub async fn list_conversations(
+        &self,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Conversation>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT id, title, created_at, updated_at FROM conversations
+                 WHERE created_at <= $2 AND updated_at >= $1
+                 ORDER BY created_at",
+                &[&from, &to],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| Conversation {
+                id: r.get(0),
+                title: r.get(1),
+                created_at: r.get(2),
+                updated_at: r.get(3),
+            })
+            .collect())
+    }
+
+    /// 按更新时间倒序列出对话及其最后一条消息；`ids` 不为空时只查询这些对话
+    pub async fn conversation_summaries(
+        &self,
+        ids: Option<Vec<String>>,
+        limit: i64,
+    ) -> Result<Vec<ConversationSummary>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT c.id, c.title, c.updated_at,
+                        (SELECT m.content FROM messages m
+                         WHERE m.conversation_id = c.id AND m.deleted_at IS NULL
+                         ORDER BY m.created_at DESC LIMIT 1)
+                 FROM conversations c
+                 WHERE $1::TEXT[] IS NULL OR c.id = ANY($1)
+                 ORDER BY c.updated_at DESC
+                 LIMIT $2",
+                &[&ids, &limit],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| ConversationSummary {
+                id: r.get(0),
+                title: r.get(1),
+                updated_at: r.get(2),
+                last_message: r.get(3),
+            })
+            .collect())
+    }
+
+    /// 添加消息（结构化消息会先校验内容块）
     pub async fn add_message(
         &self,
         message: Message,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = match &message.payload {
+            Some(blocks) => {
+                message_content::validate_blocks(blocks)?;
+                Some(serde_json::to_value(blocks)?)
+            }
+            None => None,
+        };
         let client = self.pool.get().await?;
         let role_str = match message.role {
             MessageRole::User => "user",
@@ -124,13 +334,117 @@ impl ConversationHistory {
         };
         client
             .execute(
-                "INSERT INTO messages (id, conversation_id, role, content, created_at) VALUES ($1, $2, $3, $4, $5)",
-                &[&message.id, &message.conversation_id, &role_str, &message.content, &message.created_at],
+                "INSERT INTO messages (id, conversation_id, role, content, created_at, payload, language) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[&message.id, &message.conversation_id, &role_str, &message.content, &message.created_at, &payload, &message.language],
             )
             .await?;
         Ok(())
     }
 
+    /// 导入对话及其消息（保留时间与编辑标记）；对话已存在时失败
+    pub async fn import_conversation(
+        &self,
+        conversation: &Conversation,
+        messages: &[Message],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        tx.execute(
+            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES ($1, $2, $3, $4)",
+            &[&conversation.id, &conversation.title, &conversation.created_at, &conversation.updated_at],
+        )
+        .await?;
+        for message in messages {
+            let payload = match &message.payload {
+                Some(blocks) => {
+                    message_content::validate_blocks(blocks)?;
+                    Some(serde_json::to_value(blocks)?)
+                }
+                None => None,
+            };
+            let role_str = match message.role {
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+                MessageRole::System => "system",
+            };
+            tx.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, created_at, edited_at, payload, language)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &message.id,
+                    &conversation.id,
+                    &role_str,
+                    &message.content,
+                    &message.created_at,
+                    &message.edited_at,
+                    &payload,
+                    &message.language,
+                ],
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 写入从其他设备同步的对话：对话与消息已存在时以同步内容为准，本机的修订记录保留
+    pub async fn upsert_synced_conversation(
+        &self,
+        conversation: &Conversation,
+        messages: &[Message],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        tx.execute(
+            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (id) DO UPDATE SET
+                title = EXCLUDED.title,
+                created_at = LEAST(conversations.created_at, EXCLUDED.created_at),
+                updated_at = GREATEST(conversations.updated_at, EXCLUDED.updated_at)",
+            &[&conversation.id, &conversation.title, &conversation.created_at, &conversation.updated_at],
+        )
+        .await?;
+        for message in messages {
+            let payload = match &message.payload {
+                Some(blocks) => {
+                    message_content::validate_blocks(blocks)?;
+                    Some(serde_json::to_value(blocks)?)
+                }
+                None => None,
+            };
+            let role_str = match message.role {
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+                MessageRole::System => "system",
+            };
+            // 已删除的消息只更新删除标记，保留本机的原内容
+            tx.execute(
+                "INSERT INTO messages (id, conversation_id, role, content, created_at, edited_at, deleted_at, payload, language)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (id) DO UPDATE SET
+                    content = CASE WHEN EXCLUDED.deleted_at IS NULL THEN EXCLUDED.content ELSE messages.content END,
+                    payload = CASE WHEN EXCLUDED.deleted_at IS NULL THEN EXCLUDED.payload ELSE messages.payload END,
+                    edited_at = EXCLUDED.edited_at,
+                    deleted_at = COALESCE(messages.deleted_at, EXCLUDED.deleted_at),
+                    language = EXCLUDED.language",
+                &[
+                    &message.id,
+                    &conversation.id,
+                    &role_str,
+                    &message.content,
+                    &message.created_at,
+                    &message.edited_at,
+                    &message.deleted_at,
+                    &payload,
+                    &message.language,
+                ],
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     /// 获取对话的所有消息
     pub async fn get_messages(
         &self,
@@ -139,31 +453,200 @@ impl ConversationHistory {
         let client = self.pool.get().await?;
         let rows = client
             .query(
-                "SELECT id, conversation_id, role, content, created_at FROM messages WHERE conversation_id = $1 ORDER BY created_at",
+                "SELECT id, conversation_id, role, content, created_at, edited_at, deleted_at, payload, language FROM messages WHERE conversation_id = $1 ORDER BY created_at",
                 &[&conversation_id],
             )
             .await?;
 
+        Ok(rows.iter().map(Self::row_to_message).collect())
+    }
+
+    /// 获取单条消息
+    pub async fn get_message(
+        &self,
+        message_id: &str,
+    ) -> Result<Option<Message>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT id, conversation_id, role, content, created_at, edited_at, deleted_at, payload, language FROM messages WHERE id = $1",
+                &[&message_id],
+            )
+            .await?;
+
+        Ok(row.as_ref().map(Self::row_to_message))
+    }
+
+    /// 编辑消息：旧内容写入修订表，返回新的修订号
+    ///
+    /// `payload` 为 `None` 时消息变为纯文本消息。
+    pub async fn edit_message(
+        &self,
+        message_id: &str,
+        content: &str,
+        payload: Option<&[ContentBlock]>,
+        edited_at: i64,
+    ) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = match payload {
+            Some(blocks) => {
+                message_content::validate_blocks(blocks)?;
+                Some(serde_json::to_value(blocks)?)
+            }
+            None => None,
+        };
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let row = tx
+            .query_opt(
+                "SELECT content, payload FROM messages WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+                &[&message_id],
+            )
+            .await?
+            .ok_or_else(|| format!("消息 {} 不存在或已删除", message_id))?;
+        let previous: String = row.get(0);
+        let previous_payload: Option<serde_json::Value> = row.get(1);
+
+        let revision: i32 = tx
+            .query_one(
+                "SELECT COALESCE(MAX(revision), 0) + 1 FROM message_revisions WHERE message_id = $1",
+                &[&message_id],
+            )
+            .await?
+            .get(0);
+        tx.execute(
+            "INSERT INTO message_revisions (message_id, revision, content, payload, revised_at) VALUES ($1, $2, $3, $4, $5)",
+            &[&message_id, &revision, &previous, &previous_payload, &edited_at],
+        )
+        .await?;
+        tx.execute(
+            "UPDATE messages SET content = $2, payload = $3, edited_at = $4 WHERE id = $1",
+            &[&message_id, &content, &payload, &edited_at],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(revision)
+    }
+
+    /// 软删除消息（保留记录作为墓碑）
+    pub async fn soft_delete_message(
+        &self,
+        message_id: &str,
+        deleted_at: i64,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE messages SET deleted_at = $2 WHERE id = $1 AND deleted_at IS NULL",
+                &[&message_id, &deleted_at],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    /// 获取消息的修订历史（按修订号升序）
+    pub async fn get_message_revisions(
+        &self,
+        message_id: &str,
+    ) -> Result<Vec<MessageRevision>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT message_id, revision, content, payload, revised_at FROM message_revisions WHERE message_id = $1 ORDER BY revision",
+                &[&message_id],
+            )
+            .await?;
+
         Ok(rows
             .into_iter()
-            .map(|r| {
-                let role_str: String = r.get(2);
-                let role = match role_str.as_str() {
-                    "user" => MessageRole::User,
-                    "assistant" => MessageRole::Assistant,
-                    _ => MessageRole::System,
-                };
-                Message {
-                    id: r.get(0),
-                    conversation_id: r.get(1),
-                    role,
-                    content: r.get(3),
-                    created_at: r.get(4),
-                }
+            .map(|r| MessageRevision {
+                message_id: r.get(0),
+                revision: r.get(1),
+                content: r.get(2),
+                payload: r
+                    .get::<_, Option<serde_json::Value>>(3)
+                    .and_then(|value| serde_json::from_value(value).ok()),
+                revised_at: r.get(4),
             })
             .collect())
     }
 
+    fn row_to_message(r: &tokio_postgres::Row) -> Message {
+        let role_str: String = r.get(2);
+        let role = match role_str.as_str() {
+            "user" => MessageRole::User,
+            "assistant" => MessageRole::Assistant,
+            _ => MessageRole::System,
+        };
+        let deleted_at: Option<i64> = r.get(6);
+        let payload: Option<serde_json::Value> = r.get(7);
+        Message {
+            id: r.get(0),
+            conversation_id: r.get(1),
+            role,
+            // 已删除的消息只返回墓碑，不返回内容
+            content: if deleted_at.is_some() { String::new() } else { r.get(3) },
+            created_at: r.get(4),
+            edited_at: r.get(5),
+            payload: payload
+                .filter(|_| deleted_at.is_none())
+                .and_then(|value| serde_json::from_value(value).ok()),
+            language: r.get(8),
+            deleted_at,
+        }
+    }
+
+    /// 按语言统计会话中未删除的用户消息数（按数量降序）
+    pub async fn get_language_stats(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Vec<(String, i64)>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT language, COUNT(*) FROM messages
+                 WHERE conversation_id = $1 AND role = 'user' AND deleted_at IS NULL AND language IS NOT NULL
+                 GROUP BY language ORDER BY COUNT(*) DESC, language",
+                &[&conversation_id],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|r| (r.get(0), r.get(1))).collect())
+    }
+
+    /// 全文检索未删除的消息，按相关度降序返回消息及得分
+    ///
+    /// `simple` 分词对中日韩文本无效，因此子串匹配同样计入结果并获得固定加分。
+    pub async fn search_messages(
+        &self,
+        query: &str,
+        conversation_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<(Message, f32)>, Box<dyn std::error::Error + Send + Sync>> {
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT id, conversation_id, role, content, created_at, edited_at, deleted_at, payload, language,
+                        (ts_rank(to_tsvector('simple', content), plainto_tsquery('simple', $1))
+                         + CASE WHEN content ILIKE $2 THEN 0.1 ELSE 0 END)::REAL AS rank
+                 FROM messages
+                 WHERE deleted_at IS NULL
+                   AND ($3::TEXT IS NULL OR conversation_id = $3)
+                   AND (to_tsvector('simple', content) @@ plainto_tsquery('simple', $1) OR content ILIKE $2)
+                 ORDER BY rank DESC, created_at DESC
+                 LIMIT $4",
+                &[&query, &pattern, &conversation_id, &limit],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|r| (Self::row_to_message(r), r.get(9))).collect())
+    }
+
     /// 删除对话
     pub async fn delete_conversation(
         &self,
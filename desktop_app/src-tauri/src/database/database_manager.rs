@@ -1,12 +1,21 @@
 //! 统一数据库管理器
 //!
-//! 整合 PostgreSQL、Redis 和 Qdrant，提供统一的访问接口
+//! 整合 PostgreSQL、Redis 和 Qdrant，提供统一的访问接口，并在后台监测连接健康、
+//! 中断后自动重连
 
+use std::collections::VecDeque;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::future::BoxFuture;
+use lazy_static::lazy_static;
+use serde::Serialize;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 use deadpool_postgres::Pool as PostgresPool;
 
+use crate::utils::watchdog;
+
 use super::backends::{DatabaseConfig, DatabaseError, DatabaseResult};
 use super::redis_backend::RedisBackend;
 use super::qdrant_backend::QdrantBackend;
@@ -181,6 +190,7 @@ impl DatabaseManager {
                 _ => None,
             }
         });
+        cfg.port = pg_config.get_ports().first().copied();
         cfg.dbname = pg_config.get_dbname().map(|s| s.to_string());
         cfg.user = pg_config.get_user().map(|s| s.to_string());
         cfg.password = pg_config.get_password().map(|p| {
@@ -331,6 +341,30 @@ impl DatabaseManager {
         
         result
     }
+    
+    /// 探测 Redis，连接断开时重新连接；未启用时返回 `None`
+    pub async fn probe_redis(&self) -> Option<Result<(), String>> {
+        use super::backends::DatabaseBackend;
+        
+        let backend = self.redis_backend.as_ref()?;
+        let config = self.config.redis_config.as_ref()?;
+        if backend.read().await.ping().await.is_ok() {
+            return Some(Ok(()));
+        }
+        Some(backend.write().await.connect(config).await.map_err(|e| e.to_string()))
+    }
+    
+    /// 探测 Qdrant，连接断开时重新连接；未启用时返回 `None`
+    pub async fn probe_qdrant(&self) -> Option<Result<(), String>> {
+        use super::backends::DatabaseBackend;
+        
+        let backend = self.qdrant_backend.as_ref()?;
+        let config = self.config.qdrant_config.as_ref()?;
+        if backend.read().await.ping().await.is_ok() {
+            return Some(Ok(()));
+        }
+        Some(backend.write().await.connect(config).await.map_err(|e| e.to_string()))
+    }
 }
 
 /// 健康检查结果
@@ -364,6 +398,291 @@ impl HealthCheckResult {
     }
 }
 
+// ================================
+// 连接健康监测与自动重连
+// ================================
+//
+// 后台任务定期探测 PostgreSQL（全局 `Database` 与管理器的连接池）、Redis 和 Qdrant。
+// 探测失败的后端进入重连状态，按指数退避重试：Redis / Qdrant 重新建立连接，PostgreSQL
+// 由连接池在服务恢复后丢弃断开的连接并创建新连接，探测成功即视为恢复。
+//
+// PostgreSQL 中断期间，可以延后执行的写入通过 [`queue_write`] 排队，恢复后按顺序重放。
+
+/// 正常状态下的探测间隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// 单次探测的超时
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(120);
+const HEALTH_STALL_AFTER: Duration = Duration::from_secs(10 * 60);
+/// 中断期间最多排队的写入，超出时丢弃最早的
+const MAX_QUEUED_WRITES: usize = 1000;
+/// 重放失败超过该次数的写入被丢弃
+const MAX_WRITE_ATTEMPTS: u32 = 3;
+
+/// 后端连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendStatus {
+    /// 未配置或未启用
+    #[default]
+    Disabled,
+    Healthy,
+    /// 连接中断，正在重连
+    Reconnecting,
+}
+
+/// 单个后端的健康状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendHealth {
+    pub status: BackendStatus,
+    /// 最近一次探测失败的原因
+    pub error: Option<String>,
+    /// 本次中断开始的时间（毫秒时间戳）
+    pub down_since: Option<i64>,
+    /// 本次中断以来的重连次数
+    pub attempts: u32,
+}
+
+impl BackendHealth {
+    /// 记录一次探测结果（`None` 表示后端未启用），返回状态是否变化
+    pub fn record(&mut self, probe: Option<Result<(), String>>, now_ms: i64) -> bool {
+        let previous = self.status;
+        match probe {
+            None => *self = Self::default(),
+            Some(Ok(())) => {
+                if previous == BackendStatus::Reconnecting {
+                    info!("数据库后端已恢复（重连 {} 次）", self.attempts);
+                }
+                *self = Self {
+                    status: BackendStatus::Healthy,
+                    ..Self::default()
+                };
+            }
+            Some(Err(error)) => {
+                if previous != BackendStatus::Reconnecting {
+                    self.down_since = Some(now_ms);
+                    self.attempts = 0;
+                }
+                self.status = BackendStatus::Reconnecting;
+                self.attempts += 1;
+                self.error = Some(error);
+            }
+        }
+        self.status != previous
+    }
+
+    /// 距离下次探测的时间：正常时固定间隔，中断时按重连次数指数退避
+    pub fn next_probe_delay(&self) -> Duration {
+        if self.status != BackendStatus::Reconnecting {
+            return HEALTH_CHECK_INTERVAL;
+        }
+        let exponent = self.attempts.saturating_sub(1).min(16);
+        RECONNECT_BASE_DELAY
+            .saturating_mul(1u32 << exponent)
+            .min(RECONNECT_MAX_DELAY)
+    }
+}
+
+/// 所有数据库后端的健康状态（`database-health-changed` 事件载荷）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatabaseHealth {
+    pub postgres: BackendHealth,
+    pub redis: BackendHealth,
+    pub qdrant: BackendHealth,
+    /// 等待 PostgreSQL 恢复后重放的写入数
+    pub queued_writes: usize,
+    /// 最近一次探测的时间（毫秒时间戳）
+    pub checked_at: i64,
+}
+
+impl DatabaseHealth {
+    /// 核心服务（PostgreSQL）是否可用
+    pub fn is_core_healthy(&self) -> bool {
+        self.postgres.status != BackendStatus::Reconnecting
+    }
+}
+
+type QueuedWriteFn = Box<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+struct QueuedWrite {
+    label: &'static str,
+    attempts: u32,
+    write: QueuedWriteFn,
+}
+
+lazy_static! {
+    static ref HEALTH: parking_lot::RwLock<DatabaseHealth> = parking_lot::RwLock::new(DatabaseHealth::default());
+    static ref WRITE_QUEUE: parking_lot::Mutex<VecDeque<QueuedWrite>> = parking_lot::Mutex::new(VecDeque::new());
+}
+
+/// 当前的健康状态
+pub fn health() -> DatabaseHealth {
+    let mut health = HEALTH.read().clone();
+    health.queued_writes = WRITE_QUEUE.lock().len();
+    health
+}
+
+/// PostgreSQL 当前是否可用（监测任务尚未探测时视为可用）
+pub fn postgres_available() -> bool {
+    HEALTH.read().is_core_healthy()
+}
+
+/// 排队一个写入，等 PostgreSQL 恢复后执行
+///
+/// 只用于可以延后、且重复执行无害的写入（日志、统计等）。`write` 失败时会在下次恢复后重试。
+pub fn queue_write<F, Fut>(label: &'static str, write: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let mut queue = WRITE_QUEUE.lock();
+    if queue.len() >= MAX_QUEUED_WRITES {
+        if let Some(dropped) = queue.pop_front() {
+            warn!("数据库中断期间排队的写入过多，丢弃最早的一条: {}", dropped.label);
+        }
+    }
+    queue.push_back(QueuedWrite {
+        label,
+        attempts: 0,
+        write: Box::new(move || Box::pin(write())),
+    });
+}
+
+/// 按顺序重放排队的写入，遇到失败时停止（留到下次恢复后）
+async fn replay_queued_writes() {
+    let mut replayed = 0usize;
+    loop {
+        let next = WRITE_QUEUE.lock().pop_front();
+        let Some(mut queued) = next else {
+            break;
+        };
+        match (queued.write)().await {
+            Ok(()) => replayed += 1,
+            Err(e) => {
+                queued.attempts += 1;
+                if queued.attempts >= MAX_WRITE_ATTEMPTS {
+                    warn!("重放写入 {} 失败 {} 次，已丢弃: {}", queued.label, queued.attempts, e);
+                    continue;
+                }
+                warn!("重放写入 {} 失败，稍后重试: {}", queued.label, e);
+                WRITE_QUEUE.lock().push_front(queued);
+                break;
+            }
+        }
+    }
+    if replayed > 0 {
+        info!("数据库恢复后重放了 {} 条写入", replayed);
+    }
+}
+
+async fn ping_pool(pool: &PostgresPool) -> Result<(), String> {
+    let ping = async {
+        let client = pool.get().await.map_err(|e| format!("获取PostgreSQL连接失败: {}", e))?;
+        client
+            .simple_query("SELECT 1")
+            .await
+            .map_err(|e| format!("PostgreSQL连接测试失败: {}", e))?;
+        Ok(())
+    };
+    tokio::time::timeout(PING_TIMEOUT, ping)
+        .await
+        .map_err(|_| "PostgreSQL连接超时".to_string())?
+}
+
+/// 探测 PostgreSQL（全局 `Database` 与管理器各自的连接池）；都未初始化时返回 `None`
+async fn probe_postgres() -> Option<Result<(), String>> {
+    let mut pools = Vec::new();
+    if let Some(db) = super::get_database() {
+        pools.push(Arc::new(db.get_pool()));
+    }
+    if let Some(pool) = super::get_database_manager().and_then(|manager| manager.postgres_pool.clone()) {
+        pools.push(pool);
+    }
+    if pools.is_empty() {
+        return None;
+    }
+    for pool in pools {
+        if let Err(e) = ping_pool(&pool).await {
+            return Some(Err(e));
+        }
+    }
+    Some(Ok(()))
+}
+
+async fn with_timeout(probe: impl Future<Output = Option<Result<(), String>>>) -> Option<Result<(), String>> {
+    // 重连本身可能较慢，给两倍的时间
+    tokio::time::timeout(PING_TIMEOUT * 2, probe)
+        .await
+        .unwrap_or_else(|_| Some(Err("连接超时".to_string())))
+}
+
+/// 启动数据库健康监测，任一后端状态变化时调用 `on_change`
+pub fn start_health_watchdog<F>(on_change: F)
+where
+    F: Fn(&DatabaseHealth) + Send + Sync + 'static,
+{
+    let on_change = Arc::new(on_change);
+    watchdog::supervise("database_health", HEALTH_STALL_AFTER, move |heartbeat| {
+        let on_change = on_change.clone();
+        tauri::async_runtime::spawn(async move {
+            // 依次为 PostgreSQL、Redis、Qdrant 的下次探测时间
+            let mut next_probe = [Instant::now(); 3];
+            while heartbeat.beat() {
+                let now = Instant::now();
+                let now_ms = chrono::Utc::now().timestamp_millis();
+                let manager = super::get_database_manager();
+                let mut changed = false;
+
+                if now >= next_probe[0] {
+                    let probe = probe_postgres().await;
+                    let mut health = HEALTH.write();
+                    changed |= health.postgres.record(probe, now_ms);
+                    next_probe[0] = now + health.postgres.next_probe_delay();
+                }
+                if now >= next_probe[1] {
+                    let probe = match &manager {
+                        Some(manager) => with_timeout(manager.probe_redis()).await,
+                        None => None,
+                    };
+                    let mut health = HEALTH.write();
+                    changed |= health.redis.record(probe, now_ms);
+                    next_probe[1] = now + health.redis.next_probe_delay();
+                }
+                if now >= next_probe[2] {
+                    let probe = match &manager {
+                        Some(manager) => with_timeout(manager.probe_qdrant()).await,
+                        None => None,
+                    };
+                    let mut health = HEALTH.write();
+                    changed |= health.qdrant.record(probe, now_ms);
+                    next_probe[2] = now + health.qdrant.next_probe_delay();
+                }
+                HEALTH.write().checked_at = now_ms;
+
+                if changed {
+                    let health = health();
+                    if health.is_core_healthy() {
+                        info!("数据库连接状态: PostgreSQL {:?}, Redis {:?}, Qdrant {:?}",
+                            health.postgres.status, health.redis.status, health.qdrant.status);
+                    } else {
+                        error!("PostgreSQL 连接中断，正在重连: {}",
+                            health.postgres.error.as_deref().unwrap_or("未知错误"));
+                    }
+                    on_change(&health);
+                }
+                let has_queued_writes = !WRITE_QUEUE.lock().is_empty();
+                if has_queued_writes && postgres_available() {
+                    replay_queued_writes().await;
+                }
+
+                let wake_at = next_probe.iter().min().copied().unwrap_or(now + HEALTH_CHECK_INTERVAL);
+                tokio::time::sleep_until(wake_at.max(Instant::now() + Duration::from_secs(1)).into()).await;
+            }
+        });
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -851,5 +1170,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_backend_health_tracks_outage_and_backoff() {
+        let mut health = BackendHealth::default();
+        assert!(health.record(Some(Ok(())), 1_000));
+        assert_eq!(health.next_probe_delay(), HEALTH_CHECK_INTERVAL);
+
+        // 中断：只有第一次失败算状态变化，退避时间逐次翻倍直到上限
+        assert!(health.record(Some(Err("refused".to_string())), 2_000));
+        assert_eq!(health.next_probe_delay(), RECONNECT_BASE_DELAY);
+        assert!(!health.record(Some(Err("refused".to_string())), 4_000));
+        assert_eq!(health.next_probe_delay(), RECONNECT_BASE_DELAY * 2);
+        assert_eq!(health.down_since, Some(2_000));
+        assert_eq!(health.attempts, 2);
+        for _ in 0..20 {
+            health.record(Some(Err("refused".to_string())), 5_000);
+        }
+        assert_eq!(health.next_probe_delay(), RECONNECT_MAX_DELAY);
+
+        // 恢复后清除中断信息
+        assert!(health.record(Some(Ok(())), 9_000));
+        assert_eq!(health.status, BackendStatus::Healthy);
+        assert!(health.down_since.is_none() && health.error.is_none());
+        assert!(health.record(None, 10_000));
+        assert_eq!(health.status, BackendStatus::Disabled);
+    }
+
 }
 
@@ -0,0 +1,655 @@
+//! 嵌入向量提供者
+//!
+//! 向量搜索服务通过 [`EmbeddingProvider`] 生成向量，每个集合可以单独选择提供者：
+//! 本地 ONNX 模型、Ollama、OpenAI 兼容接口，或开发用的哈希占位向量。
+//!
+//! 设置中记录每个集合建立索引时使用的提供者和维度；提供者变化后旧向量与新查询不可比，
+//! 需要通过 `VectorSearchService::reembed_collection` 重新嵌入。
+
+use async_trait::async_trait;
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use super::backends::{DatabaseError, DatabaseResult};
+
+/// 默认向量维度（与 Qdrant 后端默认值一致）
+pub const DEFAULT_DIMENSION: usize = 384;
+/// 允许的最大向量维度
+pub const MAX_DIMENSION: usize = 8192;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    static ref EMBEDDING_SETTINGS: RwLock<EmbeddingSettings> = RwLock::new(EmbeddingSettings::default());
+    static ref SETTINGS_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+    /// 按指纹缓存的提供者实例（避免重复加载本地模型）
+    static ref PROVIDERS: RwLock<HashMap<String, Arc<dyn EmbeddingProvider>>> = RwLock::new(HashMap::new());
+}
+
+/// 嵌入向量提供者
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// 输出向量维度
+    fn dimension(&self) -> usize;
+
+    /// 批量生成向量，返回顺序与输入一致
+    async fn embed(&self, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>>;
+}
+
+/// 提供者配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmbeddingProviderConfig {
+    /// 哈希占位向量（仅用于开发测试，没有语义）
+    Hash { dimension: usize },
+    /// 本地 ONNX 句向量模型（需要 `onnx-embeddings` 特性）
+    Onnx {
+        model_path: String,
+        tokenizer_path: String,
+        dimension: usize,
+        #[serde(default = "default_max_tokens")]
+        max_tokens: usize,
+    },
+    /// Ollama 嵌入接口
+    Ollama {
+        #[serde(default = "default_ollama_url")]
+        base_url: String,
+        model: String,
+        dimension: usize,
+    },
+    /// OpenAI 兼容的 `/embeddings` 接口
+    OpenAiCompatible {
+        base_url: String,
+        model: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key: Option<String>,
+        dimension: usize,
+    },
+}
+
+fn default_max_tokens() -> usize {
+    256
+}
+
+fn default_ollama_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+impl Default for EmbeddingProviderConfig {
+    fn default() -> Self {
+        Self::Hash { dimension: DEFAULT_DIMENSION }
+    }
+}
+
+impl EmbeddingProviderConfig {
+    /// 配置声明的向量维度
+    pub fn dimension(&self) -> usize {
+        match self {
+            Self::Hash { dimension }
+            | Self::Onnx { dimension, .. }
+            | Self::Ollama { dimension, .. }
+            | Self::OpenAiCompatible { dimension, .. } => *dimension,
+        }
+    }
+
+    /// 提供者指纹（类型、模型与维度），指纹不同的向量不可比较
+    pub fn fingerprint(&self) -> String {
+        match self {
+            Self::Hash { dimension } => format!("hash:{}", dimension),
+            Self::Onnx { model_path, dimension, .. } => format!("onnx:{}:{}", model_path, dimension),
+            Self::Ollama { base_url, model, dimension } => format!("ollama:{}/{}:{}", base_url, model, dimension),
+            Self::OpenAiCompatible { base_url, model, dimension, .. } => {
+                format!("openai:{}/{}:{}", base_url, model, dimension)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let dimension = self.dimension();
+        if dimension == 0 || dimension > MAX_DIMENSION {
+            return Err(format!("向量维度必须在 1 到 {} 之间", MAX_DIMENSION));
+        }
+        match self {
+            Self::Hash { .. } => Ok(()),
+            Self::Onnx { model_path, tokenizer_path, max_tokens, .. } => {
+                if model_path.trim().is_empty() || tokenizer_path.trim().is_empty() {
+                    return Err("ONNX 提供者需要模型和分词器路径".to_string());
+                }
+                if *max_tokens == 0 {
+                    return Err("最大 token 数必须大于 0".to_string());
+                }
+                Ok(())
+            }
+            Self::Ollama { base_url, model, .. } | Self::OpenAiCompatible { base_url, model, .. } => {
+                if base_url.trim().is_empty() || model.trim().is_empty() {
+                    return Err("嵌入接口需要服务地址和模型名称".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 创建提供者实例
+    pub fn build(&self) -> DatabaseResult<Arc<dyn EmbeddingProvider>> {
+        self.validate().map_err(DatabaseError::InvalidData)?;
+        Ok(match self {
+            Self::Hash { dimension } => Arc::new(HashEmbedding::new(*dimension)),
+            Self::Onnx { model_path, tokenizer_path, dimension, max_tokens } => Arc::new(OnnxEmbedding::load(
+                Path::new(model_path),
+                Path::new(tokenizer_path),
+                *dimension,
+                *max_tokens,
+            )?),
+            Self::Ollama { base_url, model, dimension } => Arc::new(OllamaEmbedding {
+                client: http_client()?,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                model: model.clone(),
+                dimension: *dimension,
+            }),
+            Self::OpenAiCompatible { base_url, model, api_key, dimension } => Arc::new(OpenAiCompatibleEmbedding {
+                client: http_client()?,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                model: model.clone(),
+                api_key: api_key.clone(),
+                dimension: *dimension,
+            }),
+        })
+    }
+}
+
+/// 集合的索引记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedCollection {
+    /// 建立索引时使用的提供者指纹
+    pub fingerprint: String,
+    /// 集合的向量维度
+    pub dimension: usize,
+    /// 记录时间
+    pub updated_at: i64,
+}
+
+/// 嵌入设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingSettings {
+    /// 未单独配置的集合使用的提供者
+    pub default_provider: EmbeddingProviderConfig,
+    /// 按集合覆盖的提供者
+    pub collections: BTreeMap<String, EmbeddingProviderConfig>,
+    /// 各集合建立索引时使用的提供者（由向量搜索服务维护）
+    pub indexed: BTreeMap<String, IndexedCollection>,
+}
+
+impl EmbeddingSettings {
+    pub fn provider_for(&self, collection: &str) -> &EmbeddingProviderConfig {
+        self.collections.get(collection).unwrap_or(&self.default_provider)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.default_provider.validate()?;
+        for (collection, provider) in &self.collections {
+            provider.validate().map_err(|e| format!("集合 {}: {}", collection, e))?;
+        }
+        Ok(())
+    }
+
+    /// 已建立索引但提供者已变化、需要重新嵌入的集合
+    pub fn stale_collections(&self) -> Vec<String> {
+        self.indexed
+            .iter()
+            .filter(|(collection, indexed)| self.provider_for(collection).fingerprint() != indexed.fingerprint)
+            .map(|(collection, _)| collection.clone())
+            .collect()
+    }
+}
+
+/// 从文件加载设置，之后的索引记录会写回该文件
+pub fn initialize(path: PathBuf) -> Result<(), String> {
+    if path.exists() {
+        let json_data = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read embedding settings: {}", e))?;
+        let settings: EmbeddingSettings = serde_json::from_str(&json_data)
+            .map_err(|e| format!("Failed to parse embedding settings: {}", e))?;
+        *EMBEDDING_SETTINGS.write() = settings;
+    }
+    *SETTINGS_PATH.write() = Some(path);
+    Ok(())
+}
+
+pub fn current_settings() -> EmbeddingSettings {
+    EMBEDDING_SETTINGS.read().clone()
+}
+
+/// 更新提供者配置（保留现有的索引记录）并保存
+pub fn set_settings(settings: EmbeddingSettings) -> Result<EmbeddingSettings, String> {
+    settings.validate()?;
+    let updated = {
+        let mut current = EMBEDDING_SETTINGS.write();
+        current.default_provider = settings.default_provider;
+        current.collections = settings.collections;
+        current.clone()
+    };
+    save(&updated)?;
+    Ok(updated)
+}
+
+fn save(settings: &EmbeddingSettings) -> Result<(), String> {
+    let Some(path) = SETTINGS_PATH.read().clone() else {
+        return Ok(());
+    };
+    let json_data = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize embedding settings: {}", e))?;
+    std::fs::write(path, json_data).map_err(|e| format!("Failed to write embedding settings: {}", e))
+}
+
+/// 集合的索引记录
+pub fn indexed_collection(collection: &str) -> Option<IndexedCollection> {
+    EMBEDDING_SETTINGS.read().indexed.get(collection).cloned()
+}
+
+/// 记录集合以指定提供者建立了索引
+pub fn record_indexed(collection: &str, provider: &EmbeddingProviderConfig) {
+    let updated = {
+        let mut settings = EMBEDDING_SETTINGS.write();
+        settings.indexed.insert(
+            collection.to_string(),
+            IndexedCollection {
+                fingerprint: provider.fingerprint(),
+                dimension: provider.dimension(),
+                updated_at: Utc::now().timestamp(),
+            },
+        );
+        settings.clone()
+    };
+    if let Err(e) = save(&updated) {
+        warn!("保存集合 {} 的索引记录失败: {}", collection, e);
+    }
+}
+
+/// 获取集合当前配置的提供者
+pub fn provider_for(collection: &str) -> DatabaseResult<(EmbeddingProviderConfig, Arc<dyn EmbeddingProvider>)> {
+    let config = EMBEDDING_SETTINGS.read().provider_for(collection).clone();
+    let fingerprint = config.fingerprint();
+    if let Some(provider) = PROVIDERS.read().get(&fingerprint) {
+        return Ok((config, provider.clone()));
+    }
+    let provider = config.build()?;
+    PROVIDERS.write().insert(fingerprint, provider.clone());
+    Ok((config, provider))
+}
+
+/// 校验向量维度
+pub fn validate_dimension(expected: usize, actual: usize) -> DatabaseResult<()> {
+    if expected != actual {
+        return Err(DatabaseError::InvalidData(format!(
+            "向量维度不匹配: 期望 {}，实际 {}",
+            expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// 用提供者生成向量并校验数量与维度
+pub async fn embed_checked(provider: &dyn EmbeddingProvider, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+    let vectors = provider.embed(texts).await?;
+    if vectors.len() != texts.len() {
+        return Err(DatabaseError::InvalidData(format!(
+            "嵌入结果数量不匹配: 输入 {}，返回 {}",
+            texts.len(),
+            vectors.len()
+        )));
+    }
+    for vector in &vectors {
+        validate_dimension(provider.dimension(), vector.len())?;
+    }
+    Ok(vectors)
+}
+
+fn normalize(vector: &mut [f32]) {
+    let magnitude: f32 = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude > 0.0 {
+        for v in vector.iter_mut() {
+            *v /= magnitude;
+        }
+    }
+}
+
+fn http_client() -> DatabaseResult<reqwest::Client> {
+    crate::http::proxy::client_builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| DatabaseError::ConnectionError(format!("创建 HTTP 客户端失败: {}", e)))
+}
+
+// ========================================
+// 提供者实现
+// ========================================
+
+/// 哈希占位向量
+pub struct HashEmbedding {
+    dimension: usize,
+}
+
+impl HashEmbedding {
+    pub fn new(dimension: usize) -> Self {
+        Self { dimension }
+    }
+
+    pub fn embed_one(&self, text: &str) -> Vec<f32> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut vector: Vec<f32> = (0..self.dimension)
+            .map(|i| {
+                let seed = hash.wrapping_add(i as u64);
+                ((seed % 10000) as f32 / 10000.0) - 0.5
+            })
+            .collect();
+        normalize(&mut vector);
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashEmbedding {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn embed(&self, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_one(text)).collect())
+    }
+}
+
+/// Ollama 嵌入（`POST /api/embed`）
+pub struct OllamaEmbedding {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dimension: usize,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbedding {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn embed(&self, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&serde_json::json!({ "model": self.model, "input": texts }))
+            .send()
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(format!("请求 Ollama 嵌入失败: {}", e)))?
+            .error_for_status()
+            .map_err(|e| DatabaseError::QueryError(format!("Ollama 嵌入返回错误: {}", e)))?;
+        let body: OllamaEmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| DatabaseError::SerializationError(format!("解析 Ollama 嵌入结果失败: {}", e)))?;
+        Ok(body.embeddings)
+    }
+}
+
+/// OpenAI 兼容嵌入（`POST {base_url}/embeddings`）
+pub struct OpenAiCompatibleEmbedding {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    dimension: usize,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbeddingResponse {
+    data: Vec<OpenAiEmbeddingItem>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbeddingItem {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiCompatibleEmbedding {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn embed(&self, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&serde_json::json!({ "model": self.model, "input": texts }));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(format!("请求嵌入接口失败: {}", e)))?
+            .error_for_status()
+            .map_err(|e| DatabaseError::QueryError(format!("嵌入接口返回错误: {}", e)))?;
+        let mut body: OpenAiEmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| DatabaseError::SerializationError(format!("解析嵌入结果失败: {}", e)))?;
+        body.data.sort_by_key(|item| item.index);
+        Ok(body.data.into_iter().map(|item| item.embedding).collect())
+    }
+}
+
+/// 本地 ONNX 句向量模型（对最后一层隐藏状态做掩码平均池化并归一化）
+#[cfg(feature = "onnx-embeddings")]
+#[derive(Clone)]
+pub struct OnnxEmbedding {
+    session: Arc<parking_lot::Mutex<ort::session::Session>>,
+    tokenizer: Arc<tokenizers::Tokenizer>,
+    dimension: usize,
+    max_tokens: usize,
+    uses_token_type_ids: bool,
+}
+
+#[cfg(feature = "onnx-embeddings")]
+impl OnnxEmbedding {
+    pub fn load(model_path: &Path, tokenizer_path: &Path, dimension: usize, max_tokens: usize) -> DatabaseResult<Self> {
+        let session = ort::session::Session::builder()
+            .and_then(|builder| builder.commit_from_file(model_path))
+            .map_err(|e| DatabaseError::Other(format!("加载 ONNX 模型失败: {}", e)))?;
+        let tokenizer = tokenizers::Tokenizer::from_file(tokenizer_path)
+            .map_err(|e| DatabaseError::Other(format!("加载分词器失败: {}", e)))?;
+        let uses_token_type_ids = session.inputs.iter().any(|input| input.name == "token_type_ids");
+        Ok(Self {
+            session: Arc::new(parking_lot::Mutex::new(session)),
+            tokenizer: Arc::new(tokenizer),
+            dimension,
+            max_tokens,
+            uses_token_type_ids,
+        })
+    }
+
+    fn run(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        use ort::value::Tensor;
+
+        let encodings = self.tokenizer.encode_batch(texts, true).map_err(|e| e.to_string())?;
+        let batch = encodings.len();
+        let seq_len = encodings
+            .iter()
+            .map(|e| e.get_ids().len().min(self.max_tokens))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut ids = vec![0i64; batch * seq_len];
+        let mut mask = vec![0i64; batch * seq_len];
+        for (row, encoding) in encodings.iter().enumerate() {
+            for (col, id) in encoding.get_ids().iter().take(seq_len).enumerate() {
+                ids[row * seq_len + col] = *id as i64;
+                mask[row * seq_len + col] = 1;
+            }
+        }
+
+        let shape = [batch, seq_len];
+        let mut inputs = vec![
+            ("input_ids", Tensor::from_array((shape, ids)).map_err(|e| e.to_string())?.into_dyn()),
+            ("attention_mask", Tensor::from_array((shape, mask.clone())).map_err(|e| e.to_string())?.into_dyn()),
+        ];
+        if self.uses_token_type_ids {
+            inputs.push((
+                "token_type_ids",
+                Tensor::from_array((shape, vec![0i64; batch * seq_len])).map_err(|e| e.to_string())?.into_dyn(),
+            ));
+        }
+
+        let session = self.session.lock();
+        let outputs = session.run(inputs).map_err(|e| e.to_string())?;
+        let (output_shape, data) = outputs[0].try_extract_raw_tensor::<f32>().map_err(|e| e.to_string())?;
+
+        let hidden = *output_shape.last().unwrap_or(&0) as usize;
+        if hidden != self.dimension {
+            return Err(format!("模型输出维度为 {}，配置为 {}", hidden, self.dimension));
+        }
+
+        let vectors = (0..batch)
+            .map(|row| {
+                let mut pooled = vec![0f32; hidden];
+                if output_shape.len() == 2 {
+                    // 模型已输出句向量
+                    pooled.copy_from_slice(&data[row * hidden..(row + 1) * hidden]);
+                } else {
+                    let mut count = 0f32;
+                    for col in 0..seq_len {
+                        if mask[row * seq_len + col] == 0 {
+                            continue;
+                        }
+                        let offset = (row * seq_len + col) * hidden;
+                        for (p, v) in pooled.iter_mut().zip(&data[offset..offset + hidden]) {
+                            *p += v;
+                        }
+                        count += 1.0;
+                    }
+                    if count > 0.0 {
+                        pooled.iter_mut().for_each(|p| *p /= count);
+                    }
+                }
+                normalize(&mut pooled);
+                pooled
+            })
+            .collect();
+        Ok(vectors)
+    }
+}
+
+#[cfg(feature = "onnx-embeddings")]
+#[async_trait]
+impl EmbeddingProvider for OnnxEmbedding {
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    async fn embed(&self, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        let this = self.clone();
+        let texts = texts.to_vec();
+        tokio::task::spawn_blocking(move || this.run(texts))
+            .await
+            .map_err(|e| DatabaseError::Other(format!("ONNX 推理任务失败: {}", e)))?
+            .map_err(|e| DatabaseError::Other(format!("ONNX 推理失败: {}", e)))
+    }
+}
+
+/// 未启用 `onnx-embeddings` 特性时的占位实现
+#[cfg(not(feature = "onnx-embeddings"))]
+pub struct OnnxEmbedding;
+
+#[cfg(not(feature = "onnx-embeddings"))]
+impl OnnxEmbedding {
+    pub fn load(_model_path: &Path, _tokenizer_path: &Path, _dimension: usize, _max_tokens: usize) -> DatabaseResult<Self> {
+        Err(DatabaseError::Other(
+            "本地 ONNX 嵌入需要启用 onnx-embeddings 特性".to_string(),
+        ))
+    }
+}
+
+#[cfg(not(feature = "onnx-embeddings"))]
+#[async_trait]
+impl EmbeddingProvider for OnnxEmbedding {
+    fn dimension(&self) -> usize {
+        0
+    }
+
+    async fn embed(&self, _texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        Err(DatabaseError::Other("本地 ONNX 嵌入需要启用 onnx-embeddings 特性".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hash_embedding_dimension_and_norm() {
+        let provider = HashEmbedding::new(64);
+        let vectors = embed_checked(&provider, &["你好".to_string(), "hello".to_string()]).await.unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[0].len(), 64);
+        let norm: f32 = vectors[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_eq!(vectors[0], provider.embed_one("你好"));
+    }
+
+    #[test]
+    fn test_stale_collections_follow_fingerprint() {
+        let ollama = EmbeddingProviderConfig::Ollama {
+            base_url: default_ollama_url(),
+            model: "nomic-embed-text".to_string(),
+            dimension: 768,
+        };
+        let mut settings = EmbeddingSettings::default();
+        for collection in ["conversations", "documents"] {
+            settings.indexed.insert(
+                collection.to_string(),
+                IndexedCollection {
+                    fingerprint: EmbeddingProviderConfig::default().fingerprint(),
+                    dimension: DEFAULT_DIMENSION,
+                    updated_at: 0,
+                },
+            );
+        }
+        assert!(settings.stale_collections().is_empty());
+
+        settings.collections.insert("documents".to_string(), ollama);
+        assert_eq!(settings.stale_collections(), vec!["documents".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_provider_config() {
+        assert!(EmbeddingProviderConfig::Hash { dimension: 0 }.validate().is_err());
+        assert!(EmbeddingProviderConfig::OpenAiCompatible {
+            base_url: "https://api.example.com/v1".to_string(),
+            model: String::new(),
+            api_key: None,
+            dimension: 1536,
+        }
+        .validate()
+        .is_err());
+        assert!(validate_dimension(384, 768).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! 混合检索的得分融合
+//!
+//! 将 PostgreSQL 全文检索与 Qdrant 向量检索的结果合并为一个排序：
+//! - 倒数排名融合（RRF）：只看名次，`score = Σ 1 / (k + rank)`，两路得分量纲不同时更稳健
+//! - 加权得分：两路得分各自做 min-max 归一化后按权重相加
+//!
+//! 同一条目在两路中都出现时合并为一个结果，并保留各自的名次与原始得分。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// RRF 的默认平滑常数
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// 单路检索的候选结果（按该路得分降序排列）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchCandidate {
+    /// 条目 ID（两路中相同 ID 视为同一条目）
+    pub id: String,
+    pub content: String,
+    pub session_id: Option<String>,
+    /// 该路的原始得分
+    pub score: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// 融合方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum FusionMethod {
+    /// 倒数排名融合
+    ReciprocalRank {
+        #[serde(default = "default_rrf_k")]
+        k: f32,
+    },
+    /// 归一化得分加权
+    Weighted { keyword_weight: f32, vector_weight: f32 },
+}
+
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
+
+impl Default for FusionMethod {
+    fn default() -> Self {
+        Self::ReciprocalRank { k: DEFAULT_RRF_K }
+    }
+}
+
+impl FusionMethod {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::ReciprocalRank { k } if !(k >= 0.0 && k.is_finite()) => Err("RRF 常数 k 必须为非负数".to_string()),
+            Self::Weighted { keyword_weight, vector_weight } => {
+                let valid = |w: f32| w >= 0.0 && w.is_finite();
+                if !valid(keyword_weight) || !valid(vector_weight) {
+                    return Err("融合权重必须为非负数".to_string());
+                }
+                if keyword_weight + vector_weight <= 0.0 {
+                    return Err("融合权重不能同时为 0".to_string());
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 融合后的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HybridHit {
+    pub id: String,
+    pub content: String,
+    pub session_id: Option<String>,
+    /// 融合得分
+    pub score: f32,
+    /// 在全文检索中的名次（从 1 开始）
+    pub keyword_rank: Option<usize>,
+    /// 在向量检索中的名次（从 1 开始）
+    pub vector_rank: Option<usize>,
+    pub keyword_score: Option<f32>,
+    pub vector_score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// 把得分 min-max 归一化到 [0, 1]；所有得分相同时均为 1
+fn normalized_scores(candidates: &[SearchCandidate]) -> Vec<f32> {
+    let min = candidates.iter().map(|c| c.score).fold(f32::INFINITY, f32::min);
+    let max = candidates.iter().map(|c| c.score).fold(f32::NEG_INFINITY, f32::max);
+    candidates
+        .iter()
+        .map(|c| if max > min { (c.score - min) / (max - min) } else { 1.0 })
+        .collect()
+}
+
+/// 融合两路结果，返回得分最高的 `limit` 条
+pub fn fuse(
+    keyword: Vec<SearchCandidate>,
+    vector: Vec<SearchCandidate>,
+    method: &FusionMethod,
+    limit: usize,
+) -> Vec<HybridHit> {
+    let contribution = |candidates: &[SearchCandidate], weight: f32| -> Vec<f32> {
+        match *method {
+            FusionMethod::ReciprocalRank { k } => (0..candidates.len()).map(|i| 1.0 / (k + (i + 1) as f32)).collect(),
+            FusionMethod::Weighted { .. } => normalized_scores(candidates).into_iter().map(|s| s * weight).collect(),
+        }
+    };
+    let (keyword_weight, vector_weight) = match *method {
+        FusionMethod::Weighted { keyword_weight, vector_weight } => (keyword_weight, vector_weight),
+        FusionMethod::ReciprocalRank { .. } => (1.0, 1.0),
+    };
+    let keyword_contribution = contribution(&keyword, keyword_weight);
+    let vector_contribution = contribution(&vector, vector_weight);
+
+    let mut hits: Vec<HybridHit> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (is_keyword, candidates, contributions) in [
+        (true, keyword, keyword_contribution),
+        (false, vector, vector_contribution),
+    ] {
+        for (rank, (candidate, contribution)) in candidates.into_iter().zip(contributions).enumerate() {
+            let position = *index.entry(candidate.id.clone()).or_insert_with(|| {
+                hits.push(HybridHit {
+                    id: candidate.id.clone(),
+                    content: candidate.content.clone(),
+                    session_id: candidate.session_id.clone(),
+                    score: 0.0,
+                    keyword_rank: None,
+                    vector_rank: None,
+                    keyword_score: None,
+                    vector_score: None,
+                    payload: candidate.payload.clone(),
+                });
+                hits.len() - 1
+            });
+            let hit = &mut hits[position];
+            hit.score += contribution;
+            if is_keyword {
+                hit.keyword_rank.get_or_insert(rank + 1);
+                hit.keyword_score.get_or_insert(candidate.score);
+            } else {
+                hit.vector_rank.get_or_insert(rank + 1);
+                hit.vector_score.get_or_insert(candidate.score);
+                if hit.payload.is_none() {
+                    hit.payload = candidate.payload;
+                }
+            }
+        }
+    }
+
+    let best_rank = |hit: &HybridHit| hit.keyword_rank.into_iter().chain(hit.vector_rank).min().unwrap_or(usize::MAX);
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| best_rank(a).cmp(&best_rank(b)))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(ids_scores: &[(&str, f32)]) -> Vec<SearchCandidate> {
+        ids_scores
+            .iter()
+            .map(|(id, score)| SearchCandidate {
+                id: id.to_string(),
+                content: format!("内容 {}", id),
+                session_id: None,
+                score: *score,
+                payload: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rrf_prefers_items_in_both_lists() {
+        let keyword = candidates(&[("a", 0.9), ("b", 0.5), ("c", 0.1)]);
+        let vector = candidates(&[("c", 0.95), ("d", 0.9), ("a", 0.2)]);
+        let hits = fuse(keyword, vector, &FusionMethod::default(), 10);
+
+        assert_eq!(hits.len(), 4);
+        // a: 1/61 + 1/63，c: 1/63 + 1/61 —— 得分相同，按最佳名次再按 ID 排序
+        assert_eq!(hits[0].id, "a");
+        assert_eq!(hits[1].id, "c");
+        assert_eq!(hits[0].keyword_rank, Some(1));
+        assert_eq!(hits[0].vector_rank, Some(3));
+        assert_eq!(hits[1].vector_score, Some(0.95));
+    }
+
+    #[test]
+    fn test_weighted_fusion_respects_weights() {
+        let keyword = candidates(&[("a", 12.0), ("b", 3.0)]);
+        let vector = candidates(&[("b", 0.9), ("a", 0.3)]);
+
+        let keyword_heavy = FusionMethod::Weighted { keyword_weight: 0.8, vector_weight: 0.2 };
+        assert_eq!(fuse(keyword.clone(), vector.clone(), &keyword_heavy, 1)[0].id, "a");
+
+        let vector_heavy = FusionMethod::Weighted { keyword_weight: 0.2, vector_weight: 0.8 };
+        let hits = fuse(keyword, vector, &vector_heavy, 2);
+        assert_eq!(hits[0].id, "b");
+        assert!((hits[0].score - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate_fusion_method() {
+        assert!(FusionMethod::default().validate().is_ok());
+        assert!(FusionMethod::Weighted { keyword_weight: 0.0, vector_weight: 0.0 }.validate().is_err());
+        assert!(FusionMethod::ReciprocalRank { k: -1.0 }.validate().is_err());
+    }
+}
@@ -1,9 +1,13 @@
 //! # 日志记录数据库模块 (PostgreSQL)
 //! 
 //! 提供结构化日志存储、查询、统计和自动清理功能
+//!
+//! 适配器日志单独保存在 `adapter_logs` 表中，按 `adapter_id` 区分，
+//! 每个适配器可以配置自己的保留策略（`adapter_log_retention` 表）。
 
 use serde::{Deserialize, Serialize};
 use crate::database::DbPool;
+use crate::utils::clock::{self, SharedClock};
 use tracing::{info, warn, debug};
 use chrono::Utc;
 use std::collections::HashMap;
@@ -13,7 +17,7 @@ use std::collections::HashMap;
 // ================================
 
 /// 日志级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Trace,
@@ -53,6 +57,22 @@ impl std::str::FromStr for LogLevel {
     }
 }
 
+impl LogLevel {
+    const ALL: [LogLevel; 6] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+        LogLevel::Fatal,
+    ];
+
+    /// 不低于该级别的所有级别名（用于 SQL 过滤）
+    pub fn at_least(self) -> Vec<String> {
+        Self::ALL.iter().filter(|level| **level >= self).map(|level| level.to_string()).collect()
+    }
+}
+
 /// 日志条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -98,12 +118,97 @@ pub struct LogStatistics {
     pub recent_warnings: i64,
 }
 
+/// 适配器日志条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdapterLogEntry {
+    pub id: i64,
+    pub adapter_id: String,
+    pub level: LogLevel,
+    pub message: String,
+    /// 结构化字段
+    pub context: Option<serde_json::Value>,
+    /// 记录时间（Unix 毫秒）
+    pub timestamp_ms: i64,
+}
+
+impl AdapterLogEntry {
+    fn from_row(row: &tokio_postgres::Row) -> Self {
+        let level: String = row.get("level");
+        Self {
+            id: row.get("id"),
+            adapter_id: row.get("adapter_id"),
+            level: level.parse().unwrap_or(LogLevel::Info),
+            message: row.get("message"),
+            context: row.get("context"),
+            timestamp_ms: row.get("timestamp_ms"),
+        }
+    }
+}
+
+/// 适配器日志查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdapterLogFilter {
+    /// 最低级别
+    pub min_level: Option<LogLevel>,
+    pub keyword: Option<String>,
+    /// 只返回 ID 小于该值的日志（向前翻页）
+    pub before_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// 适配器日志保留策略，两个条件任一满足即删除
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdapterLogRetention {
+    /// 保留天数
+    pub max_age_days: u32,
+    /// 最多保留的条数
+    pub max_entries: u32,
+}
+
+impl AdapterLogRetention {
+    pub const MAX_AGE_DAYS_RANGE: std::ops::RangeInclusive<u32> = 1..=365;
+    pub const MAX_ENTRIES_RANGE: std::ops::RangeInclusive<u32> = 100..=1_000_000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !Self::MAX_AGE_DAYS_RANGE.contains(&self.max_age_days) {
+            return Err(format!(
+                "保留天数必须在 {} 到 {} 之间",
+                Self::MAX_AGE_DAYS_RANGE.start(),
+                Self::MAX_AGE_DAYS_RANGE.end()
+            ));
+        }
+        if !Self::MAX_ENTRIES_RANGE.contains(&self.max_entries) {
+            return Err(format!(
+                "保留条数必须在 {} 到 {} 之间",
+                Self::MAX_ENTRIES_RANGE.start(),
+                Self::MAX_ENTRIES_RANGE.end()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 早于该时间（Unix 毫秒）的日志应删除
+    pub fn cutoff_ms(&self, now_ms: i64) -> i64 {
+        now_ms - self.max_age_days as i64 * 24 * 60 * 60 * 1000
+    }
+}
+
+impl Default for AdapterLogRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: 7,
+            max_entries: 10_000,
+        }
+    }
+}
+
 // ================================
 // 日志注册表
 // ================================
 
 pub struct LoggingRegistry {
     pool: DbPool,
+    clock: SharedClock,
 }
 
 // Type alias for backward compatibility
@@ -111,7 +216,13 @@ pub type LogDatabase = LoggingRegistry;
 
 impl LoggingRegistry {
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        Self { pool, clock: clock::global() }
+    }
+
+    /// 注入时钟（用于测试保留策略）
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
     }
 
     /// 初始化数据库表
@@ -146,6 +257,24 @@ impl LoggingRegistry {
             client.execute(index_sql, &[]).await?;
         }
 
+        // 适配器日志与保留策略
+        client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS adapter_logs (
+                id BIGSERIAL PRIMARY KEY,
+                adapter_id TEXT NOT NULL,
+                level TEXT NOT NULL,
+                message TEXT NOT NULL,
+                context JSONB,
+                timestamp_ms BIGINT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_adapter_logs_adapter ON adapter_logs(adapter_id, id DESC);
+            CREATE TABLE IF NOT EXISTS adapter_log_retention (
+                adapter_id TEXT PRIMARY KEY,
+                max_age_days INTEGER NOT NULL,
+                max_entries INTEGER NOT NULL
+            );",
+        ).await?;
+
         info!("✅ 日志记录表初始化完成");
         Ok(())
     }
@@ -377,7 +506,7 @@ impl LoggingRegistry {
         }
 
         // 最近24小时的错误和警告
-        let recent_cutoff = Utc::now() - chrono::Duration::hours(24);
+        let recent_cutoff = self.clock.now() - chrono::Duration::hours(24);
         
         let recent_errors_row = client.query_one(
             "SELECT COUNT(*) as count FROM logs WHERE level = 'error' AND timestamp >= $1",
@@ -404,7 +533,7 @@ impl LoggingRegistry {
     pub async fn cleanup_old_logs_async(&self, days: i64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.pool.get().await?;
 
-        let cutoff = Utc::now() - chrono::Duration::days(days);
+        let cutoff = self.clock.now() - chrono::Duration::days(days);
 
         let affected = client.execute(
             "DELETE FROM logs WHERE timestamp < $1",
@@ -419,7 +548,7 @@ impl LoggingRegistry {
     pub async fn cleanup_logs_by_level_async(&self, level: &str, days: i64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.pool.get().await?;
 
-        let cutoff = Utc::now() - chrono::Duration::days(days);
+        let cutoff = self.clock.now() - chrono::Duration::days(days);
 
         let affected = client.execute(
             "DELETE FROM logs WHERE level = $1 AND timestamp < $2",
@@ -451,7 +580,7 @@ impl LoggingRegistry {
             &[],
         ).await?;
 
-        let cutoff = Utc::now() - chrono::Duration::days(days);
+        let cutoff = self.clock.now() - chrono::Duration::days(days);
 
         // 复制到归档表
         let transaction = client.transaction().await?;
@@ -729,6 +858,138 @@ impl LoggingRegistry {
         self.cleanup_old_logs_async(retention_days as i64).await
     }
 
+    // ================================
+    // 适配器日志
+    // ================================
+
+    /// 记录适配器日志，返回带 ID 的条目
+    pub async fn append_adapter_log(
+        &self,
+        adapter_id: &str,
+        level: LogLevel,
+        message: &str,
+        context: Option<&serde_json::Value>,
+        timestamp_ms: i64,
+    ) -> Result<AdapterLogEntry, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_one(
+            "INSERT INTO adapter_logs (adapter_id, level, message, context, timestamp_ms)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *",
+            &[&adapter_id, &level.to_string(), &message, &context, &timestamp_ms],
+        ).await?;
+
+        Ok(AdapterLogEntry::from_row(&row))
+    }
+
+    /// 查询适配器日志（新的在前）
+    pub async fn get_adapter_logs(
+        &self,
+        adapter_id: &str,
+        filter: &AdapterLogFilter,
+    ) -> Result<Vec<AdapterLogEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+
+        let levels = filter.min_level.unwrap_or(LogLevel::Trace).at_least();
+        let pattern = filter.keyword.as_ref().map(|keyword| format!("%{}%", keyword));
+        let limit = filter.limit.unwrap_or(200).clamp(1, 1000);
+
+        let rows = client.query(
+            "SELECT * FROM adapter_logs
+            WHERE adapter_id = $1
+              AND level = ANY($2)
+              AND ($3::TEXT IS NULL OR message ILIKE $3)
+              AND ($4::BIGINT IS NULL OR id < $4)
+            ORDER BY id DESC
+            LIMIT $5",
+            &[&adapter_id, &levels, &pattern, &filter.before_id, &limit],
+        ).await?;
+
+        Ok(rows.iter().map(AdapterLogEntry::from_row).collect())
+    }
+
+    /// 获取适配器的日志保留策略（未配置时为默认策略）
+    pub async fn get_adapter_log_retention(&self, adapter_id: &str) -> Result<AdapterLogRetention, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+
+        let row = client.query_opt(
+            "SELECT max_age_days, max_entries FROM adapter_log_retention WHERE adapter_id = $1",
+            &[&adapter_id],
+        ).await?;
+
+        Ok(match row {
+            Some(row) => AdapterLogRetention {
+                max_age_days: row.get::<_, i32>("max_age_days") as u32,
+                max_entries: row.get::<_, i32>("max_entries") as u32,
+            },
+            None => AdapterLogRetention::default(),
+        })
+    }
+
+    /// 保存适配器的日志保留策略
+    pub async fn set_adapter_log_retention(
+        &self,
+        adapter_id: &str,
+        retention: &AdapterLogRetention,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        retention.validate()?;
+        let client = self.pool.get().await?;
+
+        client.execute(
+            "INSERT INTO adapter_log_retention (adapter_id, max_age_days, max_entries)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (adapter_id) DO UPDATE SET max_age_days = $2, max_entries = $3",
+            &[&adapter_id, &(retention.max_age_days as i32), &(retention.max_entries as i32)],
+        ).await?;
+
+        Ok(())
+    }
+
+    /// 按保留策略清理一个适配器的日志，返回删除数量
+    pub async fn apply_adapter_log_retention(&self, adapter_id: &str, now_ms: i64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let retention = self.get_adapter_log_retention(adapter_id).await?;
+        let client = self.pool.get().await?;
+
+        let expired = client.execute(
+            "DELETE FROM adapter_logs WHERE adapter_id = $1 AND timestamp_ms < $2",
+            &[&adapter_id, &retention.cutoff_ms(now_ms)],
+        ).await?;
+        // 第 max_entries + 1 新的条目及更旧的条目
+        let overflow = client.execute(
+            "DELETE FROM adapter_logs WHERE adapter_id = $1 AND id <= (
+                SELECT id FROM adapter_logs WHERE adapter_id = $1
+                ORDER BY id DESC OFFSET $2 LIMIT 1
+            )",
+            &[&adapter_id, &(retention.max_entries as i64)],
+        ).await?;
+
+        Ok(expired + overflow)
+    }
+
+    /// 按各自的保留策略清理所有适配器的日志，返回删除数量
+    pub async fn apply_all_adapter_log_retention(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let adapter_ids: Vec<String> = {
+            let client = self.pool.get().await?;
+            client
+                .query("SELECT DISTINCT adapter_id FROM adapter_logs", &[])
+                .await?
+                .iter()
+                .map(|row| row.get(0))
+                .collect()
+        };
+
+        let now_ms = self.clock.now().timestamp_millis();
+        let mut removed = 0;
+        for adapter_id in adapter_ids {
+            removed += self.apply_adapter_log_retention(&adapter_id, now_ms).await?;
+        }
+        if removed > 0 {
+            info!("🗑️  按保留策略清理了 {} 条适配器日志", removed);
+        }
+        Ok(removed)
+    }
+
     /// 获取远程日志配置
     pub async fn get_remote_config(&self) -> Result<crate::commands::logging::RemoteLogConfig, Box<dyn std::error::Error + Send + Sync>> {
         let client = self.pool.get().await?;
@@ -984,6 +1245,18 @@ mod tests {
     // LogLevel 单元测试
     // ================================
     
+    #[test]
+    fn test_adapter_log_retention() {
+        assert_eq!(LogLevel::Warn.at_least(), vec!["warn", "error", "fatal"]);
+        assert_eq!(LogLevel::Trace.at_least().len(), 6);
+
+        let retention = AdapterLogRetention::default();
+        assert!(retention.validate().is_ok());
+        assert_eq!(retention.cutoff_ms(8 * 86_400_000), 86_400_000);
+        assert!(AdapterLogRetention { max_age_days: 0, ..retention }.validate().is_err());
+        assert!(AdapterLogRetention { max_entries: 10, ..retention }.validate().is_err());
+    }
+
     #[test]
     fn test_log_level_display() {
         assert_eq!(LogLevel::Trace.to_string(), "trace");
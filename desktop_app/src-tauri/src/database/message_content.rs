@@ -0,0 +1,242 @@
+//! 结构化消息内容
+//!
+//! 消息除纯文本外还可以携带类型化的内容块（代码、表格、任务列表、手绘），
+//! 以 JSONB 形式保存在 `messages.payload` 中。保存前必须通过 [`validate_blocks`]
+//! 校验；`content` 列始终保存由内容块生成的纯文本，供搜索、向量化和旧版客户端使用。
+
+use serde::{Deserialize, Serialize};
+
+/// 单条消息最多包含的内容块数
+pub const MAX_BLOCKS: usize = 64;
+/// 表格最大行数 / 列数
+pub const MAX_TABLE_ROWS: usize = 500;
+pub const MAX_TABLE_COLUMNS: usize = 32;
+/// 手绘最大笔画数 / 单笔最大点数
+pub const MAX_STROKES: usize = 2000;
+pub const MAX_POINTS_PER_STROKE: usize = 5000;
+
+/// 任务列表项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// 手绘笔画，坐标为画布内的像素坐标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stroke {
+    /// `#RRGGBB` 或 `#RRGGBBAA`
+    pub color: String,
+    pub width: f32,
+    pub points: Vec<[f32; 2]>,
+}
+
+/// 内容块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    TaskList {
+        items: Vec<TaskItem>,
+    },
+    Drawing {
+        width: u32,
+        height: u32,
+        strokes: Vec<Stroke>,
+        /// 替代文本（用于无障碍与纯文本导出）
+        #[serde(default)]
+        alt: Option<String>,
+    },
+}
+
+fn is_valid_color(color: &str) -> bool {
+    let hex = match color.strip_prefix('#') {
+        Some(hex) => hex,
+        None => return false,
+    };
+    matches!(hex.len(), 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl ContentBlock {
+    /// 校验单个内容块
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ContentBlock::Text { .. } => Ok(()),
+            ContentBlock::Code { language, .. } => {
+                if let Some(language) = language {
+                    let valid = !language.is_empty()
+                        && language.len() <= 32
+                        && language.chars().all(|c| c.is_ascii_alphanumeric() || "+-#._".contains(c));
+                    if !valid {
+                        return Err(format!("无效的代码语言标识: {}", language));
+                    }
+                }
+                Ok(())
+            }
+            ContentBlock::Table { headers, rows } => {
+                if headers.is_empty() || headers.len() > MAX_TABLE_COLUMNS {
+                    return Err(format!("表格列数必须在 1 到 {} 之间", MAX_TABLE_COLUMNS));
+                }
+                if rows.len() > MAX_TABLE_ROWS {
+                    return Err(format!("表格行数不能超过 {}", MAX_TABLE_ROWS));
+                }
+                if let Some(index) = rows.iter().position(|row| row.len() != headers.len()) {
+                    return Err(format!("表格第 {} 行的列数与表头不一致", index + 1));
+                }
+                Ok(())
+            }
+            ContentBlock::TaskList { items } => {
+                if items.is_empty() {
+                    return Err("任务列表不能为空".to_string());
+                }
+                Ok(())
+            }
+            ContentBlock::Drawing { width, height, strokes, .. } => {
+                if *width == 0 || *height == 0 || *width > 8192 || *height > 8192 {
+                    return Err("画布尺寸必须在 1 到 8192 之间".to_string());
+                }
+                if strokes.len() > MAX_STROKES {
+                    return Err(format!("笔画数不能超过 {}", MAX_STROKES));
+                }
+                for stroke in strokes {
+                    if !is_valid_color(&stroke.color) {
+                        return Err(format!("无效的笔画颜色: {}", stroke.color));
+                    }
+                    if !(stroke.width > 0.0 && stroke.width <= 256.0) {
+                        return Err("笔画宽度必须在 0 到 256 之间".to_string());
+                    }
+                    if stroke.points.is_empty() || stroke.points.len() > MAX_POINTS_PER_STROKE {
+                        return Err(format!("单笔点数必须在 1 到 {} 之间", MAX_POINTS_PER_STROKE));
+                    }
+                    let out_of_bounds = stroke.points.iter().any(|[x, y]| {
+                        !x.is_finite() || !y.is_finite() || *x < 0.0 || *y < 0.0 || *x > *width as f32 || *y > *height as f32
+                    });
+                    if out_of_bounds {
+                        return Err("笔画坐标超出画布范围".to_string());
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 纯文本表示
+    pub fn to_plain_text(&self) -> String {
+        match self {
+            ContentBlock::Text { text } => text.clone(),
+            ContentBlock::Code { code, .. } => code.clone(),
+            ContentBlock::Table { headers, rows } => std::iter::once(headers)
+                .chain(rows.iter())
+                .map(|row| row.join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ContentBlock::TaskList { items } => items
+                .iter()
+                .map(|item| format!("{} {}", if item.done { "[x]" } else { "[ ]" }, item.text))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ContentBlock::Drawing { alt, .. } => format!("[绘图{}]", alt.as_deref().map(|a| format!(": {}", a)).unwrap_or_default()),
+        }
+    }
+
+    /// Markdown 表示
+    pub fn to_markdown(&self) -> String {
+        match self {
+            ContentBlock::Text { text } => text.clone(),
+            ContentBlock::Code { language, code } => {
+                // 代码中包含 ``` 时使用更长的围栏
+                let fence = if code.contains("```") { "````" } else { "```" };
+                format!("{}{}\n{}\n{}", fence, language.as_deref().unwrap_or(""), code, fence)
+            }
+            ContentBlock::Table { headers, rows } => {
+                let escape = |cell: &String| cell.replace('|', "\\|").replace('\n', " ");
+                let mut lines = vec![
+                    format!("| {} |", headers.iter().map(escape).collect::<Vec<_>>().join(" | ")),
+                    format!("|{}|", vec![" --- "; headers.len()].join("|")),
+                ];
+                lines.extend(rows.iter().map(|row| format!("| {} |", row.iter().map(escape).collect::<Vec<_>>().join(" | "))));
+                lines.join("\n")
+            }
+            ContentBlock::TaskList { items } => items
+                .iter()
+                .map(|item| format!("- {} {}", if item.done { "[x]" } else { "[ ]" }, item.text))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ContentBlock::Drawing { width, height, strokes, alt } => format!(
+                "*[绘图 {}×{}，{} 笔{}]*",
+                width,
+                height,
+                strokes.len(),
+                alt.as_deref().map(|a| format!("：{}", a)).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// 校验整条消息的内容块
+pub fn validate_blocks(blocks: &[ContentBlock]) -> Result<(), String> {
+    if blocks.is_empty() {
+        return Err("消息内容不能为空".to_string());
+    }
+    if blocks.len() > MAX_BLOCKS {
+        return Err(format!("内容块数量不能超过 {}", MAX_BLOCKS));
+    }
+    for (index, block) in blocks.iter().enumerate() {
+        block.validate().map_err(|e| format!("第 {} 个内容块无效: {}", index + 1, e))?;
+    }
+    Ok(())
+}
+
+/// 内容块的纯文本表示（写入 `content` 列）
+pub fn blocks_to_plain_text(blocks: &[ContentBlock]) -> String {
+    blocks.iter().map(ContentBlock::to_plain_text).collect::<Vec<_>>().join("\n\n")
+}
+
+/// 内容块的 Markdown 表示
+pub fn blocks_to_markdown(blocks: &[ContentBlock]) -> String {
+    blocks.iter().map(ContentBlock::to_markdown).collect::<Vec<_>>().join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_row_width_must_match_headers() {
+        let table = ContentBlock::Table {
+            headers: vec!["a".into(), "b".into()],
+            rows: vec![vec!["1".into(), "2".into()], vec!["3".into()]],
+        };
+        assert!(table.validate().unwrap_err().contains("第 2 行"));
+    }
+
+    #[test]
+    fn test_drawing_rejects_out_of_bounds_points() {
+        let drawing = ContentBlock::Drawing {
+            width: 100,
+            height: 100,
+            strokes: vec![Stroke { color: "#ff0000".into(), width: 2.0, points: vec![[10.0, 10.0], [150.0, 10.0]] }],
+            alt: None,
+        };
+        assert!(drawing.validate().is_err());
+    }
+
+    #[test]
+    fn test_serde_tagged_roundtrip() {
+        let json = r#"[{"type":"code","language":"rust","code":"fn main() {}"},{"type":"task_list","items":[{"text":"写测试","done":true}]}]"#;
+        let blocks: Vec<ContentBlock> = serde_json::from_str(json).unwrap();
+        assert!(validate_blocks(&blocks).is_ok());
+        assert_eq!(blocks_to_markdown(&blocks), "```rust\nfn main() {}\n```\n\n- [x] 写测试");
+    }
+}
@@ -0,0 +1,265 @@
+//! 通知中心模块
+//!
+//! 保存发给用户的通知（`notifications` 表），由 `commands::notifications` 创建与管理。
+//! 通知可以带操作按钮，按钮触发的命令必须预先注册；已读与忽略的时间分别记录，
+//! 忽略的通知不再出现在通知列表中。
+
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+
+use crate::state::tray_state::NotificationType;
+
+/// 每条通知最多的操作按钮数
+pub const MAX_ACTIONS: usize = 3;
+/// 保留的通知数量，超出时删除最旧的
+pub const MAX_STORED: i64 = 500;
+
+/// 通知的操作按钮
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+    /// 点击时执行的已注册命令
+    pub command: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+/// 通知中心中的通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredNotification {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub notification_type: NotificationType,
+    /// 发出通知的模块（如 `backup`、`storage`）
+    pub source: Option<String>,
+    pub actions: Vec<NotificationAction>,
+    pub created_at: i64,
+    pub read_at: Option<i64>,
+    pub dismissed_at: Option<i64>,
+}
+
+impl StoredNotification {
+    fn from_row(row: &Row) -> Self {
+        let notification_type: String = row.get("notification_type");
+        let actions: serde_json::Value = row.get("actions");
+        Self {
+            id: row.get("id"),
+            title: row.get("title"),
+            body: row.get("body"),
+            notification_type: parse_type(&notification_type),
+            source: row.get("source"),
+            actions: serde_json::from_value(actions).unwrap_or_default(),
+            created_at: row.get("created_at"),
+            read_at: row.get("read_at"),
+            dismissed_at: row.get("dismissed_at"),
+        }
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+
+    pub fn action(&self, action_id: &str) -> Option<&NotificationAction> {
+        self.actions.iter().find(|action| action.id == action_id)
+    }
+}
+
+pub fn type_as_str(notification_type: &NotificationType) -> &'static str {
+    match notification_type {
+        NotificationType::Info => "info",
+        NotificationType::Warning => "warning",
+        NotificationType::Error => "error",
+        NotificationType::Success => "success",
+        NotificationType::Message => "message",
+    }
+}
+
+fn parse_type(value: &str) -> NotificationType {
+    match value {
+        "warning" => NotificationType::Warning,
+        "error" => NotificationType::Error,
+        "success" => NotificationType::Success,
+        "message" => NotificationType::Message,
+        _ => NotificationType::Info,
+    }
+}
+
+/// 检查操作按钮：数量、ID 唯一、文字非空，命令已注册
+pub fn validate_actions(actions: &[NotificationAction], is_registered: impl Fn(&str) -> bool) -> Result<(), String> {
+    if actions.len() > MAX_ACTIONS {
+        return Err(format!("每条通知最多 {} 个操作按钮", MAX_ACTIONS));
+    }
+    for (index, action) in actions.iter().enumerate() {
+        if action.id.trim().is_empty() || action.label.trim().is_empty() {
+            return Err("操作按钮的 ID 与文字不能为空".to_string());
+        }
+        if actions[..index].iter().any(|other| other.id == action.id) {
+            return Err(format!("操作按钮 ID 重复: {}", action.id));
+        }
+        if !is_registered(&action.command) {
+            return Err(format!("未注册的通知命令: {}", action.command));
+        }
+    }
+    Ok(())
+}
+
+/// 通知中心数据表
+pub struct NotificationRegistry {
+    pool: Pool,
+}
+
+impl NotificationRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS notifications (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    body TEXT NOT NULL,
+                    notification_type TEXT NOT NULL,
+                    source TEXT,
+                    actions JSONB NOT NULL DEFAULT '[]',
+                    created_at BIGINT NOT NULL,
+                    read_at BIGINT,
+                    dismissed_at BIGINT
+                );
+                CREATE INDEX IF NOT EXISTS idx_notifications_created ON notifications(created_at DESC);",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 保存通知，并删除超出保留数量的旧通知
+    pub async fn insert(&self, notification: &StoredNotification) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let actions = serde_json::to_value(&notification.actions)?;
+        client
+            .execute(
+                "INSERT INTO notifications (id, title, body, notification_type, source, actions, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &notification.id,
+                    &notification.title,
+                    &notification.body,
+                    &type_as_str(&notification.notification_type),
+                    &notification.source,
+                    &actions,
+                    &notification.created_at,
+                ],
+            )
+            .await?;
+        client
+            .execute(
+                "DELETE FROM notifications WHERE id NOT IN (
+                    SELECT id FROM notifications ORDER BY created_at DESC LIMIT $1
+                 )",
+                &[&MAX_STORED],
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<StoredNotification>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt("SELECT * FROM notifications WHERE id = $1", &[&id]).await?;
+        Ok(row.as_ref().map(StoredNotification::from_row))
+    }
+
+    /// 未忽略的通知（新的在前）
+    pub async fn list(&self, unread_only: bool, limit: i64) -> Result<Vec<StoredNotification>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT * FROM notifications
+                 WHERE dismissed_at IS NULL AND (NOT $1 OR read_at IS NULL)
+                 ORDER BY created_at DESC LIMIT $2",
+                &[&unread_only, &limit],
+            )
+            .await?;
+        Ok(rows.iter().map(StoredNotification::from_row).collect())
+    }
+
+    pub async fn unread_count(&self) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one("SELECT COUNT(*) FROM notifications WHERE dismissed_at IS NULL AND read_at IS NULL", &[])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    pub async fn mark_read(&self, id: &str, now: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute("UPDATE notifications SET read_at = COALESCE(read_at, $2) WHERE id = $1", &[&id, &now])
+            .await?;
+        Ok(updated > 0)
+    }
+
+    /// 忽略通知（同时视为已读）
+    pub async fn dismiss(&self, id: &str, now: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client
+            .execute(
+                "UPDATE notifications SET dismissed_at = $2, read_at = COALESCE(read_at, $2) WHERE id = $1",
+                &[&id, &now],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    /// 删除全部通知，返回删除数量
+    pub async fn clear(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        Ok(client.execute("DELETE FROM notifications", &[]).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(id: &str, command: &str) -> NotificationAction {
+        NotificationAction {
+            id: id.to_string(),
+            label: "打开".to_string(),
+            command: command.to_string(),
+            args: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_validate_actions() {
+        let registered = |command: &str| command == "open_settings";
+        assert!(validate_actions(&[], registered).is_ok());
+        assert!(validate_actions(&[action("open", "open_settings")], registered).is_ok());
+        assert!(validate_actions(&[action("open", "delete_everything")], registered).is_err());
+        assert!(validate_actions(&[action("a", "open_settings"), action("a", "open_settings")], registered).is_err());
+        assert!(validate_actions(&[action(" ", "open_settings")], registered).is_err());
+
+        let many: Vec<_> = (0..=MAX_ACTIONS).map(|i| action(&i.to_string(), "open_settings")).collect();
+        assert!(validate_actions(&many, registered).is_err());
+    }
+
+    #[test]
+    fn test_notification_type_round_trip() {
+        for notification_type in [
+            NotificationType::Info,
+            NotificationType::Warning,
+            NotificationType::Error,
+            NotificationType::Success,
+            NotificationType::Message,
+        ] {
+            assert_eq!(parse_type(type_as_str(&notification_type)), notification_type);
+        }
+        assert_eq!(parse_type("unknown"), NotificationType::Info);
+    }
+}
@@ -569,6 +569,87 @@ impl PermissionRegistry {
         })
     }
 
+    /// 记住用户在确认对话框中的决定（授予或拒绝），到 `expires_at` 为止有效
+    pub fn remember_decision(
+        &self,
+        entity_type: String,
+        entity_id: String,
+        permission_type: PermissionType,
+        level: PermissionLevel,
+        scope: Option<String>,
+        granted: bool,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Handle::current().block_on(async {
+            let client = self.pool.get().await?;
+            let now = Utc::now().timestamp();
+            let status = if granted { PermissionStatus::Granted } else { PermissionStatus::Denied };
+            let granted_at = granted.then_some(now);
+
+            client.execute(
+                "INSERT INTO permission_grants (
+                    entity_type, entity_id, permission_type, level, status,
+                    scope, granted_by, granted_at, expires_at, created_at, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, 'user', $7, $8, $9, $10)
+                ON CONFLICT (entity_type, entity_id, permission_type, COALESCE(scope, ''))
+                DO UPDATE SET
+                    level = EXCLUDED.level,
+                    status = EXCLUDED.status,
+                    granted_by = EXCLUDED.granted_by,
+                    granted_at = EXCLUDED.granted_at,
+                    expires_at = EXCLUDED.expires_at,
+                    updated_at = EXCLUDED.updated_at",
+                &[
+                    &entity_type,
+                    &entity_id,
+                    &permission_type.to_string(),
+                    &level.to_string(),
+                    &status.to_string(),
+                    &scope,
+                    &granted_at,
+                    &expires_at.timestamp(),
+                    &now,
+                    &now,
+                ],
+            ).await?;
+
+            info!("已记住权限决定: {}::{} -> {:?} ({})", entity_type, entity_id, permission_type, status);
+            Ok(())
+        })
+    }
+
+    /// 检查是否有仍然有效的拒绝决定
+    pub fn is_denied(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        permission_type: &PermissionType,
+        scope: Option<&str>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Handle::current().block_on(async {
+            let client = self.pool.get().await?;
+            let now = Utc::now().timestamp();
+
+            let row = client.query_opt(
+                "SELECT id FROM permission_grants
+                 WHERE entity_type = $1 AND entity_id = $2
+                   AND permission_type = $3 AND COALESCE(scope, '') = $4
+                   AND status = 'denied'
+                   AND (expires_at IS NULL OR expires_at > $5)
+                 LIMIT 1",
+                &[
+                    &entity_type,
+                    &entity_id,
+                    &permission_type.to_string(),
+                    &scope.unwrap_or(""),
+                    &now,
+                ],
+            ).await?;
+
+            Ok(row.is_some())
+        })
+    }
+
     /// 获取所有权限定义
     pub fn get_all_permissions(&self) -> Result<Vec<Permission>, Box<dyn std::error::Error + Send + Sync>> {
         Handle::current().block_on(async {
@@ -0,0 +1,279 @@
+//! 桌宠统计数据模块
+//!
+//! 记录各类互动（聊天消息、点击、工作流运行等）、应用运行会话与完成的专注，
+//! 供统计页按时间范围聚合。
+
+use chrono::{Duration, NaiveDate};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::utils::focus_timer::FocusSessionRecord;
+
+/// 聊天消息
+pub const INTERACTION_MESSAGE: &str = "message";
+/// 工作流运行
+pub const INTERACTION_WORKFLOW: &str = "workflow";
+
+/// 互动记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionRecord {
+    pub kind: String,
+    pub character_id: Option<String>,
+    pub model: Option<String>,
+    /// 互动对象（如工作流 ID）
+    pub subject: Option<String>,
+    pub occurred_at: i64,
+}
+
+/// 计数项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountEntry {
+    pub key: String,
+    pub count: i64,
+}
+
+/// 应用运行会话（Unix 秒）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AppSession {
+    pub started_at: i64,
+    pub last_seen_at: i64,
+}
+
+/// 会话落在 `[from, to]` 内的总时长（秒）
+pub fn uptime_in_range(sessions: &[AppSession], from: i64, to: i64) -> i64 {
+    sessions
+        .iter()
+        .map(|s| (s.last_seen_at.min(to) - s.started_at.max(from)).max(0))
+        .sum()
+}
+
+/// 最长连续活跃天数
+pub fn longest_streak(days: &[NaiveDate]) -> i64 {
+    let days: HashSet<NaiveDate> = days.iter().copied().collect();
+    days.iter()
+        .filter(|day| !days.contains(&(**day - Duration::days(1))))
+        .map(|start| {
+            let mut length = 1;
+            while days.contains(&(*start + Duration::days(length))) {
+                length += 1;
+            }
+            length
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// 互动统计数据表
+pub struct PetStatisticsRegistry {
+    pool: Pool,
+}
+
+impl PetStatisticsRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS pet_interactions (
+                    id BIGSERIAL PRIMARY KEY,
+                    kind TEXT NOT NULL,
+                    character_id TEXT,
+                    model TEXT,
+                    subject TEXT,
+                    occurred_at BIGINT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_pet_interactions_time ON pet_interactions(occurred_at);
+                CREATE TABLE IF NOT EXISTS app_sessions (
+                    id TEXT PRIMARY KEY,
+                    started_at BIGINT NOT NULL,
+                    last_seen_at BIGINT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS focus_sessions (
+                    id BIGSERIAL PRIMARY KEY,
+                    started_at BIGINT NOT NULL,
+                    ended_at BIGINT NOT NULL,
+                    focus_secs BIGINT NOT NULL,
+                    task TEXT,
+                    character_id TEXT
+                );
+                CREATE INDEX IF NOT EXISTS idx_focus_sessions_ended ON focus_sessions(ended_at);",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 记录一次互动
+    pub async fn record_interaction(&self, record: &InteractionRecord) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO pet_interactions (kind, character_id, model, subject, occurred_at)
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[&record.kind, &record.character_id, &record.model, &record.subject, &record.occurred_at],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 更新应用会话的最后活跃时间（不存在时创建）
+    pub async fn touch_session(
+        &self,
+        session_id: &str,
+        started_at: i64,
+        now: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO app_sessions (id, started_at, last_seen_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET last_seen_at = EXCLUDED.last_seen_at",
+                &[&session_id, &started_at, &now],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 与时间范围有交集的应用会话
+    pub async fn sessions(&self, from: i64, to: i64) -> Result<Vec<AppSession>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT started_at, last_seen_at FROM app_sessions
+                 WHERE started_at <= $2 AND last_seen_at >= $1",
+                &[&from, &to],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| AppSession { started_at: row.get(0), last_seen_at: row.get(1) })
+            .collect())
+    }
+
+    /// 记录一次完成的专注
+    pub async fn record_focus_session(&self, record: &FocusSessionRecord) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO focus_sessions (started_at, ended_at, focus_secs, task, character_id)
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[&record.started_at, &record.ended_at, &record.focus_secs, &record.task, &record.character_id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 在时间范围内完成的专注
+    pub async fn focus_sessions(&self, from: i64, to: i64) -> Result<Vec<FocusSessionRecord>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT started_at, ended_at, focus_secs, task, character_id FROM focus_sessions
+                 WHERE ended_at BETWEEN $1 AND $2 ORDER BY ended_at",
+                &[&from, &to],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| FocusSessionRecord {
+                started_at: row.get(0),
+                ended_at: row.get(1),
+                focus_secs: row.get(2),
+                task: row.get(3),
+                character_id: row.get(4),
+            })
+            .collect())
+    }
+
+    /// 按互动类型计数
+    pub async fn counts_by_kind(&self, from: i64, to: i64) -> Result<Vec<CountEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT kind, COUNT(*) FROM pet_interactions
+                 WHERE occurred_at BETWEEN $1 AND $2
+                 GROUP BY kind ORDER BY COUNT(*) DESC, kind",
+                &[&from, &to],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| CountEntry { key: row.get(0), count: row.get(1) }).collect())
+    }
+
+    /// 某类互动按字段（`character_id`、`model` 或 `subject`）计数，忽略空值
+    pub async fn counts_by_field(
+        &self,
+        kind: &str,
+        field: &str,
+        from: i64,
+        to: i64,
+        limit: i64,
+    ) -> Result<Vec<CountEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        if !matches!(field, "character_id" | "model" | "subject") {
+            return Err(format!("不支持的统计字段: {}", field).into());
+        }
+        let client = self.pool.get().await?;
+        let sql = format!(
+            "SELECT {field}, COUNT(*) FROM pet_interactions
+             WHERE kind = $1 AND occurred_at BETWEEN $2 AND $3 AND {field} IS NOT NULL
+             GROUP BY {field} ORDER BY COUNT(*) DESC, {field} LIMIT $4",
+            field = field
+        );
+        let rows = client.query(sql.as_str(), &[&kind, &from, &to, &limit]).await?;
+        Ok(rows.iter().map(|row| CountEntry { key: row.get(0), count: row.get(1) }).collect())
+    }
+
+    /// 按本地小时（0-23）统计互动次数，`utc_offset` 为本地时区偏移秒数
+    pub async fn hourly_counts(
+        &self,
+        from: i64,
+        to: i64,
+        utc_offset: i64,
+    ) -> Result<[i64; 24], Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client
+            .query(
+                "SELECT (((occurred_at + $3) % 86400 + 86400) % 86400) / 3600 AS hour, COUNT(*)
+                 FROM pet_interactions WHERE occurred_at BETWEEN $1 AND $2
+                 GROUP BY hour",
+                &[&from, &to, &utc_offset],
+            )
+            .await?;
+        let mut hours = [0; 24];
+        for row in rows {
+            let hour: i64 = row.get(0);
+            if let Some(slot) = hours.get_mut(hour as usize) {
+                *slot = row.get(1);
+            }
+        }
+        Ok(hours)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_in_range_clips_sessions() {
+        let sessions = vec![
+            AppSession { started_at: 0, last_seen_at: 100 },
+            AppSession { started_at: 150, last_seen_at: 300 },
+            AppSession { started_at: 400, last_seen_at: 500 },
+        ];
+        assert_eq!(uptime_in_range(&sessions, 50, 200), 50 + 50);
+        assert_eq!(uptime_in_range(&sessions, 0, i64::MAX), 100 + 150 + 100);
+        assert_eq!(uptime_in_range(&sessions, 310, 390), 0);
+    }
+
+    #[test]
+    fn test_longest_streak() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        assert_eq!(longest_streak(&[day(1), day(2), day(4), day(5), day(6), day(9)]), 3);
+        assert_eq!(longest_streak(&[day(3)]), 1);
+        assert_eq!(longest_streak(&[]), 0);
+    }
+}
@@ -58,6 +58,15 @@ impl QdrantBackend {
             .ok_or_else(|| DatabaseError::ConnectionError("未连接到Qdrant".to_string()))
     }
 
+    /// 调用健康检查接口确认连接是否仍然可用
+    pub async fn ping(&self) -> DatabaseResult<()> {
+        self.get_client()?
+            .health_check()
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(format!("Qdrant健康检查失败: {}", e)))?;
+        Ok(())
+    }
+
     /// 将payload转换为HashMap
     fn payload_to_map(payload: &HashMap<String, qdrant_client::qdrant::Value>) -> DatabaseResult<HashMap<String, serde_json::Value>> {
         let mut map = HashMap::new();
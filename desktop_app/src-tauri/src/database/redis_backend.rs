@@ -55,6 +55,16 @@ impl RedisBackend {
             .ok_or_else(|| DatabaseError::ConnectionError("未连接到Redis".to_string()))
     }
 
+    /// 发送 PING 检查连接是否仍然可用
+    pub async fn ping(&self) -> DatabaseResult<()> {
+        let mut conn = self.get_manager()?.clone();
+        let _: String = redis::cmd("PING")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(format!("Redis PING失败: {}", e)))?;
+        Ok(())
+    }
+
     /// 构建完整的键名
     fn build_key(&self, collection: &str, key: &str) -> String {
         format!("{}{}:{}", self.key_prefix, collection, key)
@@ -64,6 +74,41 @@ impl RedisBackend {
     fn build_pattern(&self, collection: &str) -> String {
         format!("{}{}:*", self.key_prefix, collection)
     }
+
+    /// 删除所有匹配模式的键，返回删除数量
+    pub async fn delete_matching(&self, pattern: &str) -> DatabaseResult<u64> {
+        let manager = self.get_manager()?;
+        let mut conn = manager.clone();
+
+        // 使用SCAN命令获取所有匹配的键
+        let mut cursor = 0u64;
+        let mut deleted = 0u64;
+        loop {
+            let (new_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+            if !keys.is_empty() {
+                conn.del::<_, ()>(&keys)
+                    .await
+                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                deleted += keys.len() as u64;
+            }
+
+            cursor = new_cursor;
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        Ok(deleted)
+    }
 }
 
 impl Default for RedisBackend {
@@ -123,36 +168,7 @@ impl DatabaseBackend for RedisBackend {
     }
 
     async fn drop_collection(&self, name: &str) -> DatabaseResult<()> {
-        let manager = self.get_manager()?;
-        let mut conn = manager.clone();
-
-        let pattern = self.build_pattern(name);
-        
-        // 使用SCAN命令获取所有匹配的键
-        let mut cursor = 0u64;
-        loop {
-            let (new_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(&pattern)
-                .arg("COUNT")
-                .arg(100)
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-
-            if !keys.is_empty() {
-                conn.del::<_, ()>(&keys)
-                    .await
-                    .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
-            }
-
-            cursor = new_cursor;
-            if cursor == 0 {
-                break;
-            }
-        }
-
+        self.delete_matching(&self.build_pattern(name)).await?;
         info!("成功删除集合: {}", name);
         Ok(())
     }
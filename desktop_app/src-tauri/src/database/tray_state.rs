@@ -0,0 +1,56 @@
+//! 托盘状态持久化模块
+//!
+//! 保存 `TrayState` 中的最近对话与通知，启动时由 `commands::tray_state` 读取并与对话数据对账。
+
+use deadpool_postgres::Pool;
+
+/// 只保存一份托盘状态
+const ROW_ID: &str = "default";
+
+/// 托盘状态数据表
+pub struct TrayStateRegistry {
+    pool: Pool,
+}
+
+impl TrayStateRegistry {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// 初始化表结构
+    pub async fn init_tables(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS tray_state (
+                    id TEXT PRIMARY KEY,
+                    snapshot JSONB NOT NULL,
+                    updated_at BIGINT NOT NULL
+                );",
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 读取保存的托盘状态
+    pub async fn load(&self) -> Result<Option<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt("SELECT snapshot FROM tray_state WHERE id = $1", &[&ROW_ID])
+            .await?;
+        Ok(row.map(|r| r.get(0)))
+    }
+
+    /// 保存托盘状态（覆盖上一次）
+    pub async fn save(&self, snapshot: &serde_json::Value, now: i64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "INSERT INTO tray_state (id, snapshot, updated_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET snapshot = EXCLUDED.snapshot, updated_at = EXCLUDED.updated_at",
+                &[&ROW_ID, snapshot, &now],
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -5,15 +5,29 @@
 //! - 向量相似度匹配
 //! - AI对话历史检索
 //! - 文档向量化存储
+//! - 会话摘要（长期记忆）
+//! - 文档知识库（导入文档切分后的文本块）
+//! - 按集合选择嵌入提供者，提供者变化后重新嵌入
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::warn;
 
-use super::backends::{VectorDatabaseBackend, DatabaseResult, VectorSearchResult};
+use super::backends::{VectorDatabaseBackend, DatabaseError, DatabaseResult, VectorSearchResult};
+use super::embedding::{self, EmbeddingProviderConfig};
 use super::qdrant_backend::QdrantBackend;
 
+/// 重新嵌入时每批处理的条目数
+const REEMBED_BATCH_SIZE: usize = 32;
+/// 对话消息集合
+pub const CONVERSATION_COLLECTION: &str = "conversations";
+/// 会话摘要集合
+pub const SESSION_SUMMARY_COLLECTION: &str = "session_summaries";
+/// 文档知识库文本块集合
+pub const DOCUMENT_CHUNK_COLLECTION: &str = "document_chunks";
+
 /// 向量搜索服务
 pub struct VectorSearchService {
     backend: Arc<RwLock<QdrantBackend>>,
@@ -120,6 +134,7 @@ impl VectorSearchService {
         query_vector: Vec<f32>,
         limit: usize,
     ) -> DatabaseResult<Vec<VectorSearchResult>> {
+        Self::check_query_dimension(collection, &query_vector)?;
         self.backend
             .read()
             .await
@@ -135,6 +150,7 @@ impl VectorSearchService {
         limit: usize,
         filter: &super::backends::QueryOptions,
     ) -> DatabaseResult<Vec<VectorSearchResult>> {
+        Self::check_query_dimension(collection, &query_vector)?;
         self.backend
             .read()
             .await
@@ -153,12 +169,9 @@ impl VectorSearchService {
         message_vector: Vec<f32>,
         message_data: &ConversationMessage,
     ) -> DatabaseResult<()> {
-        const COLLECTION: &str = "conversations";
+        const COLLECTION: &str = CONVERSATION_COLLECTION;
         
-        // 确保集合存在
-        if !self.collection_exists(COLLECTION).await? {
-            self.create_collection(COLLECTION, 384).await?;
-        }
+        self.prepare_embedding_collection(COLLECTION, &[message_vector.len()]).await?;
         
         self.insert_vector(COLLECTION, message_id, message_vector, message_data)
             .await
@@ -170,7 +183,7 @@ impl VectorSearchService {
         query_vector: Vec<f32>,
         limit: usize,
     ) -> DatabaseResult<Vec<VectorSearchResult>> {
-        const COLLECTION: &str = "conversations";
+        const COLLECTION: &str = CONVERSATION_COLLECTION;
         
         self.search(COLLECTION, query_vector, limit).await
     }
@@ -184,7 +197,7 @@ impl VectorSearchService {
     ) -> DatabaseResult<Vec<VectorSearchResult>> {
         use crate::database::backends::{QueryCondition, QueryOperator, QueryOptions};
         
-        const COLLECTION: &str = "conversations";
+        const COLLECTION: &str = CONVERSATION_COLLECTION;
         
         let filter = QueryOptions {
             conditions: vec![QueryCondition {
@@ -203,7 +216,7 @@ impl VectorSearchService {
     
     /// 删除对话消息
     pub async fn delete_conversation_message(&self, message_id: &str) -> DatabaseResult<()> {
-        const COLLECTION: &str = "conversations";
+        const COLLECTION: &str = CONVERSATION_COLLECTION;
         self.delete_vector(COLLECTION, message_id).await
     }
     
@@ -220,10 +233,7 @@ impl VectorSearchService {
     ) -> DatabaseResult<()> {
         const COLLECTION: &str = "documents";
         
-        // 确保集合存在
-        if !self.collection_exists(COLLECTION).await? {
-            self.create_collection(COLLECTION, 384).await?;
-        }
+        self.prepare_embedding_collection(COLLECTION, &[document_vector.len()]).await?;
         
         self.insert_vector(COLLECTION, document_id, document_vector, document_data)
             .await
@@ -236,10 +246,8 @@ impl VectorSearchService {
     ) -> DatabaseResult<()> {
         const COLLECTION: &str = "documents";
         
-        // 确保集合存在
-        if !self.collection_exists(COLLECTION).await? {
-            self.create_collection(COLLECTION, 384).await?;
-        }
+        let dimensions: Vec<usize> = documents.iter().map(|(_, vector, _)| vector.len()).collect();
+        self.prepare_embedding_collection(COLLECTION, &dimensions).await?;
         
         self.batch_insert_vectors(COLLECTION, documents).await
     }
@@ -300,10 +308,7 @@ impl VectorSearchService {
     ) -> DatabaseResult<()> {
         const COLLECTION: &str = "knowledge_base";
         
-        // 确保集合存在
-        if !self.collection_exists(COLLECTION).await? {
-            self.create_collection(COLLECTION, 384).await?;
-        }
+        self.prepare_embedding_collection(COLLECTION, &[knowledge_vector.len()]).await?;
         
         self.insert_vector(COLLECTION, knowledge_id, knowledge_vector, knowledge_data)
             .await
@@ -325,6 +330,265 @@ impl VectorSearchService {
         const COLLECTION: &str = "knowledge_base";
         self.delete_vector(COLLECTION, knowledge_id).await
     }
+    
+    // ========================================
+    // 会话摘要（长期记忆）
+    // ========================================
+    
+    /// 存储会话摘要，同一会话的旧摘要被替换
+    pub async fn store_session_summary(
+        &self,
+        summary_vector: Vec<f32>,
+        summary: &SessionSummaryEntry,
+    ) -> DatabaseResult<()> {
+        self.prepare_embedding_collection(SESSION_SUMMARY_COLLECTION, &[summary_vector.len()]).await?;
+        
+        self.insert_vector(
+            SESSION_SUMMARY_COLLECTION,
+            &summary_point_id(&summary.session_id),
+            summary_vector,
+            summary,
+        )
+        .await
+    }
+    
+    /// 搜索相关的会话摘要；还没有摘要时返回空列表
+    pub async fn search_session_summaries(
+        &self,
+        query_vector: Vec<f32>,
+        limit: usize,
+    ) -> DatabaseResult<Vec<VectorSearchResult>> {
+        if !self.collection_exists(SESSION_SUMMARY_COLLECTION).await? {
+            return Ok(Vec::new());
+        }
+        self.search(SESSION_SUMMARY_COLLECTION, query_vector, limit).await
+    }
+    
+    /// 删除会话摘要
+    pub async fn delete_session_summary(&self, session_id: &str) -> DatabaseResult<()> {
+        self.delete_vector(SESSION_SUMMARY_COLLECTION, &summary_point_id(session_id))
+            .await
+    }
+    
+    // ========================================
+    // 文档知识库
+    // ========================================
+    
+    /// 写入文档文本块（同 ID 的旧块被覆盖），条目为 (点 ID, 向量, 文本块)
+    pub async fn store_document_chunks(&self, chunks: Vec<(String, Vec<f32>, Document)>) -> DatabaseResult<()> {
+        let dimensions: Vec<usize> = chunks.iter().map(|(_, vector, _)| vector.len()).collect();
+        self.prepare_embedding_collection(DOCUMENT_CHUNK_COLLECTION, &dimensions).await?;
+        
+        self.batch_insert_vectors(DOCUMENT_CHUNK_COLLECTION, chunks).await
+    }
+    
+    /// 搜索文档文本块；还没有导入文档时返回空列表
+    pub async fn search_document_chunks(
+        &self,
+        query_vector: Vec<f32>,
+        limit: usize,
+    ) -> DatabaseResult<Vec<VectorSearchResult>> {
+        if !self.collection_exists(DOCUMENT_CHUNK_COLLECTION).await? {
+            return Ok(Vec::new());
+        }
+        self.search(DOCUMENT_CHUNK_COLLECTION, query_vector, limit).await
+    }
+    
+    /// 删除文档文本块
+    pub async fn delete_document_chunk(&self, point_id: &str) -> DatabaseResult<()> {
+        self.delete_vector(DOCUMENT_CHUNK_COLLECTION, point_id).await
+    }
+    
+    // ========================================
+    // 嵌入与维度校验
+    // ========================================
+    
+    /// 使用集合配置的提供者生成向量
+    pub async fn embed(&self, collection: &str, texts: &[String]) -> DatabaseResult<Vec<Vec<f32>>> {
+        let (_, provider) = embedding::provider_for(collection)?;
+        embedding::embed_checked(provider.as_ref(), texts).await
+    }
+    
+    /// 使用集合配置的提供者生成单个向量
+    pub async fn embed_one(&self, collection: &str, text: &str) -> DatabaseResult<Vec<f32>> {
+        self.embed(collection, &[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| DatabaseError::InvalidData("嵌入结果为空".to_string()))
+    }
+    
+    /// 按集合当前嵌入提供者的维度创建集合，并记录索引使用的提供者
+    async fn create_embedding_collection(&self, collection: &str) -> DatabaseResult<()> {
+        let provider = embedding::current_settings().provider_for(collection).clone();
+        self.create_collection(collection, provider.dimension()).await?;
+        embedding::record_indexed(collection, &provider);
+        Ok(())
+    }
+    
+    /// 写入前的准备：集合不存在时按集合当前嵌入模型的维度创建，再校验待写入向量的维度
+    async fn prepare_embedding_collection(
+        &self,
+        collection: &str,
+        vector_dimensions: &[usize],
+    ) -> DatabaseResult<()> {
+        if !self.collection_exists(collection).await? {
+            self.create_embedding_collection(collection).await?;
+        }
+        Self::check_embedding(collection, vector_dimensions)
+    }
+    
+    /// 向量维度与集合当前的提供者一致，且集合已用该提供者建立索引
+    fn check_embedding(collection: &str, vector_dimensions: &[usize]) -> DatabaseResult<()> {
+        let provider = embedding::current_settings().provider_for(collection).clone();
+        for dimension in vector_dimensions {
+            embedding::validate_dimension(provider.dimension(), *dimension)?;
+        }
+        
+        let indexed = match embedding::indexed_collection(collection) {
+            Some(indexed) => indexed.fingerprint,
+            None => {
+                // 没有记录的集合由旧版本创建，使用默认维度的占位向量
+                let legacy = EmbeddingProviderConfig::default();
+                embedding::record_indexed(collection, &legacy);
+                legacy.fingerprint()
+            }
+        };
+        if indexed != provider.fingerprint() {
+            return Err(DatabaseError::InvalidData(format!(
+                "集合 {} 的嵌入提供者已变化，请先重新嵌入",
+                collection
+            )));
+        }
+        Ok(())
+    }
+    
+    /// 查询向量维度必须与集合一致
+    fn check_query_dimension(collection: &str, query_vector: &[f32]) -> DatabaseResult<()> {
+        match embedding::indexed_collection(collection) {
+            Some(indexed) => embedding::validate_dimension(indexed.dimension, query_vector.len()),
+            None => Ok(()),
+        }
+    }
+    
+    /// 列出集合中的全部条目（ID 与载荷）；集合不存在时返回空列表
+    pub async fn list_entries(&self, collection: &str) -> DatabaseResult<Vec<(String, serde_json::Value)>> {
+        use super::backends::{DatabaseBackend, QueryOptions};
+        
+        if !self.collection_exists(collection).await? {
+            return Ok(Vec::new());
+        }
+        let backend = self.backend.read().await;
+        let total = backend.count(collection, None).await?;
+        let options = QueryOptions {
+            limit: Some(total.max(1)),
+            ..Default::default()
+        };
+        backend.query(collection, &options).await
+    }
+    
+    /// 用集合当前配置的提供者重新嵌入全部条目
+    ///
+    /// 先为所有条目生成新向量，全部成功后才重建集合，失败时旧索引保持不变。
+    /// `on_progress(已处理, 总数)` 在每批完成后调用。
+    pub async fn reembed_collection<F>(&self, collection: &str, on_progress: F) -> DatabaseResult<ReembedReport>
+    where
+        F: Fn(usize, usize),
+    {
+        let (config, provider) = embedding::provider_for(collection)?;
+        let points = self.list_entries(collection).await?;
+        
+        let total = points.len();
+        let mut items = Vec::with_capacity(total);
+        let mut skipped = 0;
+        for (batch_index, batch) in points.chunks(REEMBED_BATCH_SIZE).enumerate() {
+            let mut texts = Vec::with_capacity(batch.len());
+            let mut entries = Vec::with_capacity(batch.len());
+            for (id, payload) in batch {
+                match embedding_text(collection, payload) {
+                    Some(text) => {
+                        texts.push(text);
+                        entries.push((id.clone(), payload.clone()));
+                    }
+                    None => skipped += 1,
+                }
+            }
+            if !texts.is_empty() {
+                let vectors = embedding::embed_checked(provider.as_ref(), &texts).await?;
+                items.extend(
+                    entries
+                        .into_iter()
+                        .zip(vectors)
+                        .map(|((id, payload), vector)| (id, vector, payload)),
+                );
+            }
+            on_progress((batch_index * REEMBED_BATCH_SIZE + batch.len()).min(total), total);
+        }
+        
+        if self.collection_exists(collection).await? {
+            self.delete_collection(collection).await?;
+        }
+        self.create_collection(collection, config.dimension()).await?;
+        let reembedded = items.len();
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            let batch: Vec<_> = items.by_ref().take(REEMBED_BATCH_SIZE).collect();
+            self.batch_insert_vectors(collection, batch).await?;
+        }
+        embedding::record_indexed(collection, &config);
+        
+        Ok(ReembedReport {
+            collection: collection.to_string(),
+            fingerprint: config.fingerprint(),
+            dimension: config.dimension(),
+            reembedded,
+            skipped,
+        })
+    }
+}
+
+/// 条目中用于生成向量的文本
+pub fn embedding_text(collection: &str, payload: &serde_json::Value) -> Option<String> {
+    let field = |name: &str| payload.get(name).and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty());
+    if collection == "knowledge_base" {
+        return match (field("question"), field("answer")) {
+            (Some(question), Some(answer)) => Some(format!("{}\n{}", question, answer)),
+            (question, answer) => question.or(answer).map(str::to_string),
+        };
+    }
+    field("content").map(str::to_string)
+}
+
+/// 会话摘要在集合中的点 ID（Qdrant 只接受数字 ID，由会话 ID 的哈希得到）
+pub fn summary_point_id(session_id: &str) -> String {
+    hashed_point_id(session_id)
+}
+
+/// 文档文本块的点 ID，由文件路径与块序号的哈希得到
+pub fn document_chunk_point_id(source_path: &str, chunk_index: usize) -> String {
+    hashed_point_id(&format!("{}#{}", source_path, chunk_index))
+}
+
+fn hashed_point_id(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    // 保留 63 位，避免部分客户端把 ID 当作有符号数
+    (u64::from_be_bytes(bytes) >> 1).to_string()
+}
+
+/// 重新嵌入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReembedReport {
+    /// 集合名称
+    pub collection: String,
+    /// 新提供者指纹
+    pub fingerprint: String,
+    /// 新向量维度
+    pub dimension: usize,
+    /// 重新嵌入的条目数
+    pub reembedded: usize,
+    /// 缺少文本而跳过的条目数
+    pub skipped: usize,
 }
 
 // ========================================
@@ -350,6 +614,21 @@ pub struct ConversationMessage {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// 会话摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummaryEntry {
+    /// 会话ID
+    pub session_id: String,
+    /// 会话标题
+    pub title: String,
+    /// 摘要内容
+    pub content: String,
+    /// 生成摘要时的消息数
+    pub message_count: usize,
+    /// 生成时间
+    pub generated_at: i64,
+}
+
 /// 文档
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
@@ -397,37 +676,11 @@ pub struct Knowledge {
 pub struct VectorEmbedding;
 
 impl VectorEmbedding {
-    /// 生成文本向量（需要接入实际的嵌入模型）
-    /// 这里提供一个占位实现，实际使用时应替换为真实的嵌入模型
+    /// 生成占位文本向量（哈希向量，没有语义，仅用于开发测试）
+    /// 实际的嵌入模型通过 `VectorSearchService::embed` 按集合配置选择
     pub async fn embed_text(text: &str) -> DatabaseResult<Vec<f32>> {
-        // TODO: 接入实际的嵌入模型（如 OpenAI Embeddings, Sentence Transformers 等）
-        // 这里返回一个简单的占位向量
         warn!("使用占位向量嵌入，请接入实际的嵌入模型");
-        
-        // 使用简单的哈希生成伪向量（仅用于开发测试）
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        text.hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        // 生成384维向量（默认维度）
-        let mut vector = vec![0.0f32; 384];
-        for i in 0..384 {
-            let seed = hash.wrapping_add(i as u64);
-            vector[i] = ((seed % 10000) as f32 / 10000.0) - 0.5;
-        }
-        
-        // 归一化
-        let magnitude: f32 = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
-        if magnitude > 0.0 {
-            for v in &mut vector {
-                *v /= magnitude;
-            }
-        }
-        
-        Ok(vector)
+        Ok(embedding::HashEmbedding::new(embedding::DEFAULT_DIMENSION).embed_one(text))
     }
     
     /// 批量生成文本向量
@@ -472,6 +725,18 @@ mod tests {
     // VectorEmbedding测试
     // ========================================
     
+    #[test]
+    fn test_summary_point_id_is_stable_number() {
+        let id = summary_point_id("session_123");
+        assert_eq!(id, summary_point_id("session_123"));
+        assert_ne!(id, summary_point_id("session_124"));
+        assert!(id.parse::<u64>().unwrap() < 1 << 63);
+        
+        let chunk = document_chunk_point_id("/docs/a.md", 0);
+        assert_ne!(chunk, document_chunk_point_id("/docs/a.md", 1));
+        assert!(chunk.parse::<u64>().is_ok());
+    }
+    
     #[tokio::test]
     async fn test_vector_embedding() {
         let text = "这是一个测试文本";
@@ -1110,5 +1375,18 @@ mod tests {
         assert_eq!(deserialized.question, knowledge.question);
         assert_eq!(deserialized.answer, knowledge.answer);
     }
+    
+    #[test]
+    fn test_embedding_text_by_collection() {
+        let knowledge = serde_json::json!({"question": "桌宠怎么换装？", "answer": "右键打开衣柜"});
+        assert_eq!(
+            embedding_text("knowledge_base", &knowledge).as_deref(),
+            Some("桌宠怎么换装？\n右键打开衣柜")
+        );
+        assert_eq!(
+            embedding_text("conversations", &serde_json::json!({"content": "你好"})).as_deref(),
+            Some("你好")
+        );
+        assert_eq!(embedding_text("documents", &serde_json::json!({"content": "  "})), None);
+    }
 }
-
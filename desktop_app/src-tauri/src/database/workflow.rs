@@ -1,6 +1,8 @@
 //! # 工作流数据库持久化模块 (PostgreSQL)
 //! 
 //! 提供工作流定义、执行历史、调度任务的数据库存储和管理功能
+//!
+//! 定时触发（`workflow_schedules` 表）由 `commands::workflow_schedule` 调度
 
 use serde::{Deserialize, Serialize};
 use tracing::{info, debug};
@@ -108,6 +110,24 @@ impl WorkflowRegistry {
              CREATE INDEX IF NOT EXISTS idx_workflows_created_at ON workflows(created_at);"
         ).await?;
 
+        // 创建定时触发表
+        client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS workflow_schedules (
+                id TEXT PRIMARY KEY,
+                workflow_id TEXT NOT NULL,
+                cron TEXT NOT NULL,
+                input_data JSONB,
+                paused BOOLEAN NOT NULL DEFAULT false,
+                next_run_at BIGINT,
+                last_run_at BIGINT,
+                last_status TEXT,
+                last_error TEXT,
+                created_at BIGINT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_workflow_schedules_due ON workflow_schedules(paused, next_run_at);
+            CREATE INDEX IF NOT EXISTS idx_workflow_schedules_workflow ON workflow_schedules(workflow_id);"
+        ).await?;
+
         info!("工作流数据库表初始化完成");
         Ok(())
     }
@@ -532,6 +552,137 @@ impl WorkflowRegistry {
             })
         })
     }
+
+    // ================================
+    // 定时触发
+    // ================================
+
+    /// 保存定时触发（按 ID 覆盖）
+    pub async fn save_schedule(&self, schedule: &WorkflowSchedule) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client.execute(
+            "INSERT INTO workflow_schedules (
+                id, workflow_id, cron, input_data, paused, next_run_at, last_run_at, last_status, last_error, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (id) DO UPDATE SET
+                workflow_id = EXCLUDED.workflow_id,
+                cron = EXCLUDED.cron,
+                input_data = EXCLUDED.input_data,
+                paused = EXCLUDED.paused,
+                next_run_at = EXCLUDED.next_run_at",
+            &[
+                &schedule.id,
+                &schedule.workflow_id,
+                &schedule.cron,
+                &schedule.input_data,
+                &schedule.paused,
+                &schedule.next_run_at,
+                &schedule.last_run_at,
+                &schedule.last_status,
+                &schedule.last_error,
+                &schedule.created_at,
+            ],
+        ).await?;
+        debug!("工作流定时触发已保存: {} ({})", schedule.id, schedule.cron);
+        Ok(())
+    }
+
+    /// 获取定时触发
+    pub async fn get_schedule(&self, id: &str) -> Result<Option<WorkflowSchedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt(
+            &format!("SELECT {} FROM workflow_schedules WHERE id = $1", SCHEDULE_COLUMNS),
+            &[&id],
+        ).await?;
+        Ok(row.as_ref().map(WorkflowSchedule::from_row))
+    }
+
+    /// 列出定时触发，可按工作流过滤
+    pub async fn list_schedules(&self, workflow_id: Option<&str>) -> Result<Vec<WorkflowSchedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client.query(
+            &format!(
+                "SELECT {} FROM workflow_schedules
+                 WHERE $1::TEXT IS NULL OR workflow_id = $1
+                 ORDER BY created_at",
+                SCHEDULE_COLUMNS
+            ),
+            &[&workflow_id],
+        ).await?;
+        Ok(rows.iter().map(WorkflowSchedule::from_row).collect())
+    }
+
+    /// 删除定时触发
+    pub async fn delete_schedule(&self, id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let removed = client.execute("DELETE FROM workflow_schedules WHERE id = $1", &[&id]).await?;
+        Ok(removed > 0)
+    }
+
+    /// 暂停或恢复定时触发，恢复时写入新的下次触发时间
+    pub async fn set_schedule_paused(
+        &self,
+        id: &str,
+        paused: bool,
+        next_run_at: Option<i64>,
+    ) -> Result<Option<WorkflowSchedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let row = client.query_opt(
+            &format!(
+                "UPDATE workflow_schedules SET paused = $2, next_run_at = $3 WHERE id = $1 RETURNING {}",
+                SCHEDULE_COLUMNS
+            ),
+            &[&id, &paused, &next_run_at],
+        ).await?;
+        Ok(row.as_ref().map(WorkflowSchedule::from_row))
+    }
+
+    /// 到期且未暂停的定时触发
+    pub async fn due_schedules(&self, now: i64) -> Result<Vec<WorkflowSchedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let rows = client.query(
+            &format!(
+                "SELECT {} FROM workflow_schedules
+                 WHERE NOT paused AND next_run_at IS NOT NULL AND next_run_at <= $1
+                 ORDER BY next_run_at",
+                SCHEDULE_COLUMNS
+            ),
+            &[&now],
+        ).await?;
+        Ok(rows.iter().map(WorkflowSchedule::from_row).collect())
+    }
+
+    /// 认领一次到期的触发并写入下次触发时间；下次触发时间已被改动（已认领、已暂停或已修改）时返回 false
+    pub async fn claim_schedule_run(
+        &self,
+        id: &str,
+        expected_next_run_at: i64,
+        next_run_at: Option<i64>,
+        now: i64,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        let updated = client.execute(
+            "UPDATE workflow_schedules SET next_run_at = $3, last_run_at = $4, last_status = 'running', last_error = NULL
+             WHERE id = $1 AND NOT paused AND next_run_at = $2",
+            &[&id, &expected_next_run_at, &next_run_at, &now],
+        ).await?;
+        Ok(updated > 0)
+    }
+
+    /// 记录一次触发的结果
+    pub async fn record_schedule_result(
+        &self,
+        id: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.pool.get().await?;
+        client.execute(
+            "UPDATE workflow_schedules SET last_status = $2, last_error = $3 WHERE id = $1",
+            &[&id, &status, &error],
+        ).await?;
+        Ok(())
+    }
 }
 
 impl Clone for WorkflowRegistry {
@@ -546,6 +697,45 @@ impl Clone for WorkflowRegistry {
 // 辅助数据结构
 // ================================
 
+const SCHEDULE_COLUMNS: &str =
+    "id, workflow_id, cron, input_data, paused, next_run_at, last_run_at, last_status, last_error, created_at";
+
+/// 工作流定时触发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSchedule {
+    pub id: String,
+    pub workflow_id: String,
+    /// cron 表达式（本地时间）
+    pub cron: String,
+    /// 每次执行的输入
+    pub input_data: Option<JsonValue>,
+    pub paused: bool,
+    /// 下次触发时间（Unix 秒），暂停时为空
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    /// 最近一次执行状态：running / completed / failed 等
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+impl WorkflowSchedule {
+    fn from_row(row: &tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("id"),
+            workflow_id: row.get("workflow_id"),
+            cron: row.get("cron"),
+            input_data: row.get("input_data"),
+            paused: row.get("paused"),
+            next_run_at: row.get("next_run_at"),
+            last_run_at: row.get("last_run_at"),
+            last_status: row.get("last_status"),
+            last_error: row.get("last_error"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
 /// 工作流统计信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowStats {
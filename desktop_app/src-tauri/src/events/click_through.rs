@@ -0,0 +1,143 @@
+//! 桌宠窗口点击穿透
+//!
+//! 开启点击穿透（`WindowConfig::click_through`）后，后台线程按鼠标位置查询渲染器上报的命中遮罩
+//! （遮罩与命中计算见 [`crate::utils::click_through`]）：鼠标在角色身上时窗口接收鼠标事件，
+//! 否则忽略鼠标事件，点击落到下层应用。渲染器还没有上报遮罩时整个窗口保持可交互。
+//!
+//! 开关或窗口可交互状态变化时发出 `click-through-changed` 事件。
+
+use lazy_static::lazy_static;
+use mouse_position::mouse_position::Mouse;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Window};
+use tracing::warn;
+
+use crate::state::AppState;
+use crate::utils::click_through::{self, HitTestMask};
+use crate::utils::watchdog;
+
+/// 参与点击穿透的窗口
+const CLICK_THROUGH_WINDOW: &str = "main";
+/// 开启时的鼠标轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+/// 关闭时只保持心跳
+const IDLE_INTERVAL: Duration = Duration::from_millis(250);
+const STALL_AFTER: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct ClickThroughRuntime {
+    mask: Option<HitTestMask>,
+    /// 窗口当前是否忽略鼠标事件
+    ignoring: bool,
+}
+
+lazy_static! {
+    static ref RUNTIME: Mutex<ClickThroughRuntime> = Mutex::new(ClickThroughRuntime::default());
+}
+
+/// 点击穿透状态（`get_click_through_state` 与 `click-through-changed` 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct ClickThroughState {
+    pub enabled: bool,
+    pub has_mask: bool,
+    /// 遮罩中有角色像素的格数
+    pub filled_cells: usize,
+    /// 窗口当前是否接收鼠标事件
+    pub interactive: bool,
+}
+
+fn enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.config.lock().window.click_through)
+        .unwrap_or(false)
+}
+
+pub fn state(app_handle: &AppHandle) -> ClickThroughState {
+    let runtime = RUNTIME.lock();
+    ClickThroughState {
+        enabled: enabled(app_handle),
+        has_mask: runtime.mask.is_some(),
+        filled_cells: runtime.mask.as_ref().map_or(0, HitTestMask::filled),
+        interactive: !runtime.ignoring,
+    }
+}
+
+fn emit_state(app_handle: &AppHandle) {
+    let _ = app_handle.emit_all("click-through-changed", state(app_handle));
+}
+
+/// 切换窗口是否忽略鼠标事件，状态变化时通知前端
+fn set_ignoring(window: &Window, ignore: bool) {
+    {
+        let mut runtime = RUNTIME.lock();
+        if runtime.ignoring == ignore {
+            return;
+        }
+        if let Err(e) = window.set_ignore_cursor_events(ignore) {
+            warn!("设置窗口点击穿透失败: {}", e);
+            return;
+        }
+        runtime.ignoring = ignore;
+    }
+    emit_state(&window.app_handle());
+}
+
+/// 按鼠标位置更新窗口是否可交互
+fn tick(window: &Window) {
+    let Mouse::Position { x, y } = Mouse::get_mouse_position() else {
+        return;
+    };
+    let (Ok(position), Ok(size)) = (window.inner_position(), window.inner_size()) else {
+        return;
+    };
+    let interactive = click_through::interactive(
+        RUNTIME.lock().mask.as_ref(),
+        (x - position.x) as f64,
+        (y - position.y) as f64,
+        size.width as f64,
+        size.height as f64,
+    );
+    set_ignoring(window, !interactive);
+}
+
+/// 更新渲染器上报的命中遮罩（`None` 表示清除，整个窗口可交互）
+pub fn set_mask(mask: Option<HitTestMask>) {
+    RUNTIME.lock().mask = mask;
+}
+
+/// 开关变化后立即生效：关闭时恢复窗口接收鼠标事件
+pub fn apply(app_handle: &AppHandle) {
+    if !enabled(app_handle) {
+        if let Some(window) = app_handle.get_window(CLICK_THROUGH_WINDOW) {
+            set_ignoring(&window, false);
+        }
+    }
+    emit_state(app_handle);
+}
+
+/// 启动鼠标轮询线程
+pub fn start_click_through(app_handle: AppHandle) {
+    watchdog::supervise("click_through", STALL_AFTER, move |heartbeat| {
+        let app_handle = app_handle.clone();
+        let spawned = std::thread::Builder::new()
+            .name("click-through".to_string())
+            .spawn(move || {
+                while heartbeat.beat() {
+                    if !enabled(&app_handle) {
+                        std::thread::sleep(IDLE_INTERVAL);
+                        continue;
+                    }
+                    if let Some(window) = app_handle.get_window(CLICK_THROUGH_WINDOW) {
+                        tick(&window);
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("启动点击穿透线程失败: {}", e);
+        }
+    });
+}
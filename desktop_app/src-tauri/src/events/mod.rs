@@ -3,6 +3,9 @@
 //! 提供窗口事件、托盘事件等各种事件的处理功能
 
 pub mod window;
+pub mod window_layout;
+pub mod window_dock;
+pub mod click_through;
 pub mod tray;
 pub mod chat;
 pub mod character;
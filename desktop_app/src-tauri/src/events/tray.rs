@@ -6,6 +6,8 @@
 //! - 托盘事件处理（点击、双击、右键等）
 //! - 托盘通知
 //! - 动态菜单状态更新
+//! - 适配器与工作流注册的扩展菜单项（见 `commands::tray_menu`）
+//! - 菜单、通知与对话框文字按界面语言显示（见 `utils::i18n`）
 
 use tauri::{
     api::shell, AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, 
@@ -14,6 +16,8 @@ use tauri::{
 use tracing::{debug, error, info, warn};
 
 use crate::state::AppState;
+use crate::utils::i18n::{t, t_args};
+use crate::utils::tray_menu::{self, TrayMenuItem};
 
 /// 系统托盘事件处理器
 pub struct TrayEventHandler {
@@ -110,6 +114,11 @@ impl TrayEventHandler {
             "restart" => self.restart_app(),
             "quit" => self.quit_app(),
             
+            // 适配器与工作流注册的扩展菜单项
+            id if tray_menu::is_plugin_item(id) => {
+                crate::commands::tray_menu::handle_click(&self.app_handle, id);
+            }
+            
             _ => {
                 warn!("未处理的托盘菜单项: {}", menu_id);
             }
@@ -134,7 +143,7 @@ impl TrayEventHandler {
                 "chat",
                 WindowUrl::App("index.html#/chat".into())
             )
-            .title("Zishu Sensei - 聊天")
+            .title(&t("window-title-chat"))
             .inner_size(800.0, 600.0)
             .min_inner_size(600.0, 400.0)
             .resizable(true)
@@ -145,12 +154,13 @@ impl TrayEventHandler {
             .build();
 
             match chat_window {
-                Ok(_window) => {
+                Ok(window) => {
                     info!("聊天窗口创建成功");
+                    super::window_layout::restore_position(&window);
                 }
                 Err(e) => {
                     error!("创建聊天窗口失败: {}", e);
-                    self.show_error_notification("无法打开聊天窗口", &e.to_string());
+                    self.show_error_notification(&t("notify-open-chat-failed"), &e.to_string());
                 }
             }
         }
@@ -181,7 +191,7 @@ impl TrayEventHandler {
                 window_label,
                 WindowUrl::App(format!("index.html#/settings?tab={}", tab).into())
             )
-            .title("Zishu Sensei - 设置")
+            .title(&t("window-title-settings"))
             .inner_size(900.0, 700.0)
             .min_inner_size(800.0, 600.0)
             .resizable(true)
@@ -197,7 +207,7 @@ impl TrayEventHandler {
                 }
                 Err(e) => {
                     error!("创建设置窗口失败: {}", e);
-                    self.show_error_notification("无法打开设置窗口", &e.to_string());
+                    self.show_error_notification(&t("notify-open-settings-failed"), &e.to_string());
                 }
             }
         }
@@ -210,7 +220,7 @@ impl TrayEventHandler {
         let url = "https://market.zishu.dev";
         if let Err(e) = shell::open(&self.app_handle.shell_scope(), url, None) {
             error!("打开适配器市场失败: {}", e);
-            self.show_error_notification("无法打开适配器市场", &e.to_string());
+            self.show_error_notification(&t("notify-open-market-failed"), &e.to_string());
         }
     }
 
@@ -232,7 +242,7 @@ impl TrayEventHandler {
                 window_label,
                 WindowUrl::App("index.html#/workflow".into())
             )
-            .title("Zishu Sensei - 工作流编辑器")
+            .title(&t("window-title-workflow"))
             .inner_size(1200.0, 800.0)
             .min_inner_size(1000.0, 600.0)
             .resizable(true)
@@ -248,7 +258,7 @@ impl TrayEventHandler {
                 }
                 Err(e) => {
                     error!("创建工作流编辑器窗口失败: {}", e);
-                    self.show_error_notification("无法打开工作流编辑器", &e.to_string());
+                    self.show_error_notification(&t("notify-open-workflow-failed"), &e.to_string());
                 }
             }
         }
@@ -261,7 +271,7 @@ impl TrayEventHandler {
         if let Some(main_window) = self.app_handle.get_window("main") {
             if let Err(e) = main_window.emit("take-screenshot", ()) {
                 error!("发送截图事件失败: {}", e);
-                self.show_error_notification("截图失败", &e.to_string());
+                self.show_error_notification(&t("notify-screenshot-failed"), &e.to_string());
             }
         }
     }
@@ -299,7 +309,7 @@ impl TrayEventHandler {
                     if config.system.show_notifications {
                         self.show_info_notification(
                             "Zishu Sensei",
-                            "应用已最小化到系统托盘"
+                            &t("notify-minimized-to-tray")
                         );
                     }
                 }
@@ -332,8 +342,12 @@ impl TrayEventHandler {
                     self.update_tray_menu();
                     
                     // 显示通知
-                    let msg = if new_state { "窗口已设置为置顶" } else { "窗口已取消置顶" };
-                    self.show_info_notification("Zishu Sensei", msg);
+                    let msg = if new_state {
+                        t("notify-always-on-top-enabled")
+                    } else {
+                        t("notify-always-on-top-disabled")
+                    };
+                    self.show_info_notification("Zishu Sensei", &msg);
                 }
             }
         }
@@ -359,18 +373,20 @@ impl TrayEventHandler {
         let version = self.app_handle.package_info().version.to_string();
         let message = format!(
             "🐾 Zishu Sensei Desktop Pet\n\n\
-            版本: {}\n\n\
-            基于 Tauri + React + Live2D 开发的智能桌面宠物应用\n\n\
+            {}\n\n\
+            {}\n\n\
             © 2025 Zishu Team\n\n\
             https://zishu.dev",
-            version
+            t_args("dialog-about-version", &[("version", version.as_str())]),
+            t("dialog-about-description")
         );
+        let title = t("dialog-about-title");
         
         if let Some(main_window) = self.app_handle.get_window("main") {
-            dialog::message(Some(&main_window), "关于 Zishu Sensei", message);
+            dialog::message(Some(&main_window), title, message);
         } else {
             // 如果主窗口不可用，使用 None
-            dialog::message(None::<&Window>, "关于 Zishu Sensei", message);
+            dialog::message(None::<&Window>, title, message);
         }
     }
 
@@ -385,7 +401,7 @@ impl TrayEventHandler {
             }
         }
         
-        self.show_info_notification("Zishu Sensei", "正在检查更新...");
+        self.show_info_notification("Zishu Sensei", &t("notify-checking-updates"));
     }
 
     /// 重启应用
@@ -394,8 +410,8 @@ impl TrayEventHandler {
         
         use tauri::api::dialog;
         
-        let message = "确定要重启应用吗？";
-        let title = "重启应用";
+        let message = t("dialog-restart-message");
+        let title = t("dialog-restart-title");
         
         let app_handle = self.app_handle.clone();
         
@@ -407,6 +423,7 @@ impl TrayEventHandler {
                 move |answer| {
                     if answer {
                         info!("用户确认重启应用");
+                        crate::utils::safe_mode::mark_clean_exit();
                         app_handle.restart();
                     } else {
                         info!("用户取消重启应用");
@@ -422,8 +439,8 @@ impl TrayEventHandler {
         
         use tauri::api::dialog;
         
-        let message = "确定要退出应用吗？";
-        let title = "退出应用";
+        let message = t("dialog-quit-message");
+        let title = t("dialog-quit-title");
         
         let app_handle = self.app_handle.clone();
         
@@ -435,6 +452,7 @@ impl TrayEventHandler {
                 move |answer| {
                     if answer {
                         info!("用户确认退出应用");
+                        crate::utils::safe_mode::mark_clean_exit();
                         app_handle.exit(0);
                     } else {
                         info!("用户取消退出应用");
@@ -443,6 +461,7 @@ impl TrayEventHandler {
             );
         } else {
             // 如果主窗口不可用，直接退出
+            crate::utils::safe_mode::mark_clean_exit();
             self.app_handle.exit(0);
         }
     }
@@ -473,7 +492,7 @@ impl TrayEventHandler {
     fn show_error_notification(&self, title: &str, error: &str) {
         use tauri::api::notification::Notification;
         
-        let body = format!("错误: {}", error);
+        let body = t_args("notify-error-body", &[("error", error)]);
         
         if let Err(e) = Notification::new(&self.app_handle.config().tauri.bundle.identifier)
             .title(title)
@@ -484,20 +503,37 @@ impl TrayEventHandler {
     }
 }
 
-/// 创建系统托盘菜单
+/// 创建系统托盘
 pub fn create_system_tray() -> SystemTray {
-    let chat_menu = CustomMenuItem::new("chat".to_string(), "💬 开始对话");
+    SystemTray::new().with_menu(build_tray_menu(&[]))
+}
+
+/// 扩展菜单项子菜单
+fn plugin_submenu(items: &[TrayMenuItem]) -> SystemTraySubmenu {
+    let menu = items.iter().fold(SystemTrayMenu::new(), |menu, item| {
+        let mut entry = CustomMenuItem::new(item.menu_id(), item.label.trim());
+        if !item.enabled {
+            entry = entry.disabled();
+        }
+        menu.add_item(entry)
+    });
+    SystemTraySubmenu::new(t("tray-extensions"), menu)
+}
+
+/// 构建托盘菜单，有扩展菜单项时显示「扩展」子菜单
+pub fn build_tray_menu(plugin_items: &[TrayMenuItem]) -> SystemTrayMenu {
+    let chat_menu = CustomMenuItem::new("chat".to_string(), t("tray-chat"));
     let separator1 = SystemTrayMenuItem::Separator;
     
     // 设置子菜单
-    let character_settings = CustomMenuItem::new("character_settings".to_string(), "🎭 角色设置");
-    let theme_settings = CustomMenuItem::new("theme_settings".to_string(), "🎨 主题设置");
-    let adapter_settings = CustomMenuItem::new("adapter_settings".to_string(), "🔧 适配器管理");
-    let sound_settings = CustomMenuItem::new("sound_settings".to_string(), "🔊 声音设置");
-    let system_settings = CustomMenuItem::new("system_settings".to_string(), "📱 系统设置");
+    let character_settings = CustomMenuItem::new("character_settings".to_string(), t("tray-character-settings"));
+    let theme_settings = CustomMenuItem::new("theme_settings".to_string(), t("tray-theme-settings"));
+    let adapter_settings = CustomMenuItem::new("adapter_settings".to_string(), t("tray-adapter-settings"));
+    let sound_settings = CustomMenuItem::new("sound_settings".to_string(), t("tray-sound-settings"));
+    let system_settings = CustomMenuItem::new("system_settings".to_string(), t("tray-system-settings"));
     
     let settings_submenu = SystemTraySubmenu::new(
-        "⚙️ 设置",
+        t("tray-settings"),
         SystemTrayMenu::new()
             .add_item(character_settings)
             .add_item(theme_settings)
@@ -507,12 +543,12 @@ pub fn create_system_tray() -> SystemTray {
     );
     
     // 角色动作子菜单
-    let character_idle = CustomMenuItem::new("character_idle".to_string(), "😊 待机");
-    let character_wave = CustomMenuItem::new("character_wave".to_string(), "👋 挥手");
-    let character_dance = CustomMenuItem::new("character_dance".to_string(), "💃 跳舞");
+    let character_idle = CustomMenuItem::new("character_idle".to_string(), t("tray-character-idle"));
+    let character_wave = CustomMenuItem::new("character_wave".to_string(), t("tray-character-wave"));
+    let character_dance = CustomMenuItem::new("character_dance".to_string(), t("tray-character-dance"));
     
     let character_submenu = SystemTraySubmenu::new(
-        "🎭 角色动作",
+        t("tray-character-actions"),
         SystemTrayMenu::new()
             .add_item(character_idle)
             .add_item(character_wave)
@@ -520,28 +556,32 @@ pub fn create_system_tray() -> SystemTray {
     );
     
     // 工具菜单
-    let adapter_market = CustomMenuItem::new("adapter_market".to_string(), "🔄 适配器市场");
-    let workflow_editor = CustomMenuItem::new("workflow_editor".to_string(), "📋 工作流编辑器");
-    let screenshot = CustomMenuItem::new("screenshot".to_string(), "📸 截图");
+    let adapter_market = CustomMenuItem::new("adapter_market".to_string(), t("tray-adapter-market"));
+    let workflow_editor = CustomMenuItem::new("workflow_editor".to_string(), t("tray-workflow-editor"));
+    let screenshot = CustomMenuItem::new("screenshot".to_string(), t("tray-screenshot"));
     let separator2 = SystemTrayMenuItem::Separator;
     
     // 窗口控制
-    let show_window = CustomMenuItem::new("show_window".to_string(), "👁️ 显示窗口");
-    let hide_window = CustomMenuItem::new("hide_window".to_string(), "🙈 隐藏窗口");
-    let toggle_always_on_top = CustomMenuItem::new("toggle_always_on_top".to_string(), "📌 切换置顶");
+    let show_window = CustomMenuItem::new("show_window".to_string(), t("tray-show-window"));
+    let hide_window = CustomMenuItem::new("hide_window".to_string(), t("tray-hide-window"));
+    let toggle_always_on_top = CustomMenuItem::new("toggle_always_on_top".to_string(), t("tray-toggle-always-on-top"));
     let separator3 = SystemTrayMenuItem::Separator;
     
     // 应用控制
-    let about = CustomMenuItem::new("about".to_string(), "ℹ️ 关于");
-    let check_updates = CustomMenuItem::new("check_updates".to_string(), "🔄 检查更新");
-    let restart = CustomMenuItem::new("restart".to_string(), "🔄 重启应用");
-    let quit = CustomMenuItem::new("quit".to_string(), "❌ 退出");
+    let about = CustomMenuItem::new("about".to_string(), t("tray-about"));
+    let check_updates = CustomMenuItem::new("check_updates".to_string(), t("tray-check-updates"));
+    let restart = CustomMenuItem::new("restart".to_string(), t("tray-restart"));
+    let quit = CustomMenuItem::new("quit".to_string(), t("tray-quit"));
 
-    let tray_menu = SystemTrayMenu::new()
+    let mut tray_menu = SystemTrayMenu::new()
         .add_item(chat_menu)
         .add_native_item(separator1)
         .add_submenu(settings_submenu)
-        .add_submenu(character_submenu)
+        .add_submenu(character_submenu);
+    if !plugin_items.is_empty() {
+        tray_menu = tray_menu.add_submenu(plugin_submenu(plugin_items));
+    }
+    tray_menu
         .add_item(adapter_market)
         .add_item(workflow_editor)
         .add_item(screenshot)
@@ -553,9 +593,7 @@ pub fn create_system_tray() -> SystemTray {
         .add_item(about)
         .add_item(check_updates)
         .add_item(restart)
-        .add_item(quit);
-
-    SystemTray::new().with_menu(tray_menu)
+        .add_item(quit)
 }
 
 /// 处理系统托盘事件的主函数（用于 Tauri 的 on_system_tray_event）
@@ -630,15 +668,33 @@ pub mod helpers {
         Ok(())
     }
 
-    /// 重建托盘菜单（用于动态更新菜单状态）
+    /// 重建托盘菜单（包含当前注册的扩展菜单项）
     pub fn rebuild_tray_menu(app_handle: &AppHandle) -> Result<(), String> {
-        // Note: The menu() method is private in Tauri 1.x
-        // We cannot directly rebuild the tray menu this way
-        // This is a limitation of the current Tauri version
-        warn!("托盘菜单重建功能在 Tauri 1.x 中受限");
+        let plugin_items = crate::commands::tray_menu::registered_items();
+        app_handle.tray_handle()
+            .set_menu(build_tray_menu(&plugin_items))
+            .map_err(|e| format!("重建托盘菜单失败: {}", e))?;
+        
+        debug!("托盘菜单已重建，扩展菜单项 {} 个", plugin_items.len());
         Ok(())
     }
 
+    /// 界面语言切换后重建托盘菜单，并更新已打开窗口的标题
+    pub fn refresh_localized_labels(app_handle: &AppHandle) -> Result<(), String> {
+        for (label, key) in [
+            ("chat", "window-title-chat"),
+            ("settings", "window-title-settings"),
+            ("workflow", "window-title-workflow"),
+        ] {
+            if let Some(window) = app_handle.get_window(label) {
+                if let Err(e) = window.set_title(&t(key)) {
+                    warn!("更新窗口标题失败 {}: {}", label, e);
+                }
+            }
+        }
+        rebuild_tray_menu(app_handle)
+    }
+
     /// 销毁托盘
     pub fn destroy_tray(app_handle: &AppHandle) -> Result<(), String> {
         app_handle.tray_handle()
@@ -649,6 +705,39 @@ pub mod helpers {
         Ok(())
     }
 
+    /// 根据数据库连接状态更新托盘图标状态与提示（`database-health-changed`）
+    pub fn apply_database_health(
+        app_handle: &AppHandle,
+        health: &crate::database::database_manager::DatabaseHealth,
+    ) {
+        use crate::state::tray_state::TrayIconState;
+        
+        let Some(app_state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        let current = app_state.tray.get_icon_state();
+        let (next, tooltip) = if health.is_core_healthy() {
+            // 只清除由数据库中断设置的错误状态
+            if current != TrayIconState::Error {
+                return;
+            }
+            (TrayIconState::Idle, "Zishu Sensei".to_string())
+        } else {
+            let error = health
+                .postgres
+                .error
+                .clone()
+                .unwrap_or_else(|| t("tray-connection-lost"));
+            (TrayIconState::Error, t_args("tray-tooltip-database-down", &[("error", error.as_str())]))
+        };
+        
+        app_state.tray.set_icon_state(next.clone());
+        if let Err(e) = update_tray_tooltip(app_handle, &tooltip) {
+            warn!("{}", e);
+        }
+        let _ = app_handle.emit_all("tray-status-changed", &next);
+    }
+
     /// 获取托盘菜单项状态（示例）
     pub fn get_menu_item_state(app_handle: &AppHandle, item_id: &str) -> Option<bool> {
         // Tauri 1.x 不直接支持获取菜单项状态
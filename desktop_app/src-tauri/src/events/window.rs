@@ -2,7 +2,7 @@
 //! 
 //! 负责处理所有窗口相关的事件，包括：
 //! - 窗口创建、关闭、显示、隐藏
-//! - 窗口移动、调整大小
+//! - 窗口移动、调整大小（贴边停靠见 `window_dock`）
 //! - 窗口焦点变化
 //! - 窗口状态变化（最大化、最小化等）
 //! - 配置自动保存
@@ -58,7 +58,7 @@ impl WindowEventHandler {
                         if config.system.show_notifications {
                             self.show_tray_notification(
                                 "Zishu Sensei",
-                                "应用已最小化到系统托盘"
+                                &crate::utils::i18n::t("notify-minimized-to-tray")
                             );
                         }
                     }
@@ -309,19 +309,31 @@ pub fn handle_window_event(event: tauri::GlobalWindowEvent) {
             handler.handle_focused(window, *focused);
         }
         tauri::WindowEvent::Moved(position) => {
+            // 停靠动画产生的移动不保存位置，也不带动其他窗口
+            if super::window_dock::handle_moved(window, *position) {
+                return;
+            }
             handler.handle_moved(window, *position);
+            super::window_layout::handle_moved(window, *position);
+            crate::commands::pet_placement::handle_moved(window, *position);
         }
         tauri::WindowEvent::Resized(size) => {
             handler.handle_resized(window, *size);
+            super::window_layout::handle_resized(window, *size);
+            crate::commands::pet_placement::handle_resized(window, *size);
         }
         tauri::WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
             handler.handle_scale_factor_changed(window, *scale_factor);
+            crate::commands::pet_placement::handle_scale_factor_changed(window, *scale_factor);
         }
         tauri::WindowEvent::ThemeChanged(theme) => {
             handler.handle_theme_changed(window, *theme);
         }
         tauri::WindowEvent::Destroyed => {
             info!("窗口 '{}' 已销毁", window.label());
+            crate::commands::event_subscription::handle_window_destroyed(window.label());
+            crate::commands::pet_interaction::handle_window_destroyed(&window.app_handle(), window.label());
+            crate::commands::speech_queue::handle_window_destroyed(window.label());
         }
         event => {
             // 处理文件拖放事件
@@ -0,0 +1,302 @@
+//! 桌宠贴边停靠
+//!
+//! 消费主窗口的移动事件（几何计算见 [`crate::utils::window_dock`]）：
+//!
+//! - 拖动停止一小段时间后，靠近屏幕边缘的窗口吸附到边缘并进入停靠状态，拖离边缘则取消停靠
+//! - 停靠的窗口可以收起为探头状态；前端在鼠标移入 / 移出时调用 `set_dock_hover`，
+//!   移入时滑出，开启自动收起时移出后延迟收起
+//! - 收起与滑出的动画产生的移动事件不视为用户拖动，也不写入窗口位置
+//!
+//! 状态变化时发出 `dock-state-changed` 事件。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, Position, Window};
+use tracing::{debug, warn};
+
+use crate::state::AppState;
+use crate::utils::window_dock::{self, DockEdge, DockMode, DockSettings};
+use crate::utils::window_layout::Rect;
+
+/// 参与停靠的窗口
+const DOCK_WINDOW: &str = "main";
+/// 停止拖动后等待该时长再判断是否停靠
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+const SLIDE_DURATION: Duration = Duration::from_millis(180);
+const SLIDE_STEPS: u32 = 12;
+const ANIMATION_GRACE: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct DockRuntime {
+    mode: DockMode,
+    edge: Option<DockEdge>,
+    /// 停靠时所在显示器的矩形（收起后窗口中心可能已不在该显示器上）
+    screen: Option<Rect>,
+    /// 移动计数，用于拖动防抖
+    generation: u64,
+    /// 动画计数，新的动画开始时旧的动画停止
+    animation: u64,
+    /// 动画进行中，期间的移动事件不视为拖动
+    animating: bool,
+    hovering: bool,
+}
+
+lazy_static! {
+    static ref RUNTIME: Mutex<DockRuntime> = Mutex::new(DockRuntime::default());
+}
+
+/// 停靠状态（`get_dock_state` 与 `dock-state-changed` 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct DockState {
+    pub enabled: bool,
+    pub mode: DockMode,
+    pub edge: Option<DockEdge>,
+    pub settings: DockSettings,
+}
+
+fn dock_settings(app_handle: &AppHandle) -> DockSettings {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.config.lock().window.docking.clone())
+        .unwrap_or_default()
+}
+
+pub fn state(app_handle: &AppHandle) -> DockState {
+    let settings = dock_settings(app_handle);
+    let runtime = RUNTIME.lock();
+    DockState {
+        enabled: settings.enabled,
+        mode: runtime.mode,
+        edge: runtime.edge,
+        settings,
+    }
+}
+
+fn emit_state(app_handle: &AppHandle) {
+    let _ = app_handle.emit_all("dock-state-changed", state(app_handle));
+}
+
+fn to_physical(window: &Window, logical: u32) -> i32 {
+    (logical as f64 * window.scale_factor().unwrap_or(1.0)).round() as i32
+}
+
+fn window_rect(window: &Window) -> Option<Rect> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    Some(Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width as i32,
+        height: size.height as i32,
+    })
+}
+
+fn monitor_rect(monitor: &tauri::Monitor) -> Rect {
+    Rect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width as i32,
+        height: monitor.size().height as i32,
+    }
+}
+
+/// 把窗口滑动到目标位置
+fn slide_to(window: &Window, target: (i32, i32)) {
+    let Some(start) = window_rect(window) else {
+        return;
+    };
+    let animation = {
+        let mut runtime = RUNTIME.lock();
+        runtime.animation += 1;
+        runtime.animating = true;
+        runtime.animation
+    };
+
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let step_delay = SLIDE_DURATION / SLIDE_STEPS;
+        for step in 1..=SLIDE_STEPS {
+            if RUNTIME.lock().animation != animation {
+                return;
+            }
+            // 缓出：开始快、结束慢
+            let t = step as f64 / SLIDE_STEPS as f64;
+            let eased = 1.0 - (1.0 - t).powi(3);
+            let x = start.x + ((target.0 - start.x) as f64 * eased).round() as i32;
+            let y = start.y + ((target.1 - start.y) as f64 * eased).round() as i32;
+            if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+                warn!("移动停靠窗口失败: {}", e);
+                break;
+            }
+            tokio::time::sleep(step_delay).await;
+        }
+        // 最后一步的移动事件可能稍后才到达
+        tokio::time::sleep(ANIMATION_GRACE).await;
+        let mut runtime = RUNTIME.lock();
+        if runtime.animation == animation {
+            runtime.animating = false;
+        }
+    });
+}
+
+/// 处理窗口移动事件；返回 `true` 表示这是停靠动画产生的移动，调用方应忽略
+pub fn handle_moved(window: &Window, _position: PhysicalPosition<i32>) -> bool {
+    if window.label() != DOCK_WINDOW {
+        return false;
+    }
+    let enabled = dock_settings(&window.app_handle()).enabled;
+
+    let generation = {
+        let mut runtime = RUNTIME.lock();
+        if runtime.animating {
+            return true;
+        }
+        if !enabled {
+            return false;
+        }
+        runtime.generation += 1;
+        runtime.generation
+    };
+
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SETTLE_DELAY).await;
+        settle(&window, generation);
+    });
+    false
+}
+
+/// 拖动停止后吸附到附近的边缘，或取消停靠
+fn settle(window: &Window, generation: u64) {
+    {
+        let runtime = RUNTIME.lock();
+        if runtime.generation != generation || runtime.animating {
+            return;
+        }
+    }
+    let app_handle = window.app_handle();
+    let settings = dock_settings(&app_handle);
+    let (Some(rect), Ok(Some(monitor))) = (window_rect(window), window.current_monitor()) else {
+        return;
+    };
+    let screen = monitor_rect(&monitor);
+    let monitors: Vec<Rect> = window
+        .available_monitors()
+        .map(|monitors| monitors.iter().map(monitor_rect).collect())
+        .unwrap_or_default();
+
+    let edge = window_dock::nearest_edge(rect, screen, &monitors, to_physical(window, settings.snap_distance));
+    let mode = if edge.is_some() { DockMode::Docked } else { DockMode::Floating };
+    let changed = {
+        let mut runtime = RUNTIME.lock();
+        let changed = runtime.edge != edge || runtime.mode != mode;
+        runtime.edge = edge;
+        runtime.screen = edge.map(|_| screen);
+        runtime.mode = mode;
+        changed
+    };
+
+    if let Some(edge) = edge {
+        let (x, y) = window_dock::docked_position(rect, screen, edge);
+        if (x, y) != (rect.x, rect.y) {
+            debug!("桌宠停靠到 {:?} 边缘", edge);
+            if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+                warn!("吸附到屏幕边缘失败: {}", e);
+            }
+        }
+    }
+    if changed {
+        emit_state(&app_handle);
+    }
+}
+
+/// 收起为探头状态（`peek = true`）或滑出为完整停靠
+pub fn set_peeking(window: &Window, peek: bool) -> Result<DockState, String> {
+    let app_handle = window.app_handle();
+    let settings = dock_settings(&app_handle);
+    if !settings.enabled {
+        return Err("贴边停靠未启用".to_string());
+    }
+    let rect = window_rect(window).ok_or("读取窗口位置失败")?;
+
+    let target = {
+        let mut runtime = RUNTIME.lock();
+        let (Some(edge), Some(screen)) = (runtime.edge, runtime.screen) else {
+            return Err("窗口没有停靠在屏幕边缘".to_string());
+        };
+        let wanted = if peek { DockMode::Peeking } else { DockMode::Docked };
+        if runtime.mode == wanted {
+            None
+        } else {
+            runtime.mode = wanted;
+            Some(if peek {
+                window_dock::peek_position(rect, screen, edge, to_physical(window, settings.peek_size))
+            } else {
+                window_dock::docked_position(rect, screen, edge)
+            })
+        }
+    };
+    if let Some(target) = target {
+        slide_to(window, target);
+        emit_state(&app_handle);
+    }
+    Ok(state(&app_handle))
+}
+
+/// 鼠标移入时滑出；开启自动收起时，移出后延迟收起
+pub fn set_hover(window: &Window, hovering: bool) -> Result<DockState, String> {
+    let app_handle = window.app_handle();
+    let (mode, generation) = {
+        let mut runtime = RUNTIME.lock();
+        runtime.hovering = hovering;
+        (runtime.mode, runtime.generation)
+    };
+
+    if hovering {
+        if mode == DockMode::Peeking {
+            return set_peeking(window, false);
+        }
+        return Ok(state(&app_handle));
+    }
+
+    let settings = dock_settings(&app_handle);
+    if settings.enabled && settings.auto_hide && mode == DockMode::Docked {
+        let window = window.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(settings.hide_delay_ms)).await;
+            let still_away = {
+                let runtime = RUNTIME.lock();
+                !runtime.hovering && runtime.generation == generation && runtime.mode == DockMode::Docked
+            };
+            if still_away {
+                if let Err(e) = set_peeking(&window, true) {
+                    debug!("自动收起跳过: {}", e);
+                }
+            }
+        });
+    }
+    Ok(state(&app_handle))
+}
+
+/// 关闭停靠：收起的窗口滑回屏幕内，并回到未停靠状态
+pub fn undock(window: &Window) {
+    let target = {
+        let mut runtime = RUNTIME.lock();
+        let target = match (runtime.mode, runtime.edge, runtime.screen, window_rect(window)) {
+            (DockMode::Peeking, Some(edge), Some(screen), Some(rect)) => {
+                Some(window_dock::docked_position(rect, screen, edge))
+            }
+            _ => None,
+        };
+        runtime.mode = DockMode::Floating;
+        runtime.edge = None;
+        runtime.screen = None;
+        target
+    };
+    if let Some(target) = target {
+        slide_to(window, target);
+    }
+    emit_state(&window.app_handle());
+}
@@ -0,0 +1,224 @@
+//! 窗口布局管理
+//!
+//! 消费窗口移动 / 调整大小事件，实现窗口之间的磁吸与编组移动（几何计算见
+//! [`crate::utils::window_layout`]）：
+//!
+//! - 编组移动：窗口移动时，与它贴合的窗口按相同位移跟随
+//! - 磁吸：窗口停止移动一小段时间后再吸附到附近窗口的边缘，避免与系统拖动冲突
+//! - 由本模块发起的移动会产生新的移动事件，按期望位置识别后跳过
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Position, Window};
+use tracing::{debug, warn};
+
+use crate::utils::window_layout::{self, LayoutSettings, Rect, WindowLayout};
+
+/// 停止移动后等待该时长再吸附
+const SNAP_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+struct LayoutState {
+    layout: WindowLayout,
+    /// 各窗口最近的矩形
+    rects: BTreeMap<String, Rect>,
+    /// 由本模块移动、尚未收到移动事件的窗口及其目标位置
+    expected: HashMap<String, (i32, i32)>,
+    /// 每个窗口的移动计数，用于吸附防抖
+    generations: HashMap<String, u64>,
+}
+
+lazy_static! {
+    static ref STATE: Mutex<LayoutState> = Mutex::new(LayoutState {
+        layout: window_layout::load_layout(),
+        ..LayoutState::default()
+    });
+}
+
+fn window_rect(window: &Window, position: PhysicalPosition<i32>) -> Option<Rect> {
+    let size = window.outer_size().ok()?;
+    Some(Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width as i32,
+        height: size.height as i32,
+    })
+}
+
+fn move_window(app_handle: &AppHandle, label: &str, x: i32, y: i32) {
+    if let Some(window) = app_handle.get_window(label) {
+        if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+            warn!("移动窗口 '{}' 失败: {}", label, e);
+        }
+    }
+}
+
+/// 当前可见的磁吸窗口矩形（尚未移动过的窗口直接读取其当前位置）
+fn visible_rects(app_handle: &AppHandle, rects: &BTreeMap<String, Rect>) -> BTreeMap<String, Rect> {
+    window_layout::MAGNETIC_WINDOWS
+        .iter()
+        .filter_map(|label| {
+            let window = app_handle.get_window(label)?;
+            if !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+                return None;
+            }
+            let rect = match rects.get(*label) {
+                Some(rect) => *rect,
+                None => window_rect(&window, window.outer_position().ok()?)?,
+            };
+            Some((label.to_string(), rect))
+        })
+        .collect()
+}
+
+/// 处理窗口移动事件
+pub fn handle_moved(window: &Window, position: PhysicalPosition<i32>) {
+    let label = window.label().to_string();
+    if !window_layout::is_magnetic(&label) {
+        return;
+    }
+    let Some(rect) = window_rect(window, position) else {
+        return;
+    };
+    let app_handle = window.app_handle();
+
+    let mut state = STATE.lock();
+    let previous = state.rects.insert(label.clone(), rect);
+    state.layout.positions.insert(label.clone(), (rect.x, rect.y));
+    if state.expected.get(&label) == Some(&(rect.x, rect.y)) {
+        state.expected.remove(&label);
+        return;
+    }
+    let settings = state.layout.settings.clone();
+    if !settings.enabled {
+        return;
+    }
+
+    // 编组移动：按移动前的位置找出贴合的窗口
+    let mut followers = Vec::new();
+    if let Some(previous) = previous.filter(|_| settings.move_as_group) {
+        let (dx, dy) = (rect.x - previous.x, rect.y - previous.y);
+        if dx != 0 || dy != 0 {
+            let mut before = visible_rects(&app_handle, &state.rects);
+            before.insert(label.clone(), previous);
+            for member in window_layout::attached_group(&before, &label) {
+                let moved = before[&member].translated(dx, dy);
+                state.rects.insert(member.clone(), moved);
+                state.layout.positions.insert(member.clone(), (moved.x, moved.y));
+                state.expected.insert(member.clone(), (moved.x, moved.y));
+                followers.push((member, moved));
+            }
+        }
+    }
+
+    let generation = {
+        let counter = state.generations.entry(label.clone()).or_default();
+        *counter += 1;
+        *counter
+    };
+    // 移动窗口可能同步触发移动事件，需在释放锁之后进行
+    drop(state);
+    for (member, moved) in followers {
+        move_window(&app_handle, &member, moved.x, moved.y);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SNAP_DELAY).await;
+        snap_when_settled(&app_handle, &label, generation);
+    });
+}
+
+/// 窗口停止移动后吸附到附近窗口，并保存布局
+fn snap_when_settled(app_handle: &AppHandle, label: &str, generation: u64) {
+    let layout = {
+        let state = STATE.lock();
+        if state.generations.get(label) != Some(&generation) {
+            return;
+        }
+        let Some(rect) = state.rects.get(label).copied() else {
+            return;
+        };
+        let visible = visible_rects(app_handle, &state.rects);
+        let group = if state.layout.settings.move_as_group {
+            window_layout::attached_group(&visible, label)
+        } else {
+            Default::default()
+        };
+        let others: Vec<Rect> = visible
+            .iter()
+            .filter(|(other, _)| other.as_str() != label && !group.contains(*other))
+            .map(|(_, rect)| *rect)
+            .collect();
+        let (dx, dy) = window_layout::snap_offset(rect, &others, state.layout.settings.snap_distance);
+        if dx != 0 || dy != 0 {
+            Err((rect.x + dx, rect.y + dy))
+        } else {
+            Ok(state.layout.clone())
+        }
+    };
+    let layout = match layout {
+        Ok(layout) => layout,
+        Err((x, y)) => {
+            debug!("窗口 '{}' 吸附到 ({}, {})", label, x, y);
+            // 不记为期望位置：吸附产生的移动事件会带动同组窗口，随后再次保存布局
+            move_window(app_handle, label, x, y);
+            return;
+        }
+    };
+    if let Err(e) = window_layout::save_layout(&layout) {
+        warn!("保存窗口布局失败: {}", e);
+    }
+}
+
+/// 处理窗口调整大小事件
+pub fn handle_resized(window: &Window, size: PhysicalSize<u32>) {
+    let label = window.label();
+    if !window_layout::is_magnetic(label) {
+        return;
+    }
+    if let Some(rect) = STATE.lock().rects.get_mut(label) {
+        rect.width = size.width as i32;
+        rect.height = size.height as i32;
+    }
+}
+
+/// 窗口创建后恢复保存的位置
+pub fn restore_position(window: &Window) {
+    let label = window.label();
+    let Some((x, y)) = STATE.lock().layout.positions.get(label).copied() else {
+        return;
+    };
+    // 保存的位置所在的显示器可能已断开，超出所有显示器时保持默认位置
+    let on_screen = window
+        .available_monitors()
+        .map(|monitors| {
+            monitors.iter().any(|m| {
+                let (pos, size) = (m.position(), m.size());
+                x >= pos.x && y >= pos.y && x < pos.x + size.width as i32 && y < pos.y + size.height as i32
+            })
+        })
+        .unwrap_or(false);
+    if on_screen {
+        if let Err(e) = window.set_position(Position::Physical(PhysicalPosition::new(x, y))) {
+            warn!("恢复窗口 '{}' 位置失败: {}", label, e);
+        }
+    }
+}
+
+pub fn settings() -> LayoutSettings {
+    STATE.lock().layout.settings.clone()
+}
+
+pub fn update_settings(settings: LayoutSettings) -> Result<(), String> {
+    if settings.snap_distance < 0 || settings.snap_distance > 200 {
+        return Err("吸附距离需在 0 到 200 像素之间".to_string());
+    }
+    let layout = {
+        let mut state = STATE.lock();
+        state.layout.settings = settings;
+        state.layout.clone()
+    };
+    window_layout::save_layout(&layout)
+}
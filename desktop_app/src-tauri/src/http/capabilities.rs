@@ -0,0 +1,356 @@
+//! 后端 API 版本协商
+//!
+//! 启动时客户端向每个后端端点上报支持的 API 版本，后端返回协商结果和可用功能。
+//! 后端不支持的功能会被禁用，调用时返回 [`ApiError::FeatureUnavailable`]，
+//! 而不是在请求中途失败。未实现握手接口的旧版后端视为支持全部功能。
+//!
+//! 端点无法连接（离线、超时）时功能状态记为未知，不限制调用；首次使用未知功能时
+//! 在后台重新协商（两次之间至少间隔 [`RETRY_INTERVAL`]）。
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::error::{ApiError, ApiResult};
+use crate::config::backend_profiles;
+use crate::config::BackendFeature;
+
+/// 客户端支持的 API 版本（按优先级排序）
+pub const SUPPORTED_API_VERSIONS: &[&str] = &["v1"];
+
+/// 握手接口路径
+const HANDSHAKE_PATH: &str = "/api/handshake";
+/// 单个端点握手的超时
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 功能状态未知时两次重新协商的最小间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 需要协商的功能
+pub(crate) const FEATURES: [BackendFeature; 4] = [
+    BackendFeature::Core,
+    BackendFeature::Market,
+    BackendFeature::Workflow,
+    BackendFeature::Skills,
+];
+
+lazy_static::lazy_static! {
+    static ref CAPABILITIES: RwLock<BackendCapabilities> = RwLock::new(BackendCapabilities::default());
+    /// 上次开始协商的时间
+    static ref LAST_ATTEMPT: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+/// 是否有协商正在进行
+static NEGOTIATING: AtomicBool = AtomicBool::new(false);
+
+/// 握手请求
+#[derive(Debug, Clone, Serialize)]
+struct HandshakeRequest<'a> {
+    client_version: &'a str,
+    supported_api_versions: &'a [&'a str],
+}
+
+/// 握手响应
+#[derive(Debug, Clone, Default, Deserialize)]
+struct HandshakeResponse {
+    api_version: Option<String>,
+    #[serde(default)]
+    backend_version: Option<String>,
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+/// 单个端点的协商结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointCapabilities {
+    pub base_url: String,
+    /// 协商出的 API 版本；旧版后端为 None
+    pub api_version: Option<String>,
+    pub backend_version: Option<String>,
+    /// 后端声明的功能（原样保留，供前端展示）
+    pub features: Vec<String>,
+    /// 是否为未实现握手的旧版后端
+    pub legacy: bool,
+    /// 是否因网络错误无法连接（此时功能状态未知）
+    #[serde(default)]
+    pub unreachable: bool,
+    /// 协商失败的原因
+    pub error: Option<String>,
+}
+
+/// 功能可用性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureAvailability {
+    pub available: bool,
+    /// 端点无法连接、尚未确定是否可用（不限制调用）
+    #[serde(default)]
+    pub unknown: bool,
+    pub base_url: String,
+    pub reason: Option<String>,
+}
+
+/// 协商后的后端能力
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackendCapabilities {
+    /// 是否已完成协商
+    pub negotiated: bool,
+    pub negotiated_at: Option<i64>,
+    pub client_api_versions: Vec<String>,
+    pub endpoints: Vec<EndpointCapabilities>,
+    pub features: BTreeMap<String, FeatureAvailability>,
+}
+
+pub(crate) fn feature_name(feature: BackendFeature) -> String {
+    serde_json::to_value(feature)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{:?}", feature).to_lowercase())
+}
+
+/// 与单个端点握手
+async fn handshake(base_url: &str) -> EndpointCapabilities {
+    let mut result = EndpointCapabilities {
+        base_url: base_url.to_string(),
+        api_version: None,
+        backend_version: None,
+        features: Vec::new(),
+        legacy: false,
+        unreachable: false,
+        error: None,
+    };
+
+    let client = match super::proxy::client_builder().timeout(HANDSHAKE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            result.error = Some(format!("创建 HTTP 客户端失败: {}", e));
+            return result;
+        }
+    };
+
+    let request = HandshakeRequest {
+        client_version: env!("CARGO_PKG_VERSION"),
+        supported_api_versions: SUPPORTED_API_VERSIONS,
+    };
+
+    let response = match client
+        .post(format!("{}{}", base_url, HANDSHAKE_PATH))
+        .json(&request)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            result.unreachable = true;
+            result.error = Some(super::trust_store::classify_request_error(e).to_string());
+            return result;
+        }
+    };
+
+    let status = response.status().as_u16();
+    if status == 404 || status == 405 {
+        result.legacy = true;
+        return result;
+    }
+    if !response.status().is_success() {
+        result.error = Some(format!("握手失败: HTTP {}", status));
+        return result;
+    }
+
+    match response.json::<HandshakeResponse>().await {
+        Ok(handshake) => {
+            result.api_version = handshake.api_version;
+            result.backend_version = handshake.backend_version;
+            result.features = handshake.features;
+        }
+        Err(e) => result.error = Some(format!("解析握手响应失败: {}", e)),
+    }
+    result
+}
+
+/// 根据端点协商结果计算功能可用性
+fn resolve_feature(
+    feature: BackendFeature,
+    endpoint: &EndpointCapabilities,
+) -> FeatureAvailability {
+    let unavailable = |reason: String| FeatureAvailability {
+        available: false,
+        unknown: false,
+        base_url: endpoint.base_url.clone(),
+        reason: Some(reason),
+    };
+    let available = || FeatureAvailability {
+        available: true,
+        unknown: false,
+        base_url: endpoint.base_url.clone(),
+        reason: None,
+    };
+
+    if endpoint.unreachable {
+        return FeatureAvailability {
+            available: false,
+            unknown: true,
+            base_url: endpoint.base_url.clone(),
+            reason: endpoint.error.clone(),
+        };
+    }
+    if let Some(error) = &endpoint.error {
+        return unavailable(error.clone());
+    }
+    if endpoint.legacy {
+        return available();
+    }
+
+    match endpoint.api_version.as_deref() {
+        Some(version) if SUPPORTED_API_VERSIONS.contains(&version) => {}
+        Some(version) => return unavailable(format!("后端 API 版本 {} 不受支持", version)),
+        None => return unavailable("后端未返回可用的 API 版本".to_string()),
+    }
+
+    let name = feature_name(feature);
+    if endpoint.features.iter().any(|f| f == &name) {
+        available()
+    } else {
+        unavailable(format!("后端未提供 {} 功能", name))
+    }
+}
+
+/// 与所有已配置的端点协商并更新全局能力
+pub async fn negotiate() -> BackendCapabilities {
+    let feature_urls: Vec<(BackendFeature, String)> = FEATURES
+        .iter()
+        .map(|f| (*f, backend_profiles::base_url_for(*f)))
+        .collect();
+
+    *LAST_ATTEMPT.lock() = Some(Instant::now());
+    NEGOTIATING.store(true, Ordering::SeqCst);
+
+    // 各端点并发握手，启动时最多等待一个超时
+    let urls: BTreeSet<&String> = feature_urls.iter().map(|(_, url)| url).collect();
+    let endpoints: BTreeMap<String, EndpointCapabilities> =
+        futures::future::join_all(urls.into_iter().map(|url| async move { (url.clone(), handshake(url).await) }))
+            .await
+            .into_iter()
+            .collect();
+
+    let features = feature_urls
+        .iter()
+        .map(|(feature, url)| (feature_name(*feature), resolve_feature(*feature, &endpoints[url])))
+        .collect::<BTreeMap<_, _>>();
+
+    for (name, availability) in &features {
+        let reason = availability.reason.as_deref().unwrap_or_default();
+        if availability.unknown {
+            warn!("后端功能 {} 暂时无法确认，稍后重试: {}", name, reason);
+        } else if !availability.available {
+            warn!("后端功能 {} 不可用: {}", name, reason);
+        }
+    }
+
+    let capabilities = BackendCapabilities {
+        negotiated: true,
+        negotiated_at: Some(chrono::Utc::now().timestamp()),
+        client_api_versions: SUPPORTED_API_VERSIONS.iter().map(|v| v.to_string()).collect(),
+        endpoints: endpoints.into_values().collect(),
+        features,
+    };
+
+    info!("后端能力协商完成: {} 个端点", capabilities.endpoints.len());
+    *CAPABILITIES.write() = capabilities.clone();
+    NEGOTIATING.store(false, Ordering::SeqCst);
+    capabilities
+}
+
+/// 有功能状态未知时在后台重新协商（正在协商或距上次不足 [`RETRY_INTERVAL`] 时跳过）
+fn retry_in_background() {
+    let due = LAST_ATTEMPT.lock().map_or(true, |at| at.elapsed() >= RETRY_INTERVAL);
+    if !due || NEGOTIATING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        negotiate().await;
+    });
+}
+
+/// 获取当前协商结果
+pub fn current() -> BackendCapabilities {
+    CAPABILITIES.read().clone()
+}
+
+/// 检查功能是否可用；尚未协商或状态未知时不做限制，未知时在后台重新协商
+pub fn ensure_feature(feature: BackendFeature) -> ApiResult<()> {
+    let name = feature_name(feature);
+    let result = {
+        let capabilities = CAPABILITIES.read();
+        if !capabilities.negotiated {
+            return Ok(());
+        }
+        capabilities.features.get(&name).cloned()
+    };
+
+    match result {
+        Some(availability) if availability.unknown => {
+            retry_in_background();
+            Ok(())
+        }
+        Some(availability) if !availability.available => Err(ApiError::FeatureUnavailable {
+            feature: name,
+            reason: availability.reason.unwrap_or_default(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(api_version: Option<&str>, features: &[&str]) -> EndpointCapabilities {
+        EndpointCapabilities {
+            base_url: "http://127.0.0.1:8000".to_string(),
+            api_version: api_version.map(str::to_string),
+            backend_version: None,
+            features: features.iter().map(|f| f.to_string()).collect(),
+            legacy: false,
+            unreachable: false,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_feature_name_matches_serde() {
+        assert_eq!(feature_name(BackendFeature::Workflow), "workflow");
+        assert_eq!(feature_name(BackendFeature::Market), "market");
+    }
+
+    #[test]
+    fn test_resolve_feature() {
+        let ep = endpoint(Some("v1"), &["workflow"]);
+        assert!(resolve_feature(BackendFeature::Workflow, &ep).available);
+        assert!(!resolve_feature(BackendFeature::Skills, &ep).available);
+
+        let unsupported = endpoint(Some("v9"), &["workflow"]);
+        assert!(!resolve_feature(BackendFeature::Workflow, &unsupported).available);
+
+        let mut legacy = endpoint(None, &[]);
+        legacy.legacy = true;
+        assert!(resolve_feature(BackendFeature::Skills, &legacy).available);
+
+        // 网络错误只是暂时无法确认，HTTP 错误才算不可用
+        let mut offline = endpoint(None, &[]);
+        offline.unreachable = true;
+        offline.error = Some("连接超时".to_string());
+        let availability = resolve_feature(BackendFeature::Core, &offline);
+        assert!(availability.unknown && !availability.available);
+
+        let mut failed = endpoint(None, &[]);
+        failed.error = Some("握手失败: HTTP 500".to_string());
+        assert!(!resolve_feature(BackendFeature::Core, &failed).unknown);
+    }
+
+    #[test]
+    fn test_ensure_feature_before_negotiation() {
+        assert!(ensure_feature(BackendFeature::Market).is_ok());
+    }
+}
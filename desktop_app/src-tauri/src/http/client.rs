@@ -1,7 +1,8 @@
 //! API 客户端实现
 
 use super::error::{ApiError, ApiResult};
-use reqwest::{Client, Method, RequestBuilder, Response};
+use crate::utils::metric_registry;
+use reqwest::{Method, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
@@ -34,7 +35,7 @@ pub struct ApiMeta {
 /// API 客户端
 #[derive(Debug, Clone)]
 pub struct ApiClient {
-    client: Client,
+    client: super::proxy::ReloadingClient,
     base_url: String,
     auth_token: Option<String>,
 }
@@ -42,11 +43,12 @@ pub struct ApiClient {
 impl ApiClient {
     /// 创建新的 API 客户端
     pub fn new(base_url: impl Into<String>) -> ApiResult<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .build()
-            .map_err(ApiError::RequestFailed)?;
+        let client = super::proxy::ReloadingClient::new(|builder| {
+            builder
+                .timeout(Duration::from_secs(30))
+                .connect_timeout(Duration::from_secs(10))
+        })
+        .map_err(ApiError::RequestFailed)?;
 
         Ok(Self {
             client,
@@ -74,7 +76,7 @@ impl ApiClient {
     /// 构建请求
     fn build_request(&self, method: Method, path: &str) -> RequestBuilder {
         let url = self.get_url(path);
-        let mut builder = self.client.request(method, &url);
+        let mut builder = self.client.get().request(method, &url);
 
         // 添加认证头
         if let Some(ref token) = self.auth_token {
@@ -89,11 +91,35 @@ impl ApiClient {
         builder
     }
 
+    /// 发送请求并记录请求数与耗时指标
+    async fn send(&self, builder: RequestBuilder) -> ApiResult<Response> {
+        let request = builder.build().map_err(super::trust_store::classify_request_error)?;
+        let method = request.method().clone();
+        let started = std::time::Instant::now();
+        let result = self.client.get().execute(request).await;
+        let status = match &result {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        metric_registry::increment_counter(
+            metric_registry::HTTP_REQUESTS_TOTAL,
+            &[("method", method.as_str()), ("status", &status)],
+        );
+        metric_registry::observe(
+            metric_registry::HTTP_REQUEST_DURATION,
+            &[("method", method.as_str())],
+            started.elapsed().as_secs_f64(),
+        );
+        result.map_err(super::trust_store::classify_request_error)
+    }
+
     /// 处理响应
     async fn handle_response<T>(&self, response: Response) -> ApiResult<T>
     where
         T: for<'de> Deserialize<'de>,
     {
+        super::trust_store::verify_pin(&response)?;
+
         let status = response.status();
         
         if status.is_success() {
@@ -132,10 +158,8 @@ impl ApiClient {
     {
         debug!("GET {}", path);
         let response = self
-            .build_request(Method::GET, path)
-            .send()
-            .await
-            .map_err(ApiError::RequestFailed)?;
+            .send(self.build_request(Method::GET, path))
+            .await?;
 
         self.handle_response(response).await
     }
@@ -148,11 +172,8 @@ impl ApiClient {
     {
         debug!("POST {}", path);
         let response = self
-            .build_request(Method::POST, path)
-            .json(body)
-            .send()
-            .await
-            .map_err(ApiError::RequestFailed)?;
+            .send(self.build_request(Method::POST, path).json(body))
+            .await?;
 
         self.handle_response(response).await
     }
@@ -165,11 +186,8 @@ impl ApiClient {
     {
         debug!("PUT {}", path);
         let response = self
-            .build_request(Method::PUT, path)
-            .json(body)
-            .send()
-            .await
-            .map_err(ApiError::RequestFailed)?;
+            .send(self.build_request(Method::PUT, path).json(body))
+            .await?;
 
         self.handle_response(response).await
     }
@@ -181,10 +199,8 @@ impl ApiClient {
     {
         debug!("DELETE {}", path);
         let response = self
-            .build_request(Method::DELETE, path)
-            .send()
-            .await
-            .map_err(ApiError::RequestFailed)?;
+            .send(self.build_request(Method::DELETE, path))
+            .await?;
 
         self.handle_response(response).await
     }
@@ -197,11 +213,8 @@ impl ApiClient {
     {
         debug!("PATCH {}", path);
         let response = self
-            .build_request(Method::PATCH, path)
-            .json(body)
-            .send()
-            .await
-            .map_err(ApiError::RequestFailed)?;
+            .send(self.build_request(Method::PATCH, path).json(body))
+            .await?;
 
         self.handle_response(response).await
     }
@@ -209,7 +222,7 @@ impl ApiClient {
     /// 健康检查
     pub async fn health_check(&self) -> ApiResult<bool> {
         debug!("Health check: {}", self.base_url);
-        match self.client.get(&self.base_url).send().await {
+        match self.client.get().get(&self.base_url).send().await {
             Ok(response) => Ok(response.status().is_success()),
             Err(e) => {
                 warn!("Health check failed: {}", e);
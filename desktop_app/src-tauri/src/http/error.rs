@@ -20,6 +20,20 @@ pub enum ApiError {
         message: String,
     },
 
+    /// TLS 证书校验失败
+    #[error("TLS 证书校验失败 ({host}): {reason}")]
+    TlsVerificationFailed {
+        host: String,
+        reason: String,
+    },
+
+    /// 后端不支持该功能（由版本协商确定）
+    #[error("功能不可用 ({feature}): {reason}")]
+    FeatureUnavailable {
+        feature: String,
+        reason: String,
+    },
+
     /// 网络超时
     #[error("网络请求超时")]
     Timeout,
@@ -1,12 +1,19 @@
 //! HTTP 客户端模块
 //! 用于与 Python API 服务通信
 
+pub mod capabilities;
 pub mod client;
 pub mod error;
+pub mod proxy;
+pub mod share_client;
 pub mod skills_client;
+pub mod sync_client;
+pub mod trust_store;
 pub mod workflow_client;
 
 pub use client::ApiClient;
 pub use error::{ApiError, ApiResult};
+pub use share_client::ShareApiClient;
 pub use skills_client::SkillsApiClient;
+pub use sync_client::SyncApiClient;
 pub use workflow_client::WorkflowApiClient;
@@ -0,0 +1,334 @@
+//! 出站 HTTP 代理配置
+//!
+//! 所有 reqwest 客户端都应通过 [`client_builder`] 或 [`client`] 创建，
+//! 以便统一应用运行时的代理设置（HTTP/HTTPS/SOCKS5、认证、绕过列表、系统代理）
+//! 以及用户导入的 CA 证书（见 [`super::trust_store`]）。
+//! 绕过列表在每个请求发出时按目标主机匹配（见 [`is_bypassed`]）。
+//! 未启用代理时保持 reqwest 的默认行为（读取 HTTP(S)_PROXY / NO_PROXY 环境变量）。
+//! 设置更新后，新创建的客户端立即生效；长期持有的客户端使用 [`ReloadingClient`]，
+//! 在下一次请求时按新设置重建，无需重启。
+
+use parking_lot::RwLock;
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::state::settings::{ProxySettings, ProxyType};
+
+lazy_static::lazy_static! {
+    static ref PROXY_SETTINGS: RwLock<ProxySettings> = RwLock::new(ProxySettings::default());
+}
+
+/// 客户端配置的版本号，代理或信任库变化后递增
+static CLIENT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 使已创建的 [`ReloadingClient`] 在下一次请求时重建
+pub fn invalidate_clients() {
+    CLIENT_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// 检测到的系统代理
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SystemProxyInfo {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub all_proxy: Option<String>,
+    pub no_proxy: Vec<String>,
+}
+
+impl SystemProxyInfo {
+    /// 是否检测到任何系统代理
+    pub fn is_configured(&self) -> bool {
+        self.http_proxy.is_some() || self.https_proxy.is_some() || self.all_proxy.is_some()
+    }
+}
+
+/// 获取当前代理设置
+pub fn current_settings() -> ProxySettings {
+    PROXY_SETTINGS.read().clone()
+}
+
+/// 替换当前代理设置（运行时生效）
+pub fn set_settings(settings: ProxySettings) -> Result<(), String> {
+    validate_settings(&settings)?;
+    *PROXY_SETTINGS.write() = settings;
+    invalidate_clients();
+    Ok(())
+}
+
+/// 校验代理设置
+pub fn validate_settings(settings: &ProxySettings) -> Result<(), String> {
+    if !settings.enabled || settings.use_system_proxy {
+        return Ok(());
+    }
+    if settings.host.trim().is_empty() {
+        return Err("代理服务器地址不能为空".to_string());
+    }
+    if settings.port == 0 {
+        return Err("代理端口无效".to_string());
+    }
+    if settings.proxy_type == ProxyType::Socks4 {
+        return Err("暂不支持 SOCKS4 代理，请使用 SOCKS5".to_string());
+    }
+    if let Some(entry) = settings
+        .exclude_list
+        .iter()
+        .find(|entry| entry.trim().is_empty() || entry.contains("://") || entry.contains(char::is_whitespace))
+    {
+        return Err(format!("绕过列表条目无效: {:?}，请填写主机名、域名或 IP", entry));
+    }
+    proxy_url(settings).map(|_| ())
+}
+
+/// 构建代理 URL
+fn proxy_url(settings: &ProxySettings) -> Result<String, String> {
+    let scheme = match settings.proxy_type {
+        ProxyType::Http => "http",
+        ProxyType::Https => "https",
+        ProxyType::Socks4 => "socks4",
+        // socks5h: 由代理端解析域名，避免内网 DNS 泄露
+        ProxyType::Socks5 => "socks5h",
+    };
+    let url = format!("{}://{}:{}", scheme, settings.host.trim(), settings.port);
+    url::Url::parse(&url).map_err(|e| format!("无效的代理地址 {}: {}", url, e))?;
+    Ok(url)
+}
+
+/// 主机是否命中绕过列表（与 reqwest `NoProxy` 的规则一致）
+///
+/// - `*` 匹配所有主机
+/// - `example.com`、`.example.com` 与 `*.example.com` 匹配该域名及其子域名
+/// - IP 地址精确匹配
+pub fn is_bypassed(exclude_list: &[String], host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    exclude_list.iter().any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        if entry == "*" {
+            return true;
+        }
+        let domain = entry.trim_start_matches('*').trim_start_matches('.');
+        !domain.is_empty()
+            && (host == domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.')))
+    })
+}
+
+/// 按当前设置，请求该地址时是否经过代理
+pub fn routes_through_proxy(url: &str) -> bool {
+    let settings = PROXY_SETTINGS.read().clone();
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    if !settings.enabled || settings.use_system_proxy {
+        let system = detect_system_proxy();
+        return system.is_configured() && !is_bypassed(&system.no_proxy, &host);
+    }
+    !is_bypassed(&settings.exclude_list, &host)
+}
+
+/// 从环境变量检测系统代理
+pub fn detect_system_proxy() -> SystemProxyInfo {
+    fn env_var(names: &[&str]) -> Option<String> {
+        names
+            .iter()
+            .find_map(|name| std::env::var(name).ok())
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    SystemProxyInfo {
+        http_proxy: env_var(&["HTTP_PROXY", "http_proxy"]),
+        https_proxy: env_var(&["HTTPS_PROXY", "https_proxy"]),
+        all_proxy: env_var(&["ALL_PROXY", "all_proxy"]),
+        no_proxy: env_var(&["NO_PROXY", "no_proxy"])
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// 将代理设置应用到客户端构建器
+fn apply_proxy(builder: ClientBuilder, settings: &ProxySettings) -> ClientBuilder {
+    // 未设置代理或使用系统代理：reqwest 默认读取 HTTP(S)_PROXY / ALL_PROXY / NO_PROXY
+    if !settings.enabled || settings.use_system_proxy {
+        return builder;
+    }
+
+    let url = match proxy_url(settings) {
+        Ok(url) => url,
+        Err(e) => {
+            warn!("代理配置无效，回退为直连: {}", e);
+            return builder.no_proxy();
+        }
+    };
+
+    let mut proxy = match Proxy::all(&url) {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            warn!("创建代理失败，回退为直连: {}", e);
+            return builder.no_proxy();
+        }
+    };
+
+    if let Some(username) = settings.username.as_deref().filter(|u| !u.is_empty()) {
+        proxy = proxy.basic_auth(username, settings.password.as_deref().unwrap_or(""));
+    }
+
+    if !settings.exclude_list.is_empty() {
+        proxy = proxy.no_proxy(NoProxy::from_string(&settings.exclude_list.join(",")));
+    }
+
+    debug!("使用代理: {}", url);
+    builder.no_proxy().proxy(proxy)
+}
+
+/// 创建已应用当前代理设置和信任库的客户端构建器
+pub fn client_builder() -> ClientBuilder {
+    let settings = PROXY_SETTINGS.read().clone();
+    super::trust_store::apply(apply_proxy(Client::builder(), &settings))
+}
+
+/// 创建已应用当前代理设置的默认客户端
+pub fn client() -> Client {
+    client_builder().build().unwrap_or_else(|e| {
+        warn!("创建代理客户端失败，使用默认客户端: {}", e);
+        Client::new()
+    })
+}
+
+/// 代理或信任库变化后自动重建的客户端，供长期持有客户端的调用方使用
+#[derive(Clone)]
+pub struct ReloadingClient {
+    configure: Arc<dyn Fn(ClientBuilder) -> ClientBuilder + Send + Sync>,
+    current: Arc<RwLock<(u64, Client)>>,
+}
+
+impl ReloadingClient {
+    /// 按当前设置创建客户端，`configure` 设置超时等其他选项，重建时再次调用
+    pub fn new(
+        configure: impl Fn(ClientBuilder) -> ClientBuilder + Send + Sync + 'static,
+    ) -> reqwest::Result<Self> {
+        let generation = CLIENT_GENERATION.load(Ordering::SeqCst);
+        let client = configure(client_builder()).build()?;
+        Ok(Self {
+            configure: Arc::new(configure),
+            current: Arc::new(RwLock::new((generation, client))),
+        })
+    }
+
+    /// 当前设置下的客户端；重建失败时继续使用旧客户端
+    pub fn get(&self) -> Client {
+        let generation = CLIENT_GENERATION.load(Ordering::SeqCst);
+        {
+            let current = self.current.read();
+            if current.0 == generation {
+                return current.1.clone();
+            }
+        }
+        let mut current = self.current.write();
+        match (self.configure)(client_builder()).build() {
+            Ok(client) => {
+                debug!("网络设置已变化，重建 HTTP 客户端");
+                *current = (generation, client);
+            }
+            Err(e) => {
+                warn!("重建 HTTP 客户端失败，继续使用旧客户端: {}", e);
+                current.0 = generation;
+            }
+        }
+        current.1.clone()
+    }
+}
+
+impl std::fmt::Debug for ReloadingClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadingClient").field("generation", &self.current.read().0).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manual_settings(proxy_type: ProxyType) -> ProxySettings {
+        ProxySettings {
+            enabled: true,
+            proxy_type,
+            host: "proxy.corp.local".to_string(),
+            port: 3128,
+            ..ProxySettings::default()
+        }
+    }
+
+    #[test]
+    fn test_proxy_url_schemes() {
+        assert_eq!(
+            proxy_url(&manual_settings(ProxyType::Http)).unwrap(),
+            "http://proxy.corp.local:3128"
+        );
+        assert_eq!(
+            proxy_url(&manual_settings(ProxyType::Socks5)).unwrap(),
+            "socks5h://proxy.corp.local:3128"
+        );
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(validate_settings(&ProxySettings::default()).is_ok());
+        assert!(validate_settings(&manual_settings(ProxyType::Http)).is_ok());
+        assert!(validate_settings(&manual_settings(ProxyType::Socks4)).is_err());
+
+        let mut empty_host = manual_settings(ProxyType::Http);
+        empty_host.host = String::new();
+        assert!(validate_settings(&empty_host).is_err());
+
+        let mut system = empty_host.clone();
+        system.use_system_proxy = true;
+        assert!(validate_settings(&system).is_ok());
+    }
+
+    #[test]
+    fn test_bypass_list_matching() {
+        let list: Vec<String> = ["localhost", ".corp.local", "*.example.com", "10.0.0.8"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(is_bypassed(&list, "localhost"));
+        assert!(is_bypassed(&list, "git.corp.local"));
+        assert!(is_bypassed(&list, "example.com"));
+        assert!(is_bypassed(&list, "api.example.com"));
+        assert!(is_bypassed(&list, "10.0.0.8"));
+        assert!(!is_bypassed(&list, "notexample.com"));
+        assert!(!is_bypassed(&list, "10.0.0.80"));
+        assert!(is_bypassed(&["*".to_string()], "anything.dev"));
+
+        let mut invalid = manual_settings(ProxyType::Http);
+        invalid.exclude_list = vec!["http://localhost".to_string()];
+        assert!(validate_settings(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_reloading_client_rebuilds_after_change() {
+        let client = ReloadingClient::new(|builder| builder).unwrap();
+        let before = client.current.read().0;
+        invalidate_clients();
+        let _ = client.get();
+        assert_ne!(client.current.read().0, before);
+        assert_eq!(client.current.read().0, CLIENT_GENERATION.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_client_builds_with_manual_proxy() {
+        let mut settings = manual_settings(ProxyType::Http);
+        settings.username = Some("user".to_string());
+        settings.password = Some("secret".to_string());
+        settings.exclude_list = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+        assert!(apply_proxy(Client::builder(), &settings).build().is_ok());
+    }
+}
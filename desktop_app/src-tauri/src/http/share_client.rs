@@ -0,0 +1,71 @@
+//! 会话分享 API 客户端
+//!
+//! 将脱敏后的只读会话快照上传到后端，由后端生成公开访问链接。
+
+use super::client::ApiClient;
+use super::error::ApiResult;
+use serde::{Deserialize, Serialize};
+
+/// 会话分享 API 客户端
+pub struct ShareApiClient {
+    client: ApiClient,
+}
+
+/// 快照中的单条消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMessage {
+    pub role: String,
+    pub content: String,
+    pub created_at: i64,
+    pub edited: bool,
+}
+
+/// 发布快照请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishSnapshotRequest {
+    pub title: String,
+    pub messages: Vec<SnapshotMessage>,
+    pub created_at: i64,
+    /// 过期时间（Unix 秒），为空表示不过期
+    pub expires_at: Option<i64>,
+}
+
+/// 快照响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotResponse {
+    pub id: String,
+    pub url: String,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+}
+
+impl ShareApiClient {
+    /// 创建新的会话分享 API 客户端
+    pub fn new(base_url: impl Into<String>) -> ApiResult<Self> {
+        Ok(Self {
+            client: ApiClient::new(base_url)?,
+        })
+    }
+
+    /// 设置认证令牌
+    pub fn set_auth_token(&mut self, token: Option<String>) {
+        self.client.set_auth_token(token);
+    }
+
+    /// 发布快照
+    pub async fn publish_snapshot(&self, request: &PublishSnapshotRequest) -> ApiResult<SnapshotResponse> {
+        self.client.post("/api/share/snapshots", request).await
+    }
+
+    /// 获取快照信息
+    pub async fn get_snapshot(&self, snapshot_id: &str) -> ApiResult<SnapshotResponse> {
+        let path = format!("/api/share/snapshots/{}", snapshot_id);
+        self.client.get(&path).await
+    }
+
+    /// 撤销快照
+    pub async fn revoke_snapshot(&self, snapshot_id: &str) -> ApiResult<()> {
+        let path = format!("/api/share/snapshots/{}", snapshot_id);
+        self.client.delete(&path).await
+    }
+}
@@ -0,0 +1,66 @@
+//! 多设备同步 API 客户端
+//!
+//! 在社区账号下拉取与上传加密的同步信封（格式见 [`crate::utils::device_sync`]）。
+//! 服务端按信封的向量时钟拒绝会覆盖更新内容的上传，被拒绝的条目在下一轮拉取后重新合并。
+
+use super::client::ApiClient;
+use super::error::ApiResult;
+use crate::utils::device_sync::SyncEnvelope;
+use serde::{Deserialize, Serialize};
+
+/// 多设备同步 API 客户端
+pub struct SyncApiClient {
+    client: ApiClient,
+}
+
+/// 拉取响应：游标之后变化的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullResponse {
+    pub items: Vec<SyncEnvelope>,
+    pub cursor: i64,
+}
+
+/// 上传请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushRequest {
+    pub device_id: String,
+    pub items: Vec<SyncEnvelope>,
+}
+
+/// 上传响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResponse {
+    /// 因服务端已有更新版本而被拒绝的条目键（`类别:ID`）
+    #[serde(default)]
+    pub rejected: Vec<String>,
+}
+
+impl SyncApiClient {
+    /// 创建新的同步 API 客户端
+    pub fn new(base_url: impl Into<String>) -> ApiResult<Self> {
+        Ok(Self {
+            client: ApiClient::new(base_url)?,
+        })
+    }
+
+    /// 设置认证令牌
+    pub fn set_auth_token(&mut self, token: Option<String>) {
+        self.client.set_auth_token(token);
+    }
+
+    /// 服务是否可达
+    pub async fn is_reachable(&self) -> bool {
+        self.client.health_check().await.unwrap_or(false)
+    }
+
+    /// 拉取游标之后变化的条目
+    pub async fn pull(&self, since: i64) -> ApiResult<PullResponse> {
+        let path = format!("/api/sync/items?since={}", since);
+        self.client.get(&path).await
+    }
+
+    /// 上传本设备修改的条目
+    pub async fn push(&self, request: &PushRequest) -> ApiResult<PushResponse> {
+        self.client.post("/api/sync/items", request).await
+    }
+}
@@ -0,0 +1,367 @@
+//! 自定义 CA 信任库
+//!
+//! 自托管部署通常使用内部 CA 签发证书。用户导入的 PEM 证书经校验后保存在
+//! 应用数据目录下的 `trust_store/` 中，并添加到所有出站 reqwest 客户端。
+//! 同时支持按主机固定证书指纹（SHA-256）。
+
+use base64::Engine;
+use parking_lot::RwLock;
+use futures::future::BoxFuture;
+use reqwest::{Certificate, ClientBuilder, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::error::{ApiError, ApiResult};
+
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+const PINS_FILE: &str = "pins.json";
+
+lazy_static::lazy_static! {
+    static ref TRUST_STORE: RwLock<TrustStore> = RwLock::new(TrustStore::default());
+}
+
+/// 受信任的证书
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedCertificate {
+    /// SHA-256 指纹（小写十六进制）
+    pub fingerprint: String,
+    /// 用户指定的名称
+    pub label: String,
+    /// 导入时间
+    pub added_at: i64,
+    #[serde(skip)]
+    pem: String,
+}
+
+/// 信任库状态
+#[derive(Debug, Default)]
+struct TrustStore {
+    dir: Option<PathBuf>,
+    certificates: Vec<TrustedCertificate>,
+    /// 主机 -> 允许的证书指纹
+    pins: HashMap<String, Vec<String>>,
+}
+
+/// 从 PEM 文本中拆分出各个证书块
+fn split_pem_blocks(pem: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(PEM_BEGIN) {
+        let after = &rest[start..];
+        match after.find(PEM_END) {
+            Some(end) => {
+                blocks.push(after[..end + PEM_END.len()].to_string());
+                rest = &after[end + PEM_END.len()..];
+            }
+            None => break,
+        }
+    }
+    blocks
+}
+
+/// 计算 PEM 证书的 SHA-256 指纹
+pub fn pem_fingerprint(pem_block: &str) -> Result<String, String> {
+    let body: String = pem_block
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| format!("证书内容不是有效的 Base64: {}", e))?;
+    Ok(der_fingerprint(&der))
+}
+
+/// 计算 DER 证书的 SHA-256 指纹
+pub fn der_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 规范化用户输入的指纹（去掉冒号与空白，转小写）
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// 校验 PEM 文本并解析出证书
+fn parse_certificates(pem: &str) -> Result<Vec<(String, String)>, String> {
+    let blocks = split_pem_blocks(pem);
+    if blocks.is_empty() {
+        return Err("未找到 PEM 证书（应以 -----BEGIN CERTIFICATE----- 开头）".to_string());
+    }
+
+    blocks
+        .into_iter()
+        .map(|block| {
+            Certificate::from_pem(block.as_bytes()).map_err(|e| format!("无效的证书: {}", e))?;
+            let fingerprint = pem_fingerprint(&block)?;
+            Ok((fingerprint, block))
+        })
+        .collect()
+}
+
+fn write_private_file(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// 从目录加载信任库（启动时调用）
+pub fn load(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| format!("创建信任库目录失败: {}", e))?;
+
+    let mut store = TrustStore { dir: Some(dir.clone()), ..TrustStore::default() };
+
+    let index_path = dir.join("certificates.json");
+    if index_path.exists() {
+        let index: Vec<TrustedCertificate> = serde_json::from_str(
+            &fs::read_to_string(&index_path).map_err(|e| format!("读取证书索引失败: {}", e))?,
+        )
+        .map_err(|e| format!("解析证书索引失败: {}", e))?;
+
+        for mut cert in index {
+            let pem_path = dir.join(format!("{}.pem", cert.fingerprint));
+            match fs::read_to_string(&pem_path) {
+                Ok(pem) if pem_fingerprint(&pem).ok().as_deref() == Some(cert.fingerprint.as_str()) => {
+                    cert.pem = pem;
+                    store.certificates.push(cert);
+                }
+                _ => warn!("跳过损坏或被篡改的证书: {}", cert.fingerprint),
+            }
+        }
+    }
+
+    let pins_path = dir.join(PINS_FILE);
+    if pins_path.exists() {
+        store.pins = serde_json::from_str(
+            &fs::read_to_string(&pins_path).map_err(|e| format!("读取证书固定配置失败: {}", e))?,
+        )
+        .map_err(|e| format!("解析证书固定配置失败: {}", e))?;
+    }
+
+    info!("信任库已加载: {} 个证书, {} 个固定主机", store.certificates.len(), store.pins.len());
+    *TRUST_STORE.write() = store;
+    Ok(())
+}
+
+fn persist(store: &TrustStore) -> Result<(), String> {
+    let Some(dir) = store.dir.as_ref() else {
+        return Err("信任库尚未初始化".to_string());
+    };
+
+    let index = serde_json::to_string_pretty(&store.certificates)
+        .map_err(|e| format!("序列化证书索引失败: {}", e))?;
+    write_private_file(&dir.join("certificates.json"), &index)?;
+
+    let pins = serde_json::to_string_pretty(&store.pins)
+        .map_err(|e| format!("序列化证书固定配置失败: {}", e))?;
+    write_private_file(&dir.join(PINS_FILE), &pins)
+}
+
+/// 导入 PEM 证书（可包含多个证书），返回新导入的证书
+pub fn import_pem(pem: &str, label: &str) -> Result<Vec<TrustedCertificate>, String> {
+    let parsed = parse_certificates(pem)?;
+    let mut store = TRUST_STORE.write();
+    let dir = store.dir.clone().ok_or("信任库尚未初始化")?;
+
+    let mut imported = Vec::new();
+    for (fingerprint, block) in parsed {
+        if store.certificates.iter().any(|c| c.fingerprint == fingerprint) {
+            continue;
+        }
+        write_private_file(&dir.join(format!("{}.pem", fingerprint)), &block)?;
+        let cert = TrustedCertificate {
+            fingerprint,
+            label: label.to_string(),
+            added_at: chrono::Utc::now().timestamp(),
+            pem: block,
+        };
+        store.certificates.push(cert.clone());
+        imported.push(cert);
+    }
+
+    persist(&store)?;
+    super::proxy::invalidate_clients();
+    Ok(imported)
+}
+
+/// 删除受信任的证书
+pub fn remove_certificate(fingerprint: &str) -> Result<bool, String> {
+    let fingerprint = normalize_fingerprint(fingerprint);
+    let mut store = TRUST_STORE.write();
+    let before = store.certificates.len();
+    store.certificates.retain(|c| c.fingerprint != fingerprint);
+    let removed = store.certificates.len() != before;
+
+    if removed {
+        if let Some(dir) = store.dir.as_ref() {
+            let _ = fs::remove_file(dir.join(format!("{}.pem", fingerprint)));
+        }
+        persist(&store)?;
+        super::proxy::invalidate_clients();
+    }
+    Ok(removed)
+}
+
+/// 列出受信任的证书
+pub fn list_certificates() -> Vec<TrustedCertificate> {
+    TRUST_STORE.read().certificates.clone()
+}
+
+/// 设置主机的证书固定指纹；传入空列表则取消固定
+pub fn set_host_pins(host: &str, fingerprints: Vec<String>) -> Result<(), String> {
+    let host = host.trim().to_ascii_lowercase();
+    if host.is_empty() {
+        return Err("主机名不能为空".to_string());
+    }
+
+    let fingerprints: Vec<String> = fingerprints.iter().map(|f| normalize_fingerprint(f)).collect();
+    if let Some(bad) = fingerprints.iter().find(|f| f.len() != 64) {
+        return Err(format!("无效的 SHA-256 指纹: {}", bad));
+    }
+
+    let mut store = TRUST_STORE.write();
+    if fingerprints.is_empty() {
+        store.pins.remove(&host);
+    } else {
+        store.pins.insert(host, fingerprints);
+    }
+    persist(&store)?;
+    super::proxy::invalidate_clients();
+    Ok(())
+}
+
+/// 获取所有证书固定配置
+pub fn host_pins() -> HashMap<String, Vec<String>> {
+    TRUST_STORE.read().pins.clone()
+}
+
+/// 将信任库中的证书添加到客户端构建器，并开启 TLS 信息收集以便之后固定的主机也能校验
+pub fn apply(mut builder: ClientBuilder) -> ClientBuilder {
+    let store = TRUST_STORE.read();
+    for cert in &store.certificates {
+        match Certificate::from_pem(cert.pem.as_bytes()) {
+            Ok(certificate) => builder = builder.add_root_certificate(certificate),
+            Err(e) => warn!("加载证书 {} 失败: {}", cert.fingerprint, e),
+        }
+    }
+    builder.tls_info(true)
+}
+
+/// 校验响应的服务器证书是否符合该主机的固定指纹
+pub fn verify_pin(response: &Response) -> ApiResult<()> {
+    let Some(host) = response.url().host_str().map(|h| h.to_ascii_lowercase()) else {
+        return Ok(());
+    };
+    let store = TRUST_STORE.read();
+    let Some(pins) = store.pins.get(&host) else {
+        return Ok(());
+    };
+
+    let peer = response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .map(der_fingerprint);
+
+    match peer {
+        Some(fingerprint) if pins.contains(&fingerprint) => Ok(()),
+        Some(fingerprint) => Err(ApiError::TlsVerificationFailed {
+            host,
+            reason: format!("服务器证书指纹 {} 与固定的指纹不匹配", fingerprint),
+        }),
+        None => Err(ApiError::TlsVerificationFailed {
+            host,
+            reason: "无法获取服务器证书（连接未使用 TLS？）".to_string(),
+        }),
+    }
+}
+
+/// 发送请求并校验证书固定，供直接持有 reqwest 客户端的调用方使用
+pub trait PinnedSend {
+    fn send_pinned(self) -> BoxFuture<'static, ApiResult<Response>>;
+}
+
+impl PinnedSend for RequestBuilder {
+    fn send_pinned(self) -> BoxFuture<'static, ApiResult<Response>> {
+        Box::pin(async move {
+            let response = self.send().await.map_err(classify_request_error)?;
+            verify_pin(&response)?;
+            Ok(response)
+        })
+    }
+}
+
+/// 若请求错误由 TLS 证书校验失败引起，则转换为带有提示的错误
+pub fn classify_request_error(error: reqwest::Error) -> ApiError {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&error);
+    let mut is_tls = false;
+    while let Some(err) = source {
+        let text = err.to_string().to_ascii_lowercase();
+        if text.contains("certificate") || text.contains("unknownissuer") || text.contains("self signed") {
+            is_tls = true;
+            break;
+        }
+        source = err.source();
+    }
+
+    if is_tls {
+        let host = error
+            .url()
+            .and_then(|u| u.host_str())
+            .unwrap_or("unknown")
+            .to_string();
+        ApiError::TlsVerificationFailed {
+            host,
+            reason: format!("{}。如果该服务使用内部 CA，请在网络设置中导入其 CA 证书", error),
+        }
+    } else {
+        ApiError::RequestFailed(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pem_blocks() {
+        let pem = format!("junk\n{b}\nAAAA\n{e}\n{b}\nBBBB\n{e}\n", b = PEM_BEGIN, e = PEM_END);
+        let blocks = split_pem_blocks(&pem);
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[1].contains("BBBB"));
+    }
+
+    #[test]
+    fn test_parse_certificates_rejects_non_pem() {
+        assert!(parse_certificates("not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("AB:cd:01"), "abcd01");
+    }
+
+    #[test]
+    fn test_der_fingerprint_is_sha256_hex() {
+        let fingerprint = der_fingerprint(b"test");
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(
+            fingerprint,
+            "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        );
+    }
+}
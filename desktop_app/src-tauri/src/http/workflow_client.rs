@@ -40,6 +40,75 @@ pub struct UpdateWorkflowRequest {
 pub struct ExecuteWorkflowRequest {
     pub input_data: Option<HashMap<String, serde_json::Value>>,
     pub execution_mode: String,
+    /// 调试模式：在断点节点执行前暂停
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub debug: bool,
+    /// 断点节点 ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakpoints: Vec<String>,
+}
+
+/// 调试帧（执行暂停时包含当前节点与变量作用域）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugFrame {
+    pub execution_id: String,
+    /// running / paused / finished
+    pub state: String,
+    pub breakpoints: Vec<String>,
+    pub node_id: Option<String>,
+    pub node_type: Option<String>,
+    pub variables: Option<HashMap<String, serde_json::Value>>,
+    pub input: Option<HashMap<String, serde_json::Value>>,
+    pub node_results: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// 执行追踪中的一个节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub index: usize,
+    pub node_id: String,
+    pub node_type: Option<String>,
+    /// running / success / failed
+    pub status: String,
+    /// 执行前的节点配置、输入与变量
+    #[serde(default)]
+    pub inputs: serde_json::Value,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub variables_after: Option<HashMap<String, serde_json::Value>>,
+    /// 是否使用了原执行记录的输出（回放）
+    #[serde(default)]
+    pub replayed: bool,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub duration_ms: Option<i64>,
+}
+
+/// 执行追踪（按执行顺序的节点记录）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub execution_status: String,
+    pub failed_node_id: Option<String>,
+    /// 回放执行对应的原执行
+    pub replay_of: Option<String>,
+    pub steps: Vec<TraceStep>,
+}
+
+/// 回放执行请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayExecutionRequest {
+    /// 为空时使用原执行的输入
+    pub input_data: Option<HashMap<String, serde_json::Value>>,
+    /// 仍真实执行的节点（其余有副作用的节点使用记录的输出）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub live_nodes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakpoints: Vec<String>,
+    /// 单步模式：在每个节点执行前暂停
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub step: bool,
 }
 
 /// 工作流响应
@@ -70,7 +139,11 @@ pub struct WorkflowExecutionResponse {
     pub execution_status: String,
     pub input_data: Option<HashMap<String, serde_json::Value>>,
     pub output_data: Option<HashMap<String, serde_json::Value>>,
+    /// 结构化错误代码（如适配器节点的 `timeout`、`permission_denied`）
+    pub error_code: Option<String>,
     pub error_message: Option<String>,
+    /// 失败的节点
+    pub failed_node_id: Option<String>,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub created_at: String,
@@ -179,6 +252,67 @@ impl WorkflowApiClient {
         self.client.post(&path, &serde_json::json!({})).await
     }
 
+    /// 获取执行追踪
+    pub async fn get_execution_trace(&self, execution_id: &str) -> ApiResult<ExecutionTrace> {
+        let path = format!("/api/workflows/executions/{}/trace", execution_id);
+        self.client.get(&path).await
+    }
+
+    /// 回放执行：有副作用的节点使用原执行记录的输出
+    pub async fn replay_execution(
+        &self,
+        execution_id: &str,
+        request: ReplayExecutionRequest,
+    ) -> ApiResult<WorkflowExecutionResponse> {
+        let path = format!("/api/workflows/executions/{}/replay", execution_id);
+        self.client.post(&path, &request).await
+    }
+
+    // ================================
+    // 工作流调试
+    // ================================
+
+    /// 获取调试帧
+    pub async fn get_debug_frame(&self, execution_id: &str) -> ApiResult<DebugFrame> {
+        let path = format!("/api/workflows/executions/{}/debug", execution_id);
+        self.client.get(&path).await
+    }
+
+    /// 设置断点
+    pub async fn set_debug_breakpoints(
+        &self,
+        execution_id: &str,
+        breakpoints: Vec<String>,
+    ) -> ApiResult<DebugFrame> {
+        let path = format!("/api/workflows/executions/{}/debug/breakpoints", execution_id);
+        let body = serde_json::json!({ "breakpoints": breakpoints });
+        self.client.put(&path, &body).await
+    }
+
+    /// 修改暂停中执行的变量
+    pub async fn update_debug_variables(
+        &self,
+        execution_id: &str,
+        variables: HashMap<String, serde_json::Value>,
+        remove: Vec<String>,
+    ) -> ApiResult<DebugFrame> {
+        let path = format!("/api/workflows/executions/{}/debug/variables", execution_id);
+        let body = serde_json::json!({ "variables": variables, "remove": remove });
+        self.client.patch(&path, &body).await
+    }
+
+    /// 继续执行到下一个断点
+    pub async fn debug_continue(&self, execution_id: &str) -> ApiResult<DebugFrame> {
+        let path = format!("/api/workflows/executions/{}/debug/continue", execution_id);
+        self.client.post(&path, &serde_json::json!({})).await
+    }
+
+    /// 单步执行
+    pub async fn debug_step(&self, execution_id: &str) -> ApiResult<DebugFrame> {
+        let path = format!("/api/workflows/executions/{}/debug/step", execution_id);
+        self.client.post(&path, &serde_json::json!({})).await
+    }
+
     // ================================
     // 工作流状态管理
     // ================================
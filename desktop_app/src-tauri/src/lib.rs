@@ -12,6 +12,11 @@ pub mod system_monitor;
 pub mod database;
 pub mod http;
 pub mod config;
+pub mod live2d_protocol;
+#[cfg(feature = "mock-backend")]
+pub mod mock_backend;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
 // 重新导出常用类型供测试使用
 pub use state::{
@@ -37,6 +42,7 @@ pub use config::{ApiRouter, ApiBackend};
 // 导入和重新导出AppConfig等配置类型
 mod app_config {
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
     /// 应用配置结构
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +63,12 @@ mod app_config {
         pub decorations: bool,
         pub resizable: bool,
         pub position: Option<(i32, i32)>,
+        /// 贴边停靠设置
+        #[serde(default)]
+        pub docking: crate::utils::window_dock::DockSettings,
+        /// 点击穿透：鼠标只在角色上可交互，透明区域的点击落到下层窗口
+        #[serde(default)]
+        pub click_through: bool,
     }
 
     /// 角色配置
@@ -73,6 +85,19 @@ mod app_config {
     pub struct ThemeConfig {
         pub current_theme: String,
         pub custom_css: Option<String>,
+        /// 文字缩放百分比
+        #[serde(default = "default_text_scale")]
+        pub text_scale: u32,
+        /// 按窗口标签覆盖的文字缩放百分比
+        #[serde(default)]
+        pub window_text_scales: HashMap<String, u32>,
+        /// 色盲友好调色板（独立于主题选择）
+        #[serde(default)]
+        pub color_blind_palette: Option<String>,
+    }
+
+    fn default_text_scale() -> u32 {
+        100
     }
 
     /// 系统配置
@@ -82,6 +107,10 @@ mod app_config {
         pub minimize_to_tray: bool,
         pub close_to_tray: bool,
         pub show_notifications: bool,
+        /// 出站 HTTP 代理，仅用于返回给前端；以 `proxy_settings.json` 为准，
+        /// 不从配置文件读取（密码保存在系统密钥链中）
+        #[serde(default, skip_deserializing)]
+        pub proxy: crate::state::settings::ProxySettings,
     }
 
     impl Default for AppConfig {
@@ -95,6 +124,8 @@ mod app_config {
                     decorations: false,
                     resizable: true,
                     position: None,
+                    docking: Default::default(),
+                    click_through: false,
                 },
                 character: CharacterConfig {
                     current_character: "shizuku".to_string(),
@@ -105,12 +136,16 @@ mod app_config {
                 theme: ThemeConfig {
                     current_theme: "anime".to_string(),
                     custom_css: None,
+                    text_scale: default_text_scale(),
+                    window_text_scales: HashMap::new(),
+                    color_blind_palette: None,
                 },
                 system: SystemConfig {
                     auto_start: false,
                     minimize_to_tray: true,
                     close_to_tray: true,
                     show_notifications: true,
+                    proxy: Default::default(),
                 },
             }
         }
@@ -1,3 +1,10 @@
+//! # Live2D 协议
+//!
+//! - `zishu://live2d/...`：从本地缓存提供 Live2D 模型资源（[`handle_zishu_protocol`]）
+//! - 参数帧：后端驱动模型参数的批量帧格式与混合器（见 [`parameters`]）
+
+pub mod parameters;
+
 use tauri::http::{header, Request, Response, ResponseBuilder};
 use tauri::http::status::StatusCode;
 use tracing::warn;
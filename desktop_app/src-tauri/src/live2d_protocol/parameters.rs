@@ -0,0 +1,272 @@
+//! # Live2D 参数帧
+//!
+//! 后端的多个来源（待机呼吸、视线跟随、行为、口型同步、手动设置）同时写入模型参数，
+//! [`ParameterMixer`] 按来源优先级合并，同一参数取优先级最高的来源的值。
+//!
+//! 渲染窗口订阅 `live2d-parameters` 频道，每个刷新周期（默认 60 帧/秒）最多收到一帧：
+//!
+//! ```json
+//! { "seq": 42, "t": 700, "p": { "ParamAngleX": 12.5, "ParamMouthOpenY": 0.8 }, "r": ["ParamEyeBallX"] }
+//! ```
+//!
+//! - `seq`：帧序号；`t`：距刷新开始的毫秒数
+//! - `p`：与上一帧相比发生变化的参数
+//! - `r`：不再被任何来源控制的参数，渲染端应交还给模型自身的动作与物理
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 默认参数帧率
+pub const DEFAULT_FRAME_RATE: u32 = 60;
+/// 小于该变化量时不重复发送
+const EPSILON: f32 = 1e-3;
+/// 未知参数的取值范围
+const DEFAULT_RANGE: (f32, f32) = (-100.0, 100.0);
+/// 呼吸周期（秒）
+const BREATH_PERIOD_SECS: f64 = 3.5;
+
+/// Cubism 标准参数的取值范围
+const STANDARD_RANGES: &[(&str, f32, f32)] = &[
+    ("ParamAngleX", -30.0, 30.0),
+    ("ParamAngleY", -30.0, 30.0),
+    ("ParamAngleZ", -30.0, 30.0),
+    ("ParamBodyAngleX", -10.0, 10.0),
+    ("ParamBodyAngleY", -10.0, 10.0),
+    ("ParamBodyAngleZ", -10.0, 10.0),
+    ("ParamEyeBallX", -1.0, 1.0),
+    ("ParamEyeBallY", -1.0, 1.0),
+    ("ParamEyeLOpen", 0.0, 1.0),
+    ("ParamEyeROpen", 0.0, 1.0),
+    ("ParamMouthOpenY", 0.0, 1.0),
+    ("ParamMouthForm", -1.0, 1.0),
+    ("ParamBreath", 0.0, 1.0),
+];
+
+/// 参数来源，按优先级从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterSource {
+    /// 待机呼吸
+    Idle,
+    /// 视线跟随鼠标
+    EyeTracking,
+    /// 行为引擎
+    Behavior,
+    /// 口型同步
+    LipSync,
+    /// 通过命令手动设置
+    Manual,
+}
+
+/// 参数 ID 只能包含字母、数字与 `_`
+pub fn validate_parameter_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("无效的 Live2D 参数 ID: {}", id))
+    }
+}
+
+/// 把参数值限制在取值范围内
+pub fn clamp(id: &str, value: f32) -> f32 {
+    let (min, max) = STANDARD_RANGES
+        .iter()
+        .find(|(name, _, _)| *name == id)
+        .map(|(_, min, max)| (*min, *max))
+        .unwrap_or(DEFAULT_RANGE);
+    if value.is_nan() {
+        0.0_f32.clamp(min, max)
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+/// 参数帧
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterFrame {
+    pub seq: u64,
+    pub t: u64,
+    pub p: BTreeMap<String, f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub r: Vec<String>,
+}
+
+/// 按来源优先级合并参数，并生成增量帧
+#[derive(Debug, Default)]
+pub struct ParameterMixer {
+    layers: HashMap<ParameterSource, HashMap<String, f32>>,
+    last_sent: HashMap<String, f32>,
+    seq: u64,
+}
+
+impl ParameterMixer {
+    /// 设置来源的参数值（超出范围时截断）
+    pub fn set(&mut self, source: ParameterSource, id: &str, value: f32) {
+        self.layers.entry(source).or_default().insert(id.to_string(), clamp(id, value));
+    }
+
+    /// 来源不再控制该参数
+    pub fn release(&mut self, source: ParameterSource, id: &str) {
+        if let Some(layer) = self.layers.get_mut(&source) {
+            layer.remove(id);
+        }
+    }
+
+    /// 来源不再控制任何参数
+    pub fn clear(&mut self, source: ParameterSource) {
+        self.layers.remove(&source);
+    }
+
+    /// 合并后的参数值
+    pub fn resolve(&self) -> BTreeMap<String, f32> {
+        let mut sources: Vec<_> = self.layers.iter().collect();
+        sources.sort_by_key(|(source, _)| **source);
+        let mut resolved = BTreeMap::new();
+        for (_, layer) in sources {
+            for (id, value) in layer {
+                resolved.insert(id.clone(), *value);
+            }
+        }
+        resolved
+    }
+
+    /// 生成与上一帧相比的增量帧；没有变化时返回 `None`
+    pub fn next_frame(&mut self, t: u64) -> Option<ParameterFrame> {
+        let resolved = self.resolve();
+        let changed: BTreeMap<String, f32> = resolved
+            .iter()
+            .filter(|(id, value)| !matches!(self.last_sent.get(*id), Some(last) if (*last - **value).abs() <= EPSILON))
+            .map(|(id, value)| (id.clone(), *value))
+            .collect();
+        let mut released: Vec<String> = self.last_sent.keys().filter(|id| !resolved.contains_key(*id)).cloned().collect();
+        if changed.is_empty() && released.is_empty() {
+            return None;
+        }
+        released.sort();
+
+        for id in &released {
+            self.last_sent.remove(id);
+        }
+        self.last_sent.extend(changed.iter().map(|(id, value)| (id.clone(), *value)));
+        self.seq += 1;
+        Some(ParameterFrame { seq: self.seq, t, p: changed, r: released })
+    }
+
+    /// 渲染窗口重新加载后需要完整帧
+    pub fn invalidate(&mut self) {
+        self.last_sent.clear();
+    }
+}
+
+/// 视线跟随：由鼠标相对模型中心的偏移计算眼球、头部与身体角度
+///
+/// `reach` 为偏移达到最大角度时的距离（像素）。
+pub fn eye_tracking(cursor: (f64, f64), center: (f64, f64), reach: f64) -> [(&'static str, f32); 5] {
+    let reach = reach.max(1.0);
+    let x = ((cursor.0 - center.0) / reach).clamp(-1.0, 1.0) as f32;
+    // 屏幕坐标向下为正，模型参数向上为正
+    let y = ((center.1 - cursor.1) / reach).clamp(-1.0, 1.0) as f32;
+    [
+        ("ParamEyeBallX", x),
+        ("ParamEyeBallY", y),
+        ("ParamAngleX", x * 30.0),
+        ("ParamAngleY", y * 30.0),
+        ("ParamBodyAngleX", x * 10.0),
+    ]
+}
+
+/// 待机呼吸：`amplitude`（0–1）缩放的正弦曲线
+pub fn breathing(elapsed_secs: f64, amplitude: f32) -> f32 {
+    let phase = (elapsed_secs / BREATH_PERIOD_SECS) * std::f64::consts::TAU;
+    (0.5 + 0.5 * phase.sin()) as f32 * amplitude.clamp(0.0, 1.0)
+}
+
+/// 后端驱动的动作设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionSettings {
+    /// 视线跟随鼠标
+    pub eye_tracking: bool,
+    /// 视线偏移达到最大角度时鼠标与模型中心的距离（像素）
+    pub tracking_reach: f64,
+    /// 呼吸幅度（0–1），0 表示交给模型自身的待机动作
+    pub breathing_amplitude: f32,
+    /// 参数帧率
+    pub frame_rate: u32,
+}
+
+impl Default for MotionSettings {
+    fn default() -> Self {
+        Self {
+            eye_tracking: true,
+            tracking_reach: 600.0,
+            breathing_amplitude: 0.5,
+            frame_rate: DEFAULT_FRAME_RATE,
+        }
+    }
+}
+
+impl MotionSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=120).contains(&self.frame_rate) {
+            return Err("参数帧率必须在 1 到 120 之间".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.breathing_amplitude) {
+            return Err("呼吸幅度必须在 0 到 1 之间".to_string());
+        }
+        if self.tracking_reach.is_nan() || self.tracking_reach <= 0.0 {
+            return Err("视线跟随距离必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixer_priority_and_delta_frames() {
+        let mut mixer = ParameterMixer::default();
+        mixer.set(ParameterSource::EyeTracking, "ParamAngleX", 10.0);
+        mixer.set(ParameterSource::Manual, "ParamAngleX", 99.0);
+        mixer.set(ParameterSource::LipSync, "ParamMouthOpenY", 0.6);
+
+        let frame = mixer.next_frame(0).unwrap();
+        assert_eq!(frame.seq, 1);
+        // 手动设置优先，且按标准范围截断
+        assert_eq!(frame.p.get("ParamAngleX"), Some(&30.0));
+        assert_eq!(frame.p.get("ParamMouthOpenY"), Some(&0.6));
+
+        // 没有变化时不发送
+        mixer.set(ParameterSource::LipSync, "ParamMouthOpenY", 0.6);
+        assert!(mixer.next_frame(16).is_none());
+
+        // 手动值释放后回落到视线跟随的值
+        mixer.clear(ParameterSource::Manual);
+        mixer.release(ParameterSource::LipSync, "ParamMouthOpenY");
+        let frame = mixer.next_frame(33).unwrap();
+        assert_eq!(frame.p, BTreeMap::from([("ParamAngleX".to_string(), 10.0)]));
+        assert_eq!(frame.r, vec!["ParamMouthOpenY".to_string()]);
+
+        mixer.invalidate();
+        assert_eq!(mixer.next_frame(50).unwrap().p.len(), 1);
+    }
+
+    #[test]
+    fn test_eye_tracking_breathing_and_validation() {
+        let params = eye_tracking((1300.0, 100.0), (1000.0, 400.0), 600.0);
+        assert_eq!(params[0], ("ParamEyeBallX", 0.5));
+        assert_eq!(params[1], ("ParamEyeBallY", 0.5));
+        assert_eq!(eye_tracking((-5000.0, 400.0), (0.0, 400.0), 600.0)[2], ("ParamAngleX", -30.0));
+
+        assert!((breathing(0.0, 1.0) - 0.5).abs() < 1e-6);
+        assert!(breathing(BREATH_PERIOD_SECS / 4.0, 0.4) <= 0.4 + 1e-6);
+
+        assert!(validate_parameter_id("ParamAngleX").is_ok());
+        assert!(validate_parameter_id("Param-Angle").is_err());
+        assert_eq!(clamp("CustomParam", f32::NAN), 0.0);
+        assert!(MotionSettings { frame_rate: 0, ..MotionSettings::default() }.validate().is_err());
+    }
+}
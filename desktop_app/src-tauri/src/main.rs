@@ -4,6 +4,7 @@
 use tauri::{api::shell, AppHandle, Manager, WindowBuilder, WindowUrl};
 use tracing::{error, info};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // 导入模块
 mod commands;
@@ -16,6 +17,8 @@ mod database;
 mod http;
 mod config;
 mod live2d_protocol;
+#[cfg(feature = "mock-backend")]
+mod mock_backend;
 
 use commands::*;
 use state::*;
@@ -40,6 +43,12 @@ pub struct WindowConfig {
     pub decorations: bool,
     pub resizable: bool,
     pub position: Option<(i32, i32)>,
+    /// 贴边停靠设置
+    #[serde(default)]
+    pub docking: crate::utils::window_dock::DockSettings,
+    /// 点击穿透：鼠标只在角色上可交互，透明区域的点击落到下层窗口
+    #[serde(default)]
+    pub click_through: bool,
 }
 
 /// 角色配置
@@ -56,6 +65,19 @@ pub struct CharacterConfig {
 pub struct ThemeConfig {
     pub current_theme: String,
     pub custom_css: Option<String>,
+    /// 文字缩放百分比
+    #[serde(default = "default_text_scale")]
+    pub text_scale: u32,
+    /// 按窗口标签覆盖的文字缩放百分比
+    #[serde(default)]
+    pub window_text_scales: HashMap<String, u32>,
+    /// 色盲友好调色板（独立于主题选择）
+    #[serde(default)]
+    pub color_blind_palette: Option<String>,
+}
+
+fn default_text_scale() -> u32 {
+    100
 }
 
 /// 系统配置
@@ -65,6 +87,10 @@ pub struct SystemConfig {
     pub minimize_to_tray: bool,
     pub close_to_tray: bool,
     pub show_notifications: bool,
+    /// 出站 HTTP 代理，仅用于返回给前端；以 `proxy_settings.json` 为准，
+    /// 不从配置文件读取（密码保存在系统密钥链中）
+    #[serde(default, skip_deserializing)]
+    pub proxy: crate::state::settings::ProxySettings,
 }
 
 impl Default for AppConfig {
@@ -78,6 +104,8 @@ impl Default for AppConfig {
                 decorations: false,
                 resizable: true,
                 position: None,
+                docking: Default::default(),
+                click_through: false,
             },
             character: CharacterConfig {
                 current_character: "shizuku".to_string(),
@@ -88,12 +116,16 @@ impl Default for AppConfig {
             theme: ThemeConfig {
                 current_theme: "anime".to_string(),
                 custom_css: None,
+                text_scale: default_text_scale(),
+                window_text_scales: HashMap::new(),
+                color_blind_palette: None,
             },
             system: SystemConfig {
                 auto_start: false,
                 minimize_to_tray: true,
                 close_to_tray: true,
                 show_notifications: true,
+                proxy: Default::default(),
             },
         }
     }
@@ -126,6 +158,7 @@ fn open_chat_window(app: &AppHandle) {
         match chat_window {
             Ok(window) => {
                 info!("聊天窗口创建成功");
+                events::window_layout::restore_position(&window);
                 let _ = window.show();
             }
             Err(e) => {
@@ -266,13 +299,28 @@ fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
 async fn start_background_tasks(app_handle: AppHandle) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("启动后台任务");
     
+    use utils::safe_mode::{allows, SkippedKind};
+    use utils::startup_profiler::{self, BootPhase};
+    
     // 启动适配器管理器
-    adapter::start_adapter_manager(app_handle.clone()).await?;
+    if allows(SkippedKind::Adapter, "adapter_manager") {
+        let span = startup_profiler::start(BootPhase::Adapters);
+        adapter::start_adapter_manager(app_handle.clone()).await?;
+        span.finish();
+    }
     
     // 启动系统监控
-    system_monitor::start_system_monitor(app_handle.clone()).await?;
+    if allows(SkippedKind::BackgroundTask, "system_monitor") {
+        let span = startup_profiler::start(BootPhase::SystemMonitor);
+        system_monitor::start_system_monitor(app_handle.clone()).await?;
+        span.finish();
+    }
     
     // 启动自动保存任务
+    if !allows(SkippedKind::BackgroundTask, "config_autosave") {
+        info!("后台任务启动完成（安全模式）");
+        return Ok(());
+    }
     let app_handle_clone = app_handle.clone();
     tauri::async_runtime::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5分钟
@@ -292,13 +340,29 @@ async fn start_background_tasks(app_handle: AppHandle) -> Result<(), Box<dyn std
 }
 
 fn main() {
+    // 启动耗时以此为起点
+    utils::startup_profiler::mark_process_start();
+    
+    // 日志目录与数据库都位于数据目录中，必须在打开它们之前完成迁移
+    utils::data_migration::run_startup_migrations();
+    
     // 初始化日志系统
+    let logging_span = utils::startup_profiler::start(utils::startup_profiler::BootPhase::Logging);
     if let Err(e) = init_logging() {
         eprintln!("初始化日志系统失败: {}", e);
         std::process::exit(1);
     }
+    logging_span.finish();
     
     info!("🐾 Zishu Sensei 桌面宠物应用启动");
+    utils::data_migration::log_startup_migrations();
+    
+    // 记录 panic 与原生崩溃
+    utils::crash_reporter::install();
+    
+    // 检测崩溃次数与启动参数，决定是否以安全模式启动
+    utils::safe_mode::initialize();
+    utils::metric_registry::mark_started();
     
     // 创建系统托盘
     let system_tray = events::tray::create_system_tray();
@@ -314,6 +378,24 @@ fn main() {
             let app_state = AppState::new(app_handle.clone()).map_err(|e| e.to_string())?;
             app.manage(app_state);
             
+            // 错误监控（系统健康状态依赖）
+            if let Ok(app_data_dir) = crate::utils::config::get_app_data_dir() {
+                let _ = std::fs::create_dir_all(&app_data_dir);
+                let db_path = app_data_dir.join("error_monitor.db");
+                match commands::error_monitoring::ErrorMonitorState::new(&db_path.to_string_lossy()) {
+                    Ok(state) => {
+                        app.manage(state);
+                    }
+                    Err(e) => error!("初始化错误监控失败: {}", e),
+                }
+            }
+            
+            // 监督后台子系统，心跳停滞时自动重启
+            let app_handle_watchdog = app_handle.clone();
+            utils::watchdog::start(move |name| {
+                let _ = app_handle_watchdog.emit_all("subsystem-failed", name);
+            });
+            
             // 关键：使用同步通道等待异步初始化完成
             // 这样可以确保在前端调用命令前，AppState 已经被正确管理
             info!("开始初始化关键组件");
@@ -324,19 +406,24 @@ fn main() {
             // 在异步任务中完成初始化
             let app_handle_init = app_handle.clone();
             tauri::async_runtime::spawn(async move {
+                use utils::startup_profiler::{self, BootPhase};
+                
                 // 初始化安全审计日志
-                let app_data_dir = app_handle_init.path_resolver()
-                    .app_data_dir()
+                let span = startup_profiler::start(BootPhase::AuditLog);
+                let app_data_dir = crate::utils::config::get_app_data_dir()
                     .expect("无法获取应用数据目录");
                 let audit_db_path = app_data_dir.join("security_audit.db");
                 if let Err(e) = utils::security_audit::init_global_audit_logger(&audit_db_path) {
                     error!("初始化审计日志失败: {}", e);
+                    span.fail(e.to_string());
                 } else {
                     info!("安全审计日志系统已初始化");
+                    span.finish();
                 }
                 
                 // 初始化日志数据库（PostgreSQL）
                 {
+                    let span = startup_profiler::start(BootPhase::LogDatabase);
                     use deadpool_postgres::{Config, Runtime};
                     use tokio_postgres::NoTls;
                     
@@ -357,19 +444,30 @@ fn main() {
                             
                             app_handle_init.manage(log_db);
                             info!("日志数据库系统已初始化");
+                            span.finish();
                         }
                         Err(e) => {
                             error!("创建数据库连接池失败: {}", e);
+                            span.fail(e.to_string());
                         }
                     }
                 }
                 
+                // 加载用户资料列表（决定设置目录与数据库 schema）
+                if let Err(e) = commands::profiles::initialize_profiles() {
+                    tracing::warn!("用户资料初始化失败: {}", e);
+                }
+                
                 // 初始化主数据库
+                let span = startup_profiler::start(BootPhase::Database);
                 if let Err(e) = database::init_database(app_handle_init.clone()).await {
                     error!("数据库初始化失败: {}", e);
+                    span.fail(e.to_string());
+                    commands::startup::complete_startup_profile(true);
                     let _ = init_tx.send(Err(format!("database init failed: {e}")));
                     return;
                 }
+                span.finish();
                 
 /*                 // 初始化应用状态 - 这是最关键的，必须在这里完成
                  match AppState::new(app_handle_init.clone()) {
@@ -385,9 +483,18 @@ fn main() {
                 } */
                 
                 // 加载配置
+                let span = startup_profiler::start(BootPhase::Config);
                 let config = load_config(&app_handle_init).await.unwrap_or_default();
+                span.finish();
+                
+                // 安全模式：记录未启用的主题与适配器
+                if utils::safe_mode::is_active() {
+                    commands::safe_mode::record_skipped_theme(&config);
+                    commands::safe_mode::record_skipped_adapters().await;
+                }
                 
                 // 设置主窗口属性
+                let span = startup_profiler::start(BootPhase::Window);
                 if let Some(main_window) = app_handle_init.get_window("main") {
                     // 应用窗口配置
                     let _ = main_window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
@@ -421,6 +528,15 @@ fn main() {
                     
                     info!("主窗口配置完成");
                 }
+                span.finish();
+                
+                // 注册通知按钮可执行的内置操作
+                commands::notifications::register_builtin_actions();
+                
+                // 有多个资料时让用户选择
+                commands::profiles::emit_profile_selector(&app_handle_init);
+                
+                commands::startup::complete_startup_profile(false);
                 
                 // 发送初始化完成信号
                 let _ = init_tx.send(Ok(()));
@@ -444,17 +560,307 @@ fn main() {
             // 非关键的初始化任务可以异步执行
             let app_handle_clone = app_handle.clone();
             tauri::async_runtime::spawn(async move {
+                use utils::safe_mode::{allows, SkippedKind};
+                
+                let background_span = utils::startup_profiler::start(utils::startup_profiler::BootPhase::BackgroundServices);
+                
                 // 初始化语言设置
                 if let Err(e) = commands::language::initialize_language_settings(&app_handle_clone).await {
                     tracing::warn!("语言设置初始化失败: {}", e);
                 }
                 
+                // 加载代理设置与信任库
+                if let Err(e) = commands::network::initialize_proxy_settings() {
+                    tracing::warn!("代理设置初始化失败: {}", e);
+                }
+                if let Err(e) = commands::network::initialize_trust_store() {
+                    tracing::warn!("信任库初始化失败: {}", e);
+                }
+                
+                // 加载后端端点配置
+                if let Err(e) = commands::backend::initialize_backend_profiles() {
+                    tracing::warn!("后端端点配置初始化失败: {}", e);
+                }
+                
+                // 开发模式：启动进程内模拟后端
+                #[cfg(feature = "mock-backend")]
+                if let Err(e) = mock_backend::start() {
+                    tracing::error!("模拟后端启动失败: {}", e);
+                }
+                
+                // 与后端协商 API 版本
+                commands::backend::negotiate_backend_capabilities(app_handle_clone.clone()).await;
+                
+                // 加载磁盘空间守卫设置并启动周期检查
+                if let Err(e) = commands::storage::initialize_disk_guard() {
+                    tracing::warn!("磁盘空间守卫设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "disk_space_monitor") {
+                    commands::storage::start_disk_space_monitor(app_handle_clone.clone());
+                }
+                
+                // 按数据保留策略定期清理过期数据
+                if let Err(e) = commands::privacy::initialize_data_retention() {
+                    tracing::warn!("数据保留策略初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "data_retention") {
+                    commands::privacy::start_retention_cleanup_scheduler(app_handle_clone.clone());
+                }
+                
+                // 加载音频设备路由并监控设备热插拔
+                if let Err(e) = commands::audio::initialize_audio_routing() {
+                    tracing::warn!("音频设备路由初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "audio_device_monitor") {
+                    commands::audio::start_audio_device_monitor(app_handle_clone.clone());
+                }
+                if let Err(e) = commands::live2d_parameters::initialize_motion_settings() {
+                    tracing::warn!("Live2D 动作设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "live2d_parameters") {
+                    commands::live2d_parameters::start_parameter_ticker(app_handle_clone.clone());
+                }
+                if allows(SkippedKind::BackgroundTask, "click_through") {
+                    events::click_through::start_click_through(app_handle_clone.clone());
+                }
+                if let Err(e) = commands::lipsync::initialize_lipsync_settings() {
+                    tracing::warn!("口型同步设置初始化失败: {}", e);
+                }
+                if let Err(e) = commands::tts::initialize_tts() {
+                    tracing::warn!("语音合成设置初始化失败: {}", e);
+                }
+                
+                // 加载语音输入设置（语音活动检测 / 按键说话）
+                if allows(SkippedKind::BackgroundTask, "voice_input") {
+                    if let Err(e) = commands::audio::initialize_voice_input(&app_handle_clone) {
+                        tracing::warn!("语音输入初始化失败: {}", e);
+                    }
+                }
+                
+                // 加载唤醒词设置（启用时开始监听）
+                if allows(SkippedKind::BackgroundTask, "hotword_listener") {
+                    if let Err(e) = commands::hotword::initialize_hotword(&app_handle_clone) {
+                        tracing::warn!("唤醒词初始化失败: {}", e);
+                    }
+                }
+                
+                // 加载适配器执行池并发限制
+                if let Err(e) = commands::adapter::initialize_adapter_pool() {
+                    tracing::warn!("适配器执行池设置初始化失败: {}", e);
+                }
+                if let Err(e) = commands::adapter::initialize_adapter_cache() {
+                    tracing::warn!("适配器结果缓存设置初始化失败: {}", e);
+                }
+                
+                // 加载向量搜索的嵌入提供者设置
+                if let Err(e) = commands::embedding::initialize_embedding() {
+                    tracing::warn!("嵌入提供者设置初始化失败: {}", e);
+                }
+                
+                // 后台会话话题分析
+                if allows(SkippedKind::BackgroundTask, "topic_analysis") {
+                    commands::topics::start_topic_analysis_scheduler(app_handle_clone.clone());
+                }
+                
+                // 空闲且接通电源时整理会话摘要、向量与话题
+                if let Err(e) = commands::sleep_learning::initialize_sleep_learning() {
+                    tracing::warn!("空闲整理设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "sleep_learning") {
+                    commands::sleep_learning::start_sleep_learning_scheduler(app_handle_clone.clone());
+                }
+                
+                // 按季节与节日自动切换角色服装
+                if allows(SkippedKind::BackgroundTask, "seasonal_scheduler") {
+                    commands::seasonal::start_seasonal_scheduler(app_handle_clone.clone());
+                }
+                
+                // 多个角色实例之间的互动
+                if let Err(e) = commands::pet_interaction::initialize_pet_interaction() {
+                    tracing::warn!("角色互动设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "pet_interaction") {
+                    commands::pet_interaction::start_pet_interaction_scheduler(app_handle_clone.clone());
+                }
+                
+                // 专注计时（番茄钟）
+                if let Err(e) = commands::focus::initialize_focus_timer() {
+                    tracing::warn!("专注计时设置初始化失败: {}", e);
+                }
+                
+                // 剪贴板历史（需用户开启）
+                if let Err(e) = commands::clipboard::initialize_clipboard_history() {
+                    tracing::warn!("剪贴板历史设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "clipboard_monitor") {
+                    commands::clipboard::start_clipboard_monitor(app_handle_clone.clone());
+                }
+                
+                // 角色情绪：消退后恢复平静表情
+                if allows(SkippedKind::BackgroundTask, "emotion_decay") {
+                    commands::emotion::start_emotion_decay(app_handle_clone.clone());
+                }
+                
+                // 副显示器展示模式
+                if let Err(e) = commands::kiosk::initialize_kiosk() {
+                    tracing::warn!("展示模式设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "kiosk_mode") {
+                    commands::kiosk::start_kiosk_scheduler(app_handle_clone.clone());
+                }
+                
+                // 桌宠摆放：回到上次的显示器，漫游模式下定时换显示器
+                if let Err(e) = commands::pet_placement::initialize_pet_placement(&app_handle_clone) {
+                    tracing::warn!("桌宠摆放初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "pet_placement") {
+                    commands::pet_placement::start_pet_placement_scheduler(app_handle_clone.clone());
+                }
+                
+                // 恢复当前角色的模型 / 提示词 / 语音绑定
+                commands::character::restore_character_binding(&app_handle_clone).await;
+                
+                // 聊天发件箱：后端恢复后重发离线期间的消息
+                commands::chat_outbox::initialize_chat_outbox(&app_handle_clone).await;
+                if allows(SkippedKind::BackgroundTask, "chat_outbox") {
+                    commands::chat_outbox::start_chat_outbox_scheduler(app_handle_clone.clone());
+                }
+                
+                // 数据库连接健康监测，中断后自动重连
+                if allows(SkippedKind::BackgroundTask, "database_health") {
+                    commands::database::start_database_health_watchdog(app_handle_clone.clone());
+                }
+                
+                // 适配器日志按保留策略清理
+                if allows(SkippedKind::BackgroundTask, "adapter_log_retention") {
+                    commands::adapter_logs::start_adapter_log_retention();
+                }
+                
+                // 无痕会话超时清理
+                if allows(SkippedKind::BackgroundTask, "incognito_sessions") {
+                    commands::incognito::start_incognito_sweeper(app_handle_clone.clone());
+                }
+                
+                // 工作流定时触发
+                if allows(SkippedKind::BackgroundTask, "workflow_schedule") {
+                    commands::workflow_schedule::start_workflow_scheduler(app_handle_clone.clone());
+                }
+                
+                // 托盘状态：恢复上次的最近对话与通知，并定期保存
+                commands::tray_state::initialize_tray_state(&app_handle_clone).await;
+                if allows(SkippedKind::BackgroundTask, "tray_state") {
+                    commands::tray_state::start_tray_state_saver(app_handle_clone.clone());
+                }
+                
+                // 对话气泡队列
+                if let Err(e) = commands::speech_queue::initialize_speech_queue() {
+                    tracing::warn!("对话气泡设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "speech_queue") {
+                    commands::speech_queue::start_speech_queue_scheduler(app_handle_clone.clone());
+                }
+                
+                // Live2D 模型热重载：模型文件变化时重新注册角色
+                if allows(SkippedKind::BackgroundTask, "live2d_model_watcher") {
+                    if let Err(e) = commands::live2d_assets::start_model_watcher(app_handle_clone.clone()) {
+                        tracing::warn!("Live2D 模型热重载启动失败: {}", e);
+                    }
+                }
+                
+                // 记录运行时长供统计页使用
+                if allows(SkippedKind::BackgroundTask, "uptime_tracking") {
+                    commands::statistics::start_uptime_tracking();
+                }
+                
+                // 本地自动化接口（设置中开启时监听，可触发工作流）
+                if allows(SkippedKind::Workflow, "automation_interface") {
+                    if let Err(e) = commands::automation::initialize_automation(&app_handle_clone) {
+                        tracing::warn!("自动化接口初始化失败: {}", e);
+                    }
+                }
+                
+                // 移动端通知转发与每日总结
+                if let Err(e) = commands::mobile_relay::initialize_mobile_relay() {
+                    tracing::warn!("移动端转发设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "daily_summary") {
+                    commands::mobile_relay::start_daily_summary_scheduler();
+                }
+                
+                // 多设备同步
+                if let Err(e) = commands::device_sync::initialize_device_sync(&app_handle_clone) {
+                    tracing::warn!("多设备同步设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "device_sync") {
+                    commands::device_sync::start_sync_scheduler(app_handle_clone.clone());
+                }
+                
+                // 日历宿主接口与日程提醒
+                if let Err(e) = commands::calendar_host::initialize_calendar_host() {
+                    tracing::warn!("日历设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "calendar_reminders") {
+                    commands::calendar_host::start_calendar_reminders(app_handle_clone.clone());
+                }
+                
+                // 数据库自动备份与恢复演练
+                if let Err(e) = commands::backup::initialize_database_backup() {
+                    tracing::warn!("数据库备份设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "database_backup") {
+                    commands::backup::start_backup_scheduler(app_handle_clone.clone());
+                }
+                
+                // 定时上传到远程存储的加密配置备份
+                if let Err(e) = commands::settings::initialize_remote_backup() {
+                    tracing::warn!("远程备份设置初始化失败: {}", e);
+                }
+                if allows(SkippedKind::BackgroundTask, "remote_backup") {
+                    commands::settings::start_remote_backup_scheduler(app_handle_clone.clone());
+                }
+                
+                // 资源预算（由系统监控采样驱动）
+                if let Err(e) = commands::resource_budget::initialize_resource_budget() {
+                    tracing::warn!("资源预算设置初始化失败: {}", e);
+                }
+                
+                // 大型提示词的费用预估
+                if let Err(e) = commands::cost_preview::initialize_cost_preview() {
+                    tracing::warn!("费用预估设置初始化失败: {}", e);
+                }
+                
+                // 聊天请求的中间件管道
+                if let Err(e) = commands::llm_middleware::initialize_llm_middleware() {
+                    tracing::warn!("LLM 中间件设置初始化失败: {}", e);
+                }
+                
+                // Prometheus 指标端点（设置中开启时监听本机地址）
+                if let Err(e) = commands::metrics::initialize_metrics() {
+                    tracing::warn!("指标端点初始化失败: {}", e);
+                }
+                
+                // 加载无障碍设置
+                if let Err(e) = commands::accessibility::initialize_accessibility_settings(&app_handle_clone) {
+                    tracing::warn!("无障碍设置初始化失败: {}", e);
+                }
+                if let Err(e) = commands::shortcuts::initialize_shortcut_library(&app_handle_clone) {
+                    tracing::warn!("宏与快捷回复加载失败: {}", e);
+                }
+                
                 // 启动后台任务
                 if let Err(e) = start_background_tasks(app_handle_clone.clone()).await {
                     error!("启动后台任务失败: {}", e);
                 }
                 
                 info!("✅ 后台任务初始化完成");
+                background_span.finish();
+                commands::startup::complete_startup_profile(false);
+            });
+            
+            // 稳定运行一段时间后清零崩溃计数
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(utils::safe_mode::STABLE_AFTER_SECS)).await;
+                utils::safe_mode::mark_stable();
             });
             
             // 处理 deep link
@@ -478,12 +884,30 @@ fn main() {
         .register_uri_scheme_protocol("zishu", |app, request| {
             live2d_protocol::handle_zishu_protocol(app, request)
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(commands::metrics::instrument_handler(tauri::generate_handler![
             // 聊天命令
             commands::chat::send_message,
             commands::chat::get_chat_history,
             commands::chat::clear_chat_history,
             commands::chat::set_chat_model,
+            commands::chat::edit_message,
+            commands::chat::delete_message,
+            commands::chat::get_message_revisions,
+            commands::chat::post_structured_message,
+            commands::chat::export_conversation,
+            commands::chat::export_chat_session,
+            commands::chat::import_chat_session,
+            commands::chat_outbox::list_outbox_messages,
+            commands::chat_outbox::retry_outbox_message,
+            commands::chat_outbox::cancel_outbox_message,
+            commands::incognito::start_incognito_session,
+            commands::incognito::end_incognito_session,
+            commands::incognito::get_incognito_messages,
+            commands::incognito::get_incognito_session,
+            commands::sharing::publish_session_snapshot,
+            commands::sharing::unpublish_snapshot,
+            commands::sharing::list_shared_snapshots,
+            commands::sharing::get_session_snapshot,
             
             // 模型配置命令
             commands::model_config::save_model_config,
@@ -508,6 +932,11 @@ fn main() {
             commands::settings::update_window_config,
             commands::settings::get_theme_config,
             commands::settings::update_theme_config,
+            commands::settings::preview_text_scale,
+            commands::settings::set_window_text_scale,
+            commands::settings::get_window_text_scale,
+            commands::settings::get_color_blind_palettes,
+            commands::settings::set_color_blind_palette,
             commands::settings::get_system_config,
             commands::settings::update_system_config,
             commands::settings::get_config_paths,
@@ -517,6 +946,22 @@ fn main() {
             commands::settings::create_config_snapshot,
             commands::settings::restore_from_snapshot,
             commands::settings::compare_configs,
+            commands::settings::get_config_timeline,
+            commands::settings::rollback_to_timeline_point,
+            commands::settings::undo_settings_change,
+            commands::settings::redo_settings_change,
+            commands::settings::get_settings_change_log,
+            commands::settings::get_remote_backup_settings,
+            commands::settings::update_remote_backup_settings,
+            commands::settings::create_remote_backup,
+            commands::settings::list_remote_backups,
+            commands::settings::restore_remote_backup,
+            
+            // 主题市场命令
+            commands::theme::install_market_theme,
+            commands::theme::uninstall_market_theme,
+            commands::theme::preview_theme,
+            commands::theme::end_theme_preview,
             
             // 角色命令
             commands::character::get_characters,
@@ -529,9 +974,34 @@ fn main() {
             commands::character::set_character_scale,
             commands::character::save_character_config,
             commands::character::get_character_config,
+            commands::character::get_character_bindings,
+            commands::character::set_character_binding,
+            commands::character::set_apply_binding_on_switch,
 
             // Live2D 资源缓存
             commands::live2d_assets::prepare_live2d_assets,
+            commands::live2d_assets::rescan_live2d_models,
+            
+            // 多角色互动命令
+            commands::pet_interaction::register_pet_instance,
+            commands::pet_interaction::unregister_pet_instance,
+            commands::pet_interaction::set_pet_busy,
+            commands::pet_interaction::list_pet_instances,
+            commands::pet_interaction::trigger_pet_interaction,
+            commands::pet_interaction::get_pet_interaction_settings,
+            commands::pet_interaction::update_pet_interaction_settings,
+            
+            // 展示模式命令
+            commands::kiosk::get_kiosk_settings,
+            commands::kiosk::update_kiosk_settings,
+            commands::kiosk::set_kiosk_active,
+            commands::kiosk::list_kiosk_monitors,
+            commands::kiosk::get_kiosk_dashboard,
+            
+            // 桌宠摆放命令
+            commands::pet_placement::set_pet_monitor,
+            commands::pet_placement::get_pet_placement,
+            commands::pet_placement::update_pet_placement_settings,
             
             // 窗口命令
             commands::window::minimize_to_tray,
@@ -545,6 +1015,16 @@ fn main() {
             commands::window::maximize_window,
             commands::window::unmaximize_window,
             commands::window::close_window,
+            commands::window::get_window_layout_settings,
+            commands::window::update_window_layout_settings,
+            commands::window::get_dock_state,
+            commands::window::set_docking_enabled,
+            commands::window::update_dock_settings,
+            commands::window::set_dock_peeking,
+            commands::window::set_dock_hover,
+            commands::window::get_click_through_state,
+            commands::window::set_click_through,
+            commands::window::update_hit_test_mask,
             
             // 系统命令
             commands::system::get_system_info,
@@ -556,20 +1036,51 @@ fn main() {
             commands::system::open_url,
             commands::system::get_app_data_path,
             commands::system::get_app_log_path,
+            commands::system::set_data_directory,
+            commands::system::reset_data_directory,
+            commands::system::cancel_data_directory_migration,
+            commands::system::get_data_migration_status,
+            commands::storage::get_storage_breakdown,
+            commands::storage::check_disk_space,
+            commands::storage::run_storage_cleanup,
+            commands::storage::get_disk_guard_settings,
+            commands::storage::update_disk_guard_settings,
+            commands::privacy::get_data_retention_policy,
+            commands::privacy::update_data_retention_policy,
+            commands::privacy::preview_cleanup,
+            commands::privacy::cleanup_user_data,
+            commands::privacy::purge_all_personal_data,
+            commands::database::get_database_health,
             commands::system::set_auto_start,
             commands::system::is_auto_start_enabled,
             commands::system::copy_to_clipboard,
             commands::system::read_from_clipboard,
             commands::system::upload_logs,
+            commands::system::preview_anonymized_logs,
             commands::system::check_log_rotation,
             commands::system::get_log_stats,
             commands::system::clean_old_logs,
+            commands::system::debug_get_clock_status,
+            commands::system::debug_fast_forward_time,
+            commands::system::debug_reset_time,
+            
+            // 托盘状态命令
+            commands::tray_state::rebuild_tray_state,
+            
+            // 托盘扩展菜单命令
+            commands::tray_menu::register_tray_menu_item,
+            commands::tray_menu::unregister_tray_menu_item,
+            commands::tray_menu::clear_tray_menu_items,
+            commands::tray_menu::list_tray_menu_items,
             
             // 更新管理命令
             commands::update::init_update_manager,
             commands::update::check_for_updates,
             commands::update::download_update,
             commands::update::install_update,
+            commands::update::stage_update,
+            commands::update::get_staged_update,
+            commands::update::unstage_update,
             commands::update::install_update_with_tauri,
             commands::update::cancel_download,
             commands::update::rollback_to_version,
@@ -595,6 +1106,15 @@ fn main() {
             commands::adapter::load_adapter,
             commands::adapter::unload_adapter,
             commands::adapter::get_adapter_status,
+            commands::adapter::invalidate_adapter_cache,
+            commands::adapter::purge_adapter_cache,
+            commands::adapter::get_adapter_cache_settings,
+            commands::adapter::update_adapter_cache_settings,
+            commands::adapter::get_adapter_usage_stats,
+            commands::adapter::get_adapter_pool_metrics,
+            commands::adapter::get_adapter_pool_limits,
+            commands::adapter::update_adapter_pool_limits,
+            commands::adapter::cancel_adapter_execution,
             
             // 适配器命令 - 本地管理
             commands::adapter::get_installed_adapters,
@@ -624,6 +1144,8 @@ fn main() {
             commands::market::get_featured_products,
             commands::market::get_product_reviews,
             commands::market::download_market_product,
+            commands::market::get_license_status,
+            commands::market::activate_product_license,
             commands::market::check_product_updates,
             commands::market::get_market_categories,
             
@@ -645,6 +1167,12 @@ fn main() {
             commands::shortcuts::get_shortcut_statistics,
             commands::shortcuts::check_shortcut_conflict,
             commands::shortcuts::validate_shortcut_config,
+            commands::shortcuts::save_shortcut_macro,
+            commands::shortcuts::get_shortcut_macros,
+            commands::shortcuts::save_quick_reply,
+            commands::shortcuts::get_quick_replies,
+            commands::shortcuts::export_shortcut_preset,
+            commands::shortcuts::import_shortcut_preset,
             
             // 工作流 API 命令（与 Python 服务通信）
             commands::workflow_api::api_create_workflow,
@@ -656,6 +1184,15 @@ fn main() {
             commands::workflow_api::api_list_executions,
             commands::workflow_api::api_get_execution,
             commands::workflow_api::api_cancel_execution,
+            commands::workflow_api::get_debug_frame,
+            commands::workflow_api::set_debug_breakpoints,
+            commands::workflow_api::update_debug_variables,
+            commands::workflow_api::debug_continue,
+            commands::workflow_api::debug_step,
+            commands::workflow_api::get_execution_trace,
+            commands::workflow_api::replay_execution,
+            commands::workflow_api::step_execution,
+            commands::workflow_api::export_workflow,
             commands::workflow_api::api_publish_workflow,
             commands::workflow_api::api_archive_workflow,
             commands::workflow_api::api_clone_workflow,
@@ -663,6 +1200,12 @@ fn main() {
             commands::workflow_api::api_list_templates,
             commands::workflow_api::api_create_from_template,
             commands::workflow_api::api_health_check,
+            
+            // 工作流定时触发命令
+            commands::workflow_schedule::schedule_workflow,
+            commands::workflow_schedule::unschedule_workflow,
+            commands::workflow_schedule::list_scheduled_workflows,
+            commands::workflow_schedule::pause_schedule,
 
             // Skills API 命令（与 Python 服务通信）
             commands::skills_api::api_execute_skill,
@@ -723,6 +1266,8 @@ fn main() {
             commands::permission::get_permission_group,
             commands::permission::get_all_permission_groups,
             commands::permission::grant_permission_group,
+            commands::permission_consent::respond_permission_request,
+            commands::permission_consent::get_pending_permission_requests,
             
             // 内存管理命令
             commands::memory::get_memory_info,
@@ -760,6 +1305,10 @@ fn main() {
             commands::language::update_language_settings,
             commands::language::reset_language_settings,
             commands::language::get_supported_languages,
+            commands::language::set_session_language,
+            commands::language::get_session_language,
+            commands::language::detect_text_language,
+            commands::language::get_session_language_stats,
             
             // 区域适配命令
             commands::region::detect_system_region,
@@ -812,6 +1361,9 @@ fn main() {
             commands::performance::get_monitoring_status,
             commands::performance::generate_performance_report,
             
+            // 启动耗时分析命令
+            commands::startup::get_startup_profile,
+            
             // 日志系统命令
             commands::logging::init_logging_system,
             commands::logging::write_log_entry,
@@ -842,6 +1394,10 @@ fn main() {
             commands::local_llm::delete_local_llm_model,
             commands::local_llm::verify_local_llm_model,
             commands::local_llm::get_local_llm_model,
+            commands::local_llm::start_local_inference,
+            commands::local_llm::stop_local_inference,
+            commands::local_llm::get_local_inference_status,
+            commands::local_llm::local_chat_completion,
             
             // Prompt管理命令
             commands::prompt::get_prompts,
@@ -851,6 +1407,7 @@ fn main() {
             commands::prompt::apply_prompt,
             commands::prompt::get_prompt,
             commands::prompt::get_current_prompt,
+            commands::prompt::render_prompt_preview,
             
             // 角色模板管理命令
             commands::character_template::register_character_adapter,
@@ -867,6 +1424,224 @@ fn main() {
             commands::audio::is_recording,
             commands::audio::save_audio_to_file,
             commands::audio::cancel_recording,
+            commands::audio::list_audio_devices_detailed,
+            commands::audio::get_audio_routing,
+            commands::audio::update_audio_routing,
+            commands::audio::get_voice_input_settings,
+            commands::audio::update_voice_input_settings,
+            commands::audio::push_to_talk,
+            commands::audio::transcribe_audio,
+            
+            // 口型同步播放命令
+            commands::lipsync::play_audio_with_lipsync,
+            commands::lipsync::stop_lipsync,
+            commands::lipsync::get_lipsync_settings,
+            commands::lipsync::update_lipsync_settings,
+            
+            // Live2D 参数命令
+            commands::live2d_parameters::set_live2d_parameter,
+            commands::live2d_parameters::set_live2d_parameters,
+            commands::live2d_parameters::release_live2d_parameters,
+            commands::live2d_parameters::get_live2d_parameters,
+            commands::live2d_parameters::get_live2d_motion_settings,
+            commands::live2d_parameters::update_live2d_motion_settings,
+            
+            // 用户资料命令
+            commands::profiles::list_profiles,
+            commands::profiles::create_profile,
+            commands::profiles::switch_profile,
+            commands::profiles::set_admin_password,
+            commands::profiles::delete_profile,
+            
+            // 通知中心命令
+            commands::notifications::get_notifications,
+            commands::notifications::mark_notification_read,
+            commands::notifications::dismiss_notification,
+            commands::notifications::clear_notifications,
+            commands::notifications::invoke_notification_action,
+            
+            // 文档知识库命令
+            commands::knowledge_base::import_documents,
+            commands::knowledge_base::query_knowledge_base,
+            
+            // 适配器日志命令
+            commands::adapter_logs::get_adapter_logs,
+            commands::adapter_logs::tail_adapter_logs,
+            commands::adapter_logs::stop_adapter_log_tail,
+            commands::adapter_logs::get_adapter_log_retention,
+            commands::adapter_logs::set_adapter_log_retention,
+            
+            // 语音合成命令
+            commands::tts::synthesize_speech,
+            commands::tts::play_tts,
+            commands::tts::stop_tts,
+            commands::tts::get_tts_settings,
+            commands::tts::update_tts_settings,
+            
+            // 唤醒词命令
+            commands::hotword::get_hotword_settings,
+            commands::hotword::update_hotword_settings,
+            commands::hotword::start_hotword_listening,
+            commands::hotword::stop_hotword_listening,
+            commands::hotword::get_hotword_status,
+            commands::hotword::enroll_hotword_sample,
+            commands::hotword::reset_hotword_model,
+            
+            // 嵌入提供者命令
+            commands::embedding::get_embedding_settings,
+            commands::embedding::update_embedding_settings,
+            commands::embedding::test_embedding_provider,
+            commands::embedding::reembed_collection,
+            
+            // 混合检索命令
+            commands::search::hybrid_search,
+            
+            // 会话话题命令
+            commands::topics::get_conversation_topics,
+            commands::topics::refresh_conversation_topics,
+            
+            // 空闲时整理命令
+            commands::sleep_learning::get_sleep_learning_status,
+            commands::sleep_learning::update_sleep_learning_settings,
+            commands::sleep_learning::run_sleep_learning_now,
+            commands::sleep_learning::get_session_summary,
+            
+            // 长期记忆命令
+            commands::session_memory::get_session_summaries,
+            commands::session_memory::rebuild_memory_index,
+            
+            // 季节内容命令
+            commands::seasonal::get_seasonal_config,
+            commands::seasonal::update_seasonal_config,
+            commands::seasonal::set_seasonal_override,
+            commands::seasonal::preview_seasonal_content,
+            
+            // 成就命令
+            commands::achievements::get_achievements,
+            commands::achievements::register_adapter_achievement,
+            commands::achievements::report_adapter_achievement_progress,
+            
+            // 桌宠统计命令
+            commands::statistics::get_pet_statistics,
+            commands::statistics::record_pet_interaction,
+            
+            // 专注计时命令
+            commands::focus::start_focus_session,
+            commands::focus::pause_focus_session,
+            commands::focus::stop_focus_session,
+            commands::focus::get_focus_state,
+            commands::focus::get_focus_stats,
+            commands::focus::get_focus_settings,
+            commands::focus::update_focus_settings,
+            commands::clipboard::get_clipboard_history,
+            commands::clipboard::pin_clipboard_item,
+            commands::clipboard::copy_clipboard_item,
+            commands::clipboard::clear_clipboard_history,
+            commands::clipboard::get_clipboard_settings,
+            commands::clipboard::update_clipboard_settings,
+            commands::emotion::get_character_emotion,
+            commands::emotion::get_emotion_config,
+            commands::emotion::update_emotion_config,
+            commands::cache::get_cache_stats,
+            commands::adapter_dev::validate_dev_adapter,
+            commands::adapter_dev::start_adapter_dev,
+            commands::adapter_dev::reload_adapter_dev,
+            commands::adapter_dev::stop_adapter_dev,
+            commands::adapter_dev::get_dev_adapters,
+            commands::adapter_dev::get_dev_adapter,
+            
+            // 多设备同步命令
+            commands::device_sync::get_sync_settings,
+            commands::device_sync::update_sync_settings,
+            commands::device_sync::set_sync_passphrase,
+            commands::device_sync::sync_now,
+            commands::device_sync::get_sync_status,
+            
+            // 日历宿主接口命令
+            commands::calendar_host::host_calendar_list_events,
+            commands::calendar_host::host_calendar_create_event,
+            commands::calendar_host::host_calendar_delete_event,
+            commands::calendar_host::get_upcoming_calendar_events,
+            commands::calendar_host::get_calendar_settings,
+            commands::calendar_host::update_calendar_settings,
+            
+            // 自动化接口命令
+            commands::automation::get_automation_status,
+            commands::automation::update_automation_settings,
+            commands::automation::regenerate_automation_token,
+            commands::automation::get_automation_audit_log,
+            commands::automation::grant_automation_client,
+            commands::automation::revoke_automation_client,
+            
+            // 移动端转发命令
+            commands::mobile_relay::get_mobile_relay_settings,
+            commands::mobile_relay::update_mobile_relay_settings,
+            commands::mobile_relay::generate_mobile_relay_key,
+            commands::mobile_relay::send_mobile_relay_test,
+            commands::mobile_relay::relay_mobile_notification,
+            
+            // 数据库备份命令
+            commands::backup::get_database_backup_settings,
+            commands::backup::update_database_backup_settings,
+            commands::backup::list_database_backups,
+            commands::backup::create_database_backup,
+            commands::backup::verify_latest_backup,
+            commands::backup::get_backup_verifications,
+            commands::backup::export_profile,
+            commands::backup::import_profile,
+            
+            // 安全模式命令
+            commands::safe_mode::get_safe_mode_report,
+            commands::safe_mode::restart_in_safe_mode,
+            commands::safe_mode::exit_safe_mode,
+            
+            // 指标命令
+            commands::metrics::get_metrics_status,
+            commands::metrics::get_metrics_settings,
+            commands::metrics::update_metrics_settings,
+            commands::metrics::get_metrics_text,
+            
+            // 资源预算命令
+            commands::resource_budget::get_resource_budget_status,
+            commands::resource_budget::get_resource_breakdown,
+            commands::resource_budget::update_resource_budget,
+            
+            // 费用预估命令
+            commands::cost_preview::get_cost_preview_settings,
+            commands::cost_preview::update_cost_preview_settings,
+            commands::cost_preview::set_cost_preview_skipped,
+            
+            // LLM 中间件命令
+            commands::llm_middleware::get_llm_middleware_settings,
+            commands::llm_middleware::update_llm_middleware_settings,
+            commands::llm_middleware::get_llm_middleware_metrics,
+            commands::llm_middleware::reset_llm_middleware_metrics,
+            
+            // 会话参数命令
+            commands::session_parameters::get_session_parameters,
+            commands::session_parameters::update_session_parameters,
+            
+            // 事件日志命令
+            commands::event_journal::get_event_stream,
+            commands::event_journal::replay_event_journal,
+            
+            // 事件订阅命令
+            commands::event_subscription::subscribe_events,
+            commands::event_subscription::unsubscribe_events,
+            commands::event_subscription::list_event_subscriptions,
+            
+            // 系统健康命令
+            commands::error_monitoring::get_system_health,
+            
+            // 崩溃报告命令
+            commands::crash_reports::list_crash_reports,
+            commands::crash_reports::submit_crash_report,
+            
+            // 截图命令
+            commands::capture::capture_screen,
+            commands::capture::capture_window,
+            commands::capture::capture_region,
+            commands::capture::clear_chat_attachments,
             
             // 认证命令
             commands::auth::save_auth_token,
@@ -878,12 +1653,57 @@ fn main() {
             commands::auth::get_device_name,
             commands::auth::get_device_id,
             commands::auth::get_user_agent,
-        ])
+            commands::auth::login_with_browser,
+            commands::auth::logout,
+            
+            // 无障碍命令
+            commands::accessibility::get_accessibility_settings,
+            commands::accessibility::update_accessibility_settings,
+            commands::accessibility::set_reduced_motion,
+            commands::accessibility::set_high_contrast,
+            commands::accessibility::announce_to_screen_reader,
+            
+            // 统一动作命令
+            commands::actions::list_all_actions,
+            commands::actions::invoke_action,
+            commands::actions::bind_action_shortcut,
+            
+            // 快速对话弹窗命令
+            commands::quick_chat::submit_quick_chat,
+            commands::quick_chat::close_quick_chat,
+            
+            // 对话气泡命令
+            commands::speech_queue::enqueue_utterance,
+            commands::speech_queue::dismiss_utterance,
+            commands::speech_queue::get_speech_queue,
+            commands::speech_queue::get_speech_queue_settings,
+            commands::speech_queue::update_speech_queue_settings,
+            
+            // 网络设置命令
+            commands::network::get_proxy_settings,
+            commands::network::update_proxy_settings,
+            commands::network::detect_system_proxy,
+            commands::network::test_proxy_connection,
+            commands::network::import_trusted_certificate,
+            commands::network::list_trusted_certificates,
+            commands::network::remove_trusted_certificate,
+            commands::network::get_certificate_pins,
+            commands::network::set_certificate_pins,
+            // 后端连接命令
+            commands::backend::get_backend_profiles,
+            commands::backend::update_backend_profiles,
+            commands::backend::set_backend_profile_credential,
+            commands::backend::get_backend_endpoints,
+            commands::backend::test_backend_connection,
+            commands::backend::get_backend_capabilities,
+            commands::backend::refresh_backend_capabilities,
+        ]))
         .manage(commands::shortcuts::ShortcutRegistry::new())
         .manage(commands::memory::MemoryManagerState::new())
         .manage(commands::audio::AudioState::default())
         .manage(std::sync::Arc::new(std::sync::Mutex::new(commands::rendering::RenderingState::default())))
         .manage(commands::region::RegionState::default())
+        .manage(commands::accessibility::AccessibilityState::default())
         .manage(commands::update::UpdateManagerState::new())
         .manage({
             let app_data_dir = std::env::var("APPDATA").unwrap_or_else(|_| {
@@ -906,6 +1726,7 @@ fn main() {
                 }
                 tauri::RunEvent::Exit => {
                     info!("应用正常退出");
+                    utils::safe_mode::mark_clean_exit();
                 }
                 _ => {}
             });
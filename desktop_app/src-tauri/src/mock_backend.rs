@@ -0,0 +1,574 @@
+//! 进程内模拟后端
+//!
+//! 仅在启用 `mock-backend` 特性时编译。启动后在本地随机端口提供工作流、技能和
+//! 市场 API 的模拟实现（内存数据，重启后重置），并将其设置为当前激活的后端端点，
+//! 这样无需 Python 服务即可运行完整的前端：
+//!
+//! ```bash
+//! cargo run --features mock-backend
+//! ```
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::commands::market::{
+    MarketAuthor, MarketCategory, MarketProduct, MarketProductType, ProductRequirements,
+    ProductReview, ProductVersion,
+};
+use crate::commands::PaginatedResponse;
+use crate::config::backend_profiles::{self, BackendAuthMode, BackendProfile};
+use crate::http::capabilities;
+use crate::http::workflow_client::{
+    CreateWorkflowRequest, ExecuteWorkflowRequest, UpdateWorkflowRequest, WorkflowExecutionResponse,
+    WorkflowResponse,
+};
+
+/// 模拟后端使用的端点配置ID
+pub const MOCK_PROFILE_ID: &str = "mock-backend";
+
+type ApiResponse<T> = Result<Json<T>, (StatusCode, Json<Value>)>;
+
+/// 模拟后端的内存数据
+#[derive(Default)]
+struct MockData {
+    workflows: HashMap<String, WorkflowResponse>,
+    executions: HashMap<String, WorkflowExecutionResponse>,
+}
+
+type SharedData = Arc<Mutex<MockData>>;
+
+fn not_found(what: &str, id: &str) -> (StatusCode, Json<Value>) {
+    (StatusCode::NOT_FOUND, Json(json!({ "detail": format!("{} {} 不存在", what, id) })))
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+// ================================
+// 示例数据
+// ================================
+
+fn sample_workflow(id: &str, name: &str, category: &str, status: &str) -> WorkflowResponse {
+    WorkflowResponse {
+        id: id.to_string(),
+        user_id: "mock-user".to_string(),
+        name: name.to_string(),
+        slug: id.to_string(),
+        description: Some(format!("模拟工作流：{}", name)),
+        category: Some(category.to_string()),
+        tags: Some(vec!["mock".to_string(), category.to_string()]),
+        workflow_status: status.to_string(),
+        trigger_type: "manual".to_string(),
+        trigger_config: None,
+        definition: json!({
+            "nodes": [
+                { "id": "start", "type": "start" },
+                { "id": "llm", "type": "llm", "config": { "prompt": "你好" } },
+                { "id": "end", "type": "end" }
+            ],
+            "edges": [
+                { "source": "start", "target": "llm" },
+                { "source": "llm", "target": "end" }
+            ]
+        }),
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        updated_at: "2024-01-01T00:00:00Z".to_string(),
+    }
+}
+
+fn sample_templates() -> Vec<WorkflowResponse> {
+    vec![
+        sample_workflow("tpl-daily-summary", "每日总结", "productivity", "published"),
+        sample_workflow("tpl-translate", "翻译助手", "language", "published"),
+    ]
+}
+
+fn sample_author() -> MarketAuthor {
+    MarketAuthor {
+        id: "zishu-team".to_string(),
+        name: "Zishu Team".to_string(),
+        avatar_url: None,
+        verified: true,
+    }
+}
+
+fn sample_product(id: &str, name: &str, product_type: MarketProductType, category: &str) -> MarketProduct {
+    let download_url = format!("https://example.invalid/mock/{}.zip", id);
+    MarketProduct {
+        id: id.to_string(),
+        product_type,
+        name: id.to_string(),
+        display_name: name.to_string(),
+        description: format!("{}（模拟数据）", name),
+        author: sample_author(),
+        version: "1.0.0".to_string(),
+        versions: vec![ProductVersion {
+            version: "1.0.0".to_string(),
+            released_at: "2024-01-01T00:00:00Z".to_string(),
+            changelog: Some("首个版本".to_string()),
+            download_url: download_url.clone(),
+            file_size: 1024,
+            checksum: None,
+        }],
+        download_url,
+        icon_url: None,
+        screenshots: vec![],
+        tags: vec!["mock".to_string()],
+        category: category.to_string(),
+        rating: 4.5,
+        rating_count: 12,
+        download_count: 345,
+        file_size: 1024,
+        license: "MIT".to_string(),
+        homepage_url: None,
+        documentation_url: None,
+        repository_url: None,
+        is_featured: true,
+        is_verified: true,
+        is_paid: false,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        updated_at: "2024-01-01T00:00:00Z".to_string(),
+        dependencies: vec![],
+        requirements: ProductRequirements {
+            operating_systems: vec!["windows".to_string(), "macos".to_string(), "linux".to_string()],
+            min_memory_mb: None,
+            min_disk_space_mb: None,
+            other: None,
+        },
+    }
+}
+
+fn sample_products() -> Vec<MarketProduct> {
+    vec![
+        sample_product("mock-weather", "天气查询适配器", MarketProductType::Adapter, "tools"),
+        sample_product("mock-sakura", "樱花主题", MarketProductType::Theme, "themes"),
+        sample_product("mock-summary", "每日总结工作流", MarketProductType::Workflow, "productivity"),
+    ]
+}
+
+// ================================
+// 通用接口
+// ================================
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "healthy", "version": "1.0.0-mock" }))
+}
+
+async fn handshake() -> Json<Value> {
+    Json(json!({
+        "api_version": "v1",
+        "backend_version": "1.0.0-mock",
+        "features": capabilities::FEATURES.iter().map(|f| capabilities::feature_name(*f)).collect::<Vec<_>>(),
+    }))
+}
+
+// ================================
+// 工作流接口
+// ================================
+
+async fn list_workflows(State(data): State<SharedData>) -> Json<Vec<WorkflowResponse>> {
+    let mut workflows: Vec<_> = data.lock().workflows.values().cloned().collect();
+    workflows.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Json(workflows)
+}
+
+async fn create_workflow(
+    State(data): State<SharedData>,
+    Json(request): Json<CreateWorkflowRequest>,
+) -> Json<WorkflowResponse> {
+    let timestamp = now();
+    let workflow = WorkflowResponse {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: "mock-user".to_string(),
+        name: request.name,
+        slug: request.slug,
+        description: request.description,
+        category: request.category,
+        tags: request.tags,
+        workflow_status: "draft".to_string(),
+        trigger_type: request.trigger_type,
+        trigger_config: request.trigger_config,
+        definition: request.definition,
+        created_at: timestamp.clone(),
+        updated_at: timestamp,
+    };
+    data.lock().workflows.insert(workflow.id.clone(), workflow.clone());
+    Json(workflow)
+}
+
+async fn get_workflow(State(data): State<SharedData>, Path(id): Path<String>) -> ApiResponse<WorkflowResponse> {
+    data.lock().workflows.get(&id).cloned().map(Json).ok_or_else(|| not_found("工作流", &id))
+}
+
+async fn update_workflow(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateWorkflowRequest>,
+) -> ApiResponse<WorkflowResponse> {
+    let mut data = data.lock();
+    let workflow = data.workflows.get_mut(&id).ok_or_else(|| not_found("工作流", &id))?;
+    if let Some(name) = request.name {
+        workflow.name = name;
+    }
+    if request.description.is_some() {
+        workflow.description = request.description;
+    }
+    if request.category.is_some() {
+        workflow.category = request.category;
+    }
+    if request.tags.is_some() {
+        workflow.tags = request.tags;
+    }
+    if let Some(definition) = request.definition {
+        workflow.definition = definition;
+    }
+    if let Some(trigger_type) = request.trigger_type {
+        workflow.trigger_type = trigger_type;
+    }
+    if request.trigger_config.is_some() {
+        workflow.trigger_config = request.trigger_config;
+    }
+    workflow.updated_at = now();
+    Ok(Json(workflow.clone()))
+}
+
+async fn delete_workflow(State(data): State<SharedData>, Path(id): Path<String>) -> ApiResponse<Value> {
+    data.lock().workflows.remove(&id).map(|_| Json(Value::Null)).ok_or_else(|| not_found("工作流", &id))
+}
+
+fn set_workflow_status(data: &SharedData, id: &str, status: &str) -> ApiResponse<WorkflowResponse> {
+    let mut data = data.lock();
+    let workflow = data.workflows.get_mut(id).ok_or_else(|| not_found("工作流", id))?;
+    workflow.workflow_status = status.to_string();
+    workflow.updated_at = now();
+    Ok(Json(workflow.clone()))
+}
+
+async fn publish_workflow(State(data): State<SharedData>, Path(id): Path<String>) -> ApiResponse<WorkflowResponse> {
+    set_workflow_status(&data, &id, "published")
+}
+
+async fn archive_workflow(State(data): State<SharedData>, Path(id): Path<String>) -> ApiResponse<WorkflowResponse> {
+    set_workflow_status(&data, &id, "archived")
+}
+
+async fn clone_workflow(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+    Json(body): Json<Value>,
+) -> ApiResponse<WorkflowResponse> {
+    let mut data = data.lock();
+    let mut workflow = data.workflows.get(&id).cloned().ok_or_else(|| not_found("工作流", &id))?;
+    workflow.id = uuid::Uuid::new_v4().to_string();
+    workflow.name = body
+        .get("new_name")
+        .or_else(|| body.get("name"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{} (副本)", workflow.name));
+    workflow.slug = workflow.id.clone();
+    workflow.workflow_status = "draft".to_string();
+    workflow.created_at = now();
+    workflow.updated_at = workflow.created_at.clone();
+    data.workflows.insert(workflow.id.clone(), workflow.clone());
+    Ok(Json(workflow))
+}
+
+async fn execute_workflow(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+    Json(request): Json<ExecuteWorkflowRequest>,
+) -> ApiResponse<WorkflowExecutionResponse> {
+    let mut data = data.lock();
+    if !data.workflows.contains_key(&id) {
+        return Err(not_found("工作流", &id));
+    }
+    let timestamp = now();
+    let execution = WorkflowExecutionResponse {
+        id: uuid::Uuid::new_v4().to_string(),
+        workflow_id: id,
+        user_id: "mock-user".to_string(),
+        execution_mode: request.execution_mode,
+        execution_status: "completed".to_string(),
+        output_data: Some(HashMap::from([(
+            "echo".to_string(),
+            json!(request.input_data.clone().unwrap_or_default()),
+        )])),
+        input_data: request.input_data,
+        error_code: None,
+        error_message: None,
+        failed_node_id: None,
+        started_at: Some(timestamp.clone()),
+        completed_at: Some(timestamp.clone()),
+        created_at: timestamp,
+    };
+    data.executions.insert(execution.id.clone(), execution.clone());
+    Ok(Json(execution))
+}
+
+async fn list_executions(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+) -> Json<Vec<WorkflowExecutionResponse>> {
+    Json(data.lock().executions.values().filter(|e| e.workflow_id == id).cloned().collect())
+}
+
+async fn get_execution(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+) -> ApiResponse<WorkflowExecutionResponse> {
+    data.lock().executions.get(&id).cloned().map(Json).ok_or_else(|| not_found("执行记录", &id))
+}
+
+async fn cancel_execution(
+    State(data): State<SharedData>,
+    Path(id): Path<String>,
+) -> ApiResponse<WorkflowExecutionResponse> {
+    let mut data = data.lock();
+    let execution = data.executions.get_mut(&id).ok_or_else(|| not_found("执行记录", &id))?;
+    if execution.execution_status != "completed" {
+        execution.execution_status = "cancelled".to_string();
+        execution.completed_at = Some(now());
+    }
+    Ok(Json(execution.clone()))
+}
+
+async fn search_workflows(
+    State(data): State<SharedData>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<WorkflowResponse>> {
+    let keyword = params.get("keyword").map(|k| k.to_lowercase());
+    let results = data
+        .lock()
+        .workflows
+        .values()
+        .filter(|w| keyword.as_ref().map_or(true, |k| w.name.to_lowercase().contains(k)))
+        .filter(|w| params.get("status").map_or(true, |s| &w.workflow_status == s))
+        .filter(|w| params.get("category").map_or(true, |c| w.category.as_ref() == Some(c)))
+        .cloned()
+        .collect();
+    Json(results)
+}
+
+async fn list_templates() -> Json<Vec<WorkflowResponse>> {
+    Json(sample_templates())
+}
+
+async fn create_from_template(
+    State(data): State<SharedData>,
+    Path(template_id): Path<String>,
+    Json(body): Json<Value>,
+) -> ApiResponse<WorkflowResponse> {
+    let mut workflow = sample_templates()
+        .into_iter()
+        .find(|t| t.id == template_id)
+        .ok_or_else(|| not_found("模板", &template_id))?;
+    workflow.id = uuid::Uuid::new_v4().to_string();
+    workflow.slug = workflow.id.clone();
+    if let Some(name) = body.get("name").and_then(Value::as_str) {
+        workflow.name = name.to_string();
+    }
+    workflow.workflow_status = "draft".to_string();
+    workflow.created_at = now();
+    workflow.updated_at = workflow.created_at.clone();
+    data.lock().workflows.insert(workflow.id.clone(), workflow.clone());
+    Ok(Json(workflow))
+}
+
+// ================================
+// 技能接口
+// ================================
+
+async fn execute_skill(Path(package_id): Path<String>, Json(payload): Json<Value>) -> Json<Value> {
+    Json(json!({
+        "success": true,
+        "result": { "skill": package_id, "echo": payload },
+        "error": null,
+        "execution_time": 0.01,
+    }))
+}
+
+// ================================
+// 市场接口
+// ================================
+
+async fn search_products(Query(params): Query<HashMap<String, String>>) -> Json<PaginatedResponse<MarketProduct>> {
+    let query = params.get("q").map(|q| q.to_lowercase()).unwrap_or_default();
+    let items: Vec<_> = sample_products()
+        .into_iter()
+        .filter(|p| query.is_empty() || p.display_name.to_lowercase().contains(&query) || p.name.contains(&query))
+        .filter(|p| {
+            params
+                .get("type")
+                .map_or(true, |t| format!("{:?}", p.product_type).to_lowercase() == *t)
+        })
+        .collect();
+    let total = items.len() as u32;
+    Json(PaginatedResponse::new(items, total, 1, total.max(1)))
+}
+
+async fn get_product(Path(id): Path<String>) -> ApiResponse<MarketProduct> {
+    sample_products().into_iter().find(|p| p.id == id).map(Json).ok_or_else(|| not_found("产品", &id))
+}
+
+async fn featured_products() -> Json<Vec<MarketProduct>> {
+    Json(sample_products())
+}
+
+async fn product_reviews(Path(_id): Path<String>) -> Json<PaginatedResponse<ProductReview>> {
+    let review = ProductReview {
+        id: "review-1".to_string(),
+        user: sample_author(),
+        rating: 5,
+        content: "非常好用（模拟评论）".to_string(),
+        likes: 3,
+        created_at: "2024-01-02T00:00:00Z".to_string(),
+        updated_at: "2024-01-02T00:00:00Z".to_string(),
+    };
+    Json(PaginatedResponse::new(vec![review], 1, 1, 20))
+}
+
+async fn categories() -> Json<Vec<MarketCategory>> {
+    let category = |id: &str, name: &str| MarketCategory {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: None,
+        product_count: 1,
+        icon: None,
+    };
+    Json(vec![
+        category("tools", "工具"),
+        category("themes", "主题"),
+        category("productivity", "效率"),
+    ])
+}
+
+fn router() -> Router {
+    let data = SharedData::default();
+    Router::new()
+        .route("/", get(health))
+        .route("/health", get(health))
+        .route("/api/handshake", post(handshake))
+        // 工作流
+        .route("/api/workflows", get(list_workflows).post(create_workflow))
+        .route(
+            "/api/workflows/:id",
+            get(get_workflow).put(update_workflow).delete(delete_workflow),
+        )
+        .route("/api/workflows/:id/execute", post(execute_workflow))
+        .route("/api/workflows/:id/executions", get(list_executions))
+        .route("/api/workflows/:id/publish", post(publish_workflow))
+        .route("/api/workflows/:id/archive", post(archive_workflow))
+        .route("/api/workflows/:id/clone", post(clone_workflow))
+        .route("/api/workflows/executions/:execution_id", get(get_execution))
+        .route("/api/workflows/executions/:execution_id/cancel", post(cancel_execution))
+        .route("/api/workflows/search/query", get(search_workflows))
+        .route("/api/workflows/templates/list", get(list_templates))
+        .route("/api/workflows/templates/:template_id/create", post(create_from_template))
+        // 技能
+        .route("/api/v1/skills/:package_id/execute", post(execute_skill))
+        // 市场
+        .route("/api/marketplace/search", get(search_products))
+        .route("/api/marketplace/featured", get(featured_products))
+        .route("/api/marketplace/categories", get(categories))
+        .route("/api/marketplace/products/:id", get(get_product))
+        .route("/api/marketplace/products/:id/reviews", get(product_reviews))
+        .with_state(data)
+}
+
+/// 启动模拟后端并将其设为当前激活的端点（不写入磁盘）
+pub fn start() -> Result<SocketAddr, String> {
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| format!("绑定模拟后端端口失败: {}", e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let server = axum::Server::from_tcp(listener)
+        .map_err(|e| format!("启动模拟后端失败: {}", e))?
+        .serve(router().into_make_service());
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = server.await {
+            error!("模拟后端异常退出: {}", e);
+        }
+    });
+
+    let mut settings = backend_profiles::current_settings();
+    settings.profiles.retain(|p| p.id != MOCK_PROFILE_ID);
+    settings.profiles.push(BackendProfile {
+        id: MOCK_PROFILE_ID.to_string(),
+        name: "Mock Backend".to_string(),
+        base_url: format!("http://{}", addr),
+        auth_mode: BackendAuthMode::None,
+    });
+    settings.active_profile = Some(MOCK_PROFILE_ID.to_string());
+    settings.feature_overrides.clear();
+    backend_profiles::set_settings(settings)?;
+
+    info!("模拟后端已启动: http://{}", addr);
+    Ok(addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::backend_profiles::BackendProfileSettings;
+
+    /// 测试结束时恢复全局端点配置
+    struct RestoreProfiles(BackendProfileSettings);
+
+    impl Drop for RestoreProfiles {
+        fn drop(&mut self) {
+            let _ = backend_profiles::set_settings(std::mem::take(&mut self.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_advertises_all_features() {
+        let Json(body) = handshake().await;
+        let features = body["features"].as_array().unwrap();
+        for name in ["core", "market", "workflow", "skills"] {
+            assert!(features.iter().any(|f| f == name), "缺少功能 {}", name);
+        }
+    }
+
+    #[test]
+    fn test_sample_data_round_trips() {
+        for product in sample_products() {
+            let value = serde_json::to_value(&product).unwrap();
+            assert!(serde_json::from_value::<MarketProduct>(value).is_ok());
+        }
+        assert!(sample_templates().iter().all(|t| t.workflow_status == "published"));
+    }
+
+    #[tokio::test]
+    async fn test_workflow_crud() {
+        let _restore = RestoreProfiles(backend_profiles::current_settings());
+        let addr = start().unwrap();
+        let client = crate::http::workflow_client::WorkflowApiClient::new(format!("http://{}", addr)).unwrap();
+
+        let created = client
+            .create_workflow(CreateWorkflowRequest {
+                name: "测试".to_string(),
+                slug: "test".to_string(),
+                description: None,
+                category: None,
+                tags: None,
+                definition: json!({}),
+                trigger_type: "manual".to_string(),
+                trigger_config: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(client.get_workflow(&created.id).await.unwrap().name, "测试");
+        assert_eq!(client.list_workflows(0, 10).await.unwrap().len(), 1);
+    }
+}
@@ -396,6 +396,7 @@ mod tests {
             message_count: 5,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         app_state.chat.set_current_session(session);
         
@@ -423,6 +424,7 @@ mod tests {
             message_count: 10,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         app_state.chat.set_current_session(session);
         
@@ -495,6 +497,7 @@ mod tests {
                 message_count: 1,
                 model_id: None,
                 character_id: None,
+                incognito: false,
             };
             app_state.chat.set_current_session(session);
         }
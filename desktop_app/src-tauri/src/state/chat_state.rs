@@ -4,7 +4,7 @@
 
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 /// 聊天会话状态
@@ -22,6 +22,9 @@ pub struct ChatSession {
     pub model_id: Option<String>,
     /// 角色 ID
     pub character_id: Option<String>,
+    /// 无痕会话：消息只保存在内存中，不写入数据库与日志，也不进入托盘最近对话
+    #[serde(default)]
+    pub incognito: bool,
 }
 
 /// 当前模型配置
@@ -61,6 +64,10 @@ pub struct ChatState {
     model_config: Arc<RwLock<ModelConfig>>,
     /// Python API 基础 URL
     api_base_url: Arc<RwLock<String>>,
+    /// 发件箱中未送达的消息：消息 ID -> 会话 ID
+    outbox: Arc<RwLock<HashMap<String, Option<String>>>>,
+    /// 正在生成摘要的会话
+    summarizing: Arc<RwLock<HashSet<String>>>,
 }
 
 impl ChatState {
@@ -71,6 +78,8 @@ impl ChatState {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             model_config: Arc::new(RwLock::new(ModelConfig::default())),
             api_base_url: Arc::new(RwLock::new("http://127.0.0.1:8000".to_string())),
+            outbox: Arc::new(RwLock::new(HashMap::new())),
+            summarizing: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
@@ -129,6 +138,11 @@ impl ChatState {
         *self.current_session.write() = None;
     }
 
+    /// 是否为无痕会话
+    pub fn is_incognito(&self, session_id: &str) -> bool {
+        self.sessions.read().get(session_id).is_some_and(|session| session.incognito)
+    }
+
     /// 更新会话活动时间
     pub fn update_session_activity(&self, session_id: &str) {
         if let Some(session) = self.sessions.write().get_mut(session_id) {
@@ -164,6 +178,39 @@ impl ChatState {
     pub fn set_api_base_url(&self, url: String) {
         *self.api_base_url.write() = url;
     }
+
+    /// 记录进入发件箱的消息
+    pub fn track_outbox(&self, message_id: &str, session_id: Option<String>) {
+        self.outbox.write().insert(message_id.to_string(), session_id);
+    }
+
+    /// 消息已送达或被取消
+    pub fn untrack_outbox(&self, message_id: &str) -> bool {
+        self.outbox.write().remove(message_id).is_some()
+    }
+
+    /// 未送达的消息 ID（指定会话时只返回该会话的消息）
+    pub fn pending_outbox(&self, session_id: Option<&str>) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .outbox
+            .read()
+            .iter()
+            .filter(|(_, session)| session_id.is_none() || session.as_deref() == session_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// 开始为会话生成摘要；已有摘要任务在进行时返回 false
+    pub fn begin_summary(&self, session_id: &str) -> bool {
+        self.summarizing.write().insert(session_id.to_string())
+    }
+
+    /// 摘要任务结束
+    pub fn finish_summary(&self, session_id: &str) {
+        self.summarizing.write().remove(session_id);
+    }
 }
 
 impl Default for ChatState {
@@ -194,6 +241,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session.clone());
@@ -242,6 +290,7 @@ mod tests {
             message_count: 5,
             model_id: Some("gpt-4".to_string()),
             character_id: Some("alice".to_string()),
+            incognito: false,
         };
         
         let session2 = ChatSession {
@@ -251,6 +300,7 @@ mod tests {
             message_count: 10,
             model_id: Some("claude".to_string()),
             character_id: Some("bob".to_string()),
+            incognito: false,
         };
         
         // 设置会话
@@ -284,6 +334,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session);
@@ -316,6 +367,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session);
@@ -344,6 +396,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         let other_session = ChatSession {
@@ -353,6 +406,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(current_session.clone());
@@ -454,6 +508,7 @@ mod tests {
             message_count: 15,
             model_id: Some("gpt-4".to_string()),
             character_id: Some("alice".to_string()),
+            incognito: false,
         };
         
         // 测试序列化
@@ -512,6 +567,7 @@ mod tests {
                     message_count: i,
                     model_id: Some(format!("model_{}", i)),
                     character_id: Some(format!("char_{}", i)),
+                    incognito: false,
                 };
                 
                 // 设置会话
@@ -587,6 +643,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session.clone());
@@ -624,6 +681,7 @@ mod tests {
             message_count: 0,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session);
@@ -638,6 +696,54 @@ mod tests {
         assert_eq!(state.get_all_sessions().len(), 1);
     }
 
+    #[test]
+    fn test_outbox_tracking() {
+        let state = ChatState::new();
+        state.track_outbox("m2", Some("s1".to_string()));
+        state.track_outbox("m1", Some("s1".to_string()));
+        state.track_outbox("m3", None);
+
+        assert_eq!(state.pending_outbox(None), vec!["m1", "m2", "m3"]);
+        assert_eq!(state.pending_outbox(Some("s1")), vec!["m1", "m2"]);
+        assert!(state.untrack_outbox("m1"));
+        assert!(!state.untrack_outbox("m1"));
+        assert_eq!(state.pending_outbox(Some("s1")), vec!["m2"]);
+    }
+
+    #[test]
+    fn test_summary_guard() {
+        let state = ChatState::new();
+        assert!(state.begin_summary("s1"));
+        assert!(!state.begin_summary("s1"));
+        assert!(state.begin_summary("s2"));
+        state.finish_summary("s1");
+        assert!(state.begin_summary("s1"));
+    }
+
+    #[test]
+    fn test_incognito_flag() {
+        let state = ChatState::new();
+        // 旧数据没有 incognito 字段时视为普通会话
+        let session: ChatSession = serde_json::from_str(
+            r#"{"session_id":"s1","created_at":0,"last_activity":0,"message_count":0,"model_id":null,"character_id":null}"#,
+        ).unwrap();
+        assert!(!session.incognito);
+        state.set_current_session(session);
+        state.set_current_session(ChatSession {
+            session_id: "s2".to_string(),
+            created_at: 0,
+            last_activity: 0,
+            message_count: 0,
+            model_id: None,
+            character_id: None,
+            incognito: true,
+        });
+
+        assert!(!state.is_incognito("s1"));
+        assert!(state.is_incognito("s2"));
+        assert!(!state.is_incognito("missing"));
+    }
+
     #[test]
     fn test_large_message_count() {
         let state = ChatState::new();
@@ -649,6 +755,7 @@ mod tests {
             message_count: u32::MAX - 1, // 接近最大值
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         state.set_current_session(session);
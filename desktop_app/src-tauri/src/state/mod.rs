@@ -10,7 +10,7 @@ pub mod app_state;
 pub mod character_state;
 pub mod settings;
 
-pub use chat_state::{ChatState, ModelConfig};
+pub use chat_state::{ChatSession, ChatState, ModelConfig};
 pub use tray_state::{
     TrayState, TrayIconState,
 };
@@ -103,6 +103,7 @@ mod tests {
             message_count: 5,
             model_id: Some("gpt-4".to_string()),
             character_id: Some("shizuku".to_string()),
+            incognito: false,
         };
         
         let serialized = serde_json::to_string(&session);
@@ -289,6 +290,7 @@ mod tests {
             message_count: 1,
             model_id: Some("gpt-4".to_string()),
             character_id: None,
+            incognito: false,
         };
         
         chat_state.set_current_session(session.clone());
@@ -323,6 +325,7 @@ mod tests {
             message_count: 5,
             model_id: None,
             character_id: None,
+            incognito: false,
         };
         
         chat_state.set_current_session(session);
@@ -366,6 +369,7 @@ mod tests {
                 message_count: i % 100,
                 model_id: Some(format!("model_{}", i % 10)),
                 character_id: None,
+                incognito: false,
             };
             
             chat_state.set_current_session(session);
@@ -170,6 +170,9 @@ pub struct ProxySettings {
     pub password: Option<String>,
     /// 排除列表
     pub exclude_list: Vec<String>,
+    /// 使用系统代理（HTTP_PROXY/HTTPS_PROXY/NO_PROXY）
+    #[serde(default)]
+    pub use_system_proxy: bool,
 }
 
 /// 备份设置
@@ -875,6 +878,7 @@ impl Default for ProxySettings {
             username: None,
             password: None,
             exclude_list: Vec::new(),
+            use_system_proxy: false,
         }
     }
 }
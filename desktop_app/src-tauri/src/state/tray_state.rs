@@ -5,12 +5,21 @@
 //! - 最近对话记录
 //! - 系统资源监控数据
 //! - 托盘通知队列
+//!
+//! 最近对话与通知会保存到数据库（见 `commands::tray_state`），启动时与实际的对话数据对账后恢复。
 
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// 最近对话保留条数
+const MAX_RECENT_CONVERSATIONS: usize = 10;
+/// 通知保留条数
+const MAX_NOTIFICATIONS: usize = 50;
+
 /// 托盘图标状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -105,6 +114,108 @@ pub enum NotificationType {
     Message,
 }
 
+/// 需要持久化的托盘状态（图标状态与系统资源每次启动重新获取，不保存）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraySnapshot {
+    #[serde(default)]
+    pub recent_conversations: Vec<RecentConversation>,
+    #[serde(default)]
+    pub notifications: Vec<TrayNotification>,
+}
+
+/// 数据库中对话的现状
+#[derive(Debug, Clone)]
+pub struct ConversationRecord {
+    pub id: String,
+    pub title: String,
+    /// 最后一条消息内容（未删除）
+    pub last_message: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 对账结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrayReconcileReport {
+    /// 保留的最近对话数
+    pub kept: usize,
+    /// 因对话已不存在而移除的对话 ID
+    pub dropped: Vec<String>,
+    /// 重建时从对话数据补充的对话数
+    pub added: usize,
+    /// 重复而被移除的通知数
+    pub duplicate_notifications: usize,
+}
+
+/// 对话摘要（最后一条消息）的最大字符数
+const PREVIEW_CHARS: usize = 80;
+
+fn preview(message: &str) -> String {
+    message.chars().take(PREVIEW_CHARS).collect()
+}
+
+/// 将保存的托盘状态与数据库中的对话对账
+///
+/// `records` 按更新时间倒序。已不存在的对话被移除，标题与最后消息按数据库更新，未读数保留；
+/// `fill` 为 true 时（重建）用最近的对话补足列表。
+pub fn reconcile_snapshot(
+    snapshot: TraySnapshot,
+    records: &[ConversationRecord],
+    fill: bool,
+) -> (TraySnapshot, TrayReconcileReport) {
+    let mut report = TrayReconcileReport::default();
+    let by_id: HashMap<&str, &ConversationRecord> = records.iter().map(|r| (r.id.as_str(), r)).collect();
+
+    let mut conversations: Vec<RecentConversation> = Vec::new();
+    for mut conversation in snapshot.recent_conversations {
+        if conversations.iter().any(|c| c.id == conversation.id) {
+            continue;
+        }
+        let Some(record) = by_id.get(conversation.id.as_str()) else {
+            report.dropped.push(conversation.id);
+            continue;
+        };
+        conversation.title = record.title.clone();
+        if record.updated_at >= conversation.updated_at {
+            conversation.updated_at = record.updated_at;
+            if let Some(message) = &record.last_message {
+                conversation.last_message = preview(message);
+            }
+        }
+        conversations.push(conversation);
+    }
+    report.kept = conversations.len();
+
+    if fill {
+        for record in records {
+            if conversations.len() >= MAX_RECENT_CONVERSATIONS {
+                break;
+            }
+            if conversations.iter().any(|c| c.id == record.id) {
+                continue;
+            }
+            conversations.push(RecentConversation {
+                id: record.id.clone(),
+                title: record.title.clone(),
+                last_message: record.last_message.as_deref().map(preview).unwrap_or_default(),
+                updated_at: record.updated_at,
+                unread_count: 0,
+            });
+            report.added += 1;
+        }
+    }
+    conversations.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    conversations.truncate(MAX_RECENT_CONVERSATIONS);
+
+    let mut seen = HashSet::new();
+    let mut notifications = snapshot.notifications;
+    let before = notifications.len();
+    notifications.retain(|n| seen.insert(n.id.clone()));
+    report.duplicate_notifications = before - notifications.len();
+    notifications.truncate(MAX_NOTIFICATIONS);
+
+    (TraySnapshot { recent_conversations: conversations, notifications }, report)
+}
+
 /// 托盘状态
 pub struct TrayState {
     /// 当前图标状态
@@ -117,6 +228,8 @@ pub struct TrayState {
     notifications: Arc<RwLock<Vec<TrayNotification>>>,
     /// 未读通知计数
     unread_notification_count: Arc<RwLock<u32>>,
+    /// 需要持久化的内容每次变化时递增
+    revision: Arc<AtomicU64>,
 }
 
 impl TrayState {
@@ -128,9 +241,42 @@ impl TrayState {
             system_resources: Arc::new(RwLock::new(SystemResources::default())),
             notifications: Arc::new(RwLock::new(Vec::new())),
             unread_notification_count: Arc::new(RwLock::new(0)),
+            revision: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn touch(&self) {
+        self.revision.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 持久化内容的版本号，用于判断是否需要保存
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
+    }
+
+    /// 需要持久化的内容
+    pub fn snapshot(&self) -> TraySnapshot {
+        TraySnapshot {
+            recent_conversations: self.get_recent_conversations(),
+            notifications: self.get_notifications(),
         }
     }
 
+    /// 用保存的内容替换最近对话与通知
+    pub fn restore(&self, snapshot: TraySnapshot) {
+        let mut conversations = snapshot.recent_conversations;
+        conversations.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        conversations.truncate(MAX_RECENT_CONVERSATIONS);
+        *self.recent_conversations.write() = conversations;
+
+        let mut notifications = snapshot.notifications;
+        notifications.truncate(MAX_NOTIFICATIONS);
+        let unread_count = notifications.iter().filter(|n| !n.is_read).count() as u32;
+        *self.notifications.write() = notifications;
+        *self.unread_notification_count.write() = unread_count;
+        self.touch();
+    }
+
     // ==================== 图标状态管理 ====================
 
     /// 获取当前图标状态
@@ -164,23 +310,26 @@ impl TrayState {
         }
         
         // 只保留最近 10 条
-        if conversations.len() > 10 {
-            conversations.truncate(10);
+        if conversations.len() > MAX_RECENT_CONVERSATIONS {
+            conversations.truncate(MAX_RECENT_CONVERSATIONS);
         }
         
         // 按更新时间排序
         conversations.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        self.touch();
     }
 
     /// 移除对话
     pub fn remove_conversation(&self, conversation_id: &str) {
         let mut conversations = self.recent_conversations.write();
         conversations.retain(|c| c.id != conversation_id);
+        self.touch();
     }
 
     /// 清空最近对话
     pub fn clear_conversations(&self) {
         self.recent_conversations.write().clear();
+        self.touch();
     }
 
     /// 标记对话已读
@@ -189,6 +338,7 @@ impl TrayState {
         if let Some(conversation) = conversations.iter_mut().find(|c| c.id == conversation_id) {
             conversation.unread_count = 0;
         }
+        self.touch();
     }
 
     /// 获取总未读消息数
@@ -237,13 +387,14 @@ impl TrayState {
         notifications.insert(0, notification);
         
         // 只保留最近 50 条
-        if notifications.len() > 50 {
-            notifications.truncate(50);
+        if notifications.len() > MAX_NOTIFICATIONS {
+            notifications.truncate(MAX_NOTIFICATIONS);
         }
         
         // 更新未读计数
         let unread_count = notifications.iter().filter(|n| !n.is_read).count() as u32;
         *self.unread_notification_count.write() = unread_count;
+        self.touch();
     }
 
     /// 标记通知已读
@@ -256,6 +407,7 @@ impl TrayState {
         // 更新未读计数
         let unread_count = notifications.iter().filter(|n| !n.is_read).count() as u32;
         *self.unread_notification_count.write() = unread_count;
+        self.touch();
     }
 
     /// 标记所有通知已读
@@ -265,12 +417,14 @@ impl TrayState {
             notification.is_read = true;
         }
         *self.unread_notification_count.write() = 0;
+        self.touch();
     }
 
     /// 清空通知
     pub fn clear_notifications(&self) {
         self.notifications.write().clear();
         *self.unread_notification_count.write() = 0;
+        self.touch();
     }
 
     /// 获取未读通知数
@@ -344,5 +498,70 @@ mod tests {
         state.mark_notification_read("notif1");
         assert_eq!(state.get_unread_notification_count(), 0);
     }
+
+    fn conversation(id: &str, minutes_ago: i64, unread_count: u32) -> RecentConversation {
+        RecentConversation {
+            id: id.to_string(),
+            title: format!("Old {}", id),
+            last_message: "old".to_string(),
+            updated_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            unread_count,
+        }
+    }
+
+    fn record(id: &str, minutes_ago: i64) -> ConversationRecord {
+        ConversationRecord {
+            id: id.to_string(),
+            title: format!("Title {}", id),
+            last_message: Some(format!("latest in {}", id)),
+            updated_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_drops_stale_conversations() {
+        let notification = TrayNotification {
+            id: "n1".to_string(),
+            title: "Test".to_string(),
+            body: "Test body".to_string(),
+            notification_type: NotificationType::Message,
+            created_at: Utc::now(),
+            is_read: false,
+        };
+        let snapshot = TraySnapshot {
+            recent_conversations: vec![conversation("a", 30, 2), conversation("gone", 5, 4), conversation("b", 60, 0)],
+            notifications: vec![notification.clone(), notification],
+        };
+        // a 在保存之后又有新消息，b 没有变化
+        let records = vec![record("a", 1), record("c", 10), record("b", 90)];
+
+        let (reconciled, report) = reconcile_snapshot(snapshot.clone(), &records, false);
+        assert_eq!(report.dropped, vec!["gone".to_string()]);
+        assert_eq!(report.kept, 2);
+        assert_eq!(report.duplicate_notifications, 1);
+        let a = &reconciled.recent_conversations[0];
+        assert_eq!((a.id.as_str(), a.title.as_str(), a.last_message.as_str(), a.unread_count), ("a", "Title a", "latest in a", 2));
+        assert_eq!(reconciled.recent_conversations[1].last_message, "old");
+
+        let (rebuilt, report) = reconcile_snapshot(snapshot, &records, true);
+        assert_eq!(report.added, 1);
+        let ids: Vec<&str> = rebuilt.recent_conversations.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_snapshot_restore_tracks_revision() {
+        let state = TrayState::new();
+        let before = state.revision();
+        state.add_or_update_conversation(conversation("a", 0, 3));
+        assert!(state.revision() > before);
+
+        let other = TrayState::new();
+        other.restore(state.snapshot());
+        assert_eq!(other.get_total_unread_count(), 3);
+        let revision = other.revision();
+        other.get_recent_conversations();
+        assert_eq!(other.revision(), revision);
+    }
 }
 
@@ -0,0 +1,293 @@
+//! GPU 监控
+//!
+//! 按平台采集 GPU 使用率、显存与温度：
+//! - NVIDIA（Windows / Linux）：NVML，运行时动态加载驱动库，没有 NVIDIA 驱动时跳过
+//! - Windows 其他显卡：DXGI，只能取得显存占用
+//! - macOS：读取 IOKit 中 `IOAccelerator` 的性能统计（通过 `ioreg`），没有温度
+//!
+//! 取不到的指标为 `None`，前端据此隐藏对应项。
+
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_os = "macos"))]
+use tracing::debug;
+
+/// 采集方式（只保留当前平台用得到的变体）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    #[cfg(not(target_os = "macos"))]
+    Nvml,
+    #[cfg(target_os = "windows")]
+    Dxgi,
+    Iokit,
+}
+
+/// GPU 信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    /// GPU 名称
+    pub name: String,
+    /// 采集方式
+    pub backend: GpuBackend,
+    /// 使用率（百分比）
+    pub utilization: Option<f32>,
+    /// 总显存（字节）；共享内存的集成显卡为 `None`
+    pub memory_total: Option<u64>,
+    /// 已使用显存（字节）
+    pub memory_used: Option<u64>,
+    /// 温度（摄氏度）
+    pub temperature: Option<f32>,
+}
+
+impl GpuInfo {
+    /// 显存使用率（百分比）
+    pub fn memory_usage(&self) -> Option<f32> {
+        match (self.memory_used, self.memory_total) {
+            (Some(used), Some(total)) if total > 0 => Some((used as f64 / total as f64 * 100.0) as f32),
+            _ => None,
+        }
+    }
+}
+
+/// 多块 GPU 中使用率最高的一块（用于历史曲线）
+pub fn peak_utilization(gpus: &[GpuInfo]) -> Option<f32> {
+    gpus.iter().filter_map(|gpu| gpu.utilization).reduce(f32::max)
+}
+
+/// GPU 采集器，驱动库只在创建时加载一次
+pub struct GpuCollector {
+    #[cfg(not(target_os = "macos"))]
+    nvml: Option<nvml_wrapper::Nvml>,
+}
+
+impl GpuCollector {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_os = "macos"))]
+            nvml: match nvml_wrapper::Nvml::init() {
+                Ok(nvml) => Some(nvml),
+                Err(e) => {
+                    debug!("NVML 不可用，跳过 NVIDIA GPU 监控: {}", e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// 采集所有 GPU 的当前状态
+    pub fn sample(&self) -> Vec<GpuInfo> {
+        #[cfg(target_os = "macos")]
+        {
+            sample_iokit()
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let mut gpus = self.nvml.as_ref().map(sample_nvml).unwrap_or_default();
+            #[cfg(target_os = "windows")]
+            {
+                // NVIDIA 显卡已由 NVML 采集
+                let skip_nvidia = !gpus.is_empty();
+                gpus.extend(sample_dxgi(skip_nvidia));
+            }
+            gpus
+        }
+    }
+}
+
+impl Default for GpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn sample_nvml(nvml: &nvml_wrapper::Nvml) -> Vec<GpuInfo> {
+    use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+
+    let count = match nvml.device_count() {
+        Ok(count) => count,
+        Err(e) => {
+            debug!("读取 NVIDIA GPU 数量失败: {}", e);
+            return Vec::new();
+        }
+    };
+    (0..count)
+        .filter_map(|index| nvml.device_by_index(index).ok())
+        .map(|device| {
+            let memory = device.memory_info().ok();
+            GpuInfo {
+                name: device.name().unwrap_or_else(|_| "NVIDIA GPU".to_string()),
+                backend: GpuBackend::Nvml,
+                utilization: device.utilization_rates().ok().map(|rates| rates.gpu as f32),
+                memory_total: memory.as_ref().map(|memory| memory.total),
+                memory_used: memory.as_ref().map(|memory| memory.used),
+                temperature: device.temperature(TemperatureSensor::Gpu).ok().map(|t| t as f32),
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn sample_dxgi(skip_nvidia: bool) -> Vec<GpuInfo> {
+    use windows::core::ComInterface;
+    use windows::Win32::Graphics::Dxgi::{
+        CreateDXGIFactory1, IDXGIAdapter3, IDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE,
+        DXGI_MEMORY_SEGMENT_GROUP_LOCAL, DXGI_QUERY_VIDEO_MEMORY_INFO,
+    };
+
+    const NVIDIA_VENDOR_ID: u32 = 0x10DE;
+
+    let factory: IDXGIFactory1 = match unsafe { CreateDXGIFactory1() } {
+        Ok(factory) => factory,
+        Err(e) => {
+            debug!("创建 DXGI 工厂失败: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut gpus = Vec::new();
+    let mut index = 0;
+    while let Ok(adapter) = unsafe { factory.EnumAdapters1(index) } {
+        index += 1;
+        let Ok(desc) = (unsafe { adapter.GetDesc1() }) else {
+            continue;
+        };
+        if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+            continue;
+        }
+        if skip_nvidia && desc.VendorId == NVIDIA_VENDOR_ID {
+            continue;
+        }
+
+        let name_len = desc.Description.iter().position(|&c| c == 0).unwrap_or(desc.Description.len());
+        let mut memory_used = None;
+        if let Ok(adapter3) = adapter.cast::<IDXGIAdapter3>() {
+            let mut info = DXGI_QUERY_VIDEO_MEMORY_INFO::default();
+            if unsafe { adapter3.QueryVideoMemoryInfo(0, DXGI_MEMORY_SEGMENT_GROUP_LOCAL, &mut info) }.is_ok() {
+                memory_used = Some(info.CurrentUsage);
+            }
+        }
+        // 集成显卡没有独立显存
+        let dedicated = desc.DedicatedVideoMemory as u64;
+        gpus.push(GpuInfo {
+            name: String::from_utf16_lossy(&desc.Description[..name_len]),
+            backend: GpuBackend::Dxgi,
+            utilization: None,
+            memory_total: (dedicated > 0).then_some(dedicated),
+            memory_used,
+            temperature: None,
+        });
+    }
+    gpus
+}
+
+#[cfg(target_os = "macos")]
+fn sample_iokit() -> Vec<GpuInfo> {
+    match std::process::Command::new("ioreg")
+        .args(["-r", "-d", "1", "-w", "0", "-c", "IOAccelerator"])
+        .output()
+    {
+        Ok(output) if output.status.success() => parse_ioreg(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// 从 `ioreg` 输出的属性块中取数值属性，如 `"Device Utilization %"=12` 或 `"VRAM,totalMB" = 4096`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn ioreg_number(block: &str, key: &str) -> Option<u64> {
+    let pattern = format!("\"{}\"", key);
+    let rest = block[block.find(&pattern)? + pattern.len()..].trim_start();
+    let rest = rest.strip_prefix('=')?.trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn ioreg_string(block: &str, key: &str) -> Option<String> {
+    let pattern = format!("\"{}\" = \"", key);
+    let start = block.find(&pattern)? + pattern.len();
+    let end = block[start..].find('"')?;
+    Some(block[start..start + end].to_string())
+}
+
+/// 解析 `ioreg -c IOAccelerator` 的输出，每个加速器一块
+///
+/// Apple Silicon 使用统一内存，只报告 `In use system memory`；
+/// Intel Mac 上的独立显卡报告 `vramUsedBytes` 与 `vramFreeBytes`。
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub(crate) fn parse_ioreg(output: &str) -> Vec<GpuInfo> {
+    output
+        .split("+-o ")
+        .filter(|block| block.contains("\"PerformanceStatistics\""))
+        .map(|block| {
+            let vram_used = ioreg_number(block, "vramUsedBytes");
+            let vram_free = ioreg_number(block, "vramFreeBytes");
+            let memory_used = vram_used.or_else(|| ioreg_number(block, "In use system memory"));
+            let memory_total = match (vram_used, vram_free) {
+                (Some(used), Some(free)) => Some(used + free),
+                _ => ioreg_number(block, "VRAM,totalMB").map(|mb| mb * 1024 * 1024),
+            };
+            GpuInfo {
+                name: ioreg_string(block, "model").unwrap_or_else(|| "GPU".to_string()),
+                backend: GpuBackend::Iokit,
+                utilization: ioreg_number(block, "Device Utilization %").map(|value| value as f32),
+                memory_total,
+                memory_used,
+                temperature: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ioreg() {
+        let output = r#"+-o AGXAcceleratorG13X  <class AGXAcceleratorG13X, id 0x100000abc>
+    {
+      "model" = "Apple M1 Pro"
+      "PerformanceStatistics" = {"In use system memory"=1048576,"Device Utilization %"=37,"Renderer Utilization %"=30}
+    }
++-o AMDRadeonX6000_AMDNavi14GraphicsAccelerator  <class AMDRadeonX6000>
+    {
+      "PerformanceStatistics" = {"vramFreeBytes"=3221225472,"Device Utilization %"=5,"vramUsedBytes"=1073741824}
+    }
++-o IntelAccelerator  <class IntelAccelerator>
+    {
+      "IOClass" = "IntelAccelerator"
+      "VRAM,totalMB" = 1536
+    }
+"#;
+        let gpus = parse_ioreg(output);
+        assert_eq!(gpus.len(), 2);
+
+        assert_eq!(gpus[0].name, "Apple M1 Pro");
+        assert_eq!(gpus[0].utilization, Some(37.0));
+        assert_eq!(gpus[0].memory_used, Some(1048576));
+        assert_eq!(gpus[0].memory_total, None);
+        assert_eq!(gpus[0].memory_usage(), None);
+
+        assert_eq!(gpus[1].name, "GPU");
+        assert_eq!(gpus[1].memory_total, Some(4294967296));
+        assert_eq!(gpus[1].memory_usage(), Some(25.0));
+        assert!(parse_ioreg("").is_empty());
+        assert_eq!(ioreg_number(output, "VRAM,totalMB"), Some(1536));
+    }
+
+    #[test]
+    fn test_peak_utilization() {
+        let gpu = |utilization| GpuInfo {
+            name: "GPU".to_string(),
+            backend: GpuBackend::Iokit,
+            utilization,
+            memory_total: None,
+            memory_used: None,
+            temperature: None,
+        };
+        assert_eq!(peak_utilization(&[]), None);
+        assert_eq!(peak_utilization(&[gpu(None)]), None);
+        assert_eq!(peak_utilization(&[gpu(Some(20.0)), gpu(None), gpu(Some(65.0))]), Some(65.0));
+    }
+}
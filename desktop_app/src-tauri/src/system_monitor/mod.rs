@@ -5,16 +5,27 @@
 //! - 内存使用情况
 //! - 磁盘使用情况
 //! - 网络使用情况
+//! - GPU 使用率、显存与温度（见 [`gpu`]）
 //! - 进程信息
 
+pub mod gpu;
+
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use sysinfo::{CpuExt, DiskExt, NetworkExt, ProcessExt, System, SystemExt};
+use sysinfo::{CpuExt, DiskExt, NetworkExt, PidExt, ProcessExt, System, SystemExt};
 use tauri::{AppHandle, Manager};
 use tokio::time::interval;
-use tracing::{error, info, trace, warn};
+use tracing::{info, trace, warn};
+
+use crate::utils::{resource_accounting, resource_budget, watchdog};
+use gpu::{GpuCollector, GpuInfo};
+
+/// 看门狗中的子系统名称
+const WATCHDOG_NAME: &str = "system_monitor";
+/// 监控循环心跳停滞阈值
+const STALL_AFTER: Duration = Duration::from_secs(60);
 
 /// 系统监控器状态
 pub struct SystemMonitor {
@@ -26,6 +37,8 @@ pub struct SystemMonitor {
     is_running: Arc<Mutex<bool>>,
     /// 监控统计信息
     stats: Arc<Mutex<MonitorStats>>,
+    /// GPU 采集器
+    gpu: Arc<GpuCollector>,
     /// 上次更新时间
     last_update: Arc<Mutex<Instant>>,
 }
@@ -37,6 +50,9 @@ pub struct MonitorStats {
     pub cpu_history: Vec<f32>,
     /// 内存使用率历史 (最近 60 个数据点)
     pub memory_history: Vec<f32>,
+    /// GPU 使用率历史 (最近 60 个数据点，多块 GPU 取最高值)
+    #[serde(default)]
+    pub gpu_history: Vec<f32>,
     /// 当前 CPU 使用率
     pub cpu_usage: f32,
     /// 当前内存使用率
@@ -51,6 +67,9 @@ pub struct MonitorStats {
     pub disks: Vec<DiskInfo>,
     /// 网络使用情况
     pub network: NetworkInfo,
+    /// GPU 信息
+    #[serde(default)]
+    pub gpus: Vec<GpuInfo>,
     /// 应用进程信息
     pub app_process: Option<ProcessInfo>,
     /// 最后更新时间戳
@@ -119,6 +138,7 @@ impl SystemMonitor {
             stats: Arc::new(Mutex::new(MonitorStats {
                 cpu_history: Vec::new(),
                 memory_history: Vec::new(),
+                gpu_history: Vec::new(),
                 cpu_usage: 0.0,
                 memory_usage: 0.0,
                 total_memory: 0,
@@ -131,9 +151,11 @@ impl SystemMonitor {
                     receive_rate: 0,
                     transmit_rate: 0,
                 },
+                gpus: Vec::new(),
                 app_process: None,
                 last_update: chrono::Utc::now().timestamp(),
             })),
+            gpu: Arc::new(GpuCollector::new()),
             last_update: Arc::new(Mutex::new(Instant::now())),
         }
     }
@@ -158,156 +180,224 @@ impl SystemMonitor {
         let is_running_clone = self.is_running.clone();
         let last_update = self.last_update.clone();
         let app_handle = self.app_handle.clone();
+        let gpu = self.gpu.clone();
         
-        // 启动监控任务
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(2));
-            let mut prev_network_rx = 0u64;
-            let mut prev_network_tx = 0u64;
+        // 启动监控任务（由看门狗监督，停滞时重新启动）
+        watchdog::supervise(WATCHDOG_NAME, STALL_AFTER, move |heartbeat| {
+            let system = system.clone();
+            let stats = stats.clone();
+            let is_running_clone = is_running_clone.clone();
+            let last_update = last_update.clone();
+            let app_handle = app_handle.clone();
+            let gpu = gpu.clone();
+            tokio::spawn(async move {
+                let mut interval = interval(Duration::from_secs(2));
+                let mut prev_network_rx = 0u64;
+                let mut prev_network_tx = 0u64;
             
-            loop {
-                interval.tick().await;
+                loop {
+                    interval.tick().await;
                 
-                // 检查是否应该继续运行
-                if !*is_running_clone.lock() {
-                    info!("系统监控已停止");
-                    break;
-                }
+                    // 检查是否应该继续运行
+                    if !*is_running_clone.lock() {
+                        info!("系统监控已停止");
+                        break;
+                    }
+                    if !heartbeat.beat() {
+                        break;
+                    }
                 
-                // 更新系统信息
-                let mut sys = system.lock();
-                sys.refresh_cpu();
-                sys.refresh_memory();
-                sys.refresh_disks_list();
-                sys.refresh_disks();
-                sys.refresh_networks();
-                sys.refresh_processes();
+                    // 采集 GPU 信息（驱动调用与 ioreg 可能阻塞）
+                    let gpus = {
+                        let gpu = gpu.clone();
+                        tokio::task::spawn_blocking(move || gpu.sample()).await.unwrap_or_default()
+                    };
+                    let gpu_usage = gpu::peak_utilization(&gpus);
                 
-                // 获取 CPU 使用率
-                let cpu_usage = sys.global_cpu_info().cpu_usage();
+                    // 更新系统信息
+                    let mut sys = system.lock();
+                    sys.refresh_cpu();
+                    sys.refresh_memory();
+                    sys.refresh_disks_list();
+                    sys.refresh_disks();
+                    sys.refresh_networks();
+                    sys.refresh_processes();
                 
-                // 获取内存信息
-                let total_memory = sys.total_memory();
-                let used_memory = sys.used_memory();
-                let available_memory = sys.available_memory();
-                let memory_usage = if total_memory > 0 {
-                    (used_memory as f32 / total_memory as f32) * 100.0
-                } else {
-                    0.0
-                };
+                    // 获取 CPU 使用率
+                    let cpu_usage = sys.global_cpu_info().cpu_usage();
                 
-                // 获取磁盘信息
-                let disks: Vec<DiskInfo> = sys.disks().iter().map(|disk| {
-                    let total_space = disk.total_space();
-                    let available_space = disk.available_space();
-                    let used_space = total_space.saturating_sub(available_space);
-                    let usage_percent = if total_space > 0 {
-                        (used_space as f64 / total_space as f64 * 100.0) as f32
+                    // 获取内存信息
+                    let total_memory = sys.total_memory();
+                    let used_memory = sys.used_memory();
+                    let available_memory = sys.available_memory();
+                    let memory_usage = if total_memory > 0 {
+                        (used_memory as f32 / total_memory as f32) * 100.0
                     } else {
                         0.0
                     };
+                
+                    // 获取磁盘信息
+                    let disks: Vec<DiskInfo> = sys.disks().iter().map(|disk| {
+                        let total_space = disk.total_space();
+                        let available_space = disk.available_space();
+                        let used_space = total_space.saturating_sub(available_space);
+                        let usage_percent = if total_space > 0 {
+                            (used_space as f64 / total_space as f64 * 100.0) as f32
+                        } else {
+                            0.0
+                        };
                     
-                    DiskInfo {
-                        name: disk.name().to_string_lossy().to_string(),
-                        mount_point: disk.mount_point().to_string_lossy().to_string(),
-                        total_space,
-                        available_space,
-                        usage_percent,
-                        file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
-                        is_removable: disk.is_removable(),
+                        DiskInfo {
+                            name: disk.name().to_string_lossy().to_string(),
+                            mount_point: disk.mount_point().to_string_lossy().to_string(),
+                            total_space,
+                            available_space,
+                            usage_percent,
+                            file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+                            is_removable: disk.is_removable(),
+                        }
+                    }).collect();
+                
+                    // 获取网络信息
+                    let networks = sys.networks();
+                    let mut total_rx = 0u64;
+                    let mut total_tx = 0u64;
+                
+                    for (_interface_name, data) in networks {
+                        total_rx += data.total_received();
+                        total_tx += data.total_transmitted();
                     }
-                }).collect();
                 
-                // 获取网络信息
-                let networks = sys.networks();
-                let mut total_rx = 0u64;
-                let mut total_tx = 0u64;
+                    // 计算速率（字节/秒）
+                    let now = Instant::now();
+                    let elapsed = now.duration_since(*last_update.lock()).as_secs_f64();
+                    let receive_rate = if elapsed > 0.0 {
+                        ((total_rx.saturating_sub(prev_network_rx)) as f64 / elapsed) as u64
+                    } else {
+                        0
+                    };
+                    let transmit_rate = if elapsed > 0.0 {
+                        ((total_tx.saturating_sub(prev_network_tx)) as f64 / elapsed) as u64
+                    } else {
+                        0
+                    };
+                
+                    prev_network_rx = total_rx;
+                    prev_network_tx = total_tx;
+                    *last_update.lock() = now;
                 
-                for (_interface_name, data) in networks {
-                    total_rx += data.total_received();
-                    total_tx += data.total_transmitted();
-                }
+                    let network = NetworkInfo {
+                        total_received: total_rx,
+                        total_transmitted: total_tx,
+                        receive_rate,
+                        transmit_rate,
+                    };
                 
-                // 计算速率（字节/秒）
-                let now = Instant::now();
-                let elapsed = now.duration_since(*last_update.lock()).as_secs_f64();
-                let receive_rate = if elapsed > 0.0 {
-                    ((total_rx.saturating_sub(prev_network_rx)) as f64 / elapsed) as u64
-                } else {
-                    0
-                };
-                let transmit_rate = if elapsed > 0.0 {
-                    ((total_tx.saturating_sub(prev_network_tx)) as f64 / elapsed) as u64
-                } else {
-                    0
-                };
+                    // 获取当前应用进程信息
+                    let current_pid = std::process::id();
+                    let app_process = sys.process(sysinfo::Pid::from(current_pid as usize)).map(|process| {
+                        ProcessInfo {
+                            pid: current_pid,
+                            name: process.name().to_string(),
+                            cpu_usage: process.cpu_usage(),
+                            memory: process.memory(),
+                            virtual_memory: process.virtual_memory(),
+                            run_time: process.run_time(),
+                        }
+                    });
                 
-                prev_network_rx = total_rx;
-                prev_network_tx = total_tx;
-                *last_update.lock() = now;
+                    // 应用进程及其子进程（WebView、适配器进程等），用于按子系统核算资源
+                    let parents: Vec<(u32, Option<u32>)> = sys
+                        .processes()
+                        .iter()
+                        .map(|(pid, process)| (pid.as_u32(), process.parent().map(|parent| parent.as_u32())))
+                        .collect();
+                    let family: Vec<resource_accounting::ProcessSample> = resource_accounting::descendants(current_pid, &parents)
+                        .into_iter()
+                        .filter_map(|pid| {
+                            sys.process(sysinfo::Pid::from_u32(pid)).map(|process| resource_accounting::ProcessSample {
+                                pid,
+                                parent: process.parent().map(|parent| parent.as_u32()),
+                                name: process.name().to_string(),
+                                cpu_percent: f64::from(process.cpu_usage()),
+                                memory_bytes: process.memory(),
+                            })
+                        })
+                        .collect();
                 
-                let network = NetworkInfo {
-                    total_received: total_rx,
-                    total_transmitted: total_tx,
-                    receive_rate,
-                    transmit_rate,
-                };
+                    drop(sys);
                 
-                // 获取当前应用进程信息
-                let current_pid = std::process::id();
-                let app_process = sys.process(sysinfo::Pid::from(current_pid as usize)).map(|process| {
-                    ProcessInfo {
-                        pid: current_pid,
-                        name: process.name().to_string(),
-                        cpu_usage: process.cpu_usage(),
-                        memory: process.memory(),
-                        virtual_memory: process.virtual_memory(),
-                        run_time: process.run_time(),
+                    if let Some(db) = crate::database::get_database() {
+                        let status = db.get_pool().status();
+                        let available = status.available.max(0) as usize;
+                        resource_accounting::record_pool_usage(status.size.saturating_sub(available), status.size);
                     }
-                });
+                    resource_accounting::record_sample(current_pid, family);
                 
-                drop(sys);
+                    // 更新统计信息
+                    let mut stats = stats.lock();
                 
-                // 更新统计信息
-                let mut stats = stats.lock();
+                    // 保持历史数据不超过 60 个点（2 分钟的数据，每 2 秒一个点）
+                    stats.cpu_history.push(cpu_usage);
+                    if stats.cpu_history.len() > 60 {
+                        stats.cpu_history.remove(0);
+                    }
                 
-                // 保持历史数据不超过 60 个点（2 分钟的数据，每 2 秒一个点）
-                stats.cpu_history.push(cpu_usage);
-                if stats.cpu_history.len() > 60 {
-                    stats.cpu_history.remove(0);
-                }
+                    stats.memory_history.push(memory_usage);
+                    if stats.memory_history.len() > 60 {
+                        stats.memory_history.remove(0);
+                    }
                 
-                stats.memory_history.push(memory_usage);
-                if stats.memory_history.len() > 60 {
-                    stats.memory_history.remove(0);
-                }
+                    if let Some(gpu_usage) = gpu_usage {
+                        stats.gpu_history.push(gpu_usage);
+                        if stats.gpu_history.len() > 60 {
+                            stats.gpu_history.remove(0);
+                        }
+                    }
                 
-                stats.cpu_usage = cpu_usage;
-                stats.memory_usage = memory_usage;
-                stats.total_memory = total_memory;
-                stats.used_memory = used_memory;
-                stats.available_memory = available_memory;
-                stats.disks = disks;
-                stats.network = network;
-                stats.app_process = app_process;
-                stats.last_update = chrono::Utc::now().timestamp();
+                    stats.cpu_usage = cpu_usage;
+                    stats.memory_usage = memory_usage;
+                    stats.total_memory = total_memory;
+                    stats.used_memory = used_memory;
+                    stats.available_memory = available_memory;
+                    stats.disks = disks;
+                    stats.network = network;
+                    stats.gpus = gpus;
+                    stats.app_process = app_process;
+                    stats.last_update = chrono::Utc::now().timestamp();
                 
-                let stats_clone = stats.clone();
-                drop(stats);
+                    let stats_clone = stats.clone();
+                    drop(stats);
                 
-                // 发送更新事件到前端
-                if let Err(e) = app_handle.emit_all("system-monitor-update", &stats_clone) {
-                    error!("发送系统监控更新事件失败: {}", e);
-                }
+                    // 向资源预算管理器报告应用进程的资源占用
+                    if let Some(process) = &stats_clone.app_process {
+                        let sample = resource_budget::ResourceUsage {
+                            cpu_percent: f64::from(process.cpu_usage),
+                            memory_mb: process.memory as f64 / (1024.0 * 1024.0),
+                            network_kbps: (stats_clone.network.receive_rate + stats_clone.network.transmit_rate) as f64 / 1024.0,
+                        };
+                        if let Some(allocation) = resource_budget::record_usage(sample) {
+                            crate::commands::resource_budget::broadcast_allocation(&app_handle, &allocation);
+                        }
+                    }
+                
+                    // 只发送给订阅了系统监控频道的窗口
+                    crate::commands::event_subscription::publish(
+                        &app_handle,
+                        crate::commands::event_subscription::CHANNEL_SYSTEM_MONITOR,
+                        &stats_clone,
+                    );
                 
-                trace!(
-                    "系统监控更新 - CPU: {:.2}%, 内存: {:.2}%, 网络: ↓{} ↑{}/s",
-                    cpu_usage,
-                    memory_usage,
-                    format_bytes(stats_clone.network.receive_rate),
-                    format_bytes(stats_clone.network.transmit_rate)
-                );
-            }
+                    trace!(
+                        "系统监控更新 - CPU: {:.2}%, 内存: {:.2}%, GPU: {:?}, 网络: ↓{} ↑{}/s",
+                        cpu_usage,
+                        memory_usage,
+                        gpu_usage,
+                        format_bytes(stats_clone.network.receive_rate),
+                        format_bytes(stats_clone.network.transmit_rate)
+                    );
+                }
+            });
         });
     }
     
@@ -321,6 +411,7 @@ impl SystemMonitor {
         }
         
         *is_running = false;
+        watchdog::unregister(WATCHDOG_NAME);
         info!("停止系统监控");
     }
     
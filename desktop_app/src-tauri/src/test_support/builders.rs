@@ -0,0 +1,223 @@
+//! 核心结构构建器
+//!
+//! 每个构建器都以可直接使用的默认值开始，测试只需覆盖关心的字段。
+
+use chrono::Utc;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+use crate::database::adapter::{AdapterInstallStatus, InstalledAdapter};
+use crate::database::character_registry::CharacterData;
+use crate::database::workflow::{WorkflowDefinition, WorkflowStatus};
+
+/// 角色构建器
+#[derive(Debug, Clone)]
+pub struct CharacterBuilder {
+    character: CharacterData,
+}
+
+impl CharacterBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        Self {
+            character: CharacterData {
+                name: id.clone(),
+                display_name: id.clone(),
+                path: format!("/live2d/{}/{}.model3.json", id, id),
+                preview_image: None,
+                description: String::new(),
+                gender: "female".to_string(),
+                size: "medium".to_string(),
+                features: Vec::new(),
+                motions: vec!["idle".to_string()],
+                expressions: vec!["default".to_string()],
+                is_active: false,
+                id,
+            },
+        }
+    }
+
+    pub fn display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.character.display_name = display_name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.character.description = description.into();
+        self
+    }
+
+    pub fn motions<I, S>(mut self, motions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.character.motions = motions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn expressions<I, S>(mut self, expressions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.character.expressions = expressions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn active(mut self) -> Self {
+        self.character.is_active = true;
+        self
+    }
+
+    pub fn build(self) -> CharacterData {
+        self.character
+    }
+}
+
+/// 已安装适配器构建器
+#[derive(Debug, Clone)]
+pub struct AdapterBuilder {
+    adapter: InstalledAdapter,
+}
+
+impl AdapterBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        let now = Utc::now();
+        Self {
+            adapter: InstalledAdapter {
+                name: id.clone(),
+                display_name: id.clone(),
+                version: "1.0.0".to_string(),
+                install_path: format!("/adapters/{}", id),
+                status: AdapterInstallStatus::Installed,
+                enabled: true,
+                auto_update: false,
+                source: "file".to_string(),
+                source_id: None,
+                description: None,
+                author: None,
+                license: Some("MIT".to_string()),
+                homepage_url: None,
+                installed_at: now,
+                updated_at: now,
+                last_used_at: None,
+                config: HashMap::new(),
+                metadata: HashMap::new(),
+                id,
+            },
+        }
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.adapter.version = version.into();
+        self
+    }
+
+    pub fn status(mut self, status: AdapterInstallStatus) -> Self {
+        self.adapter.status = status;
+        self
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.adapter.enabled = false;
+        self
+    }
+
+    pub fn from_market(mut self, source_id: impl Into<String>) -> Self {
+        self.adapter.source = "market".to_string();
+        self.adapter.source_id = Some(source_id.into());
+        self
+    }
+
+    pub fn config(mut self, key: impl Into<String>, value: JsonValue) -> Self {
+        self.adapter.config.insert(key.into(), value);
+        self
+    }
+
+    pub fn metadata(mut self, key: impl Into<String>, value: JsonValue) -> Self {
+        self.adapter.metadata.insert(key.into(), value);
+        self
+    }
+
+    pub fn build(self) -> InstalledAdapter {
+        self.adapter
+    }
+}
+
+/// 工作流定义构建器
+#[derive(Debug, Clone)]
+pub struct WorkflowBuilder {
+    workflow: WorkflowDefinition,
+    steps: Vec<JsonValue>,
+}
+
+impl WorkflowBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        let now = Utc::now().timestamp();
+        Self {
+            workflow: WorkflowDefinition {
+                name: id.clone(),
+                description: None,
+                version: "1.0.0".to_string(),
+                status: WorkflowStatus::Draft,
+                steps: None,
+                config: None,
+                tags: None,
+                category: "general".to_string(),
+                is_template: false,
+                template_id: None,
+                created_at: now,
+                updated_at: now,
+                id,
+            },
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.workflow.name = name.into();
+        self
+    }
+
+    pub fn status(mut self, status: WorkflowStatus) -> Self {
+        self.workflow.status = status;
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.workflow.category = category.into();
+        self
+    }
+
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let tags: Vec<String> = tags.into_iter().map(Into::into).collect();
+        self.workflow.tags = Some(serde_json::json!(tags));
+        self
+    }
+
+    /// 追加一个步骤，ID 为 `step_<序号>`
+    pub fn step(mut self, step_type: &str, config: JsonValue) -> Self {
+        let id = format!("step_{}", self.steps.len() + 1);
+        self.steps.push(serde_json::json!({ "id": id, "type": step_type, "config": config }));
+        self
+    }
+
+    pub fn template(mut self) -> Self {
+        self.workflow.is_template = true;
+        self
+    }
+
+    pub fn build(mut self) -> WorkflowDefinition {
+        if !self.steps.is_empty() {
+            self.workflow.steps = Some(JsonValue::Array(self.steps));
+        }
+        self.workflow
+    }
+}
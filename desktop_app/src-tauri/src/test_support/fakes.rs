@@ -0,0 +1,308 @@
+//! 内存注册表
+//!
+//! 与 `database` 中基于 PostgreSQL 的注册表保持相同的方法名和签名（同步/异步一致），
+//! 测试中可以直接替换，无需数据库。
+
+use chrono::Utc;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+
+use crate::database::adapter::{AdapterInstallStatus, InstalledAdapter};
+use crate::database::character_registry::{CharacterConfig, CharacterData};
+use crate::database::workflow::WorkflowDefinition;
+
+type FakeResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn not_found(kind: &str, id: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("{} {} 不存在", kind, id).into()
+}
+
+/// 内存角色注册表
+#[derive(Debug, Default)]
+pub struct InMemoryCharacterRegistry {
+    characters: RwLock<BTreeMap<String, CharacterData>>,
+    configs: RwLock<BTreeMap<String, CharacterConfig>>,
+}
+
+impl InMemoryCharacterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用初始数据创建
+    pub fn with_characters(characters: impl IntoIterator<Item = CharacterData>) -> Self {
+        let registry = Self::new();
+        for character in characters {
+            registry.characters.write().insert(character.id.clone(), character);
+        }
+        registry
+    }
+
+    pub fn register_character(&self, character: CharacterData) -> FakeResult<()> {
+        self.characters.write().insert(character.id.clone(), character);
+        Ok(())
+    }
+
+    pub async fn register_character_async(&self, character: CharacterData) -> FakeResult<()> {
+        self.register_character(character)
+    }
+
+    pub fn get_character(&self, character_id: &str) -> FakeResult<Option<CharacterData>> {
+        Ok(self.characters.read().get(character_id).cloned())
+    }
+
+    pub async fn get_character_async(&self, character_id: &str) -> FakeResult<Option<CharacterData>> {
+        self.get_character(character_id)
+    }
+
+    pub fn get_all_characters(&self) -> FakeResult<Vec<CharacterData>> {
+        Ok(self.characters.read().values().cloned().collect())
+    }
+
+    pub async fn get_all_characters_async(&self) -> FakeResult<Vec<CharacterData>> {
+        self.get_all_characters()
+    }
+
+    pub fn get_active_character(&self) -> FakeResult<Option<CharacterData>> {
+        Ok(self.characters.read().values().find(|c| c.is_active).cloned())
+    }
+
+    pub async fn get_active_character_async(&self) -> FakeResult<Option<CharacterData>> {
+        self.get_active_character()
+    }
+
+    pub fn set_active_character(&self, character_id: &str) -> FakeResult<()> {
+        let mut characters = self.characters.write();
+        if !characters.contains_key(character_id) {
+            return Err(not_found("角色", character_id));
+        }
+        for character in characters.values_mut() {
+            character.is_active = character.id == character_id;
+        }
+        Ok(())
+    }
+
+    pub async fn set_active_character_async(&self, character_id: &str) -> FakeResult<()> {
+        self.set_active_character(character_id)
+    }
+
+    pub fn update_character(&self, character: CharacterData) -> FakeResult<()> {
+        let mut characters = self.characters.write();
+        match characters.get_mut(&character.id) {
+            Some(existing) => {
+                *existing = character;
+                Ok(())
+            }
+            None => Err(not_found("角色", &character.id)),
+        }
+    }
+
+    pub fn delete_character(&self, character_id: &str) -> FakeResult<()> {
+        self.characters.write().remove(character_id);
+        self.configs.write().remove(character_id);
+        Ok(())
+    }
+
+    pub fn get_character_config(&self, character_id: &str) -> FakeResult<Option<CharacterConfig>> {
+        Ok(self.configs.read().get(character_id).cloned())
+    }
+
+    pub async fn get_character_config_async(&self, character_id: &str) -> FakeResult<Option<CharacterConfig>> {
+        self.get_character_config(character_id)
+    }
+
+    pub fn save_character_config(&self, config: CharacterConfig) -> FakeResult<()> {
+        self.configs.write().insert(config.character_id.clone(), config);
+        Ok(())
+    }
+
+    pub async fn save_character_config_async(&self, config: CharacterConfig) -> FakeResult<()> {
+        self.save_character_config(config)
+    }
+}
+
+/// 内存适配器注册表
+#[derive(Debug, Default)]
+pub struct InMemoryAdapterRegistry {
+    adapters: RwLock<BTreeMap<String, InstalledAdapter>>,
+}
+
+impl InMemoryAdapterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用初始数据创建
+    pub fn with_adapters(adapters: impl IntoIterator<Item = InstalledAdapter>) -> Self {
+        let registry = Self::new();
+        for adapter in adapters {
+            registry.adapters.write().insert(adapter.id.clone(), adapter);
+        }
+        registry
+    }
+
+    pub async fn add_adapter(&self, adapter: InstalledAdapter) -> FakeResult<()> {
+        self.adapters.write().insert(adapter.id.clone(), adapter);
+        Ok(())
+    }
+
+    pub async fn get_adapter(&self, adapter_id: &str) -> FakeResult<Option<InstalledAdapter>> {
+        Ok(self.adapters.read().get(adapter_id).cloned())
+    }
+
+    pub async fn get_all_adapters(&self) -> FakeResult<Vec<InstalledAdapter>> {
+        Ok(self.adapters.read().values().cloned().collect())
+    }
+
+    pub async fn get_enabled_adapters(&self) -> FakeResult<Vec<InstalledAdapter>> {
+        Ok(self.adapters.read().values().filter(|a| a.enabled).cloned().collect())
+    }
+
+    pub async fn update_adapter(&self, adapter: InstalledAdapter) -> FakeResult<()> {
+        let mut adapters = self.adapters.write();
+        if !adapters.contains_key(&adapter.id) {
+            return Err(not_found("适配器", &adapter.id));
+        }
+        adapters.insert(adapter.id.clone(), adapter);
+        Ok(())
+    }
+
+    pub async fn delete_adapter(&self, adapter_id: &str) -> FakeResult<()> {
+        self.adapters.write().remove(adapter_id);
+        Ok(())
+    }
+
+    pub async fn set_adapter_enabled(&self, adapter_id: &str, enabled: bool) -> FakeResult<()> {
+        self.modify(adapter_id, |a| a.enabled = enabled)
+    }
+
+    pub async fn update_adapter_status(&self, adapter_id: &str, status: AdapterInstallStatus) -> FakeResult<()> {
+        self.modify(adapter_id, |a| a.status = status)
+    }
+
+    pub async fn update_last_used(&self, adapter_id: &str) -> FakeResult<()> {
+        self.modify(adapter_id, |a| a.last_used_at = Some(Utc::now()))
+    }
+
+    pub async fn adapter_exists(&self, adapter_id: &str) -> FakeResult<bool> {
+        Ok(self.adapters.read().contains_key(adapter_id))
+    }
+
+    pub async fn count_adapters(&self) -> FakeResult<i64> {
+        Ok(self.adapters.read().len() as i64)
+    }
+
+    fn modify(&self, adapter_id: &str, f: impl FnOnce(&mut InstalledAdapter)) -> FakeResult<()> {
+        let mut adapters = self.adapters.write();
+        let adapter = adapters.get_mut(adapter_id).ok_or_else(|| not_found("适配器", adapter_id))?;
+        f(adapter);
+        adapter.updated_at = Utc::now();
+        Ok(())
+    }
+}
+
+/// 内存工作流注册表
+#[derive(Debug, Default)]
+pub struct InMemoryWorkflowRegistry {
+    workflows: RwLock<BTreeMap<String, WorkflowDefinition>>,
+}
+
+impl InMemoryWorkflowRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用初始数据创建
+    pub fn with_workflows(workflows: impl IntoIterator<Item = WorkflowDefinition>) -> Self {
+        let registry = Self::new();
+        for workflow in workflows {
+            registry.workflows.write().insert(workflow.id.clone(), workflow);
+        }
+        registry
+    }
+
+    pub fn create_workflow(&self, workflow: WorkflowDefinition) -> FakeResult<()> {
+        let mut workflows = self.workflows.write();
+        if workflows.contains_key(&workflow.id) {
+            return Err(format!("工作流 {} 已存在", workflow.id).into());
+        }
+        workflows.insert(workflow.id.clone(), workflow);
+        Ok(())
+    }
+
+    pub fn get_workflow(&self, id: &str) -> FakeResult<Option<WorkflowDefinition>> {
+        Ok(self.workflows.read().get(id).cloned())
+    }
+
+    pub fn get_all_workflows(&self) -> FakeResult<Vec<WorkflowDefinition>> {
+        Ok(self.workflows.read().values().cloned().collect())
+    }
+
+    pub fn update_workflow(&self, workflow: WorkflowDefinition) -> FakeResult<()> {
+        let mut workflows = self.workflows.write();
+        if !workflows.contains_key(&workflow.id) {
+            return Err(not_found("工作流", &workflow.id));
+        }
+        workflows.insert(workflow.id.clone(), workflow);
+        Ok(())
+    }
+
+    pub fn delete_workflow(&self, id: &str) -> FakeResult<()> {
+        self.workflows.write().remove(id);
+        Ok(())
+    }
+
+    pub fn search_workflows(&self, query: &str) -> FakeResult<Vec<WorkflowDefinition>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .workflows
+            .read()
+            .values()
+            .filter(|w| {
+                w.name.to_lowercase().contains(&query)
+                    || w.description.as_deref().unwrap_or_default().to_lowercase().contains(&query)
+            })
+            .cloned()
+            .collect())
+    }
+
+    pub fn get_templates(&self) -> FakeResult<Vec<WorkflowDefinition>> {
+        Ok(self.workflows.read().values().filter(|w| w.is_template).cloned().collect())
+    }
+
+    pub fn get_workflows_by_category(&self, category: &str) -> FakeResult<Vec<WorkflowDefinition>> {
+        Ok(self.workflows.read().values().filter(|w| w.category == category).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::builders::{AdapterBuilder, CharacterBuilder, WorkflowBuilder};
+
+    #[test]
+    fn test_character_registry_single_active() {
+        let registry = InMemoryCharacterRegistry::with_characters([
+            CharacterBuilder::new("hiyori").active().build(),
+            CharacterBuilder::new("shizuku").build(),
+        ]);
+        registry.set_active_character("shizuku").unwrap();
+        assert_eq!(registry.get_active_character().unwrap().unwrap().id, "shizuku");
+        assert!(registry.set_active_character("missing").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_adapter_registry_enable_toggle() {
+        let registry = InMemoryAdapterRegistry::with_adapters([AdapterBuilder::new("weather").build()]);
+        registry.set_adapter_enabled("weather", false).await.unwrap();
+        assert!(registry.get_enabled_adapters().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_workflow_registry_rejects_duplicates() {
+        let registry = InMemoryWorkflowRegistry::new();
+        registry.create_workflow(WorkflowBuilder::new("wf").template().build()).unwrap();
+        assert!(registry.create_workflow(WorkflowBuilder::new("wf").build()).is_err());
+        assert_eq!(registry.get_templates().unwrap().len(), 1);
+    }
+}
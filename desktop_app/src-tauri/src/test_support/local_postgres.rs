@@ -0,0 +1,158 @@
+//! 本地 PostgreSQL
+//!
+//! 用本机安装的 PostgreSQL 程序（`initdb`/`pg_ctl`）在临时目录中初始化一个只监听 `127.0.0.1`
+//! 的一次性集群，停止时删除数据目录。不需要 Docker，也不需要预先运行的数据库服务。
+//!
+//! 程序目录依次取：`ZISHU_TEST_PG_BIN`、`PATH` 中含 `initdb` 的目录、
+//! `/usr/lib/postgresql/<版本>/bin`（Debian/Ubuntu 的安装位置，GitHub Actions 的 Ubuntu 镜像自带但不在 `PATH` 中）。
+//! PostgreSQL 不允许以 root 身份初始化集群。
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::process::Command;
+use tracing::warn;
+
+/// PostgreSQL 程序目录
+pub const TEST_PG_BIN_ENV: &str = "ZISHU_TEST_PG_BIN";
+
+/// 临时 PostgreSQL 集群，释放时停止并删除
+pub struct LocalPostgres {
+    bin_dir: PathBuf,
+    data_dir: PathBuf,
+    port: u16,
+    running: bool,
+}
+
+impl LocalPostgres {
+    /// 初始化并启动集群
+    pub async fn start() -> Result<Self, String> {
+        let bin_dir = find_bin_dir().ok_or_else(|| format!("未找到 initdb，可通过 {} 指定目录", TEST_PG_BIN_ENV))?;
+        let data_dir = std::env::temp_dir().join(format!("zishu_test_pg_{}", uuid::Uuid::new_v4().simple()));
+        let port = free_port().map_err(|e| format!("分配端口失败: {}", e))?;
+
+        let mut cluster = Self {
+            bin_dir,
+            data_dir,
+            port,
+            running: false,
+        };
+        if let Err(e) = cluster.init().await {
+            let _ = tokio::fs::remove_dir_all(&cluster.data_dir).await;
+            return Err(e);
+        }
+        cluster.running = true;
+        Ok(cluster)
+    }
+
+    async fn init(&self) -> Result<(), String> {
+        run(Command::new(self.program("initdb"))
+            .arg("-D")
+            .arg(&self.data_dir)
+            .args(["-U", "postgres", "-A", "trust", "-E", "UTF8", "--no-sync"]))
+        .await?;
+
+        let options = format!(
+            "-p {} -c listen_addresses=127.0.0.1 -k \"{}\" -F",
+            self.port,
+            self.data_dir.display()
+        );
+        let started = run(Command::new(self.program("pg_ctl"))
+            .arg("-D")
+            .arg(&self.data_dir)
+            .arg("-l")
+            .arg(self.log_file())
+            .arg("-o")
+            .arg(options)
+            .args(["-w", "start"]))
+        .await;
+        started.map_err(|e| match std::fs::read_to_string(self.log_file()) {
+            Ok(log) => format!("{}\n{}", e, log.trim()),
+            Err(_) => e,
+        })
+    }
+
+    /// 超级用户 `postgres` 连接默认数据库的连接串
+    pub fn url(&self) -> String {
+        format!("postgres://postgres@127.0.0.1:{}/postgres", self.port)
+    }
+
+    /// 停止集群并删除数据目录
+    pub async fn stop(mut self) {
+        self.running = false;
+        if let Err(e) = run(&mut Command::from(self.stop_command("fast"))).await {
+            warn!("停止临时 PostgreSQL 失败: {}", e);
+        }
+        if let Err(e) = tokio::fs::remove_dir_all(&self.data_dir).await {
+            warn!("删除临时 PostgreSQL 数据目录失败: {}", e);
+        }
+    }
+
+    fn program(&self, name: &str) -> PathBuf {
+        self.bin_dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+    }
+
+    fn log_file(&self) -> PathBuf {
+        self.data_dir.join("server.log")
+    }
+
+    fn stop_command(&self, mode: &str) -> std::process::Command {
+        let mut command = std::process::Command::new(self.program("pg_ctl"));
+        command.arg("-D").arg(&self.data_dir).args(["-m", mode, "-w", "stop"]);
+        command
+    }
+}
+
+impl Drop for LocalPostgres {
+    fn drop(&mut self) {
+        // 未调用 stop 时（例如测试 panic）直接终止，只等待 pg_ctl 子进程，不涉及异步运行时
+        if self.running {
+            let _ = self.stop_command("immediate").stdout(Stdio::null()).stderr(Stdio::null()).status();
+            let _ = std::fs::remove_dir_all(&self.data_dir);
+        }
+    }
+}
+
+async fn run(command: &mut Command) -> Result<(), String> {
+    let program = Path::new(command.as_std().get_program())
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("运行 {} 失败: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} 失败: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+fn find_bin_dir() -> Option<PathBuf> {
+    let has_initdb = |dir: &Path| dir.join(format!("initdb{}", std::env::consts::EXE_SUFFIX)).is_file();
+
+    if let Some(dir) = std::env::var_os(TEST_PG_BIN_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("PATH").and_then(|paths| std::env::split_paths(&paths).find(|dir| has_initdb(dir))) {
+        return Some(dir);
+    }
+
+    // 多个版本时取最新的
+    std::fs::read_dir("/usr/lib/postgresql")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let version: u32 = entry.file_name().to_str()?.split('.').next()?.parse().ok()?;
+            let bin_dir = entry.path().join("bin");
+            has_initdb(&bin_dir).then_some((version, bin_dir))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, bin_dir)| bin_dir)
+}
+
+fn free_port() -> std::io::Result<u16> {
+    Ok(std::net::TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port())
+}
@@ -0,0 +1,336 @@
+//! 内存 Redis
+//!
+//! 实现 `RedisBackend` 和 redis 客户端建立连接时用到的 RESP2 命令，数据保存在进程内存中。
+//! 测试环境没有配置 Redis 服务器时用它代替，缓存相关的代码路径也能在 CI 上运行。
+//! 不支持的命令返回 `ERR unknown command`。
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use super::service_thread::ServiceThread;
+
+struct Entry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+type Store = Arc<Mutex<HashMap<String, Entry>>>;
+
+enum Reply {
+    Simple(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<String>),
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    fn encode(&self, out: &mut Vec<u8>) {
+        // 写入 Vec 不会失败
+        let _ = match self {
+            Self::Simple(s) => write!(out, "+{}\r\n", s),
+            Self::Error(e) => write!(out, "-{}\r\n", e),
+            Self::Integer(i) => write!(out, ":{}\r\n", i),
+            Self::Bulk(None) => write!(out, "$-1\r\n"),
+            Self::Bulk(Some(v)) => write!(out, "${}\r\n{}\r\n", v.len(), v),
+            Self::Array(items) => {
+                let _ = write!(out, "*{}\r\n", items.len());
+                items.iter().for_each(|item| item.encode(out));
+                Ok(())
+            }
+        };
+    }
+}
+
+/// 内存 Redis 服务，释放时停止
+pub struct MemoryRedis {
+    service: ServiceThread,
+}
+
+impl MemoryRedis {
+    /// 在本地随机端口上启动
+    pub fn start() -> io::Result<Self> {
+        let store = Store::default();
+        let service = ServiceThread::spawn("memory-redis", move |listener, shutdown| serve(listener, store, shutdown))?;
+        Ok(Self { service })
+    }
+
+    /// 连接串
+    pub fn url(&self) -> String {
+        format!("redis://{}", self.service.addr())
+    }
+
+    /// 停止服务
+    pub fn stop(&mut self) {
+        self.service.stop();
+    }
+}
+
+async fn serve(listener: TcpListener, store: Store, mut shutdown: oneshot::Receiver<()>) {
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, store.clone()));
+                }
+                Err(_) => break,
+            },
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, store: Store) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    while let Ok(Some(command)) = read_command(&mut reader).await {
+        let mut out = Vec::new();
+        execute(&store, command).encode(&mut out);
+        if writer.write_all(&out).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+}
+
+/// 读取一条命令：`*<n>` 后跟 n 个 `$<len>` 块；也接受空格分隔的内联命令
+async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<Vec<String>>> {
+    let Some(line) = read_line(reader).await? else {
+        return Ok(None);
+    };
+    let Some(count) = line.strip_prefix('*') else {
+        return Ok(Some(line.split_whitespace().map(str::to_string).collect()));
+    };
+
+    let count: usize = count.parse().map_err(|_| invalid_data("无效的参数个数"))?;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        let header = read_line(reader).await?.ok_or_else(|| invalid_data("命令不完整"))?;
+        let len: usize = header
+            .strip_prefix('$')
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(|| invalid_data("无效的参数长度"))?;
+        let mut buf = vec![0; len + 2];
+        reader.read_exact(&mut buf).await?;
+        buf.truncate(len);
+        args.push(String::from_utf8(buf).map_err(|_| invalid_data("参数不是 UTF-8"))?);
+    }
+    Ok(Some(args))
+}
+
+fn not_integer() -> Reply {
+    Reply::Error("ERR value is not an integer or out of range".to_string())
+}
+
+fn syntax_error() -> Reply {
+    Reply::Error("ERR syntax error".to_string())
+}
+
+fn execute(store: &Store, command: Vec<String>) -> Reply {
+    let Some((name, args)) = command.split_first() else {
+        return Reply::Error("ERR empty command".to_string());
+    };
+    let name = name.to_ascii_uppercase();
+    let now = Instant::now();
+    let mut entries = store.lock();
+    entries.retain(|_, entry| entry.expires_at.map_or(true, |at| at > now));
+
+    match (name.as_str(), args) {
+        ("PING", []) => Reply::Simple("PONG"),
+        ("PING", [message]) => Reply::Bulk(Some(message.clone())),
+        ("SELECT", [_]) => Reply::Simple("OK"),
+        ("GET", [key]) => Reply::Bulk(entries.get(key).map(|entry| entry.value.clone())),
+        ("MGET", keys) if !keys.is_empty() => Reply::Array(
+            keys.iter()
+                .map(|key| Reply::Bulk(entries.get(key).map(|entry| entry.value.clone())))
+                .collect(),
+        ),
+        ("SET", [key, value, options @ ..]) => {
+            let expires_at = match options {
+                [] => None,
+                [flag, seconds] if flag.eq_ignore_ascii_case("EX") => match seconds.parse() {
+                    Ok(seconds) => Some(now + Duration::from_secs(seconds)),
+                    Err(_) => return not_integer(),
+                },
+                _ => return syntax_error(),
+            };
+            entries.insert(key.clone(), Entry { value: value.clone(), expires_at });
+            Reply::Simple("OK")
+        }
+        ("SETEX", [key, seconds, value]) => match seconds.parse() {
+            Ok(seconds) => {
+                let expires_at = Some(now + Duration::from_secs(seconds));
+                entries.insert(key.clone(), Entry { value: value.clone(), expires_at });
+                Reply::Simple("OK")
+            }
+            Err(_) => not_integer(),
+        },
+        ("DEL", keys) if !keys.is_empty() => {
+            Reply::Integer(keys.iter().filter(|key| entries.remove(*key).is_some()).count() as i64)
+        }
+        ("EXISTS", keys) if !keys.is_empty() => {
+            Reply::Integer(keys.iter().filter(|key| entries.contains_key(*key)).count() as i64)
+        }
+        ("EXPIRE", [key, seconds]) => match seconds.parse::<i64>() {
+            Ok(_) if !entries.contains_key(key) => Reply::Integer(0),
+            Ok(seconds) if seconds <= 0 => {
+                entries.remove(key);
+                Reply::Integer(1)
+            }
+            Ok(seconds) => {
+                if let Some(entry) = entries.get_mut(key) {
+                    entry.expires_at = Some(now + Duration::from_secs(seconds as u64));
+                }
+                Reply::Integer(1)
+            }
+            Err(_) => not_integer(),
+        },
+        ("TTL", [key]) => Reply::Integer(match entries.get(key) {
+            None => -2,
+            Some(Entry { expires_at: None, .. }) => -1,
+            Some(Entry { expires_at: Some(at), .. }) => at.saturating_duration_since(now).as_secs() as i64,
+        }),
+        ("INCR", [key]) => increment(&mut entries, key, 1),
+        ("DECR", [key]) => increment(&mut entries, key, -1),
+        ("INCRBY", [key, delta]) => match delta.parse() {
+            Ok(delta) => increment(&mut entries, key, delta),
+            Err(_) => not_integer(),
+        },
+        ("DECRBY", [key, delta]) => match delta.parse::<i64>() {
+            Ok(delta) => increment(&mut entries, key, -delta),
+            Err(_) => not_integer(),
+        },
+        ("KEYS", [pattern]) => Reply::Array(matching_keys(&entries, pattern)),
+        ("SCAN", [_cursor, options @ ..]) => {
+            let mut pattern = "*";
+            for option in options.chunks(2) {
+                match option {
+                    [flag, value] if flag.eq_ignore_ascii_case("MATCH") => pattern = value,
+                    [flag, _] if flag.eq_ignore_ascii_case("COUNT") || flag.eq_ignore_ascii_case("TYPE") => {}
+                    _ => return syntax_error(),
+                }
+            }
+            // 一次返回全部匹配的键，游标直接回到 0
+            Reply::Array(vec![
+                Reply::Bulk(Some("0".to_string())),
+                Reply::Array(matching_keys(&entries, pattern)),
+            ])
+        }
+        ("DBSIZE", []) => Reply::Integer(entries.len() as i64),
+        ("FLUSHDB" | "FLUSHALL", _) => {
+            entries.clear();
+            Reply::Simple("OK")
+        }
+        _ => Reply::Error(format!("ERR unknown command or wrong number of arguments for '{}'", name)),
+    }
+}
+
+fn increment(entries: &mut HashMap<String, Entry>, key: &str, delta: i64) -> Reply {
+    let entry = entries.entry(key.to_string()).or_insert_with(|| Entry {
+        value: "0".to_string(),
+        expires_at: None,
+    });
+    match entry.value.parse::<i64>().ok().and_then(|value| value.checked_add(delta)) {
+        Some(value) => {
+            entry.value = value.to_string();
+            Reply::Integer(value)
+        }
+        None => not_integer(),
+    }
+}
+
+fn matching_keys(entries: &HashMap<String, Entry>, pattern: &str) -> Vec<Reply> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let mut keys: Vec<&String> = entries
+        .keys()
+        .filter(|key| glob_match(&pattern, &key.chars().collect::<Vec<_>>()))
+        .collect();
+    keys.sort();
+    keys.into_iter().map(|key| Reply::Bulk(Some(key.clone()))).collect()
+}
+
+/// Redis 的 glob 匹配（支持 `*`、`?` 和 `\` 转义）
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob_match(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some(('\\', [escaped, rest @ ..])) => text.first() == Some(escaped) && glob_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::backends::{CacheDatabaseBackend, DatabaseBackend, DatabaseConfig};
+    use crate::database::redis_backend::RedisBackend;
+    use serde_json::json;
+
+    async fn connect(redis: &MemoryRedis) -> RedisBackend {
+        let mut backend = RedisBackend::new();
+        backend.connect(&DatabaseConfig::redis(&redis.url())).await.unwrap();
+        backend
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let matches = |pattern: &str, text: &str| {
+            glob_match(&pattern.chars().collect::<Vec<_>>(), &text.chars().collect::<Vec<_>>())
+        };
+        assert!(matches("zishu:*", "zishu:cache:1"));
+        assert!(matches("a?c", "abc"));
+        assert!(matches(r"a\*", "a*"));
+        assert!(!matches(r"a\*", "ab"));
+        assert!(!matches("zishu:*", "other:1"));
+    }
+
+    #[tokio::test]
+    async fn test_redis_backend_against_memory_redis() {
+        let redis = MemoryRedis::start().unwrap();
+        let backend = connect(&redis).await;
+
+        backend.insert("sessions", "1", &json!({"name": "紫舒"})).await.unwrap();
+        assert!(backend.insert("sessions", "1", &json!({})).await.is_err());
+        assert_eq!(backend.get("sessions", "1").await.unwrap(), Some(json!({"name": "紫舒"})));
+        assert!(backend.collection_exists("sessions").await.unwrap());
+
+        backend.set_with_expiry("token", &json!("abc"), 60).await.unwrap();
+        assert_eq!(backend.get_cache("token").await.unwrap(), Some(json!("abc")));
+        assert!(backend.ttl("token").await.unwrap().is_some_and(|ttl| ttl > 0 && ttl <= 60));
+
+        assert_eq!(backend.increment("counter", 5).await.unwrap(), 5);
+        assert_eq!(backend.decrement("counter", 2).await.unwrap(), 3);
+
+        assert_eq!(backend.delete_matching("*sessions:*").await.unwrap(), 1);
+        assert_eq!(backend.get("sessions", "1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_keys_are_removed() {
+        let redis = MemoryRedis::start().unwrap();
+        let backend = connect(&redis).await;
+
+        backend.set_with_expiry("short", &json!(1), 60).await.unwrap();
+        backend.expire("short", 0).await.unwrap();
+        assert!(!backend.exists("short").await.unwrap());
+    }
+}
@@ -0,0 +1,29 @@
+//! 测试支持工具
+//!
+//! 仅在启用 `test-support` 特性时编译，供集成测试使用：
+//!
+//! - [`builders`]：角色、适配器、工作流等核心结构的构建器，带合理的默认值
+//! - [`fakes`]：与数据库注册表接口一致的内存实现
+//! - [`strategies`]：核心结构的 proptest 策略
+//! - [`environment`]：端到端测试环境，[`init_for_tests`] 启动一次性数据库并初始化全局实例
+//! - [`local_postgres`]：用本机 PostgreSQL 程序启动的临时集群
+//! - [`memory_redis`]：内存 Redis，实现缓存用到的命令
+//! - [`stub_qdrant`]：Qdrant gRPC 桩服务，数据保存在内存中
+//!
+//! ```toml
+//! [dev-dependencies]
+//! zishu-sensei = { path = ".", features = ["test-support"] }
+//! ```
+
+pub mod builders;
+pub mod environment;
+pub mod fakes;
+pub mod local_postgres;
+pub mod memory_redis;
+mod service_thread;
+pub mod strategies;
+pub mod stub_qdrant;
+
+pub use builders::{AdapterBuilder, CharacterBuilder, WorkflowBuilder};
+pub use environment::{init_for_tests, TestEnvError, TestEnvironment};
+pub use fakes::{InMemoryAdapterRegistry, InMemoryCharacterRegistry, InMemoryWorkflowRegistry};
@@ -0,0 +1,65 @@
+//! 测试用本地服务的运行线程
+//!
+//! 服务运行在独立线程的运行时中，不依赖测试自身的运行时：`#[tokio::test]` 默认是单线程
+//! 运行时，而 Qdrant 客户端构建时会阻塞当前线程做健康检查，共用线程时双方会互相等待。
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// 在本地随机端口上运行的服务，释放时通知服务退出
+pub(super) struct ServiceThread {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl ServiceThread {
+    /// 绑定 `127.0.0.1` 的随机端口并在新线程中运行 `serve`，`serve` 在收到关闭信号后返回
+    pub(super) fn spawn<F, Fut>(name: &str, serve: F) -> io::Result<Self>
+    where
+        F: FnOnce(TcpListener, oneshot::Receiver<()>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()>,
+    {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let (shutdown, signal) = oneshot::channel();
+        let service = name.to_string();
+
+        std::thread::Builder::new().name(name.to_string()).spawn(move || {
+            runtime.block_on(async move {
+                match TcpListener::from_std(listener) {
+                    Ok(listener) => serve(listener, signal).await,
+                    Err(e) => warn!("{} 启动失败: {}", service, e),
+                }
+            })
+        })?;
+
+        Ok(Self {
+            addr,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    pub(super) fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 通知服务退出，不等待线程结束
+    pub(super) fn stop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl Drop for ServiceThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
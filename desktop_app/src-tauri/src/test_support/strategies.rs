@@ -0,0 +1,135 @@
+//! 核心结构的 proptest 策略
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn roundtrip(workflow in strategies::workflow_definition()) {
+//!         let json = serde_json::to_string(&workflow).unwrap();
+//!         prop_assert!(serde_json::from_str::<WorkflowDefinition>(&json).is_ok());
+//!     }
+//! }
+//! ```
+
+use proptest::prelude::*;
+use serde_json::json;
+
+use super::builders::{AdapterBuilder, CharacterBuilder, WorkflowBuilder};
+use crate::database::adapter::{AdapterInstallStatus, InstalledAdapter};
+use crate::database::character_registry::CharacterData;
+use crate::database::workflow::{WorkflowDefinition, WorkflowStatus};
+
+/// 标识符：小写字母开头，由小写字母、数字、`-`、`_` 组成
+pub fn identifier() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9_-]{0,31}"
+}
+
+/// 语义化版本号
+pub fn semver() -> impl Strategy<Value = String> {
+    (0u32..20, 0u32..50, 0u32..100).prop_map(|(major, minor, patch)| format!("{}.{}.{}", major, minor, patch))
+}
+
+/// 显示名称（包含中文与空格）
+pub fn display_name() -> impl Strategy<Value = String> {
+    "[a-zA-Z\u{4e00}-\u{9fa5}][a-zA-Z0-9 \u{4e00}-\u{9fa5}]{0,23}"
+}
+
+pub fn workflow_status() -> impl Strategy<Value = WorkflowStatus> {
+    prop_oneof![
+        Just(WorkflowStatus::Draft),
+        Just(WorkflowStatus::Published),
+        Just(WorkflowStatus::Archived),
+        Just(WorkflowStatus::Disabled),
+    ]
+}
+
+pub fn adapter_install_status() -> impl Strategy<Value = AdapterInstallStatus> {
+    prop_oneof![
+        Just(AdapterInstallStatus::Downloading),
+        Just(AdapterInstallStatus::Installing),
+        Just(AdapterInstallStatus::Installed),
+        Just(AdapterInstallStatus::InstallFailed),
+        Just(AdapterInstallStatus::Updating),
+        Just(AdapterInstallStatus::UpdateFailed),
+        Just(AdapterInstallStatus::Uninstalling),
+        Just(AdapterInstallStatus::UninstallFailed),
+    ]
+}
+
+pub fn character_data() -> impl Strategy<Value = CharacterData> {
+    (
+        identifier(),
+        display_name(),
+        prop::collection::vec(identifier(), 0..6),
+        prop::collection::vec(identifier(), 0..6),
+        any::<bool>(),
+    )
+        .prop_map(|(id, name, motions, expressions, active)| {
+            let builder = CharacterBuilder::new(id)
+                .display_name(name)
+                .motions(motions)
+                .expressions(expressions);
+            if active { builder.active() } else { builder }.build()
+        })
+}
+
+pub fn installed_adapter() -> impl Strategy<Value = InstalledAdapter> {
+    (
+        identifier(),
+        semver(),
+        adapter_install_status(),
+        any::<bool>(),
+        prop::option::of(identifier()),
+    )
+        .prop_map(|(id, version, status, enabled, market_id)| {
+            let mut builder = AdapterBuilder::new(id).version(version).status(status);
+            if !enabled {
+                builder = builder.disabled();
+            }
+            if let Some(market_id) = market_id {
+                builder = builder.from_market(market_id);
+            }
+            builder.build()
+        })
+}
+
+pub fn workflow_definition() -> impl Strategy<Value = WorkflowDefinition> {
+    (
+        identifier(),
+        display_name(),
+        workflow_status(),
+        identifier(),
+        prop::collection::vec(prop_oneof![Just("llm"), Just("adapter"), Just("delay"), Just("condition")], 0..8),
+        any::<bool>(),
+    )
+        .prop_map(|(id, name, status, category, steps, is_template)| {
+            let mut builder = WorkflowBuilder::new(id).name(name).status(status).category(category);
+            for step in steps {
+                builder = builder.step(step, json!({}));
+            }
+            if is_template {
+                builder = builder.template();
+            }
+            builder.build()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_workflow_definition_serde_roundtrip(workflow in workflow_definition()) {
+            let json = serde_json::to_string(&workflow).unwrap();
+            let parsed: WorkflowDefinition = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(parsed.id, workflow.id);
+            prop_assert_eq!(parsed.status, workflow.status);
+        }
+
+        #[test]
+        fn test_adapter_status_display_parses_back(status in adapter_install_status()) {
+            let parsed: AdapterInstallStatus = status.to_string().parse().unwrap();
+            prop_assert_eq!(parsed.to_string(), status.to_string());
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Qdrant 桩服务
+//!
+//! 在本地端口上提供 Qdrant 的 gRPC 接口，实现 `QdrantBackend` 用到的调用（健康检查、集合增删查、
+//! 点的写入/读取/删除/滚动查询与余弦相似度搜索），数据保存在内存中。其余接口返回
+//! `Unimplemented`。与真实服务的差异：读取点时总是返回 payload、不返回向量；按条件过滤只支持空条件。
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use ::qdrant_client::qdrant::collections_server::{Collections, CollectionsServer};
+use ::qdrant_client::qdrant::points_server::{Points, PointsServer};
+use ::qdrant_client::qdrant::qdrant_server::{Qdrant, QdrantServer};
+use ::qdrant_client::qdrant::*;
+use tokio::sync::oneshot;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::warn;
+
+use super::service_thread::ServiceThread;
+
+/// 健康检查报告的版本，与 qdrant-client 一致，避免客户端打印版本不兼容的提示
+const STUB_VERSION: &str = "1.19.0";
+
+/// 点 ID，数字 ID 排在 UUID 之前（与 Qdrant 的滚动顺序一致）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum PointKey {
+    Num(u64),
+    Uuid(String),
+}
+
+impl PointKey {
+    fn from_id(id: Option<&PointId>) -> Result<Self, Status> {
+        match id.and_then(|id| id.point_id_options.as_ref()) {
+            Some(point_id::PointIdOptions::Num(n)) => Ok(Self::Num(*n)),
+            Some(point_id::PointIdOptions::Uuid(u)) => Ok(Self::Uuid(u.clone())),
+            None => Err(Status::invalid_argument("缺少点 ID")),
+        }
+    }
+
+    fn to_id(&self) -> PointId {
+        match self {
+            Self::Num(n) => (*n).into(),
+            Self::Uuid(u) => u.clone().into(),
+        }
+    }
+}
+
+struct StoredPoint {
+    vector: Vec<f32>,
+    payload: HashMap<String, Value>,
+}
+
+struct Collection {
+    vector_size: u64,
+    distance: i32,
+    points: BTreeMap<PointKey, StoredPoint>,
+}
+
+#[derive(Clone, Default)]
+struct StubState {
+    collections: Arc<Mutex<HashMap<String, Collection>>>,
+}
+
+impl StubState {
+    fn with_collection<T>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&mut Collection) -> Result<T, Status>,
+    ) -> Result<T, Status> {
+        let mut collections = self.collections.lock();
+        let collection = collections
+            .get_mut(name)
+            .ok_or_else(|| Status::not_found(format!("Collection `{}` doesn't exist!", name)))?;
+        f(collection)
+    }
+}
+
+/// Qdrant 桩服务，释放时停止
+pub struct StubQdrant {
+    service: ServiceThread,
+}
+
+impl StubQdrant {
+    /// 在本地随机端口上启动
+    pub fn start() -> io::Result<Self> {
+        let state = StubState::default();
+        let service = ServiceThread::spawn("stub-qdrant", move |listener, shutdown| serve(listener, state, shutdown))?;
+        Ok(Self { service })
+    }
+
+    /// gRPC 地址
+    pub fn url(&self) -> String {
+        format!("http://{}", self.service.addr())
+    }
+
+    /// 停止服务
+    pub fn stop(&mut self) {
+        self.service.stop();
+    }
+}
+
+async fn serve(listener: tokio::net::TcpListener, state: StubState, shutdown: oneshot::Receiver<()>) {
+    let result = Server::builder()
+        .add_service(QdrantServer::new(state.clone()))
+        .add_service(CollectionsServer::new(state.clone()))
+        .add_service(PointsServer::new(state))
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+            let _ = shutdown.await;
+        })
+        .await;
+    if let Err(e) = result {
+        warn!("Qdrant 桩服务异常退出: {}", e);
+    }
+}
+
+/// 生成服务实现：`methods` 中手写的方法加上对其余方法返回 `Unimplemented` 的实现
+macro_rules! stub_service {
+    (
+        impl $service:ident {
+            $($methods:tt)*
+        }
+        unimplemented {
+            $($method:ident($request:ident) -> $response:ident;)*
+        }
+    ) => {
+        #[async_trait]
+        impl $service for StubState {
+            $($methods)*
+
+            $(
+                async fn $method(&self, _request: Request<$request>) -> Result<Response<$response>, Status> {
+                    Err(Status::unimplemented(concat!("Qdrant 桩服务未实现 ", stringify!($method))))
+                }
+            )*
+        }
+    };
+}
+
+#[async_trait]
+impl Qdrant for StubState {
+    async fn health_check(&self, _request: Request<HealthCheckRequest>) -> Result<Response<HealthCheckReply>, Status> {
+        Ok(Response::new(HealthCheckReply {
+            title: "qdrant stub".to_string(),
+            version: STUB_VERSION.to_string(),
+            commit: None,
+        }))
+    }
+}
+
+stub_service! {
+    impl Collections {
+        async fn get(&self, request: Request<GetCollectionInfoRequest>) -> Result<Response<GetCollectionInfoResponse>, Status> {
+            let name = request.into_inner().collection_name;
+            let info = self.with_collection(&name, |collection| {
+                Ok(CollectionInfo {
+                    status: CollectionStatus::Green.into(),
+                    points_count: Some(collection.points.len() as u64),
+                    config: Some(CollectionConfig {
+                        params: Some(CollectionParams {
+                            vectors_config: Some(VectorsConfig {
+                                config: Some(vectors_config::Config::Params(VectorParams {
+                                    size: collection.vector_size,
+                                    distance: collection.distance,
+                                    ..Default::default()
+                                })),
+                            }),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            })?;
+            Ok(Response::new(GetCollectionInfoResponse { result: Some(info), time: 0.0 }))
+        }
+
+        async fn list(&self, _request: Request<ListCollectionsRequest>) -> Result<Response<ListCollectionsResponse>, Status> {
+            let mut collections: Vec<CollectionDescription> = self
+                .collections
+                .lock()
+                .keys()
+                .map(|name| CollectionDescription { name: name.clone() })
+                .collect();
+            collections.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(Response::new(ListCollectionsResponse { collections, time: 0.0 }))
+        }
+
+        async fn create(&self, request: Request<CreateCollection>) -> Result<Response<CollectionOperationResponse>, Status> {
+            let request = request.into_inner();
+            let params = match request.vectors_config.and_then(|config| config.config) {
+                Some(vectors_config::Config::Params(params)) => params,
+                _ => return Err(Status::invalid_argument("桩服务只支持单个未命名向量")),
+            };
+            let mut collections = self.collections.lock();
+            if collections.contains_key(&request.collection_name) {
+                return Err(Status::already_exists(format!(
+                    "Collection `{}` already exists!",
+                    request.collection_name
+                )));
+            }
+            collections.insert(
+                request.collection_name,
+                Collection {
+                    vector_size: params.size,
+                    distance: params.distance,
+                    points: BTreeMap::new(),
+                },
+            );
+            Ok(Response::new(CollectionOperationResponse { result: true, time: 0.0 }))
+        }
+
+        async fn delete(&self, request: Request<DeleteCollection>) -> Result<Response<CollectionOperationResponse>, Status> {
+            let removed = self.collections.lock().remove(&request.into_inner().collection_name).is_some();
+            Ok(Response::new(CollectionOperationResponse { result: removed, time: 0.0 }))
+        }
+
+        async fn collection_exists(&self, request: Request<CollectionExistsRequest>) -> Result<Response<CollectionExistsResponse>, Status> {
+            let exists = self.collections.lock().contains_key(&request.into_inner().collection_name);
+            Ok(Response::new(CollectionExistsResponse {
+                result: Some(CollectionExists { exists }),
+                time: 0.0,
+            }))
+        }
+    }
+    unimplemented {
+        update(UpdateCollection) -> CollectionOperationResponse;
+        update_aliases(ChangeAliases) -> CollectionOperationResponse;
+        list_collection_aliases(ListCollectionAliasesRequest) -> ListAliasesResponse;
+        list_aliases(ListAliasesRequest) -> ListAliasesResponse;
+        collection_cluster_info(CollectionClusterInfoRequest) -> CollectionClusterInfoResponse;
+        update_collection_cluster_setup(UpdateCollectionClusterSetupRequest) -> UpdateCollectionClusterSetupResponse;
+        create_shard_key(CreateShardKeyRequest) -> CreateShardKeyResponse;
+        delete_shard_key(DeleteShardKeyRequest) -> DeleteShardKeyResponse;
+        list_shard_keys(ListShardKeysRequest) -> ListShardKeysResponse;
+    }
+}
+
+fn completed() -> Response<PointsOperationResponse> {
+    Response::new(PointsOperationResponse {
+        result: Some(UpdateResult {
+            operation_id: Some(0),
+            status: UpdateStatus::Completed.into(),
+        }),
+        ..Default::default()
+    })
+}
+
+/// 稠密向量；旧版客户端把数据放在已弃用的 `data` 字段中
+#[allow(deprecated)]
+fn dense_vector(vectors: Option<Vectors>) -> Result<Vec<f32>, Status> {
+    match vectors.and_then(|vectors| vectors.vectors_options) {
+        Some(vectors::VectorsOptions::Vector(vector)) => match vector.vector {
+            Some(vector::Vector::Dense(dense)) => Ok(dense.data),
+            None if !vector.data.is_empty() => Ok(vector.data),
+            _ => Err(Status::invalid_argument("桩服务只支持稠密向量")),
+        },
+        _ => Err(Status::invalid_argument("桩服务只支持单个未命名向量")),
+    }
+}
+
+/// 选中的点；按条件选择只支持空条件（全部点）
+fn selected_keys(collection: &Collection, selector: Option<PointsSelector>) -> Result<Vec<PointKey>, Status> {
+    match selector.and_then(|selector| selector.points_selector_one_of) {
+        Some(points_selector::PointsSelectorOneOf::Points(list)) => {
+            list.ids.iter().map(|id| PointKey::from_id(Some(id))).collect()
+        }
+        Some(points_selector::PointsSelectorOneOf::Filter(filter)) => {
+            ensure_empty_filter(Some(&filter))?;
+            Ok(collection.points.keys().cloned().collect())
+        }
+        None => Err(Status::invalid_argument("缺少点选择条件")),
+    }
+}
+
+fn ensure_empty_filter(filter: Option<&Filter>) -> Result<(), Status> {
+    match filter {
+        Some(filter) if *filter != Filter::default() => Err(Status::unimplemented("桩服务不支持按条件过滤")),
+        _ => Ok(()),
+    }
+}
+
+fn retrieved(key: &PointKey, point: &StoredPoint) -> RetrievedPoint {
+    RetrievedPoint {
+        id: Some(key.to_id()),
+        payload: point.payload.clone(),
+        ..Default::default()
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+stub_service! {
+    impl Points {
+        async fn upsert(&self, request: Request<UpsertPoints>) -> Result<Response<PointsOperationResponse>, Status> {
+            let request = request.into_inner();
+            self.with_collection(&request.collection_name, |collection| {
+                for point in request.points {
+                    let key = PointKey::from_id(point.id.as_ref())?;
+                    let vector = dense_vector(point.vectors)?;
+                    if vector.len() as u64 != collection.vector_size {
+                        return Err(Status::invalid_argument(format!(
+                            "Wrong input: Vector dimension error: expected dim: {}, got {}",
+                            collection.vector_size,
+                            vector.len()
+                        )));
+                    }
+                    collection.points.insert(key, StoredPoint { vector, payload: point.payload });
+                }
+                Ok(())
+            })?;
+            Ok(completed())
+        }
+
+        async fn delete(&self, request: Request<DeletePoints>) -> Result<Response<PointsOperationResponse>, Status> {
+            let request = request.into_inner();
+            self.with_collection(&request.collection_name, |collection| {
+                for key in selected_keys(collection, request.points)? {
+                    collection.points.remove(&key);
+                }
+                Ok(())
+            })?;
+            Ok(completed())
+        }
+
+        async fn get(&self, request: Request<GetPoints>) -> Result<Response<GetResponse>, Status> {
+            let request = request.into_inner();
+            let result = self.with_collection(&request.collection_name, |collection| {
+                let mut result = Vec::new();
+                for id in &request.ids {
+                    let key = PointKey::from_id(Some(id))?;
+                    if let Some(point) = collection.points.get(&key) {
+                        result.push(retrieved(&key, point));
+                    }
+                }
+                Ok(result)
+            })?;
+            Ok(Response::new(GetResponse { result, ..Default::default() }))
+        }
+
+        async fn set_payload(&self, request: Request<SetPayloadPoints>) -> Result<Response<PointsOperationResponse>, Status> {
+            let request = request.into_inner();
+            self.with_collection(&request.collection_name, |collection| {
+                for key in selected_keys(collection, request.points_selector)? {
+                    if let Some(point) = collection.points.get_mut(&key) {
+                        point.payload.extend(request.payload.clone());
+                    }
+                }
+                Ok(())
+            })?;
+            Ok(completed())
+        }
+
+        async fn search(&self, request: Request<SearchPoints>) -> Result<Response<SearchResponse>, Status> {
+            let request = request.into_inner();
+            ensure_empty_filter(request.filter.as_ref())?;
+            let result = self.with_collection(&request.collection_name, |collection| {
+                let mut scored: Vec<ScoredPoint> = collection
+                    .points
+                    .iter()
+                    .map(|(key, point)| ScoredPoint {
+                        id: Some(key.to_id()),
+                        payload: point.payload.clone(),
+                        score: cosine_similarity(&request.vector, &point.vector),
+                        ..Default::default()
+                    })
+                    .filter(|point| request.score_threshold.map_or(true, |threshold| point.score >= threshold))
+                    .collect();
+                scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+                Ok(scored
+                    .into_iter()
+                    .skip(request.offset.unwrap_or(0) as usize)
+                    .take(request.limit as usize)
+                    .collect())
+            })?;
+            Ok(Response::new(SearchResponse { result, ..Default::default() }))
+        }
+
+        async fn scroll(&self, request: Request<ScrollPoints>) -> Result<Response<ScrollResponse>, Status> {
+            let request = request.into_inner();
+            ensure_empty_filter(request.filter.as_ref())?;
+            let (result, next_page_offset) = self.with_collection(&request.collection_name, |collection| {
+                let start = request.offset.as_ref().map(|id| PointKey::from_id(Some(id))).transpose()?;
+                let limit = request.limit.unwrap_or(10) as usize;
+                let mut points = collection
+                    .points
+                    .iter()
+                    .filter(|(key, _)| start.as_ref().map_or(true, |start| *key >= start));
+                let result: Vec<RetrievedPoint> = points.by_ref().take(limit).map(|(key, point)| retrieved(key, point)).collect();
+                Ok((result, points.next().map(|(key, _)| key.to_id())))
+            })?;
+            Ok(Response::new(ScrollResponse { result, next_page_offset, ..Default::default() }))
+        }
+
+        async fn count(&self, request: Request<CountPoints>) -> Result<Response<CountResponse>, Status> {
+            let request = request.into_inner();
+            ensure_empty_filter(request.filter.as_ref())?;
+            let count = self.with_collection(&request.collection_name, |collection| Ok(collection.points.len() as u64))?;
+            Ok(Response::new(CountResponse {
+                result: Some(CountResult { count }),
+                ..Default::default()
+            }))
+        }
+    }
+    unimplemented {
+        update_vectors(UpdatePointVectors) -> PointsOperationResponse;
+        delete_vectors(DeletePointVectors) -> PointsOperationResponse;
+        overwrite_payload(SetPayloadPoints) -> PointsOperationResponse;
+        delete_payload(DeletePayloadPoints) -> PointsOperationResponse;
+        clear_payload(ClearPayloadPoints) -> PointsOperationResponse;
+        create_field_index(CreateFieldIndexCollection) -> PointsOperationResponse;
+        delete_field_index(DeleteFieldIndexCollection) -> PointsOperationResponse;
+        create_vector_name(CreateVectorNameRequest) -> PointsOperationResponse;
+        delete_vector_name(DeleteVectorNameRequest) -> PointsOperationResponse;
+        search_batch(SearchBatchPoints) -> SearchBatchResponse;
+        search_groups(SearchPointGroups) -> SearchGroupsResponse;
+        recommend(RecommendPoints) -> RecommendResponse;
+        recommend_batch(RecommendBatchPoints) -> RecommendBatchResponse;
+        recommend_groups(RecommendPointGroups) -> RecommendGroupsResponse;
+        discover(DiscoverPoints) -> DiscoverResponse;
+        discover_batch(DiscoverBatchPoints) -> DiscoverBatchResponse;
+        update_batch(UpdateBatchPoints) -> UpdateBatchResponse;
+        query(QueryPoints) -> QueryResponse;
+        query_batch(QueryBatchPoints) -> QueryBatchResponse;
+        query_groups(QueryPointGroups) -> QueryGroupsResponse;
+        facet(FacetCounts) -> FacetResponse;
+        search_matrix_pairs(SearchMatrixPoints) -> SearchMatrixPairsResponse;
+        search_matrix_offsets(SearchMatrixPoints) -> SearchMatrixOffsetsResponse;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::backends::{DatabaseBackend, DatabaseConfig, VectorDatabaseBackend};
+    use crate::database::qdrant_backend::QdrantBackend;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_qdrant_backend_against_stub() {
+        let qdrant = StubQdrant::start().unwrap();
+        let mut backend = QdrantBackend::new();
+        backend.connect(&DatabaseConfig::qdrant(&qdrant.url())).await.unwrap();
+        backend.ping().await.unwrap();
+
+        let schema = json!({"vector_size": 3}).to_string();
+        backend.create_collection("memories", Some(&schema)).await.unwrap();
+        assert!(backend.collection_exists("memories").await.unwrap());
+        assert!(backend.create_collection("memories", Some(&schema)).await.is_err());
+
+        backend
+            .batch_insert_vectors(
+                "memories",
+                vec![
+                    ("1".to_string(), vec![1.0, 0.0, 0.0], json!({"text": "猫"})),
+                    ("2".to_string(), vec![0.0, 1.0, 0.0], json!({"text": "狗"})),
+                ],
+            )
+            .await
+            .unwrap();
+        assert!(backend
+            .insert_vector("memories", "3", vec![1.0, 0.0], &json!({}))
+            .await
+            .is_err());
+
+        let results = backend.vector_search("memories", vec![0.9, 0.1, 0.0], 1, None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "1");
+        assert_eq!(results[0].payload["text"], "猫");
+
+        backend.update("memories", "2", &json!({"text": "小狗"})).await.unwrap();
+        assert_eq!(backend.get("memories", "2").await.unwrap().unwrap()["text"], "小狗");
+        assert_eq!(backend.count("memories", None).await.unwrap(), 2);
+        assert_eq!(backend.query("memories", &Default::default()).await.unwrap().len(), 2);
+
+        backend.delete_vector("memories", "1").await.unwrap();
+        assert_eq!(backend.get("memories", "1").await.unwrap(), None);
+        backend.clear_collection("memories").await.unwrap();
+        assert_eq!(backend.count("memories", None).await.unwrap(), 0);
+
+        backend.drop_collection("memories").await.unwrap();
+        assert!(!backend.collection_exists("memories").await.unwrap());
+    }
+}
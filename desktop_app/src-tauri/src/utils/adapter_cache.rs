@@ -0,0 +1,676 @@
+//! # 适配器执行结果缓存
+//!
+//! 适配器能力在元数据中声明 `cache` 策略后，其执行结果按“适配器 + 操作 + 规范化参数”的
+//! 哈希缓存。参数规范化会对对象键排序、把整数值的浮点数写成整数，因此键顺序或 `1.0`/`1`
+//! 的差异不会导致缓存未命中。
+//!
+//! 缓存有全局条目数与字节数上限，能力也可以单独限制条目数；超出时按最近最少使用淘汰。
+//! 同时按适配器与操作统计执行次数、失败次数、耗时与缓存命中情况，作为适配器使用分析数据。
+//!
+//! 用户可以按适配器关闭缓存或改写有效期（[`AdapterCacheSettings`]）。开启持久化时
+//! `commands::adapter` 还会把结果写入 Redis，重启后内存中没有的结果从 Redis 取回。
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::utils::clock::{self, SharedClock};
+
+/// 默认缓存有效期（秒）
+const DEFAULT_TTL_SECS: u64 = 3600;
+/// 默认最多缓存的条目数
+const DEFAULT_MAX_ENTRIES: usize = 512;
+/// 默认最多占用的字节数（按结果的 JSON 序列化长度计）
+const DEFAULT_MAX_BYTES: usize = 32 * 1024 * 1024;
+/// 用户可设置的最长有效期（秒）
+pub const MAX_TTL_SECS: u64 = 7 * 24 * 3600;
+
+fn default_ttl_secs() -> u64 {
+    DEFAULT_TTL_SECS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 能力的缓存策略（在适配器元数据的能力描述中声明）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachePolicy {
+    /// 有效期（秒）
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+    /// 该能力最多缓存的条目数
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            ttl_secs: DEFAULT_TTL_SECS,
+            max_entries: None,
+        }
+    }
+}
+
+/// 用户对单个适配器的缓存设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdapterCacheOverride {
+    /// 关闭后该适配器的结果不再缓存
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 有效期（秒），为空时使用能力声明的值
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// 结果缓存设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdapterCacheSettings {
+    /// 同时把结果写入 Redis（重启后仍可命中）
+    #[serde(default = "default_true")]
+    pub persist: bool,
+    /// 按适配器 ID 的设置
+    #[serde(default)]
+    pub adapters: BTreeMap<String, AdapterCacheOverride>,
+}
+
+impl Default for AdapterCacheSettings {
+    fn default() -> Self {
+        Self {
+            persist: true,
+            adapters: BTreeMap::new(),
+        }
+    }
+}
+
+impl AdapterCacheSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for (adapter_id, adapter) in &self.adapters {
+            if let Some(ttl) = adapter.ttl_secs {
+                if ttl == 0 || ttl > MAX_TTL_SECS {
+                    return Err(format!("适配器 {} 的缓存有效期必须在 1 到 {} 秒之间", adapter_id, MAX_TTL_SECS));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 结合用户设置得到实际的缓存策略；能力没有声明缓存（非幂等操作）时始终不缓存
+    pub fn effective_policy(&self, adapter_id: &str, declared: Option<CachePolicy>) -> Option<CachePolicy> {
+        let mut policy = declared?;
+        if let Some(adapter) = self.adapters.get(adapter_id) {
+            if !adapter.enabled {
+                return None;
+            }
+            if let Some(ttl) = adapter.ttl_secs {
+                policy.ttl_secs = ttl;
+            }
+        }
+        Some(policy)
+    }
+}
+
+/// 缓存结果的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheSource {
+    Memory,
+    Redis,
+}
+
+/// 执行响应中的缓存信息，界面据此标注“缓存结果”
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheMetadata {
+    /// 缓存键（适配器 + 操作 + 参数的内容哈希）
+    pub key: String,
+    /// 结果是否来自缓存
+    pub cached: bool,
+    pub source: Option<CacheSource>,
+    /// 结果写入缓存的时间与过期时间（未写入缓存时为空）
+    pub stored_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CacheMetadata {
+    pub fn hit(key: String, result: &CachedResult, source: CacheSource) -> Self {
+        Self {
+            key,
+            cached: true,
+            source: Some(source),
+            stored_at: Some(result.stored_at),
+            expires_at: Some(result.expires_at),
+        }
+    }
+
+    pub fn miss(key: String, stored: Option<&CachedResult>) -> Self {
+        Self {
+            key,
+            cached: false,
+            source: None,
+            stored_at: stored.map(|result| result.stored_at),
+            expires_at: stored.map(|result| result.expires_at),
+        }
+    }
+}
+
+/// 缓存的执行结果（Redis 中也以此格式保存）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub value: serde_json::Value,
+    pub stored_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 全局缓存上限
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CacheLimits {
+    pub max_entries: usize,
+    pub max_bytes: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+/// 把 JSON 值写成规范形式：对象键排序，整数值的浮点数写成整数
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    use serde_json::Value;
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Number(number) => match number.as_f64() {
+            Some(f) if number.is_f64() && f.fract() == 0.0 && f.abs() < 1e15 => out.push_str(&(f as i64).to_string()),
+            _ => out.push_str(&number.to_string()),
+        },
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// 计算缓存键
+pub fn cache_key(adapter_id: &str, action: &str, params: &HashMap<String, serde_json::Value>) -> String {
+    let mut canonical = String::new();
+    write_canonical(
+        &serde_json::Value::Object(params.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+        &mut canonical,
+    );
+    let mut hasher = Sha256::new();
+    for part in [adapter_id, action, canonical.as_str()] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    adapter_id: String,
+    action: String,
+    result: CachedResult,
+    size_bytes: usize,
+    /// 最近访问序号（越大越新）
+    last_access: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ActionUsage {
+    executions: u64,
+    failures: u64,
+    total_duration_ms: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    cache_evictions: u64,
+    cache_expirations: u64,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+/// 适配器某个操作的使用统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdapterUsageStats {
+    pub adapter_id: String,
+    pub action: String,
+    /// 实际执行（未命中缓存）的次数
+    pub executions: u64,
+    pub failures: u64,
+    pub average_duration_ms: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// 命中率（未查询过缓存时为 0）
+    pub cache_hit_rate: f64,
+    pub cache_evictions: u64,
+    pub cache_expirations: u64,
+    /// 当前缓存的条目数与字节数
+    pub cached_entries: usize,
+    pub cached_bytes: usize,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// 适配器执行结果缓存
+#[derive(Debug)]
+pub struct AdapterResultCache {
+    clock: SharedClock,
+    limits: CacheLimits,
+    entries: HashMap<String, CacheEntry>,
+    usage: BTreeMap<(String, String), ActionUsage>,
+    total_bytes: usize,
+    access_counter: u64,
+}
+
+impl AdapterResultCache {
+    pub fn new(limits: CacheLimits, clock: SharedClock) -> Self {
+        Self {
+            clock,
+            limits,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            total_bytes: 0,
+            access_counter: 0,
+        }
+    }
+
+    fn usage_mut(&mut self, adapter_id: &str, action: &str) -> &mut ActionUsage {
+        self.usage
+            .entry((adapter_id.to_string(), action.to_string()))
+            .or_default()
+    }
+
+    fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.remove(key)?;
+        self.total_bytes -= entry.size_bytes;
+        Some(entry)
+    }
+
+    /// 查询缓存，过期条目视为未命中并移除
+    pub fn get(&mut self, key: &str, adapter_id: &str, action: &str) -> Option<CachedResult> {
+        let now = self.clock.now();
+        let expired = match self.entries.get(key) {
+            Some(entry) => entry.result.expires_at <= now,
+            None => false,
+        };
+        if expired {
+            self.remove(key);
+            self.usage_mut(adapter_id, action).cache_expirations += 1;
+        }
+
+        self.access_counter += 1;
+        let access = self.access_counter;
+        let value = self.entries.get_mut(key).map(|entry| {
+            entry.last_access = access;
+            entry.result.clone()
+        });
+        let usage = self.usage_mut(adapter_id, action);
+        if value.is_some() {
+            usage.cache_hits += 1;
+            usage.last_used_at = Some(now);
+        } else {
+            usage.cache_misses += 1;
+        }
+        value
+    }
+
+    /// 写入缓存并返回缓存的结果；单个结果超过字节上限时不缓存
+    pub fn put(
+        &mut self,
+        key: String,
+        adapter_id: &str,
+        action: &str,
+        value: serde_json::Value,
+        policy: &CachePolicy,
+    ) -> Option<CachedResult> {
+        if policy.ttl_secs == 0 {
+            return None;
+        }
+        let now = self.clock.now();
+        let ttl = Duration::seconds(policy.ttl_secs.min(i64::MAX as u64 / 1000) as i64);
+        let result = CachedResult { value, stored_at: now, expires_at: now + ttl };
+        self.insert(key, adapter_id, action, result.clone(), policy).then_some(result)
+    }
+
+    /// 写入从 Redis 取回的结果，并把本次内存未命中改记为命中
+    pub fn promote(&mut self, key: String, adapter_id: &str, action: &str, result: CachedResult, policy: &CachePolicy) {
+        let usage = self.usage_mut(adapter_id, action);
+        usage.cache_misses = usage.cache_misses.saturating_sub(1);
+        usage.cache_hits += 1;
+        self.insert(key, adapter_id, action, result, policy);
+    }
+
+    fn insert(&mut self, key: String, adapter_id: &str, action: &str, result: CachedResult, policy: &CachePolicy) -> bool {
+        let size_bytes = serde_json::to_vec(&result.value).map(|v| v.len()).unwrap_or(usize::MAX);
+        if size_bytes > self.limits.max_bytes || policy.max_entries == Some(0) || result.expires_at <= self.clock.now() {
+            return false;
+        }
+
+        self.remove(&key);
+        self.access_counter += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                adapter_id: adapter_id.to_string(),
+                action: action.to_string(),
+                result,
+                size_bytes,
+                last_access: self.access_counter,
+            },
+        );
+        self.total_bytes += size_bytes;
+
+        if let Some(max_entries) = policy.max_entries {
+            while self.count_entries(Some(adapter_id), Some(action)) > max_entries {
+                self.evict_lru(Some((adapter_id, action)));
+            }
+        }
+        self.enforce_limits();
+        true
+    }
+
+    fn count_entries(&self, adapter_id: Option<&str>, action: Option<&str>) -> usize {
+        self.entries
+            .values()
+            .filter(|e| matches_scope(e, adapter_id, action))
+            .count()
+    }
+
+    /// 淘汰最近最少使用的条目（可限定在某个操作内）
+    fn evict_lru(&mut self, scope: Option<(&str, &str)>) {
+        let victim = self
+            .entries
+            .iter()
+            .filter(|(_, e)| match scope {
+                Some((adapter_id, action)) => e.adapter_id == adapter_id && e.action == action,
+                None => true,
+            })
+            .min_by_key(|(_, e)| e.last_access)
+            .map(|(key, _)| key.clone());
+        if let Some(entry) = victim.and_then(|key| self.remove(&key)) {
+            self.usage_mut(&entry.adapter_id, &entry.action).cache_evictions += 1;
+        }
+    }
+
+    fn enforce_limits(&mut self) {
+        while !self.entries.is_empty()
+            && (self.entries.len() > self.limits.max_entries || self.total_bytes > self.limits.max_bytes)
+        {
+            self.evict_lru(None);
+        }
+    }
+
+    /// 记录一次实际执行
+    pub fn record_execution(&mut self, adapter_id: &str, action: &str, duration: std::time::Duration, success: bool) {
+        let now = self.clock.now();
+        let usage = self.usage_mut(adapter_id, action);
+        usage.executions += 1;
+        if !success {
+            usage.failures += 1;
+        }
+        usage.total_duration_ms += duration.as_millis() as u64;
+        usage.last_used_at = Some(now);
+    }
+
+    /// 移除缓存条目；不指定适配器时清空全部，返回移除的条目数
+    pub fn invalidate(&mut self, adapter_id: Option<&str>, action: Option<&str>) -> usize {
+        let keys: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| matches_scope(e, adapter_id, action))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.remove(key);
+        }
+        keys.len()
+    }
+
+    /// 移除所有过期条目
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.result.expires_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            if let Some(entry) = self.remove(key) {
+                self.usage_mut(&entry.adapter_id, &entry.action).cache_expirations += 1;
+            }
+        }
+        expired.len()
+    }
+
+    /// 使用统计（可限定适配器）
+    pub fn usage_stats(&self, adapter_id: Option<&str>) -> Vec<AdapterUsageStats> {
+        self.usage
+            .iter()
+            .filter(|((id, _), _)| match adapter_id {
+                Some(filter) => id == filter,
+                None => true,
+            })
+            .map(|((id, action), usage)| {
+                let cached: Vec<&CacheEntry> = self
+                    .entries
+                    .values()
+                    .filter(|e| matches_scope(e, Some(id.as_str()), Some(action.as_str())))
+                    .collect();
+                let lookups = usage.cache_hits + usage.cache_misses;
+                AdapterUsageStats {
+                    adapter_id: id.clone(),
+                    action: action.clone(),
+                    executions: usage.executions,
+                    failures: usage.failures,
+                    average_duration_ms: if usage.executions > 0 {
+                        usage.total_duration_ms as f64 / usage.executions as f64
+                    } else {
+                        0.0
+                    },
+                    cache_hits: usage.cache_hits,
+                    cache_misses: usage.cache_misses,
+                    cache_hit_rate: if lookups > 0 { usage.cache_hits as f64 / lookups as f64 } else { 0.0 },
+                    cache_evictions: usage.cache_evictions,
+                    cache_expirations: usage.cache_expirations,
+                    cached_entries: cached.len(),
+                    cached_bytes: cached.iter().map(|e| e.size_bytes).sum(),
+                    last_used_at: usage.last_used_at,
+                }
+            })
+            .collect()
+    }
+}
+
+fn matches_scope(entry: &CacheEntry, adapter_id: Option<&str>, action: Option<&str>) -> bool {
+    let adapter_matches = match adapter_id {
+        Some(id) => entry.adapter_id == id,
+        None => true,
+    };
+    let action_matches = match action {
+        Some(a) => entry.action == a,
+        None => true,
+    };
+    adapter_matches && action_matches
+}
+
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<AdapterResultCache> =
+        Mutex::new(AdapterResultCache::new(CacheLimits::default(), clock::global()));
+    static ref SETTINGS: RwLock<AdapterCacheSettings> = RwLock::new(AdapterCacheSettings::default());
+}
+
+/// 当前的缓存设置
+pub fn settings() -> AdapterCacheSettings {
+    SETTINGS.read().clone()
+}
+
+/// 替换缓存设置
+pub fn set_settings(settings: AdapterCacheSettings) {
+    *SETTINGS.write() = settings;
+}
+
+/// 按当前设置得到能力的缓存策略
+pub fn effective_policy(adapter_id: &str, declared: Option<CachePolicy>) -> Option<CachePolicy> {
+    SETTINGS.read().effective_policy(adapter_id, declared)
+}
+
+/// 查询全局缓存
+pub fn lookup(key: &str, adapter_id: &str, action: &str) -> Option<CachedResult> {
+    CACHE.lock().get(key, adapter_id, action)
+}
+
+/// 写入全局缓存
+pub fn store(key: String, adapter_id: &str, action: &str, value: serde_json::Value, policy: &CachePolicy) -> Option<CachedResult> {
+    CACHE.lock().put(key, adapter_id, action, value, policy)
+}
+
+/// 写入从 Redis 取回的结果
+pub fn promote(key: String, adapter_id: &str, action: &str, result: CachedResult, policy: &CachePolicy) {
+    CACHE.lock().promote(key, adapter_id, action, result, policy);
+}
+
+/// 记录一次实际执行
+pub fn record_execution(adapter_id: &str, action: &str, duration: std::time::Duration, success: bool) {
+    CACHE.lock().record_execution(adapter_id, action, duration, success);
+}
+
+/// 移除全局缓存中的条目
+pub fn invalidate(adapter_id: Option<&str>, action: Option<&str>) -> usize {
+    CACHE.lock().invalidate(adapter_id, action)
+}
+
+/// 全局使用统计（先清理过期条目，保证缓存占用数据准确）
+pub fn usage_stats(adapter_id: Option<&str>) -> Vec<AdapterUsageStats> {
+    let mut cache = CACHE.lock();
+    cache.purge_expired();
+    cache.usage_stats(adapter_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::{Clock, ManualClock};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn params(value: serde_json::Value) -> HashMap<String, serde_json::Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_is_normalized() {
+        let a = cache_key("ocr", "recognize", &params(json!({ "lang": "zh", "opts": { "dpi": 300.0, "fast": true } })));
+        let b = cache_key("ocr", "recognize", &params(json!({ "opts": { "fast": true, "dpi": 300 }, "lang": "zh" })));
+        let c = cache_key("ocr", "recognize", &params(json!({ "lang": "en", "opts": { "dpi": 300, "fast": true } })));
+        let d = cache_key("ocr", "detect", &params(json!({ "lang": "zh", "opts": { "dpi": 300, "fast": true } })));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+    }
+
+    #[test]
+    fn test_ttl_and_statistics() {
+        let clock = Arc::new(ManualClock::starting_now());
+        let mut cache = AdapterResultCache::new(CacheLimits::default(), clock.clone());
+        let policy = CachePolicy { ttl_secs: 60, max_entries: None };
+
+        assert!(cache.get("k", "ocr", "recognize").is_none());
+        cache.record_execution("ocr", "recognize", std::time::Duration::from_millis(200), true);
+        assert!(cache.put("k".to_string(), "ocr", "recognize", json!({ "text": "你好" }), &policy).is_some());
+        assert_eq!(cache.get("k", "ocr", "recognize").map(|r| r.value), Some(json!({ "text": "你好" })));
+
+        clock.advance(Duration::seconds(61));
+        assert!(cache.get("k", "ocr", "recognize").is_none());
+
+        let stats = cache.usage_stats(Some("ocr"));
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].executions, 1);
+        assert_eq!(stats[0].cache_hits, 1);
+        assert_eq!(stats[0].cache_misses, 2);
+        assert_eq!(stats[0].cache_expirations, 1);
+        assert_eq!(stats[0].cached_entries, 0);
+        assert!((stats[0].cache_hit_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_limits_evict_least_recently_used() {
+        let clock = Arc::new(ManualClock::starting_now());
+        let mut cache = AdapterResultCache::new(CacheLimits { max_entries: 3, max_bytes: 1024 }, clock);
+        let policy = CachePolicy::default();
+        let capped = CachePolicy { ttl_secs: 60, max_entries: Some(1) };
+
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), "tts", "speak", json!(key), &policy);
+        }
+        // 访问 a 后再写入 d，应淘汰最久未使用的 b
+        cache.get("a", "tts", "speak");
+        cache.put("d".to_string(), "tts", "speak", json!("d"), &policy);
+        assert!(cache.get("b", "tts", "speak").is_none());
+        assert!(cache.get("a", "tts", "speak").is_some());
+
+        // 能力自身的条目上限
+        cache.put("x".to_string(), "ocr", "recognize", json!(1), &capped);
+        cache.put("y".to_string(), "ocr", "recognize", json!(2), &capped);
+        assert!(cache.get("x", "ocr", "recognize").is_none());
+        assert!(cache.get("y", "ocr", "recognize").is_some());
+
+        // 超过字节上限的结果不缓存
+        assert!(cache.put("big".to_string(), "ocr", "recognize", json!("x".repeat(2048)), &policy).is_none());
+
+        assert_eq!(cache.invalidate(Some("tts"), None), 2);
+        assert_eq!(cache.usage_stats(Some("tts"))[0].cached_entries, 0);
+    }
+
+    #[test]
+    fn test_overrides_and_promotion() {
+        let declared = CachePolicy { ttl_secs: 60, max_entries: None };
+        let mut settings = AdapterCacheSettings::default();
+        settings.adapters.insert("translate".to_string(), AdapterCacheOverride { enabled: true, ttl_secs: Some(600) });
+        settings.adapters.insert("format".to_string(), AdapterCacheOverride { enabled: false, ttl_secs: None });
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.effective_policy("translate", Some(declared.clone())).unwrap().ttl_secs, 600);
+        assert_eq!(settings.effective_policy("other", Some(declared.clone())), Some(declared.clone()));
+        assert!(settings.effective_policy("format", Some(declared.clone())).is_none());
+        // 用户设置不能为没有声明缓存的操作开启缓存
+        assert!(settings.effective_policy("translate", None).is_none());
+        settings.adapters.get_mut("translate").unwrap().ttl_secs = Some(0);
+        assert!(settings.validate().is_err());
+
+        // Redis 命中后写回内存，统计记为命中，过期时间沿用原结果
+        let clock = Arc::new(ManualClock::starting_now());
+        let mut cache = AdapterResultCache::new(CacheLimits::default(), clock.clone());
+        let now = clock.now();
+        let stored = CachedResult { value: json!("hola"), stored_at: now, expires_at: now + Duration::seconds(30) };
+        assert!(cache.get("k", "translate", "run").is_none());
+        cache.promote("k".to_string(), "translate", "run", stored.clone(), &declared);
+        assert_eq!(cache.get("k", "translate", "run"), Some(stored));
+        let stats = cache.usage_stats(Some("translate"));
+        assert_eq!((stats[0].cache_hits, stats[0].cache_misses), (2, 0));
+        clock.advance(Duration::seconds(31));
+        assert!(cache.get("k", "translate", "run").is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! # 适配器开发模式
+//!
+//! 开发者把应用指向本地的适配器源码目录，目录根部的 `adapter.json`（见 [`DevAdapterManifest`]）
+//! 声明适配器 ID、版本与入口文件。开发中的适配器不经过签名校验，以来源 `dev` 登记到已安装
+//! 适配器中（元数据带 `dev` 标记），由应用直接启动入口进程；目录中的文件变化后重启进程。
+//!
+//! 进程管理、文件监控与 `dev-adapter-log` 事件见 `commands::adapter_dev`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::utils::live2d_watcher::is_temporary_file;
+
+/// 开发目录中的清单文件名
+pub const MANIFEST_FILE: &str = "adapter.json";
+/// 开发模式适配器在已安装适配器中的来源
+pub const DEV_SOURCE: &str = "dev";
+/// 默认不监控的目录（依赖、构建产物与缓存）
+const DEFAULT_IGNORED: &[&str] = &["node_modules", "__pycache__", "venv", "target", "dist", "build"];
+
+/// 开发中的适配器清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevAdapterManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// 入口文件（相对于开发目录）
+    pub entry: String,
+    /// 启动命令；为空时按入口文件的扩展名推断（`.py` 用 Python，`.js` 用 Node，其他直接执行）
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// 额外的环境变量
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// 额外不监控的目录或文件名
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// 能力声明，原样写入已安装适配器的元数据
+    #[serde(default)]
+    pub capabilities: Vec<serde_json::Value>,
+}
+
+/// 只允许开发目录内的相对路径
+fn is_contained(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+impl DevAdapterManifest {
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_id = !self.id.is_empty()
+            && self.id.len() <= 64
+            && self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err("适配器 ID 只能包含字母、数字、`-` 与 `_`，且不超过 64 个字符".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err("适配器名称不能为空".to_string());
+        }
+        let (core, _) = self.version.split_once('-').unwrap_or((&self.version, ""));
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 || parts.iter().any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit())) {
+            return Err(format!("适配器版本必须是 x.y.z 格式: {}", self.version));
+        }
+        if !is_contained(&self.entry) {
+            return Err(format!("入口文件必须是开发目录内的相对路径: {}", self.entry));
+        }
+        if self.command.first().is_some_and(|program| program.trim().is_empty()) {
+            return Err("启动命令不能为空".to_string());
+        }
+        Ok(())
+    }
+
+    /// 启动程序与参数；`./` 开头的程序解析为开发目录中的文件
+    pub fn launch_command(&self, dir: &Path) -> (PathBuf, Vec<String>) {
+        let mut command = if self.command.is_empty() {
+            let python = if cfg!(windows) { "python" } else { "python3" };
+            match Path::new(&self.entry).extension().and_then(|ext| ext.to_str()) {
+                Some("py") => vec![python.to_string(), "-u".to_string(), self.entry.clone()],
+                Some("js") | Some("mjs") => vec!["node".to_string(), self.entry.clone()],
+                _ => vec![format!("./{}", self.entry.trim_start_matches("./"))],
+            }
+        } else {
+            self.command.clone()
+        };
+        let program = command.remove(0);
+        let program = match program.strip_prefix("./") {
+            Some(relative) => dir.join(relative),
+            None => PathBuf::from(program),
+        };
+        (program, command)
+    }
+
+    /// 目录中的变化是否需要重新加载（忽略编辑器临时文件、隐藏目录与依赖目录）
+    pub fn is_relevant_change(&self, dir: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(dir) else {
+            return false;
+        };
+        let parts: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .collect();
+        !parts.is_empty()
+            && parts.iter().all(|part| {
+                !is_temporary_file(part)
+                    && !DEFAULT_IGNORED.contains(part)
+                    && !self.ignore.iter().any(|ignored| ignored == part)
+            })
+    }
+}
+
+/// 读取并校验开发目录中的清单，入口文件必须存在
+pub fn load_manifest(dir: &Path) -> Result<DevAdapterManifest, String> {
+    if !dir.is_dir() {
+        return Err(format!("适配器目录不存在: {}", dir.display()));
+    }
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("读取 {} 失败: {}", MANIFEST_FILE, e))?;
+    let manifest: DevAdapterManifest =
+        serde_json::from_str(&content).map_err(|e| format!("解析 {} 失败: {}", MANIFEST_FILE, e))?;
+    manifest.validate()?;
+    if !dir.join(&manifest.entry).is_file() {
+        return Err(format!("入口文件不存在: {}", manifest.entry));
+    }
+    Ok(manifest)
+}
+
+/// 适配器进程的输出流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DevLogStream {
+    Stdout,
+    Stderr,
+    /// 开发模式自身的消息（启动、退出、重新加载、清单错误）
+    System,
+}
+
+/// `dev-adapter-log` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct DevLogLine {
+    pub adapter_id: String,
+    pub stream: DevLogStream,
+    pub line: String,
+    /// 第几次加载（首次为 1，每次重新加载加 1）
+    pub generation: u32,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(entry: &str) -> DevAdapterManifest {
+        serde_json::from_value(serde_json::json!({
+            "id": "weather_dev",
+            "name": "Weather",
+            "version": "0.1.0-alpha",
+            "entry": entry,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_manifest_validation() {
+        assert!(manifest("main.py").validate().is_ok());
+        assert!(manifest("../outside.py").validate().is_err());
+        assert!(manifest("/abs/main.py").validate().is_err());
+
+        let mut bad = manifest("main.py");
+        bad.version = "1.0".to_string();
+        assert!(bad.validate().is_err());
+        bad.version = "1.0.0".to_string();
+        bad.id = "weather dev".to_string();
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_launch_command() {
+        let dir = Path::new("/work/weather");
+        let (program, args) = manifest("src/main.py").launch_command(dir);
+        assert!(program.to_string_lossy().starts_with("python"));
+        assert_eq!(args, vec!["-u", "src/main.py"]);
+
+        let (program, args) = manifest("run.sh").launch_command(dir);
+        assert_eq!(program, dir.join("run.sh"));
+        assert!(args.is_empty());
+
+        let mut custom = manifest("index.ts");
+        custom.command = vec!["deno".to_string(), "run".to_string(), "index.ts".to_string()];
+        assert_eq!(custom.launch_command(dir), (PathBuf::from("deno"), vec!["run".to_string(), "index.ts".to_string()]));
+    }
+
+    #[test]
+    fn test_relevant_changes() {
+        let dir = Path::new("/work/weather");
+        let mut manifest = manifest("main.py");
+        manifest.ignore.push("fixtures".to_string());
+
+        assert!(manifest.is_relevant_change(dir, &dir.join("main.py")));
+        assert!(manifest.is_relevant_change(dir, &dir.join("adapter.json")));
+        assert!(!manifest.is_relevant_change(dir, &dir.join(".git/index")));
+        assert!(!manifest.is_relevant_change(dir, &dir.join("__pycache__/main.cpython-311.pyc")));
+        assert!(!manifest.is_relevant_change(dir, &dir.join("fixtures/data.json")));
+        assert!(!manifest.is_relevant_change(dir, &dir.join("main.py.swp")));
+        assert!(!manifest.is_relevant_change(Path::new("/other"), &dir.join("main.py")));
+    }
+}
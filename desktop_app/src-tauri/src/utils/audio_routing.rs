@@ -0,0 +1,198 @@
+//! 音频设备路由
+//!
+//! 为录音、唤醒词和语音播放分别指定设备（为空表示跟随系统默认设备）。
+//! 指定的设备被拔出时回退到系统默认设备，重新插入后自动恢复。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+lazy_static::lazy_static! {
+    static ref AUDIO_ROUTING: RwLock<AudioRoutingSettings> = RwLock::new(AudioRoutingSettings::default());
+}
+
+/// 使用音频设备的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioFeature {
+    Recording,
+    Hotword,
+    TtsOutput,
+}
+
+impl AudioFeature {
+    pub const ALL: [AudioFeature; 3] = [AudioFeature::Recording, AudioFeature::Hotword, AudioFeature::TtsOutput];
+
+    pub fn is_input(self) -> bool {
+        !matches!(self, AudioFeature::TtsOutput)
+    }
+}
+
+/// 各功能的设备路由设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioRoutingSettings {
+    pub recording_device: Option<String>,
+    pub hotword_device: Option<String>,
+    pub tts_output_device: Option<String>,
+}
+
+impl AudioRoutingSettings {
+    pub fn device_for(&self, feature: AudioFeature) -> Option<&str> {
+        match feature {
+            AudioFeature::Recording => self.recording_device.as_deref(),
+            AudioFeature::Hotword => self.hotword_device.as_deref(),
+            AudioFeature::TtsOutput => self.tts_output_device.as_deref(),
+        }
+    }
+}
+
+pub fn current_settings() -> AudioRoutingSettings {
+    AUDIO_ROUTING.read().clone()
+}
+
+pub fn set_settings(settings: AudioRoutingSettings) {
+    *AUDIO_ROUTING.write() = settings;
+}
+
+/// 设备选择结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceChoice {
+    /// 实际使用的设备名（为空表示没有可用设备）
+    pub device: Option<String>,
+    /// 指定的设备不可用，已回退到默认设备
+    pub fell_back: bool,
+}
+
+/// 在可用设备中选择：优先使用指定设备，不可用时回退到默认设备
+pub fn choose_device(preferred: Option<&str>, available: &[String], default: Option<&str>) -> DeviceChoice {
+    match preferred {
+        Some(name) if available.iter().any(|d| d == name) => DeviceChoice {
+            device: Some(name.to_string()),
+            fell_back: false,
+        },
+        Some(_) => DeviceChoice {
+            device: default.map(str::to_string),
+            fell_back: true,
+        },
+        None => DeviceChoice {
+            device: default.map(str::to_string),
+            fell_back: false,
+        },
+    }
+}
+
+/// 设备列表快照
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceSnapshot {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub default_input: Option<String>,
+    pub default_output: Option<String>,
+}
+
+/// 两次快照之间的设备变化
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceChanges {
+    pub added_inputs: Vec<String>,
+    pub removed_inputs: Vec<String>,
+    pub added_outputs: Vec<String>,
+    pub removed_outputs: Vec<String>,
+    pub default_input_changed: bool,
+    pub default_output_changed: bool,
+}
+
+impl DeviceChanges {
+    pub fn is_empty(&self) -> bool {
+        self == &DeviceChanges::default()
+    }
+
+    /// 变化是否影响指定功能当前使用的设备
+    pub fn affects(&self, feature: AudioFeature, routing: &AudioRoutingSettings, current: &DeviceSnapshot) -> bool {
+        let (added, removed, available, default_changed) = if feature.is_input() {
+            (&self.added_inputs, &self.removed_inputs, &current.inputs, self.default_input_changed)
+        } else {
+            (&self.added_outputs, &self.removed_outputs, &current.outputs, self.default_output_changed)
+        };
+        match routing.device_for(feature) {
+            // 指定设备被拔出（回退）或重新插入（恢复）
+            Some(name) if added.iter().chain(removed).any(|d| d == name) => true,
+            // 指定设备不在时使用的是默认设备
+            Some(name) => default_changed && !available.iter().any(|d| d == name),
+            None => default_changed,
+        }
+    }
+}
+
+fn difference(a: &[String], b: &[String]) -> Vec<String> {
+    let b: BTreeSet<&String> = b.iter().collect();
+    a.iter().filter(|d| !b.contains(d)).cloned().collect()
+}
+
+/// 比较两次设备快照
+pub fn diff_devices(previous: &DeviceSnapshot, current: &DeviceSnapshot) -> DeviceChanges {
+    DeviceChanges {
+        added_inputs: difference(&current.inputs, &previous.inputs),
+        removed_inputs: difference(&previous.inputs, &current.inputs),
+        added_outputs: difference(&current.outputs, &previous.outputs),
+        removed_outputs: difference(&previous.outputs, &current.outputs),
+        default_input_changed: previous.default_input != current.default_input,
+        default_output_changed: previous.default_output != current.default_output,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_choose_device_falls_back_to_default() {
+        let available = names(&["Built-in Mic", "USB Mic"]);
+        assert_eq!(
+            choose_device(Some("USB Mic"), &available, Some("Built-in Mic")),
+            DeviceChoice { device: Some("USB Mic".into()), fell_back: false }
+        );
+        assert_eq!(
+            choose_device(Some("Headset"), &available, Some("Built-in Mic")),
+            DeviceChoice { device: Some("Built-in Mic".into()), fell_back: true }
+        );
+        assert!(!choose_device(None, &available, Some("Built-in Mic")).fell_back);
+    }
+
+    #[test]
+    fn test_diff_and_affects() {
+        let previous = DeviceSnapshot {
+            inputs: names(&["Built-in Mic", "USB Mic"]),
+            outputs: names(&["Speakers"]),
+            default_input: Some("USB Mic".into()),
+            default_output: Some("Speakers".into()),
+        };
+        let current = DeviceSnapshot {
+            inputs: names(&["Built-in Mic"]),
+            default_input: Some("Built-in Mic".into()),
+            ..previous.clone()
+        };
+        let changes = diff_devices(&previous, &current);
+        assert_eq!(changes.removed_inputs, names(&["USB Mic"]));
+        assert!(changes.default_input_changed);
+        assert!(!changes.default_output_changed);
+
+        let routing = AudioRoutingSettings {
+            recording_device: Some("USB Mic".into()),
+            ..Default::default()
+        };
+        assert!(changes.affects(AudioFeature::Recording, &routing, &current));
+        assert!(changes.affects(AudioFeature::Hotword, &routing, &current));
+        assert!(!changes.affects(AudioFeature::TtsOutput, &routing, &current));
+
+        let pinned = AudioRoutingSettings {
+            hotword_device: Some("Built-in Mic".into()),
+            ..Default::default()
+        };
+        assert!(!changes.affects(AudioFeature::Hotword, &pinned, &current));
+    }
+}
@@ -0,0 +1,374 @@
+//! 本地自动化接口协议
+//!
+//! 每行一个 JSON-RPC 请求/响应（`{"id":1,"method":"notify","params":{...}}`），
+//! 通过 Unix socket（Windows 上为命名管道）与本机脚本通信。连接建立后必须先调用
+//! `auth`（参数 `token` 与可选的 `client` 名称），之后的调用按方法映射到权限表中
+//! `automation` 实体的授权检查。连接、认证与每次调用都会交给 [`RpcHandler::audit`] 记录。
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::database::permission::{PermissionLevel, PermissionType};
+
+/// 单行请求的最大字节数
+pub const MAX_LINE_BYTES: u64 = 64 * 1024;
+/// 认证失败次数达到上限后断开连接
+pub const MAX_AUTH_FAILURES: u32 = 3;
+/// 未提供名称时的客户端名
+pub const DEFAULT_CLIENT: &str = "default";
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+pub const UNAUTHORIZED: i32 = -32001;
+pub const FORBIDDEN: i32 = -32003;
+
+/// RPC 请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// RPC 错误
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+}
+
+/// RPC 响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    fn reply(id: Option<Value>, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(value) => (Some(value), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: "2.0".to_string(), id, result, error }
+    }
+}
+
+/// 可调用的方法及所需权限
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub permission: Option<(PermissionType, PermissionLevel)>,
+}
+
+/// 支持的方法
+pub fn methods() -> Vec<MethodSpec> {
+    vec![
+        MethodSpec { name: "ping", description: "检查连接", permission: None },
+        MethodSpec {
+            name: "notify",
+            description: "显示系统通知（params: title?, body）",
+            permission: Some((PermissionType::SystemNotification, PermissionLevel::Write)),
+        },
+        MethodSpec {
+            name: "workflow.list",
+            description: "列出工作流",
+            permission: Some((PermissionType::Custom("workflow_list".to_string()), PermissionLevel::Read)),
+        },
+        MethodSpec {
+            name: "workflow.run",
+            description: "运行工作流（params: workflow_id, input?）",
+            permission: Some((PermissionType::Custom("workflow_run".to_string()), PermissionLevel::Write)),
+        },
+    ]
+}
+
+pub fn find_method(name: &str) -> Option<MethodSpec> {
+    methods().into_iter().find(|spec| spec.name == name)
+}
+
+/// 生成随机访问令牌
+pub fn generate_token() -> String {
+    use rand::RngCore;
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 以固定时间比较令牌
+pub fn tokens_match(expected: &str, provided: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), provided.as_bytes());
+    if a.len() != b.len() || a.is_empty() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 审计事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Connected,
+    Authenticated,
+    AuthFailed,
+    Call,
+    Denied,
+    Disconnected,
+}
+
+/// 连接审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: i64,
+    pub connection_id: String,
+    pub client: Option<String>,
+    pub event: AuditEvent,
+    pub method: Option<String>,
+    pub success: bool,
+    pub detail: Option<String>,
+}
+
+/// 方法实现与审计
+#[async_trait]
+pub trait RpcHandler: Send + Sync {
+    fn verify_token(&self, token: &str) -> bool;
+    /// 检查客户端是否有权调用方法，无权时返回原因
+    async fn authorize(&self, client: &str, method: &MethodSpec) -> Result<(), String>;
+    async fn call(&self, client: &str, method: &str, params: Value) -> Result<Value, RpcError>;
+    fn audit(&self, entry: AuditEntry);
+}
+
+struct Session<'a, H: RpcHandler + ?Sized> {
+    handler: &'a H,
+    connection_id: String,
+    client: Option<String>,
+    auth_failures: u32,
+}
+
+impl<H: RpcHandler + ?Sized> Session<'_, H> {
+    fn audit(&self, event: AuditEvent, method: Option<&str>, success: bool, detail: Option<String>) {
+        self.handler.audit(AuditEntry {
+            timestamp: chrono::Utc::now().timestamp(),
+            connection_id: self.connection_id.clone(),
+            client: self.client.clone(),
+            event,
+            method: method.map(str::to_string),
+            success,
+            detail,
+        });
+    }
+
+    fn authenticate(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let token = params.get("token").and_then(Value::as_str).unwrap_or_default();
+        let client = params
+            .get("client")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_CLIENT)
+            .to_string();
+        if !self.handler.verify_token(token) {
+            self.auth_failures += 1;
+            self.audit(AuditEvent::AuthFailed, Some("auth"), false, Some(format!("client={}", client)));
+            return Err(RpcError::new(UNAUTHORIZED, "令牌无效"));
+        }
+        self.client = Some(client.clone());
+        self.audit(AuditEvent::Authenticated, Some("auth"), true, None);
+        Ok(serde_json::json!({ "client": client }))
+    }
+
+    async fn dispatch(&mut self, request: RpcRequest) -> Result<Value, RpcError> {
+        if request.method == "auth" {
+            return self.authenticate(&request.params);
+        }
+        let Some(client) = self.client.clone() else {
+            return Err(RpcError::new(UNAUTHORIZED, "请先调用 auth 认证"));
+        };
+        let spec = find_method(&request.method)
+            .ok_or_else(|| RpcError::new(METHOD_NOT_FOUND, format!("未知方法: {}", request.method)))?;
+        if spec.permission.is_some() {
+            if let Err(reason) = self.handler.authorize(&client, &spec).await {
+                self.audit(AuditEvent::Denied, Some(spec.name), false, Some(reason.clone()));
+                return Err(RpcError::new(FORBIDDEN, reason));
+            }
+        }
+        let result = self.handler.call(&client, spec.name, request.params).await;
+        self.audit(
+            AuditEvent::Call,
+            Some(spec.name),
+            result.is_ok(),
+            result.as_ref().err().map(|e| e.message.clone()),
+        );
+        result
+    }
+}
+
+/// 处理一个连接直到对方关闭、发送超长请求或认证失败次数过多
+pub async fn serve_connection<S, H>(stream: S, handler: &H, connection_id: String) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    H: RpcHandler + ?Sized,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut session = Session { handler, connection_id, client: None, auth_failures: 0 };
+    session.audit(AuditEvent::Connected, None, true, None);
+
+    let result = loop {
+        let mut line = String::new();
+        let read = match (&mut reader).take(MAX_LINE_BYTES).read_line(&mut line).await {
+            Ok(read) => read,
+            Err(e) => break Err(e),
+        };
+        if read == 0 {
+            break Ok(());
+        }
+        if !line.ends_with('\n') && read as u64 >= MAX_LINE_BYTES {
+            let response = RpcResponse::reply(None, Err(RpcError::new(INVALID_REQUEST, "请求过长")));
+            let _ = write_response(&mut writer, &response).await;
+            break Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<RpcRequest>(&line) {
+            Ok(request) => {
+                let id = request.id.clone();
+                RpcResponse::reply(id, session.dispatch(request).await)
+            }
+            Err(e) => RpcResponse::reply(None, Err(RpcError::new(PARSE_ERROR, format!("请求格式错误: {}", e)))),
+        };
+        if let Err(e) = write_response(&mut writer, &response).await {
+            break Err(e);
+        }
+        if session.auth_failures >= MAX_AUTH_FAILURES {
+            break Ok(());
+        }
+    };
+
+    session.audit(AuditEvent::Disconnected, None, result.is_ok(), result.as_ref().err().map(|e| e.to_string()));
+    result
+}
+
+async fn write_response<W: AsyncWrite + Unpin>(writer: &mut W, response: &RpcResponse) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(response).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    struct TestHandler {
+        audit: Mutex<Vec<AuditEntry>>,
+    }
+
+    #[async_trait]
+    impl RpcHandler for TestHandler {
+        fn verify_token(&self, token: &str) -> bool {
+            tokens_match("secret", token)
+        }
+
+        async fn authorize(&self, client: &str, method: &MethodSpec) -> Result<(), String> {
+            if client == "trusted" {
+                Ok(())
+            } else {
+                Err(format!("{} 无权调用 {}", client, method.name))
+            }
+        }
+
+        async fn call(&self, _client: &str, method: &str, params: Value) -> Result<Value, RpcError> {
+            Ok(serde_json::json!({ "method": method, "params": params }))
+        }
+
+        fn audit(&self, entry: AuditEntry) {
+            self.audit.lock().push(entry);
+        }
+    }
+
+    async fn exchange(input: &str) -> (Vec<RpcResponse>, Vec<AuditEntry>) {
+        let handler = TestHandler { audit: Mutex::new(Vec::new()) };
+        let (client, server) = tokio::io::duplex(MAX_LINE_BYTES as usize * 2);
+        let (mut client_read, mut client_write) = tokio::io::split(client);
+        client_write.write_all(input.as_bytes()).await.unwrap();
+        client_write.shutdown().await.unwrap();
+        serve_connection(server, &handler, "c1".to_string()).await.unwrap();
+
+        let mut output = String::new();
+        client_read.read_to_string(&mut output).await.unwrap();
+        let responses = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        (responses, handler.audit.into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_requires_auth_and_checks_permissions() {
+        let input = concat!(
+            "{\"id\":1,\"method\":\"ping\"}\n",
+            "{\"id\":2,\"method\":\"auth\",\"params\":{\"token\":\"secret\",\"client\":\"script\"}}\n",
+            "{\"id\":3,\"method\":\"ping\"}\n",
+            "{\"id\":4,\"method\":\"notify\",\"params\":{\"body\":\"hi\"}}\n",
+            "{\"id\":5,\"method\":\"missing\"}\n",
+            "not json\n",
+        );
+        let (responses, audit) = exchange(input).await;
+        let codes: Vec<Option<i32>> = responses.iter().map(|r| r.error.as_ref().map(|e| e.code)).collect();
+        assert_eq!(codes, vec![Some(UNAUTHORIZED), None, None, Some(FORBIDDEN), Some(METHOD_NOT_FOUND), Some(PARSE_ERROR)]);
+        assert_eq!(responses[1].result, Some(serde_json::json!({ "client": "script" })));
+
+        let events: Vec<AuditEvent> = audit.iter().map(|e| e.event.clone()).collect();
+        assert_eq!(
+            events,
+            vec![AuditEvent::Connected, AuditEvent::Authenticated, AuditEvent::Call, AuditEvent::Denied, AuditEvent::Disconnected]
+        );
+        assert_eq!(audit[3].client.as_deref(), Some("script"));
+    }
+
+    #[tokio::test]
+    async fn test_disconnects_after_repeated_auth_failures() {
+        let bad = "{\"method\":\"auth\",\"params\":{\"token\":\"wrong\"}}\n";
+        let input = format!("{}{}{}{{\"method\":\"ping\"}}\n", bad, bad, bad);
+        let (responses, audit) = exchange(&input).await;
+        assert_eq!(responses.len(), MAX_AUTH_FAILURES as usize);
+        assert!(responses.iter().all(|r| r.error.as_ref().map(|e| e.code) == Some(UNAUTHORIZED)));
+        assert_eq!(audit.iter().filter(|e| e.event == AuditEvent::AuthFailed).count(), 3);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(tokens_match(&token, &token));
+        assert!(!tokens_match(&token, &generate_token()));
+        assert!(!tokens_match("", ""));
+    }
+}
@@ -4,7 +4,8 @@
 
 use anyhow::{Context, Result};
 use parking_lot::RwLock;
-use reqwest::{Client, ClientBuilder};
+use crate::http::proxy::ReloadingClient;
+use crate::http::trust_store::PinnedSend;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
@@ -42,8 +43,8 @@ impl Default for ApiConfig {
 
 /// Python API 桥接客户端
 pub struct PythonApiBridge {
-    /// HTTP 客户端
-    client: Client,
+    /// HTTP 客户端（代理设置变化后自动重建）
+    client: ReloadingClient,
     /// 配置
     config: Arc<RwLock<ApiConfig>>,
     /// 请求计数器
@@ -53,12 +54,14 @@ pub struct PythonApiBridge {
 impl PythonApiBridge {
     /// 创建新的 API 桥接实例
     pub fn new(config: ApiConfig) -> Result<Self> {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(config.timeout))
-            .pool_max_idle_per_host(config.pool_size)
-            .user_agent("Zishu-Sensei-Desktop/1.0")
-            .build()
-            .context("创建 HTTP 客户端失败")?;
+        let (timeout, pool_size) = (config.timeout, config.pool_size);
+        let client = ReloadingClient::new(move |builder| {
+            builder
+                .timeout(Duration::from_secs(timeout))
+                .pool_max_idle_per_host(pool_size)
+                .user_agent("Zishu-Sensei-Desktop/1.0")
+        })
+        .context("创建 HTTP 客户端失败")?;
 
         Ok(Self {
             client,
@@ -115,9 +118,9 @@ impl PythonApiBridge {
         
         debug!("发送 GET 请求: {}", url);
         
-        let response = self.client
+        let response = self.client.get()
             .get(&url)
-            .send()
+            .send_pinned()
             .await
             .context(format!("GET 请求失败: {}", url))?;
 
@@ -148,10 +151,10 @@ impl PythonApiBridge {
         
         debug!("发送 POST 请求: {}", url);
         
-        let response = self.client
+        let response = self.client.get()
             .post(&url)
             .json(body)
-            .send()
+            .send_pinned()
             .await
             .context(format!("POST 请求失败: {}", url))?;
 
@@ -181,9 +184,9 @@ impl PythonApiBridge {
         
         debug!("发送 DELETE 请求: {}", url);
         
-        let response = self.client
+        let response = self.client.get()
             .delete(&url)
-            .send()
+            .send_pinned()
             .await
             .context(format!("DELETE 请求失败: {}", url))?;
 
@@ -238,7 +241,7 @@ impl PythonApiBridge {
     pub async fn health_check(&self) -> Result<bool> {
         let url = self.build_url("/health");
         
-        match self.client.get(&url).send().await {
+        match self.client.get().get(&url).send_pinned().await {
             Ok(response) => {
                 let is_healthy = response.status().is_success();
                 if is_healthy {
@@ -302,6 +305,20 @@ pub struct ChatRequest {
     pub stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// 随最后一条用户消息发送的图片（多模态模型）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
+}
+
+/// 图片附件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageAttachment {
+    pub mime_type: String,
+    /// Base64 编码的图片内容
+    pub data: String,
+    /// 文件管理中的文件 ID（未保存时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
 }
 
 /// 聊天选择
@@ -399,6 +416,14 @@ impl PythonApiBridge {
     }
 }
 
+/// 请求失败是否因为 Python API 不可达（连接失败或超时），而不是服务端拒绝了请求
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout())
+}
+
 // ================================
 // 测试模块
 // ================================
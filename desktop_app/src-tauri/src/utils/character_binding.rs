@@ -0,0 +1,172 @@
+//! # 角色绑定
+//!
+//! 每个角色可以绑定偏好的模型配置、提示词模板、语音包与 TTS 音色，让不同角色有不同的
+//! “性格”。切换角色时整套绑定应用到当前会话，聊天在未显式指定模型 / 提示词时使用绑定的值。
+//!
+//! - 绑定保存在 `character_bindings.json`
+//! - `apply_on_switch` 关闭时切换角色不应用绑定，单次切换也可以显式覆盖
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BINDINGS_FILE: &str = "character_bindings.json";
+
+/// 单个角色的绑定（未设置的项沿用全局设置）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterBinding {
+    /// 模型配置 ID（见模型配置管理）
+    pub model_config_id: Option<String>,
+    /// 提示词模板 ID
+    pub prompt_id: Option<String>,
+    /// 语音包
+    pub voice_pack: Option<String>,
+    /// TTS 音色
+    pub tts_voice: Option<String>,
+}
+
+impl CharacterBinding {
+    pub fn is_empty(&self) -> bool {
+        self.model_config_id.is_none()
+            && self.prompt_id.is_none()
+            && self.voice_pack.is_none()
+            && self.tts_voice.is_none()
+    }
+}
+
+/// 绑定设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindingSettings {
+    /// 切换角色时是否应用绑定
+    pub apply_on_switch: bool,
+    /// 角色 ID -> 绑定
+    pub bindings: BTreeMap<String, CharacterBinding>,
+}
+
+impl Default for BindingSettings {
+    fn default() -> Self {
+        Self {
+            apply_on_switch: true,
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+impl BindingSettings {
+    pub fn binding_for(&self, character_id: &str) -> CharacterBinding {
+        self.bindings.get(character_id).cloned().unwrap_or_default()
+    }
+
+    /// 设置角色绑定，空绑定直接移除
+    pub fn set_binding(&mut self, character_id: &str, binding: CharacterBinding) {
+        if binding.is_empty() {
+            self.bindings.remove(character_id);
+        } else {
+            self.bindings.insert(character_id.to_string(), binding);
+        }
+    }
+
+    /// 本次切换是否应用绑定（显式覆盖优先）
+    pub fn should_apply(&self, override_apply: Option<bool>) -> bool {
+        override_apply.unwrap_or(self.apply_on_switch)
+    }
+}
+
+/// 已应用到当前会话的绑定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedBinding {
+    pub character_id: String,
+    pub binding: CharacterBinding,
+    /// 由绑定的模型配置解析出的模型（配置不存在时为空）
+    pub model_id: Option<String>,
+    pub adapter_id: Option<String>,
+}
+
+lazy_static! {
+    static ref ACTIVE: RwLock<Option<AppliedBinding>> = RwLock::new(None);
+}
+
+/// 设置当前生效的绑定（`None` 表示不使用任何绑定）
+pub fn activate(applied: Option<AppliedBinding>) {
+    *ACTIVE.write() = applied;
+}
+
+pub fn active() -> Option<AppliedBinding> {
+    ACTIVE.read().clone()
+}
+
+/// 对指定角色生效的绑定；消息显式指定了其他角色时不使用
+pub fn active_for(character_id: Option<&str>) -> Option<AppliedBinding> {
+    active().filter(|applied| match character_id {
+        Some(id) => id == applied.character_id,
+        None => true,
+    })
+}
+
+fn bindings_path(dir: &Path) -> PathBuf {
+    dir.join(BINDINGS_FILE)
+}
+
+/// 读取绑定设置（文件不存在时使用默认值）
+pub fn load(dir: &Path) -> Result<BindingSettings, String> {
+    let path = bindings_path(dir);
+    if !path.exists() {
+        return Ok(BindingSettings::default());
+    }
+    let json_data = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read character bindings: {}", e))?;
+    serde_json::from_str(&json_data).map_err(|e| format!("Failed to parse character bindings: {}", e))
+}
+
+pub fn save(dir: &Path, settings: &BindingSettings) -> Result<(), String> {
+    let json_data = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize character bindings: {}", e))?;
+    fs::write(bindings_path(dir), json_data).map_err(|e| format!("Failed to write character bindings: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_binding_is_removed() {
+        let mut settings = BindingSettings::default();
+        let binding = CharacterBinding {
+            prompt_id: Some("tsundere".to_string()),
+            tts_voice: Some("zh-CN-XiaoyiNeural".to_string()),
+            ..CharacterBinding::default()
+        };
+        settings.set_binding("hiyori", binding.clone());
+        assert_eq!(settings.binding_for("hiyori"), binding);
+        assert!(settings.binding_for("shizuku").is_empty());
+
+        settings.set_binding("hiyori", CharacterBinding::default());
+        assert!(settings.bindings.is_empty());
+
+        assert!(settings.should_apply(None));
+        assert!(!settings.should_apply(Some(false)));
+        settings.apply_on_switch = false;
+        assert!(!settings.should_apply(None));
+        assert!(settings.should_apply(Some(true)));
+    }
+
+    #[test]
+    fn test_active_binding_only_applies_to_its_character() {
+        activate(Some(AppliedBinding {
+            character_id: "hiyori".to_string(),
+            binding: CharacterBinding::default(),
+            model_id: Some("qwen".to_string()),
+            adapter_id: None,
+        }));
+        assert!(active_for(None).is_some());
+        assert!(active_for(Some("hiyori")).is_some());
+        assert!(active_for(Some("shizuku")).is_none());
+        activate(None);
+        assert!(active_for(None).is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! # 桌宠窗口点击穿透
+//!
+//! 开启点击穿透后，鼠标在窗口透明区域时事件落到下层应用，只有在角色身上时窗口才可交互。
+//! Live2D 渲染器按当前画面生成低分辨率的命中遮罩（见 [`HitTestMask`]），覆盖整个窗口客户区；
+//! 后台按鼠标位置查询遮罩，切换窗口是否忽略鼠标事件。
+//!
+//! 本模块只包含遮罩与命中计算，轮询与窗口切换见 `events::click_through`。
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// 遮罩每边最多的格数
+pub const MAX_MASK_CELLS: u32 = 512;
+/// 命中判断向外扩展的格数，角色边缘附近也可交互
+const HIT_MARGIN_CELLS: i64 = 1;
+
+/// 渲染器上报的命中遮罩（`update_hit_test_mask` 的参数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitTestMaskUpdate {
+    pub columns: u32,
+    pub rows: u32,
+    /// 按行排列的位图（每格 1 位，低位在前），Base64 编码
+    pub bits: String,
+}
+
+/// 命中遮罩：按行排列的格子，`true` 表示该格有角色像素
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitTestMask {
+    columns: u32,
+    rows: u32,
+    cells: Vec<bool>,
+}
+
+impl HitTestMask {
+    pub fn decode(update: &HitTestMaskUpdate) -> Result<Self, String> {
+        let (columns, rows) = (update.columns, update.rows);
+        if !(1..=MAX_MASK_CELLS).contains(&columns) || !(1..=MAX_MASK_CELLS).contains(&rows) {
+            return Err(format!("命中遮罩的尺寸必须在 1 到 {} 格之间", MAX_MASK_CELLS));
+        }
+        let bytes = general_purpose::STANDARD
+            .decode(&update.bits)
+            .map_err(|e| format!("解析命中遮罩失败: {}", e))?;
+        let count = (columns * rows) as usize;
+        let expected = (count + 7) / 8;
+        if bytes.len() != expected {
+            return Err(format!("命中遮罩长度不符: 需要 {} 字节，实际 {} 字节", expected, bytes.len()));
+        }
+        let cells = (0..count).map(|i| bytes[i / 8] & (1 << (i % 8)) != 0).collect();
+        Ok(Self { columns, rows, cells })
+    }
+
+    /// 有角色像素的格数
+    pub fn filled(&self) -> usize {
+        self.cells.iter().filter(|cell| **cell).count()
+    }
+
+    fn cell(&self, column: i64, row: i64) -> bool {
+        if column < 0 || row < 0 || column >= self.columns as i64 || row >= self.rows as i64 {
+            return false;
+        }
+        self.cells[(row * self.columns as i64 + column) as usize]
+    }
+
+    /// 窗口内的点 `(x, y)`（相对客户区左上角，与 `width`/`height` 同一单位）是否落在角色上
+    pub fn hit(&self, x: f64, y: f64, width: f64, height: f64) -> bool {
+        if width <= 0.0 || height <= 0.0 || x < 0.0 || y < 0.0 || x >= width || y >= height {
+            return false;
+        }
+        let column = (x / width * self.columns as f64) as i64;
+        let row = (y / height * self.rows as f64) as i64;
+        (-HIT_MARGIN_CELLS..=HIT_MARGIN_CELLS).any(|dy| {
+            (-HIT_MARGIN_CELLS..=HIT_MARGIN_CELLS).any(|dx| self.cell(column + dx, row + dy))
+        })
+    }
+}
+
+/// 点击穿透开启时窗口是否应当接收鼠标事件：没有遮罩时整个窗口可交互
+pub fn interactive(mask: Option<&HitTestMask>, x: f64, y: f64, width: f64, height: f64) -> bool {
+    match mask {
+        Some(mask) => mask.hit(x, y, width, height),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8x4 的遮罩，只有第 2 行第 5 列有角色像素
+    fn single_cell_mask() -> HitTestMask {
+        let mut bytes = vec![0u8; 4];
+        let index = 2 * 8 + 5;
+        bytes[index / 8] |= 1 << (index % 8);
+        HitTestMask::decode(&HitTestMaskUpdate {
+            columns: 8,
+            rows: 4,
+            bits: general_purpose::STANDARD.encode(bytes),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_validates_size() {
+        assert_eq!(single_cell_mask().filled(), 1);
+        let wrong_length = HitTestMaskUpdate { columns: 8, rows: 4, bits: general_purpose::STANDARD.encode([0u8; 3]) };
+        assert!(HitTestMask::decode(&wrong_length).is_err());
+        let too_large = HitTestMaskUpdate { columns: 1024, rows: 1, bits: String::new() };
+        assert!(HitTestMask::decode(&too_large).is_err());
+    }
+
+    #[test]
+    fn test_hit_with_margin() {
+        let mask = single_cell_mask();
+        // 窗口 800x400，每格 100x100；有像素的格子是 x 500..600、y 200..300
+        assert!(mask.hit(550.0, 250.0, 800.0, 400.0));
+        // 相邻格子在边缘容差内
+        assert!(mask.hit(450.0, 150.0, 800.0, 400.0));
+        assert!(!mask.hit(150.0, 50.0, 800.0, 400.0));
+        // 窗口之外
+        assert!(!mask.hit(-1.0, 250.0, 800.0, 400.0));
+        assert!(!mask.hit(550.0, 400.0, 800.0, 400.0));
+
+        assert!(interactive(None, 10.0, 10.0, 800.0, 400.0));
+        assert!(!interactive(Some(&mask), 10.0, 10.0, 800.0, 400.0));
+    }
+}
@@ -0,0 +1,336 @@
+//! # 剪贴板历史
+//!
+//! 记录复制过的文本（新的在前），相同内容只保留一条并移到最前；超出条数上限时丢弃最旧的
+//! 未固定条目，固定的条目不受上限影响。
+//!
+//! 含 API 密钥、令牌、邮箱、手机号等敏感信息的文本（见 [`DataMasker`]）只以密文保存，
+//! 列表与搜索只使用脱敏后的预览。读取剪贴板、加解密与持久化见 `commands::clipboard`。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utils::data_masking::DataMasker;
+use crate::utils::encryption::EncryptedData;
+
+/// 预览最多保留的字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 剪贴板历史设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// 是否记录剪贴板历史（默认关闭，需用户主动开启）
+    pub enabled: bool,
+    /// 保留的未固定条目数
+    pub max_entries: usize,
+    /// 检查剪贴板变化的间隔（毫秒）
+    pub poll_interval_ms: u64,
+    /// 超过该字符数的文本不记录
+    pub max_entry_chars: usize,
+    /// 是否记录含敏感信息的文本（加密保存）
+    pub record_sensitive: bool,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: 200,
+            poll_interval_ms: 1000,
+            max_entry_chars: 20_000,
+            record_sensitive: true,
+        }
+    }
+}
+
+impl ClipboardSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=2000).contains(&self.max_entries) {
+            return Err("剪贴板历史条数必须在 10 到 2000 之间".to_string());
+        }
+        if !(250..=10_000).contains(&self.poll_interval_ms) {
+            return Err("剪贴板检查间隔必须在 250 到 10000 毫秒之间".to_string());
+        }
+        if !(1..=1_000_000).contains(&self.max_entry_chars) {
+            return Err("单条剪贴板内容上限必须在 1 到 1000000 字符之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 一条剪贴板记录（时间均为 Unix 毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardEntry {
+    pub id: String,
+    /// 内容的 SHA-256（十六进制），用于去重
+    pub hash: String,
+    /// 非敏感条目的原文
+    #[serde(default)]
+    pub content: Option<String>,
+    /// 敏感条目的密文
+    #[serde(default)]
+    pub encrypted: Option<EncryptedData>,
+    pub sensitive: bool,
+    /// 脱敏并截断后的预览
+    pub preview: String,
+    pub char_count: usize,
+    pub pinned: bool,
+    pub created_at: i64,
+    pub last_copied_at: i64,
+    pub copy_count: u32,
+}
+
+impl ClipboardEntry {
+    fn matches(&self, query: &str) -> bool {
+        let text = match (&self.content, self.sensitive) {
+            (Some(content), false) => content,
+            _ => &self.preview,
+        };
+        text.to_lowercase().contains(query)
+    }
+}
+
+/// 返回给前端的条目，敏感条目不带原文
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardItem {
+    pub id: String,
+    pub content: Option<String>,
+    pub preview: String,
+    pub sensitive: bool,
+    pub char_count: usize,
+    pub pinned: bool,
+    pub created_at: i64,
+    pub last_copied_at: i64,
+    pub copy_count: u32,
+}
+
+impl From<&ClipboardEntry> for ClipboardItem {
+    fn from(entry: &ClipboardEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            content: if entry.sensitive { None } else { entry.content.clone() },
+            preview: entry.preview.clone(),
+            sensitive: entry.sensitive,
+            char_count: entry.char_count,
+            pinned: entry.pinned,
+            created_at: entry.created_at,
+            last_copied_at: entry.last_copied_at,
+            copy_count: entry.copy_count,
+        }
+    }
+}
+
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// 脱敏后的文本；与原文不同说明含敏感信息
+fn masked(text: &str) -> (String, bool) {
+    let masked = DataMasker::new().mask_all_sensitive(text);
+    let sensitive = masked != text;
+    (masked, sensitive)
+}
+
+fn preview(text: &str) -> String {
+    let single_line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if single_line.chars().count() <= PREVIEW_CHARS {
+        single_line
+    } else {
+        let mut preview: String = single_line.chars().take(PREVIEW_CHARS).collect();
+        preview.push('…');
+        preview
+    }
+}
+
+/// 剪贴板历史（新的在前）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipboardHistory {
+    entries: Vec<ClipboardEntry>,
+}
+
+impl ClipboardHistory {
+    pub fn entries(&self) -> &[ClipboardEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ClipboardEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// 记录一段复制的文本，返回记录（或移到最前）的条目；不需要记录时返回 `None`
+    ///
+    /// `encrypt` 只对敏感文本调用。
+    pub fn record<F>(
+        &mut self,
+        text: &str,
+        settings: &ClipboardSettings,
+        now: i64,
+        encrypt: F,
+    ) -> Result<Option<&ClipboardEntry>, String>
+    where
+        F: FnOnce(&str) -> Result<EncryptedData, String>,
+    {
+        let char_count = text.chars().count();
+        if text.trim().is_empty() || char_count > settings.max_entry_chars {
+            return Ok(None);
+        }
+
+        let hash = content_hash(text);
+        if let Some(index) = self.entries.iter().position(|entry| entry.hash == hash) {
+            let mut entry = self.entries.remove(index);
+            entry.last_copied_at = now;
+            entry.copy_count += 1;
+            self.entries.insert(0, entry);
+            return Ok(self.entries.first());
+        }
+
+        let (masked, sensitive) = masked(text);
+        if sensitive && !settings.record_sensitive {
+            return Ok(None);
+        }
+        let (content, encrypted) = if sensitive {
+            (None, Some(encrypt(text)?))
+        } else {
+            (Some(text.to_string()), None)
+        };
+
+        self.entries.insert(
+            0,
+            ClipboardEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                hash,
+                content,
+                encrypted,
+                sensitive,
+                preview: preview(&masked),
+                char_count,
+                pinned: false,
+                created_at: now,
+                last_copied_at: now,
+                copy_count: 1,
+            },
+        );
+        self.trim(settings.max_entries);
+        Ok(self.entries.first())
+    }
+
+    /// 丢弃超出上限的最旧未固定条目
+    pub fn trim(&mut self, max_entries: usize) {
+        let mut unpinned = 0;
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= max_entries
+        });
+    }
+
+    /// 固定或取消固定，条目不存在时返回 `false`
+    pub fn set_pinned(&mut self, id: &str, pinned: bool) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 按关键字（不区分大小写）搜索，固定的条目在前
+    pub fn search(&self, query: Option<&str>, pinned_only: bool, limit: usize) -> Vec<ClipboardItem> {
+        let query = query.map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+        let matching = self
+            .entries
+            .iter()
+            .filter(|entry| !pinned_only || entry.pinned)
+            .filter(|entry| query.as_deref().map_or(true, |q| entry.matches(q)));
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = matching.partition(|entry| entry.pinned);
+        pinned
+            .into_iter()
+            .chain(unpinned)
+            .take(limit)
+            .map(ClipboardItem::from)
+            .collect()
+    }
+
+    /// 丢弃所有敏感（加密）条目
+    pub fn remove_sensitive(&mut self) {
+        self.entries.retain(|entry| !entry.sensitive);
+    }
+
+    /// 清空历史，返回删除的条目数
+    pub fn clear(&mut self, include_pinned: bool) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.pinned && !include_pinned);
+        before - self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_encrypt(text: &str) -> Result<EncryptedData, String> {
+        Ok(EncryptedData {
+            ciphertext: format!("enc:{}", text.len()),
+            nonce: String::new(),
+            version: 1,
+            timestamp: 0,
+        })
+    }
+
+    fn settings(max_entries: usize) -> ClipboardSettings {
+        ClipboardSettings { enabled: true, max_entries, ..Default::default() }
+    }
+
+    #[test]
+    fn test_record_dedupes_and_trims_unpinned() {
+        let settings = settings(2);
+        let mut history = ClipboardHistory::default();
+        let first = history.record("hello", &settings, 1, fake_encrypt).unwrap().unwrap().id.clone();
+        history.set_pinned(&first, true);
+        history.record("world", &settings, 2, fake_encrypt).unwrap();
+        history.record("foo", &settings, 3, fake_encrypt).unwrap();
+        history.record("bar", &settings, 4, fake_encrypt).unwrap();
+
+        let contents: Vec<_> = history.entries().iter().filter_map(|e| e.content.clone()).collect();
+        assert_eq!(contents, vec!["bar", "foo", "hello"]);
+
+        // 再次复制相同内容移到最前
+        let again = history.record("hello", &settings, 5, fake_encrypt).unwrap().unwrap();
+        assert_eq!(again.id, first);
+        assert_eq!(again.copy_count, 2);
+        assert_eq!(history.entries().len(), 3);
+
+        assert!(history.record("   ", &settings, 6, fake_encrypt).unwrap().is_none());
+        assert_eq!(history.clear(false), 2);
+        assert_eq!(history.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_sensitive_entries_are_encrypted_and_searchable_by_preview() {
+        let settings = settings(10);
+        let mut history = ClipboardHistory::default();
+        history.record("Meeting notes for Friday", &settings, 1, fake_encrypt).unwrap();
+        let entry = history
+            .record("key sk-1234567890abcdefghijklmnopqrstuvwxyz123456789012", &settings, 2, fake_encrypt)
+            .unwrap()
+            .unwrap();
+        assert!(entry.sensitive);
+        assert!(entry.content.is_none() && entry.encrypted.is_some());
+        assert!(!entry.preview.contains("sk-1234567890abcdefghijklmnopqrstuvwxyz123456789012"));
+
+        assert_eq!(history.search(Some("meeting"), false, 10).len(), 1);
+        assert!(history.search(Some("abcdefghijklmnop"), false, 10).is_empty());
+        let items = history.search(Some("key"), false, 10);
+        assert_eq!(items.len(), 1);
+        assert!(items[0].content.is_none());
+
+        let no_sensitive = ClipboardSettings { record_sensitive: false, ..settings };
+        assert!(history
+            .record("token sk-abcdefghijklmnopqrstuvwxyz0987654321abcdefghijkl", &no_sensitive, 3, fake_encrypt)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//! # 时钟抽象
+//!
+//! 依赖当前时间的子系统（日志保留清理等）通过 [`SharedClock`] 读取时间，
+//! 而不是直接调用 `Utc::now()`，从而可以在测试中注入 [`ManualClock`]。
+//!
+//! 默认注入的 [`GlobalClock`] 读取系统时间加上一个全局偏移量；开发构建中可以通过
+//! 调试命令推进该偏移量，以便手动验证基于时间的功能。
+
+use chrono::{DateTime, Duration, Local, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 时钟
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// 当前 UTC 时间
+    fn now(&self) -> DateTime<Utc>;
+
+    /// 当前本地时间
+    fn local_now(&self) -> DateTime<Local> {
+        self.now().with_timezone(&Local)
+    }
+
+    /// 当前 Unix 时间戳（秒）
+    fn timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+/// 可共享的时钟句柄
+pub type SharedClock = Arc<dyn Clock>;
+
+/// 系统时钟
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 手动控制的时钟，仅在调用 [`ManualClock::set`] / [`ManualClock::advance`] 时变化
+#[derive(Debug)]
+pub struct ManualClock {
+    now: RwLock<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: RwLock::new(start) }
+    }
+
+    /// 以当前系统时间为起点
+    pub fn starting_now() -> Self {
+        Self::new(Utc::now())
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.write() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.write() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.read()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref TIME_OFFSET: RwLock<Duration> = RwLock::new(Duration::zero());
+}
+
+/// 全局时钟：系统时间 + 全局偏移量
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalClock;
+
+impl Clock for GlobalClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + *TIME_OFFSET.read()
+    }
+}
+
+/// 获取默认时钟句柄（各子系统未显式注入时使用）
+pub fn global() -> SharedClock {
+    Arc::new(GlobalClock)
+}
+
+/// 全局时钟的当前时间
+pub fn now() -> DateTime<Utc> {
+    GlobalClock.now()
+}
+
+/// 时钟状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockStatus {
+    pub now: DateTime<Utc>,
+    pub system_now: DateTime<Utc>,
+    pub offset_seconds: i64,
+}
+
+/// 获取全局时钟状态
+pub fn status() -> ClockStatus {
+    ClockStatus {
+        now: now(),
+        system_now: Utc::now(),
+        offset_seconds: TIME_OFFSET.read().num_seconds(),
+    }
+}
+
+/// 推进全局时钟（仅用于开发调试）
+pub fn fast_forward(duration: Duration) -> ClockStatus {
+    *TIME_OFFSET.write() += duration;
+    status()
+}
+
+/// 重置全局时钟偏移
+pub fn reset() -> ClockStatus {
+    *TIME_OFFSET.write() = Duration::zero();
+    status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_advances_only_when_told() {
+        let start = Utc::now();
+        let clock = ManualClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::days(3));
+        assert_eq!(clock.now(), start + Duration::days(3));
+        assert_eq!(clock.timestamp(), (start + Duration::days(3)).timestamp());
+    }
+
+    #[test]
+    fn test_shared_clock_injection() {
+        let manual = Arc::new(ManualClock::starting_now());
+        let shared: SharedClock = manual.clone();
+        let before = shared.now();
+        manual.advance(Duration::hours(1));
+        assert_eq!(shared.now() - before, Duration::hours(1));
+    }
+}
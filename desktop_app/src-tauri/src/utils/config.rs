@@ -6,8 +6,16 @@ use tokio::time::{sleep, Duration};
 
 lazy_static::lazy_static! {
     static ref CONFIG_WRITE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+    /// Cached data directory override: `None` = not loaded yet, `Some(None)` = default location
+    static ref DATA_DIR_OVERRIDE: parking_lot::RwLock<Option<Option<PathBuf>>> = parking_lot::RwLock::new(None);
 }
 
+/// File name of the data directory pointer
+pub const DATA_DIR_POINTER_FILE: &str = "data_location.json";
+
+use crate::utils::config_timeline;
+use crate::utils::profiles;
+use crate::utils::event_journal::{self, EventKind};
 use crate::AppConfig;
 
 /// Return a directory to store application logs
@@ -16,8 +24,8 @@ pub fn get_app_log_dir() -> Result<PathBuf, String> {
     Ok(dir)
 }
 
-/// Get the application data directory
-pub fn get_app_data_dir() -> Result<PathBuf, String> {
+/// Get the default (platform) application data directory
+pub fn default_app_data_dir() -> Result<PathBuf, String> {
     // Use platform-specific data directory
     let data_dir = if cfg!(target_os = "windows") {
         // Windows: %APPDATA%\zishu-sensei
@@ -39,14 +47,83 @@ pub fn get_app_data_dir() -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
+/// Pointer file (always in the default data directory) recording a relocated data directory
+pub fn get_data_dir_pointer_path() -> Result<PathBuf, String> {
+    Ok(default_app_data_dir()?.join(DATA_DIR_POINTER_FILE))
+}
+
+/// Read the data directory override from the pointer file
+fn read_data_dir_override() -> Option<PathBuf> {
+    let pointer = get_data_dir_pointer_path().ok()?;
+    let content = std::fs::read_to_string(pointer).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    value
+        .get("data_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// Set (or clear with `None`) the data directory override
+///
+/// Takes effect immediately for all paths resolved through this module.
+pub fn set_data_dir_override(path: Option<PathBuf>) -> Result<(), String> {
+    let pointer = get_data_dir_pointer_path()?;
+    match &path {
+        Some(dir) => {
+            if let Some(parent) = pointer.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create default data directory: {}", e))?;
+            }
+            let content = serde_json::json!({
+                "data_dir": dir.to_string_lossy(),
+                "updated_at": chrono::Utc::now().to_rfc3339(),
+            });
+            std::fs::write(&pointer, content.to_string())
+                .map_err(|e| format!("Failed to write data directory pointer: {}", e))?;
+        }
+        None => {
+            if pointer.exists() {
+                std::fs::remove_file(&pointer)
+                    .map_err(|e| format!("Failed to remove data directory pointer: {}", e))?;
+            }
+        }
+    }
+
+    *DATA_DIR_OVERRIDE.write() = Some(path);
+    Ok(())
+}
+
+/// Get the application data directory (honours the user override)
+pub fn get_app_data_dir() -> Result<PathBuf, String> {
+    if let Some(cached) = DATA_DIR_OVERRIDE.read().clone() {
+        return match cached {
+            Some(dir) => Ok(dir),
+            None => default_app_data_dir(),
+        };
+    }
+
+    let override_dir = read_data_dir_override();
+    *DATA_DIR_OVERRIDE.write() = Some(override_dir.clone());
+    match override_dir {
+        Some(dir) => Ok(dir),
+        None => default_app_data_dir(),
+    }
+}
+
+/// Get the data directory of the active profile (settings, timeline, change journal)
+pub fn get_profile_data_dir() -> Result<PathBuf, String> {
+    Ok(profiles::profile_dir(&get_app_data_dir()?, &profiles::active_id()))
+}
+
 /// Get the config file path
 pub fn get_config_file_path() -> Result<PathBuf, String> {
-    Ok(get_app_data_dir()?.join("config.json"))
+    Ok(get_profile_data_dir()?.join("config.json"))
 }
 
 /// Get the backup config file path
 pub fn get_config_backup_path() -> Result<PathBuf, String> {
-    Ok(get_app_data_dir()?.join("config.backup.json"))
+    Ok(get_profile_data_dir()?.join("config.backup.json"))
 }
 
 /// Load application config from disk
@@ -118,7 +195,7 @@ pub async fn save_config(_app_handle: &AppHandle, config: &AppConfig) -> Result<
     let backup_path = get_config_backup_path()?;
     
     // Ensure data directory exists
-    let data_dir = get_app_data_dir()?;
+    let data_dir = get_profile_data_dir()?;
     if !data_dir.exists() {
         fs::create_dir_all(&data_dir).await?;
         info!("创建数据目录: {:?}", data_dir);
@@ -129,7 +206,8 @@ pub async fn save_config(_app_handle: &AppHandle, config: &AppConfig) -> Result<
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
     // If config file exists, backup it first
-    if config_path.exists() {
+    let had_previous = config_path.exists();
+    if had_previous {
         if let Err(e) = fs::copy(&config_path, &backup_path).await {
             warn!("备份配置文件失败: {}", e);
         } else {
@@ -143,6 +221,8 @@ pub async fn save_config(_app_handle: &AppHandle, config: &AppConfig) -> Result<
         match fs::write(&config_path, &json).await {
             Ok(()) => {
                 // 配置保存成功，静默处理（避免频繁日志）
+                let baseline_path = if had_previous { Some(backup_path.as_path()) } else { None };
+                record_timeline(&data_dir, &json, baseline_path);
                 return Ok(());
             }
             Err(e) => {
@@ -169,6 +249,27 @@ pub async fn save_config(_app_handle: &AppHandle, config: &AppConfig) -> Result<
     Ok(())
 }
 
+/// Record the saved config in the config timeline (failures are only logged)
+fn record_timeline(data_dir: &std::path::Path, json: &str, baseline_path: Option<&std::path::Path>) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return;
+    };
+    let mut timeline = config_timeline::load(data_dir);
+    // Periodic autosaves usually write an unchanged config
+    if timeline.head() == Some(&value) {
+        return;
+    }
+    event_journal::record(EventKind::SettingsChanged, serde_json::json!({ "config": value }));
+    let baseline = || {
+        let content = std::fs::read_to_string(baseline_path?).ok()?;
+        serde_json::from_str(&content).ok()
+    };
+    timeline.record(&value, chrono::Utc::now().timestamp(), baseline);
+    if let Err(e) = config_timeline::save(data_dir, &timeline) {
+        warn!("{}", e);
+    }
+}
+
 /// Reset config to default
 pub async fn reset_config(_app_handle: &AppHandle) -> Result<AppConfig, Box<dyn std::error::Error + Send + Sync>> {
     let config_path = get_config_file_path()?;
@@ -177,7 +278,7 @@ pub async fn reset_config(_app_handle: &AppHandle) -> Result<AppConfig, Box<dyn
     // Backup current config before reset
     if config_path.exists() {
         let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let reset_backup_path = get_app_data_dir()?
+        let reset_backup_path = get_profile_data_dir()?
             .join(format!("config.reset_backup_{}.json", timestamp));
         
         if let Err(e) = fs::copy(&config_path, &reset_backup_path).await {
@@ -233,6 +334,9 @@ pub async fn export_config(config: &AppConfig, file_path: PathBuf) -> Result<(),
     Ok(())
 }
 
+/// Allowed text scale percentage range
+pub const TEXT_SCALE_RANGE: std::ops::RangeInclusive<u32> = 50..=200;
+
 /// Validate config structure
 pub fn validate_config(config: &AppConfig) -> Result<(), String> {
     // Validate window config
@@ -258,6 +362,30 @@ pub fn validate_config(config: &AppConfig) -> Result<(), String> {
         return Err("主题名称不能为空".to_string());
     }
     
+    // Validate text scale
+    if !TEXT_SCALE_RANGE.contains(&config.theme.text_scale) {
+        return Err(format!(
+            "文字缩放比例必须在 {}-{}% 之间",
+            TEXT_SCALE_RANGE.start(),
+            TEXT_SCALE_RANGE.end()
+        ));
+    }
+    if let Some(palette) = &config.theme.color_blind_palette {
+        if crate::utils::theme_contrast::find_color_blind_palette(palette).is_none() {
+            return Err(format!("未知的色盲友好调色板: {}", palette));
+        }
+    }
+    for (label, scale) in &config.theme.window_text_scales {
+        if !TEXT_SCALE_RANGE.contains(scale) {
+            return Err(format!(
+                "窗口 {} 的文字缩放比例必须在 {}-{}% 之间",
+                label,
+                TEXT_SCALE_RANGE.start(),
+                TEXT_SCALE_RANGE.end()
+            ));
+        }
+    }
+    
     Ok(())
 }
 
@@ -299,7 +427,7 @@ fn merge_json(base: &mut serde_json::Value, updates: &serde_json::Value) {
 
 /// Get all config backup files (sorted by timestamp, newest first)
 pub async fn get_backup_files() -> Result<Vec<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
-    let data_dir = get_app_data_dir()?;
+    let data_dir = get_profile_data_dir()?;
     
     if !data_dir.exists() {
         return Ok(Vec::new());
@@ -413,7 +541,7 @@ pub async fn create_config_snapshot(
     config: &AppConfig,
     description: Option<String>
 ) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
-    let data_dir = get_app_data_dir()?;
+    let data_dir = get_profile_data_dir()?;
     
     if !data_dir.exists() {
         fs::create_dir_all(&data_dir).await?;
@@ -472,7 +600,7 @@ pub async fn migrate_config(config: AppConfig) -> Result<AppConfig, Box<dyn std:
 pub async fn get_config_info() -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
     let config_path = get_config_file_path()?;
     let backup_path = get_config_backup_path()?;
-    let data_dir = get_app_data_dir()?;
+    let data_dir = get_profile_data_dir()?;
     
     let config_exists = config_path.exists();
     let backup_exists = backup_path.exists();
@@ -1239,6 +1367,8 @@ mod tests {
                 decorations: true,
                 resizable: true,
                 position: Some((100, 100)),
+                docking: Default::default(),
+                click_through: false,
             },
             character: CharacterConfig {
                 current_character: "default".to_string(),
@@ -1249,12 +1379,16 @@ mod tests {
             theme: ThemeConfig {
                 current_theme: "default".to_string(),
                 custom_css: None,
+                text_scale: 100,
+                window_text_scales: std::collections::HashMap::new(),
+                color_blind_palette: None,
             },
             system: SystemConfig {
                 auto_start: false,
                 minimize_to_tray: true,
                 close_to_tray: false,
                 show_notifications: true,
+                proxy: Default::default(),
             }
         };
         
@@ -1274,6 +1408,8 @@ mod tests {
                 decorations: true,
                 resizable: true,
                 position: Some((100, 100)),
+                docking: Default::default(),
+                click_through: false,
             },
             character: CharacterConfig {
                 current_character: "default".to_string(),
@@ -1284,12 +1420,16 @@ mod tests {
             theme: ThemeConfig {
                 current_theme: "default".to_string(),
                 custom_css: None,
+                text_scale: 100,
+                window_text_scales: std::collections::HashMap::new(),
+                color_blind_palette: None,
             },
             system: SystemConfig {
                 auto_start: false,
                 minimize_to_tray: true,
                 close_to_tray: false,
                 show_notifications: true,
+                proxy: Default::default(),
             }
         };
         
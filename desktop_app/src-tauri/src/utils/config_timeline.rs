@@ -0,0 +1,316 @@
+//! # 配置时间线
+//!
+//! 每次保存配置时记录一个轻量的时间点：只保存字段级的变化（`window.width: 400 → 480`），
+//! 外加最新配置的完整副本（head）。回滚到某个时间点时，从 head 出发按时间倒序撤销之后的
+//! 变化即可得到当时的配置。
+//!
+//! - 对象按字段展开为点分路径，数组视为整体
+//! - 短时间内连续修改同一批字段（如拖动滑块）合并为一个时间点
+//! - 拖动窗口产生的位置变化不记录
+//! - 只保留最近 [`MAX_ENTRIES`] 个时间点
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 保留的时间点数量
+pub const MAX_ENTRIES: usize = 200;
+/// 在该时间内修改相同字段时合并为一个时间点（秒）
+const COALESCE_SECS: i64 = 10;
+/// 不记录的字段
+const IGNORED_PATHS: &[&str] = &["window.position"];
+/// 描述中单个值的最大长度
+const MAX_VALUE_CHARS: usize = 60;
+
+const TIMELINE_FILE: &str = "config_timeline.json";
+
+/// 单个字段的变化（`None` 表示字段不存在）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    /// 可读描述，如 `theme.current_theme: default → dark`
+    pub description: String,
+}
+
+impl FieldChange {
+    fn new(path: String, old: Option<Value>, new: Option<Value>) -> Self {
+        let description = format!("{}: {} → {}", path, describe_value(old.as_ref()), describe_value(new.as_ref()));
+        Self { path, old, new, description }
+    }
+}
+
+/// 时间点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub id: u64,
+    pub timestamp: i64,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 配置时间线
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    /// 最近一次记录的完整配置
+    head: Option<Value>,
+    /// 按时间顺序排列
+    entries: Vec<TimelineEntry>,
+    next_id: u64,
+}
+
+fn describe_value(value: Option<&Value>) -> String {
+    let text = match value {
+        None | Some(Value::Null) => return "（未设置）".to_string(),
+        Some(Value::String(s)) if s.is_empty() => return "（空）".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    if text.chars().count() > MAX_VALUE_CHARS {
+        format!("{}…", text.chars().take(MAX_VALUE_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(&path, child, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    let mut out = BTreeMap::new();
+    flatten_into("", value, &mut out);
+    out
+}
+
+fn is_ignored(path: &str) -> bool {
+    IGNORED_PATHS
+        .iter()
+        .any(|ignored| path == *ignored || path.starts_with(&format!("{}.", ignored)))
+}
+
+/// 两份配置之间的字段级差异（按路径排序）
+pub fn diff(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let old = flatten(old);
+    let new = flatten(new);
+    let mut paths: Vec<&String> = old.keys().chain(new.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter(|path| old.get(*path) != new.get(*path))
+        .map(|path| FieldChange::new(path.clone(), old.get(path).cloned(), new.get(path).cloned()))
+        .collect()
+}
+
+/// 在点分路径上设置值（`None` 表示删除），缺失的中间对象会被创建
+pub(crate) fn set_path(root: &mut Value, path: &str, value: Option<Value>) {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let Some(last) = segments.pop() else {
+        return;
+    };
+    let mut current = root;
+    for segment in segments {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        let Value::Object(map) = current else {
+            return;
+        };
+        current = map.entry(segment.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = current {
+        match value {
+            Some(value) => {
+                map.insert(last.to_string(), value);
+            }
+            None => {
+                map.remove(last);
+            }
+        }
+    }
+}
+
+impl Timeline {
+    /// 记录新的配置，返回新增或合并后的时间点 id（没有变化时返回 `None`）
+    ///
+    /// `baseline` 只在还没有 head 时使用（首次记录），通常是保存前的配置文件。
+    pub fn record(&mut self, new: &Value, now: i64, baseline: impl FnOnce() -> Option<Value>) -> Option<u64> {
+        let previous = match self.head.take() {
+            Some(head) => Some(head),
+            None => baseline(),
+        };
+        self.head = Some(new.clone());
+        let previous = previous?;
+
+        let changes: Vec<FieldChange> = diff(&previous, new).into_iter().filter(|c| !is_ignored(&c.path)).collect();
+        if changes.is_empty() {
+            return None;
+        }
+
+        if let Some(last) = self.entries.last_mut() {
+            let same_fields = changes.iter().all(|c| last.changes.iter().any(|l| l.path == c.path));
+            if now - last.timestamp <= COALESCE_SECS && same_fields {
+                let merged: Vec<FieldChange> = last
+                    .changes
+                    .iter()
+                    .map(|l| match changes.iter().find(|c| c.path == l.path) {
+                        Some(c) => FieldChange::new(l.path.clone(), l.old.clone(), c.new.clone()),
+                        None => l.clone(),
+                    })
+                    .filter(|c| c.old != c.new)
+                    .collect();
+                if merged.is_empty() {
+                    // 改回原值，时间点本身没有意义了
+                    self.entries.pop();
+                    return None;
+                }
+                last.timestamp = now;
+                last.changes = merged;
+                return Some(last.id);
+            }
+        }
+
+        self.next_id += 1;
+        let id = self.next_id;
+        self.entries.push(TimelineEntry { id, timestamp: now, changes });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        Some(id)
+    }
+
+    /// 最近一次记录的完整配置
+    pub fn head(&self) -> Option<&Value> {
+        self.head.as_ref()
+    }
+
+    /// 时间点列表（新的在前）
+    pub fn entries(&self, limit: usize) -> Vec<TimelineEntry> {
+        self.entries.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// 指定时间点保存后的完整配置
+    pub fn state_at(&self, id: u64) -> Option<Value> {
+        let mut state = self.head.clone()?;
+        if !self.entries.iter().any(|entry| entry.id == id) {
+            return None;
+        }
+        for entry in self.entries.iter().rev().take_while(|entry| entry.id != id) {
+            for change in &entry.changes {
+                set_path(&mut state, &change.path, change.old.clone());
+            }
+        }
+        Some(state)
+    }
+}
+
+fn timeline_path(dir: &Path) -> PathBuf {
+    dir.join(TIMELINE_FILE)
+}
+
+/// 读取时间线（文件不存在或损坏时返回空时间线）
+pub fn load(dir: &Path) -> Timeline {
+    fs::read_to_string(timeline_path(dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, timeline: &Timeline) -> Result<(), String> {
+    let json = serde_json::to_string(timeline).map_err(|e| format!("序列化配置时间线失败: {}", e))?;
+    fs::write(timeline_path(dir), json).map_err(|e| format!("写入配置时间线失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(width: u32, theme: &str) -> Value {
+        json!({
+            "window": { "width": width, "position": [10, 20] },
+            "theme": { "current_theme": theme, "custom_css": null },
+        })
+    }
+
+    #[test]
+    fn test_diff_is_field_level() {
+        let changes = diff(&config(400, "default"), &json!({
+            "window": { "width": 480, "position": [10, 20] },
+            "theme": { "current_theme": "default", "custom_css": "body {}" },
+            "extra": [1, 2],
+        }));
+        let descriptions: Vec<&str> = changes.iter().map(|c| c.description.as_str()).collect();
+        assert_eq!(
+            descriptions,
+            vec!["extra: （未设置） → [1,2]", "theme.custom_css: （未设置） → body {}", "window.width: 400 → 480"]
+        );
+    }
+
+    #[test]
+    fn test_state_at_rebuilds_past_configs() {
+        let mut timeline = Timeline::default();
+        assert_eq!(timeline.record(&config(400, "default"), 0, || Some(config(300, "default"))), Some(1));
+        assert_eq!(timeline.record(&config(400, "dark"), 100, || None), Some(2));
+        let mut moved = config(500, "dark");
+        moved["window"]["position"] = json!([99, 99]);
+        assert_eq!(timeline.record(&moved, 200, || None), Some(3));
+        // 只有位置变化时不记录
+        let mut dragged = moved.clone();
+        dragged["window"]["position"] = json!([1, 1]);
+        assert_eq!(timeline.record(&dragged, 300, || None), None);
+
+        let at_first = timeline.state_at(1).unwrap();
+        assert_eq!(at_first["window"]["width"], json!(400));
+        assert_eq!(at_first["theme"]["current_theme"], json!("default"));
+        assert_eq!(timeline.state_at(2).unwrap()["theme"]["current_theme"], json!("dark"));
+        assert_eq!(timeline.state_at(3).unwrap(), dragged);
+        assert_eq!(timeline.state_at(42), None);
+        assert_eq!(timeline.entries(2).iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_rapid_changes_are_coalesced() {
+        let mut timeline = Timeline::default();
+        timeline.record(&config(400, "default"), 0, || None);
+        assert_eq!(timeline.record(&config(410, "default"), 100, || None), Some(1));
+        assert_eq!(timeline.record(&config(420, "default"), 105, || None), Some(1));
+        assert_eq!(timeline.entries(10)[0].changes[0].description, "window.width: 400 → 420");
+        // 改回原值后时间点被移除
+        assert_eq!(timeline.record(&config(400, "default"), 108, || None), None);
+        assert!(timeline.entries(10).is_empty());
+        // 修改其他字段不合并
+        timeline.record(&config(410, "default"), 200, || None);
+        assert_eq!(timeline.record(&config(410, "dark"), 201, || None), Some(3));
+    }
+
+    #[test]
+    fn test_old_entries_are_trimmed() {
+        let mut timeline = Timeline::default();
+        timeline.record(&config(0, "t"), 0, || None);
+        for i in 1..=(MAX_ENTRIES as u32 + 5) {
+            timeline.record(&config(i, "t"), i64::from(i) * 100, || None);
+        }
+        let entries = timeline.entries(usize::MAX);
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        let oldest = entries.last().unwrap().id;
+        assert_eq!(timeline.state_at(oldest).unwrap()["window"]["width"], json!(oldest));
+    }
+}
@@ -0,0 +1,415 @@
+//! 会话导出
+//!
+//! 将会话导出为 Markdown 或 JSON。结构化消息在 Markdown 中按内容块渲染
+//! （代码围栏、表格、任务列表），在 JSON 中原样保留内容块；已删除的消息不导出。
+//!
+//! [`SessionArchive`] 是可无损导入的 JSON 存档，附带角色信息与会话中的附件（base64）；
+//! [`transcript`] 生成带角色名、时间戳与附件列表的 Markdown 文稿，[`parse_transcript`]
+//! 可以把这样的文稿读回（只恢复文本内容）。
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::database::conversation::{Conversation, Message, MessageRole};
+
+/// JSON 导出格式版本
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+/// 会话存档格式版本（兼容读取版本 1 的导出）
+pub const SESSION_ARCHIVE_VERSION: u32 = 2;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const EDITED_MARK: &str = "（已编辑）";
+const ATTACHMENT_MARK: &str = "📎 ";
+const ATTACHMENTS_HEADING: &str = "## 附件";
+
+lazy_static! {
+    static ref MESSAGE_HEADING: Regex =
+        Regex::new(r"^## (.+) · (\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}) UTC(（已编辑）)?$").unwrap();
+}
+
+#[derive(Serialize)]
+struct ConversationExport<'a> {
+    version: u32,
+    exported_at: i64,
+    conversation: &'a Conversation,
+    messages: Vec<&'a Message>,
+}
+
+/// 文稿中的发言者名称
+#[derive(Debug, Clone)]
+pub struct Speakers {
+    pub user: String,
+    /// 角色名（未知时显示为“助手”）
+    pub character: Option<String>,
+}
+
+impl Default for Speakers {
+    fn default() -> Self {
+        Self {
+            user: "用户".to_string(),
+            character: None,
+        }
+    }
+}
+
+impl Speakers {
+    fn name(&self, role: &MessageRole) -> &str {
+        match role {
+            MessageRole::User => &self.user,
+            MessageRole::Assistant => self.character.as_deref().unwrap_or("助手"),
+            MessageRole::System => "系统",
+        }
+    }
+
+    fn role(&self, name: &str) -> MessageRole {
+        if name == self.user {
+            MessageRole::User
+        } else if name == "系统" {
+            MessageRole::System
+        } else {
+            MessageRole::Assistant
+        }
+    }
+}
+
+/// 导出时的角色信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedCharacter {
+    pub id: String,
+    pub name: String,
+}
+
+/// 会话中的附件（来自文件管理）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+    pub file_id: String,
+    /// 附件所属的消息
+    #[serde(default)]
+    pub message_id: Option<String>,
+    pub original_name: String,
+    pub mime_type: String,
+    pub size: i64,
+    /// 文件内容（base64，JSON 存档）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// 相对于文稿的链接（Markdown 导出到文件时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+/// 可无损导入的会话存档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionArchive {
+    pub version: u32,
+    pub exported_at: i64,
+    pub conversation: Conversation,
+    #[serde(default)]
+    pub character: Option<ArchivedCharacter>,
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub attachments: Vec<ArchivedAttachment>,
+}
+
+impl SessionArchive {
+    /// 创建存档（已删除的消息不导出）
+    pub fn new(
+        conversation: Conversation,
+        messages: Vec<Message>,
+        character: Option<ArchivedCharacter>,
+        attachments: Vec<ArchivedAttachment>,
+    ) -> Self {
+        Self {
+            version: SESSION_ARCHIVE_VERSION,
+            exported_at: Utc::now().timestamp(),
+            conversation,
+            character,
+            messages: messages.into_iter().filter(|m| !m.is_deleted()).collect(),
+            attachments,
+        }
+    }
+
+    pub fn speakers(&self) -> Speakers {
+        Speakers {
+            character: self.character.as_ref().map(|c| c.name.clone()),
+            ..Speakers::default()
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化会话失败: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let archive: Self = serde_json::from_str(json).map_err(|e| format!("解析会话存档失败: {}", e))?;
+        if archive.version > SESSION_ARCHIVE_VERSION {
+            return Err(format!("不支持的存档版本: {}", archive.version));
+        }
+        Ok(archive)
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|t| format!("{} UTC", t.format(TIMESTAMP_FORMAT)))
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn attachment_line(attachment: &ArchivedAttachment) -> String {
+    match &attachment.link {
+        Some(link) => format!("{}[{}]({})\n", ATTACHMENT_MARK, attachment.original_name, link.replace(' ', "%20")),
+        None => format!("{}{}\n", ATTACHMENT_MARK, attachment.original_name),
+    }
+}
+
+/// 导出为 Markdown
+pub fn to_markdown(conversation: &Conversation, messages: &[Message]) -> String {
+    transcript(conversation, messages, &Speakers::default(), &[])
+}
+
+/// 导出为 Markdown 文稿：消息标题带发言者与时间戳，附件列在所属消息之后，
+/// 不属于任何已导出消息的附件列在末尾
+pub fn transcript(
+    conversation: &Conversation,
+    messages: &[Message],
+    speakers: &Speakers,
+    attachments: &[ArchivedAttachment],
+) -> String {
+    let mut output = format!(
+        "# {}\n\n> 创建于 {}\n",
+        conversation.title,
+        format_timestamp(conversation.created_at)
+    );
+    if let Some(character) = &speakers.character {
+        output.push_str(&format!("> 角色：{}\n", character));
+    }
+
+    let messages: Vec<&Message> = messages.iter().filter(|m| !m.is_deleted()).collect();
+    for message in &messages {
+        output.push_str(&format!(
+            "\n## {} · {}{}\n\n{}\n",
+            speakers.name(&message.role),
+            format_timestamp(message.created_at),
+            if message.edited_at.is_some() { EDITED_MARK } else { "" },
+            message.to_markdown()
+        ));
+        let mut own = attachments
+            .iter()
+            .filter(|a| a.message_id.as_deref() == Some(message.id.as_str()))
+            .peekable();
+        if own.peek().is_some() {
+            output.push('\n');
+            own.for_each(|a| output.push_str(&attachment_line(a)));
+        }
+    }
+
+    let orphans: Vec<&ArchivedAttachment> = attachments
+        .iter()
+        .filter(|a| !messages.iter().any(|m| a.message_id.as_deref() == Some(m.id.as_str())))
+        .collect();
+    if !orphans.is_empty() {
+        output.push_str(&format!("\n{}\n\n", ATTACHMENTS_HEADING));
+        orphans.into_iter().for_each(|a| output.push_str(&attachment_line(a)));
+    }
+    output
+}
+
+/// 从 Markdown 文稿读回的消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptMessage {
+    pub role: MessageRole,
+    pub created_at: i64,
+    pub edited: bool,
+    pub content: String,
+}
+
+/// 从 Markdown 文稿读回的会话
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub title: String,
+    pub created_at: Option<i64>,
+    pub character: Option<String>,
+    pub messages: Vec<TranscriptMessage>,
+}
+
+fn parse_timestamp(text: &str) -> Option<i64> {
+    let text = text.trim().trim_end_matches(" UTC");
+    NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|t| Utc.from_utc_datetime(&t).timestamp())
+}
+
+/// 解析 [`transcript`] 生成的 Markdown 文稿；只有 `## 发言者 · 时间` 形式的标题才会开始新消息，
+/// 附件行被忽略
+pub fn parse_transcript(markdown: &str) -> Result<Transcript, String> {
+    let mut title = None;
+    let mut created_at = None;
+    let mut character = None;
+    // (发言者, 消息)，正文在遇到下一个标题时写入
+    let mut messages: Vec<(String, TranscriptMessage)> = Vec::new();
+    let mut body: Vec<&str> = Vec::new();
+
+    fn flush(messages: &mut [(String, TranscriptMessage)], body: &mut Vec<&str>) {
+        if let Some((_, message)) = messages.last_mut() {
+            message.content = body.join("\n").trim().to_string();
+        }
+        body.clear();
+    }
+
+    for line in markdown.lines() {
+        if let Some(captures) = MESSAGE_HEADING.captures(line) {
+            flush(&mut messages, &mut body);
+            let created = parse_timestamp(&captures[2]).ok_or("消息时间格式错误")?;
+            messages.push((captures[1].to_string(), TranscriptMessage {
+                role: MessageRole::Assistant,
+                created_at: created,
+                edited: captures.get(3).is_some(),
+                content: String::new(),
+            }));
+        } else if line == ATTACHMENTS_HEADING {
+            // 末尾的附件列表之后没有消息
+            break;
+        } else if messages.is_empty() {
+            if let Some(text) = line.strip_prefix("# ").filter(|_| title.is_none()) {
+                title = Some(text.trim().to_string());
+            } else if let Some(text) = line.strip_prefix("> 创建于 ") {
+                created_at = parse_timestamp(text);
+            } else if let Some(text) = line.strip_prefix("> 角色：") {
+                character = Some(text.trim().to_string());
+            }
+        } else if !line.starts_with(ATTACHMENT_MARK) {
+            body.push(line);
+        }
+    }
+    flush(&mut messages, &mut body);
+
+    let speakers = Speakers {
+        character: character.clone(),
+        ..Speakers::default()
+    };
+    let messages: Vec<TranscriptMessage> = messages
+        .into_iter()
+        .map(|(name, message)| TranscriptMessage {
+            role: speakers.role(&name),
+            ..message
+        })
+        .filter(|message| !message.content.is_empty())
+        .collect();
+    if messages.is_empty() {
+        return Err("文稿中没有可导入的消息".to_string());
+    }
+
+    Ok(Transcript {
+        title: title.unwrap_or_else(|| "导入的会话".to_string()),
+        created_at,
+        character,
+        messages,
+    })
+}
+
+/// 导出为 JSON（保留结构化内容块）
+pub fn to_json(conversation: &Conversation, messages: &[Message]) -> Result<String, String> {
+    let export = ConversationExport {
+        version: EXPORT_FORMAT_VERSION,
+        exported_at: Utc::now().timestamp(),
+        conversation,
+        messages: messages.iter().filter(|m| !m.is_deleted()).collect(),
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| format!("序列化会话失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::message_content::ContentBlock;
+
+    fn conversation() -> Conversation {
+        Conversation {
+            id: "session".to_string(),
+            title: "白板讨论".to_string(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn message(content: &str, payload: Option<Vec<ContentBlock>>, deleted: bool) -> Message {
+        Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: "session".to_string(),
+            role: MessageRole::User,
+            content: content.to_string(),
+            created_at: 0,
+            edited_at: None,
+            deleted_at: deleted.then_some(1),
+            payload,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_renders_blocks_and_skips_deleted() {
+        let blocks = vec![ContentBlock::Code { language: Some("rust".into()), code: "let x = 1;".into() }];
+        let messages = vec![
+            message("let x = 1;", Some(blocks), false),
+            message("", None, true),
+        ];
+        let markdown = to_markdown(&conversation(), &messages);
+        assert!(markdown.contains("```rust\nlet x = 1;\n```"));
+        assert_eq!(markdown.matches("## 用户").count(), 1);
+    }
+
+    #[test]
+    fn test_json_keeps_payload() {
+        let blocks = vec![ContentBlock::Text { text: "hi".into() }];
+        let json = to_json(&conversation(), &[message("hi", Some(blocks), false)]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["messages"][0]["payload"][0]["type"], "text");
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let mut reply = message("你好呀！\n\n## 不是标题", None, false);
+        reply.role = MessageRole::Assistant;
+        reply.created_at = 60;
+        reply.edited_at = Some(90);
+        let messages = vec![message("早上好", None, false), reply.clone()];
+        let attachments = vec![ArchivedAttachment {
+            file_id: "f1".into(),
+            message_id: Some(reply.id.clone()),
+            original_name: "照片 1.png".into(),
+            mime_type: "image/png".into(),
+            size: 3,
+            data: None,
+            link: Some("files/照片 1.png".into()),
+        }];
+        let speakers = Speakers { character: Some("Shizuku".into()), ..Speakers::default() };
+
+        let markdown = transcript(&conversation(), &messages, &speakers, &attachments);
+        assert!(markdown.contains("## Shizuku · 1970-01-01 00:01:00 UTC（已编辑）"));
+        assert!(markdown.contains("📎 [照片 1.png](files/照片%201.png)"));
+
+        let parsed = parse_transcript(&markdown).unwrap();
+        assert_eq!(parsed.title, "白板讨论");
+        assert_eq!(parsed.character.as_deref(), Some("Shizuku"));
+        assert_eq!(parsed.messages.len(), 2);
+        assert_eq!(parsed.messages[0].role, MessageRole::User);
+        assert_eq!(parsed.messages[1].role, MessageRole::Assistant);
+        assert_eq!(parsed.messages[1].content, "你好呀！\n\n## 不是标题");
+        assert_eq!(parsed.messages[1].created_at, 60);
+        assert!(parsed.messages[1].edited);
+    }
+
+    #[test]
+    fn test_archive_reads_version_one_export() {
+        let json = to_json(&conversation(), &[message("hi", None, false), message("", None, true)]).unwrap();
+        let archive = SessionArchive::from_json(&json).unwrap();
+        assert_eq!(archive.messages.len(), 1);
+        assert!(archive.attachments.is_empty());
+
+        let mut future: serde_json::Value = serde_json::from_str(&json).unwrap();
+        future["version"] = serde_json::json!(SESSION_ARCHIVE_VERSION + 1);
+        assert!(SessionArchive::from_json(&future.to_string()).is_err());
+    }
+}
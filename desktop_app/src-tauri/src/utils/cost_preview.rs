@@ -0,0 +1,234 @@
+//! # 对话费用预估
+//!
+//! 组装后的提示词（含 RAG / 上下文）超过阈值时，发送前预估 token 数与费用并要求前端确认：
+//!
+//! - token 数按字符粗略估算：CJK 字符约 1 token，其他字符约 4 个 1 token，每条消息另加固定开销
+//! - 费用按价格表计算，模型 ID 按最长前缀匹配；本地模型与未知模型不计费用
+//! - 会话可选择“不再询问”，仅在本次运行期间有效
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 每条消息的固定开销（角色标记等）
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// 单个模型的价格（每 1000 token）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// 模型 ID 前缀
+    pub model_prefix: String,
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+impl ModelPrice {
+    fn new(model_prefix: &str, input_per_1k: f64, output_per_1k: f64) -> Self {
+        Self {
+            model_prefix: model_prefix.to_string(),
+            input_per_1k,
+            output_per_1k,
+        }
+    }
+}
+
+/// 默认价格表（美元）
+pub fn default_prices() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice::new("gpt-4o-mini", 0.00015, 0.0006),
+        ModelPrice::new("gpt-4o", 0.0025, 0.01),
+        ModelPrice::new("gpt-4-turbo", 0.01, 0.03),
+        ModelPrice::new("gpt-4", 0.03, 0.06),
+        ModelPrice::new("gpt-3.5-turbo", 0.0005, 0.0015),
+        ModelPrice::new("claude-3-haiku", 0.00025, 0.00125),
+        ModelPrice::new("claude-3-5-sonnet", 0.003, 0.015),
+        ModelPrice::new("claude-3-opus", 0.015, 0.075),
+        ModelPrice::new("deepseek-chat", 0.00027, 0.0011),
+        ModelPrice::new("local_llm", 0.0, 0.0),
+    ]
+}
+
+/// 费用预估设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostPreviewSettings {
+    pub enabled: bool,
+    /// 预估提示词 token 数超过该值时要求确认
+    pub threshold_tokens: u32,
+    /// 未指定 max_tokens 时假设的回复长度
+    pub expected_completion_tokens: u32,
+    pub currency: String,
+    pub prices: Vec<ModelPrice>,
+}
+
+impl Default for CostPreviewSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_tokens: 4000,
+            expected_completion_tokens: 512,
+            currency: "USD".to_string(),
+            prices: default_prices(),
+        }
+    }
+}
+
+impl CostPreviewSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.threshold_tokens == 0 {
+            return Err("费用确认阈值必须大于 0".to_string());
+        }
+        if self
+            .prices
+            .iter()
+            .any(|p| p.model_prefix.trim().is_empty() || p.input_per_1k < 0.0 || p.output_per_1k < 0.0)
+        {
+            return Err("价格表中的模型前缀不能为空，价格不能为负数".to_string());
+        }
+        Ok(())
+    }
+
+    /// 模型价格（最长前缀匹配）
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|p| model.starts_with(p.model_prefix.as_str()))
+            .max_by_key(|p| p.model_prefix.len())
+    }
+}
+
+/// 发送前的费用预估
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostPreview {
+    pub model: Option<String>,
+    pub estimated_prompt_tokens: u32,
+    pub estimated_completion_tokens: u32,
+    /// 价格未知时为空
+    pub estimated_cost: Option<f64>,
+    pub currency: String,
+    pub threshold_tokens: u32,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名 / 片假名
+        | 0x3400..=0x4DBF   // CJK 扩展 A
+        | 0x4E00..=0x9FFF   // CJK 统一汉字
+        | 0xAC00..=0xD7AF   // 韩文音节
+        | 0xF900..=0xFAFF   // CJK 兼容汉字
+        | 0xFF00..=0xFFEF)  // 全角符号
+}
+
+/// 粗略估算文本的 token 数
+pub fn estimate_tokens(text: &str) -> u32 {
+    let (cjk, other) = text
+        .chars()
+        .fold((0u32, 0u32), |(cjk, other), c| if is_cjk(c) { (cjk + 1, other) } else { (cjk, other + 1) });
+    cjk + (other + 3) / 4
+}
+
+/// 预估一组消息的费用；未超过阈值时返回 `None`
+pub fn preview<'a>(
+    settings: &CostPreviewSettings,
+    model: Option<&str>,
+    messages: impl IntoIterator<Item = &'a str>,
+    max_tokens: Option<u32>,
+) -> Option<CostPreview> {
+    if !settings.enabled {
+        return None;
+    }
+    let prompt_tokens: u32 = messages
+        .into_iter()
+        .map(|content| estimate_tokens(content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum();
+    if prompt_tokens <= settings.threshold_tokens {
+        return None;
+    }
+    let completion_tokens = max_tokens.unwrap_or(settings.expected_completion_tokens);
+    let estimated_cost = model.and_then(|m| settings.price_for(m)).map(|price| {
+        (f64::from(prompt_tokens) * price.input_per_1k + f64::from(completion_tokens) * price.output_per_1k) / 1000.0
+    });
+    Some(CostPreview {
+        model: model.map(str::to_string),
+        estimated_prompt_tokens: prompt_tokens,
+        estimated_completion_tokens: completion_tokens,
+        estimated_cost,
+        currency: settings.currency.clone(),
+        threshold_tokens: settings.threshold_tokens,
+    })
+}
+
+lazy_static! {
+    static ref SETTINGS: RwLock<CostPreviewSettings> = RwLock::new(CostPreviewSettings::default());
+    /// 选择了“不再询问”的会话
+    static ref SKIPPED_SESSIONS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+}
+
+pub fn settings() -> CostPreviewSettings {
+    SETTINGS.read().clone()
+}
+
+pub fn set_settings(settings: CostPreviewSettings) {
+    *SETTINGS.write() = settings;
+}
+
+pub fn set_session_skipped(session_id: &str, skipped: bool) {
+    let mut sessions = SKIPPED_SESSIONS.write();
+    if skipped {
+        sessions.insert(session_id.to_string());
+    } else {
+        sessions.remove(session_id);
+    }
+}
+
+pub fn is_session_skipped(session_id: &str) -> bool {
+    SKIPPED_SESSIONS.read().contains(session_id)
+}
+
+/// 发送前检查：需要用户确认时返回预估
+pub fn check<'a>(
+    session_id: Option<&str>,
+    model: Option<&str>,
+    messages: impl IntoIterator<Item = &'a str>,
+    max_tokens: Option<u32>,
+) -> Option<CostPreview> {
+    if session_id.is_some_and(is_session_skipped) {
+        return None;
+    }
+    preview(&SETTINGS.read(), model, messages, max_tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello world!"), 3);
+        assert_eq!(estimate_tokens("你好，世界"), 5);
+        assert_eq!(estimate_tokens("Zishu 老师"), 4);
+    }
+
+    #[test]
+    fn test_preview_only_above_threshold() {
+        let settings = CostPreviewSettings {
+            threshold_tokens: 100,
+            ..CostPreviewSettings::default()
+        };
+        assert_eq!(preview(&settings, Some("gpt-4o"), ["short"], None), None);
+
+        let long = "a".repeat(4000);
+        let result = preview(&settings, Some("gpt-4o-mini-2024"), [long.as_str(), "hi"], Some(1000)).unwrap();
+        assert_eq!(result.estimated_prompt_tokens, 1000 + 1 + 2 * MESSAGE_OVERHEAD_TOKENS);
+        assert_eq!(result.estimated_completion_tokens, 1000);
+        // 最长前缀匹配 gpt-4o-mini 而不是 gpt-4o / gpt-4
+        let expected = (1009.0 * 0.00015 + 1000.0 * 0.0006) / 1000.0;
+        assert!((result.estimated_cost.unwrap() - expected).abs() < 1e-12);
+
+        assert_eq!(preview(&settings, Some("unknown-model"), [long.as_str()], None).unwrap().estimated_cost, None);
+        let disabled = CostPreviewSettings { enabled: false, ..settings };
+        assert_eq!(preview(&disabled, None, [long.as_str()], None), None);
+    }
+}
@@ -0,0 +1,349 @@
+//! # 崩溃报告
+//!
+//! [`install`] 在启动最早期安装 panic 钩子与原生崩溃处理器：
+//!
+//! - panic：立即在应用数据目录的 `crash_reports/` 下写入报告（消息、位置、线程、调用栈）
+//! - 原生崩溃（段错误、非法指令、Windows 异常等）：崩溃时只写入 `pending.dmp` minidump，
+//!   下次启动时再补全为报告
+//!
+//! 报告同时记录应用版本、系统信息与最近 200 行日志。报告只保存在本机，用户在崩溃报告界面
+//! 确认后才会通过 `submit_crash_report` 上传（见 `commands::crash_reports`）。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use sysinfo::{System, SystemExt};
+use tracing::{info, warn};
+
+/// 报告目录（位于应用数据目录下）
+pub const REPORT_DIR: &str = "crash_reports";
+/// 报告中保留的日志行数
+pub const LOG_TAIL_LINES: usize = 200;
+/// 原生崩溃时写入的 minidump，下次启动时整理为报告
+const PENDING_MINIDUMP: &str = "pending.dmp";
+/// 日志文件名前缀（按天滚动，见 `main.rs` 中的 `init_logging`）
+const LOG_FILE_PREFIX: &str = "zishu-sensei.log";
+
+/// 崩溃类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    Panic,
+    Native,
+}
+
+/// 系统信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OsInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub arch: String,
+}
+
+impl OsInfo {
+    pub fn current() -> Self {
+        Self {
+            name: std::env::consts::OS.to_string(),
+            version: System::new().long_os_version(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// 崩溃报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    pub message: String,
+    /// panic 发生的源码位置
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    pub log_tail: Vec<String>,
+    pub app_version: String,
+    pub os: OsInfo,
+    pub created_at: i64,
+    /// minidump 文件名（与报告位于同一目录）
+    pub minidump: Option<String>,
+    /// 上传时间，未上传时为空
+    #[serde(default)]
+    pub submitted_at: Option<i64>,
+}
+
+impl CrashReport {
+    fn new(kind: CrashKind, message: String, log_dir: Option<&Path>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            message,
+            location: None,
+            thread: None,
+            backtrace: None,
+            log_tail: log_dir.map(|dir| read_log_tail(dir, LOG_TAIL_LINES)).unwrap_or_default(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: OsInfo::current(),
+            created_at: chrono::Utc::now().timestamp(),
+            minidump: None,
+            submitted_at: None,
+        }
+    }
+}
+
+/// 文本的最后 `count` 行
+pub fn tail_lines(text: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(count);
+    lines[start..].iter().map(|line| line.to_string()).collect()
+}
+
+/// 最近写入的日志文件
+fn latest_log_file(log_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(log_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_PREFIX))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// 读取最近日志文件的最后 `count` 行
+pub fn read_log_tail(log_dir: &Path, count: usize) -> Vec<String> {
+    latest_log_file(log_dir)
+        .and_then(|path| fs::read(path).ok())
+        .map(|bytes| tail_lines(&String::from_utf8_lossy(&bytes), count))
+        .unwrap_or_default()
+}
+
+/// 报告 ID 只能是 UUID，防止拼接出目录外的路径
+fn valid_report_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+pub fn save_report(dir: &Path, report: &CrashReport) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report)?;
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+pub fn load_report(dir: &Path, id: &str) -> Option<CrashReport> {
+    if !valid_report_id(id) {
+        return None;
+    }
+    let json = fs::read_to_string(dir.join(format!("{}.json", id))).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// 列出报告，最新的在前
+pub fn list_reports(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    reports
+}
+
+/// 把上次运行留下的 minidump 整理为报告
+pub fn collect_pending_minidump(dir: &Path, log_dir: Option<&Path>) -> Option<CrashReport> {
+    let pending = dir.join(PENDING_MINIDUMP);
+    if !pending.exists() {
+        return None;
+    }
+    let mut report = CrashReport::new(CrashKind::Native, "上次运行时发生原生崩溃".to_string(), log_dir);
+    let minidump = format!("{}.dmp", report.id);
+    if let Err(e) = fs::rename(&pending, dir.join(&minidump)) {
+        warn!("整理崩溃转储失败: {}", e);
+        return None;
+    }
+    report.minidump = Some(minidump);
+    Some(report)
+}
+
+// ================================
+// 安装
+// ================================
+
+/// 报告与日志目录
+#[derive(Debug, Clone)]
+struct Paths {
+    reports: PathBuf,
+    logs: Option<PathBuf>,
+}
+
+lazy_static! {
+    static ref PATHS: Mutex<Option<Paths>> = Mutex::new(None);
+    /// 原生崩溃处理器，释放时解除注册
+    static ref NATIVE_HANDLER: Mutex<Option<crash_handler::CrashHandler>> = Mutex::new(None);
+}
+
+/// 正在写 panic 报告（写报告时再次 panic 不再处理）
+static IN_PANIC_HOOK: AtomicBool = AtomicBool::new(false);
+
+/// 报告目录；应用数据目录不可用时为空
+pub fn report_dir() -> Option<PathBuf> {
+    PATHS.lock().as_ref().map(|paths| paths.reports.clone())
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+fn record_panic(message: String, location: Option<String>) {
+    let Some(paths) = PATHS.lock().clone() else {
+        return;
+    };
+    let mut report = CrashReport::new(CrashKind::Panic, message, paths.logs.as_deref());
+    report.location = location;
+    report.thread = std::thread::current().name().map(str::to_string);
+    report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+    match save_report(&paths.reports, &report) {
+        Ok(path) => eprintln!("崩溃报告已保存: {}", path.display()),
+        Err(e) => eprintln!("保存崩溃报告失败: {}", e),
+    }
+}
+
+/// 安装 panic 钩子与原生崩溃处理器，并整理上次运行留下的原生崩溃（在初始化日志后调用）
+pub fn install() {
+    let reports = match crate::utils::config::get_app_data_dir() {
+        Ok(dir) => dir.join(REPORT_DIR),
+        Err(e) => {
+            warn!("无法获取应用数据目录，不会保存崩溃报告: {}", e);
+            return;
+        }
+    };
+    let logs = crate::utils::config::get_app_log_dir().ok();
+    *PATHS.lock() = Some(Paths { reports: reports.clone(), logs: logs.clone() });
+
+    if let Some(report) = collect_pending_minidump(&reports, logs.as_deref()) {
+        match save_report(&reports, &report) {
+            Ok(_) => warn!("发现上次运行的原生崩溃，已生成崩溃报告 {}", report.id),
+            Err(e) => warn!("保存崩溃报告失败: {}", e),
+        }
+    }
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if !IN_PANIC_HOOK.swap(true, Ordering::SeqCst) {
+            record_panic(panic_message(info.payload()), info.location().map(|location| location.to_string()));
+            IN_PANIC_HOOK.store(false, Ordering::SeqCst);
+        }
+        previous(info);
+    }));
+
+    if let Err(e) = fs::create_dir_all(&reports) {
+        warn!("创建崩溃报告目录失败: {}", e);
+        return;
+    }
+    match native::attach(reports.join(PENDING_MINIDUMP)) {
+        Ok(handler) => {
+            *NATIVE_HANDLER.lock() = Some(handler);
+            info!("崩溃报告已启用");
+        }
+        Err(e) => warn!("安装原生崩溃处理器失败，仅记录 panic: {}", e),
+    }
+}
+
+/// 原生崩溃处理：在崩溃线程中直接写 minidump，之后交还系统默认处理
+mod native {
+    use crash_handler::{make_crash_event, CrashContext, CrashEventResult, CrashHandler};
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    #[cfg(target_os = "linux")]
+    fn write_minidump(context: &CrashContext, file: &mut File) {
+        use minidump_writer::{crash_context, minidump_writer::MinidumpWriter};
+
+        let _ = MinidumpWriter::new(context.pid, context.tid)
+            .set_crash_context(crash_context::CrashContext { inner: context.clone() })
+            .dump(file);
+    }
+
+    #[cfg(target_os = "windows")]
+    fn write_minidump(context: &CrashContext, file: &mut File) {
+        use minidump_writer::minidump_writer::MinidumpWriter;
+
+        let _ = MinidumpWriter::dump_crash_context(context, None, file);
+    }
+
+    #[cfg(target_os = "macos")]
+    fn write_minidump(context: &CrashContext, file: &mut File) {
+        use minidump_writer::minidump_writer::MinidumpWriter;
+
+        let _ = MinidumpWriter::with_crash_context(context.clone()).dump(file);
+    }
+
+    pub fn attach(minidump_path: PathBuf) -> Result<CrashHandler, String> {
+        // SAFETY: 回调运行在崩溃上下文中，只创建文件并写入 minidump，不访问应用状态
+        let event = unsafe {
+            make_crash_event(move |context: &CrashContext| {
+                if let Ok(mut file) = File::create(&minidump_path) {
+                    write_minidump(context, &mut file);
+                }
+                // 不拦截崩溃，交还系统默认处理（进程照常退出）
+                CrashEventResult::Handled(false)
+            })
+        };
+        CrashHandler::attach(event).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tail_lines_and_log_tail() {
+        let text = (1..=250).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let tail = tail_lines(&text, LOG_TAIL_LINES);
+        assert_eq!(tail.len(), LOG_TAIL_LINES);
+        assert_eq!(tail.first().unwrap(), "line 51");
+        assert_eq!(tail_lines("a\nb", 10), vec!["a", "b"]);
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("other.txt"), "ignored").unwrap();
+        fs::write(dir.path().join(format!("{}.2024-01-01", LOG_FILE_PREFIX)), text).unwrap();
+        assert_eq!(read_log_tail(dir.path(), 3), vec!["line 248", "line 249", "line 250"]);
+    }
+
+    #[test]
+    fn test_reports_roundtrip_and_pending_minidump() {
+        let dir = tempdir().unwrap();
+        let mut older = CrashReport::new(CrashKind::Panic, "boom".to_string(), None);
+        older.created_at = 100;
+        save_report(dir.path(), &older).unwrap();
+
+        assert!(collect_pending_minidump(dir.path(), None).is_none());
+        fs::write(dir.path().join(PENDING_MINIDUMP), b"MDMP").unwrap();
+        let native = collect_pending_minidump(dir.path(), None).unwrap();
+        save_report(dir.path(), &native).unwrap();
+        assert!(!dir.path().join(PENDING_MINIDUMP).exists());
+        assert!(dir.path().join(native.minidump.as_deref().unwrap()).exists());
+
+        let reports = list_reports(dir.path());
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].kind, CrashKind::Native);
+        assert_eq!(load_report(dir.path(), &older.id), Some(older));
+        assert!(load_report(dir.path(), "../secrets").is_none());
+    }
+}
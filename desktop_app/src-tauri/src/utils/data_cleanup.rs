@@ -1,34 +1,501 @@
-//! 数据清理管理器 (Simplified for PostgreSQL migration)
+//! 数据清理管理器
+//!
+//! 按类别的数据保留策略：聊天记录、日志、性能数据、审计日志与文件各自有保留天数（0 表示永久保留），
+//! 超期的数据由后台任务定期删除。[`DataCleanupManager::preview`] 只统计将被删除的数据，
+//! [`DataCleanupManager::purge_all`] 清空所有个人数据并回收数据库空间。
+//!
+//! 策略的加载保存、定时清理与 Redis / Qdrant 的清理见 `commands::privacy`。
 
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tracing::{info, warn};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::database::DbPool;
+use crate::utils::disk_guard;
+
+lazy_static::lazy_static! {
+    static ref RETENTION_POLICY: RwLock<RetentionPolicy> = RwLock::new(RetentionPolicy::default());
+}
+
+/// 保留天数上限（约十年）
+const MAX_RETENTION_DAYS: u32 = 3650;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CleanupType {
     ChatHistory,
     TempFiles,
     Cache,
     Logs,
+    PerformanceData,
+    AuditLogs,
+    Files,
 }
 
+/// 受保留策略约束的类别
+pub const RETENTION_CATEGORIES: [CleanupType; 5] = [
+    CleanupType::ChatHistory,
+    CleanupType::Logs,
+    CleanupType::PerformanceData,
+    CleanupType::AuditLogs,
+    CleanupType::Files,
+];
+
+/// 清空个人数据时截断的表（不存在的表跳过）
+pub const PERSONAL_TABLES: &[&str] = &[
+    "conversations",
+    "messages",
+    "message_revisions",
+    "chat_outbox",
+    "notifications",
+    "logs",
+    "logs_archive",
+    "adapter_logs",
+    "performance_metrics",
+    "performance_snapshots",
+    "performance_alerts",
+    "network_metrics",
+    "user_operations",
+    "permission_usage_logs",
+    "consent_logs",
+    "encrypted_data_access_log",
+    "error_records",
+    "error_reports",
+    "files",
+    "file_history",
+    "pet_interactions",
+    "focus_sessions",
+    "app_sessions",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CleanupResult {
     pub items_deleted: usize,
     pub bytes_freed: u64,
 }
 
-pub struct DataCleanupManager {}
+/// 数据保留策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub auto_cleanup_enabled: bool,
+    /// 定期清理的间隔（小时）
+    pub interval_hours: u64,
+    pub chat_history_days: u32,
+    pub logs_days: u32,
+    pub performance_days: u32,
+    pub audit_log_days: u32,
+    pub files_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            auto_cleanup_enabled: true,
+            interval_hours: 24,
+            chat_history_days: 0,
+            logs_days: 30,
+            performance_days: 14,
+            audit_log_days: 180,
+            files_days: 0,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=168).contains(&self.interval_hours) {
+            return Err("清理间隔必须在 1 到 168 小时之间".to_string());
+        }
+        let days = [
+            self.chat_history_days,
+            self.logs_days,
+            self.performance_days,
+            self.audit_log_days,
+            self.files_days,
+        ];
+        if days.iter().any(|&d| d > MAX_RETENTION_DAYS) {
+            return Err(format!("保留天数不能超过 {} 天", MAX_RETENTION_DAYS));
+        }
+        Ok(())
+    }
+
+    /// 类别的保留天数；永久保留或不受策略约束的类别为空
+    pub fn ttl_days(&self, category: CleanupType) -> Option<u32> {
+        let days = match category {
+            CleanupType::ChatHistory => self.chat_history_days,
+            CleanupType::Logs => self.logs_days,
+            CleanupType::PerformanceData => self.performance_days,
+            CleanupType::AuditLogs => self.audit_log_days,
+            CleanupType::Files => self.files_days,
+            CleanupType::TempFiles | CleanupType::Cache => 0,
+        };
+        (days > 0).then_some(days)
+    }
+
+    /// 早于该时间的数据视为过期
+    pub fn cutoff(&self, category: CleanupType, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.ttl_days(category).map(|days| now - Duration::days(i64::from(days)))
+    }
+}
+
+pub fn current_policy() -> RetentionPolicy {
+    RETENTION_POLICY.read().clone()
+}
+
+pub fn set_policy(policy: RetentionPolicy) -> Result<(), String> {
+    policy.validate()?;
+    *RETENTION_POLICY.write() = policy;
+    Ok(())
+}
+
+/// 时间列的存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeColumn {
+    /// BIGINT Unix 秒
+    Seconds,
+    /// BIGINT Unix 毫秒
+    Millis,
+    /// TIMESTAMP（UTC）
+    Timestamp,
+    /// TIMESTAMPTZ
+    TimestampTz,
+}
+
+/// 按时间列过期的表
+#[derive(Debug, Clone, Copy)]
+struct ExpiringTable {
+    table: &'static str,
+    column: &'static str,
+    kind: TimeColumn,
+}
+
+const fn expiring(table: &'static str, column: &'static str, kind: TimeColumn) -> ExpiringTable {
+    ExpiringTable { table, column, kind }
+}
+
+// 先删过期消息，再删不再活跃的会话（级联删除剩余消息）
+const CHAT_HISTORY_TABLES: &[ExpiringTable] = &[
+    expiring("messages", "created_at", TimeColumn::Seconds),
+    expiring("conversations", "updated_at", TimeColumn::Seconds),
+];
+
+const LOG_TABLES: &[ExpiringTable] = &[
+    expiring("logs", "timestamp", TimeColumn::Timestamp),
+    expiring("logs_archive", "timestamp", TimeColumn::Timestamp),
+    expiring("adapter_logs", "timestamp_ms", TimeColumn::Millis),
+];
+
+const PERFORMANCE_TABLES: &[ExpiringTable] = &[
+    expiring("performance_metrics", "timestamp", TimeColumn::Seconds),
+    expiring("performance_snapshots", "timestamp", TimeColumn::Seconds),
+    expiring("performance_alerts", "timestamp", TimeColumn::Seconds),
+    expiring("network_metrics", "timestamp", TimeColumn::Seconds),
+    expiring("user_operations", "timestamp", TimeColumn::Seconds),
+];
+
+const AUDIT_TABLES: &[ExpiringTable] = &[
+    expiring("message_revisions", "revised_at", TimeColumn::Seconds),
+    expiring("permission_usage_logs", "timestamp", TimeColumn::Seconds),
+    expiring("consent_logs", "created_at", TimeColumn::TimestampTz),
+];
+
+/// 类别对应的表（文件单独处理，需要同时删除磁盘上的文件）
+fn expiring_tables(category: CleanupType) -> &'static [ExpiringTable] {
+    match category {
+        CleanupType::ChatHistory => CHAT_HISTORY_TABLES,
+        CleanupType::Logs => LOG_TABLES,
+        CleanupType::PerformanceData => PERFORMANCE_TABLES,
+        CleanupType::AuditLogs => AUDIT_TABLES,
+        CleanupType::Files | CleanupType::TempFiles | CleanupType::Cache => &[],
+    }
+}
+
+/// 与 Unix 秒参数 `$1` 比较的过期条件
+fn expired_condition(column: &str, kind: TimeColumn) -> String {
+    let cutoff = match kind {
+        TimeColumn::Seconds => "$1::BIGINT",
+        TimeColumn::Millis => "$1::BIGINT * 1000",
+        TimeColumn::Timestamp => "(to_timestamp($1::BIGINT) AT TIME ZONE 'UTC')",
+        TimeColumn::TimestampTz => "to_timestamp($1::BIGINT)",
+    };
+    format!("{} < {}", column, cutoff)
+}
+
+/// 一个类别的清理统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCleanup {
+    pub category: CleanupType,
+    pub retention_days: u32,
+    /// 早于该时间（Unix 秒）的数据被删除
+    pub cutoff: i64,
+    pub items: usize,
+    /// 可统计的释放空间（文件与日志文件）
+    pub bytes: u64,
+}
+
+/// 清理报告；`dry_run` 为真时只统计未删除
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub dry_run: bool,
+    pub categories: Vec<CategoryCleanup>,
+    pub total_items: usize,
+    pub total_bytes: u64,
+}
+
+impl CleanupReport {
+    fn new(dry_run: bool, categories: Vec<CategoryCleanup>) -> Self {
+        Self {
+            dry_run,
+            total_items: categories.iter().map(|c| c.items).sum(),
+            total_bytes: categories.iter().map(|c| c.bytes).sum(),
+            categories,
+        }
+    }
+
+    pub fn result(&self) -> CleanupResult {
+        CleanupResult {
+            items_deleted: self.total_items,
+            bytes_freed: self.total_bytes,
+        }
+    }
+}
+
+pub struct DataCleanupManager {
+    pool: DbPool,
+    log_dir: Option<PathBuf>,
+}
 
 impl DataCleanupManager {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool, log_dir: None }
     }
 
-    pub async fn cleanup(&self, _types: Vec<CleanupType>) -> Result<CleanupResult, String> {
-        Ok(CleanupResult::default())
+    /// 同时清理该目录下的日志文件
+    pub fn with_log_dir(mut self, log_dir: PathBuf) -> Self {
+        self.log_dir = Some(log_dir);
+        self
     }
 
-    pub async fn schedule_cleanup(&self, _interval_hours: u64) -> Result<(), String> {
-        Ok(())
+    /// 统计按策略将被删除的数据
+    pub async fn preview(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<CleanupReport, String> {
+        self.run(policy, &RETENTION_CATEGORIES, now, true).await
+    }
+
+    /// 按策略删除所选类别的过期数据
+    pub async fn cleanup(
+        &self,
+        policy: &RetentionPolicy,
+        types: &[CleanupType],
+        now: DateTime<Utc>,
+    ) -> Result<CleanupReport, String> {
+        let report = self.run(policy, types, now, false).await?;
+        if report.total_items > 0 {
+            info!("🧹 按保留策略清理了 {} 条数据，释放 {} 字节文件", report.total_items, report.total_bytes);
+        }
+        Ok(report)
+    }
+
+    async fn run(
+        &self,
+        policy: &RetentionPolicy,
+        types: &[CleanupType],
+        now: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<CleanupReport, String> {
+        let mut categories = Vec::new();
+        for &category in types {
+            let (Some(retention_days), Some(cutoff)) = (policy.ttl_days(category), policy.cutoff(category, now)) else {
+                continue;
+            };
+            let cutoff = cutoff.timestamp();
+            let (items, bytes) = match category {
+                CleanupType::Files => self.expire_files(cutoff, dry_run).await?,
+                _ => {
+                    let rows = self.expire_rows(category, cutoff, dry_run).await?;
+                    let files = if category == CleanupType::Logs {
+                        self.expire_log_files(retention_days, dry_run)
+                    } else {
+                        (0, 0)
+                    };
+                    (rows + files.0, files.1)
+                }
+            };
+            categories.push(CategoryCleanup { category, retention_days, cutoff, items, bytes });
+        }
+        Ok(CleanupReport::new(dry_run, categories))
+    }
+
+    async fn table_exists(&self, client: &deadpool_postgres::Object, table: &str) -> Result<bool, String> {
+        client
+            .query_one("SELECT to_regclass($1::TEXT) IS NOT NULL", &[&table])
+            .await
+            .map(|row| row.get(0))
+            .map_err(|e| format!("检查数据表 {} 失败: {}", table, e))
+    }
+
+    async fn expire_rows(&self, category: CleanupType, cutoff: i64, dry_run: bool) -> Result<usize, String> {
+        let client = self.pool.get().await.map_err(|e| format!("获取数据库连接失败: {}", e))?;
+        let mut total = 0;
+        for spec in expiring_tables(category) {
+            if !self.table_exists(&client, spec.table).await? {
+                continue;
+            }
+            let condition = expired_condition(spec.column, spec.kind);
+            let count = if dry_run {
+                let sql = format!("SELECT COUNT(*) FROM {} WHERE {}", spec.table, condition);
+                client.query_one(&sql, &[&cutoff]).await.map(|row| row.get::<_, i64>(0) as u64)
+            } else {
+                let sql = format!("DELETE FROM {} WHERE {}", spec.table, condition);
+                client.execute(&sql, &[&cutoff]).await
+            };
+            total += count.map_err(|e| format!("清理 {} 失败: {}", spec.table, e))? as usize;
+        }
+        Ok(total)
+    }
+
+    async fn expire_files(&self, cutoff: i64, dry_run: bool) -> Result<(usize, u64), String> {
+        let client = self.pool.get().await.map_err(|e| format!("获取数据库连接失败: {}", e))?;
+        if !self.table_exists(&client, "files").await? {
+            return Ok((0, 0));
+        }
+        let condition = expired_condition("created_at", TimeColumn::Timestamp);
+        let sql = if dry_run {
+            format!("SELECT file_path, thumbnail_path, file_size FROM files WHERE {}", condition)
+        } else {
+            format!("DELETE FROM files WHERE {} RETURNING file_path, thumbnail_path, file_size", condition)
+        };
+        let rows = client
+            .query(&sql, &[&cutoff])
+            .await
+            .map_err(|e| format!("清理文件记录失败: {}", e))?;
+
+        let mut bytes = 0u64;
+        for row in &rows {
+            let size: i64 = row.get(2);
+            bytes += size.max(0) as u64;
+            if !dry_run {
+                remove_file_quietly(row.get::<_, String>(0));
+                if let Some(thumbnail) = row.get::<_, Option<String>>(1) {
+                    remove_file_quietly(thumbnail);
+                }
+            }
+        }
+        Ok((rows.len(), bytes))
+    }
+
+    fn expire_log_files(&self, retention_days: u32, dry_run: bool) -> (usize, u64) {
+        let Some(log_dir) = &self.log_dir else {
+            return (0, 0);
+        };
+        let mut count = 0;
+        let mut bytes = 0;
+        for (path, size) in disk_guard::old_log_files(log_dir, i64::from(retention_days)) {
+            if dry_run || fs::remove_file(&path).is_ok() {
+                count += 1;
+                bytes += size;
+            }
+        }
+        (count, bytes)
+    }
+
+    /// 清空所有个人数据表与日志文件，并回收数据库空间
+    pub async fn purge_all(&self) -> Result<CleanupResult, String> {
+        let client = self.pool.get().await.map_err(|e| format!("获取数据库连接失败: {}", e))?;
+        let mut tables = Vec::new();
+        for table in PERSONAL_TABLES {
+            if self.table_exists(&client, table).await? {
+                tables.push(*table);
+            }
+        }
+
+        let mut result = CleanupResult::default();
+        if tables.contains(&"files") {
+            let rows = client
+                .query("SELECT file_path, thumbnail_path, file_size FROM files", &[])
+                .await
+                .map_err(|e| format!("读取文件记录失败: {}", e))?;
+            for row in rows {
+                result.bytes_freed += row.get::<_, i64>(2).max(0) as u64;
+                remove_file_quietly(row.get::<_, String>(0));
+                if let Some(thumbnail) = row.get::<_, Option<String>>(1) {
+                    remove_file_quietly(thumbnail);
+                }
+            }
+        }
+
+        for table in &tables {
+            let count: i64 = client
+                .query_one(&format!("SELECT COUNT(*) FROM {}", table), &[])
+                .await
+                .map(|row| row.get(0))
+                .map_err(|e| format!("统计 {} 失败: {}", table, e))?;
+            result.items_deleted += count as usize;
+        }
+        if !tables.is_empty() {
+            client
+                .batch_execute(&format!("TRUNCATE {} CASCADE", tables.join(", ")))
+                .await
+                .map_err(|e| format!("清空个人数据失败: {}", e))?;
+            // VACUUM 不能在事务中执行，逐表回收空间
+            for table in &tables {
+                if let Err(e) = client.batch_execute(&format!("VACUUM (ANALYZE) {}", table)).await {
+                    warn!("回收 {} 的空间失败: {}", table, e);
+                }
+            }
+        }
+
+        if let Some(log_dir) = &self.log_dir {
+            for (path, size) in disk_guard::old_log_files(log_dir, 0) {
+                if fs::remove_file(&path).is_ok() {
+                    result.items_deleted += 1;
+                    result.bytes_freed += size;
+                }
+            }
+        }
+
+        info!("🗑️  已清空个人数据：{} 张表，{} 条记录", tables.len(), result.items_deleted);
+        Ok(result)
     }
 }
 
+fn remove_file_quietly(path: String) {
+    if let Err(e) = fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("删除文件 {} 失败: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_zero_days_keeps_data_forever() {
+        let policy = RetentionPolicy { chat_history_days: 0, logs_days: 7, ..Default::default() };
+        let now = Utc.with_ymd_and_hms(2026, 5, 10, 12, 0, 0).unwrap();
+        assert_eq!(policy.ttl_days(CleanupType::ChatHistory), None);
+        assert_eq!(policy.cutoff(CleanupType::Logs, now), Some(Utc.with_ymd_and_hms(2026, 5, 3, 12, 0, 0).unwrap()));
+        // 缓存与临时文件不受保留策略约束
+        assert_eq!(policy.ttl_days(CleanupType::Cache), None);
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(RetentionPolicy::default().validate().is_ok());
+        assert!(RetentionPolicy { interval_hours: 0, ..Default::default() }.validate().is_err());
+        assert!(RetentionPolicy { files_days: MAX_RETENTION_DAYS + 1, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_expired_condition_matches_column_units() {
+        assert_eq!(expired_condition("created_at", TimeColumn::Seconds), "created_at < $1::BIGINT");
+        assert_eq!(expired_condition("timestamp_ms", TimeColumn::Millis), "timestamp_ms < $1::BIGINT * 1000");
+        assert!(expiring_tables(CleanupType::ChatHistory).iter().all(|t| PERSONAL_TABLES.contains(&t.table)));
+        assert!(expiring_tables(CleanupType::AuditLogs).iter().all(|t| PERSONAL_TABLES.contains(&t.table)));
+    }
+}
@@ -0,0 +1,607 @@
+//! 数据目录迁移
+//!
+//! 将应用数据目录（配置、备份、日志、本地 SQLite 数据库文件等）迁移到用户指定的位置。
+//! 运行中只校验目标路径与剩余空间并记录待执行的迁移；下次启动时在日志与数据库打开之前
+//! 复制 → 校验 → 切换路径，任何一步失败都会删除已复制的文件并保持原数据目录不变，
+//! 切换成功后才按需删除旧文件。
+//!
+//! PostgreSQL、Redis、Qdrant 中的数据（会话、消息、向量等）保存在各自的服务中，
+//! 不在数据目录内，不会被迁移。
+//!
+//! 启动时还会一次性把旧版本使用的 `<data_dir>/dev.zishu.sensei` 目录中的数据移入当前数据目录。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, System, SystemExt};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+use super::config::{default_app_data_dir, get_app_data_dir, set_data_dir_override, DATA_DIR_POINTER_FILE};
+
+lazy_static::lazy_static! {
+    /// 本次启动时执行的迁移结果
+    static ref STARTUP_STATUS: parking_lot::RwLock<StartupMigrationStatus> =
+        parking_lot::RwLock::new(StartupMigrationStatus::default());
+}
+
+/// 额外预留的空间（字节）
+const SPACE_MARGIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 待执行迁移的记录文件（位于默认数据目录）
+pub const PENDING_MIGRATION_FILE: &str = "pending_migration.json";
+
+/// 旧版本通过 `path_resolver().app_data_dir()` 使用 `<data_dir>/<bundle identifier>`
+const LEGACY_DIR_NAME: &str = "dev.zishu.sensei";
+
+/// 旧版目录迁移完成标记（位于当前数据目录）
+const LEGACY_MIGRATED_MARKER: &str = ".legacy_data_migrated";
+
+/// 旧版目录中属于 WebView 自身的存储，保留在原处
+const WEBVIEW_ENTRIES: &[&str] = &[
+    "EBWebView",
+    "WebKit",
+    "localstorage",
+    "databases",
+    "indexeddb",
+    "storage",
+    "mediakeys",
+    "CacheStorage",
+    "serviceworkers",
+    "deviceidhashsalts",
+    "hsts-storage.sqlite",
+];
+
+/// 迁移阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStage {
+    Validating,
+    Copying,
+    Verifying,
+    Switching,
+    CleaningUp,
+    RollingBack,
+    Completed,
+}
+
+/// 迁移进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationProgress {
+    pub stage: MigrationStage,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_file: Option<String>,
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub files_copied: usize,
+    pub bytes_copied: u64,
+    pub source_removed: bool,
+    pub warnings: Vec<String>,
+}
+
+/// 已记录、等待下次启动执行的迁移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMigration {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub remove_source: bool,
+    pub files_total: usize,
+    pub bytes_total: u64,
+    pub requested_at: String,
+}
+
+/// 本次启动时执行的迁移
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupMigrationStatus {
+    /// 从旧版目录移入的条目
+    pub legacy_moved: Vec<String>,
+    /// 当前数据目录中已存在、保留在旧版目录的条目
+    pub legacy_skipped: Vec<String>,
+    pub report: Option<MigrationReport>,
+    pub errors: Vec<String>,
+}
+
+/// 目标目录校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetCheck {
+    pub required_bytes: u64,
+    pub available_bytes: Option<u64>,
+}
+
+struct FileEntry {
+    relative: PathBuf,
+    size: u64,
+}
+
+/// 是否为只属于默认数据目录的记录文件（指针与待执行迁移）
+fn is_bookkeeping_file(relative: &Path) -> bool {
+    relative == Path::new(DATA_DIR_POINTER_FILE) || relative == Path::new(PENDING_MIGRATION_FILE)
+}
+
+/// 收集需要迁移的文件（跳过指针与待执行迁移记录）
+fn collect_files(source: &Path) -> Result<Vec<FileEntry>, String> {
+    let mut files = Vec::new();
+    if !source.exists() {
+        return Ok(files);
+    }
+
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = entry.map_err(|e| format!("读取数据目录失败: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| e.to_string())?
+            .to_path_buf();
+        if is_bookkeeping_file(&relative) {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        files.push(FileEntry { relative, size });
+    }
+    Ok(files)
+}
+
+/// 找到路径所在磁盘的可用空间
+fn available_space(path: &Path) -> Option<u64> {
+    // 目标目录可能尚不存在，使用最近的已存在祖先目录
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let existing = existing.canonicalize().ok()?;
+
+    let mut system = System::new();
+    system.refresh_disks_list();
+    system.refresh_disks();
+    system
+        .disks()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 校验目标目录：绝对路径、与当前目录不重叠、为空（可只有记录文件）或不存在、剩余空间充足
+pub fn validate_target(source: &Path, target: &Path, required_bytes: u64) -> Result<TargetCheck, String> {
+    if !target.is_absolute() {
+        return Err("目标路径必须是绝对路径".to_string());
+    }
+
+    let (source_n, target_n) = (normalize(source), normalize(target));
+    if source_n == target_n {
+        return Err("目标路径与当前数据目录相同".to_string());
+    }
+    if target_n.starts_with(&source_n) || source_n.starts_with(&target_n) {
+        return Err("目标路径不能位于当前数据目录内部，也不能包含当前数据目录".to_string());
+    }
+
+    if target.exists() {
+        if !target.is_dir() {
+            return Err("目标路径不是目录".to_string());
+        }
+        // 默认数据目录始终保留指向文件与迁移记录，重置时不算作已有数据
+        let non_empty = fs::read_dir(target)
+            .map_err(|e| format!("无法读取目标目录: {}", e))?
+            .filter_map(Result::ok)
+            .any(|entry| !is_bookkeeping_file(Path::new(&entry.file_name())));
+        if non_empty {
+            return Err("目标目录不为空".to_string());
+        }
+    }
+
+    let available_bytes = available_space(target);
+    if let Some(available) = available_bytes {
+        if available < required_bytes + SPACE_MARGIN_BYTES {
+            return Err(format!(
+                "目标磁盘空间不足：需要 {} MB，可用 {} MB",
+                (required_bytes + SPACE_MARGIN_BYTES) / 1024 / 1024,
+                available / 1024 / 1024
+            ));
+        }
+    } else {
+        warn!("无法获取目标磁盘的可用空间: {:?}", target);
+    }
+
+    Ok(TargetCheck { required_bytes, available_bytes })
+}
+
+/// 删除迁移过程中创建的文件
+fn rollback(target: &Path, created_root: bool, copied: &[PathBuf]) {
+    if created_root {
+        if let Err(e) = fs::remove_dir_all(target) {
+            warn!("回滚时删除目标目录失败: {}", e);
+        }
+        return;
+    }
+    for file in copied.iter().rev() {
+        let _ = fs::remove_file(target.join(file));
+    }
+    // 清理空目录（目标目录本身保留）
+    for entry in WalkDir::new(target).contents_first(true).min_depth(1).into_iter().flatten() {
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
+
+fn pending_migration_path() -> Result<PathBuf, String> {
+    Ok(default_app_data_dir()?.join(PENDING_MIGRATION_FILE))
+}
+
+fn read_pending(path: &Path) -> Option<PendingMigration> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_pending(path: &Path, pending: &PendingMigration) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建默认数据目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(pending).map_err(|e| format!("序列化迁移记录失败: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("写入迁移记录失败: {}", e))
+}
+
+/// 校验目标目录并记录迁移，下次启动时执行（再次调用会覆盖之前的记录）
+pub fn schedule_migration(target: &Path, remove_source: bool) -> Result<PendingMigration, String> {
+    let source = get_app_data_dir()?;
+    let files = collect_files(&source)?;
+    let bytes_total: u64 = files.iter().map(|f| f.size).sum();
+    validate_target(&source, target, bytes_total)?;
+
+    let pending = PendingMigration {
+        source,
+        target: target.to_path_buf(),
+        remove_source,
+        files_total: files.len(),
+        bytes_total,
+        requested_at: chrono::Utc::now().to_rfc3339(),
+    };
+    write_pending(&pending_migration_path()?, &pending)?;
+    info!("已记录数据目录迁移，下次启动时执行: {:?} -> {:?}", pending.source, pending.target);
+    Ok(pending)
+}
+
+/// 获取尚未执行的迁移
+pub fn pending_migration() -> Option<PendingMigration> {
+    read_pending(&pending_migration_path().ok()?)
+}
+
+/// 取消尚未执行的迁移，返回是否存在记录
+pub fn cancel_pending_migration() -> Result<bool, String> {
+    let path = pending_migration_path()?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&path).map_err(|e| format!("删除迁移记录失败: {}", e))?;
+    Ok(true)
+}
+
+/// 获取本次启动时执行的迁移结果
+pub fn startup_status() -> StartupMigrationStatus {
+    STARTUP_STATUS.read().clone()
+}
+
+/// 执行迁移：复制 → 校验 → 切换路径，失败时回滚；切换成功后按需删除旧文件
+fn migrate_data_directory<F>(pending: &PendingMigration, mut on_progress: F) -> Result<MigrationReport, String>
+where
+    F: FnMut(&MigrationProgress),
+{
+    let (source, target) = (pending.source.as_path(), pending.target.as_path());
+    let files = collect_files(source)?;
+    let bytes_total: u64 = files.iter().map(|f| f.size).sum();
+    let mut progress = MigrationProgress {
+        stage: MigrationStage::Validating,
+        files_done: 0,
+        files_total: files.len(),
+        bytes_done: 0,
+        bytes_total,
+        current_file: None,
+    };
+    on_progress(&progress);
+
+    validate_target(source, target, bytes_total)?;
+
+    let created_root = !target.exists();
+    fs::create_dir_all(target).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    let mut copied: Vec<PathBuf> = Vec::with_capacity(files.len());
+    let result = (|| -> Result<(), String> {
+        progress.stage = MigrationStage::Copying;
+        for file in &files {
+            let from = source.join(&file.relative);
+            let to = target.join(&file.relative);
+            progress.current_file = Some(file.relative.to_string_lossy().to_string());
+            on_progress(&progress);
+
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("创建目录 {:?} 失败: {}", parent, e))?;
+            }
+            fs::copy(&from, &to).map_err(|e| format!("复制 {:?} 失败: {}", file.relative, e))?;
+            copied.push(file.relative.clone());
+
+            progress.files_done += 1;
+            progress.bytes_done += file.size;
+        }
+
+        progress.stage = MigrationStage::Verifying;
+        progress.current_file = None;
+        on_progress(&progress);
+        for file in &files {
+            let copied_size = fs::metadata(target.join(&file.relative)).map(|m| m.len()).unwrap_or(u64::MAX);
+            if copied_size != file.size {
+                return Err(format!("校验失败: {:?} 大小不一致", file.relative));
+            }
+        }
+
+        progress.stage = MigrationStage::Switching;
+        on_progress(&progress);
+        let is_default = default_app_data_dir().map(|d| normalize(&d) == normalize(target)).unwrap_or(false);
+        set_data_dir_override(if is_default { None } else { Some(target.to_path_buf()) })
+    })();
+
+    if let Err(e) = result {
+        progress.stage = MigrationStage::RollingBack;
+        on_progress(&progress);
+        rollback(target, created_root, &copied);
+        return Err(e);
+    }
+
+    // 此时还没有任何文件被打开，可以安全删除旧文件
+    let mut warnings = Vec::new();
+    let mut source_removed = false;
+    if pending.remove_source {
+        progress.stage = MigrationStage::CleaningUp;
+        on_progress(&progress);
+        for file in &files {
+            if let Err(e) = fs::remove_file(source.join(&file.relative)) {
+                warnings.push(format!("删除旧文件 {:?} 失败: {}", file.relative, e));
+            }
+        }
+        for entry in WalkDir::new(source).contents_first(true).min_depth(1).into_iter().flatten() {
+            if entry.file_type().is_dir() {
+                let _ = fs::remove_dir(entry.path());
+            }
+        }
+        // 默认数据目录中保留指针文件，删除会失败，忽略即可
+        let _ = fs::remove_dir(source);
+        source_removed = warnings.is_empty();
+    }
+
+    progress.stage = MigrationStage::Completed;
+    on_progress(&progress);
+
+    Ok(MigrationReport {
+        source: source.to_path_buf(),
+        target: target.to_path_buf(),
+        files_copied: copied.len(),
+        bytes_copied: progress.bytes_done,
+        source_removed,
+        warnings,
+    })
+}
+
+/// 把旧版目录中的条目移动到 `current`，当前目录中已存在的条目保留在旧版目录
+fn move_legacy_entries(legacy: &Path, current: &Path) -> Result<(Vec<String>, Vec<String>), String> {
+    let (mut moved, mut skipped) = (Vec::new(), Vec::new());
+    fs::create_dir_all(current).map_err(|e| format!("创建数据目录失败: {}", e))?;
+
+    let entries = fs::read_dir(legacy).map_err(|e| format!("读取旧版数据目录失败: {}", e))?;
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if WEBVIEW_ENTRIES.contains(&name.as_str()) {
+            continue;
+        }
+        let to = current.join(&name);
+        if to.exists() {
+            skipped.push(name);
+            continue;
+        }
+        move_entry(&entry.path(), &to).map_err(|e| format!("移动 {} 失败: {}", name, e))?;
+        moved.push(name);
+    }
+
+    fs::write(current.join(LEGACY_MIGRATED_MARKER), chrono::Utc::now().to_rfc3339())
+        .map_err(|e| format!("写入迁移标记失败: {}", e))?;
+    Ok((moved, skipped))
+}
+
+/// 移动文件或目录；跨磁盘时复制后删除
+fn move_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if !from.is_dir() {
+        fs::copy(from, to)?;
+        return fs::remove_file(from);
+    }
+
+    let copied = WalkDir::new(from).into_iter().try_for_each(|entry| -> std::io::Result<()> {
+        let entry = entry?;
+        let dest = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest)
+        } else {
+            fs::copy(entry.path(), &dest).map(|_| ())
+        }
+    });
+    if let Err(e) = copied {
+        // 删除不完整的副本，旧版目录保持不变，下次启动重试
+        let _ = fs::remove_dir_all(to);
+        return Err(e);
+    }
+    fs::remove_dir_all(from)
+}
+
+/// 启动时执行的迁移（日志与数据库打开之前调用）
+///
+/// 先把旧版目录中的数据移入当前数据目录，再执行上次运行中记录的迁移。
+/// 日志系统此时尚未初始化，结果通过 [`log_startup_migrations`] 在日志就绪后输出。
+pub fn run_startup_migrations() {
+    let mut status = StartupMigrationStatus::default();
+
+    if let (Some(legacy), Ok(current)) = (dirs::data_dir().map(|d| d.join(LEGACY_DIR_NAME)), get_app_data_dir()) {
+        let done = current.join(LEGACY_MIGRATED_MARKER).exists();
+        if !done && legacy.is_dir() && normalize(&legacy) != normalize(&current) {
+            match move_legacy_entries(&legacy, &current) {
+                Ok((moved, skipped)) => {
+                    status.legacy_moved = moved;
+                    status.legacy_skipped = skipped;
+                }
+                Err(e) => status.errors.push(format!("旧版数据目录迁移失败: {}", e)),
+            }
+        }
+    }
+
+    if let Ok(path) = pending_migration_path() {
+        if let Some(pending) = read_pending(&path) {
+            // 先删除记录，失败后不会在每次启动时重复尝试
+            let _ = fs::remove_file(&path);
+            let current = get_app_data_dir().map(|d| normalize(&d)).unwrap_or_default();
+            let result = if normalize(&pending.source) != current {
+                Err("记录迁移后数据目录已变化，已取消迁移".to_string())
+            } else {
+                let mut last_stage = None;
+                migrate_data_directory(&pending, |progress| {
+                    if last_stage != Some(progress.stage) {
+                        last_stage = Some(progress.stage);
+                        eprintln!(
+                            "数据目录迁移: {:?} ({}/{} 个文件)",
+                            progress.stage, progress.files_done, progress.files_total
+                        );
+                    }
+                })
+            };
+            match result {
+                Ok(report) => status.report = Some(report),
+                Err(e) => status.errors.push(format!("数据目录迁移失败，已回滚: {}", e)),
+            }
+        }
+    }
+
+    *STARTUP_STATUS.write() = status;
+}
+
+/// 输出启动时迁移的结果（日志系统初始化后调用）
+pub fn log_startup_migrations() {
+    let status = STARTUP_STATUS.read();
+    if !status.legacy_moved.is_empty() {
+        info!("已从旧版数据目录移入: {}", status.legacy_moved.join(", "));
+    }
+    if !status.legacy_skipped.is_empty() {
+        warn!("当前数据目录中已存在，保留在旧版数据目录: {}", status.legacy_skipped.join(", "));
+    }
+    if let Some(report) = &status.report {
+        info!(
+            "数据目录迁移完成: {:?} -> {:?} ({} 个文件, {} 字节)",
+            report.source, report.target, report.files_copied, report.bytes_copied
+        );
+        for warning in &report.warnings {
+            warn!("{}", warning);
+        }
+    }
+    for error in &status.errors {
+        warn!("{}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_files_skips_bookkeeping_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.json"), "{}").unwrap();
+        fs::write(dir.path().join(DATA_DIR_POINTER_FILE), "{}").unwrap();
+        fs::write(dir.path().join(PENDING_MIGRATION_FILE), "{}").unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("logs").join("app.log"), "line").unwrap();
+
+        let files = collect_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| !is_bookkeeping_file(&f.relative)));
+    }
+
+    #[test]
+    fn test_pending_migration_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PENDING_MIGRATION_FILE);
+        let pending = PendingMigration {
+            source: PathBuf::from("/old"),
+            target: PathBuf::from("/new"),
+            remove_source: true,
+            files_total: 3,
+            bytes_total: 42,
+            requested_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+
+        write_pending(&path, &pending).unwrap();
+        let loaded = read_pending(&path).unwrap();
+        assert_eq!(loaded.target, pending.target);
+        assert!(loaded.remove_source);
+        assert!(read_pending(&dir.path().join("missing.json")).is_none());
+    }
+
+    #[test]
+    fn test_move_legacy_entries_keeps_existing_and_webview_data() {
+        let legacy = tempfile::tempdir().unwrap();
+        let current = tempfile::tempdir().unwrap();
+        fs::write(legacy.path().join("encrypted_storage.db"), "old").unwrap();
+        fs::create_dir(legacy.path().join("uploads")).unwrap();
+        fs::write(legacy.path().join("uploads").join("a.png"), "img").unwrap();
+        fs::write(legacy.path().join("security_audit.db"), "old").unwrap();
+        fs::write(current.path().join("security_audit.db"), "new").unwrap();
+        fs::create_dir(legacy.path().join("localstorage")).unwrap();
+
+        let (moved, skipped) = move_legacy_entries(legacy.path(), current.path()).unwrap();
+        assert_eq!(moved.len(), 2);
+        assert_eq!(skipped, vec!["security_audit.db".to_string()]);
+        assert!(current.path().join("uploads").join("a.png").exists());
+        assert!(!legacy.path().join("encrypted_storage.db").exists());
+        assert_eq!(fs::read_to_string(current.path().join("security_audit.db")).unwrap(), "new");
+        assert!(legacy.path().join("localstorage").exists());
+        assert!(current.path().join(LEGACY_MIGRATED_MARKER).exists());
+    }
+
+    #[test]
+    fn test_validate_target_rejects_nested_and_non_empty() {
+        let source = tempfile::tempdir().unwrap();
+        assert!(validate_target(source.path(), &source.path().join("nested"), 0).is_err());
+        assert!(validate_target(source.path(), Path::new("relative/path"), 0).is_err());
+
+        let target = tempfile::tempdir().unwrap();
+        fs::write(target.path().join("existing"), "x").unwrap();
+        assert!(validate_target(source.path(), target.path(), 0).is_err());
+    }
+
+    #[test]
+    fn test_validate_target_ignores_pointer_file() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        fs::write(target.path().join(DATA_DIR_POINTER_FILE), "{}").unwrap();
+        assert!(validate_target(source.path(), target.path(), 0).is_ok());
+    }
+
+    #[test]
+    fn test_rollback_removes_copied_files() {
+        let target = tempfile::tempdir().unwrap();
+        fs::create_dir(target.path().join("logs")).unwrap();
+        fs::write(target.path().join("logs").join("app.log"), "x").unwrap();
+
+        rollback(target.path(), false, &[PathBuf::from("logs/app.log")]);
+        assert!(target.path().exists());
+        assert_eq!(fs::read_dir(target.path()).unwrap().count(), 0);
+    }
+}
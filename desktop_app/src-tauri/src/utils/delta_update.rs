@@ -0,0 +1,235 @@
+//! # 增量更新
+//!
+//! 更新清单中的文件可以附带分块信息与差分补丁（见 [`super::update_manager::FileInfo`]）：
+//!
+//! - `chunks`：文件按偏移切分的块及每块的 SHA256，下载时逐块用 HTTP Range 请求并校验，
+//!   已完成的块记录在 `*.part.json` 中，中断后只重新下载未完成的块
+//! - `deltas`：从旧版本生成的 bsdiff 补丁，`base_hash` 为补丁基准文件的哈希；
+//!   本地找不到匹配的基准文件或补丁失败时回退到完整下载
+//!
+//! 选择“下次重启时安装”后，已下载的更新记录在 `staged_update.json` 中，由更新管理器初始化时安装。
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STAGED_FILE: &str = "staged_update.json";
+
+/// 文件分块
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkInfo {
+    /// 块在文件中的偏移
+    pub offset: u64,
+    /// 块大小
+    pub size: u64,
+    /// 块哈希（SHA256）
+    pub hash: String,
+}
+
+/// 差分补丁
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeltaPatch {
+    /// 补丁适用的旧版本
+    pub from_version: String,
+    /// 补丁URL
+    pub url: String,
+    /// 补丁大小
+    pub size: i64,
+    /// 补丁哈希（SHA256）
+    pub hash: String,
+    /// 补丁基准文件的哈希（SHA256）
+    pub base_hash: String,
+    /// 补丁的分块信息
+    #[serde(default)]
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// 选择适用于当前版本的补丁
+pub fn select_delta<'a>(deltas: &'a [DeltaPatch], current_version: &str) -> Option<&'a DeltaPatch> {
+    deltas.iter().find(|delta| delta.from_version == current_version)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// 检查分块连续、不重叠且恰好覆盖整个文件
+pub fn validate_chunks(chunks: &[ChunkInfo], total_size: u64) -> Result<(), String> {
+    let mut expected_offset = 0u64;
+    for (index, chunk) in chunks.iter().enumerate() {
+        if chunk.size == 0 {
+            return Err(format!("第 {} 个分块为空", index));
+        }
+        if chunk.offset != expected_offset {
+            return Err(format!("第 {} 个分块的偏移应为 {}，实际为 {}", index, expected_offset, chunk.offset));
+        }
+        expected_offset += chunk.size;
+    }
+    if expected_offset != total_size {
+        return Err(format!("分块总大小 {} 与文件大小 {} 不一致", expected_offset, total_size));
+    }
+    Ok(())
+}
+
+/// 分块的 HTTP Range 请求头
+pub fn range_header(chunk: &ChunkInfo) -> String {
+    format!("bytes={}-{}", chunk.offset, chunk.offset + chunk.size - 1)
+}
+
+pub fn verify_chunk(data: &[u8], chunk: &ChunkInfo) -> bool {
+    data.len() as u64 == chunk.size && sha256_hex(data).eq_ignore_ascii_case(&chunk.hash)
+}
+
+/// 分块下载的断点状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkProgress {
+    /// 下载的文件哈希，文件变化后已完成的块作废
+    pub hash: String,
+    /// 已完成的块序号
+    pub completed: BTreeSet<usize>,
+}
+
+impl ChunkProgress {
+    /// 已下载的字节数
+    pub fn downloaded_bytes(&self, chunks: &[ChunkInfo]) -> u64 {
+        self.completed.iter().filter_map(|index| chunks.get(*index)).map(|chunk| chunk.size).sum()
+    }
+}
+
+pub fn progress_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.as_os_str().to_owned();
+    name.push(".part.json");
+    PathBuf::from(name)
+}
+
+/// 读取断点状态；文件哈希不同或状态损坏时从头开始
+pub fn load_progress(file_path: &Path, hash: &str) -> ChunkProgress {
+    fs::read_to_string(progress_path(file_path))
+        .ok()
+        .and_then(|json| serde_json::from_str::<ChunkProgress>(&json).ok())
+        .filter(|progress| progress.hash == hash && file_path.exists())
+        .unwrap_or_else(|| ChunkProgress { hash: hash.to_string(), completed: BTreeSet::new() })
+}
+
+pub fn save_progress(file_path: &Path, progress: &ChunkProgress) -> Result<(), String> {
+    let json = serde_json::to_string(progress).map_err(|e| format!("序列化下载进度失败: {}", e))?;
+    fs::write(progress_path(file_path), json).map_err(|e| format!("写入下载进度失败: {}", e))
+}
+
+pub fn clear_progress(file_path: &Path) {
+    let _ = fs::remove_file(progress_path(file_path));
+}
+
+/// 对基准文件应用 bsdiff 补丁，并校验生成文件的哈希
+pub fn apply_patch(base: &[u8], patch: &[u8], expected_hash: &str) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    bsdiff::patch(base, &mut &patch[..], &mut output).map_err(|e| format!("应用补丁失败: {}", e))?;
+    let actual_hash = sha256_hex(&output);
+    if !actual_hash.eq_ignore_ascii_case(expected_hash) {
+        return Err(format!("补丁生成的文件校验失败: 期望 {}，实际 {}", expected_hash, actual_hash));
+    }
+    Ok(output)
+}
+
+/// 等待下次重启时安装的更新
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagedUpdate {
+    pub version: String,
+    /// 暂存时运行的版本，安装失败时回滚到该版本
+    pub from_version: String,
+    pub file_path: String,
+    pub staged_at: i64,
+}
+
+impl StagedUpdate {
+    pub fn new(version: &str, from_version: &str, file_path: &Path) -> Self {
+        Self {
+            version: version.to_string(),
+            from_version: from_version.to_string(),
+            file_path: file_path.to_string_lossy().to_string(),
+            staged_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+pub fn load_staged(dir: &Path) -> Option<StagedUpdate> {
+    fs::read_to_string(dir.join(STAGED_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+pub fn save_staged(dir: &Path, staged: &StagedUpdate) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(staged).map_err(|e| format!("序列化暂存更新失败: {}", e))?;
+    fs::write(dir.join(STAGED_FILE), json).map_err(|e| format!("写入暂存更新失败: {}", e))
+}
+
+pub fn clear_staged(dir: &Path) -> bool {
+    fs::remove_file(dir.join(STAGED_FILE)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(offset: u64, data: &[u8]) -> ChunkInfo {
+        ChunkInfo { offset, size: data.len() as u64, hash: sha256_hex(data) }
+    }
+
+    #[test]
+    fn test_chunks_validation_and_progress() {
+        let chunks = vec![chunk(0, b"hello "), chunk(6, b"world")];
+        assert!(validate_chunks(&chunks, 11).is_ok());
+        assert!(validate_chunks(&chunks, 12).is_err());
+        assert!(validate_chunks(&[chunk(0, b"ab"), chunk(3, b"c")], 4).is_err());
+        assert_eq!(range_header(&chunks[1]), "bytes=6-10");
+
+        assert!(verify_chunk(b"world", &chunks[1]));
+        assert!(!verify_chunk(b"w0rld", &chunks[1]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("zishu-sensei-1.1.0.update");
+        fs::write(&file_path, b"").unwrap();
+        let mut progress = load_progress(&file_path, "abc");
+        progress.completed.insert(1);
+        save_progress(&file_path, &progress).unwrap();
+
+        assert_eq!(load_progress(&file_path, "abc").downloaded_bytes(&chunks), 5);
+        // 文件更换后从头下载
+        assert!(load_progress(&file_path, "def").completed.is_empty());
+        clear_progress(&file_path);
+        assert!(load_progress(&file_path, "abc").completed.is_empty());
+    }
+
+    #[test]
+    fn test_apply_patch_and_staging() {
+        let base = b"zishu sensei 1.0.0 binary payload".to_vec();
+        let target = b"zishu sensei 1.1.0 binary payload with extras".to_vec();
+        let mut patch = Vec::new();
+        bsdiff::diff(&base, &target, &mut patch).unwrap();
+
+        assert_eq!(apply_patch(&base, &patch, &sha256_hex(&target)).unwrap(), target);
+        assert!(apply_patch(&base, &patch, &sha256_hex(b"other")).is_err());
+
+        let deltas = vec![DeltaPatch {
+            from_version: "1.0.0".to_string(),
+            url: "https://example.com/1.0.0-1.1.0.patch".to_string(),
+            size: patch.len() as i64,
+            hash: sha256_hex(&patch),
+            base_hash: sha256_hex(&base),
+            chunks: Vec::new(),
+        }];
+        assert!(select_delta(&deltas, "1.0.0").is_some());
+        assert!(select_delta(&deltas, "0.9.0").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_staged(dir.path()).is_none());
+        let staged = StagedUpdate::new("1.1.0", "1.0.0", &dir.path().join("zishu-sensei-1.1.0.update"));
+        save_staged(dir.path(), &staged).unwrap();
+        assert_eq!(load_staged(dir.path()), Some(staged));
+        assert!(clear_staged(dir.path()));
+        assert!(!clear_staged(dir.path()));
+    }
+}
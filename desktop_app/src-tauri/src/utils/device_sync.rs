@@ -0,0 +1,381 @@
+//! # 多设备同步
+//!
+//! 登录社区账号后，对话与设置以条目为单位端到端加密上传，其他设备拉取后合并到本地。
+//! 服务端只保存密文信封（[`SyncEnvelope`]），密钥由同步口令派生，各设备使用同一口令。
+//!
+//! 每个条目带一个向量时钟（[`VectorClock`]，设备 ID → 本设备修改次数）：
+//!
+//! - 一方的时钟包含另一方：较新的一方覆盖较旧的一方
+//! - 互不包含（两台设备各自修改过）：对话按消息合并（[`merge_conversations`]），
+//!   设置按修改时间取较新的一份（本机的修改时间为同步时发现修改的时间）；
+//!   合并结果的时钟为两者合并后本设备加一
+//!
+//! 本模块只包含数据格式与合并规则，网络状态、调度与 `sync_now` 见 `commands::device_sync`。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::database::conversation::{Conversation, Message};
+use crate::utils::encryption::{generate_salt, EncryptedData, EncryptionManager, KeyDerivationParams};
+
+/// 同步间隔范围（分钟）
+pub const MIN_INTERVAL_MINUTES: u32 = 5;
+pub const MAX_INTERVAL_MINUTES: u32 = 24 * 60;
+
+/// 同步的数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncCategory {
+    /// 对话及其消息，每个对话一个条目
+    Conversations,
+    /// 角色与主题设置（窗口与系统设置跟随设备，不同步）
+    Settings,
+}
+
+impl SyncCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncCategory::Conversations => "conversations",
+            SyncCategory::Settings => "settings",
+        }
+    }
+}
+
+/// 条目在同步状态中的键
+pub fn item_key(category: SyncCategory, item_id: &str) -> String {
+    format!("{}:{}", category.as_str(), item_id)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_interval() -> u32 {
+    15
+}
+
+/// 各类别开关
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncCategories {
+    #[serde(default = "default_true")]
+    pub conversations: bool,
+    #[serde(default = "default_true")]
+    pub settings: bool,
+}
+
+impl Default for SyncCategories {
+    fn default() -> Self {
+        Self { conversations: true, settings: true }
+    }
+}
+
+impl SyncCategories {
+    pub fn allows(&self, category: SyncCategory) -> bool {
+        match category {
+            SyncCategory::Conversations => self.conversations,
+            SyncCategory::Settings => self.settings,
+        }
+    }
+}
+
+/// 同步设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 自动同步间隔（分钟）；网络恢复时也会立即同步一次
+    #[serde(default = "default_interval")]
+    pub interval_minutes: u32,
+    #[serde(default)]
+    pub categories: SyncCategories,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_interval(),
+            categories: SyncCategories::default(),
+        }
+    }
+}
+
+impl SyncSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_INTERVAL_MINUTES..=MAX_INTERVAL_MINUTES).contains(&self.interval_minutes) {
+            return Err(format!(
+                "同步间隔必须在 {} 到 {} 分钟之间",
+                MIN_INTERVAL_MINUTES, MAX_INTERVAL_MINUTES
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 向量时钟比较结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOrdering {
+    Equal,
+    /// 本方较旧（对方包含本方的全部修改）
+    Before,
+    /// 本方较新
+    After,
+    /// 并发修改，需要合并
+    Concurrent,
+}
+
+/// 向量时钟：设备 ID → 该设备对条目的修改次数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock(pub BTreeMap<String, u64>);
+
+impl VectorClock {
+    /// 记录本设备的一次修改
+    pub fn increment(&mut self, device_id: &str) {
+        *self.0.entry(device_id.to_string()).or_insert(0) += 1;
+    }
+
+    /// 取两个时钟各设备的较大值
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device, count) in &other.0 {
+            let entry = self.0.entry(device.clone()).or_insert(0);
+            *entry = (*entry).max(*count);
+        }
+    }
+
+    pub fn compare(&self, other: &VectorClock) -> ClockOrdering {
+        let mut less = false;
+        let mut greater = false;
+        for device in self.0.keys().chain(other.0.keys()) {
+            let mine = self.0.get(device).copied().unwrap_or(0);
+            let theirs = other.0.get(device).copied().unwrap_or(0);
+            match mine.cmp(&theirs) {
+                Ordering::Less => less = true,
+                Ordering::Greater => greater = true,
+                Ordering::Equal => {}
+            }
+        }
+        match (less, greater) {
+            (false, false) => ClockOrdering::Equal,
+            (true, false) => ClockOrdering::Before,
+            (false, true) => ClockOrdering::After,
+            (true, true) => ClockOrdering::Concurrent,
+        }
+    }
+}
+
+/// 加密后的条目内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedPayload {
+    pub kdf: KeyDerivationParams,
+    pub data: EncryptedData,
+}
+
+/// 服务端保存的同步信封：元数据明文，内容加密
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEnvelope {
+    pub category: SyncCategory,
+    pub item_id: String,
+    pub clock: VectorClock,
+    /// 最后修改该条目的设备
+    pub device_id: String,
+    pub updated_at: i64,
+    /// 条目已在某台设备上删除（墓碑，不带内容）
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub payload: Option<SealedPayload>,
+}
+
+impl SyncEnvelope {
+    pub fn key(&self) -> String {
+        item_key(self.category, &self.item_id)
+    }
+}
+
+/// 用同步口令加解密条目
+///
+/// 密钥派生开销较大：同一轮同步中加密共用一个盐，解密按盐缓存派生出的密钥。
+pub struct SyncCipher {
+    passphrase: String,
+    sealing: Option<(KeyDerivationParams, EncryptionManager)>,
+    opening: HashMap<String, EncryptionManager>,
+}
+
+impl SyncCipher {
+    pub fn new(passphrase: String) -> Self {
+        Self { passphrase, sealing: None, opening: HashMap::new() }
+    }
+
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<SealedPayload, String> {
+        if self.sealing.is_none() {
+            let kdf = KeyDerivationParams {
+                salt: generate_salt().map_err(|e| e.to_string())?,
+                ..KeyDerivationParams::default()
+            };
+            let manager = EncryptionManager::from_password(&self.passphrase, &kdf).map_err(|e| e.to_string())?;
+            self.sealing = Some((kdf, manager));
+        }
+        let (kdf, manager) = self.sealing.as_ref().expect("sealing key initialized");
+        Ok(SealedPayload {
+            kdf: kdf.clone(),
+            data: manager.encrypt(plaintext).map_err(|e| e.to_string())?,
+        })
+    }
+
+    pub fn open(&mut self, payload: &SealedPayload) -> Result<Vec<u8>, String> {
+        if !self.opening.contains_key(&payload.kdf.salt) {
+            let manager =
+                EncryptionManager::from_password(&self.passphrase, &payload.kdf).map_err(|e| e.to_string())?;
+            self.opening.insert(payload.kdf.salt.clone(), manager);
+        }
+        self.opening[&payload.kdf.salt]
+            .decrypt(&payload.data)
+            .map_err(|_| "同步口令错误或数据已损坏".to_string())
+    }
+}
+
+/// 同步的对话内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationPayload {
+    pub conversation: Conversation,
+    pub messages: Vec<Message>,
+}
+
+/// 同步的设置内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsPayload {
+    pub character: serde_json::Value,
+    pub theme: serde_json::Value,
+}
+
+/// 条目内容的摘要，用于发现本地修改
+pub fn content_hash<T: Serialize>(value: &T) -> Result<String, String> {
+    let bytes = serde_json::to_vec(value).map_err(|e| format!("序列化同步内容失败: {}", e))?;
+    Ok(Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 同一条消息在两台设备上的版本取哪一个：删除优先，其次取较晚编辑的版本
+fn prefer_message(local: Message, remote: Message) -> Message {
+    match (local.deleted_at, remote.deleted_at) {
+        (Some(_), _) => local,
+        (None, Some(_)) => remote,
+        (None, None) if remote.edited_at > local.edited_at => remote,
+        _ => local,
+    }
+}
+
+/// 合并两台设备并发修改的同一对话：消息按 ID 取并集，标题取较晚更新的一方
+pub fn merge_conversations(local: ConversationPayload, remote: ConversationPayload) -> ConversationPayload {
+    let remote_newer = remote.conversation.updated_at > local.conversation.updated_at;
+    let conversation = Conversation {
+        id: local.conversation.id.clone(),
+        title: if remote_newer { remote.conversation.title.clone() } else { local.conversation.title.clone() },
+        created_at: local.conversation.created_at.min(remote.conversation.created_at),
+        updated_at: local.conversation.updated_at.max(remote.conversation.updated_at),
+    };
+
+    let mut messages: BTreeMap<String, Message> =
+        local.messages.into_iter().map(|message| (message.id.clone(), message)).collect();
+    for message in remote.messages {
+        let merged = match messages.remove(&message.id) {
+            Some(existing) => prefer_message(existing, message),
+            None => message,
+        };
+        messages.insert(merged.id.clone(), merged);
+    }
+    let mut messages: Vec<Message> = messages.into_values().collect();
+    messages.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+    ConversationPayload { conversation, messages }
+}
+
+/// 本地记录的条目同步状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemState {
+    pub clock: VectorClock,
+    /// 上次同步时的内容摘要，为空表示已删除
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// 最近一次发现本机修改的时间
+    #[serde(default)]
+    pub modified_at: i64,
+}
+
+/// 本地同步状态（保存在资料数据目录）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    /// 服务端变更游标，下次只拉取之后的变更
+    #[serde(default)]
+    pub cursor: i64,
+    #[serde(default)]
+    pub items: BTreeMap<String, ItemState>,
+    #[serde(default)]
+    pub last_sync_at: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::conversation::MessageRole;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        VectorClock(entries.iter().map(|(device, count)| (device.to_string(), *count)).collect())
+    }
+
+    #[test]
+    fn test_vector_clock_ordering() {
+        let base = clock(&[("a", 1), ("b", 2)]);
+        assert_eq!(base.compare(&base.clone()), ClockOrdering::Equal);
+        assert_eq!(base.compare(&clock(&[("a", 2), ("b", 2)])), ClockOrdering::Before);
+        assert_eq!(base.compare(&clock(&[("b", 2)])), ClockOrdering::After);
+        assert_eq!(base.compare(&clock(&[("a", 2), ("b", 1)])), ClockOrdering::Concurrent);
+        assert_eq!(VectorClock::default().compare(&clock(&[("a", 1)])), ClockOrdering::Before);
+
+        let mut merged = base.clone();
+        merged.merge(&clock(&[("a", 3), ("c", 1)]));
+        merged.increment("b");
+        assert_eq!(merged, clock(&[("a", 3), ("b", 3), ("c", 1)]));
+    }
+
+    fn message(id: &str, created_at: i64, content: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            conversation_id: "c1".to_string(),
+            role: MessageRole::User,
+            content: content.to_string(),
+            created_at,
+            edited_at: None,
+            deleted_at: None,
+            payload: None,
+            language: None,
+        }
+    }
+
+    fn payload(title: &str, updated_at: i64, messages: Vec<Message>) -> ConversationPayload {
+        ConversationPayload {
+            conversation: Conversation { id: "c1".to_string(), title: title.to_string(), created_at: 10, updated_at },
+            messages,
+        }
+    }
+
+    #[test]
+    fn test_merge_concurrent_conversations() {
+        let mut edited = message("m1", 1, "edited");
+        edited.edited_at = Some(5);
+        let mut deleted = message("m2", 2, "");
+        deleted.deleted_at = Some(6);
+
+        let local = payload("local", 20, vec![message("m1", 1, "hello"), message("m2", 2, "bye"), message("m3", 3, "local")]);
+        let remote = payload("remote", 30, vec![edited, deleted, message("m4", 4, "remote")]);
+        let merged = merge_conversations(local, remote);
+
+        assert_eq!(merged.conversation.title, "remote");
+        assert_eq!(merged.conversation.updated_at, 30);
+        let ids: Vec<&str> = merged.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["m1", "m2", "m3", "m4"]);
+        assert_eq!(merged.messages[0].content, "edited");
+        assert!(merged.messages[1].is_deleted());
+    }
+}
@@ -0,0 +1,307 @@
+//! 磁盘空间守卫
+//!
+//! 在模型下载、备份等大体积操作之前检查目标磁盘的剩余空间，
+//! 低于阈值时发出通知，并提供按类别统计的存储占用与清理建议。
+
+use chrono::{Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, System, SystemExt};
+use walkdir::WalkDir;
+
+const MB: u64 = 1024 * 1024;
+
+lazy_static::lazy_static! {
+    static ref DISK_GUARD_SETTINGS: RwLock<DiskGuardSettings> =
+        RwLock::new(DiskGuardSettings::default());
+}
+
+/// 磁盘空间守卫设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskGuardSettings {
+    pub enabled: bool,
+    /// 剩余空间低于该值（MB）时发出警告
+    pub warning_free_mb: u64,
+    /// 剩余空间低于该值（MB）时拒绝执行大体积操作
+    pub critical_free_mb: u64,
+    /// 定期检查日志目录所在磁盘的间隔（分钟），0 表示不检查
+    pub check_interval_minutes: u64,
+    /// 清理建议中"旧日志"的天数
+    pub old_log_days: i64,
+}
+
+impl Default for DiskGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warning_free_mb: 2048,
+            critical_free_mb: 512,
+            check_interval_minutes: 30,
+            old_log_days: 7,
+        }
+    }
+}
+
+impl DiskGuardSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.critical_free_mb > self.warning_free_mb {
+            return Err("严重阈值不能大于警告阈值".to_string());
+        }
+        if self.old_log_days < 1 {
+            return Err("旧日志天数至少为 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn current_settings() -> DiskGuardSettings {
+    DISK_GUARD_SETTINGS.read().clone()
+}
+
+pub fn set_settings(settings: DiskGuardSettings) -> Result<(), String> {
+    settings.validate()?;
+    *DISK_GUARD_SETTINGS.write() = settings;
+    Ok(())
+}
+
+/// 空间等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpaceLevel {
+    Ok,
+    Low,
+    Critical,
+}
+
+/// 磁盘空间检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceStatus {
+    pub path: PathBuf,
+    pub mount_point: Option<PathBuf>,
+    pub total_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+    /// 本次操作预计写入的字节数
+    pub required_bytes: u64,
+    /// 操作完成后的空间等级
+    pub level: SpaceLevel,
+}
+
+impl SpaceStatus {
+    /// 操作完成后的剩余空间
+    pub fn remaining_after(&self) -> Option<u64> {
+        self.available_bytes.map(|a| a.saturating_sub(self.required_bytes))
+    }
+}
+
+/// 根据剩余空间计算等级
+pub fn classify(remaining_bytes: u64, settings: &DiskGuardSettings) -> SpaceLevel {
+    if remaining_bytes < settings.critical_free_mb * MB {
+        SpaceLevel::Critical
+    } else if remaining_bytes < settings.warning_free_mb * MB {
+        SpaceLevel::Low
+    } else {
+        SpaceLevel::Ok
+    }
+}
+
+/// 检查 `path` 所在磁盘在写入 `required_bytes` 后的空间等级
+pub fn check_space(path: &Path, required_bytes: u64) -> SpaceStatus {
+    let settings = current_settings();
+    let disk = path
+        .ancestors()
+        .find(|p| p.exists())
+        .and_then(|p| p.canonicalize().ok())
+        .and_then(|existing| {
+            let mut system = System::new();
+            system.refresh_disks_list();
+            system.refresh_disks();
+            system
+                .disks()
+                .iter()
+                .filter(|disk| existing.starts_with(disk.mount_point()))
+                .max_by_key(|disk| disk.mount_point().as_os_str().len())
+                .map(|disk| (disk.mount_point().to_path_buf(), disk.total_space(), disk.available_space()))
+        });
+
+    let (mount_point, total_bytes, available_bytes) = match disk {
+        Some((mount, total, available)) => (Some(mount), Some(total), Some(available)),
+        None => (None, None, None),
+    };
+    let level = match available_bytes {
+        Some(available) if settings.enabled => classify(available.saturating_sub(required_bytes), &settings),
+        _ => SpaceLevel::Ok,
+    };
+
+    SpaceStatus {
+        path: path.to_path_buf(),
+        mount_point,
+        total_bytes,
+        available_bytes,
+        required_bytes,
+        level,
+    }
+}
+
+/// 存储类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    Logs,
+    AssetCache,
+    TrashedFiles,
+    Downloads,
+    Backups,
+    Models,
+    Uploads,
+    Other,
+}
+
+/// 单个类别的存储占用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageItem {
+    pub category: StorageCategory,
+    pub path: Option<PathBuf>,
+    pub bytes: u64,
+    pub file_count: usize,
+}
+
+/// 清理操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CleanupAction {
+    /// 删除早于指定天数的日志文件
+    OldLogs { older_than_days: i64 },
+    /// 清空资源缓存（下次启动时重新准备）
+    AssetCache,
+    /// 永久删除回收站中的文件
+    TrashedFiles,
+    /// 删除已完成的下载文件
+    Downloads,
+}
+
+/// 清理建议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupSuggestion {
+    pub action: CleanupAction,
+    pub estimated_bytes: u64,
+    pub file_count: usize,
+    pub description: String,
+}
+
+/// 存储占用明细
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageBreakdown {
+    pub items: Vec<StorageItem>,
+    pub total_bytes: u64,
+    pub disk: SpaceStatus,
+    pub suggestions: Vec<CleanupSuggestion>,
+}
+
+/// 早于 `older_than_days` 天的日志文件
+pub fn old_log_files(log_dir: &Path, older_than_days: i64) -> Vec<(PathBuf, u64)> {
+    let cutoff = Utc::now() - Duration::days(older_than_days);
+    WalkDir::new(log_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            let modified: chrono::DateTime<Utc> = metadata.modified().ok()?.into();
+            (modified < cutoff).then(|| (e.path().to_path_buf(), metadata.len()))
+        })
+        .collect()
+}
+
+/// 统计目录占用
+pub fn measure(category: StorageCategory, path: &Path) -> StorageItem {
+    let (bytes, file_count) = WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .fold((0u64, 0usize), |(bytes, count), m| (bytes + m.len(), count + 1));
+    StorageItem {
+        category,
+        path: Some(path.to_path_buf()),
+        bytes,
+        file_count,
+    }
+}
+
+/// 根据占用明细生成清理建议（按可释放空间降序，忽略空类别）
+pub fn suggestions_for(
+    items: &[StorageItem],
+    old_logs: &[(PathBuf, u64)],
+    settings: &DiskGuardSettings,
+) -> Vec<CleanupSuggestion> {
+    let mut suggestions = Vec::new();
+
+    if !old_logs.is_empty() {
+        suggestions.push(CleanupSuggestion {
+            action: CleanupAction::OldLogs { older_than_days: settings.old_log_days },
+            estimated_bytes: old_logs.iter().map(|(_, size)| size).sum(),
+            file_count: old_logs.len(),
+            description: format!("删除 {} 天前的日志文件", settings.old_log_days),
+        });
+    }
+
+    for item in items.iter().filter(|i| i.bytes > 0) {
+        let (action, description) = match item.category {
+            StorageCategory::AssetCache => (CleanupAction::AssetCache, "清空资源缓存，下次启动时会重新准备"),
+            StorageCategory::TrashedFiles => (CleanupAction::TrashedFiles, "永久删除回收站中的文件"),
+            StorageCategory::Downloads => (CleanupAction::Downloads, "删除已完成的下载文件"),
+            _ => continue,
+        };
+        suggestions.push(CleanupSuggestion {
+            action,
+            estimated_bytes: item.bytes,
+            file_count: item.file_count,
+            description: description.to_string(),
+        });
+    }
+
+    suggestions.sort_by(|a, b| b.estimated_bytes.cmp(&a.estimated_bytes));
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_thresholds() {
+        let settings = DiskGuardSettings::default();
+        assert_eq!(classify(10 * 1024 * MB, &settings), SpaceLevel::Ok);
+        assert_eq!(classify(1024 * MB, &settings), SpaceLevel::Low);
+        assert_eq!(classify(100 * MB, &settings), SpaceLevel::Critical);
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let settings = DiskGuardSettings {
+            warning_free_mb: 100,
+            critical_free_mb: 200,
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_suggestions_sorted_and_skip_empty() {
+        let items = vec![
+            StorageItem { category: StorageCategory::AssetCache, path: None, bytes: 10, file_count: 1 },
+            StorageItem { category: StorageCategory::Downloads, path: None, bytes: 500, file_count: 2 },
+            StorageItem { category: StorageCategory::TrashedFiles, path: None, bytes: 0, file_count: 0 },
+            StorageItem { category: StorageCategory::Models, path: None, bytes: 9999, file_count: 1 },
+        ];
+        let old_logs = vec![(PathBuf::from("a.log"), 100)];
+        let suggestions = suggestions_for(&items, &old_logs, &DiskGuardSettings::default());
+
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0].action, CleanupAction::Downloads);
+        assert!(matches!(suggestions[1].action, CleanupAction::OldLogs { .. }));
+        assert_eq!(suggestions[2].action, CleanupAction::AssetCache);
+    }
+}
@@ -0,0 +1,282 @@
+//! # 角色情绪引擎
+//!
+//! 每个角色一台情绪状态机。聊天回复带来的情绪信号（后端返回的情绪标签，没有时用本地关键词
+//! 分类）推动状态切换：同一情绪叠加强度，不同情绪需要足够强才能打断当前情绪，刚切换的情绪
+//! 至少保持 [`MIN_DWELL_SECS`] 秒；强度随时间半衰，低于阈值后回到平静。
+//!
+//! 情绪到表情 / 动作的映射存放在 `character_configs.config_json` 的 `emotion` 字段，
+//! 播放表情与动作见 `commands::emotion`。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// 强度半衰期（秒）
+pub const DECAY_HALF_LIFE_SECS: f32 = 90.0;
+/// 切换后至少保持的时间（秒），避免表情来回闪烁
+pub const MIN_DWELL_SECS: i64 = 8;
+/// 强度低于该值时回到平静
+const RESTING_INTENSITY: f32 = 0.15;
+/// 新情绪的强度至少达到当前强度的该比例才会打断当前情绪
+const SWITCH_MARGIN: f32 = 0.8;
+/// 后端情绪标签的信号强度
+pub const TAG_STRENGTH: f32 = 0.8;
+/// `character_configs.config_json` 中情绪配置的键
+const CONFIG_KEY: &str = "emotion";
+
+/// 情绪
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emotion {
+    Neutral,
+    Happy,
+    Sad,
+    Angry,
+    Surprised,
+    Shy,
+}
+
+impl Emotion {
+    pub const ALL: [Emotion; 6] = [
+        Emotion::Neutral,
+        Emotion::Happy,
+        Emotion::Sad,
+        Emotion::Angry,
+        Emotion::Surprised,
+        Emotion::Shy,
+    ];
+
+    /// 解析后端的情绪标签（兼容常见的同义词）
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let emotion = match tag.trim().to_lowercase().as_str() {
+            "neutral" | "calm" | "normal" | "平静" => Emotion::Neutral,
+            "happy" | "joy" | "excited" | "positive" | "开心" | "高兴" => Emotion::Happy,
+            "sad" | "sadness" | "negative" | "worried" | "难过" | "伤心" => Emotion::Sad,
+            "angry" | "anger" | "annoyed" | "生气" => Emotion::Angry,
+            "surprised" | "surprise" | "shocked" | "惊讶" => Emotion::Surprised,
+            "shy" | "embarrassed" | "害羞" => Emotion::Shy,
+            _ => return None,
+        };
+        Some(emotion)
+    }
+
+    fn default_expression(self) -> &'static str {
+        match self {
+            Emotion::Neutral => "normal",
+            Emotion::Happy => "smile",
+            Emotion::Sad => "sad",
+            Emotion::Angry => "angry",
+            Emotion::Surprised => "surprised",
+            Emotion::Shy => "shy",
+        }
+    }
+}
+
+/// 本地分类使用的关键词
+const KEYWORDS: &[(Emotion, &[&str])] = &[
+    (Emotion::Happy, &["哈哈", "开心", "高兴", "太好了", "喜欢", "谢谢", "😊", "😄", "~♪", "great", "glad", "love", "awesome"]),
+    (Emotion::Sad, &["难过", "伤心", "抱歉", "对不起", "遗憾", "可惜", "😢", "sorry", "sad", "unfortunately"]),
+    (Emotion::Angry, &["生气", "讨厌", "可恶", "哼", "😠", "angry", "annoying"]),
+    (Emotion::Surprised, &["哇", "诶", "竟然", "居然", "真的吗", "😮", "wow", "really?", "surprising"]),
+    (Emotion::Shy, &["害羞", "不好意思", "脸红", "///", "😳", "blush"]),
+];
+
+/// 本地关键词分类：返回命中最多的情绪与信号强度，没有命中时为空
+pub fn classify(text: &str) -> Option<(Emotion, f32)> {
+    let text = text.to_lowercase();
+    KEYWORDS
+        .iter()
+        .map(|(emotion, words)| (*emotion, words.iter().filter(|word| text.contains(*word)).count()))
+        .filter(|(_, hits)| *hits > 0)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(emotion, hits)| (emotion, (0.3 + 0.15 * hits as f32).min(0.9)))
+}
+
+/// 一次情绪切换
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EmotionTransition {
+    pub from: Emotion,
+    pub to: Emotion,
+    pub intensity: f32,
+}
+
+/// 情绪状态快照（时间均为 Unix 秒）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EmotionSnapshot {
+    pub emotion: Emotion,
+    pub intensity: f32,
+    pub since: i64,
+}
+
+/// 单个角色的情绪状态机
+#[derive(Debug, Clone)]
+pub struct EmotionMachine {
+    emotion: Emotion,
+    intensity: f32,
+    since: i64,
+    updated_at: i64,
+}
+
+impl EmotionMachine {
+    pub fn new(now: i64) -> Self {
+        Self { emotion: Emotion::Neutral, intensity: 0.0, since: now, updated_at: now }
+    }
+
+    fn intensity_at(&self, now: i64) -> f32 {
+        let elapsed = (now - self.updated_at).max(0) as f32;
+        self.intensity * 0.5f32.powf(elapsed / DECAY_HALF_LIFE_SECS)
+    }
+
+    pub fn snapshot(&self, now: i64) -> EmotionSnapshot {
+        let intensity = self.intensity_at(now);
+        if self.emotion != Emotion::Neutral && intensity < RESTING_INTENSITY {
+            return EmotionSnapshot { emotion: Emotion::Neutral, intensity: 0.0, since: now };
+        }
+        EmotionSnapshot { emotion: self.emotion, intensity, since: self.since }
+    }
+
+    fn settle(&mut self, emotion: Emotion, intensity: f32, now: i64) -> Option<EmotionTransition> {
+        let (emotion, intensity) = if intensity < RESTING_INTENSITY {
+            (Emotion::Neutral, 0.0)
+        } else {
+            (emotion, intensity)
+        };
+        let from = self.emotion;
+        self.intensity = intensity;
+        self.updated_at = now;
+        if emotion == from {
+            return None;
+        }
+        self.emotion = emotion;
+        self.since = now;
+        Some(EmotionTransition { from, to: emotion, intensity })
+    }
+
+    /// 强度衰减到阈值以下时回到平静
+    pub fn tick(&mut self, now: i64) -> Option<EmotionTransition> {
+        let intensity = self.intensity_at(now);
+        self.settle(self.emotion, intensity, now)
+    }
+
+    /// 接收一个情绪信号，发生切换时返回切换
+    pub fn observe(&mut self, signal: Emotion, strength: f32, now: i64) -> Option<EmotionTransition> {
+        let strength = strength.clamp(0.0, 1.0);
+        let current = self.intensity_at(now);
+        let dwelling = self.emotion != Emotion::Neutral && now - self.since < MIN_DWELL_SECS;
+
+        let (emotion, intensity) = if signal == Emotion::Neutral {
+            // 平静的回复让当前情绪加快消退
+            (self.emotion, current * 0.5)
+        } else if signal == self.emotion {
+            (signal, current + strength * (1.0 - current))
+        } else if !dwelling && strength >= current * SWITCH_MARGIN {
+            (signal, strength)
+        } else {
+            (self.emotion, current)
+        };
+        self.settle(emotion, intensity, now)
+    }
+}
+
+/// 角色的情绪配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmotionConfig {
+    /// 是否根据聊天自动切换表情
+    pub enabled: bool,
+    /// 后端没有返回情绪标签时是否用本地关键词分类
+    pub use_local_classifier: bool,
+    /// 情绪 -> 表情名
+    pub expressions: BTreeMap<Emotion, String>,
+    /// 情绪 -> 切换时播放的动作名
+    pub motions: BTreeMap<Emotion, String>,
+}
+
+impl Default for EmotionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            use_local_classifier: true,
+            expressions: Emotion::ALL
+                .iter()
+                .map(|emotion| (*emotion, emotion.default_expression().to_string()))
+                .collect(),
+            motions: BTreeMap::new(),
+        }
+    }
+}
+
+impl EmotionConfig {
+    /// 从角色配置的 `config_json` 读取，缺失或无法解析时使用默认配置
+    pub fn from_config_json(config_json: Option<&str>) -> Self {
+        config_json
+            .and_then(|json| serde_json::from_str::<Value>(json).ok())
+            .and_then(|value| value.get(CONFIG_KEY).cloned())
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写回 `config_json`，保留其中的其他字段
+    pub fn merge_into(&self, config_json: Option<&str>) -> Result<String, String> {
+        let mut value = config_json
+            .and_then(|json| serde_json::from_str::<Value>(json).ok())
+            .filter(Value::is_object)
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        value[CONFIG_KEY] = serde_json::to_value(self).map_err(|e| format!("序列化情绪配置失败: {}", e))?;
+        serde_json::to_string(&value).map_err(|e| format!("序列化角色配置失败: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.expressions.values().chain(self.motions.values()).any(|name| name.trim().is_empty()) {
+            return Err("表情或动作名不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_and_tags() {
+        assert_eq!(classify("哈哈，太好了！").map(|(e, _)| e), Some(Emotion::Happy));
+        assert_eq!(classify("对不起，这次没能帮上忙").map(|(e, _)| e), Some(Emotion::Sad));
+        assert!(classify("今天的会议在三点").is_none());
+        assert_eq!(Emotion::from_tag(" Joy "), Some(Emotion::Happy));
+        assert_eq!(Emotion::from_tag("unknown"), None);
+    }
+
+    #[test]
+    fn test_state_machine_transitions() {
+        let mut machine = EmotionMachine::new(0);
+        let transition = machine.observe(Emotion::Happy, 0.8, 0).unwrap();
+        assert_eq!((transition.from, transition.to), (Emotion::Neutral, Emotion::Happy));
+
+        // 刚切换时不会被打断，相同情绪叠加强度
+        assert!(machine.observe(Emotion::Sad, 0.9, 3).is_none());
+        assert!(machine.observe(Emotion::Happy, 0.5, 4).is_none());
+        assert!(machine.snapshot(4).intensity > 0.8);
+
+        // 保持时间过后，足够强的信号可以切换
+        assert!(machine.observe(Emotion::Angry, 0.3, 20).is_none());
+        assert_eq!(machine.observe(Emotion::Sad, 0.9, 20).unwrap().to, Emotion::Sad);
+
+        // 强度衰减后回到平静
+        assert_eq!(machine.snapshot(1_000).emotion, Emotion::Neutral);
+        assert_eq!(machine.tick(1_000).unwrap().to, Emotion::Neutral);
+        assert!(machine.tick(1_001).is_none());
+    }
+
+    #[test]
+    fn test_config_round_trip_preserves_other_fields() {
+        let mut config = EmotionConfig::default();
+        config.motions.insert(Emotion::Happy, "jump".to_string());
+        let json = config.merge_into(Some(r#"{"custom_param": "value"}"#)).unwrap();
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["custom_param"], "value");
+        assert_eq!(EmotionConfig::from_config_json(Some(&json)), config);
+        assert_eq!(EmotionConfig::from_config_json(None).expressions[&Emotion::Happy], "smile");
+    }
+}
@@ -224,6 +224,25 @@ pub fn generate_random_password(length: usize) -> Result<String, EncryptionError
         .collect())
 }
 
+/// 使用系统密钥链中保存的随机密钥的加密器
+///
+/// 密钥不存在时按 `create` 决定是否生成并保存；不生成时返回 `None`。
+pub fn keyring_encryption(entry_name: &str, create: bool) -> Result<Option<EncryptionManager>, String> {
+    match super::keyring_store::try_load(entry_name)? {
+        Some(encoded) => {
+            let bytes = general_purpose::STANDARD.decode(encoded).map_err(|e| format!("密钥损坏: {}", e))?;
+            let key: [u8; 32] = bytes.try_into().map_err(|_| "密钥长度错误".to_string())?;
+            Ok(Some(EncryptionManager::new(key)))
+        }
+        None if create => {
+            let key = generate_random_key().map_err(|e| format!("生成密钥失败: {}", e))?;
+            super::keyring_store::store(entry_name, Some(&general_purpose::STANDARD.encode(key)))?;
+            Ok(Some(EncryptionManager::new(key)))
+        }
+        None => Ok(None),
+    }
+}
+
 /// 快速加密辅助函数（使用默认参数）
 pub fn quick_encrypt(password: &str, plaintext: &str) -> Result<(EncryptedData, KeyDerivationParams), EncryptionError> {
     let salt = generate_salt()?;
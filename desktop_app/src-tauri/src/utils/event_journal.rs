@@ -0,0 +1,325 @@
+//! # 事件日志
+//!
+//! 只追加的事件日志（event sourcing）：设置变更、角色切换、权限授予 / 拒绝 / 撤销与会话
+//! 生命周期等重要状态变化按发生顺序写入 `event_journal.jsonl`，每行一个事件。
+//!
+//! - 事件带有递增的序号，可按序号范围读取事件流
+//! - [`replay`] 从事件重建某一时刻的状态，用于排查问题，也是之后同步与撤销功能的基础
+//! - 文件超过 [`MAX_JOURNAL_BYTES`] 时整体归档为 `event_journal.1.jsonl`（只保留一份归档）
+//! - 写入失败只记录警告，不影响业务流程
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// 当前日志文件的大小上限
+pub const MAX_JOURNAL_BYTES: u64 = 16 * 1024 * 1024;
+
+const JOURNAL_FILE: &str = "event_journal.jsonl";
+const ARCHIVE_FILE: &str = "event_journal.1.jsonl";
+
+/// 事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// 设置已保存，payload: `{ "config": 完整配置 }`
+    SettingsChanged,
+    /// payload: `{ "from": 旧角色, "to": 新角色 }`
+    CharacterSwitched,
+    /// payload: `{ "entity_id", "permission_type", "level" }`
+    PermissionGranted,
+    /// payload: `{ "entity_id", "permission_type" }`
+    PermissionDenied,
+    /// payload: `{ "entity_id", "permission_type" }`
+    PermissionRevoked,
+    /// payload: `{ "session_id", "character_id", "model" }`
+    SessionStarted,
+    /// 会话历史被清空，payload: `{ "session_id" }`
+    SessionCleared,
+}
+
+/// 日志中的一个事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEvent {
+    pub seq: u64,
+    pub timestamp: i64,
+    pub kind: EventKind,
+    pub payload: Value,
+}
+
+/// 事件流范围（序号闭区间）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventRange {
+    pub from_seq: Option<u64>,
+    pub to_seq: Option<u64>,
+    /// 最多返回的事件数（从范围起点开始）
+    pub limit: Option<usize>,
+}
+
+impl EventRange {
+    fn contains(&self, seq: u64) -> bool {
+        let after_start = match self.from_seq {
+            Some(from) => seq >= from,
+            None => true,
+        };
+        let before_end = match self.to_seq {
+            Some(to) => seq <= to,
+            None => true,
+        };
+        after_start && before_end
+    }
+}
+
+/// 重放得到的会话状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    pub started_at: i64,
+    pub character_id: Option<String>,
+    pub model: Option<String>,
+    /// 最近一次清空历史的时间
+    pub cleared_at: Option<i64>,
+}
+
+/// 从事件重建的状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayedState {
+    /// 最后应用的事件序号
+    pub last_seq: u64,
+    pub last_timestamp: Option<i64>,
+    pub settings: Option<Value>,
+    pub active_character: Option<String>,
+    /// `entity_id/permission_type` -> 授予的级别（拒绝或撤销后移除）
+    pub permissions: BTreeMap<String, String>,
+    pub sessions: BTreeMap<String, SessionState>,
+}
+
+fn payload_str(payload: &Value, key: &str) -> Option<String> {
+    payload.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+impl ReplayedState {
+    /// 应用一个事件
+    pub fn apply(&mut self, event: &JournalEvent) {
+        let payload = &event.payload;
+        match event.kind {
+            EventKind::SettingsChanged => {
+                if let Some(config) = payload.get("config") {
+                    self.settings = Some(config.clone());
+                }
+            }
+            EventKind::CharacterSwitched => self.active_character = payload_str(payload, "to"),
+            EventKind::PermissionGranted | EventKind::PermissionDenied | EventKind::PermissionRevoked => {
+                let key = format!(
+                    "{}/{}",
+                    payload_str(payload, "entity_id").unwrap_or_default(),
+                    payload_str(payload, "permission_type").unwrap_or_default()
+                );
+                match (event.kind, payload.get("level")) {
+                    (EventKind::PermissionGranted, Some(level)) => {
+                        let level = level.as_str().map(str::to_string).unwrap_or_else(|| level.to_string());
+                        self.permissions.insert(key, level);
+                    }
+                    _ => {
+                        self.permissions.remove(&key);
+                    }
+                }
+            }
+            EventKind::SessionStarted => {
+                if let Some(session_id) = payload_str(payload, "session_id") {
+                    self.sessions.insert(
+                        session_id,
+                        SessionState {
+                            started_at: event.timestamp,
+                            character_id: payload_str(payload, "character_id"),
+                            model: payload_str(payload, "model"),
+                            cleared_at: None,
+                        },
+                    );
+                }
+            }
+            EventKind::SessionCleared => {
+                if let Some(session_id) = payload_str(payload, "session_id") {
+                    self.sessions.entry(session_id).or_default().cleared_at = Some(event.timestamp);
+                }
+            }
+        }
+        self.last_seq = event.seq;
+        self.last_timestamp = Some(event.timestamp);
+    }
+}
+
+/// 按顺序重放事件，`until_seq` 为空时重放全部
+pub fn replay<'a>(events: impl IntoIterator<Item = &'a JournalEvent>, until_seq: Option<u64>) -> ReplayedState {
+    let mut state = ReplayedState::default();
+    for event in events {
+        if until_seq.is_some_and(|until| event.seq > until) {
+            break;
+        }
+        state.apply(event);
+    }
+    state
+}
+
+/// 解析日志文件内容；不完整的行（写入时崩溃）会被跳过
+fn parse_events(content: &str) -> impl Iterator<Item = JournalEvent> + '_ {
+    content.lines().filter_map(|line| serde_json::from_str(line).ok())
+}
+
+struct Journal {
+    dir: PathBuf,
+    next_seq: u64,
+    /// 文件末尾是写入一半的行，下次追加需先换行
+    torn_tail: bool,
+}
+
+impl Journal {
+    fn open(dir: PathBuf) -> Self {
+        let last_seq = [ARCHIVE_FILE, JOURNAL_FILE]
+            .iter()
+            .filter_map(|file| fs::read_to_string(dir.join(file)).ok())
+            .flat_map(|content| parse_events(&content).map(|e| e.seq).collect::<Vec<_>>())
+            .max()
+            .unwrap_or(0);
+        let torn_tail = fs::read(dir.join(JOURNAL_FILE))
+            .map(|content| content.last().is_some_and(|byte| *byte != b'\n'))
+            .unwrap_or(false);
+        Self { dir, next_seq: last_seq + 1, torn_tail }
+    }
+
+    fn append(&mut self, kind: EventKind, payload: Value, timestamp: i64) -> Result<JournalEvent, String> {
+        let path = self.dir.join(JOURNAL_FILE);
+        if fs::metadata(&path).map(|m| m.len() >= MAX_JOURNAL_BYTES).unwrap_or(false) {
+            fs::rename(&path, self.dir.join(ARCHIVE_FILE)).map_err(|e| format!("归档事件日志失败: {}", e))?;
+            self.torn_tail = false;
+        }
+
+        let event = JournalEvent { seq: self.next_seq, timestamp, kind, payload };
+        let mut line = if self.torn_tail { "\n".to_string() } else { String::new() };
+        line.push_str(&serde_json::to_string(&event).map_err(|e| format!("序列化事件失败: {}", e))?);
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("写入事件日志失败: {}", e))?;
+        self.next_seq += 1;
+        self.torn_tail = false;
+        Ok(event)
+    }
+
+    fn read(&self, range: &EventRange) -> Vec<JournalEvent> {
+        let limit = range.limit.unwrap_or(usize::MAX);
+        [ARCHIVE_FILE, JOURNAL_FILE]
+            .iter()
+            .filter_map(|file| fs::read_to_string(self.dir.join(file)).ok())
+            .flat_map(|content| parse_events(&content).collect::<Vec<_>>())
+            .filter(|event| range.contains(event.seq))
+            .take(limit)
+            .collect()
+    }
+}
+
+lazy_static! {
+    /// 首次使用时在应用数据目录打开
+    static ref JOURNAL: Mutex<Option<Journal>> = Mutex::new(None);
+}
+
+fn with_journal<T>(f: impl FnOnce(&mut Journal) -> T) -> Result<T, String> {
+    let mut journal = JOURNAL.lock();
+    if journal.is_none() {
+        let dir = crate::utils::config::get_app_data_dir()?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        *journal = Some(Journal::open(dir));
+    }
+    Ok(f(journal.as_mut().expect("journal opened above")))
+}
+
+/// 追加一个事件（失败只记录警告）
+pub fn record(kind: EventKind, payload: Value) {
+    let timestamp = chrono::Utc::now().timestamp();
+    match with_journal(|journal| journal.append(kind, payload, timestamp)) {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) | Err(e) => warn!("记录事件 {:?} 失败: {}", kind, e),
+    }
+}
+
+/// 读取指定范围的事件
+pub fn read(range: &EventRange) -> Result<Vec<JournalEvent>, String> {
+    with_journal(|journal| journal.read(range))
+}
+
+/// 从日志起点重放到 `until_seq`（为空时重放全部）
+pub fn replay_journal(until_seq: Option<u64>) -> Result<ReplayedState, String> {
+    let events = read(&EventRange { to_seq: until_seq, ..EventRange::default() })?;
+    Ok(replay(&events, until_seq))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zishu_event_journal_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_append_and_read_range_across_reopen() {
+        let dir = temp_dir("range");
+        let mut journal = Journal::open(dir.clone());
+        for i in 0..5 {
+            journal.append(EventKind::SessionStarted, json!({ "session_id": format!("s{}", i) }), i).unwrap();
+        }
+        // 模拟写入一半时崩溃
+        let mut file = OpenOptions::new().append(true).open(dir.join(JOURNAL_FILE)).unwrap();
+        file.write_all(b"{\"seq\":6,\"times").unwrap();
+
+        let mut reopened = Journal::open(dir.clone());
+        assert_eq!(reopened.next_seq, 6);
+        let event = reopened.append(EventKind::SessionCleared, json!({ "session_id": "s0" }), 10).unwrap();
+        assert_eq!(event.seq, 6);
+
+        let range = EventRange { from_seq: Some(2), to_seq: Some(5), limit: Some(3) };
+        let seqs: Vec<u64> = reopened.read(&range).iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![2, 3, 4]);
+        assert_eq!(reopened.read(&EventRange::default()).len(), 6);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replay_reconstructs_state() {
+        let event = |seq: u64, kind: EventKind, payload: Value| JournalEvent { seq, timestamp: seq as i64 * 10, kind, payload };
+        let events = vec![
+            event(1, EventKind::SettingsChanged, json!({ "config": { "theme": "light" } })),
+            event(2, EventKind::CharacterSwitched, json!({ "from": "hiyori", "to": "shizuku" })),
+            event(3, EventKind::PermissionGranted, json!({ "entity_id": "weather", "permission_type": "network", "level": "Full" })),
+            event(4, EventKind::SessionStarted, json!({ "session_id": "s1", "character_id": "shizuku" })),
+            event(5, EventKind::SettingsChanged, json!({ "config": { "theme": "dark" } })),
+            event(6, EventKind::PermissionRevoked, json!({ "entity_id": "weather", "permission_type": "network" })),
+            event(7, EventKind::SessionCleared, json!({ "session_id": "s1" })),
+        ];
+
+        let at_four = replay(&events, Some(4));
+        assert_eq!(at_four.last_seq, 4);
+        assert_eq!(at_four.settings, Some(json!({ "theme": "light" })));
+        assert_eq!(at_four.active_character.as_deref(), Some("shizuku"));
+        assert_eq!(at_four.permissions.get("weather/network").map(String::as_str), Some("Full"));
+        assert_eq!(at_four.sessions["s1"].cleared_at, None);
+
+        let latest = replay(&events, None);
+        assert_eq!(latest.settings, Some(json!({ "theme": "dark" })));
+        assert!(latest.permissions.is_empty());
+        assert_eq!(latest.sessions["s1"].cleared_at, Some(70));
+    }
+}
@@ -0,0 +1,262 @@
+//! # 事件订阅
+//!
+//! 高频事件（系统监控、资源预算等）不再通过 `emit_all` 广播给所有窗口，而是只发送给订阅了
+//! 该频道的窗口，减少 webview IPC 开销：
+//!
+//! - 窗口按频道订阅，频道名以 `*` 结尾时按前缀匹配
+//! - 可选节流：同一订阅两次投递间隔不小于 `throttle_ms`，期间的事件直接丢弃
+//! - 可选过滤：payload 中指定字段（点分路径）必须等于给定值
+//! - 窗口销毁时清除其全部订阅
+//!
+//! 投递到窗口的逻辑见 `commands::event_subscription::publish`。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// 订阅选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionOptions {
+    /// 最小投递间隔（毫秒）
+    pub throttle_ms: Option<u64>,
+    /// 字段路径（如 `cpu.usage` 或 `status`）-> 期望值
+    pub filter: BTreeMap<String, Value>,
+}
+
+/// 订阅信息（含投递统计）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub window_label: String,
+    pub channel: String,
+    pub options: SubscriptionOptions,
+    pub delivered: u64,
+    /// 因节流丢弃的事件数
+    pub throttled: u64,
+    /// 因过滤条件不满足跳过的事件数
+    pub filtered: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    options: SubscriptionOptions,
+    last_delivered: Option<Instant>,
+    delivered: u64,
+    throttled: u64,
+    filtered: u64,
+}
+
+impl Entry {
+    fn matches_filter(&self, payload: &Value) -> bool {
+        self.options.filter.iter().all(|(path, expected)| {
+            path.split('.')
+                .try_fold(payload, |current, key| current.get(key))
+                .is_some_and(|actual| actual == expected)
+        })
+    }
+
+    fn is_throttled(&self, now: Instant) -> bool {
+        match (self.options.throttle_ms, self.last_delivered) {
+            (Some(ms), Some(last)) => now.saturating_duration_since(last) < Duration::from_millis(ms),
+            _ => false,
+        }
+    }
+}
+
+fn channel_matches(pattern: &str, channel: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => channel.starts_with(prefix),
+        None => pattern == channel,
+    }
+}
+
+/// 窗口 -> 频道 -> 订阅
+#[derive(Debug, Default)]
+pub struct SubscriptionRegistry {
+    windows: HashMap<String, HashMap<String, Entry>>,
+}
+
+impl SubscriptionRegistry {
+    /// 订阅频道；重复订阅时更新选项并保留统计
+    pub fn subscribe(&mut self, window_label: &str, channel: &str, options: SubscriptionOptions) {
+        let channels = self.windows.entry(window_label.to_string()).or_default();
+        match channels.get_mut(channel) {
+            Some(entry) => entry.options = options,
+            None => {
+                channels.insert(
+                    channel.to_string(),
+                    Entry {
+                        options,
+                        last_delivered: None,
+                        delivered: 0,
+                        throttled: 0,
+                        filtered: 0,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn unsubscribe(&mut self, window_label: &str, channel: &str) -> bool {
+        let Some(channels) = self.windows.get_mut(window_label) else {
+            return false;
+        };
+        let removed = channels.remove(channel).is_some();
+        if channels.is_empty() {
+            self.windows.remove(window_label);
+        }
+        removed
+    }
+
+    /// 清除窗口的全部订阅，返回清除的数量
+    pub fn remove_window(&mut self, window_label: &str) -> usize {
+        self.windows.remove(window_label).map_or(0, |channels| channels.len())
+    }
+
+    pub fn has_subscribers(&self, channel: &str) -> bool {
+        self.windows
+            .values()
+            .any(|channels| channels.keys().any(|pattern| channel_matches(pattern, channel)))
+    }
+
+    /// 计算本次事件要投递的窗口，并更新投递统计；同一窗口最多投递一次
+    pub fn deliveries(&mut self, channel: &str, payload: &Value, now: Instant) -> Vec<String> {
+        let mut targets = Vec::new();
+        for (label, channels) in self.windows.iter_mut() {
+            let mut deliver = false;
+            for (_, entry) in channels.iter_mut().filter(|(pattern, _)| channel_matches(pattern, channel)) {
+                if !entry.matches_filter(payload) {
+                    entry.filtered += 1;
+                } else if entry.is_throttled(now) {
+                    entry.throttled += 1;
+                } else {
+                    entry.last_delivered = Some(now);
+                    entry.delivered += 1;
+                    deliver = true;
+                }
+            }
+            if deliver {
+                targets.push(label.clone());
+            }
+        }
+        targets.sort();
+        targets
+    }
+
+    /// 列出订阅；指定窗口时只列出该窗口的订阅
+    pub fn list(&self, window_label: Option<&str>) -> Vec<SubscriptionInfo> {
+        let mut infos: Vec<SubscriptionInfo> = self
+            .windows
+            .iter()
+            .filter(|(label, _)| match window_label {
+                Some(wanted) => wanted == label.as_str(),
+                None => true,
+            })
+            .flat_map(|(label, channels)| {
+                channels.iter().map(move |(channel, entry)| SubscriptionInfo {
+                    window_label: label.clone(),
+                    channel: channel.clone(),
+                    options: entry.options.clone(),
+                    delivered: entry.delivered,
+                    throttled: entry.throttled,
+                    filtered: entry.filtered,
+                })
+            })
+            .collect();
+        infos.sort_by(|a, b| (&a.window_label, &a.channel).cmp(&(&b.window_label, &b.channel)));
+        infos
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<SubscriptionRegistry> = Mutex::new(SubscriptionRegistry::default());
+}
+
+pub fn subscribe(window_label: &str, channel: &str, options: SubscriptionOptions) {
+    REGISTRY.lock().subscribe(window_label, channel, options);
+}
+
+pub fn unsubscribe(window_label: &str, channel: &str) -> bool {
+    REGISTRY.lock().unsubscribe(window_label, channel)
+}
+
+pub fn remove_window(window_label: &str) -> usize {
+    REGISTRY.lock().remove_window(window_label)
+}
+
+pub fn has_subscribers(channel: &str) -> bool {
+    REGISTRY.lock().has_subscribers(channel)
+}
+
+pub fn deliveries(channel: &str, payload: &Value) -> Vec<String> {
+    REGISTRY.lock().deliveries(channel, payload, Instant::now())
+}
+
+pub fn list(window_label: Option<&str>) -> Vec<SubscriptionInfo> {
+    REGISTRY.lock().list(window_label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_routes_only_to_subscribers_with_throttle() {
+        let mut registry = SubscriptionRegistry::default();
+        let start = Instant::now();
+        registry.subscribe("main", "system-monitor-update", SubscriptionOptions::default());
+        registry.subscribe(
+            "settings",
+            "system-*",
+            SubscriptionOptions {
+                throttle_ms: Some(1000),
+                ..SubscriptionOptions::default()
+            },
+        );
+
+        assert!(!registry.has_subscribers("resource-budget-changed"));
+        let payload = json!({"cpu": {"usage": 12.5}});
+        assert_eq!(registry.deliveries("system-monitor-update", &payload, start), ["main", "settings"]);
+        let soon = start + Duration::from_millis(200);
+        assert_eq!(registry.deliveries("system-monitor-update", &payload, soon), ["main"]);
+        let later = start + Duration::from_millis(1200);
+        assert_eq!(registry.deliveries("system-monitor-update", &payload, later), ["main", "settings"]);
+
+        let settings = &registry.list(Some("settings"))[0];
+        assert_eq!((settings.delivered, settings.throttled), (2, 1));
+
+        assert_eq!(registry.remove_window("settings"), 1);
+        assert!(registry.unsubscribe("main", "system-monitor-update"));
+        assert!(!registry.has_subscribers("system-monitor-update"));
+        assert!(registry.list(None).is_empty());
+    }
+
+    #[test]
+    fn test_filter_on_payload_fields() {
+        let mut registry = SubscriptionRegistry::default();
+        let mut filter = BTreeMap::new();
+        filter.insert("device.kind".to_string(), json!("output"));
+        registry.subscribe(
+            "main",
+            "audio-device-route-changed",
+            SubscriptionOptions {
+                filter,
+                ..SubscriptionOptions::default()
+            },
+        );
+
+        let now = Instant::now();
+        let input = json!({"device": {"kind": "input"}});
+        let output = json!({"device": {"kind": "output"}});
+        assert!(registry.deliveries("audio-device-route-changed", &input, now).is_empty());
+        assert!(registry.deliveries("audio-device-route-changed", &json!({}), now).is_empty());
+        assert_eq!(registry.deliveries("audio-device-route-changed", &output, now), ["main"]);
+
+        let info = &registry.list(None)[0];
+        assert_eq!((info.delivered, info.filtered), (1, 2));
+    }
+}
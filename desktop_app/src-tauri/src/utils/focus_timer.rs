@@ -0,0 +1,270 @@
+//! # 专注计时（番茄钟）
+//!
+//! 桌宠陪伴的专注计时：专注一段时间后休息，每完成若干次专注进入一次长休息。
+//! 每个阶段开始与结束时角色播放设置中对应的动作。
+//!
+//! 本模块包含计时状态与统计汇总，计时调度、动作推送与持久化见 `commands::focus`。
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 阶段边界时角色播放的动作（为空表示不做反应）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusMotions {
+    pub work_start: String,
+    pub work_complete: String,
+    pub break_start: String,
+    pub break_complete: String,
+}
+
+impl Default for FocusMotions {
+    fn default() -> Self {
+        Self {
+            work_start: "focus".to_string(),
+            work_complete: "cheer".to_string(),
+            break_start: "stretch".to_string(),
+            break_complete: "wave".to_string(),
+        }
+    }
+}
+
+/// 专注计时设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusSettings {
+    /// 专注时长（分钟）
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// 每完成多少次专注进入长休息
+    pub sessions_before_long_break: u32,
+    /// 专注结束后自动开始休息
+    pub auto_start_breaks: bool,
+    /// 休息结束后自动开始下一次专注
+    pub auto_start_work: bool,
+    pub motions: FocusMotions,
+}
+
+impl Default for FocusSettings {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            sessions_before_long_break: 4,
+            auto_start_breaks: true,
+            auto_start_work: false,
+            motions: FocusMotions::default(),
+        }
+    }
+}
+
+impl FocusSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=180).contains(&self.work_minutes) {
+            return Err("专注时长必须在 1 到 180 分钟之间".to_string());
+        }
+        if !(1..=60).contains(&self.short_break_minutes) || !(1..=120).contains(&self.long_break_minutes) {
+            return Err("短休息必须在 1 到 60 分钟之间，长休息必须在 1 到 120 分钟之间".to_string());
+        }
+        if !(1..=12).contains(&self.sessions_before_long_break) {
+            return Err("长休息间隔必须在 1 到 12 次专注之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 阶段的默认时长（毫秒）
+    pub fn duration_ms(&self, phase: FocusPhase) -> i64 {
+        let minutes = match phase {
+            FocusPhase::Work => self.work_minutes,
+            FocusPhase::ShortBreak => self.short_break_minutes,
+            FocusPhase::LongBreak => self.long_break_minutes,
+        };
+        i64::from(minutes) * 60_000
+    }
+
+    /// 完成一个阶段后的下一个阶段；`completed_work` 为本轮已完成的专注次数（含刚完成的）
+    pub fn next_phase(&self, finished: FocusPhase, completed_work: u32) -> FocusPhase {
+        match finished {
+            FocusPhase::Work if completed_work > 0 && completed_work % self.sessions_before_long_break == 0 => {
+                FocusPhase::LongBreak
+            }
+            FocusPhase::Work => FocusPhase::ShortBreak,
+            FocusPhase::ShortBreak | FocusPhase::LongBreak => FocusPhase::Work,
+        }
+    }
+}
+
+/// 计时阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl FocusPhase {
+    pub fn is_break(self) -> bool {
+        self != FocusPhase::Work
+    }
+}
+
+/// 一个阶段的计时（时间均为 Unix 毫秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusTimer {
+    pub phase: FocusPhase,
+    pub task: Option<String>,
+    pub duration_ms: i64,
+    pub started_at: i64,
+    /// 之前各运行段累计的时长（暂停前）
+    accumulated_ms: i64,
+    /// 当前运行段的开始时间；暂停时为空
+    running_since: Option<i64>,
+}
+
+impl FocusTimer {
+    pub fn start(phase: FocusPhase, duration_ms: i64, task: Option<String>, now: i64) -> Self {
+        Self {
+            phase,
+            task,
+            duration_ms,
+            started_at: now,
+            accumulated_ms: 0,
+            running_since: Some(now),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+
+    /// 已计时的毫秒数（不超过阶段时长）
+    pub fn elapsed_ms(&self, now: i64) -> i64 {
+        let running = self.running_since.map(|since| (now - since).max(0)).unwrap_or(0);
+        (self.accumulated_ms + running).min(self.duration_ms)
+    }
+
+    pub fn remaining_ms(&self, now: i64) -> i64 {
+        self.duration_ms - self.elapsed_ms(now)
+    }
+
+    pub fn is_finished(&self, now: i64) -> bool {
+        self.remaining_ms(now) <= 0
+    }
+
+    /// 暂停；已暂停时返回 `false`
+    pub fn pause(&mut self, now: i64) -> bool {
+        match self.running_since.take() {
+            Some(since) => {
+                self.accumulated_ms += (now - since).max(0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 继续；未暂停时返回 `false`
+    pub fn resume(&mut self, now: i64) -> bool {
+        if self.running_since.is_some() {
+            return false;
+        }
+        self.running_since = Some(now);
+        true
+    }
+
+    /// 正在运行时的结束时间
+    pub fn ends_at(&self, now: i64) -> Option<i64> {
+        self.running_since.map(|_| now + self.remaining_ms(now))
+    }
+}
+
+/// 已完成的专注记录（Unix 秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusSessionRecord {
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub focus_secs: i64,
+    pub task: Option<String>,
+    pub character_id: Option<String>,
+}
+
+/// 某一天的专注统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusDay {
+    pub date: NaiveDate,
+    pub focus_secs: i64,
+    pub sessions: usize,
+}
+
+/// 按完成日期汇总 `[first_day, last_day]` 内每天的专注时长，没有记录的日期为 0
+pub fn daily_totals(
+    records: &[FocusSessionRecord],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+    local_date: impl Fn(i64) -> Option<NaiveDate>,
+) -> Vec<FocusDay> {
+    let mut days: BTreeMap<NaiveDate, FocusDay> = BTreeMap::new();
+    let mut day = first_day;
+    while day <= last_day {
+        days.insert(day, FocusDay { date: day, focus_secs: 0, sessions: 0 });
+        day += Duration::days(1);
+    }
+    for record in records {
+        if let Some(entry) = local_date(record.ended_at).and_then(|date| days.get_mut(&date)) {
+            entry.focus_secs += record.focus_secs;
+            entry.sessions += 1;
+        }
+    }
+    days.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_pause_and_resume() {
+        let mut timer = FocusTimer::start(FocusPhase::Work, 60_000, None, 1_000);
+        assert_eq!(timer.remaining_ms(11_000), 50_000);
+        assert!(timer.pause(11_000));
+        assert!(!timer.pause(12_000));
+        // 暂停期间不计时
+        assert_eq!(timer.remaining_ms(500_000), 50_000);
+        assert_eq!(timer.ends_at(500_000), None);
+        assert!(timer.resume(500_000));
+        assert_eq!(timer.ends_at(500_000), Some(550_000));
+        assert!(!timer.is_finished(549_999));
+        assert!(timer.is_finished(560_000));
+        assert_eq!(timer.elapsed_ms(600_000), 60_000);
+    }
+
+    #[test]
+    fn test_next_phase_inserts_long_breaks() {
+        let settings = FocusSettings::default();
+        assert_eq!(settings.next_phase(FocusPhase::Work, 1), FocusPhase::ShortBreak);
+        assert_eq!(settings.next_phase(FocusPhase::Work, 4), FocusPhase::LongBreak);
+        assert_eq!(settings.next_phase(FocusPhase::LongBreak, 4), FocusPhase::Work);
+        assert_eq!(settings.duration_ms(FocusPhase::ShortBreak), 5 * 60_000);
+    }
+
+    #[test]
+    fn test_daily_totals_fills_empty_days() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let record = |ended_at: i64, focus_secs| FocusSessionRecord {
+            started_at: ended_at - focus_secs,
+            ended_at,
+            focus_secs,
+            task: None,
+            character_id: None,
+        };
+        // 用 ended_at 直接表示日期，便于测试
+        let local_date = |ts: i64| Some(day(ts as u32));
+        let totals = daily_totals(&[record(2, 1500), record(2, 1500), record(4, 600), record(9, 60)], day(1), day(4), local_date);
+        assert_eq!(totals.len(), 4);
+        assert_eq!((totals[1].focus_secs, totals[1].sessions), (3000, 2));
+        assert_eq!((totals[2].focus_secs, totals[3].focus_secs), (0, 600));
+    }
+}
@@ -0,0 +1,447 @@
+//! 唤醒词检测
+//!
+//! 本地关键词识别：用户录制 3-5 遍唤醒词（如 "Hey Zishu"）作为模板，运行时把麦克风
+//! 音频转换成滤波器组特征，并与模板做 DTW 匹配。模型只有几十 KB，完全离线运行。
+//!
+//! 检测器按固定步长评估（默认 100 ms），监听线程根据 CPU 预算动态放大步长。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// 特征提取的采样率
+pub const SAMPLE_RATE: u32 = 16_000;
+/// 帧长 25 ms / 帧移 10 ms
+const FRAME_LEN: usize = 400;
+const HOP_LEN: usize = 160;
+/// 滤波器组中心频率（Hz）
+const BAND_FREQUENCIES: [f32; 12] = [
+    250.0, 400.0, 600.0, 850.0, 1150.0, 1500.0, 1900.0, 2400.0, 3000.0, 3700.0, 4500.0, 5500.0,
+];
+/// 帧总能量（对数）低于该值视为静音
+const SILENCE_LOG_ENERGY: f32 = -3.0;
+/// 至少需要的模板数
+pub const MIN_TEMPLATES: usize = 3;
+/// 最多保存的模板数
+pub const MAX_TEMPLATES: usize = 8;
+/// 默认评估步长（帧）
+pub const DEFAULT_EVAL_STRIDE: usize = 10;
+const MAX_EVAL_STRIDE: usize = 50;
+/// 模板过于一致时（例如只录了同一段音频）阈值的下限基准
+const MIN_BASELINE_DISTANCE: f32 = 0.2;
+
+lazy_static::lazy_static! {
+    static ref HOTWORD_SETTINGS: RwLock<HotwordSettings> = RwLock::new(HotwordSettings::default());
+}
+
+/// 唤醒词设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotwordSettings {
+    /// 是否常驻监听（默认关闭）
+    pub enabled: bool,
+    /// 唤醒词文本（仅用于显示）
+    pub phrase: String,
+    /// 灵敏度（0.0 - 1.0），越高越容易唤醒
+    pub sensitivity: f32,
+    /// 监听线程允许占用的 CPU 百分比（单核）
+    pub max_cpu_percent: f32,
+    /// 唤醒后的冷却时间（毫秒）
+    pub cooldown_ms: u64,
+    /// 监听时在托盘和界面上显示指示
+    pub show_indicator: bool,
+}
+
+impl Default for HotwordSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrase: "Hey Zishu".to_string(),
+            sensitivity: 0.5,
+            max_cpu_percent: 5.0,
+            cooldown_ms: 2000,
+            show_indicator: true,
+        }
+    }
+}
+
+impl HotwordSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.sensitivity) {
+            return Err("灵敏度必须在 0 到 1 之间".to_string());
+        }
+        if !(0.5..=100.0).contains(&self.max_cpu_percent) {
+            return Err("CPU 预算必须在 0.5% 到 100% 之间".to_string());
+        }
+        if self.phrase.trim().is_empty() {
+            return Err("唤醒词不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn current_settings() -> HotwordSettings {
+    HOTWORD_SETTINGS.read().clone()
+}
+
+pub fn set_settings(settings: HotwordSettings) -> Result<(), String> {
+    settings.validate()?;
+    *HOTWORD_SETTINGS.write() = settings;
+    Ok(())
+}
+
+type Features = Vec<[f32; BAND_FREQUENCIES.len()]>;
+
+/// Goertzel 算法计算单个频点的功率
+fn goertzel_power(frame: &[f32], frequency: f32) -> f32 {
+    let coefficient = 2.0 * (2.0 * std::f32::consts::PI * frequency / SAMPLE_RATE as f32).cos();
+    let (mut s1, mut s2) = (0.0f32, 0.0f32);
+    for &x in frame {
+        let s0 = x + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    (s1 * s1 + s2 * s2 - coefficient * s1 * s2) / frame.len() as f32
+}
+
+fn frame_features(frame: &[f32]) -> [f32; BAND_FREQUENCIES.len()] {
+    let windowed: Vec<f32> = frame
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let hann = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FRAME_LEN - 1) as f32).cos();
+            x * hann
+        })
+        .collect();
+    let mut bands = [0.0f32; BAND_FREQUENCIES.len()];
+    for (band, &frequency) in bands.iter_mut().zip(BAND_FREQUENCIES.iter()) {
+        *band = (goertzel_power(&windowed, frequency) + 1e-3).ln();
+    }
+    bands
+}
+
+/// 提取整段音频（16 kHz 单声道）的特征
+pub fn extract_features(samples: &[f32]) -> Features {
+    if samples.len() < FRAME_LEN {
+        return Vec::new();
+    }
+    (0..=(samples.len() - FRAME_LEN) / HOP_LEN)
+        .map(|i| frame_features(&samples[i * HOP_LEN..i * HOP_LEN + FRAME_LEN]))
+        .collect()
+}
+
+/// 帧的总对数能量
+fn frame_log_energy(frame: &[f32; BAND_FREQUENCIES.len()]) -> f32 {
+    frame.iter().map(|band| band.exp()).sum::<f32>().ln()
+}
+
+fn mean_log_energy(features: &[[f32; BAND_FREQUENCIES.len()]]) -> f32 {
+    if features.is_empty() {
+        return f32::MIN;
+    }
+    features.iter().map(frame_log_energy).sum::<f32>() / features.len() as f32
+}
+
+/// 均值归一化，消除音量和麦克风频响差异
+fn normalize(features: &[[f32; BAND_FREQUENCIES.len()]]) -> Features {
+    let mut mean = [0.0f32; BAND_FREQUENCIES.len()];
+    for frame in features {
+        for (m, v) in mean.iter_mut().zip(frame) {
+            *m += v / features.len() as f32;
+        }
+    }
+    features
+        .iter()
+        .map(|frame| {
+            let mut out = *frame;
+            for (o, m) in out.iter_mut().zip(&mean) {
+                *o -= m;
+            }
+            out
+        })
+        .collect()
+}
+
+/// 去掉首尾静音帧
+fn trim_silence(features: &[[f32; BAND_FREQUENCIES.len()]]) -> &[[f32; BAND_FREQUENCIES.len()]] {
+    let is_voiced = |f: &[f32; BAND_FREQUENCIES.len()]| frame_log_energy(f) > SILENCE_LOG_ENERGY;
+    let start = features.iter().position(is_voiced).unwrap_or(features.len());
+    let end = features.iter().rposition(is_voiced).map_or(start, |i| i + 1);
+    &features[start..end.max(start)]
+}
+
+/// 按路径长度归一化的 DTW 距离
+pub fn dtw_distance(a: &[[f32; BAND_FREQUENCIES.len()]], b: &[[f32; BAND_FREQUENCIES.len()]]) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return f32::INFINITY;
+    }
+    let distance = |x: &[f32; BAND_FREQUENCIES.len()], y: &[f32; BAND_FREQUENCIES.len()]| {
+        x.iter().zip(y).map(|(p, q)| (p - q) * (p - q)).sum::<f32>().sqrt()
+    };
+    let mut previous = vec![f32::INFINITY; b.len() + 1];
+    previous[0] = 0.0;
+    for x in a {
+        let mut current = vec![f32::INFINITY; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let best = previous[j].min(previous[j + 1]).min(current[j]);
+            current[j + 1] = distance(x, y) + best;
+        }
+        previous = current;
+    }
+    previous[b.len()] / (a.len() + b.len()) as f32
+}
+
+/// 唤醒词模型（用户录制的模板）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HotwordModel {
+    pub phrase: String,
+    pub templates: Vec<Features>,
+    /// 模板之间的平均距离，作为匹配阈值的基准
+    pub baseline_distance: f32,
+}
+
+impl HotwordModel {
+    pub fn is_ready(&self) -> bool {
+        self.templates.len() >= MIN_TEMPLATES
+    }
+
+    /// 添加一段录音作为模板，返回当前模板数
+    pub fn enroll(&mut self, samples: &[f32]) -> Result<usize, String> {
+        let features = extract_features(samples);
+        let voiced = trim_silence(&features);
+        // 至少 0.3 秒有效语音
+        if voiced.len() < 30 {
+            return Err("录音中没有检测到足够的语音，请靠近麦克风再录一次".to_string());
+        }
+        if voiced.len() > 250 {
+            return Err("唤醒词录音过长（最长 2.5 秒）".to_string());
+        }
+        if self.templates.len() >= MAX_TEMPLATES {
+            self.templates.remove(0);
+        }
+        self.templates.push(normalize(voiced));
+        self.recalibrate();
+        Ok(self.templates.len())
+    }
+
+    fn recalibrate(&mut self) {
+        let mut distances = Vec::new();
+        for (i, a) in self.templates.iter().enumerate() {
+            for b in &self.templates[i + 1..] {
+                distances.push(dtw_distance(a, b));
+            }
+        }
+        self.baseline_distance = if distances.is_empty() {
+            0.0
+        } else {
+            distances.iter().sum::<f32>() / distances.len() as f32
+        };
+    }
+
+    /// 灵敏度对应的匹配阈值
+    pub fn threshold(&self, sensitivity: f32) -> f32 {
+        self.baseline_distance.max(MIN_BASELINE_DISTANCE) * (0.8 + 0.8 * sensitivity.clamp(0.0, 1.0))
+    }
+
+    fn template_len_range(&self) -> (usize, usize) {
+        let lengths = self.templates.iter().map(Vec::len);
+        (lengths.clone().min().unwrap_or(0), lengths.max().unwrap_or(0))
+    }
+}
+
+/// 流式唤醒词检测器
+pub struct HotwordDetector {
+    model: HotwordModel,
+    threshold: f32,
+    pending: Vec<f32>,
+    frames: VecDeque<[f32; BAND_FREQUENCIES.len()]>,
+    max_frames: usize,
+    frames_since_eval: usize,
+    eval_stride: usize,
+    cooldown_frames: usize,
+    cooldown_remaining: usize,
+}
+
+impl HotwordDetector {
+    pub fn new(model: HotwordModel, settings: &HotwordSettings) -> Self {
+        let (_, max_len) = model.template_len_range();
+        Self {
+            threshold: model.threshold(settings.sensitivity),
+            max_frames: max_len + max_len / 4 + 1,
+            model,
+            pending: Vec::with_capacity(FRAME_LEN * 4),
+            frames: VecDeque::new(),
+            frames_since_eval: 0,
+            eval_stride: DEFAULT_EVAL_STRIDE,
+            cooldown_frames: (settings.cooldown_ms / 10) as usize,
+            cooldown_remaining: 0,
+        }
+    }
+
+    pub fn eval_stride(&self) -> usize {
+        self.eval_stride
+    }
+
+    pub fn set_eval_stride(&mut self, stride: usize) {
+        self.eval_stride = stride.clamp(1, MAX_EVAL_STRIDE);
+    }
+
+    /// 送入 16 kHz 单声道样本，检测到唤醒词时返回匹配距离
+    pub fn push(&mut self, samples: &[f32]) -> Option<f32> {
+        self.pending.extend_from_slice(samples);
+        let mut detected = None;
+        let mut consumed = 0;
+
+        while self.pending.len() - consumed >= FRAME_LEN {
+            let frame = frame_features(&self.pending[consumed..consumed + FRAME_LEN]);
+            consumed += HOP_LEN;
+
+            self.frames.push_back(frame);
+            if self.frames.len() > self.max_frames {
+                self.frames.pop_front();
+            }
+            if self.cooldown_remaining > 0 {
+                self.cooldown_remaining -= 1;
+                continue;
+            }
+            self.frames_since_eval += 1;
+            if self.frames_since_eval >= self.eval_stride && detected.is_none() {
+                self.frames_since_eval = 0;
+                detected = self.evaluate();
+                if detected.is_some() {
+                    self.frames.clear();
+                    self.cooldown_remaining = self.cooldown_frames;
+                }
+            }
+        }
+
+        self.pending.drain(..consumed);
+        detected
+    }
+
+    fn evaluate(&self) -> Option<f32> {
+        let (min_len, _) = self.model.template_len_range();
+        if min_len == 0 || self.frames.len() < min_len {
+            return None;
+        }
+        let window: Vec<_> = self.frames.iter().copied().collect();
+        let voiced = trim_silence(&window);
+        if voiced.len() < min_len * 2 / 3 || mean_log_energy(voiced) < SILENCE_LOG_ENERGY {
+            return None;
+        }
+        let candidate = normalize(voiced);
+        let best = self
+            .model
+            .templates
+            .iter()
+            .map(|template| dtw_distance(&candidate, template))
+            .fold(f32::INFINITY, f32::min);
+        (best <= self.threshold).then_some(best)
+    }
+}
+
+/// 根据实际 CPU 占用调整评估步长：超出预算时加倍，远低于预算时减半
+pub fn adjust_eval_stride(stride: usize, busy: Duration, wall: Duration, max_cpu_percent: f32) -> usize {
+    if wall.is_zero() {
+        return stride;
+    }
+    let usage = busy.as_secs_f32() / wall.as_secs_f32() * 100.0;
+    if usage > max_cpu_percent {
+        (stride * 2).min(MAX_EVAL_STRIDE)
+    } else if usage < max_cpu_percent / 4.0 {
+        (stride / 2).max(DEFAULT_EVAL_STRIDE)
+    } else {
+        stride
+    }
+}
+
+/// 多声道混合为单声道并线性重采样到 16 kHz
+pub fn to_mono_16k(samples: &[f32], channels: usize, sample_rate: u32) -> Vec<f32> {
+    let channels = channels.max(1);
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    if sample_rate == SAMPLE_RATE || mono.is_empty() {
+        return mono;
+    }
+    let ratio = sample_rate as f64 / SAMPLE_RATE as f64;
+    let out_len = (mono.len() as f64 / ratio) as usize;
+    (0..out_len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let next = mono.get(index + 1).copied().unwrap_or(mono[index]);
+            mono[index] * (1.0 - fraction) + next * fraction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 合成"语音"：前半段 600 Hz、后半段 1900 Hz 的音调
+    fn utterance(first: f32, second: f32, seconds: f32) -> Vec<f32> {
+        let n = (SAMPLE_RATE as f32 * seconds) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let frequency = if i < n / 2 { first } else { second };
+                0.5 * (2.0 * std::f32::consts::PI * frequency * t).sin()
+            })
+            .collect()
+    }
+
+    fn enrolled_model() -> HotwordModel {
+        let mut model = HotwordModel::default();
+        for seconds in [0.6, 0.65, 0.7] {
+            model.enroll(&utterance(600.0, 1900.0, seconds)).unwrap();
+        }
+        model
+    }
+
+    #[test]
+    fn test_enroll_rejects_silence() {
+        let mut model = HotwordModel::default();
+        assert!(model.enroll(&vec![0.0; SAMPLE_RATE as usize]).is_err());
+        assert!(!model.is_ready());
+    }
+
+    #[test]
+    fn test_detects_matching_utterance_only() {
+        let model = enrolled_model();
+        assert!(model.is_ready());
+        let settings = HotwordSettings::default();
+
+        let mut detector = HotwordDetector::new(model.clone(), &settings);
+        let mut audio = vec![0.0; 4000];
+        audio.extend(utterance(600.0, 1900.0, 0.62));
+        audio.extend(vec![0.0; 4000]);
+        assert!(audio.chunks(1600).any(|chunk| detector.push(chunk).is_some()));
+
+        let mut detector = HotwordDetector::new(model, &settings);
+        let mut other = vec![0.0; 4000];
+        other.extend(utterance(3700.0, 250.0, 0.62));
+        other.extend(vec![0.0; 4000]);
+        assert!(other.chunks(1600).all(|chunk| detector.push(chunk).is_none()));
+    }
+
+    #[test]
+    fn test_eval_stride_follows_cpu_budget() {
+        let wall = Duration::from_secs(1);
+        assert_eq!(adjust_eval_stride(10, Duration::from_millis(100), wall, 5.0), 20);
+        assert_eq!(adjust_eval_stride(20, Duration::from_millis(1), wall, 5.0), 10);
+        assert_eq!(adjust_eval_stride(10, Duration::from_millis(1), wall, 5.0), 10);
+    }
+
+    #[test]
+    fn test_resample_to_16k() {
+        let stereo_48k = vec![0.25f32; 48_000 * 2];
+        let resampled = to_mono_16k(&stereo_48k, 2, 48_000);
+        assert_eq!(resampled.len(), 16_000);
+        assert!((resampled[100] - 0.25).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,178 @@
+//! # 后端文字本地化
+//!
+//! 托盘菜单、系统通知、对话框与窗口标题等由后端生成的文字按当前界面语言显示。
+//! 各语言的文字表位于 `locales/<语言>.ftl`，编译时嵌入，格式取 Fluent 的子集：
+//!
+//! ```text
+//! # 注释
+//! tray-chat = 💬 开始对话
+//! notify-error-body = 错误: { $error }
+//! ```
+//!
+//! 查找顺序为当前语言、回退语言、中文（源语言），都没有时返回键本身。
+//! 语言由 `commands::language` 在启动与语言设置变化时通过 [`set_language`] 切换，立即生效。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+/// 源语言，所有文字都以它为准
+pub const SOURCE_LANGUAGE: &str = "zh";
+
+/// 内置文字表：(语言, 内容)
+const CATALOG_SOURCES: [(&str, &str); 4] = [
+    ("zh", include_str!("../../locales/zh.ftl")),
+    ("en", include_str!("../../locales/en.ftl")),
+    ("ja", include_str!("../../locales/ja.ftl")),
+    ("ko", include_str!("../../locales/ko.ftl")),
+];
+
+type Catalog = HashMap<String, String>;
+
+struct ActiveLanguage {
+    language: String,
+    fallback: String,
+}
+
+lazy_static! {
+    static ref CATALOGS: HashMap<&'static str, Catalog> = CATALOG_SOURCES
+        .iter()
+        .map(|(language, source)| {
+            let catalog = parse_catalog(source).unwrap_or_else(|e| {
+                tracing::error!("解析 {} 文字表失败: {}", language, e);
+                Catalog::new()
+            });
+            (*language, catalog)
+        })
+        .collect();
+    static ref ACTIVE: RwLock<ActiveLanguage> = RwLock::new(ActiveLanguage {
+        language: SOURCE_LANGUAGE.to_string(),
+        fallback: SOURCE_LANGUAGE.to_string(),
+    });
+}
+
+/// 解析文字表：每行 `键 = 文字`，`#` 开头为注释
+fn parse_catalog(source: &str) -> Result<Catalog, String> {
+    let mut catalog = Catalog::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("第 {} 行缺少 `=`", index + 1))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("第 {} 行的键无效: {}", index + 1, key));
+        }
+        if catalog.insert(key.to_string(), value.trim().to_string()).is_some() {
+            return Err(format!("第 {} 行的键重复: {}", index + 1, key));
+        }
+    }
+    Ok(catalog)
+}
+
+/// 替换 `{ $name }` 占位符，缺少的参数原样保留
+fn format_message(pattern: &str, args: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start..start + end + 1];
+        let name = placeholder[1..placeholder.len() - 1].trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => output.push_str(value),
+            None => output.push_str(placeholder),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// 是否有该语言的文字表
+pub fn is_supported(language: &str) -> bool {
+    CATALOGS.contains_key(language)
+}
+
+/// 切换界面语言，返回语言是否变化；不支持的语言按源语言处理
+pub fn set_language(language: &str, fallback: &str) -> bool {
+    let supported = |language: &str| {
+        if is_supported(language) {
+            language.to_string()
+        } else {
+            SOURCE_LANGUAGE.to_string()
+        }
+    };
+    let mut active = ACTIVE.write();
+    let language = supported(language);
+    let changed = active.language != language;
+    active.language = language;
+    active.fallback = supported(fallback);
+    changed
+}
+
+/// 当前界面语言
+pub fn language() -> String {
+    ACTIVE.read().language.clone()
+}
+
+/// 按当前语言查找文字，找不到时返回 `None`
+pub fn lookup(key: &str) -> Option<String> {
+    let active = ACTIVE.read();
+    [active.language.as_str(), active.fallback.as_str(), SOURCE_LANGUAGE]
+        .iter()
+        .find_map(|language| CATALOGS.get(language)?.get(key))
+        .cloned()
+}
+
+/// 按当前语言取文字
+pub fn t(key: &str) -> String {
+    t_args(key, &[])
+}
+
+/// 按当前语言取文字并替换参数
+pub fn t_args(key: &str, args: &[(&str, &str)]) -> String {
+    match lookup(key) {
+        Some(pattern) => format_message(&pattern, args),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let catalog = parse_catalog("# 注释\n\ngreeting = 你好，{ $name }！\nplain=text\n").unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(format_message(&catalog["greeting"], &[("name", "Zishu")]), "你好，Zishu！");
+        assert_eq!(catalog["plain"], "text");
+        // 缺少的参数与不完整的占位符原样保留
+        assert_eq!(format_message("{ $a } and {$b}", &[("b", "B")]), "{ $a } and B");
+        assert_eq!(format_message("open { brace", &[]), "open { brace");
+
+        assert!(parse_catalog("no separator").is_err());
+        assert!(parse_catalog("a = 1\na = 2").is_err());
+        assert!(parse_catalog("bad key = 1").is_err());
+    }
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        let source = &CATALOGS[SOURCE_LANGUAGE];
+        assert!(!source.is_empty());
+        for (language, _) in CATALOG_SOURCES {
+            let catalog = &CATALOGS[language];
+            let mut missing: Vec<&String> = source.keys().filter(|key| !catalog.contains_key(*key)).collect();
+            missing.sort();
+            assert!(missing.is_empty(), "{} 缺少: {:?}", language, missing);
+            assert_eq!(catalog.len(), source.len(), "{} 有多余的键", language);
+        }
+    }
+}
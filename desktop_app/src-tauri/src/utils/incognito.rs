@@ -0,0 +1,119 @@
+//! # 无痕会话
+//!
+//! 无痕会话的消息只保存在内存中：不写入数据库、向量索引与日志，不进入发件箱与托盘最近对话，
+//! 会话关闭或超过空闲时限后立即清除。
+//!
+//! 无痕会话的 ID 带有固定前缀，即使内存中的会话已被清除（如应用重启），带该前缀的
+//! 会话 ID 也不会被当作普通会话保存。会话管理见 `commands::incognito`。
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::model_parameters::ParameterOverrides;
+
+/// 无痕会话 ID 前缀
+pub const SESSION_PREFIX: &str = "incognito_";
+/// 默认空闲时限（秒）
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30 * 60;
+
+/// 是否为无痕会话 ID
+pub fn is_incognito_id(session_id: &str) -> bool {
+    session_id.starts_with(SESSION_PREFIX)
+}
+
+pub fn new_session_id() -> String {
+    format!("{}{}", SESSION_PREFIX, uuid::Uuid::new_v4().simple())
+}
+
+pub fn validate_timeout(timeout_secs: u64) -> Result<(), String> {
+    if !(60..=24 * 3600).contains(&timeout_secs) {
+        return Err("无痕会话时限必须在 1 分钟到 24 小时之间".to_string());
+    }
+    Ok(())
+}
+
+/// 内存中的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncognitoMessage {
+    pub id: String,
+    /// user / assistant
+    pub role: String,
+    pub content: String,
+    pub created_at: i64,
+}
+
+/// 无痕会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncognitoSession {
+    pub session_id: String,
+    pub character_id: Option<String>,
+    pub created_at: i64,
+    pub last_activity: i64,
+    /// 空闲多久后清除（秒）
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub parameters: ParameterOverrides,
+    #[serde(default)]
+    pub messages: Vec<IncognitoMessage>,
+}
+
+impl IncognitoSession {
+    pub fn new(character_id: Option<String>, timeout_secs: u64, now: i64) -> Self {
+        Self {
+            session_id: new_session_id(),
+            character_id,
+            created_at: now,
+            last_activity: now,
+            timeout_secs,
+            parameters: ParameterOverrides::default(),
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn expires_at(&self) -> i64 {
+        self.last_activity + self.timeout_secs as i64
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at()
+    }
+
+    /// 追加消息并刷新活动时间
+    pub fn push(&mut self, role: &str, content: &str, now: i64) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.messages.push(IncognitoMessage {
+            id: id.clone(),
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: now,
+        });
+        self.last_activity = now;
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_ids_are_recognizable() {
+        let session = IncognitoSession::new(None, DEFAULT_TIMEOUT_SECS, 0);
+        assert!(is_incognito_id(&session.session_id));
+        assert_ne!(session.session_id, new_session_id());
+        assert!(!is_incognito_id("session_123"));
+        assert!(validate_timeout(DEFAULT_TIMEOUT_SECS).is_ok());
+        assert!(validate_timeout(10).is_err());
+    }
+
+    #[test]
+    fn test_activity_extends_expiry() {
+        let mut session = IncognitoSession::new(Some("shizuku".to_string()), 60, 1_000);
+        assert!(!session.is_expired(1_059));
+        assert!(session.is_expired(1_060));
+
+        session.push("user", "hello", 1_050);
+        assert_eq!(session.messages.len(), 1);
+        assert_eq!(session.expires_at(), 1_110);
+        assert!(!session.is_expired(1_100));
+    }
+}
@@ -0,0 +1,40 @@
+//! # 系统密钥链
+//!
+//! 密码、访问令牌与加密密钥都保存在系统密钥链的 `zishu-sensei` 服务下，不写入配置文件。
+//! 空字符串视为没有密钥：保存空值会删除条目，读取到空值返回 `None`。
+
+/// 密钥链中的服务名
+const SERVICE: &str = "zishu-sensei";
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("创建keyring条目失败: {}", e))
+}
+
+/// 读取密钥；条目不存在时返回 `Ok(None)`，密钥链不可用时返回错误
+pub fn try_load(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret).filter(|secret| !secret.is_empty())),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("读取密钥失败: {}", e)),
+    }
+}
+
+/// 读取密钥；密钥链不可用时同样返回 `None`
+pub fn load(name: &str) -> Option<String> {
+    try_load(name).unwrap_or_else(|e| {
+        tracing::warn!("{}: {}", name, e);
+        None
+    })
+}
+
+/// 保存密钥；`None` 或空字符串删除条目
+pub fn store(name: &str, secret: Option<&str>) -> Result<(), String> {
+    let entry = entry(name)?;
+    match secret.filter(|secret| !secret.is_empty()) {
+        Some(secret) => entry.set_password(secret).map_err(|e| format!("保存密钥失败: {}", e)),
+        None => match entry.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("删除密钥失败: {}", e)),
+        },
+    }
+}
@@ -0,0 +1,294 @@
+//! # 展示模式（外接显示器）
+//!
+//! 在选定的副显示器上打开全屏无边框窗口，显示角色和轮播的信息卡片（时钟、天气、统计、系统状态）：
+//!
+//! - 按计划时段自动开启与关闭（支持跨午夜，如 22:00–06:00）
+//! - 防烧屏：定期把画面内容在小范围内平移
+//! - 指定的显示器不存在时使用第一个副显示器
+//!
+//! 窗口管理与数据汇总在 `commands::kiosk`。
+
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+fn default_rotate_secs() -> u64 {
+    15
+}
+
+fn default_jitter_px() -> i32 {
+    16
+}
+
+fn default_jitter_interval_mins() -> u64 {
+    5
+}
+
+fn default_cards() -> Vec<DashboardCard> {
+    vec![DashboardCard::Clock, DashboardCard::Weather, DashboardCard::Stats, DashboardCard::System]
+}
+
+/// 信息卡片
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DashboardCard {
+    Clock,
+    Weather,
+    Stats,
+    System,
+}
+
+/// 开启时段（本地时间，`HH:MM`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KioskSchedule {
+    pub on_time: String,
+    pub off_time: String,
+    /// 生效的星期（1 = 周一 … 7 = 周日），为空表示每天
+    #[serde(default)]
+    pub weekdays: Vec<u32>,
+}
+
+impl KioskSchedule {
+    fn parse(time: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("时间格式无效: {}（应为 HH:MM）", time))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let (on, off) = (Self::parse(&self.on_time)?, Self::parse(&self.off_time)?);
+        if on == off {
+            return Err("开启与关闭时间不能相同".to_string());
+        }
+        if self.weekdays.iter().any(|day| !(1..=7).contains(day)) {
+            return Err("星期必须在 1 到 7 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 给定本地时间是否处于开启时段；跨午夜的时段按开启当天的星期判断
+    pub fn is_active(&self, weekday: Weekday, time: NaiveTime) -> bool {
+        let (Ok(on), Ok(off)) = (Self::parse(&self.on_time), Self::parse(&self.off_time)) else {
+            return false;
+        };
+        let day_allowed = |day: Weekday| self.weekdays.is_empty() || self.weekdays.contains(&day.number_from_monday());
+        if on < off {
+            day_allowed(weekday) && time >= on && time < off
+        } else if time >= on {
+            day_allowed(weekday)
+        } else {
+            time < off && day_allowed(weekday.pred())
+        }
+    }
+}
+
+/// 天气查询位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherLocation {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// 展示模式设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KioskSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 显示器名称；为空或不存在时使用第一个副显示器
+    #[serde(default)]
+    pub monitor: Option<String>,
+    #[serde(default = "default_cards")]
+    pub cards: Vec<DashboardCard>,
+    /// 卡片轮播间隔（秒）
+    #[serde(default = "default_rotate_secs")]
+    pub rotate_secs: u64,
+    /// 防烧屏平移的最大偏移（像素，0 表示关闭）
+    #[serde(default = "default_jitter_px")]
+    pub jitter_px: i32,
+    /// 防烧屏平移的间隔（分钟）
+    #[serde(default = "default_jitter_interval_mins")]
+    pub jitter_interval_mins: u64,
+    /// 开启时段，为空表示启用后一直开启
+    #[serde(default)]
+    pub schedule: Option<KioskSchedule>,
+    #[serde(default)]
+    pub weather_location: Option<WeatherLocation>,
+}
+
+impl Default for KioskSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            monitor: None,
+            cards: default_cards(),
+            rotate_secs: default_rotate_secs(),
+            jitter_px: default_jitter_px(),
+            jitter_interval_mins: default_jitter_interval_mins(),
+            schedule: None,
+            weather_location: None,
+        }
+    }
+}
+
+impl KioskSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cards.is_empty() {
+            return Err("至少需要一张信息卡片".to_string());
+        }
+        if !(5..=3600).contains(&self.rotate_secs) {
+            return Err("轮播间隔必须在 5 秒到 1 小时之间".to_string());
+        }
+        if !(0..=100).contains(&self.jitter_px) {
+            return Err("防烧屏偏移必须在 0 到 100 像素之间".to_string());
+        }
+        if !(1..=120).contains(&self.jitter_interval_mins) {
+            return Err("防烧屏间隔必须在 1 到 120 分钟之间".to_string());
+        }
+        if let Some(schedule) = &self.schedule {
+            schedule.validate()?;
+        }
+        if let Some(location) = &self.weather_location {
+            if !(-90.0..=90.0).contains(&location.latitude) || !(-180.0..=180.0).contains(&location.longitude) {
+                return Err("天气位置的经纬度无效".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 按设置与计划时段，当前是否应该显示
+    pub fn should_show(&self, weekday: Weekday, time: NaiveTime) -> bool {
+        self.enabled
+            && match &self.schedule {
+                Some(schedule) => schedule.is_active(weekday, time),
+                None => true,
+            }
+    }
+
+    /// 第 `tick` 次轮播显示的卡片（未设置天气位置时跳过天气卡片）
+    pub fn card_at(&self, tick: u64) -> DashboardCard {
+        let cards: Vec<DashboardCard> = self
+            .cards
+            .iter()
+            .copied()
+            .filter(|card| *card != DashboardCard::Weather || self.weather_location.is_some())
+            .collect();
+        match cards.len() {
+            0 => DashboardCard::Clock,
+            len => cards[(tick % len as u64) as usize],
+        }
+    }
+}
+
+/// 显示器位置与尺寸（物理像素）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorRect {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub primary: bool,
+}
+
+/// 选择展示用的显示器：优先指定名称，其次第一个副显示器；只有主显示器时返回 `None`
+pub fn choose_monitor<'a>(monitors: &'a [MonitorRect], preferred: Option<&str>) -> Option<&'a MonitorRect> {
+    preferred
+        .and_then(|name| monitors.iter().find(|m| m.name.as_deref() == Some(name)))
+        .or_else(|| monitors.iter().find(|m| !m.primary))
+}
+
+/// 防烧屏偏移：沿 8 个方向依次平移，偏移量在 1/2 到 1 倍之间交替，第 0 步不偏移
+pub fn jitter_offset(step: u64, max_px: i32) -> (i32, i32) {
+    const DIRECTIONS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+    if step == 0 || max_px <= 0 {
+        return (0, 0);
+    }
+    let (dx, dy) = DIRECTIONS[((step - 1) % 8) as usize];
+    let scale = if (step - 1) / 8 % 2 == 0 { max_px } else { max_px / 2 };
+    (dx * scale, dy * scale)
+}
+
+/// WMO 天气代码的简短描述（Open-Meteo 使用该代码）
+pub fn weather_description(code: u32) -> &'static str {
+    match code {
+        0 => "晴",
+        1..=3 => "多云",
+        45 | 48 => "雾",
+        51..=57 => "毛毛雨",
+        61..=67 => "雨",
+        71..=77 => "雪",
+        80..=82 => "阵雨",
+        85 | 86 => "阵雪",
+        95..=99 => "雷暴",
+        _ => "未知",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_handles_overnight_and_weekdays() {
+        let daytime = KioskSchedule {
+            on_time: "08:00".to_string(),
+            off_time: "18:30".to_string(),
+            weekdays: vec![1, 2, 3, 4, 5],
+        };
+        assert!(daytime.is_active(Weekday::Mon, at(8, 0)));
+        assert!(!daytime.is_active(Weekday::Mon, at(18, 30)));
+        assert!(!daytime.is_active(Weekday::Sat, at(12, 0)));
+
+        let overnight = KioskSchedule {
+            on_time: "22:00".to_string(),
+            off_time: "06:00".to_string(),
+            weekdays: vec![5],
+        };
+        assert!(overnight.is_active(Weekday::Fri, at(23, 0)));
+        // 周五晚开启的时段延续到周六早上
+        assert!(overnight.is_active(Weekday::Sat, at(5, 59)));
+        assert!(!overnight.is_active(Weekday::Fri, at(5, 0)));
+        assert!(!overnight.is_active(Weekday::Sat, at(22, 30)));
+
+        let settings = KioskSettings {
+            enabled: true,
+            schedule: Some(overnight),
+            ..KioskSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+        assert!(!settings.should_show(Weekday::Fri, at(12, 0)));
+        assert!(KioskSchedule { on_time: "25:00".to_string(), ..daytime }.validate().is_err());
+    }
+
+    #[test]
+    fn test_monitor_choice_cards_and_jitter() {
+        let monitor = |name: &str, primary| MonitorRect {
+            name: Some(name.to_string()),
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            primary,
+        };
+        let monitors = vec![monitor("DP-1", true), monitor("HDMI-1", false), monitor("HDMI-2", false)];
+        assert_eq!(choose_monitor(&monitors, Some("HDMI-2")).unwrap().name.as_deref(), Some("HDMI-2"));
+        assert_eq!(choose_monitor(&monitors, Some("gone")).unwrap().name.as_deref(), Some("HDMI-1"));
+        assert!(choose_monitor(&monitors[..1], None).is_none());
+
+        let settings = KioskSettings::default();
+        // 没有天气位置时跳过天气卡片
+        let cards: Vec<DashboardCard> = (0..3).map(|tick| settings.card_at(tick)).collect();
+        assert_eq!(cards, [DashboardCard::Clock, DashboardCard::Stats, DashboardCard::System]);
+
+        assert_eq!(weather_description(2), "多云");
+        assert_eq!(jitter_offset(0, 16), (0, 0));
+        assert_eq!(jitter_offset(1, 16), (16, 0));
+        assert_eq!(jitter_offset(10, 16), (8, 8));
+        assert!((0..100).all(|step| {
+            let (x, y) = jitter_offset(step, 16);
+            x.abs() <= 16 && y.abs() <= 16
+        }));
+    }
+}
@@ -0,0 +1,163 @@
+//! 文档知识库
+//!
+//! 导入流程中与向量库无关的部分：收集文件夹中支持的文档（txt / md / pdf），
+//! 提取文本，按段落与句子边界切分为带重叠的文本块。嵌入与写入 Qdrant 见
+//! `commands::knowledge_base`。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 每个文本块的目标长度（字符）
+pub const DEFAULT_CHUNK_SIZE: usize = 800;
+/// 相邻文本块的重叠长度（字符）
+pub const DEFAULT_CHUNK_OVERLAP: usize = 100;
+/// 单个文件的大小上限
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// 可以导入的文档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Text,
+    Markdown,
+    Pdf,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "txt" => Some(Self::Text),
+            "md" | "markdown" => Some(Self::Markdown),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Markdown => "md",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// 展开文件与文件夹，返回其中支持格式的文件（已排序、去重，跳过隐藏文件）
+pub fn collect_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| {
+            WalkDir::new(path)
+                .follow_links(false)
+                .into_iter()
+                .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'))
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+        })
+        .filter(|path| DocumentFormat::from_path(path).is_some())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// 提取文档的纯文本（阻塞操作）
+pub fn extract_text(path: &Path, format: DocumentFormat) -> Result<String, String> {
+    let size = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(format!("文件超过 {} MB", MAX_FILE_SIZE / 1024 / 1024));
+    }
+    match format {
+        DocumentFormat::Text | DocumentFormat::Markdown => {
+            let bytes = std::fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        DocumentFormat::Pdf => pdf_extract::extract_text(path).map_err(|e| format!("解析 PDF 失败: {}", e)),
+    }
+}
+
+/// 文本内容的哈希，用于跳过未变化的文件
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+fn is_break(c: char) -> bool {
+    matches!(c, '\n' | '。' | '！' | '？' | '；' | '.' | '!' | '?' | ';')
+}
+
+/// 把文本切分为不超过 `chunk_size` 个字符的块，相邻块重叠 `overlap` 个字符
+///
+/// 块尾优先落在后半段最后一个换行或句末标点之后；空白块被丢弃。
+pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
+    let chunk_size = chunk_size.max(1);
+    let overlap = overlap.min(chunk_size / 2);
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + chunk_size).min(chars.len());
+        if end < chars.len() {
+            let earliest = start + chunk_size / 2;
+            if let Some(offset) = chars[earliest..end].iter().rposition(|&c| is_break(c)) {
+                end = earliest + offset + 1;
+            }
+        }
+
+        let chunk: String = chars[start..end].iter().collect();
+        let chunk = chunk.trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        if end == chars.len() {
+            break;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text() {
+        assert!(chunk_text("", 10, 2).is_empty());
+        assert!(chunk_text(" \n\n ", 10, 2).is_empty());
+        assert_eq!(chunk_text("短文本", 10, 2), vec!["短文本"]);
+
+        // 在句末断开，下一块带上重叠部分
+        let chunks = chunk_text("第一句话。第二句话。第三句话。", 12, 2);
+        assert_eq!(chunks[0], "第一句话。第二句话。");
+        assert_eq!(chunks[1], "话。第三句话。");
+
+        // 没有断点时按长度硬切
+        let chunks = chunk_text(&"字".repeat(25), 10, 3);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 10));
+        assert_eq!(chunks.len(), 4);
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("notes");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        for name in ["a.txt", "notes/b.MD", "notes/c.pdf", "d.docx", ".git/e.txt"] {
+            std::fs::write(dir.path().join(name), "x").unwrap();
+        }
+
+        let files = collect_files(&[dir.path().to_path_buf(), dir.path().join("a.txt")]);
+        let names: Vec<_> = files
+            .iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert_eq!(names, vec!["a.txt", "notes/b.MD", "notes/c.pdf"]);
+        assert_eq!(DocumentFormat::from_path(&files[1]), Some(DocumentFormat::Markdown));
+    }
+}
@@ -0,0 +1,123 @@
+//! 轻量级语言检测
+//!
+//! 按文字系统统计字符，判断用户消息的语言（zh / ja / ko / en）。
+//! 只覆盖应用支持的语言，不依赖外部模型；文本过短或无法判断时返回 `None`。
+
+use serde::{Deserialize, Serialize};
+
+/// 参与判断的最少字母字符数
+const MIN_LETTERS: usize = 2;
+
+/// 检测结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Detection {
+    /// 语言代码
+    pub language: String,
+    /// 置信度（0.0 - 1.0）
+    pub confidence: f32,
+}
+
+#[derive(Default)]
+struct ScriptCounts {
+    han: usize,
+    kana: usize,
+    hangul: usize,
+    latin: usize,
+}
+
+impl ScriptCounts {
+    fn of(text: &str) -> Self {
+        let mut counts = Self::default();
+        for c in text.chars() {
+            match c as u32 {
+                0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => counts.kana += 1,
+                0xAC00..=0xD7AF | 0x1100..=0x11FF | 0x3130..=0x318F => counts.hangul += 1,
+                0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF => counts.han += 1,
+                _ if c.is_ascii_alphabetic() => counts.latin += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+
+    fn total(&self) -> usize {
+        self.han + self.kana + self.hangul + self.latin
+    }
+}
+
+/// 检测文本语言
+pub fn detect(text: &str) -> Option<Detection> {
+    let counts = ScriptCounts::of(text);
+    let total = counts.total();
+    if total < MIN_LETTERS {
+        return None;
+    }
+
+    // 假名是日语的强信号：日语中汉字与假名混用，只要假名占一定比例即判为日语
+    let cjk = counts.han + counts.kana;
+    let (language, score) = if counts.kana > 0 && counts.kana * 5 >= cjk {
+        ("ja", cjk)
+    } else if counts.hangul > 0 && counts.hangul >= counts.han {
+        ("ko", counts.hangul + counts.han)
+    } else if counts.han > 0 && counts.han * 4 >= counts.latin {
+        // 中文里夹杂英文单词很常见，汉字占比达到 1/5 即判为中文
+        ("zh", counts.han)
+    } else if counts.latin > 0 {
+        ("en", counts.latin)
+    } else {
+        return None;
+    };
+
+    Some(Detection {
+        language: language.to_string(),
+        confidence: (score as f32 / total as f32).min(1.0),
+    })
+}
+
+/// 语言对应的语音区域代码（用于选择 TTS 声音）
+pub fn voice_locale(language: &str) -> &'static str {
+    match language {
+        "en" => "en-US",
+        "ja" => "ja-JP",
+        "ko" => "ko-KR",
+        _ => "zh-CN",
+    }
+}
+
+/// 要求模型使用指定语言回复的系统指令
+pub fn reply_instruction(language: &str) -> &'static str {
+    match language {
+        "en" => "Please reply in English.",
+        "ja" => "日本語で返答してください。",
+        "ko" => "한국어로 답변해 주세요.",
+        _ => "请使用中文回复。",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(text: &str) -> Option<String> {
+        detect(text).map(|d| d.language)
+    }
+
+    #[test]
+    fn test_detect_supported_languages() {
+        assert_eq!(language("今天天气怎么样？").as_deref(), Some("zh"));
+        assert_eq!(language("今日はいい天気ですね").as_deref(), Some("ja"));
+        assert_eq!(language("오늘 날씨 어때요?").as_deref(), Some("ko"));
+        assert_eq!(language("How is the weather today?").as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_chinese_with_english_terms() {
+        assert_eq!(language("帮我看看这个 Rust borrow checker 报错").as_deref(), Some("zh"));
+    }
+
+    #[test]
+    fn test_too_short_or_symbols_only() {
+        assert_eq!(language("?"), None);
+        assert_eq!(language("123 !!!"), None);
+    }
+}
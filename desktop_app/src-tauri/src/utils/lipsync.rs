@@ -0,0 +1,289 @@
+//! # 音频口型同步
+//!
+//! 分析任意音频文件（目前支持 WAV）的音量包络，生成 Live2D 口型参数序列：
+//!
+//! - `ParamMouthOpenY`：按帧计算 RMS，经过增益、噪声门限和平滑后映射到 0–1
+//! - `ParamMouthForm`：按过零率粗略区分元音（高过零率偏「い/え」，低过零率偏「お/う」），范围 -1–1
+//!
+//! 播放与参数推送在 `commands::lipsync`，播放位置加上 `offset_ms` 后查找对应帧，
+//! 用于校准不同输出设备的延迟。
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+lazy_static::lazy_static! {
+    static ref LIPSYNC_SETTINGS: RwLock<LipSyncSettings> = RwLock::new(LipSyncSettings::default());
+}
+
+/// 过零率低于此值时口型为 -1（圆唇）
+const ZCR_ROUND: f32 = 0.02;
+/// 过零率高于此值时口型为 1（展唇）
+const ZCR_WIDE: f32 = 0.12;
+
+/// 口型同步设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LipSyncSettings {
+    /// 同步偏移（毫秒）；正值让口型滞后于声音，负值让口型提前
+    pub offset_ms: i64,
+    /// 音量增益
+    pub gain: f32,
+    /// 噪声门限（0–1），低于此音量时闭嘴
+    pub noise_gate: f32,
+    /// 平滑系数（0–1），越大口型变化越慢
+    pub smoothing: f32,
+    /// 参数帧率
+    pub frame_rate: u32,
+    /// 是否估计 `ParamMouthForm`
+    pub estimate_form: bool,
+}
+
+impl Default for LipSyncSettings {
+    fn default() -> Self {
+        Self {
+            offset_ms: 0,
+            gain: 4.0,
+            noise_gate: 0.05,
+            smoothing: 0.5,
+            frame_rate: 60,
+            estimate_form: true,
+        }
+    }
+}
+
+impl LipSyncSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.offset_ms.abs() > 2000 {
+            return Err("同步偏移必须在 -2000 到 2000 毫秒之间".to_string());
+        }
+        if !(self.gain > 0.0 && self.gain <= 50.0) {
+            return Err("音量增益必须在 0 到 50 之间".to_string());
+        }
+        if !(0.0..1.0).contains(&self.noise_gate) {
+            return Err("噪声门限必须在 0 到 1 之间".to_string());
+        }
+        if !(0.0..1.0).contains(&self.smoothing) {
+            return Err("平滑系数必须在 0 到 1 之间".to_string());
+        }
+        if !(10..=120).contains(&self.frame_rate) {
+            return Err("参数帧率必须在 10 到 120 之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn current_settings() -> LipSyncSettings {
+    LIPSYNC_SETTINGS.read().clone()
+}
+
+pub fn set_settings(settings: LipSyncSettings) {
+    *LIPSYNC_SETTINGS.write() = settings;
+}
+
+/// 解码后的音频（交错采样，-1–1）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl DecodedAudio {
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        (self.frame_count() as u64 * 1000) / self.sample_rate.max(1) as u64
+    }
+
+    /// 混合为单声道
+    pub fn to_mono(&self) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        self.samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect()
+    }
+
+    /// 线性插值重采样到目标采样率和声道数（用于匹配输出设备）
+    pub fn resample(&self, sample_rate: u32, channels: u16) -> DecodedAudio {
+        let mono = self.to_mono();
+        let source_channels = self.channels.max(1) as usize;
+        let target_channels = channels.max(1) as usize;
+        let ratio = self.sample_rate as f64 / sample_rate.max(1) as f64;
+        let target_frames = (self.frame_count() as f64 / ratio).floor() as usize;
+
+        let sample_at = |frame: usize, channel: usize| -> f32 {
+            if source_channels == target_channels {
+                self.samples[frame * source_channels + channel]
+            } else {
+                mono[frame]
+            }
+        };
+
+        let mut samples = Vec::with_capacity(target_frames * target_channels);
+        for i in 0..target_frames {
+            let position = i as f64 * ratio;
+            let index = position.floor() as usize;
+            let fraction = (position - index as f64) as f32;
+            let next = (index + 1).min(self.frame_count().saturating_sub(1));
+            for channel in 0..target_channels {
+                let a = sample_at(index, channel);
+                let b = sample_at(next, channel);
+                samples.push(a + (b - a) * fraction);
+            }
+        }
+        DecodedAudio {
+            sample_rate,
+            channels: channels.max(1),
+            samples,
+        }
+    }
+}
+
+/// 解码 WAV 文件
+pub fn decode_wav(path: &Path) -> Result<DecodedAudio, String> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| format!("Failed to open audio file: {}", e))?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to decode audio file: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|s| s as f32 / scale))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to decode audio file: {}", e))?
+        }
+    };
+    Ok(DecodedAudio {
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        samples,
+    })
+}
+
+/// 一帧口型参数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MouthFrame {
+    /// 该帧在音频中的起始时间（毫秒）
+    pub time_ms: u64,
+    /// `ParamMouthOpenY`
+    pub open: f32,
+    /// `ParamMouthForm`
+    pub form: f32,
+}
+
+/// 分析单声道采样，生成口型参数序列
+pub fn analyze(mono: &[f32], sample_rate: u32, settings: &LipSyncSettings) -> Vec<MouthFrame> {
+    let frame_len = (sample_rate / settings.frame_rate.max(1)).max(1) as usize;
+    let mut frames = Vec::with_capacity(mono.len() / frame_len + 1);
+    let (mut open, mut form) = (0.0f32, 0.0f32);
+
+    for (i, chunk) in mono.chunks(frame_len).enumerate() {
+        let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+        let level = (rms * settings.gain).min(1.0);
+        let target_open = if level <= settings.noise_gate {
+            0.0
+        } else {
+            (level - settings.noise_gate) / (1.0 - settings.noise_gate)
+        };
+
+        let target_form = if settings.estimate_form && target_open > 0.0 {
+            let crossings = chunk
+                .windows(2)
+                .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+                .count();
+            let zcr = crossings as f32 / chunk.len() as f32;
+            ((zcr - ZCR_ROUND) / (ZCR_WIDE - ZCR_ROUND) * 2.0 - 1.0).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        open += (target_open - open) * (1.0 - settings.smoothing);
+        form += (target_form - form) * (1.0 - settings.smoothing);
+        frames.push(MouthFrame {
+            time_ms: (i * frame_len) as u64 * 1000 / sample_rate.max(1) as u64,
+            open,
+            form,
+        });
+    }
+    frames
+}
+
+/// 按播放位置（已加上同步偏移）查找当前帧；偏移后位于音频开始前时返回 `None`
+pub fn frame_at(frames: &[MouthFrame], position_ms: u64, offset_ms: i64) -> Option<&MouthFrame> {
+    let time = position_ms as i64 - offset_ms;
+    if time < 0 {
+        return None;
+    }
+    let index = frames.partition_point(|frame| frame.time_ms as i64 <= time);
+    index.checked_sub(1).and_then(|i| frames.get(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32, amplitude: f32, sample_rate: u32, ms: u32) -> Vec<f32> {
+        (0..sample_rate * ms / 1000)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze_opens_mouth_on_voice_and_closes_on_silence() {
+        let settings = LipSyncSettings {
+            smoothing: 0.0,
+            ..LipSyncSettings::default()
+        };
+        let mut samples = vec![0.0; 16000 / 10];
+        samples.extend(tone(220.0, 0.2, 16000, 100));
+        samples.extend(tone(1600.0, 0.2, 16000, 100));
+
+        let frames = analyze(&samples, 16000, &settings);
+        assert_eq!(frames.len(), 19);
+        assert_eq!(frames[0].open, 0.0);
+        assert!(frames[8].open > 0.5);
+        // 低频偏圆唇，高频偏展唇
+        assert!(frames[8].form < 0.0);
+        assert!(frames[15].form > 0.0);
+        assert!(LipSyncSettings { frame_rate: 1000, ..settings }.validate().is_err());
+    }
+
+    #[test]
+    fn test_frame_at_applies_offset() {
+        let frames: Vec<MouthFrame> = (0..10)
+            .map(|i| MouthFrame {
+                time_ms: i * 10,
+                open: i as f32 / 10.0,
+                form: 0.0,
+            })
+            .collect();
+        assert_eq!(frame_at(&frames, 35, 0).unwrap().time_ms, 30);
+        assert_eq!(frame_at(&frames, 35, 20).unwrap().time_ms, 10);
+        assert_eq!(frame_at(&frames, 35, -20).unwrap().time_ms, 50);
+        assert!(frame_at(&frames, 10, 20).is_none());
+        assert_eq!(frame_at(&frames, 500, 0).unwrap().time_ms, 90);
+    }
+
+    #[test]
+    fn test_resample_matches_device_format() {
+        let audio = DecodedAudio {
+            sample_rate: 16000,
+            channels: 1,
+            samples: (0..1600).map(|i| i as f32 / 1600.0).collect(),
+        };
+        let resampled = audio.resample(48000, 2);
+        assert_eq!((resampled.sample_rate, resampled.channels), (48000, 2));
+        assert_eq!(resampled.frame_count(), 4800);
+        assert_eq!(resampled.duration_ms(), audio.duration_ms());
+        assert_eq!(resampled.samples[2], resampled.samples[3]);
+    }
+}
@@ -0,0 +1,197 @@
+//! # Live2D 模型热重载
+//!
+//! 监控 `live2d_models` 目录的文件变化，按模型目录归并成一批变化，之后重新扫描这些模型并
+//! 更新角色注册表，修改贴图或动作后无需重启应用。
+//!
+//! - `live2d_models/<模型 ID>/...` 下的变化归到对应模型
+//! - `live2d_models/models.json` 的变化表示清单变化，需要重新导入全部模型
+//! - 编辑器的临时文件（`.swp`、`~` 结尾、隐藏文件等）不触发重载
+//!
+//! 文件监控与注册表更新在 `commands::live2d_assets`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path};
+
+/// 模型目录名
+pub const MODELS_DIR: &str = "live2d_models";
+/// 模型清单文件名
+pub const MANIFEST_FILE: &str = "models.json";
+
+/// 一批模型文件变化
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelChanges {
+    /// 有文件变化的模型 ID
+    pub model_ids: BTreeSet<String>,
+    /// 清单文件是否变化
+    pub manifest_changed: bool,
+}
+
+impl ModelChanges {
+    pub fn is_empty(&self) -> bool {
+        self.model_ids.is_empty() && !self.manifest_changed
+    }
+
+    /// 记录一个变化的路径；与模型无关的路径返回 false
+    pub fn record(&mut self, models_root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(models_root) else {
+            return false;
+        };
+        let parts: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .collect();
+        match parts.as_slice() {
+            [] => false,
+            [.., name] if is_temporary_file(name) => false,
+            [name] if *name == MANIFEST_FILE => {
+                self.manifest_changed = true;
+                true
+            }
+            // 根目录下的其他文件与模型无关，没有扩展名的视为模型目录本身
+            [name] if name.contains('.') => false,
+            [model_id, ..] => {
+                self.model_ids.insert(model_id.to_string());
+                true
+            }
+        }
+    }
+}
+
+pub(crate) fn is_temporary_file(name: &str) -> bool {
+    name.starts_with('.')
+        || name.ends_with('~')
+        || [".swp", ".swx", ".tmp", ".part", ".crdownload"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+/// 从模型目录扫描出的信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScannedModel {
+    pub id: String,
+    /// 前端使用的 model3.json 路径（`/live2d_models/<id>/<name>.model3.json`）
+    pub path: String,
+    pub motions: Vec<String>,
+    pub expressions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Model3 {
+    #[serde(rename = "FileReferences", default)]
+    file_references: Option<Model3References>,
+}
+
+#[derive(Deserialize)]
+struct Model3References {
+    #[serde(rename = "Motions", default)]
+    motions: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(rename = "Expressions", default)]
+    expressions: Option<Vec<Model3Expression>>,
+}
+
+#[derive(Deserialize)]
+struct Model3Expression {
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+/// 扫描模型目录；目录不存在或没有 `*.model3.json` 时返回 `None`
+pub fn scan_model(models_root: &Path, model_id: &str) -> Result<Option<ScannedModel>, String> {
+    let model_dir = models_root.join(model_id);
+    if !model_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut model_files: Vec<String> = fs::read_dir(&model_dir)
+        .map_err(|e| format!("Failed to read model directory {}: {}", model_dir.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".model3.json"))
+        .collect();
+    model_files.sort();
+    // 优先使用与目录同名的模型文件
+    let preferred = format!("{}.model3.json", model_id);
+    let Some(model_file) = model_files
+        .iter()
+        .find(|name| **name == preferred)
+        .or_else(|| model_files.first())
+        .cloned()
+    else {
+        return Ok(None);
+    };
+
+    let content = fs::read_to_string(model_dir.join(&model_file))
+        .map_err(|e| format!("Failed to read {}: {}", model_file, e))?;
+    let model3: Model3 =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", model_file, e))?;
+    let references = model3.file_references;
+
+    let motions = references
+        .as_ref()
+        .and_then(|refs| refs.motions.as_ref())
+        .map(|groups| groups.keys().cloned().collect())
+        .unwrap_or_default();
+    let expressions = references
+        .and_then(|refs| refs.expressions)
+        .map(|expressions| expressions.into_iter().map(|e| e.name).collect())
+        .unwrap_or_default();
+
+    Ok(Some(ScannedModel {
+        id: model_id.to_string(),
+        path: format!("/{}/{}/{}", MODELS_DIR, model_id, model_file),
+        motions,
+        expressions,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_record_groups_paths_by_model() {
+        let root = PathBuf::from("/cache/live2d/live2d_models");
+        let mut changes = ModelChanges::default();
+        assert!(changes.record(&root, &root.join("hiyori/textures/texture_00.png")));
+        assert!(changes.record(&root, &root.join("hiyori/motions/idle.motion3.json")));
+        assert!(changes.record(&root, &root.join("shizuku")));
+        assert!(!changes.record(&root, &root.join("hiyori/.hiyori.model3.json.swp")));
+        assert!(!changes.record(&root, &root.join("hiyori/hiyori.model3.json~")));
+        assert!(!changes.record(&root, &PathBuf::from("/elsewhere/models.json")));
+        assert!(!changes.manifest_changed);
+        assert!(changes.record(&root, &root.join(MANIFEST_FILE)));
+
+        assert!(changes.manifest_changed);
+        assert_eq!(changes.model_ids.iter().collect::<Vec<_>>(), ["hiyori", "shizuku"]);
+    }
+
+    #[test]
+    fn test_scan_model_reads_motions_and_expressions() {
+        let root = std::env::temp_dir().join(format!("zishu_live2d_watch_{}", std::process::id()));
+        let model_dir = root.join("hiyori");
+        fs::create_dir_all(&model_dir).unwrap();
+        fs::write(
+            model_dir.join("hiyori.model3.json"),
+            r#"{"FileReferences": {
+                "Moc": "hiyori.moc3",
+                "Motions": {"Idle": [{"File": "idle.motion3.json"}], "TapBody": []},
+                "Expressions": [{"Name": "smile", "File": "smile.exp3.json"}]
+            }}"#,
+        )
+        .unwrap();
+
+        let scanned = scan_model(&root, "hiyori").unwrap().unwrap();
+        assert_eq!(scanned.path, "/live2d_models/hiyori/hiyori.model3.json");
+        assert_eq!(scanned.motions, ["Idle", "TapBody"]);
+        assert_eq!(scanned.expressions, ["smile"]);
+        assert_eq!(scan_model(&root, "missing").unwrap(), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,383 @@
+//! # LLM 请求中间件
+//!
+//! 发往模型的聊天请求依次经过用户配置顺序的中间件，每一步可单独开关：
+//!
+//! - `prompt_layering`：本地模型叠加当前提示词与角色设定
+//! - `rag_injection`：检索相关的历史对话片段作为参考上下文
+//! - `long_term_memory`：注入与当前消息相关的过往会话摘要；会话足够长时自动生成摘要
+//! - `translation`：语言来自会话覆盖或自动检测时，要求模型用该语言回复
+//! - `safety_filter`：拦截包含屏蔽词的消息，发送前与回复中的密钥 / Token 脱敏
+//! - `token_budget`：超出提示词预算时从最早的上下文开始裁剪
+//! - `logging`：记录请求与回复的概要
+//!
+//! 每一步的耗时与失败次数记录在指标中。中间件实现在 `commands::llm_middleware`。
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::bridge::{ChatMessage, MessageRole};
+use crate::utils::cost_preview::estimate_tokens;
+
+/// 每条消息的固定开销（与费用预估一致）
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// 中间件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MiddlewareKind {
+    PromptLayering,
+    RagInjection,
+    LongTermMemory,
+    Translation,
+    SafetyFilter,
+    TokenBudget,
+    Logging,
+}
+
+impl MiddlewareKind {
+    /// 默认顺序
+    pub const ALL: [MiddlewareKind; 7] = [
+        MiddlewareKind::PromptLayering,
+        MiddlewareKind::RagInjection,
+        MiddlewareKind::LongTermMemory,
+        MiddlewareKind::Translation,
+        MiddlewareKind::SafetyFilter,
+        MiddlewareKind::TokenBudget,
+        MiddlewareKind::Logging,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MiddlewareKind::PromptLayering => "prompt_layering",
+            MiddlewareKind::RagInjection => "rag_injection",
+            MiddlewareKind::LongTermMemory => "long_term_memory",
+            MiddlewareKind::Translation => "translation",
+            MiddlewareKind::SafetyFilter => "safety_filter",
+            MiddlewareKind::TokenBudget => "token_budget",
+            MiddlewareKind::Logging => "logging",
+        }
+    }
+
+    /// 默认是否启用（RAG 与安全过滤需要用户开启）
+    fn enabled_by_default(self) -> bool {
+        !matches!(self, MiddlewareKind::RagInjection | MiddlewareKind::SafetyFilter)
+    }
+}
+
+/// 管道中的一步
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiddlewareStep {
+    pub kind: MiddlewareKind,
+    pub enabled: bool,
+}
+
+fn default_steps() -> Vec<MiddlewareStep> {
+    MiddlewareKind::ALL
+        .iter()
+        .map(|&kind| MiddlewareStep { kind, enabled: kind.enabled_by_default() })
+        .collect()
+}
+
+/// RAG 注入选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RagOptions {
+    /// 最多注入的片段数
+    pub limit: usize,
+    /// 融合得分低于该值的片段不注入
+    pub min_score: f32,
+    /// 只检索当前会话
+    pub same_session_only: bool,
+}
+
+impl Default for RagOptions {
+    fn default() -> Self {
+        Self {
+            limit: 3,
+            min_score: 0.0,
+            same_session_only: false,
+        }
+    }
+}
+
+/// 长期记忆选项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryOptions {
+    /// 会话每新增多少条消息生成（或更新）一次摘要
+    pub summarize_every: usize,
+    /// 最多注入的过往摘要数
+    pub limit: usize,
+    /// 相似度低于该值的摘要不注入
+    pub min_score: f32,
+}
+
+impl Default for MemoryOptions {
+    fn default() -> Self {
+        Self {
+            summarize_every: 20,
+            limit: 3,
+            min_score: 0.5,
+        }
+    }
+}
+
+impl MemoryOptions {
+    /// 会话是否需要（重新）生成摘要：距上次摘要（或会话开始）已新增足够多的消息
+    pub fn summary_due(&self, message_count: usize, summarized_count: Option<usize>) -> bool {
+        message_count >= summarized_count.unwrap_or(0) + self.summarize_every
+    }
+}
+
+/// 安全过滤选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyOptions {
+    /// 屏蔽词（不区分大小写），用户消息包含时拒绝发送
+    pub blocked_terms: Vec<String>,
+    /// 对 API 密钥与 Token 脱敏
+    pub mask_secrets: bool,
+}
+
+impl Default for SafetyOptions {
+    fn default() -> Self {
+        Self {
+            blocked_terms: Vec::new(),
+            mask_secrets: true,
+        }
+    }
+}
+
+/// 提示词预算选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenBudgetOptions {
+    /// 提示词 token 上限（估算）
+    pub max_prompt_tokens: u32,
+}
+
+impl Default for TokenBudgetOptions {
+    fn default() -> Self {
+        Self { max_prompt_tokens: 8_000 }
+    }
+}
+
+/// 中间件管道设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MiddlewareSettings {
+    #[serde(default = "default_steps")]
+    pub steps: Vec<MiddlewareStep>,
+    #[serde(default)]
+    pub rag: RagOptions,
+    #[serde(default)]
+    pub memory: MemoryOptions,
+    #[serde(default)]
+    pub safety: SafetyOptions,
+    #[serde(default)]
+    pub token_budget: TokenBudgetOptions,
+}
+
+impl Default for MiddlewareSettings {
+    fn default() -> Self {
+        Self {
+            steps: default_steps(),
+            rag: RagOptions::default(),
+            memory: MemoryOptions::default(),
+            safety: SafetyOptions::default(),
+            token_budget: TokenBudgetOptions::default(),
+        }
+    }
+}
+
+impl MiddlewareSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for kind in MiddlewareKind::ALL {
+            let count = self.steps.iter().filter(|step| step.kind == kind).count();
+            if count != 1 {
+                return Err(format!("中间件 {} 必须且只能出现一次", kind.as_str()));
+            }
+        }
+        if !(1..=20).contains(&self.rag.limit) {
+            return Err("RAG 片段数必须在 1 到 20 之间".to_string());
+        }
+        if !(4..=500).contains(&self.memory.summarize_every) {
+            return Err("摘要间隔必须在 4 到 500 条消息之间".to_string());
+        }
+        if !(1..=10).contains(&self.memory.limit) {
+            return Err("注入的摘要数必须在 1 到 10 之间".to_string());
+        }
+        if self.token_budget.max_prompt_tokens < 256 {
+            return Err("提示词预算不能少于 256 tokens".to_string());
+        }
+        if self.safety.blocked_terms.iter().any(|term| term.trim().is_empty()) {
+            return Err("屏蔽词不能为空".to_string());
+        }
+        Ok(())
+    }
+
+    /// 按顺序列出启用的中间件
+    pub fn enabled_steps(&self) -> Vec<MiddlewareKind> {
+        self.steps.iter().filter(|step| step.enabled).map(|step| step.kind).collect()
+    }
+
+    pub fn is_enabled(&self, kind: MiddlewareKind) -> bool {
+        self.steps.iter().any(|step| step.kind == kind && step.enabled)
+    }
+
+    /// 旧版本保存的设置缺少后来新增的中间件时，按默认开关补到末尾
+    pub fn with_missing_steps(mut self) -> Self {
+        for kind in MiddlewareKind::ALL {
+            if !self.steps.iter().any(|step| step.kind == kind) {
+                self.steps.push(MiddlewareStep { kind, enabled: kind.enabled_by_default() });
+            }
+        }
+        self
+    }
+}
+
+/// 估算一组消息的提示词 token 数
+pub fn estimate_prompt_tokens(messages: &[ChatMessage]) -> u32 {
+    messages
+        .iter()
+        .map(|message| estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum()
+}
+
+/// 在开头的系统消息之后插入一条系统消息
+pub fn insert_system(messages: &mut Vec<ChatMessage>, content: String) {
+    let position = messages
+        .iter()
+        .position(|message| !matches!(message.role, MessageRole::System))
+        .unwrap_or(messages.len());
+    messages.insert(position, ChatMessage { role: MessageRole::System, content });
+}
+
+/// 超出预算时从最早的上下文消息开始裁剪，保留系统消息与最后一条消息，返回裁剪条数
+pub fn trim_to_budget(messages: &mut Vec<ChatMessage>, max_prompt_tokens: u32) -> usize {
+    let mut removed = 0;
+    while estimate_prompt_tokens(messages) > max_prompt_tokens {
+        let last = messages.len().saturating_sub(1);
+        let oldest = messages[..last]
+            .iter()
+            .position(|message| !matches!(message.role, MessageRole::System));
+        match oldest {
+            Some(index) => {
+                messages.remove(index);
+                removed += 1;
+            }
+            None => break,
+        }
+    }
+    removed
+}
+
+/// 查找文本中的屏蔽词（不区分大小写）
+pub fn find_blocked_term<'a>(text: &str, terms: &'a [String]) -> Option<&'a str> {
+    let lowered = text.to_lowercase();
+    terms
+        .iter()
+        .map(|term| term.trim())
+        .find(|term| !term.is_empty() && lowered.contains(&term.to_lowercase()))
+}
+
+/// 单个中间件的耗时指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MiddlewareMetrics {
+    pub runs: u64,
+    pub errors: u64,
+    pub total_ms: f64,
+    pub last_ms: f64,
+    pub max_ms: f64,
+}
+
+impl MiddlewareMetrics {
+    pub fn record(&mut self, elapsed_ms: f64, failed: bool) {
+        self.runs += 1;
+        if failed {
+            self.errors += 1;
+        }
+        self.total_ms += elapsed_ms;
+        self.last_ms = elapsed_ms;
+        self.max_ms = self.max_ms.max(elapsed_ms);
+    }
+
+    pub fn average_ms(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.total_ms / self.runs as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: MessageRole, content: &str) -> ChatMessage {
+        ChatMessage { role, content: content.to_string() }
+    }
+
+    #[test]
+    fn test_settings_order_and_validation() {
+        let mut settings = MiddlewareSettings::default();
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            settings.enabled_steps(),
+            [
+                MiddlewareKind::PromptLayering,
+                MiddlewareKind::LongTermMemory,
+                MiddlewareKind::Translation,
+                MiddlewareKind::TokenBudget,
+                MiddlewareKind::Logging,
+            ]
+        );
+
+        settings.steps.swap(0, 6);
+        settings.steps[6].enabled = false;
+        assert_eq!(settings.enabled_steps()[0], MiddlewareKind::Logging);
+        assert!(!settings.enabled_steps().contains(&MiddlewareKind::PromptLayering));
+
+        settings.steps.pop();
+        assert!(settings.validate().is_err());
+        let restored = settings.with_missing_steps();
+        assert!(restored.validate().is_ok());
+        assert!(restored.is_enabled(MiddlewareKind::PromptLayering));
+        let parsed: MiddlewareSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed, MiddlewareSettings::default());
+    }
+
+    #[test]
+    fn test_budget_trimming_and_filters() {
+        let mut messages = vec![
+            message(MessageRole::System, "系统"),
+            message(MessageRole::User, &"旧".repeat(100)),
+            message(MessageRole::Assistant, &"答".repeat(100)),
+            message(MessageRole::User, "现在的问题"),
+        ];
+        insert_system(&mut messages, "参考".to_string());
+        assert!(matches!(messages[1].role, MessageRole::System));
+
+        let budget = estimate_prompt_tokens(&messages) - 50;
+        assert_eq!(trim_to_budget(&mut messages, budget), 1);
+        assert_eq!(messages.len(), 4);
+        assert!(messages[2].content.starts_with('答'));
+        // 只剩系统消息与最后一条时不再裁剪
+        assert_eq!(trim_to_budget(&mut messages, 1), 1);
+        assert_eq!(messages.last().unwrap().content, "现在的问题");
+
+        let terms = vec!["Secret Plan".to_string()];
+        assert_eq!(find_blocked_term("the secret plan is", &terms), Some("Secret Plan"));
+        assert_eq!(find_blocked_term("nothing here", &terms), None);
+
+        let memory = MemoryOptions::default();
+        assert!(!memory.summary_due(19, None));
+        assert!(memory.summary_due(20, None));
+        assert!(!memory.summary_due(30, Some(20)));
+        assert!(memory.summary_due(40, Some(20)));
+
+        let mut metrics = MiddlewareMetrics::default();
+        metrics.record(2.0, false);
+        metrics.record(4.0, true);
+        assert_eq!((metrics.runs, metrics.errors, metrics.max_ms, metrics.average_ms()), (2, 1, 4.0, 3.0));
+    }
+}
@@ -0,0 +1,448 @@
+//! 本地 GGUF 模型推理
+//!
+//! 基于 llama.cpp（`llama-cpp-2`，需要 `local-inference` 特性；GPU 加速另需
+//! `local-inference-cuda` / `local-inference-metal`）在进程内运行已注册的 GGUF 模型。
+//! 生命周期与流式输出见 `commands::local_llm`。
+//!
+//! 本模块包含与 llama.cpp 无关、可以单独测试的部分：推理配置、生成参数、
+//! 按 UTF-8 字符边界拼接 token 字节，以及停止词检测（可能是停止词开头的文本先不输出）。
+
+use serde::{Deserialize, Serialize};
+
+/// 模型没有内置对话模板时使用的停止词
+const FALLBACK_STOP: &str = "<|im_end|>";
+
+/// 加载模型时的推理配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InferenceConfig {
+    /// 卸载到 GPU 的层数（0 为纯 CPU；超过模型层数时全部卸载）
+    pub gpu_layers: u32,
+    /// 上下文长度（token）
+    pub context_size: u32,
+    /// 预填充的批大小（token）
+    pub batch_size: u32,
+    /// 推理线程数（为空时使用物理核心数）
+    pub threads: Option<u32>,
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self {
+            gpu_layers: 0,
+            context_size: 4096,
+            batch_size: 512,
+            threads: None,
+        }
+    }
+}
+
+impl InferenceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(256..=131_072).contains(&self.context_size) {
+            return Err("上下文长度必须在 256 到 131072 之间".to_string());
+        }
+        if !(32..=self.context_size).contains(&self.batch_size) {
+            return Err("批大小必须在 32 到上下文长度之间".to_string());
+        }
+        if self.gpu_layers > 1000 {
+            return Err("GPU 层数不能超过 1000".to_string());
+        }
+        if self.threads == Some(0) {
+            return Err("线程数必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn thread_count(&self) -> u32 {
+        self.threads.unwrap_or_else(|| num_cpus::get_physical().max(1) as u32)
+    }
+}
+
+/// 单次生成的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationParams {
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub top_p: f32,
+    /// 随机种子（为空时随机）
+    pub seed: Option<u32>,
+    /// 生成到这些文本时停止（不包含在输出中）
+    pub stop: Vec<String>,
+}
+
+impl Default for GenerationParams {
+    fn default() -> Self {
+        Self {
+            max_tokens: 512,
+            temperature: 0.7,
+            top_p: 0.9,
+            seed: None,
+            stop: Vec::new(),
+        }
+    }
+}
+
+/// 对话消息（`role` 为 system / user / assistant）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// 生成结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationOutput {
+    pub text: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// `stop`（模型结束或遇到停止词）、`length`（达到 max_tokens）或 `cancelled`
+    pub finish_reason: String,
+}
+
+/// 模型没有内置对话模板时使用 ChatML 格式
+pub fn fallback_prompt(messages: &[InferenceMessage]) -> String {
+    let mut prompt = String::new();
+    for message in messages {
+        prompt.push_str(&format!("<|im_start|>{}\n{}{}\n", message.role, message.content, FALLBACK_STOP));
+    }
+    prompt.push_str("<|im_start|>assistant\n");
+    prompt
+}
+
+/// 把 token 字节拼接为完整的 UTF-8 字符（一个汉字可能跨多个 token）
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// 追加字节，返回已经完整的文本
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        match std::str::from_utf8(&self.pending) {
+            Ok(text) => {
+                let text = text.to_string();
+                self.pending.clear();
+                text
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                // 后面还有无效字节（不是被截断的字符）时按替换字符输出
+                if e.error_len().is_some() {
+                    let text = String::from_utf8_lossy(&self.pending).into_owned();
+                    self.pending.clear();
+                    return text;
+                }
+                let text = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+                self.pending.drain(..valid);
+                text
+            }
+        }
+    }
+
+    /// 结束时剩下的字节
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+/// 停止词检测：可能是停止词开头的文本先保留，确认不是停止词后再输出
+#[derive(Debug)]
+pub struct StopDetector {
+    stop: Vec<String>,
+    held: String,
+}
+
+impl StopDetector {
+    pub fn new(stop: &[String]) -> Self {
+        Self {
+            stop: stop.iter().filter(|s| !s.is_empty()).cloned().collect(),
+            held: String::new(),
+        }
+    }
+
+    /// 追加文本，返回 `(可以输出的文本, 是否遇到停止词)`
+    pub fn push(&mut self, text: &str) -> (String, bool) {
+        self.held.push_str(text);
+        if let Some(index) = self.stop.iter().filter_map(|stop| self.held.find(stop.as_str())).min() {
+            let output = self.held[..index].to_string();
+            self.held.clear();
+            return (output, true);
+        }
+
+        // 末尾与某个停止词开头重合的部分先保留
+        let keep = self
+            .stop
+            .iter()
+            .flat_map(|stop| stop.char_indices().skip(1).map(|(i, _)| &stop[..i]))
+            .filter(|prefix| self.held.ends_with(prefix))
+            .map(|prefix| prefix.len())
+            .max()
+            .unwrap_or(0);
+        let split = self.held.len() - keep;
+        let output = self.held[..split].to_string();
+        self.held.drain(..split);
+        (output, false)
+    }
+
+    /// 结束时保留的文本
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.held)
+    }
+}
+
+#[cfg(feature = "local-inference")]
+mod llama {
+    use std::num::NonZeroU32;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::OnceLock;
+
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+
+    use super::*;
+
+    /// llama.cpp 后端每个进程只能初始化一次
+    fn backend() -> Result<&'static LlamaBackend, String> {
+        static BACKEND: OnceLock<Result<LlamaBackend, String>> = OnceLock::new();
+        BACKEND
+            .get_or_init(|| {
+                let mut backend = LlamaBackend::init().map_err(|e| format!("初始化 llama.cpp 失败: {}", e))?;
+                backend.void_logs();
+                Ok(backend)
+            })
+            .as_ref()
+            .map_err(|e| e.clone())
+    }
+
+    /// 已加载的 GGUF 模型
+    pub struct LlamaRunner {
+        model: LlamaModel,
+        config: InferenceConfig,
+    }
+
+    impl LlamaRunner {
+        /// 加载模型（阻塞操作）
+        pub fn load(path: &Path, config: &InferenceConfig) -> Result<Self, String> {
+            let params = LlamaModelParams::default().with_n_gpu_layers(config.gpu_layers);
+            let model = LlamaModel::load_from_file(backend()?, path, &params)
+                .map_err(|e| format!("加载模型失败: {}", e))?;
+            Ok(Self { model, config: config.clone() })
+        }
+
+        fn prompt(&self, messages: &[InferenceMessage]) -> Result<(String, Vec<String>), String> {
+            let template = self.model.chat_template(None).ok();
+            if let Some(template) = template {
+                let chat = messages
+                    .iter()
+                    .map(|m| LlamaChatMessage::new(m.role.clone(), m.content.clone()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("消息格式错误: {}", e))?;
+                if let Ok(prompt) = self.model.apply_chat_template(&template, &chat, true) {
+                    return Ok((prompt, Vec::new()));
+                }
+            }
+            Ok((fallback_prompt(messages), vec![FALLBACK_STOP.to_string()]))
+        }
+
+        /// 生成回复（阻塞操作）；`on_text` 收到新的文本片段，`cancel` 置位后尽快停止
+        pub fn generate(
+            &self,
+            messages: &[InferenceMessage],
+            params: &GenerationParams,
+            cancel: &AtomicBool,
+            mut on_text: impl FnMut(&str),
+        ) -> Result<GenerationOutput, String> {
+            let (prompt, template_stop) = self.prompt(messages)?;
+            let tokens = self
+                .model
+                .str_to_token(&prompt, AddBos::Always)
+                .map_err(|e| format!("分词失败: {}", e))?;
+            let context_size = self.config.context_size as usize;
+            if tokens.len() + params.max_tokens as usize > context_size {
+                return Err(format!(
+                    "提示词 {} tokens 加上最多 {} 个生成 tokens 超过上下文长度 {}",
+                    tokens.len(),
+                    params.max_tokens,
+                    context_size
+                ));
+            }
+
+            let threads = self.config.thread_count() as i32;
+            let context_params = LlamaContextParams::default()
+                .with_n_ctx(NonZeroU32::new(self.config.context_size))
+                .with_n_batch(self.config.batch_size)
+                .with_n_threads(threads)
+                .with_n_threads_batch(threads);
+            let mut context = self
+                .model
+                .new_context(backend()?, context_params)
+                .map_err(|e| format!("创建推理上下文失败: {}", e))?;
+
+            // 预填充：按批大小分段解码提示词
+            let batch_size = self.config.batch_size as usize;
+            let mut batch = LlamaBatch::new(batch_size, 1);
+            for (chunk_index, chunk) in tokens.chunks(batch_size).enumerate() {
+                batch.clear();
+                for (offset, token) in chunk.iter().enumerate() {
+                    let position = chunk_index * batch_size + offset;
+                    let is_last = position == tokens.len() - 1;
+                    batch
+                        .add(*token, position as i32, &[0], is_last)
+                        .map_err(|e| format!("构建批次失败: {}", e))?;
+                }
+                context.decode(&mut batch).map_err(|e| format!("推理失败: {}", e))?;
+                if cancel.load(Ordering::Relaxed) {
+                    return Ok(GenerationOutput {
+                        text: String::new(),
+                        prompt_tokens: tokens.len() as u32,
+                        completion_tokens: 0,
+                        finish_reason: "cancelled".to_string(),
+                    });
+                }
+            }
+
+            let mut sampler = if params.temperature <= 0.0 {
+                LlamaSampler::greedy()
+            } else {
+                LlamaSampler::chain_simple([
+                    LlamaSampler::top_p(params.top_p.clamp(0.0, 1.0), 1),
+                    LlamaSampler::temp(params.temperature),
+                    LlamaSampler::dist(params.seed.unwrap_or_else(rand::random)),
+                ])
+            };
+
+            let stop: Vec<String> = params.stop.iter().chain(&template_stop).cloned().collect();
+            let mut detector = StopDetector::new(&stop);
+            let mut decoder = Utf8Decoder::default();
+            let mut text = String::new();
+            let mut emit = |piece: String, text: &mut String| {
+                if !piece.is_empty() {
+                    on_text(&piece);
+                    text.push_str(&piece);
+                }
+            };
+
+            let mut position = tokens.len();
+            let mut completion_tokens = 0u32;
+            let mut stopped_by_word = false;
+            let finish_reason = loop {
+                if cancel.load(Ordering::Relaxed) {
+                    break "cancelled";
+                }
+                if completion_tokens >= params.max_tokens {
+                    break "length";
+                }
+                let token = sampler.sample(&context, batch.n_tokens() - 1);
+                sampler.accept(token);
+                if self.model.is_eog_token(token) {
+                    break "stop";
+                }
+                completion_tokens += 1;
+
+                let bytes = self
+                    .model
+                    .token_to_bytes(token, Special::Tokenize)
+                    .map_err(|e| format!("解码 token 失败: {}", e))?;
+                let (piece, stopped) = detector.push(&decoder.push(&bytes));
+                emit(piece, &mut text);
+                if stopped {
+                    stopped_by_word = true;
+                    break "stop";
+                }
+
+                batch.clear();
+                batch
+                    .add(token, position as i32, &[0], true)
+                    .map_err(|e| format!("构建批次失败: {}", e))?;
+                position += 1;
+                context.decode(&mut batch).map_err(|e| format!("推理失败: {}", e))?;
+            };
+
+            // 停止词之后的内容丢弃，其他情况输出剩下的文本
+            if !stopped_by_word {
+                let (piece, _) = detector.push(&decoder.finish());
+                emit(piece, &mut text);
+                emit(detector.finish(), &mut text);
+            }
+
+            Ok(GenerationOutput {
+                text,
+                prompt_tokens: tokens.len() as u32,
+                completion_tokens,
+                finish_reason: finish_reason.to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "local-inference")]
+pub use llama::LlamaRunner;
+
+/// 未启用 `local-inference` 特性时的占位实现
+#[cfg(not(feature = "local-inference"))]
+pub struct LlamaRunner;
+
+#[cfg(not(feature = "local-inference"))]
+impl LlamaRunner {
+    pub fn load(_path: &std::path::Path, _config: &InferenceConfig) -> Result<Self, String> {
+        Err("本地推理需要启用 local-inference 特性".to_string())
+    }
+
+    pub fn generate(
+        &self,
+        _messages: &[InferenceMessage],
+        _params: &GenerationParams,
+        _cancel: &std::sync::atomic::AtomicBool,
+        _on_text: impl FnMut(&str),
+    ) -> Result<GenerationOutput, String> {
+        Err("本地推理需要启用 local-inference 特性".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_decoder_joins_split_characters() {
+        let bytes = "你好".as_bytes();
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.push(&bytes[..2]), "");
+        assert_eq!(decoder.push(&bytes[2..4]), "你");
+        assert_eq!(decoder.push(&bytes[4..]), "好");
+        assert_eq!(decoder.push(&[0xe5]), "");
+        assert_eq!(decoder.finish(), "\u{fffd}");
+    }
+
+    #[test]
+    fn test_stop_detector_holds_possible_prefix() {
+        let mut detector = StopDetector::new(&["<|im_end|>".to_string(), "用户：".to_string()]);
+        assert_eq!(detector.push("好的<|im"), ("好的".to_string(), false));
+        assert_eq!(detector.push("possible"), ("<|impossible".to_string(), false));
+        assert_eq!(detector.push("。用"), ("。".to_string(), false));
+        assert_eq!(detector.push("户：你好"), ("".to_string(), true));
+
+        let mut detector = StopDetector::new(&["END".to_string()]);
+        assert_eq!(detector.push("abcE"), ("abc".to_string(), false));
+        assert_eq!(detector.finish(), "E");
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(InferenceConfig::default().validate().is_ok());
+        let config = InferenceConfig { batch_size: 8192, ..InferenceConfig::default() };
+        assert!(config.validate().is_err());
+        let prompt = fallback_prompt(&[InferenceMessage { role: "user".into(), content: "hi".into() }]);
+        assert_eq!(prompt, "<|im_start|>user\nhi<|im_end|>\n<|im_start|>assistant\n");
+    }
+}
@@ -0,0 +1,256 @@
+//! # 上传前的日志匿名化
+//!
+//! `commands::system::upload_logs` 上传日志前用 [`LogAnonymizer`] 处理每条日志：
+//!
+//! - 主目录路径中的用户名替换为 `<user>`，本机用户名在任何位置出现都会被替换
+//! - IP 地址只保留前缀（[`Anonymizer::anonymize_ip`]）
+//! - 凭据字段整体替换，邮箱、电话、API 密钥与令牌按 [`Anonymizer`] 与 [`DataMasker`] 的规则脱敏
+//! - 对话内容字段（`content`、`prompt` 等）整体移除，只保留长度
+//! - 用户 ID 与会话 ID 替换为本次上传内一致的哈希
+//!
+//! `preview_anonymized_logs` 使用同一流程，用户看到的就是实际发送的内容。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::anonymizer::Anonymizer;
+use crate::utils::data_masking::DataMasker;
+
+/// 值为对话内容的字段，整体移除
+const CHAT_FIELDS: [&str; 9] = [
+    "content",
+    "prompt",
+    "response",
+    "reply",
+    "messages",
+    "transcript",
+    "user_message",
+    "assistant_message",
+    "chat_history",
+];
+
+/// 值为凭据的字段，整体替换
+const SECRET_FIELDS: [&str; 6] = ["password", "token", "access_token", "api_key", "apiKey", "secret"];
+
+/// 值为用户或会话标识的字段，替换为哈希
+const IDENTITY_FIELDS: [&str; 4] = ["userId", "user_id", "sessionId", "session_id"];
+
+/// 短于此长度的用户名不单独替换，避免误伤普通单词
+const MIN_USERNAME_LEN: usize = 3;
+
+/// 各类信息被处理的次数（按受影响的字段计）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionStats {
+    pub file_paths: usize,
+    pub usernames: usize,
+    pub ip_addresses: usize,
+    /// 邮箱与电话
+    pub contact_info: usize,
+    /// API 密钥与令牌
+    pub secrets: usize,
+    pub chat_excerpts: usize,
+    pub identifiers: usize,
+}
+
+impl RedactionStats {
+    pub fn total(&self) -> usize {
+        self.file_paths
+            + self.usernames
+            + self.ip_addresses
+            + self.contact_info
+            + self.secrets
+            + self.chat_excerpts
+            + self.identifiers
+    }
+}
+
+/// 日志匿名化器
+pub struct LogAnonymizer {
+    anonymizer: Anonymizer,
+    masker: DataMasker,
+    home_path: Regex,
+    ip_address: Regex,
+    secret_value: Regex,
+    usernames: Vec<Regex>,
+}
+
+impl LogAnonymizer {
+    /// `usernames` 为需要在任何位置替换的用户名（通常是本机登录名）
+    pub fn new(usernames: &[String]) -> Self {
+        let usernames = usernames
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| name.chars().count() >= MIN_USERNAME_LEN)
+            .filter_map(|name| Regex::new(&format!(r"(?i)\b{}\b", regex::escape(name))).ok())
+            .collect();
+        Self {
+            anonymizer: Anonymizer::new(),
+            masker: DataMasker::new(),
+            home_path: Regex::new(r#"(?i)(/home/|/Users/|[A-Z]:[\\/]+Users[\\/]+)([^/\\\s"':]+)"#)
+                .expect("invalid home path pattern"),
+            ip_address: Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").expect("invalid ip pattern"),
+            secret_value: Regex::new(r#"(?i)(api[_-]?key|token|secret|password)(['"]?\s*[:=]\s*['"]?)[^\s'",;]+"#)
+                .expect("invalid secret pattern"),
+            usernames,
+        }
+    }
+
+    /// 使用本机登录名与主目录名
+    pub fn for_current_user() -> Self {
+        let mut usernames: Vec<String> = ["USER", "USERNAME", "LOGNAME"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .collect();
+        if let Some(name) = dirs::home_dir().and_then(|home| home.file_name().map(|n| n.to_string_lossy().into_owned())) {
+            usernames.push(name);
+        }
+        usernames.sort();
+        usernames.dedup();
+        Self::new(&usernames)
+    }
+
+    /// 匿名化一段文字
+    pub fn scrub_text(&self, text: &str, stats: &mut RedactionStats) -> String {
+        let mut result = text.to_string();
+
+        let replaced = self.home_path.replace_all(&result, "${1}<user>");
+        if replaced != result {
+            stats.file_paths += 1;
+            result = replaced.into_owned();
+        }
+
+        let mut username_found = false;
+        for pattern in &self.usernames {
+            let replaced = pattern.replace_all(&result, "<user>");
+            if replaced != result {
+                username_found = true;
+                result = replaced.into_owned();
+            }
+        }
+        if username_found {
+            stats.usernames += 1;
+        }
+
+        let replaced = self
+            .ip_address
+            .replace_all(&result, |caps: &regex::Captures| self.anonymizer.anonymize_ip(&caps[0]));
+        if replaced != result {
+            stats.ip_addresses += 1;
+            result = replaced.into_owned();
+        }
+
+        let masked = self.masker.mask_tokens(&self.masker.mask_api_keys(&result)).into_owned();
+        let secrets = self.secret_value.replace_all(&masked, "${1}${2}[REDACTED]").into_owned();
+        if secrets != result {
+            stats.secrets += 1;
+            result = secrets;
+        }
+
+        // IP 已在前面处理过，这里只会替换邮箱与电话
+        let redacted = self.anonymizer.redact_sensitive_text(&result);
+        let contacts = self.masker.mask_phone(&redacted).into_owned();
+        if contacts != result {
+            stats.contact_info += 1;
+        }
+        contacts
+    }
+
+    /// 匿名化一条日志（任意 JSON 结构）
+    pub fn scrub_log(&self, log: &Value, stats: &mut RedactionStats) -> Value {
+        self.scrub_value(None, log, stats)
+    }
+
+    fn scrub_value(&self, field: Option<&str>, value: &Value, stats: &mut RedactionStats) -> Value {
+        match (field, value) {
+            (_, Value::Null) => Value::Null,
+            (Some(field), value) if CHAT_FIELDS.contains(&field) => {
+                stats.chat_excerpts += 1;
+                let length = match value {
+                    Value::String(text) => text.chars().count(),
+                    other => other.to_string().chars().count(),
+                };
+                Value::String(format!("[CHAT_REDACTED:{}]", length))
+            }
+            (Some(field), Value::String(_)) if SECRET_FIELDS.contains(&field) => {
+                stats.secrets += 1;
+                Value::String("[REDACTED]".to_string())
+            }
+            (Some(field), Value::String(id)) if IDENTITY_FIELDS.contains(&field) => {
+                stats.identifiers += 1;
+                let hash = self.anonymizer.generate_anonymous_device_id(id);
+                Value::String(hash.chars().take(16).collect())
+            }
+            (_, Value::String(text)) => Value::String(self.scrub_text(text, stats)),
+            (_, Value::Array(items)) => Value::Array(items.iter().map(|item| self.scrub_value(field, item, stats)).collect()),
+            (_, Value::Object(map)) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (self.scrub_text(key, stats), self.scrub_value(Some(key), value, stats)))
+                    .collect(),
+            ),
+            (_, other) => other.clone(),
+        }
+    }
+}
+
+/// 匿名化一批日志
+pub fn anonymize_logs(logs: &[Value]) -> (Vec<Value>, RedactionStats) {
+    let anonymizer = LogAnonymizer::for_current_user();
+    let mut stats = RedactionStats::default();
+    let logs = logs.iter().map(|log| anonymizer.scrub_log(log, &mut stats)).collect();
+    (logs, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scrub_text() {
+        let anonymizer = LogAnonymizer::new(&["alice".to_string(), "al".to_string()]);
+        let mut stats = RedactionStats::default();
+
+        let text = anonymizer.scrub_text(
+            r"打开 /home/alice/.config/zishu/app.log 与 C:\Users\Alice\AppData 失败，用户 alice 来自 192.168.1.23",
+            &mut stats,
+        );
+        assert_eq!(
+            text,
+            r"打开 /home/<user>/.config/zishu/app.log 与 C:\Users\<user>\AppData 失败，用户 <user> 来自 192.168.***.***"
+        );
+        assert_eq!((stats.file_paths, stats.usernames, stats.ip_addresses), (1, 1, 1));
+
+        let text = anonymizer.scrub_text("联系 test@example.com，api_key=abcdef123456", &mut stats);
+        assert!(!text.contains("test@example.com"));
+        assert!(!text.contains("abcdef123456"));
+        assert_eq!((stats.contact_info, stats.secrets), (1, 1));
+
+        // 过短的用户名不单独替换
+        assert_eq!(anonymizer.scrub_text("algorithm al", &mut RedactionStats::default()), "algorithm al");
+    }
+
+    #[test]
+    fn test_scrub_log_fields() {
+        let anonymizer = LogAnonymizer::new(&[]);
+        let mut stats = RedactionStats::default();
+        let log = json!({
+            "level": "error",
+            "message": "发送失败",
+            "userId": "user-42",
+            "timestamp": 1700000000,
+            "data": { "prompt": "今天天气怎么样", "messages": [{ "role": "user" }], "token": "abc", "retries": 2 },
+        });
+
+        let scrubbed = anonymizer.scrub_log(&log, &mut stats);
+        assert_eq!(scrubbed["data"]["prompt"], json!("[CHAT_REDACTED:7]"));
+        assert!(scrubbed["data"]["messages"].as_str().unwrap().starts_with("[CHAT_REDACTED:"));
+        assert_eq!(scrubbed["data"]["token"], json!("[REDACTED]"));
+        assert_eq!(scrubbed["data"]["retries"], json!(2));
+        assert_eq!(scrubbed["timestamp"], json!(1700000000));
+        assert_ne!(scrubbed["userId"], json!("user-42"));
+        assert_eq!(scrubbed["message"], json!("发送失败"));
+        assert_eq!((stats.chat_excerpts, stats.identifiers, stats.secrets), (2, 1, 1));
+        assert_eq!(stats.total(), 4);
+    }
+}
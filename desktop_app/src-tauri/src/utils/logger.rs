@@ -19,6 +19,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, error, info, trace, warn};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use crate::utils::clock::{self, SharedClock};
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
@@ -288,6 +289,7 @@ pub struct Logger {
     file_handle: Arc<Mutex<Option<File>>>,
     current_file_path: Arc<Mutex<Option<PathBuf>>>,
     current_file_size: Arc<Mutex<u64>>,
+    clock: SharedClock,
 }
 
 impl Logger {
@@ -298,11 +300,18 @@ impl Logger {
             file_handle: Arc::new(Mutex::new(None)),
             current_file_path: Arc::new(Mutex::new(None)),
             current_file_size: Arc::new(Mutex::new(0)),
+            clock: clock::global(),
         };
 
         Ok(logger)
     }
 
+    /// 注入时钟（用于测试日志保留策略）
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// 初始化日志系统
     pub fn initialize(&self) -> LoggerResult<()> {
         let config = self.config.lock().unwrap();
@@ -508,7 +517,7 @@ impl Logger {
             return Ok(());
         }
 
-        let cutoff_time = Local::now() - chrono::Duration::days(retention_days as i64);
+        let cutoff_time = self.clock.local_now() - chrono::Duration::days(retention_days as i64);
 
         for entry in fs::read_dir(log_dir)? {
             let entry = entry?;
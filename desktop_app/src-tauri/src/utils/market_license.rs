@@ -0,0 +1,257 @@
+//! # 市场购买凭证
+//!
+//! 付费产品的购买凭证由市场签发：`payload` 为 Base64 编码的 JSON 声明（见 [`ReceiptClaims`]），
+//! `signature` 为市场对 `payload` 原始字节的 Base64 编码 Ed25519 签名。验证只使用编译进程序的
+//! 凭证公钥（见 [`receipt_keys`]），不接受主题包的自定义签名公钥。凭证绑定购买的账号，
+//! 只对当前登录的账号有效。
+//!
+//! 验证通过的凭证加密缓存在本地，下载付费产品前检查；缓存的读写与命令见 `commands::market`。
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 允许的签发时间超前量（秒），容忍本机时钟偏差
+const MAX_CLOCK_SKEW_SECS: i64 = 5 * 60;
+/// 市场签发购买凭证的公钥（Base64 编码的 Ed25519 公钥）
+///
+/// 对应的私钥由社区平台运维保管，只部署在签发凭证的市场服务上，不进入代码仓库。
+/// 轮换密钥时先把新公钥追加到这里并发布客户端，市场改用新私钥签发后再移除旧公钥。
+const RECEIPT_SIGNING_KEYS: &[&str] = &["AfAecq6MIwLB80r0EjYlaO0J7xKcqi8wehtcZwzO52I="];
+
+/// 受信任的凭证签名公钥；调试构建可通过环境变量 `RECEIPT_SIGNING_KEYS` 追加测试市场的公钥
+pub fn receipt_keys() -> Vec<Vec<u8>> {
+    #[cfg(debug_assertions)]
+    let extra = std::env::var("RECEIPT_SIGNING_KEYS").unwrap_or_default();
+    #[cfg(not(debug_assertions))]
+    let extra = String::new();
+
+    RECEIPT_SIGNING_KEYS
+        .iter()
+        .copied()
+        .chain(extra.split(',').map(str::trim).filter(|key| !key.is_empty()))
+        .filter_map(|key| base64::engine::general_purpose::STANDARD.decode(key).ok())
+        .collect()
+}
+
+/// 市场签发的购买凭证
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    pub payload: String,
+    pub signature: String,
+}
+
+/// 凭证声明（时间均为 Unix 秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptClaims {
+    pub receipt_id: String,
+    pub product_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub issued_at: i64,
+    /// 订阅类产品的到期时间；买断为空
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl ReceiptClaims {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// 校验凭证签名与声明（产品、购买账号、签发与到期时间），返回声明
+pub fn verify_receipt(
+    receipt: &SignedReceipt,
+    keys: &[Vec<u8>],
+    product_id: &str,
+    account_id: &str,
+    now: i64,
+) -> Result<ReceiptClaims, String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let payload = engine
+        .decode(receipt.payload.trim())
+        .map_err(|_| "购买凭证格式无效".to_string())?;
+    let signature = engine
+        .decode(receipt.signature.trim())
+        .map_err(|_| "购买凭证签名格式无效".to_string())?;
+    if !keys
+        .iter()
+        .any(|key| UnparsedPublicKey::new(&ED25519, key).verify(&payload, &signature).is_ok())
+    {
+        return Err("购买凭证签名无效或不是由市场签发".to_string());
+    }
+
+    let claims: ReceiptClaims =
+        serde_json::from_slice(&payload).map_err(|e| format!("解析购买凭证失败: {}", e))?;
+    if claims.product_id != product_id {
+        return Err(format!("购买凭证属于产品 {}，不是 {}", claims.product_id, product_id));
+    }
+    if claims.user_id.as_deref() != Some(account_id) {
+        return Err("购买凭证不属于当前登录的账号".to_string());
+    }
+    if claims.issued_at > now + MAX_CLOCK_SKEW_SECS {
+        return Err("购买凭证的签发时间无效".to_string());
+    }
+    if claims.is_expired(now) {
+        return Err("购买凭证已过期".to_string());
+    }
+    Ok(claims)
+}
+
+/// 已验证的许可
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct License {
+    pub receipt: SignedReceipt,
+    pub claims: ReceiptClaims,
+    pub verified_at: i64,
+}
+
+/// 产品的许可状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseStatus {
+    /// 免费产品，无需凭证
+    Free,
+    Licensed,
+    Expired,
+    /// 付费产品但没有有效凭证
+    Unlicensed,
+}
+
+/// `get_license_status` 的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseInfo {
+    pub product_id: String,
+    pub status: LicenseStatus,
+    pub receipt_id: Option<String>,
+    pub issued_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub verified_at: Option<i64>,
+}
+
+impl LicenseInfo {
+    /// 由缓存的许可与产品是否付费得出状态；`is_paid` 未知时按缓存判断
+    pub fn new(product_id: &str, license: Option<&License>, is_paid: Option<bool>, now: i64) -> Self {
+        let status = match (license, is_paid) {
+            (Some(license), _) if license.claims.is_expired(now) => LicenseStatus::Expired,
+            (Some(_), _) => LicenseStatus::Licensed,
+            (None, Some(false)) => LicenseStatus::Free,
+            (None, _) => LicenseStatus::Unlicensed,
+        };
+        Self {
+            product_id: product_id.to_string(),
+            status,
+            receipt_id: license.map(|l| l.claims.receipt_id.clone()),
+            issued_at: license.map(|l| l.claims.issued_at),
+            expires_at: license.and_then(|l| l.claims.expires_at),
+            verified_at: license.map(|l| l.verified_at),
+        }
+    }
+}
+
+/// 本地许可缓存（按产品 ID）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LicenseStore {
+    licenses: BTreeMap<String, License>,
+}
+
+impl LicenseStore {
+    pub fn get(&self, product_id: &str) -> Option<&License> {
+        self.licenses.get(product_id)
+    }
+
+    /// 属于 `account_id` 且未过期的许可
+    pub fn valid(&self, product_id: &str, account_id: &str, now: i64) -> Option<&License> {
+        self.get(product_id).filter(|license| {
+            license.claims.user_id.as_deref() == Some(account_id) && !license.claims.is_expired(now)
+        })
+    }
+
+    pub fn insert(&mut self, license: License) {
+        self.licenses.insert(license.claims.product_id.clone(), license);
+    }
+
+    pub fn remove(&mut self, product_id: &str) -> Option<License> {
+        self.licenses.remove(product_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn sign(key_pair: &Ed25519KeyPair, claims: &serde_json::Value) -> SignedReceipt {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let payload = serde_json::to_vec(claims).unwrap();
+        SignedReceipt {
+            payload: engine.encode(&payload),
+            signature: engine.encode(key_pair.sign(&payload)),
+        }
+    }
+
+    #[test]
+    fn test_verify_receipt() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let keys = vec![key_pair.public_key().as_ref().to_vec()];
+        let receipt = sign(
+            &key_pair,
+            &serde_json::json!({
+                "receipt_id": "r-1",
+                "product_id": "weather",
+                "user_id": "42",
+                "issued_at": 1_000,
+                "expires_at": 5_000,
+            }),
+        );
+
+        let claims = verify_receipt(&receipt, &keys, "weather", "42", 2_000).unwrap();
+        assert_eq!(claims.receipt_id, "r-1");
+        assert!(verify_receipt(&receipt, &keys, "translator", "42", 2_000).is_err());
+        assert_eq!(verify_receipt(&receipt, &keys, "weather", "42", 6_000).unwrap_err(), "购买凭证已过期");
+        // 其他账号的凭证
+        assert_eq!(
+            verify_receipt(&receipt, &keys, "weather", "7", 2_000).unwrap_err(),
+            "购买凭证不属于当前登录的账号"
+        );
+        // 不受信任的公钥
+        assert!(verify_receipt(&receipt, &receipt_keys(), "weather", "42", 2_000).is_err());
+
+        // 篡改声明后签名失效
+        let forged = SignedReceipt {
+            payload: base64::engine::general_purpose::STANDARD
+                .encode(br#"{"receipt_id":"r-1","product_id":"weather","issued_at":1000}"#),
+            ..receipt
+        };
+        assert!(verify_receipt(&forged, &keys, "weather", "42", 2_000).is_err());
+    }
+
+    #[test]
+    fn test_license_status() {
+        let license = License {
+            receipt: SignedReceipt { payload: String::new(), signature: String::new() },
+            claims: ReceiptClaims {
+                receipt_id: "r-1".to_string(),
+                product_id: "weather".to_string(),
+                user_id: Some("42".to_string()),
+                issued_at: 1_000,
+                expires_at: Some(5_000),
+            },
+            verified_at: 1_500,
+        };
+        assert_eq!(LicenseInfo::new("weather", Some(&license), Some(true), 2_000).status, LicenseStatus::Licensed);
+        assert_eq!(LicenseInfo::new("weather", Some(&license), None, 9_000).status, LicenseStatus::Expired);
+        assert_eq!(LicenseInfo::new("clock", None, Some(false), 2_000).status, LicenseStatus::Free);
+        assert_eq!(LicenseInfo::new("clock", None, None, 2_000).status, LicenseStatus::Unlicensed);
+
+        let mut store = LicenseStore::default();
+        store.insert(license);
+        assert!(store.valid("weather", "42", 2_000).is_some());
+        assert!(store.valid("weather", "7", 2_000).is_none());
+        assert!(store.valid("weather", "42", 9_000).is_none());
+    }
+}
@@ -0,0 +1,372 @@
+//! # 指标注册表
+//!
+//! 应用内部的计数器、仪表与直方图（命令调用、HTTP 客户端、工作流执行、渲染统计等），
+//! 可按 Prometheus 文本格式导出。所有指标在 [`METRICS`] 中声明，记录未声明的指标会被忽略，
+//! 避免拼写错误悄悄产生新的时间序列。
+//!
+//! 看门狗等自行维护计数的子系统在导出时由 [`render`] 同步到注册表。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use tracing::debug;
+
+/// Prometheus 文本格式的 Content-Type
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// 单个指标的时间序列上限，防止标签取值失控
+const MAX_SERIES_PER_METRIC: usize = 1000;
+
+/// 耗时直方图的默认分桶（秒）
+pub const DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+/// 渲染耗时分桶（毫秒）
+pub const RENDER_BUCKETS: &[f64] = &[1.0, 2.0, 4.0, 8.0, 16.0, 33.0, 50.0, 100.0, 250.0];
+
+/// 指标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// 指标声明
+#[derive(Debug, Clone, Copy)]
+pub struct MetricDescriptor {
+    pub name: &'static str,
+    pub kind: MetricKind,
+    pub help: &'static str,
+    /// 仅直方图使用
+    pub buckets: &'static [f64],
+}
+
+const fn counter(name: &'static str, help: &'static str) -> MetricDescriptor {
+    MetricDescriptor { name, kind: MetricKind::Counter, help, buckets: &[] }
+}
+
+const fn gauge(name: &'static str, help: &'static str) -> MetricDescriptor {
+    MetricDescriptor { name, kind: MetricKind::Gauge, help, buckets: &[] }
+}
+
+const fn histogram(name: &'static str, help: &'static str, buckets: &'static [f64]) -> MetricDescriptor {
+    MetricDescriptor { name, kind: MetricKind::Histogram, help, buckets }
+}
+
+pub const COMMANDS_TOTAL: &str = "zishu_commands_total";
+pub const HTTP_REQUESTS_TOTAL: &str = "zishu_http_requests_total";
+pub const HTTP_REQUEST_DURATION: &str = "zishu_http_request_duration_seconds";
+pub const WORKFLOW_EXECUTIONS_TOTAL: &str = "zishu_workflow_executions_total";
+pub const WORKFLOW_EXECUTION_DURATION: &str = "zishu_workflow_execution_duration_seconds";
+pub const ADAPTER_EXECUTIONS_TOTAL: &str = "zishu_adapter_executions_total";
+pub const ADAPTER_EXECUTION_DURATION: &str = "zishu_adapter_execution_duration_seconds";
+pub const RENDER_DURATION: &str = "zishu_render_duration_milliseconds";
+pub const RENDER_FPS: &str = "zishu_render_fps";
+pub const RENDER_FRAME_TIME: &str = "zishu_render_frame_time_milliseconds";
+pub const RENDER_DRAW_CALLS: &str = "zishu_render_draw_calls";
+pub const RENDER_TEXTURE_MEMORY: &str = "zishu_render_texture_memory_bytes";
+pub const SUBSYSTEM_RESTARTS_TOTAL: &str = "zishu_subsystem_restarts_total";
+pub const SUBSYSTEM_UP: &str = "zishu_subsystem_up";
+pub const UPTIME_SECONDS: &str = "zishu_uptime_seconds";
+
+/// 所有已声明的指标
+pub const METRICS: &[MetricDescriptor] = &[
+    counter(COMMANDS_TOTAL, "Tauri commands invoked by the frontend"),
+    counter(HTTP_REQUESTS_TOTAL, "HTTP requests sent to backend services"),
+    histogram(HTTP_REQUEST_DURATION, "Latency of HTTP requests to backend services", DURATION_BUCKETS),
+    counter(WORKFLOW_EXECUTIONS_TOTAL, "Workflow executions by outcome"),
+    histogram(WORKFLOW_EXECUTION_DURATION, "Time to start a workflow execution", DURATION_BUCKETS),
+    counter(ADAPTER_EXECUTIONS_TOTAL, "Adapter actions executed by outcome"),
+    histogram(ADAPTER_EXECUTION_DURATION, "Adapter action execution time", DURATION_BUCKETS),
+    histogram(RENDER_DURATION, "Frontend component render time", RENDER_BUCKETS),
+    gauge(RENDER_FPS, "Latest reported frames per second"),
+    gauge(RENDER_FRAME_TIME, "Latest reported frame time"),
+    gauge(RENDER_DRAW_CALLS, "Latest reported WebGL draw calls per frame"),
+    gauge(RENDER_TEXTURE_MEMORY, "Latest reported WebGL texture memory"),
+    counter(SUBSYSTEM_RESTARTS_TOTAL, "Background subsystem restarts performed by the watchdog"),
+    gauge(SUBSYSTEM_UP, "Whether a supervised background subsystem is healthy"),
+    gauge(UPTIME_SECONDS, "Seconds since the application started"),
+];
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Clone)]
+struct HistogramValue {
+    /// 各分桶的计数（非累计）
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug, Clone)]
+enum SeriesValue {
+    Scalar(f64),
+    Histogram(HistogramValue),
+}
+
+#[derive(Debug)]
+struct Family {
+    descriptor: MetricDescriptor,
+    series: BTreeMap<Labels, SeriesValue>,
+}
+
+/// 指标注册表
+#[derive(Debug)]
+pub struct MetricRegistry {
+    families: BTreeMap<&'static str, Family>,
+}
+
+fn normalize_labels(labels: &[(&str, &str)]) -> Labels {
+    let mut labels: Labels = labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    labels.sort();
+    labels
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_labels(labels: &Labels, extra: Option<(&str, String)>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+        .collect();
+    if let Some((k, v)) = extra {
+        parts.push(format!("{}=\"{}\"", k, v));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+impl MetricRegistry {
+    pub fn new(descriptors: &[MetricDescriptor]) -> Self {
+        let families = descriptors
+            .iter()
+            .map(|d| (d.name, Family { descriptor: *d, series: BTreeMap::new() }))
+            .collect();
+        Self { families }
+    }
+
+    fn series(&mut self, name: &str, kind: &[MetricKind], labels: &[(&str, &str)]) -> Option<&mut SeriesValue> {
+        let Some(family) = self.families.get_mut(name) else {
+            debug!("忽略未声明的指标: {}", name);
+            return None;
+        };
+        if !kind.contains(&family.descriptor.kind) {
+            debug!("指标 {} 类型不匹配", name);
+            return None;
+        }
+        let labels = normalize_labels(labels);
+        if !family.series.contains_key(&labels) && family.series.len() >= MAX_SERIES_PER_METRIC {
+            return None;
+        }
+        let buckets = family.descriptor.buckets.len();
+        Some(family.series.entry(labels).or_insert_with(|| match family.descriptor.kind {
+            MetricKind::Histogram => {
+                SeriesValue::Histogram(HistogramValue { counts: vec![0; buckets], sum: 0.0, count: 0 })
+            }
+            _ => SeriesValue::Scalar(0.0),
+        }))
+    }
+
+    /// 计数器增加（负数被忽略）
+    pub fn increment(&mut self, name: &str, labels: &[(&str, &str)], by: f64) {
+        if by < 0.0 {
+            return;
+        }
+        if let Some(SeriesValue::Scalar(value)) = self.series(name, &[MetricKind::Counter], labels) {
+            *value += by;
+        }
+    }
+
+    /// 设置仪表值；也用于同步其他子系统自行维护的计数器
+    pub fn set(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        if let Some(SeriesValue::Scalar(current)) =
+            self.series(name, &[MetricKind::Gauge, MetricKind::Counter], labels)
+        {
+            *current = value;
+        }
+    }
+
+    /// 记录直方图观测值
+    pub fn observe(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let buckets = match self.families.get(name) {
+            Some(family) => family.descriptor.buckets,
+            None => return,
+        };
+        if let Some(SeriesValue::Histogram(histogram)) = self.series(name, &[MetricKind::Histogram], labels) {
+            if let Some(index) = buckets.iter().position(|bound| value <= *bound) {
+                histogram.counts[index] += 1;
+            }
+            histogram.sum += value;
+            histogram.count += 1;
+        }
+    }
+
+    /// 导出为 Prometheus 文本格式（没有数据的指标不输出）
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for family in self.families.values().filter(|f| !f.series.is_empty()) {
+            let d = &family.descriptor;
+            let _ = writeln!(out, "# HELP {} {}", d.name, d.help);
+            let _ = writeln!(out, "# TYPE {} {}", d.name, d.kind.as_str());
+            for (labels, value) in &family.series {
+                match value {
+                    SeriesValue::Scalar(v) => {
+                        let _ = writeln!(out, "{}{} {}", d.name, format_labels(labels, None), format_value(*v));
+                    }
+                    SeriesValue::Histogram(h) => {
+                        let mut cumulative = 0;
+                        for (bound, count) in d.buckets.iter().zip(&h.counts) {
+                            cumulative += count;
+                            let le = format_labels(labels, Some(("le", format_value(*bound))));
+                            let _ = writeln!(out, "{}_bucket{} {}", d.name, le, cumulative);
+                        }
+                        let le = format_labels(labels, Some(("le", "+Inf".to_string())));
+                        let _ = writeln!(out, "{}_bucket{} {}", d.name, le, h.count);
+                        let _ = writeln!(out, "{}_sum{} {}", d.name, format_labels(labels, None), format_value(h.sum));
+                        let _ = writeln!(out, "{}_count{} {}", d.name, format_labels(labels, None), h.count);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<MetricRegistry> = Mutex::new(MetricRegistry::new(METRICS));
+    static ref STARTED_AT: std::time::Instant = std::time::Instant::now();
+}
+
+/// 计数器加一
+pub fn increment_counter(name: &str, labels: &[(&str, &str)]) {
+    REGISTRY.lock().increment(name, labels, 1.0);
+}
+
+/// 设置仪表值
+pub fn set_gauge(name: &str, labels: &[(&str, &str)], value: f64) {
+    REGISTRY.lock().set(name, labels, value);
+}
+
+/// 记录直方图观测值
+pub fn observe(name: &str, labels: &[(&str, &str)], value: f64) {
+    REGISTRY.lock().observe(name, labels, value);
+}
+
+/// 记录一次带耗时的操作：计数器按结果计数，直方图记录耗时（秒）
+pub fn record_outcome(counter: &str, histogram: &str, labels: &[(&str, &str)], success: bool, elapsed: std::time::Duration) {
+    let mut registry = REGISTRY.lock();
+    let mut with_status = labels.to_vec();
+    with_status.push(("status", if success { "success" } else { "error" }));
+    registry.increment(counter, &with_status, 1.0);
+    registry.observe(histogram, labels, elapsed.as_secs_f64());
+}
+
+/// 记录应用启动时间（启动时调用，用于运行时长指标）
+pub fn mark_started() {
+    lazy_static::initialize(&STARTED_AT);
+}
+
+/// 同步外部维护的指标并导出全部指标
+pub fn render() -> String {
+    let subsystems = crate::utils::watchdog::health();
+    let mut registry = REGISTRY.lock();
+    registry.set(UPTIME_SECONDS, &[], STARTED_AT.elapsed().as_secs_f64());
+    for subsystem in &subsystems {
+        let labels = [("subsystem", subsystem.name.as_str())];
+        registry.set(SUBSYSTEM_RESTARTS_TOTAL, &labels, f64::from(subsystem.restarts));
+        let up = subsystem.status == crate::utils::watchdog::SubsystemStatus::Healthy;
+        registry.set(SUBSYSTEM_UP, &labels, if up { 1.0 } else { 0.0 });
+    }
+    registry.render()
+}
+
+/// 处理一个 HTTP 请求头，返回 (状态行, 响应体)
+///
+/// 只支持 `GET /metrics`（可带查询参数），其余路径返回 404，其他方法返回 405。
+pub fn handle_http_request(head: &str, body: impl FnOnce() -> String) -> (&'static str, String) {
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = target.split('?').next().unwrap_or_default();
+    match (method, path) {
+        ("GET", "/metrics") => ("200 OK", body()),
+        (_, "/metrics") => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+        _ => ("404 Not Found", "not found\n".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_escaped_labels() {
+        let mut registry = MetricRegistry::new(METRICS);
+        registry.increment(COMMANDS_TOTAL, &[("command", "send_message")], 1.0);
+        registry.increment(COMMANDS_TOTAL, &[("command", "send_message")], 2.0);
+        registry.increment(COMMANDS_TOTAL, &[("command", "a\"b\\c\nd")], 1.0);
+        registry.increment("zishu_undeclared_total", &[], 1.0);
+        registry.set(COMMANDS_TOTAL, &[("command", "ignored_negative")], 5.0);
+        registry.increment(COMMANDS_TOTAL, &[("command", "ignored_negative")], -1.0);
+
+        let text = registry.render();
+        assert!(text.contains("# TYPE zishu_commands_total counter\n"));
+        assert!(text.contains("zishu_commands_total{command=\"send_message\"} 3\n"));
+        assert!(text.contains("zishu_commands_total{command=\"a\\\"b\\\\c\\nd\"} 1\n"));
+        assert!(text.contains("zishu_commands_total{command=\"ignored_negative\"} 5\n"));
+        assert!(!text.contains("undeclared"));
+        // 没有数据的指标不输出
+        assert!(!text.contains(RENDER_FPS));
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut registry = MetricRegistry::new(METRICS);
+        for value in [0.003, 0.2, 0.2, 120.0] {
+            registry.observe(HTTP_REQUEST_DURATION, &[("method", "GET")], value);
+        }
+        // 类型不匹配的调用被忽略
+        registry.increment(HTTP_REQUEST_DURATION, &[("method", "GET")], 1.0);
+
+        let text = registry.render();
+        assert!(text.contains("zishu_http_request_duration_seconds_bucket{method=\"GET\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("zishu_http_request_duration_seconds_bucket{method=\"GET\",le=\"0.25\"} 3\n"));
+        assert!(text.contains("zishu_http_request_duration_seconds_bucket{method=\"GET\",le=\"60\"} 3\n"));
+        assert!(text.contains("zishu_http_request_duration_seconds_bucket{method=\"GET\",le=\"+Inf\"} 4\n"));
+        assert!(text.contains("zishu_http_request_duration_seconds_count{method=\"GET\"} 4\n"));
+        assert!(text.contains("zishu_http_request_duration_seconds_sum{method=\"GET\"} 120.403\n"));
+    }
+
+    #[test]
+    fn test_http_routing() {
+        let body = || "metrics".to_string();
+        assert_eq!(handle_http_request("GET /metrics HTTP/1.1\r\nHost: x\r\n", body), ("200 OK", "metrics".to_string()));
+        assert_eq!(handle_http_request("GET /metrics?x=1 HTTP/1.1", body).0, "200 OK");
+        assert_eq!(handle_http_request("POST /metrics HTTP/1.1", body).0, "405 Method Not Allowed");
+        assert_eq!(handle_http_request("GET / HTTP/1.1", body).0, "404 Not Found");
+        assert_eq!(handle_http_request("", body).0, "404 Not Found");
+    }
+}
@@ -0,0 +1,314 @@
+//! 移动端通知转发
+//!
+//! 把提醒、工作流失败、备份验证失败、每日总结等通知转发到手机：可选 Zishu 后端推送服务、
+//! ntfy 或 Gotify。开启端到端加密时，通知内容以 AES-256-GCM 加密后放入请求，
+//! 推送服务只能看到通用标题；手机端用配对时生成的密钥解密。
+
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::encryption::{EncryptedData, EncryptionManager};
+
+/// 加密通知在推送服务上显示的标题
+pub const ENCRYPTED_TITLE: &str = "Zishu-sensei";
+/// 加密通知在推送服务上显示的内容
+pub const ENCRYPTED_PLACEHOLDER: &str = "🔒 你有一条新消息";
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_summary_hour() -> u32 {
+    21
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// 通知类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayCategory {
+    Reminder,
+    WorkflowFailure,
+    DailySummary,
+    /// 数据库备份或恢复演练失败
+    BackupFailure,
+    /// 设置页的测试通知，不受类别开关影响
+    Test,
+}
+
+/// 各类别开关
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayCategories {
+    #[serde(default = "default_true")]
+    pub reminders: bool,
+    #[serde(default = "default_true")]
+    pub workflow_failures: bool,
+    #[serde(default = "default_true")]
+    pub daily_summary: bool,
+    #[serde(default = "default_true")]
+    pub backup_failures: bool,
+}
+
+impl Default for RelayCategories {
+    fn default() -> Self {
+        Self { reminders: true, workflow_failures: true, daily_summary: true, backup_failures: true }
+    }
+}
+
+impl RelayCategories {
+    pub fn allows(&self, category: RelayCategory) -> bool {
+        match category {
+            RelayCategory::Reminder => self.reminders,
+            RelayCategory::WorkflowFailure => self.workflow_failures,
+            RelayCategory::DailySummary => self.daily_summary,
+            RelayCategory::BackupFailure => self.backup_failures,
+            RelayCategory::Test => true,
+        }
+    }
+}
+
+/// 推送服务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RelayProvider {
+    /// Zishu 后端推送服务（使用核心服务的登录凭据）
+    ZishuPush { device_id: String },
+    /// ntfy（访问令牌可选）
+    Ntfy {
+        #[serde(default = "default_ntfy_server")]
+        server: String,
+        topic: String,
+    },
+    /// Gotify（访问令牌为应用令牌，必填）
+    Gotify { server: String },
+}
+
+/// 转发设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelaySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider: Option<RelayProvider>,
+    /// ntfy/Gotify 访问令牌（保存在系统密钥链中，不写入配置文件）
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub categories: RelayCategories,
+    /// 每日总结的发送时间（本地小时）
+    #[serde(default = "default_summary_hour")]
+    pub daily_summary_hour: u32,
+    #[serde(default = "default_true")]
+    pub end_to_end_encryption: bool,
+}
+
+impl Default for RelaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: None,
+            access_token: None,
+            categories: RelayCategories::default(),
+            daily_summary_hour: default_summary_hour(),
+            end_to_end_encryption: true,
+        }
+    }
+}
+
+fn validate_server(server: &str) -> Result<(), String> {
+    let url = url::Url::parse(server).map_err(|e| format!("推送服务地址无效: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("推送服务地址必须是 http 或 https".to_string());
+    }
+    Ok(())
+}
+
+impl RelaySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.daily_summary_hour > 23 {
+            return Err("每日总结时间必须在 0-23 点之间".to_string());
+        }
+        match &self.provider {
+            None if self.enabled => Err("开启转发前请选择推送服务".to_string()),
+            None | Some(RelayProvider::ZishuPush { .. }) => Ok(()),
+            Some(RelayProvider::Ntfy { server, topic }) => {
+                if topic.trim().is_empty() || topic.contains('/') {
+                    return Err("ntfy 主题无效".to_string());
+                }
+                validate_server(server)
+            }
+            Some(RelayProvider::Gotify { server }) => validate_server(server),
+        }
+    }
+}
+
+/// 待转发的通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayNotification {
+    pub category: RelayCategory,
+    pub title: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+/// 构造好的 HTTP 请求（POST）
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+/// 解析配对密钥（Base64 编码的 32 字节）
+pub fn decode_key(key: &str) -> Result<[u8; 32], String> {
+    let bytes = general_purpose::STANDARD
+        .decode(key.trim())
+        .map_err(|e| format!("加密密钥格式错误: {}", e))?;
+    bytes.try_into().map_err(|_| "加密密钥长度必须为 32 字节".to_string())
+}
+
+pub fn encode_key(key: &[u8; 32]) -> String {
+    general_purpose::STANDARD.encode(key)
+}
+
+fn encrypt(notification: &RelayNotification, key: &[u8; 32]) -> Result<EncryptedData, String> {
+    let plaintext = serde_json::to_vec(notification).map_err(|e| format!("序列化通知失败: {}", e))?;
+    EncryptionManager::new(*key)
+        .encrypt(&plaintext)
+        .map_err(|e| e.to_string())
+}
+
+fn category_tag(category: RelayCategory) -> &'static str {
+    match category {
+        RelayCategory::Reminder => "reminder",
+        RelayCategory::WorkflowFailure => "workflow_failure",
+        RelayCategory::DailySummary => "daily_summary",
+        RelayCategory::BackupFailure => "backup_failure",
+        RelayCategory::Test => "test",
+    }
+}
+
+/// 按推送服务构造请求；传入 `key` 时加密通知内容
+///
+/// `backend_url` 与 `backend_token` 仅用于 Zishu 推送服务。
+pub fn prepare_request(
+    provider: &RelayProvider,
+    notification: &RelayNotification,
+    access_token: Option<&str>,
+    key: Option<&[u8; 32]>,
+    backend_url: &str,
+) -> Result<PreparedRequest, String> {
+    let envelope = key.map(|key| encrypt(notification, key)).transpose()?;
+    let (title, body) = match envelope {
+        Some(_) => (ENCRYPTED_TITLE.to_string(), ENCRYPTED_PLACEHOLDER.to_string()),
+        None => (notification.title.clone(), notification.body.clone()),
+    };
+    let bearer = access_token.map(|token| ("Authorization".to_string(), format!("Bearer {}", token)));
+
+    match provider {
+        RelayProvider::ZishuPush { device_id } => Ok(PreparedRequest {
+            url: format!("{}/api/push/notifications", backend_url.trim_end_matches('/')),
+            headers: bearer.into_iter().collect(),
+            content_type: "application/json",
+            body: serde_json::json!({
+                "device_id": device_id,
+                "category": category_tag(notification.category),
+                "title": title,
+                "body": body,
+                "encrypted_payload": envelope,
+            })
+            .to_string(),
+        }),
+        RelayProvider::Ntfy { server, topic } => {
+            let mut headers = vec![
+                ("Title".to_string(), title),
+                ("Tags".to_string(), category_tag(notification.category).to_string()),
+            ];
+            headers.extend(bearer);
+            // 加密时消息体为密文信封，手机端解密后展示
+            let body = match &envelope {
+                Some(envelope) => serde_json::to_string(envelope).map_err(|e| e.to_string())?,
+                None => body,
+            };
+            Ok(PreparedRequest {
+                url: format!("{}/{}", server.trim_end_matches('/'), topic.trim()),
+                headers,
+                content_type: "text/plain; charset=utf-8",
+                body,
+            })
+        }
+        RelayProvider::Gotify { server } => {
+            let token = access_token.ok_or("Gotify 需要应用令牌")?;
+            Ok(PreparedRequest {
+                url: format!("{}/message", server.trim_end_matches('/')),
+                headers: vec![("X-Gotify-Key".to_string(), token.to_string())],
+                content_type: "application/json",
+                body: serde_json::json!({
+                    "title": title,
+                    "message": body,
+                    "priority": 5,
+                    "extras": { "zishu::encrypted_payload": envelope },
+                })
+                .to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification() -> RelayNotification {
+        RelayNotification {
+            category: RelayCategory::WorkflowFailure,
+            title: "工作流失败".to_string(),
+            body: "nightly-build 执行失败".to_string(),
+            created_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_ntfy_plain_request() {
+        let provider = RelayProvider::Ntfy { server: "https://ntfy.example.com/".to_string(), topic: "pet".to_string() };
+        let request = prepare_request(&provider, &notification(), Some("tk"), None, "").unwrap();
+        assert_eq!(request.url, "https://ntfy.example.com/pet");
+        assert_eq!(request.body, "nightly-build 执行失败");
+        assert!(request.headers.contains(&("Title".to_string(), "工作流失败".to_string())));
+        assert!(request.headers.contains(&("Authorization".to_string(), "Bearer tk".to_string())));
+    }
+
+    #[test]
+    fn test_encrypted_payload_hides_content_and_round_trips() {
+        let key = [7u8; 32];
+        let provider = RelayProvider::Gotify { server: "https://gotify.example.com".to_string() };
+        let request = prepare_request(&provider, &notification(), Some("app"), Some(&key), "").unwrap();
+        assert!(!request.body.contains("nightly-build"));
+
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["message"], ENCRYPTED_PLACEHOLDER);
+        let envelope: EncryptedData = serde_json::from_value(body["extras"]["zishu::encrypted_payload"].clone()).unwrap();
+        let plaintext = EncryptionManager::new(key).decrypt(&envelope).unwrap();
+        let decrypted: RelayNotification = serde_json::from_slice(&plaintext).unwrap();
+        assert_eq!(decrypted, notification());
+
+        assert_eq!(decode_key(&encode_key(&key)).unwrap(), key);
+        assert!(decode_key("c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let mut settings = RelaySettings { enabled: true, ..Default::default() };
+        assert!(settings.validate().is_err());
+        settings.provider = Some(RelayProvider::Ntfy { server: default_ntfy_server(), topic: "a/b".to_string() });
+        assert!(settings.validate().is_err());
+        settings.provider = Some(RelayProvider::Ntfy { server: default_ntfy_server(), topic: "zishu".to_string() });
+        assert!(settings.validate().is_ok());
+        assert!(!RelayCategories { reminders: false, ..Default::default() }.allows(RelayCategory::Reminder));
+        assert!(RelayCategories { reminders: false, workflow_failures: false, daily_summary: false, backup_failures: false }.allows(RelayCategory::Test));
+    }
+}
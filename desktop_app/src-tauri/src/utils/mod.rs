@@ -1,9 +1,11 @@
 pub mod config;
+pub mod config_timeline;
 pub mod bridge;
 pub mod logger;
 pub mod file_system;
 pub mod file_preview;
 pub mod encryption;
+pub mod keyring_store;
 pub mod key_manager;
 pub mod security_audit;
 pub mod data_masking;
@@ -15,10 +17,75 @@ pub mod update_manager;
 pub mod region_detector;
 pub mod region_formatter;
 pub mod startup_manager;
+pub mod theme_contrast;
+pub mod clock;
+pub mod data_migration;
+pub mod disk_guard;
+pub mod conversation_export;
+pub mod language_detector;
+pub mod hotword;
+pub mod audio_routing;
+pub mod topic_clustering;
+pub mod seasonal_content;
+pub mod adapter_cache;
+pub mod automation_rpc;
+pub mod mobile_relay;
+pub mod safe_mode;
+pub mod watchdog;
+pub mod metric_registry;
+pub mod resource_budget;
+pub mod window_layout;
+pub mod character_binding;
+pub mod cost_preview;
+pub mod event_journal;
+pub mod workflow_export;
+pub mod workflow_trace;
+pub mod event_subscription;
+pub mod live2d_watcher;
+pub mod lipsync;
+pub mod sleep_learning;
+pub mod pet_interaction;
+pub mod kiosk;
+pub mod speech_queue;
+pub mod tts;
+pub mod llm_middleware;
+pub mod workflow_schedule;
+pub mod pet_placement;
+pub mod model_parameters;
+pub mod profile_archive;
+pub mod incognito;
+pub mod tray_menu;
+pub mod crash_reporter;
+pub mod theme_package;
+pub mod screen_capture;
+pub mod settings_journal;
+pub mod delta_update;
+pub mod profiles;
+pub mod oauth;
+pub mod knowledge_base;
+pub mod window_dock;
+pub mod local_inference;
+pub mod focus_timer;
+pub mod market_license;
+pub mod clipboard_history;
+pub mod emotion_engine;
+pub mod remote_backup;
+pub mod voice_activity;
+pub mod adapter_dev;
+pub mod click_through;
+pub mod i18n;
+pub mod device_sync;
+pub mod log_anonymizer;
+pub mod startup_profiler;
+pub mod resource_accounting;
+pub mod prompt_template;
 
 pub use config::{
     get_app_log_dir,
     get_app_data_dir,
+    get_profile_data_dir,
+    default_app_data_dir,
+    set_data_dir_override,
     get_config_file_path,
     get_config_backup_path,
     load_config,
@@ -0,0 +1,135 @@
+//! # 模型参数分层
+//!
+//! 温度、Top-P 与最大 token 数按以下顺序逐层覆盖（后者优先）：
+//!
+//! 1. 默认模型配置（没有时使用内置默认值）
+//! 2. 请求携带的参数（前端按全局模型配置填写）
+//! 3. 当前角色绑定的模型配置
+//! 4. 会话覆盖（随会话保存）
+//!
+//! 每个参数单独解析，并记录最终值来自哪一层。读写会话覆盖见 `commands::session_parameters`。
+
+use serde::{Deserialize, Serialize};
+
+/// 一层参数（未设置的项沿用下一层）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParameterOverrides {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+impl ParameterOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.temperature.is_none() && self.top_p.is_none() && self.max_tokens.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err("温度必须在 0 到 2 之间".to_string());
+            }
+        }
+        if let Some(top_p) = self.top_p {
+            if top_p <= 0.0 || top_p > 1.0 {
+                return Err("Top-P 必须大于 0 且不超过 1".to_string());
+            }
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            if !(1..=128_000).contains(&max_tokens) {
+                return Err("最大 token 数必须在 1 到 128000 之间".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 参数来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterSource {
+    Default,
+    Request,
+    Character,
+    Session,
+}
+
+/// 解析后的参数及其来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveParameters {
+    pub temperature: f32,
+    pub top_p: f32,
+    pub max_tokens: u32,
+    pub temperature_source: ParameterSource,
+    pub top_p_source: ParameterSource,
+    pub max_tokens_source: ParameterSource,
+}
+
+impl EffectiveParameters {
+    /// 以完整的默认值为底层
+    pub fn from_defaults(temperature: f32, top_p: f32, max_tokens: u32) -> Self {
+        Self {
+            temperature,
+            top_p,
+            max_tokens,
+            temperature_source: ParameterSource::Default,
+            top_p_source: ParameterSource::Default,
+            max_tokens_source: ParameterSource::Default,
+        }
+    }
+
+    /// 叠加一层，已设置的项覆盖当前值
+    pub fn apply(mut self, source: ParameterSource, layer: &ParameterOverrides) -> Self {
+        if let Some(temperature) = layer.temperature {
+            self.temperature = temperature;
+            self.temperature_source = source;
+        }
+        if let Some(top_p) = layer.top_p {
+            self.top_p = top_p;
+            self.top_p_source = source;
+        }
+        if let Some(max_tokens) = layer.max_tokens {
+            self.max_tokens = max_tokens;
+            self.max_tokens_source = source;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_override_per_parameter() {
+        let request = ParameterOverrides { temperature: Some(0.7), top_p: Some(0.9), max_tokens: Some(2048) };
+        let character = ParameterOverrides { temperature: Some(1.1), ..ParameterOverrides::default() };
+        let session = ParameterOverrides { max_tokens: Some(512), ..ParameterOverrides::default() };
+
+        let effective = EffectiveParameters::from_defaults(0.5, 1.0, 1024)
+            .apply(ParameterSource::Request, &request)
+            .apply(ParameterSource::Character, &character)
+            .apply(ParameterSource::Session, &session);
+        assert_eq!((effective.temperature, effective.top_p, effective.max_tokens), (1.1, 0.9, 512));
+        assert_eq!(effective.temperature_source, ParameterSource::Character);
+        assert_eq!(effective.top_p_source, ParameterSource::Request);
+        assert_eq!(effective.max_tokens_source, ParameterSource::Session);
+
+        let untouched = EffectiveParameters::from_defaults(0.5, 1.0, 1024)
+            .apply(ParameterSource::Session, &ParameterOverrides::default());
+        assert_eq!(untouched.temperature_source, ParameterSource::Default);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(ParameterOverrides::default().is_empty());
+        assert!(ParameterOverrides::default().validate().is_ok());
+        assert!(ParameterOverrides { temperature: Some(2.5), ..Default::default() }.validate().is_err());
+        assert!(ParameterOverrides { top_p: Some(0.0), ..Default::default() }.validate().is_err());
+        assert!(ParameterOverrides { max_tokens: Some(0), ..Default::default() }.validate().is_err());
+        let parsed: ParameterOverrides = serde_json::from_str(r#"{"temperature":0.2}"#).unwrap();
+        assert_eq!(parsed.temperature, Some(0.2));
+        assert!(!parsed.is_empty());
+    }
+}
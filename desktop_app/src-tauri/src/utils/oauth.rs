@@ -0,0 +1,211 @@
+//! # OAuth2 授权码 + PKCE
+//!
+//! 社区账号登录流程（RFC 7636）：
+//!
+//! 1. 生成 `code_verifier` 与 S256 `code_challenge`，以及防 CSRF 的 `state`
+//! 2. 在 `127.0.0.1` 的随机端口监听回调，用系统浏览器打开授权页
+//! 3. 回调收到 `code` 后校验 `state`，用 `code_verifier` 换取令牌
+//! 4. 令牌加密保存，访问令牌快过期时用刷新令牌自动续期
+//!
+//! 本模块只包含与 I/O 无关的部分，网络与存储见 `commands::auth`。
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// 客户端 ID（可用 `ZISHU_OAUTH_CLIENT_ID` 覆盖）
+pub const DEFAULT_CLIENT_ID: &str = "zishu-desktop";
+/// 申请的权限
+pub const DEFAULT_SCOPE: &str = "openid profile offline_access";
+/// 访问令牌剩余有效期少于该秒数时刷新
+pub const REFRESH_MARGIN_SECS: i64 = 60;
+/// 回调路径
+pub const CALLBACK_PATH: &str = "/callback";
+
+fn random_token(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// PKCE 参数
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        // 32 字节编码后为 43 个字符，满足 RFC 7636 的 43~128 字符要求
+        Self::from_verifier(random_token(32))
+    }
+
+    pub fn from_verifier(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self { verifier, challenge }
+    }
+}
+
+pub fn generate_state() -> String {
+    random_token(16)
+}
+
+/// 授权服务器端点
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthEndpoints {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub revoke_url: String,
+    pub client_id: String,
+    pub scope: String,
+}
+
+impl OAuthEndpoints {
+    /// 社区平台上的标准端点
+    pub fn for_base_url(base_url: &str, client_id: &str) -> Self {
+        let base = base_url.trim_end_matches('/');
+        Self {
+            authorize_url: format!("{}/oauth/authorize", base),
+            token_url: format!("{}/oauth/token", base),
+            revoke_url: format!("{}/oauth/revoke", base),
+            client_id: client_id.to_string(),
+            scope: DEFAULT_SCOPE.to_string(),
+        }
+    }
+
+    /// 浏览器打开的授权地址
+    pub fn authorization_url(&self, redirect_uri: &str, pkce: &Pkce, state: &str) -> Result<String, String> {
+        url::Url::parse_with_params(
+            &self.authorize_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", redirect_uri),
+                ("scope", self.scope.as_str()),
+                ("state", state),
+                ("code_challenge", pkce.challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(|url| url.to_string())
+        .map_err(|e| format!("授权地址无效: {}", e))
+    }
+}
+
+/// 从回调请求行（`GET /callback?code=...&state=... HTTP/1.1`）中取出授权码
+///
+/// 路径不是回调路径时返回 `Ok(None)`（如浏览器请求 favicon）。
+pub fn parse_callback(request_line: &str, expected_state: &str) -> Result<Option<String>, String> {
+    let target = request_line.split_whitespace().nth(1).ok_or("回调请求格式错误")?;
+    let url = url::Url::parse(&format!("http://127.0.0.1{}", target)).map_err(|_| "回调请求格式错误")?;
+    if url.path() != CALLBACK_PATH {
+        return Ok(None);
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        return Err(format!("授权被拒绝: {} {}", error, description).trim_end().to_string());
+    }
+    if param("state").as_deref() != Some(expected_state) {
+        return Err("回调的 state 不匹配，已拒绝".to_string());
+    }
+    param("code").filter(|code| !code.is_empty()).map(Some).ok_or_else(|| "回调缺少授权码".to_string())
+}
+
+/// 令牌端点的响应
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+fn default_token_type() -> String {
+    "Bearer".to_string()
+}
+
+/// 加密保存的令牌
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// 访问令牌过期时间（Unix 秒），未知时为 `None`
+    pub expires_at: Option<i64>,
+    pub token_type: String,
+    pub scope: Option<String>,
+}
+
+impl OAuthTokens {
+    /// 由令牌响应生成；刷新响应没有返回新的刷新令牌时沿用旧的
+    pub fn from_response(response: TokenResponse, previous_refresh: Option<String>, now: i64) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token.or(previous_refresh),
+            expires_at: response.expires_in.map(|secs| now + secs),
+            token_type: response.token_type,
+            scope: response.scope,
+        }
+    }
+
+    pub fn needs_refresh(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at - now <= REFRESH_MARGIN_SECS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_and_authorization_url() {
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mJ92ZtTrvCtB4Q-FmFadrk8tMaT4Bk".to_string());
+        assert_eq!(pkce.challenge, "TdxKlzu2aBVrx--WNmEDfvj9X0X57W2avNi03T1sjyA");
+
+        let generated = Pkce::generate();
+        assert_eq!(generated.verifier.len(), 43);
+        assert_ne!(generated.verifier, Pkce::generate().verifier);
+
+        let endpoints = OAuthEndpoints::for_base_url("https://community.example.com/", DEFAULT_CLIENT_ID);
+        assert_eq!(endpoints.token_url, "https://community.example.com/oauth/token");
+        let url = endpoints.authorization_url("http://127.0.0.1:5000/callback", &pkce, "xyz").unwrap();
+        assert!(url.starts_with("https://community.example.com/oauth/authorize?response_type=code"));
+        assert!(url.contains("redirect_uri=http%3A%2F%2F127.0.0.1%3A5000%2Fcallback"));
+        assert!(url.contains("code_challenge=TdxKlzu2aBVrx--WNmEDfvj9X0X57W2avNi03T1sjyA"));
+        assert!(url.contains("code_challenge_method=S256"));
+    }
+
+    #[test]
+    fn test_parse_callback_and_tokens() {
+        assert_eq!(
+            parse_callback("GET /callback?code=abc&state=s1 HTTP/1.1", "s1").unwrap(),
+            Some("abc".to_string())
+        );
+        assert_eq!(parse_callback("GET /favicon.ico HTTP/1.1", "s1").unwrap(), None);
+        assert!(parse_callback("GET /callback?code=abc&state=evil HTTP/1.1", "s1").is_err());
+        assert!(parse_callback("GET /callback?error=access_denied&state=s1 HTTP/1.1", "s1").is_err());
+        assert!(parse_callback("GET /callback?state=s1 HTTP/1.1", "s1").is_err());
+
+        let response: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"a2","expires_in":3600}"#).unwrap();
+        let tokens = OAuthTokens::from_response(response, Some("r1".to_string()), 1000);
+        assert_eq!(tokens.refresh_token.as_deref(), Some("r1"));
+        assert_eq!(tokens.expires_at, Some(4600));
+        assert_eq!(tokens.token_type, "Bearer");
+        assert!(!tokens.needs_refresh(1000));
+        assert!(tokens.needs_refresh(4600 - REFRESH_MARGIN_SECS));
+    }
+}
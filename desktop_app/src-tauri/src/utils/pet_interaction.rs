@@ -0,0 +1,378 @@
+//! # 多角色互动
+//!
+//! 同时运行多个角色实例（每个实例一个窗口）时，角色之间会偶尔互动：
+//!
+//! - 同步动作：两个角色同时播放共有的动作
+//! - 简短对白：由 LLM 生成两人之间的几句对话，按顺序在各自的气泡中显示
+//!
+//! [`InteractionEngine`] 维护实例名册与限流状态（全局间隔、每对角色的冷却、每小时上限），
+//! 同一时间只进行一个互动，忙碌（如正在聊天）的实例不参与。互动的调度与投递在
+//! `commands::pet_interaction`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_check_interval_secs() -> u64 {
+    60
+}
+
+fn default_chance() -> f64 {
+    0.25
+}
+
+fn default_min_interval_secs() -> u64 {
+    10 * 60
+}
+
+fn default_pair_cooldown_secs() -> u64 {
+    30 * 60
+}
+
+fn default_max_per_hour() -> usize {
+    3
+}
+
+fn default_max_exchange_lines() -> usize {
+    4
+}
+
+/// 对白每行的基础停留时间与每个字符增加的时间（毫秒）
+const LINE_BASE_MS: u64 = 800;
+const LINE_PER_CHAR_MS: u64 = 120;
+const LINE_MAX_MS: u64 = 5000;
+/// 对白每行的字符数上限
+const MAX_LINE_CHARS: usize = 40;
+
+/// 互动设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PetInteractionSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 检查是否触发互动的间隔（秒）
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// 每次检查触发互动的概率（0–1）
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+    /// 两次互动之间的最小间隔（秒）
+    #[serde(default = "default_min_interval_secs")]
+    pub min_interval_secs: u64,
+    /// 同一对角色再次互动前的冷却（秒）
+    #[serde(default = "default_pair_cooldown_secs")]
+    pub pair_cooldown_secs: u64,
+    /// 每小时互动次数上限
+    #[serde(default = "default_max_per_hour")]
+    pub max_per_hour: usize,
+    /// 是否生成对白（关闭时只进行同步动作）
+    #[serde(default = "default_true")]
+    pub allow_dialogue: bool,
+    /// 对白行数上限
+    #[serde(default = "default_max_exchange_lines")]
+    pub max_exchange_lines: usize,
+}
+
+impl Default for PetInteractionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: default_check_interval_secs(),
+            chance: default_chance(),
+            min_interval_secs: default_min_interval_secs(),
+            pair_cooldown_secs: default_pair_cooldown_secs(),
+            max_per_hour: default_max_per_hour(),
+            allow_dialogue: true,
+            max_exchange_lines: default_max_exchange_lines(),
+        }
+    }
+}
+
+impl PetInteractionSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=3600).contains(&self.check_interval_secs) {
+            return Err("检查间隔必须在 10 秒到 1 小时之间".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.chance) {
+            return Err("触发概率必须在 0 到 1 之间".to_string());
+        }
+        if self.max_per_hour == 0 {
+            return Err("每小时互动次数至少为 1".to_string());
+        }
+        if !(2..=10).contains(&self.max_exchange_lines) {
+            return Err("对白行数必须在 2 到 10 之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 运行中的角色实例
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PetInstance {
+    pub window_label: String,
+    pub character_id: String,
+    /// 显示名（用于对白）
+    pub name: String,
+    pub motions: Vec<String>,
+    /// 忙碌的实例（正在聊天、播放语音等）不参与互动
+    pub busy: bool,
+}
+
+/// 对白中的一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueLine {
+    /// 说话的实例窗口
+    pub speaker: String,
+    pub text: String,
+    /// 相对互动开始的显示时间（毫秒）
+    pub at_ms: u64,
+}
+
+/// 互动内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InteractionPlan {
+    SyncMotion { motion: String },
+    Exchange { lines: Vec<DialogueLine> },
+}
+
+impl InteractionPlan {
+    /// 互动持续时间（毫秒）
+    pub fn duration_ms(&self) -> u64 {
+        match self {
+            InteractionPlan::SyncMotion { .. } => 3000,
+            InteractionPlan::Exchange { lines } => lines
+                .last()
+                .map_or(0, |line| line.at_ms + line_duration_ms(&line.text)),
+        }
+    }
+}
+
+/// 互动名册与限流状态
+#[derive(Debug, Default)]
+pub struct InteractionEngine {
+    instances: BTreeMap<String, PetInstance>,
+    last_interaction: Option<i64>,
+    pair_last: HashMap<(String, String), i64>,
+    /// 最近一小时内的互动时间
+    recent: VecDeque<i64>,
+    /// 进行中的互动 ID
+    active: Option<String>,
+}
+
+fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+impl InteractionEngine {
+    /// 注册（或更新）实例
+    pub fn register(&mut self, instance: PetInstance) {
+        self.instances.insert(instance.window_label.clone(), instance);
+    }
+
+    pub fn unregister(&mut self, window_label: &str) -> bool {
+        self.instances.remove(window_label).is_some()
+    }
+
+    pub fn set_busy(&mut self, window_label: &str, busy: bool) -> bool {
+        match self.instances.get_mut(window_label) {
+            Some(instance) => {
+                instance.busy = busy;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn instances(&self) -> Vec<PetInstance> {
+        self.instances.values().cloned().collect()
+    }
+
+    pub fn instance(&self, window_label: &str) -> Option<&PetInstance> {
+        self.instances.get(window_label)
+    }
+
+    /// 因限流不能互动时返回原因
+    pub fn rate_limited(&mut self, settings: &PetInteractionSettings, now: i64) -> Option<&'static str> {
+        while self.recent.front().is_some_and(|&t| now - t >= 3600) {
+            self.recent.pop_front();
+        }
+        if self.active.is_some() {
+            Some("已有互动正在进行")
+        } else if self.last_interaction.is_some_and(|t| now - t < settings.min_interval_secs as i64) {
+            Some("距离上次互动时间太短")
+        } else if self.recent.len() >= settings.max_per_hour {
+            Some("已达到每小时互动上限")
+        } else {
+            None
+        }
+    }
+
+    /// 不忙碌且不在冷却中的实例对
+    pub fn eligible_pairs(&self, settings: &PetInteractionSettings, now: i64) -> Vec<(String, String)> {
+        let idle: Vec<&PetInstance> = self.instances.values().filter(|i| !i.busy).collect();
+        let mut pairs = Vec::new();
+        for (index, a) in idle.iter().enumerate() {
+            for b in &idle[index + 1..] {
+                let key = pair_key(&a.window_label, &b.window_label);
+                let cooling = self
+                    .pair_last
+                    .get(&key)
+                    .is_some_and(|&t| now - t < settings.pair_cooldown_secs as i64);
+                if !cooling {
+                    pairs.push(key);
+                }
+            }
+        }
+        pairs
+    }
+
+    /// 开始互动并记录限流状态
+    pub fn begin(&mut self, interaction_id: &str, a: &str, b: &str, now: i64) {
+        self.active = Some(interaction_id.to_string());
+        self.last_interaction = Some(now);
+        self.pair_last.insert(pair_key(a, b), now);
+        self.recent.push_back(now);
+    }
+
+    pub fn finish(&mut self, interaction_id: &str) {
+        if self.active.as_deref() == Some(interaction_id) {
+            self.active = None;
+        }
+    }
+}
+
+/// 两个角色共有的动作（排除待机动作），按 `pick` 选择一个
+pub fn shared_motion(a: &PetInstance, b: &PetInstance, pick: usize) -> Option<String> {
+    let shared: Vec<&String> = a
+        .motions
+        .iter()
+        .filter(|motion| b.motions.contains(motion) && !motion.to_lowercase().starts_with("idle"))
+        .collect();
+    if shared.is_empty() {
+        return None;
+    }
+    Some(shared[pick % shared.len()].clone())
+}
+
+fn line_duration_ms(text: &str) -> u64 {
+    (LINE_BASE_MS + LINE_PER_CHAR_MS * text.chars().count() as u64).min(LINE_MAX_MS)
+}
+
+/// 按顺序排定对白的显示时间
+fn schedule(lines: Vec<(String, String)>) -> Vec<DialogueLine> {
+    let mut at_ms = 0;
+    lines
+        .into_iter()
+        .map(|(speaker, text)| {
+            let line = DialogueLine { speaker, text, at_ms };
+            at_ms += line_duration_ms(&line.text);
+            line
+        })
+        .collect()
+}
+
+/// 解析 LLM 生成的对白（每行「名字：台词」），无法对应到两位角色的行会被丢弃
+pub fn parse_exchange(text: &str, a: &PetInstance, b: &PetInstance, max_lines: usize) -> Vec<DialogueLine> {
+    let lines = text
+        .lines()
+        .filter_map(|line| {
+            let (name, content) = line.trim().split_once(['：', ':'])?;
+            let name = name.trim().trim_matches(|c| c == '*' || c == '「' || c == '」');
+            let speaker = [a, b].into_iter().find(|instance| instance.name == name)?;
+            let content: String = content
+                .trim()
+                .trim_matches(|c| c == '"' || c == '“' || c == '”' || c == '「' || c == '」')
+                .chars()
+                .take(MAX_LINE_CHARS)
+                .collect();
+            (!content.is_empty()).then(|| (speaker.window_label.clone(), content))
+        })
+        .take(max_lines)
+        .collect();
+    schedule(lines)
+}
+
+/// LLM 不可用时的简单对白
+pub fn fallback_exchange(a: &PetInstance, b: &PetInstance) -> Vec<DialogueLine> {
+    schedule(vec![
+        (a.window_label.clone(), format!("{}，你在做什么呀？", b.name)),
+        (b.window_label.clone(), "在陪主人呢～".to_string()),
+        (a.window_label.clone(), "那我们一起加油吧！".to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(label: &str, name: &str, motions: &[&str]) -> PetInstance {
+        PetInstance {
+            window_label: label.to_string(),
+            character_id: name.to_lowercase(),
+            name: name.to_string(),
+            motions: motions.iter().map(|m| m.to_string()).collect(),
+            busy: false,
+        }
+    }
+
+    #[test]
+    fn test_rate_limits_and_pair_cooldown() {
+        let settings = PetInteractionSettings::default();
+        let mut engine = InteractionEngine::default();
+        engine.register(instance("main", "Hiyori", &[]));
+        engine.register(instance("pet-1", "Shizuku", &[]));
+        engine.register(instance("pet-2", "Haru", &[]));
+        assert_eq!(engine.eligible_pairs(&settings, 0).len(), 3);
+
+        engine.set_busy("pet-2", true);
+        assert_eq!(engine.eligible_pairs(&settings, 0), [("main".to_string(), "pet-1".to_string())]);
+        assert!(engine.rate_limited(&settings, 0).is_none());
+
+        engine.begin("i-1", "pet-1", "main", 0);
+        assert!(engine.rate_limited(&settings, 10).is_some());
+        engine.finish("i-1");
+        assert!(engine.rate_limited(&settings, 60).is_some());
+        assert!(engine.rate_limited(&settings, 601).is_none());
+        // 这对角色仍在冷却中
+        assert!(engine.eligible_pairs(&settings, 601).is_empty());
+        engine.set_busy("pet-2", false);
+        assert_eq!(engine.eligible_pairs(&settings, 601).len(), 2);
+
+        engine.begin("i-2", "main", "pet-2", 700);
+        engine.finish("i-2");
+        engine.begin("i-3", "pet-1", "pet-2", 1400);
+        engine.finish("i-3");
+        assert_eq!(engine.rate_limited(&settings, 2100), Some("已达到每小时互动上限"));
+        assert!(engine.rate_limited(&settings, 3700).is_none());
+
+        assert!(engine.unregister("pet-2"));
+        assert_eq!(engine.instances().len(), 2);
+    }
+
+    #[test]
+    fn test_plans_motion_and_dialogue() {
+        let a = instance("main", "Hiyori", &["Idle", "TapBody", "Wave"]);
+        let b = instance("pet-1", "Shizuku", &["Idle", "Wave"]);
+        assert_eq!(shared_motion(&a, &b, 7).as_deref(), Some("Wave"));
+        assert_eq!(shared_motion(&a, &instance("pet-2", "Haru", &["Idle"]), 0), None);
+
+        let text = "Hiyori：今天天气真好！\n旁白：两人看向窗外\nShizuku: 「要不要出去走走？」\n\nHiyori：好呀";
+        let lines = parse_exchange(text, &a, &b, 4);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].speaker, "pet-1");
+        assert_eq!(lines[1].text, "要不要出去走走？");
+        assert_eq!(lines[0].at_ms, 0);
+        assert!(lines[1].at_ms > 0 && lines[2].at_ms > lines[1].at_ms);
+
+        let plan = InteractionPlan::Exchange { lines };
+        assert!(plan.duration_ms() > 0);
+        assert_eq!(parse_exchange(text, &a, &b, 2).len(), 2);
+    }
+}
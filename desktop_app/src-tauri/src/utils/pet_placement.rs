@@ -0,0 +1,220 @@
+//! # 桌宠摆放
+//!
+//! 记住桌宠所在的显示器以及它在该显示器上的相对位置，并在多显示器之间迁移：
+//!
+//! - 相对位置按可移动范围的比例保存（0 为左 / 上边缘，1 为右 / 下边缘），
+//!   换到分辨率或缩放不同的显示器时仍停在相同的角落
+//! - 窗口大小按逻辑像素保存，DPI 变化时按新的缩放因子换算，角色不会忽大忽小
+//! - 漫游模式下桌宠每隔一段时间换到下一个显示器
+//!
+//! 本模块只包含几何计算，窗口操作见 `commands::pet_placement`。
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::window_layout::Rect;
+
+/// 摆放模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementMode {
+    /// 停留在指定的显示器
+    #[default]
+    Pinned,
+    /// 定时换到下一个显示器
+    Roam,
+}
+
+/// 摆放设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlacementSettings {
+    pub mode: PlacementMode,
+    /// 漫游间隔（秒）
+    pub roam_interval_secs: u64,
+}
+
+impl Default for PlacementSettings {
+    fn default() -> Self {
+        Self {
+            mode: PlacementMode::Pinned,
+            roam_interval_secs: 15 * 60,
+        }
+    }
+}
+
+impl PlacementSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(60..=24 * 3600).contains(&self.roam_interval_secs) {
+            return Err("漫游间隔必须在 1 分钟到 24 小时之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 显示器（物理像素）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Screen {
+    pub name: Option<String>,
+    pub rect: Rect,
+    pub scale_factor: f64,
+    pub primary: bool,
+}
+
+/// 桌宠的摆放状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PetPlacement {
+    /// 所在显示器名称
+    pub monitor: Option<String>,
+    /// 在可移动范围内的相对位置（0..=1）
+    pub anchor_x: f64,
+    pub anchor_y: f64,
+    /// 窗口逻辑尺寸，尚未记录时为 0
+    pub logical_width: f64,
+    pub logical_height: f64,
+}
+
+impl Default for PetPlacement {
+    fn default() -> Self {
+        // 默认在右下角
+        Self {
+            monitor: None,
+            anchor_x: 1.0,
+            anchor_y: 1.0,
+            logical_width: 0.0,
+            logical_height: 0.0,
+        }
+    }
+}
+
+fn overlap_area(a: &Rect, b: &Rect) -> i64 {
+    let width = (a.right().min(b.right()) - a.x.max(b.x)).max(0) as i64;
+    let height = (a.bottom().min(b.bottom()) - a.y.max(b.y)).max(0) as i64;
+    width * height
+}
+
+/// 窗口所在的显示器：重叠面积最大的一个，完全不重叠时取中心距离最近的
+pub fn screen_containing<'a>(screens: &'a [Screen], window: &Rect) -> Option<&'a Screen> {
+    let best = screens
+        .iter()
+        .map(|screen| (overlap_area(&screen.rect, window), screen))
+        .filter(|(area, _)| *area > 0)
+        .max_by_key(|(area, _)| *area)
+        .map(|(_, screen)| screen);
+    best.or_else(|| {
+        let (cx, cy) = (window.x + window.width / 2, window.y + window.height / 2);
+        screens.iter().min_by_key(|screen| {
+            let dx = (screen.rect.x + screen.rect.width / 2 - cx) as i64;
+            let dy = (screen.rect.y + screen.rect.height / 2 - cy) as i64;
+            dx * dx + dy * dy
+        })
+    })
+}
+
+/// 按名称查找显示器，找不到时使用主显示器（再不然第一个）
+pub fn resolve_screen<'a>(screens: &'a [Screen], name: Option<&str>) -> Option<&'a Screen> {
+    name.and_then(|name| screens.iter().find(|screen| screen.name.as_deref() == Some(name)))
+        .or_else(|| screens.iter().find(|screen| screen.primary))
+        .or_else(|| screens.first())
+}
+
+/// 漫游的下一个显示器：按从左到右、从上到下的顺序轮换；只有一个显示器时返回 `None`
+pub fn next_roam_screen<'a>(screens: &'a [Screen], current: Option<&str>) -> Option<&'a Screen> {
+    if screens.len() < 2 {
+        return None;
+    }
+    let mut ordered: Vec<&Screen> = screens.iter().collect();
+    ordered.sort_by_key(|screen| (screen.rect.x, screen.rect.y));
+    let index = ordered
+        .iter()
+        .position(|screen| current.is_some() && screen.name.as_deref() == current);
+    Some(match index {
+        Some(index) => ordered[(index + 1) % ordered.len()],
+        None => ordered[0],
+    })
+}
+
+fn axis_anchor(offset: i32, free: i32) -> f64 {
+    if free <= 0 {
+        0.5
+    } else {
+        (offset as f64 / free as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// 窗口在显示器可移动范围内的相对位置
+pub fn anchor_in(window: &Rect, screen: &Rect) -> (f64, f64) {
+    (
+        axis_anchor(window.x - screen.x, screen.width - window.width),
+        axis_anchor(window.y - screen.y, screen.height - window.height),
+    )
+}
+
+/// 按相对位置计算窗口在显示器上的坐标（窗口比显示器大时贴左上角）
+pub fn position_in(screen: &Rect, anchor: (f64, f64), width: i32, height: i32) -> (i32, i32) {
+    let free_x = (screen.width - width).max(0) as f64;
+    let free_y = (screen.height - height).max(0) as f64;
+    (
+        screen.x + (free_x * anchor.0.clamp(0.0, 1.0)).round() as i32,
+        screen.y + (free_y * anchor.1.clamp(0.0, 1.0)).round() as i32,
+    )
+}
+
+/// 逻辑尺寸在指定缩放因子下的物理尺寸
+pub fn physical_size(logical_width: f64, logical_height: f64, scale_factor: f64) -> (i32, i32) {
+    (
+        (logical_width * scale_factor).round() as i32,
+        (logical_height * scale_factor).round() as i32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(name: &str, x: i32, width: i32, height: i32, scale_factor: f64, primary: bool) -> Screen {
+        Screen {
+            name: Some(name.to_string()),
+            rect: Rect { x, y: 0, width, height },
+            scale_factor,
+            primary,
+        }
+    }
+
+    #[test]
+    fn test_anchor_survives_monitor_change() {
+        let laptop = screen("laptop", 0, 1920, 1080, 1.0, true);
+        let external = screen("4k", 1920, 3840, 2160, 2.0, false);
+
+        // 右下角的窗口换到 4K 显示器后仍在右下角，尺寸按缩放因子换算
+        let window = Rect { x: 1920 - 300, y: 1080 - 400, width: 300, height: 400 };
+        let anchor = anchor_in(&window, &laptop.rect);
+        assert_eq!(anchor, (1.0, 1.0));
+        let (width, height) = physical_size(300.0, 400.0, external.scale_factor);
+        assert_eq!((width, height), (600, 800));
+        assert_eq!(position_in(&external.rect, anchor, width, height), (1920 + 3840 - 600, 2160 - 800));
+
+        // 超出显示器的坐标被限制在范围内
+        let outside = Rect { x: -50, y: 900, width: 300, height: 400 };
+        assert_eq!(anchor_in(&outside, &laptop.rect), (0.0, 1.0));
+        assert_eq!(position_in(&laptop.rect, (0.5, 0.5), 4000, 400), (0, 340));
+    }
+
+    #[test]
+    fn test_screen_lookup_and_roaming() {
+        let screens = vec![
+            screen("right", 1920, 1920, 1080, 1.0, false),
+            screen("left", 0, 1920, 1080, 1.25, true),
+        ];
+        let straddling = Rect { x: 1800, y: 100, width: 300, height: 300 };
+        assert_eq!(screen_containing(&screens, &straddling).unwrap().name.as_deref(), Some("right"));
+        let lost = Rect { x: -5000, y: 0, width: 100, height: 100 };
+        assert_eq!(screen_containing(&screens, &lost).unwrap().name.as_deref(), Some("left"));
+
+        assert_eq!(resolve_screen(&screens, Some("gone")).unwrap().name.as_deref(), Some("left"));
+        assert_eq!(next_roam_screen(&screens, Some("left")).unwrap().name.as_deref(), Some("right"));
+        assert_eq!(next_roam_screen(&screens, Some("right")).unwrap().name.as_deref(), Some("left"));
+        assert_eq!(next_roam_screen(&screens, None).unwrap().name.as_deref(), Some("left"));
+        assert!(next_roam_screen(&screens[..1], Some("right")).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+//! # 用户资料加密归档
+//!
+//! 导出文件为 JSON：明文头部记录格式、版本与密钥派生参数，内容先 gzip 压缩，再用
+//! 由密码派生的密钥做 AES-GCM 加密（见 [`crate::utils::encryption`]）。密码错误与
+//! 文件被篡改都会在解密时被 GCM 校验发现。
+//!
+//! 本模块只负责封装格式，收集与恢复资料见 `commands::backup`。
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+use crate::utils::encryption::{generate_salt, EncryptedData, EncryptionManager, KeyDerivationParams};
+
+/// 归档格式标识
+pub const PROFILE_FORMAT: &str = "zishu-profile";
+/// 归档格式版本
+pub const PROFILE_FORMAT_VERSION: u32 = 1;
+/// 归档文件扩展名
+pub const PROFILE_EXTENSION: &str = "zishuprofile";
+/// 导出密码最短长度
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// 加密的资料归档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileArchive {
+    pub format: String,
+    pub version: u32,
+    pub created_at: i64,
+    pub kdf: KeyDerivationParams,
+    pub payload: EncryptedData,
+}
+
+pub fn validate_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!("密码至少需要 {} 个字符", MIN_PASSWORD_LENGTH));
+    }
+    Ok(())
+}
+
+/// 压缩并加密资料内容
+pub fn seal(password: &str, plaintext: &[u8]) -> Result<ProfileArchive, String> {
+    validate_password(password)?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(plaintext)
+        .map_err(|e| format!("压缩资料失败: {}", e))?;
+    let compressed = encoder.finish().map_err(|e| format!("压缩资料失败: {}", e))?;
+
+    let kdf = KeyDerivationParams {
+        salt: generate_salt().map_err(|e| e.to_string())?,
+        ..KeyDerivationParams::default()
+    };
+    let manager = EncryptionManager::from_password(password, &kdf).map_err(|e| e.to_string())?;
+    let payload = manager.encrypt(&compressed).map_err(|e| e.to_string())?;
+
+    Ok(ProfileArchive {
+        format: PROFILE_FORMAT.to_string(),
+        version: PROFILE_FORMAT_VERSION,
+        created_at: chrono::Utc::now().timestamp(),
+        kdf,
+        payload,
+    })
+}
+
+/// 解密并解压资料内容
+pub fn open(password: &str, archive: &ProfileArchive) -> Result<Vec<u8>, String> {
+    if archive.format != PROFILE_FORMAT {
+        return Err("不是资料导出文件".to_string());
+    }
+    if archive.version > PROFILE_FORMAT_VERSION {
+        return Err(format!("导出文件版本 {} 过新，请先更新应用", archive.version));
+    }
+
+    let manager = EncryptionManager::from_password(password, &archive.kdf).map_err(|e| e.to_string())?;
+    let compressed = manager
+        .decrypt(&archive.payload)
+        .map_err(|_| "密码错误或文件已损坏".to_string())?;
+
+    let mut plaintext = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut plaintext)
+        .map_err(|e| format!("解压资料失败: {}", e))?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open_roundtrip() {
+        let content = br#"{"conversations":[],"prompts":[]}"#;
+        let archive = seal("correct horse", content).unwrap();
+        assert_eq!(archive.format, PROFILE_FORMAT);
+
+        // 经过文件序列化后仍能解开
+        let json = serde_json::to_string(&archive).unwrap();
+        let restored: ProfileArchive = serde_json::from_str(&json).unwrap();
+        assert_eq!(open("correct horse", &restored).unwrap(), content.to_vec());
+    }
+
+    #[test]
+    fn test_rejects_wrong_password_and_foreign_files() {
+        assert!(seal("short", b"data").is_err());
+
+        let archive = seal("correct horse", b"data").unwrap();
+        assert_eq!(open("wrong password", &archive).unwrap_err(), "密码错误或文件已损坏");
+
+        let foreign = ProfileArchive { format: "other".to_string(), ..archive.clone() };
+        assert!(open("correct horse", &foreign).is_err());
+        let future = ProfileArchive { version: PROFILE_FORMAT_VERSION + 1, ..archive };
+        assert!(open("correct horse", &future).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! # 用户资料（多用户 / 多人格）
+//!
+//! 同一台机器上的每个资料拥有独立的设置、聊天记录与角色配置：
+//!
+//! - 设置文件、配置时间线与设置变更日志保存在资料目录（默认资料为数据目录本身，
+//!   其他资料为 `profiles/<id>/`，见 `utils::config::get_profile_data_dir`）
+//! - 数据库表位于资料自己的 PostgreSQL schema（默认资料使用 `public`），
+//!   连接的 `search_path` 只包含该 schema，不会读到其他资料的表
+//! - 设置了密码的资料在密钥链中有独立的主密钥（`profile-<id>`），切换时用密码解锁
+//!
+//! 资料列表保存在数据目录的 `profiles.json` 中。只有管理员资料可以创建和删除资料；
+//! 创建普通资料前必须设置管理员密码，从其他资料进入管理员资料时需要验证该密码
+//! （设置了自己密码的管理员资料用自己的密码解锁）。
+
+use aes_gcm::aead::OsRng;
+use argon2::password_hash::{rand_core::RngCore, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 默认资料 ID（升级前的数据都属于该资料）
+pub const DEFAULT_PROFILE_ID: &str = "default";
+/// 资料名称最大长度
+pub const MAX_NAME_LENGTH: usize = 32;
+/// 最多可创建的资料数
+pub const MAX_PROFILES: usize = 16;
+
+const REGISTRY_FILE: &str = "profiles.json";
+
+lazy_static! {
+    static ref ACTIVE_PROFILE: RwLock<String> = RwLock::new(DEFAULT_PROFILE_ID.to_string());
+}
+
+/// 资料角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileRole {
+    /// 可以创建与删除资料
+    Admin,
+    /// 只能使用自己的资料
+    Member,
+}
+
+/// 用户资料
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub role: ProfileRole,
+    pub created_at: i64,
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+    /// 是否有密码保护的加密密钥
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+impl Profile {
+    /// 密钥链中的密钥 ID
+    pub fn key_id(&self) -> String {
+        format!("profile-{}", self.id)
+    }
+}
+
+/// 资料列表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileRegistry {
+    pub profiles: Vec<Profile>,
+    /// 当前资料
+    pub active: String,
+    /// 管理员密码（Argon2 PHC 字符串）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_password_hash: Option<String>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "默认".to_string(),
+                role: ProfileRole::Admin,
+                created_at: 0,
+                last_used_at: None,
+                encrypted: false,
+            }],
+            active: DEFAULT_PROFILE_ID.to_string(),
+            admin_password_hash: None,
+        }
+    }
+}
+
+impl ProfileRegistry {
+    pub fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.id == id)
+    }
+
+    pub fn active_profile(&self) -> Option<&Profile> {
+        self.get(&self.active)
+    }
+
+    /// 当前资料是否为管理员
+    pub fn require_admin(&self) -> Result<(), String> {
+        match self.active_profile() {
+            Some(profile) if profile.role == ProfileRole::Admin => Ok(()),
+            _ => Err("只有管理员资料可以管理资料".to_string()),
+        }
+    }
+
+    /// 设置管理员密码；已设置时需要提供当前密码
+    pub fn set_admin_password(&mut self, current: Option<&str>, password: &str) -> Result<(), String> {
+        if let Some(hash) = &self.admin_password_hash {
+            if !current.is_some_and(|current| verify_password(hash, current)) {
+                return Err("管理员密码错误".to_string());
+            }
+        }
+        self.admin_password_hash = Some(hash_password(password)?);
+        Ok(())
+    }
+
+    /// 检查是否可以切换到 `target`
+    ///
+    /// 从其他资料进入未设置自己密码的管理员资料时需要管理员密码；
+    /// 设置了密码的资料由调用方用密码解锁密钥来验证。
+    pub fn authorize_switch(&self, target: &Profile, password: Option<&str>) -> Result<(), String> {
+        if target.id == self.active || target.role != ProfileRole::Admin || target.encrypted {
+            return Ok(());
+        }
+        match &self.admin_password_hash {
+            Some(hash) => match password {
+                Some(password) if verify_password(hash, password) => Ok(()),
+                Some(_) => Err("管理员密码错误".to_string()),
+                None => Err("进入管理员资料需要管理员密码".to_string()),
+            },
+            // 现在只有设置管理员密码后才能创建普通资料；旧版本创建的普通资料在设置密码前无法验证
+            None => Ok(()),
+        }
+    }
+
+    /// 添加资料（名称不区分大小写地唯一）
+    pub fn create(&mut self, id: String, name: &str, role: ProfileRole, encrypted: bool, now: i64) -> Result<Profile, String> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!("资料名称长度必须在 1 到 {} 个字符之间", MAX_NAME_LENGTH));
+        }
+        if self.profiles.len() >= MAX_PROFILES {
+            return Err(format!("最多只能创建 {} 个资料", MAX_PROFILES));
+        }
+        if self.profiles.iter().any(|profile| profile.name.to_lowercase() == name.to_lowercase()) {
+            return Err(format!("资料名称已存在: {}", name));
+        }
+        if self.get(&id).is_some() {
+            return Err(format!("资料 ID 已存在: {}", id));
+        }
+        if role == ProfileRole::Member && self.admin_password_hash.is_none() {
+            return Err("创建普通资料前需要先设置管理员密码".to_string());
+        }
+
+        let profile = Profile { id, name: name.to_string(), role, created_at: now, last_used_at: None, encrypted };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    /// 删除资料；默认资料、当前资料与最后一个管理员不能删除
+    pub fn remove(&mut self, id: &str) -> Result<Profile, String> {
+        if id == DEFAULT_PROFILE_ID {
+            return Err("默认资料不能删除".to_string());
+        }
+        if id == self.active {
+            return Err("不能删除正在使用的资料".to_string());
+        }
+        let index = self
+            .profiles
+            .iter()
+            .position(|profile| profile.id == id)
+            .ok_or_else(|| format!("资料不存在: {}", id))?;
+        let admins = self.profiles.iter().filter(|profile| profile.role == ProfileRole::Admin).count();
+        if self.profiles[index].role == ProfileRole::Admin && admins <= 1 {
+            return Err("不能删除最后一个管理员资料".to_string());
+        }
+        Ok(self.profiles.remove(index))
+    }
+
+    /// 切换当前资料
+    pub fn activate(&mut self, id: &str, now: i64) -> Result<Profile, String> {
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| format!("资料不存在: {}", id))?;
+        profile.last_used_at = Some(now);
+        self.active = id.to_string();
+        Ok(profile.clone())
+    }
+
+    /// 启动时是否需要让用户选择资料
+    pub fn needs_selector(&self) -> bool {
+        self.profiles.len() > 1 || self.active_profile().is_some_and(|profile| profile.encrypted)
+    }
+}
+
+/// 新资料 ID（同时用于目录名与 schema 名，只含小写字母与数字）
+pub fn new_profile_id() -> String {
+    format!("p{}", &uuid::Uuid::new_v4().simple().to_string()[..12])
+}
+
+/// 资料的数据库 schema；默认资料使用 `public`
+pub fn schema_name(id: &str) -> Option<String> {
+    (id != DEFAULT_PROFILE_ID).then(|| format!("profile_{}", id))
+}
+
+/// 资料目录
+pub fn profile_dir(data_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE_ID {
+        data_dir.to_path_buf()
+    } else {
+        data_dir.join("profiles").join(id)
+    }
+}
+
+/// 为数据库连接串设置 `search_path`，使未限定 schema 的表名只解析到资料的 schema
+pub fn with_search_path(database_url: &str, schema: &str) -> String {
+    let separator = if database_url.contains('?') { '&' } else { '?' };
+    format!("{}{}options=-c%20search_path%3D{}", database_url, separator, schema)
+}
+
+/// 计算密码的 Argon2 哈希
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt = SaltString::encode_b64(&salt).map_err(|e| format!("生成盐值失败: {}", e))?;
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("计算密码哈希失败: {}", e))
+}
+
+/// 验证密码与哈希是否匹配
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+pub fn active_id() -> String {
+    ACTIVE_PROFILE.read().clone()
+}
+
+pub fn set_active_id(id: &str) {
+    *ACTIVE_PROFILE.write() = id.to_string();
+}
+
+/// 当前资料的数据库 schema
+pub fn active_schema() -> Option<String> {
+    schema_name(&active_id())
+}
+
+pub fn load(data_dir: &Path) -> ProfileRegistry {
+    fs::read_to_string(data_dir.join(REGISTRY_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<ProfileRegistry>(&json).ok())
+        .filter(|registry| registry.active_profile().is_some())
+        .unwrap_or_default()
+}
+
+pub fn save(data_dir: &Path, registry: &ProfileRegistry) -> Result<(), String> {
+    fs::create_dir_all(data_dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    let json = serde_json::to_string_pretty(registry).map_err(|e| format!("序列化资料列表失败: {}", e))?;
+    fs::write(data_dir.join(REGISTRY_FILE), json).map_err(|e| format!("写入资料列表失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_remove_and_roles() {
+        let mut registry = ProfileRegistry::default();
+        assert!(!registry.needs_selector());
+        assert!(registry.require_admin().is_ok());
+        assert!(registry.create("p0".to_string(), "访客", ProfileRole::Member, false, 5).is_err());
+        registry.set_admin_password(None, "admin-secret").unwrap();
+
+        let work = registry.create("p1".to_string(), " 工作 ", ProfileRole::Member, false, 10).unwrap();
+        assert_eq!(work.name, "工作");
+        assert!(registry.create("p2".to_string(), "工作", ProfileRole::Member, false, 11).is_err());
+        assert!(registry.create("p3".to_string(), "", ProfileRole::Member, false, 11).is_err());
+        assert!(registry.needs_selector());
+
+        registry.activate("p1", 20).unwrap();
+        assert_eq!(registry.active_profile().unwrap().last_used_at, Some(20));
+        assert!(registry.require_admin().is_err());
+        assert!(registry.remove("p1").is_err());
+
+        registry.activate(DEFAULT_PROFILE_ID, 30).unwrap();
+        assert!(registry.remove(DEFAULT_PROFILE_ID).is_err());
+        assert_eq!(registry.remove("p1").unwrap().id, "p1");
+        assert!(registry.remove("p1").is_err());
+    }
+
+    #[test]
+    fn test_paths_schema_and_persistence() {
+        let data_dir = Path::new("/data");
+        assert_eq!(profile_dir(data_dir, DEFAULT_PROFILE_ID), PathBuf::from("/data"));
+        assert_eq!(profile_dir(data_dir, "p1"), PathBuf::from("/data/profiles/p1"));
+        assert_eq!(schema_name(DEFAULT_PROFILE_ID), None);
+        assert_eq!(schema_name("p1").as_deref(), Some("profile_p1"));
+        assert_eq!(
+            with_search_path("postgresql://u@localhost/db", "profile_p1"),
+            "postgresql://u@localhost/db?options=-c%20search_path%3Dprofile_p1"
+        );
+        assert!(with_search_path("postgresql://u@localhost/db?sslmode=disable", "s").contains("disable&options="));
+
+        let id = new_profile_id();
+        assert_eq!(id.len(), 13);
+        assert!(id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path()), ProfileRegistry::default());
+        let mut registry = ProfileRegistry::default();
+        registry.set_admin_password(None, "admin-secret").unwrap();
+        registry.create("p1".to_string(), "访客", ProfileRole::Member, true, 1).unwrap();
+        registry.activate("p1", 2).unwrap();
+        save(dir.path(), &registry).unwrap();
+        assert_eq!(load(dir.path()), registry);
+    }
+
+    #[test]
+    fn test_admin_password_guards_admin_profiles() {
+        let mut registry = ProfileRegistry::default();
+        let admin = registry.get(DEFAULT_PROFILE_ID).cloned().unwrap();
+        registry.set_admin_password(None, "admin-secret").unwrap();
+        assert!(registry.set_admin_password(Some("wrong"), "other-secret").is_err());
+
+        registry.create("p1".to_string(), "访客", ProfileRole::Member, false, 1).unwrap();
+        let second_admin = registry.create("p2".to_string(), "家长", ProfileRole::Admin, true, 1).unwrap();
+        registry.activate("p1", 2).unwrap();
+
+        assert!(registry.authorize_switch(&admin, None).is_err());
+        assert!(registry.authorize_switch(&admin, Some("wrong")).is_err());
+        assert!(registry.authorize_switch(&admin, Some("admin-secret")).is_ok());
+        // 设置了密码的管理员资料由自己的密钥验证
+        assert!(registry.authorize_switch(&second_admin, None).is_ok());
+
+        let member = registry.get("p1").cloned().unwrap();
+        registry.activate(DEFAULT_PROFILE_ID, 3).unwrap();
+        assert!(registry.authorize_switch(&member, None).is_ok());
+    }
+}
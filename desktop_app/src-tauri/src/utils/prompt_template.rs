@@ -0,0 +1,397 @@
+//! # Prompt 模板
+//!
+//! Prompt 内容与角色设定按模板渲染后再发给模型，语法取 Handlebars 的子集：
+//!
+//! ```text
+//! {{user_name}}                     变量
+//! {{nickname | 主人}}               变量为空时使用默认值
+//! {{#if character}}…{{else}}…{{/if}} 条件块，`{{#if !name}}` 表示变量为空时
+//! {{> 通用礼仪}}                     引用另一个 Prompt（按名称或 ID）
+//! \{{                               输出字面的 `{{`
+//! ```
+//!
+//! 内置变量见 [`BUILTIN_VARIABLES`]，自定义变量保存在 Prompt 元数据的 `variables` 中。
+//! 保存时用 [`validate`] 检查语法、被引用的 Prompt 是否存在以及是否循环引用。
+
+use chrono::{DateTime, Datelike, Local, Weekday};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 内置变量
+pub const BUILTIN_VARIABLES: [&str; 6] = ["user_name", "date", "time", "weekday", "character", "language"];
+/// 子 Prompt 的最大嵌套层数
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable { name: String, default: Option<String> },
+    If { name: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node> },
+    Include(String),
+}
+
+/// 解析中尚未闭合的条件块
+struct OpenBlock {
+    name: String,
+    negate: bool,
+    line: usize,
+    then: Vec<Node>,
+    otherwise: Option<Vec<Node>>,
+    /// 条件块之前的节点
+    parent: Vec<Node>,
+}
+
+/// 渲染结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderOutput {
+    pub text: String,
+    /// 引用了但没有值（也没有默认值）的变量
+    pub missing: Vec<String>,
+    /// 用到的子 Prompt
+    pub includes: Vec<String>,
+}
+
+/// 变量名只能包含字母、数字与下划线，且不以数字开头
+pub fn is_valid_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn line_of(template: &str, offset: usize) -> usize {
+    template[..offset].matches('\n').count() + 1
+}
+
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match nodes.last_mut() {
+        Some(Node::Text(existing)) => existing.push_str(text),
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut blocks: Vec<OpenBlock> = Vec::new();
+    let mut rest = template;
+    let mut offset = 0;
+
+    while let Some(start) = rest.find("{{") {
+        // `\{{` 输出字面的 `{{`
+        if rest[..start].ends_with('\\') {
+            push_text(&mut nodes, &rest[..start - 1]);
+            push_text(&mut nodes, "{{");
+            rest = &rest[start + 2..];
+            offset += start + 2;
+            continue;
+        }
+        push_text(&mut nodes, &rest[..start]);
+        let line = line_of(template, offset + start);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| format!("第 {} 行的 {{{{ 没有闭合", line))?;
+        let tag = rest[start + 2..start + end].trim();
+        rest = &rest[start + end + 2..];
+        offset += start + end + 2;
+
+        if let Some(condition) = tag.strip_prefix("#if") {
+            let condition = condition.trim();
+            let (negate, name) = match condition.strip_prefix('!') {
+                Some(name) => (true, name.trim()),
+                None => (false, condition),
+            };
+            if !is_valid_variable_name(name) {
+                return Err(format!("第 {} 行的条件变量名无效: {}", line, name));
+            }
+            blocks.push(OpenBlock {
+                name: name.to_string(),
+                negate,
+                line,
+                then: Vec::new(),
+                otherwise: None,
+                parent: std::mem::take(&mut nodes),
+            });
+        } else if tag == "else" {
+            let block = blocks.last_mut().ok_or_else(|| format!("第 {} 行的 {{{{else}}}} 不在条件块中", line))?;
+            if block.otherwise.is_some() {
+                return Err(format!("第 {} 行的条件块有多个 {{{{else}}}}", line));
+            }
+            block.then = std::mem::take(&mut nodes);
+            block.otherwise = Some(Vec::new());
+        } else if tag == "/if" {
+            let block = blocks.pop().ok_or_else(|| format!("第 {} 行的 {{{{/if}}}} 没有对应的 {{{{#if}}}}", line))?;
+            let (then, otherwise) = match block.otherwise {
+                Some(_) => (block.then, std::mem::take(&mut nodes)),
+                None => (std::mem::take(&mut nodes), Vec::new()),
+            };
+            nodes = block.parent;
+            nodes.push(Node::If { name: block.name, negate: block.negate, then, otherwise });
+        } else if let Some(include) = tag.strip_prefix('>') {
+            let include = include.trim();
+            if include.is_empty() {
+                return Err(format!("第 {} 行的子 Prompt 名称为空", line));
+            }
+            nodes.push(Node::Include(include.to_string()));
+        } else {
+            let (name, default) = match tag.split_once('|') {
+                Some((name, default)) => (name.trim(), Some(default.trim().to_string())),
+                None => (tag, None),
+            };
+            if !is_valid_variable_name(name) {
+                return Err(format!("第 {} 行的变量名无效: {}", line, name));
+            }
+            nodes.push(Node::Variable { name: name.to_string(), default });
+        }
+    }
+    push_text(&mut nodes, rest);
+
+    if let Some(block) = blocks.last() {
+        return Err(format!("第 {} 行的 {{{{#if {}}}}} 缺少 {{{{/if}}}}", block.line, block.name));
+    }
+    Ok(nodes)
+}
+
+fn is_truthy(value: Option<&String>) -> bool {
+    value.is_some_and(|value| {
+        let value = value.trim();
+        !value.is_empty() && !matches!(value.to_ascii_lowercase().as_str(), "false" | "0" | "no" | "off")
+    })
+}
+
+struct Renderer<'a> {
+    variables: &'a BTreeMap<String, String>,
+    resolve: &'a dyn Fn(&str) -> Option<String>,
+    missing: BTreeSet<String>,
+    includes: BTreeSet<String>,
+    /// 正在渲染的子 Prompt，用于发现循环引用
+    stack: Vec<String>,
+}
+
+impl Renderer<'_> {
+    fn render_nodes(&mut self, nodes: &[Node], output: &mut String) -> Result<(), String> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Variable { name, default } => {
+                    match (self.variables.get(name).filter(|value| !value.is_empty()), default) {
+                        (Some(value), _) => output.push_str(value),
+                        (None, Some(default)) => output.push_str(default),
+                        (None, None) => {
+                            self.missing.insert(name.clone());
+                        }
+                    }
+                }
+                Node::If { name, negate, then, otherwise } => {
+                    let branch = if is_truthy(self.variables.get(name)) != *negate { then } else { otherwise };
+                    self.render_nodes(branch, output)?;
+                }
+                Node::Include(name) => self.render_include(name, output)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn render_include(&mut self, name: &str, output: &mut String) -> Result<(), String> {
+        if self.stack.iter().any(|included| included == name) {
+            let mut chain = self.stack.clone();
+            chain.push(name.to_string());
+            return Err(format!("子 Prompt 循环引用: {}", chain.join(" → ")));
+        }
+        if self.stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(format!("子 Prompt 嵌套超过 {} 层", MAX_INCLUDE_DEPTH));
+        }
+        let content = (self.resolve)(name).ok_or_else(|| format!("找不到子 Prompt: {}", name))?;
+        let nodes = parse(&content).map_err(|e| format!("子 Prompt {} 无效: {}", name, e))?;
+        self.includes.insert(name.to_string());
+        self.stack.push(name.to_string());
+        self.render_nodes(&nodes, output)?;
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// 渲染模板；`resolve` 按名称或 ID 返回子 Prompt 的内容
+pub fn render(
+    template: &str,
+    variables: &BTreeMap<String, String>,
+    resolve: &dyn Fn(&str) -> Option<String>,
+) -> Result<RenderOutput, String> {
+    let nodes = parse(template)?;
+    let mut renderer = Renderer {
+        variables,
+        resolve,
+        missing: BTreeSet::new(),
+        includes: BTreeSet::new(),
+        stack: Vec::new(),
+    };
+    let mut text = String::new();
+    renderer.render_nodes(&nodes, &mut text)?;
+    Ok(RenderOutput {
+        text,
+        missing: renderer.missing.into_iter().collect(),
+        includes: renderer.includes.into_iter().collect(),
+    })
+}
+
+/// 检查模板语法与子 Prompt 引用（条件块的两个分支都会检查）
+pub fn validate(template: &str, resolve: &dyn Fn(&str) -> Option<String>) -> Result<(), String> {
+    fn check(nodes: &[Node], resolve: &dyn Fn(&str) -> Option<String>, stack: &mut Vec<String>) -> Result<(), String> {
+        for node in nodes {
+            match node {
+                Node::If { then, otherwise, .. } => {
+                    check(then, resolve, stack)?;
+                    check(otherwise, resolve, stack)?;
+                }
+                Node::Include(name) => {
+                    if stack.iter().any(|included| included == name) {
+                        stack.push(name.clone());
+                        return Err(format!("子 Prompt 循环引用: {}", stack.join(" → ")));
+                    }
+                    if stack.len() >= MAX_INCLUDE_DEPTH {
+                        return Err(format!("子 Prompt 嵌套超过 {} 层", MAX_INCLUDE_DEPTH));
+                    }
+                    let content = resolve(name).ok_or_else(|| format!("找不到子 Prompt: {}", name))?;
+                    let nodes = parse(&content).map_err(|e| format!("子 Prompt {} 无效: {}", name, e))?;
+                    stack.push(name.clone());
+                    check(&nodes, resolve, stack)?;
+                    stack.pop();
+                }
+                Node::Text(_) | Node::Variable { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    check(&parse(template)?, resolve, &mut Vec::new())
+}
+
+/// 模板中直接引用的变量（不含子 Prompt 中的）
+pub fn referenced_variables(template: &str) -> Result<BTreeSet<String>, String> {
+    fn collect(nodes: &[Node], names: &mut BTreeSet<String>) {
+        for node in nodes {
+            match node {
+                Node::Variable { name, .. } => {
+                    names.insert(name.clone());
+                }
+                Node::If { name, then, otherwise, .. } => {
+                    names.insert(name.clone());
+                    collect(then, names);
+                    collect(otherwise, names);
+                }
+                Node::Text(_) | Node::Include(_) => {}
+            }
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    collect(&parse(template)?, &mut names);
+    Ok(names)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "星期一",
+        Weekday::Tue => "星期二",
+        Weekday::Wed => "星期三",
+        Weekday::Thu => "星期四",
+        Weekday::Fri => "星期五",
+        Weekday::Sat => "星期六",
+        Weekday::Sun => "星期日",
+    }
+}
+
+/// 内置变量的值；没有值的变量不出现在结果中
+pub fn builtin_values(
+    now: DateTime<Local>,
+    user_name: Option<String>,
+    character: Option<String>,
+    language: Option<String>,
+) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("weekday".to_string(), weekday_name(now.weekday()).to_string()),
+    ]);
+    for (name, value) in [("user_name", user_name), ("character", character), ("language", language)] {
+        if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+            values.insert(name.to_string(), value);
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn no_includes(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_render_variables_and_conditions() {
+        let template = "你是{{character}}。{{#if user_name}}称呼用户为{{user_name}}。{{else}}称呼用户为{{nickname | 主人}}。{{/if}}\
+{{#if !formal}}语气轻松。{{/if}}今天是{{date}}，\\{{不是变量}}{{mood}}";
+
+        let output = render(template, &vars(&[("character", "紫舒"), ("date", "2024-03-01")]), &no_includes).unwrap();
+        assert_eq!(output.text, "你是紫舒。称呼用户为主人。语气轻松。今天是2024-03-01，{{不是变量}}");
+        assert_eq!(output.missing, vec!["mood".to_string()]);
+
+        let output = render(
+            template,
+            &vars(&[("character", "紫舒"), ("user_name", "小明"), ("formal", "true"), ("mood", "")]),
+            &no_includes,
+        )
+        .unwrap();
+        assert_eq!(output.text, "你是紫舒。称呼用户为小明。今天是，{{不是变量}}");
+        assert_eq!(output.missing, vec!["date".to_string(), "mood".to_string()]);
+
+        let names = referenced_variables(template).unwrap();
+        assert!(names.contains("formal") && names.contains("nickname") && !names.contains("不是变量"));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert!(render("{{user_name", &BTreeMap::new(), &no_includes).unwrap_err().contains("没有闭合"));
+        assert!(render("第一行\n{{#if a}}没有结束", &BTreeMap::new(), &no_includes).unwrap_err().contains("第 2 行"));
+        assert!(render("{{/if}}", &BTreeMap::new(), &no_includes).is_err());
+        assert!(render("{{#if a}}{{else}}{{else}}{{/if}}", &BTreeMap::new(), &no_includes).is_err());
+        assert!(render("{{用户}}", &BTreeMap::new(), &no_includes).unwrap_err().contains("变量名无效"));
+    }
+
+    #[test]
+    fn test_includes() {
+        let prompts = BTreeMap::from([
+            ("礼仪".to_string(), "请保持礼貌{{#if user_name}}，{{user_name}}{{/if}}。".to_string()),
+            ("循环甲".to_string(), "{{> 循环乙}}".to_string()),
+            ("循环乙".to_string(), "{{#if x}}{{else}}{{> 循环甲}}{{/if}}".to_string()),
+        ]);
+        let resolve = |name: &str| prompts.get(name).cloned();
+
+        let output = render("开头。{{> 礼仪}}", &vars(&[("user_name", "小明")]), &resolve).unwrap();
+        assert_eq!(output.text, "开头。请保持礼貌，小明。");
+        assert_eq!(output.includes, vec!["礼仪".to_string()]);
+
+        assert!(validate("{{> 礼仪}}", &resolve).is_ok());
+        assert!(validate("{{> 不存在}}", &resolve).unwrap_err().contains("找不到"));
+        // 循环只出现在未选中的分支中时，保存时也会被发现
+        assert!(validate("{{> 循环甲}}", &resolve).unwrap_err().contains("循环甲 → 循环乙 → 循环甲"));
+    }
+
+    #[test]
+    fn test_builtin_values() {
+        let now = Local.with_ymd_and_hms(2024, 3, 1, 9, 5, 0).unwrap();
+        let values = builtin_values(now, Some("小明".to_string()), Some(" ".to_string()), None);
+        assert_eq!(values.get("date").map(String::as_str), Some("2024-03-01"));
+        assert_eq!(values.get("time").map(String::as_str), Some("09:05"));
+        assert_eq!(values.get("weekday").map(String::as_str), Some("星期五"));
+        assert_eq!(values.get("user_name").map(String::as_str), Some("小明"));
+        assert!(!values.contains_key("character") && !values.contains_key("language"));
+    }
+}
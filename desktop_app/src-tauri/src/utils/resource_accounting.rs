@@ -0,0 +1,471 @@
+//! # 子系统资源核算
+//!
+//! 把应用的 CPU 与内存占用分摊到各子系统（Live2D 渲染、适配器、工作流、数据库连接池），
+//! 设置页据此显示资源都花在了哪里。系统监控每次采样后调用 [`record_sample`]：
+//!
+//! - 应用的子进程直接计量：登记过的进程（如开发模式的适配器进程）归入登记的子系统，
+//!   WebView 进程归入 Live2D 渲染，其余归入核心
+//! - 主进程的 CPU 按各子系统的活跃时间（[`track`] 期间）估算，合计不超过主进程的实际占用
+//! - 主进程的内存按各子系统报告的估计值分摊，剩余部分归入核心
+//!
+//! 进程内的分摊都是估算，结果中以 `estimated_cpu_percent` 单独列出。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// WebView 进程名（Windows WebView2 / Linux WebKitGTK / macOS WKWebView）
+const WEBVIEW_PROCESS_NAMES: [&str; 4] = ["msedgewebview2", "WebKitWebProcess", "WebKitNetworkProcess", "com.apple.WebKit"];
+/// 每个数据库连接在客户端占用内存的估计值
+const CONNECTION_MEMORY_BYTES: u64 = 512 * 1024;
+
+/// 资源核算的子系统
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Live2DRenderer,
+    Adapters,
+    WorkflowEngine,
+    DatabasePools,
+    /// 窗口、事件循环与未归属的其他开销
+    Core,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 5] = [
+        Subsystem::Live2DRenderer,
+        Subsystem::Adapters,
+        Subsystem::WorkflowEngine,
+        Subsystem::DatabasePools,
+        Subsystem::Core,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Subsystem::Live2DRenderer => "Live2D 渲染",
+            Subsystem::Adapters => "适配器",
+            Subsystem::WorkflowEngine => "工作流引擎",
+            Subsystem::DatabasePools => "数据库连接池",
+            Subsystem::Core => "核心",
+        }
+    }
+}
+
+/// 应用或其子进程的一次采样
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    /// 按单核计的百分比
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+}
+
+/// 归入某个子系统的进程
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+}
+
+/// 一个子系统的资源占用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubsystemUsage {
+    pub subsystem: Subsystem,
+    pub label: String,
+    /// 子进程与主进程分摊部分之和
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+    /// 其中按活跃时间从主进程估算的部分
+    pub estimated_cpu_percent: f64,
+    /// 采样间隔内的活跃时间（毫秒）
+    pub active_ms: u64,
+    /// 前端报告的显存占用（Live2D 纹理）
+    pub gpu_memory_bytes: Option<u64>,
+    pub processes: Vec<ProcessUsage>,
+}
+
+/// 各子系统的资源占用（`get_resource_breakdown`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceBreakdown {
+    pub sampled_at: i64,
+    pub interval_ms: u64,
+    /// 主进程与全部子进程合计
+    pub total_cpu_percent: f64,
+    pub total_memory_bytes: u64,
+    pub subsystems: Vec<SubsystemUsage>,
+}
+
+/// 一次核算的输入
+#[derive(Debug, Clone, Default)]
+pub struct AccountingInput {
+    pub app_pid: u32,
+    pub interval: Duration,
+    pub processes: Vec<ProcessSample>,
+    /// 各子系统在采样间隔内的活跃时间
+    pub active: BTreeMap<Subsystem, Duration>,
+    /// 各子系统在主进程中的内存估计值
+    pub memory_estimates: BTreeMap<Subsystem, u64>,
+    /// 登记过的子进程
+    pub registered: HashMap<u32, Subsystem>,
+    pub gpu_memory: BTreeMap<Subsystem, u64>,
+}
+
+/// 应用进程及其全部后代进程的 PID
+pub fn descendants(app_pid: u32, parents: &[(u32, Option<u32>)]) -> HashSet<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(pid, parent) in parents {
+        if let Some(parent) = parent {
+            children.entry(parent).or_default().push(pid);
+        }
+    }
+    let mut found = HashSet::from([app_pid]);
+    let mut pending = vec![app_pid];
+    while let Some(pid) = pending.pop() {
+        for &child in children.get(&pid).map(Vec::as_slice).unwrap_or_default() {
+            if found.insert(child) {
+                pending.push(child);
+            }
+        }
+    }
+    found
+}
+
+fn is_webview(name: &str) -> bool {
+    WEBVIEW_PROCESS_NAMES
+        .iter()
+        .any(|pattern| name.to_ascii_lowercase().starts_with(&pattern.to_ascii_lowercase()))
+}
+
+/// 按比例缩小，使合计不超过 `limit`
+fn scale_to_limit(values: &BTreeMap<Subsystem, f64>, limit: f64) -> BTreeMap<Subsystem, f64> {
+    let total: f64 = values.values().sum();
+    let factor = if total > limit && total > 0.0 { limit / total } else { 1.0 };
+    values.iter().map(|(subsystem, value)| (*subsystem, value * factor)).collect()
+}
+
+/// 把一次采样的资源占用分摊到各子系统
+pub fn attribute(input: &AccountingInput, sampled_at: i64) -> ResourceBreakdown {
+    let mut usage: BTreeMap<Subsystem, SubsystemUsage> = Subsystem::ALL
+        .iter()
+        .map(|&subsystem| {
+            let entry = SubsystemUsage {
+                subsystem,
+                label: subsystem.label().to_string(),
+                cpu_percent: 0.0,
+                memory_bytes: 0,
+                estimated_cpu_percent: 0.0,
+                active_ms: input.active.get(&subsystem).map_or(0, |active| active.as_millis() as u64),
+                gpu_memory_bytes: input.gpu_memory.get(&subsystem).copied(),
+                processes: Vec::new(),
+            };
+            (subsystem, entry)
+        })
+        .collect();
+
+    // 子进程直接计量
+    let mut main_process = None;
+    for process in &input.processes {
+        if process.pid == input.app_pid {
+            main_process = Some(process);
+            continue;
+        }
+        let subsystem = match input.registered.get(&process.pid) {
+            Some(subsystem) => *subsystem,
+            None if is_webview(&process.name) => Subsystem::Live2DRenderer,
+            None => Subsystem::Core,
+        };
+        let entry = usage.get_mut(&subsystem).expect("all subsystems are present");
+        entry.cpu_percent += process.cpu_percent;
+        entry.memory_bytes += process.memory_bytes;
+        entry.processes.push(ProcessUsage {
+            pid: process.pid,
+            name: process.name.clone(),
+            cpu_percent: process.cpu_percent,
+            memory_bytes: process.memory_bytes,
+        });
+    }
+
+    // 主进程按活跃时间与内存估计值分摊
+    if let Some(main) = main_process {
+        let interval_ms = input.interval.as_millis().max(1) as f64;
+        let active_cpu: BTreeMap<Subsystem, f64> = input
+            .active
+            .iter()
+            .filter(|(subsystem, _)| **subsystem != Subsystem::Core)
+            .map(|(subsystem, active)| (*subsystem, active.as_millis() as f64 / interval_ms * 100.0))
+            .collect();
+        let estimated = scale_to_limit(&active_cpu, main.cpu_percent);
+        let memory: BTreeMap<Subsystem, f64> = input
+            .memory_estimates
+            .iter()
+            .filter(|(subsystem, _)| **subsystem != Subsystem::Core)
+            .map(|(subsystem, bytes)| (*subsystem, *bytes as f64))
+            .collect();
+        let memory = scale_to_limit(&memory, main.memory_bytes as f64);
+
+        for (subsystem, cpu) in &estimated {
+            let entry = usage.get_mut(subsystem).expect("all subsystems are present");
+            entry.cpu_percent += cpu;
+            entry.estimated_cpu_percent = *cpu;
+        }
+        for (subsystem, bytes) in &memory {
+            usage.get_mut(subsystem).expect("all subsystems are present").memory_bytes += *bytes as u64;
+        }
+
+        let core = usage.get_mut(&Subsystem::Core).expect("all subsystems are present");
+        let remaining_cpu = (main.cpu_percent - estimated.values().sum::<f64>()).max(0.0);
+        core.cpu_percent += remaining_cpu;
+        core.estimated_cpu_percent = remaining_cpu;
+        core.memory_bytes += main.memory_bytes.saturating_sub(memory.values().sum::<f64>() as u64);
+        core.processes.push(ProcessUsage {
+            pid: main.pid,
+            name: main.name.clone(),
+            cpu_percent: main.cpu_percent,
+            memory_bytes: main.memory_bytes,
+        });
+    }
+
+    let subsystems: Vec<SubsystemUsage> = usage.into_values().collect();
+    ResourceBreakdown {
+        sampled_at,
+        interval_ms: input.interval.as_millis() as u64,
+        total_cpu_percent: subsystems.iter().map(|usage| usage.cpu_percent).sum(),
+        total_memory_bytes: subsystems.iter().map(|usage| usage.memory_bytes).sum(),
+        subsystems,
+    }
+}
+
+// ================================
+// 活跃时间计量
+// ================================
+
+/// 一个子系统的活跃时间：同时进行的多项活动按项数累计
+#[derive(Debug)]
+struct ActivityMeter {
+    active: u32,
+    accumulated: Duration,
+    last_change: Instant,
+}
+
+impl ActivityMeter {
+    fn new(now: Instant) -> Self {
+        Self { active: 0, accumulated: Duration::ZERO, last_change: now }
+    }
+
+    fn settle(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_change);
+        self.accumulated += elapsed * self.active;
+        self.last_change = now;
+    }
+
+    fn begin(&mut self, now: Instant) {
+        self.settle(now);
+        self.active += 1;
+    }
+
+    fn end(&mut self, now: Instant) {
+        self.settle(now);
+        self.active = self.active.saturating_sub(1);
+    }
+
+    /// 取出上次取出以来的活跃时间
+    fn take(&mut self, now: Instant) -> Duration {
+        self.settle(now);
+        std::mem::take(&mut self.accumulated)
+    }
+}
+
+struct Accounting {
+    meters: BTreeMap<Subsystem, ActivityMeter>,
+    memory_estimates: BTreeMap<Subsystem, u64>,
+    gpu_memory: BTreeMap<Subsystem, u64>,
+    registered: HashMap<u32, Subsystem>,
+    /// 最近一次报告的使用中数据库连接数
+    pool_in_use: usize,
+    last_sample: Option<Instant>,
+    latest: Option<ResourceBreakdown>,
+}
+
+lazy_static! {
+    static ref ACCOUNTING: Mutex<Accounting> = Mutex::new(Accounting {
+        meters: BTreeMap::new(),
+        memory_estimates: BTreeMap::new(),
+        gpu_memory: BTreeMap::new(),
+        registered: HashMap::new(),
+        pool_in_use: 0,
+        last_sample: None,
+        latest: None,
+    });
+}
+
+/// 进行中的活动，丢弃时结束计时
+pub struct ActivityGuard {
+    subsystem: Subsystem,
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        if let Some(meter) = ACCOUNTING.lock().meters.get_mut(&self.subsystem) {
+            meter.end(Instant::now());
+        }
+    }
+}
+
+/// 开始一项属于该子系统的活动
+pub fn track(subsystem: Subsystem) -> ActivityGuard {
+    let now = Instant::now();
+    ACCOUNTING
+        .lock()
+        .meters
+        .entry(subsystem)
+        .or_insert_with(|| ActivityMeter::new(now))
+        .begin(now);
+    ActivityGuard { subsystem }
+}
+
+/// 报告子系统在主进程中的内存估计值（覆盖上次的值）
+pub fn set_memory_estimate(subsystem: Subsystem, bytes: u64) {
+    ACCOUNTING.lock().memory_estimates.insert(subsystem, bytes);
+}
+
+/// 报告子系统的显存占用
+pub fn set_gpu_memory(subsystem: Subsystem, bytes: u64) {
+    ACCOUNTING.lock().gpu_memory.insert(subsystem, bytes);
+}
+
+/// 报告数据库连接池状态：采样时使用中的连接计为整个间隔内活跃，每个连接按固定值估算内存
+pub fn record_pool_usage(in_use: usize, size: usize) {
+    let mut accounting = ACCOUNTING.lock();
+    accounting.pool_in_use = in_use;
+    accounting
+        .memory_estimates
+        .insert(Subsystem::DatabasePools, size as u64 * CONNECTION_MEMORY_BYTES);
+}
+
+/// 登记属于某个子系统的子进程
+pub fn register_process(pid: u32, subsystem: Subsystem) {
+    ACCOUNTING.lock().registered.insert(pid, subsystem);
+}
+
+pub fn unregister_process(pid: u32) {
+    ACCOUNTING.lock().registered.remove(&pid);
+}
+
+/// 记录一次采样（`processes` 为应用进程及其后代），返回核算结果
+pub fn record_sample(app_pid: u32, processes: Vec<ProcessSample>) -> ResourceBreakdown {
+    let now = Instant::now();
+    let mut accounting = ACCOUNTING.lock();
+    let interval = accounting
+        .last_sample
+        .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+    accounting.last_sample = Some(now);
+
+    let alive: HashSet<u32> = processes.iter().map(|process| process.pid).collect();
+    accounting.registered.retain(|pid, _| alive.contains(pid));
+
+    let mut active: BTreeMap<Subsystem, Duration> = accounting
+        .meters
+        .iter_mut()
+        .map(|(subsystem, meter)| (*subsystem, meter.take(now)))
+        .collect();
+    *active.entry(Subsystem::DatabasePools).or_default() += interval * accounting.pool_in_use as u32;
+
+    let input = AccountingInput {
+        app_pid,
+        interval,
+        processes,
+        active,
+        memory_estimates: accounting.memory_estimates.clone(),
+        registered: accounting.registered.clone(),
+        gpu_memory: accounting.gpu_memory.clone(),
+    };
+    let breakdown = attribute(&input, Utc::now().timestamp());
+    accounting.latest = Some(breakdown.clone());
+    breakdown
+}
+
+/// 最近一次核算结果
+pub fn latest() -> Option<ResourceBreakdown> {
+    ACCOUNTING.lock().latest.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent: Option<u32>, name: &str, cpu_percent: f64, memory_mb: u64) -> ProcessSample {
+        ProcessSample { pid, parent, name: name.to_string(), cpu_percent, memory_bytes: memory_mb << 20 }
+    }
+
+    fn find(breakdown: &ResourceBreakdown, subsystem: Subsystem) -> &SubsystemUsage {
+        breakdown.subsystems.iter().find(|usage| usage.subsystem == subsystem).unwrap()
+    }
+
+    #[test]
+    fn test_descendants() {
+        let parents = [(1, None), (10, Some(1)), (11, Some(10)), (12, Some(11)), (20, Some(2)), (13, Some(10))];
+        let found = descendants(10, &parents);
+        assert_eq!(found, HashSet::from([10, 11, 12, 13]));
+    }
+
+    #[test]
+    fn test_attribute() {
+        let input = AccountingInput {
+            app_pid: 10,
+            interval: Duration::from_secs(2),
+            processes: vec![
+                process(10, Some(1), "zishu-sensei", 30.0, 300),
+                process(11, Some(10), "msedgewebview2.exe", 20.0, 200),
+                process(12, Some(10), "python", 5.0, 100),
+                process(13, Some(10), "helper", 1.0, 10),
+            ],
+            // 适配器活跃 1 秒（50%），工作流 2 秒（100%），合计超出主进程的 30%
+            active: BTreeMap::from([
+                (Subsystem::Adapters, Duration::from_secs(1)),
+                (Subsystem::WorkflowEngine, Duration::from_secs(2)),
+            ]),
+            memory_estimates: BTreeMap::from([(Subsystem::DatabasePools, 50 << 20)]),
+            registered: HashMap::from([(12, Subsystem::Adapters)]),
+            gpu_memory: BTreeMap::from([(Subsystem::Live2DRenderer, 64 << 20)]),
+        };
+
+        let breakdown = attribute(&input, 0);
+        let renderer = find(&breakdown, Subsystem::Live2DRenderer);
+        assert_eq!((renderer.cpu_percent, renderer.memory_bytes), (20.0, 200 << 20));
+        assert_eq!(renderer.gpu_memory_bytes, Some(64 << 20));
+
+        let adapters = find(&breakdown, Subsystem::Adapters);
+        assert!((adapters.estimated_cpu_percent - 10.0).abs() < 1e-9);
+        assert!((adapters.cpu_percent - 15.0).abs() < 1e-9);
+        assert!((find(&breakdown, Subsystem::WorkflowEngine).cpu_percent - 20.0).abs() < 1e-9);
+
+        let core = find(&breakdown, Subsystem::Core);
+        assert!(core.estimated_cpu_percent.abs() < 1e-9);
+        assert_eq!(core.memory_bytes, (250 << 20) + (10 << 20));
+        assert_eq!(find(&breakdown, Subsystem::DatabasePools).memory_bytes, 50 << 20);
+
+        // 分摊不改变总量
+        assert!((breakdown.total_cpu_percent - 56.0).abs() < 1e-9);
+        assert_eq!(breakdown.total_memory_bytes, 610 << 20);
+    }
+
+    #[test]
+    fn test_activity_meter_counts_overlap() {
+        let start = Instant::now();
+        let mut meter = ActivityMeter::new(start);
+        meter.begin(start);
+        meter.begin(start + Duration::from_millis(500));
+        meter.end(start + Duration::from_millis(1000));
+        // 跨越采样点的活动在两次采样中各计一部分
+        assert_eq!(meter.take(start + Duration::from_millis(1500)), Duration::from_millis(2000));
+        meter.end(start + Duration::from_millis(2000));
+        assert_eq!(meter.take(start + Duration::from_millis(3000)), Duration::from_millis(500));
+    }
+}
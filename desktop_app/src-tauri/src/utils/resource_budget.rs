@@ -0,0 +1,429 @@
+//! # 资源预算
+//!
+//! 用户为应用设定 CPU、内存与网络目标，系统监控每次采样后调用 [`record_usage`]，
+//! 预算管理器据此计算统一的资源分配：
+//!
+//! - 任一资源超出预算时暂缓后台任务（话题分析、自动备份等在 [`defer`] 处等待）
+//! - CPU 超出预算时降低渲染帧率上限；无障碍设置的帧率上限（减少动态效果）同样经由分配结果下发
+//! - CPU 严重超出或内存、网络超出预算时推迟非交互式的 LLM 调用（聊天不受影响）
+//!
+//! 压力等级带有回差，避免在阈值附近来回切换；采样值做指数平滑以忽略瞬时尖峰。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 超出预算该比例视为严重
+const CRITICAL_RATIO: f64 = 1.5;
+/// 从超出预算恢复需降到该比例以下
+const RECOVER_RATIO: f64 = 0.85;
+/// 从严重恢复到超出需降到该比例以下
+const CRITICAL_RECOVER_RATIO: f64 = 1.3;
+/// 采样平滑系数
+const SMOOTHING: f64 = 0.3;
+/// 等待预算的检查间隔
+const DEFER_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// 最长推迟时间，超过后不再等待，避免任务饿死
+pub const MAX_DEFER: Duration = Duration::from_secs(30 * 60);
+
+/// CPU 超出预算时的帧率上限
+pub const ELEVATED_MAX_FPS: u32 = 30;
+/// CPU 严重超出预算时的帧率上限
+pub const CRITICAL_MAX_FPS: u32 = 15;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_cpu_percent() -> f64 {
+    25.0
+}
+
+fn default_memory_mb() -> u64 {
+    1024
+}
+
+/// 用户设定的资源预算（0 表示不限制）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceBudget {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 应用进程 CPU 占用目标（百分比，按单核计）
+    #[serde(default = "default_cpu_percent")]
+    pub cpu_percent: f64,
+    /// 应用进程内存目标（MB）
+    #[serde(default = "default_memory_mb")]
+    pub memory_mb: u64,
+    /// 网络吞吐目标（KB/s，上下行合计）
+    #[serde(default)]
+    pub network_kbps: u64,
+}
+
+impl Default for ResourceBudget {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cpu_percent: default_cpu_percent(),
+            memory_mb: default_memory_mb(),
+            network_kbps: 0,
+        }
+    }
+}
+
+impl ResourceBudget {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.cpu_percent.is_finite() || self.cpu_percent < 0.0 {
+            return Err("CPU 预算必须是非负数".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 资源使用情况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cpu_percent: f64,
+    pub memory_mb: f64,
+    pub network_kbps: f64,
+}
+
+/// 压力等级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pressure {
+    #[default]
+    Normal,
+    /// 超出预算
+    Elevated,
+    /// 严重超出预算
+    Critical,
+}
+
+impl Pressure {
+    /// 根据使用量与预算之比计算下一等级（带回差）
+    fn next(self, ratio: f64) -> Self {
+        match self {
+            _ if ratio >= CRITICAL_RATIO => Pressure::Critical,
+            Pressure::Critical if ratio >= CRITICAL_RECOVER_RATIO => Pressure::Critical,
+            _ if ratio >= 1.0 => Pressure::Elevated,
+            Pressure::Critical | Pressure::Elevated if ratio >= RECOVER_RATIO => Pressure::Elevated,
+            _ => Pressure::Normal,
+        }
+    }
+}
+
+/// 各资源的压力等级
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PressureLevels {
+    pub cpu: Pressure,
+    pub memory: Pressure,
+    pub network: Pressure,
+}
+
+/// 资源分配结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Allocation {
+    /// 暂缓后台任务
+    pub throttle_background: bool,
+    /// 渲染帧率上限（None 表示不限制）
+    pub render_max_fps: Option<u32>,
+    /// 推迟非交互式 LLM 调用
+    pub defer_llm: bool,
+}
+
+impl Allocation {
+    /// 按压力等级分配，`fps_cap` 为预算之外的帧率上限，取两者中较低的一个
+    fn from_pressure(levels: PressureLevels, fps_cap: Option<u32>) -> Self {
+        let pressure_fps = match levels.cpu {
+            Pressure::Normal => None,
+            Pressure::Elevated => Some(ELEVATED_MAX_FPS),
+            Pressure::Critical => Some(CRITICAL_MAX_FPS),
+        };
+        let render_max_fps = match (pressure_fps, fps_cap) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            throttle_background: levels.cpu.max(levels.memory).max(levels.network) != Pressure::Normal,
+            render_max_fps,
+            defer_llm: levels.cpu == Pressure::Critical
+                || levels.memory != Pressure::Normal
+                || levels.network != Pressure::Normal,
+        }
+    }
+
+    fn allows(&self, work: WorkKind) -> bool {
+        match work {
+            WorkKind::Background => !self.throttle_background,
+            WorkKind::Llm => !self.defer_llm,
+        }
+    }
+}
+
+/// 受预算约束的工作类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkKind {
+    /// 后台定时任务
+    Background,
+    /// 非交互式 LLM 调用
+    Llm,
+}
+
+/// 正在等待预算的任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeferredWork {
+    pub name: String,
+    pub kind: WorkKind,
+    pub since: i64,
+}
+
+/// 预算状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: ResourceBudget,
+    /// 平滑后的使用量（尚未采样时为空）
+    pub usage: Option<ResourceUsage>,
+    pub pressure: PressureLevels,
+    pub allocation: Allocation,
+    pub deferred: Vec<DeferredWork>,
+    pub updated_at: Option<i64>,
+}
+
+fn ratio(usage: f64, target: f64) -> f64 {
+    if target <= 0.0 {
+        0.0
+    } else {
+        usage / target
+    }
+}
+
+/// 预算管理器
+#[derive(Debug, Default)]
+pub struct BudgetManager {
+    budget: ResourceBudget,
+    usage: Option<ResourceUsage>,
+    pressure: PressureLevels,
+    allocation: Allocation,
+    /// 预算之外的渲染帧率上限（无障碍的减少动态效果）
+    fps_cap: Option<u32>,
+    deferred: BTreeMap<(WorkKind, String), i64>,
+    updated_at: Option<i64>,
+}
+
+impl BudgetManager {
+    pub fn new(budget: ResourceBudget) -> Self {
+        Self { budget, ..Self::default() }
+    }
+
+    /// 更新预算并按当前使用量重新分配，返回变化后的分配
+    pub fn set_budget(&mut self, budget: ResourceBudget) -> Option<Allocation> {
+        self.budget = budget;
+        self.reallocate()
+    }
+
+    /// 设置预算之外的渲染帧率上限（不受预算开关影响），返回变化后的分配
+    pub fn set_fps_cap(&mut self, fps_cap: Option<u32>) -> Option<Allocation> {
+        self.fps_cap = fps_cap;
+        self.reallocate()
+    }
+
+    /// 记录一次采样，返回变化后的分配
+    pub fn record(&mut self, sample: ResourceUsage, now: i64) -> Option<Allocation> {
+        let smooth = |previous: f64, current: f64| previous + SMOOTHING * (current - previous);
+        self.usage = Some(match self.usage {
+            Some(previous) => ResourceUsage {
+                cpu_percent: smooth(previous.cpu_percent, sample.cpu_percent),
+                memory_mb: smooth(previous.memory_mb, sample.memory_mb),
+                network_kbps: smooth(previous.network_kbps, sample.network_kbps),
+            },
+            None => sample,
+        });
+        self.updated_at = Some(now);
+        self.reallocate()
+    }
+
+    fn reallocate(&mut self) -> Option<Allocation> {
+        let pressure = match (self.budget.enabled, self.usage) {
+            (true, Some(usage)) => PressureLevels {
+                cpu: self.pressure.cpu.next(ratio(usage.cpu_percent, self.budget.cpu_percent)),
+                memory: self.pressure.memory.next(ratio(usage.memory_mb, self.budget.memory_mb as f64)),
+                network: self.pressure.network.next(ratio(usage.network_kbps, self.budget.network_kbps as f64)),
+            },
+            _ => PressureLevels::default(),
+        };
+        self.pressure = pressure;
+        let allocation = Allocation::from_pressure(pressure, self.fps_cap);
+        if allocation == self.allocation {
+            return None;
+        }
+        self.allocation = allocation;
+        Some(allocation)
+    }
+
+    pub fn allocation(&self) -> Allocation {
+        self.allocation
+    }
+
+    fn begin_defer(&mut self, kind: WorkKind, name: &str, now: i64) {
+        self.deferred.entry((kind, name.to_string())).or_insert(now);
+    }
+
+    fn end_defer(&mut self, kind: WorkKind, name: &str) {
+        self.deferred.remove(&(kind, name.to_string()));
+    }
+
+    pub fn status(&self) -> BudgetStatus {
+        BudgetStatus {
+            budget: self.budget.clone(),
+            usage: self.usage,
+            pressure: self.pressure,
+            allocation: self.allocation,
+            deferred: self
+                .deferred
+                .iter()
+                .map(|((kind, name), since)| DeferredWork { name: name.clone(), kind: *kind, since: *since })
+                .collect(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+lazy_static! {
+    static ref MANAGER: RwLock<BudgetManager> = RwLock::new(BudgetManager::default());
+}
+
+/// 更新预算，返回变化后的分配
+pub fn set_budget(budget: ResourceBudget) -> Option<Allocation> {
+    MANAGER.write().set_budget(budget)
+}
+
+/// 设置预算之外的渲染帧率上限，返回变化后的分配
+pub fn set_fps_cap(fps_cap: Option<u32>) -> Option<Allocation> {
+    MANAGER.write().set_fps_cap(fps_cap)
+}
+
+/// 记录一次资源采样，返回变化后的分配
+pub fn record_usage(sample: ResourceUsage) -> Option<Allocation> {
+    let changed = MANAGER.write().record(sample, chrono::Utc::now().timestamp());
+    if let Some(allocation) = &changed {
+        info!("资源分配已调整: {:?}", allocation);
+    }
+    changed
+}
+
+/// 当前分配
+pub fn allocation() -> Allocation {
+    MANAGER.read().allocation()
+}
+
+/// 当前预算状态
+pub fn status() -> BudgetStatus {
+    MANAGER.read().status()
+}
+
+/// 等待预算允许该类工作（最长 [`MAX_DEFER`]）
+///
+/// 只用于后台与非交互式工作；返回后调用方照常执行。
+pub async fn defer(kind: WorkKind, name: &str) {
+    if allocation().allows(kind) {
+        return;
+    }
+    info!("资源超出预算，推迟 {:?} 任务: {}", kind, name);
+    MANAGER.write().begin_defer(kind, name, chrono::Utc::now().timestamp());
+    let started = std::time::Instant::now();
+    while !allocation().allows(kind) {
+        if started.elapsed() >= MAX_DEFER {
+            warn!("任务 {} 已推迟 {} 分钟，不再等待", name, MAX_DEFER.as_secs() / 60);
+            break;
+        }
+        tokio::time::sleep(DEFER_POLL_INTERVAL).await;
+    }
+    MANAGER.write().end_defer(kind, name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(cpu_percent: f64, memory_mb: f64, network_kbps: f64) -> ResourceUsage {
+        ResourceUsage { cpu_percent, memory_mb, network_kbps }
+    }
+
+    #[test]
+    fn test_pressure_hysteresis() {
+        assert_eq!(Pressure::Normal.next(0.99), Pressure::Normal);
+        assert_eq!(Pressure::Normal.next(1.0), Pressure::Elevated);
+        assert_eq!(Pressure::Elevated.next(0.9), Pressure::Elevated);
+        assert_eq!(Pressure::Elevated.next(0.8), Pressure::Normal);
+        assert_eq!(Pressure::Elevated.next(1.6), Pressure::Critical);
+        assert_eq!(Pressure::Critical.next(1.4), Pressure::Critical);
+        assert_eq!(Pressure::Critical.next(1.1), Pressure::Elevated);
+        assert_eq!(Pressure::Critical.next(0.5), Pressure::Normal);
+    }
+
+    #[test]
+    fn test_allocation_follows_pressure() {
+        let budget = ResourceBudget { enabled: true, cpu_percent: 20.0, memory_mb: 500, network_kbps: 0 };
+        let mut manager = BudgetManager::new(budget.clone());
+        assert_eq!(manager.record(usage(10.0, 200.0, 10_000.0), 0), None, "网络预算为 0 时不限制");
+
+        // CPU 超出预算：暂缓后台任务并降低帧率，但不推迟 LLM
+        let allocation = manager.record(usage(60.0, 200.0, 0.0), 1).unwrap();
+        assert!(allocation.throttle_background);
+        assert_eq!(allocation.render_max_fps, Some(ELEVATED_MAX_FPS));
+        assert!(!allocation.defer_llm);
+
+        // 平滑后继续升高到严重
+        for now in 2..10 {
+            manager.record(usage(60.0, 200.0, 0.0), now);
+        }
+        assert_eq!(manager.allocation().render_max_fps, Some(CRITICAL_MAX_FPS));
+        assert!(manager.allocation().defer_llm);
+
+        // 关闭预算后立即解除限制
+        let allocation = manager.set_budget(ResourceBudget { enabled: false, ..budget }).unwrap();
+        assert_eq!(allocation, Allocation::default());
+        assert!(manager.status().usage.is_some());
+    }
+
+    #[test]
+    fn test_memory_pressure_defers_llm_without_fps_cap() {
+        let mut manager = BudgetManager::new(ResourceBudget { memory_mb: 100, ..ResourceBudget::default() });
+        let allocation = manager.record(usage(0.0, 120.0, 0.0), 0).unwrap();
+        assert!(allocation.throttle_background);
+        assert!(allocation.defer_llm);
+        assert_eq!(allocation.render_max_fps, None);
+        assert!(!allocation.allows(WorkKind::Llm));
+        assert!(!allocation.allows(WorkKind::Background));
+
+        manager.begin_defer(WorkKind::Llm, "topic_labeling", 5);
+        manager.begin_defer(WorkKind::Llm, "topic_labeling", 9);
+        assert_eq!(manager.status().deferred.len(), 1);
+        assert_eq!(manager.status().deferred[0].since, 5);
+        manager.end_defer(WorkKind::Llm, "topic_labeling");
+        assert!(manager.status().deferred.is_empty());
+    }
+
+    #[test]
+    fn test_fps_cap_combines_with_cpu_pressure() {
+        let mut manager = BudgetManager::new(ResourceBudget { cpu_percent: 20.0, ..ResourceBudget::default() });
+        assert_eq!(manager.set_fps_cap(Some(24)).unwrap().render_max_fps, Some(24));
+        assert!(!manager.allocation().throttle_background);
+
+        // CPU 超出预算时取较低的上限
+        assert_eq!(manager.record(usage(22.0, 0.0, 0.0), 0).unwrap().render_max_fps, Some(24));
+        for now in 1..10 {
+            manager.record(usage(60.0, 0.0, 0.0), now);
+        }
+        assert_eq!(manager.allocation().render_max_fps, Some(CRITICAL_MAX_FPS));
+
+        // 关闭预算后仍保留无障碍上限
+        let allocation = manager.set_budget(ResourceBudget { enabled: false, ..ResourceBudget::default() }).unwrap();
+        assert_eq!(allocation.render_max_fps, Some(24));
+        assert_eq!(manager.set_fps_cap(None).unwrap(), Allocation::default());
+    }
+}
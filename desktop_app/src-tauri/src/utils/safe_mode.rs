@@ -0,0 +1,243 @@
+//! 安全模式
+//!
+//! 安全模式下应用以默认主题启动，不加载适配器、不响应工作流触发器，也不启动后台
+//! 任务，便于用户从损坏的主题或适配器中恢复而无需手动编辑文件。进入方式：
+//!
+//! - 命令行参数 `--safe-mode`
+//! - 崩溃对话框等处请求以安全模式重启（写入一次性请求文件）
+//! - 连续多次未正常退出后自动进入
+//!
+//! 启动流程中各组件通过 [`allows`] 询问是否继续，被跳过的组件记录在报告中。
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// 命令行参数
+pub const SAFE_MODE_FLAG: &str = "--safe-mode";
+/// 连续崩溃达到该次数后自动进入安全模式
+pub const CRASH_THRESHOLD: u32 = 3;
+/// 运行超过该时长视为启动稳定，清零崩溃计数
+pub const STABLE_AFTER_SECS: u64 = 120;
+
+const LAUNCH_STATE_FILE: &str = "launch_state.json";
+const REQUEST_FILE: &str = "safe_mode_request";
+
+/// 进入安全模式的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SafeModeReason {
+    /// 命令行参数
+    CommandLine,
+    /// 用户请求（如崩溃对话框）
+    Requested,
+    /// 连续崩溃
+    RepeatedCrashes { crashes: u32 },
+}
+
+/// 被跳过的组件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkippedKind {
+    Theme,
+    Adapter,
+    Workflow,
+    BackgroundTask,
+}
+
+/// 被跳过的组件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedComponent {
+    pub kind: SkippedKind,
+    pub name: String,
+    pub detail: Option<String>,
+}
+
+/// 安全模式报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SafeModeReport {
+    pub active: bool,
+    pub reason: Option<SafeModeReason>,
+    /// 本次启动前连续未正常退出的次数
+    pub consecutive_crashes: u32,
+    pub skipped: Vec<SkippedComponent>,
+}
+
+/// 启动状态（跨进程持久化，用于检测崩溃）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchState {
+    /// 上次启动后是否仍在运行（未正常退出）
+    pub running: bool,
+    pub consecutive_crashes: u32,
+}
+
+impl LaunchState {
+    /// 记录一次启动：上次未正常退出则计为一次崩溃
+    pub fn launched(self) -> Self {
+        let consecutive_crashes = if self.running {
+            self.consecutive_crashes.saturating_add(1)
+        } else {
+            self.consecutive_crashes
+        };
+        Self { running: true, consecutive_crashes }
+    }
+}
+
+/// 根据启动参数、重启请求与崩溃次数决定是否进入安全模式
+pub fn decide<I, S>(args: I, requested: bool, consecutive_crashes: u32) -> Option<SafeModeReason>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    if args.into_iter().any(|arg| arg.as_ref() == SAFE_MODE_FLAG) {
+        Some(SafeModeReason::CommandLine)
+    } else if requested {
+        Some(SafeModeReason::Requested)
+    } else if consecutive_crashes >= CRASH_THRESHOLD {
+        Some(SafeModeReason::RepeatedCrashes { crashes: consecutive_crashes })
+    } else {
+        None
+    }
+}
+
+lazy_static! {
+    static ref REPORT: RwLock<SafeModeReport> = RwLock::new(SafeModeReport::default());
+}
+
+fn data_dir() -> Option<PathBuf> {
+    match crate::utils::config::get_app_data_dir() {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            warn!("无法获取应用数据目录，安全模式状态不会保存: {}", e);
+            None
+        }
+    }
+}
+
+fn read_launch_state(dir: &Path) -> LaunchState {
+    fs::read_to_string(dir.join(LAUNCH_STATE_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn write_launch_state(dir: &Path, state: LaunchState) {
+    let result = fs::create_dir_all(dir).and_then(|_| {
+        let json = serde_json::to_string(&state).unwrap_or_default();
+        fs::write(dir.join(LAUNCH_STATE_FILE), json)
+    });
+    if let Err(e) = result {
+        warn!("保存启动状态失败: {}", e);
+    }
+}
+
+fn update_launch_state(update: impl FnOnce(LaunchState) -> LaunchState) {
+    if let Some(dir) = data_dir() {
+        let state = update(read_launch_state(&dir));
+        write_launch_state(&dir, state);
+    }
+}
+
+/// 记录本次启动并决定是否进入安全模式（在构建 Tauri 应用前调用）
+pub fn initialize() -> bool {
+    let (requested, crashes) = match data_dir() {
+        Some(dir) => {
+            let request_path = dir.join(REQUEST_FILE);
+            // 重启请求只生效一次
+            let requested = request_path.exists() && fs::remove_file(&request_path).is_ok();
+            let state = read_launch_state(&dir).launched();
+            write_launch_state(&dir, state);
+            (requested, state.consecutive_crashes)
+        }
+        None => (false, 0),
+    };
+
+    let reason = decide(std::env::args(), requested, crashes);
+    let active = reason.is_some();
+    if let Some(reason) = &reason {
+        warn!("以安全模式启动: {:?}", reason);
+    }
+    *REPORT.write() = SafeModeReport { active, reason, consecutive_crashes: crashes, skipped: Vec::new() };
+    active
+}
+
+/// 启动已稳定，清零崩溃计数
+pub fn mark_stable() {
+    update_launch_state(|state| LaunchState { consecutive_crashes: 0, ..state });
+}
+
+/// 记录正常退出
+pub fn mark_clean_exit() {
+    update_launch_state(|state| LaunchState { running: false, ..state });
+}
+
+/// 请求下次启动进入安全模式
+pub fn request_next_launch() -> Result<(), String> {
+    let dir = data_dir().ok_or("Failed to get app data directory")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    fs::write(dir.join(REQUEST_FILE), b"1").map_err(|e| format!("写入安全模式请求失败: {}", e))
+}
+
+pub fn is_active() -> bool {
+    REPORT.read().active
+}
+
+/// 组件是否可以启用；安全模式下返回 false 并记录到报告
+pub fn allows(kind: SkippedKind, name: &str) -> bool {
+    skip_with_detail(kind, name, None)
+}
+
+/// 同 [`allows`]，附带说明
+pub fn skip_with_detail(kind: SkippedKind, name: &str, detail: Option<String>) -> bool {
+    let mut report = REPORT.write();
+    if !report.active {
+        return true;
+    }
+    if !report.skipped.iter().any(|item| item.kind == kind && item.name == name) {
+        info!("安全模式: 跳过 {:?} {}", kind, name);
+        report.skipped.push(SkippedComponent { kind, name: name.to_string(), detail });
+    }
+    false
+}
+
+/// 安全模式下拒绝的操作返回的错误
+pub fn ensure_allowed(kind: SkippedKind, name: &str) -> Result<(), String> {
+    if skip_with_detail(kind, name, Some("运行时调用被拒绝".to_string())) {
+        Ok(())
+    } else {
+        Err(format!("安全模式下已禁用: {}", name))
+    }
+}
+
+pub fn report() -> SafeModeReport {
+    REPORT.read().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_state_counts_unclean_exits() {
+        let state = LaunchState::default().launched();
+        assert_eq!(state, LaunchState { running: true, consecutive_crashes: 0 });
+        let state = state.launched().launched();
+        assert_eq!(state.consecutive_crashes, 2);
+        let clean = LaunchState { running: false, ..state }.launched();
+        assert_eq!(clean.consecutive_crashes, 2);
+    }
+
+    #[test]
+    fn test_decide_reason_priority() {
+        assert_eq!(decide(["app", "--safe-mode"], true, 5), Some(SafeModeReason::CommandLine));
+        assert_eq!(decide(["app"], true, 5), Some(SafeModeReason::Requested));
+        assert_eq!(
+            decide(["app"], false, CRASH_THRESHOLD),
+            Some(SafeModeReason::RepeatedCrashes { crashes: CRASH_THRESHOLD })
+        );
+        assert_eq!(decide(["app"], false, CRASH_THRESHOLD - 1), None);
+    }
+}
@@ -0,0 +1,106 @@
+//! # 截图区域计算
+//!
+//! 截图命令（见 `commands::capture`）使用的坐标与显示器的 `display_info` 一致：以主显示器左上角为
+//! 原点的桌面坐标，多显示器时其他显示器的坐标可能为负。区域截图只截取区域中心所在的显示器，
+//! 超出该显示器的部分被裁掉。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 单次截图的最大边长（像素）
+pub const MAX_DIMENSION: u32 = 16384;
+
+/// 桌面坐标中的矩形区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureRegion {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err("截图区域不能为空".to_string());
+        }
+        if self.width > MAX_DIMENSION || self.height > MAX_DIMENSION {
+            return Err(format!("截图区域的边长不能超过 {} 像素", MAX_DIMENSION));
+        }
+        Ok(())
+    }
+
+    fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        x >= self.x as i64 && x < self.right() && y >= self.y as i64 && y < self.bottom()
+    }
+
+    /// 两个区域的交集
+    pub fn intersect(&self, other: &CaptureRegion) -> Option<CaptureRegion> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (right > left as i64 && bottom > top as i64).then(|| CaptureRegion {
+            x: left,
+            y: top,
+            width: (right - left as i64) as u32,
+            height: (bottom - top as i64) as u32,
+        })
+    }
+}
+
+/// 找到区域中心所在的显示器，返回显示器 ID 与相对该显示器左上角的裁剪后区域
+pub fn locate(region: &CaptureRegion, displays: &[(u32, CaptureRegion)]) -> Option<(u32, CaptureRegion)> {
+    let center_x = region.x as i64 + region.width as i64 / 2;
+    let center_y = region.y as i64 + region.height as i64 / 2;
+    let (display_id, bounds) = displays.iter().find(|(_, bounds)| bounds.contains(center_x, center_y))?;
+    let clipped = region.intersect(bounds)?;
+    Some((
+        *display_id,
+        CaptureRegion { x: clipped.x - bounds.x, y: clipped.y - bounds.y, ..clipped },
+    ))
+}
+
+/// 保存到文件管理的截图文件名，如 `screenshot-region-20240101-120000.png`
+pub fn file_name(kind: &str, captured_at: DateTime<Utc>) -> String {
+    format!("screenshot-{}-{}.png", kind, captured_at.format("%Y%m%d-%H%M%S"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(x: i32, y: i32, width: u32, height: u32) -> CaptureRegion {
+        CaptureRegion { x, y, width, height }
+    }
+
+    #[test]
+    fn test_locate_picks_display_and_clips() {
+        // 主显示器 1920x1080，副显示器在左侧
+        let displays = vec![(1, region(0, 0, 1920, 1080)), (2, region(-1280, 0, 1280, 1024))];
+
+        assert_eq!(locate(&region(100, 100, 200, 50), &displays), Some((1, region(100, 100, 200, 50))));
+        // 跨越两个显示器时以中心所在的显示器为准，超出部分被裁掉
+        assert_eq!(locate(&region(-300, 10, 400, 100), &displays), Some((2, region(980, 10, 300, 100))));
+        assert_eq!(locate(&region(1800, 1000, 200, 100), &displays), Some((1, region(1800, 1000, 120, 80))));
+        assert_eq!(locate(&region(5000, 5000, 10, 10), &displays), None);
+    }
+
+    #[test]
+    fn test_validate_and_file_name() {
+        assert!(region(0, 0, 0, 10).validate().is_err());
+        assert!(region(0, 0, MAX_DIMENSION + 1, 10).validate().is_err());
+        assert!(region(-10, -10, 10, 10).validate().is_ok());
+
+        let captured_at = DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap().with_timezone(&Utc);
+        assert_eq!(file_name("screen", captured_at), "screenshot-screen-20240102-030405.png");
+    }
+}
@@ -0,0 +1,534 @@
+//! # 季节与节日内容
+//!
+//! 角色可以配置服装/贴图变体，以及按季节、日期区间或节日触发的规则。
+//! 规则按地区生效：季节按南北半球换算，节日只在相关地区触发（如春节、感恩节），
+//! 也可以在规则上显式限定国家/地区代码。
+//!
+//! 同一天命中多条规则时，服装与表情分别取优先级最高且定义了该项的规则；
+//! 用户手动覆盖（可设截止时间）优先于所有规则。
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// 南半球国家/地区（季节与北半球相反）
+const SOUTHERN_HEMISPHERE: &[&str] = &["AU", "NZ", "AR", "BR", "CL", "UY", "PY", "BO", "PE", "ZA"];
+
+/// 春节日期（2024–2035）
+const LUNAR_NEW_YEAR: &[(i32, u32, u32)] = &[
+    (2024, 2, 10),
+    (2025, 1, 29),
+    (2026, 2, 17),
+    (2027, 2, 6),
+    (2028, 1, 26),
+    (2029, 2, 13),
+    (2030, 2, 3),
+    (2031, 1, 23),
+    (2032, 2, 11),
+    (2033, 1, 31),
+    (2034, 2, 19),
+    (2035, 2, 8),
+];
+
+/// 中秋节日期（2024–2035）
+const MID_AUTUMN: &[(i32, u32, u32)] = &[
+    (2024, 9, 17),
+    (2025, 10, 6),
+    (2026, 9, 25),
+    (2027, 9, 15),
+    (2028, 10, 3),
+    (2029, 9, 22),
+    (2030, 9, 12),
+    (2031, 10, 1),
+    (2032, 9, 19),
+    (2033, 9, 8),
+    (2034, 9, 27),
+    (2035, 9, 16),
+];
+
+/// 季节（气象季节，按月份划分）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// 某地区在某天所处的季节
+    pub fn at(date: NaiveDate, country: &str) -> Self {
+        let northern = match date.month() {
+            3..=5 => Self::Spring,
+            6..=8 => Self::Summer,
+            9..=11 => Self::Autumn,
+            _ => Self::Winter,
+        };
+        if is_southern_hemisphere(country) {
+            match northern {
+                Self::Spring => Self::Autumn,
+                Self::Summer => Self::Winter,
+                Self::Autumn => Self::Spring,
+                Self::Winter => Self::Summer,
+            }
+        } else {
+            northern
+        }
+    }
+}
+
+fn is_southern_hemisphere(country: &str) -> bool {
+    SOUTHERN_HEMISPHERE.iter().any(|c| c.eq_ignore_ascii_case(country))
+}
+
+/// 内置节日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Holiday {
+    NewYear,
+    LunarNewYear,
+    ValentinesDay,
+    Easter,
+    Tanabata,
+    MidAutumn,
+    Halloween,
+    Thanksgiving,
+    Christmas,
+}
+
+impl Holiday {
+    /// 默认触发的国家/地区（空表示所有地区）
+    pub fn regions(self) -> &'static [&'static str] {
+        match self {
+            Self::LunarNewYear => &["CN", "TW", "HK", "MO", "SG", "MY", "KR", "VN"],
+            Self::MidAutumn => &["CN", "TW", "HK", "MO", "SG", "MY", "VN"],
+            Self::Tanabata => &["JP"],
+            Self::Thanksgiving => &["US", "CA"],
+            _ => &[],
+        }
+    }
+
+    /// 某年的节日日期（区间），超出内置农历表范围时返回 None
+    pub fn dates(self, year: i32, country: &str) -> Option<(NaiveDate, NaiveDate)> {
+        let single = |month, day| NaiveDate::from_ymd_opt(year, month, day).map(|d| (d, d));
+        let from_table = |table: &[(i32, u32, u32)]| {
+            table
+                .iter()
+                .find(|(y, _, _)| *y == year)
+                .and_then(|(_, month, day)| single(*month, *day))
+        };
+        match self {
+            Self::NewYear => single(1, 1),
+            Self::LunarNewYear => from_table(LUNAR_NEW_YEAR),
+            Self::ValentinesDay => single(2, 14),
+            Self::Easter => easter_sunday(year).map(|d| (d, d)),
+            Self::Tanabata => single(7, 7),
+            Self::MidAutumn => from_table(MID_AUTUMN),
+            Self::Halloween => single(10, 31),
+            // 加拿大为十月第二个星期一，美国为十一月第四个星期四
+            Self::Thanksgiving if country.eq_ignore_ascii_case("CA") => {
+                NaiveDate::from_weekday_of_month_opt(year, 10, Weekday::Mon, 2).map(|d| (d, d))
+            }
+            Self::Thanksgiving => NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Thu, 4).map(|d| (d, d)),
+            Self::Christmas => Some((NaiveDate::from_ymd_opt(year, 12, 24)?, NaiveDate::from_ymd_opt(year, 12, 25)?)),
+        }
+    }
+}
+
+/// 复活节（格里高利历，匿名算法）
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// 月-日
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl MonthDay {
+    fn of(date: NaiveDate) -> Self {
+        Self {
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+}
+
+/// 规则触发条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SeasonalTrigger {
+    Season {
+        season: Season,
+    },
+    /// 每年的日期区间（含首尾，可以跨年，如 12-20 到 01-05）
+    DateRange {
+        start: MonthDay,
+        end: MonthDay,
+    },
+    /// 节日及其前后若干天
+    Holiday {
+        holiday: Holiday,
+        #[serde(default)]
+        days_before: u32,
+        #[serde(default)]
+        days_after: u32,
+    },
+}
+
+impl SeasonalTrigger {
+    /// 未指定优先级时：节日 > 日期区间 > 季节
+    fn default_priority(&self) -> i32 {
+        match self {
+            Self::Season { .. } => 0,
+            Self::DateRange { .. } => 10,
+            Self::Holiday { .. } => 20,
+        }
+    }
+
+    fn matches(&self, date: NaiveDate, country: &str, regions: &[String]) -> bool {
+        match self {
+            Self::Season { season } => Season::at(date, country) == *season,
+            Self::DateRange { start, end } => {
+                let today = MonthDay::of(date);
+                if start <= end {
+                    *start <= today && today <= *end
+                } else {
+                    today >= *start || today <= *end
+                }
+            }
+            Self::Holiday {
+                holiday,
+                days_before,
+                days_after,
+            } => {
+                // 规则未限定地区时使用节日的默认地区
+                if regions.is_empty() {
+                    let defaults = holiday.regions();
+                    if !defaults.is_empty() && !defaults.iter().any(|c| c.eq_ignore_ascii_case(country)) {
+                        return false;
+                    }
+                }
+                // 节日窗口可能跨年（如元旦前几天），同时检查前后一年
+                (date.year() - 1..=date.year() + 1).any(|year| match holiday.dates(year, country) {
+                    Some((first, last)) => {
+                        first - Duration::days(*days_before as i64) <= date
+                            && date <= last + Duration::days(*days_after as i64)
+                    }
+                    None => false,
+                })
+            }
+        }
+    }
+}
+
+/// 服装/贴图变体
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutfitVariant {
+    pub id: String,
+    pub name: String,
+    /// 替换的贴图路径（相对模型目录）
+    #[serde(default)]
+    pub textures: Vec<String>,
+}
+
+/// 季节内容规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalRule {
+    pub id: String,
+    pub name: String,
+    pub trigger: SeasonalTrigger,
+    /// 限定的国家/地区代码（空表示不限定）
+    #[serde(default)]
+    pub regions: Vec<String>,
+    /// 切换到的服装变体
+    #[serde(default)]
+    pub outfit: Option<String>,
+    /// 特殊表情
+    #[serde(default)]
+    pub expression: Option<String>,
+    #[serde(default)]
+    pub priority: Option<i32>,
+}
+
+impl SeasonalRule {
+    fn priority(&self) -> i32 {
+        self.priority.unwrap_or_else(|| self.trigger.default_priority())
+    }
+
+    fn applies(&self, date: NaiveDate, country: &str) -> bool {
+        let region_ok = self.regions.is_empty() || self.regions.iter().any(|c| c.eq_ignore_ascii_case(country));
+        region_ok && self.trigger.matches(date, country, &self.regions)
+    }
+}
+
+/// 用户手动覆盖
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalOverride {
+    #[serde(default)]
+    pub outfit: Option<String>,
+    #[serde(default)]
+    pub expression: Option<String>,
+    /// 截止时间，为空表示一直生效
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 角色的季节内容配置（保存在角色配置的 `seasonal` 字段中）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalConfig {
+    /// 是否自动切换
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub default_outfit: Option<String>,
+    #[serde(default)]
+    pub outfits: Vec<OutfitVariant>,
+    #[serde(default)]
+    pub rules: Vec<SeasonalRule>,
+    #[serde(default, rename = "override")]
+    pub user_override: Option<SeasonalOverride>,
+}
+
+impl Default for SeasonalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_outfit: None,
+            outfits: Vec::new(),
+            rules: Vec::new(),
+            user_override: None,
+        }
+    }
+}
+
+impl SeasonalConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let known = |outfit: &Option<String>| match outfit {
+            Some(id) => self.outfits.iter().any(|o| &o.id == id),
+            None => true,
+        };
+        if !known(&self.default_outfit) {
+            return Err("默认服装不存在".to_string());
+        }
+        for rule in &self.rules {
+            if !known(&rule.outfit) {
+                return Err(format!("规则 {} 引用了不存在的服装", rule.name));
+            }
+            if let SeasonalTrigger::DateRange { start, end } = &rule.trigger {
+                for md in [start, end] {
+                    // 用闰年校验，允许 02-29
+                    if NaiveDate::from_ymd_opt(2024, md.month, md.day).is_none() {
+                        return Err(format!("规则 {} 的日期无效: {:02}-{:02}", rule.name, md.month, md.day));
+                    }
+                }
+            }
+        }
+        if let Some(user_override) = &self.user_override {
+            if !known(&user_override.outfit) {
+                return Err("覆盖的服装不存在".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 计算某天在某地区应使用的服装与表情
+    pub fn resolve(&self, date: NaiveDate, country: &str, now: DateTime<Utc>) -> SeasonalSelection {
+        let mut matched: Vec<&SeasonalRule> = if self.enabled {
+            self.rules.iter().filter(|rule| rule.applies(date, country)).collect()
+        } else {
+            Vec::new()
+        };
+        // 优先级相同时保留配置中的先后顺序
+        matched.sort_by_key(|rule| std::cmp::Reverse(rule.priority()));
+
+        let active_override = self
+            .user_override
+            .as_ref()
+            .filter(|o| match o.until {
+                Some(until) => now < until,
+                None => true,
+            });
+
+        let rule_outfit = matched.iter().find_map(|rule| rule.outfit.clone());
+        let rule_expression = matched.iter().find_map(|rule| rule.expression.clone());
+        let outfit = active_override
+            .and_then(|o| o.outfit.clone())
+            .or(rule_outfit)
+            .or_else(|| self.default_outfit.clone());
+        let expression = active_override.and_then(|o| o.expression.clone()).or(rule_expression);
+        let textures = outfit
+            .as_ref()
+            .and_then(|id| self.outfits.iter().find(|o| &o.id == id))
+            .map(|o| o.textures.clone())
+            .unwrap_or_default();
+
+        SeasonalSelection {
+            date,
+            season: Season::at(date, country),
+            outfit,
+            textures,
+            expression,
+            matched_rules: matched.iter().map(|rule| rule.id.clone()).collect(),
+            overridden: active_override.is_some(),
+        }
+    }
+}
+
+/// 季节内容的计算结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonalSelection {
+    pub date: NaiveDate,
+    pub season: Season,
+    pub outfit: Option<String>,
+    pub textures: Vec<String>,
+    pub expression: Option<String>,
+    /// 命中的规则（按优先级排序）
+    pub matched_rules: Vec<String>,
+    /// 是否由用户覆盖决定
+    pub overridden: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rule(id: &str, trigger: SeasonalTrigger, outfit: Option<&str>, expression: Option<&str>) -> SeasonalRule {
+        SeasonalRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            trigger,
+            regions: Vec::new(),
+            outfit: outfit.map(str::to_string),
+            expression: expression.map(str::to_string),
+            priority: None,
+        }
+    }
+
+    fn outfit(id: &str) -> OutfitVariant {
+        OutfitVariant {
+            id: id.to_string(),
+            name: id.to_string(),
+            textures: vec![format!("textures/{}.png", id)],
+        }
+    }
+
+    #[test]
+    fn test_holiday_dates() {
+        assert_eq!(easter_sunday(2025), Some(date(2025, 4, 20)));
+        assert_eq!(easter_sunday(2026), Some(date(2026, 4, 5)));
+        assert_eq!(Holiday::Thanksgiving.dates(2026, "US").unwrap().0, date(2026, 11, 26));
+        assert_eq!(Holiday::Thanksgiving.dates(2026, "CA").unwrap().0, date(2026, 10, 12));
+        assert_eq!(Holiday::LunarNewYear.dates(2026, "CN").unwrap().0, date(2026, 2, 17));
+        assert!(Holiday::LunarNewYear.dates(2050, "CN").is_none());
+    }
+
+    #[test]
+    fn test_region_aware_rules() {
+        let winter = SeasonalTrigger::Season { season: Season::Winter };
+        assert!(winter.matches(date(2026, 1, 10), "JP", &[]));
+        assert!(!winter.matches(date(2026, 1, 10), "AU", &[]));
+        assert!(winter.matches(date(2026, 7, 10), "AU", &[]));
+
+        let spring_festival = SeasonalTrigger::Holiday {
+            holiday: Holiday::LunarNewYear,
+            days_before: 1,
+            days_after: 3,
+        };
+        assert!(spring_festival.matches(date(2026, 2, 16), "CN", &[]));
+        assert!(spring_festival.matches(date(2026, 2, 20), "CN", &[]));
+        assert!(!spring_festival.matches(date(2026, 2, 21), "CN", &[]));
+        assert!(!spring_festival.matches(date(2026, 2, 17), "US", &[]));
+        // 规则显式限定地区时不再使用节日默认地区
+        assert!(spring_festival.matches(date(2026, 2, 17), "US", &["US".to_string()]));
+
+        let new_year_eve = SeasonalTrigger::Holiday {
+            holiday: Holiday::NewYear,
+            days_before: 2,
+            days_after: 0,
+        };
+        assert!(new_year_eve.matches(date(2025, 12, 30), "DE", &[]));
+
+        let winter_break = SeasonalTrigger::DateRange {
+            start: MonthDay { month: 12, day: 20 },
+            end: MonthDay { month: 1, day: 5 },
+        };
+        assert!(winter_break.matches(date(2026, 1, 3), "DE", &[]));
+        assert!(!winter_break.matches(date(2026, 1, 6), "DE", &[]));
+    }
+
+    #[test]
+    fn test_resolve_priorities_and_override() {
+        let config = SeasonalConfig {
+            default_outfit: Some("casual".to_string()),
+            outfits: vec![outfit("casual"), outfit("winter"), outfit("santa")],
+            rules: vec![
+                rule("winter", SeasonalTrigger::Season { season: Season::Winter }, Some("winter"), Some("shiver")),
+                rule(
+                    "christmas",
+                    SeasonalTrigger::Holiday {
+                        holiday: Holiday::Christmas,
+                        days_before: 0,
+                        days_after: 0,
+                    },
+                    Some("santa"),
+                    None,
+                ),
+            ],
+            ..SeasonalConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let now = Utc::now();
+
+        let christmas = config.resolve(date(2026, 12, 25), "JP", now);
+        assert_eq!(christmas.outfit.as_deref(), Some("santa"));
+        // 节日规则没有表情时使用季节规则的表情
+        assert_eq!(christmas.expression.as_deref(), Some("shiver"));
+        assert_eq!(christmas.matched_rules, vec!["christmas", "winter"]);
+        assert_eq!(christmas.textures, vec!["textures/santa.png"]);
+
+        let summer = config.resolve(date(2026, 7, 1), "JP", now);
+        assert_eq!(summer.outfit.as_deref(), Some("casual"));
+        assert!(summer.matched_rules.is_empty());
+
+        let mut overridden = config.clone();
+        overridden.user_override = Some(SeasonalOverride {
+            outfit: Some("casual".to_string()),
+            expression: None,
+            until: Some(now + Duration::hours(1)),
+        });
+        let selection = overridden.resolve(date(2026, 12, 25), "JP", now);
+        assert!(selection.overridden);
+        assert_eq!(selection.outfit.as_deref(), Some("casual"));
+        let expired = overridden.resolve(date(2026, 12, 25), "JP", now + Duration::hours(2));
+        assert_eq!(expired.outfit.as_deref(), Some("santa"));
+
+        let mut invalid = config;
+        invalid.rules[0].outfit = Some("missing".to_string());
+        assert!(invalid.validate().is_err());
+    }
+}
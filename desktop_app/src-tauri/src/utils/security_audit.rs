@@ -20,6 +20,8 @@ pub enum AuditEventType {
     PermissionChange,
     ConfigChange,
     SecurityViolation,
+    MessageEdit,
+    MessageDelete,
 }
 
 /// 审计级别
@@ -182,6 +184,8 @@ mod tests {
             AuditEventType::PermissionChange,
             AuditEventType::ConfigChange,
             AuditEventType::SecurityViolation,
+            AuditEventType::MessageEdit,
+            AuditEventType::MessageDelete,
         ];
 
         for event_type in event_types {
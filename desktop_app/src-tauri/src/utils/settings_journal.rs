@@ -0,0 +1,177 @@
+//! # 设置变更日志
+//!
+//! 记录每次部分更新设置（`update_partial_settings`）的字段级变化，用于逐条撤销与重做。
+//! 与配置时间线（见 [`super::config_timeline`]）不同，撤销只还原这一次修改的字段，
+//! 之后通过其他方式修改的字段保持不变。
+//!
+//! 日志是一个撤销栈：前 `applied` 条已生效，其后的条目可以重做；撤销后再记录新的修改时，
+//! 可重做的条目被丢弃。只保留最近 [`MAX_ENTRIES`] 条。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config_timeline::{set_path, FieldChange};
+
+/// 保留的变更条目数量
+pub const MAX_ENTRIES: usize = 100;
+
+const JOURNAL_FILE: &str = "settings_changes.json";
+
+/// 一次设置修改
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsChange {
+    pub id: u64,
+    pub timestamp: i64,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 变更日志中的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsChangeRecord {
+    #[serde(flatten)]
+    pub change: SettingsChange,
+    /// 已撤销（可重做）
+    pub undone: bool,
+}
+
+/// 设置变更日志
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsJournal {
+    entries: Vec<SettingsChange>,
+    /// 已生效的条目数
+    applied: usize,
+    next_id: u64,
+}
+
+impl SettingsJournal {
+    /// 记录一次修改，丢弃可重做的条目；没有变化时返回 `None`
+    pub fn record(&mut self, changes: Vec<FieldChange>, now: i64) -> Option<u64> {
+        if changes.is_empty() {
+            return None;
+        }
+        self.entries.truncate(self.applied);
+        self.next_id += 1;
+        self.entries.push(SettingsChange { id: self.next_id, timestamp: now, changes });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.applied = self.entries.len();
+        Some(self.next_id)
+    }
+
+    /// 撤销最近一次生效的修改，返回撤销的条目与还原后的配置
+    pub fn undo(&mut self, current: &Value) -> Option<(SettingsChange, Value)> {
+        let change = self.entries.get(self.applied.checked_sub(1)?)?.clone();
+        let mut state = current.clone();
+        for field in &change.changes {
+            set_path(&mut state, &field.path, field.old.clone());
+        }
+        self.applied -= 1;
+        Some((change, state))
+    }
+
+    /// 重做最近一次撤销的修改，返回重做的条目与重做后的配置
+    pub fn redo(&mut self, current: &Value) -> Option<(SettingsChange, Value)> {
+        let change = self.entries.get(self.applied)?.clone();
+        let mut state = current.clone();
+        for field in &change.changes {
+            set_path(&mut state, &field.path, field.new.clone());
+        }
+        self.applied += 1;
+        Some((change, state))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.applied > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.applied < self.entries.len()
+    }
+
+    /// 变更记录（新的在前）
+    pub fn records(&self, limit: usize) -> Vec<SettingsChangeRecord> {
+        self.entries
+            .iter()
+            .enumerate()
+            .rev()
+            .take(limit)
+            .map(|(index, change)| SettingsChangeRecord { change: change.clone(), undone: index >= self.applied })
+            .collect()
+    }
+}
+
+fn journal_path(dir: &Path) -> PathBuf {
+    dir.join(JOURNAL_FILE)
+}
+
+/// 读取变更日志（文件不存在或损坏时返回空日志）
+pub fn load(dir: &Path) -> SettingsJournal {
+    fs::read_to_string(journal_path(dir))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(dir: &Path, journal: &SettingsJournal) -> Result<(), String> {
+    let json = serde_json::to_string(journal).map_err(|e| format!("序列化设置变更日志失败: {}", e))?;
+    fs::write(journal_path(dir), json).map_err(|e| format!("写入设置变更日志失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config_timeline::diff;
+    use serde_json::json;
+
+    #[test]
+    fn test_undo_only_reverts_its_own_fields() {
+        let mut journal = SettingsJournal::default();
+        let original = json!({ "theme": { "current_theme": "default" }, "window": { "width": 400 } });
+        let themed = json!({ "theme": { "current_theme": "dark" }, "window": { "width": 400 } });
+        journal.record(diff(&original, &themed), 1);
+        let resized = json!({ "theme": { "current_theme": "dark" }, "window": { "width": 480 } });
+        journal.record(diff(&themed, &resized), 2);
+
+        // 主题之后又被其他方式修改，撤销宽度不影响主题
+        let mut current = resized.clone();
+        current["theme"]["current_theme"] = json!("anime");
+        let (change, undone) = journal.undo(&current).unwrap();
+        assert_eq!(change.id, 2);
+        assert_eq!(undone, json!({ "theme": { "current_theme": "anime" }, "window": { "width": 400 } }));
+
+        let (change, undone) = journal.undo(&undone).unwrap();
+        assert_eq!(change.id, 1);
+        assert_eq!(undone["theme"]["current_theme"], json!("default"));
+        assert!(!journal.can_undo());
+        assert!(journal.undo(&undone).is_none());
+
+        let (_, redone) = journal.redo(&undone).unwrap();
+        assert_eq!(redone["theme"]["current_theme"], json!("dark"));
+        let undone_flags: Vec<bool> = journal.records(10).iter().map(|r| r.undone).collect();
+        assert_eq!(undone_flags, vec![true, false]);
+    }
+
+    #[test]
+    fn test_new_change_discards_redo_and_trims() {
+        let mut journal = SettingsJournal::default();
+        journal.record(diff(&json!({ "a": 1 }), &json!({ "a": 2 })), 1);
+        journal.record(diff(&json!({ "a": 2 }), &json!({ "a": 3 })), 2);
+        journal.undo(&json!({ "a": 3 })).unwrap();
+        assert!(journal.can_redo());
+
+        assert_eq!(journal.record(diff(&json!({ "a": 2 }), &json!({ "a": 5 })), 3), Some(3));
+        assert!(!journal.can_redo());
+        assert_eq!(journal.records(10).iter().map(|r| r.change.id).collect::<Vec<_>>(), vec![3, 1]);
+        assert_eq!(journal.record(Vec::new(), 4), None);
+
+        for i in 0..MAX_ENTRIES as i64 {
+            journal.record(diff(&json!({ "a": i }), &json!({ "a": i + 1 })), 10 + i);
+        }
+        assert_eq!(journal.records(usize::MAX).len(), MAX_ENTRIES);
+        assert!(journal.can_undo());
+    }
+}
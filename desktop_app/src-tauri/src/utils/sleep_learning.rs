@@ -0,0 +1,426 @@
+//! # 空闲时整理（“睡眠学习”）
+//!
+//! 系统空闲且接通电源时在后台整理知识：
+//!
+//! 1. 为较长的会话重新生成摘要
+//! 2. 用当前嵌入提供者重新嵌入过期的向量集合
+//! 3. 压缩向量库：移除已删除消息遗留的向量
+//! 4. 预先计算会话话题聚类
+//!
+//! 任务按阶段推进，每完成一个工作单元就保存进度；用户回来（不再空闲）或拔掉电源时暂停，
+//! 下一个空闲时段从断点继续。本模块包含设置、进度与报告的数据结构，以及空闲时长和电源
+//! 状态的探测；实际的整理工作在 `commands::sleep_learning`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_idle_minutes() -> u64 {
+    10
+}
+
+fn default_long_session_messages() -> usize {
+    40
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+/// 空闲整理设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SleepLearningSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 无键鼠输入多久后视为空闲（分钟）
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u64,
+    /// 仅在接通电源时运行
+    #[serde(default = "default_true")]
+    pub require_ac_power: bool,
+    /// 消息数达到该值的会话视为长会话，需要摘要
+    #[serde(default = "default_long_session_messages")]
+    pub long_session_messages: usize,
+    /// 两次完整整理的最小间隔（小时）
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+}
+
+impl Default for SleepLearningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: default_idle_minutes(),
+            require_ac_power: true,
+            long_session_messages: default_long_session_messages(),
+            interval_hours: default_interval_hours(),
+        }
+    }
+}
+
+impl SleepLearningSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=24 * 60).contains(&self.idle_minutes) {
+            return Err("空闲时长必须在 1 分钟到 24 小时之间".to_string());
+        }
+        if self.long_session_messages < 2 {
+            return Err("长会话的消息数至少为 2".to_string());
+        }
+        if !(1..=24 * 30).contains(&self.interval_hours) {
+            return Err("整理间隔必须在 1 小时到 30 天之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 当前系统状态是否允许运行；空闲时长未知时不运行，电源状态未知时视为接通电源
+    pub fn allows(&self, conditions: &SystemConditions) -> bool {
+        let idle = matches!(conditions.idle_secs, Some(secs) if secs >= self.idle_minutes * 60);
+        let powered = !self.require_ac_power || conditions.on_ac_power != Some(false);
+        self.enabled && idle && powered
+    }
+}
+
+/// 探测到的系统状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemConditions {
+    /// 距上次键鼠输入的秒数（无法探测时为空）
+    pub idle_secs: Option<u64>,
+    /// 是否接通电源（无法探测时为空）
+    pub on_ac_power: Option<bool>,
+}
+
+impl SystemConditions {
+    pub fn detect() -> Self {
+        Self {
+            idle_secs: idle_seconds(),
+            on_ac_power: on_ac_power(),
+        }
+    }
+}
+
+/// 整理阶段（按顺序执行）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsolidationPhase {
+    Summaries,
+    Embeddings,
+    Compaction,
+    TopicClusters,
+    Done,
+}
+
+impl ConsolidationPhase {
+    pub fn next(self) -> Self {
+        match self {
+            ConsolidationPhase::Summaries => ConsolidationPhase::Embeddings,
+            ConsolidationPhase::Embeddings => ConsolidationPhase::Compaction,
+            ConsolidationPhase::Compaction => ConsolidationPhase::TopicClusters,
+            ConsolidationPhase::TopicClusters | ConsolidationPhase::Done => ConsolidationPhase::Done,
+        }
+    }
+}
+
+/// 整理报告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    /// 重新生成摘要的会话
+    pub summarized_sessions: Vec<String>,
+    /// 重新嵌入的集合
+    pub reembedded_collections: Vec<String>,
+    /// 重新嵌入的条目数
+    pub reembedded_points: usize,
+    /// 压缩时移除的向量数
+    pub removed_vectors: usize,
+    /// 预计算得到的话题数
+    pub topic_count: Option<usize>,
+    /// 因不再空闲而暂停的次数
+    pub pauses: u32,
+    /// 跳过的工作单元及原因
+    pub errors: Vec<String>,
+}
+
+/// 整理进度（每完成一个工作单元保存一次）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationProgress {
+    pub run_id: String,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub phase: ConsolidationPhase,
+    /// 本轮已处理（成功或跳过）的会话与集合，恢复时不再重复
+    #[serde(default)]
+    pub processed: BTreeSet<String>,
+    #[serde(default)]
+    pub report: ConsolidationReport,
+}
+
+impl ConsolidationProgress {
+    pub fn new(run_id: String, now: i64) -> Self {
+        Self {
+            run_id,
+            started_at: now,
+            finished_at: None,
+            phase: ConsolidationPhase::Summaries,
+            processed: BTreeSet::new(),
+            report: ConsolidationReport::default(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == ConsolidationPhase::Done
+    }
+
+    /// 工作单元的键：阶段 + 会话或集合名
+    fn unit_key(phase: ConsolidationPhase, unit: &str) -> String {
+        format!("{:?}:{}", phase, unit)
+    }
+
+    pub fn is_processed(&self, unit: &str) -> bool {
+        self.processed.contains(&Self::unit_key(self.phase, unit))
+    }
+
+    pub fn mark_processed(&mut self, unit: &str) {
+        self.processed.insert(Self::unit_key(self.phase, unit));
+    }
+
+    /// 记录跳过的工作单元，之后不再重试
+    pub fn mark_failed(&mut self, unit: &str, error: &str) {
+        self.report.errors.push(format!("{:?} {}: {}", self.phase, unit, error));
+        self.mark_processed(unit);
+    }
+
+    /// 进入下一阶段；全部完成时记录完成时间
+    pub fn advance(&mut self, now: i64) {
+        self.phase = self.phase.next();
+        if self.is_finished() {
+            self.finished_at = Some(now);
+        }
+    }
+}
+
+/// 会话摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub summary: String,
+    /// 生成摘要时的消息数
+    pub message_count: usize,
+    pub generated_at: i64,
+}
+
+/// 会话是否需要（重新）生成摘要：足够长，且没有摘要或摘要之后又有新消息
+pub fn needs_summary(message_count: usize, existing: Option<&SessionSummary>, settings: &SleepLearningSettings) -> bool {
+    if message_count < settings.long_session_messages {
+        return false;
+    }
+    match existing {
+        Some(summary) => message_count > summary.message_count,
+        None => true,
+    }
+}
+
+/// 距离上次完成的整理是否已经超过设定间隔
+pub fn is_due(last_finished_at: Option<i64>, settings: &SleepLearningSettings, now: i64) -> bool {
+    match last_finished_at {
+        Some(finished) => now - finished >= (settings.interval_hours * 3600) as i64,
+        None => true,
+    }
+}
+
+// ================================
+// 空闲时长与电源状态探测
+// ================================
+
+/// 距上次键鼠输入的秒数
+#[cfg(target_os = "windows")]
+pub fn idle_seconds() -> Option<u64> {
+    use winapi::um::sysinfoapi::GetTickCount;
+    use winapi::um::winuser::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: info 已按 API 要求设置 cbSize
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    let now = unsafe { GetTickCount() };
+    Some(u64::from(now.wrapping_sub(info.dwTime)) / 1000)
+}
+
+/// 距上次键鼠输入的秒数
+#[cfg(target_os = "macos")]
+pub fn idle_seconds() -> Option<u64> {
+    use std::process::Command;
+
+    let output = Command::new("ioreg").args(["-c", "IOHIDSystem", "-d", "4"]).output().ok()?;
+    parse_hid_idle_time(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// 距上次键鼠输入的秒数（需要 X11 下的 `xprintidle`）
+#[cfg(target_os = "linux")]
+pub fn idle_seconds() -> Option<u64> {
+    use std::process::Command;
+
+    let output = Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let millis: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Some(millis / 1000)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn idle_seconds() -> Option<u64> {
+    None
+}
+
+/// 是否接通电源
+#[cfg(target_os = "windows")]
+pub fn on_ac_power() -> Option<bool> {
+    use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: SYSTEM_POWER_STATUS 是纯数据结构，全零是合法值
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    match status.ACLineStatus {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+/// 是否接通电源
+#[cfg(target_os = "macos")]
+pub fn on_ac_power() -> Option<bool> {
+    use std::process::Command;
+
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    parse_pmset_power_source(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// 是否接通电源；没有电池的设备视为接通电源
+#[cfg(target_os = "linux")]
+pub fn on_ac_power() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut has_battery = false;
+    let mut mains_online = None;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let read = |name: &str| std::fs::read_to_string(entry.path().join(name)).map(|s| s.trim().to_string());
+        match read("type").as_deref() {
+            Ok("Battery") => has_battery = true,
+            Ok("Mains") => {
+                let online = read("online").map(|s| s == "1").unwrap_or(false);
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            _ => {}
+        }
+    }
+    match (has_battery, mains_online) {
+        (false, _) => Some(true),
+        (true, online) => online,
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn on_ac_power() -> Option<bool> {
+    None
+}
+
+/// 解析 `ioreg -c IOHIDSystem` 输出中的 `HIDIdleTime`（纳秒）
+#[cfg_attr(not(any(test, target_os = "macos")), allow(dead_code))]
+fn parse_hid_idle_time(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|nanos| nanos / 1_000_000_000)
+}
+
+/// 解析 `pmset -g batt` 输出中的电源来源
+#[cfg_attr(not(any(test, target_os = "macos")), allow(dead_code))]
+fn parse_pmset_power_source(output: &str) -> Option<bool> {
+    let first_line = output.lines().next()?;
+    if first_line.contains("'AC Power'") {
+        Some(true)
+    } else if first_line.contains("'Battery Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_requires_idle_and_power() {
+        let settings = SleepLearningSettings::default();
+        let idle = |secs, power| SystemConditions {
+            idle_secs: secs,
+            on_ac_power: power,
+        };
+        assert!(settings.allows(&idle(Some(900), Some(true))));
+        assert!(settings.allows(&idle(Some(900), None)));
+        assert!(!settings.allows(&idle(Some(900), Some(false))));
+        assert!(!settings.allows(&idle(Some(60), Some(true))));
+        assert!(!settings.allows(&idle(None, Some(true))));
+
+        let on_battery = SleepLearningSettings {
+            require_ac_power: false,
+            ..settings.clone()
+        };
+        assert!(on_battery.allows(&idle(Some(900), Some(false))));
+        assert!(!SleepLearningSettings { enabled: false, ..settings }.allows(&idle(Some(900), Some(true))));
+    }
+
+    #[test]
+    fn test_progress_resumes_without_repeating_units() {
+        let mut progress = ConsolidationProgress::new("run-1".to_string(), 100);
+        progress.mark_processed("conv-1");
+        progress.mark_failed("conv-2", "LLM 不可用");
+
+        let restored: ConsolidationProgress =
+            serde_json::from_str(&serde_json::to_string(&progress).unwrap()).unwrap();
+        assert!(restored.is_processed("conv-1"));
+        assert!(restored.is_processed("conv-2"));
+        assert_eq!(restored.report.errors.len(), 1);
+
+        let mut progress = restored;
+        progress.advance(200);
+        // 不同阶段的同名单元互不影响
+        assert!(!progress.is_processed("conv-1"));
+        for _ in 0..3 {
+            progress.advance(300);
+        }
+        assert!(progress.is_finished());
+        assert_eq!(progress.finished_at, Some(300));
+    }
+
+    #[test]
+    fn test_summary_selection_and_platform_parsing() {
+        let settings = SleepLearningSettings::default();
+        let summary = SessionSummary {
+            summary: "讨论了旅行计划".to_string(),
+            message_count: 50,
+            generated_at: 0,
+        };
+        assert!(!needs_summary(10, None, &settings));
+        assert!(needs_summary(40, None, &settings));
+        assert!(!needs_summary(50, Some(&summary), &settings));
+        assert!(needs_summary(60, Some(&summary), &settings));
+        assert!(is_due(Some(0), &settings, 24 * 3600));
+        assert!(!is_due(Some(0), &settings, 3600));
+
+        let ioreg = "    | |   \"HIDIdleTime\" = 125000000000\n";
+        assert_eq!(parse_hid_idle_time(ioreg), Some(125));
+        assert_eq!(parse_pmset_power_source("Now drawing from 'Battery Power'\n -InternalBattery-0"), Some(false));
+        assert_eq!(parse_pmset_power_source("Now drawing from 'AC Power'\n"), Some(true));
+    }
+}
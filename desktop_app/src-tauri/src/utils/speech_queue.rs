@@ -0,0 +1,461 @@
+//! # 对话气泡队列
+//!
+//! 角色说的话（问候、提醒、聊天回复、系统警报、闲聊）统一进入每个角色窗口的队列，
+//! 同一时间只显示一个气泡：
+//!
+//! - 按优先级显示：警报 > 回复 > 提醒 > 问候 > 闲聊，同优先级先到先显示
+//! - 更高优先级的话可以打断正在显示的闲聊、问候和提醒，但不会打断回复和警报；
+//!   被打断的提醒与问候重新排队，闲聊直接丢弃
+//! - 显示时长按字数计算，并受各类型的最长显示时间限制
+//! - 排队太久的闲聊、问候和提醒会过期丢弃
+//!
+//! 窗口与事件推送在 `commands::speech_queue`。
+
+use serde::{Deserialize, Serialize};
+
+/// 话语类型（决定优先级与打断规则）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UtteranceKind {
+    /// 空闲时的闲聊
+    Chatter,
+    Greeting,
+    Reminder,
+    /// 聊天回复
+    Reply,
+    /// 系统警报
+    Alert,
+}
+
+impl UtteranceKind {
+    pub fn priority(self) -> u8 {
+        match self {
+            UtteranceKind::Chatter => 0,
+            UtteranceKind::Greeting => 1,
+            UtteranceKind::Reminder => 2,
+            UtteranceKind::Reply => 3,
+            UtteranceKind::Alert => 4,
+        }
+    }
+
+    /// 显示中能否被更高优先级的话打断
+    pub fn interruptible(self) -> bool {
+        !matches!(self, UtteranceKind::Reply | UtteranceKind::Alert)
+    }
+
+    /// 排队超过该时间（毫秒）仍未显示时丢弃；回复与警报不过期
+    fn max_wait_ms(self) -> Option<i64> {
+        match self {
+            UtteranceKind::Chatter => Some(10_000),
+            UtteranceKind::Greeting => Some(60_000),
+            UtteranceKind::Reminder => Some(10 * 60_000),
+            UtteranceKind::Reply | UtteranceKind::Alert => None,
+        }
+    }
+
+    /// 被打断后是否重新排队
+    fn requeue_on_interrupt(self) -> bool {
+        self != UtteranceKind::Chatter
+    }
+}
+
+/// 一句话
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Utterance {
+    pub id: String,
+    pub kind: UtteranceKind,
+    pub text: String,
+    /// 来源模块，如 `quick-chat`
+    pub source: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub message_id: Option<String>,
+    /// 指定的显示时长（毫秒），仍受最长显示时间限制
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+impl Utterance {
+    pub fn new(kind: UtteranceKind, text: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            text: text.into(),
+            source: source.into(),
+            session_id: None,
+            message_id: None,
+            duration_ms: None,
+        }
+    }
+
+    /// 关联到会话中的消息
+    pub fn in_session(mut self, session_id: impl Into<String>, message_id: Option<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self.message_id = message_id;
+        self
+    }
+
+    pub fn with_duration(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = Some(duration_ms);
+        self
+    }
+}
+
+fn default_min_display_ms() -> u64 {
+    1500
+}
+
+fn default_ms_per_char() -> u64 {
+    150
+}
+
+fn default_max_pending() -> usize {
+    8
+}
+
+/// 各类型的最长显示时间（毫秒）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaxDisplayDurations {
+    pub chatter: u64,
+    pub greeting: u64,
+    pub reminder: u64,
+    pub reply: u64,
+    pub alert: u64,
+}
+
+impl Default for MaxDisplayDurations {
+    fn default() -> Self {
+        Self {
+            chatter: 5_000,
+            greeting: 6_000,
+            reminder: 10_000,
+            reply: 20_000,
+            alert: 15_000,
+        }
+    }
+}
+
+impl MaxDisplayDurations {
+    pub fn get(&self, kind: UtteranceKind) -> u64 {
+        match kind {
+            UtteranceKind::Chatter => self.chatter,
+            UtteranceKind::Greeting => self.greeting,
+            UtteranceKind::Reminder => self.reminder,
+            UtteranceKind::Reply => self.reply,
+            UtteranceKind::Alert => self.alert,
+        }
+    }
+}
+
+/// 对话气泡设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeechQueueSettings {
+    /// 最短显示时间（毫秒）
+    #[serde(default = "default_min_display_ms")]
+    pub min_display_ms: u64,
+    /// 每个字增加的显示时间（毫秒）
+    #[serde(default = "default_ms_per_char")]
+    pub ms_per_char: u64,
+    #[serde(default)]
+    pub max_display_ms: MaxDisplayDurations,
+    /// 每个窗口最多排队的条数，超出时丢弃优先级最低的
+    #[serde(default = "default_max_pending")]
+    pub max_pending: usize,
+}
+
+impl Default for SpeechQueueSettings {
+    fn default() -> Self {
+        Self {
+            min_display_ms: default_min_display_ms(),
+            ms_per_char: default_ms_per_char(),
+            max_display_ms: MaxDisplayDurations::default(),
+            max_pending: default_max_pending(),
+        }
+    }
+}
+
+impl SpeechQueueSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(500..=10_000).contains(&self.min_display_ms) {
+            return Err("最短显示时间必须在 0.5 到 10 秒之间".to_string());
+        }
+        if self.ms_per_char > 1_000 {
+            return Err("每字显示时间不能超过 1 秒".to_string());
+        }
+        let limits = &self.max_display_ms;
+        for max in [limits.chatter, limits.greeting, limits.reminder, limits.reply, limits.alert] {
+            if max < self.min_display_ms || max > 120_000 {
+                return Err("最长显示时间必须不短于最短显示时间且不超过 2 分钟".to_string());
+            }
+        }
+        if !(1..=100).contains(&self.max_pending) {
+            return Err("排队条数必须在 1 到 100 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 显示时长：指定时长或按字数计算，限制在最短与该类型的最长显示时间之间
+    pub fn display_ms(&self, utterance: &Utterance) -> u64 {
+        let max = self.max_display_ms.get(utterance.kind).max(self.min_display_ms);
+        let by_length = self.min_display_ms + self.ms_per_char * utterance.text.chars().count() as u64;
+        utterance.duration_ms.unwrap_or(by_length).clamp(self.min_display_ms, max)
+    }
+}
+
+/// 正在显示的话
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveUtterance {
+    pub utterance: Utterance,
+    /// Unix 毫秒
+    pub started_at: i64,
+    pub ends_at: i64,
+}
+
+/// 气泡结束或未显示的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    /// 显示时间到
+    Expired,
+    /// 被更高优先级的话打断
+    Interrupted,
+    /// 用户关闭或调用方撤回
+    Dismissed,
+    /// 排队太久
+    Stale,
+    /// 队列已满
+    QueueFull,
+}
+
+/// 队列变化，由调用方推送给窗口
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpeechEvent {
+    Show(ActiveUtterance),
+    /// 正在显示的气泡结束
+    Hide { id: String, reason: EndReason },
+    /// 排队的话未显示就被移除
+    Dropped { id: String, reason: EndReason },
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    utterance: Utterance,
+    enqueued_at: i64,
+    seq: u64,
+}
+
+/// 一个窗口的对话气泡队列
+#[derive(Debug, Default)]
+pub struct SpeechQueue {
+    current: Option<ActiveUtterance>,
+    pending: Vec<Pending>,
+    seq: u64,
+}
+
+impl SpeechQueue {
+    pub fn current(&self) -> Option<&ActiveUtterance> {
+        self.current.as_ref()
+    }
+
+    /// 排队中的话，按显示顺序
+    pub fn pending(&self) -> Vec<Utterance> {
+        let mut pending = self.pending.clone();
+        pending.sort_by_key(|p| (std::cmp::Reverse(p.utterance.kind.priority()), p.seq));
+        pending.into_iter().map(|p| p.utterance).collect()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.current.is_none() && self.pending.is_empty()
+    }
+
+    fn push(&mut self, utterance: Utterance, enqueued_at: i64) {
+        self.seq += 1;
+        self.pending.push(Pending { utterance, enqueued_at, seq: self.seq });
+    }
+
+    fn show(&mut self, utterance: Utterance, settings: &SpeechQueueSettings, now: i64) -> SpeechEvent {
+        let active = ActiveUtterance {
+            ends_at: now + settings.display_ms(&utterance) as i64,
+            started_at: now,
+            utterance,
+        };
+        self.current = Some(active.clone());
+        SpeechEvent::Show(active)
+    }
+
+    /// 没有正在显示的话时，显示排队中优先级最高的一条（先丢弃过期的）
+    fn advance(&mut self, settings: &SpeechQueueSettings, now: i64, events: &mut Vec<SpeechEvent>) {
+        if self.current.is_some() {
+            return;
+        }
+        let mut kept = Vec::with_capacity(self.pending.len());
+        for pending in self.pending.drain(..) {
+            let stale = match pending.utterance.kind.max_wait_ms() {
+                Some(max_wait) => now - pending.enqueued_at > max_wait,
+                None => false,
+            };
+            if stale {
+                events.push(SpeechEvent::Dropped { id: pending.utterance.id, reason: EndReason::Stale });
+            } else {
+                kept.push(pending);
+            }
+        }
+        self.pending = kept;
+
+        let next = self
+            .pending
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, p)| (p.utterance.kind.priority(), std::cmp::Reverse(p.seq)))
+            .map(|(index, _)| index);
+        if let Some(index) = next {
+            let pending = self.pending.remove(index);
+            events.push(self.show(pending.utterance, settings, now));
+        }
+    }
+
+    /// 加入一句话：空闲时立即显示，能打断时打断当前的话，否则排队
+    pub fn enqueue(&mut self, utterance: Utterance, settings: &SpeechQueueSettings, now: i64) -> Vec<SpeechEvent> {
+        let mut events = Vec::new();
+        let Some(current) = &self.current else {
+            events.push(self.show(utterance, settings, now));
+            return events;
+        };
+
+        let current_kind = current.utterance.kind;
+        if current_kind.interruptible() && utterance.kind.priority() > current_kind.priority() {
+            let interrupted = self.current.take().map(|active| active.utterance);
+            if let Some(interrupted) = interrupted {
+                events.push(SpeechEvent::Hide { id: interrupted.id.clone(), reason: EndReason::Interrupted });
+                if interrupted.kind.requeue_on_interrupt() {
+                    self.push(interrupted, now);
+                }
+            }
+            events.push(self.show(utterance, settings, now));
+        } else {
+            self.push(utterance, now);
+        }
+
+        while self.pending.len() > settings.max_pending {
+            let lowest = self
+                .pending
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, p)| (p.utterance.kind.priority(), p.seq))
+                .map(|(index, _)| index);
+            let Some(index) = lowest else { break };
+            let dropped = self.pending.remove(index);
+            events.push(SpeechEvent::Dropped { id: dropped.utterance.id, reason: EndReason::QueueFull });
+        }
+        events
+    }
+
+    /// 结束到时的气泡并显示下一条
+    pub fn tick(&mut self, settings: &SpeechQueueSettings, now: i64) -> Vec<SpeechEvent> {
+        let mut events = Vec::new();
+        let expired = match &self.current {
+            Some(active) => active.ends_at <= now,
+            None => false,
+        };
+        if expired {
+            if let Some(active) = self.current.take() {
+                events.push(SpeechEvent::Hide { id: active.utterance.id, reason: EndReason::Expired });
+            }
+        }
+        self.advance(settings, now, &mut events);
+        events
+    }
+
+    /// 关闭正在显示的气泡或撤回排队中的话
+    pub fn dismiss(&mut self, id: &str, settings: &SpeechQueueSettings, now: i64) -> Vec<SpeechEvent> {
+        let mut events = Vec::new();
+        if self.current.as_ref().is_some_and(|active| active.utterance.id == id) {
+            self.current = None;
+            events.push(SpeechEvent::Hide { id: id.to_string(), reason: EndReason::Dismissed });
+            self.advance(settings, now, &mut events);
+        } else if let Some(index) = self.pending.iter().position(|p| p.utterance.id == id) {
+            self.pending.remove(index);
+            events.push(SpeechEvent::Dropped { id: id.to_string(), reason: EndReason::Dismissed });
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn say(kind: UtteranceKind, id: &str) -> Utterance {
+        Utterance { id: id.to_string(), ..Utterance::new(kind, "你好", "test") }
+    }
+
+    fn shown(events: &[SpeechEvent]) -> Vec<&str> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                SpeechEvent::Show(active) => Some(active.utterance.id.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_interruption_rules() {
+        let settings = SpeechQueueSettings::default();
+        let mut queue = SpeechQueue::default();
+
+        assert_eq!(shown(&queue.enqueue(say(UtteranceKind::Chatter, "chatter"), &settings, 0)), ["chatter"]);
+        // 警报打断闲聊，闲聊不再显示
+        let events = queue.enqueue(say(UtteranceKind::Alert, "alert"), &settings, 10);
+        assert_eq!(events[0], SpeechEvent::Hide { id: "chatter".to_string(), reason: EndReason::Interrupted });
+        assert_eq!(shown(&events), ["alert"]);
+        assert!(queue.pending().is_empty());
+
+        let mut queue = SpeechQueue::default();
+        queue.enqueue(say(UtteranceKind::Reply, "reply"), &settings, 0);
+        // 回复显示中，警报只能排队
+        assert!(shown(&queue.enqueue(say(UtteranceKind::Alert, "alert"), &settings, 10)).is_empty());
+        queue.enqueue(say(UtteranceKind::Greeting, "greeting"), &settings, 20);
+        let ids: Vec<String> = queue.pending().into_iter().map(|u| u.id).collect();
+        assert_eq!(ids, ["alert", "greeting"]);
+
+        let end = queue.current().unwrap().ends_at;
+        assert!(queue.tick(&settings, end - 1).is_empty());
+        assert_eq!(shown(&queue.tick(&settings, end)), ["alert"]);
+
+        // 被打断的提醒重新排队
+        let mut queue = SpeechQueue::default();
+        queue.enqueue(say(UtteranceKind::Reminder, "reminder"), &settings, 0);
+        queue.enqueue(say(UtteranceKind::Reply, "reply"), &settings, 5);
+        assert_eq!(queue.pending()[0].id, "reminder");
+        let events = queue.dismiss("reply", &settings, 6);
+        assert_eq!(shown(&events), ["reminder"]);
+    }
+
+    #[test]
+    fn test_durations_staleness_and_capacity() {
+        let settings = SpeechQueueSettings { max_pending: 2, ..SpeechQueueSettings::default() };
+        assert!(settings.validate().is_ok());
+        let short = say(UtteranceKind::Chatter, "short");
+        assert_eq!(settings.display_ms(&short), 1500 + 2 * 150);
+        let long = Utterance::new(UtteranceKind::Chatter, "很".repeat(200), "test");
+        assert_eq!(settings.display_ms(&long), settings.max_display_ms.chatter);
+        assert_eq!(settings.display_ms(&short.clone().with_duration(10)), settings.min_display_ms);
+
+        let mut queue = SpeechQueue::default();
+        queue.enqueue(say(UtteranceKind::Alert, "alert"), &settings, 0);
+        queue.enqueue(say(UtteranceKind::Chatter, "chatter"), &settings, 0);
+        queue.enqueue(say(UtteranceKind::Reply, "reply"), &settings, 0);
+        // 队列已满时丢弃优先级最低的
+        let events = queue.enqueue(say(UtteranceKind::Greeting, "greeting"), &settings, 0);
+        assert_eq!(events, [SpeechEvent::Dropped { id: "chatter".to_string(), reason: EndReason::QueueFull }]);
+
+        // 问候排队超过一分钟后过期，回复不过期
+        let events = queue.tick(&settings, 120_000);
+        assert!(events.contains(&SpeechEvent::Dropped { id: "greeting".to_string(), reason: EndReason::Stale }));
+        assert_eq!(shown(&events), ["reply"]);
+        assert_eq!(queue.dismiss("reply", &settings, 120_001).len(), 1);
+        assert!(queue.is_idle());
+    }
+}
@@ -0,0 +1,324 @@
+//! # 启动耗时分析
+//!
+//! 启动流程的各阶段（日志、数据库、配置、适配器、Live2D 资源准备等）用 [`start`] 记录耗时。
+//! 时间以进程启动为起点，关键初始化与后台初始化并行执行，因此各阶段按起点与耗时画成瀑布图。
+//!
+//! 关键阶段都记录后本次启动视为完成，由 `commands::startup` 追加到历史
+//! （最多保留 [`MAX_BOOTS`] 次）并保存到应用数据目录，设置页通过 `get_startup_profile` 查看。
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 保留的启动记录数
+pub const MAX_BOOTS: usize = 20;
+
+/// 记录耗时的启动阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BootPhase {
+    Logging,
+    AuditLog,
+    LogDatabase,
+    Database,
+    Config,
+    Window,
+    Adapters,
+    SystemMonitor,
+    /// 非关键的设置加载与后台服务
+    BackgroundServices,
+    /// 前端请求的 Live2D 资源准备（每次启动只记录第一次）
+    Live2DAssets,
+}
+
+impl BootPhase {
+    pub const ALL: [BootPhase; 10] = [
+        BootPhase::Logging,
+        BootPhase::AuditLog,
+        BootPhase::LogDatabase,
+        BootPhase::Database,
+        BootPhase::Config,
+        BootPhase::Window,
+        BootPhase::Adapters,
+        BootPhase::SystemMonitor,
+        BootPhase::BackgroundServices,
+        BootPhase::Live2DAssets,
+    ];
+
+    /// 这些阶段都记录后启动视为完成
+    const READY: [BootPhase; 4] = [
+        BootPhase::Database,
+        BootPhase::Config,
+        BootPhase::Window,
+        BootPhase::BackgroundServices,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BootPhase::Logging => "日志系统",
+            BootPhase::AuditLog => "安全审计日志",
+            BootPhase::LogDatabase => "日志数据库",
+            BootPhase::Database => "主数据库",
+            BootPhase::Config => "配置加载",
+            BootPhase::Window => "主窗口",
+            BootPhase::Adapters => "适配器管理器",
+            BootPhase::SystemMonitor => "系统监控",
+            BootPhase::BackgroundServices => "后台服务",
+            BootPhase::Live2DAssets => "Live2D 资源准备",
+        }
+    }
+}
+
+/// 一个阶段的耗时
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootSpan {
+    pub phase: BootPhase,
+    /// 相对进程启动的开始时间（毫秒）
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 一次启动的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootProfile {
+    /// 进程启动时间（Unix 毫秒），同时作为记录的标识
+    pub started_at: i64,
+    pub app_version: String,
+    pub safe_mode: bool,
+    /// 从进程启动到启动完成的毫秒数
+    pub ready_ms: Option<u64>,
+    pub spans: Vec<BootSpan>,
+}
+
+impl BootProfile {
+    fn new(started_at: i64) -> Self {
+        Self {
+            started_at,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            safe_mode: false,
+            ready_ms: None,
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn span(&self, phase: BootPhase) -> Option<&BootSpan> {
+        self.spans.iter().find(|span| span.phase == phase)
+    }
+}
+
+/// 某个阶段在历史记录中的耗时汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseBreakdown {
+    pub phase: BootPhase,
+    pub label: String,
+    /// 最近一次启动的耗时
+    pub last_ms: Option<u64>,
+    pub average_ms: f64,
+    pub max_ms: u64,
+    /// 有该阶段记录的启动次数
+    pub samples: usize,
+}
+
+/// 启动历史（按时间先后排列）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileHistory {
+    #[serde(default)]
+    pub boots: Vec<BootProfile>,
+}
+
+impl ProfileHistory {
+    /// 追加或更新（同一次启动）记录，只保留最近 [`MAX_BOOTS`] 次
+    pub fn record(&mut self, profile: BootProfile) {
+        match self.boots.iter_mut().find(|boot| boot.started_at == profile.started_at) {
+            Some(existing) => *existing = profile,
+            None => self.boots.push(profile),
+        }
+        if self.boots.len() > MAX_BOOTS {
+            let excess = self.boots.len() - MAX_BOOTS;
+            self.boots.drain(..excess);
+        }
+    }
+
+    /// 各阶段耗时汇总
+    pub fn breakdown(&self) -> Vec<PhaseBreakdown> {
+        BootPhase::ALL
+            .iter()
+            .filter_map(|&phase| {
+                let durations: Vec<u64> = self
+                    .boots
+                    .iter()
+                    .filter_map(|boot| boot.span(phase).map(|span| span.duration_ms))
+                    .collect();
+                if durations.is_empty() {
+                    return None;
+                }
+                Some(PhaseBreakdown {
+                    phase,
+                    label: phase.label().to_string(),
+                    last_ms: self.boots.last().and_then(|boot| boot.span(phase)).map(|span| span.duration_ms),
+                    average_ms: durations.iter().sum::<u64>() as f64 / durations.len() as f64,
+                    max_ms: durations.iter().copied().max().unwrap_or(0),
+                    samples: durations.len(),
+                })
+            })
+            .collect()
+    }
+}
+
+struct BootRecorder {
+    process_start: Instant,
+    profile: BootProfile,
+}
+
+impl BootRecorder {
+    fn elapsed_ms(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.process_start).as_millis() as u64
+    }
+}
+
+lazy_static! {
+    static ref RECORDER: Mutex<BootRecorder> = Mutex::new(BootRecorder {
+        process_start: Instant::now(),
+        profile: BootProfile::new(Utc::now().timestamp_millis()),
+    });
+}
+
+/// 以当前时刻作为进程启动时间（在 `main` 开头调用）
+pub fn mark_process_start() {
+    lazy_static::initialize(&RECORDER);
+}
+
+/// 正在计时的阶段；调用 [`PhaseSpan::finish`] 或 [`PhaseSpan::fail`] 结束，
+/// 未结束就被丢弃（如 `?` 提前返回）时记为失败
+pub struct PhaseSpan {
+    phase: BootPhase,
+    started: Instant,
+    recorded: bool,
+}
+
+impl PhaseSpan {
+    pub fn finish(mut self) {
+        self.record(None);
+    }
+
+    pub fn fail(mut self, error: impl Into<String>) {
+        self.record(Some(error.into()));
+    }
+
+    fn record(&mut self, error: Option<String>) {
+        if std::mem::replace(&mut self.recorded, true) {
+            return;
+        }
+        let now = Instant::now();
+        let mut recorder = RECORDER.lock();
+        // 同一阶段每次启动只记录第一次
+        if recorder.profile.span(self.phase).is_some() {
+            return;
+        }
+        let span = BootSpan {
+            phase: self.phase,
+            offset_ms: recorder.elapsed_ms(self.started),
+            duration_ms: now.saturating_duration_since(self.started).as_millis() as u64,
+            success: error.is_none(),
+            error,
+        };
+        recorder.profile.spans.push(span);
+    }
+}
+
+impl Drop for PhaseSpan {
+    fn drop(&mut self) {
+        self.record(Some("阶段未正常结束".to_string()));
+    }
+}
+
+/// 开始记录一个阶段
+pub fn start(phase: BootPhase) -> PhaseSpan {
+    PhaseSpan {
+        phase,
+        started: Instant::now(),
+        recorded: false,
+    }
+}
+
+/// 本次启动是否已记录该阶段
+pub fn has_span(phase: BootPhase) -> bool {
+    RECORDER.lock().profile.span(phase).is_some()
+}
+
+/// 关键阶段都已记录时标记启动完成（`force` 用于初始化失败提前结束），返回本次调用是否完成了启动
+pub fn mark_ready(safe_mode: bool, force: bool) -> bool {
+    let mut recorder = RECORDER.lock();
+    if recorder.profile.ready_ms.is_some() {
+        return false;
+    }
+    let complete = BootPhase::READY.iter().all(|phase| recorder.profile.span(*phase).is_some());
+    if !complete && !force {
+        return false;
+    }
+    let ready_ms = recorder.elapsed_ms(Instant::now());
+    recorder.profile.ready_ms = Some(ready_ms);
+    recorder.profile.safe_mode = safe_mode;
+    true
+}
+
+/// 启动是否已完成
+pub fn is_ready() -> bool {
+    RECORDER.lock().profile.ready_ms.is_some()
+}
+
+/// 本次启动的记录
+pub fn current() -> BootProfile {
+    RECORDER.lock().profile.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boot(started_at: i64, spans: &[(BootPhase, u64)]) -> BootProfile {
+        let mut profile = BootProfile::new(started_at);
+        profile.spans = spans
+            .iter()
+            .map(|&(phase, duration_ms)| BootSpan { phase, offset_ms: 0, duration_ms, success: true, error: None })
+            .collect();
+        profile
+    }
+
+    #[test]
+    fn test_history_keeps_recent_boots() {
+        let mut history = ProfileHistory::default();
+        for i in 0..(MAX_BOOTS as i64 + 5) {
+            history.record(boot(i, &[]));
+        }
+        assert_eq!(history.boots.len(), MAX_BOOTS);
+        assert_eq!(history.boots[0].started_at, 5);
+
+        // 同一次启动的记录被更新而不是重复追加
+        let updated = boot(MAX_BOOTS as i64 + 4, &[(BootPhase::Live2DAssets, 900)]);
+        history.record(updated.clone());
+        assert_eq!(history.boots.len(), MAX_BOOTS);
+        assert_eq!(history.boots.last(), Some(&updated));
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let mut history = ProfileHistory::default();
+        history.record(boot(1, &[(BootPhase::Database, 300), (BootPhase::Adapters, 1200)]));
+        history.record(boot(2, &[(BootPhase::Database, 100)]));
+
+        let breakdown = history.breakdown();
+        assert_eq!(breakdown.len(), 2);
+        let database = &breakdown[0];
+        assert_eq!(database.phase, BootPhase::Database);
+        assert_eq!((database.last_ms, database.max_ms, database.samples), (Some(100), 300, 2));
+        assert!((database.average_ms - 200.0).abs() < f64::EPSILON);
+        // 最近一次启动没有该阶段
+        assert_eq!(breakdown[1].last_ms, None);
+        assert_eq!(breakdown[1].average_ms, 1200.0);
+    }
+}
@@ -0,0 +1,344 @@
+//! # 主题对比度校验
+//!
+//! 按 WCAG 2.1 计算主题调色板中前景/背景色的对比度，检查是否满足 AA 级要求，
+//! 并提供一组独立于主题的色盲友好调色板。
+
+use serde::{Deserialize, Serialize};
+
+/// WCAG AA 普通文本最低对比度
+pub const WCAG_AA_NORMAL_TEXT: f64 = 4.5;
+
+/// WCAG AA 大号文本/界面组件最低对比度
+pub const WCAG_AA_LARGE_TEXT: f64 = 3.0;
+
+/// 需要校验的颜色组合：(前景变量, 背景变量, 最低对比度)
+const CONTRAST_PAIRS: &[(&str, &str, f64)] = &[
+    ("foreground", "background", WCAG_AA_NORMAL_TEXT),
+    ("muted-foreground", "background", WCAG_AA_NORMAL_TEXT),
+    ("primary-foreground", "primary", WCAG_AA_NORMAL_TEXT),
+    ("secondary-foreground", "secondary", WCAG_AA_NORMAL_TEXT),
+    ("accent-foreground", "accent", WCAG_AA_NORMAL_TEXT),
+    ("success-foreground", "success", WCAG_AA_LARGE_TEXT),
+    ("warning-foreground", "warning", WCAG_AA_LARGE_TEXT),
+    ("error-foreground", "error", WCAG_AA_LARGE_TEXT),
+    ("info-foreground", "info", WCAG_AA_LARGE_TEXT),
+    ("ring", "background", WCAG_AA_LARGE_TEXT),
+];
+
+/// RGB 颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// 解析颜色字符串
+    ///
+    /// 支持 `#rgb`、`#rrggbb`、`#rrggbbaa`、`rgb(r, g, b)` 以及
+    /// 前端 CSS 变量使用的 `r g b` 空格分隔格式。
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if let Some(hex) = value.strip_prefix('#') {
+            // 先排除非十六进制字符，避免按字节切片时落在多字节字符中间
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let hex = match hex.len() {
+                3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+                6 | 8 => hex[..6].to_string(),
+                _ => return None,
+            };
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(Self { r: channel(0)?, g: channel(2)?, b: channel(4)? });
+        }
+
+        let inner = value
+            .strip_prefix("rgba(")
+            .or_else(|| value.strip_prefix("rgb("))
+            .and_then(|v| v.strip_suffix(')'))
+            .unwrap_or(value);
+
+        let channels: Vec<u8> = inner
+            .split(|c: char| c == ',' || c.is_whitespace() || c == '/')
+            .filter(|part| !part.is_empty())
+            .take(3)
+            .map(|part| part.parse::<u8>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        match channels.as_slice() {
+            [r, g, b] => Some(Self { r: *r, g: *g, b: *b }),
+            _ => None,
+        }
+    }
+
+    /// WCAG 相对亮度
+    pub fn relative_luminance(&self) -> f64 {
+        fn linearize(channel: u8) -> f64 {
+            let c = channel as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        0.2126 * linearize(self.r) + 0.7152 * linearize(self.g) + 0.0722 * linearize(self.b)
+    }
+
+    /// 以 `#rrggbb` 格式输出
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// 计算两个颜色的对比度（1.0 - 21.0）
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (la, lb) = (a.relative_luminance(), b.relative_luminance());
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// 单个颜色组合的校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContrastCheck {
+    pub foreground: String,
+    pub background: String,
+    pub ratio: f64,
+    pub required: f64,
+    pub passes: bool,
+}
+
+/// 主题对比度报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContrastReport {
+    pub checks: Vec<ContrastCheck>,
+    pub failures: Vec<ContrastCheck>,
+}
+
+impl ContrastReport {
+    /// 是否全部满足 WCAG AA
+    pub fn passes_aa(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// 生成可读的警告信息
+    pub fn warnings(&self) -> Vec<String> {
+        self.failures
+            .iter()
+            .map(|f| {
+                format!(
+                    "{} / {} 对比度 {:.2}:1 低于 WCAG AA 要求的 {:.1}:1",
+                    f.foreground, f.background, f.ratio, f.required
+                )
+            })
+            .collect()
+    }
+}
+
+/// 规范化变量名：`--color-primary-foreground`、`primaryForeground`、`primary_foreground`
+/// 均映射为 `primary-foreground`
+fn normalize_key(key: &str) -> String {
+    let key = key.trim_start_matches("--").trim_start_matches("color-");
+    let mut normalized = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                normalized.push('-');
+            }
+            normalized.push(c.to_ascii_lowercase());
+        } else if c == '_' {
+            normalized.push('-');
+        } else {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// 从主题变量中提取调色板（兼容顶层或 `colors` 子对象）
+pub fn extract_palette(variables: &serde_json::Value) -> std::collections::HashMap<String, Rgb> {
+    let mut palette = std::collections::HashMap::new();
+    let sources = [Some(variables), variables.get("colors")];
+
+    for source in sources.into_iter().flatten() {
+        if let Some(map) = source.as_object() {
+            for (key, value) in map {
+                if let Some(color) = value.as_str().and_then(Rgb::parse) {
+                    palette.insert(normalize_key(key), color);
+                }
+            }
+        }
+    }
+
+    palette
+}
+
+/// 校验主题变量中的对比度
+///
+/// 仅校验两个颜色都存在的组合，缺失的变量由前端回退到默认值，不视为失败。
+pub fn validate_theme_contrast(variables: &serde_json::Value) -> ContrastReport {
+    let palette = extract_palette(variables);
+    let mut report = ContrastReport::default();
+
+    for (fg_key, bg_key, required) in CONTRAST_PAIRS {
+        if let (Some(fg), Some(bg)) = (palette.get(*fg_key), palette.get(*bg_key)) {
+            let ratio = contrast_ratio(*fg, *bg);
+            let check = ContrastCheck {
+                foreground: fg_key.to_string(),
+                background: bg_key.to_string(),
+                ratio: (ratio * 100.0).round() / 100.0,
+                required: *required,
+                passes: ratio >= *required,
+            };
+            if !check.passes {
+                report.failures.push(check.clone());
+            }
+            report.checks.push(check);
+        }
+    }
+
+    report
+}
+
+// ================================
+// 色盲友好调色板
+// ================================
+
+/// 色盲友好调色板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorBlindPalette {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// 覆盖的 CSS 变量（`r g b` 格式，与前端变量一致）
+    pub variables: std::collections::HashMap<String, String>,
+}
+
+/// 内置调色板：(ID, 名称, 说明, [(变量, 颜色)])
+const BUILTIN_PALETTES: &[(&str, &str, &str, &[(&str, &str)])] = &[
+    (
+        "okabe-ito",
+        "Okabe-Ito",
+        "适用于红绿色盲（deuteranopia/protanopia）的通用安全配色",
+        &[
+            ("--color-success", "0 158 115"),
+            ("--color-warning", "230 159 0"),
+            ("--color-error", "213 94 0"),
+            ("--color-info", "0 114 178"),
+            ("--color-accent", "204 121 167"),
+            ("--color-ring", "0 114 178"),
+        ],
+    ),
+    (
+        "tritan-safe",
+        "Tritan Safe",
+        "适用于蓝黄色盲（tritanopia）的配色，避免蓝/黄作为区分色",
+        &[
+            ("--color-success", "0 128 128"),
+            ("--color-warning", "204 51 17"),
+            ("--color-error", "170 34 68"),
+            ("--color-info", "51 34 136"),
+            ("--color-accent", "238 119 51"),
+            ("--color-ring", "51 34 136"),
+        ],
+    ),
+    (
+        "monochrome",
+        "Monochrome",
+        "仅依靠明度区分状态，适用于全色盲（achromatopsia）",
+        &[
+            ("--color-success", "38 38 38"),
+            ("--color-warning", "89 89 89"),
+            ("--color-error", "0 0 0"),
+            ("--color-info", "64 64 64"),
+            ("--color-accent", "115 115 115"),
+            ("--color-ring", "0 0 0"),
+        ],
+    ),
+];
+
+/// 获取所有内置色盲友好调色板
+pub fn color_blind_palettes() -> Vec<ColorBlindPalette> {
+    BUILTIN_PALETTES
+        .iter()
+        .map(|(id, name, description, vars)| ColorBlindPalette {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            variables: vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        })
+        .collect()
+}
+
+/// 按ID查找色盲友好调色板
+pub fn find_color_blind_palette(id: &str) -> Option<ColorBlindPalette> {
+    color_blind_palettes().into_iter().find(|p| p.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_formats() {
+        let white = Rgb { r: 255, g: 255, b: 255 };
+        assert_eq!(Rgb::parse("#fff"), Some(white));
+        assert_eq!(Rgb::parse("#ffffff"), Some(white));
+        assert_eq!(Rgb::parse("#ffffff80"), Some(white));
+        assert_eq!(Rgb::parse("rgb(255, 255, 255)"), Some(white));
+        assert_eq!(Rgb::parse("255 255 255"), Some(white));
+        assert_eq!(Rgb::parse("not-a-color"), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_non_ascii_hex() {
+        assert_eq!(Rgb::parse("#aébcd"), None);
+        assert_eq!(Rgb::parse("#ééé"), None);
+        assert_eq!(Rgb::parse("#+f+f+f"), None);
+    }
+
+    #[test]
+    fn test_contrast_ratio_extremes() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb { r: 255, g: 255, b: 255 };
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(white, white) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(normalize_key("--color-primary-foreground"), "primary-foreground");
+        assert_eq!(normalize_key("primaryForeground"), "primary-foreground");
+        assert_eq!(normalize_key("primary_foreground"), "primary-foreground");
+    }
+
+    #[test]
+    fn test_validate_theme_contrast_reports_failures() {
+        let variables = serde_json::json!({
+            "colors": {
+                "background": "#ffffff",
+                "foreground": "#111827",
+                "mutedForeground": "#d1d5db",
+            }
+        });
+
+        let report = validate_theme_contrast(&variables);
+        assert_eq!(report.checks.len(), 2);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].foreground, "muted-foreground");
+        assert!(!report.passes_aa());
+    }
+
+    #[test]
+    fn test_builtin_palettes_are_parseable() {
+        for palette in color_blind_palettes() {
+            for value in palette.variables.values() {
+                assert!(Rgb::parse(value).is_some(), "{} 中存在无效颜色", palette.id);
+            }
+        }
+        assert!(find_color_blind_palette("okabe-ito").is_some());
+    }
+}
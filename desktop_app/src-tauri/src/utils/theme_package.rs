@@ -0,0 +1,241 @@
+//! # 主题包
+//!
+//! 主题市场分发的主题包是 zip 文件：
+//!
+//! - `manifest.json`：主题 ID、名称、版本、主题变量等（见 [`ThemeManifest`]）
+//! - 样式表（默认 `theme.css`），以及样式表引用的图片、字体等资源
+//! - `signature.sig`：Base64 编码的 Ed25519 签名
+//!
+//! 签名针对包内其余全部文件计算的摘要（见 [`package_digest`]），任何文件被增删或修改都会
+//! 导致校验失败。签名公钥为内置的市场公钥，自托管市场可通过环境变量 `THEME_SIGNING_KEYS`
+//! （逗号分隔的 Base64 公钥）追加。安装与预览见 `commands::theme`。
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const SIGNATURE_FILE: &str = "signature.sig";
+/// 主题包最大体积
+pub const MAX_PACKAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// 主题市场的签名公钥（Base64）
+const MARKET_SIGNING_KEYS: &[&str] = &["AfAecq6MIwLB80r0EjYlaO0J7xKcqi8wehtcZwzO52I="];
+/// 允许打包的资源类型
+const ALLOWED_EXTENSIONS: &[&str] = &[
+    "json", "css", "png", "jpg", "jpeg", "webp", "gif", "svg", "woff", "woff2", "ttf", "otf", "sig",
+];
+
+fn default_category() -> String {
+    "general".to_string()
+}
+
+fn default_stylesheet() -> String {
+    "theme.css".to_string()
+}
+
+/// 主题包清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_category")]
+    pub category: String,
+    #[serde(default)]
+    pub is_dark: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 主题变量（颜色、字体等）
+    #[serde(default)]
+    pub variables: serde_json::Value,
+    /// 包内样式表路径
+    #[serde(default = "default_stylesheet")]
+    pub stylesheet: String,
+    /// 包内预览图路径
+    #[serde(default)]
+    pub preview_image: Option<String>,
+}
+
+impl ThemeManifest {
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_id = !self.id.is_empty()
+            && self.id.len() <= 64
+            && self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err("主题 ID 只能包含字母、数字、`-` 与 `_`，且不超过 64 个字符".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err("主题名称不能为空".to_string());
+        }
+        if self.version.trim().is_empty() {
+            return Err("主题版本不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 包内文件的摘要：按路径排序，依次写入路径、0 字节与文件内容的 SHA-256
+pub fn package_digest(files: &BTreeMap<String, Vec<u8>>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for (path, content) in files {
+        hasher.update(path.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(Sha256::digest(content));
+    }
+    hasher.finalize().into()
+}
+
+/// 受信任的签名公钥
+pub fn trusted_keys() -> Vec<Vec<u8>> {
+    let extra = std::env::var("THEME_SIGNING_KEYS").unwrap_or_default();
+    MARKET_SIGNING_KEYS
+        .iter()
+        .copied()
+        .chain(extra.split(',').map(str::trim).filter(|key| !key.is_empty()))
+        .filter_map(|key| base64::engine::general_purpose::STANDARD.decode(key).ok())
+        .collect()
+}
+
+/// 解析后的主题包
+#[derive(Debug, Clone)]
+pub struct ThemePackage {
+    pub manifest: ThemeManifest,
+    /// 除签名外的全部文件
+    pub files: BTreeMap<String, Vec<u8>>,
+    pub signature: Option<Vec<u8>>,
+}
+
+impl ThemePackage {
+    /// 读取 zip 格式的主题包
+    pub fn read(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() as u64 > MAX_PACKAGE_BYTES {
+            return Err("主题包过大".to_string());
+        }
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("读取主题包失败: {}", e))?;
+
+        let mut files = BTreeMap::new();
+        let mut total: u64 = 0;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| format!("读取主题包条目失败: {}", e))?;
+            if entry.is_dir() {
+                continue;
+            }
+            // 拒绝绝对路径与 `..`，防止解压到主题目录之外
+            let path = entry
+                .enclosed_name()
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+                .ok_or_else(|| format!("主题包包含非法路径: {}", entry.name()))?;
+            let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+            if !ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
+                return Err(format!("主题包包含不允许的文件类型: {}", path));
+            }
+            total += entry.size();
+            if total > MAX_PACKAGE_BYTES {
+                return Err("主题包解压后过大".to_string());
+            }
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).map_err(|e| format!("读取 {} 失败: {}", path, e))?;
+            files.insert(path, content);
+        }
+
+        let signature = match files.remove(SIGNATURE_FILE) {
+            Some(encoded) => Some(
+                base64::engine::general_purpose::STANDARD
+                    .decode(String::from_utf8_lossy(&encoded).trim())
+                    .map_err(|_| "主题包签名格式无效".to_string())?,
+            ),
+            None => None,
+        };
+        let manifest: ThemeManifest = serde_json::from_slice(files.get(MANIFEST_FILE).ok_or("主题包缺少 manifest.json")?)
+            .map_err(|e| format!("解析 manifest.json 失败: {}", e))?;
+        manifest.validate()?;
+        if !files.contains_key(&manifest.stylesheet) {
+            return Err(format!("主题包缺少样式表 {}", manifest.stylesheet));
+        }
+
+        Ok(Self { manifest, files, signature })
+    }
+
+    /// 校验签名：至少一个受信任的公钥能验证通过
+    pub fn verify(&self, keys: &[Vec<u8>]) -> Result<(), String> {
+        let signature = self.signature.as_ref().ok_or("主题包未签名")?;
+        let digest = package_digest(&self.files);
+        if keys
+            .iter()
+            .any(|key| UnparsedPublicKey::new(&ED25519, key).verify(&digest, signature).is_ok())
+        {
+            Ok(())
+        } else {
+            Err("主题包签名无效或来自未受信任的发布者".to_string())
+        }
+    }
+
+    pub fn stylesheet(&self) -> Result<String, String> {
+        let bytes = self.files.get(&self.manifest.stylesheet).ok_or("主题包缺少样式表")?;
+        String::from_utf8(bytes.clone()).map_err(|_| "样式表不是有效的 UTF-8 文本".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use std::io::Write;
+
+    fn build_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer.start_file(*path, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn signed_package(key_pair: &Ed25519KeyPair, css: &[u8]) -> Vec<u8> {
+        let manifest = br##"{"id":"sakura","name":"Sakura","version":"1.0.0","variables":{"primary":"#ff88aa"}}"##;
+        let files: BTreeMap<String, Vec<u8>> = [
+            (MANIFEST_FILE.to_string(), manifest.to_vec()),
+            ("theme.css".to_string(), b"body { color: pink; }".to_vec()),
+        ]
+        .into_iter()
+        .collect();
+        let signature = base64::engine::general_purpose::STANDARD.encode(key_pair.sign(&package_digest(&files)));
+        build_zip(&[(MANIFEST_FILE, manifest), ("theme.css", css), (SIGNATURE_FILE, signature.as_bytes())])
+    }
+
+    #[test]
+    fn test_signed_package_verifies_and_detects_tampering() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let keys = vec![key_pair.public_key().as_ref().to_vec()];
+
+        let package = ThemePackage::read(&signed_package(&key_pair, b"body { color: pink; }")).unwrap();
+        assert_eq!(package.manifest.id, "sakura");
+        assert_eq!(package.manifest.category, "general");
+        assert!(package.verify(&keys).is_ok());
+        assert!(package.verify(&trusted_keys()).is_err());
+
+        let tampered = ThemePackage::read(&signed_package(&key_pair, b"body { color: red; }")).unwrap();
+        assert!(tampered.verify(&keys).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_packages() {
+        let manifest: &[u8] = br#"{"id":"sakura","name":"Sakura","version":"1.0.0"}"#;
+        assert!(ThemePackage::read(&build_zip(&[("theme.css", b"")])).is_err());
+        assert!(ThemePackage::read(&build_zip(&[(MANIFEST_FILE, manifest)])).is_err());
+        assert!(ThemePackage::read(&build_zip(&[(MANIFEST_FILE, manifest), ("theme.css", b""), ("run.exe", b"")])).is_err());
+        assert!(ThemePackage::read(&build_zip(&[(MANIFEST_FILE, manifest), ("theme.css", b""), ("../evil.css", b"")])).is_err());
+
+        let unsigned = ThemePackage::read(&build_zip(&[(MANIFEST_FILE, manifest), ("theme.css", b"")])).unwrap();
+        assert_eq!(unsigned.verify(&trusted_keys()).unwrap_err(), "主题包未签名");
+    }
+}
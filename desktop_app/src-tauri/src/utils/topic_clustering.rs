@@ -0,0 +1,292 @@
+//! 会话话题聚类
+//!
+//! 对会话向量做球面 k-means（余弦距离），k 按轮廓系数在候选范围内自动选择；
+//! 初始中心取离全局均值最近的点再依次取最远点，结果可复现。
+//! 另提供按簇提取关键词的工具，用于 LLM 命名话题的提示和命名失败时的兜底标签。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// k-means 最大迭代次数
+const MAX_ITERATIONS: usize = 50;
+/// 最优轮廓系数低于该值时认为没有明显的话题结构，全部归为一簇
+const MIN_SILHOUETTE: f32 = 0.05;
+/// 只有两个会话时，相似度达到该值才归为同一话题
+const SAME_TOPIC_SIMILARITY: f32 = 0.8;
+
+/// 聚类结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clustering {
+    /// 每个输入所属的簇（0..k）
+    pub assignments: Vec<usize>,
+    pub k: usize,
+}
+
+impl Clustering {
+    /// 各簇的成员下标（按簇大小降序）
+    pub fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.k];
+        for (index, cluster) in self.assignments.iter().enumerate() {
+            members[*cluster].push(index);
+        }
+        members.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        members
+    }
+}
+
+fn normalized(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter().map(|x| x / norm).collect()
+    } else {
+        vector.to_vec()
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    (1.0 - dot(a, b)).max(0.0)
+}
+
+fn centroid(points: &[Vec<f32>], members: impl Iterator<Item = usize>) -> Vec<f32> {
+    let mut sum = vec![0.0; points.first().map_or(0, Vec::len)];
+    for index in members {
+        for (s, v) in sum.iter_mut().zip(&points[index]) {
+            *s += v;
+        }
+    }
+    normalized(&sum)
+}
+
+fn kmeans(points: &[Vec<f32>], k: usize) -> Vec<usize> {
+    // 初始中心：离全局均值最近的点，之后每次取离已有中心最远的点
+    let mean = centroid(points, 0..points.len());
+    let first = (0..points.len())
+        .min_by(|&a, &b| distance(&points[a], &mean).total_cmp(&distance(&points[b], &mean)))
+        .unwrap_or(0);
+    let mut centers = vec![points[first].clone()];
+    while centers.len() < k {
+        let next = (0..points.len())
+            .max_by(|&a, &b| {
+                let nearest = |i: usize| centers.iter().map(|c| distance(&points[i], c)).fold(f32::INFINITY, f32::min);
+                nearest(a).total_cmp(&nearest(b)).then_with(|| b.cmp(&a))
+            })
+            .unwrap_or(0);
+        centers.push(points[next].clone());
+    }
+
+    let mut assignments = vec![usize::MAX; points.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (index, point) in points.iter().enumerate() {
+            let nearest = (0..k)
+                .min_by(|&a, &b| distance(point, &centers[a]).total_cmp(&distance(point, &centers[b])))
+                .unwrap_or(0);
+            if assignments[index] != nearest {
+                assignments[index] = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        for (cluster, center) in centers.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..points.len()).filter(|&i| assignments[i] == cluster).collect();
+            // 空簇保留原中心
+            if !members.is_empty() {
+                *center = centroid(points, members.into_iter());
+            }
+        }
+    }
+    assignments
+}
+
+/// 平均轮廓系数（单成员簇的系数记为 0）
+fn silhouette(points: &[Vec<f32>], assignments: &[usize], k: usize) -> f32 {
+    let n = points.len();
+    let total: f32 = (0..n)
+        .map(|i| {
+            let mut sums = vec![0.0f32; k];
+            let mut counts = vec![0usize; k];
+            for j in (0..n).filter(|&j| j != i) {
+                sums[assignments[j]] += distance(&points[i], &points[j]);
+                counts[assignments[j]] += 1;
+            }
+            let own = assignments[i];
+            if counts[own] == 0 {
+                return 0.0;
+            }
+            let a = sums[own] / counts[own] as f32;
+            let b = (0..k)
+                .filter(|&c| c != own && counts[c] > 0)
+                .map(|c| sums[c] / counts[c] as f32)
+                .fold(f32::INFINITY, f32::min);
+            if !b.is_finite() || a.max(b) <= 0.0 {
+                0.0
+            } else {
+                (b - a) / a.max(b)
+            }
+        })
+        .sum();
+    total / n as f32
+}
+
+/// 把 `assignments` 重新编号为连续的 0..k
+fn compact(assignments: Vec<usize>) -> Clustering {
+    let mut mapping = BTreeMap::new();
+    let assignments: Vec<usize> = assignments
+        .into_iter()
+        .map(|cluster| {
+            let next = mapping.len();
+            *mapping.entry(cluster).or_insert(next)
+        })
+        .collect();
+    Clustering { k: mapping.len(), assignments }
+}
+
+/// 聚类，簇数不超过 `max_k`
+pub fn cluster(vectors: &[Vec<f32>], max_k: usize) -> Clustering {
+    let points: Vec<Vec<f32>> = vectors.iter().map(|v| normalized(v)).collect();
+    match points.len() {
+        0 => return Clustering { assignments: Vec::new(), k: 0 },
+        1 => return Clustering { assignments: vec![0], k: 1 },
+        2 => {
+            let same = dot(&points[0], &points[1]) >= SAME_TOPIC_SIMILARITY || max_k < 2;
+            return Clustering { assignments: vec![0, if same { 0 } else { 1 }], k: if same { 1 } else { 2 } };
+        }
+        _ => {}
+    }
+
+    let mut best: Option<(f32, Vec<usize>, usize)> = None;
+    for k in 2..=max_k.min(points.len() - 1) {
+        let assignments = kmeans(&points, k);
+        let score = silhouette(&points, &assignments, k);
+        let better = match &best {
+            Some((best_score, _, _)) => score > *best_score,
+            None => true,
+        };
+        if better {
+            best = Some((score, assignments, k));
+        }
+    }
+    match best {
+        Some((score, assignments, _)) if score >= MIN_SILHOUETTE => compact(assignments),
+        _ => Clustering { assignments: vec![0; points.len()], k: 1 },
+    }
+}
+
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "you", "are", "with", "that", "this", "what", "how", "can", "not", "have", "but", "was",
+    "your", "from", "about", "please", "thanks",
+];
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF)
+}
+
+/// 文本中的词项：拉丁字母单词（小写，至少 3 个字母）与连续中日韩字符的二元组
+fn terms(text: &str) -> HashSet<String> {
+    let mut terms = HashSet::new();
+    let mut word = String::new();
+    let mut cjk: Vec<char> = Vec::new();
+    // 末尾补一个分隔符，统一处理最后一个词
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphabetic() {
+            word.push(c.to_ascii_lowercase());
+        } else if word.len() >= 3 && !STOP_WORDS.contains(&word.as_str()) {
+            terms.insert(std::mem::take(&mut word));
+        } else {
+            word.clear();
+        }
+
+        if is_cjk(c) {
+            cjk.push(c);
+        } else {
+            terms.extend(cjk.windows(2).map(|pair| pair.iter().collect::<String>()));
+            cjk.clear();
+        }
+    }
+    terms
+}
+
+/// 提取某个簇区别于其他会话的关键词（簇内文档频率 / 全局文档频率）
+pub fn cluster_keywords(texts: &[String], members: &[usize], limit: usize) -> Vec<String> {
+    let documents: Vec<HashSet<String>> = texts.iter().map(|t| terms(t)).collect();
+    let mut global: HashMap<&str, usize> = HashMap::new();
+    for document in &documents {
+        for term in document {
+            *global.entry(term.as_str()).or_default() += 1;
+        }
+    }
+    let mut local: HashMap<&str, usize> = HashMap::new();
+    for &index in members {
+        for term in &documents[index] {
+            *local.entry(term.as_str()).or_default() += 1;
+        }
+    }
+
+    // 只出现在一个会话里的词不具代表性（簇只有一个会话时除外）
+    let min_count = if members.len() > 1 { 2 } else { 1 };
+    let mut scored: Vec<(&str, f32, usize)> = local
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(term, count)| (term, count as f32 * count as f32 / global[term] as f32, count))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| b.2.cmp(&a.2)).then_with(|| a.0.cmp(b.0)));
+    scored.into_iter().take(limit).map(|(term, _, _)| term.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn around(center: &[f32], jitter: f32) -> Vec<f32> {
+        center.iter().enumerate().map(|(i, v)| v + jitter * ((i % 3) as f32 - 1.0)).collect()
+    }
+
+    #[test]
+    fn test_cluster_separates_topics() {
+        let topics = [
+            [1.0, 0.0, 0.0, 0.0, 0.2, 0.0],
+            [0.0, 1.0, 0.0, 0.1, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0, 0.0, 0.3],
+        ];
+        let mut vectors = Vec::new();
+        for i in 0..12 {
+            vectors.push(around(&topics[i % 3], 0.02 * (i / 3) as f32));
+        }
+        let clustering = cluster(&vectors, 6);
+        assert_eq!(clustering.k, 3);
+        for i in 0..12 {
+            assert_eq!(clustering.assignments[i], clustering.assignments[i % 3]);
+        }
+        assert_eq!(clustering.members().iter().map(Vec::len).collect::<Vec<_>>(), vec![4, 4, 4]);
+    }
+
+    #[test]
+    fn test_cluster_small_inputs() {
+        assert_eq!(cluster(&[], 5).k, 0);
+        assert_eq!(cluster(&[vec![1.0, 0.0]], 5).k, 1);
+        assert_eq!(cluster(&[vec![1.0, 0.0], vec![0.99, 0.05]], 5).k, 1);
+        assert_eq!(cluster(&[vec![1.0, 0.0], vec![0.0, 1.0]], 5).k, 2);
+    }
+
+    #[test]
+    fn test_cluster_keywords() {
+        let texts: Vec<String> = [
+            "帮我复习 Rust 生命周期",
+            "Rust 生命周期报错怎么办",
+            "今天的晚饭吃什么",
+            "晚饭想吃火锅",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let keywords = cluster_keywords(&texts, &[0, 1], 5);
+        assert!(keywords.contains(&"rust".to_string()));
+        assert!(keywords.contains(&"生命".to_string()));
+        assert!(!keywords.iter().any(|k| k == "晚饭"));
+    }
+}
@@ -0,0 +1,194 @@
+//! # 托盘扩展菜单项
+//!
+//! 适配器与工作流可以在运行时向托盘添加菜单项，菜单项显示在托盘菜单的「扩展」子菜单中。
+//! 每个菜单项属于一个所有者（适配器的某个操作，或某个工作流），点击时由所有者处理。
+//!
+//! 托盘菜单 ID 为 `plugin:<所有者>:<菜单项 ID>`，与内置菜单项不会冲突。菜单项只保存在内存中，
+//! 应用重启后需要重新注册。注册、托盘重建与点击分发见 `commands::tray_menu`。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 扩展菜单项的托盘菜单 ID 前缀
+pub const PLUGIN_ITEM_PREFIX: &str = "plugin:";
+/// 每个所有者最多注册的菜单项数
+pub const MAX_ITEMS_PER_OWNER: usize = 5;
+/// 菜单文字最大长度（字符）
+pub const MAX_LABEL_CHARS: usize = 40;
+
+/// 菜单项的所有者，决定点击时由谁处理
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrayMenuOwner {
+    /// 点击时执行适配器的操作
+    Adapter { adapter_id: String, action: String },
+    /// 点击时执行工作流
+    Workflow { workflow_id: String },
+}
+
+impl TrayMenuOwner {
+    /// 所有者标识，用于组成菜单 ID
+    pub fn key(&self) -> String {
+        match self {
+            Self::Adapter { adapter_id, .. } => format!("adapter:{}", adapter_id),
+            Self::Workflow { workflow_id } => format!("workflow:{}", workflow_id),
+        }
+    }
+}
+
+/// 注册的菜单项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrayMenuItem {
+    /// 所有者内唯一的菜单项 ID
+    pub item_id: String,
+    pub label: String,
+    pub owner: TrayMenuOwner,
+    /// 点击时传给适配器操作的参数或工作流的输入
+    #[serde(default)]
+    pub params: HashMap<String, Value>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl TrayMenuItem {
+    /// 托盘菜单 ID
+    pub fn menu_id(&self) -> String {
+        format!("{}{}:{}", PLUGIN_ITEM_PREFIX, self.owner.key(), self.item_id)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_id = |id: &str| {
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        };
+        if !valid_id(&self.item_id) {
+            return Err("菜单项 ID 只能包含字母、数字、`_`、`-` 与 `.`".to_string());
+        }
+        let owner_valid = match &self.owner {
+            TrayMenuOwner::Adapter { adapter_id, action } => valid_id(adapter_id) && !action.trim().is_empty(),
+            TrayMenuOwner::Workflow { workflow_id } => valid_id(workflow_id),
+        };
+        if !owner_valid {
+            return Err("菜单项的所有者无效".to_string());
+        }
+        let label_chars = self.label.trim().chars().count();
+        if label_chars == 0 || label_chars > MAX_LABEL_CHARS {
+            return Err(format!("菜单文字长度必须在 1 到 {} 个字符之间", MAX_LABEL_CHARS));
+        }
+        Ok(())
+    }
+}
+
+/// 是否为扩展菜单项的托盘菜单 ID
+pub fn is_plugin_item(menu_id: &str) -> bool {
+    menu_id.starts_with(PLUGIN_ITEM_PREFIX)
+}
+
+/// 扩展菜单项列表（按注册顺序显示）
+#[derive(Debug, Clone, Default)]
+pub struct TrayMenuRegistry {
+    items: Vec<TrayMenuItem>,
+}
+
+impl TrayMenuRegistry {
+    pub fn items(&self) -> &[TrayMenuItem] {
+        &self.items
+    }
+
+    pub fn find(&self, menu_id: &str) -> Option<&TrayMenuItem> {
+        self.items.iter().find(|item| item.menu_id() == menu_id)
+    }
+
+    /// 注册菜单项，同一菜单 ID 的旧菜单项被原位替换；返回菜单 ID
+    pub fn register(&mut self, item: TrayMenuItem) -> Result<String, String> {
+        item.validate()?;
+        let menu_id = item.menu_id();
+        if let Some(existing) = self.items.iter_mut().find(|existing| existing.menu_id() == menu_id) {
+            *existing = item;
+            return Ok(menu_id);
+        }
+
+        let owner_key = item.owner.key();
+        let owned = self.items.iter().filter(|existing| existing.owner.key() == owner_key).count();
+        if owned >= MAX_ITEMS_PER_OWNER {
+            return Err(format!("每个适配器或工作流最多注册 {} 个托盘菜单项", MAX_ITEMS_PER_OWNER));
+        }
+        self.items.push(item);
+        Ok(menu_id)
+    }
+
+    /// 移除菜单项，返回是否存在
+    pub fn unregister(&mut self, menu_id: &str) -> bool {
+        let before = self.items.len();
+        self.items.retain(|item| item.menu_id() != menu_id);
+        self.items.len() != before
+    }
+
+    /// 移除所有者的全部菜单项，返回移除数量
+    pub fn remove_owner(&mut self, owner_key: &str) -> usize {
+        let before = self.items.len();
+        self.items.retain(|item| item.owner.key() != owner_key);
+        before - self.items.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter_item(item_id: &str) -> TrayMenuItem {
+        TrayMenuItem {
+            item_id: item_id.to_string(),
+            label: "翻译剪贴板".to_string(),
+            owner: TrayMenuOwner::Adapter {
+                adapter_id: "translator".to_string(),
+                action: "translate_clipboard".to_string(),
+            },
+            params: HashMap::new(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_register_replaces_and_limits_per_owner() {
+        let mut registry = TrayMenuRegistry::default();
+        let menu_id = registry.register(adapter_item("translate")).unwrap();
+        assert_eq!(menu_id, "plugin:adapter:translator:translate");
+        assert!(is_plugin_item(&menu_id));
+        assert!(!is_plugin_item("quit"));
+
+        let renamed = TrayMenuItem { label: "翻译".to_string(), ..adapter_item("translate") };
+        registry.register(renamed).unwrap();
+        assert_eq!(registry.items().len(), 1);
+        assert_eq!(registry.find(&menu_id).unwrap().label, "翻译");
+
+        for i in 1..MAX_ITEMS_PER_OWNER {
+            registry.register(adapter_item(&format!("item{}", i))).unwrap();
+        }
+        assert!(registry.register(adapter_item("overflow")).is_err());
+
+        // 其他所有者不受影响
+        let workflow = TrayMenuItem {
+            owner: TrayMenuOwner::Workflow { workflow_id: "daily-report".to_string() },
+            ..adapter_item("run")
+        };
+        assert!(registry.register(workflow).is_ok());
+        assert_eq!(registry.remove_owner("adapter:translator"), MAX_ITEMS_PER_OWNER);
+        assert_eq!(registry.items().len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_invalid_items() {
+        assert!(adapter_item("bad id").validate().is_err());
+        assert!(TrayMenuItem { label: " ".to_string(), ..adapter_item("ok") }.validate().is_err());
+        let no_action = TrayMenuItem {
+            owner: TrayMenuOwner::Adapter { adapter_id: "translator".to_string(), action: String::new() },
+            ..adapter_item("ok")
+        };
+        assert!(no_action.validate().is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! # 语音合成（TTS）
+//!
+//! 把文本合成为 WAV 文件，由 `commands::tts` 通过 TTS 输出设备播放并同步口型。引擎可替换：
+//!
+//! - `system`：系统自带的语音（Windows SAPI、macOS `say`、Linux `espeak-ng`）
+//! - `local`：本地模型的命令行程序（如 Piper），参数中的占位符在合成时替换
+//! - `remote`：OpenAI 兼容的 `/audio/speech` 接口
+//!
+//! 合成前去掉代码块、链接与 Markdown 符号，并在句末截断过长的文本。
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// 单次合成的超时
+const SYNTH_TIMEOUT: Duration = Duration::from_secs(60);
+/// 朗读的最大字数上限
+const MAX_CHARS_LIMIT: usize = 5_000;
+
+/// 一次合成请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct TtsRequest {
+    pub text: String,
+    /// 音色（引擎相关：系统语音名、模型路径或接口的 voice 参数）
+    pub voice: Option<String>,
+    /// 语音区域代码，如 `zh-CN`
+    pub locale: String,
+    /// 语速倍率（1.0 为正常）
+    pub rate: f32,
+}
+
+/// 语音合成引擎
+#[async_trait]
+pub trait TtsEngine: Send + Sync {
+    /// 合成 `request.text`，把 WAV 写入 `output`
+    async fn synthesize(&self, request: &TtsRequest, output: &Path) -> Result<(), String>;
+}
+
+/// 引擎配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TtsEngineConfig {
+    #[default]
+    System,
+    /// 本地命令行合成程序；参数中可使用 `{text_file}`、`{output}`、`{voice}`、`{locale}`、`{rate}`
+    Local {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// 通过标准输入传入文本（Piper 等）
+        #[serde(default)]
+        stdin_text: bool,
+    },
+    /// OpenAI 兼容的 `/audio/speech` 接口
+    Remote {
+        base_url: String,
+        model: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key: Option<String>,
+    },
+}
+
+impl TtsEngineConfig {
+    /// 引擎指纹，用于区分缓存
+    pub fn fingerprint(&self) -> String {
+        match self {
+            Self::System => "system".to_string(),
+            Self::Local { program, args, .. } => format!("local:{}:{}", program, args.join(" ")),
+            Self::Remote { base_url, model, .. } => format!("remote:{}/{}", base_url, model),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::System => Ok(()),
+            Self::Local { program, args, stdin_text } => {
+                if program.trim().is_empty() {
+                    return Err("本地语音引擎需要程序路径".to_string());
+                }
+                if !args.iter().any(|arg| arg.contains("{output}")) {
+                    return Err("本地语音引擎的参数必须包含 {output}".to_string());
+                }
+                if !stdin_text && !args.iter().any(|arg| arg.contains("{text_file}")) {
+                    return Err("本地语音引擎需要通过 {text_file} 或标准输入传入文本".to_string());
+                }
+                Ok(())
+            }
+            Self::Remote { base_url, model, .. } => {
+                if base_url.trim().is_empty() || model.trim().is_empty() {
+                    return Err("语音接口需要服务地址和模型名称".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 创建引擎实例
+    pub fn build(&self) -> Result<Box<dyn TtsEngine>, String> {
+        self.validate()?;
+        Ok(match self {
+            Self::System => Box::new(SystemTts),
+            Self::Local { program, args, stdin_text } => Box::new(LocalTts {
+                program: program.clone(),
+                args: args.clone(),
+                stdin_text: *stdin_text,
+            }),
+            Self::Remote { base_url, model, api_key } => Box::new(RemoteTts {
+                client: crate::http::proxy::client_builder()
+                    .timeout(SYNTH_TIMEOUT)
+                    .build()
+                    .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                model: model.clone(),
+                api_key: api_key.clone(),
+            }),
+        })
+    }
+}
+
+fn default_rate() -> f32 {
+    1.0
+}
+
+fn default_max_chars() -> usize {
+    500
+}
+
+/// 语音合成设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TtsSettings {
+    #[serde(default)]
+    pub engine: TtsEngineConfig,
+    /// 自动朗读聊天回复
+    #[serde(default)]
+    pub speak_replies: bool,
+    /// 默认音色；角色绑定了 TTS 音色时使用绑定的音色
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default = "default_rate")]
+    pub rate: f32,
+    /// 每次最多朗读的字数
+    #[serde(default = "default_max_chars")]
+    pub max_chars: usize,
+}
+
+impl Default for TtsSettings {
+    fn default() -> Self {
+        Self {
+            engine: TtsEngineConfig::default(),
+            speak_replies: false,
+            voice: None,
+            rate: default_rate(),
+            max_chars: default_max_chars(),
+        }
+    }
+}
+
+impl TtsSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.engine.validate()?;
+        if !(0.5..=2.0).contains(&self.rate) {
+            return Err("语速必须在 0.5 到 2.0 之间".to_string());
+        }
+        if !(1..=MAX_CHARS_LIMIT).contains(&self.max_chars) {
+            return Err(format!("朗读字数必须在 1 到 {} 之间", MAX_CHARS_LIMIT));
+        }
+        Ok(())
+    }
+}
+
+/// 整理要朗读的文本：去掉代码块、链接与 Markdown 符号，超长时在 `max_chars` 内最后一个句末截断
+pub fn speakable_text(text: &str, max_chars: usize) -> String {
+    let mut cleaned = String::new();
+    let mut in_code_block = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let words: Vec<&str> = line
+            .split_whitespace()
+            .filter(|word| !word.starts_with("http://") && !word.starts_with("https://"))
+            .collect();
+        cleaned.push_str(&words.join(" "));
+        cleaned.push(' ');
+    }
+    let cleaned: String = cleaned
+        .chars()
+        .filter(|c| !matches!(c, '*' | '_' | '`' | '#' | '>' | '~' | '|'))
+        .collect();
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if cleaned.chars().count() <= max_chars {
+        return cleaned;
+    }
+    let truncated: String = cleaned.chars().take(max_chars).collect();
+    match truncated.rfind(['。', '！', '？', '.', '!', '?']) {
+        Some(end) => {
+            let end = end + truncated[end..].chars().next().map_or(1, char::len_utf8);
+            truncated[..end].to_string()
+        }
+        None => truncated,
+    }
+}
+
+/// 替换参数中的占位符
+pub fn expand_args(args: &[String], vars: &[(&str, &str)]) -> Vec<String> {
+    args.iter()
+        .map(|arg| {
+            vars.iter()
+                .fold(arg.clone(), |arg, (name, value)| arg.replace(&format!("{{{}}}", name), value))
+        })
+        .collect()
+}
+
+/// 系统语音的命令行（文本从 `text_file` 读取）
+pub fn system_command(os: &str, request: &TtsRequest, text_file: &str, output: &str) -> Result<(String, Vec<String>), String> {
+    match os {
+        "windows" => {
+            // SAPI 语速范围 -10..10
+            let rate = ((request.rate - 1.0) * 10.0).round().clamp(-10.0, 10.0) as i32;
+            let select_voice = match &request.voice {
+                Some(voice) => format!("$s.SelectVoice('{}');", voice.replace('\'', "''")),
+                None => String::new(),
+            };
+            let script = format!(
+                "Add-Type -AssemblyName System.Speech; \
+                 $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+                 $s.Rate = {}; {} \
+                 $s.SetOutputToWaveFile('{}'); \
+                 $s.Speak([IO.File]::ReadAllText('{}', [Text.Encoding]::UTF8)); \
+                 $s.Dispose()",
+                rate,
+                select_voice,
+                output.replace('\'', "''"),
+                text_file.replace('\'', "''"),
+            );
+            Ok(("powershell".to_string(), vec!["-NoProfile".to_string(), "-Command".to_string(), script]))
+        }
+        "macos" => {
+            let mut args = vec![
+                "-o".to_string(),
+                output.to_string(),
+                "--data-format=LEI16@22050".to_string(),
+                "-r".to_string(),
+                ((175.0 * request.rate).round() as u32).to_string(),
+                "-f".to_string(),
+                text_file.to_string(),
+            ];
+            if let Some(voice) = &request.voice {
+                args.extend(["-v".to_string(), voice.clone()]);
+            }
+            Ok(("say".to_string(), args))
+        }
+        "linux" => {
+            let voice = request.voice.clone().unwrap_or_else(|| request.locale.to_lowercase());
+            Ok((
+                "espeak-ng".to_string(),
+                vec![
+                    "-w".to_string(),
+                    output.to_string(),
+                    "-v".to_string(),
+                    voice,
+                    "-s".to_string(),
+                    ((175.0 * request.rate).round() as u32).to_string(),
+                    "-f".to_string(),
+                    text_file.to_string(),
+                ],
+            ))
+        }
+        other => Err(format!("当前系统不支持系统语音: {}", other)),
+    }
+}
+
+/// 运行合成程序直到结束
+async fn run_command(program: &str, args: &[String], stdin_text: Option<&str>) -> Result<(), String> {
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(args)
+        .stdin(if stdin_text.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = command.spawn().map_err(|e| format!("启动语音合成程序 {} 失败: {}", program, e))?;
+    if let (Some(text), Some(mut stdin)) = (stdin_text, child.stdin.take()) {
+        stdin
+            .write_all(text.as_bytes())
+            .await
+            .map_err(|e| format!("向语音合成程序传入文本失败: {}", e))?;
+    }
+
+    let output = tokio::time::timeout(SYNTH_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| "语音合成超时".to_string())?
+        .map_err(|e| format!("语音合成程序异常退出: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("语音合成失败（{}）: {}", output.status, stderr.trim()));
+    }
+    Ok(())
+}
+
+/// 写入临时文本文件，返回路径（合成后删除）
+async fn write_text_file(output: &Path, text: &str) -> Result<std::path::PathBuf, String> {
+    let text_file = output.with_extension("txt");
+    tokio::fs::write(&text_file, text)
+        .await
+        .map_err(|e| format!("写入朗读文本失败: {}", e))?;
+    Ok(text_file)
+}
+
+// ========================================
+// 引擎实现
+// ========================================
+
+/// 系统语音
+pub struct SystemTts;
+
+#[async_trait]
+impl TtsEngine for SystemTts {
+    async fn synthesize(&self, request: &TtsRequest, output: &Path) -> Result<(), String> {
+        let text_file = write_text_file(output, &request.text).await?;
+        let (program, args) = system_command(
+            std::env::consts::OS,
+            request,
+            &text_file.to_string_lossy(),
+            &output.to_string_lossy(),
+        )?;
+        let result = run_command(&program, &args, None).await;
+        let _ = tokio::fs::remove_file(&text_file).await;
+        result
+    }
+}
+
+/// 本地模型的命令行程序
+pub struct LocalTts {
+    program: String,
+    args: Vec<String>,
+    stdin_text: bool,
+}
+
+#[async_trait]
+impl TtsEngine for LocalTts {
+    async fn synthesize(&self, request: &TtsRequest, output: &Path) -> Result<(), String> {
+        let text_file = write_text_file(output, &request.text).await?;
+        let text_path = text_file.to_string_lossy().to_string();
+        let output_path = output.to_string_lossy().to_string();
+        let rate = request.rate.to_string();
+        let args = expand_args(
+            &self.args,
+            &[
+                ("text_file", text_path.as_str()),
+                ("output", output_path.as_str()),
+                ("voice", request.voice.as_deref().unwrap_or_default()),
+                ("locale", request.locale.as_str()),
+                ("rate", rate.as_str()),
+            ],
+        );
+        let stdin_text = self.stdin_text.then_some(request.text.as_str());
+        let result = run_command(&self.program, &args, stdin_text).await;
+        let _ = tokio::fs::remove_file(&text_file).await;
+        result
+    }
+}
+
+/// OpenAI 兼容的语音接口
+pub struct RemoteTts {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[async_trait]
+impl TtsEngine for RemoteTts {
+    async fn synthesize(&self, request: &TtsRequest, output: &Path) -> Result<(), String> {
+        let mut http_request = self.client.post(format!("{}/audio/speech", self.base_url)).json(&serde_json::json!({
+            "model": self.model,
+            "input": request.text,
+            "voice": request.voice.as_deref().unwrap_or("alloy"),
+            "response_format": "wav",
+            "speed": request.rate,
+        }));
+        if let Some(api_key) = &self.api_key {
+            http_request = http_request.bearer_auth(api_key);
+        }
+        let audio = http_request
+            .send()
+            .await
+            .map_err(|e| format!("请求语音接口失败: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("语音接口返回错误: {}", e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("读取语音数据失败: {}", e))?;
+        tokio::fs::write(output, &audio)
+            .await
+            .map_err(|e| format!("保存语音文件失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable_text_strips_markup_and_truncates() {
+        let text = "**你好**！这是`代码`说明。\n```rust\nfn main() {}\n```\n详见 https://example.com 的文档。";
+        assert_eq!(speakable_text(text, 100), "你好！这是代码说明。 详见 的文档。");
+        assert_eq!(speakable_text("第一句。第二句很长很长", 8), "第一句。");
+        assert_eq!(speakable_text("没有句号的一段话", 4), "没有句号");
+    }
+
+    #[test]
+    fn test_engine_commands() {
+        let args = vec!["--model".to_string(), "{voice}".to_string(), "--output_file={output}".to_string()];
+        assert_eq!(
+            expand_args(&args, &[("voice", "zh.onnx"), ("output", "/tmp/a.wav")]),
+            ["--model", "zh.onnx", "--output_file=/tmp/a.wav"]
+        );
+        let piper = TtsEngineConfig::Local { program: "piper".to_string(), args, stdin_text: true };
+        assert!(piper.validate().is_ok());
+        let no_text = TtsEngineConfig::Local {
+            program: "piper".to_string(),
+            args: vec!["{output}".to_string()],
+            stdin_text: false,
+        };
+        assert!(no_text.validate().is_err());
+
+        let request = TtsRequest {
+            text: "hi".to_string(),
+            voice: None,
+            locale: "en-US".to_string(),
+            rate: 1.2,
+        };
+        let (program, args) = system_command("linux", &request, "in.txt", "out.wav").unwrap();
+        assert_eq!(program, "espeak-ng");
+        assert_eq!(args, ["-w", "out.wav", "-v", "en-us", "-s", "210", "-f", "in.txt"]);
+        let (program, args) = system_command("windows", &request, "in.txt", "it's.wav").unwrap();
+        assert_eq!(program, "powershell");
+        assert!(args[2].contains("$s.Rate = 2;") && args[2].contains("'it''s.wav'"));
+        assert!(system_command("plan9", &request, "in.txt", "out.wav").is_err());
+    }
+}
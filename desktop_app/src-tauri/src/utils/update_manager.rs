@@ -7,13 +7,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{info, warn, error, debug};
 use sha2::{Sha256, Digest};
 use std::cmp::Ordering;
+use super::delta_update::{self, ChunkInfo, DeltaPatch, StagedUpdate};
 
 /// 版本比较结果
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +29,9 @@ pub enum VersionComparison {
     Invalid,
 }
 
+/// 单个分块的最大尝试次数
+const CHUNK_RETRIES: u32 = 3;
+
 /// 更新事件类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -98,6 +102,10 @@ pub enum UpdateEvent {
     RollbackFailed {
         error: String,
     },
+    /// 更新已暂存，下次重启时安装
+    UpdateStaged {
+        version: String,
+    },
 }
 
 /// 远程更新清单
@@ -138,6 +146,12 @@ pub struct FileInfo {
     pub platform: Option<String>,
     /// 目标架构
     pub arch: Option<String>,
+    /// 分块信息（存在时分块下载，支持断点续传）
+    #[serde(default)]
+    pub chunks: Vec<ChunkInfo>,
+    /// 从旧版本生成的差分补丁
+    #[serde(default)]
+    pub deltas: Vec<DeltaPatch>,
 }
 
 /// 更新管理器
@@ -162,6 +176,15 @@ pub struct UpdateManager {
 }
 
 impl UpdateManager {
+    /// 获取应用了当前代理设置的 HTTP 客户端
+    fn http_client(&self) -> Client {
+        crate::http::proxy::client_builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(format!("ZishuSensei/{}", self.current_version))
+            .build()
+            .unwrap_or_else(|_| self.client.clone())
+    }
+
     /// 创建新的更新管理器
     pub fn new(
         pool: DbPool,
@@ -171,7 +194,7 @@ impl UpdateManager {
     ) -> Result<Self> {
         let db = Arc::new(Mutex::new(UpdateDatabase::from_pool(pool)));
 
-        let client = Client::builder()
+        let client = crate::http::proxy::client_builder()
             .timeout(Duration::from_secs(30))
             .user_agent(format!("ZishuSensei/{}", current_version))
             .build()
@@ -262,7 +285,7 @@ impl UpdateManager {
         info!("Checking update from: {}", url);
 
         // 发送请求
-        match self.client.get(&url).send().await {
+        match self.http_client().get(&url).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     match response.json::<UpdateManifest>().await {
@@ -329,6 +352,11 @@ impl UpdateManager {
                                         db.save_update_info(&mut update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
                                     }
 
+                                    // 分块与补丁信息不在数据库中，下载时从清单文件读取
+                                    if let Err(e) = self.save_file_info(&manifest.version, file_info) {
+                                        warn!("Failed to save update file info: {}", e);
+                                    }
+
                                     info!("Update available: {} -> {}", self.current_version, manifest.version);
                                     self.emit_event(UpdateEvent::CheckCompleted {
                                         has_update: true,
@@ -425,8 +453,15 @@ impl UpdateManager {
         let file_name = format!("zishu-sensei-{}.update", version);
         let file_path = self.download_dir.join(&file_name);
 
+        // 优先使用差分补丁或分块下载
+        if let Some(file_info) = self.load_file_info(version) {
+            if self.download_incremental(version, &file_info, &file_path, &mut update_info).await? {
+                return self.complete_download(version, &file_path, &mut update_info);
+            }
+        }
+
         // 开始下载
-        let response = self.client.get(&download_url).send().await
+        let response = self.http_client().get(&download_url).send().await
             .context("Failed to start download")?;
 
         if !response.status().is_success() {
@@ -444,13 +479,30 @@ impl UpdateManager {
             bail!(error_msg);
         }
 
+        let mut downloaded = 0i64;
+        let total = response.content_length().map(|l| l as i64).or(file_size);
+
+        // 检查磁盘空间
+        let space = super::disk_guard::check_space(&self.download_dir, total.unwrap_or(0).max(0) as u64);
+        if space.level == super::disk_guard::SpaceLevel::Critical {
+            let error_msg = "Not enough disk space to download the update".to_string();
+            update_info.status = UpdateStatus::Failed;
+            update_info.error_message = Some(error_msg.clone());
+            {
+                let db = self.db.lock().unwrap();
+                db.save_update_info(&mut update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            }
+            self.emit_event(UpdateEvent::DownloadFailed {
+                version: version.to_string(),
+                error: error_msg.clone(),
+            });
+            bail!(error_msg);
+        }
+
         // 创建文件
         let mut file = fs::File::create(&file_path)
             .context("Failed to create download file")?;
 
-        let mut downloaded = 0i64;
-        let total = response.content_length().map(|l| l as i64).or(file_size);
-
         // 创建哈希计算器
         let mut hasher = Sha256::new();
 
@@ -532,12 +584,16 @@ impl UpdateManager {
             }
         }
 
-        // 下载完成
+        self.complete_download(version, &file_path, &mut update_info)
+    }
+
+    /// 标记下载完成
+    fn complete_download(&self, version: &str, file_path: &Path, update_info: &mut UpdateInfo) -> Result<String> {
         update_info.status = UpdateStatus::Downloaded;
         update_info.download_progress = 100.0;
         {
             let db = self.db.lock().unwrap();
-            db.save_update_info(&mut update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            db.save_update_info(update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
         }
 
         let file_path_str = file_path.to_string_lossy().to_string();
@@ -551,6 +607,209 @@ impl UpdateManager {
         Ok(file_path_str)
     }
 
+    fn file_info_path(&self, version: &str) -> PathBuf {
+        self.download_dir.join(format!("zishu-sensei-{}.manifest.json", version))
+    }
+
+    fn save_file_info(&self, version: &str, file_info: &FileInfo) -> Result<()> {
+        let json = serde_json::to_string(file_info)?;
+        fs::write(self.file_info_path(version), json).context("Failed to write update file info")?;
+        Ok(())
+    }
+
+    fn load_file_info(&self, version: &str) -> Option<FileInfo> {
+        fs::read_to_string(self.file_info_path(version))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    /// 差分补丁或分块下载；返回 `false` 表示清单中没有分块信息，需要完整下载
+    async fn download_incremental(
+        &self,
+        version: &str,
+        file_info: &FileInfo,
+        file_path: &Path,
+        update_info: &mut UpdateInfo,
+    ) -> Result<bool> {
+        if let Some(delta) = delta_update::select_delta(&file_info.deltas, &self.current_version) {
+            match self.download_delta(version, delta, &file_info.hash, file_path, update_info).await {
+                Ok(()) => return Ok(true),
+                Err(e) => warn!("Delta update from {} failed, falling back to full download: {}", self.current_version, e),
+            }
+        }
+
+        if file_info.chunks.is_empty() {
+            return Ok(false);
+        }
+
+        if let Err(e) = self.download_chunks(version, &file_info.url, &file_info.hash, &file_info.chunks, file_path, update_info).await {
+            update_info.status = UpdateStatus::Failed;
+            update_info.error_message = Some(e.to_string());
+            update_info.retry_count += 1;
+            {
+                let db = self.db.lock().unwrap();
+                db.save_update_info(update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            }
+            self.emit_event(UpdateEvent::DownloadFailed {
+                version: version.to_string(),
+                error: e.to_string(),
+            });
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// 查找哈希与补丁基准一致的本地文件：上一版本保留的安装包或当前可执行文件
+    fn find_delta_base(&self, base_hash: &str) -> Option<Vec<u8>> {
+        let previous = self.download_dir.join(format!("zishu-sensei-{}.update", self.current_version));
+        let candidates = [Some(previous), std::env::current_exe().ok()];
+        candidates
+            .into_iter()
+            .flatten()
+            .filter_map(|path| fs::read(path).ok())
+            .find(|data| delta_update::sha256_hex(data).eq_ignore_ascii_case(base_hash))
+    }
+
+    /// 下载补丁并应用到基准文件
+    async fn download_delta(
+        &self,
+        version: &str,
+        delta: &DeltaPatch,
+        target_hash: &str,
+        file_path: &Path,
+        update_info: &mut UpdateInfo,
+    ) -> Result<()> {
+        let base = self.find_delta_base(&delta.base_hash)
+            .context("No local file matches the delta base")?;
+
+        info!("Downloading delta patch {} -> {} ({} bytes)", delta.from_version, version, delta.size);
+        let patch_path = self.download_dir.join(format!("zishu-sensei-{}.patch", version));
+        if delta.chunks.is_empty() {
+            let response = self.http_client().get(&delta.url).send().await?.error_for_status()?;
+            let bytes = response.bytes().await?;
+            if !delta_update::sha256_hex(&bytes).eq_ignore_ascii_case(&delta.hash) {
+                bail!("Delta patch hash mismatch");
+            }
+            fs::write(&patch_path, &bytes).context("Failed to write delta patch")?;
+        } else {
+            self.download_chunks(version, &delta.url, &delta.hash, &delta.chunks, &patch_path, update_info).await?;
+        }
+
+        let patch = fs::read(&patch_path).context("Failed to read delta patch")?;
+        let target_hash = target_hash.to_string();
+        let output = tokio::task::spawn_blocking(move || delta_update::apply_patch(&base, &patch, &target_hash))
+            .await?
+            .map_err(|e| anyhow::anyhow!(e))?;
+        fs::write(file_path, output).context("Failed to write patched update file")?;
+        let _ = fs::remove_file(&patch_path);
+        Ok(())
+    }
+
+    /// 请求单个分块
+    async fn fetch_chunk(&self, url: &str, chunk: &ChunkInfo) -> Result<Vec<u8>> {
+        let response = self.http_client()
+            .get(url)
+            .header(reqwest::header::RANGE, delta_update::range_header(chunk))
+            .send()
+            .await?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            bail!("Server does not support range requests (status {})", response.status());
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// 按 HTTP Range 逐块下载并校验，已完成的块在中断后不重新下载
+    async fn download_chunks(
+        &self,
+        version: &str,
+        url: &str,
+        hash: &str,
+        chunks: &[ChunkInfo],
+        file_path: &Path,
+        update_info: &mut UpdateInfo,
+    ) -> Result<()> {
+        use std::io::{Seek, SeekFrom};
+
+        let total = chunks.iter().map(|chunk| chunk.size).sum::<u64>();
+        delta_update::validate_chunks(chunks, total).map_err(|e| anyhow::anyhow!(e))?;
+
+        let mut progress = delta_update::load_progress(file_path, hash);
+        let remaining = total - progress.downloaded_bytes(chunks);
+        let space = super::disk_guard::check_space(&self.download_dir, remaining);
+        if space.level == super::disk_guard::SpaceLevel::Critical {
+            bail!("Not enough disk space to download the update");
+        }
+        if !progress.completed.is_empty() {
+            info!("Resuming download of {} ({}/{} chunks done)", version, progress.completed.len(), chunks.len());
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(file_path)
+            .context("Failed to open download file")?;
+        file.set_len(total).context("Failed to allocate download file")?;
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            if progress.completed.contains(&index) {
+                continue;
+            }
+
+            // 用户取消后停止，保留已完成的块
+            let cancelled = {
+                let db = self.db.lock().unwrap();
+                db.get_update_info_by_version(version)
+                    .map_err(|e| anyhow::anyhow!(e.to_string()))?
+                    .map(|info| info.status == UpdateStatus::Cancelled)
+                    .unwrap_or(false)
+            };
+            if cancelled {
+                bail!("Download cancelled");
+            }
+
+            let mut attempt = 0;
+            let data = loop {
+                attempt += 1;
+                match self.fetch_chunk(url, chunk).await {
+                    Ok(data) if delta_update::verify_chunk(&data, chunk) => break data,
+                    Ok(_) if attempt < CHUNK_RETRIES => warn!("Chunk {} of {} failed verification, retrying", index, version),
+                    Err(e) if attempt < CHUNK_RETRIES => warn!("Chunk {} of {} failed: {}, retrying", index, version, e),
+                    Ok(_) => bail!("Chunk {} hash mismatch", index),
+                    Err(e) => return Err(e),
+                }
+            };
+
+            file.seek(SeekFrom::Start(chunk.offset))?;
+            file.write_all(&data).context("Failed to write downloaded data")?;
+            progress.completed.insert(index);
+            delta_update::save_progress(file_path, &progress).map_err(|e| anyhow::anyhow!(e))?;
+
+            let downloaded = progress.downloaded_bytes(chunks) as i64;
+            let percentage = downloaded as f64 / total.max(1) as f64 * 100.0;
+            update_info.download_progress = percentage;
+            {
+                let db = self.db.lock().unwrap();
+                db.save_update_info(update_info).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            }
+            self.emit_event(UpdateEvent::DownloadProgress {
+                version: version.to_string(),
+                downloaded,
+                total: Some(total as i64),
+                percentage,
+            });
+        }
+        file.sync_all()?;
+        drop(file);
+
+        let actual_hash = delta_update::sha256_hex(&fs::read(file_path)?);
+        delta_update::clear_progress(file_path);
+        if !actual_hash.eq_ignore_ascii_case(hash) {
+            let _ = fs::remove_file(file_path);
+            bail!("Downloaded file hash mismatch");
+        }
+        Ok(())
+    }
+
     /// 安装更新
     pub async fn install_update(&self, version: &str) -> Result<bool> {
         info!("Starting installation for version: {}", version);
@@ -697,6 +956,10 @@ impl UpdateManager {
                                   Utc::now().format("%Y%m%d-%H%M%S"));
         let backup_path = self.backup_dir.join(&backup_name);
 
+        if super::disk_guard::check_space(&self.backup_dir, 0).level == super::disk_guard::SpaceLevel::Critical {
+            bail!("Not enough disk space to create backup");
+        }
+
         fs::create_dir_all(&backup_path)
             .context("Failed to create backup directory")?;
 
@@ -780,6 +1043,7 @@ impl UpdateManager {
             if file_path.exists() {
                 let _ = fs::remove_file(&file_path);
             }
+            delta_update::clear_progress(&file_path);
 
             info!("Download cancelled for version: {}", version);
         }
@@ -787,6 +1051,71 @@ impl UpdateManager {
         Ok(())
     }
 
+    /// 暂存已下载的更新，下次重启时安装
+    pub fn stage_update(&self, version: &str) -> Result<StagedUpdate> {
+        let update_info = {
+            let db = self.db.lock().unwrap();
+            db.get_update_info_by_version(version)
+                .map_err(|e| anyhow::anyhow!("Database operation failed: {}", e))?
+                .context("Update info not found")?
+        };
+        if update_info.status != UpdateStatus::Downloaded {
+            bail!("Update is not ready for installation");
+        }
+
+        let file_path = self.download_dir.join(format!("zishu-sensei-{}.update", version));
+        if !file_path.exists() {
+            bail!("Downloaded update file is missing");
+        }
+
+        let staged = StagedUpdate::new(version, &self.current_version, &file_path);
+        delta_update::save_staged(&self.download_dir, &staged).map_err(|e| anyhow::anyhow!(e))?;
+        info!("Update {} staged for next restart", version);
+        self.emit_event(UpdateEvent::UpdateStaged {
+            version: version.to_string(),
+        });
+        Ok(staged)
+    }
+
+    /// 获取暂存的更新
+    pub fn staged_update(&self) -> Option<StagedUpdate> {
+        delta_update::load_staged(&self.download_dir)
+    }
+
+    /// 取消暂存的更新
+    pub fn unstage_update(&self) -> bool {
+        delta_update::clear_staged(&self.download_dir)
+    }
+
+    /// 安装暂存的更新（更新管理器初始化时调用），失败时回滚到暂存前的版本
+    pub async fn apply_staged_update(&self) -> Result<Option<bool>> {
+        let Some(staged) = self.staged_update() else {
+            return Ok(None);
+        };
+        // 先清除标记，避免安装导致崩溃时每次启动都重试
+        self.unstage_update();
+
+        if staged.from_version != self.current_version {
+            info!("Staged update {} no longer applies to {}", staged.version, self.current_version);
+            return Ok(None);
+        }
+
+        info!("Installing staged update {}", staged.version);
+        match self.install_update(&staged.version).await {
+            Ok(needs_restart) => Ok(Some(needs_restart)),
+            Err(e) => {
+                error!("Staged update {} failed: {}", staged.version, e);
+                if let Err(rollback_error) = self.rollback_to_version(&staged.from_version).await {
+                    warn!("Rollback after failed staged update failed: {}", rollback_error);
+                    self.emit_event(UpdateEvent::RollbackFailed {
+                        error: rollback_error.to_string(),
+                    });
+                }
+                Err(e)
+            }
+        }
+    }
+
     /// 比较版本号
     fn compare_versions(&self, current: &str, remote: &str) -> VersionComparison {
         // 简单的语义版本比较
@@ -1025,6 +1354,8 @@ mod tests {
             hash: "abc123".to_string(),
             platform: Some("windows".to_string()),
             arch: Some("x64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         });
 
         let manifest = UpdateManifest {
@@ -1058,6 +1389,8 @@ mod tests {
             hash: "def456".to_string(),
             platform: Some("linux".to_string()),
             arch: Some("x64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         };
 
         assert_eq!(file_info.url, "https://example.com/file.exe");
@@ -1125,6 +1458,8 @@ mod tests {
             hash: "abc123".to_string(),
             platform: Some("linux".to_string()),
             arch: Some("x86_64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         };
 
         // 验证基本属性
@@ -1388,6 +1723,8 @@ mod tests {
             hash: "linux123".to_string(),
             platform: Some("linux".to_string()),
             arch: Some("x64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         });
         
         files.insert("windows-x64".to_string(), FileInfo {
@@ -1396,6 +1733,8 @@ mod tests {
             hash: "windows123".to_string(),
             platform: Some("windows".to_string()),
             arch: Some("x64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         });
 
         let manifest = UpdateManifest {
@@ -1498,6 +1837,8 @@ mod tests {
             hash: "abc123".to_string(),
             platform: Some("linux".to_string()),
             arch: Some("x64".to_string()),
+            chunks: Vec::new(),
+            deltas: Vec::new(),
         });
 
         let manifest = UpdateManifest {
@@ -0,0 +1,335 @@
+//! # 语音活动检测
+//!
+//! 基于帧能量的语音活动检测：16 kHz 单声道样本按 30 ms 分帧，能量超过阈值（固定阈值与
+//! 自适应噪声底的较大者）持续 [`VoiceInputSettings::min_speech_ms`] 视为开始说话，静音持续
+//! [`VoiceInputSettings::silence_ms`] 视为说完。开始前的一小段音频（pre-roll）一并保留，
+//! 避免句首被截断。
+//!
+//! 采集、按键说话与转写见 `commands::audio`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Cursor;
+
+use crate::utils::hotword::SAMPLE_RATE;
+
+/// 帧长（毫秒）
+const FRAME_MS: u32 = 30;
+const FRAME_SAMPLES: usize = (SAMPLE_RATE * FRAME_MS / 1000) as usize;
+/// 自适应阈值为噪声底的倍数
+const NOISE_MARGIN: f32 = 3.0;
+/// 噪声底的平滑系数
+const NOISE_SMOOTHING: f32 = 0.02;
+
+/// 语音输入模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceInputMode {
+    #[default]
+    Off,
+    /// 检测到说话自动开始 / 结束
+    Vad,
+    /// 按下快捷键开始，再按一次（或松开窗口内按键）结束
+    PushToTalk,
+}
+
+/// 转写引擎
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionEngine {
+    /// 配置了本地 whisper 时用本地，否则用后端
+    #[default]
+    Auto,
+    /// 本地 whisper.cpp 命令行
+    Local,
+    /// 后端语音识别接口
+    Backend,
+}
+
+/// 语音输入设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceInputSettings {
+    pub mode: VoiceInputMode,
+    /// 判定为语音的最低帧能量（RMS，0~1）
+    pub threshold: f32,
+    /// 持续多久的语音才算开始说话（毫秒）
+    pub min_speech_ms: u32,
+    /// 静音多久算说完（毫秒）
+    pub silence_ms: u32,
+    /// 保留开始说话前的音频（毫秒）
+    pub pre_roll_ms: u32,
+    /// 单句最长时长（秒），超过后自动结束
+    pub max_utterance_secs: u32,
+    /// 说完后自动转写
+    pub auto_transcribe: bool,
+    pub engine: TranscriptionEngine,
+    /// 转写语言（如 `zh`），为空时自动识别
+    pub language: Option<String>,
+    /// whisper.cpp 可执行文件路径
+    pub whisper_binary: Option<String>,
+    /// whisper.cpp 模型文件路径
+    pub whisper_model: Option<String>,
+}
+
+impl Default for VoiceInputSettings {
+    fn default() -> Self {
+        Self {
+            mode: VoiceInputMode::Off,
+            threshold: 0.015,
+            min_speech_ms: 200,
+            silence_ms: 800,
+            pre_roll_ms: 300,
+            max_utterance_secs: 30,
+            auto_transcribe: true,
+            engine: TranscriptionEngine::Auto,
+            language: None,
+            whisper_binary: None,
+            whisper_model: None,
+        }
+    }
+}
+
+impl VoiceInputSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.threshold > 0.0 && self.threshold < 1.0) {
+            return Err("语音阈值必须在 0 到 1 之间".to_string());
+        }
+        if !(60..=2000).contains(&self.min_speech_ms) {
+            return Err("开始说话的判定时长必须在 60 到 2000 毫秒之间".to_string());
+        }
+        if !(200..=5000).contains(&self.silence_ms) {
+            return Err("静音判定时长必须在 200 到 5000 毫秒之间".to_string());
+        }
+        if self.pre_roll_ms > 2000 {
+            return Err("句首保留时长不能超过 2000 毫秒".to_string());
+        }
+        if !(1..=120).contains(&self.max_utterance_secs) {
+            return Err("单句最长时长必须在 1 到 120 秒之间".to_string());
+        }
+        if self.engine == TranscriptionEngine::Local && !self.local_whisper_configured() {
+            return Err("使用本地转写需要设置 whisper 可执行文件与模型路径".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn local_whisper_configured(&self) -> bool {
+        let set = |path: &Option<String>| path.as_deref().map_or(false, |p| !p.trim().is_empty());
+        set(&self.whisper_binary) && set(&self.whisper_model)
+    }
+
+    /// 解析实际使用的引擎（`Auto` 解析为本地或后端）
+    pub fn resolve_engine(&self, requested: Option<TranscriptionEngine>) -> TranscriptionEngine {
+        match requested.unwrap_or(self.engine) {
+            TranscriptionEngine::Auto if self.local_whisper_configured() => TranscriptionEngine::Local,
+            TranscriptionEngine::Auto => TranscriptionEngine::Backend,
+            engine => engine,
+        }
+    }
+
+    pub fn max_utterance_samples(&self) -> usize {
+        self.max_utterance_secs as usize * SAMPLE_RATE as usize
+    }
+}
+
+/// 检测事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum VadEvent {
+    SpeechStarted,
+    /// 一句话的完整音频（16 kHz 单声道）
+    SpeechEnded(Vec<f32>),
+}
+
+fn frames_for(ms: u32) -> usize {
+    ((ms + FRAME_MS - 1) / FRAME_MS).max(1) as usize
+}
+
+/// 帧能量（RMS）
+pub fn frame_rms(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// 语音活动检测器
+#[derive(Debug, Clone)]
+pub struct VoiceActivityDetector {
+    threshold: f32,
+    min_speech_frames: usize,
+    silence_frames: usize,
+    pre_roll_samples: usize,
+    max_samples: usize,
+    noise_floor: Option<f32>,
+    pending: Vec<f32>,
+    /// 说话前的音频（含正在判定的语音帧）
+    pre_roll: VecDeque<f32>,
+    utterance: Vec<f32>,
+    speaking: bool,
+    voiced_run: usize,
+    silent_run: usize,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(settings: &VoiceInputSettings) -> Self {
+        Self {
+            threshold: settings.threshold,
+            min_speech_frames: frames_for(settings.min_speech_ms),
+            silence_frames: frames_for(settings.silence_ms),
+            pre_roll_samples: (settings.pre_roll_ms * SAMPLE_RATE / 1000) as usize,
+            max_samples: settings.max_utterance_samples(),
+            noise_floor: None,
+            pending: Vec::with_capacity(FRAME_SAMPLES),
+            pre_roll: VecDeque::new(),
+            utterance: Vec::new(),
+            speaking: false,
+            voiced_run: 0,
+            silent_run: 0,
+        }
+    }
+
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// 送入 16 kHz 单声道样本，返回期间发生的事件
+    pub fn push(&mut self, samples: &[f32]) -> Vec<VadEvent> {
+        let mut events = Vec::new();
+        for &sample in samples {
+            self.pending.push(sample);
+            if self.pending.len() == FRAME_SAMPLES {
+                let frame = std::mem::take(&mut self.pending);
+                events.extend(self.process_frame(&frame));
+                self.pending = frame;
+                self.pending.clear();
+            }
+        }
+        events
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) -> Option<VadEvent> {
+        let rms = frame_rms(frame);
+        let threshold = self.noise_floor.map_or(self.threshold, |floor| self.threshold.max(floor * NOISE_MARGIN));
+        let voiced = rms >= threshold;
+
+        if self.speaking {
+            self.utterance.extend_from_slice(frame);
+            self.silent_run = if voiced { 0 } else { self.silent_run + 1 };
+            if self.silent_run >= self.silence_frames || self.utterance.len() >= self.max_samples {
+                self.speaking = false;
+                self.silent_run = 0;
+                return Some(VadEvent::SpeechEnded(std::mem::take(&mut self.utterance)));
+            }
+            return None;
+        }
+
+        // 只在没有说话时跟踪噪声底
+        self.noise_floor = Some(match self.noise_floor {
+            Some(floor) => floor * (1.0 - NOISE_SMOOTHING) + rms * NOISE_SMOOTHING,
+            None => rms,
+        });
+        self.pre_roll.extend(frame.iter().copied());
+        let keep = self.pre_roll_samples + self.min_speech_frames * FRAME_SAMPLES;
+        while self.pre_roll.len() > keep {
+            self.pre_roll.pop_front();
+        }
+
+        self.voiced_run = if voiced { self.voiced_run + 1 } else { 0 };
+        if self.voiced_run < self.min_speech_frames {
+            return None;
+        }
+        self.speaking = true;
+        self.voiced_run = 0;
+        self.utterance = self.pre_roll.drain(..).collect();
+        Some(VadEvent::SpeechStarted)
+    }
+}
+
+/// 编码为 16 位单声道 WAV
+pub fn encode_wav(samples: &[f32]) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).map_err(|e| format!("编码音频失败: {}", e))?;
+        for &sample in samples {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer.write_sample(sample).map_err(|e| format!("编码音频失败: {}", e))?;
+        }
+        writer.finalize().map_err(|e| format!("编码音频失败: {}", e))?;
+    }
+    Ok(cursor.into_inner())
+}
+
+/// 16 位小端 PCM（`stop_recording` 返回的格式）转为样本
+pub fn pcm16_to_samples(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / i16::MAX as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(ms: u32, amplitude: f32) -> Vec<f32> {
+        let len = (SAMPLE_RATE * ms / 1000) as usize;
+        (0..len).map(|i| amplitude * (i as f32 * 0.3).sin()).collect()
+    }
+
+    fn run(detector: &mut VoiceActivityDetector, parts: &[Vec<f32>]) -> Vec<VadEvent> {
+        parts.iter().flat_map(|part| detector.push(part)).collect()
+    }
+
+    #[test]
+    fn test_detects_utterance_with_pre_roll() {
+        let settings = VoiceInputSettings::default();
+        let mut detector = VoiceActivityDetector::new(&settings);
+        let events = run(&mut detector, &[tone(600, 0.001), tone(900, 0.3), tone(1200, 0.001)]);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], VadEvent::SpeechStarted);
+        let VadEvent::SpeechEnded(audio) = &events[1] else {
+            panic!("expected speech end");
+        };
+        // 完整的语音 + 句首保留 + 结尾的静音判定时长
+        let min_len = (SAMPLE_RATE * (900 + 300 + 800) / 1000) as usize - FRAME_SAMPLES;
+        assert!(audio.len() >= min_len, "{} < {}", audio.len(), min_len);
+        assert!(!detector.is_speaking());
+    }
+
+    #[test]
+    fn test_ignores_short_blips_and_caps_length() {
+        let settings = VoiceInputSettings { max_utterance_secs: 1, ..Default::default() };
+        let mut detector = VoiceActivityDetector::new(&settings);
+        assert!(run(&mut detector, &[tone(300, 0.001), tone(90, 0.3), tone(300, 0.001)]).is_empty());
+
+        // 持续说话超过单句上限时强制结束
+        let events = run(&mut detector, &[tone(1500, 0.3)]);
+        assert_eq!(events[0], VadEvent::SpeechStarted);
+        assert!(matches!(&events[1], VadEvent::SpeechEnded(audio) if audio.len() <= SAMPLE_RATE as usize + FRAME_SAMPLES));
+    }
+
+    #[test]
+    fn test_settings_and_wav_encoding() {
+        let mut settings = VoiceInputSettings::default();
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.resolve_engine(None), TranscriptionEngine::Backend);
+        settings.engine = TranscriptionEngine::Local;
+        assert!(settings.validate().is_err());
+        settings.whisper_binary = Some("/usr/local/bin/whisper-cli".to_string());
+        settings.whisper_model = Some("/models/ggml-base.bin".to_string());
+        assert_eq!(settings.resolve_engine(Some(TranscriptionEngine::Auto)), TranscriptionEngine::Local);
+
+        let wav = encode_wav(&tone(100, 0.5)).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        let reader = hound::WavReader::new(Cursor::new(wav)).unwrap();
+        assert_eq!(reader.len() as usize, (SAMPLE_RATE / 10) as usize);
+        assert_eq!(pcm16_to_samples(&[0xff, 0x7f, 0x00]), vec![1.0]);
+    }
+}
@@ -0,0 +1,345 @@
+//! # 后台子系统看门狗
+//!
+//! 系统监控、各类调度器等后台循环可能因 panic 静默退出，或卡在某个等待上不再前进。
+//! 子系统通过 [`supervise`] 注册启动函数，循环每轮调用 [`Heartbeat::beat`] 上报心跳；
+//! 看门狗定期检查，心跳超过阈值未更新时重新调用启动函数。
+//!
+//! 每次重启都会递增代数，旧循环下一次上报心跳时得到 `false` 并应自行退出，避免卡住的
+//! 旧任务恢复后与新任务并存。连续重启按指数退避，超过上限后放弃并标记为失败，直到
+//! 子系统再次上报心跳。
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::utils::clock::{self, SharedClock};
+
+/// 看门狗检查间隔
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// 第二次及以后重启前的基础等待时间（第一次立即重启）
+const BACKOFF_BASE_SECS: i64 = 30;
+/// 重启等待时间上限
+const BACKOFF_MAX_SECS: i64 = 30 * 60;
+/// 连续重启次数上限
+pub const MAX_CONSECUTIVE_RESTARTS: u32 = 5;
+/// 重启后稳定运行该时长即清零连续重启次数
+const RECOVERY_SECS: i64 = 10 * 60;
+
+/// 子系统状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubsystemStatus {
+    Healthy,
+    /// 心跳停滞，等待重启
+    Stalled,
+    /// 连续重启次数超过上限，已放弃
+    Failed,
+}
+
+/// 子系统健康信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: SubsystemStatus,
+    pub last_heartbeat: i64,
+    pub stall_after_secs: u64,
+    pub restarts: u32,
+    pub consecutive_restarts: u32,
+    pub last_restart_at: Option<i64>,
+    pub next_restart_at: Option<i64>,
+}
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decision {
+    None,
+    Restart(u64),
+    GiveUp,
+}
+
+/// 单个子系统的监督状态（不含启动函数，便于测试）
+#[derive(Debug, Clone)]
+struct Supervision {
+    stall_after: i64,
+    generation: u64,
+    status: SubsystemStatus,
+    last_heartbeat: i64,
+    restarts: u32,
+    consecutive_restarts: u32,
+    last_restart_at: Option<i64>,
+    next_restart_at: Option<i64>,
+}
+
+fn backoff_secs(consecutive_restarts: u32) -> i64 {
+    if consecutive_restarts == 0 {
+        return 0;
+    }
+    let factor = 1i64 << (consecutive_restarts - 1).min(16);
+    (BACKOFF_BASE_SECS * factor).min(BACKOFF_MAX_SECS)
+}
+
+impl Supervision {
+    fn new(stall_after: Duration, now: i64) -> Self {
+        Self {
+            stall_after: stall_after.as_secs() as i64,
+            generation: 0,
+            status: SubsystemStatus::Healthy,
+            last_heartbeat: now,
+            restarts: 0,
+            consecutive_restarts: 0,
+            last_restart_at: None,
+            next_restart_at: None,
+        }
+    }
+
+    /// 上报心跳，返回该代是否仍为当前代
+    fn beat(&mut self, generation: u64, now: i64) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+        self.last_heartbeat = now;
+        self.status = SubsystemStatus::Healthy;
+        self.next_restart_at = None;
+        if matches!(self.last_restart_at, Some(at) if now - at >= RECOVERY_SECS) {
+            self.consecutive_restarts = 0;
+        }
+        true
+    }
+
+    fn poll(&mut self, now: i64) -> Decision {
+        if self.status == SubsystemStatus::Failed || now - self.last_heartbeat < self.stall_after {
+            return Decision::None;
+        }
+        let restart_at = match self.next_restart_at {
+            Some(at) => at,
+            None => {
+                if self.consecutive_restarts >= MAX_CONSECUTIVE_RESTARTS {
+                    self.status = SubsystemStatus::Failed;
+                    return Decision::GiveUp;
+                }
+                let at = now + backoff_secs(self.consecutive_restarts);
+                self.status = SubsystemStatus::Stalled;
+                self.next_restart_at = Some(at);
+                at
+            }
+        };
+        if now < restart_at {
+            return Decision::None;
+        }
+
+        self.generation += 1;
+        self.restarts += 1;
+        self.consecutive_restarts += 1;
+        self.last_restart_at = Some(now);
+        self.next_restart_at = None;
+        // 给新任务一个完整的心跳周期
+        self.last_heartbeat = now;
+        self.status = SubsystemStatus::Healthy;
+        Decision::Restart(self.generation)
+    }
+
+    fn health(&self, name: &str) -> SubsystemHealth {
+        SubsystemHealth {
+            name: name.to_string(),
+            status: self.status,
+            last_heartbeat: self.last_heartbeat,
+            stall_after_secs: self.stall_after as u64,
+            restarts: self.restarts,
+            consecutive_restarts: self.consecutive_restarts,
+            last_restart_at: self.last_restart_at,
+            next_restart_at: self.next_restart_at,
+        }
+    }
+}
+
+type SpawnFn = Arc<dyn Fn(Heartbeat) + Send + Sync>;
+
+struct Entry {
+    supervision: Supervision,
+    spawn: SpawnFn,
+}
+
+/// 看门狗
+pub struct Watchdog {
+    entries: Mutex<BTreeMap<String, Entry>>,
+    clock: SharedClock,
+}
+
+/// 子系统心跳句柄
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    name: String,
+    generation: u64,
+}
+
+impl Heartbeat {
+    /// 上报心跳；返回 `false` 表示已被看门狗替换，调用方应退出循环
+    pub fn beat(&self) -> bool {
+        WATCHDOG.beat(&self.name, self.generation)
+    }
+}
+
+impl Watchdog {
+    pub fn new(clock: SharedClock) -> Self {
+        Self { entries: Mutex::new(BTreeMap::new()), clock }
+    }
+
+    fn register(&self, name: &str, stall_after: Duration, spawn: SpawnFn) -> Heartbeat {
+        let mut supervision = Supervision::new(stall_after, self.clock.timestamp());
+        let mut entries = self.entries.lock();
+        // 重复注册时沿用递增的代数，让之前启动的循环退出
+        if let Some(previous) = entries.get(name) {
+            supervision.generation = previous.supervision.generation + 1;
+        }
+        let generation = supervision.generation;
+        entries.insert(name.to_string(), Entry { supervision, spawn });
+        Heartbeat { name: name.to_string(), generation }
+    }
+
+    fn beat(&self, name: &str, generation: u64) -> bool {
+        let now = self.clock.timestamp();
+        match self.entries.lock().get_mut(name) {
+            Some(entry) => entry.supervision.beat(generation, now),
+            // 未注册（如已注销）的子系统不受看门狗管理
+            None => true,
+        }
+    }
+
+    /// 检查所有子系统，返回需要重启的子系统与已放弃的子系统
+    fn poll(&self) -> (Vec<(SpawnFn, Heartbeat)>, Vec<String>) {
+        let now = self.clock.timestamp();
+        let mut restarts = Vec::new();
+        let mut failed = Vec::new();
+        for (name, entry) in self.entries.lock().iter_mut() {
+            match entry.supervision.poll(now) {
+                Decision::None => {}
+                Decision::Restart(generation) => {
+                    restarts.push((entry.spawn.clone(), Heartbeat { name: name.clone(), generation }));
+                }
+                Decision::GiveUp => failed.push(name.clone()),
+            }
+        }
+        (restarts, failed)
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.entries.lock().remove(name);
+    }
+
+    pub fn health(&self) -> Vec<SubsystemHealth> {
+        self.entries
+            .lock()
+            .iter()
+            .map(|(name, entry)| entry.supervision.health(name))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref WATCHDOG: Watchdog = Watchdog::new(clock::global());
+}
+
+/// 注册并启动受监督的子系统
+///
+/// `spawn` 负责启动子系统的后台循环（首次启动与每次重启都会调用），循环中应定期调用
+/// [`Heartbeat::beat`]；`stall_after` 应明显大于循环的最长间隔。
+pub fn supervise<F>(name: &str, stall_after: Duration, spawn: F)
+where
+    F: Fn(Heartbeat) + Send + Sync + 'static,
+{
+    let spawn: SpawnFn = Arc::new(spawn);
+    let heartbeat = WATCHDOG.register(name, stall_after, spawn.clone());
+    spawn(heartbeat);
+}
+
+/// 子系统主动停止时注销，避免被误判为停滞
+pub fn unregister(name: &str) {
+    WATCHDOG.unregister(name);
+}
+
+/// 所有受监督子系统的健康信息
+pub fn health() -> Vec<SubsystemHealth> {
+    WATCHDOG.health()
+}
+
+/// 启动看门狗检查循环；放弃重启的子系统通过 `on_failed` 交给调用方提醒用户
+pub fn start(on_failed: impl Fn(&str) + Send + 'static) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let (restarts, failed) = WATCHDOG.poll();
+            for (spawn, heartbeat) in restarts {
+                warn!("子系统 {} 心跳停滞，正在重启（第 {} 代）", heartbeat.name, heartbeat.generation);
+                spawn(heartbeat);
+            }
+            for name in failed {
+                error!("子系统 {} 连续重启 {} 次仍无响应，已停止重启", name, MAX_CONSECUTIVE_RESTARTS);
+                on_failed(&name);
+            }
+        }
+    });
+    info!("后台子系统看门狗已启动");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalled_subsystem_restarts_with_backoff() {
+        let mut supervision = Supervision::new(Duration::from_secs(60), 0);
+        assert_eq!(supervision.poll(59), Decision::None);
+        // 第一次立即重启
+        assert_eq!(supervision.poll(60), Decision::Restart(1));
+        assert!(!supervision.beat(0, 61), "旧代的心跳应被拒绝");
+
+        // 第二次需等待退避时间
+        assert_eq!(supervision.poll(120), Decision::None);
+        assert_eq!(supervision.status, SubsystemStatus::Stalled);
+        assert_eq!(supervision.poll(120 + BACKOFF_BASE_SECS), Decision::Restart(2));
+        assert_eq!(supervision.restarts, 2);
+
+        // 新代恢复心跳后回到健康状态
+        assert!(supervision.beat(2, 200));
+        assert_eq!(supervision.status, SubsystemStatus::Healthy);
+        assert_eq!(supervision.consecutive_restarts, 2);
+        assert!(supervision.beat(2, 150 + RECOVERY_SECS + 60));
+        assert_eq!(supervision.consecutive_restarts, 0);
+        assert_eq!(supervision.restarts, 2);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_restarts() {
+        let mut supervision = Supervision::new(Duration::from_secs(10), 0);
+        let mut now = 0;
+        let mut restarts = 0;
+        loop {
+            now += 1;
+            match supervision.poll(now) {
+                Decision::Restart(_) => restarts += 1,
+                Decision::GiveUp => break,
+                Decision::None => {}
+            }
+            assert!(now < 100_000);
+        }
+        assert_eq!(restarts, MAX_CONSECUTIVE_RESTARTS);
+        assert_eq!(supervision.status, SubsystemStatus::Failed);
+        assert_eq!(supervision.poll(now + 1_000), Decision::None);
+
+        // 当前代再次上报心跳后恢复
+        assert!(supervision.beat(supervision.generation, now + 1));
+        assert_eq!(supervision.status, SubsystemStatus::Healthy);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        assert_eq!(backoff_secs(0), 0);
+        assert_eq!(backoff_secs(1), BACKOFF_BASE_SECS);
+        assert_eq!(backoff_secs(2), BACKOFF_BASE_SECS * 2);
+        assert_eq!(backoff_secs(40), BACKOFF_MAX_SECS);
+    }
+}
@@ -0,0 +1,162 @@
+//! # 桌宠贴边停靠
+//!
+//! 拖动桌宠窗口靠近屏幕边缘时吸附到边缘（停靠）；停靠后可以收起为只露出一小条的
+//! 探头状态，鼠标移上去时滑出。与其他显示器相邻的边不参与停靠，避免收起的窗口露在
+//! 另一块屏幕上。
+//!
+//! 本模块只包含几何计算与设置，事件处理与动画见 `events::window_dock`。
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::window_layout::Rect;
+
+/// 停靠设置（保存在 `WindowConfig::docking`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockSettings {
+    /// 是否启用贴边停靠
+    pub enabled: bool,
+    /// 距离边缘多少逻辑像素以内时吸附
+    pub snap_distance: u32,
+    /// 探头状态露出的逻辑像素
+    pub peek_size: u32,
+    /// 鼠标离开后自动收起为探头状态
+    pub auto_hide: bool,
+    /// 鼠标离开后多久收起（毫秒）
+    pub hide_delay_ms: u64,
+}
+
+impl Default for DockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            snap_distance: 24,
+            peek_size: 48,
+            auto_hide: false,
+            hide_delay_ms: 800,
+        }
+    }
+}
+
+impl DockSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(4..=200).contains(&self.snap_distance) {
+            return Err("吸附距离必须在 4 到 200 像素之间".to_string());
+        }
+        if !(8..=200).contains(&self.peek_size) {
+            return Err("探头露出宽度必须在 8 到 200 像素之间".to_string());
+        }
+        if self.hide_delay_ms > 10_000 {
+            return Err("自动收起延迟不能超过 10 秒".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 屏幕边缘
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DockEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// 停靠状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DockMode {
+    /// 未停靠
+    #[default]
+    Floating,
+    /// 贴在边缘，完整显示
+    Docked,
+    /// 收起到边缘外，只露出一小条
+    Peeking,
+}
+
+/// 与 `screen` 的某条边相邻的其他显示器是否存在
+fn edge_is_shared(screen: Rect, edge: DockEdge, monitors: &[Rect]) -> bool {
+    let overlaps = |a0: i32, a1: i32, b0: i32, b1: i32| a0 < b1 && b0 < a1;
+    monitors.iter().filter(|monitor| **monitor != screen).any(|monitor| match edge {
+        DockEdge::Left => monitor.right() == screen.x && overlaps(monitor.y, monitor.bottom(), screen.y, screen.bottom()),
+        DockEdge::Right => monitor.x == screen.right() && overlaps(monitor.y, monitor.bottom(), screen.y, screen.bottom()),
+        DockEdge::Top => monitor.bottom() == screen.y && overlaps(monitor.x, monitor.right(), screen.x, screen.right()),
+        DockEdge::Bottom => monitor.y == screen.bottom() && overlaps(monitor.x, monitor.right(), screen.x, screen.right()),
+    })
+}
+
+/// 窗口在 `distance` 以内最近的可停靠边缘；越出边缘的窗口也算靠近
+pub fn nearest_edge(window: Rect, screen: Rect, monitors: &[Rect], distance: i32) -> Option<DockEdge> {
+    [
+        (DockEdge::Left, window.x - screen.x),
+        (DockEdge::Right, screen.right() - window.right()),
+        (DockEdge::Top, window.y - screen.y),
+        (DockEdge::Bottom, screen.bottom() - window.bottom()),
+    ]
+    .into_iter()
+    .filter(|(_, gap)| *gap <= distance)
+    .filter(|(edge, _)| !edge_is_shared(screen, *edge, monitors))
+    .min_by_key(|(_, gap)| *gap)
+    .map(|(edge, _)| edge)
+}
+
+/// 贴在边缘时的位置（沿边缘方向限制在屏幕内）
+pub fn docked_position(window: Rect, screen: Rect, edge: DockEdge) -> (i32, i32) {
+    let clamp_x = window.x.clamp(screen.x, (screen.right() - window.width).max(screen.x));
+    let clamp_y = window.y.clamp(screen.y, (screen.bottom() - window.height).max(screen.y));
+    match edge {
+        DockEdge::Left => (screen.x, clamp_y),
+        DockEdge::Right => (screen.right() - window.width, clamp_y),
+        DockEdge::Top => (clamp_x, screen.y),
+        DockEdge::Bottom => (clamp_x, screen.bottom() - window.height),
+    }
+}
+
+/// 探头状态的位置：窗口移到边缘外，只露出 `peek_size` 像素
+pub fn peek_position(window: Rect, screen: Rect, edge: DockEdge, peek_size: i32) -> (i32, i32) {
+    let (x, y) = docked_position(window, screen, edge);
+    match edge {
+        DockEdge::Left => (screen.x - window.width + peek_size.min(window.width), y),
+        DockEdge::Right => (screen.right() - peek_size.min(window.width), y),
+        DockEdge::Top => (x, screen.y - window.height + peek_size.min(window.height)),
+        DockEdge::Bottom => (x, screen.bottom() - peek_size.min(window.height)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    #[test]
+    fn test_nearest_edge_skips_shared_edges() {
+        let screen = rect(0, 0, 1920, 1080);
+        let window = rect(1900 - 300, 500, 300, 400);
+        assert_eq!(nearest_edge(window, screen, &[screen], 24), Some(DockEdge::Right));
+        assert_eq!(nearest_edge(rect(500, 300, 300, 400), screen, &[screen], 24), None);
+        // 拖出屏幕一部分也算靠近
+        assert_eq!(nearest_edge(rect(-50, 300, 300, 400), screen, &[screen], 24), Some(DockEdge::Left));
+
+        // 右侧有另一块显示器时不停靠到右边
+        let second = rect(1920, 0, 1920, 1080);
+        assert_eq!(nearest_edge(window, screen, &[screen, second], 24), None);
+        // 角落里取更近的边
+        assert_eq!(nearest_edge(rect(10, 5, 300, 400), screen, &[screen, second], 24), Some(DockEdge::Top));
+    }
+
+    #[test]
+    fn test_docked_and_peek_positions() {
+        let screen = rect(0, 0, 1920, 1080);
+        let window = rect(1650, 900, 300, 400);
+        assert_eq!(docked_position(window, screen, DockEdge::Right), (1620, 680));
+        assert_eq!(peek_position(window, screen, DockEdge::Right, 48), (1872, 680));
+        assert_eq!(peek_position(window, screen, DockEdge::Left, 48), (-252, 680));
+        assert_eq!(peek_position(window, screen, DockEdge::Top, 48), (1620, -352));
+        assert_eq!(peek_position(window, screen, DockEdge::Bottom, 1000), (1620, 680));
+    }
+}
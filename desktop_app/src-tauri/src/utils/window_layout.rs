@@ -0,0 +1,242 @@
+//! # 窗口布局
+//!
+//! 桌宠窗口、聊天窗口与快速对话窗口之间的磁吸与编组：
+//!
+//! - 拖动窗口靠近另一个窗口的边缘时贴合（边对边，并对齐顶/底或左/右边）
+//! - 贴合在一起的窗口组成一组，开启编组移动时拖动其中一个，其余窗口跟随
+//! - 各窗口位置与设置保存在 `window_layout.json`
+//!
+//! 本模块只包含几何计算与持久化，事件处理见 `events::window_layout`。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+
+/// 参与磁吸的窗口
+pub const MAGNETIC_WINDOWS: &[&str] = &["main", "chat", "quick-chat"];
+/// 判断两个窗口是否贴合的容差（像素）
+const TOUCH_TOLERANCE: i32 = 2;
+
+const LAYOUT_FILE: &str = "window_layout.json";
+
+pub fn is_magnetic(label: &str) -> bool {
+    MAGNETIC_WINDOWS.contains(&label)
+}
+
+/// 窗口矩形（物理像素）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    pub fn translated(&self, dx: i32, dy: i32) -> Self {
+        Self { x: self.x + dx, y: self.y + dy, ..*self }
+    }
+
+    /// 两个区间 [a0, a1) 与 [b0, b1) 在放宽 `slack` 后是否重叠
+    fn overlaps(a0: i32, a1: i32, b0: i32, b1: i32, slack: i32) -> bool {
+        a0 < b1 + slack && b0 < a1 + slack
+    }
+
+    /// 是否与另一窗口边对边贴合
+    pub fn touches(&self, other: &Rect) -> bool {
+        let side_by_side = ((self.right() - other.x).abs() <= TOUCH_TOLERANCE
+            || (other.right() - self.x).abs() <= TOUCH_TOLERANCE)
+            && Self::overlaps(self.y, self.bottom(), other.y, other.bottom(), 0);
+        let stacked = ((self.bottom() - other.y).abs() <= TOUCH_TOLERANCE
+            || (other.bottom() - self.y).abs() <= TOUCH_TOLERANCE)
+            && Self::overlaps(self.x, self.right(), other.x, other.right(), 0);
+        side_by_side || stacked
+    }
+}
+
+/// 磁吸设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutSettings {
+    /// 是否启用磁吸
+    pub enabled: bool,
+    /// 吸附距离（像素）
+    pub snap_distance: i32,
+    /// 贴合的窗口是否一起移动
+    pub move_as_group: bool,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            snap_distance: 16,
+            move_as_group: true,
+        }
+    }
+}
+
+/// 持久化的窗口布局
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowLayout {
+    pub settings: LayoutSettings,
+    /// 各窗口的位置
+    pub positions: BTreeMap<String, (i32, i32)>,
+}
+
+/// 在候选偏移中选出绝对值最小且不超过吸附距离的一个
+fn nearest(candidates: impl Iterator<Item = i32>, distance: i32) -> i32 {
+    candidates
+        .filter(|offset| offset.abs() <= distance)
+        .min_by_key(|offset| offset.abs())
+        .unwrap_or(0)
+}
+
+/// 计算窗口吸附到其他窗口所需的偏移
+pub fn snap_offset(moving: Rect, others: &[Rect], distance: i32) -> (i32, i32) {
+    if distance <= 0 {
+        return (0, 0);
+    }
+    let mut dx_candidates = Vec::new();
+    let mut dy_candidates = Vec::new();
+    for other in others {
+        let vertical_overlap = Rect::overlaps(moving.y, moving.bottom(), other.y, other.bottom(), distance);
+        let horizontal_overlap = Rect::overlaps(moving.x, moving.right(), other.x, other.right(), distance);
+
+        if vertical_overlap {
+            let to_left_edge = other.x - moving.right();
+            let to_right_edge = other.right() - moving.x;
+            dx_candidates.extend([to_left_edge, to_right_edge]);
+            // 左右相邻时对齐顶边或底边
+            if to_left_edge.abs() <= distance || to_right_edge.abs() <= distance {
+                dy_candidates.extend([other.y - moving.y, other.bottom() - moving.bottom()]);
+            }
+        }
+        if horizontal_overlap {
+            let to_top_edge = other.y - moving.bottom();
+            let to_bottom_edge = other.bottom() - moving.y;
+            dy_candidates.extend([to_top_edge, to_bottom_edge]);
+            // 上下相邻时对齐左边或右边
+            if to_top_edge.abs() <= distance || to_bottom_edge.abs() <= distance {
+                dx_candidates.extend([other.x - moving.x, other.right() - moving.right()]);
+            }
+        }
+    }
+    (
+        nearest(dx_candidates.into_iter(), distance),
+        nearest(dy_candidates.into_iter(), distance),
+    )
+}
+
+/// 与指定窗口直接或间接贴合的窗口（不含自身）
+pub fn attached_group(rects: &BTreeMap<String, Rect>, label: &str) -> BTreeSet<String> {
+    let mut group = BTreeSet::new();
+    let mut queue = VecDeque::from([label.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        let Some(rect) = rects.get(&current) else {
+            continue;
+        };
+        for (other, other_rect) in rects {
+            if other != label && !group.contains(other) && rect.touches(other_rect) {
+                group.insert(other.clone());
+                queue.push_back(other.clone());
+            }
+        }
+    }
+    group
+}
+
+/// 把弹出窗口放在锚点窗口下方居中（下方放不下时放在上方），并限制在屏幕范围内
+pub fn anchor_popup(anchor: Rect, width: i32, height: i32, screen: Rect, gap: i32) -> (i32, i32) {
+    let x = anchor.x + (anchor.width - width) / 2;
+    let y = if anchor.bottom() + gap + height <= screen.bottom() {
+        anchor.bottom() + gap
+    } else {
+        anchor.y - gap - height
+    };
+    (
+        x.clamp(screen.x, (screen.right() - width).max(screen.x)),
+        y.clamp(screen.y, (screen.bottom() - height).max(screen.y)),
+    )
+}
+
+fn layout_path() -> Result<PathBuf, String> {
+    Ok(crate::utils::config::get_app_data_dir()?.join(LAYOUT_FILE))
+}
+
+/// 读取保存的布局（不存在或损坏时使用默认值）
+pub fn load_layout() -> WindowLayout {
+    layout_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_layout(layout: &WindowLayout) -> Result<(), String> {
+    let path = layout_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(layout)
+        .map_err(|e| format!("Failed to serialize window layout: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write window layout: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    #[test]
+    fn test_snaps_edge_to_edge_and_aligns() {
+        let pet = rect(1000, 500, 300, 400);
+        // 聊天窗口在桌宠左侧，右边缘差 10 像素，顶边差 6 像素
+        let chat = rect(190, 494, 800, 600);
+        assert_eq!(snap_offset(chat, &[pet], 16), (10, 6));
+        // 超出吸附距离时不移动
+        assert_eq!(snap_offset(rect(100, 494, 800, 600), &[pet], 16), (0, 0));
+        // 上下相邻时对齐左边
+        let below = rect(1005, 910, 300, 100);
+        assert_eq!(snap_offset(below, &[pet], 16), (-5, -10));
+        // 垂直方向没有交集时不吸附水平边
+        assert_eq!(snap_offset(rect(190, 2000, 800, 600), &[pet], 16), (0, 0));
+        assert_eq!(snap_offset(chat, &[pet], 0), (0, 0));
+    }
+
+    #[test]
+    fn test_attached_group_is_transitive() {
+        let mut rects = BTreeMap::new();
+        rects.insert("main".to_string(), rect(1000, 500, 300, 400));
+        rects.insert("chat".to_string(), rect(200, 500, 800, 600));
+        rects.insert("quick-chat".to_string(), rect(200, 1101, 400, 100));
+        let group = attached_group(&rects, "main");
+        assert_eq!(group.into_iter().collect::<Vec<_>>(), vec!["chat".to_string(), "quick-chat".to_string()]);
+
+        rects.insert("chat".to_string(), rect(100, 500, 800, 600));
+        assert!(attached_group(&rects, "main").is_empty());
+    }
+
+    #[test]
+    fn test_anchor_popup_flips_and_clamps() {
+        let screen = rect(0, 0, 1920, 1080);
+        assert_eq!(anchor_popup(rect(1000, 500, 300, 400), 360, 60, screen, 8), (970, 908));
+        // 下方空间不足时放在上方
+        assert_eq!(anchor_popup(rect(1000, 600, 300, 440), 360, 60, screen, 8), (970, 532));
+        // 靠近屏幕右边缘时向内收
+        assert_eq!(anchor_popup(rect(1800, 100, 200, 300), 360, 60, screen, 8), (1560, 408));
+    }
+}
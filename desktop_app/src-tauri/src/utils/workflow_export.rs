@@ -0,0 +1,582 @@
+//! # 工作流导出
+//!
+//! 把工作流导出为可以在应用之外分享或纳入版本管理的文件：
+//!
+//! - `json`：完整的 JSON 包，内嵌工作流定义以及节点引用的提示词与模板
+//! - `shell` / `python`：简单线性工作流（无分支、循环、并行）的近似脚本，适配器节点通过
+//!   后端 `/api/adapters/{id}/execute` 调用，`${...}` 占位符在脚本运行时解析
+//!
+//! 这里只负责生成内容，获取工作流与引用资源的逻辑在 `commands::workflow_api`。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 导出包格式版本
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// 引用提示词的配置键
+const PROMPT_KEYS: &[&str] = &["prompt_id", "prompt_template_id"];
+/// 引用工作流模板的配置键
+const TEMPLATE_KEYS: &[&str] = &["template_id"];
+/// 无法按线性顺序导出为脚本的节点类型
+const NON_LINEAR_NODE_TYPES: &[&str] = &["condition", "loop", "parallel"];
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Shell,
+    Python,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "zishu-workflow.json",
+            ExportFormat::Shell => "sh",
+            ExportFormat::Python => "py",
+        }
+    }
+}
+
+/// 导出的工作流（不含用户 ID、时间戳等实例信息）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedWorkflow {
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub trigger_type: String,
+    pub trigger_config: Option<Value>,
+    pub definition: Value,
+}
+
+/// 内嵌的提示词
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedPrompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub description: Option<String>,
+    pub character_setting: Option<String>,
+}
+
+/// 内嵌的工作流模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub definition: Value,
+}
+
+/// JSON 导出包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowBundle {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub workflow: ExportedWorkflow,
+    pub prompts: Vec<EmbeddedPrompt>,
+    pub templates: Vec<EmbeddedTemplate>,
+    /// 引用了但未能找到的资源 ID
+    pub missing_references: Vec<String>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowExport {
+    pub format: ExportFormat,
+    /// 建议的文件名
+    pub file_name: String,
+    pub content: String,
+}
+
+/// 工作流定义中引用的资源
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct References {
+    pub prompt_ids: BTreeSet<String>,
+    pub template_ids: BTreeSet<String>,
+}
+
+fn collect_into(value: &Value, refs: &mut References) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if let Some(id) = child.as_str().filter(|id| !id.is_empty()) {
+                    if PROMPT_KEYS.contains(&key.as_str()) {
+                        refs.prompt_ids.insert(id.to_string());
+                    } else if TEMPLATE_KEYS.contains(&key.as_str()) {
+                        refs.template_ids.insert(id.to_string());
+                    }
+                }
+                collect_into(child, refs);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_into(item, refs)),
+        _ => {}
+    }
+}
+
+/// 收集节点配置中引用的提示词与模板
+pub fn collect_references(definition: &Value) -> References {
+    let mut refs = References::default();
+    if let Some(nodes) = definition.get("nodes") {
+        collect_into(nodes, &mut refs);
+    }
+    refs
+}
+
+fn node_type(node: &Value) -> &str {
+    node.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+/// 按执行顺序排列线性工作流的节点；有分支、循环或并行时返回错误
+pub fn linear_order(definition: &Value) -> Result<Vec<&Value>, String> {
+    let nodes: Vec<&Value> = definition
+        .get("nodes")
+        .and_then(Value::as_array)
+        .map(|nodes| nodes.iter().collect())
+        .unwrap_or_default();
+    let by_id: HashMap<&str, &Value> = nodes
+        .iter()
+        .filter_map(|node| node.get("id").and_then(Value::as_str).map(|id| (id, *node)))
+        .collect();
+
+    let mut next: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in definition.get("edges").and_then(Value::as_array).into_iter().flatten() {
+        if let (Some(source), Some(target)) = (
+            edge.get("source").and_then(Value::as_str),
+            edge.get("target").and_then(Value::as_str),
+        ) {
+            next.entry(source).or_default().push(target);
+        }
+    }
+
+    let starts: Vec<&Value> = nodes.iter().copied().filter(|node| node_type(node) == "start").collect();
+    let mut current = match starts.as_slice() {
+        [start] => *start,
+        [] => return Err("工作流缺少开始节点".to_string()),
+        _ => return Err("工作流有多个开始节点，无法导出为脚本".to_string()),
+    };
+
+    let mut ordered = Vec::new();
+    let mut visited = HashSet::new();
+    loop {
+        let id = current.get("id").and_then(Value::as_str).unwrap_or("");
+        if !visited.insert(id) {
+            return Err(format!("工作流在节点 {} 处形成循环，无法导出为脚本", id));
+        }
+        if NON_LINEAR_NODE_TYPES.contains(&node_type(current)) {
+            return Err(format!(
+                "节点 {} 的类型 {} 不是线性步骤，只能导出为 JSON",
+                id,
+                node_type(current)
+            ));
+        }
+        ordered.push(current);
+
+        current = match next.get(id).map(Vec::as_slice).unwrap_or_default() {
+            [] => break,
+            [target] => *by_id
+                .get(target)
+                .ok_or_else(|| format!("连接指向不存在的节点: {}", target))?,
+            _ => return Err(format!("节点 {} 有多个后续节点，只能导出为 JSON", id)),
+        };
+    }
+    Ok(ordered)
+}
+
+fn config<'a>(node: &'a Value, key: &str) -> Option<&'a Value> {
+    node.get("config").and_then(|config| config.get(key))
+}
+
+fn config_str<'a>(node: &'a Value, key: &str) -> Option<&'a str> {
+    config(node, key).and_then(Value::as_str)
+}
+
+fn is_valid_token(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+fn sh_single_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// 把 `${...}` 占位符转换为 shell 表达式（通过 jq 从 `$INPUT` / `$VAR_x` 取值）
+fn sh_placeholder(token: &str) -> String {
+    let mut parts = token.split('.');
+    let head = parts.next().unwrap_or_default();
+    let (source, keys): (String, Vec<&str>) = match head {
+        "input" => ("$INPUT".to_string(), parts.collect()),
+        "variables" => match parts.next() {
+            Some(var) => (format!("$VAR_{}", var), parts.collect()),
+            None => return "{}".to_string(),
+        },
+        var => (format!("$VAR_{}", var), parts.collect()),
+    };
+    let filter = if keys.is_empty() {
+        ".".to_string()
+    } else {
+        keys.iter().map(|key| format!("[\"{}\"]", key)).collect::<String>().replacen('[', ".[", 1)
+    };
+    format!("$(zishu_get \"{}\" '{}')", source, filter)
+}
+
+/// 生成双引号字符串内容：转义 shell 特殊字符，占位符替换为运行时取值
+fn sh_interpolate(text: &str) -> String {
+    let escape = |s: &str| {
+        s.chars().fold(String::new(), |mut out, c| {
+            if matches!(c, '\\' | '$' | '`' | '"') {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&escape(&rest[..start]));
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) if is_valid_token(&after[..end]) => {
+                out.push_str(&sh_placeholder(&after[..end]));
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push_str(&escape("${"));
+                rest = after;
+            }
+        }
+    }
+    out.push_str(&escape(rest));
+    out
+}
+
+fn sh_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("\"{}\"", sh_interpolate(text)),
+        other => format!("\"{}\"", sh_interpolate(&other.to_string())),
+    }
+}
+
+fn output_variable(node: &Value) -> Option<&str> {
+    config_str(node, "output_variable").filter(|var| is_valid_token(var) && !var.contains('.'))
+}
+
+/// 执行命令；节点配置了输出变量时把输出保存到 `VAR_<name>`
+fn sh_run(node: &Value, command: &str) -> String {
+    match output_variable(node) {
+        Some(var) => format!("VAR_{}=$({})\n", var, command),
+        None => format!("{} >/dev/null\n", command),
+    }
+}
+
+/// 生成线性工作流的 shell 脚本（依赖 curl 与 jq）
+pub fn render_shell(workflow: &ExportedWorkflow) -> Result<String, String> {
+    let steps = linear_order(&workflow.definition)?;
+    let mut script = String::new();
+    script.push_str("#!/usr/bin/env bash\n");
+    script.push_str(&format!("# 工作流: {}\n", workflow.name.replace('\n', " ")));
+    script.push_str("# 由 Zishu 导出的近似脚本，依赖 curl 与 jq\n");
+    script.push_str("# 用法: ./script.sh '<输入 JSON>'，ZISHU_API_URL 指定后端地址\n");
+    script.push_str("set -eo pipefail\n\n");
+    script.push_str("ZISHU_API_URL=\"${ZISHU_API_URL:-http://127.0.0.1:8000}\"\n");
+    script.push_str("INPUT=\"${1:-}\"\n[ -n \"$INPUT\" ] || INPUT='{}'\n\n");
+    script.push_str("zishu_get() { printf '%s' \"$1\" | jq -r \"$2 // empty\"; }\n");
+
+    for node in steps {
+        let id = node.get("id").and_then(Value::as_str).unwrap_or("");
+        script.push_str(&format!("\n# [{}] {}\n", node_type(node), id.replace('\n', " ")));
+        match node_type(node) {
+            "start" => {}
+            "adapter" => {
+                let adapter_id = config_str(node, "adapter_id").ok_or_else(|| format!("适配器节点 {} 缺少 adapter_id", id))?;
+                let params = config(node, "parameters").cloned().unwrap_or_else(|| Value::Object(Default::default()));
+                let query = params
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| params.to_string());
+                let body = serde_json::json!({ "query": query, "context": params });
+                script.push_str(&format!("BODY={}\n", sh_value(&body)));
+                let request = format!(
+                    "curl -sS -X POST \"$ZISHU_API_URL/api/adapters/{}/execute\" -H 'Content-Type: application/json' -d \"$BODY\"",
+                    sh_interpolate(adapter_id)
+                );
+                script.push_str(&sh_run(node, &request));
+            }
+            "http" => {
+                let url = config_str(node, "url").ok_or_else(|| format!("HTTP 节点 {} 缺少 url", id))?;
+                let method = config_str(node, "method").unwrap_or("GET");
+                let mut request = format!("curl -sS -X {} \"{}\"", sh_single_quote(method), sh_interpolate(url));
+                if let Some(headers) = config(node, "headers").and_then(Value::as_object) {
+                    for (name, value) in headers {
+                        let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                        request.push_str(&format!(" -H \"{}: {}\"", sh_interpolate(name), sh_interpolate(&value)));
+                    }
+                }
+                if let Some(body) = config(node, "body").filter(|body| !body.is_null()) {
+                    request.push_str(&format!(" -d {}", sh_value(body)));
+                }
+                script.push_str(&sh_run(node, &request));
+            }
+            "delay" => {
+                let seconds = config(node, "delay_seconds").and_then(Value::as_f64).unwrap_or(1.0);
+                script.push_str(&format!("sleep {}\n", seconds));
+            }
+            "transform" => {
+                if let Some(var) = output_variable(node) {
+                    let rules = config(node, "rules").cloned().unwrap_or_else(|| Value::Object(Default::default()));
+                    script.push_str(&format!("VAR_{}={}\n", var, sh_value(&rules)));
+                }
+            }
+            "script" => match config_str(node, "language").unwrap_or("python") {
+                "shell" | "bash" | "sh" => {
+                    script.push_str(config_str(node, "script").unwrap_or_default());
+                    script.push('\n');
+                }
+                language => {
+                    script.push_str(&format!("# {} 脚本节点无法在 shell 中执行，原脚本如下：\n", language));
+                    for line in config_str(node, "script").unwrap_or_default().lines() {
+                        script.push_str(&format!("# {}\n", line));
+                    }
+                }
+            },
+            "end" => {
+                let output = config(node, "output").cloned().unwrap_or_else(|| Value::Object(Default::default()));
+                script.push_str(&format!("printf '%s\\n' {}\n", sh_value(&output)));
+            }
+            other => return Err(format!("节点 {} 的类型 {} 不支持导出为脚本", id, other)),
+        }
+    }
+    Ok(script)
+}
+
+const PYTHON_TEMPLATE: &str = r#"#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+# 工作流: __NAME__
+# 由 Zishu 导出的近似脚本，仅依赖 Python 标准库
+# 用法: python3 script.py '<输入 JSON>'，ZISHU_API_URL 指定后端地址
+
+import json
+import os
+import re
+import sys
+import time
+import urllib.request
+
+API_URL = os.environ.get("ZISHU_API_URL", "http://127.0.0.1:8000").rstrip("/")
+STEPS = json.loads(__STEPS__)
+PLACEHOLDER = re.compile(r"\$\{([A-Za-z0-9_]+(?:\.[A-Za-z0-9_]+)*)\}")
+
+
+def lookup(path, state):
+    if path in ("input", "variables"):
+        return state[path]
+    if path.startswith("input."):
+        current, keys = state["input"], path[6:].split(".")
+    elif path.startswith("variables."):
+        current, keys = state["variables"], path[10:].split(".")
+    else:
+        current, keys = state["variables"], path.split(".")
+    for key in keys:
+        if not isinstance(current, dict) or key not in current:
+            raise ValueError("无法解析占位符: ${%s}" % path)
+        current = current[key]
+    return current
+
+
+def resolve(value, state):
+    if isinstance(value, str):
+        whole = PLACEHOLDER.fullmatch(value)
+        if whole:
+            return lookup(whole.group(1), state)
+        return PLACEHOLDER.sub(lambda m: str(lookup(m.group(1), state)), value)
+    if isinstance(value, dict):
+        return {k: resolve(v, state) for k, v in value.items()}
+    if isinstance(value, list):
+        return [resolve(v, state) for v in value]
+    return value
+
+
+def request(method, url, headers=None, body=None):
+    headers = dict(headers or {})
+    data = None
+    if body is not None:
+        data = body if isinstance(body, str) else json.dumps(body, ensure_ascii=False)
+        data = data.encode("utf-8")
+        headers.setdefault("Content-Type", "application/json")
+    req = urllib.request.Request(url, data=data, headers=headers, method=method)
+    with urllib.request.urlopen(req) as resp:
+        text = resp.read().decode("utf-8")
+        try:
+            payload = json.loads(text)
+        except ValueError:
+            payload = text
+        return {"status_code": resp.status, "body": payload}
+
+
+def run_step(node, state):
+    config = node.get("config", {})
+    kind = node["type"]
+    result = None
+    if kind == "adapter":
+        params = resolve(config.get("parameters", {}), state)
+        query = params.get("query")
+        if not isinstance(query, str):
+            query = json.dumps(params, ensure_ascii=False)
+        url = "%s/api/adapters/%s/execute" % (API_URL, config["adapter_id"])
+        result = request("POST", url, body={"query": query, "context": params})["body"]
+    elif kind == "http":
+        result = request(
+            config.get("method", "GET"),
+            resolve(config["url"], state),
+            resolve(config.get("headers", {}), state),
+            resolve(config.get("body"), state),
+        )
+    elif kind == "delay":
+        time.sleep(config.get("delay_seconds", 1))
+    elif kind == "transform":
+        result = resolve(config.get("rules", {}), state)
+    elif kind == "script":
+        if config.get("language", "python") != "python":
+            raise RuntimeError("节点 %s 不是 Python 脚本，无法执行" % node["id"])
+        scope = {"input": state["input"], "variables": state["variables"]}
+        exec(config.get("script", ""), scope)
+        result = scope.get("result")
+    elif kind == "end":
+        state["output"] = resolve(config.get("output", {}), state)
+    output_variable = config.get("output_variable")
+    if output_variable:
+        state["variables"][output_variable] = result
+
+
+def main():
+    state = {
+        "input": json.loads(sys.argv[1]) if len(sys.argv) > 1 else {},
+        "variables": {},
+        "output": {},
+    }
+    for node in STEPS:
+        run_step(node, state)
+    print(json.dumps(state["output"], ensure_ascii=False, indent=2))
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+/// 生成线性工作流的 Python 脚本（节点按执行顺序内嵌）
+pub fn render_python(workflow: &ExportedWorkflow) -> Result<String, String> {
+    let steps = linear_order(&workflow.definition)?;
+    let steps_json = serde_json::to_string(&steps)
+        .map_err(|e| format!("Failed to serialize workflow steps: {}", e))?;
+    // JSON 字符串字面量同时也是合法的 Python 字符串字面量
+    let steps_literal = serde_json::to_string(&steps_json)
+        .map_err(|e| format!("Failed to serialize workflow steps: {}", e))?;
+    Ok(PYTHON_TEMPLATE
+        .replace("__NAME__", &workflow.name.replace('\n', " "))
+        .replace("__STEPS__", &steps_literal))
+}
+
+/// 建议的导出文件名
+pub fn file_name(workflow: &ExportedWorkflow, format: ExportFormat) -> String {
+    let stem: String = workflow
+        .slug
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let stem = if stem.is_empty() { "workflow".to_string() } else { stem };
+    format!("{}.{}", stem, format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn workflow(definition: Value) -> ExportedWorkflow {
+        ExportedWorkflow {
+            name: "早安问候".to_string(),
+            slug: "morning greeting".to_string(),
+            description: None,
+            category: None,
+            tags: None,
+            trigger_type: "manual".to_string(),
+            trigger_config: None,
+            definition,
+        }
+    }
+
+    fn linear_definition() -> Value {
+        json!({
+            "nodes": [
+                {"id": "end", "type": "end", "config": {"output": {"reply": "${reply}"}}},
+                {"id": "start", "type": "start"},
+                {"id": "chat", "type": "adapter", "config": {
+                    "adapter_id": "chat",
+                    "parameters": {"query": "你好 ${input.name}", "prompt_id": "tsundere"},
+                    "output_variable": "reply"
+                }},
+            ],
+            "edges": [
+                {"source": "start", "target": "chat"},
+                {"source": "chat", "target": "end"},
+            ],
+        })
+    }
+
+    #[test]
+    fn test_linear_order_and_references() {
+        let definition = linear_definition();
+        let order: Vec<&str> = linear_order(&definition)
+            .unwrap()
+            .iter()
+            .map(|node| node["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(order, ["start", "chat", "end"]);
+
+        let refs = collect_references(&definition);
+        assert_eq!(refs.prompt_ids.into_iter().collect::<Vec<_>>(), ["tsundere"]);
+        assert!(refs.template_ids.is_empty());
+
+        let mut branching = definition.clone();
+        branching["edges"].as_array_mut().unwrap().push(json!({"source": "start", "target": "end"}));
+        assert!(linear_order(&branching).unwrap_err().contains("多个后续节点"));
+
+        let mut cyclic = definition;
+        cyclic["edges"].as_array_mut().unwrap().push(json!({"source": "end", "target": "chat"}));
+        assert!(linear_order(&cyclic).unwrap_err().contains("循环"));
+    }
+
+    #[test]
+    fn test_render_scripts() {
+        let workflow = workflow(linear_definition());
+        assert_eq!(file_name(&workflow, ExportFormat::Shell), "morning_greeting.sh");
+
+        let shell = render_shell(&workflow).unwrap();
+        assert!(shell.contains("VAR_reply=$(curl -sS -X POST \"$ZISHU_API_URL/api/adapters/chat/execute\""));
+        assert!(shell.contains("你好 $(zishu_get \"$INPUT\" '.[\"name\"]')"));
+        assert!(shell.contains("$(zishu_get \"$VAR_reply\" '.')"));
+
+        let python = render_python(&workflow).unwrap();
+        assert!(python.contains("# 工作流: 早安问候"));
+        assert!(!python.contains("__STEPS__"));
+        assert!(python.find("\\\"chat\\\"").unwrap() < python.find("\\\"end\\\"").unwrap());
+    }
+
+    #[test]
+    fn test_sh_interpolate_escapes_literals() {
+        assert_eq!(sh_interpolate("cost: $5 `x` \"q\""), "cost: \\$5 \\`x\\` \\\"q\\\"");
+        assert_eq!(sh_interpolate("${bad token}"), "\\${bad token}");
+        assert_eq!(sh_interpolate("${variables.a.b}"), "$(zishu_get \"$VAR_a\" '.[\"b\"]')");
+    }
+}
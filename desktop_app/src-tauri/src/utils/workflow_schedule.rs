@@ -0,0 +1,76 @@
+//! # 工作流定时触发
+//!
+//! 定时规则使用 cron 表达式，按本地时间计算：
+//!
+//! - 5 段为标准格式（分 时 日 月 周），补齐秒为 0
+//! - 6 / 7 段为带秒（及年）的扩展格式
+//!
+//! 两次触发的间隔不得短于一分钟。调度与持久化在 `commands::workflow_schedule`
+//! 与 `database::workflow`。
+
+use chrono::{DateTime, TimeZone};
+use cron::Schedule;
+use std::str::FromStr;
+
+/// 两次触发的最小间隔（秒）
+pub const MIN_INTERVAL_SECS: i64 = 60;
+
+/// 解析 cron 表达式
+pub fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let normalized = match fields.len() {
+        5 => format!("0 {}", fields.join(" ")),
+        6 | 7 => fields.join(" "),
+        _ => return Err(format!("cron 表达式应为 5 到 7 段，实际为 {} 段", fields.len())),
+    };
+    Schedule::from_str(&normalized).map_err(|e| format!("无效的 cron 表达式: {}", e))
+}
+
+/// `after` 之后的下一次触发时间（Unix 秒）
+pub fn next_run<Tz: TimeZone>(schedule: &Schedule, after: &DateTime<Tz>) -> Option<i64> {
+    schedule.after(after).next().map(|time| time.timestamp())
+}
+
+/// 校验 cron 表达式：能解析、之后还会触发，且触发间隔不短于一分钟
+pub fn validate_cron<Tz: TimeZone>(expression: &str, now: &DateTime<Tz>) -> Result<Schedule, String> {
+    let schedule = parse_cron(expression)?;
+    let mut upcoming = schedule.after(now);
+    let Some(first) = upcoming.next() else {
+        return Err("该 cron 表达式之后不会再触发".to_string());
+    };
+    if let Some(second) = upcoming.next() {
+        if second.timestamp() - first.timestamp() < MIN_INTERVAL_SECS {
+            return Err("触发间隔不能短于一分钟".to_string());
+        }
+    }
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_parse_standard_and_extended_expressions() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 8, 30, 0).unwrap();
+
+        let schedule = validate_cron("0 9 * * *", &now).unwrap();
+        assert_eq!(next_run(&schedule, &now), Some(Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap().timestamp()));
+
+        let schedule = validate_cron("0 */15 * * * *", &now).unwrap();
+        assert_eq!(next_run(&schedule, &now), Some(Utc.with_ymd_and_hms(2024, 5, 1, 8, 45, 0).unwrap().timestamp()));
+
+        assert!(parse_cron("* * *").is_err());
+        assert!(parse_cron("0 99 * * *").is_err());
+    }
+
+    #[test]
+    fn test_rejects_too_frequent_or_expired_schedules() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 8, 30, 0).unwrap();
+        assert!(validate_cron("*/10 * * * * *", &now).is_err());
+        assert!(validate_cron("* * * * *", &now).is_ok());
+        // 只在过去的年份触发
+        assert!(validate_cron("0 0 0 1 1 * 2020", &now).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! # 工作流执行追踪的逐节点查看
+//!
+//! 在本地按执行顺序逐个查看追踪中的节点（前进、后退、跳到失败的节点），每一帧带上截至该节点
+//! 已完成节点的输出。只读取后端记录的追踪，不会重新执行任何节点；需要用修改后的输入或定义
+//! 重跑时使用回放（`replay_execution`）。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::http::workflow_client::{ExecutionTrace, TraceStep};
+
+/// 单步方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepDirection {
+    Next,
+    Previous,
+    /// 回到第一个节点之前（同时重新获取追踪）
+    Restart,
+    /// 跳到第一个失败的节点
+    Failure,
+}
+
+/// 单步查看的当前帧
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceFrame {
+    pub execution_id: String,
+    pub execution_status: String,
+    /// 当前节点在追踪中的位置，尚未开始时为空
+    pub position: Option<usize>,
+    pub total: usize,
+    pub step: Option<TraceStep>,
+    /// 当前节点之前已成功的节点输出
+    pub node_results: HashMap<String, serde_json::Value>,
+    pub has_previous: bool,
+    pub has_next: bool,
+}
+
+/// 追踪上的游标
+#[derive(Debug, Clone)]
+pub struct TraceCursor {
+    trace: ExecutionTrace,
+    position: Option<usize>,
+}
+
+impl TraceCursor {
+    pub fn new(trace: ExecutionTrace) -> Self {
+        Self { trace, position: None }
+    }
+
+    /// 按方向移动游标，返回移动后的帧；已经到头时停在原处
+    pub fn step(&mut self, direction: StepDirection) -> TraceFrame {
+        let total = self.trace.steps.len();
+        self.position = match direction {
+            StepDirection::Next => match self.position {
+                None if total > 0 => Some(0),
+                Some(position) if position + 1 < total => Some(position + 1),
+                position => position,
+            },
+            StepDirection::Previous => match self.position {
+                Some(0) | None => None,
+                Some(position) => Some(position - 1),
+            },
+            StepDirection::Restart => None,
+            StepDirection::Failure => self
+                .trace
+                .steps
+                .iter()
+                .position(|step| step.status == "failed")
+                .or(self.position),
+        };
+        self.frame()
+    }
+
+    pub fn frame(&self) -> TraceFrame {
+        let total = self.trace.steps.len();
+        let completed = self.position.unwrap_or(0).min(total);
+        let node_results = self.trace.steps[..completed]
+            .iter()
+            .filter(|step| step.status == "success")
+            .filter_map(|step| step.output.clone().map(|output| (step.node_id.clone(), output)))
+            .collect();
+
+        TraceFrame {
+            execution_id: self.trace.execution_id.clone(),
+            execution_status: self.trace.execution_status.clone(),
+            position: self.position,
+            total,
+            step: self.position.and_then(|position| self.trace.steps.get(position).cloned()),
+            node_results,
+            has_previous: self.position.is_some(),
+            has_next: self.position.map_or(total > 0, |position| position + 1 < total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(index: usize, node_id: &str, status: &str) -> TraceStep {
+        TraceStep {
+            index,
+            node_id: node_id.to_string(),
+            node_type: None,
+            status: status.to_string(),
+            inputs: serde_json::Value::Null,
+            output: (status == "success").then(|| serde_json::json!({ "node": node_id })),
+            error: (status == "failed").then(|| "timeout".to_string()),
+            variables_after: None,
+            replayed: false,
+            started_at: String::new(),
+            completed_at: None,
+            duration_ms: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_step_through_trace() {
+        let mut cursor = TraceCursor::new(ExecutionTrace {
+            execution_id: "exec-1".to_string(),
+            workflow_id: "wf-1".to_string(),
+            execution_status: "failed".to_string(),
+            failed_node_id: Some("call".to_string()),
+            replay_of: None,
+            steps: vec![step(0, "start", "success"), step(1, "call", "failed")],
+        });
+
+        let frame = cursor.frame();
+        assert_eq!((frame.position, frame.has_previous, frame.has_next), (None, false, true));
+
+        let frame = cursor.step(StepDirection::Next);
+        assert_eq!(frame.step.unwrap().node_id, "start");
+        assert!(frame.node_results.is_empty());
+
+        let frame = cursor.step(StepDirection::Next);
+        assert_eq!(frame.position, Some(1));
+        assert!(frame.node_results.contains_key("start"));
+        assert!(!frame.has_next);
+        // 到头后停在最后一个节点
+        assert_eq!(cursor.step(StepDirection::Next).position, Some(1));
+
+        assert_eq!(cursor.step(StepDirection::Restart).position, None);
+        let frame = cursor.step(StepDirection::Failure);
+        assert_eq!(frame.step.unwrap().error.as_deref(), Some("timeout"));
+        assert_eq!(cursor.step(StepDirection::Previous).position, Some(0));
+        assert_eq!(cursor.step(StepDirection::Previous).position, None);
+    }
+}
@@ -411,3 +411,75 @@ enum AdapterError {
     MissingDependency(String),
     InvalidConfiguration(String),
 }
+
+/// 端到端测试：适配器注册表读写一次性数据库
+///
+/// 需要 `--features test-support`；数据库来源见 `zishu_sensei::test_support::environment`
+#[cfg(feature = "test-support")]
+mod registry_environment_tests {
+    use zishu_sensei::database::adapter::{AdapterDependency, AdapterInstallStatus};
+    use zishu_sensei::require_test_env;
+    use zishu_sensei::test_support::AdapterBuilder;
+
+    /// 测试适配器安装、启用、升级和卸载
+    #[tokio::test]
+    async fn test_adapter_registry_lifecycle() {
+        let environment = require_test_env!();
+        let db = environment.database();
+        let registry = &db.adapter_registry;
+
+        registry.add_adapter(AdapterBuilder::new("e2e-adapter").build()).await.unwrap();
+        assert!(registry.adapter_exists("e2e-adapter").await.unwrap());
+
+        registry.set_adapter_enabled("e2e-adapter", false).await.unwrap();
+        assert!(registry.get_enabled_adapters().await.unwrap().is_empty());
+
+        registry.update_adapter_status("e2e-adapter", AdapterInstallStatus::Updating).await.unwrap();
+        registry
+            .update_adapter(AdapterBuilder::new("e2e-adapter").version("1.1.0").build())
+            .await
+            .unwrap();
+        let adapter = registry.get_adapter("e2e-adapter").await.unwrap().expect("适配器应存在");
+        assert_eq!(adapter.version, "1.1.0");
+        assert_eq!(adapter.status, AdapterInstallStatus::Installed);
+
+        registry.delete_adapter("e2e-adapter").await.unwrap();
+        assert!(registry.get_adapter("e2e-adapter").await.unwrap().is_none());
+        assert_eq!(registry.count_adapters().await.unwrap(), 0);
+
+        drop(db);
+        environment.teardown().await;
+    }
+
+    /// 测试适配器依赖的增删
+    #[tokio::test]
+    async fn test_adapter_registry_dependencies() {
+        let environment = require_test_env!();
+        let db = environment.database();
+        let registry = &db.adapter_registry;
+
+        registry.add_adapter(AdapterBuilder::new("e2e-base").build()).await.unwrap();
+        registry.add_adapter(AdapterBuilder::new("e2e-plugin").build()).await.unwrap();
+        registry
+            .add_dependency(AdapterDependency {
+                id: 0,
+                adapter_id: "e2e-plugin".to_string(),
+                dependency_id: "e2e-base".to_string(),
+                version_requirement: ">=1.0.0".to_string(),
+                required: true,
+            })
+            .await
+            .unwrap();
+
+        let dependencies = registry.get_dependencies("e2e-plugin").await.unwrap();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].dependency_id, "e2e-base");
+        assert!(dependencies[0].required);
+
+        registry.delete_dependency("e2e-plugin", "e2e-base").await.unwrap();
+        assert!(registry.get_dependencies("e2e-plugin").await.unwrap().is_empty());
+
+        drop(db);
+        environment.teardown().await;
+    }
+}
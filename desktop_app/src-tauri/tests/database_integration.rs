@@ -233,3 +233,103 @@ fn create_minimal_test_config() -> DatabaseManagerConfig {
         enable_vector_search: false,
     }
 }
+
+/// 端到端测试：使用一次性数据库运行命令层依赖的全局实例
+///
+/// 需要 `--features test-support`；没有可用的 PostgreSQL（连接串、本机程序或 Docker）时跳过。
+/// Redis 和 Qdrant 未配置时使用 test_support 中的内存实现
+#[cfg(feature = "test-support")]
+mod ephemeral_environment_tests {
+    use serde_json::json;
+    use zishu_sensei::database::cache_service::CacheService;
+    use zishu_sensei::database::conversation::{Message, MessageRole};
+    use zishu_sensei::database::vector_search_service::VectorSearchService;
+    use zishu_sensei::database::{get_database, get_database_manager};
+    use zishu_sensei::require_test_env;
+
+    #[tokio::test]
+    async fn test_init_for_tests_provides_global_databases() {
+        let environment = require_test_env!();
+
+        let db = get_database().expect("init_for_tests 应设置全局数据库");
+        assert!(get_database_manager().is_some());
+        assert!(environment.has_redis());
+        assert!(environment.manager().qdrant_backend.is_some());
+
+        let history = &db.conversation_history;
+        history.ensure_conversation("e2e-session", "端到端测试", 1).await.unwrap();
+        history
+            .add_message(Message {
+                id: "e2e-message".to_string(),
+                conversation_id: "e2e-session".to_string(),
+                role: MessageRole::User,
+                content: "你好".to_string(),
+                created_at: 1,
+                edited_at: None,
+                deleted_at: None,
+                payload: None,
+                language: Some("zh".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(history.get_messages("e2e-session").await.unwrap().len(), 1);
+
+        environment.teardown().await;
+        assert!(get_database().is_none());
+    }
+
+    /// 测试 Redis 缓存的读写、过期时间和计数器
+    #[tokio::test]
+    async fn test_cache_service_roundtrip() {
+        let environment = require_test_env!();
+        let cache = CacheService::new(environment.manager().redis().expect("测试环境应启用 Redis"));
+
+        cache.create_session("e2e", &json!({ "user": "zishu" }), 60).await.unwrap();
+        let session: Option<serde_json::Value> = cache.get_session("e2e").await.unwrap();
+        assert_eq!(session, Some(json!({ "user": "zishu" })));
+        let ttl = cache.get_session_ttl("e2e").await.unwrap().expect("会话应有过期时间");
+        assert!(ttl > 0 && ttl <= 60);
+
+        assert_eq!(cache.increment_counter("e2e", 3).await.unwrap(), 3);
+        assert_eq!(cache.decrement_counter("e2e", 1).await.unwrap(), 2);
+
+        cache.destroy_session("e2e").await.unwrap();
+        assert!(!cache.exists("session:", "e2e").await.unwrap());
+
+        environment.teardown().await;
+    }
+
+    /// 测试 Qdrant 集合管理和相似度搜索
+    #[tokio::test]
+    async fn test_vector_search_roundtrip() {
+        let environment = require_test_env!();
+        let vectors = VectorSearchService::new(environment.manager().qdrant().expect("测试环境应启用 Qdrant"));
+
+        vectors.create_collection("e2e_vectors", 3).await.unwrap();
+        assert!(vectors.collection_exists("e2e_vectors").await.unwrap());
+
+        vectors
+            .batch_insert_vectors(
+                "e2e_vectors",
+                vec![
+                    ("1".to_string(), vec![1.0, 0.0, 0.0], json!({ "text": "猫" })),
+                    ("2".to_string(), vec![0.0, 1.0, 0.0], json!({ "text": "狗" })),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let results = vectors.search("e2e_vectors", vec![0.9, 0.1, 0.0], 1).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].payload["text"], "猫");
+
+        vectors.delete_vector("e2e_vectors", "1").await.unwrap();
+        let results = vectors.search("e2e_vectors", vec![0.9, 0.1, 0.0], 1).await.unwrap();
+        assert_eq!(results[0].payload["text"], "狗");
+
+        vectors.delete_collection("e2e_vectors").await.unwrap();
+        assert!(!vectors.collection_exists("e2e_vectors").await.unwrap());
+
+        environment.teardown().await;
+    }
+}
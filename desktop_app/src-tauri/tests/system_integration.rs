@@ -687,3 +687,27 @@ impl ErrorMonitor {
         ])
     }
 }
+
+/// 端到端测试：数据库健康检查和探测
+///
+/// 需要 `--features test-support`；数据库来源见 `zishu_sensei::test_support::environment`
+#[cfg(feature = "test-support")]
+mod database_health_tests {
+    use zishu_sensei::require_test_env;
+
+    /// 测试三种存储都可用时健康检查全部通过
+    #[tokio::test]
+    async fn test_health_check_reports_all_backends() {
+        let environment = require_test_env!();
+        let manager = environment.manager();
+
+        let health = manager.health_check().await;
+        assert!(health.is_all_healthy(), "健康检查未通过: {:?}", health);
+        assert!(health.postgres_error.is_none());
+
+        assert_eq!(manager.probe_redis().await, Some(Ok(())));
+        assert_eq!(manager.probe_qdrant().await, Some(Ok(())));
+
+        environment.teardown().await;
+    }
+}
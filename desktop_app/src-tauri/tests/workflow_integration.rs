@@ -639,3 +639,96 @@ enum WorkflowError {
     ExecutionFailed,
     InvalidDefinition,
 }
+
+/// 端到端测试：工作流注册表读写一次性数据库
+///
+/// 需要 `--features test-support`；数据库来源见 `zishu_sensei::test_support::environment`
+#[cfg(feature = "test-support")]
+mod registry_environment_tests {
+    use serde_json::json;
+    use zishu_sensei::database::workflow::{WorkflowSchedule, WorkflowStatus};
+    use zishu_sensei::require_test_env;
+    use zishu_sensei::test_support::WorkflowBuilder;
+
+    /// 测试工作流的保存、查询、更新和删除
+    ///
+    /// 工作流的 CRUD 接口是同步的（内部 `block_on`），需要在阻塞线程中调用
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_workflow_registry_crud() {
+        let environment = require_test_env!();
+        let db = environment.database();
+
+        let registry = db.clone();
+        tokio::task::spawn_blocking(move || {
+            let registry = &registry.workflow_registry;
+            let workflow = WorkflowBuilder::new("e2e-workflow")
+                .name("端到端工作流")
+                .category("e2e")
+                .tags(["测试"])
+                .step("delay", json!({ "ms": 10 }))
+                .build();
+            registry.create_workflow(workflow.clone()).unwrap();
+
+            let stored = registry.get_workflow("e2e-workflow").unwrap().expect("工作流应存在");
+            assert_eq!(stored.name, "端到端工作流");
+            assert_eq!(stored.steps, workflow.steps);
+            assert_eq!(registry.get_workflows_by_category("e2e").unwrap().len(), 1);
+            assert_eq!(registry.search_workflows("端到端").unwrap().len(), 1);
+
+            let mut published = stored;
+            published.status = WorkflowStatus::Published;
+            registry.update_workflow(published).unwrap();
+            assert_eq!(registry.get_workflow_stats().unwrap().published_count, 1);
+
+            registry.delete_workflow("e2e-workflow").unwrap();
+            assert!(registry.get_workflow("e2e-workflow").unwrap().is_none());
+        })
+        .await
+        .unwrap();
+
+        drop(db);
+        environment.teardown().await;
+    }
+
+    /// 测试定时触发的保存、到期查询和删除
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_workflow_schedules() {
+        let environment = require_test_env!();
+        let db = environment.database();
+
+        let registry = db.clone();
+        tokio::task::spawn_blocking(move || {
+            registry
+                .workflow_registry
+                .create_workflow(WorkflowBuilder::new("e2e-scheduled").build())
+                .unwrap();
+        })
+        .await
+        .unwrap();
+
+        let registry = &db.workflow_registry;
+        let schedule = WorkflowSchedule {
+            id: "e2e-schedule".to_string(),
+            workflow_id: "e2e-scheduled".to_string(),
+            cron: "0 * * * * *".to_string(),
+            input_data: Some(json!({ "source": "e2e" })),
+            paused: false,
+            next_run_at: Some(100),
+            last_run_at: None,
+            last_status: None,
+            last_error: None,
+            created_at: 1,
+        };
+        registry.save_schedule(&schedule).await.unwrap();
+
+        assert_eq!(registry.list_schedules(Some("e2e-scheduled")).await.unwrap().len(), 1);
+        assert!(registry.due_schedules(99).await.unwrap().is_empty());
+        assert_eq!(registry.due_schedules(100).await.unwrap().len(), 1);
+
+        assert!(registry.delete_schedule("e2e-schedule").await.unwrap());
+        assert!(registry.get_schedule("e2e-schedule").await.unwrap().is_none());
+
+        drop(db);
+        environment.teardown().await;
+    }
+}
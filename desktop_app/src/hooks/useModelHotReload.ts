@@ -26,6 +26,16 @@ export interface HotReloadOptions {
     onReloadError?: (error: Error) => void
 }
 
+/** 后端 `character-models-changed` 事件 */
+export interface CharacterModelsChangedPayload {
+    /** 重新注册的模型 */
+    model_ids: string[]
+    /** 目录已删除的模型 */
+    removed_ids: string[]
+    /** 模型清单是否变化 */
+    manifest_changed: boolean
+}
+
 export interface HotReloadState {
     /** 是否正在重新加载 */
     isReloading: boolean
@@ -157,19 +167,16 @@ export const useModelHotReload = (options: HotReloadOptions = {}) => {
 
         const setupListener = async () => {
             try {
-                // 监听模型文件变化事件
-                const unlisten = await listen<{ path: string }>('model-file-changed', (event) => {
-                    console.log('📁 检测到模型文件变化:', event.payload.path)
-                    
-                    // 提取模型ID（假设路径包含模型ID）
-                    const pathParts = event.payload.path.split('/')
-                    const modelIndex = pathParts.findIndex(part => part === 'live2d_models')
-                    if (modelIndex !== -1 && pathParts.length > modelIndex + 1) {
-                        const modelId = pathParts[modelIndex + 1]
-                        debouncedReload(modelId)
-                    } else {
-                        // 无法确定具体模型，重新加载所有
+                // 监听后端重新注册模型后的变化事件
+                const unlisten = await listen<CharacterModelsChangedPayload>('character-models-changed', (event) => {
+                    const { model_ids, manifest_changed } = event.payload
+                    console.log('📁 检测到模型文件变化:', model_ids)
+
+                    if (manifest_changed) {
+                        // 清单变化，重新加载所有
                         reloadAll()
+                    } else {
+                        model_ids.forEach(modelId => debouncedReload(modelId))
                     }
                 })
 
@@ -6,6 +6,7 @@
 
 import type { UnlistenFn } from '@tauri-apps/api/event'
 import { emit, listen, once } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/tauri'
 
 import type {
     AppEventType,
@@ -349,3 +350,39 @@ export const emitEvent = <T extends AppEventType>(
 export const createEventListenerGroup = (): EventListenerGroup => {
     return eventService.createListenerGroup()
 }
+
+/**
+ * 频道订阅选项（后端节流与过滤）
+ */
+export interface ChannelSubscriptionOptions {
+    /** 最小投递间隔（毫秒） */
+    throttle_ms?: number
+    /** 字段路径（如 `cpu.usage`）-> 期望值 */
+    filter?: Record<string, unknown>
+}
+
+/**
+ * 便捷函数：订阅高频事件频道
+ *
+ * 高频事件（如 `system-monitor-update`）只发送给订阅了该频道的窗口，
+ * 返回的函数会同时取消后端订阅和本地监听
+ */
+export const subscribeChannel = async <T = unknown>(
+    channel: string,
+    callback: (payload: T) => void | Promise<void>,
+    options?: ChannelSubscriptionOptions
+): Promise<UnlistenFn> => {
+    const unlisten = await listen<T>(channel, event => callback(event.payload))
+    try {
+        await invoke('subscribe_events', { channel, options })
+    } catch (error) {
+        unlisten()
+        throw error
+    }
+    return () => {
+        unlisten()
+        invoke('unsubscribe_events', { channel }).catch(error => {
+            console.error(`Failed to unsubscribe channel '${channel}':`, error)
+        })
+    }
+}
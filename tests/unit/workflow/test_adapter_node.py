@@ -0,0 +1,120 @@
+# -*- coding: utf-8 -*-
+"""
+适配器节点执行器测试
+"""
+
+import asyncio
+from types import SimpleNamespace
+
+import pytest
+
+from zishu.workflow.executor import AdapterNodeExecutor, NodeExecutionError, get_path
+
+
+class FakeAdapterManager:
+    """按顺序返回预设结果的适配器管理器"""
+
+    def __init__(self, outcomes, allowed=True):
+        self.is_running = True
+        self._adapters = {"echo": object()}
+        self.outcomes = list(outcomes)
+        self.allowed = allowed
+        self.calls = []
+
+    async def get_adapter(self, adapter_id):
+        return SimpleNamespace() if adapter_id == "echo" else None
+
+    async def check_permission(self, session_id, resource, action, adapter_id=None):
+        return self.allowed
+
+    async def process_with_adapter(self, adapter_id, params, context):
+        self.calls.append(params)
+        outcome = self.outcomes.pop(0)
+        if isinstance(outcome, Exception):
+            raise outcome
+        if outcome == "hang":
+            await asyncio.sleep(10)
+        return outcome
+
+
+def _node(**config):
+    return {"id": "call", "type": "adapter", "config": {"adapter_id": "echo", **config}}
+
+
+def _context(manager):
+    return {
+        "input": {"text": "你好"},
+        "variables": {},
+        "adapter_manager": manager,
+        "user_id": "user-1",
+        "execution_id": "exec-1",
+    }
+
+
+class TestAdapterNodeExecutor:
+    """适配器节点测试类"""
+
+    @pytest.mark.asyncio
+    async def test_retry_then_map_outputs(self):
+        """第一次失败后重试成功，输入与输出按映射处理"""
+        result = SimpleNamespace(status="success", output={"reply": {"text": "hi"}})
+        manager = FakeAdapterManager([RuntimeError("boom"), result])
+        context = _context(manager)
+        node = _node(
+            input_mapping={"prompt": "${input.text}"},
+            output_mapping={"reply_text": "reply.text"},
+            output_variable="raw",
+            max_retries=1,
+            retry_delay_seconds=0,
+        )
+
+        output = await AdapterNodeExecutor().execute(node, context, {})
+
+        assert output == {"reply": {"text": "hi"}}
+        assert manager.calls == [{"prompt": "你好"}, {"prompt": "你好"}]
+        assert context["variables"] == {"raw": output, "reply_text": "hi"}
+
+    @pytest.mark.asyncio
+    async def test_timeout_and_adapter_errors_are_structured(self):
+        """超时与适配器错误转换为带错误代码的失败"""
+        manager = FakeAdapterManager(["hang", "hang"])
+        with pytest.raises(NodeExecutionError) as info:
+            await AdapterNodeExecutor().execute(
+                _node(timeout_seconds=0.01, max_retries=1, retry_delay_seconds=0), _context(manager), {}
+            )
+        assert info.value.to_dict()["code"] == "timeout"
+        assert info.value.attempts == 2
+        assert info.value.node_id == "call"
+
+        failed = SimpleNamespace(status="cancelled", error="用户取消", error_details={"reason": "cancel"})
+        manager = FakeAdapterManager([failed])
+        with pytest.raises(NodeExecutionError) as info:
+            await AdapterNodeExecutor().execute(_node(max_retries=3), _context(manager), {})
+        assert info.value.code == "execution_failed"
+        assert info.value.details == {"reason": "cancel"}
+        # 取消不重试
+        assert len(manager.calls) == 1
+
+    @pytest.mark.asyncio
+    async def test_permission_and_config_checks(self):
+        """没有权限或配置无效时不调用适配器"""
+        manager = FakeAdapterManager([], allowed=False)
+        with pytest.raises(NodeExecutionError) as info:
+            await AdapterNodeExecutor().execute(_node(), _context(manager), {})
+        assert info.value.code == "permission_denied"
+
+        with pytest.raises(NodeExecutionError) as info:
+            await AdapterNodeExecutor().execute(_node(max_retries=99), _context(manager), {})
+        assert info.value.code == "invalid_config"
+
+        with pytest.raises(NodeExecutionError) as info:
+            await AdapterNodeExecutor().execute(
+                _node(output_mapping={"x": "missing"}),
+                _context(FakeAdapterManager([{"reply": 1}])),
+                {},
+            )
+        assert info.value.code == "output_mapping_failed"
+        assert manager.calls == []
+
+        assert get_path({"a": [{"b": 1}]}, "a.0.b") == 1
+        assert get_path(5, "") == 5
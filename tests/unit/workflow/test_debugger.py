@@ -0,0 +1,93 @@
+# -*- coding: utf-8 -*-
+"""
+工作流调试器测试
+"""
+
+import asyncio
+
+import pytest
+
+from zishu.workflow.debugger import (
+    DebugSessionError,
+    WorkflowDebugSession,
+    create_debug_session,
+    get_debug_session,
+    remove_debug_session,
+)
+
+
+async def _run_nodes(debugger, nodes, context, results):
+    """按顺序模拟引擎执行节点：调试钩子后记录当时的变量"""
+    for node in nodes:
+        await debugger.before_node(node, context, results)
+        results[node["id"]] = {"status": "success", "output": dict(context["variables"])}
+
+
+async def _wait_paused(debugger):
+    while debugger.state != "paused":
+        await asyncio.sleep(0)
+
+
+class TestWorkflowDebugSession:
+    """调试会话测试类"""
+
+    @pytest.mark.asyncio
+    async def test_breakpoint_edit_and_step(self):
+        """命中断点后修改变量，单步到下一个节点再继续"""
+        debugger = WorkflowDebugSession("exec-1", breakpoints=["b"])
+        nodes = [{"id": "a", "type": "start"}, {"id": "b", "type": "adapter"}, {"id": "c", "type": "end"}]
+        context = {"variables": {"count": 1}, "input": {}}
+        results = {}
+
+        task = asyncio.create_task(_run_nodes(debugger, nodes, context, results))
+        await _wait_paused(debugger)
+
+        frame = debugger.frame()
+        assert frame["node_id"] == "b"
+        assert frame["variables"] == {"count": 1}
+        assert list(frame["node_results"]) == ["a"]
+
+        debugger.update_variables({"count": 5})
+        debugger.resume(step=True)
+        await _wait_paused(debugger)
+        assert debugger.frame()["node_id"] == "c"
+        assert results["b"]["output"] == {"count": 5}
+
+        debugger.resume()
+        await task
+        assert debugger.state == "running"
+
+        events = [entry["event"] for entry in debugger.finish()]
+        assert events == ["paused", "variables_edited", "stepped", "paused", "resumed", "finished"]
+        assert debugger.trace[1]["changes"] == [{"name": "count", "old": 1, "new": 5}]
+
+    @pytest.mark.asyncio
+    async def test_edit_requires_pause_and_abort_wakes_execution(self):
+        """未暂停时不能修改变量；终止会唤醒暂停中的执行"""
+        debugger = WorkflowDebugSession("exec-2", breakpoints=["a"])
+        with pytest.raises(DebugSessionError):
+            debugger.update_variables({"x": 1})
+
+        context = {"variables": {}, "input": {}}
+        task = asyncio.create_task(_run_nodes(debugger, [{"id": "a", "type": "start"}], context, {}))
+        await _wait_paused(debugger)
+        debugger.abort()
+        with pytest.raises(RuntimeError):
+            await task
+        assert debugger.state == "finished"
+
+    @pytest.mark.asyncio
+    async def test_pause_timeout(self):
+        """暂停超时后终止执行"""
+        debugger = WorkflowDebugSession("exec-3", breakpoints=["a"], pause_timeout=0.01)
+        with pytest.raises(TimeoutError):
+            await debugger.before_node({"id": "a", "type": "start"}, {"variables": {}}, {})
+        assert debugger.trace[-1]["event"] == "timeout"
+
+    def test_registry(self):
+        """调试会话注册表"""
+        debugger = create_debug_session("exec-4", "user-1", ["n1"])
+        assert get_debug_session("exec-4") is debugger
+        assert debugger.frame()["breakpoints"] == ["n1"]
+        assert remove_debug_session("exec-4") is debugger
+        assert get_debug_session("exec-4") is None
@@ -0,0 +1,102 @@
+# -*- coding: utf-8 -*-
+"""
+工作流执行追踪与回放测试
+"""
+
+from types import SimpleNamespace
+
+import pytest
+
+from zishu.workflow.engine import WorkflowEngine
+from zishu.workflow.trace import ExecutionTrace, ReplayError, ReplayPlan, find_trace
+
+
+class CountingAdapterExecutor:
+    """记录调用次数，把输入文本写入变量的假适配器节点"""
+
+    def __init__(self):
+        self.calls = 0
+
+    async def execute(self, node, context, results):
+        self.calls += 1
+        context["variables"]["reply"] = f"echo:{context['input']['text']}"
+        return {"text": context["variables"]["reply"]}
+
+
+def _workflow():
+    definition = {
+        "nodes": [
+            {"id": "start", "type": "start"},
+            {"id": "call", "type": "adapter", "config": {"adapter_id": "echo"}},
+            {"id": "end", "type": "end", "config": {"output": {"reply": "${reply}"}}},
+        ],
+        "edges": [
+            {"source": "start", "target": "call"},
+            {"source": "call", "target": "end"},
+        ],
+    }
+    return SimpleNamespace(id="wf-1", name="回声", definition=definition)
+
+
+def _engine(adapter):
+    engine = WorkflowEngine()
+    engine.node_executors["adapter"] = adapter
+    return engine
+
+
+class TestExecutionTrace:
+    """执行追踪测试类"""
+
+    @pytest.mark.asyncio
+    async def test_trace_records_every_node(self):
+        """每个节点记录输入、输出、变量与耗时"""
+        adapter = CountingAdapterExecutor()
+        execution = SimpleNamespace(id="exec-1", user_id="user-1", input_data={"text": "你好"})
+        result = await _engine(adapter).execute(_workflow(), execution, {"variables": {}})
+
+        assert result["status"] == "success"
+        steps = result["trace"]
+        assert [step["node_id"] for step in steps] == ["start", "call", "end"]
+        call = steps[1]
+        assert call["inputs"]["variables"] == {}
+        assert call["variables_after"] == {"reply": "echo:你好"}
+        assert call["output"] == {"text": "echo:你好"}
+        assert call["duration_ms"] is not None and not call["replayed"]
+
+    @pytest.mark.asyncio
+    async def test_replay_uses_recorded_outputs(self):
+        """回放时有副作用的节点不再执行，变量修改按记录恢复"""
+        adapter = CountingAdapterExecutor()
+        engine = _engine(adapter)
+        execution = SimpleNamespace(id="exec-1", user_id="user-1", input_data={"text": "你好"})
+        original = await engine.execute(_workflow(), execution, {"variables": {}})
+
+        replay = ReplayPlan("exec-1", original["trace"])
+        replayed = SimpleNamespace(id="exec-2", user_id="user-1", input_data={"text": "换一句"})
+        result = await engine.execute(_workflow(), replayed, {"variables": {}, "replay": replay})
+
+        assert adapter.calls == 1
+        assert result["output"] == {"reply": "echo:你好"}
+        assert [step["replayed"] for step in result["trace"]] == [False, True, False]
+
+        # live_nodes 中的节点真实执行
+        live = ReplayPlan("exec-1", original["trace"], live_nodes=["call"])
+        result = await engine.execute(_workflow(), replayed, {"variables": {}, "replay": live})
+        assert adapter.calls == 2
+        assert result["output"] == {"reply": "echo:换一句"}
+
+    def test_replay_without_recorded_output(self):
+        """原执行中失败或未执行的副作用节点无法回放"""
+        trace = ExecutionTrace()
+        step = trace.start_step({"id": "call", "type": "adapter"}, {"variables": {}})
+        trace.finish_step(step, {"variables": {}}, error=RuntimeError("timeout"))
+
+        replay = ReplayPlan("exec-1", trace.to_list())
+        assert replay.should_replay({"id": "call", "type": "adapter"})
+        assert not replay.should_replay({"id": "check", "type": "condition"})
+        with pytest.raises(ReplayError):
+            replay.output_for("call", {"variables": {}})
+
+        log = [{"type": "debug_session", "events": []}, {"type": "trace", "steps": trace.to_list()}]
+        assert find_trace(log)[0]["error"] == "timeout"
+        assert find_trace([]) is None
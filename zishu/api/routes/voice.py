@@ -11,6 +11,7 @@ from datetime import datetime
 
 from fastapi import APIRouter, WebSocket, WebSocketDisconnect, Depends, HTTPException
 from fastapi.responses import StreamingResponse
+from pydantic import BaseModel, Field
 import httpx
 
 from ..services.voice_service import get_stt_service, get_tts_service, STTService, TTSService
@@ -96,6 +97,46 @@ class VoiceSession:
 active_sessions: Dict[str, VoiceSession] = {}
 
 
+class TranscribeRequest(BaseModel):
+    """一次性语音识别请求"""
+
+    audio: str = Field(..., description="Base64 编码的 WAV 音频")
+    language: Optional[str] = Field(None, description="语言代码（如 'zh', 'en'），为空时使用服务默认语言")
+
+
+class TranscribeResponse(BaseModel):
+    """一次性语音识别结果"""
+
+    text: str
+    language: Optional[str] = None
+
+
+@router.post("/transcribe", response_model=TranscribeResponse)
+async def transcribe_audio(request: TranscribeRequest):
+    """
+    识别一段完整的录音
+
+    供桌面端的语音活动检测与按键说话使用：客户端切好一句话后整段上传。
+    """
+    try:
+        audio_data = base64.b64decode(request.audio, validate=True)
+    except (ValueError, TypeError):
+        raise HTTPException(status_code=400, detail="音频不是有效的 Base64 数据")
+    if not audio_data:
+        raise HTTPException(status_code=400, detail="音频为空")
+
+    try:
+        stt = await get_stt_service()
+        result = await stt.transcribe(audio_data, language=request.language)
+    except RuntimeError as e:
+        raise HTTPException(status_code=503, detail=str(e))
+    except Exception as e:
+        logger.error(f"语音识别失败: {e}", exc_info=True)
+        raise HTTPException(status_code=500, detail="语音识别失败")
+
+    return TranscribeResponse(text=result.get("text", ""), language=result.get("language"))
+
+
 @router.websocket("/ws/{session_id}")
 async def voice_websocket(
     websocket: WebSocket,
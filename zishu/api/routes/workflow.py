@@ -19,6 +19,7 @@ from ...models.workflow import (
     ExecutionMode,
 )
 from ..services.workflow_service import workflow_service
+from ...workflow.debugger import DebugSessionError
 from ..dependencies import get_current_user
 from sqlalchemy.ext.asyncio import AsyncSession
 
@@ -45,6 +46,71 @@ class ExecuteWorkflowRequest(BaseModel):
     execution_mode: ExecutionMode = Field(
         default=ExecutionMode.MANUAL, description="执行模式"
     )
+    debug: bool = Field(default=False, description="调试模式（在断点节点前暂停）")
+    breakpoints: List[str] = Field(default_factory=list, description="断点节点ID列表")
+
+
+class DebugFrameResponse(BaseModel):
+    """调试帧响应"""
+
+    execution_id: str
+    state: str = Field(..., description="running / paused / finished")
+    breakpoints: List[str]
+    node_id: Optional[str] = None
+    node_type: Optional[str] = None
+    variables: Optional[Dict[str, Any]] = None
+    input: Optional[Dict[str, Any]] = None
+    node_results: Optional[Dict[str, Any]] = None
+
+
+class SetBreakpointsRequest(BaseModel):
+    """设置断点请求"""
+
+    breakpoints: List[str] = Field(default_factory=list, description="断点节点ID列表")
+
+
+class UpdateDebugVariablesRequest(BaseModel):
+    """修改调试变量请求"""
+
+    variables: Dict[str, Any] = Field(default_factory=dict, description="要设置的变量")
+    remove: List[str] = Field(default_factory=list, description="要删除的变量名")
+
+
+class TraceStepResponse(BaseModel):
+    """执行追踪中的一个节点"""
+
+    index: int
+    node_id: str
+    node_type: Optional[str] = None
+    status: str = Field(..., description="running / success / failed")
+    inputs: Dict[str, Any] = Field(default_factory=dict, description="执行前的配置、输入与变量")
+    output: Optional[Any] = None
+    error: Optional[str] = None
+    variables_after: Optional[Dict[str, Any]] = None
+    replayed: bool = Field(default=False, description="是否使用了原执行记录的输出")
+    started_at: str
+    completed_at: Optional[str] = None
+    duration_ms: Optional[int] = None
+
+
+class ExecutionTraceResponse(BaseModel):
+    """执行追踪响应"""
+
+    execution_id: str
+    workflow_id: str
+    execution_status: ExecutionStatus
+    failed_node_id: Optional[str] = None
+    replay_of: Optional[str] = Field(default=None, description="回放执行对应的原执行ID")
+    steps: List[TraceStepResponse]
+
+
+class ReplayExecutionRequest(BaseModel):
+    """回放执行请求"""
+
+    input_data: Optional[Dict[str, Any]] = Field(default=None, description="输入数据（默认使用原执行的输入）")
+    live_nodes: List[str] = Field(default_factory=list, description="仍真实执行的节点ID列表")
+    breakpoints: List[str] = Field(default_factory=list, description="断点节点ID列表")
+    step: bool = Field(default=False, description="单步模式（在每个节点前暂停）")
 
 
 class CloneWorkflowRequest(BaseModel):
@@ -383,6 +449,8 @@ async def execute_workflow(
             current_user["id"],
             request.input_data,
             request.execution_mode,
+            debug=request.debug,
+            breakpoints=request.breakpoints,
         )
         return execution
     except ValueError as e:
@@ -475,6 +543,182 @@ async def cancel_execution(
         raise HTTPException(status_code=status.HTTP_403_FORBIDDEN, detail=str(e))
 
 
+@router.get(
+    "/executions/{execution_id}/trace",
+    response_model=ExecutionTraceResponse,
+    summary="获取执行追踪",
+)
+async def get_execution_trace(
+    execution_id: str,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """
+    获取执行的节点追踪
+
+    - 按执行顺序列出每个节点的输入、输出（或错误）与耗时
+    """
+    try:
+        return await workflow_service.get_execution_trace(
+            session, execution_id, current_user["id"]
+        )
+    except (ValueError, LookupError) as e:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail=str(e))
+    except PermissionError as e:
+        raise HTTPException(status_code=status.HTTP_403_FORBIDDEN, detail=str(e))
+
+
+@router.post(
+    "/executions/{execution_id}/replay",
+    response_model=WorkflowExecutionResponse,
+    status_code=status.HTTP_201_CREATED,
+    summary="回放执行",
+)
+async def replay_execution(
+    execution_id: str,
+    request: ReplayExecutionRequest,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """
+    回放执行
+
+    - 适配器、HTTP、脚本、延迟节点使用原执行记录的输出，不产生外部副作用
+    - live_nodes 中的节点仍真实执行
+    - step 为 true 时在每个节点前暂停，通过调试接口单步执行
+    """
+    try:
+        return await workflow_service.replay_execution(
+            session,
+            execution_id,
+            current_user["id"],
+            input_data=request.input_data,
+            live_nodes=request.live_nodes,
+            breakpoints=request.breakpoints,
+            step=request.step,
+        )
+    except LookupError as e:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail=str(e))
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_400_BAD_REQUEST, detail=str(e))
+    except PermissionError as e:
+        raise HTTPException(status_code=status.HTTP_403_FORBIDDEN, detail=str(e))
+
+
+# ================================
+# 工作流调试
+# ================================
+
+
+async def _get_debugger(session: AsyncSession, execution_id: str, user_id: str):
+    """获取调试会话，并把服务层异常转换为 HTTP 错误"""
+    try:
+        return await workflow_service.get_debug_session(session, execution_id, user_id)
+    except ValueError as e:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail=str(e))
+    except PermissionError as e:
+        raise HTTPException(status_code=status.HTTP_403_FORBIDDEN, detail=str(e))
+
+
+@router.get(
+    "/executions/{execution_id}/debug",
+    response_model=DebugFrameResponse,
+    summary="获取调试帧",
+)
+async def get_debug_frame(
+    execution_id: str,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """
+    获取调试中执行的当前帧
+
+    - 暂停时包含当前节点、变量作用域、输入与已执行节点结果
+    """
+    debugger = await _get_debugger(session, execution_id, current_user["id"])
+    return debugger.frame()
+
+
+@router.put(
+    "/executions/{execution_id}/debug/breakpoints",
+    response_model=DebugFrameResponse,
+    summary="设置断点",
+)
+async def set_debug_breakpoints(
+    execution_id: str,
+    request: SetBreakpointsRequest,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """替换调试中执行的断点集合"""
+    debugger = await _get_debugger(session, execution_id, current_user["id"])
+    debugger.set_breakpoints(request.breakpoints)
+    return debugger.frame()
+
+
+@router.patch(
+    "/executions/{execution_id}/debug/variables",
+    response_model=DebugFrameResponse,
+    summary="修改调试变量",
+)
+async def update_debug_variables(
+    execution_id: str,
+    request: UpdateDebugVariablesRequest,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """
+    修改暂停中执行的变量
+
+    - 只能在暂停时修改
+    - 修改会记录到执行日志
+    """
+    debugger = await _get_debugger(session, execution_id, current_user["id"])
+    try:
+        debugger.update_variables(request.variables, request.remove)
+    except DebugSessionError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e))
+    return debugger.frame()
+
+
+@router.post(
+    "/executions/{execution_id}/debug/continue",
+    response_model=DebugFrameResponse,
+    summary="继续执行",
+)
+async def continue_debug_execution(
+    execution_id: str,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """继续执行到下一个断点"""
+    debugger = await _get_debugger(session, execution_id, current_user["id"])
+    try:
+        debugger.resume(step=False)
+    except DebugSessionError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e))
+    return debugger.frame()
+
+
+@router.post(
+    "/executions/{execution_id}/debug/step",
+    response_model=DebugFrameResponse,
+    summary="单步执行",
+)
+async def step_debug_execution(
+    execution_id: str,
+    current_user: dict = Depends(get_current_user),
+    session: AsyncSession = Depends(get_session),
+):
+    """执行当前节点，并在下一个节点前再次暂停"""
+    debugger = await _get_debugger(session, execution_id, current_user["id"])
+    try:
+        debugger.resume(step=True)
+    except DebugSessionError as e:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(e))
+    return debugger.frame()
+
+
 # ================================
 # 工作流模板
 # ================================
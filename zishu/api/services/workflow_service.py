@@ -33,6 +33,13 @@ from ...database.repositories.workflow_repository import (
     WorkflowRepository,
     WorkflowExecutionRepository,
 )
+from ...workflow.debugger import (
+    WorkflowDebugSession,
+    create_debug_session,
+    get_debug_session,
+    remove_debug_session,
+)
+from ...workflow.trace import ReplayPlan, find_trace, find_replay_of
 
 logger = logging.getLogger(__name__)
 
@@ -276,8 +283,17 @@ class WorkflowService:
         user_id: str,
         input_data: Optional[Dict[str, Any]] = None,
         execution_mode: ExecutionMode = ExecutionMode.MANUAL,
+        debug: bool = False,
+        breakpoints: Optional[List[str]] = None,
+        replay: Optional[ReplayPlan] = None,
+        step: bool = False,
     ) -> WorkflowExecution:
-        """执行工作流"""
+        """
+        执行工作流
+
+        debug 为 True 时在断点节点前暂停，step 为 True 时在每个节点前暂停；
+        replay 不为空时按回放计划使用原执行记录的节点输出。
+        """
         workflow = await self.get_workflow_with_details(session, workflow_id)
         if not workflow:
             raise ValueError(f"工作流不存在: {workflow_id}")
@@ -293,6 +309,7 @@ class WorkflowService:
             execution_mode=execution_mode,
             execution_status=ExecutionStatus.PENDING,
             input_data=input_data or {},
+            execution_log=[replay.describe()] if replay is not None else None,
             started_at=datetime.now(timezone.utc),
         )
 
@@ -304,9 +321,15 @@ class WorkflowService:
         workflow.execution_count += 1
         workflow.last_executed_at = execution.started_at
 
+        # 调试会话需在后台任务开始前注册，保证第一个节点就能命中断点
+        if debug or step:
+            create_debug_session(execution.id, user_id, breakpoints, step=step)
+
         # 异步执行工作流（在后台任务中）
         task = asyncio.create_task(
-            self._execute_workflow_async_task(workflow_id=workflow.id, execution_id=execution.id)
+            self._execute_workflow_async_task(
+                workflow_id=workflow.id, execution_id=execution.id, replay=replay
+            )
         )
 
         def _log_task_exception(t: asyncio.Task) -> None:
@@ -325,7 +348,9 @@ class WorkflowService:
         task.add_done_callback(_log_task_exception)
         return execution
 
-    async def _execute_workflow_async_task(self, workflow_id: str, execution_id: str) -> None:
+    async def _execute_workflow_async_task(
+        self, workflow_id: str, execution_id: str, replay: Optional[ReplayPlan] = None
+    ) -> None:
         """
         异步执行工作流的任务入口。
 
@@ -343,17 +368,20 @@ class WorkflowService:
                 logger.error(
                     f"Workflow async execution missing records: workflow_id={workflow_id}, execution_id={execution_id}"
                 )
+                remove_debug_session(execution_id)
                 return
 
-            await self._execute_workflow_async(session, workflow, execution)
+            await self._execute_workflow_async(session, workflow, execution, replay)
 
     async def _execute_workflow_async(
         self,
         session: AsyncSession,
         workflow: Workflow,
         execution: WorkflowExecution,
+        replay: Optional[ReplayPlan] = None,
     ) -> None:
         """异步执行工作流的实际逻辑"""
+        debugger = get_debug_session(execution.id)
         try:
             execution.execution_status = ExecutionStatus.RUNNING
             await session.commit()
@@ -386,6 +414,8 @@ class WorkflowService:
                 "user_id": execution.user_id,
                 "adapter_start_policy": "auto",
                 "interpolation_mode": "strict",
+                "debugger": debugger,
+                "replay": replay,
             }
 
             result = await workflow_engine.execute(workflow, execution, context)
@@ -398,6 +428,10 @@ class WorkflowService:
             )
             execution.output_data = result.get("output", {})
             execution.node_results = result.get("node_results", {})
+            # 节点追踪写入执行日志，供查看与回放
+            execution.execution_log = list(execution.execution_log or []) + [
+                {"type": "trace", "steps": result.get("trace", [])}
+            ]
             if result.get("status") == "success":
                 execution.execution_status = ExecutionStatus.COMPLETED
                 workflow.success_count += 1
@@ -405,6 +439,8 @@ class WorkflowService:
             else:
                 execution.execution_status = ExecutionStatus.FAILED
                 execution.error_message = result.get("error") or "Workflow execution failed"
+                execution.error_code = result.get("error_code")
+                execution.failed_node_id = result.get("failed_node_id")
                 workflow.failure_count += 1
                 workflow.last_execution_status = "failed"
 
@@ -422,6 +458,16 @@ class WorkflowService:
             workflow.last_execution_status = "failed"
 
         finally:
+            if debugger is not None:
+                # 调试过程写入执行日志
+                remove_debug_session(execution.id)
+                execution.execution_log = list(execution.execution_log or []) + [
+                    {
+                        "type": "debug_session",
+                        "breakpoints": sorted(debugger.breakpoints),
+                        "events": debugger.finish(),
+                    }
+                ]
             await session.commit()
 
     async def get_execution(
@@ -475,6 +521,9 @@ class WorkflowService:
             raise ValueError(f"无法取消状态为 {execution.execution_status} 的执行")
 
         execution.execution_status = ExecutionStatus.CANCELLED
+        debugger = get_debug_session(execution_id)
+        if debugger is not None:
+            debugger.abort()
         execution.completed_at = datetime.now(timezone.utc)
         if execution.started_at:
             execution.duration_ms = int(
@@ -487,6 +536,88 @@ class WorkflowService:
 
         return execution
 
+    # ================================
+    # 工作流调试
+    # ================================
+
+    async def _get_own_execution(
+        self, session: AsyncSession, execution_id: str, user_id: str
+    ) -> WorkflowExecution:
+        execution = await self.get_execution(session, execution_id)
+        if not execution:
+            raise ValueError(f"执行记录不存在: {execution_id}")
+
+        if execution.user_id != user_id:
+            raise PermissionError("无权限访问此执行记录")
+        return execution
+
+    async def get_execution_trace(
+        self, session: AsyncSession, execution_id: str, user_id: str
+    ) -> Dict[str, Any]:
+        """获取执行的节点追踪（每个节点的输入、输出与耗时）"""
+        execution = await self._get_own_execution(session, execution_id, user_id)
+        steps = find_trace(execution.execution_log)
+        if steps is None:
+            raise LookupError("该执行没有节点追踪记录")
+
+        return {
+            "execution_id": execution.id,
+            "workflow_id": execution.workflow_id,
+            "execution_status": execution.execution_status,
+            "failed_node_id": execution.failed_node_id,
+            "replay_of": find_replay_of(execution.execution_log),
+            "steps": steps,
+        }
+
+    async def replay_execution(
+        self,
+        session: AsyncSession,
+        execution_id: str,
+        user_id: str,
+        input_data: Optional[Dict[str, Any]] = None,
+        live_nodes: Optional[List[str]] = None,
+        breakpoints: Optional[List[str]] = None,
+        step: bool = False,
+    ) -> WorkflowExecution:
+        """
+        回放执行
+
+        按工作流当前定义重新执行，有副作用的节点使用原执行记录的输出（live_nodes 除外），
+        不再调用外部服务。未指定输入时使用原执行的输入。
+        """
+        original = await self._get_own_execution(session, execution_id, user_id)
+        steps = find_trace(original.execution_log)
+        if steps is None:
+            raise LookupError("该执行没有节点追踪记录，无法回放")
+
+        return await self.execute_workflow(
+            session,
+            original.workflow_id,
+            user_id,
+            input_data if input_data is not None else original.input_data,
+            ExecutionMode.MANUAL,
+            debug=bool(breakpoints),
+            breakpoints=breakpoints,
+            replay=ReplayPlan(original.id, steps, live_nodes),
+            step=step,
+        )
+
+    async def get_debug_session(
+        self, session: AsyncSession, execution_id: str, user_id: str
+    ) -> WorkflowDebugSession:
+        """获取执行的调试会话（校验执行归属）"""
+        execution = await self.get_execution(session, execution_id)
+        if not execution:
+            raise ValueError(f"执行记录不存在: {execution_id}")
+
+        if execution.user_id != user_id:
+            raise PermissionError("无权限调试此执行")
+
+        debugger = get_debug_session(execution_id)
+        if debugger is None:
+            raise ValueError("该执行未处于调试模式或已结束")
+        return debugger
+
     # ================================
     # 工作流模板
     # ================================
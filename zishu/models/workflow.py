@@ -713,9 +713,29 @@ class WorkflowExecutionResponse(BaseModel):
     started_at: Optional[datetime]
     completed_at: Optional[datetime]
     duration_ms: Optional[int]
+    error_code: Optional[str] = None
     error_message: Optional[str]
+    failed_node_id: Optional[str] = None
     created_at: datetime
 
     class Config:
         from_attributes = True
 
+
+class AdapterNode(BaseModel):
+    """适配器节点配置（节点定义中的 `config`）
+
+    `input_mapping` 的值可以是 `${input.xxx}` / `${variables.xxx}` 占位符，
+    `output_mapping` 把适配器输出中的路径（如 `result.text`，空字符串为整个输出）写入工作流变量。
+    """
+
+    adapter_id: str = Field(..., min_length=1)
+    input_mapping: Dict[str, Any] = Field(default_factory=dict)
+    # 旧版节点的参数写法，与 input_mapping 合并（input_mapping 优先）
+    parameters: Dict[str, Any] = Field(default_factory=dict)
+    output_mapping: Dict[str, str] = Field(default_factory=dict)
+    output_variable: Optional[str] = None
+    timeout_seconds: Optional[float] = Field(None, gt=0, le=3600)
+    max_retries: int = Field(0, ge=0, le=5)
+    retry_delay_seconds: float = Field(1.0, ge=0, le=60)
+
@@ -6,9 +6,14 @@
 from .engine import WorkflowEngine
 from .executor import NodeExecutor
 from .scheduler import WorkflowScheduler
+from .debugger import WorkflowDebugSession
+from .trace import ExecutionTrace, ReplayPlan
 
 __all__ = [
     "WorkflowEngine",
     "NodeExecutor",
     "WorkflowScheduler",
+    "WorkflowDebugSession",
+    "ExecutionTrace",
+    "ReplayPlan",
 ]
@@ -0,0 +1,213 @@
+"""
+工作流调试器
+提供断点、单步执行与变量编辑功能
+"""
+
+import asyncio
+import copy
+from typing import Dict, Any, Optional, List, Iterable
+from datetime import datetime, timezone
+import logging
+
+logger = logging.getLogger(__name__)
+
+# 暂停等待的默认超时（秒），超时后终止执行，避免后台任务无限挂起
+DEFAULT_PAUSE_TIMEOUT = 30 * 60
+
+
+class DebugSessionError(Exception):
+    """调试会话操作错误（如未暂停时编辑变量）"""
+
+
+class WorkflowDebugSession:
+    """
+    单次执行的调试会话
+
+    功能：
+    1. 在断点节点执行前暂停，暴露当前变量作用域
+    2. 暂停时允许编辑变量，再继续或单步执行
+    3. 记录调试过程（暂停、继续、单步、变量修改），写入执行日志
+    """
+
+    def __init__(
+        self,
+        execution_id: str,
+        user_id: Optional[str] = None,
+        breakpoints: Optional[Iterable[str]] = None,
+        pause_timeout: float = DEFAULT_PAUSE_TIMEOUT,
+        step: bool = False,
+    ):
+        self.execution_id = execution_id
+        self.user_id = user_id
+        self.breakpoints = set(breakpoints or [])
+        self.pause_timeout = pause_timeout
+        self.state = "running"  # running / paused / finished
+        self.trace: List[Dict[str, Any]] = []
+        # 单步模式从第一个节点前开始暂停
+        self._stepping = step
+        self._aborted = False
+        self._resume_event = asyncio.Event()
+        self._node: Optional[Dict[str, Any]] = None
+        self._context: Optional[Dict[str, Any]] = None
+        self._results: Optional[Dict[str, Any]] = None
+
+    def _record(self, event: str, **data: Any) -> None:
+        entry = {
+            "event": event,
+            "timestamp": datetime.now(timezone.utc).isoformat(),
+        }
+        entry.update(data)
+        self.trace.append(entry)
+
+    def should_pause(self, node_id: str) -> bool:
+        """节点执行前是否需要暂停"""
+        return self._stepping or node_id in self.breakpoints
+
+    async def before_node(
+        self,
+        node: Dict[str, Any],
+        context: Dict[str, Any],
+        results: Dict[str, Any],
+    ) -> None:
+        """
+        节点执行前的调试钩子
+
+        命中断点或处于单步模式时暂停，直到调用 resume() 或 abort()。
+        """
+        if self._aborted:
+            raise RuntimeError("调试会话已终止")
+
+        node_id = node["id"]
+        if not self.should_pause(node_id):
+            return
+
+        self._node = node
+        self._context = context
+        self._results = results
+        self.state = "paused"
+        self._resume_event.clear()
+        self._record(
+            "paused",
+            node_id=node_id,
+            reason="step" if self._stepping and node_id not in self.breakpoints else "breakpoint",
+        )
+        logger.info(f"调试暂停: 执行 {self.execution_id} 节点 {node_id}")
+
+        try:
+            await asyncio.wait_for(self._resume_event.wait(), timeout=self.pause_timeout)
+        except asyncio.TimeoutError:
+            self._aborted = True
+            self._record("timeout", node_id=node_id)
+            raise TimeoutError(f"调试暂停超时（{self.pause_timeout} 秒），执行已终止")
+        finally:
+            self.state = "finished" if self._aborted else "running"
+            self._node = None
+            self._context = None
+            self._results = None
+
+        if self._aborted:
+            raise RuntimeError("调试会话已终止")
+
+    def frame(self) -> Dict[str, Any]:
+        """当前调试帧（未暂停时只包含状态与断点）"""
+        frame: Dict[str, Any] = {
+            "execution_id": self.execution_id,
+            "state": self.state,
+            "breakpoints": sorted(self.breakpoints),
+            "node_id": None,
+            "node_type": None,
+            "variables": None,
+            "input": None,
+            "node_results": None,
+        }
+        if self.state == "paused" and self._node is not None and self._context is not None:
+            frame.update(
+                {
+                    "node_id": self._node["id"],
+                    "node_type": self._node.get("type"),
+                    "variables": copy.deepcopy(self._context.get("variables", {})),
+                    "input": copy.deepcopy(self._context.get("input", {})),
+                    "node_results": copy.deepcopy(self._results or {}),
+                }
+            )
+        return frame
+
+    def set_breakpoints(self, node_ids: Iterable[str]) -> List[str]:
+        """替换断点集合"""
+        self.breakpoints = set(node_ids)
+        self._record("breakpoints_changed", breakpoints=sorted(self.breakpoints))
+        return sorted(self.breakpoints)
+
+    def update_variables(
+        self, updates: Dict[str, Any], remove: Optional[Iterable[str]] = None
+    ) -> Dict[str, Any]:
+        """暂停时修改变量，返回修改后的变量作用域"""
+        if self.state != "paused" or self._context is None:
+            raise DebugSessionError("执行未暂停，无法修改变量")
+
+        variables = self._context.setdefault("variables", {})
+        changes = []
+        for key, value in updates.items():
+            changes.append({"name": key, "old": variables.get(key), "new": value})
+            variables[key] = value
+        for key in remove or []:
+            if key in variables:
+                changes.append({"name": key, "old": variables.pop(key), "new": None})
+
+        if changes:
+            self._record(
+                "variables_edited",
+                node_id=self._node["id"] if self._node else None,
+                changes=copy.deepcopy(changes),
+            )
+        return copy.deepcopy(variables)
+
+    def resume(self, step: bool = False) -> None:
+        """继续执行；step 为 True 时在下一个节点前再次暂停"""
+        if self.state != "paused":
+            raise DebugSessionError("执行未暂停")
+        self._stepping = step
+        self._record("stepped" if step else "resumed", node_id=self._node["id"] if self._node else None)
+        self.state = "running"
+        self._resume_event.set()
+
+    def abort(self) -> None:
+        """终止调试（取消执行时调用），唤醒暂停中的执行"""
+        if self.state == "finished":
+            return
+        self._aborted = True
+        self.state = "finished"
+        self._record("aborted")
+        self._resume_event.set()
+
+    def finish(self) -> List[Dict[str, Any]]:
+        """结束调试会话，返回调试记录"""
+        self.state = "finished"
+        self._record("finished")
+        return self.trace
+
+
+# 执行 ID -> 调试会话
+_debug_sessions: Dict[str, WorkflowDebugSession] = {}
+
+
+def create_debug_session(
+    execution_id: str,
+    user_id: Optional[str] = None,
+    breakpoints: Optional[Iterable[str]] = None,
+    step: bool = False,
+) -> WorkflowDebugSession:
+    """创建并注册调试会话（step 为 True 时在每个节点前暂停）"""
+    session = WorkflowDebugSession(execution_id, user_id, breakpoints, step=step)
+    _debug_sessions[execution_id] = session
+    return session
+
+
+def get_debug_session(execution_id: str) -> Optional[WorkflowDebugSession]:
+    """获取调试会话"""
+    return _debug_sessions.get(execution_id)
+
+
+def remove_debug_session(execution_id: str) -> Optional[WorkflowDebugSession]:
+    """移除调试会话"""
+    return _debug_sessions.pop(execution_id, None)
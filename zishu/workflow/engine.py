@@ -16,6 +16,9 @@ from ..models.workflow import (
     ExecutionStatus,
 )
 
+from .executor import NodeExecutionError
+from .trace import ExecutionTrace
+
 logger = logging.getLogger(__name__)
 
 
@@ -69,6 +72,8 @@ class WorkflowEngine:
         """
         logger.info(f"开始执行工作流: {workflow.name} (ID: {workflow.id})")
 
+        node_results = {}
+        trace = ExecutionTrace()
         try:
             # 解析工作流定义
             nodes = self._parse_nodes(workflow.definition)
@@ -83,7 +88,6 @@ class WorkflowEngine:
                 raise ValueError("工作流缺少开始节点")
 
             # 初始化执行状态
-            node_results = {}
             execution_context = {
                 "input": execution.input_data or {},
                 "variables": context.get("variables", {}),
@@ -95,6 +99,10 @@ class WorkflowEngine:
                 "workflow_id": workflow.id,
                 "execution_id": execution.id,
                 "all_nodes": nodes,  # 添加所有节点引用
+                "debugger": context.get("debugger"),
+                "trace": trace,
+                # 回放模式：有副作用的节点使用原执行记录的输出
+                "replay": context.get("replay"),
             }
 
             # 从开始节点执行
@@ -111,15 +119,23 @@ class WorkflowEngine:
                 "status": "success",
                 "output": execution_context.get("output", {}),
                 "node_results": node_results,
+                "trace": trace.to_list(),
             }
 
         except Exception as e:
             logger.error(f"工作流执行失败: {str(e)}", exc_info=True)
-            return {
+            failure = {
                 "status": "failed",
                 "error": str(e),
-                "node_results": {},
+                "node_results": node_results,
+                "trace": trace.to_list(),
             }
+            # 节点的结构化错误（错误代码、失败节点、重试次数等）
+            if isinstance(e, NodeExecutionError):
+                failure["error_code"] = e.code
+                failure["failed_node_id"] = e.node_id
+                failure["error_details"] = e.to_dict()
+            return failure
 
     async def _execute_node(
         self,
@@ -151,11 +167,32 @@ class WorkflowEngine:
             if not executor:
                 raise ValueError(f"不支持的节点类型: {node_type}")
 
-            # 执行节点
-            result = await executor.execute(node, context, results)
+            # 调试模式：命中断点或单步时在节点执行前暂停
+            debugger = context.get("debugger")
+            if debugger is not None:
+                await debugger.before_node(node, context, results)
+
+            # 执行节点（回放时有副作用的节点直接取记录的输出）
+            trace = context.get("trace")
+            replay = context.get("replay")
+            replayed = replay is not None and replay.should_replay(node)
+            step = trace.start_step(node, context) if trace is not None else None
+            try:
+                if replayed:
+                    result = replay.output_for(node_id, context)
+                else:
+                    result = await executor.execute(node, context, results)
+            except Exception as e:
+                if step is not None:
+                    trace.finish_step(step, context, error=e, replayed=replayed)
+                raise
+            if step is not None:
+                trace.finish_step(step, context, output=result, replayed=replayed)
+
             results[node_id] = {
                 "status": "success",
                 "output": result,
+                "replayed": replayed,
                 "timestamp": datetime.now(timezone.utc).isoformat(),
             }
 
@@ -180,11 +217,18 @@ class WorkflowEngine:
 
         except Exception as e:
             logger.error(f"节点执行失败: {node_id} - {str(e)}")
+            # 后续节点的失败已记录，不覆盖
+            if node_id in results and results[node_id].get("status") == "success":
+                raise
+            if isinstance(e, NodeExecutionError) and e.node_id is None:
+                e.node_id = node_id
             results[node_id] = {
                 "status": "failed",
                 "error": str(e),
                 "timestamp": datetime.now(timezone.utc).isoformat(),
             }
+            if isinstance(e, NodeExecutionError):
+                results[node_id]["error_details"] = e.to_dict()
             raise
 
     def _parse_nodes(self, definition: Dict[str, Any]) -> List[Dict[str, Any]]:
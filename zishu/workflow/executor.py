@@ -9,7 +9,10 @@ from typing import Dict, Any, Optional, Union
 import logging
 import re
 
+from pydantic import ValidationError
+
 from zishu.adapters.base.adapter import ExecutionContext
+from zishu.models.workflow import AdapterNode
 
 logger = logging.getLogger(__name__)
 
@@ -82,6 +85,53 @@ def resolve_parameters(obj: Any, context: Dict[str, Any], mode: str = "strict")
         return obj
 
 
+def get_path(value: Any, path: str) -> Any:
+    """按点分路径读取嵌套的 dict / list 值，空路径返回整个值；路径不存在时抛出 KeyError"""
+    current = value
+    for key in [part for part in path.split(".") if part]:
+        if isinstance(current, dict) and key in current:
+            current = current[key]
+        elif isinstance(current, list) and key.isdigit() and int(key) < len(current):
+            current = current[int(key)]
+        else:
+            raise KeyError(path)
+    return current
+
+
+class NodeExecutionError(Exception):
+    """节点执行失败，携带可写入执行记录的结构化信息"""
+
+    def __init__(
+        self,
+        code: str,
+        message: str,
+        node_id: Optional[str] = None,
+        adapter_id: Optional[str] = None,
+        attempts: int = 1,
+        retryable: bool = False,
+        details: Optional[Dict[str, Any]] = None,
+    ):
+        super().__init__(message)
+        self.code = code
+        self.message = message
+        self.node_id = node_id
+        self.adapter_id = adapter_id
+        self.attempts = attempts
+        self.retryable = retryable
+        self.details = details
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "code": self.code,
+            "message": self.message,
+            "node_id": self.node_id,
+            "adapter_id": self.adapter_id,
+            "attempts": self.attempts,
+            "retryable": self.retryable,
+            "details": self.details,
+        }
+
+
 class NodeExecutor(ABC):
     """节点执行器基类"""
 
@@ -154,7 +204,12 @@ class EndNodeExecutor(NodeExecutor):
 
 
 class AdapterNodeExecutor(NodeExecutor):
-    """适配器节点执行器"""
+    """适配器节点执行器
+
+    节点配置见 `zishu.models.workflow.AdapterNode`。执行前检查适配器权限，
+    每次尝试受 `timeout_seconds` 限制，超时或适配器报错时按 `max_retries` 重试；
+    最终失败抛出 `NodeExecutionError`，由引擎写入节点结果与执行记录。
+    """
 
     async def execute(
         self,
@@ -165,15 +220,14 @@ class AdapterNodeExecutor(NodeExecutor):
         """
         执行适配器节点
 
-        调用指定的适配器并传递参数
+        调用指定的适配器并传递映射后的参数
         """
-        config = node.get("config", {})
-        adapter_id = config.get("adapter_id")
-        raw_params = config.get("parameters", {})
-        output_variable = config.get("output_variable")
-
-        if not adapter_id:
-            raise ValueError("adapter_id is required in adapter node config")
+        node_id = node.get("id")
+        try:
+            config = AdapterNode.model_validate(node.get("config", {}))
+        except ValidationError as e:
+            raise NodeExecutionError("invalid_config", f"适配器节点配置无效: {e}", node_id=node_id) from e
+        adapter_id = config.adapter_id
 
         logger.info(f"执行适配器: {adapter_id}")
 
@@ -186,16 +240,60 @@ class AdapterNodeExecutor(NodeExecutor):
         if not user_id:
             raise ValueError("user_id is required in context for adapter execution")
 
-        # 获取插值和启动策略
-        interpolation_mode = context.get("interpolation_mode", "strict")
-        adapter_start_policy = context.get("adapter_start_policy", "auto")
-
         # 解析参数中的占位符
+        interpolation_mode = context.get("interpolation_mode", "strict")
         try:
-            adapter_params = resolve_parameters(raw_params, context, interpolation_mode)
+            adapter_params = resolve_parameters(
+                {**config.parameters, **config.input_mapping}, context, interpolation_mode
+            )
         except ValueError as e:
             logger.error(f"Parameter interpolation failed: {e}")
-            raise
+            raise NodeExecutionError(
+                "input_mapping_failed", str(e), node_id=node_id, adapter_id=adapter_id
+            ) from e
+
+        await self._ensure_running(adapter_manager, adapter_id, context, node_id)
+
+        # 权限检查（未启用安全管理器时 AdapterManager 默认允许）
+        session_id = context.get("session_id") or user_id
+        if not await adapter_manager.check_permission(session_id, "adapter", "execute", adapter_id):
+            raise NodeExecutionError(
+                "permission_denied",
+                f"没有执行适配器 {adapter_id} 的权限",
+                node_id=node_id,
+                adapter_id=adapter_id,
+            )
+
+        adapter_output = await self._execute_with_retries(
+            adapter_manager, config, adapter_params, context, node_id
+        )
+
+        # 将结果保存到上下文变量
+        variables = context.setdefault("variables", {})
+        if config.output_variable:
+            variables[config.output_variable] = adapter_output
+        for variable, path in config.output_mapping.items():
+            try:
+                variables[variable] = get_path(adapter_output, path)
+            except KeyError as e:
+                raise NodeExecutionError(
+                    "output_mapping_failed",
+                    f"适配器输出中没有 {path}",
+                    node_id=node_id,
+                    adapter_id=adapter_id,
+                ) from e
+
+        return adapter_output
+
+    async def _ensure_running(
+        self,
+        adapter_manager: Any,
+        adapter_id: str,
+        context: Dict[str, Any],
+        node_id: Optional[str],
+    ) -> None:
+        """检查适配器已注册并运行，根据启动策略决定是否启动"""
+        adapter_start_policy = context.get("adapter_start_policy", "auto")
 
         # 检查适配器是否已注册（使用公开 API）
         if not adapter_manager.is_running:
@@ -203,92 +301,145 @@ class AdapterNodeExecutor(NodeExecutor):
 
         registration = await adapter_manager.get_adapter(adapter_id)
         if not registration:
-            raise ValueError(f"Adapter {adapter_id} is not registered")
+            raise NodeExecutionError(
+                "adapter_not_found",
+                f"Adapter {adapter_id} is not registered",
+                node_id=node_id,
+                adapter_id=adapter_id,
+            )
 
-        # 检查适配器是否运行，根据策略决定是否启动
         # 注意：adapter_manager._adapters 是内部字段，属于实现细节
-        if adapter_id not in adapter_manager._adapters:
-            if adapter_start_policy == "auto":
-                logger.info(f"Starting adapter {adapter_id} (auto policy)")
-                success = await adapter_manager.start_adapter(adapter_id)
-                if not success:
-                    diag = None
-                    adapter_instance = None
-                    try:
-                        adapter_config = getattr(registration, "configuration", None)
-                        adapter_class = getattr(adapter_config, "adapter_class", None) if adapter_config else None
-                        adapter_cfg = getattr(adapter_config, "config", None) if adapter_config else None
-
-                        if not adapter_config:
-                            diag = "missing registration.configuration"
-                        elif not adapter_class:
-                            diag = "missing adapter_class in configuration"
-                        else:
-                            adapter_instance = adapter_class(adapter_cfg or {})
-                            if hasattr(adapter_instance, "initialize"):
-                                await adapter_instance.initialize()
-                            if hasattr(adapter_instance, "start"):
-                                await adapter_instance.start()
-                            diag = "manual start succeeded (AdapterManager returned False)"
-                    except Exception as e:
-                        diag = f"{type(e).__name__}: {e}"
-                    finally:
-                        if adapter_instance is not None:
-                            try:
-                                if hasattr(adapter_instance, "stop"):
-                                    await adapter_instance.stop()
-                                if hasattr(adapter_instance, "cleanup"):
-                                    await adapter_instance.cleanup()
-                            except Exception:
-                                pass
-
-                    raise RuntimeError(
-                        f"Failed to start adapter: {adapter_id}"
-                        + (f" ({diag})" if diag else "")
-                    )
-            elif adapter_start_policy == "strict_running":
-                raise RuntimeError(f"Adapter {adapter_id} is not running and policy is strict_running")
-            else:
-                raise RuntimeError(f"Unknown adapter start policy: {adapter_start_policy}")
-
-        # 创建 ExecutionContext
-        # request_id 使用 workflow execution_id，execution_id 使用组合确保唯一性
-        execution_context = ExecutionContext(
-            request_id=context.get("execution_id"),  # workflow execution_id 作为 request_id
-            user_id=user_id,
-            session_id=context.get("session_id"),
-            execution_id=f"{context.get('execution_id', 'unknown')}:{node.get('id', 'unknown')}",  # 组合保证唯一性
-            metadata={
-                "workflow_id": context.get("workflow_id"),
-                "execution_id": context.get("execution_id"),
-                "node_id": node.get("id"),
-                "adapter_id": adapter_id,
-            }
-        )
+        if adapter_id in adapter_manager._adapters:
+            return
+
+        if adapter_start_policy == "strict_running":
+            raise NodeExecutionError(
+                "adapter_unavailable",
+                f"Adapter {adapter_id} is not running and policy is strict_running",
+                node_id=node_id,
+                adapter_id=adapter_id,
+            )
+        if adapter_start_policy != "auto":
+            raise RuntimeError(f"Unknown adapter start policy: {adapter_start_policy}")
 
-        # 调用适配器 - 直接传递 ExecutionContext 对象
+        logger.info(f"Starting adapter {adapter_id} (auto policy)")
+        if await adapter_manager.start_adapter(adapter_id):
+            return
+
+        diag = None
+        adapter_instance = None
         try:
-            result = await adapter_manager.process_with_adapter(
-                adapter_id,
-                adapter_params,
-                execution_context  # 直接传递对象，不是 __dict__
-            )
+            adapter_config = getattr(registration, "configuration", None)
+            adapter_class = getattr(adapter_config, "adapter_class", None) if adapter_config else None
+            adapter_cfg = getattr(adapter_config, "config", None) if adapter_config else None
+
+            if not adapter_config:
+                diag = "missing registration.configuration"
+            elif not adapter_class:
+                diag = "missing adapter_class in configuration"
+            else:
+                adapter_instance = adapter_class(adapter_cfg or {})
+                if hasattr(adapter_instance, "initialize"):
+                    await adapter_instance.initialize()
+                if hasattr(adapter_instance, "start"):
+                    await adapter_instance.start()
+                diag = "manual start succeeded (AdapterManager returned False)"
         except Exception as e:
-            logger.error(f"Adapter {adapter_id} execution failed: {e}")
-            raise
-
-        # workflow_executions.output_data/node_results 存在于 JSONB 字段，必须可 JSON 序列化。
-        # adapter_manager.process_with_adapter 通常返回 ExecutionResult（包含 output + 元信息），
-        # 这里默认只把 output 写入 workflow 变量与后续节点。
-        adapter_output = getattr(result, "output", result)
+            diag = f"{type(e).__name__}: {e}"
+        finally:
+            if adapter_instance is not None:
+                try:
+                    if hasattr(adapter_instance, "stop"):
+                        await adapter_instance.stop()
+                    if hasattr(adapter_instance, "cleanup"):
+                        await adapter_instance.cleanup()
+                except Exception:
+                    pass
+
+        raise NodeExecutionError(
+            "adapter_unavailable",
+            f"Failed to start adapter: {adapter_id}" + (f" ({diag})" if diag else ""),
+            node_id=node_id,
+            adapter_id=adapter_id,
+        )
 
-        # 将结果保存到上下文变量
-        if output_variable:
-            if "variables" not in context:
-                context["variables"] = {}
-            context["variables"][output_variable] = adapter_output
+    async def _execute_with_retries(
+        self,
+        adapter_manager: Any,
+        config: AdapterNode,
+        adapter_params: Dict[str, Any],
+        context: Dict[str, Any],
+        node_id: Optional[str],
+    ) -> Any:
+        """调用适配器，超时或失败时重试，返回适配器输出"""
+        adapter_id = config.adapter_id
+        attempts = config.max_retries + 1
+        last_error: Optional[NodeExecutionError] = None
+
+        for attempt in range(1, attempts + 1):
+            # request_id 使用 workflow execution_id，execution_id 使用组合确保唯一性
+            execution_context = ExecutionContext(
+                request_id=context.get("execution_id"),
+                user_id=context.get("user_id"),
+                session_id=context.get("session_id"),
+                execution_id=f"{context.get('execution_id', 'unknown')}:{node_id or 'unknown'}:{attempt}",
+                metadata={
+                    "workflow_id": context.get("workflow_id"),
+                    "execution_id": context.get("execution_id"),
+                    "node_id": node_id,
+                    "adapter_id": adapter_id,
+                    "attempt": attempt,
+                },
+            )
 
-        return adapter_output
+            try:
+                result = await asyncio.wait_for(
+                    adapter_manager.process_with_adapter(adapter_id, adapter_params, execution_context),
+                    timeout=config.timeout_seconds,
+                )
+            except asyncio.TimeoutError:
+                last_error = NodeExecutionError(
+                    "timeout",
+                    f"适配器 {adapter_id} 执行超过 {config.timeout_seconds} 秒",
+                    node_id=node_id,
+                    adapter_id=adapter_id,
+                    attempts=attempt,
+                    retryable=True,
+                )
+            except Exception as e:
+                last_error = NodeExecutionError(
+                    "execution_failed",
+                    str(e),
+                    node_id=node_id,
+                    adapter_id=adapter_id,
+                    attempts=attempt,
+                    retryable=True,
+                )
+            else:
+                # adapter_manager.process_with_adapter 通常返回 ExecutionResult（包含 output + 元信息），
+                # 失败状态同样按适配器错误处理
+                status = getattr(result, "status", "success")
+                if status == "success":
+                    # workflow_executions.output_data/node_results 存在于 JSONB 字段，必须可 JSON 序列化，
+                    # 这里只把 output 写入 workflow 变量与后续节点。
+                    return getattr(result, "output", result)
+                last_error = NodeExecutionError(
+                    "timeout" if status == "timeout" else "execution_failed",
+                    getattr(result, "error", None) or f"适配器 {adapter_id} 返回状态 {status}",
+                    node_id=node_id,
+                    adapter_id=adapter_id,
+                    attempts=attempt,
+                    retryable=status != "cancelled",
+                    details=getattr(result, "error_details", None),
+                )
+
+            logger.warning(f"Adapter {adapter_id} attempt {attempt}/{attempts} failed: {last_error}")
+            if not last_error.retryable:
+                break
+            if attempt < attempts and config.retry_delay_seconds > 0:
+                await asyncio.sleep(config.retry_delay_seconds)
+
+        raise last_error
 
 
 class ConditionNodeExecutor(NodeExecutor):
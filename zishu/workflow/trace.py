@@ -0,0 +1,152 @@
+"""
+工作流执行追踪
+记录每个节点的输入、输出与耗时，并支持用记录的输出回放执行
+"""
+
+import copy
+from datetime import datetime, timezone
+from typing import Dict, Any, Optional, List, Iterable
+
+from ..models.workflow import NodeType
+
+# 有外部副作用的节点：回放时使用原执行记录的输出，不再真实执行
+SIDE_EFFECT_NODE_TYPES = {
+    NodeType.ADAPTER.value,
+    NodeType.DELAY.value,
+    NodeType.HTTP.value,
+    NodeType.SCRIPT.value,
+}
+
+
+class ReplayError(Exception):
+    """回放所需的节点输出不存在"""
+
+
+def _now() -> datetime:
+    return datetime.now(timezone.utc)
+
+
+class ExecutionTrace:
+    """
+    单次执行的节点追踪
+
+    每个节点执行一次记录一步：执行前的配置、输入与变量，执行后的输出（或错误）、
+    变量与耗时。
+    """
+
+    def __init__(self):
+        self.steps: List[Dict[str, Any]] = []
+        self._started: Dict[int, datetime] = {}
+
+    def start_step(self, node: Dict[str, Any], context: Dict[str, Any]) -> Dict[str, Any]:
+        """节点开始执行，返回该步记录"""
+        started_at = _now()
+        step = {
+            "index": len(self.steps),
+            "node_id": node["id"],
+            "node_type": node.get("type"),
+            "status": "running",
+            "inputs": {
+                "config": copy.deepcopy(node.get("config", {})),
+                "input": copy.deepcopy(context.get("input", {})),
+                "variables": copy.deepcopy(context.get("variables", {})),
+            },
+            "output": None,
+            "error": None,
+            "variables_after": None,
+            "replayed": False,
+            "started_at": started_at.isoformat(),
+            "completed_at": None,
+            "duration_ms": None,
+        }
+        self._started[step["index"]] = started_at
+        self.steps.append(step)
+        return step
+
+    def finish_step(
+        self,
+        step: Dict[str, Any],
+        context: Dict[str, Any],
+        output: Any = None,
+        error: Optional[BaseException] = None,
+        replayed: bool = False,
+    ) -> None:
+        """节点执行结束（成功或失败）"""
+        completed_at = _now()
+        started_at = self._started.pop(step["index"], completed_at)
+        step.update(
+            {
+                "status": "failed" if error is not None else "success",
+                "output": copy.deepcopy(output) if error is None else None,
+                "error": str(error) if error is not None else None,
+                "variables_after": copy.deepcopy(context.get("variables", {})),
+                "replayed": replayed,
+                "completed_at": completed_at.isoformat(),
+                "duration_ms": int((completed_at - started_at).total_seconds() * 1000),
+            }
+        )
+
+    def to_list(self) -> List[Dict[str, Any]]:
+        return copy.deepcopy(self.steps)
+
+
+class ReplayPlan:
+    """
+    回放计划
+
+    有副作用的节点按原执行中的顺序取记录的输出，并恢复它对变量的修改；
+    `live_nodes` 中的节点仍真实执行。原执行中没有成功输出的副作用节点无法回放。
+    """
+
+    def __init__(self, replay_of: str, steps: List[Dict[str, Any]], live_nodes: Optional[Iterable[str]] = None):
+        self.replay_of = replay_of
+        self.live_nodes = set(live_nodes or [])
+        self._recorded: Dict[str, List[Dict[str, Any]]] = {}
+        for step in steps:
+            if step.get("status") == "success":
+                self._recorded.setdefault(step["node_id"], []).append(step)
+
+    def should_replay(self, node: Dict[str, Any]) -> bool:
+        return node.get("type") in SIDE_EFFECT_NODE_TYPES and node["id"] not in self.live_nodes
+
+    def output_for(self, node_id: str, context: Dict[str, Any]) -> Any:
+        """取节点的下一条记录输出，并把记录的变量修改应用到当前上下文"""
+        recorded = self._recorded.get(node_id)
+        if not recorded:
+            raise ReplayError(f"节点 {node_id} 在原执行中没有成功的输出，无法回放；可将其设为真实执行的节点")
+        step = recorded.pop(0)
+
+        before = step["inputs"].get("variables") or {}
+        after = step.get("variables_after") or {}
+        variables = context.setdefault("variables", {})
+        for key, value in after.items():
+            if key not in before or before[key] != value:
+                variables[key] = copy.deepcopy(value)
+        for key in before:
+            if key not in after:
+                variables.pop(key, None)
+        return copy.deepcopy(step["output"])
+
+    def describe(self) -> Dict[str, Any]:
+        """写入回放执行日志的说明"""
+        return {
+            "type": "replay",
+            "replay_of": self.replay_of,
+            "live_nodes": sorted(self.live_nodes),
+        }
+
+
+def find_trace(execution_log: Optional[List[Dict[str, Any]]]) -> Optional[List[Dict[str, Any]]]:
+    """从执行日志中取出节点追踪"""
+    for entry in execution_log or []:
+        if entry.get("type") == "trace":
+            return entry.get("steps", [])
+    return None
+
+
+def find_replay_of(execution_log: Optional[List[Dict[str, Any]]]) -> Optional[str]:
+    """回放执行对应的原执行 ID"""
+    for entry in execution_log or []:
+        if entry.get("type") == "replay":
+            return entry.get("replay_of")
+    return None